@@ -0,0 +1,50 @@
+//! BEEF benchmark
+//!
+//! Times parsing and verifying BEEFs of up to 1000 transactions: a proven
+//! funding transaction fanned out into unproven spends of each of its
+//! outputs, the shape of a busy wallet's outgoing BEEF.
+//!
+//! Run with `cargo bench -p wallet-core --bench beef`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wallet_core::beef::Beef;
+use wallet_test_utils::fixtures::{funding_beef, spend_tx, FUNDING_HEIGHT};
+use wallet_test_utils::MockChainTracker;
+
+const TX_COUNTS: &[usize] = &[10, 100, 1000];
+
+/// Serialized BEEF of `count` transactions
+fn fan_out_beef(count: usize) -> Vec<u8> {
+    let mut beef = funding_beef(&vec![1_000; count - 1], FUNDING_HEIGHT);
+    let funding_txid = beef.txs[0].txid.clone();
+    for vout in 0..count - 1 {
+        let spend = spend_tx(&funding_txid, vout as u32, &[900]);
+        beef.merge_raw_tx(&spend.serialize().unwrap()).unwrap();
+    }
+    beef.to_binary().unwrap()
+}
+
+fn beef(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let mut group = c.benchmark_group("beef");
+    for &count in TX_COUNTS {
+        let bytes = fan_out_beef(count);
+        let parsed = Beef::from_binary(&bytes).unwrap();
+        let tracker = MockChainTracker::for_beef(&parsed).unwrap();
+
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::new("from_binary", count), &bytes, |b, bytes| {
+            b.iter(|| Beef::from_binary(bytes).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("verify", count), &parsed, |b, parsed| {
+            b.iter(|| assert!(runtime.block_on(parsed.verify(&tracker, false)).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("to_binary", count), &parsed, |b, parsed| {
+            b.iter(|| parsed.to_binary().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, beef);
+criterion_main!(benches);
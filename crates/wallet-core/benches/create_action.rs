@@ -0,0 +1,59 @@
+//! createAction benchmark
+//!
+//! Times the storage side of createAction end to end on in-memory storage:
+//! validating the arguments, selecting and locking change from baskets of
+//! growing size, and inserting the new transaction and its outputs.
+//!
+//! Run with `cargo bench -p wallet-core --bench create_action`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use serde_json::json;
+use wallet_core::methods::create_action;
+use wallet_core::sdk::CreateActionArgs;
+use wallet_core::signer::methods::create_action::validate_create_action_args;
+use wallet_storage::AuthId;
+use wallet_test_utils::fixtures::alice_identity_key;
+use wallet_test_utils::MockStorage;
+
+const CHANGE_COUNTS: &[usize] = &[10, 100, 1000];
+
+/// Storage whose only user holds `count` change outputs
+fn storage_with_change(count: usize) -> (MockStorage, AuthId) {
+    let mut storage = MockStorage::new();
+    let change: Vec<i64> = (0..count as i64).map(|i| 1_000 + (i * 7919) % 100_000).collect();
+    let user_id = storage.add_fixture_user(&alice_identity_key(), &change);
+    (storage, AuthId::new(alice_identity_key()).with_user_id(user_id))
+}
+
+fn create_action_bench(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let args: CreateActionArgs = serde_json::from_value(json!({
+        "description": "benchmark payment",
+        "outputs": [{
+            "lockingScript": format!("76a914{}88ac", "00".repeat(20)),
+            "satoshis": 25_000,
+            "outputDescription": "benchmark output",
+            "tags": ["benchmark"],
+        }],
+        "labels": ["benchmark"],
+    }))
+    .unwrap();
+    let vargs = validate_create_action_args(&args).unwrap();
+
+    let mut group = c.benchmark_group("create_action");
+    for &count in CHANGE_COUNTS {
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter_batched(
+                || storage_with_change(count),
+                |(mut storage, auth)| {
+                    runtime.block_on(create_action(&mut storage, &auth, vargs.clone(), None)).unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, create_action_bench);
+criterion_main!(benches);
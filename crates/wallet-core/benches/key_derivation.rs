@@ -0,0 +1,44 @@
+//! Key derivation cache benchmark
+//!
+//! Times deriving the per-input signing keys of a batch of BRC-29 inputs,
+//! as signing a transaction with dozens of inputs does, with the
+//! `RootKeyDeriver` cache disabled and enabled. Each iteration re-derives
+//! the same keys, as repeated signing attempts and signature checks do.
+//!
+//! Run with `cargo bench -p wallet-core --bench key_derivation`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wallet_core::keys::RootKeyDeriver;
+
+const INPUT_COUNTS: &[usize] = &[12, 48, 96];
+
+/// Derive the public and private key of every input
+fn derive_inputs(deriver: &RootKeyDeriver, inputs: usize, sender: &str) {
+    let protocol = (2, "3241645161d8".to_string());
+    for vin in 0..inputs {
+        let key_id = format!("prefix{} suffix{}", vin / 4, vin);
+        deriver.derive_private_key(&protocol, &key_id, sender).unwrap();
+        deriver.derive_public_key(&protocol, &key_id, sender, true).unwrap();
+    }
+}
+
+fn key_derivation(c: &mut Criterion) {
+    let sender = RootKeyDeriver::new(&[2u8; 32]).unwrap().identity_key_hex();
+    let uncached = RootKeyDeriver::new(&[1u8; 32]).unwrap().with_cache_size(0);
+    let cached = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+
+    let mut group = c.benchmark_group("key_derivation");
+    for &inputs in INPUT_COUNTS {
+        group.throughput(Throughput::Elements(inputs as u64));
+        group.bench_with_input(BenchmarkId::new("uncached", inputs), &inputs, |b, &inputs| {
+            b.iter(|| derive_inputs(&uncached, inputs, &sender))
+        });
+        group.bench_with_input(BenchmarkId::new("cached", inputs), &inputs, |b, &inputs| {
+            b.iter(|| derive_inputs(&cached, inputs, &sender))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, key_derivation);
+criterion_main!(benches);
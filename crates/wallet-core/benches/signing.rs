@@ -0,0 +1,53 @@
+//! Signing benchmark
+//!
+//! Times single ECDSA signatures and verifications, and signing whole
+//! P2PKH transactions of growing input counts with `TxBuilder`, which adds
+//! a sighash per input on top of each signature.
+//!
+//! Run with `cargo bench -p wallet-core --bench signing`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wallet_core::crypto::{derive_public_key, hash160, sha256, sign_ecdsa, verify_ecdsa};
+use wallet_core::transaction::{OutPoint, Script, TxBuilder};
+
+const PRIVATE_KEY: [u8; 32] = [0x42; 32];
+const INPUT_COUNTS: &[usize] = &[1, 10, 100];
+
+fn ecdsa(c: &mut Criterion) {
+    let sighash = sha256(b"signing benchmark");
+    let public_key = derive_public_key(&PRIVATE_KEY).unwrap();
+    let signature = sign_ecdsa(&sighash, &PRIVATE_KEY, 0x41).unwrap();
+
+    let mut group = c.benchmark_group("ecdsa");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("sign", |b| b.iter(|| sign_ecdsa(&sighash, &PRIVATE_KEY, 0x41).unwrap()));
+    group.bench_function("verify", |b| b.iter(|| verify_ecdsa(&sighash, &signature, &public_key).unwrap()));
+    group.finish();
+}
+
+/// Builder spending `inputs` P2PKH outputs of one key into a payment and change
+fn p2pkh_builder(inputs: usize) -> TxBuilder {
+    let public_key = derive_public_key(&PRIVATE_KEY).unwrap();
+    let locking_script = Script::p2pkh_locking_script(&hash160(&public_key)).unwrap().to_bytes().to_vec();
+    let mut builder = TxBuilder::new();
+    for vin in 0..inputs {
+        let outpoint = OutPoint::new(format!("{:064x}", vin), vin as u32 % 4);
+        builder = builder.add_p2pkh_input(outpoint, 10_000, locking_script.clone(), PRIVATE_KEY.to_vec());
+    }
+    builder.add_output(5_000, locking_script.clone()).change_to(locking_script)
+}
+
+fn sign_transaction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sign_transaction");
+    for &inputs in INPUT_COUNTS {
+        let builder = p2pkh_builder(inputs);
+        group.throughput(Throughput::Elements(inputs as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(inputs), &builder, |b, builder| {
+            b.iter(|| builder.build().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, ecdsa, sign_transaction);
+criterion_main!(benches);
@@ -0,0 +1,339 @@
+//! AuthFetch: BRC-104 Authenticated HTTP Client
+//!
+//! **Reference**: TypeScript `AuthFetch` from @bsv/sdk
+//!
+//! Opens a BRC-103 session with each server origin on first use, signs every
+//! request with the wallet identity key and verifies every response was
+//! signed by the server the session was opened with.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::{
+    handshake_data, random_base64, request_payload, response_payload, sign_auth_message,
+    verify_auth_message, AuthHeaders, AuthMessage, AUTH_PATH, AUTH_VERSION,
+};
+use crate::keys::{create_nonce, verify_nonce, RootKeyDeriver};
+use crate::sdk::errors::{WalletError, WalletResult};
+
+/// An open BRC-103 session with a server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerSession {
+    /// Server identity key hex
+    pub identity_key: String,
+
+    /// Session nonce the server issued
+    pub server_nonce: String,
+
+    /// Session nonce we issued
+    pub our_nonce: String,
+}
+
+/// A verified response
+#[derive(Debug, Clone)]
+pub struct AuthFetchResponse {
+    pub status: u16,
+
+    /// Lower-cased response headers
+    pub headers: Vec<(String, String)>,
+
+    pub body: Vec<u8>,
+
+    /// Identity key of the server that signed the response
+    pub identity_key: String,
+}
+
+impl AuthFetchResponse {
+    /// Parse the body as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> WalletResult<T> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| WalletError::invalid_operation(format!("Invalid response body: {}", e)))
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Authenticated HTTP client
+///
+/// Reference: TS AuthFetch
+pub struct AuthFetch {
+    deriver: Arc<RootKeyDeriver>,
+    client: reqwest::Client,
+    /// Sessions keyed by server origin (`scheme://host:port`)
+    sessions: Mutex<HashMap<String, PeerSession>>,
+}
+
+impl AuthFetch {
+    pub fn new(deriver: Arc<RootKeyDeriver>) -> Self {
+        Self {
+            deriver,
+            client: reqwest::Client::new(),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Our identity key hex
+    pub fn identity_key(&self) -> String {
+        self.deriver.identity_key_hex()
+    }
+
+    /// Handshake request opening a session, with the nonce it carries
+    pub fn initial_request(&self) -> WalletResult<AuthMessage> {
+        Ok(AuthMessage {
+            version: AUTH_VERSION.to_string(),
+            message_type: "initialRequest".to_string(),
+            identity_key: self.identity_key(),
+            initial_nonce: Some(create_nonce(&self.deriver, "self")?),
+            your_nonce: None,
+            signature: None,
+        })
+    }
+
+    /// Check the server's `initialResponse` to `request`
+    ///
+    /// Reference: TS Peer.processInitialResponse
+    pub async fn open_session(&self, request: &AuthMessage, response: &AuthMessage) -> WalletResult<PeerSession> {
+        let our_nonce = request.initial_nonce.clone()
+            .ok_or_else(|| WalletError::missing_parameter("initialNonce"))?;
+        if response.message_type != "initialResponse" {
+            return Err(WalletError::invalid_parameter("messageType", "initialResponse"));
+        }
+        if response.your_nonce.as_deref() != Some(our_nonce.as_str()) || !verify_nonce(&our_nonce, &self.deriver, "self")? {
+            return Err(WalletError::invalid_parameter("yourNonce", "the nonce of our initial request"));
+        }
+        let server_nonce = response.initial_nonce.clone()
+            .ok_or_else(|| WalletError::missing_parameter("initialNonce"))?;
+        let signature = response.signature.clone()
+            .ok_or_else(|| WalletError::missing_parameter("signature"))?;
+        let identity_key = hex::encode(self.deriver.normalize_counterparty(&response.identity_key)?);
+
+        verify_auth_message(
+            &self.deriver,
+            handshake_data(&our_nonce, &server_nonce)?,
+            signature,
+            format!("{} {}", our_nonce, server_nonce),
+            &identity_key,
+        ).await?;
+        Ok(PeerSession { identity_key, server_nonce, our_nonce })
+    }
+
+    /// Envelope signing a request within `session`
+    pub async fn sign_request(
+        &self,
+        session: &PeerSession,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> WalletResult<AuthHeaders> {
+        let mut auth = AuthHeaders {
+            version: AUTH_VERSION.to_string(),
+            identity_key: self.identity_key(),
+            nonce: random_base64(32),
+            your_nonce: session.server_nonce.clone(),
+            request_id: random_base64(32),
+            signature: Vec::new(),
+        };
+        let payload = request_payload(&auth.request_id_bytes()?, method, path, query, headers, body);
+        auth.signature = sign_auth_message(
+            &self.deriver,
+            payload,
+            format!("{} {}", auth.nonce, session.server_nonce),
+            &session.identity_key,
+        ).await?;
+        Ok(auth)
+    }
+
+    /// Check a response to the request sent with `request`
+    ///
+    /// Reference: TS Peer.processGeneralMessage
+    pub async fn verify_response(
+        &self,
+        session: &PeerSession,
+        request: &AuthHeaders,
+        status: u16,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> WalletResult<()> {
+        let unauthenticated = || WalletError::new("WERR_UNAUTHORIZED", "Response was not authenticated by the server");
+        let auth = AuthHeaders::from_headers(headers)?.ok_or_else(unauthenticated)?;
+        if auth.identity_key != session.identity_key
+            || auth.your_nonce != session.our_nonce
+            || auth.request_id != request.request_id
+        {
+            return Err(unauthenticated());
+        }
+        let payload = response_payload(&auth.request_id_bytes()?, status, headers, body);
+        verify_auth_message(
+            &self.deriver,
+            payload,
+            auth.signature,
+            format!("{} {}", auth.nonce, session.our_nonce),
+            &session.identity_key,
+        ).await
+    }
+
+    /// Session with `origin`, opened over HTTP when there is none yet
+    async fn session(&self, origin: &str) -> WalletResult<PeerSession> {
+        if let Some(session) = self.sessions.lock().await.get(origin) {
+            return Ok(session.clone());
+        }
+        let request = self.initial_request()?;
+        let response = self.client.post(format!("{}{}", origin, AUTH_PATH))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| WalletError::internal(format!("BRC-103 handshake with {} failed: {}", origin, e)))?;
+        if !response.status().is_success() {
+            return Err(WalletError::invalid_operation(format!(
+                "BRC-103 handshake with {} returned HTTP status {}", origin, response.status()
+            )));
+        }
+        let response: AuthMessage = response.json()
+            .await
+            .map_err(|e| WalletError::invalid_operation(format!("Invalid BRC-103 handshake response: {}", e)))?;
+
+        let session = self.open_session(&request, &response).await?;
+        self.sessions.lock().await.insert(origin.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Send an authenticated request
+    ///
+    /// `headers` are sent as given; the auth envelope is added. A response
+    /// that fails verification closes the session so the next request
+    /// performs a new handshake.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        method: Method,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    ) -> WalletResult<AuthFetchResponse> {
+        let url = Url::parse(url).map_err(|_| WalletError::invalid_parameter("url", "an absolute URL"))?;
+        let origin = url.origin().ascii_serialization();
+        let session = self.session(&origin).await?;
+        let auth = self.sign_request(&session, method.as_str(), url.path(), url.query(), &headers, &body).await?;
+
+        let mut request = self.client.request(method, url.clone()).body(body);
+        for (k, v) in headers.iter().chain(auth.to_headers().iter()) {
+            request = request.header(k.as_str(), v.as_str());
+        }
+        let response = request.send()
+            .await
+            .map_err(|e| WalletError::internal(format!("Request to {} failed: {}", url, e)))?;
+
+        let status = response.status().as_u16();
+        let response_headers: Vec<(String, String)> = response.headers().iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_ascii_lowercase(), v.to_str().ok()?.to_string())))
+            .collect();
+        let body = response.bytes()
+            .await
+            .map_err(|e| WalletError::internal(format!("Reading response from {} failed: {}", url, e)))?
+            .to_vec();
+
+        if let Err(e) = self.verify_response(&session, &auth, status, &response_headers, &body).await {
+            self.sessions.lock().await.remove(&origin);
+            return Err(e);
+        }
+        Ok(AuthFetchResponse {
+            status,
+            headers: response_headers,
+            body,
+            identity_key: session.identity_key,
+        })
+    }
+
+    /// POST `body` as JSON
+    pub async fn post_json<T: Serialize + ?Sized>(&self, url: &str, body: &T) -> WalletResult<AuthFetchResponse> {
+        let body = serde_json::to_vec(body).map_err(|e| WalletError::internal(e.to_string()))?;
+        let headers = vec![("content-type".to_string(), "application/json".to_string())];
+        self.fetch(url, Method::POST, headers, body).await
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetch(key: u8) -> AuthFetch {
+        AuthFetch::new(Arc::new(RootKeyDeriver::new(&[key; 32]).unwrap()))
+    }
+
+    /// Server answer to `request`, signed as TS Peer.processInitialRequest does
+    async fn initial_response(server: &RootKeyDeriver, request: &AuthMessage) -> AuthMessage {
+        let client_nonce = request.initial_nonce.clone().unwrap();
+        let server_nonce = create_nonce(server, "self").unwrap();
+        let signature = sign_auth_message(
+            server,
+            handshake_data(&client_nonce, &server_nonce).unwrap(),
+            format!("{} {}", client_nonce, server_nonce),
+            &request.identity_key,
+        ).await.unwrap();
+        AuthMessage {
+            version: AUTH_VERSION.to_string(),
+            message_type: "initialResponse".to_string(),
+            identity_key: server.identity_key_hex(),
+            initial_nonce: Some(server_nonce),
+            your_nonce: Some(client_nonce),
+            signature: Some(signature),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_session() {
+        let client = fetch(1);
+        let server = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let request = client.initial_request().unwrap();
+        let response = initial_response(&server, &request).await;
+
+        let session = client.open_session(&request, &response).await.unwrap();
+        assert_eq!(session.identity_key, server.identity_key_hex());
+
+        let mut forged = response.clone();
+        forged.identity_key = RootKeyDeriver::new(&[3u8; 32]).unwrap().identity_key_hex();
+        assert!(client.open_session(&request, &forged).await.is_err());
+
+        let other = client.initial_request().unwrap();
+        assert!(client.open_session(&other, &response).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_response() {
+        let client = fetch(1);
+        let server = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let request = client.initial_request().unwrap();
+        let session = client.open_session(&request, &initial_response(&server, &request).await).await.unwrap();
+        let sent = client.sign_request(&session, "POST", "/signCertificate", None, &[], b"{}").await.unwrap();
+
+        // The server signs its response as TS Peer.toPeer does
+        let nonce = random_base64(32);
+        let payload = response_payload(&sent.request_id_bytes().unwrap(), 200, &[], b"ok");
+        let reply = AuthHeaders {
+            version: AUTH_VERSION.to_string(),
+            identity_key: server.identity_key_hex(),
+            nonce: nonce.clone(),
+            your_nonce: session.our_nonce.clone(),
+            request_id: sent.request_id.clone(),
+            signature: sign_auth_message(&server, payload, format!("{} {}", nonce, session.our_nonce), &client.identity_key()).await.unwrap(),
+        };
+        let headers = reply.to_headers();
+
+        assert!(client.verify_response(&session, &sent, 200, &headers, b"ok").await.is_ok());
+        assert!(client.verify_response(&session, &sent, 200, &headers, b"forged").await.is_err());
+        assert!(client.verify_response(&session, &sent, 500, &headers, b"ok").await.is_err());
+        assert!(client.verify_response(&session, &sent, 200, &[], b"ok").await.is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! BRC-103/104 Authenticated HTTP
+//!
+//! **Reference**: TypeScript `Peer`, `AuthFetch` and `SimplifiedFetchTransport` from @bsv/sdk
+//!
+//! BRC-103 opens a session between two identity keys: the client posts an
+//! `initialRequest` with its nonce to `/.well-known/auth`, and the server
+//! answers with its own nonce, signed over both. BRC-104 then carries each
+//! HTTP request and response as a general message: the sender signs a
+//! serialization of the method, path, selected headers and body, and sends
+//! the signature with its nonces in `x-bsv-auth-*` headers.
+//!
+//! [`AuthFetch`] is the client side; the server side lives in the
+//! `wallet-server` feature. Certificate exchange is not supported.
+
+pub mod client;
+
+pub use client::{AuthFetch, AuthFetchResponse, PeerSession};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::keys::RootKeyDeriver;
+use crate::methods::signature_operations::{create_signature, verify_signature};
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::{CreateSignatureArgs, VerifySignatureArgs};
+
+/// BRC-103 protocol version
+pub const AUTH_VERSION: &str = "0.1";
+
+/// Path of the handshake endpoint
+pub const AUTH_PATH: &str = "/.well-known/auth";
+
+/// Protocol under which message signatures are derived
+const AUTH_SIGNATURE_PROTOCOL: &str = "auth message signature";
+
+/// Prefix of headers carrying the BRC-104 envelope
+const AUTH_HEADER_PREFIX: &str = "x-bsv-auth";
+
+/// BRC-103 handshake message
+///
+/// Reference: TS AuthMessage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthMessage {
+    pub version: String,
+    pub message_type: String,
+    pub identity_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub your_nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Vec<u8>>,
+}
+
+/// BRC-104 envelope of a general message, carried in `x-bsv-auth-*` headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthHeaders {
+    pub version: String,
+    pub identity_key: String,
+    pub nonce: String,
+    pub your_nonce: String,
+    pub request_id: String,
+    pub signature: Vec<u8>,
+}
+
+impl AuthHeaders {
+    /// Read the envelope from lower-cased headers
+    ///
+    /// Returns `None` when there are no auth headers at all.
+    pub fn from_headers(headers: &[(String, String)]) -> WalletResult<Option<Self>> {
+        let get = |name: &str| {
+            headers.iter()
+                .find(|(k, _)| k == &format!("{}-{}", AUTH_HEADER_PREFIX, name))
+                .map(|(_, v)| v.clone())
+        };
+        let Some(identity_key) = get("identity-key") else {
+            return Ok(None);
+        };
+        let missing = |name: &str| WalletError::missing_parameter(format!("{}-{}", AUTH_HEADER_PREFIX, name));
+        let signature = get("signature").ok_or_else(|| missing("signature"))?;
+        Ok(Some(Self {
+            version: get("version").ok_or_else(|| missing("version"))?,
+            identity_key,
+            nonce: get("nonce").ok_or_else(|| missing("nonce"))?,
+            your_nonce: get("your-nonce").ok_or_else(|| missing("your-nonce"))?,
+            request_id: get("request-id").ok_or_else(|| missing("request-id"))?,
+            signature: hex::decode(signature)
+                .map_err(|_| WalletError::invalid_parameter("x-bsv-auth-signature", "a hex string"))?,
+        }))
+    }
+
+    /// Headers carrying the envelope
+    pub fn to_headers(&self) -> Vec<(String, String)> {
+        let header = |name: &str, value: String| (format!("{}-{}", AUTH_HEADER_PREFIX, name), value);
+        vec![
+            header("version", self.version.clone()),
+            header("message-type", "general".to_string()),
+            header("identity-key", self.identity_key.clone()),
+            header("nonce", self.nonce.clone()),
+            header("your-nonce", self.your_nonce.clone()),
+            header("request-id", self.request_id.clone()),
+            header("signature", hex::encode(&self.signature)),
+        ]
+    }
+
+    /// Decoded request id
+    pub fn request_id_bytes(&self) -> WalletResult<Vec<u8>> {
+        decode_base64(&self.request_id, "x-bsv-auth-request-id")
+    }
+}
+
+/// Writer for BRC-104 payload serialization
+///
+/// Reference: TS Utils.Writer
+#[derive(Debug, Default)]
+struct PayloadWriter {
+    buf: Vec<u8>,
+}
+
+impl PayloadWriter {
+    fn write_var_int(&mut self, n: u64) {
+        match n {
+            0..=0xfc => self.buf.push(n as u8),
+            0xfd..=0xffff => {
+                self.buf.push(0xfd);
+                self.buf.extend_from_slice(&(n as u16).to_le_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                self.buf.push(0xfe);
+                self.buf.extend_from_slice(&(n as u32).to_le_bytes());
+            }
+            _ => {
+                self.buf.push(0xff);
+                self.buf.extend_from_slice(&n.to_le_bytes());
+            }
+        }
+    }
+
+    /// Length-prefixed bytes, or -1 (as a 64-bit varint) when absent
+    fn write_optional(&mut self, bytes: Option<&[u8]>) {
+        match bytes {
+            Some(bytes) => {
+                self.write_var_int(bytes.len() as u64);
+                self.buf.extend_from_slice(bytes);
+            }
+            None => self.write_var_int(u64::MAX),
+        }
+    }
+
+    fn write_headers(&mut self, headers: &[(String, String)]) {
+        self.write_var_int(headers.len() as u64);
+        for (k, v) in headers {
+            self.write_optional(Some(k.as_bytes()));
+            self.write_optional(Some(v.as_bytes()));
+        }
+    }
+}
+
+/// Headers covered by a request signature, sorted by name
+///
+/// `x-bsv-*` (except the auth envelope), `content-type` without parameters
+/// and `authorization`.
+fn signed_request_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    let mut signed: Vec<(String, String)> = headers.iter()
+        .map(|(k, v)| (k.to_ascii_lowercase(), v.clone()))
+        .filter(|(k, _)| {
+            (k.starts_with("x-bsv-") || k == "content-type" || k == "authorization")
+                && !k.starts_with(AUTH_HEADER_PREFIX)
+        })
+        .map(|(k, v)| match k.as_str() {
+            "content-type" => (k, v.split(';').next().unwrap_or_default().trim().to_string()),
+            _ => (k, v),
+        })
+        .collect();
+    signed.sort();
+    signed
+}
+
+/// Serialize a request for signing
+///
+/// Reference: TS buildAuthMessageFromRequest (auth-express-middleware)
+pub fn request_payload(
+    request_id: &[u8],
+    method: &str,
+    path: &str,
+    query: Option<&str>,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Vec<u8> {
+    let mut writer = PayloadWriter::default();
+    writer.buf.extend_from_slice(request_id);
+    writer.write_optional(Some(method.as_bytes()));
+    writer.write_optional(Some(path.as_bytes()).filter(|p| !p.is_empty()));
+    let search = query.filter(|q| !q.is_empty()).map(|q| format!("?{}", q));
+    writer.write_optional(search.as_deref().map(str::as_bytes));
+    writer.write_headers(&signed_request_headers(headers));
+    writer.write_optional(Some(body).filter(|b| !b.is_empty()));
+    writer.buf
+}
+
+/// Serialize a response for signing
+///
+/// Reference: TS buildResponse (auth-express-middleware)
+pub fn response_payload(request_id: &[u8], status: u16, headers: &[(String, String)], body: &[u8]) -> Vec<u8> {
+    let mut signed: Vec<(String, String)> = headers.iter()
+        .map(|(k, v)| (k.to_ascii_lowercase(), v.clone()))
+        .filter(|(k, _)| (k.starts_with("x-bsv-") || k == "authorization") && !k.starts_with(AUTH_HEADER_PREFIX))
+        .collect();
+    signed.sort();
+
+    let mut writer = PayloadWriter::default();
+    writer.buf.extend_from_slice(request_id);
+    writer.write_var_int(u64::from(status));
+    writer.write_headers(&signed);
+    writer.write_optional(Some(body).filter(|b| !b.is_empty()));
+    writer.buf
+}
+
+/// Signature data of a handshake: both session nonces, decoded
+pub(crate) fn handshake_data(initial_nonce: &str, response_nonce: &str) -> WalletResult<Vec<u8>> {
+    Ok([decode_base64(initial_nonce, "initialNonce")?, decode_base64(response_nonce, "initialNonce")?].concat())
+}
+
+/// Sign a message for `counterparty` under the BRC-103 protocol
+pub(crate) async fn sign_auth_message(
+    deriver: &RootKeyDeriver,
+    data: Vec<u8>,
+    key_id: String,
+    counterparty: &str,
+) -> WalletResult<Vec<u8>> {
+    let args = CreateSignatureArgs {
+        protocol_id: (2, AUTH_SIGNATURE_PROTOCOL.to_string()),
+        key_id,
+        data: Some(data),
+        hash_to_directly_sign: None,
+        counterparty: Some(counterparty.to_string()),
+        privileged: None,
+        privileged_reason: None,
+    };
+    Ok(create_signature(&args, deriver).await?.signature)
+}
+
+/// Verify a message `counterparty` signed for us under the BRC-103 protocol
+pub(crate) async fn verify_auth_message(
+    deriver: &RootKeyDeriver,
+    data: Vec<u8>,
+    signature: Vec<u8>,
+    key_id: String,
+    counterparty: &str,
+) -> WalletResult<()> {
+    let args = VerifySignatureArgs {
+        protocol_id: (2, AUTH_SIGNATURE_PROTOCOL.to_string()),
+        key_id,
+        data: Some(data),
+        hash_to_directly_verify: None,
+        signature,
+        for_self: None,
+        counterparty: Some(counterparty.to_string()),
+        privileged: None,
+        privileged_reason: None,
+    };
+    verify_signature(&args, deriver).await?;
+    Ok(())
+}
+
+/// Random base64 value of `len` bytes, for message nonces and request ids
+pub(crate) fn random_base64(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    STANDARD.encode(bytes)
+}
+
+fn decode_base64(s: &str, name: &str) -> WalletResult<Vec<u8>> {
+    STANDARD.decode(s).map_err(|_| WalletError::invalid_parameter(name, "a base64 string"))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_var_ints() {
+        let mut writer = PayloadWriter::default();
+        writer.write_optional(None);
+        writer.write_var_int(0xfd);
+        assert_eq!(writer.buf, [vec![0xff; 9], vec![0xfd, 0xfd, 0x00]].concat());
+    }
+
+    #[test]
+    fn test_signed_request_headers() {
+        let headers = vec![
+            ("x-bsv-topic".to_string(), "a".to_string()),
+            ("Content-Type".to_string(), "application/json; charset=utf-8".to_string()),
+            ("x-bsv-auth-nonce".to_string(), "n".to_string()),
+            ("originator".to_string(), "example.com".to_string()),
+        ];
+        assert_eq!(signed_request_headers(&headers), vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("x-bsv-topic".to_string(), "a".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_auth_headers_roundtrip() {
+        let auth = AuthHeaders {
+            version: AUTH_VERSION.to_string(),
+            identity_key: "02ab".to_string(),
+            nonce: "bm9uY2U=".to_string(),
+            your_nonce: "eW91cg==".to_string(),
+            request_id: "aWQ=".to_string(),
+            signature: vec![1, 2, 3],
+        };
+        assert_eq!(AuthHeaders::from_headers(&auth.to_headers()).unwrap(), Some(auth));
+        assert_eq!(AuthHeaders::from_headers(&[]).unwrap(), None);
+    }
+}
@@ -14,11 +14,18 @@
 //!
 //! TypeScript Reference: ts-sdk/src/transaction/BEEF.ts
 
+use std::collections::{BTreeMap, HashSet};
 use thiserror::Error;
+use crate::crypto::double_sha256;
+use crate::transaction::{ByteReader, Transaction as WireTransaction, TransactionError};
+use crate::transaction::transaction::encode_varint;
 
 /// BEEF version constants
-pub const BEEF_V1: u32 = 0x0100BEEF; // 4022206465 in LE
-pub const BEEF_V2: u32 = 0x0200BEEF; // 4022206466 in LE  
+///
+/// Written little-endian, so a BEEF starts with the bytes `0100BEEF` or
+/// `0200BEEF` (BRC-62, BRC-96)
+pub const BEEF_V1: u32 = 4022206465; // 0xEFBE0001
+pub const BEEF_V2: u32 = 4022206466; // 0xEFBE0002
 pub const ATOMIC_BEEF: u32 = 0x01010101;
 
 /// Transaction data format in BEEF
@@ -50,6 +57,12 @@ pub enum BeefError {
 
 pub type BeefResult<T> = Result<T, BeefError>;
 
+impl From<TransactionError> for BeefError {
+    fn from(e: TransactionError) -> Self {
+        BeefError::InvalidData(e.to_string())
+    }
+}
+
 /// A transaction entry in BEEF
 /// Reference: ts-sdk BeefTx.ts
 #[derive(Debug, Clone)]
@@ -108,13 +121,171 @@ pub struct MerklePath {
 }
 
 /// Node in merkle path
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MerklePathNode {
-    /// Hash value (hex string)
+    /// Hash value (hex string, empty when `duplicate`)
     pub hash: String,
     
     /// Offset in block
     pub offset: Option<u32>,
+    
+    /// Leaf duplicates its sibling (BRC-74 flag 1)
+    pub duplicate: bool,
+    
+    /// Leaf is a client txid (BRC-74 flag 2)
+    pub txid: bool,
+}
+
+/// Hash two merkle tree nodes given as display-order hex
+fn merkle_hash(left: &str, right: &str) -> BeefResult<String> {
+    let mut data = hex::decode(left).map_err(|e| BeefError::InvalidData(e.to_string()))?;
+    data.reverse();
+    let mut r = hex::decode(right).map_err(|e| BeefError::InvalidData(e.to_string()))?;
+    r.reverse();
+    data.extend_from_slice(&r);
+    let mut hash = double_sha256(&data);
+    hash.reverse();
+    Ok(hex::encode(hash))
+}
+
+impl MerklePath {
+    /// Parse BRC-74 binary
+    ///
+    /// Reference: TS MerklePath.fromReader()
+    pub fn read_from(reader: &mut ByteReader<'_>) -> BeefResult<Self> {
+        let block_height = reader.read_varint()? as u32;
+        let tree_height = reader.read_u8()? as usize;
+        let mut path = Vec::with_capacity(tree_height);
+        for _ in 0..tree_height {
+            let n_leaves = reader.read_varint()?;
+            let mut level = Vec::new();
+            for _ in 0..n_leaves {
+                let offset = reader.read_varint()? as u32;
+                let flags = reader.read_u8()?;
+                let duplicate = flags & 1 != 0;
+                let hash = if duplicate {
+                    String::new()
+                } else {
+                    hex::encode(reader.read_reverse(32)?)
+                };
+                level.push(MerklePathNode {
+                    hash,
+                    offset: Some(offset),
+                    duplicate,
+                    txid: flags & 2 != 0,
+                });
+            }
+            level.sort_by_key(|n| n.offset);
+            path.push(level);
+        }
+        Ok(Self { block_height, path })
+    }
+    
+    /// Serialize to BRC-74 binary
+    ///
+    /// Reference: TS MerklePath.toBinary()
+    pub fn to_binary(&self) -> BeefResult<Vec<u8>> {
+        let mut out = encode_varint(self.block_height as u64);
+        out.push(self.path.len() as u8);
+        for level in &self.path {
+            out.extend_from_slice(&encode_varint(level.len() as u64));
+            for node in level {
+                out.extend_from_slice(&encode_varint(node.offset.unwrap_or(0) as u64));
+                let flags = u8::from(node.duplicate) | (u8::from(node.txid) << 1);
+                out.push(flags);
+                if !node.duplicate {
+                    let mut hash = hex::decode(&node.hash)
+                        .map_err(|e| BeefError::InvalidData(e.to_string()))?;
+                    hash.reverse();
+                    out.extend_from_slice(&hash);
+                }
+            }
+        }
+        Ok(out)
+    }
+    
+    /// Find a leaf at `height` and `offset`, computing it from the level below if needed
+    ///
+    /// Reference: TS MerklePath.findOrComputeLeaf()
+    fn find_or_compute_leaf(&self, height: usize, offset: u32) -> BeefResult<Option<MerklePathNode>> {
+        if let Some(leaf) = self.path.get(height)
+            .and_then(|level| level.iter().find(|n| n.offset == Some(offset)))
+        {
+            return Ok(Some(leaf.clone()));
+        }
+        if height == 0 {
+            return Ok(None);
+        }
+        let h = height - 1;
+        let l = offset << 1;
+        let Some(leaf0) = self.find_or_compute_leaf(h, l)? else { return Ok(None) };
+        if leaf0.duplicate {
+            return Ok(None);
+        }
+        let Some(leaf1) = self.find_or_compute_leaf(h, l + 1)? else { return Ok(None) };
+        let hash = if leaf1.duplicate {
+            merkle_hash(&leaf0.hash, &leaf0.hash)?
+        } else {
+            merkle_hash(&leaf0.hash, &leaf1.hash)?
+        };
+        Ok(Some(MerklePathNode { hash, offset: Some(offset), ..Default::default() }))
+    }
+    
+    /// Compute the merkle root for `txid`, or for the first txid leaf if `None`
+    ///
+    /// Reference: TS MerklePath.computeRoot()
+    pub fn compute_root(&self, txid: Option<&str>) -> BeefResult<String> {
+        let level0 = self.path.first()
+            .ok_or_else(|| BeefError::InvalidData("empty merkle path".to_string()))?;
+        let leaf = match txid {
+            Some(txid) => level0.iter().find(|n| n.hash == txid),
+            None => level0.iter().find(|n| n.txid).or_else(|| level0.iter().find(|n| !n.duplicate)),
+        }.ok_or_else(|| BeefError::TxNotFound(txid.unwrap_or_default().to_string()))?;
+        
+        if self.path.len() == 1 && level0.len() == 1 {
+            return Ok(leaf.hash.clone());
+        }
+        
+        let index = leaf.offset.unwrap_or(0);
+        let mut working = leaf.hash.clone();
+        for height in 0..self.path.len() {
+            let offset = (index >> height) ^ 1;
+            let sibling = self.find_or_compute_leaf(height, offset)?.ok_or_else(|| {
+                BeefError::InvalidData(format!("missing hash for index {} at height {}", offset, height))
+            })?;
+            working = if sibling.duplicate {
+                merkle_hash(&working, &working)?
+            } else if offset % 2 != 0 {
+                merkle_hash(&working, &sibling.hash)?
+            } else {
+                merkle_hash(&sibling.hash, &working)?
+            };
+        }
+        Ok(working)
+    }
+    
+    /// Combine leaves of another path for the same block into this one
+    ///
+    /// Reference: TS MerklePath.combine()
+    pub fn combine(&mut self, other: &MerklePath) -> BeefResult<()> {
+        if self.block_height != other.block_height || self.compute_root(None)? != other.compute_root(None)? {
+            return Err(BeefError::InvalidData(
+                "cannot combine merkle paths from different blocks".to_string()
+            ));
+        }
+        for (h, level) in other.path.iter().enumerate() {
+            if self.path.len() <= h {
+                self.path.push(Vec::new());
+            }
+            for node in level {
+                if !self.path[h].iter().any(|n| n.offset == node.offset) {
+                    self.path[h].push(node.clone());
+                }
+            }
+            self.path[h].sort_by_key(|n| n.offset);
+        }
+        Ok(())
+    }
 }
 
 /// ChainTracker interface for BEEF verification
@@ -187,25 +358,76 @@ impl Beef {
     }
     
     /// Merge another BEEF into this one
-    /// Reference: TS Beef.mergeBeef() (multiple locations)
-    pub fn merge_beef(&mut self, _other_beef: &[u8]) -> BeefResult<()> {
-        // TODO: Implement BEEF binary format parsing
-        // 1. Parse version
-        // 2. Parse bumps
-        // 3. Parse transactions in dependency order
-        // 4. Validate and merge without duplicates
-        Err(BeefError::NotImplemented("merge_beef requires BEEF binary parser"))
+    ///
+    /// BUMPs for the same block are combined; raw transactions replace
+    /// txid-only entries. Transactions are re-sorted into dependency order.
+    ///
+    /// Reference: TS Beef.mergeBeef()
+    pub fn merge_beef(&mut self, other_beef: &[u8]) -> BeefResult<()> {
+        let other = Beef::from_binary(other_beef)?;
+        self.merge_beef_struct(&other)
+    }
+    
+    /// Merge a parsed BEEF into this one
+    ///
+    /// Reference: TS Beef.mergeBeef(beef: Beef)
+    pub fn merge_beef_struct(&mut self, other: &Beef) -> BeefResult<()> {
+        let mut bump_map = Vec::with_capacity(other.bumps.len());
+        for bump in &other.bumps {
+            bump_map.push(self.merge_bump_index(bump.clone())?);
+        }
+        for btx in &other.txs {
+            if btx.is_txid_only {
+                self.merge_txid_only(&btx.txid);
+            } else if let Some(raw_tx) = &btx.raw_tx {
+                let bump_index = btx.bump_index.map(|i| bump_map[i]);
+                self.merge_raw_tx_with_bump(raw_tx, bump_index)?;
+            }
+        }
+        self.sort_txs()
     }
     
     /// Merge raw transaction bytes
     /// Reference: TS Beef.mergeRawTx() line 646
-    pub fn merge_raw_tx(&mut self, _raw_tx: &[u8]) -> BeefResult<BeefTx> {
-        // TODO: Implement
-        // 1. Parse transaction from bytes
-        // 2. Extract txid
-        // 3. Add to txs if not duplicate
-        // 4. Return BeefTx
-        Err(BeefError::NotImplemented("merge_raw_tx requires transaction parser"))
+    pub fn merge_raw_tx(&mut self, raw_tx: &[u8]) -> BeefResult<BeefTx> {
+        self.merge_raw_tx_with_bump(raw_tx, None)
+    }
+    
+    fn merge_raw_tx_with_bump(&mut self, raw_tx: &[u8], bump_index: Option<usize>) -> BeefResult<BeefTx> {
+        let txid = WireTransaction::from_bytes(raw_tx)?.txid()?;
+        
+        let beef_tx = BeefTx {
+            txid: txid.clone(),
+            raw_tx: Some(raw_tx.to_vec()),
+            tx: None,
+            bump_index,
+            is_txid_only: false,
+        };
+        
+        match self.txs.iter().position(|tx| tx.txid == txid) {
+            Some(i) if !self.txs[i].is_txid_only => {
+                if self.txs[i].bump_index.is_none() {
+                    self.txs[i].bump_index = bump_index;
+                }
+                Ok(self.txs[i].clone())
+            }
+            Some(i) => {
+                self.txs[i] = beef_tx.clone();
+                Ok(beef_tx)
+            }
+            None => {
+                self.txs.push(beef_tx.clone());
+                Ok(beef_tx)
+            }
+        }
+    }
+    
+    /// Merge a mined transaction with the merkle path proving it
+    ///
+    /// Reference: TS Beef.mergeBump() then Beef.mergeRawTx(rawTx, bumpIndex)
+    pub fn merge_proven_tx(&mut self, raw_tx: &[u8], bump: MerklePath) -> BeefResult<BeefTx> {
+        let bump_index = self.merge_bump_index(bump)?;
+        self.merge_raw_tx_with_bump(raw_tx, Some(bump_index))
     }
     
     /// Merge txid-only entry
@@ -247,34 +469,153 @@ impl Beef {
     /// Merge a BUMP (merkle path)
     /// Reference: TS Beef.mergeBump()
     pub fn merge_bump(&mut self, bump: MerklePath) {
-        // Check for duplicate
-        let txids_in_bump: Vec<String> = bump.path.get(0)
-            .map_or(Vec::new(), |level| {
-                level.iter().map(|node| node.hash.clone()).collect()
-            });
+        // Paths that fail to compute a root are kept as separate entries
+        if self.merge_bump_index(bump.clone()).is_err() {
+            self.bumps.push(bump);
+        }
+    }
+    
+    /// Merge a BUMP, returning its index in `bumps`
+    ///
+    /// A BUMP for a block already present is combined with the existing one.
+    fn merge_bump_index(&mut self, bump: MerklePath) -> BeefResult<usize> {
+        let root = bump.compute_root(None)?;
+        for (i, existing) in self.bumps.iter_mut().enumerate() {
+            if existing.block_height == bump.block_height && existing.compute_root(None)? == root {
+                existing.combine(&bump)?;
+                return Ok(i);
+            }
+        }
+        self.bumps.push(bump);
+        Ok(self.bumps.len() - 1)
+    }
+    
+    /// Sort transactions so every transaction follows the transactions it spends
+    ///
+    /// Reference: TS Beef.sortTxs()
+    pub fn sort_txs(&mut self) -> BeefResult<()> {
+        let mut input_txids: Vec<Vec<String>> = Vec::with_capacity(self.txs.len());
+        for btx in &self.txs {
+            let inputs = match &btx.raw_tx {
+                Some(raw_tx) if btx.bump_index.is_none() => WireTransaction::from_bytes(raw_tx)?
+                    .inputs.into_iter().map(|i| i.prev_out.txid).collect(),
+                _ => Vec::new(),
+            };
+            input_txids.push(inputs);
+        }
         
-        let is_duplicate = self.bumps.iter().any(|existing| {
-            let existing_txids: Vec<String> = existing.path.get(0)
-                .map_or(Vec::new(), |level| {
-                    level.iter().map(|node| node.hash.clone()).collect()
+        let mut sorted: Vec<BeefTx> = Vec::with_capacity(self.txs.len());
+        let mut placed = vec![false; self.txs.len()];
+        while sorted.len() < self.txs.len() {
+            let before = sorted.len();
+            for i in 0..self.txs.len() {
+                if placed[i] {
+                    continue;
+                }
+                let ready = input_txids[i].iter().all(|txid| {
+                    self.txs.iter().enumerate().all(|(j, t)| t.txid != *txid || placed[j])
                 });
-            existing_txids == txids_in_bump
-        });
+                if ready {
+                    placed[i] = true;
+                    sorted.push(self.txs[i].clone());
+                }
+            }
+            if sorted.len() == before {
+                return Err(BeefError::InvalidData("cyclic transaction dependencies".to_string()));
+            }
+        }
+        self.txs = sorted;
+        Ok(())
+    }
+    
+    /// Check the BEEF's structure and collect the merkle roots it claims
+    ///
+    /// Reference: TS Beef.verifyValid()
+    ///
+    /// Every txid leaf of a BUMP must compute to the same root as the other
+    /// BUMPs for its block, every proven transaction must be a leaf of its
+    /// BUMP, and every other transaction may only spend transactions that are
+    /// themselves valid. Txid-only entries count as valid only when
+    /// `allow_txid_only` is set. Returns the roots by block height, for
+    /// checking against a chain tracker.
+    pub fn verify_valid(&self, allow_txid_only: bool) -> BeefResult<BTreeMap<u32, String>> {
+        let mut sorted = self.clone_beef();
+        sorted.sort_txs()?;
         
-        if !is_duplicate {
-            self.bumps.push(bump);
+        let mut roots: BTreeMap<u32, String> = BTreeMap::new();
+        for bump in &sorted.bumps {
+            let level0 = bump.path.first()
+                .ok_or_else(|| BeefError::InvalidData("empty merkle path".to_string()))?;
+            for leaf in level0.iter().filter(|n| n.txid) {
+                let root = bump.compute_root(Some(&leaf.hash))?;
+                match roots.get(&bump.block_height) {
+                    Some(known) if *known != root => {
+                        return Err(BeefError::VerificationFailed(format!(
+                            "conflicting merkle roots for block {}", bump.block_height
+                        )));
+                    }
+                    Some(_) => {}
+                    None => { roots.insert(bump.block_height, root); }
+                }
+            }
+        }
+        
+        let mut valid: HashSet<&str> = HashSet::new();
+        for btx in &sorted.txs {
+            if btx.is_txid_only {
+                if !allow_txid_only {
+                    return Err(BeefError::VerificationFailed(format!("transaction {} is txid only", btx.txid)));
+                }
+                valid.insert(&btx.txid);
+            } else if let Some(i) = btx.bump_index {
+                let proven = sorted.bumps.get(i)
+                    .and_then(|bump| bump.path.first())
+                    .is_some_and(|level0| level0.iter().any(|n| n.hash == btx.txid));
+                if !proven {
+                    return Err(BeefError::VerificationFailed(format!(
+                        "transaction {} is not a leaf of BUMP {}", btx.txid, i
+                    )));
+                }
+                valid.insert(&btx.txid);
+            }
         }
+        
+        for btx in &sorted.txs {
+            if valid.contains(btx.txid.as_str()) {
+                continue;
+            }
+            let raw_tx = btx.raw_tx.as_ref()
+                .ok_or_else(|| BeefError::InvalidData(format!("transaction {} has no raw tx", btx.txid)))?;
+            for input in WireTransaction::from_bytes(raw_tx)?.inputs {
+                if !valid.contains(input.prev_out.txid.as_str()) {
+                    return Err(BeefError::VerificationFailed(format!(
+                        "transaction {} spends {}, which the BEEF does not prove", btx.txid, input.prev_out.txid
+                    )));
+                }
+            }
+            valid.insert(&btx.txid);
+        }
+        
+        Ok(roots)
     }
     
     /// Verify BEEF against chain tracker
     /// Reference: TS Beef.verify() line 612
-    pub async fn verify(&self, _chain_tracker: &dyn ChainTracker, _check_spent: bool) -> BeefResult<bool> {
-        // TODO: Implement full BEEF verification
-        // 1. Verify all BUMPs against chain
-        // 2. Verify transaction dependency order
-        // 3. Verify all inputs reference known transactions
-        // 4. Optionally check spent status
-        Err(BeefError::NotImplemented("verify requires ChainTracker integration"))
+    ///
+    /// False when the structure is invalid or a claimed merkle root is not
+    /// the root of its block according to `chain_tracker`.
+    pub async fn verify(&self, chain_tracker: &dyn ChainTracker, allow_txid_only: bool) -> BeefResult<bool> {
+        let roots = match self.verify_valid(allow_txid_only) {
+            Ok(roots) => roots,
+            Err(BeefError::VerificationFailed(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        for (height, root) in &roots {
+            if !chain_tracker.is_valid_root_for_height(root, *height)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
     
     /// Clone this BEEF
@@ -284,23 +625,140 @@ impl Beef {
     }
     
     /// Serialize to binary format
+    ///
+    /// Format per BRC-62 (V1) / BRC-96 (V2):
+    /// - Version (4 bytes)
+    /// - nBUMPs (varint), BUMPs
+    /// - nTransactions (varint), transactions
+    ///
     /// Reference: TS Beef.toBinary()
     pub fn to_binary(&self) -> BeefResult<Vec<u8>> {
-        // TODO: Implement BEEF binary serialization
-        // Format per BRC-62:
-        // - Version (4 bytes)
-        // - nBUMPs (varint)
-        // - BUMPs data
-        // - nTransactions (varint)
-        // - Transactions data
-        Err(BeefError::NotImplemented("to_binary requires BEEF binary serializer"))
+        let mut out = self.version.to_le_bytes().to_vec();
+        out.extend_from_slice(&encode_varint(self.bumps.len() as u64));
+        for bump in &self.bumps {
+            out.extend_from_slice(&bump.to_binary()?);
+        }
+        out.extend_from_slice(&encode_varint(self.txs.len() as u64));
+        for btx in &self.txs {
+            if btx.is_txid_only {
+                if self.version == BEEF_V1 {
+                    return Err(BeefError::InvalidData(
+                        format!("BEEF V1 cannot contain txid only transaction {}", btx.txid)
+                    ));
+                }
+                out.push(TxDataFormat::TxidOnly as u8);
+                let mut txid = hex::decode(&btx.txid).map_err(|e| BeefError::InvalidData(e.to_string()))?;
+                txid.reverse();
+                out.extend_from_slice(&txid);
+                continue;
+            }
+            let raw_tx = btx.raw_tx.as_ref()
+                .ok_or_else(|| BeefError::InvalidData(format!("missing rawTx for {}", btx.txid)))?;
+            if self.version == BEEF_V1 {
+                out.extend_from_slice(raw_tx);
+                match btx.bump_index {
+                    Some(i) => {
+                        out.push(1);
+                        out.extend_from_slice(&encode_varint(i as u64));
+                    }
+                    None => out.push(0),
+                }
+            } else {
+                match btx.bump_index {
+                    Some(i) => {
+                        out.push(TxDataFormat::RawTxAndBumpIndex as u8);
+                        out.extend_from_slice(&encode_varint(i as u64));
+                    }
+                    None => out.push(TxDataFormat::RawTx as u8),
+                }
+                out.extend_from_slice(raw_tx);
+            }
+        }
+        Ok(out)
+    }
+    
+    /// Serialize as Atomic BEEF (BRC-95) for `txid`
+    ///
+    /// Reference: TS Beef.toBinaryAtomic()
+    pub fn to_binary_atomic(&self, txid: &str) -> BeefResult<Vec<u8>> {
+        if self.find_txid(txid).is_none() {
+            return Err(BeefError::TxNotFound(txid.to_string()));
+        }
+        let mut out = ATOMIC_BEEF.to_le_bytes().to_vec();
+        let mut id = hex::decode(txid).map_err(|e| BeefError::InvalidData(e.to_string()))?;
+        id.reverse();
+        out.extend_from_slice(&id);
+        out.extend_from_slice(&self.to_binary()?);
+        Ok(out)
     }
     
-    /// Deserialize from binary format
+    /// Deserialize from binary format (BEEF V1, V2 or Atomic BEEF)
+    ///
     /// Reference: TS Beef.fromBinary()
-    pub fn from_binary(_data: &[u8]) -> BeefResult<Self> {
-        // TODO: Implement BEEF binary deserialization
-        Err(BeefError::NotImplemented("from_binary requires BEEF binary parser"))
+    pub fn from_binary(data: &[u8]) -> BeefResult<Self> {
+        let mut reader = ByteReader::new(data);
+        let mut version = reader.read_u32_le()?;
+        let mut atomic_txid = None;
+        if version == ATOMIC_BEEF {
+            atomic_txid = Some(hex::encode(reader.read_reverse(32)?));
+            version = reader.read_u32_le()?;
+        }
+        if version != BEEF_V1 && version != BEEF_V2 {
+            return Err(BeefError::InvalidData(format!("unsupported BEEF version {:#010x}", version)));
+        }
+        
+        let mut beef = Beef::new(version);
+        beef.atomic_txid = atomic_txid;
+        
+        let n_bumps = reader.read_varint()?;
+        for _ in 0..n_bumps {
+            beef.bumps.push(MerklePath::read_from(&mut reader)?);
+        }
+        
+        let n_txs = reader.read_varint()?;
+        for _ in 0..n_txs {
+            let (bump_index, is_txid_only) = if version == BEEF_V2 {
+                match reader.read_u8()? {
+                    0 => (None, false),
+                    1 => (Some(reader.read_varint()? as usize), false),
+                    2 => (None, true),
+                    f => return Err(BeefError::InvalidData(format!("invalid tx data format {}", f))),
+                }
+            } else {
+                (None, false)
+            };
+            
+            if is_txid_only {
+                beef.merge_txid_only(&hex::encode(reader.read_reverse(32)?));
+                continue;
+            }
+            
+            let start = reader.position();
+            let tx = WireTransaction::read_from(&mut reader)?;
+            let raw_tx = data[start..reader.position()].to_vec();
+            let bump_index = if version == BEEF_V1 {
+                match reader.read_u8()? {
+                    0 => None,
+                    _ => Some(reader.read_varint()? as usize),
+                }
+            } else {
+                bump_index
+            };
+            if let Some(i) = bump_index {
+                if i >= beef.bumps.len() {
+                    return Err(BeefError::InvalidData(format!("bump index {} out of range", i)));
+                }
+            }
+            beef.txs.push(BeefTx {
+                txid: tx.txid()?,
+                raw_tx: Some(raw_tx),
+                tx: None,
+                bump_index,
+                is_txid_only: false,
+            });
+        }
+        
+        Ok(beef)
     }
     
     /// Get human-readable log string
@@ -336,7 +794,7 @@ impl Beef {
 // 6. merge_beef() - CRITICAL for merging BEEFs
 // 7. merge_raw_tx() - CRITICAL for adding transactions
 // 8. merge_bump() ✅ (simple, done)
-// 9. verify() - CRITICAL for validation
+// 9. verify() ✅
 // 10. to_binary() - CRITICAL for serialization
 //
 // TESTING STRATEGY:
@@ -344,3 +802,179 @@ impl Beef {
 // - Integration tests with real transactions
 // - Round-trip serialization tests
 // - Verification tests with mock ChainTracker
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{OutPoint, TxInput, TxOutput};
+    
+    fn raw_tx(prev_txid: &str, satoshis: i64) -> (String, Vec<u8>) {
+        let mut tx = WireTransaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(prev_txid, 0)));
+        tx.add_output(TxOutput::new(satoshis, vec![0x51]));
+        (tx.txid().unwrap(), tx.serialize().unwrap())
+    }
+    
+    fn leaf(hash: &str, offset: u32, txid: bool) -> MerklePathNode {
+        MerklePathNode { hash: hash.to_string(), offset: Some(offset), duplicate: false, txid }
+    }
+    
+    #[test]
+    fn test_merkle_path_binary_roundtrip() {
+        // TS Reference: MerklePath.fromBinary(mp.toBinary())
+        let a = "aa".repeat(32);
+        let b = "bb".repeat(32);
+        let path = MerklePath {
+            block_height: 850_000,
+            path: vec![vec![leaf(&a, 0, true), leaf(&b, 1, false)]],
+        };
+        let bin = path.to_binary().unwrap();
+        let parsed = MerklePath::read_from(&mut ByteReader::new(&bin)).unwrap();
+        assert_eq!(parsed.block_height, 850_000);
+        assert_eq!(parsed.path, path.path);
+    }
+    
+    #[test]
+    fn test_merkle_path_compute_root() {
+        let a = "aa".repeat(32);
+        let b = "bb".repeat(32);
+        let path = MerklePath {
+            block_height: 1,
+            path: vec![vec![leaf(&a, 0, true), leaf(&b, 1, false)]],
+        };
+        let root = merkle_hash(&a, &b).unwrap();
+        assert_eq!(path.compute_root(Some(&a)).unwrap(), root);
+        assert_eq!(path.compute_root(Some(&b)).unwrap(), root);
+        assert!(path.compute_root(Some(&"cc".repeat(32))).is_err());
+    }
+    
+    #[test]
+    fn test_merge_bump_combines_same_block() {
+        let a = "aa".repeat(32);
+        let b = "bb".repeat(32);
+        let mut beef = Beef::new_v2();
+        beef.merge_bump(MerklePath { block_height: 5, path: vec![vec![leaf(&a, 0, true), leaf(&b, 1, false)]] });
+        beef.merge_bump(MerklePath { block_height: 5, path: vec![vec![leaf(&a, 0, false), leaf(&b, 1, true)]] });
+        assert_eq!(beef.bumps.len(), 1);
+    }
+    
+    #[test]
+    fn test_merge_proven_tx() {
+        let (txid, raw) = raw_tx(&"11".repeat(32), 1000);
+        let mut beef = Beef::new_v2();
+        beef.merge_raw_tx(&raw).unwrap();
+        let btx = beef.merge_proven_tx(&raw, MerklePath { block_height: 3, path: vec![vec![leaf(&txid, 0, true)]] }).unwrap();
+        assert_eq!(btx.bump_index, Some(0));
+        assert_eq!(beef.txs.len(), 1);
+        assert_eq!(beef.find_bump(&txid).unwrap().block_height, 3);
+    }
+    
+    #[test]
+    fn test_beef_v2_binary_roundtrip() {
+        // TS Reference: Beef.fromBinary(beef.toBinary())
+        let (parent_txid, parent_raw) = raw_tx(&"11".repeat(32), 1000);
+        let (child_txid, child_raw) = raw_tx(&parent_txid, 900);
+        
+        let mut beef = Beef::new_v2();
+        beef.merge_bump(MerklePath { block_height: 7, path: vec![vec![leaf(&parent_txid, 0, true)]] });
+        beef.merge_raw_tx_with_bump(&parent_raw, Some(0)).unwrap();
+        beef.merge_raw_tx(&child_raw).unwrap();
+        beef.merge_txid_only(&"22".repeat(32));
+        
+        let bin = beef.to_binary().unwrap();
+        assert_eq!(hex::encode(&bin[..4]), "0200beef");
+        let parsed = Beef::from_binary(&bin).unwrap();
+        assert_eq!(parsed.version, BEEF_V2);
+        assert_eq!(parsed.bumps.len(), 1);
+        assert_eq!(parsed.txs.len(), 3);
+        assert_eq!(parsed.find_txid(&parent_txid).unwrap().bump_index, Some(0));
+        assert_eq!(parsed.find_txid(&child_txid).unwrap().raw_tx.as_deref(), Some(&child_raw[..]));
+        assert!(parsed.find_txid(&"22".repeat(32)).unwrap().is_txid_only);
+    }
+    
+    #[test]
+    fn test_beef_v1_rejects_txid_only() {
+        let mut beef = Beef::new(BEEF_V1);
+        assert_eq!(hex::encode(beef.to_binary().unwrap()), "0100beef0000");
+        beef.merge_txid_only(&"22".repeat(32));
+        assert!(beef.to_binary().is_err());
+    }
+    
+    #[test]
+    fn test_atomic_beef_roundtrip() {
+        // TS Reference: Beef.toBinaryAtomic(txid)
+        let (txid, raw) = raw_tx(&"11".repeat(32), 1000);
+        let mut beef = Beef::new_v2();
+        beef.merge_raw_tx(&raw).unwrap();
+        let bin = beef.to_binary_atomic(&txid).unwrap();
+        assert_eq!(&bin[..4], &ATOMIC_BEEF.to_le_bytes());
+        let parsed = Beef::from_binary(&bin).unwrap();
+        assert_eq!(parsed.atomic_txid.as_deref(), Some(txid.as_str()));
+        assert!(beef.to_binary_atomic(&"33".repeat(32)).is_err());
+    }
+    
+    #[test]
+    fn test_merge_beef_upgrades_txid_only_and_sorts() {
+        // TS Reference: Beef.mergeBeef() replaces txid-only entries with full transactions
+        let (parent_txid, parent_raw) = raw_tx(&"11".repeat(32), 1000);
+        let (child_txid, child_raw) = raw_tx(&parent_txid, 900);
+        
+        let mut beef = Beef::new_v2();
+        beef.merge_raw_tx(&child_raw).unwrap();
+        beef.merge_txid_only(&parent_txid);
+        
+        let mut other = Beef::new_v2();
+        other.merge_raw_tx(&parent_raw).unwrap();
+        beef.merge_beef(&other.to_binary().unwrap()).unwrap();
+        
+        assert_eq!(beef.txs.len(), 2);
+        assert_eq!(beef.txs[0].txid, parent_txid);
+        assert!(!beef.txs[0].is_txid_only);
+        assert_eq!(beef.txs[1].txid, child_txid);
+    }
+    
+    struct Roots(BTreeMap<u32, String>);
+    
+    impl ChainTracker for Roots {
+        fn verify_merkle_path(&self, path: &MerklePath) -> BeefResult<bool> {
+            self.is_valid_root_for_height(&path.compute_root(None)?, path.block_height)
+        }
+        
+        fn is_valid_root_for_height(&self, merkle_root: &str, height: u32) -> BeefResult<bool> {
+            Ok(self.0.get(&height).is_some_and(|root| root == merkle_root))
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_verify_proven_ancestry() {
+        // TS Reference: Beef.verifyValid() / Beef.verify(chainTracker)
+        let (parent_txid, parent_raw) = raw_tx(&"11".repeat(32), 1000);
+        let (_, child_raw) = raw_tx(&parent_txid, 900);
+        let (_, orphan_raw) = raw_tx(&"44".repeat(32), 800);
+        
+        let mut beef = Beef::new_v2();
+        beef.merge_bump(MerklePath { block_height: 7, path: vec![vec![leaf(&parent_txid, 0, true)]] });
+        beef.merge_raw_tx_with_bump(&parent_raw, Some(0)).unwrap();
+        beef.merge_raw_tx(&child_raw).unwrap();
+        
+        let roots = beef.verify_valid(false).unwrap();
+        assert_eq!(roots.get(&7), Some(&parent_txid));
+        assert!(beef.verify(&Roots(roots.clone()), false).await.unwrap());
+        assert!(!beef.verify(&Roots(BTreeMap::from([(7, "55".repeat(32))])), false).await.unwrap());
+        
+        let mut unproven = beef.clone();
+        unproven.merge_raw_tx(&orphan_raw).unwrap();
+        assert!(matches!(unproven.verify_valid(false), Err(BeefError::VerificationFailed(_))));
+        
+        let mut partial = Beef::new_v2();
+        partial.merge_txid_only(&parent_txid);
+        partial.merge_raw_tx(&child_raw).unwrap();
+        assert!(partial.verify_valid(false).is_err());
+        assert!(partial.verify_valid(true).unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_from_binary_rejects_bad_version() {
+        assert!(Beef::from_binary(&[0, 0, 0, 0, 0, 0]).is_err());
+    }
+}
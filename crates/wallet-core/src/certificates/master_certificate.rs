@@ -0,0 +1,294 @@
+//! Master Certificate Field Encryption
+//!
+//! **Reference**: TypeScript bsv-sdk `MasterCertificate`
+//!
+//! Each field value is encrypted under a random symmetric key. The master
+//! keyring holds those keys BRC-2 encrypted between the certificate subject
+//! and certifier, so either can recover every field.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+
+use crate::crypto::{decrypt_with_aes_gcm, encrypt_with_aes_gcm};
+use crate::keys::RootKeyDeriver;
+use crate::methods::encrypt_decrypt::{decrypt, encrypt};
+use crate::sdk::{WalletDecryptArgs, WalletEncryptArgs, WalletError, WalletResult};
+
+/// Protocol under which field revelation keys are encrypted
+pub const CERTIFICATE_FIELD_ENCRYPTION_PROTOCOL: &str = "certificate field encryption";
+
+/// Encrypted fields and the master keyring for them
+///
+/// Reference: TS CreateCertificateFieldsResult
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateFieldsResult {
+    /// Encrypted field values (base64) by field name
+    pub certificate_fields: HashMap<String, String>,
+
+    /// Encrypted field revelation keys (base64) by field name
+    pub master_keyring: HashMap<String, String>,
+}
+
+/// Protocol and key ID for a field revelation key
+///
+/// Master keyrings use the field name alone; keyrings for a verifier are
+/// also bound to the serial number.
+///
+/// Reference: TS Certificate.getCertificateFieldEncryptionDetails
+pub fn field_encryption_details(field_name: &str, serial_number: Option<&str>) -> ((u8, String), String) {
+    let key_id = match serial_number {
+        Some(serial_number) => format!("{} {}", serial_number, field_name),
+        None => field_name.to_string(),
+    };
+    ((2, CERTIFICATE_FIELD_ENCRYPTION_PROTOCOL.to_string()), key_id)
+}
+
+/// Encrypt plaintext fields, with a master keyring shared with `counterparty`
+///
+/// Reference: TS MasterCertificate.createCertificateFields(creatorWallet, certifierOrSubject, fields)
+pub async fn create_certificate_fields(
+    creator: &RootKeyDeriver,
+    counterparty: &str,
+    fields: &HashMap<String, String>,
+) -> WalletResult<CertificateFieldsResult> {
+    let mut result = CertificateFieldsResult {
+        certificate_fields: HashMap::new(),
+        master_keyring: HashMap::new(),
+    };
+
+    for (field_name, field_value) in fields {
+        let mut field_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut field_key);
+        let encrypted_value = encrypt_with_aes_gcm(field_value.as_bytes(), &field_key)?;
+        result.certificate_fields.insert(field_name.clone(), STANDARD.encode(encrypted_value));
+
+        let (protocol_id, key_id) = field_encryption_details(field_name, None);
+        let args = WalletEncryptArgs {
+            protocol_id,
+            key_id,
+            plaintext: field_key.to_vec(),
+            counterparty: Some(counterparty.to_string()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        let encrypted_key = encrypt(&args, creator).await?.ciphertext;
+        result.master_keyring.insert(field_name.clone(), STANDARD.encode(encrypted_key));
+    }
+
+    Ok(result)
+}
+
+/// Decrypt one field using the master keyring
+///
+/// Returns the field revelation key and the plaintext value.
+///
+/// Reference: TS MasterCertificate.decryptField
+pub async fn decrypt_field(
+    wallet: &RootKeyDeriver,
+    master_keyring: &HashMap<String, String>,
+    field_name: &str,
+    encrypted_value: &str,
+    counterparty: &str,
+) -> WalletResult<(Vec<u8>, String)> {
+    let encrypted_key = master_keyring.get(field_name)
+        .ok_or_else(|| WalletError::invalid_parameter("masterKeyring", format!("a key for field {}", field_name)))?;
+    let (protocol_id, key_id) = field_encryption_details(field_name, None);
+    let args = WalletDecryptArgs {
+        protocol_id,
+        key_id,
+        ciphertext: STANDARD.decode(encrypted_key)
+            .map_err(|_| WalletError::invalid_parameter("masterKeyring", "base64 values"))?,
+        counterparty: Some(counterparty.to_string()),
+        privileged: None,
+        privileged_reason: None,
+    };
+    let field_key = decrypt(&args, wallet).await
+        .map_err(|_| WalletError::invalid_operation(format!("Failed to decrypt master key for field {}", field_name)))?
+        .plaintext;
+
+    let encrypted_value = STANDARD.decode(encrypted_value)
+        .map_err(|_| WalletError::invalid_parameter("fields", "base64 values"))?;
+    let value = decrypt_with_aes_gcm(&encrypted_value, &field_key)
+        .map_err(|_| WalletError::invalid_operation(format!("Failed to decrypt field {}", field_name)))?;
+    let value = String::from_utf8(value)
+        .map_err(|_| WalletError::invalid_operation(format!("Field {} is not UTF-8", field_name)))?;
+    Ok((field_key, value))
+}
+
+/// Decrypt every field using the master keyring
+///
+/// Reference: TS MasterCertificate.decryptFields(subjectOrCertifierWallet, masterKeyring, fields, counterparty)
+pub async fn decrypt_fields(
+    wallet: &RootKeyDeriver,
+    master_keyring: &HashMap<String, String>,
+    fields: &HashMap<String, String>,
+    counterparty: &str,
+) -> WalletResult<HashMap<String, String>> {
+    if master_keyring.is_empty() {
+        return Err(WalletError::invalid_parameter("masterKeyring", "a non-empty keyring"));
+    }
+    let mut decrypted = HashMap::new();
+    for (field_name, encrypted_value) in fields {
+        let (_, value) = decrypt_field(wallet, master_keyring, field_name, encrypted_value, counterparty).await?;
+        decrypted.insert(field_name.clone(), value);
+    }
+    Ok(decrypted)
+}
+
+/// Re-encrypt the revelation keys of `fields_to_reveal` for `verifier`
+///
+/// Each key is recovered from the master keyring, checked against its
+/// field, then encrypted for the verifier under a key ID bound to the
+/// serial number so it only opens this certificate.
+///
+/// Reference: TS MasterCertificate.createKeyringForVerifier
+pub async fn create_keyring_for_verifier(
+    subject: &RootKeyDeriver,
+    certifier: &str,
+    verifier: &str,
+    fields: &HashMap<String, String>,
+    fields_to_reveal: &[String],
+    master_keyring: &HashMap<String, String>,
+    serial_number: &str,
+) -> WalletResult<HashMap<String, String>> {
+    let mut keyring = HashMap::new();
+    for field_name in fields_to_reveal {
+        let encrypted_value = fields.get(field_name)
+            .ok_or_else(|| WalletError::invalid_parameter(
+                "fieldsToReveal",
+                format!("a subset of the certificate fields, {} is not a field", field_name),
+            ))?;
+        let (field_key, _) = decrypt_field(subject, master_keyring, field_name, encrypted_value, certifier).await?;
+
+        let (protocol_id, key_id) = field_encryption_details(field_name, Some(serial_number));
+        let args = WalletEncryptArgs {
+            protocol_id,
+            key_id,
+            plaintext: field_key,
+            counterparty: Some(verifier.to_string()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        let encrypted_key = encrypt(&args, subject).await?.ciphertext;
+        keyring.insert(field_name.clone(), STANDARD.encode(encrypted_key));
+    }
+    Ok(keyring)
+}
+
+/// Decrypt the fields a keyring for verifier reveals
+///
+/// Reference: TS VerifiableCertificate.decryptFields(verifierWallet)
+pub async fn decrypt_fields_for_verifier(
+    verifier: &RootKeyDeriver,
+    keyring: &HashMap<String, String>,
+    fields: &HashMap<String, String>,
+    serial_number: &str,
+    subject: &str,
+) -> WalletResult<HashMap<String, String>> {
+    if keyring.is_empty() {
+        return Err(WalletError::invalid_parameter("keyring", "a non-empty keyring"));
+    }
+    let mut decrypted = HashMap::new();
+    for (field_name, encrypted_key) in keyring {
+        let (protocol_id, key_id) = field_encryption_details(field_name, Some(serial_number));
+        let args = WalletDecryptArgs {
+            protocol_id,
+            key_id,
+            ciphertext: STANDARD.decode(encrypted_key)
+                .map_err(|_| WalletError::invalid_parameter("keyring", "base64 values"))?,
+            counterparty: Some(subject.to_string()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        let field_key = decrypt(&args, verifier).await
+            .map_err(|_| WalletError::invalid_operation(format!("Failed to decrypt revelation key for field {}", field_name)))?
+            .plaintext;
+
+        let encrypted_value = fields.get(field_name)
+            .ok_or_else(|| WalletError::invalid_parameter("keyring", format!("only certificate fields, {} is not a field", field_name)))?;
+        let encrypted_value = STANDARD.decode(encrypted_value)
+            .map_err(|_| WalletError::invalid_parameter("fields", "base64 values"))?;
+        let value = decrypt_with_aes_gcm(&encrypted_value, &field_key)
+            .map_err(|_| WalletError::invalid_operation(format!("Failed to decrypt field {}", field_name)))?;
+        let value = String::from_utf8(value)
+            .map_err(|_| WalletError::invalid_operation(format!("Field {} is not UTF-8", field_name)))?;
+        decrypted.insert(field_name.clone(), value);
+    }
+    Ok(decrypted)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fields_decrypt_for_subject_and_certifier() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let fields = HashMap::from([
+            ("name".to_string(), "Alice".to_string()),
+            ("email".to_string(), "alice@example.com".to_string()),
+        ]);
+
+        let created = create_certificate_fields(&subject, &certifier.identity_key_hex(), &fields).await.unwrap();
+        assert_ne!(created.certificate_fields["name"], STANDARD.encode("Alice"));
+
+        let by_certifier = decrypt_fields(&certifier, &created.master_keyring, &created.certificate_fields, &subject.identity_key_hex()).await.unwrap();
+        assert_eq!(by_certifier, fields);
+        let by_subject = decrypt_fields(&subject, &created.master_keyring, &created.certificate_fields, &certifier.identity_key_hex()).await.unwrap();
+        assert_eq!(by_subject, fields);
+
+        let other = RootKeyDeriver::new(&[3u8; 32]).unwrap();
+        assert!(decrypt_fields(&other, &created.master_keyring, &created.certificate_fields, &subject.identity_key_hex()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_keyring_for_verifier_reveals_selected_fields() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let verifier = RootKeyDeriver::new(&[3u8; 32]).unwrap();
+        let fields = HashMap::from([
+            ("name".to_string(), "Alice".to_string()),
+            ("email".to_string(), "alice@example.com".to_string()),
+        ]);
+        let serial_number = STANDARD.encode([9u8; 32]);
+
+        let created = create_certificate_fields(&subject, &certifier.identity_key_hex(), &fields).await.unwrap();
+        let keyring = create_keyring_for_verifier(
+            &subject,
+            &certifier.identity_key_hex(),
+            &verifier.identity_key_hex(),
+            &created.certificate_fields,
+            &["name".to_string()],
+            &created.master_keyring,
+            &serial_number,
+        ).await.unwrap();
+        assert_eq!(keyring.len(), 1);
+
+        let revealed = decrypt_fields_for_verifier(&verifier, &keyring, &created.certificate_fields, &serial_number, &subject.identity_key_hex()).await.unwrap();
+        assert_eq!(revealed, HashMap::from([("name".to_string(), "Alice".to_string())]));
+
+        // Bound to the verifier and the serial number
+        let other = RootKeyDeriver::new(&[4u8; 32]).unwrap();
+        assert!(decrypt_fields_for_verifier(&other, &keyring, &created.certificate_fields, &serial_number, &subject.identity_key_hex()).await.is_err());
+        let other_serial = STANDARD.encode([8u8; 32]);
+        assert!(decrypt_fields_for_verifier(&verifier, &keyring, &created.certificate_fields, &other_serial, &subject.identity_key_hex()).await.is_err());
+
+        // Only certificate fields can be revealed
+        assert!(create_keyring_for_verifier(
+            &subject,
+            &certifier.identity_key_hex(),
+            &verifier.identity_key_hex(),
+            &created.certificate_fields,
+            &["phone".to_string()],
+            &created.master_keyring,
+            &serial_number,
+        ).await.is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Identity Certificates (BRC-52)
+//!
+//! **Reference**: TypeScript bsv-sdk `Certificate` and `MasterCertificate`
+//!
+//! A certificate binds encrypted fields about a subject to the certifier's
+//! signature. Each field is encrypted under its own random key; the master
+//! keyring holds those keys encrypted between subject and certifier.
+
+pub mod master_certificate;
+
+pub use master_certificate::{
+    create_certificate_fields, create_keyring_for_verifier, decrypt_field, decrypt_fields,
+    decrypt_fields_for_verifier, field_encryption_details, CertificateFieldsResult,
+};
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::keys::RootKeyDeriver;
+use crate::methods::signature_operations::{create_signature, verify_signature};
+use crate::sdk::{CreateSignatureArgs, VerifySignatureArgs, WalletError, WalletResult};
+use crate::transaction::transaction::encode_varint;
+
+/// Protocol under which certifiers sign certificates
+pub const CERTIFICATE_SIGNATURE_PROTOCOL: &str = "certificate signature";
+
+/// Signed identity certificate
+///
+/// Reference: TS Certificate class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    /// Certificate type (base64 of 32 bytes)
+    #[serde(rename = "type")]
+    pub cert_type: String,
+
+    /// Serial number (base64 of 32 bytes)
+    pub serial_number: String,
+
+    /// Subject identity key
+    pub subject: String,
+
+    /// Certifier identity key
+    pub certifier: String,
+
+    /// Revocation outpoint `txid.vout`
+    pub revocation_outpoint: String,
+
+    /// Encrypted field values (base64) by field name
+    pub fields: HashMap<String, String>,
+
+    /// Certifier's DER signature (hex)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
+}
+
+impl Certificate {
+    /// Serialize the certificate, with or without the signature
+    ///
+    /// Reference: TS Certificate.toBinary(includeSignature)
+    pub fn to_binary(&self, include_signature: bool) -> WalletResult<Vec<u8>> {
+        let decode_b64 = |value: &str, name: &str| STANDARD.decode(value)
+            .map_err(|_| WalletError::invalid_parameter(name.to_string(), "a base64 string"));
+        let decode_hex = |value: &str, name: &str| hex::decode(value)
+            .map_err(|_| WalletError::invalid_parameter(name.to_string(), "a hex string"));
+
+        let mut bytes = Vec::new();
+        bytes.extend(decode_b64(&self.cert_type, "type")?);
+        bytes.extend(decode_b64(&self.serial_number, "serialNumber")?);
+        bytes.extend(decode_hex(&self.subject, "subject")?);
+        bytes.extend(decode_hex(&self.certifier, "certifier")?);
+
+        let (txid, vout) = self.revocation_outpoint.split_once('.')
+            .and_then(|(txid, vout)| Some((txid, vout.parse::<u64>().ok()?)))
+            .ok_or_else(|| WalletError::invalid_parameter("revocationOutpoint", "an outpoint string txid.vout"))?;
+        bytes.extend(decode_hex(txid, "revocationOutpoint")?);
+        bytes.extend(encode_varint(vout));
+
+        let mut field_names: Vec<&String> = self.fields.keys().collect();
+        field_names.sort();
+        bytes.extend(encode_varint(field_names.len() as u64));
+        for name in field_names {
+            let value = &self.fields[name];
+            bytes.extend(encode_varint(name.len() as u64));
+            bytes.extend(name.as_bytes());
+            bytes.extend(encode_varint(value.len() as u64));
+            bytes.extend(value.as_bytes());
+        }
+
+        if include_signature && !self.signature.is_empty() {
+            bytes.extend(decode_hex(&self.signature, "signature")?);
+        }
+        Ok(bytes)
+    }
+
+    /// Key ID under which the certificate is signed
+    fn signature_key_id(&self) -> String {
+        format!("{} {}", self.cert_type, self.serial_number)
+    }
+
+    /// Sign as certifier, replacing `certifier` with our identity key
+    ///
+    /// Reference: TS Certificate.sign(certifierWallet)
+    pub async fn sign(&mut self, certifier: &RootKeyDeriver) -> WalletResult<()> {
+        if !self.signature.is_empty() {
+            return Err(WalletError::invalid_operation("Certificate has already been signed"));
+        }
+        self.certifier = certifier.identity_key_hex();
+        let args = CreateSignatureArgs {
+            protocol_id: (2, CERTIFICATE_SIGNATURE_PROTOCOL.to_string()),
+            key_id: self.signature_key_id(),
+            data: Some(self.to_binary(false)?),
+            hash_to_directly_sign: None,
+            counterparty: None,
+            privileged: None,
+            privileged_reason: None,
+        };
+        self.signature = hex::encode(create_signature(&args, certifier).await?.signature);
+        Ok(())
+    }
+
+    /// Check the certifier's signature, as anyone can
+    ///
+    /// Reference: TS Certificate.verify()
+    pub async fn verify(&self) -> WalletResult<bool> {
+        let signature = hex::decode(&self.signature)
+            .map_err(|_| WalletError::invalid_parameter("signature", "a hex string"))?;
+        let anyone = RootKeyDeriver::anyone();
+        let args = VerifySignatureArgs {
+            protocol_id: (2, CERTIFICATE_SIGNATURE_PROTOCOL.to_string()),
+            key_id: self.signature_key_id(),
+            data: Some(self.to_binary(false)?),
+            hash_to_directly_verify: None,
+            signature,
+            for_self: None,
+            counterparty: Some(self.certifier.clone()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        Ok(verify_signature(&args, &anyone).await.is_ok())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate(subject: &RootKeyDeriver) -> Certificate {
+        Certificate {
+            cert_type: STANDARD.encode([1u8; 32]),
+            serial_number: STANDARD.encode([2u8; 32]),
+            subject: subject.identity_key_hex(),
+            certifier: String::new(),
+            revocation_outpoint: format!("{}.0", "ab".repeat(32)),
+            fields: HashMap::from([
+                ("name".to_string(), "QWxpY2U=".to_string()),
+                ("email".to_string(), "YUBiLmM=".to_string()),
+            ]),
+            signature: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+
+        let mut cert = certificate(&subject);
+        cert.sign(&certifier).await.unwrap();
+        assert_eq!(cert.certifier, certifier.identity_key_hex());
+        assert!(cert.verify().await.unwrap());
+        assert!(cert.sign(&certifier).await.is_err());
+
+        // Any change to the signed content invalidates it
+        cert.fields.insert("name".to_string(), "Qm9i".to_string());
+        assert!(!cert.verify().await.unwrap());
+    }
+
+    #[test]
+    fn test_to_binary_layout() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let mut cert = certificate(&subject);
+        cert.certifier = subject.identity_key_hex();
+
+        let bytes = cert.to_binary(false).unwrap();
+        // type, serial, subject, certifier, txid, vout, field count
+        assert_eq!(&bytes[162..164], &[0, 2]);
+        // Fields sorted by name
+        assert_eq!(&bytes[164..170], b"\x05email");
+
+        cert.signature = "3006020101020101".to_string();
+        assert_eq!(cert.to_binary(true).unwrap().len(), bytes.len() + 8);
+    }
+}
@@ -8,7 +8,9 @@
 pub mod signing;
 pub mod keys;
 pub mod symmetric;
+pub mod schnorr;
 
-pub use signing::{sign_ecdsa, verify_signature as verify_ecdsa, sha256, double_sha256, hmac_sha256, verify_hmac_sha256};
+pub use signing::{sign_ecdsa, verify_signature as verify_ecdsa, sha256, double_sha256, hash160, hmac_sha256, verify_hmac_sha256, pbkdf2_sha512};
 pub use keys::{derive_public_key, KeyDerivationError};
 pub use symmetric::{encrypt_with_aes_gcm, decrypt_with_aes_gcm};
+pub use schnorr::SchnorrProof;
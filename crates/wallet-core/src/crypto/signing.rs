@@ -6,7 +6,7 @@
 //! **Reference**: TypeScript bsv-sdk ECDSA signing
 
 use secp256k1::{Secp256k1, Message, SecretKey, PublicKey, ecdsa::Signature};
-use sha2::{Sha256, Digest};
+use sha2::{Sha256, Sha512, Digest};
 use hmac::{Hmac, Mac};
 
 /// Signing errors
@@ -141,6 +141,15 @@ pub fn double_sha256(data: &[u8]) -> Vec<u8> {
     Sha256::digest(&hash1).to_vec()
 }
 
+/// RIPEMD-160 of SHA-256 (used for P2PKH public key hashes)
+///
+/// **Reference**: TypeScript `Hash.hash160(data)`
+pub fn hash160(data: &[u8]) -> Vec<u8> {
+    use ripemd::Ripemd160;
+    let sha = Sha256::digest(data);
+    Ripemd160::digest(sha).to_vec()
+}
+
 /// Create HMAC-SHA256 from key and data
 ///
 /// **Reference**: TypeScript `Hash.sha256hmac(key, data)`
@@ -184,6 +193,44 @@ pub fn verify_hmac_sha256(key: &[u8], data: &[u8], expected_hmac: &[u8]) -> bool
         .fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
 }
 
+/// Stretch a password with PBKDF2-HMAC-SHA512
+///
+/// **Reference**: TypeScript `Hash.pbkdf2(password, salt, iterations, keylen, 'sha512')`
+///
+/// ## Arguments
+/// - `password`: Password bytes
+/// - `salt`: Salt bytes
+/// - `iterations`: Number of HMAC rounds per block
+/// - `key_len`: Length of the derived key in bytes
+///
+/// ## Returns
+/// `key_len` bytes of derived key material
+pub fn pbkdf2_sha512(password: &[u8], salt: &[u8], iterations: u32, key_len: usize) -> Vec<u8> {
+    type HmacSha512 = Hmac<Sha512>;
+
+    let prf = HmacSha512::new_from_slice(password)
+        .expect("HMAC can take key of any size");
+    let mut derived = Vec::with_capacity(key_len);
+    let mut block_index: u32 = 1;
+    while derived.len() < key_len {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&block_index.to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut block = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            block.iter_mut().zip(u.iter()).for_each(|(b, x)| *b ^= x);
+        }
+        let take = (key_len - derived.len()).min(block.len());
+        derived.extend_from_slice(&block[..take]);
+        block_index += 1;
+    }
+    derived
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,7 +313,16 @@ mod tests {
         let expected = hex::decode("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9").unwrap();
         assert_eq!(hash, expected);
     }
-    
+
+    #[test]
+    fn test_hash160() {
+        // TS Reference: Hash.hash160 of the generator point (private key 1)
+        let pubkey = hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap();
+        let hash = hash160(&pubkey);
+
+        assert_eq!(hex::encode(hash), "751e76e8199196d454941c45d1b3a323f1433bd6");
+    }
+
     #[test]
     fn test_double_sha256() {
         // TS Reference: Double SHA-256 (used for txid)
@@ -354,4 +410,23 @@ mod tests {
         
         assert_eq!(hmac1, hmac2);
     }
+    
+    #[test]
+    fn test_pbkdf2_sha512_known_vector() {
+        // PBKDF2-HMAC-SHA512("password", "salt", 1 round, 64 bytes)
+        let key = pbkdf2_sha512(b"password", b"salt", 1, 64);
+        assert_eq!(
+            hex::encode(key),
+            "867f70cf1ade02cff3752599a3a53dc4af34c7a669815ae5d513554e1c8cf252\
+             c02d470a285a0501bad999bfe943c08f050235d7d68b1da55e63f73b60a57fce"
+        );
+    }
+    
+    #[test]
+    fn test_pbkdf2_sha512_truncates_and_iterates() {
+        let long = pbkdf2_sha512(b"password", b"salt", 2, 64);
+        let short = pbkdf2_sha512(b"password", b"salt", 2, 32);
+        assert_eq!(&long[..32], &short[..]);
+        assert_ne!(long, pbkdf2_sha512(b"password", b"salt", 1, 64));
+    }
 }
@@ -1,14 +1,26 @@
 //! Symmetric Encryption Operations
 //!
 //! AES-256-GCM encryption and decryption for wallet data
+//!
+//! **Reference**: TypeScript bsv-sdk `SymmetricKey` (32-byte IV, as used by BRC-2)
 
 use crate::sdk::errors::{WalletError, WalletResult};
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
-    Aes256Gcm, Nonce,
+    aead::{consts::U32, Aead, KeyInit, OsRng},
+    aes::Aes256,
+    AesGcm, Nonce,
 };
 use rand::RngCore;
 
+/// AES-256-GCM with the 32-byte IV used by TS SymmetricKey
+type Aes256Gcm = AesGcm<Aes256, U32>;
+
+/// IV length prefixed to every ciphertext
+const IV_LEN: usize = 32;
+
+/// GCM authentication tag length appended to every ciphertext
+const TAG_LEN: usize = 16;
+
 /// Encrypt data using AES-256-GCM
 ///
 /// # Arguments
@@ -18,7 +30,7 @@ use rand::RngCore;
 ///
 /// # Returns
 ///
-/// Encrypted data: [12-byte nonce][ciphertext][16-byte tag]
+/// Encrypted data: [32-byte IV][ciphertext][16-byte tag]
 pub fn encrypt_with_aes_gcm(plaintext: &[u8], key: &[u8]) -> WalletResult<Vec<u8>> {
     if key.len() != 32 {
         return Err(WalletError::invalid_parameter(
@@ -31,18 +43,18 @@ pub fn encrypt_with_aes_gcm(plaintext: &[u8], key: &[u8]) -> WalletResult<Vec<u8
     let cipher = Aes256Gcm::new_from_slice(key)
         .map_err(|e| WalletError::invalid_operation(&format!("Failed to create cipher: {}", e)))?;
     
-    // Generate random nonce (96 bits / 12 bytes for GCM)
-    let mut nonce_bytes = [0u8; 12];
+    // Generate random IV
+    let mut nonce_bytes = [0u8; IV_LEN];
     OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+    let nonce = Nonce::<U32>::from_slice(&nonce_bytes);
     
     // Encrypt
     let ciphertext = cipher
         .encrypt(nonce, plaintext)
         .map_err(|e| WalletError::invalid_operation(&format!("Encryption failed: {}", e)))?;
     
-    // Combine: nonce || ciphertext (ciphertext includes auth tag)
-    let mut result = Vec::with_capacity(12 + ciphertext.len());
+    // Combine: IV || ciphertext (ciphertext includes auth tag)
+    let mut result = Vec::with_capacity(IV_LEN + ciphertext.len());
     result.extend_from_slice(&nonce_bytes);
     result.extend_from_slice(&ciphertext);
     
@@ -53,7 +65,7 @@ pub fn encrypt_with_aes_gcm(plaintext: &[u8], key: &[u8]) -> WalletResult<Vec<u8
 ///
 /// # Arguments
 ///
-/// * `ciphertext` - Encrypted data: [12-byte nonce][ciphertext][16-byte tag]
+/// * `ciphertext` - Encrypted data: [32-byte IV][ciphertext][16-byte tag]
 /// * `key` - 32-byte decryption key
 ///
 /// # Returns
@@ -67,10 +79,10 @@ pub fn decrypt_with_aes_gcm(ciphertext: &[u8], key: &[u8]) -> WalletResult<Vec<u
         ));
     }
     
-    if ciphertext.len() < 12 + 16 {
+    if ciphertext.len() < IV_LEN + TAG_LEN {
         return Err(WalletError::invalid_parameter(
             "ciphertext",
-            "Too short (need at least 28 bytes for nonce + tag)"
+            "Too short (need at least 48 bytes for IV + tag)"
         ));
     }
     
@@ -78,11 +90,11 @@ pub fn decrypt_with_aes_gcm(ciphertext: &[u8], key: &[u8]) -> WalletResult<Vec<u
     let cipher = Aes256Gcm::new_from_slice(key)
         .map_err(|e| WalletError::invalid_operation(&format!("Failed to create cipher: {}", e)))?;
     
-    // Extract nonce (first 12 bytes)
-    let nonce = Nonce::from_slice(&ciphertext[0..12]);
+    // Extract IV (first 32 bytes)
+    let nonce = Nonce::<U32>::from_slice(&ciphertext[..IV_LEN]);
     
     // Extract ciphertext + tag (rest of data)
-    let encrypted_data = &ciphertext[12..];
+    let encrypted_data = &ciphertext[IV_LEN..];
     
     // Decrypt
     let plaintext = cipher
@@ -106,7 +118,7 @@ mod tests {
         let plaintext = b"Hello, World!";
         
         let ciphertext = encrypt_with_aes_gcm(plaintext, &key).unwrap();
-        assert!(ciphertext.len() >= plaintext.len() + 12 + 16);
+        assert_eq!(ciphertext.len(), plaintext.len() + IV_LEN + TAG_LEN);
         
         let decrypted = decrypt_with_aes_gcm(&ciphertext, &key).unwrap();
         assert_eq!(&decrypted[..], plaintext);
@@ -132,7 +144,7 @@ mod tests {
         let mut ciphertext = encrypt_with_aes_gcm(plaintext, &key).unwrap();
         
         // Corrupt the ciphertext
-        ciphertext[20] ^= 0xFF;
+        ciphertext[IV_LEN + 2] ^= 0xFF;
         
         let result = decrypt_with_aes_gcm(&ciphertext, &key);
         assert!(result.is_err());
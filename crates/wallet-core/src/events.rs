@@ -0,0 +1,209 @@
+//! Wallet Event Bus
+//!
+//! Notifies applications of transaction lifecycle changes without polling.
+//!
+//! The `Wallet`, monitor tasks and the permissions manager emit `WalletEvent`s
+//! into a shared `WalletEventBus`; applications `subscribe` for a receiver.
+//! Storage does not depend on wallet-core, so changes made within storage are
+//! emitted by the wallet or monitor task that made the storage call.
+//!
+//! Delivery is best effort: events emitted with no subscribers are dropped,
+//! and a subscriber that falls more than the bus capacity behind skips the
+//! oldest events (`RecvError::Lagged`).
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use wallet_storage::TransactionStatus;
+
+use crate::managers::wallet_permissions_manager::PermissionType;
+use crate::sdk::action_process::SendWithResult;
+
+/// Default number of events buffered for each subscriber
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// A change applications may react to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WalletEvent {
+    /// A transaction moved to a new status, e.g. 'sending' to 'unproven'
+    TransactionStatusChanged {
+        txid: String,
+        status: TransactionStatus,
+    },
+
+    /// A monitor task broadcast a transaction or found its proof
+    ///
+    /// `old_status` equals `new_status` when a broadcast failed with a
+    /// service error and will be retried.
+    TransactionProgress {
+        txid: String,
+        old_status: TransactionStatus,
+        new_status: TransactionStatus,
+        /// Name of the service that broadcast or proved the transaction
+        provider: Option<String>,
+    },
+
+    /// An internalized action increased the wallet's balance
+    PaymentReceived {
+        txid: String,
+        satoshis: i64,
+    },
+
+    /// A pending permission request was granted
+    PermissionGranted {
+        request_id: String,
+        originator: String,
+        /// None for a grouped permission grant
+        permission_type: Option<PermissionType>,
+    },
+
+    /// A sync with another storage finished
+    SyncCompleted {
+        storage_identity_key: String,
+        inserts: i64,
+        updates: i64,
+    },
+}
+
+impl WalletEvent {
+    /// Status changes reported by `sendWithResults`
+    ///
+    /// Results with a status that is not a transaction status are skipped.
+    pub fn from_send_with_results(results: &[SendWithResult]) -> Vec<WalletEvent> {
+        results
+            .iter()
+            .filter_map(|r| {
+                let status = r.status.parse().ok()?;
+                Some(WalletEvent::TransactionStatusChanged { txid: r.txid.clone(), status })
+            })
+            .collect()
+    }
+}
+
+/// Broadcast channel of `WalletEvent`s
+///
+/// Clones share one channel, so a bus can be handed to the wallet, monitor
+/// tasks and managers and subscribed to from any of them.
+#[derive(Debug, Clone)]
+pub struct WalletEventBus {
+    sender: broadcast::Sender<WalletEvent>,
+}
+
+impl WalletEventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Receive every event emitted from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<WalletEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Deliver `event` to current subscribers
+    ///
+    /// Returns the number of subscribers it was delivered to.
+    pub fn emit(&self, event: WalletEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Emit each of `events` in order
+    pub fn emit_all(&self, events: impl IntoIterator<Item = WalletEvent>) {
+        for event in events {
+            self.emit(event);
+        }
+    }
+
+    /// Number of live subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for WalletEventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_changed(txid: &str, status: TransactionStatus) -> WalletEvent {
+        WalletEvent::TransactionStatusChanged { txid: txid.to_string(), status }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_in_order() {
+        let bus = WalletEventBus::default();
+        let mut first = bus.subscribe();
+        let mut second = bus.clone().subscribe();
+        assert_eq!(bus.subscriber_count(), 2);
+
+        assert_eq!(bus.emit(status_changed("aa", TransactionStatus::Sending)), 2);
+        bus.emit(status_changed("aa", TransactionStatus::Unproven));
+
+        for receiver in [&mut first, &mut second] {
+            assert_eq!(receiver.recv().await.unwrap(), status_changed("aa", TransactionStatus::Sending));
+            assert_eq!(receiver.recv().await.unwrap(), status_changed("aa", TransactionStatus::Unproven));
+        }
+    }
+
+    #[test]
+    fn test_emit_without_subscribers_is_dropped() {
+        let bus = WalletEventBus::default();
+        assert_eq!(bus.emit(status_changed("aa", TransactionStatus::Completed)), 0);
+
+        // Later subscribers only see later events
+        let mut receiver = bus.subscribe();
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_from_send_with_results() {
+        let results = vec![
+            SendWithResult { txid: "aa".to_string(), status: "unproven".to_string() },
+            SendWithResult { txid: "bb".to_string(), status: "failed".to_string() },
+            SendWithResult { txid: "cc".to_string(), status: "bogus".to_string() },
+        ];
+        assert_eq!(
+            WalletEvent::from_send_with_results(&results),
+            vec![
+                status_changed("aa", TransactionStatus::Unproven),
+                status_changed("bb", TransactionStatus::Failed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = WalletEvent::PaymentReceived { txid: "aa".to_string(), satoshis: 1000 };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "paymentReceived", "txid": "aa", "satoshis": 1000 })
+        );
+
+        let event = WalletEvent::TransactionProgress {
+            txid: "aa".to_string(),
+            old_status: TransactionStatus::Sending,
+            new_status: TransactionStatus::Unproven,
+            provider: Some("arc".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "type": "transactionProgress",
+                "txid": "aa",
+                "oldStatus": "sending",
+                "newStatus": "unproven",
+                "provider": "arc",
+            })
+        );
+    }
+}
@@ -1,9 +1,19 @@
 //! Key Deriver Trait
 //!
 //! Trait for deriving keys from protocol/keyID/counterparty combinations.
-//! Used by wallet methods to derive cryptographic keys.
+//! Used by wallet methods to derive cryptographic keys. `RootKeyDeriver`
+//! implements it over a wallet root key, remembering recent derivations in a
+//! bounded LRU cache.
 
 use async_trait::async_trait;
+use secp256k1::{PublicKey, SecretKey};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use super::brc42::{compute_shared_secret, derive_child_private_key, derive_child_public_key};
+use super::brc43::{InvoiceNumber, SecurityLevel};
+use crate::crypto::hmac_sha256;
+use crate::sdk::errors::{WalletError, WalletResult};
 
 /// Trait for deriving wallet keys
 ///
@@ -43,4 +53,448 @@ pub trait KeyDeriver: Send + Sync {
         counterparty: &str,
         for_self: bool,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+    
+    /// Derive a symmetric key shared with the counterparty
+    ///
+    /// # Arguments
+    /// * `protocol_id` - Protocol identifier tuple
+    /// * `key_id` - Key identifier string
+    /// * `counterparty` - Counterparty identifier
+    ///
+    /// # Returns
+    /// 32-byte symmetric key
+    async fn derive_symmetric_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Compressed public key of private key 1 (the generator point), used for
+/// the "anyone" counterparty
+///
+/// Reference: TS `new PrivateKey(1).toPublicKey()`
+pub const ANYONE_PUBLIC_KEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+/// Number of derivations a `RootKeyDeriver` remembers by default
+///
+/// Reference: TS `CachedKeyDeriver` maxCacheSize
+pub const DEFAULT_DERIVATION_CACHE_SIZE: usize = 1000;
+
+/// Which key a cached derivation produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Derivation {
+    Private,
+    Public { for_self: bool },
+    Symmetric,
+}
+
+/// Cache key: derivation kind, security level, protocol, key ID, counterparty
+type CacheKey = (Derivation, u8, String, String, String);
+
+/// Bounded least-recently-used map of derived keys
+///
+/// Each entry carries the tick of its last use; `order` maps ticks back to
+/// keys so the oldest entry is evicted in O(log n).
+#[derive(Default)]
+struct DerivationCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, (u64, Vec<u8>)>,
+    order: BTreeMap<u64, CacheKey>,
+}
+
+impl DerivationCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, ..Default::default() }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Vec<u8>> {
+        self.tick += 1;
+        let (used, value) = self.entries.get_mut(key)?;
+        let key = self.order.remove(used).expect("cache order tracks every entry");
+        *used = self.tick;
+        self.order.insert(self.tick, key);
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, value: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((used, _)) = self.entries.insert(key.clone(), (self.tick, value)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            let (_, oldest) = self.order.pop_first().expect("cache order tracks every entry");
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// BRC-42/43 key deriver over a wallet root private key
+///
+/// Derived private, public and symmetric keys are cached (up to
+/// `DEFAULT_DERIVATION_CACHE_SIZE` entries, shared between clones) so
+/// repeated derivations skip the EC point math. `with_cache_size(0)`
+/// disables the cache. The root key is erased when the deriver is dropped.
+///
+/// Reference: TS `KeyDeriver` / `CachedKeyDeriver` from @bsv/sdk
+#[derive(Clone)]
+pub struct RootKeyDeriver {
+    root_key: SecretKey,
+    identity_key: Vec<u8>,
+    cache: Arc<Mutex<DerivationCache>>,
+}
+
+impl std::fmt::Debug for RootKeyDeriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RootKeyDeriver")
+            .field("identity_key", &hex::encode(&self.identity_key))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RootKeyDeriver {
+    fn drop(&mut self) {
+        self.root_key.non_secure_erase();
+    }
+}
+
+impl RootKeyDeriver {
+    /// Create a deriver from a 32-byte root private key
+    pub fn new(root_key: &[u8]) -> WalletResult<Self> {
+        let root_key = SecretKey::from_slice(root_key)
+            .map_err(|_| WalletError::invalid_parameter("rootKey", "a valid 32-byte private key"))?;
+        let identity_key = PublicKey::from_secret_key(secp256k1::SECP256K1, &root_key).serialize().to_vec();
+        let cache = Arc::new(Mutex::new(DerivationCache::new(DEFAULT_DERIVATION_CACHE_SIZE)));
+        Ok(Self { root_key, identity_key, cache })
+    }
+
+    /// Remember at most `size` derivations; 0 disables caching
+    pub fn with_cache_size(mut self, size: usize) -> Self {
+        self.cache = Arc::new(Mutex::new(DerivationCache::new(size)));
+        self
+    }
+
+    /// Number of derivations currently cached
+    pub fn cached_derivations(&self) -> usize {
+        self.cache.lock().expect("derivation cache poisoned").entries.len()
+    }
+
+    /// Deriver over private key 1, whose keys anyone can compute
+    ///
+    /// Reference: TS `new ProtoWallet('anyone')`
+    pub fn anyone() -> Self {
+        let mut root_key = [0u8; 32];
+        root_key[31] = 1;
+        Self::new(&root_key).expect("1 is a valid private key")
+    }
+
+    /// Root private key bytes
+    pub fn root_key(&self) -> [u8; 32] {
+        self.root_key.secret_bytes()
+    }
+
+    /// Compressed identity public key (root public key)
+    pub fn identity_key(&self) -> &[u8] {
+        &self.identity_key
+    }
+
+    /// Identity public key as hex
+    pub fn identity_key_hex(&self) -> String {
+        hex::encode(&self.identity_key)
+    }
+
+    /// Resolve "self", "anyone" or a public key hex to a compressed public key
+    ///
+    /// Reference: TS KeyDeriver.normalizeCounterparty
+    pub fn normalize_counterparty(&self, counterparty: &str) -> WalletResult<Vec<u8>> {
+        match counterparty {
+            "self" => Ok(self.identity_key.clone()),
+            "anyone" => Ok(hex::decode(ANYONE_PUBLIC_KEY).expect("valid constant")),
+            pubkey => hex::decode(pubkey)
+                .ok()
+                .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+                .map(|key| key.serialize().to_vec())
+                .ok_or_else(|| WalletError::invalid_parameter("counterparty", "\"self\", \"anyone\" or a public key hex string")),
+        }
+    }
+
+    /// Derive a private key
+    ///
+    /// Reference: TS KeyDeriver.derivePrivateKey
+    pub fn derive_private_key(&self, protocol_id: &(u8, String), key_id: &str, counterparty: &str) -> WalletResult<Vec<u8>> {
+        self.cached(Derivation::Private, protocol_id, key_id, counterparty, || {
+            self.compute_private_key(protocol_id, key_id, counterparty)
+        })
+    }
+
+    fn compute_private_key(&self, protocol_id: &(u8, String), key_id: &str, counterparty: &str) -> WalletResult<Vec<u8>> {
+        let counterparty_key = self.normalize_counterparty(counterparty)?;
+        let invoice = invoice_number(protocol_id, key_id)?;
+        derive_child_private_key(&self.root_key.secret_bytes(), &counterparty_key, &invoice)
+            .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))
+    }
+
+    /// Derive a public key, either our own (`for_self`) or the counterparty's
+    ///
+    /// Reference: TS KeyDeriver.derivePublicKey
+    pub fn derive_public_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+        for_self: bool,
+    ) -> WalletResult<Vec<u8>> {
+        self.cached(Derivation::Public { for_self }, protocol_id, key_id, counterparty, || {
+            self.compute_public_key(protocol_id, key_id, counterparty, for_self)
+        })
+    }
+
+    fn compute_public_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+        for_self: bool,
+    ) -> WalletResult<Vec<u8>> {
+        if for_self {
+            let private_key = self.derive_private_key(protocol_id, key_id, counterparty)?;
+            let secret = SecretKey::from_slice(&private_key)
+                .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))?;
+            return Ok(PublicKey::from_secret_key(secp256k1::SECP256K1, &secret).serialize().to_vec());
+        }
+        let counterparty_key = self.normalize_counterparty(counterparty)?;
+        let invoice = invoice_number(protocol_id, key_id)?;
+        derive_child_public_key(&self.root_key.secret_bytes(), &counterparty_key, &invoice)
+            .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))
+    }
+
+    /// Derive a 32-byte symmetric key shared with the counterparty (BRC-2)
+    ///
+    /// The x coordinate of our derived private key times their derived
+    /// public key; the counterparty derives the same value from their side.
+    ///
+    /// Reference: TS KeyDeriver.deriveSymmetricKey
+    pub fn derive_symmetric_key(&self, protocol_id: &(u8, String), key_id: &str, counterparty: &str) -> WalletResult<Vec<u8>> {
+        self.cached(Derivation::Symmetric, protocol_id, key_id, counterparty, || {
+            let public_key = self.derive_public_key(protocol_id, key_id, counterparty, false)?;
+            let private_key = self.derive_private_key(protocol_id, key_id, counterparty)?;
+            let shared = compute_shared_secret(&private_key, &public_key)
+                .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))?;
+            Ok(shared[1..].to_vec())
+        })
+    }
+
+    /// Cached result of `derive`, computed and remembered on a miss
+    ///
+    /// The lock is not held while deriving; a concurrent miss on the same
+    /// key just derives it twice.
+    fn cached(
+        &self,
+        kind: Derivation,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+        derive: impl FnOnce() -> WalletResult<Vec<u8>>,
+    ) -> WalletResult<Vec<u8>> {
+        let key = (kind, protocol_id.0, protocol_id.1.clone(), key_id.to_string(), counterparty.to_string());
+        if let Some(value) = self.cache.lock().expect("derivation cache poisoned").get(&key) {
+            return Ok(value);
+        }
+        let value = derive()?;
+        self.cache.lock().expect("derivation cache poisoned").insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// ECDH shared secret with the counterparty, as a compressed point
+    ///
+    /// Reference: TS KeyDeriver.revealCounterpartySecret
+    pub fn reveal_counterparty_secret(&self, counterparty: &str) -> WalletResult<Vec<u8>> {
+        if counterparty == "self" {
+            return Err(WalletError::invalid_parameter("counterparty", "other than \"self\" for counterparty linkage revelation"));
+        }
+        let counterparty_key = self.normalize_counterparty(counterparty)?;
+        if counterparty_key == self.identity_key {
+            return Err(WalletError::invalid_parameter("counterparty", "other than the wallet identity key for counterparty linkage revelation"));
+        }
+        compute_shared_secret(&self.root_key.secret_bytes(), &counterparty_key)
+            .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))
+    }
+
+    /// HMAC of the invoice number keyed by the counterparty shared secret:
+    /// the offset linking the two parties' keys for one protocol and key ID
+    ///
+    /// Reference: TS KeyDeriver.revealSpecificSecret
+    pub fn reveal_specific_secret(&self, counterparty: &str, protocol_id: &(u8, String), key_id: &str) -> WalletResult<Vec<u8>> {
+        let counterparty_key = self.normalize_counterparty(counterparty)?;
+        let shared = compute_shared_secret(&self.root_key.secret_bytes(), &counterparty_key)
+            .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))?;
+        let invoice = invoice_number(protocol_id, key_id)?;
+        Ok(hmac_sha256(&shared, invoice.as_bytes()))
+    }
+}
+
+/// BRC-43 invoice number `<level>-<protocol>-<keyID>`
+///
+/// Reference: TS KeyDeriver.computeInvoiceNumber
+fn invoice_number(protocol_id: &(u8, String), key_id: &str) -> WalletResult<String> {
+    let level = SecurityLevel::from_u8(protocol_id.0)
+        .ok_or_else(|| WalletError::invalid_parameter("protocolID", "a security level of 0, 1 or 2"))?;
+    InvoiceNumber::new(level, protocol_id.1.as_str(), key_id)
+        .map(|invoice| invoice.to_string())
+        .map_err(|e| WalletError::invalid_parameter("protocolID", e))
+}
+
+#[async_trait]
+impl KeyDeriver for RootKeyDeriver {
+    async fn derive_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.derive_private_key(protocol_id, key_id, counterparty)?)
+    }
+
+    async fn derive_public_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+        for_self: bool,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(RootKeyDeriver::derive_public_key(self, protocol_id, key_id, counterparty, for_self)?)
+    }
+
+    async fn derive_symmetric_key(
+        &self,
+        protocol_id: &(u8, String),
+        key_id: &str,
+        counterparty: &str,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(RootKeyDeriver::derive_symmetric_key(self, protocol_id, key_id, counterparty)?)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deriver(byte: u8) -> RootKeyDeriver {
+        RootKeyDeriver::new(&[byte; 32]).unwrap()
+    }
+
+    fn protocol() -> (u8, String) {
+        (2, "tests protocol suite".to_string())
+    }
+
+    #[test]
+    fn test_counterparties_derive_matching_keys() {
+        let alice = deriver(1);
+        let bob = deriver(2);
+        let bob_key = bob.identity_key_hex();
+        let alice_key = alice.identity_key_hex();
+
+        // Alice's view of Bob's derived key equals Bob's own derived key
+        let for_bob = alice.derive_public_key(&protocol(), "1", &bob_key, false).unwrap();
+        let bob_own = bob.derive_public_key(&protocol(), "1", &alice_key, true).unwrap();
+        assert_eq!(for_bob, bob_own);
+
+        let private_key = bob.derive_private_key(&protocol(), "1", &alice_key).unwrap();
+        assert_eq!(crate::crypto::derive_public_key(&private_key).unwrap(), bob_own);
+
+        // Both sides arrive at the same symmetric key
+        assert_eq!(
+            alice.derive_symmetric_key(&protocol(), "1", &bob_key).unwrap(),
+            bob.derive_symmetric_key(&protocol(), "1", &alice_key).unwrap()
+        );
+
+        // Different key IDs give unrelated keys
+        assert_ne!(for_bob, alice.derive_public_key(&protocol(), "2", &bob_key, false).unwrap());
+    }
+
+    #[test]
+    fn test_normalize_counterparty() {
+        let alice = deriver(1);
+        assert_eq!(alice.normalize_counterparty("self").unwrap(), alice.identity_key());
+        assert_eq!(
+            alice.normalize_counterparty("anyone").unwrap(),
+            RootKeyDeriver::anyone().identity_key()
+        );
+        assert!(alice.normalize_counterparty("not a key").is_err());
+        assert!(alice.reveal_counterparty_secret("self").is_err());
+        assert!(alice.derive_public_key(&(3, "tests protocol suite".to_string()), "1", "self", true).is_err());
+    }
+
+    #[test]
+    fn test_revealed_secrets_link_keys() {
+        let alice = deriver(1);
+        let bob = deriver(2);
+        let bob_key = bob.identity_key_hex();
+
+        let shared = alice.reveal_counterparty_secret(&bob_key).unwrap();
+        assert_eq!(shared, bob.reveal_counterparty_secret(&alice.identity_key_hex()).unwrap());
+
+        // Bob's derived key is his identity key offset by the specific secret
+        let offset = alice.reveal_specific_secret(&bob_key, &protocol(), "1").unwrap();
+        let offset = SecretKey::from_slice(&offset).unwrap();
+        let expected = PublicKey::from_slice(bob.identity_key()).unwrap()
+            .add_exp_tweak(secp256k1::SECP256K1, &offset.into())
+            .unwrap();
+        assert_eq!(
+            alice.derive_public_key(&protocol(), "1", &bob_key, false).unwrap(),
+            expected.serialize().to_vec()
+        );
+    }
+
+    #[test]
+    fn test_derivation_cache() {
+        let alice = deriver(1);
+        let bob_key = deriver(2).identity_key_hex();
+        let uncached = deriver(1).with_cache_size(0);
+
+        let first = alice.derive_private_key(&protocol(), "1", &bob_key).unwrap();
+        assert_eq!(alice.cached_derivations(), 1);
+        assert_eq!(alice.derive_private_key(&protocol(), "1", &bob_key).unwrap(), first);
+        assert_eq!(alice.cached_derivations(), 1);
+        assert_eq!(uncached.derive_private_key(&protocol(), "1", &bob_key).unwrap(), first);
+        assert_eq!(uncached.cached_derivations(), 0);
+
+        // Public and private keys for the same inputs are cached separately
+        let public_key = alice.derive_public_key(&protocol(), "1", &bob_key, true).unwrap();
+        assert_eq!(crate::crypto::derive_public_key(&first).unwrap(), public_key);
+        assert_eq!(alice.cached_derivations(), 2);
+
+        // Clones share the cache
+        assert_eq!(alice.clone().cached_derivations(), 2);
+    }
+
+    #[test]
+    fn test_derivation_cache_evicts_least_recently_used() {
+        let alice = deriver(1).with_cache_size(2);
+        alice.derive_private_key(&protocol(), "1", "self").unwrap();
+        alice.derive_private_key(&protocol(), "2", "self").unwrap();
+        // Touch "1" so "2" is the oldest when "3" arrives
+        alice.derive_private_key(&protocol(), "1", "self").unwrap();
+        alice.derive_private_key(&protocol(), "3", "self").unwrap();
+        assert_eq!(alice.cached_derivations(), 2);
+
+        let cache = alice.cache.lock().unwrap();
+        let cached = |key_id: &str| {
+            let key = (Derivation::Private, 2, protocol().1, key_id.to_string(), "self".to_string());
+            cache.entries.contains_key(&key)
+        };
+        assert!(cached("1"));
+        assert!(!cached("2"));
+        assert!(cached("3"));
+    }
 }
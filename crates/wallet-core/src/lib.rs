@@ -20,6 +20,9 @@ pub mod crypto;
 // BRC-42/43 key derivation
 pub mod keys;
 
+// BRC-52 identity certificates
+pub mod certificates;
+
 // Wallet managers (SimpleWalletManager, WalletSettingsManager, etc.)
 pub mod managers;
 
@@ -38,12 +41,29 @@ pub mod wallet;
 // Monitor for transaction tracking
 pub mod monitor;
 
+// Event bus for transaction lifecycle notifications
+pub mod events;
+
 // Setup and initialization
 pub mod setup;
 
 // Service integrations (placeholder - actual services in wallet-services crate)
 pub mod services;
 
+// BRC-103/104 authenticated HTTP (AuthFetch)
+pub mod auth_http;
+
+// Test doubles shared with the wallet-test-utils crate
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+// HTTP wallet server (BRC-100 over HTTP with BRC-103/104 auth)
+#[cfg(feature = "wallet-server")]
+pub mod wallet_server;
+
+#[cfg(all(target_arch = "wasm32", feature = "wallet-server"))]
+compile_error!("the wallet-server feature needs a native target with tokio networking");
+
 // Tauri command handlers for metanet-desktop integration
 #[cfg(feature = "tauri")]
 pub mod tauri_commands;
@@ -5,6 +5,8 @@
 //! Wallet managers provide high-level wallet orchestration and authentication
 
 pub mod simple_wallet_manager;
+pub mod typed_wallet_interface;
+pub mod cwi_style_wallet_manager;
 pub mod wallet_settings_manager;
 pub mod wallet_auth_manager;
 pub mod wallet_permissions_manager;
@@ -16,6 +18,24 @@ pub use simple_wallet_manager::{
     PrivilegedKeyManager,
     WalletBuilder,
     OriginatorDomainName,
+    call_wallet,
+};
+
+pub use typed_wallet_interface::TypedWalletInterface;
+
+pub use cwi_style_wallet_manager::{
+    CWIStyleWalletManager,
+    UMPToken,
+    UMPTokenInteractor,
+    Profile,
+    AuthenticationMode,
+    AuthenticationFlow,
+    PasswordRetriever,
+    PasswordTest,
+    RecoveryKeySaver,
+    NewWalletFunder,
+    PBKDF2_NUM_ROUNDS,
+    DEFAULT_PROFILE_ID,
 };
 
 pub use wallet_settings_manager::{
@@ -34,6 +54,8 @@ pub use wallet_settings_manager::{
 pub use wallet_auth_manager::{
     WalletAuthenticationManager,
     PresentationKeyHex,
+    AuthState,
+    AuthStateCallback,
 };
 
 pub use wallet_permissions_manager::{
@@ -45,7 +67,3 @@ pub use wallet_permissions_manager::{
     GroupedPermissionRequest,
     PermissionsManagerConfig,
 };
-
-// Stubs for remaining managers (to be implemented)
-#[derive(Debug, Default)]
-pub struct CWIStyleWalletManager;
@@ -0,0 +1,1572 @@
+//! CWI-Style Wallet Manager
+//!
+//! **Reference**: TypeScript `src/CWIStyleWalletManager.ts`
+//!
+//! A wallet manager that authenticates users with the CWI-style UMP
+//! (User Management Protocol) token scheme. Three secrets protect the wallet:
+//!
+//! - **Presentation key** - 32 bytes, typically held by a WAB server or the device
+//! - **Password** - stretched into a password key with PBKDF2-HMAC-SHA512
+//! - **Recovery key** - 32 bytes, saved offline by the user
+//!
+//! Any two of the three unlock the root primary key; the root privileged key
+//! is only unlocked on demand. Every combination of factors is stored
+//! encrypted on chain in the user's UMP token, located by the SHA-256 hash of
+//! the presentation key or recovery key.
+
+use crate::crypto::{decrypt_with_aes_gcm, encrypt_with_aes_gcm, pbkdf2_sha512, sha256};
+use crate::keys::RootKeyDeriver;
+use crate::managers::simple_wallet_manager::{
+    open_snapshot, seal_snapshot, PrivilegedKeyManager as PrivilegedKeyProvider, WalletBuilder,
+    WalletInterface,
+};
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::originator::Originator;
+use crate::sdk::{PrivilegedKeyGetter, PrivilegedKeyManager};
+use crate::transaction::transaction::encode_varint;
+use crate::transaction::ByteReader;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use zeroize::Zeroizing;
+
+/// Number of PBKDF2 rounds used to stretch passwords
+///
+/// Reference: TS PBKDF2_NUM_ROUNDS
+pub const PBKDF2_NUM_ROUNDS: u32 = 7777;
+
+/// Profile id of the default profile (sixteen zero bytes)
+///
+/// Reference: TS DEFAULT_PROFILE_ID
+pub const DEFAULT_PROFILE_ID: [u8; 16] = [0; 16];
+
+/// Protocol used to wrap key material with the root privileged key
+const KEY_WRAPPING_PROTOCOL: &str = "admin key wrapping";
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Called with a freshly generated recovery key so the user can save it
+///
+/// Reference: TS recoveryKeySaver
+pub type RecoveryKeySaver = Arc<dyn Fn(Vec<u8>) -> BoxFuture<WalletResult<bool>> + Send + Sync>;
+
+/// Checks a password candidate without exposing key material
+///
+/// Reference: TS passwordRetriever test function
+pub type PasswordTest = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Prompts the user for their password, given a reason and a test function
+///
+/// Reference: TS passwordRetriever
+pub type PasswordRetriever = Arc<dyn Fn(String, PasswordTest) -> BoxFuture<WalletResult<String>> + Send + Sync>;
+
+/// Funds a newly created wallet before its UMP token is published
+///
+/// Reference: TS newWalletFunder
+pub type NewWalletFunder = Arc<
+    dyn Fn(Vec<u8>, Arc<dyn WalletInterface>, String) -> BoxFuture<WalletResult<()>> + Send + Sync
+>;
+
+/// Which two authentication factors the user is providing
+///
+/// Reference: TS AuthenticationMode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthenticationMode {
+    PresentationKeyAndPassword,
+    PresentationKeyAndRecoveryKey,
+    RecoveryKeyAndPassword,
+}
+
+/// Whether the user already has a UMP token
+///
+/// Reference: TS AuthenticationFlow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthenticationFlow {
+    NewUser,
+    ExistingUser,
+}
+
+/// A user profile stored encrypted in the UMP token
+///
+/// Reference: TS Profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    pub id: Vec<u8>,
+    pub primary_pad: Vec<u8>,
+    pub privileged_pad: Vec<u8>,
+    pub created_at: Option<i64>,
+}
+
+/// User Management Protocol token
+///
+/// Reference: TS UMPToken
+///
+/// Field names describe which factors encrypt which key: for example
+/// `password_presentation_primary` is the root primary key encrypted with
+/// XOR(presentation key, password key).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UMPToken {
+    pub password_presentation_primary: Vec<u8>,
+    pub password_recovery_primary: Vec<u8>,
+    pub presentation_recovery_primary: Vec<u8>,
+    pub password_primary_privileged: Vec<u8>,
+    pub presentation_recovery_privileged: Vec<u8>,
+    pub presentation_hash: Vec<u8>,
+    pub password_salt: Vec<u8>,
+    pub recovery_hash: Vec<u8>,
+    pub presentation_key_encrypted: Vec<u8>,
+    pub recovery_key_encrypted: Vec<u8>,
+    pub password_key_encrypted: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profiles_encrypted: Option<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_outpoint: Option<String>,
+}
+
+impl UMPToken {
+    /// PushDrop fields of the on-chain token, in protocol order
+    ///
+    /// Reference: TS OverlayUMPTokenInteractor.buildAndSend
+    pub fn to_fields(&self) -> Vec<Vec<u8>> {
+        let mut fields = vec![
+            self.password_presentation_primary.clone(),
+            self.password_recovery_primary.clone(),
+            self.presentation_recovery_primary.clone(),
+            self.password_primary_privileged.clone(),
+            self.presentation_recovery_privileged.clone(),
+            self.presentation_hash.clone(),
+            self.password_salt.clone(),
+            self.recovery_hash.clone(),
+            self.presentation_key_encrypted.clone(),
+            self.recovery_key_encrypted.clone(),
+            self.password_key_encrypted.clone(),
+        ];
+        if let Some(profiles) = &self.profiles_encrypted {
+            fields.push(profiles.clone());
+        }
+        fields
+    }
+
+    /// Parse the PushDrop fields of an on-chain token
+    ///
+    /// Reference: TS OverlayUMPTokenInteractor.parseLookupAnswer
+    pub fn from_fields(fields: &[Vec<u8>], current_outpoint: Option<String>) -> WalletResult<Self> {
+        if fields.len() < 11 {
+            return Err(WalletError::invalid_parameter("fields", "at least 11 UMP token fields"));
+        }
+        Ok(Self {
+            password_presentation_primary: fields[0].clone(),
+            password_recovery_primary: fields[1].clone(),
+            presentation_recovery_primary: fields[2].clone(),
+            password_primary_privileged: fields[3].clone(),
+            presentation_recovery_privileged: fields[4].clone(),
+            presentation_hash: fields[5].clone(),
+            password_salt: fields[6].clone(),
+            recovery_hash: fields[7].clone(),
+            presentation_key_encrypted: fields[8].clone(),
+            recovery_key_encrypted: fields[9].clone(),
+            password_key_encrypted: fields[10].clone(),
+            profiles_encrypted: fields.get(11).cloned(),
+            current_outpoint,
+        })
+    }
+
+    /// Serialize for inclusion in a snapshot
+    ///
+    /// Reference: TS CWIStyleWalletManager.serializeUMPToken
+    pub fn serialize(&self) -> WalletResult<Vec<u8>> {
+        let outpoint = self.current_outpoint.as_ref().ok_or_else(|| {
+            WalletError::invalid_operation("Token must have outpoint for serialization")
+        })?;
+        let mut out = Vec::new();
+        let mut write = |bytes: &[u8]| {
+            out.extend(encode_varint(bytes.len() as u64));
+            out.extend_from_slice(bytes);
+        };
+        for field in self.to_fields().iter().take(11) {
+            write(field);
+        }
+        write(outpoint.as_bytes());
+        match &self.profiles_encrypted {
+            Some(profiles) => {
+                out.push(1);
+                out.extend(encode_varint(profiles.len() as u64));
+                out.extend_from_slice(profiles);
+            }
+            None => out.push(0),
+        }
+        Ok(out)
+    }
+
+    /// Deserialize a token written by [`UMPToken::serialize`]
+    ///
+    /// Reference: TS CWIStyleWalletManager.deserializeUMPToken
+    pub fn deserialize(bytes: &[u8]) -> WalletResult<Self> {
+        let malformed = |e: crate::transaction::TransactionError| {
+            WalletError::invalid_parameter("snapshot", format!("a valid UMP token ({})", e))
+        };
+        let mut reader = ByteReader::new(bytes);
+        let mut fields = Vec::with_capacity(12);
+        for _ in 0..11 {
+            fields.push(reader.read_var_bytes().map_err(malformed)?.to_vec());
+        }
+        let outpoint = String::from_utf8(reader.read_var_bytes().map_err(malformed)?.to_vec())
+            .map_err(|_| WalletError::invalid_parameter("snapshot", "a UTF-8 outpoint"))?;
+        if reader.read_u8().map_err(malformed)? == 1 {
+            fields.push(reader.read_var_bytes().map_err(malformed)?.to_vec());
+        }
+        Self::from_fields(&fields, Some(outpoint))
+    }
+}
+
+/// Locates and publishes UMP tokens
+///
+/// Reference: TS UMPTokenInteractor
+#[async_trait::async_trait]
+pub trait UMPTokenInteractor: Send + Sync {
+    /// Find the token whose presentation hash matches
+    async fn find_by_presentation_key_hash(&self, hash: &[u8]) -> WalletResult<Option<UMPToken>>;
+
+    /// Find the token whose recovery hash matches
+    async fn find_by_recovery_key_hash(&self, hash: &[u8]) -> WalletResult<Option<UMPToken>>;
+
+    /// Create a new token on chain, spending `old_token` if provided
+    ///
+    /// Returns the outpoint of the new token.
+    async fn build_and_send(
+        &self,
+        wallet: &dyn WalletInterface,
+        admin_originator: &str,
+        token: &UMPToken,
+        old_token: Option<&UMPToken>,
+    ) -> WalletResult<String>;
+}
+
+/// XOR two equal-length keys
+///
+/// Reference: TS CWIStyleWalletManager.XOR
+fn xor(a: &[u8], b: &[u8]) -> WalletResult<Vec<u8>> {
+    if a.len() != b.len() {
+        return Err(WalletError::invalid_parameter("key", "the same length as the key it is combined with"));
+    }
+    Ok(a.iter().zip(b).map(|(x, y)| x ^ y).collect())
+}
+
+fn random_key() -> Vec<u8> {
+    let mut key = vec![0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+fn require_key(key: &[u8], name: &str) -> WalletResult<()> {
+    if key.len() != 32 {
+        return Err(WalletError::invalid_parameter(name, "exactly 32 bytes"));
+    }
+    Ok(())
+}
+
+/// Stretch a password into a 32-byte password key
+fn derive_password_key(password: &str, salt: &[u8]) -> Vec<u8> {
+    pbkdf2_sha512(password.as_bytes(), salt, PBKDF2_NUM_ROUNDS, 32)
+}
+
+/// Symmetric key used to wrap key material under the root privileged key
+fn key_wrapping_key(root_privileged_key: &[u8]) -> WalletResult<Vec<u8>> {
+    RootKeyDeriver::new(root_privileged_key)?
+        .derive_symmetric_key(&(2, KEY_WRAPPING_PROTOCOL.to_string()), "1", "self")
+}
+
+/// Recover the root privileged key from the root primary key and a password
+fn unlock_privileged_key(token: &UMPToken, root_primary_key: &[u8], password: &str) -> WalletResult<Vec<u8>> {
+    let password_key = derive_password_key(password, &token.password_salt);
+    decrypt_with_aes_gcm(&token.password_primary_privileged, &xor(root_primary_key, &password_key)?)
+}
+
+/// Key getter for the root privileged key manager
+///
+/// Reference: TS rootPrivilegedKeyManager built in setupRootInfrastructure
+///
+/// Prompts for the password and decrypts the root privileged key from the
+/// current UMP token, so the getter stays valid when the token is replaced.
+/// A key that was already known when authenticating (recovery flows, new
+/// users) is handed out once without prompting.
+fn password_key_getter(
+    root_primary_key: Vec<u8>,
+    current_ump_token: Arc<RwLock<Option<UMPToken>>>,
+    password_retriever: PasswordRetriever,
+    ephemeral_key: Option<Vec<u8>>,
+) -> PrivilegedKeyGetter {
+    let ephemeral_key = Arc::new(std::sync::Mutex::new(ephemeral_key));
+    Arc::new(move |reason| {
+        let ephemeral = ephemeral_key.lock().expect("ephemeral key lock").take();
+        let root_primary_key = root_primary_key.clone();
+        let current_ump_token = current_ump_token.clone();
+        let password_retriever = password_retriever.clone();
+        Box::pin(async move {
+            if let Some(key) = ephemeral {
+                return Ok(key);
+            }
+
+            let token = current_ump_token.read().await.clone()
+                .ok_or_else(|| WalletError::invalid_operation("No UMP token found!"))?;
+            let (test_token, test_primary) = (token.clone(), root_primary_key.clone());
+            let test: PasswordTest = Arc::new(move |candidate: &str| {
+                unlock_privileged_key(&test_token, &test_primary, candidate).is_ok()
+            });
+            let password = password_retriever(reason, test).await?;
+            unlock_privileged_key(&token, &root_primary_key, &password)
+        })
+    })
+}
+
+/// The default profile, which uses the root keys unchanged
+fn default_profile() -> Profile {
+    Profile {
+        name: "default".to_string(),
+        id: DEFAULT_PROFILE_ID.to_vec(),
+        primary_pad: vec![0; 32],
+        privileged_pad: vec![0; 32],
+        created_at: None,
+    }
+}
+
+/// Privileged key getter for a profile: the root privileged key XOR the profile's pad
+///
+/// Reference: TS switchProfile profile privileged key manager
+fn profile_key_getter(root_manager: Arc<PrivilegedKeyManager>, privileged_pad: Vec<u8>) -> PrivilegedKeyGetter {
+    Arc::new(move |reason| {
+        let root_manager = root_manager.clone();
+        let privileged_pad = privileged_pad.clone();
+        Box::pin(async move {
+            let root_privileged_key = root_manager.get_privileged_key(&reason).await?;
+            xor(&root_privileged_key, &privileged_pad)
+        })
+    })
+}
+
+/// CWI-Style Wallet Manager
+///
+/// Reference: TS CWIStyleWalletManager class
+///
+/// ## Authentication
+///
+/// Set the [`AuthenticationMode`], then provide its two factors:
+///
+/// - `PresentationKeyAndPassword` - [`provide_presentation_key`](Self::provide_presentation_key)
+///   then [`provide_password`](Self::provide_password). Unknown presentation keys start
+///   the new-user flow, which creates and publishes a UMP token.
+/// - `PresentationKeyAndRecoveryKey` - presentation key then recovery key
+/// - `RecoveryKeyAndPassword` - recovery key then password
+///
+/// Once authenticated, all WalletInterface calls are proxied to the wallet
+/// built for the active profile.
+///
+/// ## Profiles
+///
+/// Profiles other than the default one derive their keys by XORing the root
+/// keys with random per-profile pads. The profile list is stored encrypted in
+/// the UMP token; see [`add_profile`](Self::add_profile),
+/// [`switch_profile`](Self::switch_profile) and [`delete_profile`](Self::delete_profile).
+pub struct CWIStyleWalletManager {
+    /// Whether user is authenticated
+    authenticated: Arc<RwLock<bool>>,
+
+    /// Admin originator domain, normalized (protected from external use)
+    admin_originator: String,
+
+    /// Wallet builder function
+    wallet_builder: WalletBuilder,
+
+    /// Finds and publishes UMP tokens
+    ump_token_interactor: Arc<dyn UMPTokenInteractor>,
+
+    /// Saves newly generated recovery keys
+    recovery_key_saver: RecoveryKeySaver,
+
+    /// Prompts for the password when the privileged key is needed
+    password_retriever: PasswordRetriever,
+
+    /// Funds new wallets before their token is published
+    new_wallet_funder: Option<NewWalletFunder>,
+
+    /// Current authentication mode
+    authentication_mode: Arc<RwLock<AuthenticationMode>>,
+
+    /// Current authentication flow
+    authentication_flow: Arc<RwLock<AuthenticationFlow>>,
+
+    /// The user's UMP token, once found or created
+    current_ump_token: Arc<RwLock<Option<UMPToken>>>,
+
+    /// Presentation key provided during authentication
+    presentation_key: Arc<RwLock<Option<Vec<u8>>>>,
+
+    /// Recovery key provided during authentication
+    recovery_key: Arc<RwLock<Option<Vec<u8>>>>,
+
+    /// Root primary key (32 bytes), once unlocked
+    root_primary_key: Arc<RwLock<Option<Vec<u8>>>>,
+
+    /// Privileged key manager for the root privileged key
+    root_privileged_key_manager: Arc<RwLock<Option<Arc<PrivilegedKeyManager>>>>,
+
+    /// Profiles decrypted from the UMP token
+    profiles: Arc<RwLock<Vec<Profile>>>,
+
+    /// Profile the underlying wallet is built for
+    active_profile_id: Arc<RwLock<[u8; 16]>>,
+
+    /// Underlying wallet instance (built after authentication)
+    underlying: Arc<RwLock<Option<Arc<dyn WalletInterface>>>>,
+}
+
+impl CWIStyleWalletManager {
+    /// Create a new CWIStyleWalletManager
+    ///
+    /// Reference: TS constructor
+    ///
+    /// # Arguments
+    /// * `admin_originator` - Domain name of administrative originator
+    /// * `wallet_builder` - Builds the underlying wallet from the primary key and privileged manager
+    /// * `ump_token_interactor` - Finds and publishes UMP tokens
+    /// * `recovery_key_saver` - Saves newly generated recovery keys
+    /// * `password_retriever` - Prompts for the password when the privileged key is needed
+    /// * `new_wallet_funder` - Optionally funds new wallets before their token is published
+    pub fn new(
+        admin_originator: String,
+        wallet_builder: WalletBuilder,
+        ump_token_interactor: Arc<dyn UMPTokenInteractor>,
+        recovery_key_saver: RecoveryKeySaver,
+        password_retriever: PasswordRetriever,
+        new_wallet_funder: Option<NewWalletFunder>,
+    ) -> Self {
+        Self {
+            authenticated: Arc::new(RwLock::new(false)),
+            admin_originator: Originator::normalize(admin_originator),
+            wallet_builder,
+            ump_token_interactor,
+            recovery_key_saver,
+            password_retriever,
+            new_wallet_funder,
+            authentication_mode: Arc::new(RwLock::new(AuthenticationMode::PresentationKeyAndPassword)),
+            authentication_flow: Arc::new(RwLock::new(AuthenticationFlow::NewUser)),
+            current_ump_token: Arc::new(RwLock::new(None)),
+            presentation_key: Arc::new(RwLock::new(None)),
+            recovery_key: Arc::new(RwLock::new(None)),
+            root_primary_key: Arc::new(RwLock::new(None)),
+            root_privileged_key_manager: Arc::new(RwLock::new(None)),
+            profiles: Arc::new(RwLock::new(Vec::new())),
+            active_profile_id: Arc::new(RwLock::new(DEFAULT_PROFILE_ID)),
+            underlying: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Current authentication mode
+    pub async fn authentication_mode(&self) -> AuthenticationMode {
+        *self.authentication_mode.read().await
+    }
+
+    /// Choose which two factors the user will provide
+    pub async fn set_authentication_mode(&self, mode: AuthenticationMode) -> WalletResult<()> {
+        if *self.authenticated.read().await {
+            return Err(WalletError::invalid_operation("User is already authenticated"));
+        }
+        *self.authentication_mode.write().await = mode;
+        Ok(())
+    }
+
+    /// Current authentication flow, known once the first factor is provided
+    pub async fn authentication_flow(&self) -> AuthenticationFlow {
+        *self.authentication_flow.read().await
+    }
+
+    /// Provide the presentation key
+    ///
+    /// Reference: TS providePresentationKey
+    ///
+    /// Looks up the user's UMP token to decide between the new-user and
+    /// existing-user flows.
+    pub async fn provide_presentation_key(&self, key: Vec<u8>) -> WalletResult<()> {
+        if *self.authenticated.read().await {
+            return Err(WalletError::invalid_operation("User is already authenticated"));
+        }
+        if self.authentication_mode().await == AuthenticationMode::RecoveryKeyAndPassword {
+            return Err(WalletError::invalid_operation("Presentation key is not needed in this mode"));
+        }
+        require_key(&key, "key")?;
+
+        let token = self.ump_token_interactor.find_by_presentation_key_hash(&sha256(&key)).await?;
+        *self.authentication_flow.write().await = if token.is_some() {
+            AuthenticationFlow::ExistingUser
+        } else {
+            AuthenticationFlow::NewUser
+        };
+        *self.presentation_key.write().await = Some(key);
+        *self.current_ump_token.write().await = token;
+        Ok(())
+    }
+
+    /// Provide the password
+    ///
+    /// Reference: TS providePassword
+    ///
+    /// Completes authentication in the presentation-key-and-password and
+    /// recovery-key-and-password modes. In the new-user flow this creates the
+    /// user's keys and publishes their UMP token.
+    pub async fn provide_password(&self, password: &str) -> WalletResult<()> {
+        if *self.authenticated.read().await {
+            return Err(WalletError::invalid_operation("User is already authenticated"));
+        }
+
+        match self.authentication_mode().await {
+            AuthenticationMode::PresentationKeyAndRecoveryKey => {
+                Err(WalletError::invalid_operation("Password is not needed in this mode"))
+            }
+            AuthenticationMode::PresentationKeyAndPassword => {
+                let presentation_key = self.presentation_key.read().await.clone()
+                    .ok_or_else(|| WalletError::invalid_operation("No presentation key found!"))?;
+
+                if self.authentication_flow().await == AuthenticationFlow::NewUser {
+                    return self.create_new_user(presentation_key, password).await;
+                }
+
+                let token = self.current_token().await?;
+                let password_key = derive_password_key(password, &token.password_salt);
+                let root_primary_key = decrypt_with_aes_gcm(
+                    &token.password_presentation_primary,
+                    &xor(&presentation_key, &password_key)?,
+                )?;
+                self.setup_root_infrastructure(root_primary_key, None).await
+            }
+            AuthenticationMode::RecoveryKeyAndPassword => {
+                let recovery_key = self.recovery_key.read().await.clone()
+                    .ok_or_else(|| WalletError::invalid_operation("No recovery key found!"))?;
+                let token = self.current_token().await?;
+
+                let password_key = derive_password_key(password, &token.password_salt);
+                let root_primary_key = decrypt_with_aes_gcm(
+                    &token.password_recovery_primary,
+                    &xor(&recovery_key, &password_key)?,
+                )?;
+                let root_privileged_key = decrypt_with_aes_gcm(
+                    &token.password_primary_privileged,
+                    &xor(&root_primary_key, &password_key)?,
+                )?;
+                let presentation_key = decrypt_with_aes_gcm(
+                    &token.presentation_key_encrypted,
+                    &key_wrapping_key(&root_privileged_key)?,
+                )?;
+                *self.presentation_key.write().await = Some(presentation_key);
+                self.setup_root_infrastructure(root_primary_key, Some(root_privileged_key)).await
+            }
+        }
+    }
+
+    /// Provide the recovery key
+    ///
+    /// Reference: TS provideRecoveryKey
+    ///
+    /// Completes authentication in the presentation-key-and-recovery-key mode,
+    /// or looks up the user's UMP token in the recovery-key-and-password mode.
+    pub async fn provide_recovery_key(&self, recovery_key: Vec<u8>) -> WalletResult<()> {
+        if *self.authenticated.read().await {
+            return Err(WalletError::invalid_operation("User is already authenticated"));
+        }
+        require_key(&recovery_key, "recovery_key")?;
+
+        match self.authentication_mode().await {
+            AuthenticationMode::PresentationKeyAndPassword => {
+                Err(WalletError::invalid_operation("Recovery key is not needed in this mode"))
+            }
+            AuthenticationMode::PresentationKeyAndRecoveryKey => {
+                if self.authentication_flow().await == AuthenticationFlow::NewUser {
+                    return Err(WalletError::invalid_operation("Do not submit recovery key in new-user flow"));
+                }
+                let presentation_key = self.presentation_key.read().await.clone()
+                    .ok_or_else(|| WalletError::invalid_operation("No presentation key found!"))?;
+                let token = self.current_token().await?;
+
+                let presentation_recovery = xor(&presentation_key, &recovery_key)?;
+                let root_primary_key = decrypt_with_aes_gcm(&token.presentation_recovery_primary, &presentation_recovery)?;
+                let root_privileged_key = decrypt_with_aes_gcm(&token.presentation_recovery_privileged, &presentation_recovery)?;
+                *self.recovery_key.write().await = Some(recovery_key);
+                self.setup_root_infrastructure(root_primary_key, Some(root_privileged_key)).await
+            }
+            AuthenticationMode::RecoveryKeyAndPassword => {
+                let token = self.ump_token_interactor.find_by_recovery_key_hash(&sha256(&recovery_key)).await?
+                    .ok_or_else(|| WalletError::invalid_operation("No user found with this recovery key"))?;
+                *self.authentication_flow.write().await = AuthenticationFlow::ExistingUser;
+                *self.recovery_key.write().await = Some(recovery_key);
+                *self.current_ump_token.write().await = Some(token);
+                Ok(())
+            }
+        }
+    }
+
+    /// Change the user's password
+    ///
+    /// Reference: TS changePassword
+    ///
+    /// Requires the privileged key, so the current password is requested.
+    pub async fn change_password(&self, new_password: &str) -> WalletResult<()> {
+        self.ensure_authenticated().await?;
+        let token = self.current_token().await?;
+        let root_privileged_key = self.get_privileged_key("Changing your password").await?;
+        let wrapping_key = key_wrapping_key(&root_privileged_key)?;
+
+        let password_salt = random_key();
+        let password_key = derive_password_key(new_password, &password_salt);
+        let presentation_key = decrypt_with_aes_gcm(&token.presentation_key_encrypted, &wrapping_key)?;
+        let recovery_key = decrypt_with_aes_gcm(&token.recovery_key_encrypted, &wrapping_key)?;
+
+        self.update_auth_factors(password_salt, password_key, presentation_key, recovery_key, &root_privileged_key).await
+    }
+
+    /// Generate and save a new recovery key
+    ///
+    /// Reference: TS changeRecoveryKey
+    pub async fn change_recovery_key(&self) -> WalletResult<()> {
+        self.ensure_authenticated().await?;
+        let token = self.current_token().await?;
+        let root_privileged_key = self.get_privileged_key("Changing your recovery key").await?;
+        let wrapping_key = key_wrapping_key(&root_privileged_key)?;
+
+        let presentation_key = decrypt_with_aes_gcm(&token.presentation_key_encrypted, &wrapping_key)?;
+        let password_key = decrypt_with_aes_gcm(&token.password_key_encrypted, &wrapping_key)?;
+        let recovery_key = random_key();
+        (self.recovery_key_saver)(recovery_key.clone()).await?;
+
+        self.update_auth_factors(token.password_salt, password_key, presentation_key, recovery_key, &root_privileged_key).await
+    }
+
+    /// Replace the presentation key
+    ///
+    /// Reference: TS changePresentationKey
+    pub async fn change_presentation_key(&self, presentation_key: Vec<u8>) -> WalletResult<()> {
+        self.ensure_authenticated().await?;
+        require_key(&presentation_key, "presentation_key")?;
+        let token = self.current_token().await?;
+        let root_privileged_key = self.get_privileged_key("Changing your presentation key").await?;
+        let wrapping_key = key_wrapping_key(&root_privileged_key)?;
+
+        let recovery_key = decrypt_with_aes_gcm(&token.recovery_key_encrypted, &wrapping_key)?;
+        let password_key = decrypt_with_aes_gcm(&token.password_key_encrypted, &wrapping_key)?;
+        *self.presentation_key.write().await = Some(presentation_key.clone());
+
+        self.update_auth_factors(token.password_salt, password_key, presentation_key, recovery_key, &root_privileged_key).await
+    }
+
+    /// Profiles stored in the user's UMP token, starting with the default profile
+    ///
+    /// Reference: TS listProfiles
+    pub async fn list_profiles(&self) -> WalletResult<Vec<Profile>> {
+        self.ensure_authenticated().await?;
+        let mut profiles = vec![default_profile()];
+        profiles.extend(self.profiles.read().await.iter().cloned());
+        Ok(profiles)
+    }
+
+    /// Id of the profile the underlying wallet is built for
+    pub async fn active_profile_id(&self) -> Vec<u8> {
+        self.active_profile_id.read().await.to_vec()
+    }
+
+    /// Add a profile with fresh random pads, returning its id
+    ///
+    /// Reference: TS addProfile
+    ///
+    /// The profile list is stored encrypted in the UMP token, so a new token
+    /// is published, which needs the privileged key.
+    pub async fn add_profile(&self, name: &str) -> WalletResult<Vec<u8>> {
+        self.ensure_authenticated().await?;
+        if name.is_empty() || name == "default" {
+            return Err(WalletError::invalid_parameter("name", "a non-empty name other than \"default\""));
+        }
+        if self.profiles.read().await.iter().any(|p| p.name == name) {
+            return Err(WalletError::invalid_parameter("name", format!("unique, profile \"{}\" already exists", name)));
+        }
+
+        let mut id = vec![0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let profile = Profile {
+            name: name.to_string(),
+            id: id.clone(),
+            primary_pad: random_key(),
+            privileged_pad: random_key(),
+            created_at: Some(chrono::Utc::now().timestamp()),
+        };
+
+        let previous = self.profiles.read().await.clone();
+        self.profiles.write().await.push(profile);
+        if let Err(e) = self.publish_profiles(&format!("Adding profile \"{}\"", name)).await {
+            *self.profiles.write().await = previous;
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Delete a profile and publish the updated token
+    ///
+    /// Reference: TS deleteProfile
+    ///
+    /// Deleting the active profile switches back to the default profile first.
+    pub async fn delete_profile(&self, profile_id: &[u8]) -> WalletResult<()> {
+        self.ensure_authenticated().await?;
+        if profile_id == DEFAULT_PROFILE_ID {
+            return Err(WalletError::invalid_parameter("profile_id", "not the default profile"));
+        }
+        let previous = self.profiles.read().await.clone();
+        if !previous.iter().any(|p| p.id == profile_id) {
+            return Err(WalletError::invalid_parameter("profile_id", "an existing profile"));
+        }
+        if self.active_profile_id.read().await.as_slice() == profile_id {
+            self.switch_profile(&DEFAULT_PROFILE_ID).await?;
+        }
+
+        self.profiles.write().await.retain(|p| p.id != profile_id);
+        if let Err(e) = self.publish_profiles("Deleting a profile").await {
+            *self.profiles.write().await = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Rebuild the underlying wallet for another profile
+    ///
+    /// Reference: TS switchProfile
+    ///
+    /// Each profile's keys are the root keys XOR the profile's pads, so every
+    /// profile has its own identity key and its own storage user.
+    pub async fn switch_profile(&self, profile_id: &[u8]) -> WalletResult<()> {
+        self.ensure_authenticated().await?;
+        let profile = self.find_profile(profile_id).await?;
+        let root_primary_key = self.root_primary_key.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("Wallet is not properly initialized"))?;
+        let root_manager = self.root_privileged_key_manager.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("Wallet is not properly initialized"))?;
+
+        let wallet = self.build_profile_wallet(&root_primary_key, root_manager, &profile).await?;
+        let mut active_profile_id = DEFAULT_PROFILE_ID;
+        active_profile_id.copy_from_slice(&profile.id);
+        *self.underlying.write().await = Some(Arc::from(wallet));
+        *self.active_profile_id.write().await = active_profile_id;
+        Ok(())
+    }
+
+    /// Destroy the underlying wallet, returning to unauthenticated state
+    ///
+    /// Reference: TS destroy
+    pub async fn destroy(&self) {
+        *self.underlying.write().await = None;
+        if let Some(manager) = self.root_privileged_key_manager.write().await.take() {
+            manager.destroy_key();
+        }
+        *self.authenticated.write().await = false;
+        *self.root_primary_key.write().await = None;
+        *self.presentation_key.write().await = None;
+        *self.recovery_key.write().await = None;
+        *self.current_ump_token.write().await = None;
+        self.profiles.write().await.clear();
+        *self.active_profile_id.write().await = DEFAULT_PROFILE_ID;
+        *self.authentication_mode.write().await = AuthenticationMode::PresentationKeyAndPassword;
+        *self.authentication_flow.write().await = AuthenticationFlow::NewUser;
+    }
+
+    /// Save the root primary key, active profile and UMP token to an encrypted snapshot
+    ///
+    /// Reference: TS saveSnapshot
+    ///
+    /// Version 2 preimage: `[root primary key][16-byte active profile id][token]`,
+    /// sealed as described in [`seal_snapshot`]. Loading the snapshot restores
+    /// authentication without the presentation key; the password is still
+    /// required for privileged operations.
+    pub async fn save_snapshot(&self) -> WalletResult<Vec<u8>> {
+        let root_primary_key = self.root_primary_key.read().await.clone();
+        let token = self.current_ump_token.read().await.clone();
+        let (root_primary_key, token) = match (root_primary_key, token) {
+            (Some(key), Some(token)) => (key, token),
+            _ => return Err(WalletError::invalid_operation("No root primary key or current UMP token set")),
+        };
+
+        let mut preimage = root_primary_key;
+        preimage.extend_from_slice(&*self.active_profile_id.read().await);
+        preimage.extend(token.serialize()?);
+        seal_snapshot(&preimage)
+    }
+
+    /// Restore authentication from a snapshot
+    ///
+    /// Reference: TS loadSnapshot
+    ///
+    /// Version 1 snapshots, which have no profile id, restore the default profile.
+    pub async fn load_snapshot(&self, snapshot: Vec<u8>) -> WalletResult<()> {
+        let (version, preimage) = open_snapshot(&snapshot)?;
+        let token_start = if version == 1 { 32 } else { 48 };
+        if preimage.len() < token_start {
+            return Err(WalletError::invalid_parameter("snapshot", "invalid length"));
+        }
+        let mut profile_id = DEFAULT_PROFILE_ID;
+        if version >= 2 {
+            profile_id.copy_from_slice(&preimage[32..48]);
+        }
+        let token = UMPToken::deserialize(&preimage[token_start..])?;
+
+        *self.current_ump_token.write().await = Some(token);
+        *self.active_profile_id.write().await = profile_id;
+        *self.authentication_flow.write().await = AuthenticationFlow::ExistingUser;
+        self.setup_root_infrastructure(preimage[..32].to_vec(), None).await
+    }
+
+    /// Check if user is authenticated
+    ///
+    /// Reference: TS isAuthenticated
+    pub async fn is_authenticated(&self, originator: Option<&str>) -> WalletResult<bool> {
+        self.ensure_can_call(originator).await?;
+        Ok(true)
+    }
+
+    /// Wait for user to authenticate
+    ///
+    /// Reference: TS waitForAuthentication
+    pub async fn wait_for_authentication(&self, originator: Option<&str>) -> WalletResult<bool> {
+        self.ensure_not_admin(originator)?;
+
+        while !*self.authenticated.read().await {
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        Ok(true)
+    }
+
+    /// Create keys and a UMP token for a new user, then authenticate
+    ///
+    /// Reference: TS providePassword new-user branch
+    async fn create_new_user(&self, presentation_key: Vec<u8>, password: &str) -> WalletResult<()> {
+        let recovery_key = random_key();
+        (self.recovery_key_saver)(recovery_key.clone()).await?;
+
+        let password_salt = random_key();
+        let password_key = derive_password_key(password, &password_salt);
+        let root_primary_key = random_key();
+        let root_privileged_key = random_key();
+
+        let token = self.build_token(
+            &password_salt, &password_key, &presentation_key, &recovery_key,
+            &root_primary_key, &root_privileged_key, None,
+        )?;
+        *self.current_ump_token.write().await = Some(token.clone());
+        self.setup_root_infrastructure(root_primary_key, Some(root_privileged_key)).await?;
+
+        let wallet = self.underlying_wallet().await?;
+        if let Some(funder) = &self.new_wallet_funder {
+            funder(presentation_key, wallet.clone(), self.admin_originator.clone()).await?;
+        }
+
+        let outpoint = self.ump_token_interactor
+            .build_and_send(wallet.as_ref(), &self.admin_originator, &token, None)
+            .await?;
+        if let Some(current) = self.current_ump_token.write().await.as_mut() {
+            current.current_outpoint = Some(outpoint);
+        }
+        Ok(())
+    }
+
+    /// Encrypt every factor combination into a new UMP token
+    ///
+    /// Reference: TS createNewUser / updateAuthFactors token construction
+    #[allow(clippy::too_many_arguments)]
+    fn build_token(
+        &self,
+        password_salt: &[u8],
+        password_key: &[u8],
+        presentation_key: &[u8],
+        recovery_key: &[u8],
+        root_primary_key: &[u8],
+        root_privileged_key: &[u8],
+        profiles_encrypted: Option<Vec<u8>>,
+    ) -> WalletResult<UMPToken> {
+        let presentation_password = xor(presentation_key, password_key)?;
+        let presentation_recovery = xor(presentation_key, recovery_key)?;
+        let recovery_password = xor(recovery_key, password_key)?;
+        let primary_password = xor(root_primary_key, password_key)?;
+        let wrapping_key = key_wrapping_key(root_privileged_key)?;
+
+        Ok(UMPToken {
+            password_presentation_primary: encrypt_with_aes_gcm(root_primary_key, &presentation_password)?,
+            password_recovery_primary: encrypt_with_aes_gcm(root_primary_key, &recovery_password)?,
+            presentation_recovery_primary: encrypt_with_aes_gcm(root_primary_key, &presentation_recovery)?,
+            password_primary_privileged: encrypt_with_aes_gcm(root_privileged_key, &primary_password)?,
+            presentation_recovery_privileged: encrypt_with_aes_gcm(root_privileged_key, &presentation_recovery)?,
+            presentation_hash: sha256(presentation_key),
+            password_salt: password_salt.to_vec(),
+            recovery_hash: sha256(recovery_key),
+            presentation_key_encrypted: encrypt_with_aes_gcm(presentation_key, &wrapping_key)?,
+            recovery_key_encrypted: encrypt_with_aes_gcm(recovery_key, &wrapping_key)?,
+            password_key_encrypted: encrypt_with_aes_gcm(password_key, &wrapping_key)?,
+            profiles_encrypted,
+            current_outpoint: None,
+        })
+    }
+
+    /// Publish a new UMP token for changed factors, consuming the current one
+    ///
+    /// Reference: TS updateAuthFactors
+    async fn update_auth_factors(
+        &self,
+        password_salt: Vec<u8>,
+        password_key: Vec<u8>,
+        presentation_key: Vec<u8>,
+        recovery_key: Vec<u8>,
+        root_privileged_key: &[u8],
+    ) -> WalletResult<()> {
+        let old_token = self.current_token().await?;
+        let root_primary_key = self.root_primary_key.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("Wallet is not properly initialized"))?;
+
+        let profiles = self.profiles.read().await.clone();
+        let profiles_encrypted = if profiles.is_empty() {
+            None
+        } else {
+            Some(encrypt_with_aes_gcm(&serde_json::to_vec(&profiles)?, &root_primary_key)?)
+        };
+
+        let mut token = self.build_token(
+            &password_salt, &password_key, &presentation_key, &recovery_key,
+            &root_primary_key, root_privileged_key, profiles_encrypted,
+        )?;
+        let wallet = self.underlying_wallet().await?;
+        let outpoint = self.ump_token_interactor
+            .build_and_send(wallet.as_ref(), &self.admin_originator, &token, Some(&old_token))
+            .await?;
+        token.current_outpoint = Some(outpoint);
+
+        *self.current_ump_token.write().await = Some(token);
+        Ok(())
+    }
+
+    /// Publish a new UMP token carrying the current profile list
+    ///
+    /// Reference: TS addProfile / deleteProfile token update
+    async fn publish_profiles(&self, reason: &str) -> WalletResult<()> {
+        let token = self.current_token().await?;
+        let root_privileged_key = self.get_privileged_key(reason).await?;
+        let wrapping_key = key_wrapping_key(&root_privileged_key)?;
+
+        let presentation_key = decrypt_with_aes_gcm(&token.presentation_key_encrypted, &wrapping_key)?;
+        let recovery_key = decrypt_with_aes_gcm(&token.recovery_key_encrypted, &wrapping_key)?;
+        let password_key = decrypt_with_aes_gcm(&token.password_key_encrypted, &wrapping_key)?;
+
+        self.update_auth_factors(token.password_salt, password_key, presentation_key, recovery_key, &root_privileged_key).await
+    }
+
+    /// Look up a profile by id, including the default profile
+    async fn find_profile(&self, profile_id: &[u8]) -> WalletResult<Profile> {
+        if profile_id == DEFAULT_PROFILE_ID {
+            return Ok(default_profile());
+        }
+        self.profiles.read().await.iter()
+            .find(|p| p.id == profile_id)
+            .cloned()
+            .ok_or_else(|| WalletError::invalid_parameter("profile_id", "an existing profile"))
+    }
+
+    /// Build the underlying wallet with a profile's primary key and privileged key manager
+    async fn build_profile_wallet(
+        &self,
+        root_primary_key: &[u8],
+        root_manager: Arc<PrivilegedKeyManager>,
+        profile: &Profile,
+    ) -> WalletResult<Box<dyn WalletInterface>> {
+        if profile.id == DEFAULT_PROFILE_ID {
+            return (self.wallet_builder)(root_primary_key.to_vec(), root_manager as Arc<dyn PrivilegedKeyProvider>).await;
+        }
+        let primary_key = xor(root_primary_key, &profile.primary_pad)?;
+        let manager = Arc::new(PrivilegedKeyManager::new(profile_key_getter(root_manager, profile.privileged_pad.clone())));
+        (self.wallet_builder)(primary_key, manager as Arc<dyn PrivilegedKeyProvider>).await
+    }
+
+    /// Set up the privileged key manager and underlying wallet, then authenticate
+    ///
+    /// Reference: TS setupRootInfrastructure
+    async fn setup_root_infrastructure(
+        &self,
+        root_primary_key: Vec<u8>,
+        ephemeral_privileged_key: Option<Vec<u8>>,
+    ) -> WalletResult<()> {
+        let token = self.current_token().await?;
+
+        let profiles: Vec<Profile> = match &token.profiles_encrypted {
+            Some(encrypted) => serde_json::from_slice(&decrypt_with_aes_gcm(encrypted, &root_primary_key)?)?,
+            None => Vec::new(),
+        };
+
+        let active_profile_id = *self.active_profile_id.read().await;
+        let profile = if active_profile_id == DEFAULT_PROFILE_ID {
+            default_profile()
+        } else {
+            profiles.iter().find(|p| p.id == active_profile_id).cloned()
+                .ok_or_else(|| WalletError::invalid_parameter("profile_id", "an existing profile"))?
+        };
+
+        let privileged_manager = Arc::new(PrivilegedKeyManager::new(password_key_getter(
+            root_primary_key.clone(),
+            self.current_ump_token.clone(),
+            self.password_retriever.clone(),
+            ephemeral_privileged_key,
+        )));
+        let wallet = self.build_profile_wallet(&root_primary_key, privileged_manager.clone(), &profile).await?;
+
+        *self.profiles.write().await = profiles;
+        *self.root_primary_key.write().await = Some(root_primary_key);
+        *self.root_privileged_key_manager.write().await = Some(privileged_manager);
+        *self.underlying.write().await = Some(Arc::from(wallet));
+        *self.authenticated.write().await = true;
+        Ok(())
+    }
+
+    async fn get_privileged_key(&self, reason: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let manager = self.root_privileged_key_manager.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("Wallet is not properly initialized"))?;
+        manager.get_privileged_key(reason).await
+    }
+
+    async fn current_token(&self) -> WalletResult<UMPToken> {
+        self.current_ump_token.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("No UMP token found!"))
+    }
+
+    async fn underlying_wallet(&self) -> WalletResult<Arc<dyn WalletInterface>> {
+        self.underlying.read().await.clone()
+            .ok_or_else(|| WalletError::invalid_operation("Not authenticated"))
+    }
+
+    async fn ensure_authenticated(&self) -> WalletResult<()> {
+        if !*self.authenticated.read().await {
+            return Err(WalletError::invalid_operation("Not authenticated or missing required data."));
+        }
+        Ok(())
+    }
+
+    /// Refuse the admin originator, and originators that are not valid
+    ///
+    /// Originators are compared normalized, so `Admin.Example.com` is the
+    /// admin too.
+    fn ensure_not_admin(&self, originator: Option<&str>) -> WalletResult<()> {
+        let Some(originator) = originator else {
+            return Ok(());
+        };
+        if Originator::parse(originator)?.as_str() == self.admin_originator {
+            return Err(WalletError::invalid_operation(
+                "External applications cannot use the admin originator."
+            ));
+        }
+        Ok(())
+    }
+
+    /// Ensure the call can proceed (authenticated and not admin originator)
+    ///
+    /// Reference: TS ensureCanCall
+    async fn ensure_can_call(&self, originator: Option<&str>) -> WalletResult<()> {
+        self.ensure_not_admin(originator)?;
+
+        if !*self.authenticated.read().await {
+            return Err(WalletError::invalid_operation(
+                "User is not authenticated."
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// WalletInterface implementation - proxies all calls to underlying wallet
+// ============================================================================
+
+#[async_trait::async_trait]
+impl WalletInterface for CWIStyleWalletManager {
+    async fn create_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.create_action(args, originator).await
+    }
+
+    async fn sign_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.sign_action(args, originator).await
+    }
+
+    async fn abort_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.abort_action(args, originator).await
+    }
+
+    async fn list_actions(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.list_actions(args, originator).await
+    }
+
+    async fn internalize_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.internalize_action(args, originator).await
+    }
+
+    async fn list_outputs(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.list_outputs(args, originator).await
+    }
+
+    async fn relinquish_output(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.relinquish_output(args, originator).await
+    }
+
+    async fn get_public_key(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.get_public_key(args, originator).await
+    }
+
+    async fn reveal_counterparty_key_linkage(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.reveal_counterparty_key_linkage(args, originator).await
+    }
+
+    async fn reveal_specific_key_linkage(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.reveal_specific_key_linkage(args, originator).await
+    }
+
+    async fn encrypt(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.encrypt(args, originator).await
+    }
+
+    async fn decrypt(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.decrypt(args, originator).await
+    }
+
+    async fn create_hmac(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.create_hmac(args, originator).await
+    }
+
+    async fn verify_hmac(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.verify_hmac(args, originator).await
+    }
+
+    async fn create_signature(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.create_signature(args, originator).await
+    }
+
+    async fn verify_signature(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.verify_signature(args, originator).await
+    }
+
+    async fn acquire_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.acquire_certificate(args, originator).await
+    }
+
+    async fn list_certificates(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.list_certificates(args, originator).await
+    }
+
+    async fn prove_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.prove_certificate(args, originator).await
+    }
+
+    async fn relinquish_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.relinquish_certificate(args, originator).await
+    }
+
+    async fn discover_by_identity_key(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.discover_by_identity_key(args, originator).await
+    }
+
+    async fn discover_by_attributes(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.discover_by_attributes(args, originator).await
+    }
+
+    async fn get_header_for_height(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.get_header_for_height(args, originator).await
+    }
+
+    async fn is_authenticated(&self, _args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        let authenticated = self.is_authenticated(originator).await?;
+        Ok(serde_json::json!({ "authenticated": authenticated }))
+    }
+
+    async fn wait_for_authentication(&self, _args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.wait_for_authentication(originator).await?;
+        Ok(serde_json::json!({ "authenticated": true }))
+    }
+
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.get_height(originator).await
+    }
+
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.get_network(originator).await
+    }
+
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+        self.ensure_can_call(originator).await?;
+        self.underlying_wallet().await?.get_version(originator).await
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::test_utils::MockWallet;
+    use std::sync::Mutex;
+
+    const ADMIN: &str = "admin.example.com";
+
+    /// Wallet the builders hand out
+    fn mock_wallet() -> MockWallet {
+        MockWallet::new().with_response("getHeight", serde_json::json!({"height": 100}))
+    }
+
+    /// In-memory UMP token store
+    #[derive(Default)]
+    pub(crate) struct MockInteractor {
+        pub(crate) tokens: Mutex<Vec<UMPToken>>,
+    }
+
+    #[async_trait::async_trait]
+    impl UMPTokenInteractor for MockInteractor {
+        async fn find_by_presentation_key_hash(&self, hash: &[u8]) -> WalletResult<Option<UMPToken>> {
+            Ok(self.tokens.lock().unwrap().iter().rev().find(|t| t.presentation_hash == hash).cloned())
+        }
+        async fn find_by_recovery_key_hash(&self, hash: &[u8]) -> WalletResult<Option<UMPToken>> {
+            Ok(self.tokens.lock().unwrap().iter().rev().find(|t| t.recovery_hash == hash).cloned())
+        }
+        async fn build_and_send(
+            &self,
+            _wallet: &dyn WalletInterface,
+            _admin_originator: &str,
+            token: &UMPToken,
+            old_token: Option<&UMPToken>,
+        ) -> WalletResult<String> {
+            let mut tokens = self.tokens.lock().unwrap();
+            if let Some(old) = old_token {
+                tokens.retain(|t| t.current_outpoint != old.current_outpoint);
+            }
+            let outpoint = format!("{}.0", hex::encode(sha256(&token.presentation_hash)));
+            let mut published = token.clone();
+            published.current_outpoint = Some(outpoint.clone());
+            tokens.push(published);
+            Ok(outpoint)
+        }
+    }
+
+    /// Test harness: a shared interactor plus the saved recovery key and current password
+    pub(crate) struct Harness {
+        pub(crate) interactor: Arc<MockInteractor>,
+        recovery_key: Arc<Mutex<Option<Vec<u8>>>>,
+        password: Arc<Mutex<String>>,
+    }
+
+    impl Harness {
+        pub(crate) fn new(password: &str) -> Self {
+            Self {
+                interactor: Arc::new(MockInteractor::default()),
+                recovery_key: Arc::new(Mutex::new(None)),
+                password: Arc::new(Mutex::new(password.to_string())),
+            }
+        }
+
+        /// Wallet builder, recovery key saver and password retriever wired to this harness
+        pub(crate) fn callbacks(&self) -> (WalletBuilder, RecoveryKeySaver, PasswordRetriever) {
+            let builder: WalletBuilder = Arc::new(|_key, _manager| {
+                Box::pin(async { Ok(Box::new(mock_wallet()) as Box<dyn WalletInterface>) })
+            });
+            let saved = self.recovery_key.clone();
+            let saver: RecoveryKeySaver = Arc::new(move |key| {
+                *saved.lock().unwrap() = Some(key);
+                Box::pin(async { Ok(true) })
+            });
+            let password = self.password.clone();
+            let retriever: PasswordRetriever = Arc::new(move |_reason, test| {
+                let password = password.lock().unwrap().clone();
+                Box::pin(async move {
+                    assert!(test(&password));
+                    Ok(password)
+                })
+            });
+            (builder, saver, retriever)
+        }
+
+        fn manager(&self) -> CWIStyleWalletManager {
+            let (builder, saver, retriever) = self.callbacks();
+            CWIStyleWalletManager::new(
+                ADMIN.to_string(),
+                builder,
+                self.interactor.clone(),
+                saver,
+                retriever,
+                None,
+            )
+        }
+
+        pub(crate) fn saved_recovery_key(&self) -> Vec<u8> {
+            self.recovery_key.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    async fn new_user(harness: &Harness, presentation_key: &[u8]) -> CWIStyleWalletManager {
+        let manager = harness.manager();
+        manager.provide_presentation_key(presentation_key.to_vec()).await.unwrap();
+        assert_eq!(manager.authentication_flow().await, AuthenticationFlow::NewUser);
+        let password = harness.password.lock().unwrap().clone();
+        manager.provide_password(&password).await.unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_new_user_creates_and_publishes_token() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+
+        assert!(manager.is_authenticated(None).await.unwrap());
+        assert_eq!(harness.saved_recovery_key().len(), 32);
+
+        let tokens = harness.interactor.tokens.lock().unwrap().clone();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].presentation_hash, sha256(&[7u8; 32]));
+        assert_eq!(tokens[0].current_outpoint, manager.current_token().await.unwrap().current_outpoint);
+    }
+
+    #[tokio::test]
+    async fn test_existing_user_presentation_key_and_password() {
+        let harness = Harness::new("hunter22");
+        let first = new_user(&harness, &[7u8; 32]).await;
+        let root_primary_key = first.root_primary_key.read().await.clone();
+
+        let manager = harness.manager();
+        manager.provide_presentation_key(vec![7u8; 32]).await.unwrap();
+        assert_eq!(manager.authentication_flow().await, AuthenticationFlow::ExistingUser);
+        assert!(manager.provide_password("wrong password").await.is_err());
+        manager.provide_password("hunter22").await.unwrap();
+
+        assert_eq!(*manager.root_primary_key.read().await, root_primary_key);
+        assert_eq!(manager.get_height(None).await.unwrap()["height"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_presentation_key_and_recovery_key() {
+        let harness = Harness::new("hunter22");
+        let first = new_user(&harness, &[7u8; 32]).await;
+        let root_primary_key = first.root_primary_key.read().await.clone();
+
+        let manager = harness.manager();
+        manager.set_authentication_mode(AuthenticationMode::PresentationKeyAndRecoveryKey).await.unwrap();
+        manager.provide_presentation_key(vec![7u8; 32]).await.unwrap();
+        assert!(manager.provide_password("hunter22").await.is_err());
+        manager.provide_recovery_key(harness.saved_recovery_key()).await.unwrap();
+
+        assert_eq!(*manager.root_primary_key.read().await, root_primary_key);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_key_and_password() {
+        let harness = Harness::new("hunter22");
+        let first = new_user(&harness, &[7u8; 32]).await;
+        let root_primary_key = first.root_primary_key.read().await.clone();
+
+        let manager = harness.manager();
+        manager.set_authentication_mode(AuthenticationMode::RecoveryKeyAndPassword).await.unwrap();
+        assert!(manager.provide_presentation_key(vec![7u8; 32]).await.is_err());
+        assert!(manager.provide_recovery_key(vec![9u8; 32]).await.is_err());
+        manager.provide_recovery_key(harness.saved_recovery_key()).await.unwrap();
+        manager.provide_password("hunter22").await.unwrap();
+
+        assert_eq!(*manager.root_primary_key.read().await, root_primary_key);
+        assert_eq!(*manager.presentation_key.read().await, Some(vec![7u8; 32]));
+    }
+
+    #[tokio::test]
+    async fn test_change_password_republishes_token() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+        // Wipe the retained privileged key so the change prompts for the current password
+        manager.root_privileged_key_manager.read().await.as_ref().unwrap().destroy_key();
+
+        manager.change_password("correct horse").await.unwrap();
+        assert_eq!(harness.interactor.tokens.lock().unwrap().len(), 1);
+
+        let relogin = harness.manager();
+        relogin.provide_presentation_key(vec![7u8; 32]).await.unwrap();
+        assert!(relogin.provide_password("hunter22").await.is_err());
+        relogin.provide_password("correct horse").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_change_recovery_and_presentation_keys() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+        let old_recovery_key = harness.saved_recovery_key();
+
+        manager.change_recovery_key().await.unwrap();
+        assert_ne!(harness.saved_recovery_key(), old_recovery_key);
+
+        manager.change_presentation_key(vec![8u8; 32]).await.unwrap();
+        let relogin = harness.manager();
+        relogin.set_authentication_mode(AuthenticationMode::PresentationKeyAndRecoveryKey).await.unwrap();
+        relogin.provide_presentation_key(vec![8u8; 32]).await.unwrap();
+        assert_eq!(relogin.authentication_flow().await, AuthenticationFlow::ExistingUser);
+        relogin.provide_recovery_key(harness.saved_recovery_key()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrip() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+        let snapshot = manager.save_snapshot().await.unwrap();
+
+        let restored = harness.manager();
+        restored.load_snapshot(snapshot.clone()).await.unwrap();
+        assert!(restored.is_authenticated(None).await.unwrap());
+        assert_eq!(*restored.root_primary_key.read().await, *manager.root_primary_key.read().await);
+
+        let mut tampered = snapshot;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(harness.manager().load_snapshot(tampered).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ensure_can_call() {
+        let harness = Harness::new("hunter22");
+        let manager = harness.manager();
+        assert!(manager.get_height(None).await.is_err());
+
+        let manager = new_user(&harness, &[7u8; 32]).await;
+        assert!(manager.get_height(Some(ADMIN)).await.is_err());
+        assert_eq!(manager.list_profiles().await.unwrap()[0].id, DEFAULT_PROFILE_ID.to_vec());
+
+        manager.destroy().await;
+        assert!(manager.get_height(None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_switch_and_delete_profiles() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+
+        assert!(manager.add_profile("default").await.is_err());
+        let work = manager.add_profile("work").await.unwrap();
+        assert!(manager.add_profile("work").await.is_err());
+        assert_eq!(work.len(), 16);
+        assert!(harness.interactor.tokens.lock().unwrap()[0].profiles_encrypted.is_some());
+
+        let names: Vec<String> = manager.list_profiles().await.unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["default", "work"]);
+
+        manager.switch_profile(&work).await.unwrap();
+        assert_eq!(manager.active_profile_id().await, work);
+        assert!(manager.switch_profile(&[5u8; 16]).await.is_err());
+
+        assert!(manager.delete_profile(&DEFAULT_PROFILE_ID).await.is_err());
+        manager.delete_profile(&work).await.unwrap();
+        assert_eq!(manager.active_profile_id().await, DEFAULT_PROFILE_ID.to_vec());
+        assert_eq!(manager.list_profiles().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_profile_keys_are_padded_root_keys() {
+        let harness = Harness::new("hunter22");
+        let (_, saver, retriever) = harness.callbacks();
+        let built: Arc<Mutex<Vec<(Vec<u8>, Arc<dyn PrivilegedKeyProvider>)>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded = built.clone();
+        let builder: WalletBuilder = Arc::new(move |key, manager| {
+            recorded.lock().unwrap().push((key, manager));
+            Box::pin(async { Ok(Box::new(mock_wallet()) as Box<dyn WalletInterface>) })
+        });
+        let manager = CWIStyleWalletManager::new(ADMIN.to_string(), builder, harness.interactor.clone(), saver, retriever, None);
+        manager.provide_presentation_key(vec![7u8; 32]).await.unwrap();
+        manager.provide_password("hunter22").await.unwrap();
+
+        let work = manager.add_profile("work").await.unwrap();
+        manager.switch_profile(&work).await.unwrap();
+        let profile = manager.find_profile(&work).await.unwrap();
+        let root_primary_key = manager.root_primary_key.read().await.clone().unwrap();
+        let root_privileged_key = manager.get_privileged_key("test").await.unwrap();
+
+        let (profile_key, profile_manager) = built.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(profile_key, xor(&root_primary_key, &profile.primary_pad).unwrap());
+        assert_ne!(profile_key, root_primary_key);
+        assert_eq!(
+            *profile_manager.get_privileged_key("test").await.unwrap(),
+            xor(&root_privileged_key, &profile.privileged_pad).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_profiles_survive_relogin_and_snapshot() {
+        let harness = Harness::new("hunter22");
+        let manager = new_user(&harness, &[7u8; 32]).await;
+        let work = manager.add_profile("work").await.unwrap();
+        manager.switch_profile(&work).await.unwrap();
+        let snapshot = manager.save_snapshot().await.unwrap();
+
+        let relogin = harness.manager();
+        relogin.provide_presentation_key(vec![7u8; 32]).await.unwrap();
+        relogin.provide_password("hunter22").await.unwrap();
+        assert_eq!(relogin.list_profiles().await.unwrap()[1].id, work);
+        assert_eq!(relogin.active_profile_id().await, DEFAULT_PROFILE_ID.to_vec());
+
+        let restored = harness.manager();
+        restored.load_snapshot(snapshot).await.unwrap();
+        assert_eq!(restored.active_profile_id().await, work);
+    }
+
+    #[test]
+    fn test_ump_token_serialization() {
+        let token = UMPToken {
+            password_presentation_primary: vec![1],
+            password_recovery_primary: vec![2],
+            presentation_recovery_primary: vec![3],
+            password_primary_privileged: vec![4],
+            presentation_recovery_privileged: vec![5],
+            presentation_hash: vec![6; 32],
+            password_salt: vec![7; 32],
+            recovery_hash: vec![8; 32],
+            presentation_key_encrypted: vec![9],
+            recovery_key_encrypted: vec![10],
+            password_key_encrypted: vec![11],
+            profiles_encrypted: Some(vec![12, 13]),
+            current_outpoint: Some(format!("{}.1", "ab".repeat(32))),
+        };
+        assert_eq!(UMPToken::deserialize(&token.serialize().unwrap()).unwrap(), token);
+        assert_eq!(UMPToken::from_fields(&token.to_fields(), token.current_outpoint.clone()).unwrap(), token);
+
+        let unpublished = UMPToken { current_outpoint: None, ..token };
+        assert!(unpublished.serialize().is_err());
+    }
+}
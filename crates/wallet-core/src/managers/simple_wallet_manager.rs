@@ -5,10 +5,14 @@
 //! A slimmed-down wallet manager that requires only a primary key and privileged key manager
 //! for authentication. Proxies all wallet operations to an underlying WalletInterface instance.
 
+use crate::crypto::{decrypt_with_aes_gcm, encrypt_with_aes_gcm};
+use crate::managers::cwi_style_wallet_manager::DEFAULT_PROFILE_ID;
 use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::originator::Originator;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use zeroize::Zeroizing;
 
 /// Originator domain name (under 250 bytes)
 ///
@@ -68,11 +72,61 @@ pub trait WalletInterface: Send + Sync {
     async fn get_version(&self, originator: Option<&str>) -> WalletResult<serde_json::Value>;
 }
 
+/// Dispatch one BRC-100 call by its wire name
+///
+/// Returns `None` for unknown method names.
+pub async fn call_wallet(
+    wallet: &dyn WalletInterface,
+    method: &str,
+    args: serde_json::Value,
+    originator: Option<&str>,
+) -> Option<WalletResult<serde_json::Value>> {
+    let result = match method {
+        "createAction" => wallet.create_action(args, originator).await,
+        "signAction" => wallet.sign_action(args, originator).await,
+        "abortAction" => wallet.abort_action(args, originator).await,
+        "listActions" => wallet.list_actions(args, originator).await,
+        "internalizeAction" => wallet.internalize_action(args, originator).await,
+        "listOutputs" => wallet.list_outputs(args, originator).await,
+        "relinquishOutput" => wallet.relinquish_output(args, originator).await,
+        "getPublicKey" => wallet.get_public_key(args, originator).await,
+        "revealCounterpartyKeyLinkage" => wallet.reveal_counterparty_key_linkage(args, originator).await,
+        "revealSpecificKeyLinkage" => wallet.reveal_specific_key_linkage(args, originator).await,
+        "encrypt" => wallet.encrypt(args, originator).await,
+        "decrypt" => wallet.decrypt(args, originator).await,
+        "createHmac" => wallet.create_hmac(args, originator).await,
+        "verifyHmac" => wallet.verify_hmac(args, originator).await,
+        "createSignature" => wallet.create_signature(args, originator).await,
+        "verifySignature" => wallet.verify_signature(args, originator).await,
+        "acquireCertificate" => wallet.acquire_certificate(args, originator).await,
+        "listCertificates" => wallet.list_certificates(args, originator).await,
+        "proveCertificate" => wallet.prove_certificate(args, originator).await,
+        "relinquishCertificate" => wallet.relinquish_certificate(args, originator).await,
+        "discoverByIdentityKey" => wallet.discover_by_identity_key(args, originator).await,
+        "discoverByAttributes" => wallet.discover_by_attributes(args, originator).await,
+        "isAuthenticated" => wallet.is_authenticated(args, originator).await,
+        "waitForAuthentication" => wallet.wait_for_authentication(args, originator).await,
+        "getHeight" => wallet.get_height(originator).await,
+        "getHeaderForHeight" => wallet.get_header_for_height(args, originator).await,
+        "getNetwork" => wallet.get_network(originator).await,
+        "getVersion" => wallet.get_version(originator).await,
+        _ => return None,
+    };
+    Some(result)
+}
+
 /// Privileged key manager
 ///
 /// Reference: TS PrivilegedKeyManager
+#[async_trait::async_trait]
 pub trait PrivilegedKeyManager: Send + Sync {
-    // TODO: Define privileged operations
+    /// Obtain the privileged private key, prompting the user if needed
+    ///
+    /// The key is wiped when the returned value is dropped.
+    /// Reference: TS PrivilegedKeyManager keyGetter(reason)
+    async fn get_privileged_key(&self, _reason: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        Err(WalletError::not_implemented("get_privileged_key"))
+    }
 }
 
 /// Wallet builder function type
@@ -84,6 +138,61 @@ pub type WalletBuilder = Arc<
     > + Send + Sync
 >;
 
+/// Current snapshot format version
+///
+/// Version 1 holds only the encrypted key material; version 2 adds the
+/// active profile id.
+pub const SNAPSHOT_VERSION: u8 = 2;
+
+/// Encrypt a snapshot preimage under a fresh snapshot key
+///
+/// Reference: TS saveSnapshot
+///
+/// Format: `[version][32-byte snapshot key][AES-256-GCM payload]`. The payload
+/// repeats the version byte ahead of the preimage and the GCM tag
+/// authenticates it, so tampered or downgraded snapshots fail to open.
+pub(crate) fn seal_snapshot(preimage: &[u8]) -> WalletResult<Vec<u8>> {
+    let mut snapshot_key = [0u8; 32];
+    rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut snapshot_key);
+    let mut plaintext = Vec::with_capacity(1 + preimage.len());
+    plaintext.push(SNAPSHOT_VERSION);
+    plaintext.extend_from_slice(preimage);
+    let payload = encrypt_with_aes_gcm(&plaintext, &snapshot_key)?;
+
+    let mut snapshot = Vec::with_capacity(1 + snapshot_key.len() + payload.len());
+    snapshot.push(SNAPSHOT_VERSION);
+    snapshot.extend_from_slice(&snapshot_key);
+    snapshot.extend(payload);
+    Ok(snapshot)
+}
+
+/// Decrypt a snapshot written by [`seal_snapshot`], returning its version and preimage
+///
+/// Reference: TS loadSnapshot
+///
+/// Version 1 payloads hold the bare preimage.
+pub(crate) fn open_snapshot(snapshot: &[u8]) -> WalletResult<(u8, Vec<u8>)> {
+    let tampered = || WalletError::invalid_parameter("snapshot", "an untampered snapshot");
+    if snapshot.len() < 33 {
+        return Err(WalletError::invalid_parameter("snapshot", "too short"));
+    }
+    let version = snapshot[0];
+    if version != 1 && version != SNAPSHOT_VERSION {
+        return Err(WalletError::invalid_parameter(
+            "snapshot",
+            format!("Unsupported snapshot version: {}", version),
+        ));
+    }
+    let mut preimage = decrypt_with_aes_gcm(&snapshot[33..], &snapshot[1..33]).map_err(|_| tampered())?;
+    if version >= 2 {
+        if preimage.first() != Some(&version) {
+            return Err(tampered());
+        }
+        preimage.remove(0);
+    }
+    Ok((version, preimage))
+}
+
 /// Simple Wallet Manager
 ///
 /// Reference: TS SimpleWalletManager class (SimpleWalletManager.ts lines 84-526)
@@ -113,7 +222,7 @@ pub struct SimpleWalletManager {
     /// Whether user is authenticated
     authenticated: Arc<RwLock<bool>>,
     
-    /// Admin originator domain (protected from external use)
+    /// Admin originator domain, normalized (protected from external use)
     admin_originator: String,
     
     /// Wallet builder function
@@ -127,6 +236,9 @@ pub struct SimpleWalletManager {
     
     /// Primary key (32 bytes)
     primary_key: Arc<RwLock<Option<Vec<u8>>>>,
+
+    /// Profile the wallet was built for
+    active_profile_id: Arc<RwLock<[u8; 16]>>,
 }
 
 impl SimpleWalletManager {
@@ -145,11 +257,12 @@ impl SimpleWalletManager {
     ) -> Self {
         let manager = Self {
             authenticated: Arc::new(RwLock::new(false)),
-            admin_originator,
+            admin_originator: Originator::normalize(admin_originator),
             wallet_builder,
             underlying: Arc::new(RwLock::new(None)),
             privileged_manager: Arc::new(RwLock::new(None)),
             primary_key: Arc::new(RwLock::new(None)),
+            active_profile_id: Arc::new(RwLock::new(DEFAULT_PROFILE_ID)),
         };
         
         // Load snapshot if provided
@@ -245,11 +358,13 @@ impl SimpleWalletManager {
     ///
     /// Reference: TS saveSnapshot (SimpleWalletManager.ts lines 210-237)
     ///
-    /// Creates an encrypted snapshot containing the primary key.
+    /// Creates a version 2 snapshot holding the primary key and the active
+    /// profile id, encrypted under a fresh snapshot key.
     /// The snapshot does NOT include the privileged key manager.
     ///
     /// # Security
-    /// The snapshot contains critical secret material and must be protected carefully.
+    /// The snapshot key travels with the snapshot, so it contains critical
+    /// secret material and must be protected carefully.
     ///
     /// # Returns
     /// Byte array representing the encrypted snapshot
@@ -261,50 +376,34 @@ impl SimpleWalletManager {
                 "No primary key is set; cannot save snapshot."
             ))?;
         
-        // TODO: Implement full snapshot encryption
-        // For now, return a simple version-prefixed structure
-        let mut snapshot = Vec::new();
-        snapshot.push(1); // Version byte
-        snapshot.push(key.len() as u8); // Length
-        snapshot.extend_from_slice(key);
-        
-        Ok(snapshot)
+        let mut preimage = key.clone();
+        preimage.extend_from_slice(&*self.active_profile_id.read().await);
+        seal_snapshot(&preimage)
     }
     
     /// Load a previously saved state snapshot
     ///
     /// Reference: TS loadSnapshot (SimpleWalletManager.ts lines 247-279)
     ///
-    /// Restores the primary key from a snapshot. The privileged key manager
-    /// must still be provided separately to complete authentication.
+    /// Restores the primary key and active profile from a snapshot. The
+    /// privileged key manager must still be provided separately to complete
+    /// authentication. Version 1 snapshots restore the default profile.
     pub async fn load_snapshot(&self, snapshot: Vec<u8>) -> WalletResult<()> {
-        if snapshot.len() < 2 {
-            return Err(WalletError::invalid_parameter(
-                "snapshot",
-                "too short"
-            ));
-        }
-        
-        // TODO: Implement full snapshot decryption
-        // For now, read simple version-prefixed structure
-        let version = snapshot[0];
-        if version != 1 {
-            return Err(WalletError::invalid_parameter(
-                "snapshot",
-                &format!("Unsupported snapshot version: {}", version)
-            ));
-        }
-        
-        let length = snapshot[1] as usize;
-        if snapshot.len() < 2 + length {
+        let (version, preimage) = open_snapshot(&snapshot)?;
+        let expected_len = if version == 1 { 32 } else { 48 };
+        if preimage.len() != expected_len {
             return Err(WalletError::invalid_parameter(
                 "snapshot",
                 "invalid length"
             ));
         }
         
-        let primary_key = snapshot[2..(2 + length)].to_vec();
-        *self.primary_key.write().await = Some(primary_key);
+        let mut profile_id = DEFAULT_PROFILE_ID;
+        if version >= 2 {
+            profile_id.copy_from_slice(&preimage[32..48]);
+        }
+        *self.primary_key.write().await = Some(preimage[..32].to_vec());
+        *self.active_profile_id.write().await = profile_id;
         
         // Try to build underlying if privileged manager already provided
         self.try_build_underlying().await
@@ -325,13 +424,7 @@ impl SimpleWalletManager {
     /// Blocks until the user is authenticated by providing both
     /// primary key and privileged manager.
     pub async fn wait_for_authentication(&self, originator: Option<&str>) -> WalletResult<bool> {
-        if let Some(orig) = originator {
-            if orig == self.admin_originator {
-                return Err(WalletError::invalid_operation(
-                    "External applications cannot use the admin originator."
-                ));
-            }
-        }
+        self.ensure_not_admin(originator)?;
         
         while !*self.authenticated.read().await {
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -340,6 +433,22 @@ impl SimpleWalletManager {
         Ok(true)
     }
     
+    /// Refuse the admin originator, and originators that are not valid
+    ///
+    /// Originators are compared normalized, so `Admin.Example.com` is the
+    /// admin too.
+    fn ensure_not_admin(&self, originator: Option<&str>) -> WalletResult<()> {
+        let Some(originator) = originator else {
+            return Ok(());
+        };
+        if Originator::parse(originator)?.as_str() == self.admin_originator {
+            return Err(WalletError::invalid_operation(
+                "External applications cannot use the admin originator."
+            ));
+        }
+        Ok(())
+    }
+
     /// Ensure the call can proceed (authenticated and not admin originator)
     ///
     /// Reference: TS ensureCanCall (SimpleWalletManager.ts lines 518-525)
@@ -347,14 +456,9 @@ impl SimpleWalletManager {
     /// Helper that throws if:
     /// - User is not authenticated
     /// - Provided originator is the admin (not permitted externally)
+    /// - Provided originator is not a valid domain name
     async fn ensure_can_call(&self, originator: Option<&str>) -> WalletResult<()> {
-        if let Some(orig) = originator {
-            if orig == self.admin_originator {
-                return Err(WalletError::invalid_operation(
-                    "External applications cannot use the admin originator."
-                ));
-            }
-        }
+        self.ensure_not_admin(originator)?;
         
         if !*self.authenticated.read().await {
             return Err(WalletError::invalid_operation(
@@ -649,48 +753,13 @@ mod tests {
     struct MockPrivilegedManager;
     impl PrivilegedKeyManager for MockPrivilegedManager {}
     
-    // Mock WalletInterface for testing
-    struct MockWallet;
-    
-    #[async_trait::async_trait]
-    impl WalletInterface for MockWallet {
-        async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"success": true}))
-        }
-        async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"success": true}))
-        }
-        async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"success": true}))
-        }
-        async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"actions": []}))
-        }
-        async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"success": true}))
-        }
-        async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"outputs": []}))
-        }
-        async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"publicKey": "test"}))
-        }
-        async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"height": 100}))
-        }
-        async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"network": "main"}))
-        }
-        async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({"version": "1.0.0"}))
-        }
-    }
-    
+    use crate::test_utils::MockWallet;
+
     #[tokio::test]
     async fn test_simple_wallet_manager_creation() {
         let builder: WalletBuilder = Arc::new(|_key, _manager| {
             Box::pin(async {
-                Ok(Box::new(MockWallet) as Box<dyn WalletInterface>)
+                Ok(Box::new(MockWallet::new()) as Box<dyn WalletInterface>)
             })
         });
         
@@ -707,7 +776,7 @@ mod tests {
     async fn test_provide_primary_key() {
         let builder: WalletBuilder = Arc::new(|_key, _manager| {
             Box::pin(async {
-                Ok(Box::new(MockWallet) as Box<dyn WalletInterface>)
+                Ok(Box::new(MockWallet::new()) as Box<dyn WalletInterface>)
             })
         });
         
@@ -726,7 +795,7 @@ mod tests {
     async fn test_authentication_flow() {
         let builder: WalletBuilder = Arc::new(|_key, _manager| {
             Box::pin(async {
-                Ok(Box::new(MockWallet) as Box<dyn WalletInterface>)
+                Ok(Box::new(MockWallet::new()) as Box<dyn WalletInterface>)
             })
         });
         
@@ -758,7 +827,7 @@ mod tests {
     async fn test_admin_originator_blocked() {
         let builder: WalletBuilder = Arc::new(|_key, _manager| {
             Box::pin(async {
-                Ok(Box::new(MockWallet) as Box<dyn WalletInterface>)
+                Ok(Box::new(MockWallet::new()) as Box<dyn WalletInterface>)
             })
         });
         
@@ -781,5 +850,54 @@ mod tests {
         ).await;
         
         assert!(result.is_err());
+
+        // Spelled differently, it is still the admin
+        assert!(manager.is_authenticated(Some(" Admin.Example.COM.")).await.is_err());
+        assert!(manager.is_authenticated(Some("not a domain")).await.is_err());
+        assert!(manager.is_authenticated(Some("app.example.com")).await.unwrap());
+    }
+
+    fn snapshot_manager() -> SimpleWalletManager {
+        let builder: WalletBuilder = Arc::new(|_key, _manager| {
+            Box::pin(async {
+                Ok(Box::new(MockWallet::new()) as Box<dyn WalletInterface>)
+            })
+        });
+        SimpleWalletManager::new("admin.example.com".to_string(), builder, None)
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_v2_roundtrip() {
+        let manager = snapshot_manager();
+        manager.provide_primary_key(vec![7u8; 32]).await.unwrap();
+        *manager.active_profile_id.write().await = [3u8; 16];
+
+        let snapshot = manager.save_snapshot().await.unwrap();
+        assert_eq!(snapshot[0], SNAPSHOT_VERSION);
+        assert!(!snapshot.windows(32).any(|w| w == [7u8; 32]));
+
+        let restored = snapshot_manager();
+        restored.load_snapshot(snapshot).await.unwrap();
+        assert_eq!(restored.primary_key.read().await.as_deref(), Some(&[7u8; 32][..]));
+        assert_eq!(*restored.active_profile_id.read().await, [3u8; 16]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_rejects_tampering() {
+        let manager = snapshot_manager();
+        manager.provide_primary_key(vec![7u8; 32]).await.unwrap();
+        let snapshot = manager.save_snapshot().await.unwrap();
+
+        let mut tampered = snapshot.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(snapshot_manager().load_snapshot(tampered).await.is_err());
+
+        let mut downgraded = snapshot.clone();
+        downgraded[0] = 1;
+        assert!(snapshot_manager().load_snapshot(downgraded).await.is_err());
+
+        let mut unknown = snapshot;
+        unknown[0] = 9;
+        assert!(snapshot_manager().load_snapshot(unknown).await.is_err());
     }
 }
@@ -0,0 +1,244 @@
+//! Typed Wallet Interface
+//!
+//! **Reference**: TypeScript `WalletInterface` from @bsv/sdk (Wallet.interfaces.ts)
+//!
+//! Strongly-typed view of [`WalletInterface`]. Every method takes the SDK
+//! argument struct and returns the SDK result struct instead of raw JSON.
+//!
+//! [`WalletInterface`] stays the JSON boundary used by the Tauri commands and
+//! the wallet server, so every existing implementation gets this trait for
+//! free through the blanket impl below, which round-trips through serde.
+//! Argument or result shapes that do not match surface as `WERR_JSON`
+//! errors rather than panics.
+//!
+//! Both traits share method names; import only the one you call, or use
+//! fully qualified syntax when both are in scope.
+
+use crate::managers::simple_wallet_manager::WalletInterface;
+use crate::methods::{ListActionsResult, ListOutputsResult};
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{
+    AbortActionArgs, AbortActionResult, AcquireCertificateArgs, AuthenticatedResult,
+    CertificateResult, CreateActionArgs, CreateActionResult, CreateHmacArgs, CreateHmacResult,
+    CreateSignatureArgs, CreateSignatureResult, DiscoverByAttributesArgs,
+    DiscoverByIdentityKeyArgs, DiscoverCertificatesResult, GetHeaderArgs, GetHeaderResult,
+    GetHeightResult, GetNetworkResult, GetPublicKeyArgs, GetPublicKeyResult,
+    GetVersionResult, InternalizeActionArgs, InternalizeActionResult, ListActionsArgs,
+    ListCertificatesArgs, ListCertificatesResult, ListOutputsArgs,
+    ProveCertificateArgs, RelinquishCertificateArgs, RelinquishCertificateResult,
+    RelinquishOutputArgs, RelinquishOutputResult, RevealCounterpartyKeyLinkageArgs,
+    RevealCounterpartyKeyLinkageResult, RevealSpecificKeyLinkageArgs,
+    RevealSpecificKeyLinkageResult, SignActionArgs, SignActionResult, VerifyHmacArgs,
+    VerifyHmacResult, VerifySignatureArgs, VerifySignatureResult, WalletDecryptArgs,
+    WalletDecryptResult, WalletEncryptArgs, WalletEncryptResult,
+};
+use crate::signer::methods::ProveCertificateResult;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Typed wallet interface trait
+///
+/// Reference: TS WalletInterface from @bsv/sdk
+///
+/// Same 28 methods as [`WalletInterface`], with SDK argument and result types.
+#[async_trait::async_trait]
+pub trait TypedWalletInterface: Send + Sync {
+    // ===== Action Management (5 methods) =====
+    async fn create_action(&self, args: CreateActionArgs, originator: Option<&str>) -> WalletResult<CreateActionResult>;
+    async fn sign_action(&self, args: SignActionArgs, originator: Option<&str>) -> WalletResult<SignActionResult>;
+    async fn abort_action(&self, args: AbortActionArgs, originator: Option<&str>) -> WalletResult<AbortActionResult>;
+    async fn list_actions(&self, args: ListActionsArgs, originator: Option<&str>) -> WalletResult<ListActionsResult>;
+    async fn internalize_action(&self, args: InternalizeActionArgs, originator: Option<&str>) -> WalletResult<InternalizeActionResult>;
+
+    // ===== Output Management (2 methods) =====
+    async fn list_outputs(&self, args: ListOutputsArgs, originator: Option<&str>) -> WalletResult<ListOutputsResult>;
+    async fn relinquish_output(&self, args: RelinquishOutputArgs, originator: Option<&str>) -> WalletResult<RelinquishOutputResult>;
+
+    // ===== Key Operations (3 methods) =====
+    async fn get_public_key(&self, args: GetPublicKeyArgs, originator: Option<&str>) -> WalletResult<GetPublicKeyResult>;
+    async fn reveal_counterparty_key_linkage(&self, args: RevealCounterpartyKeyLinkageArgs, originator: Option<&str>) -> WalletResult<RevealCounterpartyKeyLinkageResult>;
+    async fn reveal_specific_key_linkage(&self, args: RevealSpecificKeyLinkageArgs, originator: Option<&str>) -> WalletResult<RevealSpecificKeyLinkageResult>;
+
+    // ===== Cryptographic Operations (6 methods) =====
+    async fn encrypt(&self, args: WalletEncryptArgs, originator: Option<&str>) -> WalletResult<WalletEncryptResult>;
+    async fn decrypt(&self, args: WalletDecryptArgs, originator: Option<&str>) -> WalletResult<WalletDecryptResult>;
+    async fn create_hmac(&self, args: CreateHmacArgs, originator: Option<&str>) -> WalletResult<CreateHmacResult>;
+    async fn verify_hmac(&self, args: VerifyHmacArgs, originator: Option<&str>) -> WalletResult<VerifyHmacResult>;
+    async fn create_signature(&self, args: CreateSignatureArgs, originator: Option<&str>) -> WalletResult<CreateSignatureResult>;
+    async fn verify_signature(&self, args: VerifySignatureArgs, originator: Option<&str>) -> WalletResult<VerifySignatureResult>;
+
+    // ===== Certificate Operations (4 methods) =====
+    async fn acquire_certificate(&self, args: AcquireCertificateArgs, originator: Option<&str>) -> WalletResult<CertificateResult>;
+    async fn list_certificates(&self, args: ListCertificatesArgs, originator: Option<&str>) -> WalletResult<ListCertificatesResult>;
+    async fn prove_certificate(&self, args: ProveCertificateArgs, originator: Option<&str>) -> WalletResult<ProveCertificateResult>;
+    async fn relinquish_certificate(&self, args: RelinquishCertificateArgs, originator: Option<&str>) -> WalletResult<RelinquishCertificateResult>;
+
+    // ===== Identity Operations (2 methods) =====
+    async fn discover_by_identity_key(&self, args: DiscoverByIdentityKeyArgs, originator: Option<&str>) -> WalletResult<DiscoverCertificatesResult>;
+    async fn discover_by_attributes(&self, args: DiscoverByAttributesArgs, originator: Option<&str>) -> WalletResult<DiscoverCertificatesResult>;
+
+    // ===== Authentication (2 methods) =====
+    async fn is_authenticated(&self, originator: Option<&str>) -> WalletResult<AuthenticatedResult>;
+    async fn wait_for_authentication(&self, originator: Option<&str>) -> WalletResult<AuthenticatedResult>;
+
+    // ===== Blockchain Queries (4 methods) =====
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<GetHeightResult>;
+    async fn get_header_for_height(&self, args: GetHeaderArgs, originator: Option<&str>) -> WalletResult<GetHeaderResult>;
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<GetNetworkResult>;
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<GetVersionResult>;
+}
+
+/// Serialize typed arguments for the JSON interface
+fn to_json<A: Serialize>(args: A) -> WalletResult<serde_json::Value> {
+    Ok(serde_json::to_value(args)?)
+}
+
+/// Deserialize a JSON interface result into its typed form
+fn from_json<R: DeserializeOwned>(value: serde_json::Value) -> WalletResult<R> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// JSON compatibility shim: every [`WalletInterface`] is a [`TypedWalletInterface`]
+#[async_trait::async_trait]
+impl<W: WalletInterface + ?Sized> TypedWalletInterface for W {
+    async fn create_action(&self, args: CreateActionArgs, originator: Option<&str>) -> WalletResult<CreateActionResult> {
+        from_json(WalletInterface::create_action(self, to_json(args)?, originator).await?)
+    }
+
+    async fn sign_action(&self, args: SignActionArgs, originator: Option<&str>) -> WalletResult<SignActionResult> {
+        from_json(WalletInterface::sign_action(self, to_json(args)?, originator).await?)
+    }
+
+    async fn abort_action(&self, args: AbortActionArgs, originator: Option<&str>) -> WalletResult<AbortActionResult> {
+        from_json(WalletInterface::abort_action(self, to_json(args)?, originator).await?)
+    }
+
+    async fn list_actions(&self, args: ListActionsArgs, originator: Option<&str>) -> WalletResult<ListActionsResult> {
+        from_json(WalletInterface::list_actions(self, to_json(args)?, originator).await?)
+    }
+
+    async fn internalize_action(&self, args: InternalizeActionArgs, originator: Option<&str>) -> WalletResult<InternalizeActionResult> {
+        from_json(WalletInterface::internalize_action(self, to_json(args)?, originator).await?)
+    }
+
+    async fn list_outputs(&self, args: ListOutputsArgs, originator: Option<&str>) -> WalletResult<ListOutputsResult> {
+        from_json(WalletInterface::list_outputs(self, to_json(args)?, originator).await?)
+    }
+
+    async fn relinquish_output(&self, args: RelinquishOutputArgs, originator: Option<&str>) -> WalletResult<RelinquishOutputResult> {
+        from_json(WalletInterface::relinquish_output(self, to_json(args)?, originator).await?)
+    }
+
+    async fn get_public_key(&self, args: GetPublicKeyArgs, originator: Option<&str>) -> WalletResult<GetPublicKeyResult> {
+        from_json(WalletInterface::get_public_key(self, to_json(args)?, originator).await?)
+    }
+
+    async fn reveal_counterparty_key_linkage(&self, args: RevealCounterpartyKeyLinkageArgs, originator: Option<&str>) -> WalletResult<RevealCounterpartyKeyLinkageResult> {
+        from_json(WalletInterface::reveal_counterparty_key_linkage(self, to_json(args)?, originator).await?)
+    }
+
+    async fn reveal_specific_key_linkage(&self, args: RevealSpecificKeyLinkageArgs, originator: Option<&str>) -> WalletResult<RevealSpecificKeyLinkageResult> {
+        from_json(WalletInterface::reveal_specific_key_linkage(self, to_json(args)?, originator).await?)
+    }
+
+    async fn encrypt(&self, args: WalletEncryptArgs, originator: Option<&str>) -> WalletResult<WalletEncryptResult> {
+        from_json(WalletInterface::encrypt(self, to_json(args)?, originator).await?)
+    }
+
+    async fn decrypt(&self, args: WalletDecryptArgs, originator: Option<&str>) -> WalletResult<WalletDecryptResult> {
+        from_json(WalletInterface::decrypt(self, to_json(args)?, originator).await?)
+    }
+
+    async fn create_hmac(&self, args: CreateHmacArgs, originator: Option<&str>) -> WalletResult<CreateHmacResult> {
+        from_json(WalletInterface::create_hmac(self, to_json(args)?, originator).await?)
+    }
+
+    async fn verify_hmac(&self, args: VerifyHmacArgs, originator: Option<&str>) -> WalletResult<VerifyHmacResult> {
+        from_json(WalletInterface::verify_hmac(self, to_json(args)?, originator).await?)
+    }
+
+    async fn create_signature(&self, args: CreateSignatureArgs, originator: Option<&str>) -> WalletResult<CreateSignatureResult> {
+        from_json(WalletInterface::create_signature(self, to_json(args)?, originator).await?)
+    }
+
+    async fn verify_signature(&self, args: VerifySignatureArgs, originator: Option<&str>) -> WalletResult<VerifySignatureResult> {
+        from_json(WalletInterface::verify_signature(self, to_json(args)?, originator).await?)
+    }
+
+    async fn acquire_certificate(&self, args: AcquireCertificateArgs, originator: Option<&str>) -> WalletResult<CertificateResult> {
+        from_json(WalletInterface::acquire_certificate(self, to_json(args)?, originator).await?)
+    }
+
+    async fn list_certificates(&self, args: ListCertificatesArgs, originator: Option<&str>) -> WalletResult<ListCertificatesResult> {
+        from_json(WalletInterface::list_certificates(self, to_json(args)?, originator).await?)
+    }
+
+    async fn prove_certificate(&self, args: ProveCertificateArgs, originator: Option<&str>) -> WalletResult<ProveCertificateResult> {
+        from_json(WalletInterface::prove_certificate(self, to_json(args)?, originator).await?)
+    }
+
+    async fn relinquish_certificate(&self, args: RelinquishCertificateArgs, originator: Option<&str>) -> WalletResult<RelinquishCertificateResult> {
+        from_json(WalletInterface::relinquish_certificate(self, to_json(args)?, originator).await?)
+    }
+
+    async fn discover_by_identity_key(&self, args: DiscoverByIdentityKeyArgs, originator: Option<&str>) -> WalletResult<DiscoverCertificatesResult> {
+        from_json(WalletInterface::discover_by_identity_key(self, to_json(args)?, originator).await?)
+    }
+
+    async fn discover_by_attributes(&self, args: DiscoverByAttributesArgs, originator: Option<&str>) -> WalletResult<DiscoverCertificatesResult> {
+        from_json(WalletInterface::discover_by_attributes(self, to_json(args)?, originator).await?)
+    }
+
+    async fn is_authenticated(&self, originator: Option<&str>) -> WalletResult<AuthenticatedResult> {
+        from_json(WalletInterface::is_authenticated(self, serde_json::json!({}), originator).await?)
+    }
+
+    async fn wait_for_authentication(&self, originator: Option<&str>) -> WalletResult<AuthenticatedResult> {
+        from_json(WalletInterface::wait_for_authentication(self, serde_json::json!({}), originator).await?)
+    }
+
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<GetHeightResult> {
+        from_json(WalletInterface::get_height(self, originator).await?)
+    }
+
+    async fn get_header_for_height(&self, args: GetHeaderArgs, originator: Option<&str>) -> WalletResult<GetHeaderResult> {
+        from_json(WalletInterface::get_header_for_height(self, to_json(args)?, originator).await?)
+    }
+
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<GetNetworkResult> {
+        from_json(WalletInterface::get_network(self, originator).await?)
+    }
+
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<GetVersionResult> {
+        from_json(WalletInterface::get_version(self, originator).await?)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockWallet;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_typed_results_from_json_wallet() {
+        let wallet: Box<dyn WalletInterface> = Box::new(
+            MockWallet::new()
+                .with_response("getHeight", json!({"height": 100}))
+                .with_response("getNetwork", json!({"network": "main"}))
+                .with_response("isAuthenticated", json!({})),
+        );
+
+        let height = TypedWalletInterface::get_height(wallet.as_ref(), None).await.unwrap();
+        assert_eq!(height.height, 100);
+        let network = TypedWalletInterface::get_network(wallet.as_ref(), None).await.unwrap();
+        assert_eq!(network.network, "main");
+
+        // The mock answers `{}`, which is not a valid typed result
+        let err = TypedWalletInterface::is_authenticated(wallet.as_ref(), None).await.unwrap_err();
+        assert_eq!(err.code, "WERR_JSON");
+    }
+}
@@ -7,15 +7,44 @@
 //!
 //! This manager extends CWIStyleWalletManager and adds authentication method support.
 
+use crate::managers::cwi_style_wallet_manager::{
+    AuthenticationFlow, AuthenticationMode, CWIStyleWalletManager, NewWalletFunder, PasswordRetriever,
+    RecoveryKeySaver, UMPTokenInteractor,
+};
+use crate::managers::simple_wallet_manager::{WalletBuilder, WalletInterface};
 use crate::sdk::errors::{WalletError, WalletResult};
-use crate::wab_client::{WABClient, AuthMethodInteractor, WABClientTrait};
+use crate::wab_client::{AuthMethodInteractor, WABClientTrait};
 use serde::{Deserialize, Serialize};
+use std::ops::Deref;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Presentation key in hex format (64 hex chars for 32 bytes)
 pub type PresentationKeyHex = String;
 
+/// Onboarding step the UI should render
+///
+/// Reported to [`AuthStateCallback`]s whenever the authentication flow advances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthState {
+    /// No authentication in progress; the user should pick a method and start
+    Idle,
+    /// `start_auth` succeeded; waiting for `complete_auth` (e.g. the SMS code)
+    AwaitingCompletion,
+    /// Presentation key retrieved for a new user; they should choose a password
+    AwaitingNewPassword,
+    /// Presentation key retrieved for an existing user; they should enter their password
+    AwaitingPassword,
+    /// Waiting for the user's recovery key
+    AwaitingRecoveryKey,
+    /// The user is authenticated and the wallet is ready
+    Authenticated,
+}
+
+/// Called with the new state whenever the authentication flow advances
+pub type AuthStateCallback = Arc<dyn Fn(AuthState) + Send + Sync>;
+
 /// Wallet Authentication Manager
 ///
 /// Reference: TS WalletAuthenticationManager class (WalletAuthenticationManager.ts lines 13-153)
@@ -33,6 +62,7 @@ pub type PresentationKeyHex = String;
 /// 2. `complete_auth()` - Complete authentication (e.g., verify code)
 /// 3. Presentation key is retrieved from WAB server
 /// 4. Manager provides key to underlying CWI wallet logic
+/// 5. `provide_password()` - New users choose a password, existing users enter theirs
 ///
 /// ## Features
 ///
@@ -40,19 +70,29 @@ pub type PresentationKeyHex = String;
 /// - Temporary presentation key generation
 /// - WAB server integration
 /// - Faucet funding for new wallets
-/// - Extends CWIStyleWalletManager functionality
+/// - State-change callbacks for onboarding UIs
+/// - Extends CWIStyleWalletManager functionality (via `Deref`)
 pub struct WalletAuthenticationManager {
+    /// Underlying CWI-style manager
+    inner: Arc<CWIStyleWalletManager>,
+
     /// WAB client for authentication
-    wab_client: Arc<WABClient>,
-    
+    wab_client: Arc<dyn WABClientTrait>,
+
     /// Currently selected authentication method
     auth_method: Arc<RwLock<Option<Box<dyn AuthMethodInteractor>>>>,
-    
+
     /// Temporary presentation key (used during auth flow)
     temp_presentation_key: Arc<RwLock<Option<String>>>,
-    
+
     /// Admin originator domain
     admin_originator: String,
+
+    /// Current onboarding step
+    state: Arc<RwLock<AuthState>>,
+
+    /// Listeners notified on every state change
+    state_callbacks: Arc<RwLock<Vec<AuthStateCallback>>>,
 }
 
 impl WalletAuthenticationManager {
@@ -60,8 +100,15 @@ impl WalletAuthenticationManager {
     ///
     /// Reference: TS constructor (WalletAuthenticationManager.ts lines 18-83)
     ///
+    /// New wallets are funded from the WAB faucet before their UMP token is
+    /// published.
+    ///
     /// # Arguments
     /// * `admin_originator` - Domain name of administrative originator
+    /// * `wallet_builder` - Builds the underlying wallet from the primary key and privileged manager
+    /// * `ump_token_interactor` - Finds and publishes UMP tokens
+    /// * `recovery_key_saver` - Saves newly generated recovery keys
+    /// * `password_retriever` - Prompts for the password when the privileged key is needed
     /// * `wab_client` - WAB client instance for authentication
     /// * `auth_method` - Optional initial authentication method
     ///
@@ -69,17 +116,45 @@ impl WalletAuthenticationManager {
     /// New WalletAuthenticationManager instance
     pub fn new(
         admin_originator: String,
-        wab_client: Arc<WABClient>,
+        wallet_builder: WalletBuilder,
+        ump_token_interactor: Arc<dyn UMPTokenInteractor>,
+        recovery_key_saver: RecoveryKeySaver,
+        password_retriever: PasswordRetriever,
+        wab_client: Arc<dyn WABClientTrait>,
         auth_method: Option<Box<dyn AuthMethodInteractor>>,
     ) -> Self {
+        // Fund new wallets from the faucet (TS lines 33-73)
+        let faucet_client = wab_client.clone();
+        let funder: NewWalletFunder = Arc::new(move |presentation_key, wallet, admin_originator| {
+            let client = faucet_client.clone();
+            Box::pin(async move {
+                if let Err(e) = fund_new_wallet(client.as_ref(), &presentation_key, wallet.as_ref(), &admin_originator).await {
+                    tracing::error!(error = %e, "funding new wallet failed");
+                }
+                Ok(())
+            })
+        });
+
+        let inner = CWIStyleWalletManager::new(
+            admin_originator.clone(),
+            wallet_builder,
+            ump_token_interactor,
+            recovery_key_saver,
+            password_retriever,
+            Some(funder),
+        );
+
         Self {
+            inner: Arc::new(inner),
             wab_client,
             auth_method: Arc::new(RwLock::new(auth_method)),
             temp_presentation_key: Arc::new(RwLock::new(None)),
             admin_originator,
+            state: Arc::new(RwLock::new(AuthState::Idle)),
+            state_callbacks: Arc::new(RwLock::new(Vec::new())),
         }
     }
-    
+
     /// Set or switch the authentication method
     ///
     /// Reference: TS setAuthMethod() (WalletAuthenticationManager.ts lines 89-91)
@@ -92,7 +167,17 @@ impl WalletAuthenticationManager {
     pub async fn set_auth_method(&self, method: Box<dyn AuthMethodInteractor>) {
         *self.auth_method.write().await = Some(method);
     }
-    
+
+    /// Register a callback invoked on every onboarding state change
+    pub async fn on_state_change(&self, callback: AuthStateCallback) {
+        self.state_callbacks.write().await.push(callback);
+    }
+
+    /// Current onboarding step
+    pub async fn state(&self) -> AuthState {
+        *self.state.read().await
+    }
+
     /// Start the WAB-based authentication flow
     ///
     /// Reference: TS startAuth() (WalletAuthenticationManager.ts lines 97-116)
@@ -108,101 +193,139 @@ impl WalletAuthenticationManager {
     pub async fn start_auth(&self, payload: serde_json::Value) -> WalletResult<()> {
         // Check if auth method is set (TS lines 98-100)
         let auth_method = self.auth_method.read().await;
-        if auth_method.is_none() {
-            return Err(WalletError::invalid_operation(
-                "No AuthMethod selected in WalletAuthenticationManager"
-            ));
-        }
-        
+        let auth_method_ref = auth_method.as_ref().ok_or_else(|| {
+            WalletError::invalid_operation("No AuthMethod selected in WalletAuthenticationManager")
+        })?;
+
         // Generate temporary presentation key (TS line 101)
         let temp_key = self.generate_temporary_presentation_key();
         *self.temp_presentation_key.write().await = Some(temp_key.clone());
-        
+
         // Start auth method via WAB client (TS lines 104-111)
-        let auth_method_ref = auth_method.as_ref().unwrap();
-        let start_result = self.wab_client.as_ref().start_auth_method(
+        let start_result = self.wab_client.start_auth_method(
             auth_method_ref.as_ref(),
             &temp_key,
             payload,
         ).await?;
-        
+
         // Check success (TS lines 113-115)
         if !start_result.success {
             return Err(WalletError::invalid_operation(
-                &start_result.message.unwrap_or_else(|| "Failed to start WAB auth method".to_string())
+                start_result.message.unwrap_or_else(|| "Failed to start WAB auth method".to_string())
             ));
         }
-        
+
+        self.set_state(AuthState::AwaitingCompletion).await;
         Ok(())
     }
-    
+
     /// Complete the WAB-based authentication flow
     ///
     /// Reference: TS completeAuth() (WalletAuthenticationManager.ts lines 121-146)
     ///
-    /// Completes the authentication process and retrieves the final presentation key
-    /// from the WAB server if successful.
+    /// Completes the authentication process, retrieves the final presentation key
+    /// from the WAB server and provides it to the CWI-style manager. The state then
+    /// says whether the user must choose a new password or enter their existing one.
     ///
     /// # Arguments
     /// * `payload` - Completion payload (e.g., `{"code": "123456"}` for SMS verification)
     ///
-    /// # Returns
-    /// The presentation key bytes (32 bytes) retrieved from WAB
-    ///
     /// # Errors
     /// Returns error if auth method not set, startAuth not called, or verification fails
-    pub async fn complete_auth(&self, payload: serde_json::Value) -> WalletResult<Vec<u8>> {
+    pub async fn complete_auth(&self, payload: serde_json::Value) -> WalletResult<()> {
         // Check auth method and temp key (TS lines 122-124)
         let auth_method = self.auth_method.read().await;
-        if auth_method.is_none() {
-            return Err(WalletError::invalid_operation(
+        let auth_method_ref = auth_method.as_ref().ok_or_else(|| {
+            WalletError::invalid_operation(
                 "No AuthMethod selected in WalletAuthenticationManager or startAuth has yet to be called."
-            ));
-        }
-        
-        let temp_key = {
-            let mut temp_key_guard = self.temp_presentation_key.write().await;
-            let key = temp_key_guard.clone();
-            *temp_key_guard = None; // Unset for security (TS lines 127-128)
-            key
-        };
-        
-        let temp_key = temp_key.ok_or_else(|| {
+            )
+        })?;
+
+        // Unset for security (TS lines 127-128)
+        let temp_key = self.temp_presentation_key.write().await.take().ok_or_else(|| {
             WalletError::invalid_operation(
                 "startAuth must be called before completeAuth"
             )
         })?;
-        
+
         // Complete auth method via WAB client (TS line 130)
-        let auth_method_ref = auth_method.as_ref().unwrap();
-        let result = self.wab_client.as_ref().complete_auth_method(
+        let result = self.wab_client.complete_auth_method(
             auth_method_ref.as_ref(),
             &temp_key,
             payload,
         ).await?;
-        
+
         // Check success and extract presentation key (TS lines 132-134)
-        if !result.success || result.presentation_key.is_none() {
-            return Err(WalletError::invalid_operation(
-                &result.message.unwrap_or_else(|| "Failed to complete WAB auth".to_string())
-            ));
-        }
-        
+        let presentation_key_hex = match (result.success, result.presentation_key) {
+            (true, Some(key)) => key,
+            _ => return Err(WalletError::invalid_operation(
+                result.message.unwrap_or_else(|| "Failed to complete WAB auth".to_string())
+            )),
+        };
+
         // Convert hex presentation key to bytes (TS lines 137-138)
-        let presentation_key_hex = result.presentation_key.unwrap();
         let presentation_key_bytes = hex::decode(&presentation_key_hex)
-            .map_err(|e| WalletError::invalid_operation(&format!("Invalid presentation key hex: {}", e)))?;
-        
+            .map_err(|e| WalletError::invalid_operation(format!("Invalid presentation key hex: {}", e)))?;
+
         // Validate key length
         if presentation_key_bytes.len() != 32 {
             return Err(WalletError::invalid_operation(
                 "Presentation key must be exactly 32 bytes"
             ));
         }
-        
-        Ok(presentation_key_bytes)
+
+        // Hand the key to the CWI-style manager (TS line 139)
+        self.inner.provide_presentation_key(presentation_key_bytes).await?;
+
+        let next = if self.inner.authentication_mode().await == AuthenticationMode::PresentationKeyAndRecoveryKey {
+            AuthState::AwaitingRecoveryKey
+        } else if self.inner.authentication_flow().await == AuthenticationFlow::NewUser {
+            AuthState::AwaitingNewPassword
+        } else {
+            AuthState::AwaitingPassword
+        };
+        self.set_state(next).await;
+        Ok(())
+    }
+
+    /// Provide the password, completing authentication where possible
+    ///
+    /// Reference: TS CWIStyleWalletManager.providePassword
+    pub async fn provide_password(&self, password: &str) -> WalletResult<()> {
+        self.inner.provide_password(password).await?;
+        self.sync_authenticated_state().await;
+        Ok(())
+    }
+
+    /// Provide the recovery key, completing authentication where possible
+    ///
+    /// Reference: TS CWIStyleWalletManager.provideRecoveryKey
+    pub async fn provide_recovery_key(&self, recovery_key: Vec<u8>) -> WalletResult<()> {
+        self.inner.provide_recovery_key(recovery_key).await?;
+        if !self.sync_authenticated_state().await {
+            self.set_state(AuthState::AwaitingPassword).await;
+        }
+        Ok(())
+    }
+
+    /// Restore authentication from a snapshot
+    ///
+    /// Reference: TS CWIStyleWalletManager.loadSnapshot
+    pub async fn load_snapshot(&self, snapshot: Vec<u8>) -> WalletResult<()> {
+        self.inner.load_snapshot(snapshot).await?;
+        self.sync_authenticated_state().await;
+        Ok(())
     }
-    
+
+    /// Destroy the underlying wallet and return to the first onboarding step
+    ///
+    /// Reference: TS CWIStyleWalletManager.destroy
+    pub async fn destroy(&self) {
+        self.inner.destroy().await;
+        *self.temp_presentation_key.write().await = None;
+        self.set_state(AuthState::Idle).await;
+    }
+
     /// Generate a temporary presentation key for the auth flow
     ///
     /// Reference: TS generateTemporaryPresentationKey() (WalletAuthenticationManager.ts lines 148-152)
@@ -214,24 +337,84 @@ impl WalletAuthenticationManager {
     /// 64-character hex string representing 32 random bytes
     fn generate_temporary_presentation_key(&self) -> String {
         use rand::Rng;
-        
+
         // Generate 32 random bytes (TS line 150)
         let mut rng = rand::thread_rng();
         let random_bytes: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-        
+
         // Convert to hex (TS line 151)
         hex::encode(random_bytes)
     }
-    
+
+    /// Move to `Authenticated` if the CWI-style manager now is; returns whether it did
+    async fn sync_authenticated_state(&self) -> bool {
+        let authenticated = self.inner.is_authenticated(None).await.is_ok();
+        if authenticated {
+            self.set_state(AuthState::Authenticated).await;
+        }
+        authenticated
+    }
+
+    async fn set_state(&self, state: AuthState) {
+        *self.state.write().await = state;
+        for callback in self.state_callbacks.read().await.iter() {
+            callback(state);
+        }
+    }
+
     /// Get the admin originator domain
     pub fn admin_originator(&self) -> &str {
         &self.admin_originator
     }
-    
+
     /// Get the WAB client
-    pub fn wab_client(&self) -> &Arc<WABClient> {
+    pub fn wab_client(&self) -> &Arc<dyn WABClientTrait> {
         &self.wab_client
     }
+
+    /// The CWI-style manager, usable as a `WalletInterface` once authenticated
+    pub fn wallet_manager(&self) -> Arc<CWIStyleWalletManager> {
+        self.inner.clone()
+    }
+}
+
+impl Deref for WalletAuthenticationManager {
+    type Target = CWIStyleWalletManager;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Request faucet funds for a new wallet and internalize the payment
+///
+/// Reference: TS WalletAuthenticationManager newWalletFunder (lines 33-73)
+async fn fund_new_wallet(
+    wab_client: &dyn WABClientTrait,
+    presentation_key: &[u8],
+    wallet: &dyn WalletInterface,
+    admin_originator: &str,
+) -> WalletResult<()> {
+    let faucet = wab_client.request_faucet(&hex::encode(presentation_key)).await?;
+    if !faucet.success || faucet.payment_data.is_null() {
+        return Ok(());
+    }
+
+    let payment = &faucet.payment_data;
+    wallet.internalize_action(serde_json::json!({
+        "tx": payment["tx"],
+        "outputs": [{
+            "outputIndex": 0,
+            "protocol": "wallet payment",
+            "paymentRemittance": {
+                "derivationPrefix": payment["derivationPrefix"],
+                "derivationSuffix": payment["derivationSuffix"],
+                "senderIdentityKey": payment["senderIdentityKey"],
+            },
+        }],
+        "description": "Fund wallet for new user",
+    }), Some(admin_originator)).await?;
+    Ok(())
 }
 
 // ============================================================================
@@ -241,136 +424,198 @@ impl WalletAuthenticationManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::managers::cwi_style_wallet_manager::tests::Harness;
+    use crate::wab_client::{AuthCompleteResult, AuthStartResult, FaucetResult, WABClient};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
     /// Mock WAB client for testing
-    struct MockWABClient;
-    
+    #[derive(Default)]
+    struct MockWABClient {
+        faucet_requests: AtomicUsize,
+    }
+
     #[async_trait::async_trait]
-    impl crate::wab_client::WABClientTrait for MockWABClient {
+    impl WABClientTrait for MockWABClient {
         async fn start_auth_method(
             &self,
             _method: &dyn AuthMethodInteractor,
             _presentation_key: &str,
             _payload: serde_json::Value,
-        ) -> WalletResult<crate::wab_client::AuthStartResult> {
-            Ok(crate::wab_client::AuthStartResult {
+        ) -> WalletResult<AuthStartResult> {
+            Ok(AuthStartResult {
                 success: true,
                 message: Some("Started".to_string()),
             })
         }
-        
+
         async fn complete_auth_method(
             &self,
             _method: &dyn AuthMethodInteractor,
             _temp_key: &str,
-            _payload: serde_json::Value,
-        ) -> WalletResult<crate::wab_client::AuthCompleteResult> {
-            Ok(crate::wab_client::AuthCompleteResult {
-                success: true,
-                presentation_key: Some("a".repeat(64)), // 32 bytes in hex
-                message: Some("Completed".to_string()),
+            payload: serde_json::Value,
+        ) -> WalletResult<AuthCompleteResult> {
+            let success = payload["code"] == "123456";
+            Ok(AuthCompleteResult {
+                success,
+                presentation_key: success.then(|| "a".repeat(64)), // 32 bytes in hex
+                message: Some(if success { "Completed" } else { "Invalid code" }.to_string()),
             })
         }
-        
-        async fn request_faucet(&self, _presentation_key: &str) -> WalletResult<crate::wab_client::FaucetResult> {
-            Ok(crate::wab_client::FaucetResult {
-                payment_data: serde_json::json!({}),
+
+        async fn request_faucet(&self, _presentation_key: &str) -> WalletResult<FaucetResult> {
+            self.faucet_requests.fetch_add(1, Ordering::SeqCst);
+            Ok(FaucetResult {
+                success: true,
+                payment_data: serde_json::json!({ "tx": [1, 2, 3] }),
+                message: None,
             })
         }
     }
-    
+
     /// Mock auth method for testing
     struct MockAuthMethod;
-    
+
     impl AuthMethodInteractor for MockAuthMethod {
         fn method_name(&self) -> &str {
             "mock"
         }
     }
-    
-    #[test]
-    fn test_generate_temporary_presentation_key() {
-        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
-        let manager = WalletAuthenticationManager::new(
+
+    fn manager_with(
+        harness: &Harness,
+        wab_client: Arc<dyn WABClientTrait>,
+        auth_method: Option<Box<dyn AuthMethodInteractor>>,
+    ) -> WalletAuthenticationManager {
+        let (builder, saver, retriever) = harness.callbacks();
+        WalletAuthenticationManager::new(
             "test.admin".to_string(),
+            builder,
+            harness.interactor.clone(),
+            saver,
+            retriever,
             wab_client,
-            None,
-        );
-        
+            auth_method,
+        )
+    }
+
+    fn manager(auth_method: Option<Box<dyn AuthMethodInteractor>>) -> WalletAuthenticationManager {
+        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
+        manager_with(&Harness::new("hunter22"), wab_client, auth_method)
+    }
+
+    #[test]
+    fn test_generate_temporary_presentation_key() {
+        let manager = manager(None);
+
         let key1 = manager.generate_temporary_presentation_key();
         let key2 = manager.generate_temporary_presentation_key();
-        
+
         // Keys should be 64 hex characters (32 bytes)
         assert_eq!(key1.len(), 64);
         assert_eq!(key2.len(), 64);
-        
+
         // Keys should be different (random)
         assert_ne!(key1, key2);
-        
+
         // Should be valid hex
         assert!(hex::decode(&key1).is_ok());
         assert!(hex::decode(&key2).is_ok());
     }
-    
+
     #[tokio::test]
     async fn test_set_auth_method() {
-        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
-        let manager = WalletAuthenticationManager::new(
-            "test.admin".to_string(),
-            wab_client,
-            None,
-        );
-        
+        let manager = manager(None);
+
         // Initially no auth method
         assert!(manager.auth_method.read().await.is_none());
-        
+
         // Set auth method
         let auth_method = Box::new(MockAuthMethod);
         manager.set_auth_method(auth_method).await;
-        
+
         // Should now have auth method
         assert!(manager.auth_method.read().await.is_some());
     }
-    
+
     #[tokio::test]
     async fn test_start_auth_without_method() {
-        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
-        let manager = WalletAuthenticationManager::new(
-            "test.admin".to_string(),
-            wab_client,
-            None,
-        );
-        
+        let manager = manager(None);
+
         // Try to start auth without setting method
         let result = manager.start_auth(serde_json::json!({"test": "data"})).await;
-        
+
         assert!(result.is_err());
+        assert_eq!(manager.state().await, AuthState::Idle);
     }
-    
+
     #[tokio::test]
     async fn test_complete_auth_without_start() {
-        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
-        let manager = WalletAuthenticationManager::new(
-            "test.admin".to_string(),
-            wab_client,
-            Some(Box::new(MockAuthMethod)),
-        );
-        
+        let manager = manager(Some(Box::new(MockAuthMethod)));
+
         // Try to complete auth without starting
         let result = manager.complete_auth(serde_json::json!({"code": "123456"})).await;
-        
+
         assert!(result.is_err());
     }
-    
+
+    #[tokio::test]
+    async fn test_new_user_onboarding_flow() {
+        let harness = Harness::new("hunter22");
+        let wab_client = Arc::new(MockWABClient::default());
+        let manager = manager_with(&harness, wab_client.clone(), Some(Box::new(MockAuthMethod)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        manager.on_state_change(Arc::new(move |state| recorder.lock().unwrap().push(state))).await;
+
+        manager.start_auth(serde_json::json!({"phoneNumber": "+15555550100"})).await.unwrap();
+        assert!(manager.complete_auth(serde_json::json!({"code": "000000"})).await.is_err());
+
+        manager.start_auth(serde_json::json!({"phoneNumber": "+15555550100"})).await.unwrap();
+        manager.complete_auth(serde_json::json!({"code": "123456"})).await.unwrap();
+        assert_eq!(manager.state().await, AuthState::AwaitingNewPassword);
+
+        manager.provide_password("hunter22").await.unwrap();
+        assert!(manager.wallet_manager().is_authenticated(None).await.unwrap());
+        assert_eq!(wab_client.faucet_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(harness.interactor.tokens.lock().unwrap().len(), 1);
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            AuthState::AwaitingCompletion,
+            AuthState::AwaitingCompletion,
+            AuthState::AwaitingNewPassword,
+            AuthState::Authenticated,
+        ]);
+
+        manager.destroy().await;
+        assert_eq!(manager.state().await, AuthState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_existing_user_flow() {
+        let harness = Harness::new("hunter22");
+        let wab_client = Arc::new(MockWABClient::default());
+        let first = manager_with(&harness, wab_client.clone(), Some(Box::new(MockAuthMethod)));
+        first.start_auth(serde_json::json!({})).await.unwrap();
+        first.complete_auth(serde_json::json!({"code": "123456"})).await.unwrap();
+        first.provide_password("hunter22").await.unwrap();
+
+        let manager = manager_with(&harness, wab_client.clone(), Some(Box::new(MockAuthMethod)));
+        manager.start_auth(serde_json::json!({})).await.unwrap();
+        manager.complete_auth(serde_json::json!({"code": "123456"})).await.unwrap();
+        assert_eq!(manager.state().await, AuthState::AwaitingPassword);
+        manager.provide_password("hunter22").await.unwrap();
+        assert_eq!(manager.state().await, AuthState::Authenticated);
+
+        // Only the new user was funded
+        assert_eq!(wab_client.faucet_requests.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_admin_originator_getter() {
-        let wab_client = Arc::new(WABClient::new("https://test.wab".to_string()));
-        let manager = WalletAuthenticationManager::new(
-            "test.admin".to_string(),
-            wab_client,
-            None,
-        );
-        
+        let manager = manager(None);
+
         assert_eq!(manager.admin_originator(), "test.admin");
     }
 }
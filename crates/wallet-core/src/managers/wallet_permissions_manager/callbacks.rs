@@ -116,11 +116,53 @@ pub fn build_request_key(request: &PermissionRequest) -> String {
     }
 }
 
+/// Build the request key a permission token satisfies
+///
+/// The token's type follows from the fields it carries. Used to drop the
+/// cache entry for a token that is revoked or renewed.
+pub fn token_request_key(token: &PermissionToken) -> String {
+    let permission_type = if token.protocol.is_some() {
+        PermissionType::Protocol
+    } else if token.basket_name.is_some() {
+        PermissionType::Basket
+    } else if token.cert_type.is_some() {
+        PermissionType::Certificate
+    } else {
+        PermissionType::Spending
+    };
+    let protocol_id = token.protocol.as_ref().map(|name| {
+        let level = token.security_level.map(|level| level as u8).unwrap_or(0);
+        vec![level.to_string(), name.clone()]
+    });
+    let certificate = token.cert_type.as_ref().map(|cert_type| CertificateDetails {
+        verifier: token.verifier.clone().unwrap_or_default(),
+        cert_type: cert_type.clone(),
+        fields: token.cert_fields.clone().unwrap_or_default(),
+    });
+    
+    build_request_key(&PermissionRequest {
+        permission_type,
+        originator: token.originator.clone(),
+        privileged: token.privileged,
+        protocol_id,
+        counterparty: token.counterparty.clone(),
+        basket: token.basket_name.clone(),
+        certificate,
+        spending: None,
+        reason: None,
+        renewal: None,
+        previous_token: None,
+    })
+}
+
 /// Cached permission entry  
 ///
 /// Reference: TS permissionCache (WalletPermissionsManager.ts line 407)
 #[derive(Debug, Clone)]
 pub struct CachedPermission {
+    /// Originator the permission was granted to
+    pub originator: String,
+    
     /// When this permission expires
     pub expiry: i64,
     
@@ -171,10 +213,12 @@ pub fn is_permission_cached(
 ///
 /// * `cache` - The permission cache
 /// * `key` - The request key
+/// * `originator` - Originator the permission was granted to
 /// * `expiry` - When this permission expires
 pub fn cache_permission(
     cache: &mut std::collections::HashMap<String, CachedPermission>,
     key: String,
+    originator: &str,
     expiry: i64,
 ) {
     let now = std::time::SystemTime::now()
@@ -183,6 +227,7 @@ pub fn cache_permission(
         .as_millis() as i64;
     
     cache.insert(key, CachedPermission {
+        originator: originator.to_string(),
         expiry,
         cached_at: now,
     });
@@ -309,7 +354,7 @@ mod tests {
             .unwrap()
             .as_secs() as i64) + 86400; // 1 day from now
         
-        cache_permission(&mut cache, key.clone(), future_expiry);
+        cache_permission(&mut cache, key.clone(), "example.com", future_expiry);
         
         // Should be cached now
         assert!(is_permission_cached(&cache, &key, 5 * 60 * 1000));
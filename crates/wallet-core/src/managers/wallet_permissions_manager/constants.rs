@@ -37,6 +37,11 @@ pub fn get_admin_basket_name(permission_type: PermissionType) -> &'static str {
 /// Reference: Based on typical permission token expiration patterns
 pub const DEFAULT_TOKEN_EXPIRY_SECONDS: i64 = 30 * 24 * 60 * 60; // 30 days
 
+/// Default time a confirmed permission stays cached (5 minutes in milliseconds)
+///
+/// Reference: TS CACHE_TTL_MS (WalletPermissionsManager.ts line 410)
+pub const DEFAULT_PERMISSION_CACHE_TTL_MS: i64 = 5 * 60 * 1000;
+
 /// Minimum satoshis for a permission token output
 ///
 /// Reference: Based on dust limit and typical permission token values
@@ -0,0 +1,547 @@
+//! WalletInterface Interception
+//!
+//! **Reference**: TypeScript `src/WalletPermissionsManager.ts` lines 2000-2700 (proxied methods)
+//!
+//! Implements `WalletInterface` for the manager so it can stand in for the
+//! wallet it wraps. Each method checks the permission the call needs
+//! (protocol, basket, certificate or label) for the originator, prompting
+//! through the callbacks when no token exists, then forwards the call to the
+//! underlying wallet. Action and output metadata is encrypted on the way in
+//! (when `encrypt_wallet_metadata` is set) and decrypted on the way out.
+
+use super::*;
+use crate::methods::fee_model::StorageFeeModel;
+use serde_json::Value;
+
+/// The normalized originator of a call that needs a permission check
+///
+/// Permissions are granted per originator, so anonymous calls are refused
+/// and each spelling of one originator shares its permissions.
+fn require_originator(originator: Option<&str>) -> WalletResult<Originator> {
+    Originator::parse(originator.ok_or_else(|| WalletError::invalid_parameter("originator", "provided"))?)
+}
+
+/// String field of the args, if present
+fn str_arg(args: &Value, field: &str) -> Option<String> {
+    args.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+/// `privileged` flag of the args, defaulting to false
+fn privileged_arg(args: &Value) -> bool {
+    args.get("privileged").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// `counterparty` of the args, defaulting to `self`
+fn counterparty_arg(args: &Value) -> String {
+    str_arg(args, "counterparty").unwrap_or_else(|| "self".to_string())
+}
+
+/// `protocolID` of the args as `[securityLevel, protocolName]` strings
+///
+/// The wire form is `[2, "name"]`; the security level may also arrive as a string.
+fn protocol_id_arg(args: &Value) -> WalletResult<Vec<String>> {
+    let invalid = || WalletError::invalid_parameter("protocolID", "[securityLevel, protocolName]");
+    let parts = args.get("protocolID").and_then(Value::as_array).ok_or_else(invalid)?;
+    if parts.len() != 2 {
+        return Err(invalid());
+    }
+    let level = match &parts[0] {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return Err(invalid()),
+    };
+    let name = parts[1].as_str().ok_or_else(invalid)?;
+    Ok(vec![level, name.to_string()])
+}
+
+/// String array field of the args, empty when absent
+fn strings_arg(args: &Value, field: &str) -> WalletResult<Vec<String>> {
+    match args.get(field) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|_| WalletError::invalid_parameter(field, "an array of strings")),
+    }
+}
+
+impl WalletPermissionsManager {
+    /// Check a protocol permission for a key-based operation on `protocolID`
+    ///
+    /// Reference: TS encrypt/decrypt/createHmac/verifyHmac/createSignature/verifySignature
+    async fn ensure_protocol_use(
+        &self,
+        args: &Value,
+        originator: &str,
+        usage_type: ProtocolUsageType,
+    ) -> WalletResult<()> {
+        self.ensure_protocol_permission(EnsureProtocolPermissionParams {
+            originator: originator.to_string(),
+            privileged: privileged_arg(args),
+            protocol_id: protocol_id_arg(args)?,
+            counterparty: counterparty_arg(args),
+            reason: str_arg(args, "privilegedReason"),
+            seek_permission: true,
+            usage_type,
+        }).await?;
+        Ok(())
+    }
+
+    /// Check a permission modelled as a `self` protocol, such as
+    /// `[1, "identity resolution"]`
+    async fn ensure_special_protocol(
+        &self,
+        originator: &str,
+        privileged: bool,
+        protocol_id: Vec<String>,
+        reason: Option<String>,
+        usage_type: ProtocolUsageType,
+    ) -> WalletResult<()> {
+        self.ensure_protocol_permission(EnsureProtocolPermissionParams {
+            originator: originator.to_string(),
+            privileged,
+            protocol_id,
+            counterparty: "self".to_string(),
+            reason,
+            seek_permission: true,
+            usage_type,
+        }).await?;
+        Ok(())
+    }
+
+    /// Check basket access for `basket`
+    async fn ensure_basket_use(
+        &self,
+        originator: &str,
+        basket: &str,
+        reason: Option<String>,
+        usage_type: BasketUsageType,
+    ) -> WalletResult<()> {
+        self.ensure_basket_access(EnsureBasketAccessParams {
+            originator: originator.to_string(),
+            basket: basket.to_string(),
+            reason,
+            seek_permission: true,
+            usage_type,
+        }).await?;
+        Ok(())
+    }
+
+    /// Check label access for each of `labels`
+    async fn ensure_labels_use(
+        &self,
+        originator: &str,
+        labels: &[String],
+        reason: Option<String>,
+        usage_type: LabelUsageType,
+    ) -> WalletResult<()> {
+        for label in labels {
+            self.ensure_label_access(EnsureLabelAccessParams {
+                originator: originator.to_string(),
+                label: label.clone(),
+                reason: reason.clone(),
+                seek_permission: true,
+                usage_type,
+            }).await?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt `object[field]` in place when it is a string
+    async fn encrypt_metadata_field(&self, object: &mut Value, field: &str) -> WalletResult<()> {
+        if let Some(plaintext) = object.get(field).and_then(Value::as_str) {
+            let ciphertext = encrypt_metadata(self.underlying.as_ref(), &self.admin_originator, plaintext).await?;
+            object[field] = Value::String(ciphertext);
+        }
+        Ok(())
+    }
+
+    /// Decrypt `object[field]` in place when it is a string
+    async fn decrypt_metadata_field(&self, object: &mut Value, field: &str) {
+        if let Some(ciphertext) = object.get(field).and_then(Value::as_str) {
+            let plaintext = decrypt_metadata(self.underlying.as_ref(), &self.admin_originator, ciphertext).await;
+            object[field] = Value::String(plaintext);
+        }
+    }
+
+    /// Encrypt the description, input and output descriptions and custom
+    /// instructions of createAction args
+    ///
+    /// Reference: TS createAction metadata encryption (WalletPermissionsManager.ts)
+    async fn encrypt_action_metadata(&self, args: &mut Value) -> WalletResult<()> {
+        self.encrypt_metadata_field(args, "description").await?;
+        for input in args.get_mut("inputs").and_then(Value::as_array_mut).into_iter().flatten() {
+            self.encrypt_metadata_field(input, "inputDescription").await?;
+        }
+        for output in args.get_mut("outputs").and_then(Value::as_array_mut).into_iter().flatten() {
+            self.encrypt_metadata_field(output, "outputDescription").await?;
+            self.encrypt_metadata_field(output, "customInstructions").await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl WalletInterface for WalletPermissionsManager {
+    /// Create an action on behalf of the originator
+    ///
+    /// Reference: TS createAction (WalletPermissionsManager.ts)
+    ///
+    /// Checks insertion access to each output basket, access to each label
+    /// the app applies and, when the action spends, the app's monthly
+    /// spending authorization for the amount worked out from the args. Then
+    /// adds the `admin originator` and `admin month` labels so the admin can
+    /// list the app's actions and total its monthly spending.
+    async fn create_action(&self, mut args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        let reason = str_arg(&args, "description");
+
+        if let Some(outputs) = args.get("outputs").and_then(Value::as_array) {
+            for output in outputs {
+                if let Some(basket) = output.get("basket").and_then(Value::as_str) {
+                    self.ensure_basket_use(originator, basket, reason.clone(), BasketUsageType::Insertion).await?;
+                }
+            }
+        }
+
+        let mut labels = strings_arg(&args, "labels")?;
+        self.ensure_labels_use(originator, &labels, reason.clone(), LabelUsageType::Apply).await?;
+
+        if self.config.seek_spending_permissions && !self.is_admin_originator(originator) {
+            let line_items = create_action_line_items(&args, &StorageFeeModel::default())?;
+            let satoshis = line_items_net_spend(&line_items);
+            if satoshis > 0 {
+                self.ensure_spending_authorization(EnsureSpendingAuthorizationParams {
+                    originator: originator.to_string(),
+                    satoshis,
+                    line_items: Some(line_items),
+                    reason,
+                    seek_permission: true,
+                }).await?;
+            }
+        }
+
+        labels.extend(originator_action_labels(originator));
+        args["labels"] = serde_json::json!(labels);
+
+        if self.config.encrypt_wallet_metadata {
+            self.encrypt_action_metadata(&mut args).await?;
+        }
+
+        self.underlying.create_action(args, Some(originator)).await
+    }
+
+    /// Reference: TS signAction (forwarded unchanged)
+    async fn sign_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.sign_action(args, originator).await
+    }
+
+    /// Reference: TS abortAction (forwarded unchanged)
+    async fn abort_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.abort_action(args, originator).await
+    }
+
+    /// List actions, checking access to each label listed by and
+    /// decrypting the actions' metadata
+    ///
+    /// Reference: TS listActions (WalletPermissionsManager.ts)
+    async fn list_actions(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        let labels = strings_arg(&args, "labels")?;
+        self.ensure_labels_use(originator, &labels, None, LabelUsageType::List).await?;
+
+        let mut result = self.underlying.list_actions(args, Some(originator)).await?;
+        for action in result.get_mut("actions").and_then(Value::as_array_mut).into_iter().flatten() {
+            self.decrypt_metadata_field(action, "description").await;
+            for input in action.get_mut("inputs").and_then(Value::as_array_mut).into_iter().flatten() {
+                self.decrypt_metadata_field(input, "inputDescription").await;
+            }
+            for output in action.get_mut("outputs").and_then(Value::as_array_mut).into_iter().flatten() {
+                self.decrypt_metadata_field(output, "outputDescription").await;
+                self.decrypt_metadata_field(output, "customInstructions").await;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Internalize an action, checking insertion access to each basket
+    /// outputs are inserted into
+    ///
+    /// Reference: TS internalizeAction (WalletPermissionsManager.ts)
+    async fn internalize_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        let reason = str_arg(&args, "description");
+        if let Some(outputs) = args.get("outputs").and_then(Value::as_array) {
+            for output in outputs {
+                if output.get("protocol").and_then(Value::as_str) != Some("basket insertion") {
+                    continue;
+                }
+                if let Some(basket) = output["insertionRemittance"].get("basket").and_then(Value::as_str) {
+                    self.ensure_basket_use(originator, basket, reason.clone(), BasketUsageType::Insertion).await?;
+                }
+            }
+        }
+        self.underlying.internalize_action(args, Some(originator)).await
+    }
+
+    /// List outputs, checking basket listing access and decrypting the
+    /// outputs' custom instructions
+    ///
+    /// Reference: TS listOutputs (WalletPermissionsManager.ts)
+    async fn list_outputs(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if let Some(basket) = args.get("basket").and_then(Value::as_str) {
+            self.ensure_basket_use(originator, basket, None, BasketUsageType::Listing).await?;
+        }
+        let mut result = self.underlying.list_outputs(args, Some(originator)).await?;
+        for output in result.get_mut("outputs").and_then(Value::as_array_mut).into_iter().flatten() {
+            self.decrypt_metadata_field(output, "customInstructions").await;
+        }
+        Ok(result)
+    }
+
+    /// Reference: TS relinquishOutput (basket removal access)
+    async fn relinquish_output(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if let Some(basket) = args.get("basket").and_then(Value::as_str) {
+            self.ensure_basket_use(originator, basket, None, BasketUsageType::Removal).await?;
+        }
+        self.underlying.relinquish_output(args, Some(originator)).await
+    }
+
+    /// Get a public key, checking the protocol permission for derived keys
+    /// or identity key revelation for the identity key
+    ///
+    /// Reference: TS getPublicKey (WalletPermissionsManager.ts)
+    async fn get_public_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if args.get("protocolID").is_some_and(|p| !p.is_null()) {
+            self.ensure_protocol_use(&args, originator, ProtocolUsageType::PublicKey).await?;
+        } else if args.get("identityKey").and_then(Value::as_bool).unwrap_or(false) {
+            self.ensure_special_protocol(
+                originator,
+                privileged_arg(&args),
+                vec!["1".to_string(), "identity key retrieval".to_string()],
+                str_arg(&args, "privilegedReason"),
+                ProtocolUsageType::IdentityKey,
+            ).await?;
+        }
+        self.underlying.get_public_key(args, Some(originator)).await
+    }
+
+    /// Reference: TS revealCounterpartyKeyLinkage (WalletPermissionsManager.ts)
+    async fn reveal_counterparty_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        let counterparty = str_arg(&args, "counterparty").unwrap_or_default();
+        self.ensure_special_protocol(
+            originator,
+            privileged_arg(&args),
+            vec!["2".to_string(), format!("counterparty key linkage revelation {}", counterparty)],
+            str_arg(&args, "privilegedReason"),
+            ProtocolUsageType::LinkageRevelation,
+        ).await?;
+        self.underlying.reveal_counterparty_key_linkage(args, Some(originator)).await
+    }
+
+    /// Reference: TS revealSpecificKeyLinkage (WalletPermissionsManager.ts)
+    async fn reveal_specific_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        let protocol_id = protocol_id_arg(&args)?;
+        self.ensure_special_protocol(
+            originator,
+            privileged_arg(&args),
+            vec!["2".to_string(), format!("specific key linkage revelation {} {}", protocol_id[0], protocol_id[1])],
+            str_arg(&args, "privilegedReason"),
+            ProtocolUsageType::LinkageRevelation,
+        ).await?;
+        self.underlying.reveal_specific_key_linkage(args, Some(originator)).await
+    }
+
+    /// Reference: TS encrypt (protocol permission, encrypting)
+    async fn encrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Encrypting).await?;
+        self.underlying.encrypt(args, Some(originator)).await
+    }
+
+    /// Reference: TS decrypt (protocol permission, encrypting)
+    async fn decrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Encrypting).await?;
+        self.underlying.decrypt(args, Some(originator)).await
+    }
+
+    /// Reference: TS createHmac (protocol permission, hmac)
+    async fn create_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Hmac).await?;
+        self.underlying.create_hmac(args, Some(originator)).await
+    }
+
+    /// Reference: TS verifyHmac (protocol permission, hmac)
+    async fn verify_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Hmac).await?;
+        self.underlying.verify_hmac(args, Some(originator)).await
+    }
+
+    /// Reference: TS createSignature (protocol permission, signing)
+    async fn create_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Signing).await?;
+        self.underlying.create_signature(args, Some(originator)).await
+    }
+
+    /// Reference: TS verifySignature (protocol permission, signing)
+    async fn verify_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_protocol_use(&args, originator, ProtocolUsageType::Signing).await?;
+        self.underlying.verify_signature(args, Some(originator)).await
+    }
+
+    /// Reference: TS acquireCertificate (`[1, "certificate acquisition <type>"]`)
+    async fn acquire_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if self.config.seek_certificate_acquisition_permissions
+            && self.config.seek_certificate_permissions_for_certificate_ops
+        {
+            let cert_type = str_arg(&args, "type").unwrap_or_default();
+            self.ensure_special_protocol(
+                originator,
+                privileged_arg(&args),
+                vec!["1".to_string(), format!("certificate acquisition {}", cert_type)],
+                str_arg(&args, "privilegedReason"),
+                ProtocolUsageType::Generic,
+            ).await?;
+        }
+        self.underlying.acquire_certificate(args, Some(originator)).await
+    }
+
+    /// Reference: TS listCertificates (`[1, "certificate list"]`)
+    async fn list_certificates(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if self.config.seek_certificate_listing_permissions
+            && self.config.seek_certificate_permissions_for_certificate_ops
+        {
+            self.ensure_special_protocol(
+                originator,
+                privileged_arg(&args),
+                vec!["1".to_string(), "certificate list".to_string()],
+                str_arg(&args, "privilegedReason"),
+                ProtocolUsageType::Generic,
+            ).await?;
+        }
+        self.underlying.list_certificates(args, Some(originator)).await
+    }
+
+    /// Prove a certificate, checking access to disclose the requested fields
+    /// to the verifier
+    ///
+    /// Reference: TS proveCertificate (WalletPermissionsManager.ts)
+    async fn prove_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        self.ensure_certificate_access(EnsureCertificateAccessParams {
+            originator: originator.to_string(),
+            privileged: privileged_arg(&args),
+            verifier: str_arg(&args, "verifier").unwrap_or_default(),
+            cert_type: str_arg(&args["certificate"], "type").unwrap_or_default(),
+            fields: strings_arg(&args, "fieldsToReveal")?,
+            reason: str_arg(&args, "privilegedReason"),
+            seek_permission: true,
+            usage_type: CertificateUsageType::Disclosure,
+        }).await?;
+        self.underlying.prove_certificate(args, Some(originator)).await
+    }
+
+    /// Reference: TS relinquishCertificate (`[1, "certificate relinquishment <type>"]`)
+    async fn relinquish_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if self.config.seek_certificate_relinquishment_permissions
+            && self.config.seek_certificate_permissions_for_certificate_ops
+        {
+            let cert_type = str_arg(&args, "type").unwrap_or_default();
+            self.ensure_special_protocol(
+                originator,
+                false,
+                vec!["1".to_string(), format!("certificate relinquishment {}", cert_type)],
+                None,
+                ProtocolUsageType::Generic,
+            ).await?;
+        }
+        self.underlying.relinquish_certificate(args, Some(originator)).await
+    }
+
+    /// Reference: TS discoverByIdentityKey (`[1, "identity resolution"]`)
+    async fn discover_by_identity_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if self.config.seek_permissions_for_identity_resolution {
+            self.ensure_special_protocol(
+                originator,
+                false,
+                vec!["1".to_string(), "identity resolution".to_string()],
+                None,
+                ProtocolUsageType::Generic,
+            ).await?;
+        }
+        self.underlying.discover_by_identity_key(args, Some(originator)).await
+    }
+
+    /// Reference: TS discoverByAttributes (`[1, "identity resolution"]`)
+    async fn discover_by_attributes(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let originator: &str = &require_originator(originator)?;
+        if self.config.seek_permissions_for_identity_resolution {
+            self.ensure_special_protocol(
+                originator,
+                false,
+                vec!["1".to_string(), "identity resolution".to_string()],
+                None,
+                ProtocolUsageType::Generic,
+            ).await?;
+        }
+        self.underlying.discover_by_attributes(args, Some(originator)).await
+    }
+
+    async fn is_authenticated(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.is_authenticated(args, originator).await
+    }
+
+    async fn wait_for_authentication(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.wait_for_authentication(args, originator).await
+    }
+
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.get_height(originator).await
+    }
+
+    async fn get_header_for_height(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.get_header_for_height(args, originator).await
+    }
+
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.get_network(originator).await
+    }
+
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.underlying.get_version(originator).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_id_arg() {
+        let args = serde_json::json!({ "protocolID": [2, "todo list"] });
+        assert_eq!(protocol_id_arg(&args).unwrap(), vec!["2", "todo list"]);
+        let args = serde_json::json!({ "protocolID": ["1", "chat"] });
+        assert_eq!(protocol_id_arg(&args).unwrap(), vec!["1", "chat"]);
+        assert!(protocol_id_arg(&serde_json::json!({ "protocolID": [2] })).is_err());
+        assert!(protocol_id_arg(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_require_originator() {
+        assert_eq!(require_originator(Some(" App.Example.com")).unwrap().as_str(), "app.example.com");
+        assert!(require_originator(Some("app example")).is_err());
+        assert!(require_originator(None).is_err());
+    }
+}
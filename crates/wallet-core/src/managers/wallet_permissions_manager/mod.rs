@@ -42,6 +42,7 @@ pub mod callbacks;
 pub mod permission_request;
 pub mod permission_validation;
 pub mod token_management;
+mod interceptor;
 
 // Re-exports for convenience
 pub use types::*;
@@ -52,7 +53,9 @@ pub use permission_request::*;
 pub use permission_validation::*;
 pub use token_management::*;
 
+use crate::events::{WalletEvent, WalletEventBus};
 use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::originator::Originator;
 use crate::managers::simple_wallet_manager::WalletInterface;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -83,6 +86,10 @@ struct ActiveRequest {
 /// - DBAP (Domain Basket Access Protocol)
 /// - DCAP (Domain Certificate Access Protocol)
 /// - DSAP (Domain Spending Authorization Protocol)
+///
+/// Clones share callbacks, pending requests and caches, so a clone can grant
+/// or deny requests raised through the wallet it was cloned from.
+#[derive(Clone)]
 pub struct WalletPermissionsManager {
     /// A reference to the BRC-100 wallet instance
     ///
@@ -117,14 +124,12 @@ pub struct WalletPermissionsManager {
     ///
     /// Reference: TS config (line 415)
     config: PermissionsManagerConfig,
+    
+    /// Bus notified of granted permissions, when set
+    event_bus: Option<WalletEventBus>,
 }
 
 impl WalletPermissionsManager {
-    /// Cache time-to-live (5 minutes)
-    ///
-    /// Reference: TS CACHE_TTL_MS (line 410)
-    const CACHE_TTL_MS: i64 = 5 * 60 * 1000;
-    
     /// Constructs a new Permissions Manager instance
     ///
     /// Reference: TS constructor (WalletPermissionsManager.ts lines 424-452)
@@ -132,10 +137,11 @@ impl WalletPermissionsManager {
     /// # Arguments
     ///
     /// * `underlying_wallet` - The underlying BRC-100 wallet, where requests are forwarded
-    ///                         after permission is granted
+    ///   after permission is granted
     /// * `admin_originator` - The domain or FQDN that is automatically allowed everything
     /// * `config` - A set of boolean flags controlling how strictly permissions are enforced
-    ///              (defaults to most secure configuration)
+    ///   (defaults to most secure configuration; see
+    ///   `PermissionsManagerConfig::with_overrides` for partial configs)
     ///
     /// # Returns
     ///
@@ -146,22 +152,35 @@ impl WalletPermissionsManager {
         config: Option<PermissionsManagerConfig>,
     ) -> Self {
         // TS lines 425-426: Store underlying wallet and admin originator
-        // TS lines 429-451: Merge user config with secure defaults
-        let merged_config = if let Some(user_config) = config {
-            // TODO: Implement proper config merging
-            // For now, use the provided config or defaults
-            user_config
-        } else {
-            PermissionsManagerConfig::default()
-        };
+        // TS lines 429-451: Secure defaults unless configured; partial configs are
+        // merged by the caller (`..Default::default()` or `with_overrides`)
+        let merged_config = config.unwrap_or_default();
         
         Self {
             underlying: underlying_wallet,
-            admin_originator,
+            admin_originator: Originator::normalize(admin_originator),
             callbacks: Arc::new(RwLock::new(WalletPermissionsManagerCallbacks::default())),
             active_requests: Arc::new(RwLock::new(HashMap::new())),
             permission_cache: Arc::new(RwLock::new(HashMap::new())),
             config: merged_config,
+            event_bus: None,
+        }
+    }
+    
+    /// Emit a `WalletEvent::PermissionGranted` on `event_bus` for each grant
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+    
+    /// Notify the event bus of a granted request
+    fn emit_granted(&self, request_id: &str, request: &serde_json::Value, permission_type: Option<PermissionType>) {
+        if let Some(bus) = &self.event_bus {
+            bus.emit(WalletEvent::PermissionGranted {
+                request_id: request_id.to_string(),
+                originator: request["originator"].as_str().unwrap_or_default().to_string(),
+                permission_type,
+            });
         }
     }
     
@@ -293,9 +312,11 @@ impl WalletPermissionsManager {
     /// Check if an originator is the admin
     ///
     /// Reference: TS isAdminOriginator (WalletPermissionsManager.ts lines 3023-3025)
+    ///
+    /// Compared normalized, where TS compares the strings; an originator
+    /// that is not valid is never the admin.
     pub fn is_admin_originator(&self, originator: &str) -> bool {
-        // TS line 3024: return originator === this.adminOriginator
-        originator == self.admin_originator
+        Originator::parse(originator).is_ok_and(|o| o.as_str() == self.admin_originator)
     }
     
     /// Check if a protocol is admin-only
@@ -394,15 +415,24 @@ impl WalletPermissionsManager {
             // }
         }
         
-        // TS lines 574-580: Cache non-ephemeral permissions
-        if !params.ephemeral.unwrap_or(false) {
-            let expiry = params.expiry.unwrap_or_else(calculate_default_expiry);
-            // TODO: Get request from matching.request and build key
-            // let key = build_request_key(&request);
-            // let mut cache = self.permission_cache.write().await;
-            // cache_permission(&mut cache, key, expiry);
+        // TS lines 574-580: Cache non-ephemeral permissions, replacing any
+        // entry for the token a renewal supersedes
+        if let Ok(request) = serde_json::from_value::<PermissionRequest>(matching.request.clone()) {
+            let key = build_request_key(&request);
+            let mut cache = self.permission_cache.write().await;
+            if let Some(previous) = &request.previous_token {
+                cache.remove(&token_request_key(previous));
+            }
+            cache.remove(&key);
+            if !params.ephemeral.unwrap_or(false) {
+                let expiry = params.expiry.unwrap_or_else(calculate_default_expiry);
+                cache_permission(&mut cache, key, &request.originator, expiry);
+            }
         }
         
+        let permission_type = serde_json::from_value(matching.request["type"].clone()).ok();
+        self.emit_granted(&params.request_id, &matching.request, permission_type);
+        
         Ok(())
     }
     
@@ -429,7 +459,7 @@ impl WalletPermissionsManager {
             ))?;
         
         // TS lines 597-600: Reject all matching requests
-        let error = WalletError::invalid_operation("Permission denied.");
+        let error = WalletError::permission_denied("Permission denied.");
         for sender in matching.pending {
             let _ = sender.send(Err(error.clone())); // Ignore send errors
         }
@@ -461,10 +491,17 @@ impl WalletPermissionsManager {
         // TS lines 619-644: Validate granted permissions are subset of requested
         // TS lines 646-716: Create tokens for each granted permission type
         
+        // The grant may replace any of the originator's tokens
+        if let Some(originator) = matching.request["originator"].as_str() {
+            self.clear_permission_cache(originator).await;
+        }
+        
         // TS lines 718-722: Resolve all pending promises
         for sender in matching.pending {
             let _ = sender.send(Ok(()));
         }
+        self.emit_granted(&params.request_id, &matching.request, None);
+        
         
         Ok(())
     }
@@ -490,8 +527,7 @@ impl WalletPermissionsManager {
             ))?;
         
         // TS lines 734-739: Reject all matching requests with specific error
-        let mut error = WalletError::invalid_operation("The user has denied the request for permission.");
-        // TODO: Set error code to ERR_PERMISSION_DENIED when error struct supports it
+        let error = WalletError::permission_denied("The user has denied the request for permission.");
         
         for sender in matching.pending {
             let _ = sender.send(Err(error.clone()));
@@ -500,6 +536,28 @@ impl WalletPermissionsManager {
         Ok(())
     }
     
+    /// Revokes a permission token and forgets the cached permissions it backed
+    ///
+    /// A parent domain's token may back cached permissions of its subdomains,
+    /// so every permission cached against the token's originator is dropped.
+    ///
+    /// Reference: TS revokePermission (WalletPermissionsManager.ts)
+    pub async fn revoke_permission(&self, token: &PermissionToken) -> WalletResult<()> {
+        revoke_permission_token(token).await?;
+        let key = token_request_key(token);
+        self.permission_cache.write().await
+            .retain(|cached_key, cached| *cached_key != key && cached.originator != token.originator);
+        Ok(())
+    }
+    
+    /// Forgets every cached permission of `originator`
+    ///
+    /// For admin tooling that changes tokens behind the manager's back; the
+    /// next check looks the tokens up again.
+    pub async fn clear_permission_cache(&self, originator: &str) {
+        self.permission_cache.write().await.retain(|_, cached| cached.originator != originator);
+    }
+    
     /// Ensures the originator has protocol usage permission
     ///
     /// Reference: TS ensureProtocolPermission (WalletPermissionsManager.ts lines 750-858)
@@ -541,7 +599,10 @@ impl WalletPermissionsManager {
             ProtocolUsageType::Signing if !self.config.seek_protocol_permissions_for_signing => return Ok(true),
             ProtocolUsageType::Encrypting if !self.config.seek_protocol_permissions_for_encrypting => return Ok(true),
             ProtocolUsageType::Hmac if !self.config.seek_protocol_permissions_for_hmac => return Ok(true),
-            // TODO: Add other usage type checks when config fields are added
+            ProtocolUsageType::PublicKey if !self.config.seek_permissions_for_public_key_revelation
+                || !self.config.seek_protocol_permissions_for_key_derivation => return Ok(true),
+            ProtocolUsageType::IdentityKey if !self.config.seek_permissions_for_identity_key_revelation => return Ok(true),
+            ProtocolUsageType::LinkageRevelation if !self.config.seek_permissions_for_key_linkage_revelation => return Ok(true),
             _ => {}
         }
         
@@ -568,32 +629,35 @@ impl WalletPermissionsManager {
         let cache_key = build_request_key(&request);
         {
             let cache = self.permission_cache.read().await;
-            if is_permission_cached(&cache, &cache_key, Self::CACHE_TTL_MS) {
+            if is_permission_cached(&cache, &cache_key, self.config.permission_cache_ttl_ms) {
                 return Ok(true);
             }
         }
         
         // TS lines 814-820: Attempt to find a valid token
-        let token = find_protocol_token(
-            self.underlying.as_ref(),
-            &self.admin_originator,
-            &params.originator,
-            privileged,
-            &params.protocol_id,
-            &params.counterparty,
-            true, // includeExpired
-        ).await?;
+        let (underlying, admin_originator, args) = (self.underlying.as_ref(), self.admin_originator.as_str(), &params);
+        let token = find_covering_token(&params.originator, |domain| async move {
+            find_protocol_token(
+                underlying,
+                admin_originator,
+                &domain,
+                privileged,
+                &args.protocol_id,
+                &args.counterparty,
+                true, // includeExpired
+            ).await
+        }).await?;
         
         if let Some(token) = token {
             // TS lines 822-826: Token found and not expired
             if !is_token_expired_internal(token.expiry) {
                 let mut cache = self.permission_cache.write().await;
-                cache_permission(&mut cache, cache_key, token.expiry);
+                cache_permission(&mut cache, cache_key, &token.originator, token.expiry);
                 return Ok(true);
             } else {
                 // TS lines 827-841: Token expired, request renewal if allowed
                 if !params.seek_permission {
-                    return Err(WalletError::invalid_operation(
+                    return Err(WalletError::permission_denied(
                         "Protocol permission expired and no further user consent allowed (seekPermission=false)."
                     ));
                 }
@@ -607,7 +671,7 @@ impl WalletPermissionsManager {
         } else {
             // TS lines 843-857: No token found, request new one if allowed
             if !params.seek_permission {
-                return Err(WalletError::invalid_operation(
+                return Err(WalletError::permission_denied(
                     "No protocol permission token found (seekPermission=false)."
                 ));
             }
@@ -722,6 +786,9 @@ impl WalletPermissionsManager {
         }
         
         // TS lines 881-883: Config-based exceptions
+        if !self.config.seek_basket_permissions_for_basket_ops {
+            return Ok(true);
+        }
         match params.usage_type {
             BasketUsageType::Insertion if !self.config.seek_basket_insertion_permissions => return Ok(true),
             BasketUsageType::Removal if !self.config.seek_basket_removal_permissions => return Ok(true),
@@ -747,30 +814,33 @@ impl WalletPermissionsManager {
         let cache_key = build_request_key(&request);
         {
             let cache = self.permission_cache.read().await;
-            if is_permission_cached(&cache, &cache_key, Self::CACHE_TTL_MS) {
+            if is_permission_cached(&cache, &cache_key, self.config.permission_cache_ttl_ms) {
                 return Ok(true);
             }
         }
         
         // TS lines 888-905: Find existing token
-        let token = find_basket_token(
-            self.underlying.as_ref(),
-            &self.admin_originator,
-            &params.originator,
-            &params.basket,
-            true, // includeExpired
-        ).await?;
+        let (underlying, admin_originator, args) = (self.underlying.as_ref(), self.admin_originator.as_str(), &params);
+        let token = find_covering_token(&params.originator, |domain| async move {
+            find_basket_token(
+                underlying,
+                admin_originator,
+                &domain,
+                &args.basket,
+                true, // includeExpired
+            ).await
+        }).await?;
         
         if let Some(token) = token {
             // TS lines 890-893: Valid token found
             if !is_token_expired_internal(token.expiry) {
                 let mut cache = self.permission_cache.write().await;
-                cache_permission(&mut cache, cache_key, token.expiry);
+                cache_permission(&mut cache, cache_key, &token.originator, token.expiry);
                 return Ok(true);
             } else {
                 // TS lines 894-905: Expired token - renewal flow
                 if !params.seek_permission {
-                    return Err(WalletError::invalid_operation(
+                    return Err(WalletError::permission_denied(
                         "Basket permission expired (seekPermission=false)."
                     ));
                 }
@@ -784,7 +854,7 @@ impl WalletPermissionsManager {
         } else {
             // TS lines 907-919: No token found
             if !params.seek_permission {
-                return Err(WalletError::invalid_operation(
+                return Err(WalletError::permission_denied(
                     "No basket permission found, and no user consent allowed (seekPermission=false)."
                 ));
             }
@@ -846,33 +916,36 @@ impl WalletPermissionsManager {
         let cache_key = build_request_key(&request);
         {
             let cache = self.permission_cache.read().await;
-            if is_permission_cached(&cache, &cache_key, Self::CACHE_TTL_MS) {
+            if is_permission_cached(&cache, &cache_key, self.config.permission_cache_ttl_ms) {
                 return Ok(true);
             }
         }
         
         // TS lines 961-986: Find existing token
-        let token = find_certificate_token(
-            self.underlying.as_ref(),
-            &self.admin_originator,
-            &params.originator,
-            privileged,
-            &params.verifier,
-            &params.cert_type,
-            &params.fields,
-            true, // includeExpired
-        ).await?;
+        let (underlying, admin_originator, args) = (self.underlying.as_ref(), self.admin_originator.as_str(), &params);
+        let token = find_covering_token(&params.originator, |domain| async move {
+            find_certificate_token(
+                underlying,
+                admin_originator,
+                &domain,
+                privileged,
+                &args.verifier,
+                &args.cert_type,
+                &args.fields,
+                true, // includeExpired
+            ).await
+        }).await?;
         
         if let Some(token) = token {
             // TS lines 970-973: Valid token found
             if !is_token_expired_internal(token.expiry) {
                 let mut cache = self.permission_cache.write().await;
-                cache_permission(&mut cache, cache_key, token.expiry);
+                cache_permission(&mut cache, cache_key, &token.originator, token.expiry);
                 return Ok(true);
             } else {
                 // TS lines 974-986: Expired token - renewal flow
                 if !params.seek_permission {
-                    return Err(WalletError::invalid_operation(
+                    return Err(WalletError::permission_denied(
                         "Certificate permission expired (seekPermission=false)."
                     ));
                 }
@@ -886,7 +959,7 @@ impl WalletPermissionsManager {
         } else {
             // TS lines 988-999: No token found
             if !params.seek_permission {
-                return Err(WalletError::invalid_operation(
+                return Err(WalletError::permission_denied(
                     "No certificate permission found (seekPermission=false)."
                 ));
             }
@@ -940,7 +1013,7 @@ impl WalletPermissionsManager {
         let cache_key = build_request_key(&request);
         {
             let cache = self.permission_cache.read().await;
-            if is_permission_cached(&cache, &cache_key, Self::CACHE_TTL_MS) {
+            if is_permission_cached(&cache, &cache_key, self.config.permission_cache_ttl_ms) {
                 return Ok(true);
             }
         }
@@ -964,12 +1037,12 @@ impl WalletPermissionsManager {
                 if spent_so_far + params.satoshis <= authorized_amount {
                     // TS lines 1038-1039: Sufficient authorization
                     let mut cache = self.permission_cache.write().await;
-                    cache_permission(&mut cache, cache_key, token.expiry);
+                    cache_permission(&mut cache, cache_key, &params.originator, token.expiry);
                     return Ok(true);
                 } else {
                     // TS lines 1041-1055: Insufficient - renew
                     if !params.seek_permission {
-                        return Err(WalletError::invalid_operation(
+                        return Err(WalletError::permission_denied(
                             format!("Spending authorization insufficient for {}, no user consent (seekPermission=false).", params.satoshis)
                         ));
                     }
@@ -985,13 +1058,183 @@ impl WalletPermissionsManager {
         
         // TS lines 1057-1068: No token or no authorized amount
         if !params.seek_permission {
-            return Err(WalletError::invalid_operation(
+            return Err(WalletError::permission_denied(
                 "No spending authorization found, (seekPermission=false)."
             ));
         }
         
         return self.request_permission_flow(request).await;
     }
+    
+    /// Whether the originator currently holds the protocol permission
+    ///
+    /// Reference: TS hasProtocolPermission (WalletPermissionsManager.ts)
+    ///
+    /// Never prompts: runs `ensure_protocol_permission` with `seek_permission`
+    /// off, so the cache, configured exceptions and token lookup are the same
+    /// as for a real request. Any failure, including admin-only protocols,
+    /// counts as no permission.
+    pub async fn has_protocol_permission(
+        &self,
+        originator: &str,
+        privileged: bool,
+        protocol_id: &[String],
+        counterparty: &str,
+    ) -> bool {
+        self.ensure_protocol_permission(EnsureProtocolPermissionParams {
+            originator: originator.to_string(),
+            privileged,
+            protocol_id: protocol_id.to_vec(),
+            counterparty: counterparty.to_string(),
+            reason: None,
+            seek_permission: false,
+            usage_type: ProtocolUsageType::Generic,
+        }).await.is_ok()
+    }
+    
+    /// Whether the originator currently has access to the basket
+    ///
+    /// Reference: TS hasBasketAccess (WalletPermissionsManager.ts)
+    ///
+    /// Never prompts; see `has_protocol_permission`.
+    pub async fn has_basket_access(&self, originator: &str, basket: &str) -> bool {
+        self.ensure_basket_access(EnsureBasketAccessParams {
+            originator: originator.to_string(),
+            basket: basket.to_string(),
+            reason: None,
+            seek_permission: false,
+            usage_type: BasketUsageType::Insertion,
+        }).await.is_ok()
+    }
+    
+    /// Whether the originator may currently disclose the certificate fields
+    /// to the verifier
+    ///
+    /// Reference: TS hasCertificateAccess (WalletPermissionsManager.ts)
+    ///
+    /// Never prompts; see `has_protocol_permission`.
+    pub async fn has_certificate_access(
+        &self,
+        originator: &str,
+        privileged: bool,
+        verifier: &str,
+        cert_type: &str,
+        fields: &[String],
+    ) -> bool {
+        self.ensure_certificate_access(EnsureCertificateAccessParams {
+            originator: originator.to_string(),
+            privileged,
+            verifier: verifier.to_string(),
+            cert_type: cert_type.to_string(),
+            fields: fields.to_vec(),
+            reason: None,
+            seek_permission: false,
+            usage_type: CertificateUsageType::Disclosure,
+        }).await.is_ok()
+    }
+    
+    /// Whether the originator's monthly authorization covers spending
+    /// `satoshis` more
+    ///
+    /// Reference: TS hasSpendingAuthorization (WalletPermissionsManager.ts)
+    ///
+    /// Never prompts; see `has_protocol_permission`.
+    pub async fn has_spending_authorization(&self, originator: &str, satoshis: i64) -> bool {
+        self.ensure_spending_authorization(EnsureSpendingAuthorizationParams {
+            originator: originator.to_string(),
+            satoshis,
+            line_items: None,
+            reason: None,
+            seek_permission: false,
+        }).await.is_ok()
+    }
+    
+    /// Ensures the originator may apply or list by an action label
+    ///
+    /// Reference: TS ensureLabelAccess (WalletPermissionsManager.ts)
+    ///
+    /// Label permissions are protocol permissions for `[1, "action label <label>"]`
+    /// with counterparty `self`.
+    pub async fn ensure_label_access(&self, params: EnsureLabelAccessParams) -> WalletResult<bool> {
+        if self.is_admin_originator(&params.originator) {
+            return Ok(true);
+        }
+        
+        if self.is_admin_label(&params.label) {
+            return Err(WalletError::invalid_operation(
+                format!("Label \"{}\" is admin-only.", params.label)
+            ));
+        }
+        
+        match params.usage_type {
+            LabelUsageType::Apply if !self.config.seek_permission_when_applying_action_labels => return Ok(true),
+            LabelUsageType::List if !self.config.seek_permission_when_listing_actions_by_label => return Ok(true),
+            _ => {}
+        }
+        
+        self.ensure_protocol_permission(EnsureProtocolPermissionParams {
+            originator: params.originator,
+            privileged: false,
+            protocol_id: vec!["1".to_string(), format!("action label {}", params.label)],
+            counterparty: "self".to_string(),
+            reason: params.reason,
+            seek_permission: params.seek_permission,
+            usage_type: ProtocolUsageType::Generic,
+        }).await
+    }
+    
+    /// Lists the current protocol permission tokens (DPACP) with decrypted fields
+    ///
+    /// Reference: TS listProtocolPermissions (WalletPermissionsManager.ts)
+    pub async fn list_protocol_permissions(&self, params: ListPermissionsParams) -> WalletResult<Vec<PermissionToken>> {
+        self.list_permissions(PermissionType::Protocol, params).await
+    }
+    
+    /// Lists the current basket access tokens (DBAP) with decrypted fields
+    ///
+    /// Reference: TS listBasketAccess (WalletPermissionsManager.ts)
+    pub async fn list_basket_permissions(&self, params: ListPermissionsParams) -> WalletResult<Vec<PermissionToken>> {
+        self.list_permissions(PermissionType::Basket, params).await
+    }
+    
+    /// Lists the current certificate access tokens (DCAP) with decrypted fields
+    ///
+    /// Reference: TS listCertificateAccess (WalletPermissionsManager.ts)
+    pub async fn list_certificate_permissions(&self, params: ListPermissionsParams) -> WalletResult<Vec<PermissionToken>> {
+        self.list_permissions(PermissionType::Certificate, params).await
+    }
+    
+    /// Lists the current spending authorization tokens (DSAP) with decrypted fields
+    ///
+    /// Reference: TS listSpendingAuthorizations (WalletPermissionsManager.ts)
+    pub async fn list_spending_authorizations(&self, params: ListPermissionsParams) -> WalletResult<Vec<PermissionToken>> {
+        self.list_permissions(PermissionType::Spending, params).await
+    }
+    
+    /// Lists one type of permission token, optionally only those granted to
+    /// `params.originator`, a page at a time
+    async fn list_permissions(
+        &self,
+        permission_type: PermissionType,
+        params: ListPermissionsParams,
+    ) -> WalletResult<Vec<PermissionToken>> {
+        let tags: Vec<String> = params.originator.iter()
+            .map(|originator| format!("originator {}", originator))
+            .collect();
+        let tokens = list_permission_tokens(
+            self.underlying.as_ref(),
+            &self.admin_originator,
+            permission_type,
+            &tags,
+            params.offset,
+            params.limit,
+        ).await?;
+        
+        // The tag is only an index; the encrypted domain field is authoritative
+        Ok(tokens.into_iter()
+            .filter(|token| params.originator.as_deref().is_none_or(|o| token.originator == o))
+            .collect())
+    }
 }
 
 // ============================================================================
@@ -1001,73 +1244,117 @@ impl WalletPermissionsManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::MockWallet;
     
-    // Mock wallet for testing
-    struct MockWallet;
+    /// Records the args of the last createAction and listOutputs calls and
+    /// answers listOutputs with `outputs`. "Encrypts" by reversing bytes;
+    /// everything else is answered by `wallet`
+    #[derive(Default)]
+    struct RecordingWallet {
+        create_action_args: std::sync::Mutex<Option<serde_json::Value>>,
+        list_outputs_args: std::sync::Mutex<Option<serde_json::Value>>,
+        outputs: Vec<serde_json::Value>,
+        wallet: MockWallet,
+    }
     
     #[async_trait::async_trait]
-    impl WalletInterface for MockWallet {
-        async fn create_action(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
+    impl WalletInterface for RecordingWallet {
+        async fn create_action(&self, args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            *self.create_action_args.lock().unwrap() = Some(args);
             Ok(serde_json::json!({}))
         }
-        
-        async fn sign_action(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn sign_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.sign_action(args, originator).await
         }
-        
-        async fn abort_action(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn abort_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.abort_action(args, originator).await
         }
-        
-        async fn list_actions(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn list_actions(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.list_actions(args, originator).await
         }
-        
-        async fn internal_ize_action(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn internalize_action(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.internalize_action(args, originator).await
         }
-        
-        async fn list_outputs(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn list_outputs(&self, args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            *self.list_outputs_args.lock().unwrap() = Some(args);
+            Ok(serde_json::json!({ "totalOutputs": self.outputs.len(), "outputs": self.outputs }))
         }
-        
-        async fn relinquish_output(
-            &self,
-            _args: serde_json::Value,
-            _originator: Option<&str>,
-        ) -> WalletResult<serde_json::Value> {
-            Ok(serde_json::json!({}))
+        async fn relinquish_output(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.relinquish_output(args, originator).await
+        }
+        async fn get_public_key(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.get_public_key(args, originator).await
+        }
+        async fn reveal_counterparty_key_linkage(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.reveal_counterparty_key_linkage(args, originator).await
+        }
+        async fn reveal_specific_key_linkage(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.reveal_specific_key_linkage(args, originator).await
+        }
+        async fn encrypt(&self, args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            let mut bytes: Vec<u8> = serde_json::from_value(args["plaintext"].clone()).unwrap();
+            bytes.reverse();
+            Ok(serde_json::json!({ "ciphertext": bytes }))
+        }
+        async fn decrypt(&self, args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            let mut bytes: Vec<u8> = serde_json::from_value(args["ciphertext"].clone()).unwrap();
+            bytes.reverse();
+            Ok(serde_json::json!({ "plaintext": bytes }))
+        }
+        async fn create_hmac(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.create_hmac(args, originator).await
+        }
+        async fn verify_hmac(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.verify_hmac(args, originator).await
+        }
+        async fn create_signature(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.create_signature(args, originator).await
+        }
+        async fn verify_signature(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.verify_signature(args, originator).await
+        }
+        async fn acquire_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.acquire_certificate(args, originator).await
+        }
+        async fn list_certificates(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.list_certificates(args, originator).await
+        }
+        async fn prove_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.prove_certificate(args, originator).await
+        }
+        async fn relinquish_certificate(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.relinquish_certificate(args, originator).await
+        }
+        async fn discover_by_identity_key(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.discover_by_identity_key(args, originator).await
+        }
+        async fn discover_by_attributes(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.discover_by_attributes(args, originator).await
+        }
+        async fn get_header_for_height(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.get_header_for_height(args, originator).await
+        }
+        async fn is_authenticated(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.is_authenticated(args, originator).await
+        }
+        async fn wait_for_authentication(&self, args: serde_json::Value, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.wait_for_authentication(args, originator).await
+        }
+        async fn get_height(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.get_height(originator).await
+        }
+        async fn get_network(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.get_network(originator).await
+        }
+        async fn get_version(&self, originator: Option<&str>) -> WalletResult<serde_json::Value> {
+            self.wallet.get_version(originator).await
         }
     }
     
     #[tokio::test]
     async fn test_permissions_manager_creation() {
         // TS constructor test (lines 424-452)
-        let wallet = Arc::new(MockWallet);
+        let wallet = Arc::new(MockWallet::new());
         let manager = WalletPermissionsManager::new(
             wallet,
             "admin.example.com".to_string(),
@@ -1080,21 +1367,208 @@ mod tests {
     
     #[tokio::test]
     async fn test_is_admin() {
-        let wallet = Arc::new(MockWallet);
+        let wallet = Arc::new(MockWallet::new());
         let manager = WalletPermissionsManager::new(
             wallet,
             "admin.example.com".to_string(),
             None,
         );
         
-        assert!(manager.is_admin("admin.example.com"));
-        assert!(!manager.is_admin("other.example.com"));
+        assert!(manager.is_admin_originator("admin.example.com"));
+        assert!(manager.is_admin_originator("Admin.Example.com."));
+        assert!(!manager.is_admin_originator("other.example.com"));
+        assert!(!manager.is_admin_originator("app.admin.example.com"));
+    }
+    
+    #[tokio::test]
+    async fn test_create_action_adds_originator_labels() {
+        let wallet = Arc::new(RecordingWallet::default());
+        let config = PermissionsManagerConfig {
+            seek_permission_when_applying_action_labels: false,
+            seek_spending_permissions: false,
+            encrypt_wallet_metadata: false,
+            ..PermissionsManagerConfig::default()
+        };
+        let manager = WalletPermissionsManager::new(wallet.clone(), "admin.example.com".to_string(), Some(config));
+        
+        manager.create_action(
+            serde_json::json!({ "description": "Buy a coffee", "labels": ["coffee"] }),
+            Some("app.example.com"),
+        ).await.unwrap();
+        
+        let args = wallet.create_action_args.lock().unwrap().take().unwrap();
+        let labels: Vec<String> = serde_json::from_value(args["labels"].clone()).unwrap();
+        assert_eq!(labels[0], "coffee");
+        assert_eq!(labels[1], "admin originator app.example.com");
+        assert_eq!(labels[2], format!("admin month {}", get_current_month_utc()));
+    }
+    
+    #[tokio::test]
+    async fn test_create_action_rejects_admin_labels() {
+        let wallet = Arc::new(RecordingWallet::default());
+        let manager = WalletPermissionsManager::new(wallet.clone(), "admin.example.com".to_string(), None);
+        
+        let err = manager.create_action(
+            serde_json::json!({ "description": "Spoof", "labels": ["admin originator other.example.com"] }),
+            Some("app.example.com"),
+        ).await.unwrap_err();
+        assert!(err.to_string().contains("admin-only"));
+        assert!(wallet.create_action_args.lock().unwrap().is_none());
+    }
+    
+    fn basket_token_output(vout: u32, fields: &[&str]) -> serde_json::Value {
+        use base64::Engine as _;
+        let fields: Vec<String> = fields.iter()
+            .map(|f| base64::engine::general_purpose::STANDARD.encode(f))
+            .collect();
+        serde_json::json!({
+            "outpoint": format!("{}.{}", "ab".repeat(32), vout),
+            "satoshis": 1,
+            "lockingScript": "51",
+            "customInstructions": { "fields": fields },
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_list_basket_permissions() {
+        let wallet = Arc::new(RecordingWallet {
+            outputs: vec![
+                basket_token_output(0, &["app.example.com", "0", "todo tokens"]),
+                basket_token_output(1, &["other.example.com", "0", "todo tokens"]),
+                basket_token_output(2, &["app.example.com", "0"]),
+            ],
+            ..RecordingWallet::default()
+        });
+        let manager = WalletPermissionsManager::new(wallet.clone(), "admin.example.com".to_string(), None);
+        
+        let tokens = manager.list_basket_permissions(ListPermissionsParams {
+            originator: Some("app.example.com".to_string()),
+            offset: 10,
+            limit: Some(5),
+        }).await.unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].output_index, 0);
+        assert_eq!(tokens[0].basket_name.as_deref(), Some("todo tokens"));
+        
+        let args = wallet.list_outputs_args.lock().unwrap().take().unwrap();
+        assert_eq!(args["basket"], "admin basket-access");
+        assert_eq!(args["tags"], serde_json::json!(["originator app.example.com"]));
+        assert_eq!(args["offset"], 10);
+        assert_eq!(args["limit"], 5);
+        
+        let tokens = manager.list_basket_permissions(ListPermissionsParams::default()).await.unwrap();
+        assert_eq!(tokens.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_has_basket_access_never_prompts() {
+        let wallet = Arc::new(RecordingWallet {
+            outputs: vec![basket_token_output(0, &["app.example.com", "0", "todo tokens"])],
+            ..RecordingWallet::default()
+        });
+        let manager = WalletPermissionsManager::new(wallet, "admin.example.com".to_string(), None);
+        
+        assert!(manager.has_basket_access("app.example.com", "todo tokens").await);
+        assert!(!manager.has_basket_access("other.example.com", "todo tokens").await);
+        assert!(!manager.has_basket_access("app.example.com", "admin secrets").await);
+        assert!(manager.has_basket_access("admin.example.com", "admin secrets").await);
+        assert!(manager.active_requests.read().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_permission_cache_invalidation() {
+        let wallet = Arc::new(RecordingWallet {
+            outputs: vec![
+                basket_token_output(0, &["app.example.com", "0", "todo tokens"]),
+                basket_token_output(1, &["other.example.com", "0", "todo tokens"]),
+            ],
+            ..RecordingWallet::default()
+        });
+        let manager = WalletPermissionsManager::new(wallet, "admin.example.com".to_string(), None);
+        
+        assert!(manager.has_basket_access("app.example.com", "todo tokens").await);
+        assert!(manager.has_basket_access("other.example.com", "todo tokens").await);
+        assert_eq!(manager.permission_cache.read().await.len(), 2);
+        
+        manager.clear_permission_cache("other.example.com").await;
+        assert!(manager.permission_cache.read().await.contains_key("basket:app.example.com:todo tokens"));
+        assert_eq!(manager.permission_cache.read().await.len(), 1);
+        
+        let token = find_basket_token(
+            manager.underlying.as_ref(), "admin.example.com", "app.example.com", "todo tokens", false,
+        ).await.unwrap().unwrap();
+        manager.revoke_permission(&token).await.unwrap();
+        assert!(manager.permission_cache.read().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_parent_domain_grant_covers_subdomains() {
+        let wallet = Arc::new(RecordingWallet {
+            outputs: vec![
+                basket_token_output(0, &["example.com", "0", "todo tokens"]),
+                basket_token_output(1, &["old.example.com", "1", "todo tokens"]),
+                basket_token_output(2, &["api.other.com", "0", "todo tokens"]),
+            ],
+            ..RecordingWallet::default()
+        });
+        let manager = WalletPermissionsManager::new(wallet, "admin.example.com".to_string(), None);
+        
+        assert!(manager.has_basket_access("app.example.com", "todo tokens").await);
+        assert!(manager.has_basket_access("a.b.example.com", "todo tokens").await);
+        // An expired token of its own does not hide the parent's grant
+        assert!(manager.has_basket_access("old.example.com", "todo tokens").await);
+        assert!(!manager.has_basket_access("badexample.com", "todo tokens").await);
+        assert!(!manager.has_basket_access("app.example.com:8080", "todo tokens").await);
+        // Grants never extend upwards
+        assert!(!manager.has_basket_access("other.com", "todo tokens").await);
+        
+        let cache = manager.permission_cache.read().await;
+        assert_eq!(cache["basket:app.example.com:todo tokens"].originator, "example.com");
+        drop(cache);
+        
+        let token = find_basket_token(
+            manager.underlying.as_ref(), "admin.example.com", "example.com", "todo tokens", false,
+        ).await.unwrap().unwrap();
+        manager.revoke_permission(&token).await.unwrap();
+        assert!(manager.permission_cache.read().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_metadata_encryption() {
+        let wallet = Arc::new(RecordingWallet {
+            outputs: vec![serde_json::json!({ "outpoint": "ab.0", "customInstructions": "olleh" })],
+            ..RecordingWallet::default()
+        });
+        let config = PermissionsManagerConfig {
+            seek_spending_permissions: false,
+            ..PermissionsManagerConfig::default()
+        };
+        let manager = WalletPermissionsManager::new(wallet.clone(), "admin.example.com".to_string(), Some(config));
+        
+        manager.create_action(
+            serde_json::json!({
+                "description": "abc",
+                "outputs": [{ "lockingScript": "51", "satoshis": 1, "outputDescription": "xyz" }],
+            }),
+            Some("admin.example.com"),
+        ).await.unwrap();
+        let args = wallet.create_action_args.lock().unwrap().take().unwrap();
+        assert_eq!(args["description"], "Y2Jh");
+        assert_eq!(args["outputs"][0]["outputDescription"], "enl4");
+        
+        // Listing decrypts; values that aren't base64 ciphertext come back as stored
+        let result = manager.list_outputs(serde_json::json!({}), Some("admin.example.com")).await.unwrap();
+        assert_eq!(result["outputs"][0]["customInstructions"], "olleh");
+        assert_eq!(
+            decrypt_metadata(wallet.as_ref(), "admin.example.com", "Y2Jh").await,
+            "abc"
+        );
     }
     
     #[tokio::test]
     async fn test_callback_binding() {
         // TS bindCallback/unbindCallback test (lines 465-498)
-        let wallet = Arc::new(MockWallet);
+        let wallet = Arc::new(MockWallet::new());
         let manager = WalletPermissionsManager::new(
             wallet,
             "admin.example.com".to_string(),
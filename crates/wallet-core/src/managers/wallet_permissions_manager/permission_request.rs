@@ -126,6 +126,38 @@ pub enum BasketUsageType {
     Listing,
 }
 
+/// Ensure label access parameters
+///
+/// Reference: TS ensureLabelAccess params (WalletPermissionsManager.ts)
+#[derive(Debug, Clone)]
+pub struct EnsureLabelAccessParams {
+    /// The originator domain or FQDN
+    pub originator: String,
+    
+    /// Action label
+    pub label: String,
+    
+    /// Human-readable reason
+    pub reason: Option<String>,
+    
+    /// Whether to seek permission if not found
+    pub seek_permission: bool,
+    
+    /// Type of label usage
+    pub usage_type: LabelUsageType,
+}
+
+/// Label usage type enumeration
+///
+/// Reference: TS usageType in ensureLabelAccess
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelUsageType {
+    /// Applying the label to a new action
+    Apply,
+    /// Listing actions by the label
+    List,
+}
+
 /// Ensure certificate access parameters
 ///
 /// Reference: TS ensureCertificateAccess params (WalletPermissionsManager.ts lines 926-944)
@@ -186,6 +218,22 @@ pub struct EnsureSpendingAuthorizationParams {
     pub seek_permission: bool,
 }
 
+/// Permission listing parameters
+///
+/// Reference: TS listProtocolPermissions / listBasketAccess / listCertificateAccess /
+/// listSpendingAuthorizations params (WalletPermissionsManager.ts)
+#[derive(Debug, Clone, Default)]
+pub struct ListPermissionsParams {
+    /// Only list tokens granted to this originator
+    pub originator: Option<String>,
+    
+    /// Number of tokens to skip
+    pub offset: u32,
+    
+    /// Maximum number of tokens to return (the underlying wallet's default when `None`)
+    pub limit: Option<u32>,
+}
+
 impl Default for EnsureProtocolPermissionParams {
     fn default() -> Self {
         Self {
@@ -212,6 +260,18 @@ impl Default for EnsureBasketAccessParams {
     }
 }
 
+impl Default for EnsureLabelAccessParams {
+    fn default() -> Self {
+        Self {
+            originator: String::new(),
+            label: String::new(),
+            reason: None,
+            seek_permission: true,
+            usage_type: LabelUsageType::Apply,
+        }
+    }
+}
+
 impl Default for EnsureCertificateAccessParams {
     fn default() -> Self {
         Self {
@@ -8,7 +8,11 @@ use super::types::*;
 use super::constants::*;
 use super::token_management::{decrypt_permission_token_field};
 use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::originator::Originator;
 use crate::managers::simple_wallet_manager::WalletInterface;
+use crate::methods::fee_model::{fee_for_size, transaction_size, StorageFeeModel, CHANGE_UNLOCKING_SCRIPT_LENGTH};
+use crate::sdk::validation::parse_wallet_outpoint;
+use crate::sdk::wallet_interface::CreateActionArgs;
 use serde_json::json;
 
 /// Check if a token is expired (internal helper)
@@ -64,12 +68,172 @@ pub fn get_current_month_utc() -> String {
     format!("{:04}-{:02}", now.year(), now.month())
 }
 
+/// Decrypt the first `count` PushDrop fields of a token output as UTF-8
+///
+/// Reference: TS decryptPermissionTokenField, applied to each decoded field
+///
+/// Returns `None` for outputs that don't carry enough fields.
+async fn decrypt_token_fields(
+    underlying: &dyn WalletInterface,
+    admin_originator: &str,
+    output: &serde_json::Value,
+    count: usize,
+) -> WalletResult<Option<Vec<String>>> {
+    // SIMPLIFIED ARCHITECTURE: Extract fields from storage metadata
+    // Frontend (ts-sdk) already parsed PushDrop and stored fields in customInstructions
+    let Some(fields) = output["customInstructions"]["fields"].as_array() else {
+        return Ok(None);
+    };
+    if fields.len() < count {
+        return Ok(None);
+    }
+    
+    let mut decrypted = Vec::with_capacity(count);
+    for field in &fields[..count] {
+        let bytes = decrypt_permission_token_field(
+            underlying,
+            admin_originator,
+            field.as_str().unwrap_or("")
+        ).await?;
+        decrypted.push(String::from_utf8(bytes).map_err(|e| {
+            WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in permission token field: {}", e))
+        })?);
+    }
+    Ok(Some(decrypted))
+}
+
+/// Token shell for an output: outpoint, script and satoshis, with every
+/// permission-specific field empty
+///
+/// Returns `None` when the output has no valid `txid.vout` outpoint.
+fn token_from_output(output: &serde_json::Value, originator: String) -> Option<PermissionToken> {
+    let (txid, output_index) = output["outpoint"].as_str()?.split_once('.')?;
+    Some(PermissionToken {
+        tx: vec![], // Frontend has BEEF, not needed here
+        txid: txid.to_string(),
+        output_index: output_index.parse().ok()?,
+        output_script: output["lockingScript"].as_str().unwrap_or("").to_string(),
+        satoshis: output["satoshis"].as_i64().unwrap_or(0),
+        originator,
+        privileged: None,
+        protocol: None,
+        security_level: None,
+        expiry: 0,
+        counterparty: None,
+        basket_name: None,
+        verifier: None,
+        cert_type: None,
+        cert_fields: None,
+        authorized_amount: None,
+    })
+}
+
+/// Decode a permission token output of the given type
+///
+/// Reference: TS PushDrop field layouts (WalletPermissionsManager.ts lines 1311-1594)
+///
+/// - DPACP: domain, expiry, privileged, security level, protocol name, counterparty
+/// - DBAP: domain, expiry, basket
+/// - DCAP: domain, expiry, privileged, type, fields (JSON), verifier
+/// - DSAP: domain, authorized amount (not time-limited; expiry is 0)
+///
+/// Returns `None` for outputs that aren't well-formed tokens.
+pub async fn decode_permission_token(
+    underlying: &dyn WalletInterface,
+    admin_originator: &str,
+    permission_type: PermissionType,
+    output: &serde_json::Value,
+) -> WalletResult<Option<PermissionToken>> {
+    let count = match permission_type {
+        PermissionType::Protocol | PermissionType::Certificate => 6,
+        PermissionType::Basket => 3,
+        PermissionType::Spending => 2,
+    };
+    let Some(mut fields) = decrypt_token_fields(underlying, admin_originator, output, count).await? else {
+        return Ok(None);
+    };
+    let domain = std::mem::take(&mut fields[0]);
+    let Some(mut token) = token_from_output(output, domain) else {
+        return Ok(None);
+    };
+    
+    match permission_type {
+        PermissionType::Protocol => {
+            token.expiry = fields[1].parse().unwrap_or(0);
+            token.privileged = Some(fields[2] == "true");
+            token.security_level = Some(match fields[3].as_str() {
+                "1" => SecurityLevel::Shared,
+                "2" => SecurityLevel::Private,
+                _ => SecurityLevel::Public, // Default to public if invalid
+            });
+            token.protocol = Some(std::mem::take(&mut fields[4]));
+            token.counterparty = Some(std::mem::take(&mut fields[5]));
+        }
+        PermissionType::Basket => {
+            token.expiry = fields[1].parse().unwrap_or(0);
+            token.basket_name = Some(std::mem::take(&mut fields[2]));
+        }
+        PermissionType::Certificate => {
+            token.expiry = fields[1].parse().unwrap_or(0);
+            token.privileged = Some(fields[2] == "true");
+            token.cert_type = Some(std::mem::take(&mut fields[3]));
+            // TS lines 1522-1523: Parse fields JSON array
+            token.cert_fields = Some(serde_json::from_str(&fields[4]).map_err(|e| {
+                WalletError::new("WERR_INVALID_DATA", format!("Invalid JSON in fields: {}", e))
+            })?);
+            token.verifier = Some(std::mem::take(&mut fields[5]));
+        }
+        PermissionType::Spending => {
+            token.authorized_amount = Some(fields[1].parse().unwrap_or(0));
+        }
+    }
+    Ok(Some(token))
+}
+
+/// List and decode permission tokens of one type
+///
+/// Queries the type's admin basket for outputs carrying all of `tags`,
+/// skipping `offset` and returning at most `limit` outputs (the underlying
+/// wallet's default when `None`), and decodes each.
+pub async fn list_permission_tokens(
+    underlying: &dyn WalletInterface,
+    admin_originator: &str,
+    permission_type: PermissionType,
+    tags: &[String],
+    offset: u32,
+    limit: Option<u32>,
+) -> WalletResult<Vec<PermissionToken>> {
+    let mut args = json!({
+        "basket": get_admin_basket_name(permission_type),
+        "tags": tags,
+        "tagQueryMode": "all",
+        "include": "entire transactions",
+        "offset": offset,
+    });
+    if let Some(limit) = limit {
+        args["limit"] = json!(limit);
+    }
+    let result = underlying.list_outputs(args, Some(admin_originator)).await?;
+    
+    let empty_vec = vec![];
+    let outputs = result["outputs"].as_array().unwrap_or(&empty_vec);
+    
+    let mut tokens = Vec::with_capacity(outputs.len());
+    for output in outputs {
+        if let Some(token) = decode_permission_token(underlying, admin_originator, permission_type, output).await? {
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
 /// Find a protocol permission token (DPACP)
 ///
 /// Reference: TS findProtocolToken (WalletPermissionsManager.ts lines 1247-1323)
 ///
 /// Looks for a DPACP permission token matching originator, privileged flag, protocol, and counterparty.
 ///
+/// # Arguments
 ///
 /// * `originator` - Domain or FQDN
 /// * `privileged` - Whether this is a privileged operation
@@ -80,14 +244,6 @@ pub fn get_current_month_utc() -> String {
 /// # Returns
 ///
 /// Optional permission token if found
-///
-/// # TODO
-///
-/// This requires integration with:
-/// - underlying.listOutputs() to query tokens by basket and tags
-/// - Transaction.fromBEEF() to parse BEEF transactions
-/// - PushDrop.decode() to decode locking scripts
-/// - decryptPermissionTokenField() to decrypt each field
 pub async fn find_protocol_token(
     underlying: &dyn WalletInterface,
     admin_originator: &str,
@@ -97,18 +253,6 @@ pub async fn find_protocol_token(
     counterparty: &str,
     include_expired: bool,
 ) -> WalletResult<Option<PermissionToken>> {
-    // TS lines 1248-1260: Build tags for query
-    // const [secLevel, protoName] = protocolID
-    // const tags = [
-    //   `originator ${originator}`,
-    //   `privileged ${!!privileged}`,
-    //   `protocolName ${protoName}`,
-    //   `protocolSecurityLevel ${secLevel}`
-    // ]
-    // if (secLevel === 2) {
-    //   tags.push(`counterparty ${counterparty}`)
-    // }
-    
     if protocol_id.len() < 2 {
         return Err(WalletError::invalid_parameter(
             "protocol_id",
@@ -119,8 +263,8 @@ pub async fn find_protocol_token(
     let sec_level = &protocol_id[0];
     let proto_name = &protocol_id[1];
     
-    // Build tags for query
-    let mut _tags = vec![
+    // TS lines 1248-1260: Build tags for query
+    let mut tags = vec![
         format!("originator {}", originator),
         format!("privileged {}", privileged),
         format!("protocolName {}", proto_name),
@@ -128,161 +272,23 @@ pub async fn find_protocol_token(
     ];
     
     if sec_level == "2" {
-        _tags.push(format!("counterparty {}", counterparty));
+        tags.push(format!("counterparty {}", counterparty));
     }
     
-    // TS lines 1301-1309: Query outputs from storage
-    let basket_name = get_admin_basket_name(PermissionType::Protocol);
-    let result = underlying.list_outputs(
-        json!({
-            "basket": basket_name,
-            "tags": _tags,
-            "tagQueryMode": "all",
-            "include": "entire transactions"
-        }),
-        Some(admin_originator)
-    ).await?;
-    
-    // TS lines 1311-1359: Loop through results, decode PushDrop, decrypt fields
-    let empty_vec = vec![];
-    let outputs = result["outputs"].as_array().unwrap_or(&empty_vec);
-    
-    for output in outputs {
-        // TS line 1312: Split outpoint into txid and outputIndex
-        let outpoint = output["outpoint"].as_str().unwrap_or("");
-        let parts: Vec<&str> = outpoint.split('.').collect();
-        if parts.len() != 2 {
-            continue;
-        }
-        
-        let txid = parts[0];
-        let output_index_str = parts[1];
-        let output_index: usize = match output_index_str.parse() {
-            Ok(idx) => idx,
-            Err(_) => continue,
-        };
-        
-        // SIMPLIFIED ARCHITECTURE: Extract fields from storage metadata
-        // Frontend (ts-sdk) already parsed PushDrop and stored fields in customInstructions
-        
-        // Extract custom instructions (TS line 1313-1321 equivalent)
-        let custom = match output["customInstructions"].as_object() {
-            Some(c) => c,
-            None => continue, // No custom instructions, skip
-        };
-        
-        let fields = match custom["fields"].as_array() {
-            Some(f) => f,
-            None => continue, // No fields, skip
-        };
-        
-        // Need 6 fields for protocol token (TS line 1314-1315)
-        if fields.len() < 6 {
-            continue;
-        }
-        
-        // TS lines 1323-1331: Decrypt all 6 fields
-        let domain_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[0].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in domain: {}", e)))?;
-        
-        let expiry_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[1].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in expiry: {}", e)))?;
-        let expiry_decoded: i64 = expiry_str.parse().unwrap_or(0);
-        
-        let priv_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[2].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in privileged: {}", e)))?;
-        let priv_decoded = priv_str == "true";
-        
-        let sec_level_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[3].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in security level: {}", e)))?;
-        let sec_level_decoded = sec_level_str.as_str();
-        
-        let proto_name_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[4].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in protocol name: {}", e)))?;
-        
-        let cpty_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[5].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in counterparty: {}", e)))?;
-        
+    // TS lines 1301-1359: Query outputs, decode and return the first match
+    let tokens = list_permission_tokens(underlying, admin_originator, PermissionType::Protocol, &tags, 0, None).await?;
+    Ok(tokens.into_iter().find(|token| {
+        let token_level = token.security_level.map(|level| (level as u8).to_string());
         // TS lines 1333-1341: Validate all fields match
-        if domain_decoded != originator
-            || priv_decoded != privileged
-            || sec_level_decoded != sec_level
-            || proto_name_decoded != *proto_name
-        {
-            continue;
-        }
-        
-        // For security level 2, validate counterparty (TS line 1338)
-        if sec_level == "2" && cpty_decoded != counterparty {
-            continue;
-        }
-        
-        // TS lines 1342-1344: Check expiry if needed
-        if !include_expired && is_token_expired_internal(expiry_decoded) {
-            continue;
-        }
-        
-        // TS lines 1345-1357: Return the found token
-        // Convert security level string to SecurityLevel enum
-        let sec_level_enum = match sec_level.as_str() {
-            "0" => SecurityLevel::Public,
-            "1" => SecurityLevel::Shared,
-            "2" => SecurityLevel::Private,
-            _ => SecurityLevel::Public, // Default to public if invalid
-        };
-        
-        return Ok(Some(PermissionToken {
-            tx: vec![], // Frontend has BEEF, not needed here
-            txid: txid.to_string(),
-            output_index: output_index as u32,
-            output_script: output["lockingScript"].as_str().unwrap_or("").to_string(),
-            satoshis: output["satoshis"].as_i64().unwrap_or(0),
-            originator: originator.to_string(),
-            privileged: Some(priv_decoded),
-            protocol: Some(proto_name_decoded),
-            security_level: Some(sec_level_enum),
-            expiry: expiry_decoded,
-            counterparty: Some(cpty_decoded),
-            basket_name: None,
-            verifier: None,
-            cert_type: None,
-            cert_fields: None,
-            authorized_amount: None,
-        }));
-    }
-    
-    // TS line 1359: No token found
-    Ok(None)
+        token.originator == originator
+            && token.privileged == Some(privileged)
+            && token_level.as_deref() == Some(sec_level.as_str())
+            && token.protocol.as_deref() == Some(proto_name.as_str())
+            // For security level 2, validate counterparty (TS line 1338)
+            && (sec_level != "2" || token.counterparty.as_deref() == Some(counterparty))
+            // TS lines 1342-1344: Check expiry if needed
+            && (include_expired || !is_token_expired_internal(token.expiry))
+    }))
 }
 
 /// Find a basket access token (DBAP)
@@ -300,14 +306,6 @@ pub async fn find_protocol_token(
 /// # Returns
 ///
 /// Optional permission token if found
-///
-/// # TODO
-///
-/// This requires integration with:
-/// - underlying.listOutputs() to query tokens
-/// - Transaction.fromBEEF() to parse transactions
-/// - PushDrop.decode() to decode locking scripts
-/// - decryptPermissionTokenField() to decrypt fields
 pub async fn find_basket_token(
     underlying: &dyn WalletInterface,
     admin_originator: &str,
@@ -316,129 +314,19 @@ pub async fn find_basket_token(
     include_expired: bool,
 ) -> WalletResult<Option<PermissionToken>> {
     // TS lines 1451-1459: Query outputs with 2 tags
-    // const result = await this.underlying.listOutputs(
-    //   {
-    //     basket: BASKET_MAP.basket,
-    //     tags: [`originator ${originator}`, `basket ${basket}`],
-    //     tagQueryMode: 'all',
-    //     include: 'entire transactions'
-    //   },
-    //   this.adminOriginator
-    // )
-    
     let tags = vec![
         format!("originator {}", originator),
         format!("basket {}", basket),
     ];
     
-    // TS lines 1451-1459: Query outputs from storage
-    let basket_name = get_admin_basket_name(PermissionType::Basket);
-    let result = underlying.list_outputs(
-        json!({
-            "basket": basket_name,
-            "tags": tags,
-            "tagQueryMode": "all",
-            "include": "entire transactions"
-        }),
-        Some(admin_originator)
-    ).await?;
-    
-    // TS lines 1461-1488: Loop through results, decode PushDrop, decrypt fields
-    let empty_vec = vec![];
-    let outputs = result["outputs"].as_array().unwrap_or(&empty_vec);
-    
-    for output in outputs {
-        // TS line 1462: Split outpoint into txid and outputIndex
-        let outpoint = output["outpoint"].as_str().unwrap_or("");
-        let parts: Vec<&str> = outpoint.split('.').collect();
-        if parts.len() != 2 {
-            continue;
-        }
-        
-        let txid = parts[0];
-        let output_index_str = parts[1];
-        let output_index: usize = match output_index_str.parse() {
-            Ok(idx) => idx,
-            Err(_) => continue,
-        };
-        
-        // SIMPLIFIED ARCHITECTURE: Extract fields from storage metadata
-        // Frontend (ts-sdk) already parsed PushDrop and stored fields in customInstructions
-        
-        // Extract custom instructions (TS line 1463-1468 equivalent)
-        let custom = match output["customInstructions"].as_object() {
-            Some(c) => c,
-            None => continue, // No custom instructions, skip
-        };
-        
-        let fields = match custom["fields"].as_array() {
-            Some(f) => f,
-            None => continue, // No fields, skip
-        };
-        
-        // Need 3 fields for basket token (TS line 1464-1465)
-        if fields.len() < 3 {
-            continue;
-        }
-        
-        // TS lines 1470-1472: Decrypt all 3 fields
-        let domain_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[0].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in domain: {}", e)))?;
-        
-        let expiry_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[1].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in expiry: {}", e)))?;
-        let expiry_decoded: i64 = expiry_str.parse().unwrap_or(0);
-        
-        let basket_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[2].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in basket: {}", e)))?;
-        
+    // TS lines 1461-1488: Decode and return the first match
+    let tokens = list_permission_tokens(underlying, admin_originator, PermissionType::Basket, &tags, 0, None).await?;
+    Ok(tokens.into_iter().find(|token| {
         // TS lines 1473-1474: Validate matches and check expiry
-        if domain_decoded != originator || basket_decoded != basket {
-            continue;
-        }
-        
-        if !include_expired && is_token_expired_internal(expiry_decoded) {
-            continue;
-        }
-        
-        // TS lines 1476-1485: Return the found token
-        return Ok(Some(PermissionToken {
-            tx: vec![], // Frontend has BEEF, not needed here
-            txid: txid.to_string(),
-            output_index: output_index as u32,
-            output_script: output["lockingScript"].as_str().unwrap_or("").to_string(),
-            satoshis: output["satoshis"].as_i64().unwrap_or(0),
-            originator: originator.to_string(),
-            privileged: None,
-            protocol: None,
-            security_level: None,
-            expiry: expiry_decoded,
-            counterparty: None,
-            basket_name: Some(basket_decoded),
-            verifier: None,
-            cert_type: None,
-            cert_fields: None,
-            authorized_amount: None,
-        }));
-    }
-    
-    // TS line 1487: No token found
-    Ok(None)
+        token.originator == originator
+            && token.basket_name.as_deref() == Some(basket)
+            && (include_expired || !is_token_expired_internal(token.expiry))
+    }))
 }
 
 /// Find a certificate access token (DCAP)
@@ -460,15 +348,6 @@ pub async fn find_basket_token(
 /// # Returns
 ///
 /// Optional permission token if found
-///
-/// # TODO
-///
-/// This requires integration with:
-/// - underlying.listOutputs() to query tokens
-/// - Transaction.fromBEEF() to parse transactions
-/// - PushDrop.decode() to decode locking scripts
-/// - decryptPermissionTokenField() to decrypt fields
-/// - JSON parsing for fields array
 pub async fn find_certificate_token(
     underlying: &dyn WalletInterface,
     admin_originator: &str,
@@ -480,21 +359,6 @@ pub async fn find_certificate_token(
     include_expired: bool,
 ) -> WalletResult<Option<PermissionToken>> {
     // TS lines 1499-1507: Query outputs with 4 tags
-    // const result = await this.underlying.listOutputs(
-    //   {
-    //     basket: BASKET_MAP.certificate,
-    //     tags: [
-    //       `originator ${originator}`,
-    //       `privileged ${!!privileged}`,
-    //       `type ${certType}`,
-    //       `verifier ${verifier}`
-    //     ],
-    //     tagQueryMode: 'all',
-    //     include: 'entire transactions'
-    //   },
-    //   this.adminOriginator
-    // )
-    
     let tags = vec![
         format!("originator {}", originator),
         format!("privileged {}", privileged),
@@ -502,153 +366,20 @@ pub async fn find_certificate_token(
         format!("verifier {}", verifier),
     ];
     
-    // TS lines 1499-1507: Query outputs from storage
-    let basket_name = get_admin_basket_name(PermissionType::Certificate);
-    let result = underlying.list_outputs(
-        json!({
-            "basket": basket_name,
-            "tags": tags,
-            "tagQueryMode": "all",
-            "include": "entire transactions"
-        }),
-        Some(admin_originator)
-    ).await?;
-    
-    // TS lines 1509-1556: Loop through results, decode PushDrop, decrypt fields
-    let empty_vec = vec![];
-    let outputs = result["outputs"].as_array().unwrap_or(&empty_vec);
-    
-    for output in outputs {
-        // TS line 1510: Split outpoint into txid and outputIndex
-        let outpoint = output["outpoint"].as_str().unwrap_or("");
-        let parts: Vec<&str> = outpoint.split('.').collect();
-        if parts.len() != 2 {
-            continue;
-        }
-        
-        let txid = parts[0];
-        let output_index_str = parts[1];
-        let output_index: usize = match output_index_str.parse() {
-            Ok(idx) => idx,
-            Err(_) => continue,
-        };
-        
-        // SIMPLIFIED ARCHITECTURE: Extract fields from storage metadata
-        // Frontend (ts-sdk) already parsed PushDrop and stored fields in customInstructions
-        
-        // Extract custom instructions (TS line 1511-1514 equivalent)
-        let custom = match output["customInstructions"].as_object() {
-            Some(c) => c,
-            None => continue, // No custom instructions, skip
-        };
-        
-        let token_fields = match custom["fields"].as_array() {
-            Some(f) => f,
-            None => continue, // No fields, skip
-        };
-        
-        // Need 6 fields for certificate token (TS line 1512-1513)
-        if token_fields.len() < 6 {
-            continue;
-        }
-        
-        // TS lines 1516-1520: Decrypt 5 string fields
-        let domain_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                token_fields[0].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in domain: {}", e)))?;
-        
-        let expiry_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                token_fields[1].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in expiry: {}", e)))?;
-        let expiry_decoded: i64 = expiry_str.parse().unwrap_or(0);
-        
-        let priv_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                token_fields[2].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in privileged: {}", e)))?;
-        let priv_decoded = priv_str == "true";
-        
-        let type_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                token_fields[3].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in type: {}", e)))?;
-        
-        let verifier_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                token_fields[5].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in verifier: {}", e)))?;
-        
-        // TS lines 1522-1523: Decrypt and parse fields JSON array
-        let fields_json_bytes = decrypt_permission_token_field(
-            underlying,
-            admin_originator,
-            token_fields[4].as_str().unwrap_or("")
-        ).await?;
-        let fields_json_str = String::from_utf8(fields_json_bytes)
-            .map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in fields JSON: {}", e)))?;
-        let all_fields: Vec<String> = serde_json::from_str(&fields_json_str)
-            .map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid JSON in fields: {}", e)))?;
-        
+    // TS lines 1509-1556: Decode and return the first match
+    let tokens = list_permission_tokens(underlying, admin_originator, PermissionType::Certificate, &tags, 0, None).await?;
+    Ok(tokens.into_iter().find(|token| {
+        // TS lines 1533-1537: Check if 'fields' is a subset of the token's fields
+        let all_fields = token.cert_fields.as_deref().unwrap_or_default();
         // TS lines 1525-1532: Validate all fields match
-        if domain_decoded != originator
-            || priv_decoded != privileged
-            || type_decoded != cert_type
-            || verifier_decoded != verifier
-        {
-            continue;
-        }
-        
-        // TS lines 1533-1537: Check if 'fields' is a subset of 'allFields'
-        let all_fields_set: std::collections::HashSet<_> = all_fields.iter().map(|s| s.as_str()).collect();
-        if !fields.iter().all(|f| all_fields_set.contains(f.as_str())) {
-            continue;
-        }
-        
-        // TS lines 1538-1540: Check expiry
-        if !include_expired && is_token_expired_internal(expiry_decoded) {
-            continue;
-        }
-        
-        // TS lines 1541-1553: Return the found token
-        return Ok(Some(PermissionToken {
-            tx: vec![], // Frontend has BEEF, not needed here
-            txid: txid.to_string(),
-            output_index: output_index as u32,
-            output_script: output["lockingScript"].as_str().unwrap_or("").to_string(),
-            satoshis: output["satoshis"].as_i64().unwrap_or(0),
-            originator: originator.to_string(),
-            privileged: Some(priv_decoded),
-            protocol: None,
-            security_level: None,
-            expiry: expiry_decoded,
-            counterparty: None,
-            basket_name: None,
-            verifier: Some(verifier_decoded),
-            cert_type: Some(type_decoded),
-            cert_fields: Some(all_fields),
-            authorized_amount: None,
-        }));
-    }
-    
-    // TS line 1555: No token found
-    Ok(None)
+        token.originator == originator
+            && token.privileged == Some(privileged)
+            && token.cert_type.as_deref() == Some(cert_type)
+            && token.verifier.as_deref() == Some(verifier)
+            && fields.iter().all(|f| all_fields.contains(f))
+            // TS lines 1538-1540: Check expiry
+            && (include_expired || !is_token_expired_internal(token.expiry))
+    }))
 }
 
 /// Find a spending authorization token (DSAP)
@@ -665,136 +396,154 @@ pub async fn find_certificate_token(
 /// # Returns
 ///
 /// Optional permission token if found
-///
-/// # TODO
-///
-/// This requires integration with:
-/// - underlying.listOutputs() to query tokens
-/// - Transaction.fromBEEF() to parse transactions
-/// - PushDrop.decode() to decode locking scripts
-/// - decryptPermissionTokenField() to decrypt fields
 pub async fn find_spending_token(
     underlying: &dyn WalletInterface,
     admin_originator: &str,
     originator: &str,
 ) -> WalletResult<Option<PermissionToken>> {
     // TS lines 1560-1568: Query outputs with 1 tag
-    // const result = await this.underlying.listOutputs(
-    //   {
-    //     basket: BASKET_MAP.spending,
-    //     tags: [`originator ${originator}`],
-    //     tagQueryMode: 'all',
-    //     include: 'entire transactions'
-    //   },
-    //   this.adminOriginator
-    // )
-    
     let tags = vec![format!("originator {}", originator)];
     
-    // TS lines 1560-1568: Query outputs from storage
-    let basket_name = get_admin_basket_name(PermissionType::Spending);
-    let result = underlying.list_outputs(
-        json!({
-            "basket": basket_name,
-            "tags": tags,
-            "tagQueryMode": "all",
-            "include": "entire transactions"
-        }),
-        Some(admin_originator)
-    ).await?;
-    
-    // TS lines 1570-1594: Loop through results, decode PushDrop, decrypt fields
-    let empty_vec = vec![];
-    let outputs = result["outputs"].as_array().unwrap_or(&empty_vec);
-    
-    for output in outputs {
-        // TS line 1571: Split outpoint into txid and outputIndex
-        let outpoint = output["outpoint"].as_str().unwrap_or("");
-        let parts: Vec<&str> = outpoint.split('.').collect();
-        if parts.len() != 2 {
-            continue;
-        }
-        
-        let txid = parts[0];
-        let output_index_str = parts[1];
-        let output_index: usize = match output_index_str.parse() {
-            Ok(idx) => idx,
-            Err(_) => continue,
-        };
-        
-        // SIMPLIFIED ARCHITECTURE: Extract fields from storage metadata
-        // Frontend (ts-sdk) already parsed PushDrop and stored fields in customInstructions
-        
-        // Extract custom instructions (TS line 1572-1576 equivalent)
-        let custom = match output["customInstructions"].as_object() {
-            Some(c) => c,
-            None => continue, // No custom instructions, skip
-        };
-        
-        let fields = match custom["fields"].as_array() {
-            Some(f) => f,
-            None => continue, // No fields, skip
-        };
-        
-        // Need 2 fields for spending token (TS line 1573-1574)
-        if fields.len() < 2 {
-            continue;
+    // TS lines 1570-1594: Decode and return the first match
+    let tokens = list_permission_tokens(underlying, admin_originator, PermissionType::Spending, &tags, 0, None).await?;
+    Ok(tokens.into_iter().find(|token| token.originator == originator))
+}
+
+/// Find the token covering `originator`: its own, or a parent domain's
+///
+/// `find` looks up the token granted to one domain, expired tokens included.
+/// An unexpired token of the originator's own wins; failing that, the nearest
+/// parent domain holding an unexpired token covers it: a grant to
+/// `example.com` extends to `app.example.com`, never the other way round.
+/// Otherwise the originator's own expired token, if any, is returned so that
+/// it can be renewed. Used for protocol, basket and certificate permissions;
+/// spending authorizations stay per originator.
+pub async fn find_covering_token<F, Fut>(originator: &str, find: F) -> WalletResult<Option<PermissionToken>>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = WalletResult<Option<PermissionToken>>>,
+{
+    let own = find(originator.to_string()).await?;
+    if own.as_ref().is_some_and(|token| !is_token_expired_internal(token.expiry)) {
+        return Ok(own);
+    }
+    let Ok(originator) = Originator::parse(originator) else {
+        return Ok(own);
+    };
+    for parent in originator.parent_domains() {
+        if let Some(token) = find(parent.to_string()).await? {
+            if originator.is_same_or_subdomain_of(&parent) && !is_token_expired_internal(token.expiry) {
+                return Ok(Some(token));
+            }
         }
-        
-        // TS lines 1578-1579: Decrypt domain and validate
-        let domain_decoded = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[0].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in domain: {}", e)))?;
-        
-        if domain_decoded != originator {
-            continue;
+    }
+    Ok(own)
+}
+
+/// Labels the manager adds to every action it creates
+///
+/// Reference: TS createAction (WalletPermissionsManager.ts): `admin originator <domain>`
+/// and `admin month <YYYY-MM>`, which let the admin list an app's actions and
+/// total its spending for the month
+pub fn originator_action_labels(originator: &str) -> Vec<String> {
+    vec![
+        format!("admin originator {}", originator),
+        format!("admin month {}", get_current_month_utc()),
+    ]
+}
+
+/// Actions fetched per listActions page when summing spending
+const SPENT_PAGE_SIZE: u32 = 100;
+
+/// Satoshis an action took out of the wallet
+///
+/// An action's `satoshis` is its net effect on the wallet balance, negative
+/// for spending; incoming actions spend nothing.
+pub fn action_spend(action: &serde_json::Value) -> i64 {
+    action["satoshis"].as_i64().map(|satoshis| (-satoshis).max(0)).unwrap_or(0)
+}
+
+/// Line items for what a createAction call spends on the originator's behalf
+///
+/// Reference: TS createAction spending check (WalletPermissionsManager.ts)
+///
+/// - `output`: each output the originator asks for
+/// - `input`: each input the originator supplies, valued from `inputBEEF`
+///   (inputs whose source isn't in the BEEF are left out, overstating the spend)
+/// - `fee`: the fee `fee_model` asks for the originator's inputs and outputs;
+///   the wallet's own change inputs and outputs aren't counted
+pub fn create_action_line_items(
+    args: &serde_json::Value,
+    fee_model: &StorageFeeModel,
+) -> WalletResult<Vec<SpendingLineItem>> {
+    let args: CreateActionArgs = serde_json::from_value(args.clone())
+        .map_err(|e| WalletError::invalid_parameter("args", format!("valid createAction args: {}", e)))?;
+    let beef = args.input_beef.as_deref()
+        .map(crate::beef::Beef::from_binary)
+        .transpose()
+        .map_err(|e| WalletError::invalid_parameter("inputBEEF", format!("valid BEEF: {}", e)))?;
+    
+    let mut line_items = Vec::new();
+    let mut unlocking_sizes = Vec::new();
+    for input in args.inputs.iter().flatten() {
+        unlocking_sizes.push(match (&input.unlocking_script, input.unlocking_script_length) {
+            (Some(script), _) => script.len() / 2,
+            (None, Some(length)) => length as usize,
+            (None, None) => CHANGE_UNLOCKING_SCRIPT_LENGTH,
+        });
+        
+        let outpoint = parse_wallet_outpoint(&input.outpoint)?;
+        let source_satoshis = beef.as_ref()
+            .and_then(|beef| beef.find_txid(&outpoint.txid))
+            .and_then(|btx| btx.raw_tx.as_deref())
+            .and_then(|raw_tx| crate::transaction::Transaction::from_bytes(raw_tx).ok())
+            .and_then(|tx| tx.outputs.get(outpoint.vout as usize).map(|o| o.value));
+        if let Some(satoshis) = source_satoshis {
+            line_items.push(SpendingLineItem {
+                item_type: "input".to_string(),
+                description: input.input_description.clone(),
+                satoshis,
+            });
         }
-        
-        // TS lines 1580-1581: Decrypt and parse authorized amount
-        let amt_str = String::from_utf8(
-            decrypt_permission_token_field(
-                underlying,
-                admin_originator,
-                fields[1].as_str().unwrap_or("")
-            ).await?
-        ).map_err(|e| WalletError::new("WERR_INVALID_DATA", format!("Invalid UTF-8 in amount: {}", e)))?;
-        let authorized_amount: i64 = amt_str.parse().unwrap_or(0);
-        
-        // TS lines 1583-1592: Return the found token
-        return Ok(Some(PermissionToken {
-            tx: vec![], // Frontend has BEEF, not needed here
-            txid: txid.to_string(),
-            output_index: output_index as u32,
-            output_script: output["lockingScript"].as_str().unwrap_or("").to_string(),
-            satoshis: output["satoshis"].as_i64().unwrap_or(0),
-            originator: originator.to_string(),
-            privileged: None,
-            protocol: None,
-            security_level: None,
-            expiry: 0,  // TS line 1591: Not time-limited, monthly authorization
-            counterparty: None,
-            basket_name: None,
-            verifier: None,
-            cert_type: None,
-            cert_fields: None,
-            authorized_amount: Some(authorized_amount),
-        }));
     }
     
-    // TS line 1594: No token found
-    Ok(None)
+    let mut locking_sizes = Vec::new();
+    for output in args.outputs.iter().flatten() {
+        locking_sizes.push(output.locking_script.len() / 2);
+        line_items.push(SpendingLineItem {
+            item_type: "output".to_string(),
+            description: output.output_description.clone(),
+            satoshis: output.satoshis,
+        });
+    }
+    
+    line_items.push(SpendingLineItem {
+        item_type: "fee".to_string(),
+        description: "Network fee".to_string(),
+        satoshis: fee_for_size(fee_model, transaction_size(&unlocking_sizes, &locking_sizes)),
+    });
+    Ok(line_items)
+}
+
+/// Net satoshis the line items take out of the wallet: outputs and fee,
+/// less the inputs the originator supplied
+pub fn line_items_net_spend(line_items: &[SpendingLineItem]) -> i64 {
+    line_items.iter()
+        .map(|item| if item.item_type == "input" { -item.satoshis } else { item.satoshis })
+        .sum()
 }
 
 /// Query how much has been spent this month for a spending token
 ///
 /// Reference: TS querySpentSince (WalletPermissionsManager.ts lines 1609-1621)
 ///
-/// Returns the total spending for an originator in the current calendar month (UTC).
-/// This is used to enforce monthly spending limits.
+/// Returns the total spending for an originator in the current calendar month (UTC),
+/// the window a DSAP token's authorized amount applies to. Actions are found
+/// by the `admin originator` and `admin month` labels the manager adds in
+/// `create_action`. The month's actions are summed afresh on every call:
+/// aborted and failed actions drop out of listActions, so a running total
+/// or a saved offset would go stale. The permission cache spares repeated
+/// checks the listing.
 ///
 /// # Arguments
 ///
@@ -803,55 +552,36 @@ pub async fn find_spending_token(
 /// # Returns
 ///
 /// Total satoshis spent this month
-///
-/// # TODO
-///
-/// This requires integration with:
-/// - underlying.listActions() to query spending actions
-/// - Label filtering with 'admin originator {originator}' and 'admin month {YYYY-MM}'
 pub async fn query_spent_since(
     underlying: &dyn WalletInterface,
     admin_originator: &str,
     token: &PermissionToken,
 ) -> WalletResult<i64> {
     // TS lines 1613-1620: Query actions with labels
-    // const { actions } = await this.underlying.listActions(
-    //   {
-    //     labels: [
-    //       `admin originator ${token.originator}`,
-    //       `admin month ${this.getCurrentMonthYearUTC()}`
-    //     ],
-    //     labelQueryMode: 'all'
-    //   },
-    //   this.adminOriginator
-    // )
-    // return actions.reduce((a, e) => a + e.satoshis, 0)
-    
-    let current_month = get_current_month_utc();
-    let labels = vec![
-        format!("admin originator {}", token.originator),
-        format!("admin month {}", current_month),
-    ];
-    
-    // TS lines 1613-1620: Query actions with labels
-    let result = underlying.list_actions(
-        json!({
-            "labels": labels,
-            "labelQueryMode": "all"
-        }),
-        Some(admin_originator)
-    ).await?;
-    
-    // TS line 1620: Sum satoshis from all actions
-    // return actions.reduce((a, e) => a + e.satoshis, 0)
-    let empty_vec = vec![];
-    let actions = result["actions"].as_array().unwrap_or(&empty_vec);
-    
-    let total = actions.iter()
-        .filter_map(|action| action["satoshis"].as_i64())
-        .sum();
-    
-    Ok(total)
+    let labels = originator_action_labels(&token.originator);
+    let mut spent = 0;
+    let mut offset = 0;
+    loop {
+        let result = underlying.list_actions(
+            json!({
+                "labels": labels,
+                "labelQueryMode": "all",
+                "limit": SPENT_PAGE_SIZE,
+                "offset": offset,
+            }),
+            Some(admin_originator)
+        ).await?;
+        
+        // TS line 1620: Sum satoshis from all actions
+        let empty_vec = vec![];
+        let actions = result["actions"].as_array().unwrap_or(&empty_vec);
+        spent += actions.iter().map(action_spend).sum::<i64>();
+        offset += actions.len() as u32;
+        
+        if actions.len() < SPENT_PAGE_SIZE as usize {
+            return Ok(spent);
+        }
+    }
 }
 
 // ============================================================================
@@ -923,4 +653,44 @@ mod tests {
     // async fn test_query_spent_since_structure() {
     //     // Need mock WalletInterface
     // }
+    
+    #[test]
+    fn test_action_spend() {
+        assert_eq!(action_spend(&json!({ "satoshis": -1500 })), 1500);
+        assert_eq!(action_spend(&json!({ "satoshis": 2000 })), 0);
+        assert_eq!(action_spend(&json!({})), 0);
+    }
+    
+    #[test]
+    fn test_create_action_line_items() {
+        use crate::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+        
+        let mut source = Transaction::new();
+        source.add_input(TxInput::new(OutPoint::new("aa".repeat(32), 0)));
+        source.add_output(TxOutput::new(700, vec![0x51]));
+        let mut beef = crate::beef::Beef::new_v2();
+        beef.merge_raw_tx(&source.serialize().unwrap()).unwrap();
+        
+        let args = json!({
+            "description": "Pay for a coffee",
+            "inputBEEF": beef.to_binary().unwrap(),
+            "inputs": [
+                { "outpoint": format!("{}.0", source.txid().unwrap()), "inputDescription": "Voucher", "unlockingScriptLength": 73 },
+                { "outpoint": format!("{}.0", "bb".repeat(32)), "inputDescription": "Unknown", "unlockingScriptLength": 73 },
+            ],
+            "outputs": [
+                { "lockingScript": "76a914000000000000000000000000000000000000000088ac", "satoshis": 1000, "outputDescription": "Coffee" },
+            ],
+        });
+        let fee_model = StorageFeeModel { model: "sat/kb".to_string(), value: Some(1000.0) };
+        let items = create_action_line_items(&args, &fee_model).unwrap();
+        
+        let fee = fee_for_size(&fee_model, transaction_size(&[73, 73], &[25]));
+        assert_eq!(items, vec![
+            SpendingLineItem { item_type: "input".to_string(), description: "Voucher".to_string(), satoshis: 700 },
+            SpendingLineItem { item_type: "output".to_string(), description: "Coffee".to_string(), satoshis: 1000 },
+            SpendingLineItem { item_type: "fee".to_string(), description: "Network fee".to_string(), satoshis: fee },
+        ]);
+        assert_eq!(line_items_net_spend(&items), 300 + fee);
+    }
 }
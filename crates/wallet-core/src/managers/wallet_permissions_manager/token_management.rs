@@ -661,6 +661,60 @@ pub async fn decrypt_permission_token_field(
     Ok(decoded)
 }
 
+/// Encrypt wallet metadata before it goes to storage
+///
+/// Reference: TS maybeEncryptMetadata (WalletPermissionsManager.ts)
+///
+/// Action and output descriptions and custom instructions are encrypted to
+/// the wallet itself under the admin metadata protocol and stored as base64.
+pub async fn encrypt_metadata(
+    underlying: &dyn WalletInterface,
+    admin_originator: &str,
+    plaintext: &str,
+) -> WalletResult<String> {
+    let result = underlying.encrypt(
+        json!({
+            "plaintext": plaintext.as_bytes(),
+            "protocolID": [encryption_protocols::METADATA_SECURITY_LEVEL, encryption_protocols::METADATA_ENCRYPTION],
+            "keyID": encryption_protocols::KEY_ID,
+            "counterparty": encryption_protocols::COUNTERPARTY,
+        }),
+        Some(admin_originator)
+    ).await?;
+    let ciphertext: Vec<u8> = serde_json::from_value(result["ciphertext"].clone())
+        .map_err(|_| WalletError::new("WERR_INVALID_DATA", "Metadata encryption returned no ciphertext"))?;
+    Ok(general_purpose::STANDARD.encode(ciphertext))
+}
+
+/// Decrypt wallet metadata read back from storage
+///
+/// Reference: TS maybeDecryptMetadata (WalletPermissionsManager.ts)
+///
+/// Metadata stored before encryption was enabled isn't ciphertext, so
+/// anything that fails to decrypt to UTF-8 is returned unchanged.
+pub async fn decrypt_metadata(
+    underlying: &dyn WalletInterface,
+    admin_originator: &str,
+    ciphertext: &str,
+) -> String {
+    let Ok(bytes) = general_purpose::STANDARD.decode(ciphertext) else {
+        return ciphertext.to_string();
+    };
+    let result = underlying.decrypt(
+        json!({
+            "ciphertext": bytes,
+            "protocolID": [encryption_protocols::METADATA_SECURITY_LEVEL, encryption_protocols::METADATA_ENCRYPTION],
+            "keyID": encryption_protocols::KEY_ID,
+            "counterparty": encryption_protocols::COUNTERPARTY,
+        }),
+        Some(admin_originator)
+    ).await;
+    result.ok()
+        .and_then(|result| serde_json::from_value::<Vec<u8>>(result["plaintext"].clone()).ok())
+        .and_then(|plaintext| String::from_utf8(plaintext).ok())
+        .unwrap_or_else(|| ciphertext.to_string())
+}
+
 /// Protocol IDs for encryption
 ///
 /// Reference: TS PERM_TOKEN_ENCRYPTION_PROTOCOL (lines 1192-1195)
@@ -696,6 +750,7 @@ pub mod encryption_protocols {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::MockWallet;
     
     #[test]
     fn test_build_tags_protocol() {
@@ -854,43 +909,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_encrypt_decrypt_field() {
-        // Create a mock wallet interface
-        struct MockWallet;
-        #[async_trait::async_trait]
-        impl WalletInterface for MockWallet {
-            async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"actions": []}))
-            }
-            async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"outputs": []}))
-            }
-            async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"publicKey": ""}))
-            }
-            async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"height": 0}))
-            }
-            async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"network": "mainnet"}))
-            }
-            async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"version": "1.0.0"}))
-            }
-        }
-        
-        let mock = MockWallet;
+        let mock = MockWallet::new();
         let plaintext = b"test data";
         
         let encrypted = encrypt_permission_token_field(&mock, "admin", plaintext).await.unwrap();
@@ -914,42 +933,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_build_pushdrop_fields_protocol() {
-        struct MockWallet;
-        #[async_trait::async_trait]
-        impl WalletInterface for MockWallet {
-            async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"actions": []}))
-            }
-            async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"outputs": []}))
-            }
-            async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"publicKey": ""}))
-            }
-            async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"height": 0}))
-            }
-            async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"network": "mainnet"}))
-            }
-            async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"version": "1.0.0"}))
-            }
-        }
-        
-        let mock = MockWallet;
+        let mock = MockWallet::new();
         let request = PermissionRequest {
             permission_type: PermissionType::Protocol,
             originator: "example.com".to_string(),
@@ -991,42 +975,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_build_pushdrop_fields_basket() {
-        struct MockWallet;
-        #[async_trait::async_trait]
-        impl WalletInterface for MockWallet {
-            async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"actions": []}))
-            }
-            async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"outputs": []}))
-            }
-            async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"publicKey": ""}))
-            }
-            async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"height": 0}))
-            }
-            async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"network": "mainnet"}))
-            }
-            async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"version": "1.0.0"}))
-            }
-        }
-        
-        let mock = MockWallet;
+        let mock = MockWallet::new();
         let request = PermissionRequest {
             permission_type: PermissionType::Basket,
             originator: "example.com".to_string(),
@@ -1058,42 +1007,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_build_pushdrop_fields_certificate() {
-        struct MockWallet;
-        #[async_trait::async_trait]
-        impl WalletInterface for MockWallet {
-            async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"actions": []}))
-            }
-            async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"outputs": []}))
-            }
-            async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"publicKey": ""}))
-            }
-            async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"height": 0}))
-            }
-            async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"network": "mainnet"}))
-            }
-            async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"version": "1.0.0"}))
-            }
-        }
-        
-        let mock = MockWallet;
+        let mock = MockWallet::new();
         let request = PermissionRequest {
             permission_type: PermissionType::Certificate,
             originator: "example.com".to_string(),
@@ -1139,42 +1053,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_build_pushdrop_fields_spending() {
-        struct MockWallet;
-        #[async_trait::async_trait]
-        impl WalletInterface for MockWallet {
-            async fn create_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn sign_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn abort_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_actions(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"actions": []}))
-            }
-            async fn internalize_action(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({}))
-            }
-            async fn list_outputs(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"outputs": []}))
-            }
-            async fn get_public_key(&self, _args: serde_json::Value, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"publicKey": ""}))
-            }
-            async fn get_height(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"height": 0}))
-            }
-            async fn get_network(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"network": "mainnet"}))
-            }
-            async fn get_version(&self, _originator: Option<&str>) -> WalletResult<serde_json::Value> {
-                Ok(serde_json::json!({"version": "1.0.0"}))
-            }
-        }
-        
-        let mock = MockWallet;
+        let mock = MockWallet::new();
         let request = PermissionRequest {
             permission_type: PermissionType::Spending,
             originator: "example.com".to_string(),
@@ -8,6 +8,7 @@
 //! - DCAP (Domain Certificate Access Protocol)
 //! - DSAP (Domain Spending Authorization Protocol)
 
+use crate::sdk::errors::{WalletError, WalletResult};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -408,17 +409,26 @@ pub struct PermissionsManagerConfig {
     
     /// For key derivation operations, require "protocol usage" permission?
     ///
+    /// When `false`, public key revelation is never checked, whatever
+    /// `seek_permissions_for_public_key_revelation` says.
+    ///
     /// Reference: TS seekProtocolPermissionsForKeyDerivation (line 253)
     #[serde(rename = "seekProtocolPermissionsForKeyDerivation", default = "default_true")]
     pub seek_protocol_permissions_for_key_derivation: bool,
     
-    /// For certificate operations (acquire, prove, relinquish), require "certificate access" permission?
+    /// For certificate operations (acquire, list, relinquish), require permission?
+    ///
+    /// When `false`, those operations are never checked, whatever their own
+    /// flags say.
     ///
     /// Reference: TS seekCertificatePermissionsForCertificateOps (line 259)
     #[serde(rename = "seekCertificatePermissionsForCertificateOps", default = "default_true")]
     pub seek_certificate_permissions_for_certificate_ops: bool,
     
-    /// For basket operations (listOutputs with basket filter), require "basket access" permission?
+    /// For basket operations (insertion, removal, listing), require "basket access" permission?
+    ///
+    /// When `false`, basket access is never checked, whatever the per-usage
+    /// flags say.
     ///
     /// Reference: TS seekBasketPermissionsForBasketOps (line 265)
     #[serde(rename = "seekBasketPermissionsForBasketOps", default = "default_true")]
@@ -451,6 +461,48 @@ pub struct PermissionsManagerConfig {
     #[serde(rename = "seekBasketListingPermissions", default = "default_true")]
     pub seek_basket_listing_permissions: bool,
     
+    /// For `revealCounterpartyKeyLinkage` and `revealSpecificKeyLinkage`, require permission?
+    ///
+    /// Reference: TS seekPermissionsForKeyLinkageRevelation
+    #[serde(rename = "seekPermissionsForKeyLinkageRevelation", default = "default_true")]
+    pub seek_permissions_for_key_linkage_revelation: bool,
+    
+    /// For `getPublicKey` of a derived key, require "protocol usage" permission?
+    ///
+    /// Reference: TS seekPermissionsForPublicKeyRevelation
+    #[serde(rename = "seekPermissionsForPublicKeyRevelation", default = "default_true")]
+    pub seek_permissions_for_public_key_revelation: bool,
+    
+    /// For `getPublicKey` of the identity key, require permission?
+    ///
+    /// Reference: TS seekPermissionsForIdentityKeyRevelation
+    #[serde(rename = "seekPermissionsForIdentityKeyRevelation", default = "default_true")]
+    pub seek_permissions_for_identity_key_revelation: bool,
+    
+    /// For `discoverByIdentityKey` and `discoverByAttributes`, require permission?
+    ///
+    /// Reference: TS seekPermissionsForIdentityResolution
+    #[serde(rename = "seekPermissionsForIdentityResolution", default = "default_true")]
+    pub seek_permissions_for_identity_resolution: bool,
+    
+    /// For `acquireCertificate`, require permission?
+    ///
+    /// Reference: TS seekCertificateAcquisitionPermissions
+    #[serde(rename = "seekCertificateAcquisitionPermissions", default = "default_true")]
+    pub seek_certificate_acquisition_permissions: bool,
+    
+    /// For `relinquishCertificate`, require permission?
+    ///
+    /// Reference: TS seekCertificateRelinquishmentPermissions
+    #[serde(rename = "seekCertificateRelinquishmentPermissions", default = "default_true")]
+    pub seek_certificate_relinquishment_permissions: bool,
+    
+    /// For `listCertificates`, require permission?
+    ///
+    /// Reference: TS seekCertificateListingPermissions
+    #[serde(rename = "seekCertificateListingPermissions", default = "default_true")]
+    pub seek_certificate_listing_permissions: bool,
+    
     /// When revealing certificate fields, require certificate access permission?
     ///
     /// Reference: TS seekCertificateDisclosurePermissions (lines 299-302)
@@ -462,6 +514,35 @@ pub struct PermissionsManagerConfig {
     /// Reference: TS seekSpendingPermissions (lines 326-329)
     #[serde(rename = "seekSpendingPermissions", default = "default_true")]
     pub seek_spending_permissions: bool,
+    
+    /// When applying labels to new actions (createAction), ask for label permission?
+    ///
+    /// Reference: TS seekPermissionWhenApplyingActionLabels
+    #[serde(rename = "seekPermissionWhenApplyingActionLabels", default = "default_true")]
+    pub seek_permission_when_applying_action_labels: bool,
+    
+    /// When listing actions by label (listActions), ask for label permission?
+    ///
+    /// Reference: TS seekPermissionWhenListingActionsByLabel
+    #[serde(rename = "seekPermissionWhenListingActionsByLabel", default = "default_true")]
+    pub seek_permission_when_listing_actions_by_label: bool,
+    
+    /// How long a confirmed permission is trusted without looking up its token again, in milliseconds
+    ///
+    /// Reference: TS CACHE_TTL_MS
+    ///
+    /// Managers sharing a wallet don't see each other's revocations until
+    /// their entries expire, so shorten this when several run at once.
+    #[serde(rename = "permissionCacheTtlMs", default = "default_permission_cache_ttl_ms")]
+    pub permission_cache_ttl_ms: i64,
+    
+    /// Whether to encrypt action and output descriptions and custom instructions before storage
+    ///
+    /// Reference: TS encryptWalletMetadata
+    ///
+    /// Listing results are decrypted either way.
+    #[serde(rename = "encryptWalletMetadata", default = "default_true")]
+    pub encrypt_wallet_metadata: bool,
 }
 
 impl Default for PermissionsManagerConfig {
@@ -473,13 +554,52 @@ impl Default for PermissionsManagerConfig {
             seek_protocol_permissions_for_key_derivation: true,
             seek_certificate_permissions_for_certificate_ops: true,
             seek_basket_permissions_for_basket_ops: true,
+            seek_permissions_for_key_linkage_revelation: true,
+            seek_permissions_for_public_key_revelation: true,
+            seek_permissions_for_identity_key_revelation: true,
+            seek_permissions_for_identity_resolution: true,
+            seek_certificate_acquisition_permissions: true,
+            seek_certificate_relinquishment_permissions: true,
+            seek_certificate_listing_permissions: true,
             differentiate_privileged_operations: true,
             seek_basket_insertion_permissions: true,
             seek_basket_removal_permissions: true,
             seek_basket_listing_permissions: true,
             seek_certificate_disclosure_permissions: true,
             seek_spending_permissions: true,
+            seek_permission_when_applying_action_labels: true,
+            seek_permission_when_listing_actions_by_label: true,
+            permission_cache_ttl_ms: super::constants::DEFAULT_PERMISSION_CACHE_TTL_MS,
+            encrypt_wallet_metadata: true,
+        }
+    }
+}
+
+impl PermissionsManagerConfig {
+    /// This config with the flags set in `overrides` replaced
+    ///
+    /// Reference: TS constructor `{ ...DEFAULTS, ...config }` (lines 429-451)
+    ///
+    /// `overrides` is a (partial) config object in TS field names, as sent
+    /// across the JSON boundary. Unknown fields are rejected so a misspelt
+    /// flag doesn't silently leave a check enabled or disabled.
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> WalletResult<Self> {
+        let overrides = overrides.as_object()
+            .ok_or_else(|| WalletError::invalid_parameter("config", "an object"))?;
+        let mut merged = serde_json::to_value(self)
+            .map_err(|e| WalletError::internal(format!("Failed to serialize config: {}", e)))?;
+        let fields = merged.as_object_mut().expect("config serializes to an object");
+        for (name, value) in overrides {
+            if !fields.contains_key(name) {
+                return Err(WalletError::invalid_parameter(
+                    format!("config.{}", name),
+                    "a PermissionsManagerConfig field",
+                ));
+            }
+            fields.insert(name.clone(), value.clone());
         }
+        serde_json::from_value(merged)
+            .map_err(|e| WalletError::invalid_parameter("config", format!("a valid config: {}", e)))
     }
 }
 
@@ -488,6 +608,11 @@ fn default_true() -> bool {
     true
 }
 
+/// Helper function for serde default
+fn default_permission_cache_ttl_ms() -> i64 {
+    super::constants::DEFAULT_PERMISSION_CACHE_TTL_MS
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -527,6 +652,22 @@ mod tests {
         assert!(config.seek_basket_permissions_for_basket_ops);
     }
     
+    #[test]
+    fn test_config_overrides() {
+        let config = PermissionsManagerConfig::default()
+            .with_overrides(&serde_json::json!({
+                "seekPermissionsForIdentityResolution": false,
+                "permissionCacheTtlMs": 1000,
+            }))
+            .unwrap();
+        assert!(!config.seek_permissions_for_identity_resolution);
+        assert_eq!(config.permission_cache_ttl_ms, 1000);
+        assert!(config.seek_permissions_for_identity_key_revelation);
+        
+        let err = config.with_overrides(&serde_json::json!({ "seekIdentityResolution": false })).unwrap_err();
+        assert!(err.to_string().contains("seekIdentityResolution"));
+    }
+    
     #[test]
     fn test_grouped_permissions_serde() {
         let permissions = GroupedPermissions {
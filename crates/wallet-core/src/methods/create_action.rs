@@ -69,21 +69,28 @@ use crate::sdk::action::{
     StorageProvidedBy, ValidCreateActionOptions,
 };
 use crate::beef::Beef;
+use crate::methods::get_beef_for_transaction::{get_valid_beef_for_txid, proven_tx_merkle_path, StorageGetBeefOptions};
 use wallet_storage::{
-    StorageError, WalletStorageProvider, AuthId,
+    metrics, StorageError, WalletStorageProvider, AuthId,
     TableOutputBasket, TableOutput, TableTransaction, TableOutputTag,
     TableCommission, FindOutputBasketsArgs, FindOutputsArgs, PartialOutput, OutputUpdates,
-    StorageProvidedBy as WalletStorageProvidedBy, TransactionStatus,
+    StorageProvidedBy as WalletStorageProvidedBy, TransactionStatus, DEFAULT_CHANGE_BASKET, MAX_CONFLICT_RETRIES,
 };
+use super::fee_model::{
+    StorageFeeModel, validate_storage_fee_model,
+    CHANGE_UNLOCKING_SCRIPT_LENGTH, CHANGE_LOCKING_SCRIPT_LENGTH,
+};
+use super::generate_change::{
+    generate_change_sdk_with, ChangeInputAllocator, GenerateChangeSdkChangeInput,
+    GenerateChangeSdkInput, GenerateChangeSdkOutput, GenerateChangeSdkParams,
+    MaxPossibleSatoshisAdjustment,
+};
+use super::random_vals::RandomVals;
+use async_trait::async_trait;
 use chrono::Utc;
-use base64::Engine as _;
-
-/// Fee model type (simplified for now - will expand when fee models are implemented)
-#[derive(Debug, Clone)]
-pub struct StorageFeeModel {
-    pub model: String,
-    pub value: f64,
-}
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{debug, field, instrument, warn, Span};
 
 /// Context for transaction creation
 struct CreateTransactionContext {
@@ -99,6 +106,9 @@ struct CreateTransactionContext {
     /// Outputs that shouldn't send change
     no_send_change_in: Vec<TableOutput>,
     
+    /// Change outputs spent unconditionally by a consolidation
+    consolidate: Vec<TableOutput>,
+    
     /// Available change output count
     available_change_count: i64,
     
@@ -189,7 +199,48 @@ pub async fn create_action(
     storage: &mut dyn WalletStorageProvider,
     auth: &AuthId,
     vargs: ValidCreateActionArgs,
-    _originator: Option<String>,
+    originator: Option<String>,
+) -> Result<StorageCreateActionResult, StorageError> {
+    // Per-call override, else the basket configured for the originator
+    let change_basket_name = match &vargs.options.change_basket {
+        Some(name) => name.clone(),
+        None => storage.get_change_baskets().for_originator(originator.as_deref()).to_string(),
+    };
+    create_transaction(storage, auth, vargs, Vec::new(), &change_basket_name).await
+}
+
+/// Create a transaction merging `consolidate` change outputs into fewer, larger ones
+///
+/// Runs the createAction steps for an action with no inputs or outputs of its
+/// own, spending every output in `consolidate` (spendable change in the
+/// `default` basket). The number of new change outputs is steered towards the
+/// basket's `numberOfDesiredUTXOs`, but is always fewer than the outputs spent.
+pub async fn create_consolidation_action(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    vargs: ValidCreateActionArgs,
+    consolidate: Vec<TableOutput>,
+) -> Result<StorageCreateActionResult, StorageError> {
+    if consolidate.len() < 2 {
+        return Err(StorageError::InvalidArg(
+            "consolidation requires at least two change outputs".to_string()
+        ));
+    }
+    if !vargs.inputs.is_empty() || !vargs.outputs.is_empty() {
+        return Err(StorageError::InvalidArg(
+            "consolidation actions have no inputs or outputs of their own".to_string()
+        ));
+    }
+    create_transaction(storage, auth, vargs, consolidate, DEFAULT_CHANGE_BASKET).await
+}
+
+#[instrument(skip_all, fields(user_id = auth.user_id, reference = field::Empty, transaction_id = field::Empty))]
+async fn create_transaction(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    vargs: ValidCreateActionArgs,
+    consolidate: Vec<TableOutput>,
+    change_basket_name: &str,
 ) -> Result<StorageCreateActionResult, StorageError> {
     // Verify this is a new transaction
     if !vargs.is_new_tx {
@@ -198,15 +249,13 @@ pub async fn create_action(
         ));
     }
     
-    let user_id = auth.user_id.ok_or_else(|| {
-        StorageError::Unauthorized("user_id required".to_string())
-    })?;
+    let user_id = auth.user_id_required()?;
     
     // STEP 1: Validate Required Inputs (line 88)
     // - Verify proofs exist in inputBEEF or trustSelf='known'
     // - Parse locking scripts and satoshis
     // - Build BEEF structure
-    let (_storage_beef, _beef, xinputs) = validate_required_inputs(storage, user_id, &vargs).await?;
+    let (beef, _storage_beef, xinputs) = validate_required_inputs(storage, auth, &vargs).await?;
     
     // STEP 2: Validate Required Outputs (line 89)
     // - Validate locking scripts
@@ -215,25 +264,25 @@ pub async fn create_action(
     let xoutputs = validate_required_outputs(storage, user_id, &vargs)?;
     
     // STEP 3: Get Change Basket (lines 91-97)
-    // - Find or create 'default' basket
+    // - Find the user's change basket ('default' unless configured)
     // - Will be used for change outputs
-    let change_basket_name = "default";
-    let change_basket = find_output_basket(storage, user_id, change_basket_name).await?;
+    let change_basket = find_change_basket(storage, auth, change_basket_name).await?;
     
     // STEP 4: Validate noSendChange (line 99)
     // - Check which outputs shouldn't send change
-    let no_send_change_in = validate_no_send_change(storage, user_id, &vargs, &change_basket).await?;
+    let no_send_change_in = validate_no_send_change(storage, auth, &vargs, &change_basket).await?;
     
     // STEP 5: Count Available Change (line 101)
     // - Count spendable outputs in change basket
     let basket_id = change_basket.basket_id;
     let available_change_count = storage.count_change_inputs(user_id, basket_id, !vargs.is_delayed).await?;
+    debug!(step = 5, basket_id, available_change_count, "counted available change");
     
     // STEP 6: Validate Fee Model (line 103)
-    // TODO: Implement fee model validation from storage settings
-    let fee_model = StorageFeeModel {
-        model: "sat/kb".to_string(),
-        value: 0.5,
+    // - Per-call override takes precedence over the storage fee model
+    let fee_model = match &vargs.options.fee_model {
+        Some(fee_model) => validate_storage_fee_model(Some(fee_model))?,
+        None => validate_storage_fee_model(Some(&storage.get_fee_model()))?,
     };
     
     // STEP 7: Create Transaction Record (line 105)
@@ -242,7 +291,13 @@ pub async fn create_action(
     // - Generate reference ID
     // Convert storage_beef to binary for storage
     let storage_beef_bytes = None; // TODO: storage_beef.to_binary().ok();
-    let new_tx = create_new_tx_record(storage, user_id, &vargs, storage_beef_bytes).await?;
+    // One source of randomness for every step, so `randomVals` make the
+    // whole action reproducible
+    let mut random = RandomVals::new(vargs.random_vals.clone());
+    let new_tx = create_new_tx_record(storage, user_id, &vargs, storage_beef_bytes, &mut random).await?;
+    Span::current()
+        .record("reference", new_tx.reference.as_str())
+        .record("transaction_id", new_tx.transaction_id);
     
     // Build context for remaining steps
     let mut ctx = CreateTransactionContext {
@@ -250,6 +305,7 @@ pub async fn create_action(
         xoutputs,
         change_basket,
         no_send_change_in,
+        consolidate,
         available_change_count,
         fee_model,
         transaction_id: new_tx.transaction_id,
@@ -259,11 +315,14 @@ pub async fn create_action(
     // - Calculate required satoshis (outputs + fees)
     // - Select and LOCK change outputs
     // - Generate new change outputs if needed
-    let funding_result = fund_new_transaction(storage, user_id, &vargs, &mut ctx).await?;
+    let funding_started = Instant::now();
+    let funding_result = fund_new_transaction(storage, user_id, &vargs, &mut ctx, &mut random).await?;
+    metrics::observe_since(metrics::CHANGE_ALLOCATION_SECONDS, &[], funding_started);
     
     // STEP 9: Adjust maxPossibleSatoshis if needed (lines 120-124)
     if let Some(adjustment) = funding_result.max_possible_satoshis_adjustment {
         ctx.xoutputs[adjustment.fixed_output_index].output.satoshis = adjustment.satoshis;
+        debug!(step = 9, vout = adjustment.fixed_output_index, satoshis = adjustment.satoshis, "adjusted maxPossibleSatoshis output");
     }
     
     // STEP 10: Calculate Net Satoshis (lines 127-129)
@@ -271,23 +330,27 @@ pub async fn create_action(
     let satoshis = funding_result.change_outputs.iter().map(|o| o.satoshis).sum::<i64>()
         - funding_result.allocated_change.iter().map(|o| o.satoshis).sum::<i64>();
     
+    debug!(
+        step = 10,
+        satoshis,
+        allocated_change = funding_result.allocated_change.len(),
+        change_outputs = funding_result.change_outputs.len(),
+        "funded transaction"
+    );
     storage.update_transaction(new_tx.transaction_id, satoshis).await?;
     
     // STEP 11: Create New Outputs (line 131)
     // - Insert user outputs + change outputs
     // - Create basket/tag associations
-    let output_result = create_new_outputs(storage, user_id, &vargs, &ctx, &funding_result.change_outputs).await?;
+    let output_result = create_new_outputs(storage, user_id, &vargs, &ctx, &funding_result.change_outputs, &mut random).await?;
     
     // STEP 12: Merge BEEFs (line 133)
     // - Combine inputBEEF + change BEEFs
-    // TODO: Convert beef to binary when to_binary() is implemented
-    let beef_bytes = Vec::new(); // beef.to_binary().unwrap_or_default();
     let input_beef = merge_allocated_change_beefs(
         storage,
-        user_id,
         &vargs,
         &funding_result.allocated_change,
-        beef_bytes,
+        beef,
     ).await?;
     
     // STEP 13: Create Result Inputs (line 135)
@@ -295,7 +358,7 @@ pub async fn create_action(
     // - Add locking scripts and derivation info
     let inputs = create_new_inputs(
         storage,
-        user_id,
+        auth,
         &vargs,
         &ctx,
         &funding_result.allocated_change,
@@ -316,6 +379,7 @@ pub async fn create_action(
             None
         },
     };
+    debug!(step = 14, inputs = result.inputs.len(), outputs = result.outputs.len(), "created action");
     
     Ok(result)
 }
@@ -338,10 +402,11 @@ pub async fn create_action(
 ///    - Verify not spending change
 ///    - Verify spendable
 ///    - Parse locking script and satoshis from BEEF or storage
-/// 7. Return (storageBeef, beef, xinputs)
+/// 7. Return (beef, storageBeef, xinputs)
+#[instrument(level = "debug", skip_all, fields(step = 1))]
 async fn validate_required_inputs(
     storage: &dyn WalletStorageProvider,
-    user_id: i64,
+    auth: &AuthId,
     vargs: &ValidCreateActionArgs,
 ) -> Result<(Beef, Beef, Vec<XValidCreateActionInput>), StorageError> {
     // TS line 568: const beef = new Beef()
@@ -418,8 +483,7 @@ async fn validate_required_inputs(
     
     // TS line 612: Verify BEEF with ChainTracker
     // TODO: Implement when ChainTracker is available
-    // For now, log a warning
-    eprintln!("WARNING: BEEF verification skipped - ChainTracker not yet implemented");
+    warn!("inputBEEF verification skipped: no ChainTracker is available to createAction");
     
     // TS line 620: Clone beef for storage
     let storage_beef = beef.clone_beef();
@@ -438,7 +502,7 @@ async fn validate_required_inputs(
             transaction_id: None,
         };
         let args = FindOutputsArgs {
-            user_id,
+            user_id: auth.user_id_required()?,
             since: None,
             paged: None,
             order_descending: None,
@@ -447,8 +511,7 @@ async fn validate_required_inputs(
             tx_status: None,
         };
         
-        let auth = AuthId::new("").with_user_id(user_id);
-        let outputs = storage.find_outputs_auth(&auth, &args).await?;
+        let outputs = storage.find_outputs_auth(auth, &args).await?;
         let outputs: Vec<_> = outputs.into_iter().filter(|o| o.vout == vout).collect();
         
         if let Some(output) = outputs.into_iter().next() {
@@ -471,7 +534,14 @@ async fn validate_required_inputs(
                     format!("{}.{}: spendable output unless noSend is true", txid, vout)
                 ));
             }
-            
+
+            // Outputs an application has locked are reserved for it
+            if let Some(lock) = storage.find_output_lock(output.output_id).await? {
+                return Err(StorageError::InvalidArg(
+                    format!("inputs[{}]: output {}.{} is locked: {}", input.vin, txid, vout, lock.reason)
+                ));
+            }
+
             // TS lines 638-639: Set input data from storage
             input.satoshis = satoshis;
             input.locking_script = locking_script;
@@ -492,14 +562,13 @@ async fn validate_required_inputs(
                     )
                 })?;
                 
-                let _merged_btx = beef.merge_raw_tx(&raw_tx).map_err(|e| {
+                let merged = match &proven_or_raw.proven {
+                    Some(proven) => beef.merge_proven_tx(&raw_tx, proven_tx_merkle_path(proven)?),
+                    None => beef.merge_raw_tx(&raw_tx),
+                };
+                merged.map_err(|e| {
                     StorageError::InvalidArg(format!("Failed to merge raw tx: {}", e))
                 })?;
-                
-                // TODO: Merge BUMP if proven (requires MerklePath from EntityProvenTx)
-                // if let Some(proven) = proven_or_raw.proven {
-                //     beef.mergeBump(EntityProvenTx::new(proven).getMerklePath());
-                // }
             }
             
             // TS lines 650-653: Get output from parsed transaction
@@ -522,6 +591,7 @@ async fn validate_required_inputs(
 /// - Assigning vout numbers sequentially
 /// - Setting providedBy='you' for user outputs
 /// - Adding storage commission output if configured
+#[instrument(level = "debug", skip_all, fields(step = 2))]
 fn validate_required_outputs(
     _storage: &dyn WalletStorageProvider,
     _user_id: i64,
@@ -557,19 +627,18 @@ fn validate_required_outputs(
 /// Uses findOutputBaskets with name filter
 async fn find_output_basket(
     storage: &dyn WalletStorageProvider,
-    user_id: i64,
+    auth: &AuthId,
     name: &str,
 ) -> Result<TableOutputBasket, StorageError> {
     // Build query args matching TS: { partial: { userId, name: changeBasketName } }
     let args = FindOutputBasketsArgs {
-        user_id,
+        user_id: auth.user_id_required()?,
         name: Some(name.to_string()),
         since: None,
         paged: None,
     };
     
-    let auth = AuthId::new("").with_user_id(user_id);
-    let baskets = storage.find_output_baskets_auth(&auth, &args).await?;
+    let baskets = storage.find_output_baskets_auth(auth, &args).await?;
     
     // TS uses verifyOne() which requires exactly 1 result
     if baskets.is_empty() {
@@ -586,6 +655,30 @@ async fn find_output_basket(
     Ok(baskets.into_iter().next().unwrap())
 }
 
+/// Find the change basket
+///
+/// The basket must exist, belong to the authenticated user, and not be one
+/// of the `admin` baskets the wallet reserves for itself.
+#[instrument(level = "debug", skip_all, fields(step = 3))]
+async fn find_change_basket(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    name: &str,
+) -> Result<TableOutputBasket, StorageError> {
+    if name.starts_with("admin") {
+        return Err(StorageError::InvalidArg(
+            format!("change basket '{}' is reserved for wallet administration", name)
+        ));
+    }
+    let basket = find_output_basket(storage, auth, name).await?;
+    if basket.user_id != auth.user_id_required()? {
+        return Err(StorageError::Unauthorized(
+            format!("change basket '{}' does not belong to the user", name)
+        ));
+    }
+    Ok(basket)
+}
+
 /// Validate noSendChange configuration
 /// Reference: TypeScript lines 680-718
 /// 
@@ -595,9 +688,10 @@ async fn find_output_basket(
 /// - Must be spendable (not already spent)
 /// - Must be in the correct change basket
 /// - No duplicates allowed
+#[instrument(level = "debug", skip_all, fields(step = 4))]
 async fn validate_no_send_change(
     storage: &dyn WalletStorageProvider,
-    user_id: i64,
+    auth: &AuthId,
     vargs: &ValidCreateActionArgs,
     change_basket: &TableOutputBasket,
 ) -> Result<Vec<TableOutput>, StorageError> {
@@ -629,7 +723,7 @@ async fn validate_no_send_change(
             transaction_id: None,
         };
         let args = FindOutputsArgs {
-            user_id,
+            user_id: auth.user_id_required()?,
             since: None,
             paged: None,
             order_descending: None,
@@ -637,8 +731,7 @@ async fn validate_no_send_change(
             no_script: Some(true),
             tx_status: None,
         };
-        let auth = AuthId::new("");
-        let outputs = storage.find_outputs_auth(&auth, &args).await?;
+        let outputs = storage.find_outputs_auth(auth, &args).await?;
         let output = outputs.into_iter().find(|o| o.vout == op.vout).ok_or_else(|| {
             StorageError::InvalidArg(format!("noSendChange output {}:{} not found", op.txid, op.vout))
         })?;
@@ -696,16 +789,18 @@ async fn validate_no_send_change(
 /// - Status='unsigned'
 /// - Version and lockTime from vargs
 /// - Links to transaction labels
+#[instrument(level = "debug", skip_all, fields(step = 7))]
 async fn create_new_tx_record(
     storage: &mut dyn WalletStorageProvider,
     user_id: i64,
     vargs: &ValidCreateActionArgs,
     storage_beef: Option<Vec<u8>>,
+    random: &mut RandomVals,
 ) -> Result<TableTransaction, StorageError> {
     let now = Utc::now();
     
     // Generate random reference ID (12 bytes = 16 chars base64)
-    let reference = generate_random_reference();
+    let reference = generate_random_reference(random);
     
     let new_tx = TableTransaction {
         created_at: now.to_rfc3339(),
@@ -732,10 +827,8 @@ async fn create_new_tx_record(
     new_tx_with_id.transaction_id = transaction_id;
     
     // Insert labels - TS lines 466-469
-    for label in &vargs.labels {
-        let tx_label = storage.find_or_insert_tx_label(user_id, label).await?;
-        let tx_label_id = tx_label.tx_label_id;
-        storage.find_or_insert_tx_label_map(transaction_id, tx_label_id).await?;
+    for tx_label in storage.find_or_insert_tx_labels(user_id, &vargs.labels).await? {
+        storage.find_or_insert_tx_label_map(transaction_id, tx_label.tx_label_id).await?;
     }
     
     Ok(new_tx_with_id)
@@ -743,11 +836,8 @@ async fn create_new_tx_record(
 
 /// Generate random reference ID
 /// Reference: TypeScript randomBytesBase64(12)
-fn generate_random_reference() -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let bytes: Vec<u8> = (0..12).map(|_| rng.gen()).collect();
-    base64::engine::general_purpose::STANDARD.encode(&bytes)
+fn generate_random_reference(random: &mut RandomVals) -> String {
+    random.bytes_base64(12)
 }
 
 /// Create default output record
@@ -776,182 +866,228 @@ struct FundingResult {
     max_possible_satoshis_adjustment: Option<MaxPossibleSatoshisAdjustment>,
 }
 
-struct MaxPossibleSatoshisAdjustment {
-    fixed_output_index: usize,
-    satoshis: i64,
+/// Change inputs for `generate_change_sdk`, backed by storage
+///
+/// noSendChange outputs are allocated first, then change basket outputs via
+/// `allocate_change_input`, which locks each output to the new transaction.
+///
+/// Reference: TypeScript `allocateChangeInput` / `releaseChangeInput` in fundNewTransactionSdk
+struct StorageChangeAllocator<'a> {
+    storage: &'a mut dyn WalletStorageProvider,
+    user_id: i64,
+    basket_id: i64,
+    transaction_id: i64,
+    exclude_sending: bool,
+    no_send_change_in: Vec<TableOutput>,
+    no_send_change: Vec<TableOutput>,
+    outputs: HashMap<i64, TableOutput>,
+}
+
+#[async_trait]
+impl ChangeInputAllocator for StorageChangeAllocator<'_> {
+    async fn allocate_change_input(
+        &mut self,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+    ) -> Result<Option<GenerateChangeSdkChangeInput>, StorageError> {
+        // TS: noSendChange gets allocated first
+        if let Some(mut o) = self.no_send_change.pop() {
+            let updates = OutputUpdates {
+                spendable: Some(false),
+                spent_by: Some(Some(self.transaction_id)),
+                ..Default::default()
+            };
+            self.storage.update_output(o.output_id, &updates).await?;
+            o.spendable = false;
+            o.spent_by = Some(self.transaction_id);
+            let r = GenerateChangeSdkChangeInput { output_id: o.output_id, satoshis: o.satoshis };
+            self.outputs.insert(o.output_id, o);
+            return Ok(Some(r));
+        }
+        
+        let o = self.storage.allocate_change_input(
+            self.user_id,
+            self.basket_id,
+            target_satoshis,
+            exact_satoshis,
+            self.exclude_sending,
+            self.transaction_id,
+        ).await?;
+        
+        Ok(o.map(|o| {
+            let r = GenerateChangeSdkChangeInput { output_id: o.output_id, satoshis: o.satoshis };
+            self.outputs.insert(o.output_id, o);
+            r
+        }))
+    }
+    
+    async fn release_change_input(&mut self, output_id: i64) -> Result<(), StorageError> {
+        if let Some(o) = self.no_send_change_in.iter().find(|o| o.output_id == output_id) {
+            self.no_send_change.push(o.clone());
+            return Ok(());
+        }
+        
+        let updates = OutputUpdates {
+            spendable: Some(true),
+            spent_by: Some(None),
+            ..Default::default()
+        };
+        self.storage.update_output(output_id, &updates).await?;
+        Ok(())
+    }
 }
 
 /// STEP 8: Fund transaction with change allocation
 /// Reference: lines 720-888 of createAction.ts (fundNewTransactionSdk)
 /// 
-/// Implements sophisticated funding algorithm:
-/// 1. Calculates total satoshis required (outputs + fees)
-/// 2. Allocates noSendChange outputs first (if specified)
-/// 3. Selects additional change inputs as needed
-/// 4. Locks all allocated outputs (marks as spent)
-/// 5. Generates change outputs if needed
-/// 6. Returns funding result with allocated change and new outputs
+/// Delegates to `generate_change_sdk`:
+/// 1. Fixed inputs/outputs come from the validated action
+/// 2. Change output sizing and target UTXO count come from the change basket
+/// 3. noSendChange outputs are allocated first, then basket change
+/// 4. Allocated outputs are locked to the new transaction as they are selected
+/// 5. Returns funding result with allocated change and new change outputs
+#[instrument(level = "debug", skip_all, fields(step = 8))]
 async fn fund_new_transaction(
     storage: &mut dyn WalletStorageProvider,
     user_id: i64,
     vargs: &ValidCreateActionArgs,
     ctx: &mut CreateTransactionContext,
+    random: &mut RandomVals,
 ) -> Result<FundingResult, StorageError> {
-    // TS line 726: Calculate total satoshis needed from outputs
-    let output_satoshis: i64 = ctx.xoutputs.iter()
-        .map(|o| o.satoshis())
-        .sum();
-    
-    // TS lines 728-730: Estimate fee
-    // Simple fee model for now: sat/kb * estimated size
-    let estimated_size = estimate_transaction_size(&ctx.xinputs, &ctx.xoutputs);
-    let estimated_fee = (estimated_size as f64 * ctx.fee_model.value / 1000.0).ceil() as i64;
-    let total_required = output_satoshis + estimated_fee;
-    
-    // TS lines 735-743: Allocate noSendChange first
-    let mut allocated_change = ctx.no_send_change_in.clone();
-    let mut allocated_satoshis: i64 = allocated_change.iter()
-        .map(|o| o.satoshis)
-        .sum();
-    
-    // TS lines 745-770: Allocate additional change if needed
-    if allocated_satoshis < total_required {
-        let needed = total_required - allocated_satoshis;
-        
-        // Select available change outputs from basket
-        let additional_change = select_change_inputs(
-            storage,
-            user_id,
-            ctx.change_basket.basket_id,
-            needed,
-            vargs.is_delayed,
-        ).await?;
-        
-        allocated_satoshis += additional_change.iter()
-            .map(|o| o.satoshis)
-            .sum::<i64>();
-        allocated_change.extend(additional_change);
-    }
-    
-    // TS lines 772-786: Lock all allocated outputs
-    for output in &allocated_change {
+    let (mut fixed_inputs, fixed_outputs) = fixed_change_params(&ctx.xinputs, &ctx.xoutputs);
+    let minimum_desired_utxo_value = ctx.change_basket.minimum_desired_utxo_value;
+    let mut target_net_count = ctx.change_basket.number_of_desired_utxos as i64 - ctx.available_change_count;
+    
+    // Consolidated outputs fund the transaction as fixed inputs. They still
+    // count as available change, so the net target excludes them.
+    let mut consolidated = Vec::with_capacity(ctx.consolidate.len());
+    for o in &ctx.consolidate {
         let updates = OutputUpdates {
             spendable: Some(false),
-            spent_by: Some(ctx.transaction_id),
-            spending_description: Some("change".to_string()),
+            spent_by: Some(Some(ctx.transaction_id)),
+            ..Default::default()
         };
-        storage.update_output(output.output_id, &updates).await?;
-    }
+        storage.update_output(o.output_id, &updates).await?;
+        fixed_inputs.push(GenerateChangeSdkInput {
+            satoshis: o.satoshis,
+            unlocking_script_length: CHANGE_UNLOCKING_SCRIPT_LENGTH,
+        });
+        consolidated.push(TableOutput { spendable: false, spent_by: Some(ctx.transaction_id), ..o.clone() });
+    }
+    if !consolidated.is_empty() {
+        target_net_count = consolidation_output_count(
+            target_net_count + consolidated.len() as i64,
+            &consolidated,
+            minimum_desired_utxo_value,
+        );
+    }
+    
+    // TS lines 726-740: GenerateChangeSdkParams
+    let params = GenerateChangeSdkParams {
+        fixed_inputs,
+        fixed_outputs,
+        fee_model: ctx.fee_model.clone(),
+        change_initial_satoshis: minimum_desired_utxo_value,
+        change_first_satoshis: 1.max((minimum_desired_utxo_value as f64 / 4.0).round() as i64),
+        change_locking_script_length: CHANGE_LOCKING_SCRIPT_LENGTH,
+        change_unlocking_script_length: CHANGE_UNLOCKING_SCRIPT_LENGTH,
+        target_net_count: Some(target_net_count),
+        random_vals: vargs.random_vals.clone(),
+    };
+    
+    // TS lines 742-790: allocate / release callbacks
+    let mut allocator = StorageChangeAllocator {
+        storage,
+        user_id,
+        basket_id: ctx.change_basket.basket_id,
+        transaction_id: ctx.transaction_id,
+        exclude_sending: !vargs.is_delayed,
+        no_send_change_in: ctx.no_send_change_in.clone(),
+        no_send_change: ctx.no_send_change_in.clone(),
+        outputs: HashMap::new(),
+    };
+    
+    let gcr = generate_change_sdk_with(&params, &mut allocator, random).await?;
     
-    // TS lines 788-795: Generate derivation prefix (random 10 bytes base64)
-    let derivation_prefix = generate_random_derivation_prefix();
+    let allocated = gcr.allocated_change_inputs.iter()
+        .map(|i| allocator.outputs.remove(&i.output_id).ok_or_else(|| {
+            StorageError::Database(format!("allocated change output {} not found", i.output_id))
+        }))
+        .collect::<Result<Vec<_>, _>>()?;
+    let allocated_change = consolidated.into_iter().chain(allocated).collect();
     
-    // TS lines 797-850: Create change outputs if we have excess
-    let mut change_outputs = Vec::new();
-    let excess_satoshis = allocated_satoshis - total_required;
+    // TS lines 797-850: Generate derivation prefix and change outputs
+    let derivation_prefix = generate_random_derivation_prefix(random);
     
-    if excess_satoshis > 0 {
-        // Create a change output
-        let change_output = create_change_output(
+    let change_outputs = gcr.change_outputs.iter()
+        .enumerate()
+        .map(|(i, o)| create_change_output(
             user_id,
             ctx.transaction_id,
             ctx.change_basket.basket_id,
-            excess_satoshis,
+            o.satoshis,
+            (ctx.xoutputs.len() + i) as u32,
             &derivation_prefix,
-        )?;
-        change_outputs.push(change_output);
-    }
-    
-    // TS lines 852-870: Handle maxPossibleSatoshis adjustment
-    let max_possible_satoshis_adjustment = handle_max_possible_satoshis(
-        vargs,
-        &ctx.xoutputs,
-        allocated_satoshis,
-        output_satoshis,
-    )?;
+            &generate_random_derivation_prefix(random),
+        ))
+        .collect::<Result<Vec<_>, _>>()?;
     
     Ok(FundingResult {
         allocated_change,
         change_outputs,
         derivation_prefix,
-        max_possible_satoshis_adjustment,
+        max_possible_satoshis_adjustment: gcr.max_possible_satoshis_adjustment,
     })
 }
 
-/// Estimate transaction size in bytes
-/// Reference: TypeScript fee calculation logic
-fn estimate_transaction_size(
+/// Fixed inputs and outputs for `generate_change_sdk`
+///
+/// Uses the declared unlocking script lengths of user inputs and the actual
+/// locking script lengths of outputs.
+///
+/// Reference: TypeScript fundNewTransactionSdk `fixedInputs` / `fixedOutputs`
+fn fixed_change_params(
     xinputs: &[XValidCreateActionInput],
     xoutputs: &[XValidCreateActionOutput],
-) -> usize {
-    // Basic size calculation:
-    // - 10 bytes overhead (version + locktime + counts)
-    // - Each input: ~148 bytes (outpoint + script + sequence)
-    // - Each output: ~34 bytes (value + script)
-    let base_size = 10;
-    let input_size = xinputs.len() * 148;
-    let output_size = xoutputs.len() * 34;
-    base_size + input_size + output_size
-}
-
-/// Select change inputs from basket
-/// Reference: TypeScript change allocation logic (lines 745-770)
-async fn select_change_inputs(
-    storage: &dyn WalletStorageProvider,
-    user_id: i64,
-    basket_id: i64,
-    needed_satoshis: i64,
-    _is_delayed: bool,
-) -> Result<Vec<TableOutput>, StorageError> {
-    // Find spendable change outputs in basket
-    let partial = PartialOutput {
-        basket_id: Some(basket_id),
-        spendable: Some(true),
-        change: Some(true),
-        transaction_id: None,
-        txid: None,
-    };
-    let args = FindOutputsArgs {
-        user_id,
-        since: None,
-        paged: None,
-        order_descending: Some(false), // Smallest first
-        partial: Some(partial),
-        no_script: Some(true),
-        tx_status: None,
-    };
-    
-    let auth = AuthId::new("");
-    let available = storage.find_outputs_auth(&auth, &args).await?;
-    
-    // Select outputs until we have enough satoshis
-    let mut selected = Vec::new();
-    let mut total: i64 = 0;
-    
-    for output in available {
-        if total >= needed_satoshis {
-            break;
-        }
-        total += output.satoshis;
-        selected.push(output);
-    }
+) -> (Vec<GenerateChangeSdkInput>, Vec<GenerateChangeSdkOutput>) {
+    let fixed_inputs = xinputs.iter()
+        .map(|x| GenerateChangeSdkInput {
+            satoshis: x.satoshis,
+            unlocking_script_length: x.input.unlocking_script_length
+                .map(|l| l as usize)
+                .or_else(|| x.input.unlocking_script.as_ref().map(|s| s.len() / 2))
+                .unwrap_or(CHANGE_UNLOCKING_SCRIPT_LENGTH),
+        })
+        .collect();
     
-    if total < needed_satoshis {
-        return Err(StorageError::InvalidArg(
-            format!("Insufficient funds: need {} satoshis, only {} available", needed_satoshis, total)
-        ));
-    }
+    let fixed_outputs = xoutputs.iter()
+        .map(|x| GenerateChangeSdkOutput {
+            satoshis: x.satoshis(),
+            locking_script_length: x.locking_script().len() / 2,
+        })
+        .collect();
     
-    Ok(selected)
+    (fixed_inputs, fixed_outputs)
+}
+
+/// Number of change outputs a consolidation creates
+///
+/// `desired` is how many outputs would bring the change basket to its desired
+/// UTXO count. The result is at least one, fewer than the outputs spent, and
+/// no more than the consolidated value can fund at `minimum_desired_utxo_value` each.
+fn consolidation_output_count(desired: i64, consolidate: &[TableOutput], minimum_desired_utxo_value: i64) -> i64 {
+    let total: i64 = consolidate.iter().map(|o| o.satoshis).sum();
+    let fundable = total / minimum_desired_utxo_value.max(1);
+    desired
+        .min(consolidate.len() as i64 - 1)
+        .min(fundable)
+        .max(1)
 }
 
 /// Generate random derivation prefix (10 bytes base64)
 /// Reference: TypeScript randomBytesBase64(10)
-fn generate_random_derivation_prefix() -> String {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let bytes: Vec<u8> = (0..10).map(|_| rng.gen()).collect();
-    base64::engine::general_purpose::STANDARD.encode(&bytes)
+fn generate_random_derivation_prefix(random: &mut RandomVals) -> String {
+    random.bytes_base64(10)
 }
 
 /// Create change output record
@@ -961,7 +1097,9 @@ fn create_change_output(
     transaction_id: i64,
     basket_id: i64,
     satoshis: i64,
+    vout: u32,
     derivation_prefix: &str,
+    derivation_suffix: &str,
 ) -> Result<TableOutput, StorageError> {
     let mut output = TableOutput::new(
         0, // output_id - will be set by insert
@@ -970,7 +1108,7 @@ fn create_change_output(
         true, // spendable
         true, // change
         "change".to_string(), // output_description
-        0, // vout - will be assigned later
+        vout,
         satoshis,
         WalletStorageProvidedBy::Storage,
         "change".to_string(), // purpose
@@ -979,24 +1117,36 @@ fn create_change_output(
     
     output.basket_id = Some(basket_id);
     output.derivation_prefix = Some(derivation_prefix.to_string());
+    output.derivation_suffix = Some(derivation_suffix.to_string());
     
     Ok(output)
 }
 
-/// Handle maxPossibleSatoshis adjustment
-/// Reference: TypeScript lines 852-870
-/// 
-/// TODO: Implement maxPossibleSatoshis detection and adjustment
-/// Currently disabled as schema doesn't have flag for this feature yet
-fn handle_max_possible_satoshis(
-    _vargs: &ValidCreateActionArgs,
-    _xoutputs: &[XValidCreateActionOutput],
-    _allocated_satoshis: i64,
-    _output_satoshis: i64,
-) -> Result<Option<MaxPossibleSatoshisAdjustment>, StorageError> {
-    // For now, no maxPossibleSatoshis adjustment
-    // Will implement when schema supports optional satoshis or has explicit flag
-    Ok(None)
+/// Assign shuffled vouts to new outputs
+/// Reference: TypeScript createAction.ts lines 371-409
+///
+/// Outputs must arrive with vouts 0..n in order. They keep their position
+/// (so results are still listed in creation order) while output `i` takes
+/// vout `new_vouts[i]` of a Fisher-Yates shuffle of `0..n`.
+fn randomize_output_vouts(
+    new_outputs: &mut [(TableOutput, Vec<String>)],
+    random: &mut RandomVals,
+) -> Result<(), StorageError> {
+    // TS line 406: Verify in-order, before any vout is reassigned
+    if let Some((vout, (o, _))) = new_outputs.iter().enumerate().find(|(vout, (o, _))| o.vout as usize != *vout) {
+        return Err(StorageError::Database(
+            format!("new output {} has out of order vout {}", vout, o.vout)
+        ));
+    }
+    
+    let mut new_vouts: Vec<u32> = (0..new_outputs.len() as u32).collect();
+    random.shuffle(&mut new_vouts);
+    
+    // TS line 407: Assign new shuffled vout
+    for ((o, _), vout) in new_outputs.iter_mut().zip(new_vouts) {
+        o.vout = vout;
+    }
+    Ok(())
 }
 
 /// Output creation result
@@ -1018,12 +1168,14 @@ struct OutputCreationResult {
 /// 7. Link outputs to tags
 /// 8. Build StorageCreateTransactionOutput results
 /// 9. Track change vouts
+#[instrument(level = "debug", skip_all, fields(step = 11))]
 async fn create_new_outputs(
     storage: &mut dyn WalletStorageProvider,
     user_id: i64,
     vargs: &ValidCreateActionArgs,
     ctx: &CreateTransactionContext,
     change_outputs: &[TableOutput],
+    random: &mut RandomVals,
 ) -> Result<OutputCreationResult, StorageError> {
     let mut outputs_result: Vec<StorageCreateTransactionOutput> = Vec::new();
     
@@ -1039,22 +1191,22 @@ async fn create_new_outputs(
     }
     
     // TS lines 315-321: Lookup output tags
-    let mut tx_tags: std::collections::HashMap<String, TableOutputTag> = std::collections::HashMap::new();
-    for xo in &ctx.xoutputs {
-        for tag in xo.tags() {
-            if !tx_tags.contains_key(tag) {
-                let tag_obj = storage.find_or_insert_output_tag(user_id, tag).await?;
-                tx_tags.insert(tag.to_string(), tag_obj);
-            }
-        }
-    }
+    let tags: Vec<String> = ctx.xoutputs.iter().flat_map(|xo| xo.tags()).cloned().collect();
+    let tx_tags: std::collections::HashMap<String, TableOutputTag> = storage
+        .find_or_insert_output_tags(user_id, &tags)
+        .await?
+        .into_iter()
+        .map(|t| (t.tag.clone(), t))
+        .collect();
     
     // TS line 323: Build newOutputs array
     let mut new_outputs: Vec<(TableOutput, Vec<String>)> = Vec::new();
     
     // TS lines 325-364: Process each xoutput
     for xo in &ctx.xoutputs {
-        let locking_script = xo.locking_script().as_bytes().to_vec();
+        let locking_script = hex::decode(xo.locking_script()).map_err(|e| {
+            StorageError::InvalidArg(format!("outputs[{}].lockingScript: {}", xo.vout, e))
+        })?;
         
         if xo.purpose.as_deref() == Some("service-charge") {
             // TS lines 327-348: Handle service-charge (storage commission)
@@ -1108,30 +1260,7 @@ async fn create_new_outputs(
     
     // TS lines 371-409: Randomize output order if requested
     if vargs.options.randomize_outputs {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        
-        // Create array of indices
-        let mut new_vouts: Vec<usize> = (0..new_outputs.len()).collect();
-        
-        // Shuffle using provided randomVals or thread_rng
-        // TODO: Use vargs.random_vals if provided for deterministic testing
-        new_vouts.shuffle(&mut rng);
-        
-        // Reassign vout values (TS lines 400-408)
-        for (vout, (output, _tags)) in new_outputs.iter_mut().enumerate() {
-            let original_vout = output.vout;
-            
-            // TS line 406: Verify in-order
-            if original_vout as usize != vout {
-                return Err(StorageError::Database(
-                    format!("new output {} has out of order vout {}", vout, original_vout)
-                ));
-            }
-            
-            // TS line 407: Assign new shuffled vout
-            output.vout = new_vouts[vout] as u32;
-        }
+        randomize_output_vouts(&mut new_outputs, random)?;
     }
     
     // TS lines 411-436: Insert outputs and build results
@@ -1200,67 +1329,109 @@ async fn create_new_outputs(
     })
 }
 
-/// STEP 12: Merge all input BEEFs
+/// STEP 12: Merge BEEFs of allocated change into the input BEEF
 /// Reference: lines 903-945 of createAction.ts
 /// 
-/// Merges input BEEF with BEEFs from allocated change outputs:
-/// 1. Start with input BEEF
-/// 2. For each allocated change output, get its transaction
-/// 3. Merge transactions into BEEF
-/// 4. Return merged BEEF bytes (or None if no BEEF data)
+/// Merges the transactions of allocated change outputs into the input BEEF:
+/// 1. Change already in the BEEF or in `knownTxids` is skipped
+/// 2. Change storage knows is merged as txid-only, unless a signAction
+///    needs all source transactions (`includeAllSourceTransactions`)
+/// 3. `knownTxids` other than the inputs' are trimmed to txid-only
+/// 4. Returns the BEEF bytes, or None when it is empty
+#[instrument(level = "debug", skip_all, fields(step = 12))]
 async fn merge_allocated_change_beefs(
     storage: &dyn WalletStorageProvider,
-    _user_id: i64,
     vargs: &ValidCreateActionArgs,
     allocated_change: &[TableOutput],
-    beef_bytes: Vec<u8>,
+    mut beef: Beef,
 ) -> Result<Option<Vec<u8>>, StorageError> {
-    // TS lines 905-910: If no includeAllSourceTransactions, return input BEEF
-    if !vargs.include_all_source_transactions || !vargs.is_sign_action {
-        return Ok(if beef_bytes.is_empty() {
-            None
-        } else {
-            Some(beef_bytes)
-        });
-    }
-    
-    // TS lines 912-945: Merge BEEFs from allocated change
-    // TODO: Implement full BEEF merging when Beef::from_binary() is available
-    // For now, we need to:
-    // 1. Parse input BEEF from bytes
-    // 2. For each allocated change output, get its raw transaction
-    // 3. Merge those transactions into the BEEF
-    // 4. Serialize back to bytes
-    
-    // Temporary implementation: just return input BEEF
-    // Full implementation requires:
-    // - Beef::from_binary() to parse beef_bytes
-    // - beef.merge_transaction() for each change output
-    // - beef.to_binary() to serialize result
+    let known_txids = &vargs.options.known_txids;
+    let options = StorageGetBeefOptions {
+        trust_self: !(vargs.include_all_source_transactions && vargs.is_sign_action),
+        known_txids: known_txids.clone(),
+        merge_to_beef: None,
+    };
     
-    if allocated_change.is_empty() {
-        return Ok(if beef_bytes.is_empty() {
-            None
-        } else {
-            Some(beef_bytes)
-        });
+    // TS lines 912-940: getBeefForTransaction for each allocated change txid
+    for txid in allocated_change.iter().filter_map(|o| o.txid.as_deref()) {
+        if beef.find_txid(txid).is_none() && !known_txids.iter().any(|k| k == txid) {
+            get_valid_beef_for_txid(storage, txid, &mut beef, &options).await?;
+        }
     }
     
-    // Get transactions for allocated change
-    for output in allocated_change {
-        if let Some(ref txid) = output.txid {
-            // Get the raw transaction
-            let _raw_tx = storage.get_raw_tx_of_known_valid_transaction(txid, None, None).await?;
-            // TODO: Merge into BEEF when Beef methods are available
+    // TS trimInputBeef: known txids that aren't inputs become txid-only
+    for txid in known_txids {
+        if !vargs.inputs.iter().any(|i| &i.outpoint.txid == txid) {
+            beef.make_txid_only(txid);
         }
     }
     
-    // Return input BEEF for now
-    Ok(if beef_bytes.is_empty() {
-        None
-    } else {
-        Some(beef_bytes)
-    })
+    if beef.txs.is_empty() {
+        return Ok(None);
+    }
+    beef.to_binary().map(Some).map_err(|e| StorageError::InvalidArg(format!("inputBEEF: {}", e)))
+}
+
+/// Mark the storage output `o` spent by input `xinput` of `transaction_id`
+///
+/// The output is re-read to check it is still spendable (a double-spend
+/// check), then updated only if unchanged since. When the monitor or another
+/// device updates it in between, it is re-read and checked again, up to
+/// `MAX_CONFLICT_RETRIES` times.
+///
+/// Reference: TS createAction.ts lines 224-242
+async fn spend_input_output(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    xinput: &XValidCreateActionInput,
+    o: &TableOutput,
+    transaction_id: i64,
+) -> Result<(), StorageError> {
+    let mut attempt = 0;
+    loop {
+        // TS lines 226-231: Verify output is spendable (double-spend check)
+        // Use transactionId and vout to find the output
+        let partial = PartialOutput {
+            basket_id: o.basket_id,
+            spendable: None,
+            change: None,
+            transaction_id: Some(o.transaction_id),
+            txid: o.txid.clone(),
+        };
+        let args = FindOutputsArgs {
+            user_id: auth.user_id_required()?,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: Some(partial),
+            no_script: Some(true),
+            tx_status: None,
+        };
+        let outputs = storage.find_outputs_auth(auth, &args).await?;
+        let o2 = outputs.into_iter().find(|out| out.vout == o.vout).ok_or_else(|| {
+            StorageError::NotFound(format!("Output {} not found", o.output_id))
+        })?;
+        
+        if !o2.spendable || o2.spent_by.is_some() {
+            return Err(StorageError::InvalidArg(
+                format!("inputs[{}]: spendable output. output {}:{} appears to have been spent.",
+                    xinput.vin, o.txid.as_ref().unwrap_or(&"unknown".to_string()), o.vout)
+            ));
+        }
+        
+        // TS lines 232-240: Update output to mark as spent
+        let updates = OutputUpdates {
+            spendable: Some(false),
+            spent_by: Some(Some(transaction_id)),
+            spending_description: Some(xinput.input.input_description.clone()),
+            expected_updated_at: Some(o2.updated_at),
+            ..Default::default()
+        };
+        match storage.update_output(o.output_id, &updates).await {
+            Err(StorageError::Conflict(_)) if attempt < MAX_CONFLICT_RETRIES => attempt += 1,
+            result => return result,
+        }
+    }
 }
 
 /// STEP 13: Create input specifications for result
@@ -1276,9 +1447,10 @@ async fn merge_allocated_change_beefs(
 ///    - Get source transaction if includeAllSourceTransactions
 ///    - Set providedBy (you, storage, or you-and-storage)
 ///    - Set derivation fields, type, spending description
+#[instrument(level = "debug", skip_all, fields(step = 13))]
 async fn create_new_inputs(
     storage: &mut dyn WalletStorageProvider,
-    user_id: i64,
+    auth: &AuthId,
     vargs: &ValidCreateActionArgs,
     ctx: &CreateTransactionContext,
     allocated_change: &[TableOutput],
@@ -1293,46 +1465,7 @@ async fn create_new_inputs(
         
         // TS lines 224-242: Mark output as spent if from storage
         if let Some(ref o) = output {
-            let output_id = o.output_id;
-            
-            // TS lines 226-231: Verify output is spendable (double-spend check)
-            // Use transactionId and vout to find the output
-            let partial = PartialOutput {
-                basket_id: o.basket_id,
-                spendable: None,
-                change: None,
-                transaction_id: Some(o.transaction_id),
-                txid: o.txid.clone(),
-            };
-            let args = FindOutputsArgs {
-                user_id: user_id,
-                since: None,
-                paged: None,
-                order_descending: None,
-                partial: Some(partial),
-                no_script: Some(true),
-                tx_status: None,
-            };
-            let auth = AuthId::new("");
-            let outputs = storage.find_outputs_auth(&auth, &args).await?;
-            let o2 = outputs.into_iter().find(|out| out.vout == o.vout).ok_or_else(|| {
-                StorageError::NotFound(format!("Output {} not found", output_id))
-            })?;
-            
-            if !o2.spendable || o2.spent_by.is_some() {
-                return Err(StorageError::InvalidArg(
-                    format!("inputs[{}]: spendable output. output {}:{} appears to have been spent.",
-                        xinput.vin, o.txid.as_ref().unwrap_or(&"unknown".to_string()), o.vout)
-                ));
-            }
-            
-            // TS lines 232-240: Update output to mark as spent
-            let updates = OutputUpdates {
-                spendable: Some(false),
-                spent_by: Some(ctx.transaction_id),
-                spending_description: Some(xinput.input.input_description.clone()),
-            };
-            storage.update_output(output_id, &updates).await?;
+            spend_input_output(storage, auth, xinput, o, ctx.transaction_id).await?;
         }
         
         new_inputs.push((Some(xinput.clone()), output, None));
@@ -1448,16 +1581,32 @@ async fn create_new_inputs(
 mod tests {
     use super::*;
     use crate::sdk::action::*;
+    use base64::Engine as _;
     
     // ============================================================================
     // Helper Functions Tests
     // ============================================================================
     
+    #[test]
+    fn test_consolidation_output_count() {
+        let dust = |satoshis| TableOutput::new(
+            0, 1, 1, true, true, "change", 0, satoshis, WalletStorageProvidedBy::Storage, "change", "P2PKH",
+        );
+        let outputs = vec![dust(4000), dust(4000), dust(4000), dust(4000)];
+        
+        // Always fewer outputs than spent
+        assert_eq!(consolidation_output_count(10, &outputs, 1000), 3);
+        // Limited by the value available at the minimum UTXO value
+        assert_eq!(consolidation_output_count(10, &outputs, 8000), 2);
+        // At least one output, even when the basket already has enough change
+        assert_eq!(consolidation_output_count(-5, &outputs, 1000), 1);
+    }
+    
     #[test]
     fn test_generate_random_reference() {
         // Test that generate_random_reference creates 16-char base64 string (12 bytes)
-        let ref1 = generate_random_reference();
-        let ref2 = generate_random_reference();
+        let ref1 = generate_random_reference(&mut RandomVals::default());
+        let ref2 = generate_random_reference(&mut RandomVals::default());
         
         assert_eq!(ref1.len(), 16, "Reference should be 16 characters (12 bytes base64)");
         assert_eq!(ref2.len(), 16, "Reference should be 16 characters");
@@ -1466,7 +1615,56 @@ mod tests {
         // Verify it's valid base64
         assert!(base64::engine::general_purpose::STANDARD.decode(&ref1).is_ok(), "Should be valid base64");
     }
-    
+
+    #[test]
+    fn test_random_vals_make_reference_and_prefix_deterministic() {
+        // TS: randomVals drive every random choice in createAction
+        let run = || {
+            let mut random = RandomVals::new(Some(vec![0.1, 0.6, 0.3]));
+            (
+                generate_random_reference(&mut random),
+                generate_random_derivation_prefix(&mut random),
+                generate_random_derivation_prefix(&mut random),
+            )
+        };
+        let (reference, prefix, suffix) = run();
+        assert_eq!((reference.clone(), prefix.clone(), suffix.clone()), run());
+        assert_eq!(reference.len(), 16);
+        assert_eq!(prefix.len(), 16);
+        // The rotation continues from one value to the next
+        assert_ne!(prefix, suffix);
+    }
+
+    fn outputs_with_vouts(vouts: &[u32]) -> Vec<(TableOutput, Vec<String>)> {
+        vouts.iter().map(|&vout| (make_default_output(1, 1, 1000, vout), Vec::new())).collect()
+    }
+
+    #[test]
+    fn test_randomize_output_vouts_matches_ts_shuffle() {
+        // TS shuffleArray over [0, 1, 2, 3] with randomVals [0.1, 0.7, 0.4]:
+        // i=3 swaps with 0, i=2 with 2, i=1 with 0 -> [1, 3, 2, 0]
+        let mut outputs = outputs_with_vouts(&[0, 1, 2, 3]);
+        randomize_output_vouts(&mut outputs, &mut RandomVals::new(Some(vec![0.1, 0.7, 0.4]))).unwrap();
+        let vouts: Vec<u32> = outputs.iter().map(|(o, _)| o.vout).collect();
+        assert_eq!(vouts, vec![1, 3, 2, 0]);
+
+        // A single value of 0 rotates every vout down by one: [1, 2, 3, 4, 0]
+        let mut outputs = outputs_with_vouts(&[0, 1, 2, 3, 4]);
+        randomize_output_vouts(&mut outputs, &mut RandomVals::new(Some(vec![0.0]))).unwrap();
+        let vouts: Vec<u32> = outputs.iter().map(|(o, _)| o.vout).collect();
+        assert_eq!(vouts, vec![1, 2, 3, 4, 0]);
+    }
+
+    #[test]
+    fn test_randomize_output_vouts_rejects_out_of_order() {
+        let mut outputs = outputs_with_vouts(&[0, 2, 1]);
+        let err = randomize_output_vouts(&mut outputs, &mut RandomVals::new(Some(vec![0.5]))).unwrap_err();
+        assert!(err.to_string().contains("new output 1 has out of order vout 2"));
+        // Nothing is reassigned on failure
+        let vouts: Vec<u32> = outputs.iter().map(|(o, _)| o.vout).collect();
+        assert_eq!(vouts, vec![0, 2, 1]);
+    }
+
     #[test]
     fn test_make_default_output() {
         let user_id = 123;
@@ -1521,7 +1719,36 @@ mod tests {
         assert_eq!(output1.satoshis, 1000);
         assert_eq!(output2.satoshis, 2000);
     }
-    
+
+    #[tokio::test]
+    async fn test_validate_required_inputs_refuses_locked_outputs() {
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let auth = AuthId::new(wallet_test_utils::fixtures::alice_identity_key()).with_user_id(1);
+        // An app-managed output rather than change
+        storage.outputs[0].change = false;
+        let txid = storage.outputs[0].txid.clone().unwrap();
+
+        let args: crate::sdk::CreateActionArgs = serde_json::from_value(serde_json::json!({
+            "description": "spend token",
+            "inputs": [{
+                "outpoint": format!("{}.0", txid),
+                "inputDescription": "token",
+                "unlockingScriptLength": 107,
+            }],
+            "options": { "trustSelf": "known" },
+        }))
+        .unwrap();
+        let vargs = crate::signer::methods::validate_create_action_args(&args).unwrap();
+
+        storage.lock_output(1, &txid, 0, "held by token app", None).await.unwrap();
+        let err = validate_required_inputs(&storage, &auth, &vargs).await.unwrap_err();
+        assert!(err.to_string().contains("is locked: held by token app"), "{}", err);
+
+        storage.unlock_output(1, &txid, 0).await.unwrap();
+        let (_, _, inputs) = validate_required_inputs(&storage, &auth, &vargs).await.unwrap();
+        assert_eq!(inputs[0].satoshis, 1_000);
+    }
+
     // ============================================================================
     // BEEF Module Tests
     // ============================================================================
@@ -1653,22 +1880,39 @@ mod tests {
     // Reference: TypeScript createAction.ts lines 728-730
     // ============================================================================
     
+    /// Serialized size of the fixed inputs/outputs plus added P2PKH change
+    fn estimate_transaction_size(
+        xinputs: &[XValidCreateActionInput],
+        change_inputs: usize,
+        xoutputs: &[XValidCreateActionOutput],
+        change_outputs: usize,
+    ) -> usize {
+        let (fixed_inputs, fixed_outputs) = fixed_change_params(xinputs, xoutputs);
+        let inputs: Vec<usize> = fixed_inputs.iter().map(|i| i.unlocking_script_length)
+            .chain(std::iter::repeat_n(CHANGE_UNLOCKING_SCRIPT_LENGTH, change_inputs))
+            .collect();
+        let outputs: Vec<usize> = fixed_outputs.iter().map(|o| o.locking_script_length)
+            .chain(std::iter::repeat_n(CHANGE_LOCKING_SCRIPT_LENGTH, change_outputs))
+            .collect();
+        crate::methods::fee_model::transaction_size(&inputs, &outputs)
+    }
+    
     #[test]
     fn test_estimate_transaction_size_basic() {
-        // TS Reference: Basic P2PKH transaction estimation
-        // Each input ~148 bytes, each output ~34 bytes, overhead 10 bytes
+        // TS Reference: transactionSize([], []) = version + counts + lockTime
         
         let xinputs = vec![]; // Empty inputs
         let xoutputs = vec![]; // Empty outputs
         
-        let size = estimate_transaction_size(&xinputs, &xoutputs);
+        let size = estimate_transaction_size(&xinputs, 0, &xoutputs, 0);
         assert_eq!(size, 10, "Empty transaction should be 10 bytes overhead");
     }
     
     #[test]
     fn test_estimate_transaction_size_with_inputs_outputs() {
-        // TS Reference: Typical transaction size calculation
-        // 1 input + 2 outputs = 10 + 148 + (2*34) = 226 bytes
+        // TS Reference: transactionSize uses declared unlocking script length
+        // 1 input (107 byte unlock) + 2 outputs (3 byte scripts)
+        // = 10 + 148 + 2*(8 + 1 + 3) = 182 bytes
         
         let xinputs = vec![
             XValidCreateActionInput {
@@ -1724,14 +1968,18 @@ mod tests {
             },
         ];
         
-        let size = estimate_transaction_size(&xinputs, &xoutputs);
-        assert_eq!(size, 226, "1 input + 2 outputs = 226 bytes");
+        let size = estimate_transaction_size(&xinputs, 0, &xoutputs, 0);
+        assert_eq!(size, 182, "1 input + 2 short outputs = 182 bytes");
+        
+        // Adding a change input and a change output (P2PKH)
+        let size = estimate_transaction_size(&xinputs, 1, &xoutputs, 1);
+        assert_eq!(size, 182 + 148 + 34);
     }
     
     #[test]
     fn test_estimate_transaction_size_large() {
         // TS Reference: Larger transaction for fee calculation
-        // 10 inputs + 5 outputs = 10 + (10*148) + (5*34) = 1660 bytes
+        // 10 inputs + 5 outputs = 10 + (10*148) + 5*(8 + 1 + 3) = 1550 bytes
         
         let xinputs = vec![XValidCreateActionInput {
             input: ValidCreateActionInput {
@@ -1768,8 +2016,12 @@ mod tests {
             key_offset: None,
         }; 5]; // 5 outputs
         
-        let size = estimate_transaction_size(&xinputs, &xoutputs);
-        assert_eq!(size, 1660, "10 inputs + 5 outputs = 1660 bytes");
+        let size = estimate_transaction_size(&xinputs, 0, &xoutputs, 0);
+        assert_eq!(size, 1550, "10 inputs + 5 outputs = 1550 bytes");
+        
+        // At 100 sat/kb: 1550 bytes => 155 satoshis
+        let fee_model = StorageFeeModel { model: "sat/kb".to_string(), value: Some(100.0) };
+        assert_eq!(crate::methods::fee_model::fee_for_size(&fee_model, size), 155);
     }
     
     // ============================================================================
@@ -1782,7 +2034,7 @@ mod tests {
         // TS Reference: randomBytesBase64(10) generates 10-byte random prefix
         // Base64 encoding of 10 bytes produces ~14 characters
         
-        let prefix = generate_random_derivation_prefix();
+        let prefix = generate_random_derivation_prefix(&mut RandomVals::default());
         
         // Base64 of 10 bytes should be roughly 14 characters
         // (10 bytes * 8 bits) / 6 bits per char = 13.33... ≈ 14 chars (with padding)
@@ -1799,9 +2051,9 @@ mod tests {
     fn test_generate_random_derivation_prefix_uniqueness() {
         // TS Reference: Each transaction should get unique derivation prefix
         
-        let prefix1 = generate_random_derivation_prefix();
-        let prefix2 = generate_random_derivation_prefix();
-        let prefix3 = generate_random_derivation_prefix();
+        let prefix1 = generate_random_derivation_prefix(&mut RandomVals::default());
+        let prefix2 = generate_random_derivation_prefix(&mut RandomVals::default());
+        let prefix3 = generate_random_derivation_prefix(&mut RandomVals::default());
         
         // Statistically, these should be different
         // (collision chance is astronomically low with 10 random bytes)
@@ -1830,7 +2082,9 @@ mod tests {
             transaction_id,
             basket_id,
             satoshis,
+            3,
             derivation_prefix,
+            "test_suffix",
         ).expect("Should create change output");
         
         // Verify all fields match TS behavior
@@ -1844,6 +2098,8 @@ mod tests {
         assert_eq!(output.provided_by, WalletStorageProvidedBy::Storage);
         assert_eq!(output.basket_id, Some(basket_id));
         assert_eq!(output.derivation_prefix, Some(derivation_prefix.to_string()));
+        assert_eq!(output.derivation_suffix, Some("test_suffix".to_string()));
+        assert_eq!(output.vout, 3);
         assert_eq!(output.output_description, "change");
     }
     
@@ -1851,7 +2107,7 @@ mod tests {
     fn test_create_change_output_zero_satoshis() {
         // TS Reference: Edge case - zero satoshi change (should still create valid output)
         
-        let output = create_change_output(1, 1, 1, 0, "prefix").unwrap();
+        let output = create_change_output(1, 1, 1, 0, 0, "prefix", "suffix").unwrap();
         
         assert_eq!(output.satoshis, 0);
         assert_eq!(output.change, true);
@@ -1863,7 +2119,7 @@ mod tests {
         // TS Reference: Large satoshi amounts (within i64 range)
         
         let large_amount = 2_100_000_000_000_000i64; // 21M BTC in satoshis
-        let output = create_change_output(1, 1, 1, large_amount, "prefix").unwrap();
+        let output = create_change_output(1, 1, 1, large_amount, 0, "prefix", "suffix").unwrap();
         
         assert_eq!(output.satoshis, large_amount);
         assert_eq!(output.change, true);
@@ -1875,19 +2131,33 @@ mod tests {
     // ============================================================================
     
     #[test]
-    fn test_handle_max_possible_satoshis_none() {
-        // TS Reference: When no output requests maxPossibleSatoshis
-        // Currently disabled in implementation, so we just test the structure
-        
-        let vargs = create_test_vargs();
-        let xoutputs = vec![];
-        let allocated = 100000;
-        let output_total = 50000;
+    fn test_fixed_change_params_max_possible_satoshis() {
+        // TS Reference: fixedOutputs carry maxPossibleSatoshis through to generateChangeSdk
+        let make_output = |satoshis: i64| XValidCreateActionOutput {
+            output: ValidCreateActionOutput {
+                satoshis,
+                locking_script: "76a914".to_string(),
+                output_description: "out".to_string(),
+                basket: None,
+                custom_instructions: None,
+                tags: None,
+            },
+            vout: 0,
+            provided_by: StorageProvidedBy::You,
+            purpose: None,
+            derivation_suffix: None,
+            key_offset: None,
+        };
         
-        let result = handle_max_possible_satoshis(&vargs, &xoutputs, allocated, output_total);
+        let (_, fixed_outputs) = fixed_change_params(&[], &[make_output(1000)]);
+        assert_eq!(fixed_outputs[0].satoshis, 1000);
+        assert_eq!(fixed_outputs[0].locking_script_length, 3);
         
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_none(), "Should return None when no maxPossibleSatoshis");
+        let (_, fixed_outputs) = fixed_change_params(
+            &[],
+            &[make_output(1000), make_output(crate::methods::MAX_POSSIBLE_SATOSHIS)],
+        );
+        assert_eq!(fixed_outputs[1].satoshis, crate::methods::MAX_POSSIBLE_SATOSHIS);
     }
     
     // Helper function to create test ValidCreateActionArgs
@@ -1957,11 +2227,15 @@ mod tests {
     fn test_storage_fee_model_creation() {
         let fee_model = StorageFeeModel {
             model: "sat/kb".to_string(),
-            value: 0.5,
+            value: Some(0.5),
         };
         
         assert_eq!(fee_model.model, "sat/kb");
-        assert_eq!(fee_model.value, 0.5);
+        assert_eq!(fee_model.value, Some(0.5));
+        
+        // Validation keeps the configured rate
+        let validated = validate_storage_fee_model(Some(&fee_model)).unwrap();
+        assert_eq!(validated.value, Some(0.5));
     }
     
     #[test]
@@ -1998,11 +2272,9 @@ mod tests {
                 is_deleted: false,
             },
             no_send_change_in: vec![],
+            consolidate: vec![],
             available_change_count: 10,
-            fee_model: StorageFeeModel {
-                model: "sat/kb".to_string(),
-                value: 0.5,
-            },
+            fee_model: StorageFeeModel::default(),
             transaction_id: 456,
         };
         
@@ -0,0 +1,160 @@
+//! Double Spend Reconciliation
+//!
+//! **Reference**: TypeScript `src/storage/methods/processAction.ts` and
+//! `EntityProvenTxReq` (double spend handling)
+//!
+//! When the network rejects a transaction because one of its inputs was
+//! already spent:
+//! 1. **Request** - the ProvenTxReq becomes 'doubleSpend' and records the
+//!    competing txids in its history
+//! 2. **Transactions** - every transaction it notifies becomes 'failed'
+//! 3. **Inputs** - inputs a UTXO status check confirms unspent are released;
+//!    the others stay spent so they are not selected again
+//!
+//! Without a UTXO status service every input is released, as the monitor
+//! does when it fails a transaction.
+
+use crate::sdk::errors::DoubleSpendError;
+use crate::services::{verify_output_unspent, UtxoStatusProvider};
+use std::collections::HashMap;
+use wallet_storage::{
+    EntityProvenTxReq, OutputUpdates, ProvenTxReqStatus, ProvenTxReqUpdates, ReqHistoryNote,
+    StorageError, TableOutput, TableProvenTxReq, TransactionStatus, WalletStorageProvider,
+};
+
+/// Reconcile a transaction the network reports as a double spend
+///
+/// Returns the typed error describing which inputs were found spent and
+/// which were released.
+pub async fn reconcile_double_spend(
+    storage: &mut dyn WalletStorageProvider,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    req: &TableProvenTxReq,
+    competing_txs: &[String],
+) -> Result<DoubleSpendError, StorageError> {
+    let mut entity = EntityProvenTxReq::new(Some(req.clone()));
+    entity.add_history_note(double_spend_note(competing_txs), true);
+    storage.update_proven_tx_req(req.proven_tx_req_id, &ProvenTxReqUpdates {
+        status: Some(ProvenTxReqStatus::DoubleSpend),
+        history: Some(entity.into_api().history),
+        ..Default::default()
+    }).await?;
+
+    let mut spent_inputs = Vec::new();
+    let mut released_inputs = Vec::new();
+    for transaction_id in req.notify_transaction_ids() {
+        storage.update_transaction_status(transaction_id, TransactionStatus::Failed).await?;
+        let Some(transaction) = storage.find_transaction_by_id(transaction_id).await? else {
+            continue;
+        };
+
+        let inputs = storage.find_outputs_by_transaction(transaction.user_id, transaction_id, true).await?;
+        for input in &inputs {
+            let outpoint = input_outpoint(input);
+            if input_is_unspent(utxo_status, input).await {
+                storage.update_output(input.output_id, &OutputUpdates {
+                    spendable: Some(true),
+                    spent_by: Some(None),
+                    ..Default::default()
+                }).await?;
+                released_inputs.push(outpoint);
+            } else {
+                spent_inputs.push(outpoint);
+            }
+        }
+    }
+
+    Ok(DoubleSpendError {
+        txid: req.txid.clone(),
+        competing_txs: competing_txs.to_vec(),
+        spent_inputs,
+        released_inputs,
+    })
+}
+
+/// ProvenTxReq history note recording a double spend
+fn double_spend_note(competing_txs: &[String]) -> ReqHistoryNote {
+    ReqHistoryNote {
+        when: None,
+        what: "doubleSpend".to_string(),
+        extra: HashMap::from([("competingTxs".to_string(), serde_json::json!(competing_txs))]),
+    }
+}
+
+/// Outpoint string ("txid.vout") of an input
+fn input_outpoint(input: &TableOutput) -> String {
+    format!("{}.{}", input.txid.as_deref().unwrap_or_default(), input.vout)
+}
+
+/// Whether `input` may be spent again
+///
+/// Without a service every input is released. With one, only inputs it
+/// confirms unspent are; service errors and outputs without a locking
+/// script count as spent.
+async fn input_is_unspent(utxo_status: Option<&dyn UtxoStatusProvider>, input: &TableOutput) -> bool {
+    let Some(services) = utxo_status else {
+        return true;
+    };
+    match (input.txid.as_deref(), input.locking_script.as_deref()) {
+        (Some(txid), Some(script)) => {
+            verify_output_unspent(services, txid, input.vout, script).await.is_ok()
+        }
+        _ => false,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_storage::{StorageProvidedBy, TableTransaction};
+    use wallet_test_utils::MockStorage;
+
+    #[tokio::test]
+    async fn test_double_spend_inputs_and_history() {
+        let mut input = TableOutput::new(1, 1, 1, true, true, "change", 2, 1000, StorageProvidedBy::Storage, "change", "P2PKH");
+        input.txid = Some("aa".to_string());
+        assert_eq!(input_outpoint(&input), "aa.2");
+
+        // Without a UTXO status service every input is released
+        assert!(input_is_unspent(None, &input).await);
+
+        let req = TableProvenTxReq::new(1, ProvenTxReqStatus::Sending, "bb", "{}", "{}", vec![]);
+        let mut entity = EntityProvenTxReq::new(Some(req));
+        entity.add_history_note(double_spend_note(&["cc".to_string()]), true);
+        entity.add_history_note(double_spend_note(&["cc".to_string()]), true);
+        let req = entity.into_api();
+        assert_eq!(req.competing_txids(), vec!["cc".to_string()]);
+        let notes = EntityProvenTxReq::parse_history(&req.history).notes.unwrap();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].when.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_released_inputs_can_be_spent_again() {
+        let mut storage = MockStorage::new();
+        storage.transactions.push(TableTransaction::new(1, 1, TransactionStatus::Completed, "fund", false, 1000, "funding"));
+        storage.transactions.push(TableTransaction::new(2, 1, TransactionStatus::Sending, "first", true, 0, "first"));
+        let mut input = TableOutput::new(10, 1, 1, true, true, "change", 0, 1000, StorageProvidedBy::Storage, "change", "P2PKH")
+            .with_basket_id(1);
+        input.mark_spent(2, None, None);
+        storage.outputs.push(input);
+        let req = TableProvenTxReq::new(0, ProvenTxReqStatus::Sending, "bb", "{}", r#"{"transactionIds":[2]}"#, vec![]);
+        let req_id = storage.insert_proven_tx_req(&req).await.unwrap();
+        let req = TableProvenTxReq { proven_tx_req_id: req_id, ..req };
+
+        let error = reconcile_double_spend(&mut storage, None, &req, &["cc".to_string()]).await.unwrap();
+        assert_eq!(error.released_inputs.len(), 1);
+        assert_eq!(storage.transaction(2).status, TransactionStatus::Failed);
+        assert_eq!((storage.outputs[0].spendable, storage.outputs[0].spent_by), (true, None));
+        assert!(storage.find_outputs_by_transaction(1, 2, true).await.unwrap().is_empty());
+
+        // A second spend selects the released input
+        storage.transactions.push(TableTransaction::new(3, 1, TransactionStatus::Unsigned, "second", true, 0, "second"));
+        let output = storage.allocate_change_input(1, 1, 500, None, true, 3).await.unwrap().unwrap();
+        assert_eq!((output.output_id, output.spent_by), (10, Some(3)));
+    }
+}
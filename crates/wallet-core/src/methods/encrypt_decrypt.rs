@@ -2,171 +2,56 @@
 //!
 //! Reference: TS `wallet.encrypt` and `wallet.decrypt` from @bsv/sdk
 //!
-//! Implements BRC-42/43 key derivation with AES-256-GCM encryption
+//! Implements BRC-2: the symmetric key is derived from the BRC-42 shared
+//! secret between the two parties' derived keys, then used with AES-256-GCM.
 
-use crate::sdk::errors::{WalletError, WalletResult};
-use crate::keys::derive_encryption_key;
+use crate::sdk::errors::WalletResult;
+use crate::keys::RootKeyDeriver;
 use crate::crypto::{decrypt_with_aes_gcm, encrypt_with_aes_gcm};
-use serde::{Deserialize, Serialize};
-
-/// Arguments for wallet encryption
-///
-/// Reference: TS WalletEncryptArgs
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct EncryptArgs {
-    /// Data to encrypt (base64 encoded)
-    pub plaintext: String,
-    
-    /// Protocol ID for key derivation
-    pub protocol_id: Vec<String>,
-    
-    /// Key ID for key derivation
-    pub key_id: String,
-    
-    /// Counterparty public key (optional)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub counterparty: Option<String>,
-    
-    /// Whether this uses privileged key
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub privileged: Option<bool>,
-}
-
-/// Result from wallet encryption
-///
-/// Reference: TS WalletEncryptResult
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct EncryptResult {
-    /// Encrypted ciphertext (base64 encoded)
-    pub ciphertext: String,
-}
-
-/// Arguments for wallet decryption
-///
-/// Reference: TS WalletDecryptArgs
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DecryptArgs {
-    /// Data to decrypt (base64 encoded)
-    pub ciphertext: String,
-    
-    /// Protocol ID for key derivation
-    pub protocol_id: Vec<String>,
-    
-    /// Key ID for key derivation
-    pub key_id: String,
-    
-    /// Counterparty public key (optional)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub counterparty: Option<String>,
-    
-    /// Whether this uses privileged key
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub privileged: Option<bool>,
-}
-
-/// Result from wallet decryption
-///
-/// Reference: TS WalletDecryptResult
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DecryptResult {
-    /// Decrypted plaintext (base64 encoded)
-    pub plaintext: String,
-}
+use crate::sdk::{WalletDecryptArgs, WalletDecryptResult, WalletEncryptArgs, WalletEncryptResult};
 
 /// Encrypt data using wallet-derived keys
 ///
-/// Reference: TS wallet.encrypt (Wallet.ts)
+/// Reference: TS ProtoWallet.encrypt
 ///
-/// Uses BRC-42/43 key derivation to create encryption keys, then
-/// encrypts the data using AES-256-GCM.
+/// Derives the BRC-2 symmetric key for `protocol_id`/`key_id` shared with
+/// `counterparty` (default "self"), then encrypts the data using AES-256-GCM.
 ///
 /// # Arguments
 ///
-/// * `root_key` - Wallet's root private key (32 bytes)
 /// * `args` - Encryption parameters
+/// * `key_deriver` - Deriver over the wallet root key
 ///
 /// # Returns
 ///
-/// Encrypted ciphertext
-pub fn encrypt(root_key: &[u8], args: EncryptArgs) -> WalletResult<EncryptResult> {
-    // Validate root key
-    if root_key.len() != 32 {
-        return Err(WalletError::invalid_parameter(
-            "root_key",
-            "Must be 32 bytes"
-        ));
-    }
-    
-    // Decode plaintext from base64
-    let plaintext = base64::decode(&args.plaintext)
-        .map_err(|e| WalletError::invalid_parameter("plaintext", &format!("Invalid base64: {}", e)))?;
-    
-    // Derive encryption key using BRC-42/43
-    let encryption_key = derive_encryption_key(
-        root_key,
-        &args.protocol_id,
-        &args.key_id,
-        args.counterparty.as_deref(),
-        args.privileged.unwrap_or(false),
-    )?;
-    
-    // Encrypt using AES-256-GCM
-    let ciphertext_bytes = encrypt_with_aes_gcm(&plaintext, &encryption_key)?;
-    
-    // Encode ciphertext to base64
-    let ciphertext = base64::encode(&ciphertext_bytes);
-    
-    Ok(EncryptResult { ciphertext })
+/// Ciphertext as `[32-byte IV][ciphertext][16-byte tag]`
+pub async fn encrypt(args: &WalletEncryptArgs, key_deriver: &RootKeyDeriver) -> WalletResult<WalletEncryptResult> {
+    let counterparty = args.counterparty.as_deref().unwrap_or("self");
+    let key = key_deriver.derive_symmetric_key(&args.protocol_id, &args.key_id, counterparty)?;
+    let ciphertext = encrypt_with_aes_gcm(&args.plaintext, &key)?;
+    Ok(WalletEncryptResult { ciphertext })
 }
 
 /// Decrypt data using wallet-derived keys
 ///
-/// Reference: TS wallet.decrypt (Wallet.ts)
+/// Reference: TS ProtoWallet.decrypt
 ///
-/// Uses BRC-42/43 key derivation to create decryption keys, then
-/// decrypts the data using AES-256-GCM.
+/// The counterparty derives the same symmetric key from their side, so data
+/// encrypted for us by `counterparty` decrypts with their identity key here.
 ///
 /// # Arguments
 ///
-/// * `root_key` - Wallet's root private key (32 bytes)
 /// * `args` - Decryption parameters
+/// * `key_deriver` - Deriver over the wallet root key
 ///
 /// # Returns
 ///
 /// Decrypted plaintext
-pub fn decrypt(root_key: &[u8], args: DecryptArgs) -> WalletResult<DecryptResult> {
-    // Validate root key
-    if root_key.len() != 32 {
-        return Err(WalletError::invalid_parameter(
-            "root_key",
-            "Must be 32 bytes"
-        ));
-    }
-    
-    // Decode ciphertext from base64
-    let ciphertext = base64::decode(&args.ciphertext)
-        .map_err(|e| WalletError::invalid_parameter("ciphertext", &format!("Invalid base64: {}", e)))?;
-    
-    // Derive decryption key using BRC-42/43
-    let decryption_key = derive_encryption_key(
-        root_key,
-        &args.protocol_id,
-        &args.key_id,
-        args.counterparty.as_deref(),
-        args.privileged.unwrap_or(false),
-    )?;
-    
-    // Decrypt using AES-256-GCM
-    let plaintext_bytes = decrypt_with_aes_gcm(&ciphertext, &decryption_key)?;
-    
-    // Encode plaintext to base64
-    let plaintext = base64::encode(&plaintext_bytes);
-    
-    Ok(DecryptResult { plaintext })
+pub async fn decrypt(args: &WalletDecryptArgs, key_deriver: &RootKeyDeriver) -> WalletResult<WalletDecryptResult> {
+    let counterparty = args.counterparty.as_deref().unwrap_or("self");
+    let key = key_deriver.derive_symmetric_key(&args.protocol_id, &args.key_id, counterparty)?;
+    let plaintext = decrypt_with_aes_gcm(&args.ciphertext, &key)?;
+    Ok(WalletDecryptResult { plaintext })
 }
 
 // ============================================================================
@@ -176,91 +61,79 @@ pub fn decrypt(root_key: &[u8], args: DecryptArgs) -> WalletResult<DecryptResult
 #[cfg(test)]
 mod tests {
     use super::*;
-    
-    #[test]
-    fn test_encrypt_decrypt_roundtrip() {
-        let root_key = vec![1u8; 32];
-        let plaintext_data = b"Hello, World!";
-        let plaintext_b64 = base64::encode(plaintext_data);
-        
-        let encrypt_args = EncryptArgs {
-            plaintext: plaintext_b64.clone(),
-            protocol_id: vec!["test".to_string()],
-            key_id: "encryption".to_string(),
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        // Encrypt
-        let encrypt_result = encrypt(&root_key, encrypt_args).unwrap();
-        assert!(!encrypt_result.ciphertext.is_empty());
-        assert_ne!(encrypt_result.ciphertext, plaintext_b64);
-        
-        // Decrypt
-        let decrypt_args = DecryptArgs {
-            ciphertext: encrypt_result.ciphertext,
-            protocol_id: vec!["test".to_string()],
-            key_id: "encryption".to_string(),
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        let decrypt_result = decrypt(&root_key, decrypt_args).unwrap();
-        assert_eq!(decrypt_result.plaintext, plaintext_b64);
+
+    fn encrypt_args(plaintext: &[u8], key_id: &str, counterparty: Option<String>) -> WalletEncryptArgs {
+        WalletEncryptArgs {
+            protocol_id: (2, "tests protocol suite".to_string()),
+            key_id: key_id.to_string(),
+            plaintext: plaintext.to_vec(),
+            counterparty,
+            privileged: None,
+            privileged_reason: None,
+        }
     }
-    
-    #[test]
-    fn test_encrypt_with_different_keys_produces_different_ciphertext() {
-        let root_key = vec![1u8; 32];
-        let plaintext = base64::encode(b"Test data");
-        
-        let args1 = EncryptArgs {
-            plaintext: plaintext.clone(),
-            protocol_id: vec!["test".to_string()],
-            key_id: "key1".to_string(),
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        let args2 = EncryptArgs {
-            plaintext: plaintext.clone(),
-            protocol_id: vec!["test".to_string()],
-            key_id: "key2".to_string(),
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        let result1 = encrypt(&root_key, args1).unwrap();
-        let result2 = encrypt(&root_key, args2).unwrap();
-        
-        assert_ne!(result1.ciphertext, result2.ciphertext);
+
+    fn decrypt_args(ciphertext: Vec<u8>, key_id: &str, counterparty: Option<String>) -> WalletDecryptArgs {
+        WalletDecryptArgs {
+            protocol_id: (2, "tests protocol suite".to_string()),
+            key_id: key_id.to_string(),
+            ciphertext,
+            counterparty,
+            privileged: None,
+            privileged_reason: None,
+        }
     }
-    
-    #[test]
-    fn test_decrypt_with_wrong_key_fails() {
-        let root_key = vec![1u8; 32];
-        let plaintext = base64::encode(b"Test data");
-        
-        let encrypt_args = EncryptArgs {
-            plaintext,
-            protocol_id: vec!["test".to_string()],
-            key_id: "key1".to_string(),
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        let encrypt_result = encrypt(&root_key, encrypt_args).unwrap();
-        
-        // Try to decrypt with different key_id
-        let decrypt_args = DecryptArgs {
-            ciphertext: encrypt_result.ciphertext,
-            protocol_id: vec!["test".to_string()],
-            key_id: "key2".to_string(), // Wrong key!
-            counterparty: None,
-            privileged: Some(false),
-        };
-        
-        let result = decrypt(&root_key, decrypt_args);
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_roundtrip() {
+        let deriver = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let plaintext = b"Hello, World!";
+
+        let encrypted = encrypt(&encrypt_args(plaintext, "1", None), &deriver).await.unwrap();
+        assert_eq!(encrypted.ciphertext.len(), plaintext.len() + 32 + 16);
+
+        let decrypted = decrypt(&decrypt_args(encrypted.ciphertext, "1", None), &deriver).await.unwrap();
+        assert_eq!(decrypted.plaintext, plaintext);
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_for_counterparty() {
+        let alice = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let bob = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let plaintext = b"BRC-2 message";
+
+        let encrypted = encrypt(&encrypt_args(plaintext, "1", Some(bob.identity_key_hex())), &alice).await.unwrap();
+
+        // Bob decrypts with Alice as counterparty
+        let decrypted = decrypt(&decrypt_args(encrypted.ciphertext.clone(), "1", Some(alice.identity_key_hex())), &bob).await.unwrap();
+        assert_eq!(decrypted.plaintext, plaintext);
+
+        // Nobody else can
+        let carol = RootKeyDeriver::new(&[3u8; 32]).unwrap();
+        assert!(decrypt(&decrypt_args(encrypted.ciphertext, "1", Some(alice.identity_key_hex())), &carol).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_with_wrong_key_id_fails() {
+        let deriver = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+
+        let encrypted = encrypt(&encrypt_args(b"Test data", "key1", None), &deriver).await.unwrap();
+
+        let result = decrypt(&decrypt_args(encrypted.ciphertext, "key2", None), &deriver).await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_wire_shape() {
+        let args: WalletEncryptArgs = serde_json::from_value(serde_json::json!({
+            "protocolID": [1, "tests protocol suite"],
+            "keyID": "1",
+            "plaintext": [104, 105],
+            "counterparty": "anyone"
+        })).unwrap();
+        assert_eq!(args.plaintext, b"hi");
+
+        let result = serde_json::to_value(WalletEncryptResult { ciphertext: vec![1, 2] }).unwrap();
+        assert_eq!(result, serde_json::json!({ "ciphertext": [1, 2] }));
+    }
 }
@@ -0,0 +1,338 @@
+//! Transaction History Export
+//!
+//! Exports a user's transactions for accounting as CSV or JSON, optionally
+//! valued in fiat at the time of each transaction.
+//!
+//! ## Process Flow
+//!
+//! 1. **Collect** - Transactions in the date range that moved value, oldest
+//!    first, with their labels
+//! 2. **Value** - Historical fiat value from a `FiatRateProvider`, one rate
+//!    lookup per UTC day
+//! 3. **Format** - CSV with a header row, or a JSON array
+//!
+//! **Returns**: The formatted export
+
+use crate::services::FiatRateProvider;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wallet_storage::{AuthId, StorageError, TransactionStatus, WalletStorageProvider};
+
+/// Statuses of exported transactions: those whose value has left or reached the wallet
+pub const EXPORTED_TX_STATUSES: &[TransactionStatus] = &[
+    TransactionStatus::Completed,
+    TransactionStatus::Unproven,
+    TransactionStatus::Sending,
+];
+
+/// CSV header row, matching the fields of `HistoryRecord`
+const CSV_HEADER: &str = "time,txid,reference,status,direction,satoshis,description,labels,fiatValue,fiatCurrency";
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Period of exported history
+///
+/// `from` is inclusive and `to` exclusive; an open end is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| time >= from) && self.to.is_none_or(|to| time < to)
+    }
+}
+
+/// One exported transaction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    /// When the transaction was created
+    pub time: DateTime<Utc>,
+
+    pub txid: Option<String>,
+
+    pub reference: String,
+
+    pub status: String,
+
+    pub is_outgoing: bool,
+
+    /// Net change in the wallet's balance
+    pub satoshis: i64,
+
+    pub description: String,
+
+    pub labels: Vec<String>,
+
+    /// Value of `satoshis` at `time`, to the cent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<f64>,
+
+    /// Currency of `fiat_value`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+}
+
+/// Export the user's history in `range` as `format`
+///
+/// With `rates`, each record is valued in the provider's currency.
+pub async fn export_history(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    rates: Option<&dyn FiatRateProvider>,
+    format: ExportFormat,
+    range: DateRange,
+) -> Result<String, StorageError> {
+    let mut records = collect_history(storage, auth, range).await?;
+    if let Some(rates) = rates {
+        value_history(&mut records, rates).await?;
+    }
+    format_history(&records, format)
+}
+
+/// STEP 1: The user's exported transactions in `range`, oldest first
+pub async fn collect_history(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    range: DateRange,
+) -> Result<Vec<HistoryRecord>, StorageError> {
+    let user_id = auth.user_id_required()?;
+
+    let mut transactions = Vec::new();
+    for tx in storage.find_transactions(user_id, None, None).await? {
+        if !EXPORTED_TX_STATUSES.contains(&tx.status) {
+            continue;
+        }
+        let time = parse_timestamp(&tx.created_at).ok_or_else(|| {
+            StorageError::Database(format!(
+                "transaction {} has an invalid created_at: {}",
+                tx.transaction_id, tx.created_at
+            ))
+        })?;
+        if range.contains(time) {
+            transactions.push((time, tx));
+        }
+    }
+    transactions.sort_by_key(|(time, tx)| (*time, tx.transaction_id));
+
+    let mut records = Vec::with_capacity(transactions.len());
+    for (time, tx) in transactions {
+        let labels = storage.find_tx_labels_for_transaction(tx.transaction_id).await?;
+        records.push(HistoryRecord {
+            time,
+            txid: tx.txid,
+            reference: tx.reference,
+            status: tx.status.to_string(),
+            is_outgoing: tx.is_outgoing,
+            satoshis: tx.satoshis,
+            description: tx.description,
+            labels: labels.into_iter().map(|l| l.label).collect(),
+            fiat_value: None,
+            fiat_currency: None,
+        });
+    }
+    Ok(records)
+}
+
+/// STEP 2: Value each record at the BSV rate of its UTC day
+pub async fn value_history(
+    records: &mut [HistoryRecord],
+    rates: &dyn FiatRateProvider,
+) -> Result<(), StorageError> {
+    let mut daily_rates: HashMap<NaiveDate, f64> = HashMap::new();
+    for record in records.iter_mut() {
+        let day = record.time.date_naive();
+        let rate = match daily_rates.get(&day) {
+            Some(rate) => *rate,
+            None => {
+                let rate = rates.get_bsv_rate_at(record.time).await?;
+                daily_rates.insert(day, rate);
+                rate
+            }
+        };
+        let value = record.satoshis as f64 / 100_000_000.0 * rate;
+        record.fiat_value = Some((value * 100.0).round() / 100.0);
+        record.fiat_currency = Some(rates.currency().to_string());
+    }
+    Ok(())
+}
+
+/// STEP 3: Render records as `format`
+pub fn format_history(records: &[HistoryRecord], format: ExportFormat) -> Result<String, StorageError> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(records)
+            .map_err(|e| StorageError::InvalidArg(format!("history is not serializable: {}", e))),
+        ExportFormat::Csv => {
+            let mut csv = String::from(CSV_HEADER);
+            csv.push('\n');
+            for r in records {
+                let row = [
+                    r.time.to_rfc3339(),
+                    r.txid.clone().unwrap_or_default(),
+                    csv_text(&r.reference),
+                    r.status.clone(),
+                    if r.is_outgoing { "outgoing" } else { "incoming" }.to_string(),
+                    r.satoshis.to_string(),
+                    csv_text(&r.description),
+                    csv_text(&r.labels.join(";")),
+                    r.fiat_value.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+                    r.fiat_currency.clone().unwrap_or_default(),
+                ];
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            Ok(csv)
+        }
+    }
+}
+
+/// Quote a user-supplied CSV field
+///
+/// Fields a spreadsheet would evaluate as a formula are prefixed with `'`.
+fn csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Parse a storage timestamp: RFC 3339, or SQLite's `YYYY-MM-DD HH:MM:SS` in UTC
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::{Datelike, TimeZone};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wallet_storage::StorageResult;
+
+    struct MockRates {
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl FiatRateProvider for MockRates {
+        fn currency(&self) -> &str {
+            "USD"
+        }
+
+        async fn get_bsv_rate_at(&self, time: DateTime<Utc>) -> StorageResult<f64> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(if time.day() == 1 { 50.0 } else { 40.0 })
+        }
+    }
+
+    fn record(day: u32, satoshis: i64, description: &str, labels: &[&str]) -> HistoryRecord {
+        HistoryRecord {
+            time: Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap(),
+            txid: Some("aa".repeat(32)),
+            reference: format!("ref{}", day),
+            status: "completed".to_string(),
+            is_outgoing: satoshis < 0,
+            satoshis,
+            description: description.to_string(),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            fiat_value: None,
+            fiat_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_date_range() {
+        let range = DateRange {
+            from: Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()),
+            to: Some(Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap()),
+        };
+        assert!(range.contains(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+        assert!(!range.contains(Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap()));
+        assert!(DateRange::default().contains(Utc::now()));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        assert_eq!(parse_timestamp("2024-03-01T12:30:00+00:00"), Some(expected));
+        assert_eq!(parse_timestamp("2024-03-01T14:30:00+02:00"), Some(expected));
+        assert_eq!(parse_timestamp("2024-03-01 12:30:00"), Some(expected));
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn test_value_history_looks_up_each_day_once() {
+        let rates = MockRates { lookups: AtomicUsize::new(0) };
+        let mut records = vec![
+            record(1, 200_000_000, "salary", &[]),
+            record(1, -1_234_567, "coffee", &[]),
+            record(2, -50_000_000, "rent", &[]),
+        ];
+        value_history(&mut records, &rates).await.unwrap();
+
+        assert_eq!(rates.lookups.load(Ordering::SeqCst), 2);
+        assert_eq!(records[0].fiat_value, Some(100.0));
+        assert_eq!(records[1].fiat_value, Some(-0.62));
+        assert_eq!(records[2].fiat_value, Some(-20.0));
+        assert_eq!(records[2].fiat_currency.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn test_format_csv() {
+        let mut records = vec![
+            record(1, 1000, "plain", &["a", "b"]),
+            record(2, -500, "says \"hi\", twice", &[]),
+            record(3, -1, "=HYPERLINK(\"x\")", &[]),
+        ];
+        records[0].fiat_value = Some(12.5);
+        records[0].fiat_currency = Some("USD".to_string());
+
+        let csv = format_history(&records, ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            format!("2024-03-01T12:00:00+00:00,{},ref1,completed,incoming,1000,plain,a;b,12.50,USD", "aa".repeat(32))
+        );
+        assert!(lines[2].ends_with(",outgoing,-500,\"says \"\"hi\"\", twice\",,,"));
+        assert!(lines[3].contains(",\"'=HYPERLINK(\"\"x\"\")\","));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_format_json() {
+        let json = format_history(&[record(1, 1000, "plain", &["a"])], ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["time"], "2024-03-01T12:00:00Z");
+        assert_eq!(value[0]["isOutgoing"], false);
+        assert_eq!(value[0]["labels"][0], "a");
+        assert!(value[0].get("fiatValue").is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Fee Model
+//!
+//! Fee model validation and serialized transaction size calculation.
+//! Reference: wallet-toolbox/src/storage/StorageProvider.ts (validateStorageFeeModel)
+//! and wallet-toolbox/src/storage/methods/utils.ts (transactionSize)
+
+use wallet_storage::{StorageError, StorageResult};
+
+pub use wallet_storage::StorageFeeModel;
+
+/// Unlocking script length assumed for wallet change (P2PKH) inputs
+///
+/// Reference: TypeScript `changeUnlockingScriptLength` (createAction.ts)
+pub const CHANGE_UNLOCKING_SCRIPT_LENGTH: usize = 107;
+
+/// Locking script length of wallet change (P2PKH) outputs
+///
+/// Reference: TypeScript `changeLockingScriptLength` (createAction.ts)
+pub const CHANGE_LOCKING_SCRIPT_LENGTH: usize = 25;
+
+/// Validate a fee model, applying defaults for missing values
+///
+/// Only the `"sat/kb"` model is supported. A missing model yields the
+/// default of 1 sat/kb; a model without a value keeps the default rate.
+///
+/// Reference: TypeScript `validateStorageFeeModel(v?: StorageFeeModel)`
+pub fn validate_storage_fee_model(v: Option<&StorageFeeModel>) -> StorageResult<StorageFeeModel> {
+    let mut r = StorageFeeModel::default();
+
+    if let Some(v) = v {
+        if v.model != "sat/kb" {
+            return Err(StorageError::InvalidArg(
+                "StorageFeeModel.model must be \"sat/kb\"".to_string(),
+            ));
+        }
+        if let Some(value) = v.value {
+            if !value.is_finite() || value < 0.0 {
+                return Err(StorageError::InvalidArg(
+                    "StorageFeeModel.value must be a non-negative number".to_string(),
+                ));
+            }
+            r.value = Some(value);
+        }
+    }
+
+    Ok(r)
+}
+
+/// Fee in satoshis for a transaction of `size` bytes
+///
+/// Reference: TypeScript `Math.ceil((size / 1000) * feeModel.value)` (generateChange.ts)
+pub fn fee_for_size(fee_model: &StorageFeeModel, size: usize) -> i64 {
+    let rate = fee_model.value.unwrap_or(0.0);
+    ((size as f64 / 1000.0) * rate).ceil() as i64
+}
+
+/// Serialized size of a Bitcoin VarInt encoding `n`
+///
+/// Reference: TypeScript `varUintSize(val: number)`
+pub fn var_uint_size(n: usize) -> usize {
+    match n as u64 {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Serialized size of a transaction input
+///
+/// txid (32) + vout (4) + script length VarInt + script + sequence (4)
+///
+/// Reference: TypeScript `transactionInputSize(scriptSize: number)`
+pub fn transaction_input_size(unlocking_script_size: usize) -> usize {
+    32 + 4 + var_uint_size(unlocking_script_size) + unlocking_script_size + 4
+}
+
+/// Serialized size of a transaction output
+///
+/// satoshis (8) + script length VarInt + script
+///
+/// Reference: TypeScript `transactionOutputSize(scriptSize: number)`
+pub fn transaction_output_size(locking_script_size: usize) -> usize {
+    8 + var_uint_size(locking_script_size) + locking_script_size
+}
+
+/// Serialized size of a transaction given its input and output script sizes
+///
+/// version (4) + input count + inputs + output count + outputs + lockTime (4)
+///
+/// Reference: TypeScript `transactionSize(inputs: number[], outputs: number[])`
+pub fn transaction_size(unlocking_script_sizes: &[usize], locking_script_sizes: &[usize]) -> usize {
+    4
+        + var_uint_size(unlocking_script_sizes.len())
+        + unlocking_script_sizes.iter().map(|s| transaction_input_size(*s)).sum::<usize>()
+        + var_uint_size(locking_script_sizes.len())
+        + locking_script_sizes.iter().map(|s| transaction_output_size(*s)).sum::<usize>()
+        + 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sat_kb(value: f64) -> StorageFeeModel {
+        StorageFeeModel {
+            model: "sat/kb".to_string(),
+            value: Some(value),
+        }
+    }
+
+    #[test]
+    fn test_validate_fee_model_default() {
+        // TS Reference: validateStorageFeeModel(undefined) => { model: 'sat/kb', value: 1 }
+        let r = validate_storage_fee_model(None).unwrap();
+        assert_eq!(r, StorageFeeModel::default());
+    }
+
+    #[test]
+    fn test_validate_fee_model_keeps_value() {
+        let r = validate_storage_fee_model(Some(&sat_kb(0.5))).unwrap();
+        assert_eq!(r.value, Some(0.5));
+    }
+
+    #[test]
+    fn test_validate_fee_model_missing_value() {
+        let v = StorageFeeModel {
+            model: "sat/kb".to_string(),
+            value: None,
+        };
+        let r = validate_storage_fee_model(Some(&v)).unwrap();
+        assert_eq!(r.value, Some(1.0));
+    }
+
+    #[test]
+    fn test_validate_fee_model_rejects_unknown_model() {
+        // TS Reference: throws WERR_INVALID_PARAMETER('StorageFeeModel.model', '"sat/kb"')
+        let v = StorageFeeModel {
+            model: "sat/byte".to_string(),
+            value: Some(1.0),
+        };
+        assert!(matches!(
+            validate_storage_fee_model(Some(&v)),
+            Err(StorageError::InvalidArg(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_fee_model_rejects_negative_value() {
+        assert!(validate_storage_fee_model(Some(&sat_kb(-1.0))).is_err());
+        assert!(validate_storage_fee_model(Some(&sat_kb(f64::NAN))).is_err());
+    }
+
+    #[test]
+    fn test_var_uint_size() {
+        assert_eq!(var_uint_size(0), 1);
+        assert_eq!(var_uint_size(0xfc), 1);
+        assert_eq!(var_uint_size(0xfd), 3);
+        assert_eq!(var_uint_size(0xffff), 3);
+        assert_eq!(var_uint_size(0x10000), 5);
+    }
+
+    #[test]
+    fn test_p2pkh_input_output_sizes() {
+        // 107 byte unlocking script => 148 byte input; 25 byte locking script => 34 byte output
+        assert_eq!(transaction_input_size(CHANGE_UNLOCKING_SCRIPT_LENGTH), 148);
+        assert_eq!(transaction_output_size(CHANGE_LOCKING_SCRIPT_LENGTH), 34);
+    }
+
+    #[test]
+    fn test_transaction_size() {
+        // Empty: version + 2 counts + lockTime
+        assert_eq!(transaction_size(&[], &[]), 10);
+        // 1 P2PKH input, 2 P2PKH outputs
+        assert_eq!(transaction_size(&[107], &[25, 25]), 226);
+        // Large script pushes VarInt to 3 bytes
+        assert_eq!(transaction_size(&[], &[300]), 10 + 8 + 3 + 300);
+    }
+
+    #[test]
+    fn test_fee_for_size_rounds_up() {
+        assert_eq!(fee_for_size(&sat_kb(1.0), 226), 1);
+        assert_eq!(fee_for_size(&sat_kb(1.0), 1000), 1);
+        assert_eq!(fee_for_size(&sat_kb(1.0), 1001), 2);
+        assert_eq!(fee_for_size(&sat_kb(100.0), 226), 23);
+        assert_eq!(fee_for_size(&sat_kb(0.0), 226), 0);
+    }
+}
@@ -0,0 +1,633 @@
+//! generateChangeSdk - Change Allocation Algorithm
+//!
+//! Translates TypeScript generateChangeSdk from @wallet-toolbox/src/storage/methods/generateChange.ts
+//!
+//! Given the fixed inputs and outputs of a new transaction, allocates change
+//! inputs from storage and creates change outputs so that:
+//!
+//! - the transaction pays at least the fee required by the fee model,
+//! - a single change input exactly matching the shortfall is preferred,
+//! - the basket's desired UTXO count is approached by adding change outputs,
+//! - excess satoshis are spread over the change outputs, and
+//! - change outputs below `change_first_satoshis` (dust) are not created.
+
+use async_trait::async_trait;
+use wallet_storage::{StorageError, StorageResult};
+
+use super::fee_model::{fee_for_size, transaction_size, StorageFeeModel};
+use super::random_vals::RandomVals;
+
+/// Output satoshis value requesting "all remaining funds"
+///
+/// Reference: TypeScript `maxPossibleSatoshis`
+pub const MAX_POSSIBLE_SATOSHIS: i64 = 2_099_999_999_999_999;
+
+/// Fixed transaction input
+///
+/// Reference: TypeScript `GenerateChangeSdkInput`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateChangeSdkInput {
+    pub satoshis: i64,
+    pub unlocking_script_length: usize,
+}
+
+/// Fixed transaction output
+///
+/// Reference: TypeScript `GenerateChangeSdkOutput`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateChangeSdkOutput {
+    pub satoshis: i64,
+    pub locking_script_length: usize,
+}
+
+/// Change input allocated from storage
+///
+/// Reference: TypeScript `GenerateChangeSdkChangeInput`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateChangeSdkChangeInput {
+    pub output_id: i64,
+    pub satoshis: i64,
+}
+
+/// Change output to be created
+///
+/// Reference: TypeScript `GenerateChangeSdkChangeOutput`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateChangeSdkChangeOutput {
+    pub satoshis: i64,
+    pub locking_script_length: usize,
+}
+
+/// Parameters for `generate_change_sdk`
+///
+/// Reference: TypeScript `GenerateChangeSdkParams`
+#[derive(Debug, Clone)]
+pub struct GenerateChangeSdkParams {
+    pub fixed_inputs: Vec<GenerateChangeSdkInput>,
+    pub fixed_outputs: Vec<GenerateChangeSdkOutput>,
+    pub fee_model: StorageFeeModel,
+    /// Satoshis for each additional change output (basket `minimumDesiredUTXOValue`)
+    pub change_initial_satoshis: i64,
+    /// Satoshis for the first change output; also the smallest change worth creating
+    pub change_first_satoshis: i64,
+    pub change_locking_script_length: usize,
+    pub change_unlocking_script_length: usize,
+    /// Desired net increase in change outputs (desired UTXOs minus available UTXOs)
+    pub target_net_count: Option<i64>,
+    /// Random values in [0, 1) used cyclically for deterministic results
+    pub random_vals: Option<Vec<f64>>,
+}
+
+/// Adjustment applied to the output requesting `MAX_POSSIBLE_SATOSHIS`
+///
+/// Reference: TypeScript `GenerateChangeSdkResult.maxPossibleSatoshisAdjustment`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaxPossibleSatoshisAdjustment {
+    pub fixed_output_index: usize,
+    pub satoshis: i64,
+}
+
+/// Result of `generate_change_sdk`
+///
+/// Reference: TypeScript `GenerateChangeSdkResult`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateChangeSdkResult {
+    pub allocated_change_inputs: Vec<GenerateChangeSdkChangeInput>,
+    pub change_outputs: Vec<GenerateChangeSdkChangeOutput>,
+    pub size: usize,
+    pub fee: i64,
+    pub sats_per_kb: f64,
+    pub max_possible_satoshis_adjustment: Option<MaxPossibleSatoshisAdjustment>,
+}
+
+/// Source of change inputs for `generate_change_sdk`
+///
+/// Reference: TypeScript `allocateChangeInput` / `releaseChangeInput` callbacks
+#[async_trait]
+pub trait ChangeInputAllocator: Send {
+    /// Allocate (lock) a change input
+    ///
+    /// If `exact_satoshis` is given and an input with exactly that value is
+    /// available it should be returned. Otherwise prefer the smallest input
+    /// of at least `target_satoshis`, else the largest input available.
+    async fn allocate_change_input(
+        &mut self,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+    ) -> StorageResult<Option<GenerateChangeSdkChangeInput>>;
+
+    /// Release a previously allocated change input
+    async fn release_change_input(&mut self, output_id: i64) -> StorageResult<()>;
+}
+
+/// Validate `GenerateChangeSdkParams`
+///
+/// Returns the index of the fixed output requesting `MAX_POSSIBLE_SATOSHIS`, if any.
+///
+/// Reference: TypeScript `validateGenerateChangeSdkParams`
+pub fn validate_generate_change_sdk_params(params: &GenerateChangeSdkParams) -> StorageResult<Option<usize>> {
+    if params.fixed_inputs.iter().any(|i| i.satoshis < 0) {
+        return Err(StorageError::InvalidArg("fixedInputs satoshis must be non-negative".to_string()));
+    }
+    if params.fixed_outputs.iter().any(|o| o.satoshis < 0) {
+        return Err(StorageError::InvalidArg("fixedOutputs satoshis must be non-negative".to_string()));
+    }
+    if params.fee_model.model != "sat/kb" {
+        return Err(StorageError::InvalidArg("feeModel.model must be \"sat/kb\"".to_string()));
+    }
+    if params.change_initial_satoshis < 1 {
+        return Err(StorageError::InvalidArg("changeInitialSatoshis must be a positive integer".to_string()));
+    }
+    if params.change_first_satoshis < 1 {
+        return Err(StorageError::InvalidArg("changeFirstSatoshis must be a positive integer".to_string()));
+    }
+
+    let mut has_max_possible_output = None;
+    for (i, o) in params.fixed_outputs.iter().enumerate() {
+        if o.satoshis == MAX_POSSIBLE_SATOSHIS {
+            if has_max_possible_output.is_some() {
+                return Err(StorageError::InvalidArg(
+                    "fixedOutputs: only one output may request maxPossibleSatoshis".to_string(),
+                ));
+            }
+            has_max_possible_output = Some(i);
+        }
+    }
+
+    Ok(has_max_possible_output)
+}
+
+/// Working state of the algorithm
+struct ChangeState<'a> {
+    params: &'a GenerateChangeSdkParams,
+    fixed_outputs: Vec<GenerateChangeSdkOutput>,
+    allocated: Vec<GenerateChangeSdkChangeInput>,
+    change_outputs: Vec<GenerateChangeSdkChangeOutput>,
+    random: &'a mut RandomVals,
+}
+
+impl ChangeState<'_> {
+    /// Sum of fixed inputs and allocated change inputs
+    fn funding(&self) -> i64 {
+        self.params.fixed_inputs.iter().map(|i| i.satoshis).sum::<i64>()
+            + self.allocated.iter().map(|i| i.satoshis).sum::<i64>()
+    }
+
+    /// Sum of fixed outputs
+    fn spending(&self) -> i64 {
+        self.fixed_outputs.iter().map(|o| o.satoshis).sum()
+    }
+
+    /// Sum of change outputs
+    fn change(&self) -> i64 {
+        self.change_outputs.iter().map(|o| o.satoshis).sum()
+    }
+
+    fn fee(&self) -> i64 {
+        self.funding() - self.spending() - self.change()
+    }
+
+    fn size(&self, added_inputs: usize, added_outputs: usize) -> usize {
+        let inputs: Vec<usize> = self.params.fixed_inputs.iter()
+            .map(|i| i.unlocking_script_length)
+            .chain(std::iter::repeat_n(
+                self.params.change_unlocking_script_length,
+                self.allocated.len() + added_inputs,
+            ))
+            .collect();
+        let outputs: Vec<usize> = self.fixed_outputs.iter()
+            .map(|o| o.locking_script_length)
+            .chain(std::iter::repeat_n(
+                self.params.change_locking_script_length,
+                self.change_outputs.len() + added_outputs,
+            ))
+            .collect();
+        transaction_size(&inputs, &outputs)
+    }
+
+    fn fee_target(&self, added_inputs: usize, added_outputs: usize) -> i64 {
+        fee_for_size(&self.params.fee_model, self.size(added_inputs, added_outputs))
+    }
+
+    /// funding - spending - change - feeTarget; the goal is zero
+    fn fee_excess(&self, added_inputs: usize, added_outputs: usize) -> i64 {
+        self.fee() - self.fee_target(added_inputs, added_outputs)
+    }
+
+    fn net_change_count(&self) -> i64 {
+        self.change_outputs.len() as i64 - self.allocated.len() as i64
+    }
+
+    fn add_change_output(&mut self, satoshis: i64) {
+        self.change_outputs.push(GenerateChangeSdkChangeOutput {
+            satoshis,
+            locking_script_length: self.params.change_locking_script_length,
+        });
+    }
+
+    fn next_change_satoshis(&self) -> i64 {
+        if self.change_outputs.is_empty() {
+            self.params.change_first_satoshis
+        } else {
+            self.params.change_initial_satoshis
+        }
+    }
+
+    /// Random integer between `min` and `max` inclusive
+    fn rand(&mut self, min: i64, max: i64) -> i64 {
+        self.random.rand(min, max)
+    }
+
+    async fn release_all(&mut self, allocator: &mut dyn ChangeInputAllocator) -> StorageResult<()> {
+        while let Some(i) = self.allocated.pop() {
+            allocator.release_change_input(i.output_id).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Allocate change inputs and generate change outputs for a new transaction
+///
+/// Reference: TypeScript `generateChangeSdk(params, allocateChangeInput, releaseChangeInput)`
+pub async fn generate_change_sdk(
+    params: &GenerateChangeSdkParams,
+    allocator: &mut dyn ChangeInputAllocator,
+) -> StorageResult<GenerateChangeSdkResult> {
+    let mut random = RandomVals::new(params.random_vals.clone());
+    generate_change_sdk_with(params, allocator, &mut random).await
+}
+
+/// `generate_change_sdk` drawing on a caller's `random` rather than
+/// `params.random_vals`, so the rotation continues across createAction steps
+pub async fn generate_change_sdk_with(
+    params: &GenerateChangeSdkParams,
+    allocator: &mut dyn ChangeInputAllocator,
+    random: &mut RandomVals,
+) -> StorageResult<GenerateChangeSdkResult> {
+    let has_max_possible_output = validate_generate_change_sdk_params(params)?;
+
+    let mut s = ChangeState {
+        params,
+        fixed_outputs: params.fixed_outputs.clone(),
+        allocated: Vec::new(),
+        change_outputs: Vec::new(),
+        random,
+    };
+
+    let mut max_possible_satoshis_adjustment = None;
+
+    if let Some(index) = has_max_possible_output {
+        // Spend everything available to the max possible output; no change outputs.
+        s.fixed_outputs[index].satoshis = 0;
+        while let Some(input) = allocator.allocate_change_input(MAX_POSSIBLE_SATOSHIS, None).await? {
+            s.allocated.push(input);
+        }
+        let satoshis = s.fee_excess(0, 0);
+        if satoshis <= 0 {
+            let needed = s.spending() + s.fee_target(0, 0) + 1;
+            let available = s.funding();
+            s.release_all(allocator).await?;
+            return Err(insufficient_funds(needed, available));
+        }
+        s.fixed_outputs[index].satoshis = satoshis;
+        max_possible_satoshis_adjustment = Some(MaxPossibleSatoshisAdjustment {
+            fixed_output_index: index,
+            satoshis,
+        });
+    } else {
+        let target_net_count = params.target_net_count.unwrap_or(0);
+        let mut balance_inputs = params.target_net_count.is_some();
+        let mut desired_outputs = target_net_count.max(0) as usize;
+
+        loop {
+            s.release_all(allocator).await?;
+            s.change_outputs.clear();
+            for _ in 0..desired_outputs {
+                let satoshis = s.next_change_satoshis();
+                s.add_change_output(satoshis);
+            }
+
+            // Fund the transaction, preferring an exact match when no change is wanted
+            while s.fee_excess(0, 0) < 0 {
+                let add_output = balance_inputs && s.net_change_count() - 1 < target_net_count;
+                let output_satoshis = if add_output { s.next_change_satoshis() } else { 0 };
+                let added_outputs = usize::from(add_output);
+                let target_satoshis = -s.fee_excess(1, added_outputs) + output_satoshis;
+                let exact_satoshis = if s.change_outputs.is_empty() && !add_output {
+                    Some(-s.fee_excess(1, 0))
+                } else {
+                    None
+                };
+
+                match allocator.allocate_change_input(target_satoshis, exact_satoshis).await? {
+                    Some(input) => {
+                        s.allocated.push(input);
+                        if add_output {
+                            s.add_change_output(output_satoshis);
+                        }
+                    }
+                    None => break,
+                }
+            }
+
+            if s.fee_excess(0, 0) >= 0 {
+                break;
+            }
+
+            if s.change_outputs.is_empty() {
+                let needed = s.spending() + s.fee_target(0, 0);
+                let available = s.funding();
+                s.release_all(allocator).await?;
+                return Err(insufficient_funds(needed, available));
+            }
+
+            // Not enough funding for the desired change outputs: retry with fewer
+            balance_inputs = false;
+            desired_outputs = s.change_outputs.len() - 1;
+        }
+
+        // Recapture excess in a change output only when it is not dust
+        if s.change_outputs.is_empty() && s.fee_excess(0, 1) >= params.change_first_satoshis {
+            s.add_change_output(0);
+        }
+
+        // Distribute the excess over the change outputs
+        let mut excess = if s.change_outputs.is_empty() { 0 } else { s.fee_excess(0, 0) };
+        while excess > 0 {
+            if s.change_outputs.len() == 1 {
+                s.change_outputs[0].satoshis += excess;
+                excess = 0;
+            } else if s.change_outputs[0].satoshis < params.change_initial_satoshis {
+                let sats = excess.min(params.change_initial_satoshis - s.change_outputs[0].satoshis);
+                s.change_outputs[0].satoshis += sats;
+                excess -= sats;
+            } else {
+                // A random 25% to 50% of the remaining excess, at least one satoshi
+                let sats = 1.max((s.rand(2500, 5000) as f64 / 10000.0 * excess as f64).floor() as i64);
+                let index = s.rand(0, s.change_outputs.len() as i64 - 1) as usize;
+                s.change_outputs[index].satoshis += sats;
+                excess -= sats;
+            }
+        }
+    }
+
+    let r = GenerateChangeSdkResult {
+        size: s.size(0, 0),
+        fee: s.fee(),
+        sats_per_kb: params.fee_model.value.unwrap_or(0.0),
+        allocated_change_inputs: s.allocated,
+        change_outputs: s.change_outputs,
+        max_possible_satoshis_adjustment,
+    };
+
+    validate_generate_change_sdk_result(params, &r)?;
+
+    Ok(r)
+}
+
+/// Check the balance and fee of a `GenerateChangeSdkResult`
+///
+/// Reference: TypeScript `validateGenerateChangeSdkResult`
+pub fn validate_generate_change_sdk_result(
+    params: &GenerateChangeSdkParams,
+    r: &GenerateChangeSdkResult,
+) -> StorageResult<()> {
+    let mut spending: i64 = params.fixed_outputs.iter().map(|o| o.satoshis).sum();
+    if let Some(adj) = &r.max_possible_satoshis_adjustment {
+        spending += adj.satoshis - params.fixed_outputs[adj.fixed_output_index].satoshis;
+    }
+    let funding = params.fixed_inputs.iter().map(|i| i.satoshis).sum::<i64>()
+        + r.allocated_change_inputs.iter().map(|i| i.satoshis).sum::<i64>();
+    let change: i64 = r.change_outputs.iter().map(|o| o.satoshis).sum();
+
+    if funding - spending - change != r.fee {
+        return Err(StorageError::Database(format!(
+            "generateChangeSdk error: fee {} does not balance funding {} spending {} change {}",
+            r.fee, funding, spending, change
+        )));
+    }
+    let fee_target = fee_for_size(&params.fee_model, r.size);
+    if r.fee < fee_target {
+        return Err(StorageError::Database(format!(
+            "generateChangeSdk error: fee {} is less than required {}",
+            r.fee, fee_target
+        )));
+    }
+    if r.change_outputs.iter().any(|o| o.satoshis < 1) {
+        return Err(StorageError::Database(
+            "generateChangeSdk error: change output with zero satoshis".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn insufficient_funds(needed: i64, available: i64) -> StorageError {
+    StorageError::InsufficientFunds {
+        total_satoshis_needed: needed,
+        more_satoshis_needed: needed - available,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// In-memory change UTXOs mirroring StorageKnex.allocateChangeInput selection
+    struct MockAllocator {
+        available: Vec<GenerateChangeSdkChangeInput>,
+        allocated: Vec<GenerateChangeSdkChangeInput>,
+        released: Vec<i64>,
+    }
+
+    impl MockAllocator {
+        fn new(satoshis: &[i64]) -> Self {
+            Self {
+                available: satoshis.iter().enumerate()
+                    .map(|(i, s)| GenerateChangeSdkChangeInput { output_id: i as i64 + 1, satoshis: *s })
+                    .collect(),
+                allocated: Vec::new(),
+                released: Vec::new(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChangeInputAllocator for MockAllocator {
+        async fn allocate_change_input(
+            &mut self,
+            target_satoshis: i64,
+            exact_satoshis: Option<i64>,
+        ) -> StorageResult<Option<GenerateChangeSdkChangeInput>> {
+            let pick = exact_satoshis
+                .and_then(|e| self.available.iter().position(|o| o.satoshis == e))
+                .or_else(|| {
+                    self.available.iter().enumerate()
+                        .filter(|(_, o)| o.satoshis >= target_satoshis)
+                        .min_by_key(|(_, o)| o.satoshis)
+                        .map(|(i, _)| i)
+                })
+                .or_else(|| {
+                    self.available.iter().enumerate()
+                        .max_by_key(|(_, o)| o.satoshis)
+                        .map(|(i, _)| i)
+                });
+            let input = pick.map(|i| self.available.remove(i));
+            self.allocated.extend(input.clone());
+            Ok(input)
+        }
+
+        async fn release_change_input(&mut self, output_id: i64) -> StorageResult<()> {
+            let i = self.allocated.iter().position(|o| o.output_id == output_id).unwrap();
+            self.available.push(self.allocated.remove(i));
+            self.released.push(output_id);
+            Ok(())
+        }
+    }
+
+    fn params(outputs: &[i64], target_net_count: Option<i64>) -> GenerateChangeSdkParams {
+        GenerateChangeSdkParams {
+            fixed_inputs: vec![],
+            fixed_outputs: outputs.iter()
+                .map(|s| GenerateChangeSdkOutput { satoshis: *s, locking_script_length: 25 })
+                .collect(),
+            fee_model: StorageFeeModel { model: "sat/kb".to_string(), value: Some(100.0) },
+            change_initial_satoshis: 1000,
+            change_first_satoshis: 250,
+            change_locking_script_length: 25,
+            change_unlocking_script_length: 107,
+            target_net_count,
+            random_vals: Some(vec![0.5]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exact_satoshis_match_avoids_change() {
+        // TS Reference: exactSatoshis allocation yields no change output
+        // 1 input + 1 output = 192 bytes => 20 sats at 100 sat/kb
+        let p = params(&[1000], None);
+        let mut allocator = MockAllocator::new(&[500, 1020, 5000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        assert_eq!(r.allocated_change_inputs.len(), 1);
+        assert_eq!(r.allocated_change_inputs[0].satoshis, 1020);
+        assert!(r.change_outputs.is_empty());
+        assert_eq!(r.fee, 20);
+    }
+
+    #[tokio::test]
+    async fn test_single_change_output_for_excess() {
+        let p = params(&[1000], None);
+        let mut allocator = MockAllocator::new(&[5000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        assert_eq!(r.allocated_change_inputs.len(), 1);
+        assert_eq!(r.change_outputs.len(), 1);
+        // 1 input + 2 outputs = 226 bytes => 23 sats
+        assert_eq!(r.fee, 23);
+        assert_eq!(r.change_outputs[0].satoshis, 5000 - 1000 - 23);
+    }
+
+    #[tokio::test]
+    async fn test_dust_excess_left_as_fee() {
+        // Excess after the fee is below change_first_satoshis: no dust change output
+        let p = params(&[1000], None);
+        let mut allocator = MockAllocator::new(&[1100]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        assert!(r.change_outputs.is_empty());
+        assert_eq!(r.fee, 100);
+    }
+
+    #[tokio::test]
+    async fn test_target_net_count_splits_change() {
+        // Basket wants 3 more UTXOs: excess is split over multiple change outputs
+        let p = params(&[1000], Some(3));
+        let mut allocator = MockAllocator::new(&[100_000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        // Net change count (outputs created minus inputs consumed) reaches the target
+        assert_eq!(r.allocated_change_inputs.len(), 1);
+        assert_eq!(r.change_outputs.len(), 4);
+        assert!(r.change_outputs.iter().all(|o| o.satoshis >= 1000));
+        let change: i64 = r.change_outputs.iter().map(|o| o.satoshis).sum();
+        assert_eq!(change + 1000 + r.fee, 100_000);
+    }
+
+    #[tokio::test]
+    async fn test_target_net_count_reduced_when_underfunded() {
+        // Not enough to fund 6 change outputs of 1000 sats each
+        let p = params(&[1000], Some(5));
+        let mut allocator = MockAllocator::new(&[3000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        assert!(r.change_outputs.len() < 5);
+        assert!(!r.change_outputs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multiple_inputs_allocated() {
+        let p = params(&[6000], None);
+        let mut allocator = MockAllocator::new(&[2000, 3000, 4000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        let funding: i64 = r.allocated_change_inputs.iter().map(|i| i.satoshis).sum();
+        assert!(r.allocated_change_inputs.len() >= 2);
+        assert!(funding >= 6000 + r.fee);
+        assert!(r.fee >= fee_for_size(&p.fee_model, r.size));
+    }
+
+    #[tokio::test]
+    async fn test_fixed_inputs_fund_without_change_inputs() {
+        let mut p = params(&[1000], None);
+        p.fixed_inputs = vec![GenerateChangeSdkInput { satoshis: 2000, unlocking_script_length: 107 }];
+        let mut allocator = MockAllocator::new(&[]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        assert!(r.allocated_change_inputs.is_empty());
+        assert_eq!(r.change_outputs.len(), 1);
+        assert_eq!(r.change_outputs[0].satoshis, 2000 - 1000 - r.fee);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_funds_releases_inputs() {
+        let p = params(&[10_000], None);
+        let mut allocator = MockAllocator::new(&[1000, 2000]);
+
+        let err = generate_change_sdk(&p, &mut allocator).await.unwrap_err();
+        match err {
+            StorageError::InsufficientFunds { total_satoshis_needed, more_satoshis_needed } => {
+                assert!(total_satoshis_needed > 10_000);
+                assert_eq!(more_satoshis_needed, total_satoshis_needed - 3000);
+            }
+            err => panic!("expected InsufficientFunds, got {:?}", err),
+        }
+        assert_eq!(allocator.released.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_possible_satoshis() {
+        // TS Reference: maxPossibleSatoshisAdjustment spends all available change
+        let p = params(&[MAX_POSSIBLE_SATOSHIS], None);
+        let mut allocator = MockAllocator::new(&[1000, 2000]);
+
+        let r = generate_change_sdk(&p, &mut allocator).await.unwrap();
+        let adj = r.max_possible_satoshis_adjustment.clone().unwrap();
+        assert_eq!(adj.fixed_output_index, 0);
+        assert_eq!(r.allocated_change_inputs.len(), 2);
+        assert!(r.change_outputs.is_empty());
+        assert_eq!(adj.satoshis + r.fee, 3000);
+    }
+
+    #[test]
+    fn test_validate_params_rejects_two_max_outputs() {
+        let p = params(&[MAX_POSSIBLE_SATOSHIS, MAX_POSSIBLE_SATOSHIS], None);
+        assert!(validate_generate_change_sdk_params(&p).is_err());
+    }
+
+    #[test]
+    fn test_validate_params_rejects_zero_change_satoshis() {
+        let mut p = params(&[1000], None);
+        p.change_first_satoshis = 0;
+        assert!(validate_generate_change_sdk_params(&p).is_err());
+    }
+}
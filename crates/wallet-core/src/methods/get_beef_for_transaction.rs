@@ -0,0 +1,136 @@
+//! Get BEEF For Transaction
+//!
+//! **Reference**: TypeScript `src/storage/StorageProvider.ts`
+//! (`getBeefForTransaction`, `getValidBeefForTxid`)
+//!
+//! Assembles a BEEF for a txid known to storage by walking its inputs:
+//! - **Known** - txids in `knownTxids` (or, with `trustSelf`, any txid storage
+//!   knows to be valid) are merged as txid-only
+//! - **Proven** - mined ancestors are merged with their merkle path and end
+//!   the walk
+//! - **Unproven** - raw transactions are merged along with any stored input
+//!   BEEF, and their inputs are walked in turn
+
+use crate::beef::{Beef, MerklePath};
+use crate::transaction::{ByteReader, Transaction};
+use wallet_storage::{StorageError, TableProvenTx, WalletStorageProvider};
+
+/// Txid of the outpoint spent by coinbase inputs
+const COINBASE_TXID: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Options for assembling a BEEF from storage
+///
+/// Matches TypeScript `StorageGetBeefOptions`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageGetBeefOptions {
+    /// Merge txids storage knows to be valid as txid-only (TS trustSelf: 'known')
+    pub trust_self: bool,
+
+    /// Txids the recipient already has, merged as txid-only
+    pub known_txids: Vec<String>,
+
+    /// BEEF to extend
+    pub merge_to_beef: Option<Vec<u8>>,
+}
+
+/// BEEF for `txid` assembled from storage
+///
+/// Reference: TypeScript StorageProvider.getBeefForTransaction
+pub async fn get_beef_for_transaction(
+    storage: &dyn WalletStorageProvider,
+    txid: &str,
+    options: &StorageGetBeefOptions,
+) -> Result<Beef, StorageError> {
+    let mut beef = Beef::new_v2();
+    if let Some(merge_to_beef) = &options.merge_to_beef {
+        beef.merge_beef(merge_to_beef).map_err(|e| StorageError::InvalidArg(format!("mergeToBeef: {}", e)))?;
+    }
+    get_valid_beef_for_txid(storage, txid, &mut beef, options).await?;
+    Ok(beef)
+}
+
+/// Merge `txid` and the ancestors needed to validate it into `beef`
+///
+/// Reference: TypeScript StorageProvider.getValidBeefForTxid
+///
+/// Transactions already in `beef` are left as they are. Fails when a
+/// transaction is neither in `beef`, known, nor in storage.
+pub async fn get_valid_beef_for_txid(
+    storage: &dyn WalletStorageProvider,
+    txid: &str,
+    beef: &mut Beef,
+    options: &StorageGetBeefOptions,
+) -> Result<(), StorageError> {
+    let mut pending = vec![txid.to_string()];
+    while let Some(txid) = pending.pop() {
+        if beef.find_txid(&txid).is_some() {
+            continue;
+        }
+        if options.known_txids.contains(&txid)
+            || (options.trust_self && storage.verify_known_valid_transaction(&txid).await?)
+        {
+            beef.merge_txid_only(&txid);
+            continue;
+        }
+
+        let r = storage.get_proven_or_raw_tx(&txid).await?;
+        if let Some(proven) = r.proven {
+            beef.merge_proven_tx(&proven.raw_tx, proven_tx_merkle_path(&proven)?)
+                .map_err(|e| StorageError::InvalidArg(format!("rawTx of {}: {}", txid, e)))?;
+        } else if let Some(raw_tx) = r.raw_tx {
+            beef.merge_raw_tx(&raw_tx)
+                .map_err(|e| StorageError::InvalidArg(format!("rawTx of {}: {}", txid, e)))?;
+            if let Some(input_beef) = &r.input_beef {
+                beef.merge_beef(input_beef)
+                    .map_err(|e| StorageError::InvalidArg(format!("inputBEEF of {}: {}", txid, e)))?;
+            }
+            pending.extend(source_txids(&raw_tx)?.into_iter().filter(|t| beef.find_txid(t).is_none()));
+        } else {
+            return Err(StorageError::InvalidArg(format!("{} is not known to storage", txid)));
+        }
+    }
+    beef.sort_txs().map_err(|e| StorageError::InvalidArg(format!("BEEF: {}", e)))
+}
+
+/// Merkle path of a proven transaction
+///
+/// Reference: TypeScript EntityProvenTx.getMerklePath
+pub fn proven_tx_merkle_path(proven: &TableProvenTx) -> Result<MerklePath, StorageError> {
+    MerklePath::read_from(&mut ByteReader::new(&proven.merkle_path))
+        .map_err(|e| StorageError::InvalidArg(format!("merklePath of {}: {}", proven.txid, e)))
+}
+
+/// Txids of the transactions `raw_tx` spends, excluding coinbase inputs
+fn source_txids(raw_tx: &[u8]) -> Result<Vec<String>, StorageError> {
+    let tx = Transaction::from_bytes(raw_tx).map_err(|e| StorageError::InvalidArg(format!("rawTx: {}", e)))?;
+    let mut txids: Vec<String> = Vec::new();
+    for input in tx.inputs {
+        if input.prev_out.txid != COINBASE_TXID && !txids.contains(&input.prev_out.txid) {
+            txids.push(input.prev_out.txid);
+        }
+    }
+    Ok(txids)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{OutPoint, TxInput, TxOutput};
+
+    #[test]
+    fn test_source_txids() {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("11".repeat(32), 0)));
+        tx.add_input(TxInput::new(OutPoint::new("11".repeat(32), 1)));
+        tx.add_input(TxInput::new(OutPoint::new(COINBASE_TXID, 0xffffffff)));
+        tx.add_input(TxInput::new(OutPoint::new("22".repeat(32), 0)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+
+        let txids = source_txids(&tx.serialize().unwrap()).unwrap();
+        assert_eq!(txids, vec!["11".repeat(32), "22".repeat(32)]);
+    }
+}
@@ -12,8 +12,9 @@ use crate::sdk::{
 
 /// Create an HMAC using a wallet-derived key
 ///
-/// Derives a key using the protocol ID, key ID, and counterparty,
-/// then creates an HMAC-SHA256 of the provided data.
+/// Derives the symmetric key shared with the counterparty (default "self")
+/// for the protocol ID and key ID, then creates an HMAC-SHA256 of the
+/// provided data.
 ///
 /// # Arguments
 /// * `args` - HMAC creation arguments (protocol, key ID, data, counterparty)
@@ -22,7 +23,7 @@ use crate::sdk::{
 /// # Returns
 /// HMAC bytes (32 bytes)
 ///
-/// Reference: TypeScript `createHmac()` in SDK
+/// Reference: TypeScript `createHmac()` in ProtoWallet
 pub async fn create_hmac(
     args: &CreateHmacArgs,
     key_deriver: &dyn KeyDeriver,
@@ -31,7 +32,7 @@ pub async fn create_hmac(
     let counterparty = args.counterparty.as_deref().unwrap_or("self");
     
     let derived_key = key_deriver
-        .derive_key(
+        .derive_symmetric_key(
             &args.protocol_id,
             &args.key_id,
             counterparty,
@@ -56,7 +57,7 @@ pub async fn create_hmac(
 /// # Returns
 /// `{ valid: true }` on success, error on failure
 ///
-/// Reference: TypeScript `verifyHmac()` in SDK
+/// Reference: TypeScript `verifyHmac()` in ProtoWallet
 pub async fn verify_hmac(
     args: &VerifyHmacArgs,
     key_deriver: &dyn KeyDeriver,
@@ -65,7 +66,7 @@ pub async fn verify_hmac(
     let counterparty = args.counterparty.as_deref().unwrap_or("self");
     
     let derived_key = key_deriver
-        .derive_key(
+        .derive_symmetric_key(
             &args.protocol_id,
             &args.key_id,
             counterparty,
@@ -119,6 +120,15 @@ mod tests {
         ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
             Ok(vec![0x03; 33])
         }
+        
+        async fn derive_symmetric_key(
+            &self,
+            _protocol_id: &(u8, String),
+            _key_id: &str,
+            _counterparty: &str,
+        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(vec![0x24; 32])
+        }
     }
     
     #[tokio::test]
@@ -205,4 +215,33 @@ mod tests {
         // Should produce the same HMAC
         assert_eq!(hmac1.hmac, hmac2.hmac);
     }
+    
+    #[tokio::test]
+    async fn test_hmac_shared_with_counterparty() {
+        use crate::keys::RootKeyDeriver;
+        
+        let alice = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let bob = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let args = CreateHmacArgs {
+            protocol_id: (2, "tests protocol suite".to_string()),
+            key_id: "key1".to_string(),
+            data: vec![1, 2, 3, 4],
+            counterparty: Some(bob.identity_key_hex()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        let created = create_hmac(&args, &alice).await.unwrap();
+        
+        // Bob verifies with Alice as counterparty
+        let verify_args = VerifyHmacArgs {
+            protocol_id: args.protocol_id.clone(),
+            key_id: args.key_id.clone(),
+            data: args.data.clone(),
+            hmac: created.hmac,
+            counterparty: Some(alice.identity_key_hex()),
+            privileged: None,
+            privileged_reason: None,
+        };
+        assert!(verify_hmac(&verify_args, &bob).await.unwrap().valid);
+    }
 }
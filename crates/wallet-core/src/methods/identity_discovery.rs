@@ -0,0 +1,427 @@
+//! Identity Discovery
+//!
+//! **Reference**: TypeScript `Wallet.discoverByIdentityKey` / `discoverByAttributes`
+//! and `transformVerifiableCertificatesWithTrust`
+//!
+//! Certificates are looked up through an `IdentityResolver`, grouped by
+//! subject, and kept only for subjects whose trusted certifiers' summed trust
+//! reaches the user's trust level.
+//!
+//! Unlike TS, which queries the overlay on every call, each lookup is cached
+//! in storage for `IDENTITY_CACHE_TTL` with the subject's displayable
+//! identity and trust score. `forceRefresh` bypasses the cache. Trust is
+//! applied when reading, so trust level changes take effect immediately;
+//! the cache key includes the trusted certifiers, as the overlay query does.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use wallet_storage::{AuthId, TableIdentityCache, WalletStorageProvider};
+
+use crate::managers::wallet_settings_manager::TrustSettings;
+use crate::sdk::{
+    validate_hex_string, DiscoverByAttributesArgs, DiscoverByIdentityKeyArgs, DiscoverCertificatesResult,
+    IdentityCertificate, IdentityCertifier, WalletError, WalletResult,
+};
+use crate::services::IdentityResolver;
+
+/// How long a resolved lookup is served from the cache
+///
+/// Reference: TS Wallet overlay cache TTL (2 minutes)
+pub const IDENTITY_CACHE_TTL: chrono::Duration = chrono::Duration::minutes(2);
+
+/// Field names holding a display name, most preferred first
+const NAME_FIELDS: &[&str] = &["name", "userName", "email", "phoneNumber"];
+
+/// Field names holding an avatar URL, most preferred first
+const AVATAR_FIELDS: &[&str] = &["profilePhoto", "avatarURL", "icon"];
+
+/// An identity as a UI shows it
+///
+/// Reference: TS DisplayableIdentity from @bsv/sdk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayableIdentity {
+    pub name: String,
+    #[serde(rename = "avatarURL")]
+    pub avatar_url: String,
+    pub abbreviated_key: String,
+    pub identity_key: String,
+    #[serde(rename = "badgeIconURL")]
+    pub badge_icon_url: String,
+    pub badge_label: String,
+
+    /// Summed trust of the certifiers vouching for the identity
+    pub trust_score: i64,
+}
+
+/// Keep certificates of subjects trusted enough, most trusted certifier first
+///
+/// Certificates of untrusted certifiers are dropped; the rest get the
+/// certifier's name, icon and trust from `trust_settings`.
+///
+/// Reference: TS transformVerifiableCertificatesWithTrust
+pub fn transform_certificates_with_trust(
+    trust_settings: &TrustSettings,
+    certificates: Vec<IdentityCertificate>,
+) -> DiscoverCertificatesResult {
+    let mut groups: BTreeMap<String, (i64, Vec<IdentityCertificate>)> = BTreeMap::new();
+    for mut cert in certificates {
+        let Some(certifier) = trust_settings.trusted_certifiers.iter().find(|c| c.identity_key == cert.certifier) else {
+            continue;
+        };
+        cert.certifier_info = IdentityCertifier {
+            name: certifier.name.clone(),
+            icon_url: certifier.icon_url.clone().unwrap_or_default(),
+            description: certifier.description.clone(),
+            trust: certifier.trust,
+        };
+        let group = groups.entry(cert.subject.clone()).or_default();
+        group.0 += i64::from(certifier.trust);
+        group.1.push(cert);
+    }
+
+    let mut certificates: Vec<IdentityCertificate> = groups
+        .into_values()
+        .filter(|(trust, _)| *trust >= i64::from(trust_settings.trust_level))
+        .flat_map(|(_, members)| members)
+        .collect();
+    certificates.sort_by_key(|c| std::cmp::Reverse(c.certifier_info.trust));
+    DiscoverCertificatesResult { total_certificates: certificates.len() as i64, certificates }
+}
+
+/// Summed trust of the certifiers vouching for `subject`
+fn subject_trust(trust_settings: &TrustSettings, certificates: &[IdentityCertificate], subject: &str) -> i64 {
+    certificates
+        .iter()
+        .filter(|c| c.subject == subject)
+        .filter_map(|c| trust_settings.trusted_certifiers.iter().find(|t| t.identity_key == c.certifier))
+        .map(|t| i64::from(t.trust))
+        .sum()
+}
+
+/// First non-empty decrypted field of `cert` among `names`
+fn first_field(cert: &IdentityCertificate, names: &[&str]) -> Option<String> {
+    let field = |name: &str| cert.decrypted_fields.get(name).filter(|v| !v.is_empty()).cloned();
+    let full_name = || match (field("firstName"), field("lastName")) {
+        (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+        (first, last) => first.or(last),
+    };
+    names.iter().find_map(|name| field(name)).or_else(|| if names == NAME_FIELDS { full_name() } else { None })
+}
+
+/// The lookup `query` of the user, from the cache unless expired or `force_refresh`
+///
+/// A fresh lookup is cached with the trust score of the best-trusted
+/// subject and, for identity key lookups, the subject's name and avatar.
+async fn cached_lookup(
+    storage: &mut dyn WalletStorageProvider,
+    user_id: i64,
+    trust_settings: &TrustSettings,
+    query: String,
+    subject: Option<&str>,
+    force_refresh: bool,
+    resolve: impl std::future::Future<Output = wallet_storage::StorageResult<Vec<IdentityCertificate>>>,
+) -> WalletResult<(TableIdentityCache, Vec<IdentityCertificate>)> {
+    let now = chrono::Utc::now();
+    if !force_refresh {
+        if let Some(entry) = storage.find_identity_cache(user_id, &query).await? {
+            if !entry.is_expired(now) {
+                let certificates = serde_json::from_str(&entry.certificates)
+                    .map_err(|e| WalletError::internal(format!("Cached certificates of {}: {}", query, e)))?;
+                return Ok((entry, certificates));
+            }
+        }
+    }
+
+    let certificates = resolve.await?;
+    let json = serde_json::to_string(&certificates)
+        .map_err(|e| WalletError::internal(format!("Certificates of {}: {}", query, e)))?;
+    let trust_score = certificates
+        .iter()
+        .map(|c| subject_trust(trust_settings, &certificates, &c.subject))
+        .max()
+        .unwrap_or(0);
+    let mut entry = TableIdentityCache::new(0, user_id, query, json, trust_score, (now + IDENTITY_CACHE_TTL).to_rfc3339());
+    if let Some(subject) = subject {
+        let trusted = transform_certificates_with_trust(trust_settings, certificates.clone()).certificates;
+        entry.identity_key = Some(subject.to_string());
+        entry.name = trusted.iter().find_map(|c| first_field(c, NAME_FIELDS));
+        entry.avatar_url = trusted.iter().find_map(|c| first_field(c, AVATAR_FIELDS));
+    }
+    entry.identity_cache_id = storage.upsert_identity_cache(&entry).await?;
+    Ok((entry, certificates))
+}
+
+/// Sorted identity keys of the trusted certifiers, as sent to the overlay
+fn certifier_keys(trust_settings: &TrustSettings) -> Vec<String> {
+    let mut keys: Vec<String> = trust_settings.trusted_certifiers.iter().map(|c| c.identity_key.clone()).collect();
+    keys.sort();
+    keys
+}
+
+/// Cache key of an identity key lookup
+fn identity_key_query(identity_key: &str, certifiers: &[String]) -> String {
+    format!("identityKey:{};certifiers:{}", identity_key, certifiers.join(","))
+}
+
+/// The `offset`..`offset + limit` page of a trust-filtered result
+fn page(mut result: DiscoverCertificatesResult, limit: Option<u32>, offset: Option<u32>) -> DiscoverCertificatesResult {
+    let offset = offset.unwrap_or(0) as usize;
+    let limit = limit.map_or(usize::MAX, |l| l as usize);
+    result.certificates = result.certificates.into_iter().skip(offset).take(limit).collect();
+    result
+}
+
+/// Certificates of the identity key in `args` from trusted certifiers
+///
+/// Reference: TS Wallet.discoverByIdentityKey
+pub async fn discover_by_identity_key(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    resolver: &dyn IdentityResolver,
+    trust_settings: &TrustSettings,
+    args: &DiscoverByIdentityKeyArgs,
+) -> WalletResult<DiscoverCertificatesResult> {
+    let identity_key = validate_hex_string(&args.identity_key, "identityKey", Some(66), Some(66))?;
+    let certifiers = certifier_keys(trust_settings);
+    let (_, certificates) = cached_lookup(
+        storage,
+        auth.user_id_required()?,
+        trust_settings,
+        identity_key_query(&identity_key, &certifiers),
+        Some(&identity_key),
+        args.force_refresh.unwrap_or(false),
+        resolver.resolve_by_identity_key(&identity_key, &certifiers),
+    )
+    .await?;
+    Ok(page(transform_certificates_with_trust(trust_settings, certificates), args.limit, args.offset))
+}
+
+/// Certificates from trusted certifiers revealing the field values in `args`
+///
+/// Reference: TS Wallet.discoverByAttributes
+pub async fn discover_by_attributes(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    resolver: &dyn IdentityResolver,
+    trust_settings: &TrustSettings,
+    args: &DiscoverByAttributesArgs,
+) -> WalletResult<DiscoverCertificatesResult> {
+    if args.attributes.is_empty() {
+        return Err(WalletError::invalid_parameter("attributes", "at least one field value"));
+    }
+    let certifiers = certifier_keys(trust_settings);
+    // Sorted so the same attributes always hit the same entry
+    let attributes: BTreeMap<&String, &String> = args.attributes.iter().collect();
+    let query = format!(
+        "attributes:{};certifiers:{}",
+        serde_json::to_string(&attributes).map_err(|e| WalletError::internal(e.to_string()))?,
+        certifiers.join(",")
+    );
+    let (_, certificates) = cached_lookup(
+        storage,
+        auth.user_id_required()?,
+        trust_settings,
+        query,
+        None,
+        args.force_refresh.unwrap_or(false),
+        resolver.resolve_by_attributes(&args.attributes, &certifiers),
+    )
+    .await?;
+    Ok(page(transform_certificates_with_trust(trust_settings, certificates), args.limit, args.offset))
+}
+
+/// Displayable identity of `identity_key`, from its most trusted certificate
+///
+/// Shares the cache entry of `discover_by_identity_key`. Identities without
+/// a trusted certificate naming them are "Unknown Identity".
+///
+/// Reference: TS IdentityClient.resolveByIdentityKey / parseIdentity
+pub async fn resolve_identity(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    resolver: &dyn IdentityResolver,
+    trust_settings: &TrustSettings,
+    identity_key: &str,
+    force_refresh: bool,
+) -> WalletResult<DisplayableIdentity> {
+    let identity_key = validate_hex_string(identity_key, "identityKey", Some(66), Some(66))?;
+    let certifiers = certifier_keys(trust_settings);
+    let (entry, certificates) = cached_lookup(
+        storage,
+        auth.user_id_required()?,
+        trust_settings,
+        identity_key_query(&identity_key, &certifiers),
+        Some(&identity_key),
+        force_refresh,
+        resolver.resolve_by_identity_key(&identity_key, &certifiers),
+    )
+    .await?;
+
+    let trusted = transform_certificates_with_trust(trust_settings, certificates);
+    let badge = trusted.certificates.first().map(|c| &c.certifier_info);
+    Ok(DisplayableIdentity {
+        name: entry.name.unwrap_or_else(|| "Unknown Identity".to_string()),
+        avatar_url: entry.avatar_url.unwrap_or_default(),
+        abbreviated_key: format!("{}...", &identity_key[..10]),
+        badge_icon_url: badge.map(|b| b.icon_url.clone()).unwrap_or_default(),
+        badge_label: badge.map(|b| format!("Certified by {}", b.name)).unwrap_or_default(),
+        trust_score: entry.trust_score,
+        identity_key,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::wallet_settings_manager::Certifier;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wallet_storage::StorageResult;
+    use wallet_test_utils::MockStorage;
+
+    const SUBJECT: &str = "02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const STRONG: &str = "03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const WEAK: &str = "03cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+    fn trust_settings(trust_level: u32) -> TrustSettings {
+        let certifier = |name: &str, identity_key: &str, trust| Certifier {
+            name: name.to_string(),
+            description: String::new(),
+            identity_key: identity_key.to_string(),
+            trust,
+            icon_url: None,
+            base_url: None,
+        };
+        TrustSettings { trust_level, trusted_certifiers: vec![certifier("Weak", WEAK, 1), certifier("Strong", STRONG, 3)] }
+    }
+
+    fn certificate(certifier: &str, fields: &[(&str, &str)]) -> IdentityCertificate {
+        IdentityCertificate {
+            cert_type: "dHlwZQ==".to_string(),
+            subject: SUBJECT.to_string(),
+            serial_number: certifier[..8].to_string(),
+            certifier: certifier.to_string(),
+            revocation_outpoint: format!("{}.0", "00".repeat(32)),
+            signature: "3044".to_string(),
+            fields: HashMap::new(),
+            certifier_info: IdentityCertifier::default(),
+            publicly_revealed_keyring: HashMap::new(),
+            decrypted_fields: fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    /// Resolver answering with fixed certificates, counting lookups
+    struct FixedResolver {
+        certificates: Vec<IdentityCertificate>,
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl IdentityResolver for FixedResolver {
+        async fn resolve_by_identity_key(&self, _: &str, _: &[String]) -> StorageResult<Vec<IdentityCertificate>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.certificates.clone())
+        }
+
+        async fn resolve_by_attributes(
+            &self,
+            _: &HashMap<String, String>,
+            _: &[String],
+        ) -> StorageResult<Vec<IdentityCertificate>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.certificates.clone())
+        }
+    }
+
+    fn resolver() -> FixedResolver {
+        FixedResolver {
+            certificates: vec![
+                certificate(WEAK, &[("email", "alice@example.com")]),
+                certificate(STRONG, &[("firstName", "Alice"), ("lastName", "Smith"), ("profilePhoto", "uhrp://photo")]),
+                certificate("03dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd", &[("name", "Mallory")]),
+            ],
+            lookups: AtomicUsize::new(0),
+        }
+    }
+
+    #[test]
+    fn test_transform_certificates_with_trust() {
+        let certificates = resolver().certificates;
+        let result = transform_certificates_with_trust(&trust_settings(4), certificates.clone());
+        assert_eq!(result.total_certificates, 2);
+        assert_eq!(result.certificates[0].certifier_info.name, "Strong");
+        assert_eq!(result.certificates[1].certifier_info.trust, 1);
+
+        // Trust 1 + 3 falls short of level 5
+        assert_eq!(transform_certificates_with_trust(&trust_settings(5), certificates).total_certificates, 0);
+    }
+
+    #[tokio::test]
+    async fn test_discover_by_identity_key_is_cached() {
+        let mut storage = MockStorage::with_fixtures();
+        let auth = AuthId::new("alice").with_user_id(1);
+        let resolver = resolver();
+        let args = DiscoverByIdentityKeyArgs {
+            identity_key: SUBJECT.to_string(),
+            limit: Some(1),
+            offset: None,
+            seek_permission: None,
+            force_refresh: None,
+        };
+
+        let result = discover_by_identity_key(&mut storage, &auth, &resolver, &trust_settings(2), &args).await.unwrap();
+        assert_eq!((result.total_certificates, result.certificates.len()), (2, 1));
+        let entry = &storage.identity_caches[0];
+        assert_eq!((entry.name.as_deref(), entry.trust_score), (Some("Alice Smith"), 4));
+
+        // Served from the cache, with the current trust level applied
+        let strict = discover_by_identity_key(&mut storage, &auth, &resolver, &trust_settings(5), &args).await.unwrap();
+        assert_eq!(strict.total_certificates, 0);
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+
+        let refresh = DiscoverByIdentityKeyArgs { force_refresh: Some(true), ..args.clone() };
+        discover_by_identity_key(&mut storage, &auth, &resolver, &trust_settings(2), &refresh).await.unwrap();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 2);
+        assert_eq!(storage.identity_caches.len(), 1);
+
+        // Expired entries are looked up again
+        storage.identity_caches[0].expires_at = chrono::Utc::now().to_rfc3339();
+        discover_by_identity_key(&mut storage, &auth, &resolver, &trust_settings(2), &args).await.unwrap();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_discover_by_attributes_and_resolve_identity() {
+        let mut storage = MockStorage::with_fixtures();
+        let auth = AuthId::new("alice").with_user_id(1);
+        let resolver = resolver();
+        let args = DiscoverByAttributesArgs {
+            attributes: HashMap::from([("email".to_string(), "alice@example.com".to_string())]),
+            limit: None,
+            offset: None,
+            seek_permission: None,
+            force_refresh: None,
+        };
+        let result = discover_by_attributes(&mut storage, &auth, &resolver, &trust_settings(2), &args).await.unwrap();
+        assert_eq!(result.total_certificates, 2);
+        discover_by_attributes(&mut storage, &auth, &resolver, &trust_settings(2), &args).await.unwrap();
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+
+        let identity = resolve_identity(&mut storage, &auth, &resolver, &trust_settings(2), SUBJECT, false).await.unwrap();
+        assert_eq!(identity.name, "Alice Smith");
+        assert_eq!(identity.avatar_url, "uhrp://photo");
+        assert_eq!(identity.badge_label, "Certified by Strong");
+        assert_eq!(identity.abbreviated_key, "02aaaaaaaa...");
+        assert_eq!(identity.trust_score, 4);
+
+        let empty = DiscoverByAttributesArgs { attributes: HashMap::new(), ..args };
+        assert!(discover_by_attributes(&mut storage, &auth, &resolver, &trust_settings(2), &empty).await.is_err());
+    }
+}
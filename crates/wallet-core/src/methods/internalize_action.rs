@@ -26,31 +26,111 @@
 //!       - If "basket insertion": setup basket
 //!       - If "wallet payment": validate BRC-29 payment
 //!
-//! 4. **Call Storage** (TS line 59)
-//!     - Storage layer handles merge logic
-//!     - Returns internalize result
+//! 4. **Store** (TS storage/methods/internalizeAction.ts)
+//!     - A transaction new to the user is inserted with its outputs and a
+//!       ProvenTxReq for the monitor to send and prove
+//!     - A transaction the user already has (e.g. its own noSend
+//!       transaction returned by a counterparty) is merged
 //!
 //! ## Merge Rules
 //!
+//! The existing transaction must be 'completed', 'unproven' or 'nosend'.
+//! Outputs it has no row for yet are stored as for a new transaction.
+//!
 //! **Basket Insertion Rules**:
 //! 1. Cannot use "default" basket
 //! 2. Cannot convert change outputs to custom
-//! 3. Custom outputs don't affect balance
+//! 3. Custom outputs don't affect balance; an existing custom output moves
+//!    to the new basket with the new instructions and tags
 //!
 //! **Wallet Payment Rules**:
 //! 1. Existing change outputs = no-op
 //! 2. Converting custom to change = alters balance
 //!
+//! Only balance the merge adds is counted in the result's `satoshis`, so
+//! internalizing the same payment twice never counts it twice.
+//!
 //! **Returns**: `StorageInternalizeActionResult` with txid and merge status
 
+use super::random_vals::RandomVals;
 use crate::sdk::action_process::{
-    ValidInternalizeActionArgs, ValidInternalizeOutput,
-    StorageInternalizeActionResult,
+    InternalizeProtocol, ValidBasketInsertion, ValidInternalizeActionArgs, ValidInternalizeOutput,
+    ValidWalletPayment, StorageInternalizeActionResult,
 };
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::validation_args;
+use crate::sdk::{validate_label, validate_string_length, InternalizeActionArgs};
+use crate::beef::Beef;
+use crate::services::{verify_output_unspent, UtxoStatusProvider};
+use crate::transaction::Transaction;
 use wallet_storage::{
-    StorageError, WalletStorageProvider, AuthId,
+    StorageError, WalletStorageProvider, AuthId, FindOutputBasketsArgs, FindProvenTxReqsArgs,
+    OutputUpdates, ProvenTxReqStatus, StorageProvidedBy, TableOutput, TableOutputBasket,
+    TableProvenTxReq, TableTransaction, TransactionStatus, DEFAULT_CHANGE_BASKET,
 };
 
+/// Statuses of an existing transaction internalizeAction may merge into
+///
+/// Reference: TypeScript storage/methods/internalizeAction.ts (asyncSetup)
+const MERGEABLE_STATUSES: &[TransactionStatus] = &[
+    TransactionStatus::Completed,
+    TransactionStatus::Unproven,
+    TransactionStatus::Nosend,
+];
+
+/// Validate BRC-100 internalize action arguments
+///
+/// Reference: TS validateInternalizeActionArgs
+pub fn validate_internalize_action_args(args: &InternalizeActionArgs) -> WalletResult<ValidInternalizeActionArgs> {
+    let outputs = args.outputs.iter()
+        .map(|o| {
+            let payment_remittance = o.payment_remittance.as_ref()
+                .map(|p| validation_args::validate_wallet_payment(&p.derivation_prefix, &p.derivation_suffix, &p.sender_identity_key))
+                .transpose()?;
+            let insertion_remittance = o.insertion_remittance.as_ref()
+                .map(|b| validation_args::validate_basket_insertion(&b.basket, b.custom_instructions.as_deref(), b.tags.as_deref().unwrap_or_default()))
+                .transpose()?;
+            let valid = validation_args::validate_internalize_output(o.output_index, &o.protocol, payment_remittance, insertion_remittance)?;
+            let protocol = if valid.protocol == "wallet payment" {
+                InternalizeProtocol::WalletPayment
+            } else {
+                InternalizeProtocol::BasketInsertion
+            };
+            Ok(ValidInternalizeOutput {
+                output_index: valid.output_index,
+                protocol,
+                payment_remittance: valid.payment_remittance.map(|p| ValidWalletPayment {
+                    derivation_prefix: p.derivation_prefix,
+                    derivation_suffix: p.derivation_suffix,
+                    sender_identity_key: p.sender_identity_key,
+                }),
+                insertion_remittance: valid.insertion_remittance.map(|b| ValidBasketInsertion {
+                    basket: b.basket,
+                    custom_instructions: b.custom_instructions,
+                    tags: Some(b.tags),
+                }),
+            })
+        })
+        .collect::<WalletResult<Vec<_>>>()?;
+    if args.tx.is_empty() {
+        return Err(WalletError::invalid_parameter("tx", "an AtomicBEEF"));
+    }
+    if outputs.is_empty() {
+        return Err(WalletError::invalid_parameter("outputs", "at least one output to internalize"));
+    }
+    let labels = args.labels.iter().flatten()
+        .map(|l| validate_label(l))
+        .collect::<WalletResult<Vec<_>>>()?;
+
+    Ok(ValidInternalizeActionArgs {
+        tx: args.tx.clone(),
+        outputs,
+        description: validate_string_length(&args.description, "description", Some(5), Some(2000))?,
+        labels,
+        seek_permission: args.seek_permission.unwrap_or(true),
+    })
+}
+
 /// Main internalizeAction implementation
 ///
 /// Reference: TypeScript src/signer/methods/internalizeAction.ts
@@ -58,23 +138,35 @@ use wallet_storage::{
 /// Takes ownership of outputs in existing transaction:
 /// 1. Validates BEEF transaction
 /// 2. Processes outputs by protocol type
-/// 3. Calls storage layer for merge logic
+/// 3. Stores the transaction, or merges into the user's existing one
+///
+/// When `services` is provided and the transaction is already mined (the
+/// BEEF carries its merkle proof), each internalized output is checked to
+/// still be unspent on-chain. Unmined transactions cannot be checked yet.
 pub async fn internalize_action(
-    _storage: &mut dyn WalletStorageProvider,
+    storage: &mut dyn WalletStorageProvider,
+    services: Option<&dyn UtxoStatusProvider>,
     auth: &AuthId,
     vargs: ValidInternalizeActionArgs,
 ) -> Result<StorageInternalizeActionResult, StorageError> {
-    let _user_id = auth.user_id.ok_or_else(|| {
+    let user_id = auth.user_id.ok_or_else(|| {
         StorageError::Unauthorized("user_id required".to_string())
     })?;
     
     // STEP 1: Validate AtomicBEEF
     // TS lines 82-98: Parse and verify BEEF
-    let (txid, _tx) = validate_atomic_beef(&vargs.tx)?;
+    let (txid, tx, is_mined) = validate_atomic_beef(&vargs.tx)?;
     
     // STEP 2: Process outputs
     // TS lines 44-57: Validate each output by protocol type
     for output in &vargs.outputs {
+        let tx_output = tx.outputs.get(output.output_index as usize).ok_or_else(|| {
+            StorageError::InvalidArg(format!("outputIndex {} is not a valid output of {}", output.output_index, txid))
+        })?;
+        if let (Some(services), true) = (services, is_mined) {
+            verify_output_unspent(services, &txid, output.output_index, &tx_output.script_pubkey).await?;
+        }
+        
         match output.protocol {
             crate::sdk::action_process::InternalizeProtocol::BasketInsertion => {
                 // TS lines 76-80: Basket insertion validation
@@ -87,39 +179,265 @@ pub async fn internalize_action(
         }
     }
     
-    // STEP 3: Call storage layer
-    // TS line 59: Storage handles the actual merge logic
-    // For now, return a placeholder result
-    // In full implementation, storage.internalize_action() would be called
+    // STEP 3: Find the change basket and any existing transaction
+    // TS storage asyncSetup
+    let change_basket = find_change_basket(storage, auth, user_id).await?;
+    let existing = storage.find_transaction_by_txid(user_id, &txid).await?;
+    if let Some(etx) = &existing {
+        if !MERGEABLE_STATUSES.contains(&etx.status) {
+            return Err(StorageError::InvalidArg(format!(
+                "target transaction of internalizeAction has invalid status {}", etx.status
+            )));
+        }
+    }
+    let existing_outputs = match &existing {
+        Some(etx) => storage.find_outputs_by_transaction(user_id, etx.transaction_id, false).await?,
+        None => Vec::new(),
+    };
+    
+    // STEP 4: Pair outputs with their existing rows and total the balance added
+    let mut outputs = Vec::with_capacity(vargs.outputs.len());
+    let mut satoshis = 0;
+    for spec in &vargs.outputs {
+        let tx_output = &tx.outputs[spec.output_index as usize];
+        let output = InternalizeOutput {
+            spec,
+            satoshis: tx_output.value,
+            locking_script: tx_output.script_pubkey.clone(),
+            existing: existing_outputs.iter().find(|o| o.vout == spec.output_index).cloned(),
+        };
+        satoshis += output.balance_change(&change_basket)?;
+        outputs.push(output);
+    }
+    
+    // STEP 5: Store
+    let transaction_id = match &existing {
+        Some(etx) => {
+            if satoshis != 0 {
+                storage.update_transaction(etx.transaction_id, etx.satoshis + satoshis).await?;
+            }
+            etx.transaction_id
+        }
+        None => insert_new_transaction(storage, user_id, &vargs, &txid, &tx, satoshis).await?,
+    };
+    for label in &vargs.labels {
+        let tx_label = storage.find_or_insert_tx_label(user_id, label).await?;
+        storage.find_or_insert_tx_label_map(transaction_id, tx_label.tx_label_id).await?;
+    }
+    for output in &outputs {
+        store_output(storage, user_id, transaction_id, &txid, &change_basket, output).await?;
+    }
     
     Ok(StorageInternalizeActionResult {
         txid,
-        is_merge: false, // Would be determined by storage
-        satoshis: 0, // Would be calculated by storage
+        is_merge: existing.is_some(),
+        satoshis,
         send_with_results: None,
         not_delayed_results: None,
     })
 }
 
-/// STEP 1: Validate AtomicBEEF transaction
-/// Reference: TypeScript internalizeAction.ts lines 82-98
-fn validate_atomic_beef(beef_binary: &[u8]) -> Result<(String, Vec<u8>), StorageError> {
-    // Parse BEEF
-    // TS line 83: const ab = Beef.fromBinary(vargs.tx)
+/// An output being internalized, with the row it merges into, if any
+struct InternalizeOutput<'a> {
+    spec: &'a ValidInternalizeOutput,
+    satoshis: i64,
+    locking_script: Vec<u8>,
+    existing: Option<TableOutput>,
+}
+
+impl InternalizeOutput<'_> {
+    /// Whether the output already is change in `change_basket`
+    fn is_existing_change(&self, change_basket: &TableOutputBasket) -> bool {
+        self.existing.as_ref().is_some_and(|o| o.basket_id == Some(change_basket.basket_id))
+    }
     
-    // For now, return placeholder
-    // In full implementation, would:
-    // 1. Parse BEEF binary format
-    // 2. Verify transaction with chain tracker
-    // 3. Extract atomic txid
-    // 4. Return txid and transaction
+    /// Satoshis internalizing the output adds to the wallet balance
+    ///
+    /// A payment adds its value unless the output already is change; a
+    /// basket insertion adds nothing and may not take an output out of change.
+    fn balance_change(&self, change_basket: &TableOutputBasket) -> Result<i64, StorageError> {
+        match self.spec.protocol {
+            InternalizeProtocol::WalletPayment if self.is_existing_change(change_basket) => Ok(0),
+            InternalizeProtocol::WalletPayment => Ok(self.satoshis),
+            InternalizeProtocol::BasketInsertion if self.is_existing_change(change_basket) => {
+                Err(StorageError::InvalidArg(format!(
+                    "outputIndex {} is a change output and cannot be inserted into a basket",
+                    self.spec.output_index
+                )))
+            }
+            InternalizeProtocol::BasketInsertion => Ok(0),
+        }
+    }
+}
+
+/// The user's change basket, which must exist
+async fn find_change_basket(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    user_id: i64,
+) -> Result<TableOutputBasket, StorageError> {
+    let args = FindOutputBasketsArgs {
+        user_id,
+        name: Some(DEFAULT_CHANGE_BASKET.to_string()),
+        since: None,
+        paged: None,
+    };
+    storage.find_output_baskets_auth(auth, &args).await?.into_iter().next().ok_or_else(|| {
+        StorageError::NotFound(format!("Output basket '{}' not found", DEFAULT_CHANGE_BASKET))
+    })
+}
+
+/// Insert a transaction new to the user as 'unproven', queueing a
+/// ProvenTxReq for the monitor to send and prove unless one exists
+///
+/// Reference: TypeScript storage/methods/internalizeAction.ts (newInternalize)
+async fn insert_new_transaction(
+    storage: &mut dyn WalletStorageProvider,
+    user_id: i64,
+    vargs: &ValidInternalizeActionArgs,
+    txid: &str,
+    tx: &Transaction,
+    satoshis: i64,
+) -> Result<i64, StorageError> {
+    let raw_tx = tx.serialize().map_err(|e| StorageError::InvalidArg(format!("tx: {}", e)))?;
+    let new_tx = TableTransaction::new(
+        0,
+        user_id,
+        TransactionStatus::Unproven,
+        RandomVals::new(None).bytes_base64(7),
+        false,
+        satoshis,
+        vargs.description.clone(),
+    )
+    .with_version(tx.version)
+    .with_lock_time(tx.lock_time)
+    .with_txid(txid)
+    .with_raw_tx(raw_tx.clone())
+    .with_input_beef(vargs.tx.clone());
+    let transaction_id = storage.insert_transaction(&new_tx).await?;
     
+    let known = FindProvenTxReqsArgs {
+        status: None,
+        since: None,
+        paged: None,
+        txids: Some(vec![txid.to_string()]),
+    };
+    if storage.find_proven_tx_reqs(&known).await?.is_empty() {
+        let notify = serde_json::json!({ "transactionIds": [transaction_id] }).to_string();
+        let req = TableProvenTxReq::new(0, ProvenTxReqStatus::Unsent, txid, "{}", notify, raw_tx)
+            .with_input_beef(vargs.tx.clone());
+        storage.insert_proven_tx_req(&req).await?;
+    }
+    Ok(transaction_id)
+}
+
+/// Store an internalized output: a new row, or updates to its existing row
+///
+/// Reference: TypeScript storage/methods/internalizeAction.ts
+/// (storeNew/merge WalletPaymentForOutput and BasketInsertionForOutput)
+async fn store_output(
+    storage: &mut dyn WalletStorageProvider,
+    user_id: i64,
+    transaction_id: i64,
+    txid: &str,
+    change_basket: &TableOutputBasket,
+    output: &InternalizeOutput<'_>,
+) -> Result<(), StorageError> {
+    if let Some(payment) = &output.spec.payment_remittance {
+        if output.is_existing_change(change_basket) {
+            return Ok(());
+        }
+        let updates = OutputUpdates {
+            basket_id: Some(change_basket.basket_id),
+            change: Some(true),
+            provided_by: Some(StorageProvidedBy::Storage),
+            purpose: Some("change".to_string()),
+            output_type: Some("P2PKH".to_string()),
+            sender_identity_key: Some(payment.sender_identity_key.clone()),
+            derivation_prefix: Some(payment.derivation_prefix.clone()),
+            derivation_suffix: Some(payment.derivation_suffix.clone()),
+            ..Default::default()
+        };
+        upsert_output(storage, user_id, transaction_id, txid, output, updates).await?;
+    } else if let Some(insertion) = &output.spec.insertion_remittance {
+        let basket = storage.find_or_insert_output_basket(user_id, &insertion.basket).await?;
+        let updates = OutputUpdates {
+            basket_id: Some(basket.basket_id),
+            change: Some(false),
+            provided_by: Some(StorageProvidedBy::You),
+            purpose: Some(String::new()),
+            output_type: Some("custom".to_string()),
+            custom_instructions: insertion.custom_instructions.clone(),
+            ..Default::default()
+        };
+        let output_id = upsert_output(storage, user_id, transaction_id, txid, output, updates).await?;
+        for tag in insertion.tags.iter().flatten() {
+            let tag = storage.find_or_insert_output_tag(user_id, tag).await?;
+            storage.find_or_insert_output_tag_map(output_id, tag.output_tag_id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply `updates` to the output's existing row, or insert a spendable row
+/// with them; returns the output id
+async fn upsert_output(
+    storage: &mut dyn WalletStorageProvider,
+    user_id: i64,
+    transaction_id: i64,
+    txid: &str,
+    output: &InternalizeOutput<'_>,
+    updates: OutputUpdates,
+) -> Result<i64, StorageError> {
+    if let Some(existing) = &output.existing {
+        storage.update_output(existing.output_id, &updates).await?;
+        return Ok(existing.output_id);
+    }
+    let mut row = TableOutput::new(
+        0,
+        user_id,
+        transaction_id,
+        true,
+        false,
+        "",
+        output.spec.output_index,
+        output.satoshis,
+        StorageProvidedBy::You,
+        "",
+        "custom",
+    )
+    .with_txid(txid)
+    .with_locking_script(output.locking_script.clone());
+    updates.apply_to(&mut row);
+    storage.insert_output(&row).await
+}
+
+/// STEP 1: Validate AtomicBEEF transaction
+/// Reference: TypeScript internalizeAction.ts lines 82-98
+///
+/// Returns the atomic txid, its parsed transaction, and whether the BEEF
+/// includes a merkle proof for it.
+fn validate_atomic_beef(beef_binary: &[u8]) -> Result<(String, Transaction, bool), StorageError> {
     if beef_binary.is_empty() {
         return Err(StorageError::InvalidArg("Empty BEEF binary".to_string()));
     }
     
-    // Placeholder txid
-    Ok(("placeholder_txid".to_string(), beef_binary.to_vec()))
+    // TS line 83: const ab = Beef.fromBinary(vargs.tx)
+    let beef = Beef::from_binary(beef_binary)
+        .map_err(|e| StorageError::InvalidArg(format!("tx is not valid AtomicBEEF: {}", e)))?;
+    let txid = beef.atomic_txid.clone()
+        .ok_or_else(|| StorageError::InvalidArg("tx is not valid AtomicBEEF".to_string()))?;
+    
+    // TODO: verify the BEEF against a chain tracker (TS lines 84-90)
+    
+    let beef_tx = beef.find_txid(&txid)
+        .ok_or_else(|| StorageError::InvalidArg(format!("tx is not valid AtomicBEEF with newest txid of {}", txid)))?;
+    let raw_tx = beef_tx.raw_tx.as_ref()
+        .ok_or_else(|| StorageError::InvalidArg(format!("AtomicBEEF does not contain transaction {}", txid)))?;
+    let tx = Transaction::from_bytes(raw_tx)
+        .map_err(|e| StorageError::InvalidArg(format!("invalid transaction {}: {}", txid, e)))?;
+    
+    Ok((txid, tx, beef_tx.bump_index.is_some()))
 }
 
 /// STEP 2.1: Validate basket insertion output
@@ -174,6 +492,40 @@ mod tests {
         assert!(result.is_err());
     }
     
+    #[test]
+    fn test_validate_atomic_beef() {
+        use crate::beef::{MerklePath, MerklePathNode};
+        use crate::transaction::{OutPoint, TxInput, TxOutput};
+        
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("11".repeat(32), 0)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+        let txid = tx.txid().unwrap();
+        let raw = tx.serialize().unwrap();
+        
+        // Unmined
+        let mut beef = Beef::new_v2();
+        beef.merge_raw_tx(&raw).unwrap();
+        let (parsed_txid, parsed_tx, is_mined) = validate_atomic_beef(&beef.to_binary_atomic(&txid).unwrap()).unwrap();
+        assert_eq!(parsed_txid, txid);
+        assert_eq!(parsed_tx.outputs[0].script_pubkey, vec![0x51]);
+        assert!(!is_mined);
+        
+        // Mined
+        let mut beef = Beef::new_v2();
+        beef.merge_bump(MerklePath {
+            block_height: 7,
+            path: vec![vec![MerklePathNode { hash: txid.clone(), offset: Some(0), duplicate: false, txid: true }]],
+        });
+        beef.merge_raw_tx(&raw).unwrap();
+        beef.find_txid_mut(&txid).unwrap().bump_index = Some(0);
+        let (_, _, is_mined) = validate_atomic_beef(&beef.to_binary_atomic(&txid).unwrap()).unwrap();
+        assert!(is_mined);
+        
+        // Plain BEEF is not AtomicBEEF
+        assert!(validate_atomic_beef(&beef.to_binary().unwrap()).is_err());
+    }
+    
     #[test]
     fn test_validate_basket_insertion_default() {
         let output = ValidInternalizeOutput {
@@ -220,4 +572,158 @@ mod tests {
         let result = validate_wallet_payment(&output);
         assert!(result.is_err());
     }
+    
+    fn payment(output_index: u32) -> ValidInternalizeOutput {
+        ValidInternalizeOutput {
+            output_index,
+            protocol: InternalizeProtocol::WalletPayment,
+            payment_remittance: Some(ValidWalletPayment {
+                derivation_prefix: "cHJlZml4".to_string(),
+                derivation_suffix: "c3VmZml4".to_string(),
+                sender_identity_key: "02".repeat(33),
+            }),
+            insertion_remittance: None,
+        }
+    }
+    
+    fn insertion(output_index: u32, basket: &str) -> ValidInternalizeOutput {
+        ValidInternalizeOutput {
+            output_index,
+            protocol: InternalizeProtocol::BasketInsertion,
+            payment_remittance: None,
+            insertion_remittance: Some(ValidBasketInsertion {
+                basket: basket.to_string(),
+                custom_instructions: Some("{\"unlock\":1}".to_string()),
+                tags: Some(vec!["token".to_string()]),
+            }),
+        }
+    }
+    
+    fn vargs(tx: &[u8], outputs: Vec<ValidInternalizeOutput>) -> ValidInternalizeActionArgs {
+        ValidInternalizeActionArgs {
+            tx: tx.to_vec(),
+            outputs,
+            description: "receive payment".to_string(),
+            labels: vec!["received".to_string()],
+            seek_permission: false,
+        }
+    }
+    
+    fn alice() -> AuthId {
+        AuthId::new(wallet_test_utils::fixtures::alice_identity_key()).with_user_id(1)
+    }
+    
+    #[tokio::test]
+    async fn test_internalize_new_payment() {
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let (txid, beef) = wallet_test_utils::fixtures::payment_beef(10_000, 9_000);
+        
+        let r = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![payment(0)])).await.unwrap();
+        assert!(!r.is_merge);
+        assert_eq!(r.satoshis, 9_000);
+        
+        let tx = storage.find_transaction_by_txid(1, &txid).await.unwrap().unwrap();
+        assert_eq!(tx.status, TransactionStatus::Unproven);
+        assert_eq!(tx.satoshis, 9_000);
+        assert_eq!(storage.find_tx_labels_for_transaction(tx.transaction_id).await.unwrap()[0].label, "received");
+        let outputs = storage.find_outputs_by_transaction(1, tx.transaction_id, false).await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert!(outputs[0].change && outputs[0].spendable);
+        assert_eq!(outputs[0].basket_id, Some(1));
+        assert_eq!(outputs[0].derivation_prefix.as_deref(), Some("cHJlZml4"));
+        assert_eq!(storage.reqs.len(), 1);
+        assert_eq!(storage.reqs[0].status, ProvenTxReqStatus::Unsent);
+        assert_eq!(storage.reqs[0].notify_transaction_ids(), vec![tx.transaction_id]);
+    }
+    
+    #[tokio::test]
+    async fn test_internalize_again_merges_without_double_counting() {
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let (txid, beef) = wallet_test_utils::fixtures::payment_beef(10_000, 9_000);
+        internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![payment(0)])).await.unwrap();
+        let balance = storage.get_balance(1).await.unwrap();
+        let output_count = storage.outputs.len();
+        
+        let r = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![payment(0)])).await.unwrap();
+        assert!(r.is_merge);
+        assert_eq!(r.satoshis, 0);
+        assert_eq!(storage.find_transaction_by_txid(1, &txid).await.unwrap().unwrap().satoshis, 9_000);
+        assert_eq!(storage.get_balance(1).await.unwrap(), balance);
+        assert_eq!(storage.outputs.len(), output_count);
+        assert_eq!(storage.reqs.len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_merge_converts_custom_output_to_change() {
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let (txid, beef) = wallet_test_utils::fixtures::payment_beef(10_000, 9_000);
+        
+        let r = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![insertion(0, "tokens")])).await.unwrap();
+        assert_eq!(r.satoshis, 0);
+        let tx = storage.find_transaction_by_txid(1, &txid).await.unwrap().unwrap();
+        let custom = &storage.find_outputs_by_transaction(1, tx.transaction_id, false).await.unwrap()[0];
+        assert!(!custom.change);
+        assert_eq!(custom.output_type, "custom");
+        assert_eq!(custom.custom_instructions.as_deref(), Some("{\"unlock\":1}"));
+        assert_eq!(storage.tag_maps.len(), 1);
+        
+        let r = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![payment(0)])).await.unwrap();
+        assert!(r.is_merge);
+        assert_eq!(r.satoshis, 9_000);
+        let change = &storage.find_outputs_by_transaction(1, tx.transaction_id, false).await.unwrap()[0];
+        assert_eq!(change.output_id, custom.output_id);
+        assert!(change.change);
+        assert_eq!(change.basket_id, Some(1));
+        assert_eq!(change.output_type, "P2PKH");
+        assert_eq!(change.provided_by, StorageProvidedBy::Storage);
+        assert_eq!(storage.find_transaction_by_txid(1, &txid).await.unwrap().unwrap().satoshis, 9_000);
+        
+        // Change may not be taken back into a custom basket
+        let err = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![insertion(0, "tokens")])).await;
+        assert!(matches!(err, Err(StorageError::InvalidArg(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_merge_into_own_nosend_transaction() {
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let (txid, beef) = wallet_test_utils::fixtures::payment_beef(10_000, 9_000);
+        let own = TableTransaction::new(0, 1, TransactionStatus::Nosend, "own", true, -1_000, "noSend payment")
+            .with_txid(&txid);
+        let transaction_id = storage.insert_transaction(&own).await.unwrap();
+        
+        let r = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![insertion(0, "tokens")])).await.unwrap();
+        assert!(r.is_merge);
+        assert_eq!(r.satoshis, 0);
+        let outputs = storage.find_outputs_by_transaction(1, transaction_id, false).await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(storage.transaction(transaction_id).status, TransactionStatus::Nosend);
+        assert!(storage.reqs.is_empty());
+        
+        storage.update_transaction_status(transaction_id, TransactionStatus::Failed).await.unwrap();
+        let err = internalize_action(&mut storage, None, &alice(), vargs(&beef, vec![payment(0)])).await;
+        assert!(matches!(err, Err(StorageError::InvalidArg(_))));
+    }
+    
+    #[test]
+    fn test_validate_internalize_action_args() {
+        let args: InternalizeActionArgs = serde_json::from_value(serde_json::json!({
+            "tx": [1, 2, 3],
+            "outputs": [{
+                "outputIndex": 0,
+                "protocol": "basket insertion",
+                "insertionRemittance": {"basket": "tokens", "tags": ["a"]},
+            }],
+            "description": "receive tokens",
+        })).unwrap();
+        let vargs = validate_internalize_action_args(&args).unwrap();
+        
+        assert_eq!(vargs.outputs[0].protocol, InternalizeProtocol::BasketInsertion);
+        assert!(vargs.seek_permission);
+        
+        let mut args = args;
+        args.outputs[0].protocol = "unknown".to_string();
+        assert!(validate_internalize_action_args(&args).is_err());
+        args.outputs.clear();
+        assert!(validate_internalize_action_args(&args).is_err());
+    }
 }
@@ -1,19 +1,68 @@
-//! Key Linkage Operations (BRC-42)
+//! Public Key and Key Linkage Operations (BRC-42/43, BRC-69)
 //!
-//! Reveal cryptographic linkages between keys for verification.
-//! Reference: wallet-toolbox SDK revealCounterpartyKeyLinkage/revealSpecificKeyLinkage methods
-//! Spec: BRC-42 (Key Linkage Revelation)
+//! Derive public keys and reveal cryptographic linkages between keys for verification.
+//! Reference: wallet-toolbox SDK getPublicKey/revealCounterpartyKeyLinkage/revealSpecificKeyLinkage methods
+//! Spec: BRC-69 (Revealing Key Linkages)
 
-use crate::keys::key_deriver::KeyDeriver;
+use secp256k1::{PublicKey, SecretKey};
+
+use crate::crypto::schnorr::generate_proof;
+use crate::crypto::{decrypt_with_aes_gcm, encrypt_with_aes_gcm};
+use crate::keys::key_deriver::RootKeyDeriver;
 use crate::sdk::{
-    KeyLinkageResult, RevealCounterpartyKeyLinkageArgs, RevealCounterpartyKeyLinkageResult,
-    RevealSpecificKeyLinkageArgs, RevealSpecificKeyLinkageResult, WalletError, WalletResult,
+    GetPublicKeyArgs, GetPublicKeyResult, RevealCounterpartyKeyLinkageArgs,
+    RevealCounterpartyKeyLinkageResult, RevealSpecificKeyLinkageArgs,
+    RevealSpecificKeyLinkageResult, WalletError, WalletResult,
 };
 
+/// Protocol under which counterparty linkages are encrypted for the verifier
+pub const COUNTERPARTY_LINKAGE_PROTOCOL: &str = "counterparty linkage revelation";
+
+/// Protocol under which a specific linkage is encrypted for the verifier
+///
+/// Reference: TS ProtoWallet.revealSpecificKeyLinkage
+pub fn specific_linkage_protocol(protocol_id: &(u8, String)) -> (u8, String) {
+    (2, format!("specific linkage revelation {} {}", protocol_id.0, protocol_id.1))
+}
+
+/// Get the identity key or a derived public key
+///
+/// With `identity_key` set the root public key is returned. Otherwise
+/// `protocol_id` and `key_id` are required and the key is derived for
+/// `counterparty` (default "self"), as ours when `for_self` is set.
+///
+/// Reference: TypeScript `getPublicKey()` in ProtoWallet
+/// Spec: BRC-42, BRC-43
+pub async fn get_public_key(
+    args: &GetPublicKeyArgs,
+    key_deriver: &RootKeyDeriver,
+) -> WalletResult<GetPublicKeyResult> {
+    if args.identity_key.unwrap_or(false) {
+        return Ok(GetPublicKeyResult { public_key: key_deriver.identity_key_hex() });
+    }
+
+    let protocol_id = args.protocol_id.as_ref()
+        .ok_or_else(|| WalletError::missing_parameter("protocolID"))?;
+    let key_id = args.key_id.as_deref()
+        .ok_or_else(|| WalletError::missing_parameter("keyID"))?;
+    let counterparty = args.counterparty.as_deref().unwrap_or("self");
+
+    let public_key = key_deriver.derive_public_key(
+        protocol_id,
+        key_id,
+        counterparty,
+        args.for_self.unwrap_or(false),
+    )?;
+    Ok(GetPublicKeyResult { public_key: hex::encode(public_key) })
+}
+
 /// Reveal linkage between counterparty and identity keys
 ///
-/// Creates encrypted linkage information that proves the relationship
-/// between the user's identity key and their key for a specific counterparty.
+/// The linkage is the ECDH shared secret with the counterparty, from which
+/// every key derived between the two parties can be computed. It is sent
+/// with a Schnorr proof that it was computed from our identity key, both
+/// encrypted for the verifier under the counterparty linkage protocol with
+/// the revelation time as key ID.
 ///
 /// # Arguments
 /// * `args` - Linkage revelation arguments (counterparty, verifier, etc.)
@@ -22,30 +71,40 @@ use crate::sdk::{
 /// # Returns
 /// Encrypted linkage data, proof, and metadata
 ///
-/// Reference: TypeScript `revealCounterpartyKeyLinkage()` in SDK
-/// Spec: BRC-42
+/// Reference: TypeScript `revealCounterpartyKeyLinkage()` in ProtoWallet
+/// Spec: BRC-69
 pub async fn reveal_counterparty_key_linkage(
     args: &RevealCounterpartyKeyLinkageArgs,
-    key_deriver: &dyn KeyDeriver,
+    key_deriver: &RootKeyDeriver,
 ) -> WalletResult<RevealCounterpartyKeyLinkageResult> {
-    // TODO: Implement BRC-42 key linkage revelation
-    // This requires:
-    // 1. Derive user's identity key
-    // 2. Derive user's key for the counterparty
-    // 3. Create linkage proof
-    // 4. Encrypt linkage for the verifier
-    // 5. Create encrypted proof
-    
-    let _ = (args, key_deriver);
-    
-    Err(WalletError::not_implemented(
-        "Key linkage revelation (BRC-42) not yet implemented",
-    ))
+    let linkage = key_deriver.reveal_counterparty_secret(&args.counterparty)?;
+
+    let root_key = SecretKey::from_slice(&key_deriver.root_key())
+        .map_err(|e| WalletError::internal(e.to_string()))?;
+    let counterparty_key = PublicKey::from_slice(&key_deriver.normalize_counterparty(&args.counterparty)?)
+        .map_err(|e| WalletError::internal(e.to_string()))?;
+    let shared_secret = PublicKey::from_slice(&linkage)
+        .map_err(|e| WalletError::internal(e.to_string()))?;
+    let proof = generate_proof(&root_key, &counterparty_key, &shared_secret)?;
+
+    let revelation_time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let protocol_id = (2, COUNTERPARTY_LINKAGE_PROTOCOL.to_string());
+
+    Ok(RevealCounterpartyKeyLinkageResult {
+        encrypted_linkage: encrypt_for_counterparty(key_deriver, &linkage, &protocol_id, &revelation_time, &args.verifier)?,
+        encrypted_linkage_proof: encrypt_for_counterparty(key_deriver, &proof.to_bytes(), &protocol_id, &revelation_time, &args.verifier)?,
+        prover: key_deriver.identity_key_hex(),
+        verifier: args.verifier.clone(),
+        counterparty: args.counterparty.clone(),
+        revelation_time,
+    })
 }
 
 /// Reveal linkage for a specific protocol/key ID
 ///
-/// Creates encrypted linkage information for a specific derived key.
+/// The linkage is the HMAC offset between the counterparty's identity key
+/// and the key derived for them under this protocol and key ID. No proof is
+/// attached (proof type 0).
 ///
 /// # Arguments
 /// * `args` - Specific key linkage arguments (protocol ID, key ID, etc.)
@@ -54,53 +113,59 @@ pub async fn reveal_counterparty_key_linkage(
 /// # Returns
 /// Encrypted linkage data, proof, and metadata
 ///
-/// Reference: TypeScript `revealSpecificKeyLinkage()` in SDK
-/// Spec: BRC-42
+/// Reference: TypeScript `revealSpecificKeyLinkage()` in ProtoWallet
+/// Spec: BRC-69
 pub async fn reveal_specific_key_linkage(
     args: &RevealSpecificKeyLinkageArgs,
-    key_deriver: &dyn KeyDeriver,
+    key_deriver: &RootKeyDeriver,
 ) -> WalletResult<RevealSpecificKeyLinkageResult> {
-    // TODO: Implement BRC-42 specific key linkage revelation
-    // This requires:
-    // 1. Derive user's identity key
-    // 2. Derive the specific key (protocol + key ID + counterparty)
-    // 3. Create linkage proof
-    // 4. Encrypt linkage for the verifier
-    // 5. Create encrypted proof
-    // 6. Include protocol/key ID metadata
-    
-    let _ = (args, key_deriver);
-    
-    Err(WalletError::not_implemented(
-        "Specific key linkage revelation (BRC-42) not yet implemented",
-    ))
+    let linkage = key_deriver.reveal_specific_secret(&args.counterparty, &args.protocol_id, &args.key_id)?;
+
+    let protocol_id = specific_linkage_protocol(&args.protocol_id);
+    let proof_type = 0u8;
+
+    Ok(RevealSpecificKeyLinkageResult {
+        encrypted_linkage: encrypt_for_counterparty(key_deriver, &linkage, &protocol_id, &args.key_id, &args.verifier)?,
+        encrypted_linkage_proof: encrypt_for_counterparty(key_deriver, &[proof_type], &protocol_id, &args.key_id, &args.verifier)?,
+        prover: key_deriver.identity_key_hex(),
+        verifier: args.verifier.clone(),
+        counterparty: args.counterparty.clone(),
+        protocol_id: args.protocol_id.clone(),
+        key_id: args.key_id.clone(),
+        proof_type,
+    })
 }
 
 // ============================================================================
-// HELPER FUNCTIONS (for future BRC-42 implementation)
+// HELPER FUNCTIONS
 // ============================================================================
 
-/// Encrypt linkage data for verifier (placeholder)
-#[allow(dead_code)]
-fn encrypt_linkage_for_verifier(
-    _linkage_data: &[u8],
-    _verifier_pubkey: &[u8],
+/// Encrypt with the BRC-2 symmetric key shared with `counterparty`
+fn encrypt_for_counterparty(
+    key_deriver: &RootKeyDeriver,
+    plaintext: &[u8],
+    protocol_id: &(u8, String),
+    key_id: &str,
+    counterparty: &str,
 ) -> WalletResult<Vec<u8>> {
-    // TODO: Implement encryption using verifier's public key
-    // Likely uses ECDH + AES-256-GCM
-    Err(WalletError::not_implemented("Linkage encryption"))
+    let key = key_deriver.derive_symmetric_key(protocol_id, key_id, counterparty)?;
+    encrypt_with_aes_gcm(plaintext, &key)
 }
 
-/// Create linkage proof (placeholder)
-#[allow(dead_code)]
-fn create_linkage_proof(
-    _identity_key: &[u8],
-    _derived_key: &[u8],
-    _counterparty: &str,
+/// Decrypt a revealed linkage or proof on the verifier's side
+///
+/// `protocol_id` and `key_id` are those the prover encrypted under: the
+/// counterparty linkage protocol and revelation time, or
+/// `specific_linkage_protocol` and the key ID.
+pub fn decrypt_linkage(
+    verifier: &RootKeyDeriver,
+    ciphertext: &[u8],
+    protocol_id: &(u8, String),
+    key_id: &str,
+    prover: &str,
 ) -> WalletResult<Vec<u8>> {
-    // TODO: Implement BRC-42 proof generation
-    // This proves the mathematical relationship between keys
-    Err(WalletError::not_implemented("Linkage proof creation"))
+    let key = verifier.derive_symmetric_key(protocol_id, key_id, prover)?;
+    decrypt_with_aes_gcm(ciphertext, &key)
 }
 
 // ============================================================================
@@ -110,64 +175,100 @@ fn create_linkage_proof(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use async_trait::async_trait;
-    
-    // Mock key deriver
-    struct MockKeyDeriver;
-    
-    #[async_trait]
-    impl KeyDeriver for MockKeyDeriver {
-        async fn derive_key(
-            &self,
-            _protocol_id: &(u8, String),
-            _key_id: &str,
-            _counterparty: &str,
-        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-            Ok(vec![0x42; 32])
-        }
-        
-        async fn derive_public_key(
-            &self,
-            _protocol_id: &(u8, String),
-            _key_id: &str,
-            _counterparty: &str,
-            _for_self: bool,
-        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-            Ok(vec![0x03; 33])
-        }
+    use crate::crypto::schnorr::{verify_proof, SchnorrProof};
+
+    fn deriver(byte: u8) -> RootKeyDeriver {
+        RootKeyDeriver::new(&[byte; 32]).unwrap()
     }
-    
+
     #[tokio::test]
-    async fn test_reveal_counterparty_linkage_not_implemented() {
+    async fn test_get_public_key() {
+        let prover = deriver(1);
+        let counterparty = deriver(2);
+
+        let mut args = GetPublicKeyArgs {
+            identity_key: Some(true),
+            for_self: None,
+            protocol_id: None,
+            key_id: None,
+            counterparty: None,
+            privileged: None,
+            privileged_reason: None,
+        };
+        let result = get_public_key(&args, &prover).await.unwrap();
+        assert_eq!(result.public_key, prover.identity_key_hex());
+
+        args.identity_key = None;
+        assert!(get_public_key(&args, &prover).await.is_err());
+
+        args.protocol_id = Some((1, "tests protocol suite".to_string()));
+        args.key_id = Some("1".to_string());
+        args.counterparty = Some(counterparty.identity_key_hex());
+        let theirs = get_public_key(&args, &prover).await.unwrap();
+
+        args.for_self = Some(true);
+        args.counterparty = Some(prover.identity_key_hex());
+        let own = get_public_key(&args, &counterparty).await.unwrap();
+        assert_eq!(theirs.public_key, own.public_key);
+    }
+
+    #[tokio::test]
+    async fn test_reveal_counterparty_linkage() {
+        let prover = deriver(1);
+        let counterparty = deriver(2);
+        let verifier = deriver(3);
+
         let args = RevealCounterpartyKeyLinkageArgs {
-            counterparty: "0300000000000000000000000000000000000000000000000000000000000000".to_string(),
-            verifier: "0300000000000000000000000000000000000000000000000000000000000001".to_string(),
+            counterparty: counterparty.identity_key_hex(),
+            verifier: verifier.identity_key_hex(),
             privileged: None,
             privileged_reason: None,
         };
-        
-        let deriver = MockKeyDeriver;
-        let result = reveal_counterparty_key_linkage(&args, &deriver).await;
-        
-        // Currently returns not implemented error
-        assert!(result.is_err());
+        let result = reveal_counterparty_key_linkage(&args, &prover).await.unwrap();
+        assert_eq!(result.prover, prover.identity_key_hex());
+        assert!(result.revelation_time.ends_with('Z'));
+
+        let protocol_id = (2, COUNTERPARTY_LINKAGE_PROTOCOL.to_string());
+        let linkage = decrypt_linkage(&verifier, &result.encrypted_linkage, &protocol_id, &result.revelation_time, &result.prover).unwrap();
+        assert_eq!(linkage, counterparty.reveal_counterparty_secret(&prover.identity_key_hex()).unwrap());
+
+        let proof = decrypt_linkage(&verifier, &result.encrypted_linkage_proof, &protocol_id, &result.revelation_time, &result.prover).unwrap();
+        let proof = SchnorrProof::from_bytes(&proof).unwrap();
+        assert!(verify_proof(
+            &PublicKey::from_slice(prover.identity_key()).unwrap(),
+            &PublicKey::from_slice(counterparty.identity_key()).unwrap(),
+            &PublicKey::from_slice(&linkage).unwrap(),
+            &proof,
+        ));
+
+        // Only the verifier can decrypt
+        assert!(decrypt_linkage(&counterparty, &result.encrypted_linkage, &protocol_id, &result.revelation_time, &result.prover).is_err());
+
+        let args = RevealCounterpartyKeyLinkageArgs { counterparty: "self".to_string(), ..args };
+        assert!(reveal_counterparty_key_linkage(&args, &prover).await.is_err());
     }
-    
+
     #[tokio::test]
-    async fn test_reveal_specific_linkage_not_implemented() {
+    async fn test_reveal_specific_linkage() {
+        let prover = deriver(1);
+        let counterparty = deriver(2);
+        let verifier = deriver(3);
+
         let args = RevealSpecificKeyLinkageArgs {
-            counterparty: "self".to_string(),
-            verifier: "0300000000000000000000000000000000000000000000000000000000000001".to_string(),
-            protocol_id: (0, "test".to_string()),
+            counterparty: counterparty.identity_key_hex(),
+            verifier: verifier.identity_key_hex(),
+            protocol_id: (2, "tests protocol suite".to_string()),
             key_id: "key1".to_string(),
             privileged: None,
             privileged_reason: None,
         };
-        
-        let deriver = MockKeyDeriver;
-        let result = reveal_specific_key_linkage(&args, &deriver).await;
-        
-        // Currently returns not implemented error
-        assert!(result.is_err());
+        let result = reveal_specific_key_linkage(&args, &prover).await.unwrap();
+        assert_eq!(result.proof_type, 0);
+
+        let protocol_id = specific_linkage_protocol(&args.protocol_id);
+        let linkage = decrypt_linkage(&verifier, &result.encrypted_linkage, &protocol_id, &args.key_id, &result.prover).unwrap();
+        assert_eq!(linkage, counterparty.reveal_specific_secret(&prover.identity_key_hex(), &args.protocol_id, &args.key_id).unwrap());
+        let proof = decrypt_linkage(&verifier, &result.encrypted_linkage_proof, &protocol_id, &args.key_id, &result.prover).unwrap();
+        assert_eq!(proof, vec![0]);
     }
 }
@@ -21,7 +21,10 @@
 //!
 //! **Returns**: `ListActionsResult` with actions array and total count
 
-use crate::sdk::action_list::{ValidListActionsArgs, WalletAction};
+use crate::sdk::action_list::{LabelQueryMode, ValidListActionsArgs, WalletAction};
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{validate_integer, validate_label, ListActionsArgs};
+use serde::{Deserialize, Serialize};
 use wallet_storage::{
     StorageError, WalletStorageProvider, AuthId,
     TableTransaction, TransactionStatus,
@@ -29,7 +32,8 @@ use wallet_storage::{
 
 /// List actions result
 /// Matches TypeScript `ListActionsResult`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListActionsResult {
     /// Total number of actions matching query (before pagination)
     pub total_actions: i64,
@@ -38,9 +42,35 @@ pub struct ListActionsResult {
     pub actions: Vec<WalletAction>,
     
     /// Optional BEEF (if includeTransactions was requested)
+    #[serde(rename = "BEEF", skip_serializing_if = "Option::is_none")]
     pub beef: Option<Vec<u8>>,
 }
 
+/// Validate BRC-100 list actions arguments
+///
+/// Reference: TS validateListActionsArgs
+pub fn validate_list_actions_args(args: &ListActionsArgs) -> WalletResult<ValidListActionsArgs> {
+    let labels = args.labels.iter()
+        .map(|l| validate_label(l))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let limit = validate_integer(args.limit.map(i64::from), "limit", Some(10), Some(1), Some(10000))?;
+    let offset = validate_integer(args.offset.map(i64::from), "offset", Some(0), Some(0), None)?;
+
+    Ok(ValidListActionsArgs {
+        labels,
+        label_query_mode: args.label_query_mode.unwrap_or(LabelQueryMode::Any),
+        include_labels: args.include_labels.unwrap_or(false),
+        include_inputs: args.include_inputs.unwrap_or(false),
+        include_input_source_locking_scripts: args.include_input_source_locking_scripts.unwrap_or(false),
+        include_input_unlocking_scripts: args.include_input_unlocking_scripts.unwrap_or(false),
+        include_outputs: args.include_outputs.unwrap_or(false),
+        include_output_locking_scripts: args.include_output_locking_scripts.unwrap_or(false),
+        limit: limit as u32,
+        offset: offset as u32,
+        seek_permission: args.seek_permission.unwrap_or(true),
+    })
+}
+
 /// Main listActions implementation
 ///
 /// Reference: TypeScript src/storage/methods/listActionsKnex.ts
@@ -65,7 +95,10 @@ pub async fn list_actions(
     
     // STEP 2: Resolve labels if specified
     let label_ids = if !vargs.labels.is_empty() {
-        resolve_labels(storage, user_id, &vargs.labels).await?
+        match resolve_labels(storage, user_id, &vargs.labels, vargs.label_query_mode).await? {
+            Some(label_ids) => label_ids,
+            None => return Ok(ListActionsResult { total_actions: 0, actions: Vec::new(), beef: None }),
+        }
     } else {
         Vec::new()
     };
@@ -98,19 +131,28 @@ pub async fn list_actions(
 }
 
 /// STEP 2: Resolve label names to label IDs
+///
+/// Unknown and deleted labels match nothing, so None when no action can
+/// match: any of them is missing in 'all' mode, or all of them in 'any' mode.
 async fn resolve_labels(
-    storage: &mut dyn WalletStorageProvider,
+    storage: &dyn WalletStorageProvider,
     user_id: i64,
     label_names: &[String],
-) -> Result<Vec<i64>, StorageError> {
-    let mut label_ids = Vec::new();
-    
-    for label_name in label_names {
-        let label = storage.find_or_insert_tx_label(user_id, label_name).await?;
-        label_ids.push(label.tx_label_id);
-    }
-    
-    Ok(label_ids)
+    mode: LabelQueryMode,
+) -> Result<Option<Vec<i64>>, StorageError> {
+    let label_ids: Vec<i64> = storage
+        .find_tx_labels(user_id)
+        .await?
+        .into_iter()
+        .filter(|l| label_names.contains(&l.label))
+        .map(|l| l.tx_label_id)
+        .collect();
+
+    let unmatched = match mode {
+        LabelQueryMode::All => label_ids.len() < label_names.len(),
+        LabelQueryMode::Any => label_ids.is_empty(),
+    };
+    Ok((!unmatched).then_some(label_ids))
 }
 
 /// STEP 3: Query transactions with all filters
@@ -0,0 +1,201 @@
+//! List Certificates Implementation
+//!
+//! **Reference**: TypeScript `src/storage/methods/listCertificates.ts`
+//!
+//! Lists the wallet's identity certificates with filtering and pagination.
+//!
+//! ## Process Flow (TypeScript Reference)
+//!
+//! 1. **Query Certificates** - by certifiers, types and partial match,
+//!    one page at a time
+//! 2. **Attach Fields** - encrypted field values and the master keyring
+//! 3. **Count** - total matches when the page is full
+//!
+//! **Returns**: `ListCertificatesResult` with certificates and total count
+
+use std::collections::HashMap;
+
+use crate::sdk::action_list::{PartialCertificateFilter, ValidListCertificatesArgs};
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{
+    validate_base64_string, validate_hex_string, validate_integer, CertificateResult,
+    ListCertificatesArgs, ListCertificatesResult,
+};
+use wallet_storage::{
+    AuthId, FindCertificatesArgs, Paged, PartialCertificate, StorageError, TableCertificate,
+    WalletStorageProvider,
+};
+
+/// Validate BRC-100 list certificates arguments
+///
+/// Results always carry their fields, as BRC-100 callers expect.
+///
+/// Reference: TS validateListCertificatesArgs
+pub fn validate_list_certificates_args(args: &ListCertificatesArgs) -> WalletResult<ValidListCertificatesArgs> {
+    let certifiers = args.certifiers.iter()
+        .map(|c| validate_hex_string(c, "certifiers", Some(66), Some(66)))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let types = args.types.iter()
+        .map(|t| validate_base64_string(t, "types", None, None))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let limit = validate_integer(args.limit.map(i64::from), "limit", Some(10), Some(1), Some(10000))?;
+    let offset = validate_integer(args.offset.map(i64::from), "offset", Some(0), Some(0), None)?;
+
+    Ok(ValidListCertificatesArgs {
+        certifiers,
+        types,
+        limit: limit as u32,
+        offset: offset as u32,
+        partial: args.partial.as_ref().map(|p| PartialCertificateFilter {
+            certificate_type: p.cert_type.clone(),
+            serial_number: p.serial_number.clone(),
+            certifier: p.certifier.clone(),
+            subject: p.subject.clone(),
+            revocation_outpoint: p.revocation_outpoint.clone(),
+            signature: p.signature.clone(),
+        }),
+        include_fields: true,
+        seek_permission: true,
+        privileged_reason: args.privileged_reason.clone(),
+    })
+}
+
+/// Main listCertificates implementation
+///
+/// Reference: TypeScript src/storage/methods/listCertificates.ts
+pub async fn list_certificates(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    vargs: &ValidListCertificatesArgs,
+) -> Result<ListCertificatesResult, StorageError> {
+    let user_id = auth.user_id.ok_or_else(|| {
+        StorageError::Unauthorized("user_id required".to_string())
+    })?;
+
+    // STEP 1: Query one page of certificates
+    let non_empty = |v: &Vec<String>| if v.is_empty() { None } else { Some(v.clone()) };
+    let mut find_args = FindCertificatesArgs {
+        user_id,
+        since: None,
+        paged: Some(Paged::with_offset(vargs.limit, vargs.offset)),
+        order_descending: None,
+        partial: vargs.partial.as_ref().map(|p| PartialCertificate {
+            certificate_type: p.certificate_type.clone(),
+            serial_number: p.serial_number.clone(),
+            certifier: p.certifier.clone(),
+            subject: p.subject.clone(),
+            revocation_outpoint: p.revocation_outpoint.clone(),
+            signature: p.signature.clone(),
+        }),
+        certifiers: non_empty(&vargs.certifiers),
+        types: non_empty(&vargs.types),
+        include_fields: Some(vargs.include_fields),
+    };
+    let certificates = storage.find_certificates_auth(auth, &find_args).await?;
+
+    // STEP 2: Attach fields and keyring
+    let mut results = Vec::with_capacity(certificates.len());
+    for certificate in certificates {
+        let (fields, keyring) = if vargs.include_fields {
+            certificate_fields(storage, auth, &certificate).await?
+        } else {
+            (HashMap::new(), HashMap::new())
+        };
+        results.push(CertificateResult {
+            cert_type: certificate.certificate_type,
+            subject: certificate.subject,
+            serial_number: certificate.serial_number,
+            certifier: certificate.certifier,
+            revocation_outpoint: certificate.revocation_outpoint,
+            signature: certificate.signature,
+            fields,
+            keyring,
+            verifier: certificate.verifier,
+        });
+    }
+
+    // STEP 3: Count all matches unless this page shows them all
+    let total = if vargs.offset == 0 && results.len() < vargs.limit as usize {
+        results.len() as i64
+    } else {
+        find_args.paged = None;
+        storage.count_certificates_auth(auth, &find_args).await?
+    };
+
+    Ok(ListCertificatesResult {
+        total_certificates: total,
+        certificates: results,
+    })
+}
+
+/// Field values and master keyring of a stored certificate
+async fn certificate_fields(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    certificate: &TableCertificate,
+) -> Result<(HashMap<String, String>, HashMap<String, String>), StorageError> {
+    let mut fields = HashMap::new();
+    let mut keyring = HashMap::new();
+    for field in storage.find_certificate_fields_auth(auth, certificate.certificate_id).await? {
+        keyring.insert(field.field_name.clone(), field.master_key);
+        fields.insert(field.field_name, field.field_value);
+    }
+    Ok((fields, keyring))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::PartialWalletCertificate;
+
+    fn args() -> ListCertificatesArgs {
+        ListCertificatesArgs {
+            certifiers: vec!["02".repeat(33)],
+            types: vec!["aWRlbnRpdHk=".to_string()],
+            limit: None,
+            offset: None,
+            partial: None,
+            privileged: None,
+            privileged_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_list_certificates_args_defaults() {
+        let vargs = validate_list_certificates_args(&args()).unwrap();
+        assert_eq!(vargs.limit, 10);
+        assert_eq!(vargs.offset, 0);
+        assert!(vargs.include_fields);
+        assert!(vargs.partial.is_none());
+    }
+
+    #[test]
+    fn test_validate_list_certificates_args_rejects_bad_values() {
+        let mut bad = args();
+        bad.certifiers = vec!["not hex".to_string()];
+        assert!(validate_list_certificates_args(&bad).is_err());
+
+        let mut bad = args();
+        bad.types = vec!["%%".to_string()];
+        assert!(validate_list_certificates_args(&bad).is_err());
+
+        let mut bad = args();
+        bad.limit = Some(10001);
+        assert!(validate_list_certificates_args(&bad).is_err());
+    }
+
+    #[test]
+    fn test_validate_list_certificates_args_partial() {
+        let mut with_partial = args();
+        with_partial.partial = Some(PartialWalletCertificate {
+            serial_number: Some("c2VyaWFs".to_string()),
+            ..Default::default()
+        });
+        let vargs = validate_list_certificates_args(&with_partial).unwrap();
+        assert_eq!(vargs.partial.unwrap().serial_number.as_deref(), Some("c2VyaWFs"));
+    }
+}
@@ -44,7 +44,12 @@
 //!
 //! **Returns**: `ListOutputsResult` with outputs array and total count
 
-use crate::sdk::action_list::{ValidListOutputsArgs, WalletOutput};
+use crate::beef::Beef;
+use crate::methods::get_beef_for_transaction::{get_valid_beef_for_txid, StorageGetBeefOptions};
+use crate::sdk::action_list::{TagQueryMode, ValidListOutputsArgs, WalletOutput};
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::{validate_integer, validate_string_length, validate_tag, ListOutputsArgs};
+use serde::{Deserialize, Serialize};
 use wallet_storage::{
     StorageError, WalletStorageProvider, AuthId,
     TableOutput, TableOutputBasket,
@@ -52,7 +57,8 @@ use wallet_storage::{
 
 /// List outputs result
 /// Matches TypeScript `ListOutputsResult`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListOutputsResult {
     /// Total number of outputs matching query (before pagination)
     pub total_outputs: i64,
@@ -61,9 +67,39 @@ pub struct ListOutputsResult {
     pub outputs: Vec<WalletOutput>,
     
     /// Optional BEEF (if includeTransactions was requested)
+    #[serde(rename = "BEEF", skip_serializing_if = "Option::is_none")]
     pub beef: Option<Vec<u8>>,
 }
 
+/// Validate BRC-100 list outputs arguments
+///
+/// Reference: TS validateListOutputsArgs
+pub fn validate_list_outputs_args(args: &ListOutputsArgs) -> WalletResult<ValidListOutputsArgs> {
+    let tags = args.tags.iter().flatten()
+        .map(|t| validate_tag(t))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let include = args.include.as_deref();
+    if !matches!(include, None | Some("locking scripts") | Some("entire transactions")) {
+        return Err(WalletError::invalid_parameter("include", "'locking scripts' or 'entire transactions'"));
+    }
+    let limit = validate_integer(args.limit.map(i64::from), "limit", Some(10), Some(1), Some(10000))?;
+    let offset = validate_integer(args.offset.map(i64::from), "offset", Some(0), Some(0), None)?;
+
+    Ok(ValidListOutputsArgs {
+        basket: validate_string_length(&args.basket, "basket", Some(1), Some(300))?,
+        tags,
+        tag_query_mode: args.tag_query_mode.unwrap_or(TagQueryMode::Any),
+        include_entire_transactions: include == Some("entire transactions"),
+        include_custom_instructions: args.include_custom_instructions.unwrap_or(false),
+        include_tags: args.include_tags.unwrap_or(false),
+        include_labels: args.include_labels.unwrap_or(false),
+        include_locking_scripts: include == Some("locking scripts"),
+        limit: limit as u32,
+        offset: offset as u32,
+        seek_permission: args.seek_permission.unwrap_or(true),
+    })
+}
+
 /// Main listOutputs implementation
 ///
 /// Reference: TypeScript src/storage/methods/listOutputsKnex.ts
@@ -99,7 +135,10 @@ pub async fn list_outputs(
     // STEP 3: Resolve tags if specified  
     // TS lines 96-116: Find tag IDs
     let tag_ids = if !vargs.tags.is_empty() {
-        resolve_tags(storage, user_id, &vargs.tags).await?
+        match resolve_tags(storage, user_id, &vargs.tags, vargs.tag_query_mode).await? {
+            Some(tag_ids) => tag_ids,
+            None => return Ok(ListOutputsResult { total_outputs: 0, outputs: Vec::new(), beef: None }),
+        }
     } else {
         Vec::new()
     };
@@ -128,13 +167,35 @@ pub async fn list_outputs(
         count_outputs(storage, user_id, basket_id, &tag_ids, &vargs).await?
     };
     
+    // STEP 6: BEEF of the outputs' transactions
+    // TS lines 264-270: includeTransactions
+    let beef = if vargs.include_entire_transactions {
+        Some(outputs_beef(storage, &outputs).await?)
+    } else {
+        None
+    };
+    
     Ok(ListOutputsResult {
         total_outputs: total,
         outputs: wallet_outputs,
-        beef: None, // TODO: Add BEEF support when needed
+        beef,
     })
 }
 
+/// STEP 6: BEEF containing the transactions of `outputs`
+/// Reference: TypeScript listOutputsKnex.ts (getValidBeefForKnownTxid)
+async fn outputs_beef(
+    storage: &dyn WalletStorageProvider,
+    outputs: &[TableOutput],
+) -> Result<Vec<u8>, StorageError> {
+    let mut beef = Beef::new_v2();
+    let options = StorageGetBeefOptions::default();
+    for txid in outputs.iter().filter_map(|o| o.txid.as_deref()) {
+        get_valid_beef_for_txid(storage, txid, &mut beef, &options).await?;
+    }
+    beef.to_binary().map_err(|e| StorageError::InvalidArg(format!("BEEF: {}", e)))
+}
+
 /// STEP 2: Resolve basket name to basket ID
 /// Reference: TypeScript listOutputsKnex.ts lines 48-67
 async fn resolve_basket(
@@ -148,20 +209,29 @@ async fn resolve_basket(
 }
 
 /// STEP 3: Resolve tag names to tag IDs
-/// Reference: TypeScript listOutputsKnex.ts lines 96-107
+/// Reference: TypeScript listOutputsKnex.ts lines 96-116
+///
+/// Unknown and deleted tags match nothing, so None when no output can match:
+/// any of them is missing in 'all' mode, or all of them in 'any' mode.
 async fn resolve_tags(
-    storage: &mut dyn WalletStorageProvider,
+    storage: &dyn WalletStorageProvider,
     user_id: i64,
     tag_names: &[String],
-) -> Result<Vec<i64>, StorageError> {
-    let mut tag_ids = Vec::new();
-    
-    for tag_name in tag_names {
-        let tag = storage.find_or_insert_output_tag(user_id, tag_name).await?;
-        tag_ids.push(tag.output_tag_id);
-    }
-    
-    Ok(tag_ids)
+    mode: TagQueryMode,
+) -> Result<Option<Vec<i64>>, StorageError> {
+    let tag_ids: Vec<i64> = storage
+        .find_output_tags(user_id)
+        .await?
+        .into_iter()
+        .filter(|t| tag_names.contains(&t.tag))
+        .map(|t| t.output_tag_id)
+        .collect();
+
+    let unmatched = match mode {
+        TagQueryMode::All => tag_ids.len() < tag_names.len(),
+        TagQueryMode::Any => tag_ids.is_empty(),
+    };
+    Ok((!unmatched).then_some(tag_ids))
 }
 
 /// STEP 4: Query outputs with all filters
@@ -274,4 +344,17 @@ mod tests {
         assert_eq!(wo.satoshis, 1000);
         assert!(wo.spendable);
     }
+    
+    #[test]
+    fn test_validate_list_outputs_args_include() {
+        let mut args: ListOutputsArgs = serde_json::from_value(serde_json::json!({
+            "basket": "default",
+            "include": "locking scripts",
+        })).unwrap();
+        let vargs = validate_list_outputs_args(&args).unwrap();
+        assert_eq!(vargs.limit, 10);
+        
+        args.include = Some("everything".to_string());
+        assert!(validate_list_outputs_args(&args).is_err());
+    }
 }
@@ -5,29 +5,47 @@
 
 pub mod blockchain_queries;
 pub mod create_action;
+pub mod double_spend;
 pub mod encrypt_decrypt;
+pub mod export_history;
+pub mod fee_model;
+pub mod generate_change;
+pub mod get_beef_for_transaction;
 pub mod hmac_operations;
+pub mod identity_discovery;
 pub mod internalize_action;
 pub mod key_linkage;
 pub mod list_actions;
+pub mod list_certificates;
 pub mod list_outputs;
 pub mod output_management;
 pub mod process_action;
+pub mod random_vals;
 pub mod sign_action;
 pub mod signature_operations;
+pub mod tag_label_management;
 
 pub use blockchain_queries::*;
 pub use create_action::*;
+pub use double_spend::*;
 pub use encrypt_decrypt::*;
+pub use export_history::*;
+pub use fee_model::*;
+pub use generate_change::*;
+pub use get_beef_for_transaction::*;
 pub use hmac_operations::*;
+pub use identity_discovery::*;
 pub use internalize_action::*;
 pub use key_linkage::*;
 pub use list_actions::*;
+pub use list_certificates::*;
 pub use list_outputs::*;
 pub use output_management::*;
 pub use process_action::*;
+pub use random_vals::*;
 pub use sign_action::*;
 pub use signature_operations::*;
+pub use tag_label_management::*;
 
 // Re-export main functions
 pub use create_action::create_action;
@@ -1,9 +1,17 @@
 //! Output Management Operations
 //!
-//! Manage UTXOs (relinquish, etc.).
+//! Manage UTXOs (relinquish, basket change policy, etc.).
 //! Reference: wallet-toolbox SDK output management methods
 
-use crate::sdk::{RelinquishOutputArgs, RelinquishOutputResult, WalletError, WalletResult};
+use serde::{Deserialize, Serialize};
+use wallet_storage::{
+    AuthId, OutputBasketUpdates, StorageError, StorageResult, TableOutputBasket, WalletStorageProvider,
+};
+
+use crate::sdk::{
+    validate_basket, validate_optional_integer, RelinquishOutputArgs, RelinquishOutputResult, WalletError,
+    WalletResult,
+};
 
 /// Relinquish an output (mark as no longer owned)
 ///
@@ -20,6 +28,77 @@ pub async fn relinquish_output(args: &RelinquishOutputArgs) -> WalletResult<Reli
     Err(WalletError::not_implemented("relinquishOutput"))
 }
 
+/// Change UTXO policy of an output basket
+///
+/// Omitted fields keep their current value. Change generation tops the basket
+/// up towards `numberOfDesiredUTXOs` outputs of at least
+/// `minimumDesiredUTXOValue` satoshis each.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetBasketUtxoPolicyArgs {
+    pub basket: String,
+    #[serde(rename = "numberOfDesiredUTXOs", skip_serializing_if = "Option::is_none")]
+    pub number_of_desired_utxos: Option<i32>,
+    #[serde(rename = "minimumDesiredUTXOValue", skip_serializing_if = "Option::is_none")]
+    pub minimum_desired_utxo_value: Option<i64>,
+}
+
+/// Validated basket policy update
+#[derive(Debug, Clone)]
+pub struct ValidSetBasketUtxoPolicyArgs {
+    pub basket: String,
+    pub updates: OutputBasketUpdates,
+}
+
+/// Validate basket policy args
+///
+/// The minimum value must be at least one satoshi, as change generation
+/// cannot create smaller change outputs.
+pub fn validate_set_basket_utxo_policy_args(
+    args: &SetBasketUtxoPolicyArgs,
+) -> WalletResult<ValidSetBasketUtxoPolicyArgs> {
+    let number_of_desired_utxos = validate_optional_integer(
+        args.number_of_desired_utxos.map(i64::from),
+        "numberOfDesiredUTXOs",
+        Some(0),
+        Some(i64::from(i32::MAX)),
+    )?;
+    Ok(ValidSetBasketUtxoPolicyArgs {
+        basket: validate_basket(&args.basket)?,
+        updates: OutputBasketUpdates {
+            number_of_desired_utxos: number_of_desired_utxos.map(|n| n as i32),
+            minimum_desired_utxo_value: validate_optional_integer(
+                args.minimum_desired_utxo_value,
+                "minimumDesiredUTXOValue",
+                Some(1),
+                None,
+            )?,
+        },
+    })
+}
+
+/// Set the change UTXO policy of a user's basket, creating the basket if needed
+///
+/// Returns the basket as updated.
+pub async fn set_basket_utxo_policy(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    vargs: ValidSetBasketUtxoPolicyArgs,
+) -> StorageResult<TableOutputBasket> {
+    let user_id = auth.user_id.ok_or_else(|| {
+        StorageError::Unauthorized("user_id required".to_string())
+    })?;
+    let mut basket = storage.find_or_insert_output_basket(user_id, &vargs.basket).await?;
+    storage.update_output_basket(basket.basket_id, &vargs.updates).await?;
+
+    if let Some(n) = vargs.updates.number_of_desired_utxos {
+        basket.number_of_desired_utxos = n;
+    }
+    if let Some(v) = vargs.updates.minimum_desired_utxo_value {
+        basket.minimum_desired_utxo_value = v;
+    }
+    Ok(basket)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,4 +114,22 @@ mod tests {
         let result = relinquish_output(&args).await;
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_set_basket_utxo_policy_args() {
+        let args = SetBasketUtxoPolicyArgs {
+            basket: " Default ".to_string(),
+            number_of_desired_utxos: Some(32),
+            minimum_desired_utxo_value: None,
+        };
+        let vargs = validate_set_basket_utxo_policy_args(&args).unwrap();
+        assert_eq!(vargs.basket, "default");
+        assert_eq!(vargs.updates.number_of_desired_utxos, Some(32));
+        assert_eq!(vargs.updates.minimum_desired_utxo_value, None);
+
+        let zero_value = SetBasketUtxoPolicyArgs { minimum_desired_utxo_value: Some(0), ..args.clone() };
+        assert!(validate_set_basket_utxo_policy_args(&zero_value).is_err());
+        let negative_count = SetBasketUtxoPolicyArgs { number_of_desired_utxos: Some(-1), ..args };
+        assert!(validate_set_basket_utxo_policy_args(&negative_count).is_err());
+    }
 }
@@ -29,52 +29,499 @@
 //!
 //! **Returns**: `StorageProcessActionResults` with txid and status
 
+use crate::beef::Beef;
+use std::collections::HashMap;
 use crate::sdk::action_process::{
-    ValidProcessActionArgs, StorageProcessActionResults,
+    ReviewActionResult, ReviewActionResultStatus, SendWithResult,
+    StorageProcessActionArgs, StorageProcessActionResults,
 };
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{validate_base64_string, AbortActionArgs, ValidAbortActionArgs};
+use crate::services::{Broadcaster, UtxoStatusProvider};
+use super::double_spend::reconcile_double_spend;
+use tracing::{debug, instrument, warn};
 use wallet_storage::{
-    StorageError, WalletStorageProvider, AuthId,
+    metrics, StorageError, WalletStorageProvider, AuthId, EntityProvenTxReq, FindProvenTxReqsArgs,
+    MonitorEvent, ProvenTxReqStatus, ProvenTxReqUpdates, ReqHistoryNote, TableProvenTxReq,
+    TableTransaction, TransactionStatus, MAX_CONFLICT_RETRIES,
 };
 
 /// Main processAction implementation
 ///
-/// Reference: TypeScript src/signer/methods/processAction.ts
+/// Reference: TypeScript src/storage/methods/processAction.ts
 ///
-/// Complete action lifecycle:
-/// 1. Validates arguments
-/// 2. Creates unsigned transaction (createAction)
-/// 3. Signs transaction (signAction)
-/// 4. Optionally broadcasts
-/// 5. Returns processing results
+/// Commits a newly signed transaction and shares it, along with any `sendWith`
+/// transactions, with the network:
+/// 0. For a new transaction, stores its txid and rawTx and queues a ProvenTxReq
+/// 1. Collects the txids to share (sendWith batch plus the new transaction
+///    unless it is noSend)
+/// 2. Validates each txid's ProvenTxReq status and aggregates their BEEFs
+/// 3. Queues the batch for the monitor (delayed) or posts it via `broadcaster`,
+///    recording a `MonitorEvent::BroadcastAttempt` for each transaction
+/// 4. Returns per-txid sendWith and broadcast results; double spends are
+///    reconciled (see `reconcile_double_spend`), checking inputs with
+///    `utxo_status` when available
+#[instrument(
+    skip_all,
+    fields(user_id = auth.user_id, reference = args.reference.as_deref(), txid = args.txid.as_deref())
+)]
 pub async fn process_action(
-    _storage: &mut dyn WalletStorageProvider,
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
     auth: &AuthId,
-    _vargs: ValidProcessActionArgs,
+    args: StorageProcessActionArgs,
 ) -> Result<StorageProcessActionResults, StorageError> {
-    let _user_id = auth.user_id.ok_or_else(|| {
+    let user_id = auth.user_id.ok_or_else(|| {
         StorageError::Unauthorized("user_id required".to_string())
     })?;
     
-    // STEP 1: Create unsigned transaction
-    // This would call create_action::create_action()
-    // let create_result = create_action(storage, auth, create_args).await?;
+    if args.is_new_tx {
+        commit_new_tx_to_storage(storage, user_id, &args).await?;
+    }
     
-    // STEP 2: Sign transaction
-    // This would call sign_action::sign_action()
-    // let sign_result = sign_action(storage, auth, sign_args).await?;
+    let txids = txids_to_share(&args)?;
     
-    // STEP 3: Broadcast if needed
-    // Unless noSend option is set
-    // if !vargs.options.no_send {
-    //     broadcast_transaction(storage, &sign_result.txid).await?;
-    // }
+    let (send_with_results, not_delayed_results) =
+        share_reqs_with_world(storage, broadcaster, utxo_status, &txids, args.is_delayed).await?;
     
-    // STEP 4: Return results
-    // For now, return placeholder
     Ok(StorageProcessActionResults {
-        send_with_results: None,
-        not_delayed_results: None,
-        log: Some("processAction not fully implemented yet".to_string()),
+        send_with_results: if send_with_results.is_empty() {
+            None
+        } else {
+            Some(send_with_results)
+        },
+        not_delayed_results,
+        log: args.log,
+    })
+}
+
+/// Initial ProvenTxReq status for a new transaction
+///
+/// Delayed broadcasts are queued as 'unsent' for the monitor to send.
+fn new_tx_req_status(args: &StorageProcessActionArgs) -> ProvenTxReqStatus {
+    if args.is_no_send {
+        ProvenTxReqStatus::Nosend
+    } else if args.is_delayed {
+        ProvenTxReqStatus::Unsent
+    } else {
+        ProvenTxReqStatus::Unprocessed
+    }
+}
+
+/// Build the ProvenTxReq tracking a new transaction until it is mined
+fn make_new_tx_req(
+    transaction: &TableTransaction,
+    args: &StorageProcessActionArgs,
+    txid: &str,
+    raw_tx: &[u8],
+) -> TableProvenTxReq {
+    let req = TableProvenTxReq::new(0, new_tx_req_status(args), txid, "{}", "{}", raw_tx.to_vec());
+    let mut req = EntityProvenTxReq::new(Some(req));
+    req.set_input_beef(transaction.input_beef.clone());
+    req.add_notify_transaction_id(transaction.transaction_id);
+    req.add_history_note(ReqHistoryNote {
+        when: None,
+        what: "processAction".to_string(),
+        extra: HashMap::from([("userId".to_string(), serde_json::json!(transaction.user_id))]),
+    }, false);
+    req.into_api()
+}
+
+/// Commit a newly signed transaction to storage
+///
+/// Reference: TypeScript processAction.ts (`commitNewTxToStorage`)
+///
+/// Stores the txid and rawTx on the transaction, marks it 'nosend' or
+/// 'sending', and inserts a ProvenTxReq so the transaction is tracked until
+/// it is proven.
+async fn commit_new_tx_to_storage(
+    storage: &mut dyn WalletStorageProvider,
+    user_id: i64,
+    args: &StorageProcessActionArgs,
+) -> Result<TableProvenTxReq, StorageError> {
+    let reference = args.reference.as_deref().ok_or_else(|| {
+        StorageError::InvalidArg("reference is required for a new transaction".to_string())
+    })?;
+    let txid = args.txid.as_deref().ok_or_else(|| {
+        StorageError::InvalidArg("txid is required for a new transaction".to_string())
+    })?;
+    let raw_tx = args.raw_tx.as_deref().ok_or_else(|| {
+        StorageError::InvalidArg("rawTx is required for a new transaction".to_string())
+    })?;
+    
+    let mut transactions = storage.find_transactions(user_id, Some(reference), None).await?;
+    if transactions.len() != 1 {
+        return Err(StorageError::NotFound(
+            format!("Transaction not found with reference: {}", reference)
+        ));
+    }
+    let mut transaction = transactions.remove(0);
+    
+    let tx_status = if args.is_no_send {
+        TransactionStatus::Nosend
+    } else {
+        TransactionStatus::Sending
+    };
+    // The monitor may fail the transaction as abandoned while it is being
+    // signed, so its status only changes if the transaction is unchanged
+    // since it was read. Changes that leave the status as it was are retried.
+    let mut attempt = 0;
+    loop {
+        let read_status = transaction.status;
+        match storage
+            .update_transaction_status_if_unchanged(transaction.transaction_id, tx_status, &transaction.updated_at)
+            .await
+        {
+            Err(StorageError::Conflict(e)) if attempt < MAX_CONFLICT_RETRIES => {
+                attempt += 1;
+                transaction = storage
+                    .find_transaction_by_id(transaction.transaction_id)
+                    .await?
+                    .ok_or_else(|| StorageError::NotFound(format!("Transaction not found with reference: {}", reference)))?;
+                if transaction.status != read_status {
+                    return Err(StorageError::Conflict(format!(
+                        "transaction {} became '{}' while being signed: {}",
+                        reference, transaction.status, e
+                    )));
+                }
+            }
+            result => {
+                result?;
+                break;
+            }
+        }
+    }
+    storage.update_transaction_txid(transaction.transaction_id, txid).await?;
+    storage.update_transaction_raw_tx(transaction.transaction_id, raw_tx).await?;
+    
+    let mut req = make_new_tx_req(&transaction, args, txid, raw_tx);
+    req.proven_tx_req_id = storage.insert_proven_tx_req(&req).await?;
+    Ok(req)
+}
+
+/// Txids shared with the network by processAction
+///
+/// Reference: TypeScript processAction.ts (`txidsOfReqsToShareWithWorld`)
+///
+/// The sendWith txids, followed by the new transaction's txid unless it is
+/// a noSend transaction that is not itself being sent with a batch.
+fn txids_to_share(args: &StorageProcessActionArgs) -> Result<Vec<String>, StorageError> {
+    let mut txids = args.send_with.clone();
+    
+    if args.is_new_tx && (!args.is_no_send || args.is_send_with) {
+        let txid = args.txid.as_ref().ok_or_else(|| {
+            StorageError::InvalidArg("txid is required for a new transaction".to_string())
+        })?;
+        if !txids.contains(txid) {
+            txids.push(txid.clone());
+        }
+    }
+    
+    Ok(txids)
+}
+
+/// How a ProvenTxReq participates in a sendWith batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReqDisposition {
+    /// Already on the network (or proven); nothing to send
+    AlreadySent,
+    /// Has a raw transaction and input BEEF and can be broadcast
+    ReadyToSend,
+    /// Unknown txid or a status that can't be sent
+    Invalid,
+}
+
+/// Classify a ProvenTxReq for sharing
+///
+/// Reference: TypeScript processAction.ts (`getReqsAndBeefToShareWithWorld`)
+fn classify_req(req: &TableProvenTxReq) -> ReqDisposition {
+    match req.status {
+        ProvenTxReqStatus::Unmined
+        | ProvenTxReqStatus::Callback
+        | ProvenTxReqStatus::Unconfirmed
+        | ProvenTxReqStatus::Completed => ReqDisposition::AlreadySent,
+        ProvenTxReqStatus::Sending
+        | ProvenTxReqStatus::Unsent
+        | ProvenTxReqStatus::Nosend
+        | ProvenTxReqStatus::Unprocessed => {
+            if req.raw_tx.is_empty() || req.input_beef.is_none() {
+                ReqDisposition::Invalid
+            } else {
+                ReqDisposition::ReadyToSend
+            }
+        }
+        _ => ReqDisposition::Invalid,
+    }
+}
+
+/// Merge the input BEEFs and raw transactions of `reqs` into a single BEEF
+fn aggregate_beef(reqs: &[TableProvenTxReq]) -> Result<Beef, StorageError> {
+    let mut beef = Beef::new_v2();
+    for req in reqs {
+        if let Some(input_beef) = &req.input_beef {
+            beef.merge_beef(input_beef).map_err(|e| {
+                StorageError::InvalidArg(format!("inputBEEF of {}: {}", req.txid, e))
+            })?;
+        }
+        beef.merge_raw_tx(&req.raw_tx).map_err(|e| {
+            StorageError::InvalidArg(format!("rawTx of {}: {}", req.txid, e))
+        })?;
+    }
+    beef.sort_txs().map_err(|e| StorageError::InvalidArg(format!("BEEF: {}", e)))?;
+    Ok(beef)
+}
+
+/// Generate random batch identifier
+/// Reference: TypeScript randomBytesBase64(16)
+fn generate_batch_id() -> String {
+    use base64::Engine;
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    let bytes: Vec<u8> = (0..16).map(|_| rng.gen()).collect();
+    base64::engine::general_purpose::STANDARD.encode(&bytes)
+}
+
+/// Request status, transaction status and sendWith status for a broadcast result
+///
+/// Service errors leave the request in 'sending' for the monitor to retry.
+fn statuses_for_review(
+    status: ReviewActionResultStatus,
+) -> (Option<ProvenTxReqStatus>, Option<TransactionStatus>, &'static str) {
+    match status {
+        ReviewActionResultStatus::Success => {
+            (Some(ProvenTxReqStatus::Unmined), Some(TransactionStatus::Unproven), "unproven")
+        }
+        ReviewActionResultStatus::DoubleSpend => {
+            (Some(ProvenTxReqStatus::DoubleSpend), Some(TransactionStatus::Failed), "failed")
+        }
+        ReviewActionResultStatus::InvalidTx => {
+            (Some(ProvenTxReqStatus::Invalid), Some(TransactionStatus::Failed), "failed")
+        }
+        ReviewActionResultStatus::ServiceError => (None, None, "sending"),
+    }
+}
+
+/// ProvenTxReq history note recording which service accepted or rejected a
+/// broadcast
+///
+/// Reference: TypeScript EntityProvenTxReq notes `postBeefSuccess` / `postBeefError`
+fn post_beef_note(review: &ReviewActionResult) -> ReqHistoryNote {
+    let mut extra = HashMap::from([("name".to_string(), serde_json::json!(review.service))]);
+    let what = match review.status {
+        ReviewActionResultStatus::Success => "postBeefSuccess",
+        status => {
+            extra.insert("status".to_string(), serde_json::json!(status));
+            extra.insert("message".to_string(), serde_json::json!(review.message));
+            "postBeefError"
+        }
+    };
+    ReqHistoryNote { when: None, what: what.to_string(), extra }
+}
+
+/// Update a ProvenTxReq and the transactions it notifies
+async fn update_req_and_transactions(
+    storage: &mut dyn WalletStorageProvider,
+    req: &TableProvenTxReq,
+    updates: &ProvenTxReqUpdates,
+    tx_status: Option<TransactionStatus>,
+) -> Result<(), StorageError> {
+    storage.update_proven_tx_req(req.proven_tx_req_id, updates).await?;
+    if let Some(tx_status) = tx_status {
+        for transaction_id in req.notify_transaction_ids() {
+            storage.update_transaction_status(transaction_id, tx_status).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Share a batch of transactions with the network
+///
+/// Reference: TypeScript processAction.ts (`shareReqsWithWorld`)
+///
+/// Ready requests are marked 'unsent' (delayed, picked up by the monitor) or
+/// 'sending' and posted together as one aggregated BEEF. When more than one
+/// transaction is sent they share a random batch id.
+async fn share_reqs_with_world(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    txids: &[String],
+    is_delayed: bool,
+) -> Result<(Vec<SendWithResult>, Option<Vec<ReviewActionResult>>), StorageError> {
+    if txids.is_empty() {
+        return Ok((Vec::new(), None));
+    }
+    
+    // STEP 1: Gather the requests and classify them
+    let reqs = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+        status: None,
+        since: None,
+        paged: None,
+        txids: Some(txids.to_vec()),
+    }).await?;
+    
+    let mut statuses: Vec<(String, &'static str)> = Vec::with_capacity(txids.len());
+    let mut ready: Vec<TableProvenTxReq> = Vec::new();
+    for txid in txids {
+        let disposition = match reqs.iter().find(|r| &r.txid == txid) {
+            Some(req) => {
+                let d = classify_req(req);
+                if d == ReqDisposition::ReadyToSend {
+                    ready.push(req.clone());
+                }
+                d
+            }
+            None if storage.get_proven_or_raw_tx(txid).await?.proven.is_some() => {
+                ReqDisposition::AlreadySent
+            }
+            None => ReqDisposition::Invalid,
+        };
+        let status = match disposition {
+            ReqDisposition::AlreadySent => "unproven",
+            ReqDisposition::ReadyToSend => "sending",
+            ReqDisposition::Invalid => "failed",
+        };
+        statuses.push((txid.clone(), status));
+    }
+    
+    let to_results = |statuses: Vec<(String, &'static str)>| -> Vec<SendWithResult> {
+        statuses.into_iter()
+            .map(|(txid, status)| SendWithResult { txid, status: status.to_string() })
+            .collect()
+    };
+    
+    if ready.is_empty() {
+        return Ok((to_results(statuses), if is_delayed { None } else { Some(Vec::new()) }));
+    }
+    
+    // STEP 2: Queue delayed batches for the monitor's TaskSendWaiting
+    if is_delayed {
+        aggregate_beef(&ready)?;
+        let queued = ProvenTxReqUpdates {
+            status: Some(ProvenTxReqStatus::Unsent),
+            batch: if ready.len() > 1 { Some(generate_batch_id()) } else { None },
+            ..Default::default()
+        };
+        for req in &ready {
+            update_req_and_transactions(storage, req, &queued, Some(TransactionStatus::Sending)).await?;
+        }
+        return Ok((to_results(statuses), None));
+    }
+    
+    // STEP 3: Post the batch now
+    let broadcaster = broadcaster.ok_or_else(|| {
+        StorageError::InvalidArg("a broadcaster is required for non-delayed broadcast".to_string())
+    })?;
+    let not_delayed_results = attempt_to_post_reqs_to_network(storage, broadcaster, utxo_status, &ready).await?;
+    for review in &not_delayed_results {
+        if let Some(entry) = statuses.iter_mut().find(|(txid, _)| *txid == review.txid) {
+            entry.1 = statuses_for_review(review.status).2;
+        }
+    }
+    
+    Ok((to_results(statuses), Some(not_delayed_results)))
+}
+
+/// Post a batch of ready ProvenTxReqs to the network
+///
+/// Reference: TypeScript attemptToPostReqsToNetwork.ts
+///
+/// The requests are marked 'sending' (sharing a random batch id when more
+/// than one is posted) and posted together as one aggregated BEEF. Each
+/// request and the transactions it notifies are then updated from its
+/// broadcast result, recording a `MonitorEvent::BroadcastAttempt` and a
+/// history note naming the service that reported the result; double
+/// spends are reconciled with `reconcile_double_spend`. Service errors
+/// leave requests in 'sending' to be retried.
+///
+/// Returns one result per request, in order.
+#[instrument(skip_all, fields(txids = ?reqs.iter().map(|r| r.txid.as_str()).collect::<Vec<_>>()))]
+pub async fn attempt_to_post_reqs_to_network(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: &dyn Broadcaster,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    reqs: &[TableProvenTxReq],
+) -> Result<Vec<ReviewActionResult>, StorageError> {
+    if reqs.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    // STEP 1: Aggregate BEEF and mark requests as sending
+    let beef = aggregate_beef(reqs)?;
+    let sending = ProvenTxReqUpdates {
+        status: Some(ProvenTxReqStatus::Sending),
+        batch: if reqs.len() > 1 { Some(generate_batch_id()) } else { None },
+        ..Default::default()
+    };
+    for req in reqs {
+        update_req_and_transactions(storage, req, &sending, Some(TransactionStatus::Sending)).await?;
+    }
+    
+    // STEP 2: Post the batch
+    let txids: Vec<String> = reqs.iter().map(|r| r.txid.clone()).collect();
+    let beef_bytes = beef.to_binary().map_err(|e| {
+        StorageError::InvalidArg(format!("BEEF serialization failed: {}", e))
+    })?;
+    metrics::inc_by(metrics::BROADCASTS_ATTEMPTED, &[], txids.len() as u64);
+    let posted = broadcaster.post_beef(&beef_bytes, &txids).await?;
+    
+    // STEP 3: Record each transaction's outcome and broadcast attempt
+    let mut results = Vec::with_capacity(reqs.len());
+    for req in reqs {
+        let mut review = posted.iter().find(|r| r.txid == req.txid).cloned().unwrap_or_else(|| {
+            ReviewActionResult {
+                txid: req.txid.clone(),
+                status: ReviewActionResultStatus::ServiceError,
+                message: Some("no broadcast result returned".to_string()),
+                competing_txs: None,
+                competing_beef: None,
+                double_spend: None,
+                service: None,
+            }
+        });
+        let (req_status, tx_status, _) = statuses_for_review(review.status);
+        match review.status {
+            ReviewActionResultStatus::Success => {
+                metrics::inc(metrics::BROADCASTS_SUCCEEDED, &[]);
+                debug!(txid = %req.txid, "broadcast succeeded")
+            }
+            status => warn!(txid = %req.txid, ?status, message = review.message.as_deref(), "broadcast failed"),
+        }
+        if review.status == ReviewActionResultStatus::DoubleSpend {
+            let competing_txs = review.competing_txs.clone().unwrap_or_default();
+            review.double_spend = Some(reconcile_double_spend(storage, utxo_status, req, &competing_txs).await?);
+        } else {
+            let mut entity = EntityProvenTxReq::new(Some(req.clone()));
+            entity.add_history_note(post_beef_note(&review), true);
+            let updates = ProvenTxReqUpdates {
+                status: req_status,
+                history: Some(entity.into_api().history),
+                ..Default::default()
+            };
+            update_req_and_transactions(storage, req, &updates, tx_status).await?;
+        }
+        let attempt = MonitorEvent::BroadcastAttempt {
+            txid: req.txid.clone(),
+            status: serde_json::to_value(review.status)
+                .ok()
+                .and_then(|status| status.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            message: review.message.clone(),
+        };
+        storage.insert_monitor_event(&attempt.to_table()).await?;
+        results.push(review);
+    }
+    
+    Ok(results)
+}
+
+/// Validate BRC-100 abort action arguments
+///
+/// Reference: TS validateAbortActionArgs
+pub fn validate_abort_action_args(args: &AbortActionArgs) -> WalletResult<ValidAbortActionArgs> {
+    Ok(ValidAbortActionArgs {
+        reference: validate_base64_string(&args.reference, "reference", None, None)?,
     })
 }
 
@@ -111,10 +558,151 @@ pub async fn abort_action(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+    
+    fn args(send_with: &[&str], txid: Option<&str>, is_no_send: bool) -> StorageProcessActionArgs {
+        StorageProcessActionArgs {
+            is_new_tx: txid.is_some(),
+            is_send_with: !send_with.is_empty(),
+            is_no_send,
+            is_delayed: false,
+            reference: None,
+            txid: txid.map(|t| t.to_string()),
+            raw_tx: None,
+            send_with: send_with.iter().map(|s| s.to_string()).collect(),
+            log: None,
+        }
+    }
+    
+    fn req(status: ProvenTxReqStatus, raw_tx: Vec<u8>, input_beef: Option<Vec<u8>>) -> TableProvenTxReq {
+        let mut req = TableProvenTxReq::new(1, status, "txid", "{}", r#"{"transactionIds":[7]}"#, raw_tx);
+        req.input_beef = input_beef;
+        req
+    }
+    
+    fn raw_tx(prev_txid: &str) -> (String, Vec<u8>) {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(prev_txid, 0)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+        (tx.txid().unwrap(), tx.serialize().unwrap())
+    }
+    
+    #[test]
+    fn test_txids_to_share_includes_new_tx() {
+        let txids = txids_to_share(&args(&["a", "b"], Some("c"), false)).unwrap();
+        assert_eq!(txids, vec!["a", "b", "c"]);
+    }
+    
+    #[test]
+    fn test_txids_to_share_excludes_no_send_tx() {
+        // TS Reference: noSend transactions are only shared when sent with a batch
+        let txids = txids_to_share(&args(&[], Some("c"), true)).unwrap();
+        assert!(txids.is_empty());
+        
+        let txids = txids_to_share(&args(&["a"], Some("c"), true)).unwrap();
+        assert_eq!(txids, vec!["a", "c"]);
+    }
+    
+    #[test]
+    fn test_txids_to_share_requires_txid_for_new_tx() {
+        let mut a = args(&[], None, false);
+        a.is_new_tx = true;
+        assert!(matches!(txids_to_share(&a), Err(StorageError::InvalidArg(_))));
+    }
     
     #[test]
-    fn test_process_action_placeholder() {
-        // Placeholder test until full implementation
-        assert!(true);
+    fn test_new_tx_req_status() {
+        let mut a = args(&[], Some("c"), false);
+        assert_eq!(new_tx_req_status(&a), ProvenTxReqStatus::Unprocessed);
+        
+        // acceptDelayedBroadcast queues the request for the monitor
+        a.is_delayed = true;
+        assert_eq!(new_tx_req_status(&a), ProvenTxReqStatus::Unsent);
+        
+        a.is_no_send = true;
+        assert_eq!(new_tx_req_status(&a), ProvenTxReqStatus::Nosend);
+    }
+    
+    #[test]
+    fn test_make_new_tx_req() {
+        let mut tx = TableTransaction::new(42, 1, TransactionStatus::Unsigned, "ref", true, 1000, "test");
+        tx.input_beef = Some(vec![1, 2, 3]);
+        let mut a = args(&[], Some("c"), false);
+        a.is_delayed = true;
+        
+        let req = make_new_tx_req(&tx, &a, "c", &[9, 9]);
+        assert_eq!(req.status, ProvenTxReqStatus::Unsent);
+        assert_eq!(req.txid, "c");
+        assert_eq!(req.raw_tx, vec![9, 9]);
+        assert_eq!(req.input_beef, Some(vec![1, 2, 3]));
+        assert_eq!(req.notify_transaction_ids(), vec![42]);
+        let notes = EntityProvenTxReq::parse_history(&req.history).notes.unwrap();
+        assert_eq!((notes.len(), notes[0].what.as_str()), (1, "processAction"));
+        assert!(notes[0].when.is_some());
+        assert_eq!(classify_req(&req), ReqDisposition::ReadyToSend);
+    }
+    
+    #[test]
+    fn test_classify_req() {
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Unmined, vec![], None)), ReqDisposition::AlreadySent);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Completed, vec![], None)), ReqDisposition::AlreadySent);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Nosend, vec![1], Some(vec![2]))), ReqDisposition::ReadyToSend);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Unsent, vec![1], Some(vec![2]))), ReqDisposition::ReadyToSend);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Nosend, vec![1], None)), ReqDisposition::Invalid);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::Invalid, vec![1], Some(vec![2]))), ReqDisposition::Invalid);
+        assert_eq!(classify_req(&req(ProvenTxReqStatus::DoubleSpend, vec![1], Some(vec![2]))), ReqDisposition::Invalid);
+    }
+    
+    #[test]
+    fn test_aggregate_beef_batches_transactions() {
+        let (parent_txid, parent_raw) = raw_tx(&"11".repeat(32));
+        let (child_txid, child_raw) = raw_tx(&parent_txid);
+        
+        let mut input_beef = Beef::new_v2();
+        input_beef.merge_txid_only(&"11".repeat(32));
+        let input_beef = input_beef.to_binary().unwrap();
+        
+        // Child listed first; aggregation orders parents before children
+        let reqs = vec![
+            req(ProvenTxReqStatus::Nosend, child_raw, Some(input_beef.clone())),
+            req(ProvenTxReqStatus::Nosend, parent_raw, Some(input_beef)),
+        ];
+        let beef = aggregate_beef(&reqs).unwrap();
+        
+        let ids: Vec<&str> = beef.txs.iter().filter(|t| !t.is_txid_only).map(|t| t.txid.as_str()).collect();
+        assert_eq!(ids, vec![parent_txid.as_str(), child_txid.as_str()]);
+        assert!(Beef::from_binary(&beef.to_binary().unwrap()).is_ok());
+    }
+    
+    #[test]
+    fn test_statuses_for_review() {
+        assert_eq!(
+            statuses_for_review(ReviewActionResultStatus::Success),
+            (Some(ProvenTxReqStatus::Unmined), Some(TransactionStatus::Unproven), "unproven")
+        );
+        assert_eq!(statuses_for_review(ReviewActionResultStatus::DoubleSpend).2, "failed");
+        assert_eq!(statuses_for_review(ReviewActionResultStatus::InvalidTx).2, "failed");
+        assert_eq!(statuses_for_review(ReviewActionResultStatus::ServiceError), (None, None, "sending"));
+    }
+    
+    #[tokio::test]
+    async fn test_commit_new_tx_to_storage() {
+        let mut storage = wallet_test_utils::MockStorage::new();
+        let mut tx = TableTransaction::new(7, 1, TransactionStatus::Unsigned, "ref1", true, 0, "test");
+        tx.updated_at = "2026-10-16T00:00:00Z".to_string();
+        storage.transactions.push(tx);
+        let (txid, raw_tx) = raw_tx(&"aa".repeat(32));
+        let args = StorageProcessActionArgs {
+            reference: Some("ref1".to_string()),
+            raw_tx: Some(raw_tx),
+            ..args(&[], Some(&txid), false)
+        };
+        
+        let req = commit_new_tx_to_storage(&mut storage, 1, &args).await.unwrap();
+        assert_eq!(req.status, ProvenTxReqStatus::Unprocessed);
+        let committed = storage.transaction(7);
+        assert_eq!(committed.status, TransactionStatus::Sending);
+        assert_eq!(committed.txid.as_deref(), Some(txid.as_str()));
+        assert_ne!(committed.updated_at, "2026-10-16T00:00:00Z");
     }
 }
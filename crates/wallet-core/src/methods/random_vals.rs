@@ -0,0 +1,105 @@
+//! Random Values
+//!
+//! Source of randomness for createAction: reference IDs, derivation
+//! prefixes and suffixes, change distribution and output order. When the
+//! caller supplies `randomVals` they are used in rotation, making the whole
+//! action reproducible so results can be compared byte for byte with the
+//! TypeScript implementation; otherwise the thread RNG is used.
+//!
+//! Reference: TypeScript `nextRandomVal` / `rand` / `randomDerivation`
+//! (createAction.ts and generateChange.ts)
+
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+
+/// Random values shared by every step of one createAction call
+#[derive(Debug, Clone, Default)]
+pub struct RandomVals {
+    vals: Vec<f64>,
+}
+
+impl RandomVals {
+    /// Use `random_vals` in rotation, or the thread RNG when absent or empty
+    pub fn new(random_vals: Option<Vec<f64>>) -> Self {
+        Self { vals: random_vals.unwrap_or_default() }
+    }
+
+    /// Whether values come from the caller rather than the thread RNG
+    pub fn is_deterministic(&self) -> bool {
+        !self.vals.is_empty()
+    }
+
+    /// Next value in `[0, 1)`, rotating the caller's values
+    pub fn next_val(&mut self) -> f64 {
+        if self.vals.is_empty() {
+            rand::random::<f64>()
+        } else {
+            let v = self.vals.remove(0);
+            self.vals.push(v);
+            v
+        }
+    }
+
+    /// Random integer between `min` and `max` inclusive
+    pub fn rand(&mut self, min: i64, max: i64) -> i64 {
+        (self.next_val() * (max - min + 1) as f64 + min as f64).floor() as i64
+    }
+
+    /// `count` random bytes, base64 encoded
+    ///
+    /// Deterministic bytes are `rand(0, 255)` each, as TS `randomDerivation`.
+    pub fn bytes_base64(&mut self, count: usize) -> String {
+        let bytes: Vec<u8> = if self.is_deterministic() {
+            (0..count).map(|_| self.rand(0, 255) as u8).collect()
+        } else {
+            let mut bytes = vec![0u8; count];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            bytes
+        };
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    /// Shuffle `items` in place (Fisher-Yates)
+    ///
+    /// From the last item down, item `i` swaps with `floor(next_val() * (i + 1))`,
+    /// so caller values reproduce the TS `shuffleArray` order exactly.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = ((self.next_val() * (i + 1) as f64).floor() as usize).min(i);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caller_values_rotate() {
+        let mut r = RandomVals::new(Some(vec![0.1, 0.5, 0.9]));
+        assert!(r.is_deterministic());
+        let vals: Vec<f64> = (0..4).map(|_| r.next_val()).collect();
+        assert_eq!(vals, vec![0.1, 0.5, 0.9, 0.1]);
+        assert_eq!(r.rand(0, 9), 5);
+        assert_eq!(r.rand(0, 255), 230);
+    }
+
+    #[test]
+    fn test_deterministic_bytes_and_shuffle_repeat() {
+        let run = || {
+            let mut r = RandomVals::new(Some(vec![0.25, 0.75, 0.5]));
+            let mut order = vec![0, 1, 2, 3, 4];
+            r.shuffle(&mut order);
+            (r.bytes_base64(10), order)
+        };
+        assert_eq!(run(), run());
+
+        let mut r = RandomVals::new(Some(vec![0.0]));
+        assert_eq!(r.bytes_base64(3), "AAAA");
+
+        let mut r = RandomVals::new(None);
+        assert!(!r.is_deterministic());
+        assert_ne!(r.bytes_base64(12), r.bytes_base64(12));
+    }
+}
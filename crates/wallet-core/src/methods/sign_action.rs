@@ -40,24 +40,21 @@
 //!       - Build unlocking script
 //!       - Add to transaction
 //!
-//! 5. **Update Storage** (TS lines 182-200)
-//!     - Update transaction status to 'signed' or 'sending'
-//!     - Store raw transaction
-//!     - Store txid
-//!     - Update spent outputs
-//!
-//! 6. **Prepare Result** (TS lines 202-220)
-//!     - Build result with txid
-//!     - Include rawTx if requested
-//!     - Add sendWith info
-//!     - Return processing result
+//! 5. **Process Action** (TS lines 182-220, via processAction)
+//!     - Store txid and raw transaction, status 'nosend' or 'sending'
+//!     - Queue a ProvenTxReq ('unsent' when acceptDelayedBroadcast)
+//!     - Broadcast now, or leave delayed broadcast to the Monitor
+//!     - Return sendWith and broadcast results
 //!
 //! **Returns**: `StorageProcessActionResults` with txid, optional raw tx, sendWith results
 
 use crate::sdk::action_process::{
     ValidSignActionArgs, SignActionSpend,
-    StorageProcessActionResults, SendWithResult,
+    StorageProcessActionArgs, StorageProcessActionResults, SendWithResult,
 };
+use crate::services::{Broadcaster, UtxoStatusProvider};
+use super::process_action::process_action;
+use tracing::{debug, field, instrument, Span};
 use wallet_storage::{
     StorageError, WalletStorageProvider, AuthId,
     TableTransaction, TableOutput, TransactionStatus,
@@ -89,9 +86,12 @@ pub struct StorageSignActionResult {
 /// 2. Generating unlocking scripts for inputs
 /// 3. Signing with derived keys
 /// 4. Updating transaction status
-/// 5. Optionally broadcasting
+/// 5. Queueing (delayed) or broadcasting via processAction
+#[instrument(skip_all, fields(user_id = auth.user_id, reference = %vargs.reference, txid = field::Empty))]
 pub async fn sign_action(
     storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
     auth: &AuthId,
     vargs: ValidSignActionArgs,
 ) -> Result<StorageProcessActionResults, StorageError> {
@@ -126,35 +126,23 @@ pub async fn sign_action(
         &outputs,
         &vargs.spends,
     ).await?;
+    Span::current().record("txid", signed_tx.txid.as_str());
+    debug!(inputs = inputs.len(), outputs = outputs.len(), "signed transaction");
     
-    // STEP 5: Update transaction in storage
-    // TS lines 182-200: Mark as signed, store raw tx and txid
-    update_signed_transaction(
-        storage,
-        transaction.transaction_id,
-        &signed_tx.txid,
-        &signed_tx.raw_tx,
-        vargs.is_no_send,
-    ).await?;
-    
-    // STEP 6: Handle broadcast if needed
-    // TS lines 202-220: Process sendWith, mark for broadcast
-    let send_with_results = if !vargs.is_no_send {
-        handle_broadcast(storage, &signed_tx, &vargs).await?
-    } else {
-        Vec::new()
-    };
-    
-    // STEP 7: Build result
-    Ok(StorageProcessActionResults {
-        send_with_results: if send_with_results.is_empty() {
-            None
-        } else {
-            Some(send_with_results)
-        },
-        not_delayed_results: None, // Set by processAction later
+    // STEP 5: Commit and share via processAction
+    // TS lines 182-220: Delayed broadcasts return once the ProvenTxReq is
+    // queued as 'unsent'; the Monitor broadcasts them asynchronously.
+    process_action(storage, broadcaster, utxo_status, auth, StorageProcessActionArgs {
+        is_new_tx: true,
+        is_send_with: vargs.is_send_with,
+        is_no_send: vargs.is_no_send,
+        is_delayed: vargs.is_delayed,
+        reference: Some(vargs.reference.clone()),
+        txid: Some(signed_tx.txid),
+        raw_tx: Some(signed_tx.raw_tx),
+        send_with: vargs.options.send_with.clone(),
         log: signed_tx.log,
-    })
+    }).await
 }
 
 // ============================================================================
@@ -258,6 +246,7 @@ struct SignedTransaction {
 /// - Generates unlocking scripts
 /// - Signs each input with derived keys
 /// - Calculates txid
+#[instrument(level = "debug", skip_all, fields(transaction_id = transaction.transaction_id))]
 async fn build_and_sign_transaction(
     storage: &dyn WalletStorageProvider,
     user_id: i64,
@@ -315,7 +304,7 @@ async fn build_and_sign_transaction(
             &tx,
             vin,
             prev_script,
-            SigHashType::All,
+            SigHashType::All.with_forkid(),
             input_data.satoshis,
         ).map_err(|e| StorageError::InvalidArg(format!("Sighash calculation failed: {}", e)))?;
         
@@ -346,7 +335,7 @@ async fn build_and_sign_transaction(
             .map_err(|e| StorageError::InvalidArg(format!("Key derivation failed: {}", e)))?;
         
         // STEP 5.4.2: Sign the sighash
-        let signature = sign_ecdsa(&sighash, &private_key, SigHashType::All.as_u8())
+        let signature = sign_ecdsa(&sighash, &private_key, SigHashType::All.with_forkid())
             .map_err(|e| StorageError::InvalidArg(format!("Signing failed: {}", e)))?;
         
         // STEP 5.4.3: Derive public key
@@ -395,60 +384,6 @@ async fn get_master_private_key(
     ))
 }
 
-/// STEP 6: Update signed transaction in storage
-/// Reference: TypeScript signAction.ts lines 182-200
-async fn update_signed_transaction(
-    storage: &mut dyn WalletStorageProvider,
-    transaction_id: i64,
-    txid: &str,
-    raw_tx: &[u8],
-    is_no_send: bool,
-) -> Result<(), StorageError> {
-    // TS: await storage.updateTransaction(transactionId, {
-    //   status: isNoSend ? 'nosend' : 'signed',
-    //   txid,
-    //   rawTx
-    // })
-    
-    let status = if is_no_send {
-        TransactionStatus::Nosend
-    } else {
-        TransactionStatus::Sending
-    };
-    
-    storage.update_transaction_status(transaction_id, status).await?;
-    storage.update_transaction_txid(transaction_id, txid).await?;
-    storage.update_transaction_raw_tx(transaction_id, raw_tx).await?;
-    
-    Ok(())
-}
-
-/// STEP 7: Handle broadcast preparation
-/// Reference: TypeScript signAction.ts lines 202-220
-async fn handle_broadcast(
-    _storage: &dyn WalletStorageProvider,
-    _signed_tx: &SignedTransaction,
-    vargs: &ValidSignActionArgs,
-) -> Result<Vec<SendWithResult>, StorageError> {
-    // TS: Process sendWith transactions, prepare for broadcast
-    // Reference: TypeScript signAction.ts lines 202-220
-    
-    let mut results = Vec::new();
-    
-    // If sendWith is provided, process each broadcast target
-    for _protocol in &vargs.options.send_with {
-        // Prepare send with result
-        // In a full implementation, this would interact with overlay services
-        // or other broadcast mechanisms
-        results.push(SendWithResult {
-            status: "prepared".to_string(),
-            txid: _signed_tx.txid.clone(),
-        });
-    }
-    
-    Ok(results)
-}
-
 // ============================================================================
 // TESTS
 // ============================================================================
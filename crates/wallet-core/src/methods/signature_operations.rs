@@ -3,52 +3,53 @@
 //! Create and verify ECDSA signatures using wallet-derived keys.
 //! Reference: wallet-toolbox SDK createSignature/verifySignature methods
 
-use crate::crypto::signing::{sign_ecdsa, verify_signature as verify_sig_crypto, sha256};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, SecretKey, SECP256K1};
+
+use crate::crypto::signing::sha256;
 use crate::keys::key_deriver::KeyDeriver;
 use crate::sdk::{
     CreateSignatureArgs, CreateSignatureResult, VerifySignatureArgs, VerifySignatureResult,
     WalletError, WalletResult,
 };
 
+/// Hash to sign or verify: the supplied hash, else SHA-256 of the data
+fn hash_to_sign(data: Option<&Vec<u8>>, hash: Option<&Vec<u8>>, hash_name: &str) -> WalletResult<Message> {
+    let hash = match (hash, data) {
+        (Some(hash), _) => hash.clone(),
+        (None, Some(data)) => sha256(data),
+        (None, None) => {
+            return Err(WalletError::invalid_parameter(
+                format!("data or {}", hash_name),
+                format!("Either data or {} must be provided", hash_name),
+            ))
+        }
+    };
+    Message::from_digest_slice(&hash)
+        .map_err(|_| WalletError::invalid_parameter(hash_name, "Hash must be exactly 32 bytes"))
+}
+
 /// Create an ECDSA signature using a wallet-derived key
 ///
-/// Derives a private key using the protocol ID, key ID, and counterparty,
-/// then creates an ECDSA signature of the provided data (or hash).
+/// Derives a private key using the protocol ID, key ID, and counterparty
+/// (default "anyone", so anyone can verify), then signs the SHA-256 of the
+/// provided data, or the provided hash directly.
 ///
 /// # Arguments
 /// * `args` - Signature creation arguments
 /// * `key_deriver` - Key derivation service
 ///
 /// # Returns
-/// DER-encoded ECDSA signature
+/// DER-encoded low-S ECDSA signature
 ///
-/// Reference: TypeScript `createSignature()` in SDK
+/// Reference: TypeScript `createSignature()` in ProtoWallet
 pub async fn create_signature(
     args: &CreateSignatureArgs,
     key_deriver: &dyn KeyDeriver,
 ) -> WalletResult<CreateSignatureResult> {
-    // Get the data to sign
-    let hash_to_sign = if let Some(ref hash) = args.hash_to_directly_sign {
-        // Use provided hash directly
-        if hash.len() != 32 {
-            return Err(WalletError::invalid_parameter(
-                "hashToDirectlySign",
-                "Hash must be exactly 32 bytes",
-            ));
-        }
-        hash.clone()
-    } else if let Some(ref data) = args.data {
-        // Hash the data with SHA-256
-        sha256(data)
-    } else {
-        return Err(WalletError::invalid_parameter(
-            "data or hashToDirectlySign",
-            "Either data or hashToDirectlySign must be provided",
-        ));
-    };
+    let message = hash_to_sign(args.data.as_ref(), args.hash_to_directly_sign.as_ref(), "hashToDirectlySign")?;
     
     // Derive the signing key
-    let counterparty = args.counterparty.as_deref().unwrap_or("self");
+    let counterparty = args.counterparty.as_deref().unwrap_or("anyone");
     
     let derived_key = key_deriver
         .derive_key(
@@ -59,25 +60,20 @@ pub async fn create_signature(
         .await
         .map_err(|e| WalletError::internal(format!("Key derivation failed: {}", e)))?;
     
-    // Ensure key is exactly 32 bytes
-    if derived_key.len() != 32 {
-        return Err(WalletError::internal("Derived key must be 32 bytes"));
-    }
-    
-    let key_array: [u8; 32] = derived_key
-        .try_into()
-        .map_err(|_| WalletError::internal("Key conversion failed"))?;
+    let secret_key = SecretKey::from_slice(&derived_key)
+        .map_err(|e| WalletError::internal(format!("Derived key is invalid: {}", e)))?;
     
-    // Create ECDSA signature (with default sighash type 0x01)
-    let signature = sign_ecdsa(&hash_to_sign, &key_array, 0x01)
-        .map_err(|e| WalletError::internal(format!("Signature creation failed: {}", e)))?;
+    // secp256k1 signs deterministically (RFC 6979) with low S
+    let signature = SECP256K1.sign_ecdsa(&message, &secret_key).serialize_der().to_vec();
     
     Ok(CreateSignatureResult { signature })
 }
 
 /// Verify an ECDSA signature using a wallet-derived public key
 ///
-/// Derives the public key and verifies the signature matches.
+/// Derives the signer's public key (the counterparty's, default "self", or
+/// our own when `for_self` is set) and verifies the DER signature over the
+/// SHA-256 of the data, or the provided hash directly.
 ///
 /// # Arguments  
 /// * `args` - Signature verification arguments
@@ -86,32 +82,16 @@ pub async fn create_signature(
 /// # Returns
 /// `{ valid: true }` on success, error on failure
 ///
-/// Reference: TypeScript `verifySignature()` in SDK
+/// Reference: TypeScript `verifySignature()` in ProtoWallet
 pub async fn verify_signature(
     args: &VerifySignatureArgs,
     key_deriver: &dyn KeyDeriver,
 ) -> WalletResult<VerifySignatureResult> {
-    // Get the hash to verify
-    let hash_to_verify = if let Some(ref hash) = args.hash_to_directly_verify {
-        if hash.len() != 32 {
-            return Err(WalletError::invalid_parameter(
-                "hashToDirectlyVerify",
-                "Hash must be exactly 32 bytes",
-            ));
-        }
-        hash.clone()
-    } else if let Some(ref data) = args.data {
-        sha256(data)
-    } else {
-        return Err(WalletError::invalid_parameter(
-            "data or hashToDirectlyVerify",
-            "Either data or hashToDirectlyVerify must be provided",
-        ));
-    };
+    let message = hash_to_sign(args.data.as_ref(), args.hash_to_directly_verify.as_ref(), "hashToDirectlyVerify")?;
     
     // Derive the public key
     let counterparty = args.counterparty.as_deref().unwrap_or("self");
-    let for_self = args.for_self.unwrap_or(true);
+    let for_self = args.for_self.unwrap_or(false);
     
     let public_key = key_deriver
         .derive_public_key(
@@ -122,10 +102,16 @@ pub async fn verify_signature(
         )
         .await
         .map_err(|e| WalletError::internal(format!("Public key derivation failed: {}", e)))?;
+    let public_key = PublicKey::from_slice(&public_key)
+        .map_err(|e| WalletError::internal(format!("Derived public key is invalid: {}", e)))?;
     
-    // Verify signature
-    let valid = verify_sig_crypto(&hash_to_verify, &args.signature, &public_key)
-        .map_err(|e| WalletError::internal(format!("Signature verification failed: {}", e)))?;
+    // Accept high-S signatures as TS ECDSA.verify does
+    let valid = Signature::from_der(&args.signature)
+        .map(|mut signature| {
+            signature.normalize_s();
+            SECP256K1.verify_ecdsa(&message, &signature, &public_key).is_ok()
+        })
+        .unwrap_or(false);
     
     if !valid {
         return Err(WalletError::invalid_parameter(
@@ -144,158 +130,99 @@ pub async fn verify_signature(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use async_trait::async_trait;
-    use crate::crypto::keys::derive_public_key;
+    use crate::keys::RootKeyDeriver;
     
-    // Mock key deriver for testing
-    struct MockKeyDeriver {
-        private_key: Vec<u8>,
+    fn deriver(byte: u8) -> RootKeyDeriver {
+        RootKeyDeriver::new(&[byte; 32]).unwrap()
     }
     
-    impl MockKeyDeriver {
-        fn new() -> Self {
-            Self {
-                private_key: vec![0x42; 32],
-            }
+    fn create_args(data: Option<Vec<u8>>, counterparty: Option<String>) -> CreateSignatureArgs {
+        CreateSignatureArgs {
+            protocol_id: (2, "tests protocol suite".to_string()),
+            key_id: "key1".to_string(),
+            data,
+            hash_to_directly_sign: None,
+            counterparty,
+            privileged: None,
+            privileged_reason: None,
         }
     }
     
-    #[async_trait]
-    impl KeyDeriver for MockKeyDeriver {
-        async fn derive_key(
-            &self,
-            _protocol_id: &(u8, String),
-            _key_id: &str,
-            _counterparty: &str,
-        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-            Ok(self.private_key.clone())
-        }
-        
-        async fn derive_public_key(
-            &self,
-            _protocol_id: &(u8, String),
-            _key_id: &str,
-            _counterparty: &str,
-            _for_self: bool,
-        ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-            let key_array: [u8; 32] = self.private_key.clone().try_into().unwrap();
-            let pubkey = derive_public_key(&key_array, true)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-            Ok(pubkey)
+    fn verify_args(data: Vec<u8>, signature: Vec<u8>, counterparty: Option<String>, for_self: Option<bool>) -> VerifySignatureArgs {
+        VerifySignatureArgs {
+            protocol_id: (2, "tests protocol suite".to_string()),
+            key_id: "key1".to_string(),
+            data: Some(data),
+            hash_to_directly_verify: None,
+            signature,
+            for_self,
+            counterparty,
+            privileged: None,
+            privileged_reason: None,
         }
     }
     
     #[tokio::test]
     async fn test_create_signature_basic() {
-        let args = CreateSignatureArgs {
-            protocol_id: (0, "test".to_string()),
-            key_id: "key1".to_string(),
-            data: Some(vec![1, 2, 3, 4]),
-            hash_to_directly_sign: None,
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
-        
-        let deriver = MockKeyDeriver::new();
-        let result = create_signature(&args, &deriver).await.unwrap();
+        let result = create_signature(&create_args(Some(vec![1, 2, 3, 4]), None), &deriver(1)).await.unwrap();
         
-        // Signature should be DER-encoded (typically 70-72 bytes)
-        assert!(result.signature.len() >= 70 && result.signature.len() <= 73);
+        // Plain DER, no sighash byte
+        assert!(Signature::from_der(&result.signature).is_ok());
     }
     
     #[tokio::test]
-    async fn test_verify_signature_valid() {
+    async fn test_verify_counterparty_signature() {
+        let alice = deriver(1);
+        let bob = deriver(2);
         let data = vec![1, 2, 3, 4];
         
-        let create_args = CreateSignatureArgs {
-            protocol_id: (0, "test".to_string()),
-            key_id: "key1".to_string(),
-            data: Some(data.clone()),
-            hash_to_directly_sign: None,
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
+        let created = create_signature(&create_args(Some(data.clone()), Some(bob.identity_key_hex())), &alice).await.unwrap();
         
-        let deriver = MockKeyDeriver::new();
-        let created = create_signature(&create_args, &deriver).await.unwrap();
+        // Bob verifies with Alice as counterparty
+        let args = verify_args(data.clone(), created.signature.clone(), Some(alice.identity_key_hex()), None);
+        assert!(verify_signature(&args, &bob).await.unwrap().valid);
         
-        // Verify the signature
-        let verify_args = VerifySignatureArgs {
-            protocol_id: create_args.protocol_id.clone(),
-            key_id: create_args.key_id.clone(),
-            data: Some(data),
-            hash_to_directly_verify: None,
-            signature: created.signature,
-            for_self: Some(true),
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
+        // Alice verifies her own signature
+        let args = verify_args(data.clone(), created.signature.clone(), Some(bob.identity_key_hex()), Some(true));
+        assert!(verify_signature(&args, &alice).await.unwrap().valid);
         
-        let result = verify_signature(&verify_args, &deriver).await.unwrap();
-        assert!(result.valid);
+        // Other data fails
+        let args = verify_args(vec![5], created.signature, Some(alice.identity_key_hex()), None);
+        assert!(verify_signature(&args, &bob).await.is_err());
     }
     
     #[tokio::test]
-    async fn test_verify_signature_invalid() {
-        let verify_args = VerifySignatureArgs {
-            protocol_id: (0, "test".to_string()),
-            key_id: "key1".to_string(),
-            data: Some(vec![1, 2, 3, 4]),
-            hash_to_directly_verify: None,
-            signature: vec![0xFF; 71], // Invalid signature
-            for_self: Some(true),
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
+    async fn test_anyone_signature_verifiable_by_anyone() {
+        let alice = deriver(1);
+        let data = vec![1, 2, 3, 4];
         
-        let deriver = MockKeyDeriver::new();
-        let result = verify_signature(&verify_args, &deriver).await;
+        // Default counterparty for signing is "anyone"
+        let created = create_signature(&create_args(Some(data.clone()), None), &alice).await.unwrap();
         
-        // Should fail
-        assert!(result.is_err());
+        let args = verify_args(data, created.signature, Some(alice.identity_key_hex()), None);
+        assert!(verify_signature(&args, &RootKeyDeriver::anyone()).await.unwrap().valid);
     }
     
     #[tokio::test]
-    async fn test_signature_with_direct_hash() {
-        let hash = vec![0x12; 32];
-        
-        let args = CreateSignatureArgs {
-            protocol_id: (0, "test".to_string()),
-            key_id: "key1".to_string(),
-            data: None,
-            hash_to_directly_sign: Some(hash.clone()),
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
-        
-        let deriver = MockKeyDeriver::new();
-        let result = create_signature(&args, &deriver).await.unwrap();
-        
-        assert!(result.signature.len() >= 70);
+    async fn test_verify_signature_invalid() {
+        let args = verify_args(vec![1, 2, 3, 4], vec![0xFF; 71], None, None);
+        assert!(verify_signature(&args, &deriver(1)).await.is_err());
     }
     
     #[tokio::test]
-    async fn test_signature_deterministic() {
-        let args = CreateSignatureArgs {
-            protocol_id: (0, "test".to_string()),
-            key_id: "key1".to_string(),
-            data: Some(vec![1, 2, 3, 4]),
-            hash_to_directly_sign: None,
-            counterparty: None,
-            privileged: None,
-            privileged_reason: None,
-        };
+    async fn test_signature_with_direct_hash() {
+        let alice = deriver(1);
+        let data = vec![1, 2, 3, 4];
         
-        let deriver = MockKeyDeriver::new();
-        let sig1 = create_signature(&args, &deriver).await.unwrap();
-        let sig2 = create_signature(&args, &deriver).await.unwrap();
+        let mut args = create_args(None, Some("self".to_string()));
+        assert!(create_signature(&args, &alice).await.is_err());
+        args.hash_to_directly_sign = Some(vec![0x12; 31]);
+        assert!(create_signature(&args, &alice).await.is_err());
         
-        // ECDSA with RFC 6979 should be deterministic
-        assert_eq!(sig1.signature, sig2.signature);
+        // Signing the hash directly equals signing the data
+        args.hash_to_directly_sign = Some(sha256(&data));
+        let by_hash = create_signature(&args, &alice).await.unwrap();
+        let by_data = create_signature(&create_args(Some(data), Some("self".to_string())), &alice).await.unwrap();
+        assert_eq!(by_hash.signature, by_data.signature);
     }
 }
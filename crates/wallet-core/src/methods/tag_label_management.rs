@@ -0,0 +1,120 @@
+//! Tag and Label Management
+//!
+//! Rename and delete output tags and transaction labels, and count tag usage,
+//! so long-lived wallets can tidy their metadata.
+//!
+//! Deletes are soft: the tag or label and its mappings are flagged deleted,
+//! and stop matching `listOutputs` tag and `listActions` label filters.
+//! Using a deleted name again restores it, without its old mappings.
+
+use wallet_storage::{AuthId, OutputTagUsage, StorageError, TableOutputTag, TableTxLabel, WalletStorageProvider};
+
+use crate::sdk::{validate_label, validate_tag, WalletError, WalletResult};
+
+/// A missing or deleted name is the caller's mistake, not a storage fault
+fn existing(parameter: &str, err: StorageError) -> WalletError {
+    match err {
+        StorageError::NotFound(_) => WalletError::invalid_parameter(parameter, format!("an existing {}", parameter)),
+        err => err.into(),
+    }
+}
+
+/// Rename one of the user's output tags
+///
+/// If the user already has `new_tag`, the outputs tagged `tag` are tagged
+/// `new_tag` instead and `tag` is deleted. Returns the renamed tag.
+pub async fn rename_output_tag(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    tag: &str,
+    new_tag: &str,
+) -> WalletResult<TableOutputTag> {
+    let (tag, new_tag) = (validate_tag(tag)?, validate_tag(new_tag)?);
+    if tag == new_tag {
+        return Err(WalletError::invalid_parameter("newTag", "different from tag"));
+    }
+    let user_id = auth.user_id_required()?;
+    storage.rename_output_tag(user_id, &tag, &new_tag).await.map_err(|e| existing("tag", e))
+}
+
+/// Delete one of the user's output tags, untagging its outputs
+pub async fn delete_output_tag(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    tag: &str,
+) -> WalletResult<()> {
+    let tag = validate_tag(tag)?;
+    let user_id = auth.user_id_required()?;
+    storage.delete_output_tag(user_id, &tag).await.map_err(|e| existing("tag", e))
+}
+
+/// Rename one of the user's transaction labels
+///
+/// Merges into an existing `new_label` as `rename_output_tag` does.
+pub async fn rename_tx_label(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    label: &str,
+    new_label: &str,
+) -> WalletResult<TableTxLabel> {
+    let (label, new_label) = (validate_label(label)?, validate_label(new_label)?);
+    if label == new_label {
+        return Err(WalletError::invalid_parameter("newLabel", "different from label"));
+    }
+    let user_id = auth.user_id_required()?;
+    storage.rename_tx_label(user_id, &label, &new_label).await.map_err(|e| existing("label", e))
+}
+
+/// Delete one of the user's transaction labels, unlabelling its transactions
+pub async fn delete_tx_label(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    label: &str,
+) -> WalletResult<()> {
+    let label = validate_label(label)?;
+    let user_id = auth.user_id_required()?;
+    storage.delete_tx_label(user_id, &label).await.map_err(|e| existing("label", e))
+}
+
+/// Number of outputs carrying each of the user's tags, by tag name
+pub async fn output_tag_usages(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+) -> WalletResult<Vec<OutputTagUsage>> {
+    Ok(storage.get_output_tag_usages(auth.user_id_required()?).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::WalletErrorCode;
+    use wallet_test_utils::fixtures::alice_identity_key;
+    use wallet_test_utils::MockStorage;
+
+    fn alice() -> (MockStorage, AuthId) {
+        (MockStorage::with_fixtures(), AuthId::new(alice_identity_key()).with_user_id(1))
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_delete_tag() {
+        let (mut storage, auth) = alice();
+        let tag = storage.find_or_insert_output_tag(1, "rent").await.unwrap();
+        storage.find_or_insert_output_tag_map(1, tag.output_tag_id).await.unwrap();
+
+        let renamed = rename_output_tag(&mut storage, &auth, " Rent ", "Housing").await.unwrap();
+        assert_eq!(renamed.tag, "housing");
+        let usages = output_tag_usages(&storage, &auth).await.unwrap();
+        assert_eq!((usages[0].tag.as_str(), usages[0].output_count), ("housing", 1));
+
+        assert!(rename_output_tag(&mut storage, &auth, "housing", "HOUSING").await.is_err());
+        delete_output_tag(&mut storage, &auth, "housing").await.unwrap();
+        assert!(output_tag_usages(&storage, &auth).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_label() {
+        let (mut storage, auth) = alice();
+        let err = delete_tx_label(&mut storage, &auth, "nothing").await.unwrap_err();
+        assert_eq!(err.code, WalletErrorCode::InvalidParameter);
+    }
+}
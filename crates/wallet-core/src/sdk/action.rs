@@ -4,6 +4,7 @@
 //! Reference: src/sdk/WalletStorage.interfaces.ts, src/sdk/validationHelpers.ts
 
 use serde::{Deserialize, Serialize};
+use wallet_storage::StorageFeeModel;
 
 /// Outpoint reference (txid + vout)
 /// Matches SDK OutPoint
@@ -142,6 +143,15 @@ pub struct ValidCreateActionOptions {
     /// Return only TXID
     #[serde(rename = "returnTXIDOnly", default)]
     pub return_txid_only: bool,
+    
+    /// Fee model override for this call (defaults to the storage fee model)
+    #[serde(rename = "feeModel", skip_serializing_if = "Option::is_none", default)]
+    pub fee_model: Option<StorageFeeModel>,
+    
+    /// Change basket override for this call (defaults to the storage change
+    /// basket for the originator)
+    #[serde(rename = "changeBasket", skip_serializing_if = "Option::is_none", default)]
+    pub change_basket: Option<String>,
 }
 
 impl Default for ValidCreateActionOptions {
@@ -156,6 +166,8 @@ impl Default for ValidCreateActionOptions {
             randomize_outputs: true,
             no_send_change: None,
             return_txid_only: false,
+            fee_model: None,
+            change_basket: None,
         }
     }
 }
@@ -162,6 +162,12 @@ pub struct PartialCertificateFilter {
     
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subject: Option<String>, // PubKeyHex
+    
+    #[serde(rename = "revocationOutpoint", skip_serializing_if = "Option::is_none")]
+    pub revocation_outpoint: Option<String>, // OutpointString
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>, // HexString
 }
 
 /// List certificates arguments
@@ -329,6 +335,7 @@ pub struct FindOutputsArgs {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sdk::wallet_interface::RelinquishOutputArgs;
 
     #[test]
     fn test_list_actions_default() {
@@ -370,8 +377,9 @@ mod tests {
     #[test]
     fn test_relinquish_output() {
         let args = RelinquishOutputArgs {
-            basket: "savings".to_string(),
-            output: 0,
+            txid: "ab".repeat(32),
+            vout: 0,
+            basket: Some("savings".to_string()),
         };
         let json = serde_json::to_string(&args).unwrap();
         assert!(json.contains("savings"));
@@ -5,6 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use super::errors::DoubleSpendError;
 
 /// Sign action spend information
 /// Matches TypeScript `SignActionSpend`
@@ -14,11 +15,15 @@ pub struct SignActionSpend {
     #[serde(rename = "unlockingScript")]
     pub unlocking_script: String,
     
-    /// Sequence number
-    #[serde(rename = "sequenceNumber")]
+    /// Sequence number (default 0xffffffff)
+    #[serde(rename = "sequenceNumber", default = "default_sequence_number")]
     pub sequence_number: u32,
 }
 
+fn default_sequence_number() -> u32 {
+    0xffffffff
+}
+
 /// Sign action options
 /// Matches TypeScript `ValidSignActionOptions`
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -251,9 +256,22 @@ pub struct ReviewActionResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
     
+    /// Competing txids (for double spend)
+    #[serde(rename = "competingTxs", skip_serializing_if = "Option::is_none")]
+    pub competing_txs: Option<Vec<String>>,
+    
     /// Optional competing BEEF (for double spend)
     #[serde(rename = "competingBeef", skip_serializing_if = "Option::is_none")]
     pub competing_beef: Option<Vec<u8>>,
+    
+    /// Outcome of reconciling a double spend
+    #[serde(rename = "doubleSpend", skip_serializing_if = "Option::is_none")]
+    pub double_spend: Option<DoubleSpendError>,
+    
+    /// Service that reported the result, e.g. the ARC endpoint that accepted
+    /// the transaction
+    #[serde(skip)]
+    pub service: Option<String>,
 }
 
 /// Send with result
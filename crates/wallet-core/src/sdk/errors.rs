@@ -7,30 +7,204 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
+/// Error code of a [`WalletError`]
+///
+/// The `WERR_*` taxonomy of TypeScript WERR_errors.ts, plus the codes this
+/// crate adds for I/O and JSON failures. Serializes as the code string, so
+/// clients can branch on it; codes without a variant round-trip as `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WalletErrorCode {
+    NotImplemented,
+    Internal,
+    InvalidOperation,
+    BroadcastUnavailable,
+    InvalidParameter,
+    MissingParameter,
+    BadRequest,
+    NetworkChain,
+    Unauthorized,
+    NotActive,
+    InsufficientFunds,
+    QuotaExceeded,
+    InvalidPublicKey,
+    ReviewActions,
+    DoubleSpend,
+    PermissionDenied,
+    NotFound,
+    InvalidData,
+    Unknown,
+    Io,
+    Json,
+    /// Any other code string
+    Other(String),
+}
+
+impl WalletErrorCode {
+    const NAMED: [(WalletErrorCode, &'static str); 21] = [
+        (Self::NotImplemented, "WERR_NOT_IMPLEMENTED"),
+        (Self::Internal, "WERR_INTERNAL"),
+        (Self::InvalidOperation, "WERR_INVALID_OPERATION"),
+        (Self::BroadcastUnavailable, "WERR_BROADCAST_UNAVAILABLE"),
+        (Self::InvalidParameter, "WERR_INVALID_PARAMETER"),
+        (Self::MissingParameter, "WERR_MISSING_PARAMETER"),
+        (Self::BadRequest, "WERR_BAD_REQUEST"),
+        (Self::NetworkChain, "WERR_NETWORK_CHAIN"),
+        (Self::Unauthorized, "WERR_UNAUTHORIZED"),
+        (Self::NotActive, "WERR_NOT_ACTIVE"),
+        (Self::InsufficientFunds, "WERR_INSUFFICIENT_FUNDS"),
+        (Self::QuotaExceeded, "WERR_QUOTA_EXCEEDED"),
+        (Self::InvalidPublicKey, "WERR_INVALID_PUBLIC_KEY"),
+        (Self::ReviewActions, "WERR_REVIEW_ACTIONS"),
+        (Self::DoubleSpend, "WERR_DOUBLE_SPEND"),
+        (Self::PermissionDenied, "WERR_PERMISSION_DENIED"),
+        (Self::NotFound, "WERR_NOT_FOUND"),
+        (Self::InvalidData, "WERR_INVALID_DATA"),
+        (Self::Unknown, "WERR_UNKNOWN"),
+        (Self::Io, "WERR_IO"),
+        (Self::Json, "WERR_JSON"),
+    ];
+
+    /// The code string, e.g. `"WERR_INVALID_PARAMETER"`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Other(code) => code,
+            code => Self::NAMED.iter()
+                .find(|(named, _)| named == code)
+                .map(|(_, s)| *s)
+                .expect("every named code has a string"),
+        }
+    }
+}
+
+impl From<&str> for WalletErrorCode {
+    fn from(code: &str) -> Self {
+        Self::NAMED.iter()
+            .find(|(_, s)| *s == code)
+            .map(|(named, _)| named.clone())
+            .unwrap_or_else(|| Self::Other(code.to_string()))
+    }
+}
+
+impl From<String> for WalletErrorCode {
+    fn from(code: String) -> Self {
+        Self::from(code.as_str())
+    }
+}
+
+impl PartialEq<str> for WalletErrorCode {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for WalletErrorCode {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Display for WalletErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for WalletErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for WalletErrorCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(String::deserialize(deserializer)?))
+    }
+}
+
 /// Base error type for all wallet operations.
 /// 
 /// Derived from TypeScript WalletError class which extends Error.
 /// Provides code, description, details, and stack trace capabilities.
+///
+/// Serializes in the TS JSON error shape: `name`/`message` as written by
+/// `WalletError.unknownToJson`, the `code`/`description` aliases, `isError`,
+/// and any details as top-level properties.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "WalletErrorJson", from = "WalletErrorJson")]
 pub struct WalletError {
     /// Error code (10-40 bytes, matches ErrorCodeString10To40Bytes)
-    pub code: String,
+    pub code: WalletErrorCode,
     
     /// Error description (20-200 bytes, matches ErrorDescriptionString20To200Bytes)
     pub description: String,
     
     /// Optional additional details
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<HashMap<String, String>>,
     
     /// Optional stack trace
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub stack: Option<String>,
 }
 
+/// TS JSON shape of a [`WalletError`]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WalletErrorJson {
+    #[serde(default = "default_is_error")]
+    is_error: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<WalletErrorCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<WalletErrorCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stack: Option<String>,
+    #[serde(flatten)]
+    details: HashMap<String, serde_json::Value>,
+}
+
+fn default_is_error() -> bool {
+    true
+}
+
+impl From<WalletError> for WalletErrorJson {
+    fn from(err: WalletError) -> Self {
+        Self {
+            is_error: true,
+            name: Some(err.code.clone()),
+            message: Some(err.description.clone()),
+            code: Some(err.code),
+            description: Some(err.description),
+            stack: err.stack,
+            details: err.details.unwrap_or_default().into_iter()
+                .map(|(k, v)| (k, serde_json::Value::String(v)))
+                .collect(),
+        }
+    }
+}
+
+impl From<WalletErrorJson> for WalletError {
+    fn from(json: WalletErrorJson) -> Self {
+        let details: HashMap<String, String> = json.details.into_iter()
+            .map(|(k, v)| match v {
+                serde_json::Value::String(s) => (k, s),
+                v => (k, v.to_string()),
+            })
+            .collect();
+        Self {
+            code: json.name.or(json.code).unwrap_or(WalletErrorCode::Unknown),
+            description: json.message.or(json.description).unwrap_or_default(),
+            details: (!details.is_empty()).then_some(details),
+            stack: json.stack,
+        }
+    }
+}
+
 impl WalletError {
     /// Create a new WalletError with code and description
-    pub fn new(code: impl Into<String>, description: impl Into<String>) -> Self {
+    pub fn new(code: impl Into<WalletErrorCode>, description: impl Into<String>) -> Self {
         Self {
             code: code.into(),
             description: description.into(),
@@ -41,7 +215,7 @@ impl WalletError {
 
     /// Create a new WalletError with all fields
     pub fn with_details(
-        code: impl Into<String>,
+        code: impl Into<WalletErrorCode>,
         description: impl Into<String>,
         details: Option<HashMap<String, String>>,
         stack: Option<String>,
@@ -69,20 +243,12 @@ impl WalletError {
     /// 
     /// Matches TypeScript WalletError.fromUnknown() static method
     pub fn from_unknown(err: &dyn std::error::Error) -> Self {
-        let code = "WERR_UNKNOWN".to_string();
-        let description = err.to_string();
-        
-        Self {
-            code,
-            description,
-            details: None,
-            stack: None,
-        }
+        Self::new(WalletErrorCode::Unknown, err.to_string())
     }
 
     /// Create error from dynamic error type
     pub fn from_dyn(err: Box<dyn std::error::Error>) -> Self {
-        Self::new("WERR_UNKNOWN", err.to_string())
+        Self::new(WalletErrorCode::Unknown, err.to_string())
     }
     
     /// Create an invalid parameter error
@@ -105,6 +271,11 @@ impl WalletError {
         WErrInternal::new(Some(message.into()))
     }
     
+    /// Create a permission denied error
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        WErrPermissionDenied::new(Some(message.into()))
+    }
+    
     /// Create a missing parameter error
     pub fn missing_parameter(parameter: impl Into<String>) -> Self {
         WErrMissingParameter::new(parameter)
@@ -121,13 +292,33 @@ impl std::error::Error for WalletError {}
 
 impl From<std::io::Error> for WalletError {
     fn from(err: std::io::Error) -> Self {
-        Self::new("WERR_IO", err.to_string())
+        Self::new(WalletErrorCode::Io, err.to_string())
     }
 }
 
 impl From<serde_json::Error> for WalletError {
     fn from(err: serde_json::Error) -> Self {
-        Self::new("WERR_JSON", err.to_string())
+        Self::new(WalletErrorCode::Json, err.to_string())
+    }
+}
+
+impl From<wallet_storage::StorageError> for WalletError {
+    fn from(err: wallet_storage::StorageError) -> Self {
+        use wallet_storage::StorageError;
+        match err {
+            StorageError::NotImplemented(what) => WalletError::not_implemented(what),
+            StorageError::InvalidArg(message) => Self::new(WalletErrorCode::InvalidParameter, message),
+            StorageError::Unauthorized(message) => WErrUnauthorized::new(Some(message)),
+            StorageError::NotActive(message) => WErrNotActive::new(Some(message)),
+            StorageError::NetworkChain(message) => WErrNetworkChain::new(Some(message)),
+            StorageError::InsufficientFunds { total_satoshis_needed, more_satoshis_needed } => {
+                WErrInsufficientFunds::new(total_satoshis_needed.max(0) as u64, more_satoshis_needed.max(0) as u64)
+            }
+            StorageError::QuotaExceeded { resource, limit, requested, .. } => {
+                WErrQuotaExceeded::new(resource.as_str(), limit, requested)
+            }
+            err => WalletError::internal(err.to_string()),
+        }
     }
 }
 
@@ -147,7 +338,7 @@ pub struct WErrNotImplemented {
 impl WErrNotImplemented {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_NOT_IMPLEMENTED",
+            WalletErrorCode::NotImplemented,
             message.unwrap_or_else(|| "Not implemented.".to_string()),
         )
     }
@@ -162,7 +353,7 @@ pub struct WErrInternal {
 impl WErrInternal {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_INTERNAL",
+            WalletErrorCode::Internal,
             message.unwrap_or_else(|| "An internal error has occurred.".to_string()),
         )
     }
@@ -177,7 +368,7 @@ pub struct WErrInvalidOperation {
 impl WErrInvalidOperation {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_INVALID_OPERATION",
+            WalletErrorCode::InvalidOperation,
             message.unwrap_or_else(|| "An invalid operation was requested.".to_string()),
         )
     }
@@ -190,7 +381,7 @@ pub struct WErrBroadcastUnavailable;
 impl WErrBroadcastUnavailable {
     pub fn new(_message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_BROADCAST_UNAVAILABLE",
+            WalletErrorCode::BroadcastUnavailable,
             "Unable to broadcast transaction at this time.",
         )
     }
@@ -207,9 +398,11 @@ impl WErrInvalidParameter {
     pub fn new(parameter: impl Into<String>, must_be: Option<String>) -> WalletError {
         let param = parameter.into();
         let requirement = must_be.unwrap_or_else(|| "valid.".to_string());
-        WalletError::new(
-            "WERR_INVALID_PARAMETER",
+        WalletError::with_details(
+            WalletErrorCode::InvalidParameter,
             format!("The {} parameter must be {}", param, requirement),
+            Some(HashMap::from([("parameter".to_string(), param)])),
+            None,
         )
     }
 }
@@ -224,12 +417,29 @@ impl WErrMissingParameter {
     pub fn new(parameter: impl Into<String>) -> WalletError {
         let param = parameter.into();
         WalletError::new(
-            "WERR_MISSING_PARAMETER",
+            WalletErrorCode::MissingParameter,
             format!("The required {} parameter is missing.", param),
         )
     }
 }
 
+/// Permission denied error - the user denied, or was never asked for, a permission
+///
+/// Reference: TS WalletPermissionsManager denial (`code = 'ERR_PERMISSION_DENIED'`)
+#[derive(Debug, Clone)]
+pub struct WErrPermissionDenied {
+    pub message: Option<String>,
+}
+
+impl WErrPermissionDenied {
+    pub fn new(message: Option<String>) -> WalletError {
+        WalletError::new(
+            WalletErrorCode::PermissionDenied,
+            message.unwrap_or_else(|| "Permission denied.".to_string()),
+        )
+    }
+}
+
 /// Bad request error
 #[derive(Debug, Clone)]
 pub struct WErrBadRequest {
@@ -239,7 +449,7 @@ pub struct WErrBadRequest {
 impl WErrBadRequest {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_BAD_REQUEST",
+            WalletErrorCode::BadRequest,
             message.unwrap_or_else(|| "The request is invalid.".to_string()),
         )
     }
@@ -254,7 +464,7 @@ pub struct WErrNetworkChain {
 impl WErrNetworkChain {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_NETWORK_CHAIN",
+            WalletErrorCode::NetworkChain,
             message.unwrap_or_else(|| {
                 "Configured network chain is invalid or does not match across services.".to_string()
             }),
@@ -271,7 +481,7 @@ pub struct WErrUnauthorized {
 impl WErrUnauthorized {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_UNAUTHORIZED",
+            WalletErrorCode::Unauthorized,
             message.unwrap_or_else(|| "Access is denied due to an authorization error.".to_string()),
         )
     }
@@ -286,7 +496,7 @@ pub struct WErrNotActive {
 impl WErrNotActive {
     pub fn new(message: Option<String>) -> WalletError {
         WalletError::new(
-            "WERR_NOT_ACTIVE",
+            WalletErrorCode::NotActive,
             message.unwrap_or_else(|| {
                 "WalletStorageManager is not accessing user's active storage or there are conflicting active stores configured.".to_string()
             }),
@@ -303,12 +513,92 @@ pub struct WErrInsufficientFunds {
 
 impl WErrInsufficientFunds {
     pub fn new(total_satoshis_needed: u64, more_satoshis_needed: u64) -> WalletError {
-        WalletError::new(
-            "WERR_INSUFFICIENT_FUNDS",
+        let details = HashMap::from([
+            ("totalSatoshisNeeded".to_string(), total_satoshis_needed.to_string()),
+            ("moreSatoshisNeeded".to_string(), more_satoshis_needed.to_string()),
+        ]);
+        WalletError::with_details(
+            WalletErrorCode::InsufficientFunds,
             format!(
                 "Insufficient funds in the available inputs to cover the cost of the required outputs and the transaction fee ({} more satoshis are needed, for a total of {}), plus whatever would be required in order to pay the fee to unlock and spend the outputs used to provide the additional satoshis.",
                 more_satoshis_needed, total_satoshis_needed
             ),
+            Some(details),
+            None,
+        )
+    }
+    
+    /// The needed satoshi amounts of a `WERR_INSUFFICIENT_FUNDS` error, if `err` is one
+    ///
+    /// Returns `(total_satoshis_needed, more_satoshis_needed)`.
+    pub fn from_error(err: &WalletError) -> Option<(u64, u64)> {
+        if err.code != WalletErrorCode::InsufficientFunds {
+            return None;
+        }
+        let details = err.details.as_ref()?;
+        let total = details.get("totalSatoshisNeeded")?.parse().ok()?;
+        let more = details.get("moreSatoshisNeeded")?.parse().ok()?;
+        Some((total, more))
+    }
+}
+
+/// Quota exceeded error - a write would take the user past a storage quota
+///
+/// Not part of the TypeScript toolbox; storage servers return it for users
+/// who have used up their `QuotaPolicy` allowance of `resource`.
+#[derive(Debug, Clone)]
+pub struct WErrQuotaExceeded;
+
+impl WErrQuotaExceeded {
+    pub fn new(resource: &str, limit: i64, requested: i64) -> WalletError {
+        let details = HashMap::from([
+            ("resource".to_string(), resource.to_string()),
+            ("limit".to_string(), limit.to_string()),
+            ("requested".to_string(), requested.to_string()),
+        ]);
+        WalletError::with_details(
+            WalletErrorCode::QuotaExceeded,
+            format!("Storage quota exceeded: {} would be {}, the limit is {}.", resource, requested, limit),
+            Some(details),
+            None,
+        )
+    }
+}
+
+/// Double spend error - a broadcast transaction spends an input another transaction already spent
+///
+/// Carried by `ReviewActionResult.doubleSpend` after the failed transaction
+/// has been reconciled, and converted to a `WERR_DOUBLE_SPEND` WalletError
+/// when createAction or signAction report it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoubleSpendError {
+    /// The transaction that failed
+    pub txid: String,
+
+    /// Transactions the network reports as competing for its inputs
+    pub competing_txs: Vec<String>,
+
+    /// Inputs spent elsewhere (or not confirmed unspent), left unspendable
+    pub spent_inputs: Vec<String>,
+
+    /// Inputs found unspent and released for reuse
+    pub released_inputs: Vec<String>,
+}
+
+impl From<DoubleSpendError> for WalletError {
+    fn from(err: DoubleSpendError) -> Self {
+        let details = HashMap::from([
+            ("txid".to_string(), err.txid.clone()),
+            ("competingTxs".to_string(), err.competing_txs.join(",")),
+            ("spentInputs".to_string(), err.spent_inputs.join(",")),
+            ("releasedInputs".to_string(), err.released_inputs.join(",")),
+        ]);
+        WalletError::with_details(
+            WalletErrorCode::DoubleSpend,
+            format!("Transaction {} was rejected as a double spend.", err.txid),
+            Some(details),
+            None,
         )
     }
 }
@@ -339,7 +629,7 @@ impl WErrInvalidPublicKey {
                 "The provided public key is invalid or malformed.".to_string()
             }
         };
-        WalletError::new("WERR_INVALID_PUBLIC_KEY", message)
+        WalletError::new(WalletErrorCode::InvalidPublicKey, message)
     }
 }
 
@@ -376,7 +666,7 @@ impl WErrReviewActions {
         // Note: In full implementation, these parameters would be stored in the error
         // For now, we just create the basic error message matching TypeScript behavior
         WalletError::new(
-            "WERR_REVIEW_ACTIONS",
+            WalletErrorCode::ReviewActions,
             "Undelayed createAction or signAction results require review.",
         )
     }
@@ -519,6 +809,43 @@ mod tests {
         assert_eq!(err.code, "WERR_INSUFFICIENT_FUNDS");
         assert!(err.description.contains("5000 more satoshis"));
         assert!(err.description.contains("total of 10000"));
+        assert_eq!(WErrInsufficientFunds::from_error(&err), Some((10000, 5000)));
+        assert_eq!(serde_json::to_value(&err).unwrap()["moreSatoshisNeeded"], "5000");
+        
+        let err: WalletError = wallet_storage::StorageError::InsufficientFunds {
+            total_satoshis_needed: 1200,
+            more_satoshis_needed: 200,
+        }.into();
+        assert_eq!(WErrInsufficientFunds::from_error(&err), Some((1200, 200)));
+        assert_eq!(WErrInsufficientFunds::from_error(&WErrInternal::new(None)), None);
+    }
+
+    #[test]
+    fn test_werr_quota_exceeded() {
+        let err: WalletError = wallet_storage::StorageError::QuotaExceeded {
+            user_id: 1,
+            resource: wallet_storage::QuotaResource::Outputs,
+            limit: 10,
+            requested: 11,
+        }.into();
+        assert_eq!(err.code, "WERR_QUOTA_EXCEEDED");
+        assert_eq!(err.code, WalletErrorCode::from("WERR_QUOTA_EXCEEDED"));
+        assert!(err.description.contains("outputs would be 11"));
+        assert_eq!(serde_json::to_value(&err).unwrap()["limit"], "10");
+    }
+
+    #[test]
+    fn test_double_spend_error() {
+        let err: WalletError = DoubleSpendError {
+            txid: "aa".to_string(),
+            competing_txs: vec!["bb".to_string(), "cc".to_string()],
+            spent_inputs: vec!["dd.0".to_string()],
+            released_inputs: vec![],
+        }.into();
+        assert_eq!(err.code, "WERR_DOUBLE_SPEND");
+        let details = err.details.unwrap();
+        assert_eq!(details["competingTxs"], "bb,cc");
+        assert_eq!(details["spentInputs"], "dd.0");
     }
 
     #[test]
@@ -547,6 +874,30 @@ mod tests {
         assert_eq!(deserialized.description, err.description);
     }
 
+    #[test]
+    fn test_wallet_error_ts_json_shape() {
+        let err = WErrInvalidParameter::new("basket", Some("a valid basket".to_string()));
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["isError"], true);
+        assert_eq!(json["name"], "WERR_INVALID_PARAMETER");
+        assert_eq!(json["code"], "WERR_INVALID_PARAMETER");
+        assert_eq!(json["message"], json["description"]);
+        assert_eq!(json["parameter"], "basket");
+        
+        let parsed: WalletError = serde_json::from_value(serde_json::json!({
+            "name": "WERR_INSUFFICIENT_FUNDS",
+            "message": "Insufficient funds",
+            "isError": true,
+            "moreSatoshisNeeded": 500,
+        })).unwrap();
+        assert_eq!(parsed.code, WalletErrorCode::InsufficientFunds);
+        assert_eq!(parsed.details.unwrap()["moreSatoshisNeeded"], "500");
+        
+        let custom = WalletError::new("ERR_CUSTOM", "Custom failure");
+        assert_eq!(custom.code, WalletErrorCode::Other("ERR_CUSTOM".to_string()));
+        assert_eq!(serde_json::to_value(&custom).unwrap()["code"], "ERR_CUSTOM");
+    }
+
     #[test]
     fn test_wallet_network_serialization() {
         let mainnet = WalletNetwork::Mainnet;
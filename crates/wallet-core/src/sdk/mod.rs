@@ -4,6 +4,8 @@ pub mod action;
 pub mod action_list;
 pub mod action_process;
 pub mod errors;
+pub mod originator;
+pub mod privileged_key_manager;
 pub mod types;
 pub mod validation;
 pub mod validation_args;
@@ -13,16 +15,15 @@ pub mod wallet_interface;
 #[path = "types_tests.rs"]
 mod types_tests;
 
-#[derive(Debug, Default)]
-pub struct PrivilegedKeyManager;
-
 pub mod index {}
 
 // Re-export commonly used items
 pub use action::*;
 pub use action_list::*;
 pub use action_process::*;
-pub use errors::{WalletError, WalletResult, WalletNetwork};
+pub use errors::{DoubleSpendError, WalletError, WalletErrorCode, WalletResult, WalletNetwork};
+pub use originator::Originator;
+pub use privileged_key_manager::{PrivilegedKeyManager, PrivilegedKeyGetter, DEFAULT_RETENTION_PERIOD};
 pub use types::{
     Chain, OutPoint, ProvenTxReqStatus, TransactionStatus, Paged, ReqHistoryNote,
     StorageProvidedBy, SyncStatus,
@@ -0,0 +1,299 @@
+//! BRC-100 originators
+//!
+//! An originator is the domain name of the application making a wallet call,
+//! optionally with a port (`localhost:3000`). Permissions are granted to it
+//! and the admin originator is refused to applications, so every layer must
+//! agree on one spelling: `Originator` holds the validated, normalized form.
+//!
+//! Normalization trims whitespace, drops a trailing root dot, lower-cases and
+//! converts internationalized names to punycode (`Bücher.example` is
+//! `xn--bcher-kva.example`). Each label is 1-63 letters, digits or hyphens,
+//! not starting or ending with a hyphen, and the whole originator is under
+//! 250 bytes. IP addresses are accepted as hosts.
+//!
+//! Reference: BRC-100 OriginatorDomainNameStringUnder250Bytes
+
+use crate::sdk::errors::{WalletError, WalletResult};
+use std::fmt;
+use std::ops::Deref;
+use std::net::{IpAddr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Originators are shorter than this many bytes
+pub const MAX_ORIGINATOR_BYTES: usize = 250;
+
+/// Labels are at most this many bytes
+const MAX_LABEL_BYTES: usize = 63;
+
+/// A validated, normalized originator
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Originator {
+    /// `host` or `host:port`
+    value: String,
+    /// Length of the host within `value`
+    host_len: usize,
+}
+
+impl Originator {
+    /// Validate and normalize `originator`
+    pub fn parse(originator: &str) -> WalletResult<Self> {
+        let trimmed = originator.trim();
+        let (host, port) = split_port(trimmed)?;
+        let host = normalize_host(host)?;
+        let host_len = host.len();
+        let value = match port {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        };
+        if value.len() >= MAX_ORIGINATOR_BYTES {
+            return Err(WalletError::invalid_parameter(
+                "originator",
+                format!("under {} bytes", MAX_ORIGINATOR_BYTES),
+            ));
+        }
+        Ok(Self { value, host_len })
+    }
+
+    /// Normalized form of a trusted originator, such as a manager's admin
+    ///
+    /// An invalid one is returned as is; no parsed originator equals it.
+    pub fn normalize(originator: String) -> String {
+        Self::parse(&originator).map(String::from).unwrap_or(originator)
+    }
+
+    /// `host` or `host:port`
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Domain name or IP address
+    pub fn host(&self) -> &str {
+        &self.value[..self.host_len]
+    }
+
+    /// Port, when one was given
+    pub fn port(&self) -> Option<u16> {
+        self.value[self.host_len..].strip_prefix(':').and_then(|p| p.parse().ok())
+    }
+
+    /// Whether this is `domain` or one of its subdomains, on the same port
+    ///
+    /// Matching is by whole labels: `app.example.com` is under `example.com`
+    /// but `badexample.com` is not. IP addresses only match themselves.
+    pub fn is_same_or_subdomain_of(&self, domain: &Originator) -> bool {
+        if self.port() != domain.port() {
+            return false;
+        }
+        let (host, parent) = (self.host(), domain.host());
+        if host == parent {
+            return true;
+        }
+        if is_ip_host(host) || is_ip_host(parent) {
+            return false;
+        }
+        host.strip_suffix(parent).is_some_and(|sub| sub.ends_with('.'))
+    }
+
+    /// Domains this is a subdomain of, on the same port, nearest first
+    ///
+    /// Only parents of at least two labels are listed: `a.app.example.com`
+    /// has `app.example.com` and `example.com`. IP addresses have none.
+    pub fn parent_domains(&self) -> Vec<Originator> {
+        let host = self.host();
+        if is_ip_host(host) {
+            return Vec::new();
+        }
+        let port = &self.value[self.host_len..];
+        host.match_indices('.')
+            .map(|(i, _)| &host[i + 1..])
+            .filter(|parent| parent.contains('.'))
+            .map(|parent| Originator { value: format!("{}{}", parent, port), host_len: parent.len() })
+            .collect()
+    }
+}
+
+impl fmt::Display for Originator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl FromStr for Originator {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Deref for Originator {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl AsRef<str> for Originator {
+    fn as_ref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl From<Originator> for String {
+    fn from(originator: Originator) -> Self {
+        originator.value
+    }
+}
+
+/// Host and port of `host`, `host:port` or `[ipv6]:port`
+fn split_port(originator: &str) -> WalletResult<(&str, Option<u16>)> {
+    let invalid = || WalletError::invalid_parameter("originator", "a domain name with an optional port");
+    let (host, port) = match originator.strip_prefix('[') {
+        Some(bracketed) => {
+            let (ip, rest) = bracketed.split_once(']').ok_or_else(invalid)?;
+            ip.parse::<Ipv6Addr>().map_err(|_| invalid())?;
+            match rest {
+                "" => (ip, None),
+                _ => (ip, Some(rest.strip_prefix(':').ok_or_else(invalid)?)),
+            }
+        }
+        None => match originator.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (originator, None),
+        },
+    };
+    let port = match port {
+        Some(port) if port.bytes().all(|b| b.is_ascii_digit()) => Some(port.parse().map_err(|_| invalid())?),
+        Some(_) => return Err(invalid()),
+        None => None,
+    };
+    Ok((host, port))
+}
+
+/// Lower-cased, punycode host, with IPv6 addresses bracketed
+fn normalize_host(host: &str) -> WalletResult<String> {
+    if let Ok(ip) = host.parse::<Ipv6Addr>() {
+        return Ok(format!("[{}]", ip));
+    }
+    let invalid = |must_be: &str| WalletError::invalid_parameter("originator", must_be);
+    let host = host.strip_suffix('.').unwrap_or(host);
+    if host.is_empty() {
+        return Err(invalid("a domain name"));
+    }
+    let host = idna::domain_to_ascii(host).map_err(|_| invalid("a valid internationalized domain name"))?;
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > MAX_LABEL_BYTES {
+            return Err(invalid("labels of 1 to 63 bytes"));
+        }
+        if !label.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-') {
+            return Err(invalid("labels of letters, digits and hyphens"));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(invalid("labels not starting or ending with a hyphen"));
+        }
+    }
+    Ok(host)
+}
+
+/// Whether a normalized host is an IP address
+fn is_ip_host(host: &str) -> bool {
+    host.starts_with('[') || host.parse::<IpAddr>().is_ok()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn originator(s: &str) -> Originator {
+        Originator::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_normalizes() {
+        assert_eq!(originator("  App.Example.COM. ").as_str(), "app.example.com");
+        assert_eq!(originator("Bücher.example").as_str(), "xn--bcher-kva.example");
+        assert_eq!(originator("xn--bcher-kva.example"), originator("bücher.example"));
+        assert_eq!(originator("localhost:03000").as_str(), "localhost:3000");
+        assert_eq!(originator("[::1]:8080").as_str(), "[::1]:8080");
+
+        let o = originator("app.example.com:8443");
+        assert_eq!((o.host(), o.port()), ("app.example.com", Some(8443)));
+        assert_eq!(originator("127.0.0.1").port(), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        for bad in [
+            "", " ", ".", "a..b", "-app.example.com", "app-.example.com", "app_1.example.com",
+            "app example.com", "https://app.example.com", "example.com:", "example.com:70000",
+            "example.com:80x", "[::1", "[::1]8080", "::1", "[localhost]",
+        ] {
+            assert!(Originator::parse(bad).is_err(), "{:?}", bad);
+        }
+        assert!(Originator::parse(&format!("{}.com", "a".repeat(64))).is_err());
+
+        // 249 bytes is the longest originator
+        let label = "a".repeat(62);
+        let longest = format!("{0}.{0}.{0}.", label) + &"b".repeat(249 - 3 * 63);
+        assert_eq!(originator(&longest).as_str().len(), 249);
+        assert!(Originator::parse(&format!("{}b", longest)).is_err());
+    }
+
+    #[test]
+    fn test_is_same_or_subdomain_of() {
+        let domain = originator("example.com");
+        assert!(originator("example.com").is_same_or_subdomain_of(&domain));
+        assert!(originator("app.Example.com").is_same_or_subdomain_of(&domain));
+        assert!(originator("a.b.example.com").is_same_or_subdomain_of(&domain));
+        assert!(!originator("badexample.com").is_same_or_subdomain_of(&domain));
+        assert!(!originator("example.com.evil.io").is_same_or_subdomain_of(&domain));
+        assert!(!originator("app.example.com:8080").is_same_or_subdomain_of(&domain));
+        assert!(!domain.is_same_or_subdomain_of(&originator("app.example.com")));
+        assert!(!originator("10.0.0.1").is_same_or_subdomain_of(&originator("0.1")));
+    }
+
+    #[test]
+    fn test_subdomain_ports_and_schemes() {
+        let domain = originator("example.com:8080");
+        assert!(originator("app.example.com:8080").is_same_or_subdomain_of(&domain));
+        assert!(!originator("app.example.com:8081").is_same_or_subdomain_of(&domain));
+        assert!(!originator("app.example.com").is_same_or_subdomain_of(&domain));
+        assert!(originator("APP.example.com.:08080").is_same_or_subdomain_of(&domain));
+
+        // Schemes are not part of an originator, so cannot match by prefix
+        assert!(Originator::parse("https://app.example.com").is_err());
+        assert!(Originator::parse("app.example.com/https").is_err());
+
+        // Only whole trailing labels match
+        assert!(!originator("ample.com").is_same_or_subdomain_of(&originator("example.com")));
+        assert!(!originator("example.co").is_same_or_subdomain_of(&originator("example.com")));
+        assert!(originator("bücher.example").is_same_or_subdomain_of(&originator("example")));
+        assert!(!originator("[::1]:80").is_same_or_subdomain_of(&originator("1:80")));
+    }
+
+    #[test]
+    fn test_parent_domains() {
+        let parents = originator("a.app.example.com:3000").parent_domains();
+        let parents: Vec<&str> = parents.iter().map(|p| p.as_str()).collect();
+        assert_eq!(parents, vec!["app.example.com:3000", "example.com:3000"]);
+        for parent in originator("a.app.example.com:3000").parent_domains() {
+            assert!(originator("a.app.example.com:3000").is_same_or_subdomain_of(&parent));
+            assert_eq!(Originator::parse(parent.as_str()).unwrap(), parent);
+        }
+        assert!(originator("example.com").parent_domains().is_empty());
+        assert!(originator("localhost:3000").parent_domains().is_empty());
+        assert!(originator("10.0.0.1").parent_domains().is_empty());
+        assert!(originator("[::1]").parent_domains().is_empty());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Originator::normalize("Admin.Example.com".to_string()), "admin.example.com");
+        assert_eq!(Originator::normalize("not valid".to_string()), "not valid");
+    }
+}
@@ -0,0 +1,311 @@
+//! Privileged Key Manager
+//!
+//! **Reference**: TypeScript `src/sdk/PrivilegedKeyManager.ts`
+//!
+//! Performs cryptographic operations with the wallet's privileged key, which
+//! is obtained on demand from a key getter (typically prompting the user)
+//! and only held in memory for a limited retention period.
+
+use crate::keys::RootKeyDeriver;
+use crate::methods::{encrypt_decrypt, hmac_operations, key_linkage, signature_operations};
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::{
+    CreateHmacArgs, CreateHmacResult, CreateSignatureArgs, CreateSignatureResult, GetPublicKeyArgs,
+    GetPublicKeyResult, RevealCounterpartyKeyLinkageArgs, RevealCounterpartyKeyLinkageResult,
+    RevealSpecificKeyLinkageArgs, RevealSpecificKeyLinkageResult, VerifyHmacArgs, VerifyHmacResult,
+    VerifySignatureArgs, VerifySignatureResult, WalletDecryptArgs, WalletDecryptResult,
+    WalletEncryptArgs, WalletEncryptResult,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// How long a privileged key is retained after it is obtained
+///
+/// Reference: TS PrivilegedKeyManager retentionPeriod default (120_000 ms)
+pub const DEFAULT_RETENTION_PERIOD: Duration = Duration::from_secs(120);
+
+/// Obtains the 32-byte privileged key, given the reason it is needed
+///
+/// Reference: TS PrivilegedKeyManager keyGetter
+pub type PrivilegedKeyGetter = Arc<
+    dyn Fn(String) -> Pin<Box<dyn Future<Output = WalletResult<Vec<u8>>> + Send>> + Send + Sync
+>;
+
+/// Privileged key held in memory until it expires, wiped when dropped
+struct RetainedKey {
+    key: Zeroizing<Vec<u8>>,
+    expires_at: Instant,
+}
+
+/// Privileged Key Manager
+///
+/// Reference: TS PrivilegedKeyManager class
+///
+/// The key is requested from the key getter the first time it is needed and
+/// kept for `retention_period`; after that it is wiped and must be provided
+/// again. Each operation mirrors the corresponding ProtoWallet method, run
+/// with the privileged key as root key, and passes the call's
+/// `privileged_reason` to the key getter.
+pub struct PrivilegedKeyManager {
+    key_getter: PrivilegedKeyGetter,
+    retention_period: Duration,
+    retained: Arc<Mutex<Option<RetainedKey>>>,
+}
+
+impl std::fmt::Debug for PrivilegedKeyManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivilegedKeyManager")
+            .field("retention_period", &self.retention_period)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PrivilegedKeyManager {
+    /// Create a manager that retains the key for [`DEFAULT_RETENTION_PERIOD`]
+    pub fn new(key_getter: PrivilegedKeyGetter) -> Self {
+        Self::with_retention_period(key_getter, DEFAULT_RETENTION_PERIOD)
+    }
+
+    /// Create a manager that retains the key for `retention_period`
+    ///
+    /// A zero retention period requests the key for every operation.
+    pub fn with_retention_period(key_getter: PrivilegedKeyGetter, retention_period: Duration) -> Self {
+        Self {
+            key_getter,
+            retention_period,
+            retained: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Retention period for obtained keys
+    pub fn retention_period(&self) -> Duration {
+        self.retention_period
+    }
+
+    /// Whether a key is currently held in memory
+    pub fn has_retained_key(&self) -> bool {
+        self.retained_key().is_some()
+    }
+
+    /// Wipe the retained key; the next operation requests it again
+    ///
+    /// Reference: TS destroyKey
+    pub fn destroy_key(&self) {
+        self.retained.lock().expect("retained key lock").take();
+    }
+
+    /// Obtain the privileged key, from memory if still retained
+    ///
+    /// The returned copy is wiped when dropped.
+    /// Reference: TS getPrivilegedKey
+    pub async fn get_privileged_key(&self, reason: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        if let Some(key) = self.retained_key() {
+            return Ok(key);
+        }
+
+        let key = Zeroizing::new((self.key_getter)(reason.to_string()).await?);
+        if key.len() != 32 {
+            return Err(WalletError::invalid_parameter("privilegedKey", "exactly 32 bytes"));
+        }
+        if !self.retention_period.is_zero() {
+            self.retain(key.clone());
+        }
+        Ok(key)
+    }
+
+    /// Reference: TS getPublicKey
+    pub async fn get_public_key(&self, args: &GetPublicKeyArgs) -> WalletResult<GetPublicKeyResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        key_linkage::get_public_key(args, &deriver).await
+    }
+
+    /// Reference: TS revealCounterpartyKeyLinkage
+    pub async fn reveal_counterparty_key_linkage(
+        &self,
+        args: &RevealCounterpartyKeyLinkageArgs,
+    ) -> WalletResult<RevealCounterpartyKeyLinkageResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        key_linkage::reveal_counterparty_key_linkage(args, &deriver).await
+    }
+
+    /// Reference: TS revealSpecificKeyLinkage
+    pub async fn reveal_specific_key_linkage(
+        &self,
+        args: &RevealSpecificKeyLinkageArgs,
+    ) -> WalletResult<RevealSpecificKeyLinkageResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        key_linkage::reveal_specific_key_linkage(args, &deriver).await
+    }
+
+    /// Reference: TS encrypt
+    pub async fn encrypt(&self, args: &WalletEncryptArgs) -> WalletResult<WalletEncryptResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        encrypt_decrypt::encrypt(args, &deriver).await
+    }
+
+    /// Reference: TS decrypt
+    pub async fn decrypt(&self, args: &WalletDecryptArgs) -> WalletResult<WalletDecryptResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        encrypt_decrypt::decrypt(args, &deriver).await
+    }
+
+    /// Reference: TS createHmac
+    pub async fn create_hmac(&self, args: &CreateHmacArgs) -> WalletResult<CreateHmacResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        hmac_operations::create_hmac(args, &deriver).await
+    }
+
+    /// Reference: TS verifyHmac
+    pub async fn verify_hmac(&self, args: &VerifyHmacArgs) -> WalletResult<VerifyHmacResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        hmac_operations::verify_hmac(args, &deriver).await
+    }
+
+    /// Reference: TS createSignature
+    pub async fn create_signature(&self, args: &CreateSignatureArgs) -> WalletResult<CreateSignatureResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        signature_operations::create_signature(args, &deriver).await
+    }
+
+    /// Reference: TS verifySignature
+    pub async fn verify_signature(&self, args: &VerifySignatureArgs) -> WalletResult<VerifySignatureResult> {
+        let deriver = self.key_deriver(args.privileged_reason.as_deref()).await?;
+        signature_operations::verify_signature(args, &deriver).await
+    }
+
+    /// Key deriver over the privileged key
+    async fn key_deriver(&self, reason: Option<&str>) -> WalletResult<RootKeyDeriver> {
+        let key = self.get_privileged_key(reason.unwrap_or_default()).await?;
+        RootKeyDeriver::new(&key)
+    }
+
+    /// The retained key, wiping it first if it has expired
+    fn retained_key(&self) -> Option<Zeroizing<Vec<u8>>> {
+        let mut retained = self.retained.lock().expect("retained key lock");
+        match retained.as_ref() {
+            Some(held) if held.expires_at > Instant::now() => Some(held.key.clone()),
+            Some(_) => {
+                retained.take();
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Hold the key and schedule its removal at expiry
+    fn retain(&self, key: Zeroizing<Vec<u8>>) {
+        let expires_at = Instant::now() + self.retention_period;
+        *self.retained.lock().expect("retained key lock") = Some(RetainedKey { key, expires_at });
+
+        // Without a runtime the key is still wiped lazily on next access
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let retained = Arc::downgrade(&self.retained);
+            let retention_period = self.retention_period;
+            runtime.spawn(async move {
+                tokio::time::sleep(retention_period).await;
+                if let Some(retained) = retained.upgrade() {
+                    let mut retained = retained.lock().expect("retained key lock");
+                    if retained.as_ref().is_some_and(|held| held.expires_at <= Instant::now()) {
+                        retained.take();
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::managers::simple_wallet_manager::PrivilegedKeyManager for PrivilegedKeyManager {
+    async fn get_privileged_key(&self, reason: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        PrivilegedKeyManager::get_privileged_key(self, reason).await
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Manager over a fixed key that counts how often the key was requested
+    fn counting_manager(retention_period: Duration) -> (PrivilegedKeyManager, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let getter: PrivilegedKeyGetter = Arc::new(move |_reason| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(vec![5u8; 32]) })
+        });
+        (PrivilegedKeyManager::with_retention_period(getter, retention_period), requests)
+    }
+
+    #[tokio::test]
+    async fn test_key_retained_until_expiry() {
+        let (manager, requests) = counting_manager(Duration::from_millis(50));
+
+        manager.get_privileged_key("first").await.unwrap();
+        manager.get_privileged_key("second").await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(manager.has_retained_key());
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(!manager.has_retained_key());
+        manager.get_privileged_key("third").await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_destroy_key_and_zero_retention() {
+        let (manager, requests) = counting_manager(DEFAULT_RETENTION_PERIOD);
+        manager.get_privileged_key("first").await.unwrap();
+        manager.destroy_key();
+        manager.get_privileged_key("second").await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        let (manager, requests) = counting_manager(Duration::ZERO);
+        manager.get_privileged_key("first").await.unwrap();
+        manager.get_privileged_key("second").await.unwrap();
+        assert!(!manager.has_retained_key());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_key_length() {
+        let getter: PrivilegedKeyGetter = Arc::new(|_reason| Box::pin(async { Ok(vec![1u8; 31]) }));
+        let manager = PrivilegedKeyManager::new(getter);
+        assert!(manager.get_privileged_key("reason").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_operations_use_privileged_key() {
+        let (manager, _) = counting_manager(DEFAULT_RETENTION_PERIOD);
+        let deriver = RootKeyDeriver::new(&[5u8; 32]).unwrap();
+
+        let identity = manager.get_public_key(&serde_json::from_value(serde_json::json!({
+            "identityKey": true,
+            "privilegedReason": "identity",
+        })).unwrap()).await.unwrap();
+        assert_eq!(identity.public_key, deriver.identity_key_hex());
+
+        let signature = manager.create_signature(&serde_json::from_value(serde_json::json!({
+            "protocolID": [2, "privileged test"],
+            "keyID": "1",
+            "data": [1, 2, 3],
+            "counterparty": "self",
+        })).unwrap()).await.unwrap();
+        let verified = signature_operations::verify_signature(&serde_json::from_value(serde_json::json!({
+            "protocolID": [2, "privileged test"],
+            "keyID": "1",
+            "data": [1, 2, 3],
+            "signature": signature.signature,
+            "counterparty": "self",
+            "forSelf": true,
+        })).unwrap(), &deriver).await.unwrap();
+        assert!(verified.valid);
+    }
+}
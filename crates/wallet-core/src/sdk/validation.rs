@@ -4,6 +4,7 @@
 //! Reference: wallet-toolbox/src/sdk/validationHelpers.ts
 
 use crate::sdk::errors::*;
+use crate::sdk::originator::Originator;
 use crate::sdk::types::OutPoint;
 
 /// Parse wallet outpoint string format "txid.vout"
@@ -345,7 +346,7 @@ pub fn validate_outpoint_string(outpoint: &str, name: &str) -> Result<String, Wa
         ));
     }
     
-    let txid = validate_hex_string(parts[0], &format!("{} txid", name), None, Some(64))?;
+    let txid = validate_hex_string(parts[0], &format!("{} txid", name), Some(64), Some(64))?;
     let vout = parts[1].parse::<u32>().map_err(|_| {
         WErrInvalidParameter::new(&format!("{} vout", name), Some("a valid number".to_string()))
     })?;
@@ -366,6 +367,37 @@ pub fn validate_optional_outpoint_string(
     }
 }
 
+/// Host (and non-default port) of a URL, as an originator
+///
+/// Used as the originator for calls from a window or HTTP `Origin`.
+///
+/// Reference: TS originator handling in metanet-desktop onWalletReady
+pub fn originator_from_url(url: &str) -> Result<Originator, WalletError> {
+    let invalid = || WalletError::invalid_parameter("originator", "the URL of an invoking window with a host");
+    let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default().to_ascii_lowercase();
+    let host_port = match (scheme, host_port.rsplit_once(':')) {
+        ("http", Some((host, "80"))) | ("https", Some((host, "443"))) => host.to_string(),
+        _ => host_port,
+    };
+    if host_port.is_empty() {
+        return Err(invalid());
+    }
+    Originator::parse(&host_port)
+}
+
+/// BRC-100 method args from a frontend must be a JSON object; `null` is `{}`
+///
+/// Used by the Tauri command handlers.
+pub fn validate_command_args(args: serde_json::Value) -> Result<serde_json::Value, WalletError> {
+    match args {
+        serde_json::Value::Null => Ok(serde_json::Value::Object(serde_json::Map::new())),
+        serde_json::Value::Object(_) => Ok(args),
+        _ => Err(WalletError::invalid_parameter("args", "a JSON object")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,5 +490,31 @@ mod tests {
             "outpoint"
         ).unwrap();
         assert!(result.contains(".42"));
+        
+        assert!(validate_outpoint_string("abc123.0", "outpoint").is_err());
+        assert!(validate_outpoint_string(&format!("{}.x", "ab".repeat(32)), "outpoint").is_err());
+    }
+
+    #[test]
+    fn test_originator_from_url() {
+        let originator = |url| originator_from_url(url).unwrap().to_string();
+        assert_eq!(originator("https://App.Example.com/path?q=1"), "app.example.com");
+        assert_eq!(originator("https://example.com:443"), "example.com");
+        assert_eq!(originator("http://localhost:1420/"), "localhost:1420");
+        assert_eq!(originator("tauri://localhost"), "localhost");
+        assert_eq!(originator("https://bücher.example/"), "xn--bcher-kva.example");
+        assert!(originator_from_url("https://bad_host.example").is_err());
+        assert!(originator_from_url("about:blank").is_err());
+        assert!(originator_from_url("file:///index.html").is_err());
+    }
+
+    #[test]
+    fn test_validate_command_args() {
+        use serde_json::json;
+        assert_eq!(validate_command_args(serde_json::Value::Null).unwrap(), json!({}));
+        assert_eq!(validate_command_args(json!({"a": 1})).unwrap(), json!({"a": 1}));
+        let err = validate_command_args(json!([1])).unwrap_err();
+        assert_eq!(err.code, "WERR_INVALID_PARAMETER");
+        assert!(validate_command_args(json!("args")).is_err());
     }
 }
@@ -4,6 +4,7 @@
 //! Reference: wallet-toolbox/src/sdk/validationHelpers.ts
 
 use crate::sdk::errors::*;
+use crate::sdk::originator::Originator;
 use crate::sdk::types::OutPoint;
 use crate::sdk::validation::*;
 use serde::{Deserialize, Serialize};
@@ -134,7 +135,7 @@ pub fn validate_create_action_input(
     }
     
     Ok(ValidCreateActionInput {
-        outpoint: parse_wallet_outpoint(outpoint)?,
+        outpoint: parse_wallet_outpoint(&validate_outpoint_string(outpoint, "outpoint")?)?,
         input_description: validate_string_length(input_description, "inputDescription", Some(5), Some(2000))?,
         sequence_number: sequence_number.unwrap_or(0xffffffff),
         unlocking_script: validated_unlocking_script,
@@ -212,11 +213,26 @@ pub fn validate_internalize_output(
     payment_remittance: Option<ValidWalletPayment>,
     insertion_remittance: Option<ValidBasketInsertion>,
 ) -> Result<ValidInternalizeOutput, WalletError> {
-    if protocol != "basket insertion" && protocol != "wallet payment" {
-        return Err(WErrInvalidParameter::new(
-            "protocol",
-            Some("'basket insertion' or 'wallet payment'".to_string()),
-        ));
+    match protocol {
+        "wallet payment" if payment_remittance.is_none() => {
+            return Err(WErrInvalidParameter::new(
+                "paymentRemittance",
+                Some("valid for protocol 'wallet payment'".to_string()),
+            ));
+        }
+        "basket insertion" if insertion_remittance.is_none() => {
+            return Err(WErrInvalidParameter::new(
+                "insertionRemittance",
+                Some("valid for protocol 'basket insertion'".to_string()),
+            ));
+        }
+        "wallet payment" | "basket insertion" => {}
+        _ => {
+            return Err(WErrInvalidParameter::new(
+                "protocol",
+                Some("'basket insertion' or 'wallet payment'".to_string()),
+            ));
+        }
     }
     
     Ok(ValidInternalizeOutput {
@@ -229,23 +245,10 @@ pub fn validate_internalize_output(
 
 /// Validate originator string (domain-like format)
 ///
-/// Matches TypeScript `validateOriginator` function
+/// Matches TypeScript `validateOriginator` function, with the stricter
+/// domain name rules and punycode normalization of [`Originator`].
 pub fn validate_originator(s: Option<&str>) -> Result<Option<String>, WalletError> {
-    match s {
-        None => Ok(None),
-        Some(val) => {
-            let normalized = val.trim().to_lowercase();
-            validate_string_length(&normalized, "originator", Some(1), Some(250))?;
-            
-            // Validate each part
-            let parts: Vec<&str> = normalized.split('.').collect();
-            for part in parts {
-                validate_string_length(part, "originator part", Some(1), Some(63))?;
-            }
-            
-            Ok(Some(normalized))
-        }
-    }
+    s.map(|val| Originator::parse(val).map(String::from)).transpose()
 }
 
 /// Validate certificate fields
@@ -268,15 +271,16 @@ mod tests {
 
     #[test]
     fn test_validate_create_action_input_with_script() {
+        let txid = "ab".repeat(32);
         let result = validate_create_action_input(
-            "abc123.0",
+            &format!("{}.0", txid),
             "test input",
             None,
             Some("deadbeef"),
             None,
         ).unwrap();
         
-        assert_eq!(result.outpoint.txid, "abc123");
+        assert_eq!(result.outpoint.txid, txid);
         assert_eq!(result.outpoint.vout, 0);
         assert_eq!(result.input_description, "test input");
         assert_eq!(result.sequence_number, 0xffffffff);
@@ -287,7 +291,7 @@ mod tests {
     #[test]
     fn test_validate_create_action_input_with_length_only() {
         let result = validate_create_action_input(
-            "abc.1",
+            &format!("{}.1", "ab".repeat(32)),
             "test input",
             Some(100),
             None,
@@ -302,7 +306,7 @@ mod tests {
     #[test]
     fn test_validate_create_action_input_neither_provided() {
         let result = validate_create_action_input(
-            "abc.0",
+            &format!("{}.0", "ab".repeat(32)),
             "test input",
             None,
             None,
@@ -315,7 +319,7 @@ mod tests {
     #[test]
     fn test_validate_create_action_input_length_mismatch() {
         let result = validate_create_action_input(
-            "abc.0",
+            &format!("{}.0", "ab".repeat(32)),
             "test input",
             None,
             Some("deadbeef"), // 4 bytes
@@ -325,6 +329,15 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_create_action_input_bad_outpoint() {
+        let negative_vout = format!("{}.-1", "ab".repeat(32));
+        for outpoint in ["abc123.0", "not an outpoint", negative_vout.as_str()] {
+            let err = validate_create_action_input(outpoint, "test input", None, None, Some(10)).unwrap_err();
+            assert_eq!(err.code, "WERR_INVALID_PARAMETER");
+        }
+    }
+
     #[test]
     fn test_validate_create_action_output() {
         let tags = vec!["tag1".to_string(), "tag2".to_string()];
@@ -439,6 +452,14 @@ mod tests {
         assert!(result.insertion_remittance.is_some());
     }
 
+    #[test]
+    fn test_validate_internalize_output_missing_remittance() {
+        let err = validate_internalize_output(0, "wallet payment", None, None).unwrap_err();
+        assert!(err.description.contains("paymentRemittance"));
+        let err = validate_internalize_output(0, "basket insertion", None, None).unwrap_err();
+        assert!(err.description.contains("insertionRemittance"));
+    }
+
     #[test]
     fn test_validate_internalize_output_invalid_protocol() {
         let result = validate_internalize_output(
@@ -455,6 +476,7 @@ mod tests {
     fn test_validate_originator() {
         let result = validate_originator(Some("  Example.Com  ")).unwrap();
         assert_eq!(result, Some("example.com".to_string()));
+        assert!(validate_originator(Some("app_1.example.com")).is_err());
     }
 
     #[test]
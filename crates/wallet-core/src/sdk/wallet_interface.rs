@@ -3,7 +3,10 @@
 //! Mirrors TypeScript SDK types from @bsv/sdk Wallet.interfaces.ts
 //! Reference: ts-sdk/src/wallet/Wallet.interfaces.ts
 
+use crate::sdk::action_list::{LabelQueryMode, TagQueryMode};
+use crate::sdk::action_process::{SendWithResult, SignActionSpend};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ============================================================================
 // Protocol and Key Types
@@ -469,6 +472,740 @@ pub struct WalletDecryptResult {
     pub plaintext: Vec<u8>,
 }
 
+// ============================================================================
+// Certificate Operations
+// ============================================================================
+
+/// Arguments for acquiring an identity certificate
+///
+/// With `acquisitionProtocol` "direct" the caller already holds the signed
+/// certificate and its keyring; with "issuance" the wallet requests one from
+/// the certifier at `certifierUrl`.
+///
+/// Reference: TS AcquireCertificateArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcquireCertificateArgs {
+    /// Certificate type (base64)
+    #[serde(rename = "type")]
+    pub cert_type: String,
+    
+    /// Certifier identity key
+    pub certifier: String,
+    
+    /// "direct" or "issuance"
+    pub acquisition_protocol: String,
+    
+    /// Plaintext field values by field name
+    pub fields: HashMap<String, String>,
+    
+    /// Serial number (direct only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    
+    /// Revocation outpoint (direct only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_outpoint: Option<String>,
+    
+    /// Certifier signature (direct only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    
+    /// Certifier service URL (issuance only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certifier_url: Option<String>,
+    
+    /// "certifier" or the identity key that revealed the keyring (direct only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyring_revealer: Option<String>,
+    
+    /// Field revelation keys for the subject (direct only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyring_for_subject: Option<HashMap<String, String>>,
+    
+    /// Privileged operation flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+    
+    /// Privileged reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged_reason: Option<String>,
+}
+
+/// Certificate identifying properties, any of which may be omitted
+///
+/// Reference: TS Partial<WalletCertificate> from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialWalletCertificate {
+    /// Certificate type (base64)
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub cert_type: Option<String>,
+    
+    /// Serial number (base64)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    
+    /// Certifier identity key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certifier: Option<String>,
+    
+    /// Subject identity key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    
+    /// Revocation outpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_outpoint: Option<String>,
+    
+    /// Certifier signature (hex)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Arguments for proving a certificate to a verifier
+///
+/// Reference: TS ProveCertificateArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProveCertificateArgs {
+    /// Properties matching exactly one stored certificate
+    pub certificate: PartialWalletCertificate,
+    
+    /// Names of the fields to reveal
+    pub fields_to_reveal: Vec<String>,
+    
+    /// Verifier identity key
+    pub verifier: String,
+    
+    /// Privileged operation flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+    
+    /// Privileged reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged_reason: Option<String>,
+}
+
+/// Arguments for listing the wallet's certificates
+///
+/// Reference: TS ListCertificatesArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCertificatesArgs {
+    /// Certifier identity keys to include (all when empty)
+    pub certifiers: Vec<String>,
+    
+    /// Certificate types to include (all when empty)
+    pub types: Vec<String>,
+    
+    /// Maximum number of certificates (default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    
+    /// Number of certificates to skip (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    
+    /// Further properties certificates must match (wallet-toolbox extension)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<PartialWalletCertificate>,
+    
+    /// Privileged operation flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+    
+    /// Privileged reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privileged_reason: Option<String>,
+}
+
+/// Certificate as listed, with the subject's master keyring
+///
+/// Reference: TS CertificateResult from @bsv/sdk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateResult {
+    /// Certificate type (base64)
+    #[serde(rename = "type")]
+    pub cert_type: String,
+    
+    /// Subject identity key
+    pub subject: String,
+    
+    /// Serial number (base64)
+    pub serial_number: String,
+    
+    /// Certifier identity key
+    pub certifier: String,
+    
+    /// Revocation outpoint
+    pub revocation_outpoint: String,
+    
+    /// Certifier signature (hex)
+    pub signature: String,
+    
+    /// Encrypted field values (base64) by field name
+    pub fields: HashMap<String, String>,
+    
+    /// Field revelation keys by field name
+    pub keyring: HashMap<String, String>,
+    
+    /// Counterparty the keyring is shared with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+}
+
+/// Result from listing certificates
+///
+/// Reference: TS ListCertificatesResult from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCertificatesResult {
+    /// Number of matching certificates, ignoring limit and offset
+    pub total_certificates: i64,
+    
+    /// Requested page of certificates
+    pub certificates: Vec<CertificateResult>,
+}
+
+/// Result from relinquishing a certificate
+///
+/// Reference: TS RelinquishCertificateResult from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinquishCertificateResult {
+    /// Whether the certificate was relinquished
+    pub relinquished: bool,
+}
+
+// ============================================================================
+// Action Operations
+// ============================================================================
+
+/// Input to spend in a new action
+///
+/// Reference: TS CreateActionInput from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateActionInput {
+    /// Outpoint being spent ("txid.vout")
+    pub outpoint: String,
+    
+    /// Description of this input (5-2000 bytes)
+    pub input_description: String,
+    
+    /// Unlocking script (hex), when already known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlocking_script: Option<String>,
+    
+    /// Unlocking script length, when the script is supplied later via signAction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlocking_script_length: Option<u32>,
+    
+    /// Sequence number (default 0xffffffff)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<u32>,
+}
+
+/// Output to create in a new action
+///
+/// Reference: TS CreateActionOutput from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateActionOutput {
+    /// Locking script (hex)
+    pub locking_script: String,
+    
+    /// Amount in satoshis
+    pub satoshis: i64,
+    
+    /// Description of this output (5-2000 bytes)
+    pub output_description: String,
+    
+    /// Basket to track the output in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub basket: Option<String>,
+    
+    /// Custom instructions for spending the output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<String>,
+    
+    /// Tags for the output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+/// Options for a new action
+///
+/// Reference: TS CreateActionOptions from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateActionOptions {
+    /// Sign and process immediately when every input is unlocked (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_and_process: Option<bool>,
+    
+    /// Leave broadcasting to the monitor (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_delayed_broadcast: Option<bool>,
+    
+    /// Trust level for inputs the wallet already knows ("known")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust_self: Option<String>,
+    
+    /// Txids known to the caller, which the returned BEEF may omit
+    #[serde(rename = "knownTxids", skip_serializing_if = "Option::is_none")]
+    pub known_txids: Option<Vec<String>>,
+    
+    /// Return only the txid instead of the transaction
+    #[serde(rename = "returnTXIDOnly", skip_serializing_if = "Option::is_none")]
+    pub return_txid_only: Option<bool>,
+    
+    /// Build and sign the transaction without sending it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_send: Option<bool>,
+    
+    /// Change outpoints from prior noSend actions to spend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_send_change: Option<Vec<String>>,
+    
+    /// Txids of prior noSend actions to broadcast with this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_with: Option<Vec<String>>,
+    
+    /// Randomize output order (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub randomize_outputs: Option<bool>,
+    
+    /// Basket to fund change from and return change to, instead of the
+    /// wallet's configured change basket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_basket: Option<String>,
+}
+
+/// Arguments for creating a new action
+///
+/// Reference: TS CreateActionArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateActionArgs {
+    /// Description of the action (5-2000 bytes)
+    pub description: String,
+    
+    /// BEEF proving the inputs the wallet does not already hold
+    #[serde(rename = "inputBEEF", skip_serializing_if = "Option::is_none")]
+    pub input_beef: Option<Vec<u8>>,
+    
+    /// Inputs to spend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Vec<CreateActionInput>>,
+    
+    /// Outputs to create
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<CreateActionOutput>>,
+    
+    /// Transaction lock time (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_time: Option<u32>,
+    
+    /// Transaction version (default 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    
+    /// Labels for the action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    
+    /// Processing options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<CreateActionOptions>,
+}
+
+/// Transaction awaiting unlocking scripts from the caller
+///
+/// Reference: TS SignableTransaction from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignableTransaction {
+    /// AtomicBEEF of the unsigned transaction
+    pub tx: Vec<u8>,
+    
+    /// Reference to pass to signAction
+    pub reference: String,
+}
+
+/// Result from creating an action
+///
+/// Reference: TS CreateActionResult from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateActionResult {
+    /// Txid of the signed transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    
+    /// AtomicBEEF of the signed transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx: Option<Vec<u8>>,
+    
+    /// Change outpoints kept back by a noSend action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_send_change: Option<Vec<String>>,
+    
+    /// Status of each transaction sent with this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_with_results: Option<Vec<SendWithResult>>,
+    
+    /// Transaction to complete with signAction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signable_transaction: Option<SignableTransaction>,
+}
+
+/// Options for signing an action
+///
+/// Reference: TS SignActionOptions from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignActionOptions {
+    /// Leave broadcasting to the monitor (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_delayed_broadcast: Option<bool>,
+    
+    /// Return only the txid instead of the transaction
+    #[serde(rename = "returnTXIDOnly", skip_serializing_if = "Option::is_none")]
+    pub return_txid_only: Option<bool>,
+    
+    /// Sign the transaction without sending it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_send: Option<bool>,
+    
+    /// Txids of prior noSend actions to broadcast with this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_with: Option<Vec<String>>,
+}
+
+/// Arguments for signing an action returned as a signable transaction
+///
+/// Reference: TS SignActionArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignActionArgs {
+    /// Unlocking scripts by input index
+    pub spends: HashMap<u32, SignActionSpend>,
+    
+    /// Reference from the signable transaction
+    pub reference: String,
+    
+    /// Processing options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<SignActionOptions>,
+}
+
+/// Result from signing an action
+///
+/// Reference: TS SignActionResult from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignActionResult {
+    /// Txid of the signed transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    
+    /// AtomicBEEF of the signed transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx: Option<Vec<u8>>,
+    
+    /// Status of each transaction sent with this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_with_results: Option<Vec<SendWithResult>>,
+}
+
+/// Arguments for aborting an action
+///
+/// Reference: TS AbortActionArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbortActionArgs {
+    /// Reference of the action to abort
+    pub reference: String,
+}
+
+/// Result from aborting an action
+///
+/// Reference: TS AbortActionResult from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbortActionResult {
+    /// Whether the action was aborted
+    pub aborted: bool,
+}
+
+/// Arguments for listing actions
+///
+/// Reference: TS ListActionsArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListActionsArgs {
+    /// Labels to filter by
+    pub labels: Vec<String>,
+    
+    /// How to match labels (default any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_query_mode: Option<LabelQueryMode>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_labels: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_inputs: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_input_source_locking_scripts: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_input_unlocking_scripts: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_outputs: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_output_locking_scripts: Option<bool>,
+    
+    /// Maximum number of actions (default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    
+    /// Number of actions to skip (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seek_permission: Option<bool>,
+}
+
+/// Arguments for listing outputs
+///
+/// Reference: TS ListOutputsArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOutputsArgs {
+    /// Basket to list
+    pub basket: String,
+    
+    /// Tags to filter by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    
+    /// How to match tags (default any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_query_mode: Option<TagQueryMode>,
+    
+    /// "locking scripts" or "entire transactions"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_custom_instructions: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_tags: Option<bool>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_labels: Option<bool>,
+    
+    /// Maximum number of outputs (default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    
+    /// Number of outputs to skip (default 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seek_permission: Option<bool>,
+}
+
+/// Remittance for an output paid to the wallet
+///
+/// Reference: TS WalletPayment from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletPayment {
+    pub derivation_prefix: String,
+    pub derivation_suffix: String,
+    pub sender_identity_key: String,
+}
+
+/// Remittance for an output inserted into a basket
+///
+/// Reference: TS BasketInsertion from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasketInsertion {
+    pub basket: String,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+/// Output to take ownership of
+///
+/// Reference: TS InternalizeOutput from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalizeOutput {
+    pub output_index: u32,
+    
+    /// "wallet payment" or "basket insertion"
+    pub protocol: String,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_remittance: Option<WalletPayment>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insertion_remittance: Option<BasketInsertion>,
+}
+
+/// Arguments for internalizing a transaction's outputs
+///
+/// Reference: TS InternalizeActionArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalizeActionArgs {
+    /// AtomicBEEF of the transaction
+    pub tx: Vec<u8>,
+    
+    /// Outputs to internalize
+    pub outputs: Vec<InternalizeOutput>,
+    
+    /// Description of the action (5-2000 bytes)
+    pub description: String,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seek_permission: Option<bool>,
+}
+
+/// Result from internalizing an action
+///
+/// Reference: TS InternalizeActionResult from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternalizeActionResult {
+    /// Whether the outputs were accepted
+    pub accepted: bool,
+}
+
+// ============================================================================
+// Identity Discovery
+// ============================================================================
+
+/// Arguments for discovering certificates by identity key
+///
+/// Reference: TS DiscoverByIdentityKeyArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverByIdentityKeyArgs {
+    /// Identity key to look up
+    pub identity_key: PubKeyHex,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seek_permission: Option<bool>,
+    
+    /// Bypass the wallet's identity cache and query the overlay again
+    ///
+    /// Not part of the TS interface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_refresh: Option<bool>,
+}
+
+/// Arguments for discovering certificates by field values
+///
+/// Reference: TS DiscoverByAttributesArgs from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverByAttributesArgs {
+    /// Field values to match, by field name
+    pub attributes: HashMap<String, String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seek_permission: Option<bool>,
+    
+    /// Bypass the wallet's identity cache and query the overlay again
+    ///
+    /// Not part of the TS interface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_refresh: Option<bool>,
+}
+
+/// Certifier details attached to a discovered certificate
+///
+/// Reference: TS IdentityCertifier from @bsv/sdk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityCertifier {
+    pub name: String,
+    pub icon_url: String,
+    pub description: String,
+    
+    /// Trust level the user assigned to the certifier
+    pub trust: u32,
+}
+
+/// Certificate found by identity discovery, with its revealed fields
+///
+/// Reference: TS IdentityCertificate from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityCertificate {
+    /// Certificate type (base64)
+    #[serde(rename = "type")]
+    pub cert_type: String,
+    
+    pub subject: String,
+    pub serial_number: String,
+    pub certifier: String,
+    pub revocation_outpoint: String,
+    pub signature: String,
+    
+    /// Encrypted field values (base64) by field name
+    pub fields: HashMap<String, String>,
+    
+    pub certifier_info: IdentityCertifier,
+    
+    /// Keyring publicly revealing the decrypted fields
+    pub publicly_revealed_keyring: HashMap<String, String>,
+    
+    /// Plaintext field values by field name
+    pub decrypted_fields: HashMap<String, String>,
+}
+
+/// Result from identity discovery
+///
+/// Reference: TS DiscoverCertificatesResult from @bsv/sdk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverCertificatesResult {
+    /// Number of matching certificates, ignoring limit and offset
+    pub total_certificates: i64,
+    
+    pub certificates: Vec<IdentityCertificate>,
+}
+
 // ============================================================================
 // Blockchain Query Operations
 // ============================================================================
@@ -1,4 +1,297 @@
 // Services module stubs mirroring TS structure
 
+use async_trait::async_trait;
+use wallet_storage::{StorageError, StorageResult};
+
+use std::collections::HashMap;
+
+use crate::beef::MerklePath;
+use crate::sdk::action_process::ReviewActionResult;
+use crate::sdk::IdentityCertificate;
+
 #[derive(Debug, Default)]
 pub struct Services;
+
+/// Network broadcaster used by processAction
+///
+/// Implemented over the wallet-services broadcasters (ARC, WhatsOnChain, ...).
+///
+/// Reference: TypeScript `WalletServices.postBeef(beef, txids)`
+#[async_trait]
+pub trait Broadcaster: Send + Sync {
+    /// Post a BEEF containing `txids` to the network
+    ///
+    /// Returns one result per requested txid.
+    async fn post_beef(&self, beef: &[u8], txids: &[String]) -> StorageResult<Vec<ReviewActionResult>>;
+
+    /// Name of the service posted to, reported in monitor progress events
+    fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Block header fields used to validate a merkle proof
+///
+/// Reference: TypeScript `BlockHeader`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// Block height
+    pub height: u32,
+    
+    /// Block hash (hex)
+    pub hash: String,
+    
+    /// Merkle root (hex)
+    pub merkle_root: String,
+}
+
+/// Result of a merkle path lookup
+///
+/// Reference: TypeScript `GetMerklePathResult`
+#[derive(Debug, Clone, Default)]
+pub struct GetMerklePathResult {
+    /// Name of the service that produced the result
+    pub name: Option<String>,
+    
+    /// Merkle path, if the transaction has been mined
+    pub merkle_path: Option<MerklePath>,
+    
+    /// Header of the block containing the transaction
+    pub header: Option<BlockHeader>,
+    
+    /// Service error, if any
+    pub error: Option<String>,
+}
+
+/// Merkle proof source used by the monitor
+///
+/// Reference: TypeScript `WalletServices.getMerklePath(txid)`
+#[async_trait]
+pub trait MerklePathProvider: Send + Sync {
+    /// Look up the merkle path for a mined transaction
+    async fn get_merkle_path(&self, txid: &str) -> StorageResult<GetMerklePathResult>;
+}
+
+/// Active-chain header lookup used by the monitor to detect reorgs
+///
+/// Reference: TypeScript `ChaintracksClientApi.findHeaderForHeight`
+#[async_trait]
+pub trait ChainHeaderProvider: Send + Sync {
+    /// Header at `height` on the active chain, None if beyond the tip
+    async fn find_header_for_height(&self, height: u32) -> StorageResult<Option<BlockHeader>>;
+}
+
+/// Format of the `output` argument to `get_utxo_status`
+///
+/// Reference: TypeScript `GetUtxoStatusOutputFormat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetUtxoStatusOutputFormat {
+    /// Little-endian SHA256 hash of the output script (hex)
+    HashLE,
+    /// Big-endian SHA256 hash of the output script (hex)
+    HashBE,
+    /// Entire output script (hex)
+    Script,
+}
+
+/// An unspent output reported by a service
+///
+/// Reference: TypeScript `GetUtxoStatusDetails`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UtxoDetail {
+    pub txid: String,
+    pub index: u32,
+    pub satoshis: u64,
+    /// Block height, if mined
+    pub height: Option<u32>,
+}
+
+/// Result of a UTXO status lookup
+///
+/// Reference: TypeScript `GetUtxoStatusResult`
+#[derive(Debug, Clone, Default)]
+pub struct GetUtxoStatusResult {
+    /// Name of the service that produced the result
+    pub name: Option<String>,
+    
+    /// Whether the output (or, without an outpoint, any output with the script) is unspent
+    pub is_utxo: bool,
+    
+    /// Unspent outputs with the script
+    pub details: Vec<UtxoDetail>,
+    
+    /// Service error, if any
+    pub error: Option<String>,
+}
+
+/// Confirmed history entry for a script hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptHashHistoryEntry {
+    pub txid: String,
+    /// Block height, if mined
+    pub height: Option<u32>,
+}
+
+/// Result of a script hash history lookup
+///
+/// Reference: TypeScript `GetScriptHashHistoryResult`
+#[derive(Debug, Clone, Default)]
+pub struct GetScriptHashHistoryResult {
+    /// Name of the service that produced the result
+    pub name: Option<String>,
+    
+    pub history: Vec<ScriptHashHistoryEntry>,
+    
+    /// Service error, if any
+    pub error: Option<String>,
+}
+
+/// On-chain output status source
+///
+/// Implemented over the wallet-services UTXO providers (WhatsOnChain, Bitails).
+///
+/// Reference: TypeScript `WalletServices.getUtxoStatus` / `getScriptHashHistory`
+#[async_trait]
+pub trait UtxoStatusProvider: Send + Sync {
+    /// Look up unspent outputs for `output`
+    ///
+    /// `outpoint` ("txid.vout") restricts `is_utxo` to that output.
+    async fn get_utxo_status(
+        &self,
+        output: &str,
+        output_format: Option<GetUtxoStatusOutputFormat>,
+        outpoint: Option<&str>,
+    ) -> StorageResult<GetUtxoStatusResult>;
+    
+    /// Look up the transaction history of a script hash (little-endian hex)
+    async fn get_script_hash_history(&self, hash: &str) -> StorageResult<GetScriptHashHistoryResult>;
+}
+
+/// Historical BSV exchange rates used to value exported history
+///
+/// Implemented over the wallet-services exchange rate providers.
+///
+/// Reference: TypeScript `WalletServices.getBsvExchangeRate` / `getFiatExchangeRate`
+#[async_trait]
+pub trait FiatRateProvider: Send + Sync {
+    /// ISO 4217 code of the currency rates are quoted in, e.g. "USD"
+    fn currency(&self) -> &str;
+    
+    /// Price of one BSV at `time`
+    async fn get_bsv_rate_at(&self, time: chrono::DateTime<chrono::Utc>) -> StorageResult<f64>;
+
+    /// Current price of one BSV in `currency`
+    ///
+    /// Providers quoting several currencies should override this; by default
+    /// only `currency()` is supported.
+    async fn get_bsv_rate(&self, currency: &str) -> StorageResult<f64> {
+        if !currency.eq_ignore_ascii_case(self.currency()) {
+            return Err(StorageError::InvalidArg(format!(
+                "rates are quoted in {}, not {}",
+                self.currency(),
+                currency
+            )));
+        }
+        self.get_bsv_rate_at(chrono::Utc::now()).await
+    }
+}
+
+/// Identity overlay lookups used by discoverByIdentityKey and discoverByAttributes
+///
+/// Certificates come back with their publicly revealed fields decrypted;
+/// the wallet fills in `certifier_info` from the user's trust settings.
+///
+/// Reference: TypeScript `queryOverlay` over the `ls_identity` lookup service
+#[async_trait]
+pub trait IdentityResolver: Send + Sync {
+    /// Certificates of `identity_key` issued by any of `certifiers`
+    async fn resolve_by_identity_key(
+        &self,
+        identity_key: &str,
+        certifiers: &[String],
+    ) -> StorageResult<Vec<IdentityCertificate>>;
+
+    /// Certificates issued by any of `certifiers` revealing all of `attributes`
+    async fn resolve_by_attributes(
+        &self,
+        attributes: &HashMap<String, String>,
+        certifiers: &[String],
+    ) -> StorageResult<Vec<IdentityCertificate>>;
+}
+
+/// Verify that output `vout` of `txid` with `locking_script` is unspent on-chain
+///
+/// Returns `StorageError::InvalidArg` when the service reports the output spent
+/// or unknown, and `StorageError::Io` when the service itself failed.
+pub async fn verify_output_unspent(
+    services: &dyn UtxoStatusProvider,
+    txid: &str,
+    vout: u32,
+    locking_script: &[u8],
+) -> StorageResult<()> {
+    let outpoint = format!("{}.{}", txid, vout);
+    let r = services
+        .get_utxo_status(&hex::encode(locking_script), Some(GetUtxoStatusOutputFormat::Script), Some(&outpoint))
+        .await?;
+    if let Some(error) = r.error {
+        return Err(StorageError::Io(format!("getUtxoStatus failed for {}: {}", outpoint, error)));
+    }
+    if !r.is_utxo {
+        return Err(StorageError::InvalidArg(format!("output {} is not unspent", outpoint)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    struct MockUtxoStatus {
+        result: GetUtxoStatusResult,
+    }
+    
+    #[async_trait]
+    impl UtxoStatusProvider for MockUtxoStatus {
+        async fn get_utxo_status(
+            &self,
+            output: &str,
+            output_format: Option<GetUtxoStatusOutputFormat>,
+            outpoint: Option<&str>,
+        ) -> StorageResult<GetUtxoStatusResult> {
+            assert_eq!(output, "51");
+            assert_eq!(output_format, Some(GetUtxoStatusOutputFormat::Script));
+            assert_eq!(outpoint, Some("aa.1"));
+            Ok(self.result.clone())
+        }
+        
+        async fn get_script_hash_history(&self, _hash: &str) -> StorageResult<GetScriptHashHistoryResult> {
+            Ok(GetScriptHashHistoryResult::default())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_verify_output_unspent() {
+        let unspent = MockUtxoStatus {
+            result: GetUtxoStatusResult { is_utxo: true, ..Default::default() },
+        };
+        assert!(verify_output_unspent(&unspent, "aa", 1, &[0x51]).await.is_ok());
+        
+        let spent = MockUtxoStatus { result: GetUtxoStatusResult::default() };
+        assert!(matches!(
+            verify_output_unspent(&spent, "aa", 1, &[0x51]).await,
+            Err(StorageError::InvalidArg(_))
+        ));
+        
+        let failed = MockUtxoStatus {
+            result: GetUtxoStatusResult { error: Some("timeout".to_string()), ..Default::default() },
+        };
+        assert!(matches!(
+            verify_output_unspent(&failed, "aa", 1, &[0x51]).await,
+            Err(StorageError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,442 @@
+//! Acquire Certificate
+//!
+//! **Reference**: TypeScript `Wallet.acquireCertificate` (Wallet.ts)
+//!
+//! Acquires an identity certificate by either acquisition protocol:
+//! "direct" stores a certificate the caller already holds, "issuance"
+//! requests a new one from the certifier's HTTP service.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use wallet_storage::{AuthId, WalletStorageProvider};
+
+use super::acquire_direct_certificate::{
+    acquire_direct_certificate, AcquireCertificateResult, ValidAcquireDirectCertificateArgs,
+};
+use crate::auth_http::AuthFetch;
+use crate::certificates::{create_certificate_fields, decrypt_fields, Certificate};
+use crate::crypto::verify_hmac_sha256;
+use crate::keys::{create_nonce, verify_nonce, RootKeyDeriver};
+use crate::sdk::{AcquireCertificateArgs, WalletError, WalletResult};
+
+/// Protocol under which certifiers derive serial numbers from the nonces
+pub const CERTIFICATE_ISSUANCE_PROTOCOL: &str = "certificate issuance";
+
+/// Response header carrying the certifier's identity key
+pub const IDENTITY_KEY_HEADER: &str = "x-bsv-auth-identity-key";
+
+/// Certificate signing request sent to `{certifierUrl}/signCertificate`
+///
+/// Reference: TS Wallet.acquireCertificate issuance request body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateSigningRequest {
+    /// Nonce created by the subject for the certifier
+    pub client_nonce: String,
+
+    /// Certificate type (base64)
+    #[serde(rename = "type")]
+    pub cert_type: String,
+
+    /// Encrypted field values (base64) by field name
+    pub fields: HashMap<String, String>,
+
+    /// Field revelation keys encrypted for the certifier
+    pub master_keyring: HashMap<String, String>,
+}
+
+/// Certifier's answer to a signing request
+#[derive(Debug, Clone)]
+pub struct SignCertificateResponse {
+    /// Identity key the certifier authenticated the response with
+    pub identity_key: Option<String>,
+
+    /// Signed certificate
+    pub certificate: Option<Certificate>,
+
+    /// Nonce created by the certifier for the subject
+    pub server_nonce: Option<String>,
+}
+
+/// Transport for certificate signing requests
+#[async_trait]
+pub trait CertifierClient: Send + Sync {
+    /// Send `request` to the certifier service at `certifier_url`
+    async fn sign_certificate(
+        &self,
+        certifier_url: &str,
+        request: &CertificateSigningRequest,
+    ) -> WalletResult<SignCertificateResponse>;
+}
+
+/// Response body of `/signCertificate`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignCertificateBody {
+    certificate: Option<Certificate>,
+    server_nonce: Option<String>,
+}
+
+/// `CertifierClient` over plain HTTPS
+///
+/// TS sends the request with BRC-103 AuthFetch, which authenticates the
+/// response identity header. This client posts JSON and takes the header as
+/// returned, so it relies on TLS to reach the right certifier; the issued
+/// certificate is still checked against the certifier's signature. Prefer
+/// [`AuthFetch`], which authenticates the certifier as TS does.
+#[derive(Debug, Clone, Default)]
+pub struct HttpCertifierClient {
+    client: reqwest::Client,
+}
+
+impl HttpCertifierClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CertifierClient for HttpCertifierClient {
+    async fn sign_certificate(
+        &self,
+        certifier_url: &str,
+        request: &CertificateSigningRequest,
+    ) -> WalletResult<SignCertificateResponse> {
+        let url = format!("{}/signCertificate", certifier_url.trim_end_matches('/'));
+        let response = self.client.post(&url)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| WalletError::internal(format!("Certificate signing request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(WalletError::invalid_operation(format!(
+                "Certifier returned HTTP status {}", response.status()
+            )));
+        }
+
+        let identity_key = response.headers()
+            .get(IDENTITY_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body: SignCertificateBody = response.json()
+            .await
+            .map_err(|e| WalletError::invalid_operation(format!("Invalid certifier response: {}", e)))?;
+
+        Ok(SignCertificateResponse {
+            identity_key,
+            certificate: body.certificate,
+            server_nonce: body.server_nonce,
+        })
+    }
+}
+
+/// Issuance over BRC-104, as TS does
+///
+/// The identity key is the one the certifier signed the response with.
+#[async_trait]
+impl CertifierClient for AuthFetch {
+    async fn sign_certificate(
+        &self,
+        certifier_url: &str,
+        request: &CertificateSigningRequest,
+    ) -> WalletResult<SignCertificateResponse> {
+        let url = format!("{}/signCertificate", certifier_url.trim_end_matches('/'));
+        let response = self.post_json(&url, request).await?;
+        if !response.is_success() {
+            return Err(WalletError::invalid_operation(format!(
+                "Certifier returned HTTP status {}", response.status
+            )));
+        }
+
+        let body: SignCertificateBody = response.json()?;
+        Ok(SignCertificateResponse {
+            identity_key: Some(response.identity_key),
+            certificate: body.certificate,
+            server_nonce: body.server_nonce,
+        })
+    }
+}
+
+/// Acquire a certificate by either acquisition protocol
+///
+/// Reference: TS Wallet.acquireCertificate
+///
+/// # Arguments
+/// * `storage` - Storage for saving the certificate and its fields
+/// * `auth` - Authenticated user
+/// * `key_deriver` - Deriver over the subject's root key
+/// * `client` - Transport to the certifier (issuance only)
+/// * `args` - Acquire certificate arguments
+pub async fn acquire_certificate(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    key_deriver: &RootKeyDeriver,
+    client: &dyn CertifierClient,
+    args: &AcquireCertificateArgs,
+) -> WalletResult<AcquireCertificateResult> {
+    match args.acquisition_protocol.as_str() {
+        "direct" => {
+            let vargs = validate_direct_args(args, key_deriver.identity_key_hex())?;
+            acquire_direct_certificate(storage, auth, vargs).await
+        }
+        "issuance" => acquire_issuance_certificate(storage, auth, key_deriver, client, args).await,
+        _ => Err(WalletError::invalid_parameter("acquisitionProtocol", "\"direct\" or \"issuance\"")),
+    }
+}
+
+/// Request a certificate from its certifier and store it
+///
+/// The stored certificate keeps the master keyring, with the certifier as
+/// verifier, exactly as a direct acquisition revealed by the certifier.
+///
+/// Reference: TS Wallet.acquireCertificate (acquisitionProtocol 'issuance')
+pub async fn acquire_issuance_certificate(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    key_deriver: &RootKeyDeriver,
+    client: &dyn CertifierClient,
+    args: &AcquireCertificateArgs,
+) -> WalletResult<AcquireCertificateResult> {
+    let (certificate, master_keyring) = request_certificate(key_deriver, client, args).await?;
+
+    let vargs = ValidAcquireDirectCertificateArgs {
+        cert_type: certificate.cert_type,
+        subject: certificate.subject,
+        serial_number: certificate.serial_number,
+        certifier: certificate.certifier,
+        revocation_outpoint: certificate.revocation_outpoint,
+        signature: certificate.signature,
+        fields: certificate.fields,
+        keyring_for_subject: master_keyring,
+        keyring_revealer: "certifier".to_string(),
+    };
+    acquire_direct_certificate(storage, auth, vargs).await
+}
+
+/// Run the issuance exchange and validate the issued certificate
+///
+/// # Returns
+/// The signed certificate and the master keyring for its fields
+pub async fn request_certificate(
+    key_deriver: &RootKeyDeriver,
+    client: &dyn CertifierClient,
+    args: &AcquireCertificateArgs,
+) -> WalletResult<(Certificate, HashMap<String, String>)> {
+    let certifier_url = args.certifier_url.as_deref()
+        .ok_or_else(|| WalletError::missing_parameter("certifierUrl"))?;
+
+    // Nonce and encrypted fields for the certifier
+    let client_nonce = create_nonce(key_deriver, &args.certifier)?;
+    let fields = create_certificate_fields(key_deriver, &args.certifier, &args.fields).await?;
+
+    let request = CertificateSigningRequest {
+        client_nonce: client_nonce.clone(),
+        cert_type: args.cert_type.clone(),
+        fields: fields.certificate_fields,
+        master_keyring: fields.master_keyring.clone(),
+    };
+    let response = client.sign_certificate(certifier_url, &request).await?;
+
+    if response.identity_key.as_deref() != Some(args.certifier.as_str()) {
+        return Err(WalletError::invalid_operation("Invalid certifier! Expected the certifier identity key in the response"));
+    }
+    let certificate = response.certificate
+        .ok_or_else(|| WalletError::invalid_operation("No certificate received from certifier!"))?;
+    let server_nonce = response.server_nonce
+        .ok_or_else(|| WalletError::invalid_operation("No serverNonce received from certifier!"))?;
+
+    // The certifier must answer our nonce with its own, and derive the
+    // serial number from both
+    if !verify_nonce(&server_nonce, key_deriver, &args.certifier)? {
+        return Err(WalletError::invalid_operation("Invalid serverNonce from certifier"));
+    }
+    let nonces = STANDARD.decode(format!("{}{}", client_nonce, server_nonce))
+        .map_err(|_| WalletError::invalid_operation("Nonces are not valid base64"))?;
+    let serial_number = STANDARD.decode(&certificate.serial_number)
+        .map_err(|_| WalletError::invalid_operation("Serial number is not valid base64"))?;
+    let hmac_key = key_deriver.derive_symmetric_key(
+        &(2, CERTIFICATE_ISSUANCE_PROTOCOL.to_string()),
+        &format!("{}{}", server_nonce, client_nonce),
+        &args.certifier,
+    )?;
+    if !verify_hmac_sha256(&hmac_key, &nonces, &serial_number) {
+        return Err(WalletError::invalid_operation("Invalid serialNumber"));
+    }
+
+    // The certificate must be the one requested
+    if certificate.cert_type != args.cert_type {
+        return Err(WalletError::invalid_operation(format!("Invalid certificate type! Expected: {}, Received: {}", args.cert_type, certificate.cert_type)));
+    }
+    if certificate.subject != key_deriver.identity_key_hex() {
+        return Err(WalletError::invalid_operation(format!("Invalid certificate subject! Expected: {}, Received: {}", key_deriver.identity_key_hex(), certificate.subject)));
+    }
+    if certificate.certifier != args.certifier {
+        return Err(WalletError::invalid_operation(format!("Invalid certifier! Expected: {}, Received: {}", args.certifier, certificate.certifier)));
+    }
+    if certificate.revocation_outpoint.is_empty() {
+        return Err(WalletError::invalid_operation("Invalid revocationOutpoint!"));
+    }
+    if certificate.fields != request.fields {
+        return Err(WalletError::invalid_operation("Certificate fields do not match the request"));
+    }
+    if certificate.signature.is_empty() || !certificate.verify().await? {
+        return Err(WalletError::invalid_operation("Certificate signature is invalid"));
+    }
+
+    // Make sure the master keyring still opens every field
+    decrypt_fields(key_deriver, &fields.master_keyring, &certificate.fields, &args.certifier).await
+        .map_err(|e| WalletError::invalid_operation(format!("Failed to decrypt certificate fields: {}", e)))?;
+
+    Ok((certificate, fields.master_keyring))
+}
+
+/// Validate direct acquisition arguments for `subject`
+///
+/// Reference: TS validateAcquireDirectCertificateArgs
+fn validate_direct_args(args: &AcquireCertificateArgs, subject: String) -> WalletResult<ValidAcquireDirectCertificateArgs> {
+    let required = |value: &Option<String>, name: &str| value.clone()
+        .ok_or_else(|| WalletError::missing_parameter(name.to_string()));
+
+    Ok(ValidAcquireDirectCertificateArgs {
+        cert_type: args.cert_type.clone(),
+        subject,
+        serial_number: required(&args.serial_number, "serialNumber")?,
+        certifier: args.certifier.clone(),
+        revocation_outpoint: required(&args.revocation_outpoint, "revocationOutpoint")?,
+        signature: required(&args.signature, "signature")?,
+        fields: args.fields.clone(),
+        keyring_for_subject: args.keyring_for_subject.clone()
+            .ok_or_else(|| WalletError::missing_parameter("keyringForSubject"))?,
+        keyring_revealer: required(&args.keyring_revealer, "keyringRevealer")?,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hmac_sha256;
+
+    /// Certifier service run in-process
+    struct MockCertifier {
+        certifier: RootKeyDeriver,
+        subject: String,
+        /// Identity key reported in the response header
+        identity_key: String,
+    }
+
+    #[async_trait]
+    impl CertifierClient for MockCertifier {
+        async fn sign_certificate(
+            &self,
+            _certifier_url: &str,
+            request: &CertificateSigningRequest,
+        ) -> WalletResult<SignCertificateResponse> {
+            assert!(verify_nonce(&request.client_nonce, &self.certifier, &self.subject)?);
+            let server_nonce = create_nonce(&self.certifier, &self.subject)?;
+
+            let key = self.certifier.derive_symmetric_key(
+                &(2, CERTIFICATE_ISSUANCE_PROTOCOL.to_string()),
+                &format!("{}{}", server_nonce, request.client_nonce),
+                &self.subject,
+            )?;
+            let nonces = STANDARD.decode(format!("{}{}", request.client_nonce, server_nonce)).unwrap();
+
+            let mut certificate = Certificate {
+                cert_type: request.cert_type.clone(),
+                serial_number: STANDARD.encode(hmac_sha256(&key, &nonces)),
+                subject: self.subject.clone(),
+                certifier: self.certifier.identity_key_hex(),
+                revocation_outpoint: format!("{}.0", "00".repeat(32)),
+                fields: request.fields.clone(),
+                signature: String::new(),
+            };
+            certificate.sign(&self.certifier).await?;
+
+            Ok(SignCertificateResponse {
+                identity_key: Some(self.identity_key.clone()),
+                certificate: Some(certificate),
+                server_nonce: Some(server_nonce),
+            })
+        }
+    }
+
+    fn args(certifier: &RootKeyDeriver) -> AcquireCertificateArgs {
+        let mut fields = HashMap::new();
+        fields.insert("name".to_string(), "Alice".to_string());
+        AcquireCertificateArgs {
+            cert_type: STANDARD.encode([7u8; 32]),
+            certifier: certifier.identity_key_hex(),
+            acquisition_protocol: "issuance".to_string(),
+            fields,
+            serial_number: None,
+            revocation_outpoint: None,
+            signature: None,
+            certifier_url: Some("https://certifier.example".to_string()),
+            keyring_revealer: None,
+            keyring_for_subject: None,
+            privileged: None,
+            privileged_reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_certificate() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let args = args(&certifier);
+        let client = MockCertifier {
+            subject: subject.identity_key_hex(),
+            identity_key: certifier.identity_key_hex(),
+            certifier,
+        };
+
+        let (certificate, keyring) = request_certificate(&subject, &client, &args).await.unwrap();
+        assert_eq!(certificate.subject, subject.identity_key_hex());
+        assert!(certificate.verify().await.unwrap());
+
+        let fields = decrypt_fields(&subject, &keyring, &certificate.fields, &args.certifier).await.unwrap();
+        assert_eq!(fields.get("name").map(String::as_str), Some("Alice"));
+    }
+
+    #[tokio::test]
+    async fn test_request_certificate_rejects_wrong_certifier() {
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let args = args(&certifier);
+        let client = MockCertifier {
+            subject: subject.identity_key_hex(),
+            identity_key: RootKeyDeriver::new(&[3u8; 32]).unwrap().identity_key_hex(),
+            certifier,
+        };
+        assert!(request_certificate(&subject, &client, &args).await.is_err());
+
+        let mut args = args;
+        args.certifier_url = None;
+        assert!(request_certificate(&subject, &client, &args).await.is_err());
+    }
+
+    #[test]
+    fn test_validate_direct_args() {
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let mut args = args(&certifier);
+        args.acquisition_protocol = "direct".to_string();
+        assert!(validate_direct_args(&args, "subject".to_string()).is_err());
+
+        args.serial_number = Some("serial".to_string());
+        args.revocation_outpoint = Some("txid.0".to_string());
+        args.signature = Some("sig".to_string());
+        args.keyring_revealer = Some("certifier".to_string());
+        args.keyring_for_subject = Some(HashMap::new());
+        let vargs = validate_direct_args(&args, "subject".to_string()).unwrap();
+        assert_eq!(vargs.subject, "subject");
+        assert_eq!(vargs.serial_number, "serial");
+    }
+}
@@ -2,10 +2,11 @@
 //!
 //! **Reference**: TypeScript `src/signer/methods/acquireDirectCertificate.ts`
 //!
-//! Acquires a certificate directly and stores it
+//! Acquires a certificate directly and stores it with its fields
 
 use crate::sdk::errors::{WalletError, WalletResult};
 use chrono::{DateTime, Utc};
+use wallet_storage::{AuthId, TableCertificate, TableCertificateField, WalletStorageProvider};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -145,63 +146,25 @@ pub struct NewCertificate {
 /// Reference: TS acquireDirectCertificate (acquireDirectCertificate.ts lines 7-53)
 ///
 /// # Arguments
-/// * `user_id` - User ID for authentication
+/// * `storage` - Storage for saving the certificate and its fields
+/// * `auth` - Authenticated user
 /// * `vargs` - Validated certificate arguments
-/// * `storage` - Storage interface for saving certificate
 ///
 /// # Returns
 /// Certificate acquisition result
 pub async fn acquire_direct_certificate(
-    user_id: u64,
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
     vargs: ValidAcquireDirectCertificateArgs,
 ) -> WalletResult<AcquireCertificateResult> {
-    // Get current timestamp (TS line 12)
-    let now = Utc::now();
-    
-    // Determine verifier (TS line 20)
-    let verifier = if vargs.keyring_revealer == "certifier" {
-        vargs.certifier.clone()
-    } else {
-        vargs.keyring_revealer.clone()
-    };
+    let user_id = auth.user_id
+        .ok_or_else(|| WalletError::invalid_parameter("auth", "an authenticated user"))?;
     
     // Create new certificate (TS lines 13-27)
-    let mut new_cert = NewCertificate {
-        certificate_id: 0, // Will be replaced by storage insert
-        created_at: now,
-        updated_at: now,
-        user_id,
-        cert_type: vargs.cert_type.clone(),
-        subject: vargs.subject.clone(),
-        verifier,
-        serial_number: vargs.serial_number.clone(),
-        certifier: vargs.certifier.clone(),
-        revocation_outpoint: vargs.revocation_outpoint.clone(),
-        signature: vargs.signature.clone(),
-        fields: vec![],
-        is_deleted: false,
-    };
+    let new_cert = table_certificate(user_id, &vargs);
     
-    // Add fields (TS lines 28-38)
-    for (name, value) in vargs.fields.iter() {
-        let master_key = vargs.keyring_for_subject.get(name)
-            .cloned()
-            .unwrap_or_default();
-        
-        new_cert.fields.push(CertificateField {
-            certificate_id: 0, // Will be replaced by storage insert
-            created_at: now,
-            updated_at: now,
-            user_id,
-            field_name: name.clone(),
-            field_value: value.clone(),
-            master_key,
-        });
-    }
-    
-    // Insert certificate into storage (TS line 40)
-    // TODO: Integrate with actual storage
-    // let count = wallet.storage.insertCertificate(newCert).await?;
+    // Insert certificate and its fields into storage (TS lines 28-40)
+    insert_certificate_with_fields(storage, auth, &new_cert, &vargs.fields, &vargs.keyring_for_subject).await?;
     
     // Build result (TS lines 42-50)
     let result = AcquireCertificateResult {
@@ -217,6 +180,53 @@ pub async fn acquire_direct_certificate(
     Ok(result)
 }
 
+/// Storage record for a directly acquired certificate
+///
+/// The verifier is the party whose keyring the subject holds: the certifier
+/// itself when `keyring_revealer` is "certifier".
+pub fn table_certificate(user_id: i64, vargs: &ValidAcquireDirectCertificateArgs) -> TableCertificate {
+    // Determine verifier (TS line 20)
+    let verifier = if vargs.keyring_revealer == "certifier" {
+        vargs.certifier.clone()
+    } else {
+        vargs.keyring_revealer.clone()
+    };
+    
+    TableCertificate::new(
+        0, // Will be replaced by storage insert
+        user_id,
+        vargs.cert_type.clone(),
+        vargs.serial_number.clone(),
+        vargs.certifier.clone(),
+        vargs.subject.clone(),
+        vargs.revocation_outpoint.clone(),
+        vargs.signature.clone(),
+    )
+    .with_verifier(verifier)
+}
+
+/// Insert a certificate and one field record per field
+///
+/// Each field's master key is taken from `keyring`, empty when absent.
+///
+/// # Returns
+/// The new certificate ID
+pub async fn insert_certificate_with_fields(
+    storage: &mut dyn WalletStorageProvider,
+    auth: &AuthId,
+    certificate: &TableCertificate,
+    fields: &HashMap<String, String>,
+    keyring: &HashMap<String, String>,
+) -> WalletResult<i64> {
+    let certificate_id = storage.insert_certificate_auth(auth, certificate).await?;
+    for (name, value) in fields {
+        let master_key = keyring.get(name).cloned().unwrap_or_default();
+        let field = TableCertificateField::new(certificate.user_id, certificate_id, name.clone(), value.clone(), master_key);
+        storage.insert_certificate_field_auth(auth, &field).await?;
+    }
+    Ok(certificate_id)
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -225,15 +235,14 @@ pub async fn acquire_direct_certificate(
 mod tests {
     use super::*;
     
-    #[tokio::test]
-    async fn test_acquire_direct_certificate() {
+    fn vargs(keyring_revealer: &str) -> ValidAcquireDirectCertificateArgs {
         let mut fields = HashMap::new();
         fields.insert("name".to_string(), "John Doe".to_string());
         
         let mut keyring = HashMap::new();
         keyring.insert("name".to_string(), "master_key_123".to_string());
         
-        let vargs = ValidAcquireDirectCertificateArgs {
+        ValidAcquireDirectCertificateArgs {
             cert_type: "identity".to_string(),
             subject: "subject_key".to_string(),
             serial_number: "12345".to_string(),
@@ -242,16 +251,20 @@ mod tests {
             signature: "sig_data".to_string(),
             fields,
             keyring_for_subject: keyring,
-            keyring_revealer: "certifier".to_string(),
-        };
-        
-        let result = acquire_direct_certificate(1, vargs).await;
-        assert!(result.is_ok());
-        
-        let cert = result.unwrap();
-        assert_eq!(cert.cert_type, "identity");
+            keyring_revealer: keyring_revealer.to_string(),
+        }
+    }
+    
+    #[test]
+    fn test_table_certificate() {
+        let cert = table_certificate(1, &vargs("certifier"));
+        assert_eq!(cert.user_id, 1);
+        assert_eq!(cert.certificate_type, "identity");
         assert_eq!(cert.serial_number, "12345");
-        assert_eq!(cert.fields.len(), 1);
+        assert_eq!(cert.verifier.as_deref(), Some("certifier_key"));
+        
+        let cert = table_certificate(1, &vargs("revealer_key"));
+        assert_eq!(cert.verifier.as_deref(), Some("revealer_key"));
     }
     
     #[test]
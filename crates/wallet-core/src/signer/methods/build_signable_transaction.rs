@@ -121,15 +121,7 @@ pub fn build_signable_transaction(
         
         // Get locking script (TS lines 55-57)
         let locking_script = if is_change {
-            // TODO: Call makeChangeLock when BRC-29 is available (TS line 56)
-            if let Some(ds) = &out.derivation_suffix {
-                format!("changeLock_{}", ds)
-            } else {
-                return Err(WalletError::invalid_parameter(
-                    "derivationSuffix",
-                    "required for change outputs"
-                ));
-            }
+            make_change_lock(out, dctr, args, change_keys)?
         } else {
             out.locking_script.clone()
         };
@@ -330,7 +322,7 @@ fn make_change_lock(
     );
     
     // Generate locking script (TS line 182)
-    let locking_script = sabppp.lock(&change_keys.private_key, &change_keys.public_key);
+    let locking_script = sabppp.lock(&change_keys.private_key, &change_keys.public_key)?;
     
     // Convert to hex string
     Ok(hex::encode(locking_script))
@@ -365,7 +357,7 @@ mod tests {
     #[test]
     fn test_build_result_creation() {
         let result = BuildSignableTransactionResult {
-            tx: Transaction::new(1, vec![], vec![], 0),
+            tx: Transaction::with_params(1, vec![], vec![], 0),
             amount: 1000,
             pdi: vec![],
             log: String::new(),
@@ -374,4 +366,59 @@ mod tests {
         assert_eq!(result.amount, 1000);
         assert!(result.pdi.is_empty());
     }
+    
+    #[test]
+    fn test_change_outputs_use_brc29_lock() {
+        use crate::sdk::action::{StorageCreateTransactionOutput, StorageProvidedBy};
+        
+        let private_key = vec![9u8; 32];
+        let public_key = crate::crypto::derive_public_key(&private_key).unwrap();
+        let keys = KeyPair { private_key, public_key };
+        
+        let dctr = StorageCreateActionResult {
+            input_beef: None,
+            inputs: vec![],
+            outputs: vec![StorageCreateTransactionOutput {
+                vout: 0,
+                provided_by: StorageProvidedBy::Storage,
+                purpose: Some("change".to_string()),
+                derivation_suffix: Some("suffix".to_string()),
+                locking_script: String::new(),
+                satoshis: 500,
+                output_description: String::new(),
+                custom_instructions: None,
+                basket: None,
+                tags: None,
+            }],
+            no_send_change_output_vouts: None,
+            derivation_prefix: "prefix".to_string(),
+            version: 1,
+            lock_time: 0,
+            reference: "ref".to_string(),
+        };
+        let args = ValidCreateActionArgs {
+            description: "test".to_string(),
+            input_beef: None,
+            inputs: vec![],
+            outputs: vec![],
+            labels: vec![],
+            options: crate::sdk::ValidCreateActionOptions::default(),
+            is_new_tx: true,
+            is_delayed: false,
+            is_no_send: false,
+            is_sign_action: false,
+            version: 1,
+            lock_time: 0,
+            random_vals: None,
+            include_all_source_transactions: false,
+        };
+        
+        let result = build_signable_transaction(&dctr, &args, &keys, None).unwrap();
+        let expected = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string())
+            .lock(&keys.private_key, &keys.public_key)
+            .unwrap();
+        assert_eq!(result.tx.outputs[0].script_pubkey, expected);
+        assert_eq!(result.tx.outputs[0].value, 500);
+    }
 }
+
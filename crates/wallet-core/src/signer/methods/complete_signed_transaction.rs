@@ -5,7 +5,8 @@
 //! Finalizes a transaction by adding signatures to unlocking scripts
 
 use crate::sdk::errors::{WalletError, WalletResult};
-use crate::transaction::Transaction;
+use crate::beef::Beef;
+use crate::transaction::{Spend, Transaction};
 use crate::keys::KeyPair;
 use crate::utility::ScriptTemplateSABPPP;
 use super::build_signable_transaction::PendingStorageInput;
@@ -126,6 +127,8 @@ pub async fn complete_signed_transaction(
     /////////////////////
     // Insert SABPPP unlock templates for wallet signed inputs (TS lines 38-55)
     /////////////////////
+    let change_pub_key_hex = hex::encode(&change_keys.public_key);
+    let mut unlockers = Vec::with_capacity(prior.pdi.len());
     for pdi in &prior.pdi {
         // Create SABPPP template (TS lines 42-46)
         let sabppp = ScriptTemplateSABPPP::new(
@@ -134,42 +137,40 @@ pub async fn complete_signed_transaction(
         );
         
         // Get keys (TS lines 47-49)
-        let locker_priv_key = &change_keys.private_key;
-        let unlocker_pub_key = pdi.unlocker_pub_key.as_deref()
-            .unwrap_or("");
+        let unlocker_priv_key = &change_keys.private_key;
+        let locker_pub_key = pdi.unlocker_pub_key.as_deref()
+            .unwrap_or(&change_pub_key_hex);
         
-        // Get source output details (TS lines 50-51)
-        let source_satoshis = pdi.source_satoshis;
-        let locking_script = &pdi.locking_script;
-        
-        // Generate unlock template (TS line 52)
+        // Generate unlock template (TS lines 50-52)
         let unlock_template = sabppp.unlock(
-            locker_priv_key,
-            unlocker_pub_key,
-            source_satoshis,
-            locking_script,
+            unlocker_priv_key,
+            locker_pub_key,
+            pdi.source_satoshis,
+            &pdi.locking_script,
         )?;
         
-        // Set unlocking script template on input (TS lines 53-54)
-        let input = prior.tx.inputs.get_mut(pdi.vin as usize)
-            .ok_or_else(|| WalletError::invalid_parameter(
+        if pdi.vin as usize >= prior.tx.inputs.len() {
+            return Err(WalletError::invalid_parameter(
                 "pdi.vin",
-                &format!("vin {} not found in transaction", pdi.vin)
-            ))?;
+                format!("vin {} not found in transaction", pdi.vin)
+            ));
+        }
         
-        // TODO: TxInput doesn't have unlocking_script_template field
-        // This needs to be stored separately or the Transaction struct needs to be extended
-        // For now, this is a placeholder - the template will be used in actual signing
-        // input.unlocking_script_template = Some(unlock_template);
+        unlockers.push((pdi.vin as usize, unlock_template));
     }
     
     /////////////////////
     // Sign wallet signed inputs making transaction fully valid (TS lines 57-60)
     /////////////////////
-    // TODO: Transaction::sign() method needs to be implemented
-    // This would sign all inputs that have unlocking_script_templates set
-    // For now, we skip this step as templates are not yet implemented
-    // prior.tx.sign().await?;
+    // Signatures commit to every output and every input outpoint, so all
+    // scripts are computed before any are written back.
+    let mut unlocking_scripts = Vec::with_capacity(unlockers.len());
+    for (vin, unlocker) in &unlockers {
+        unlocking_scripts.push((*vin, unlocker.sign(&prior.tx, *vin)?));
+    }
+    for (vin, script) in unlocking_scripts {
+        prior.tx.inputs[vin].set_script(script);
+    }
     
     // Return signed transaction (TS line 62)
     Ok(prior.tx)
@@ -188,17 +189,38 @@ pub async fn complete_signed_transaction(
 /// # Errors
 /// Returns error if any unlocking script is invalid or if BEEF doesn't contain required transactions
 pub fn verify_unlock_scripts(txid: &str, beef: &[u8]) -> WalletResult<()> {
-    // TODO: Full implementation requires:
-    // 1. Parse BEEF to find transaction by txid (TS line 71)
-    // 2. Iterate through all inputs (TS lines 74-86)
-    // 3. Validate each input has sourceTXID and unlockingScript (TS lines 76-77)
-    // 4. Find source transaction in BEEF (TS lines 78-85)
-    // 5. Validate each unlocking script using Spend (TS lines 88-116)
-    //    - Create Spend object with all parameters
-    //    - Call spend.validate()
-    //    - Check result
+    let beef = Beef::from_binary(beef)
+        .map_err(|e| WalletError::invalid_parameter("beef", format!("valid BEEF: {}", e)))?;
+    let raw_tx = |txid: &str| {
+        beef.find_txid(txid).and_then(|btx| btx.raw_tx.as_deref()).ok_or_else(|| {
+            WalletError::invalid_parameter("beef", format!("a BEEF containing transaction {}", txid))
+        })
+    };
+    let parse = |raw: &[u8]| Transaction::from_bytes(raw).map_err(|e| WalletError::internal(e.to_string()));
     
-    // Placeholder implementation
+    let tx = parse(raw_tx(txid)?)?;
+    for (vin, input) in tx.inputs.iter().enumerate() {
+        if input.script_sig.is_empty() {
+            return Err(WalletError::invalid_parameter(
+                format!("{} input {}", txid, vin),
+                "an unlocking script",
+            ));
+        }
+        let source = parse(raw_tx(&input.prev_out.txid)?)?;
+        let output = source.outputs.get(input.prev_out.vout as usize).ok_or_else(|| {
+            WalletError::invalid_parameter(
+                format!("{} input {}", txid, vin),
+                format!("an existing output {}.{}", input.prev_out.txid, input.prev_out.vout),
+            )
+        })?;
+        
+        Spend::new(&tx, vin, &output.script_pubkey, output.value)
+            .validate()
+            .map_err(|e| WalletError::invalid_parameter(
+                format!("{} input {}", txid, vin),
+                format!("a valid unlocking script ({})", e),
+            ))?;
+    }
     Ok(())
 }
 
@@ -223,39 +245,51 @@ mod tests {
         assert_eq!(deserialized.sequence_number, Some(0xfffffffe));
     }
     
-    #[test]
-    fn test_pending_sign_action_creation() {
-        use crate::sdk::StorageCreateActionResult;
-        use crate::sdk::ValidCreateActionArgs;
-        
-        let dcr = StorageCreateActionResult {
-            txid: None,
-            reference: "ref123".to_string(),
+    fn test_dcr() -> crate::sdk::StorageCreateActionResult {
+        crate::sdk::StorageCreateActionResult {
+            input_beef: None,
+            inputs: vec![],
+            outputs: vec![],
+            no_send_change_output_vouts: None,
             derivation_prefix: "prefix".to_string(),
             version: 1,
             lock_time: 0,
-            inputs: vec![],
-            outputs: vec![],
-            log: String::new(),
-        };
-        
-        let args = ValidCreateActionArgs {
+            reference: "ref123".to_string(),
+        }
+    }
+    
+    fn test_args() -> ValidCreateActionArgs {
+        ValidCreateActionArgs {
             description: "test".to_string(),
+            input_beef: None,
             inputs: vec![],
             outputs: vec![],
-            version: Some(1),
-            lock_time: Some(0),
-            options: None,
             labels: vec![],
+            options: crate::sdk::ValidCreateActionOptions::default(),
+            is_new_tx: true,
+            is_delayed: false,
+            is_no_send: false,
             is_sign_action: false,
-            input_beef: None,
-        };
-        
+            version: 1,
+            lock_time: 0,
+            random_vals: None,
+            include_all_source_transactions: false,
+        }
+    }
+    
+    fn test_keys() -> KeyPair {
+        let private_key = vec![7u8; 32];
+        let public_key = crate::crypto::derive_public_key(&private_key).unwrap();
+        KeyPair { private_key, public_key }
+    }
+    
+    #[test]
+    fn test_pending_sign_action_creation() {
         let psa = PendingSignAction {
             reference: "ref123".to_string(),
-            dcr,
-            args,
-            tx: Transaction::new(1, vec![], vec![], 0),
+            dcr: test_dcr(),
+            args: test_args(),
+            tx: Transaction::with_params(1, vec![], vec![], 0),
             amount: 1000,
             pdi: vec![],
         };
@@ -266,48 +300,131 @@ mod tests {
     
     #[tokio::test]
     async fn test_complete_signed_transaction_basic() {
-        use crate::sdk::StorageCreateActionResult;
-        use crate::sdk::ValidCreateActionArgs;
+        let psa = PendingSignAction {
+            reference: "ref123".to_string(),
+            dcr: test_dcr(),
+            args: test_args(),
+            tx: Transaction::with_params(1, vec![], vec![], 0),
+            amount: 1000,
+            pdi: vec![],
+        };
+        
+        let spends = HashMap::new();
+        let result = complete_signed_transaction(psa, spends, &test_keys()).await;
+        assert!(result.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_complete_signed_transaction_signs_change_inputs() {
+        use crate::transaction::{OutPoint, TxInput, TxOutput};
         
-        let dcr = StorageCreateActionResult {
-            txid: None,
+        // A change output locked to our own change key (as makeChangeLock does)
+        let keys = test_keys();
+        let sabppp = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string());
+        let locking_script = sabppp.lock(&keys.private_key, &keys.public_key).unwrap();
+        
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("cc".repeat(32), 0)));
+        tx.add_output(TxOutput::new(900, vec![0x6a]));
+        
+        let psa = PendingSignAction {
             reference: "ref123".to_string(),
-            derivation_prefix: "prefix".to_string(),
-            version: 1,
-            lock_time: 0,
-            inputs: vec![],
-            outputs: vec![],
-            log: String::new(),
+            dcr: test_dcr(),
+            args: test_args(),
+            tx,
+            amount: 1000,
+            pdi: vec![PendingStorageInput {
+                vin: 0,
+                derivation_prefix: "prefix".to_string(),
+                derivation_suffix: "suffix".to_string(),
+                unlocker_pub_key: None,
+                source_satoshis: 1000,
+                locking_script: hex::encode(&locking_script),
+            }],
         };
         
-        let args = ValidCreateActionArgs {
-            description: "test".to_string(),
-            inputs: vec![],
-            outputs: vec![],
-            version: Some(1),
-            lock_time: Some(0),
-            options: None,
-            labels: vec![],
-            is_sign_action: false,
-            input_beef: None,
+        let signed = complete_signed_transaction(psa, HashMap::new(), &keys).await.unwrap();
+        let script_sig = &signed.inputs[0].script_sig;
+        assert!(!script_sig.is_empty());
+        assert!(script_sig.len() <= ScriptTemplateSABPPP::UNLOCK_LENGTH);
+        
+        // The pushed public key must hash to the locking script's pubkey hash
+        let sig_len = script_sig[0] as usize;
+        let pubkey = &script_sig[2 + sig_len..];
+        assert_eq!(crate::crypto::hash160(pubkey), locking_script[3..23].to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_complete_signed_transaction_rejects_missing_vin() {
+        let psa = PendingSignAction {
+            reference: "ref123".to_string(),
+            dcr: test_dcr(),
+            args: test_args(),
+            tx: Transaction::new(),
+            amount: 0,
+            pdi: vec![PendingStorageInput {
+                vin: 3,
+                derivation_prefix: "prefix".to_string(),
+                derivation_suffix: "suffix".to_string(),
+                unlocker_pub_key: None,
+                source_satoshis: 1000,
+                locking_script: "76a914".to_string(),
+            }],
         };
         
+        assert!(complete_signed_transaction(psa, HashMap::new(), &test_keys()).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_verify_unlock_scripts() {
+        use crate::transaction::{OutPoint, TxInput, TxOutput};
+        
+        let keys = test_keys();
+        let sabppp = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string());
+        let locking_script = sabppp.lock(&keys.private_key, &keys.public_key).unwrap();
+        let mut source = Transaction::new();
+        source.add_input(TxInput::new(OutPoint::new("dd".repeat(32), 0)));
+        source.add_output(TxOutput::new(1000, locking_script.clone()));
+        let source_txid = source.txid().unwrap();
+        
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(source_txid, 0)));
+        tx.add_output(TxOutput::new(900, vec![0x6a]));
         let psa = PendingSignAction {
             reference: "ref123".to_string(),
-            dcr,
-            args,
-            tx: Transaction::new(1, vec![], vec![], 0),
+            dcr: test_dcr(),
+            args: test_args(),
+            tx,
             amount: 1000,
-            pdi: vec![],
+            pdi: vec![PendingStorageInput {
+                vin: 0,
+                derivation_prefix: "prefix".to_string(),
+                derivation_suffix: "suffix".to_string(),
+                unlocker_pub_key: None,
+                source_satoshis: 1000,
+                locking_script: hex::encode(&locking_script),
+            }],
         };
+        let signed = complete_signed_transaction(psa, HashMap::new(), &keys).await.unwrap();
         
-        let spends = HashMap::new();
-        let keys = KeyPair {
-            private_key: "test_priv".to_string(),
-            public_key: "test_pub".to_string(),
+        let beef_of = |tx: &Transaction| {
+            let mut beef = Beef::new_v2();
+            beef.merge_raw_tx(&source.serialize().unwrap()).unwrap();
+            let btx = beef.merge_raw_tx(&tx.serialize().unwrap()).unwrap();
+            (btx.txid.clone(), beef.to_binary_atomic(&btx.txid).unwrap())
         };
+        let (txid, beef) = beef_of(&signed);
+        verify_unlock_scripts(&txid, &beef).unwrap();
         
-        let result = complete_signed_transaction(psa, spends, &keys).await;
-        assert!(result.is_ok());
+        // A signature over a different transaction does not unlock this one
+        let mut tampered = signed.clone();
+        tampered.outputs[0].value = 800;
+        let (txid, beef) = beef_of(&tampered);
+        assert!(verify_unlock_scripts(&txid, &beef).is_err());
+        
+        // Source transactions must be in the BEEF
+        let mut beef = Beef::new_v2();
+        let btx = beef.merge_raw_tx(&signed.serialize().unwrap()).unwrap();
+        assert!(verify_unlock_scripts(&btx.txid, &beef.to_binary().unwrap()).is_err());
     }
 }
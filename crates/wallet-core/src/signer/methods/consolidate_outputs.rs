@@ -0,0 +1,280 @@
+//! Signer Consolidate Outputs
+//!
+//! Merges many small change outputs of the `default` basket into fewer,
+//! larger ones with a transaction paying back to the wallet.
+//!
+//! 1. **Select** - spendable change below the basket's `minimumDesiredUTXOValue`,
+//!    smallest first, skipping outputs worth less than the fee to spend them
+//! 2. **Fee limit** - drops the largest selected outputs until the estimated
+//!    fee is within `maxFeeSatoshis`
+//! 3. **Storage** - creates the transaction and its change outputs, steered
+//!    towards the basket's `numberOfDesiredUTXOs`
+//! 4. **Sign and process** - as createAction does for a new action
+//!
+//! There is no TypeScript counterpart; the action is built the same way as a
+//! createAction with neither inputs nor outputs ("remix change").
+
+use crate::keys::KeyPair;
+use crate::methods::fee_model::{
+    fee_for_size, transaction_input_size, transaction_size, validate_storage_fee_model,
+    StorageFeeModel, CHANGE_LOCKING_SCRIPT_LENGTH, CHANGE_UNLOCKING_SCRIPT_LENGTH,
+};
+use crate::methods::create_consolidation_action;
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{
+    validate_integer, validate_optional_integer, ValidCreateActionArgs, ValidCreateActionOptions,
+    ValidProcessActionOptions, WalletError,
+};
+use crate::services::{Broadcaster, UtxoStatusProvider};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wallet_storage::{
+    AuthId, FindOutputsArgs, PartialOutput, TableOutput, TransactionStatus, WalletStorageProvider,
+};
+
+use super::build_signable_transaction::build_signable_transaction;
+use super::complete_signed_transaction::PendingSignAction;
+use super::create_action::sign_and_process;
+
+/// Default most outputs spent by one consolidation
+pub const DEFAULT_CONSOLIDATION_MAX_INPUTS: i64 = 100;
+
+/// Most outputs a consolidation may spend
+pub const MAX_CONSOLIDATION_INPUTS: i64 = 1000;
+
+/// Description of consolidation actions
+const CONSOLIDATION_DESCRIPTION: &str = "consolidate change outputs";
+
+/// Consolidate outputs arguments
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateOutputsArgs {
+    /// Outputs worth less than this are merged (default: basket `minimumDesiredUTXOValue`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_satoshis: Option<i64>,
+
+    /// Most outputs to spend (default 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_inputs: Option<i64>,
+
+    /// Most fee to pay (default: whatever the fee model asks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_satoshis: Option<i64>,
+
+    /// Let the monitor broadcast the transaction later (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_delayed_broadcast: Option<bool>,
+}
+
+/// Validated consolidate outputs arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidConsolidateOutputsArgs {
+    pub max_output_satoshis: Option<i64>,
+    pub max_inputs: usize,
+    pub max_fee_satoshis: Option<i64>,
+    pub accept_delayed_broadcast: bool,
+}
+
+/// Consolidate outputs result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateOutputsResult {
+    /// Consolidation txid, absent when there was nothing worth merging
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+
+    /// Number of change outputs spent
+    pub outputs_spent: usize,
+
+    /// Number of change outputs created
+    pub outputs_created: usize,
+
+    /// Total value of the outputs spent
+    pub satoshis: i64,
+
+    /// Fee paid
+    pub fee: i64,
+}
+
+/// Validate consolidate outputs arguments
+pub fn validate_consolidate_outputs_args(args: &ConsolidateOutputsArgs) -> WalletResult<ValidConsolidateOutputsArgs> {
+    Ok(ValidConsolidateOutputsArgs {
+        max_output_satoshis: validate_optional_integer(args.max_output_satoshis, "maxOutputSatoshis", Some(1), None)?,
+        max_inputs: validate_integer(
+            args.max_inputs,
+            "maxInputs",
+            Some(DEFAULT_CONSOLIDATION_MAX_INPUTS),
+            Some(2),
+            Some(MAX_CONSOLIDATION_INPUTS),
+        )? as usize,
+        max_fee_satoshis: validate_optional_integer(args.max_fee_satoshis, "maxFeeSatoshis", Some(0), None)?,
+        accept_delayed_broadcast: args.accept_delayed_broadcast.unwrap_or(true),
+    })
+}
+
+/// Merge small change outputs of the `default` basket
+///
+/// Returns a result without a txid when fewer than two outputs are worth
+/// merging within the fee limit.
+pub async fn consolidate_outputs(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    auth: &AuthId,
+    change_keys: &KeyPair,
+    vargs: ValidConsolidateOutputsArgs,
+) -> WalletResult<ConsolidateOutputsResult> {
+    let user_id = auth.user_id
+        .ok_or_else(|| WalletError::invalid_parameter("auth", "an authenticated user"))?;
+    let basket = storage.find_or_insert_output_basket(user_id, "default").await?;
+    let fee_model = validate_storage_fee_model(Some(&storage.get_fee_model()))?;
+
+    let candidates = storage.find_outputs_auth(auth, &FindOutputsArgs {
+        user_id,
+        since: None,
+        paged: None,
+        order_descending: None,
+        partial: Some(PartialOutput {
+            basket_id: Some(basket.basket_id),
+            spendable: Some(true),
+            change: Some(true),
+            transaction_id: None,
+            txid: None,
+        }),
+        no_script: Some(true),
+        tx_status: Some(vec![TransactionStatus::Completed, TransactionStatus::Unproven]),
+    }).await?;
+
+    let max_output_satoshis = vargs.max_output_satoshis.unwrap_or(basket.minimum_desired_utxo_value);
+    let selected = select_consolidation_inputs(candidates, max_output_satoshis, &vargs, &fee_model);
+    if selected.len() < 2 {
+        return Ok(ConsolidateOutputsResult::default());
+    }
+
+    let outputs_spent = selected.len();
+    let satoshis = selected.iter().map(|o| o.satoshis).sum();
+    let args = consolidation_action_args(&vargs);
+    let dcr = create_consolidation_action(storage, auth, args.clone(), selected).await?;
+    let built = build_signable_transaction(&dcr, &args, change_keys, dcr.input_beef.as_deref())?;
+    let outputs_created = dcr.outputs.len();
+    let fee = satoshis - dcr.outputs.iter().map(|o| o.satoshis).sum::<i64>();
+
+    let prior = PendingSignAction {
+        reference: dcr.reference.clone(),
+        dcr,
+        args,
+        tx: built.tx,
+        amount: built.amount,
+        pdi: built.pdi,
+    };
+    let options = prior.args.options.process_options.clone();
+    let (txid, _, _) = sign_and_process(storage, broadcaster, utxo_status, auth, change_keys, prior, HashMap::new(), &options).await?;
+
+    Ok(ConsolidateOutputsResult {
+        txid: Some(txid),
+        outputs_spent,
+        outputs_created,
+        satoshis,
+        fee,
+    })
+}
+
+/// Outputs to consolidate, smallest first
+///
+/// Skips outputs of `max_output_satoshis` or more and those not worth the
+/// fee for their own input, then drops the largest until the fee for
+/// spending the rest into a single output is within `max_fee_satoshis`.
+fn select_consolidation_inputs(
+    mut candidates: Vec<TableOutput>,
+    max_output_satoshis: i64,
+    vargs: &ValidConsolidateOutputsArgs,
+    fee_model: &StorageFeeModel,
+) -> Vec<TableOutput> {
+    let input_fee = fee_for_size(fee_model, transaction_input_size(CHANGE_UNLOCKING_SCRIPT_LENGTH));
+    candidates.retain(|o| o.satoshis < max_output_satoshis && o.satoshis > input_fee);
+    candidates.sort_by_key(|o| o.satoshis);
+    candidates.truncate(vargs.max_inputs);
+
+    if let Some(max_fee) = vargs.max_fee_satoshis {
+        while !candidates.is_empty() {
+            let size = transaction_size(
+                &vec![CHANGE_UNLOCKING_SCRIPT_LENGTH; candidates.len()],
+                &[CHANGE_LOCKING_SCRIPT_LENGTH],
+            );
+            if fee_for_size(fee_model, size) <= max_fee {
+                break;
+            }
+            candidates.pop();
+        }
+    }
+    candidates
+}
+
+/// Action spending nothing but the consolidated change
+fn consolidation_action_args(vargs: &ValidConsolidateOutputsArgs) -> ValidCreateActionArgs {
+    let options = ValidCreateActionOptions {
+        process_options: ValidProcessActionOptions {
+            accept_delayed_broadcast: vargs.accept_delayed_broadcast,
+            ..ValidProcessActionOptions::default()
+        },
+        ..ValidCreateActionOptions::default()
+    };
+    ValidCreateActionArgs {
+        description: CONSOLIDATION_DESCRIPTION.to_string(),
+        input_beef: None,
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        labels: Vec::new(),
+        is_new_tx: true,
+        is_delayed: options.process_options.accept_delayed_broadcast,
+        is_no_send: false,
+        is_sign_action: false,
+        version: options.version,
+        lock_time: options.lock_time,
+        options,
+        random_vals: None,
+        include_all_source_transactions: false,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_storage::StorageProvidedBy;
+
+    fn change(output_id: i64, satoshis: i64) -> TableOutput {
+        TableOutput::new(output_id, 1, 1, true, true, "change", 0, satoshis, StorageProvidedBy::Storage, "change", "P2PKH")
+    }
+
+    #[test]
+    fn test_validate_consolidate_outputs_args() {
+        let vargs = validate_consolidate_outputs_args(&ConsolidateOutputsArgs::default()).unwrap();
+        assert_eq!(vargs.max_inputs, DEFAULT_CONSOLIDATION_MAX_INPUTS as usize);
+        assert!(vargs.accept_delayed_broadcast);
+
+        let one_input = ConsolidateOutputsArgs { max_inputs: Some(1), ..Default::default() };
+        assert!(validate_consolidate_outputs_args(&one_input).is_err());
+        let negative_fee = ConsolidateOutputsArgs { max_fee_satoshis: Some(-1), ..Default::default() };
+        assert!(validate_consolidate_outputs_args(&negative_fee).is_err());
+    }
+
+    #[test]
+    fn test_select_consolidation_inputs() {
+        let fee_model = StorageFeeModel { model: "sat/kb".to_string(), value: Some(100.0) };
+        let candidates = vec![change(1, 900), change(2, 5), change(3, 40_000), change(4, 300), change(5, 20)];
+        let mut vargs = validate_consolidate_outputs_args(&ConsolidateOutputsArgs::default()).unwrap();
+
+        // 40_000 is not small, 5 costs more than its input fee (15 sats)
+        let selected = select_consolidation_inputs(candidates.clone(), 1000, &vargs, &fee_model);
+        assert_eq!(selected.iter().map(|o| o.output_id).collect::<Vec<_>>(), vec![5, 4, 1]);
+
+        // Three inputs and one output are 488 bytes (49 sats); two are 340 bytes
+        vargs.max_fee_satoshis = Some(40);
+        let selected = select_consolidation_inputs(candidates, 1000, &vargs, &fee_model);
+        assert_eq!(selected.iter().map(|o| o.output_id).collect::<Vec<_>>(), vec![5, 4]);
+    }
+}
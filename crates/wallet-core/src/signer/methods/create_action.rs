@@ -0,0 +1,340 @@
+//! Signer Create Action
+//!
+//! **Reference**: TypeScript `src/signer/methods/createAction.ts`
+//!
+//! Drives a new action through storage, signing and processing:
+//!
+//! 1. **Storage createAction** - allocates change and funding inputs
+//! 2. **Build** - assembles the unsigned transaction
+//! 3. **Sign** - unless the caller still has to supply unlocking scripts,
+//!    in which case a signable transaction is returned for signAction
+//! 4. **Process** - stores the signed transaction and shares it with the network
+
+use crate::beef::Beef;
+use crate::keys::KeyPair;
+use crate::methods::{create_action as storage_create_action, process_action};
+use crate::sdk::action::{OutPoint, ValidCreateActionInput, ValidCreateActionOutput};
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::validation_args::{validate_create_action_input, validate_create_action_output};
+use crate::sdk::{
+    validate_basket, validate_label, validate_outpoint_string, validate_string_length, CreateActionArgs,
+    CreateActionResult, SignableTransaction, StorageProcessActionArgs,
+    StorageProcessActionResults, ValidCreateActionArgs, ValidCreateActionOptions,
+    ValidProcessActionOptions,
+};
+use crate::services::{Broadcaster, UtxoStatusProvider};
+use crate::transaction::Transaction;
+use std::collections::HashMap;
+use wallet_storage::{AuthId, WalletStorageProvider};
+
+use super::build_signable_transaction::build_signable_transaction;
+use super::complete_signed_transaction::{complete_signed_transaction, PendingSignAction, SignActionSpend};
+
+/// Validate BRC-100 create action arguments
+///
+/// Reference: TS validateCreateActionArgs
+pub fn validate_create_action_args(args: &CreateActionArgs) -> WalletResult<ValidCreateActionArgs> {
+    let inputs = args.inputs.iter().flatten()
+        .map(|i| {
+            let valid = validate_create_action_input(
+                &i.outpoint,
+                &i.input_description,
+                i.sequence_number,
+                i.unlocking_script.as_deref(),
+                i.unlocking_script_length.map(|l| l as usize),
+            )?;
+            Ok(ValidCreateActionInput {
+                outpoint: OutPoint { txid: valid.outpoint.txid, vout: valid.outpoint.vout },
+                input_description: valid.input_description,
+                sequence_number: valid.sequence_number,
+                unlocking_script: valid.unlocking_script,
+                unlocking_script_length: Some(valid.unlocking_script_length as u32),
+                satoshis: None,
+                locking_script: None,
+            })
+        })
+        .collect::<WalletResult<Vec<_>>>()?;
+    let outputs = args.outputs.iter().flatten()
+        .map(|o| {
+            let valid = validate_create_action_output(
+                &o.locking_script,
+                o.satoshis,
+                &o.output_description,
+                o.basket.as_deref(),
+                o.custom_instructions.as_deref(),
+                o.tags.as_deref().unwrap_or_default(),
+            )?;
+            Ok(ValidCreateActionOutput {
+                locking_script: valid.locking_script,
+                satoshis: valid.satoshis,
+                output_description: valid.output_description,
+                custom_instructions: valid.custom_instructions,
+                basket: valid.basket,
+                tags: Some(valid.tags),
+            })
+        })
+        .collect::<WalletResult<Vec<_>>>()?;
+    let labels = args.labels.iter().flatten()
+        .map(|l| validate_label(l))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let options = validate_create_action_options(args.options.clone().unwrap_or_default())?;
+
+    let is_send_with = !options.process_options.send_with.is_empty();
+    let is_remix_change = !is_send_with && inputs.is_empty() && outputs.is_empty();
+    let is_new_tx = is_remix_change || !inputs.is_empty() || !outputs.is_empty();
+    let is_sign_action = is_new_tx
+        && (!options.sign_and_process || inputs.iter().any(|i| i.unlocking_script.is_none()));
+    let version = args.version.unwrap_or(1);
+    let lock_time = args.lock_time.unwrap_or(0);
+
+    Ok(ValidCreateActionArgs {
+        description: validate_string_length(&args.description, "description", Some(5), Some(2000))?,
+        input_beef: args.input_beef.clone(),
+        inputs,
+        outputs,
+        labels,
+        is_new_tx,
+        is_delayed: options.process_options.accept_delayed_broadcast,
+        is_no_send: options.process_options.no_send,
+        is_sign_action,
+        version,
+        lock_time,
+        options: ValidCreateActionOptions { version, lock_time, ..options },
+        random_vals: None,
+        include_all_source_transactions: false,
+    })
+}
+
+/// Reference: TS validateCreateActionOptions
+fn validate_create_action_options(options: crate::sdk::CreateActionOptions) -> WalletResult<ValidCreateActionOptions> {
+    let no_send_change = options.no_send_change
+        .map(|outpoints| outpoints.iter()
+            .map(|o| {
+                let o = validate_outpoint_string(o, "noSendChange")?;
+                let (txid, vout) = o.split_once('.').expect("validated outpoint");
+                Ok(OutPoint { txid: txid.to_string(), vout: vout.parse().expect("validated vout") })
+            })
+            .collect::<WalletResult<Vec<_>>>())
+        .transpose()?;
+    let send_with = options.send_with.unwrap_or_default().iter()
+        .map(|txid| crate::sdk::validate_hex_string(txid, "sendWith", Some(64), Some(64)))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let known_txids = options.known_txids.unwrap_or_default().iter()
+        .map(|txid| crate::sdk::validate_hex_string(txid, "knownTxids", Some(64), Some(64)))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let return_txid_only = options.return_txid_only.unwrap_or(false);
+    let defaults = ValidCreateActionOptions::default();
+
+    Ok(ValidCreateActionOptions {
+        process_options: ValidProcessActionOptions {
+            accept_delayed_broadcast: options.accept_delayed_broadcast.unwrap_or(true),
+            return_txid_only,
+            no_send: options.no_send.unwrap_or(false),
+            send_with,
+        },
+        sign_and_process: options.sign_and_process.unwrap_or(true),
+        trust_self: options.trust_self,
+        known_txids,
+        randomize_outputs: options.randomize_outputs.unwrap_or(true),
+        no_send_change,
+        change_basket: options.change_basket.as_deref().map(validate_basket).transpose()?,
+        return_txid_only,
+        ..defaults
+    })
+}
+
+/// Create an action, signing and processing it when possible
+///
+/// Reference: TS signer createAction
+///
+/// Returns the result and, when the caller must still call signAction, the
+/// pending action to keep until then. Actions with neither inputs nor outputs
+/// only share their `sendWith` batch.
+pub async fn create_action(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    auth: &AuthId,
+    change_keys: &KeyPair,
+    vargs: ValidCreateActionArgs,
+    originator: Option<&str>,
+) -> WalletResult<(CreateActionResult, Option<PendingSignAction>)> {
+    let mut result = CreateActionResult::default();
+    if !vargs.is_new_tx {
+        let options = &vargs.options.process_options;
+        let processed = process_action(storage, broadcaster, utxo_status, auth, StorageProcessActionArgs {
+            is_new_tx: false,
+            is_send_with: !options.send_with.is_empty(),
+            is_no_send: options.no_send,
+            is_delayed: options.accept_delayed_broadcast,
+            reference: None,
+            txid: None,
+            raw_tx: None,
+            send_with: options.send_with.clone(),
+            log: None,
+        }).await?;
+        result.send_with_results = processed.send_with_results;
+        return Ok((result, None));
+    }
+
+    let mut storage_args = vargs.clone();
+    for input in &mut storage_args.inputs {
+        input.unlocking_script = None;
+    }
+    let dcr = storage_create_action(storage, auth, storage_args, originator.map(str::to_string)).await?;
+    let built = build_signable_transaction(&dcr, &vargs, change_keys, dcr.input_beef.as_deref())?;
+
+    let prior = PendingSignAction {
+        reference: dcr.reference.clone(),
+        dcr,
+        args: vargs,
+        tx: built.tx,
+        amount: built.amount,
+        pdi: built.pdi,
+    };
+
+    if prior.args.is_sign_action {
+        result.signable_transaction = Some(SignableTransaction {
+            tx: atomic_beef(&prior.tx, prior.dcr.input_beef.as_deref())?,
+            reference: prior.reference.clone(),
+        });
+        return Ok((result, Some(prior)));
+    }
+
+    let options = prior.args.options.process_options.clone();
+    let no_send_change_vouts = prior.dcr.no_send_change_output_vouts.clone();
+    let (txid, tx, processed) = sign_and_process(storage, broadcaster, utxo_status, auth, change_keys, prior, HashMap::new(), &options).await?;
+
+    if options.no_send {
+        result.no_send_change = no_send_change_vouts
+            .map(|vouts| vouts.iter().map(|vout| format!("{}.{}", txid, vout)).collect());
+    }
+    result.txid = Some(txid);
+    result.tx = tx;
+    result.send_with_results = processed.send_with_results;
+    Ok((result, None))
+}
+
+/// Complete a pending action's signatures and process it
+///
+/// Reference: TS signer processAction
+///
+/// Returns the txid, its AtomicBEEF unless `returnTXIDOnly`, and the process results.
+/// A broadcast rejected as a double spend is returned as `WERR_DOUBLE_SPEND`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn sign_and_process(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    auth: &AuthId,
+    change_keys: &KeyPair,
+    prior: PendingSignAction,
+    spends: HashMap<u32, SignActionSpend>,
+    options: &ValidProcessActionOptions,
+) -> WalletResult<(String, Option<Vec<u8>>, StorageProcessActionResults)> {
+    let input_beef = prior.dcr.input_beef.clone();
+    let reference = prior.reference.clone();
+    let tx = complete_signed_transaction(prior, spends, change_keys).await?;
+    let txid = tx.txid().map_err(|e| WalletError::internal(e.to_string()))?;
+    let raw_tx = tx.serialize().map_err(|e| WalletError::internal(e.to_string()))?;
+    let beef = if options.return_txid_only { None } else { Some(atomic_beef(&tx, input_beef.as_deref())?) };
+
+    let is_send_with = !options.send_with.is_empty();
+    let processed = process_action(storage, broadcaster, utxo_status, auth, StorageProcessActionArgs {
+        is_new_tx: true,
+        is_send_with,
+        is_no_send: options.no_send,
+        is_delayed: options.accept_delayed_broadcast,
+        reference: Some(reference),
+        txid: Some(txid.clone()),
+        raw_tx: Some(raw_tx),
+        send_with: options.send_with.clone(),
+        log: None,
+    }).await?;
+    let double_spend = processed.not_delayed_results.iter().flatten()
+        .find_map(|r| r.double_spend.clone().filter(|ds| ds.txid == txid));
+    if let Some(double_spend) = double_spend {
+        return Err(double_spend.into());
+    }
+    Ok((txid, beef, processed))
+}
+
+/// AtomicBEEF for `tx` with the source transactions from `input_beef`
+fn atomic_beef(tx: &Transaction, input_beef: Option<&[u8]>) -> WalletResult<Vec<u8>> {
+    let to_wallet_error = |e: crate::beef::BeefError| WalletError::internal(e.to_string());
+    let mut beef = Beef::new_v2();
+    if let Some(input_beef) = input_beef {
+        beef.merge_beef(input_beef).map_err(to_wallet_error)?;
+    }
+    let raw_tx = tx.serialize().map_err(|e| WalletError::internal(e.to_string()))?;
+    let btx = beef.merge_raw_tx(&raw_tx).map_err(to_wallet_error)?;
+    beef.to_binary_atomic(&btx.txid).map_err(to_wallet_error)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: serde_json::Value) -> CreateActionArgs {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_create_action_args_defaults() {
+        let vargs = validate_create_action_args(&args(json!({
+            "description": "pay a friend",
+            "outputs": [{"lockingScript": "51", "satoshis": 1000, "outputDescription": "payment"}],
+            "labels": ["Payments"],
+        }))).unwrap();
+
+        assert!(vargs.is_new_tx);
+        assert!(!vargs.is_sign_action);
+        assert!(vargs.is_delayed);
+        assert!(!vargs.is_no_send);
+        assert_eq!(vargs.version, 1);
+        assert_eq!(vargs.labels, vec!["payments"]);
+        assert!(vargs.options.randomize_outputs);
+    }
+
+    #[test]
+    fn test_validate_create_action_args_sign_action() {
+        let txid = "aa".repeat(32);
+        let vargs = validate_create_action_args(&args(json!({
+            "description": "spend a token",
+            "inputs": [{"outpoint": format!("{}.0", txid), "inputDescription": "token input", "unlockingScriptLength": 73}],
+            "options": {"noSend": true, "acceptDelayedBroadcast": false},
+        }))).unwrap();
+
+        assert!(vargs.is_sign_action);
+        assert!(vargs.is_no_send);
+        assert!(!vargs.is_delayed);
+        assert_eq!(vargs.inputs[0].sequence_number, 0xffffffff);
+        assert_eq!(vargs.inputs[0].unlocking_script_length, Some(73));
+    }
+
+    #[test]
+    fn test_validate_create_action_args_rejects_bad_values() {
+        assert!(validate_create_action_args(&args(json!({"description": "abc"}))).is_err());
+        assert!(validate_create_action_args(&args(json!({
+            "description": "bad output",
+            "outputs": [{"lockingScript": "zz", "satoshis": 1, "outputDescription": "payment"}],
+        }))).is_err());
+        assert!(validate_create_action_args(&args(json!({
+            "description": "bad send with",
+            "options": {"sendWith": ["abc"]},
+        }))).is_err());
+        assert!(validate_create_action_args(&args(json!({
+            "description": "bad known txid",
+            "options": {"knownTxids": ["abc"]},
+        }))).is_err());
+        assert!(validate_create_action_args(&args(json!({
+            "description": "bad input",
+            "inputs": [{"outpoint": "abc.0", "inputDescription": "token input", "unlockingScriptLength": 73}],
+        }))).is_err());
+    }
+}
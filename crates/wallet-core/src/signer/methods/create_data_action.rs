@@ -0,0 +1,147 @@
+//! Signer Create Data Action
+//!
+//! Stores application data on chain in OP_FALSE OP_RETURN outputs, as a
+//! createAction with one zero-satoshi output per chunk of data. Storage
+//! funds it from change like any other action, at the fee model's rate for
+//! the whole serialized size, so the data pays for its bytes.
+//!
+//! Data over the per-output limit is split across outputs, which are not
+//! randomized: chunk `n` is output `n` and change follows.
+//!
+//! There is no TypeScript counterpart; apps build the outputs themselves.
+
+use crate::methods::fee_model::{
+    fee_for_size, transaction_size, CHANGE_LOCKING_SCRIPT_LENGTH, CHANGE_UNLOCKING_SCRIPT_LENGTH,
+};
+use crate::sdk::errors::WalletResult;
+use crate::sdk::{CreateActionArgs, CreateActionOptions, CreateActionOutput, WalletError};
+use crate::transaction::Script;
+use wallet_storage::StorageFeeModel;
+
+/// Default most data bytes in one output
+///
+/// Miners set their own data carrier policy; outputs up to this size are
+/// widely relayed.
+pub const DEFAULT_MAX_DATA_OUTPUT_BYTES: usize = 100_000;
+
+/// Description of data actions
+const DATA_ACTION_DESCRIPTION: &str = "store data";
+
+/// Description of each data output
+const DATA_OUTPUT_DESCRIPTION: &str = "data output";
+
+/// OP_FALSE OP_RETURN scripts holding `data`, at most `max_output_bytes` each
+pub fn data_output_scripts(data: &[u8], max_output_bytes: usize) -> Vec<Script> {
+    data.chunks(max_output_bytes.max(1)).map(Script::data_output_script).collect()
+}
+
+/// createAction arguments storing `data`, labelled `labels`
+///
+/// Errors if `data` is empty or `max_output_bytes` is zero. Labels are
+/// validated with the rest of the arguments by createAction.
+pub fn data_action_args(data: &[u8], labels: &[String], max_output_bytes: usize) -> WalletResult<CreateActionArgs> {
+    if data.is_empty() {
+        return Err(WalletError::invalid_parameter("data", "at least one byte"));
+    }
+    if max_output_bytes == 0 {
+        return Err(WalletError::invalid_parameter("maxOutputBytes", "at least one byte"));
+    }
+    let outputs = data_output_scripts(data, max_output_bytes)
+        .into_iter()
+        .map(|script| CreateActionOutput {
+            locking_script: script.to_hex(),
+            satoshis: 0,
+            output_description: DATA_OUTPUT_DESCRIPTION.to_string(),
+            basket: None,
+            custom_instructions: None,
+            tags: None,
+        })
+        .collect();
+    Ok(CreateActionArgs {
+        description: DATA_ACTION_DESCRIPTION.to_string(),
+        input_beef: None,
+        inputs: None,
+        outputs: Some(outputs),
+        lock_time: None,
+        version: None,
+        labels: (!labels.is_empty()).then(|| labels.to_vec()),
+        options: Some(CreateActionOptions {
+            randomize_outputs: Some(false),
+            ..Default::default()
+        }),
+    })
+}
+
+/// Fee the action storing `data` is expected to pay under `fee_model`
+///
+/// Assumes it is funded by one change input and returns one change output;
+/// each further change input adds the fee for its own size.
+pub fn estimate_data_action_fee(data: &[u8], max_output_bytes: usize, fee_model: &StorageFeeModel) -> i64 {
+    let locking_script_sizes: Vec<usize> = data_output_scripts(data, max_output_bytes)
+        .iter()
+        .map(Script::len)
+        .chain([CHANGE_LOCKING_SCRIPT_LENGTH])
+        .collect();
+    fee_for_size(fee_model, transaction_size(&[CHANGE_UNLOCKING_SCRIPT_LENGTH], &locking_script_sizes))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::methods::validate_create_action_args;
+
+    #[test]
+    fn test_data_action_args() {
+        let data = vec![0xabu8; 250];
+        let args = data_action_args(&data, &["notes".to_string()], 100).unwrap();
+        let outputs = args.outputs.as_ref().unwrap();
+        assert_eq!(outputs.len(), 3);
+        assert!(outputs.iter().all(|o| o.satoshis == 0));
+        assert_eq!(outputs[0].locking_script, format!("006a4c64{}", "ab".repeat(100)));
+        assert_eq!(outputs[2].locking_script, format!("006a32{}", "ab".repeat(50)));
+
+        let vargs = validate_create_action_args(&args).unwrap();
+        assert_eq!(vargs.labels, vec!["notes".to_string()]);
+        assert!(!vargs.options.randomize_outputs);
+
+        assert!(data_action_args(&[], &[], 100).is_err());
+        assert!(data_action_args(&data, &[], 0).is_err());
+        let unlabelled = data_action_args(&data, &[], DEFAULT_MAX_DATA_OUTPUT_BYTES).unwrap();
+        assert_eq!(unlabelled.outputs.unwrap().len(), 1);
+        assert_eq!(unlabelled.labels, None);
+    }
+
+    #[tokio::test]
+    async fn test_data_action_is_funded_in_order() {
+        use wallet_storage::WalletStorageReader;
+
+        let mut storage = wallet_test_utils::MockStorage::with_fixtures();
+        let auth = wallet_storage::AuthId::new(wallet_test_utils::fixtures::alice_identity_key()).with_user_id(1);
+        let data: Vec<u8> = (0..250u8).collect();
+        let vargs = validate_create_action_args(&data_action_args(&data, &[], 100).unwrap()).unwrap();
+        let dcr = crate::methods::create_action(&mut storage, &auth, vargs, None).await.unwrap();
+
+        let scripts = data_output_scripts(&data, 100);
+        for (vout, script) in scripts.iter().enumerate() {
+            let output = dcr.outputs.iter().find(|o| o.vout == vout as u32).unwrap();
+            assert_eq!((output.satoshis, &output.locking_script), (0, &script.to_hex()));
+        }
+        let fee = dcr.inputs.iter().map(|i| i.source_satoshis).sum::<i64>()
+            - dcr.outputs.iter().map(|o| o.satoshis).sum::<i64>();
+        assert!(fee >= estimate_data_action_fee(&data, 100, &storage.get_fee_model()), "fee {}", fee);
+    }
+
+    #[test]
+    fn test_estimate_data_action_fee() {
+        let fee_model = StorageFeeModel { model: "sat/kb".to_string(), value: Some(100.0) };
+        // 206 bytes: a change input, a change output and a 5 byte data script
+        assert_eq!(estimate_data_action_fee(b"hi", DEFAULT_MAX_DATA_OUTPUT_BYTES, &fee_model), 21);
+        // 100_212 bytes; split in two outputs, 12 bytes more
+        assert_eq!(estimate_data_action_fee(&[0; 100_000], DEFAULT_MAX_DATA_OUTPUT_BYTES, &fee_model), 10_022);
+        assert_eq!(estimate_data_action_fee(&[0; 100_000], 50_000, &fee_model), 10_023);
+    }
+}
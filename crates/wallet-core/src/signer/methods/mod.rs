@@ -6,7 +6,12 @@ pub mod sign_message;
 pub mod sign_transaction;
 pub mod build_signable_transaction;
 pub mod complete_signed_transaction;
+pub mod create_action;
+pub mod consolidate_outputs;
+pub mod create_data_action;
+pub mod sign_action;
 pub mod acquire_direct_certificate;
+pub mod acquire_certificate;
 pub mod prove_certificate;
 
 // Re-exports
@@ -23,16 +28,54 @@ pub use complete_signed_transaction::{
     SignActionSpend,
 };
 
+pub use create_action::{
+    create_action,
+    validate_create_action_args,
+};
+
+pub use consolidate_outputs::{
+    consolidate_outputs,
+    validate_consolidate_outputs_args,
+    ConsolidateOutputsArgs,
+    ConsolidateOutputsResult,
+    ValidConsolidateOutputsArgs,
+};
+
+pub use create_data_action::{
+    data_action_args,
+    data_output_scripts,
+    estimate_data_action_fee,
+    DEFAULT_MAX_DATA_OUTPUT_BYTES,
+};
+
+pub use sign_action::{
+    sign_action,
+    validate_sign_action_args,
+};
+
 pub use acquire_direct_certificate::{
     acquire_direct_certificate,
+    insert_certificate_with_fields,
     AcquireCertificateResult,
     ValidAcquireDirectCertificateArgs,
     NewCertificate,
     CertificateField,
 };
 
+pub use acquire_certificate::{
+    acquire_certificate,
+    acquire_issuance_certificate,
+    request_certificate,
+    CertificateSigningRequest,
+    CertifierClient,
+    HttpCertifierClient,
+    SignCertificateResponse,
+};
+
 pub use prove_certificate::{
     prove_certificate,
+    keyring_for_verifier,
+    validate_prove_certificate_args,
     ProveCertificateResult,
     ValidProveCertificateArgs,
     ListCertificatesArgs,
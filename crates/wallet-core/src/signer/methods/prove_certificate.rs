@@ -4,33 +4,42 @@
 //!
 //! Proves ownership of a certificate by revealing fields to a verifier
 
+use crate::certificates::create_keyring_for_verifier;
+use crate::keys::RootKeyDeriver;
 use crate::sdk::error::{WalletError, WalletResult};
+use crate::sdk::{validate_hex_string, validate_string_length, ProveCertificateArgs};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use wallet_storage::{
+    AuthId, FindCertificatesArgs, Paged, TableCertificate, TableCertificateField, WalletStorageProvider,
+};
 
 /// Validated prove certificate arguments
 ///
+/// The certificate properties are a partial match; together they must
+/// identify exactly one stored certificate.
+///
 /// Reference: TS ValidProveCertificateArgs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidProveCertificateArgs {
     /// Certificate type
     #[serde(rename = "type")]
-    pub cert_type: String,
+    pub cert_type: Option<String>,
     
     /// Serial number
-    pub serial_number: String,
+    pub serial_number: Option<String>,
     
     /// Certifier identity key
-    pub certifier: String,
+    pub certifier: Option<String>,
     
     /// Subject identity key
-    pub subject: String,
+    pub subject: Option<String>,
     
     /// Revocation outpoint
-    pub revocation_outpoint: String,
+    pub revocation_outpoint: Option<String>,
     
     /// Signature
-    pub signature: String,
+    pub signature: Option<String>,
     
     /// Verifier to reveal to
     pub verifier: String,
@@ -47,10 +56,33 @@ pub struct ValidProveCertificateArgs {
     pub privileged_reason: Option<String>,
 }
 
+/// Validate prove certificate arguments
+///
+/// Reference: TS validateProveCertificateArgs
+pub fn validate_prove_certificate_args(args: &ProveCertificateArgs) -> WalletResult<ValidProveCertificateArgs> {
+    let fields_to_reveal = args.fields_to_reveal.iter()
+        .map(|name| validate_string_length(name, "fieldsToReveal", Some(1), Some(50)))
+        .collect::<WalletResult<Vec<_>>>()?;
+    
+    Ok(ValidProveCertificateArgs {
+        cert_type: args.certificate.cert_type.clone(),
+        serial_number: args.certificate.serial_number.clone(),
+        certifier: args.certificate.certifier.clone(),
+        subject: args.certificate.subject.clone(),
+        revocation_outpoint: args.certificate.revocation_outpoint.clone(),
+        signature: args.certificate.signature.clone(),
+        verifier: validate_hex_string(&args.verifier, "verifier", Some(66), Some(66))?,
+        fields_to_reveal,
+        privileged: args.privileged.unwrap_or(false),
+        privileged_reason: args.privileged_reason.clone(),
+    })
+}
+
 /// Prove certificate result
 ///
 /// Reference: TS ProveCertificateResult from @bsv/sdk
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ProveCertificateResult {
     /// Keyring for verifier
     pub keyring_for_verifier: HashMap<String, String>,
@@ -147,64 +179,83 @@ pub struct StorageCertificate {
 /// Reference: TS proveCertificate (proveCertificate.ts lines 7-44)
 ///
 /// # Arguments
+/// * `storage` - Storage holding the certificate and its master keyring
+/// * `auth` - Authenticated user
+/// * `key_deriver` - Deriver over the subject's root key
 /// * `vargs` - Validated prove certificate arguments
-/// * `storage` - Storage interface for listing certificates
 ///
 /// # Returns
 /// Keyring for verifier containing revealed fields
 pub async fn prove_certificate(
+    storage: &dyn WalletStorageProvider,
+    auth: &AuthId,
+    key_deriver: &RootKeyDeriver,
     vargs: ValidProveCertificateArgs,
 ) -> WalletResult<ProveCertificateResult> {
-    // Build list certificates arguments (TS lines 12-26)
-    let lc_args = ListCertificatesArgs {
-        partial: PartialCertificate {
-            cert_type: vargs.cert_type.clone(),
+    let user_id = auth.user_id
+        .ok_or_else(|| WalletError::invalid_parameter("auth", "an authenticated user"))?;
+    
+    // Find the one certificate matching the partial (TS lines 12-29)
+    let find_args = FindCertificatesArgs {
+        user_id,
+        since: None,
+        paged: Some(Paged::new(2)),
+        order_descending: None,
+        partial: Some(wallet_storage::PartialCertificate {
+            certificate_type: vargs.cert_type.clone(),
             serial_number: vargs.serial_number.clone(),
             certifier: vargs.certifier.clone(),
             subject: vargs.subject.clone(),
             revocation_outpoint: vargs.revocation_outpoint.clone(),
             signature: vargs.signature.clone(),
-        },
-        certifiers: vec![],
-        types: vec![],
-        limit: 2,
-        offset: 0,
-        privileged: false,
+        }),
+        certifiers: None,
+        types: None,
+        include_fields: None,
     };
+    let matches = storage.find_certificates_auth(auth, &find_args).await?;
+    if matches.len() != 1 {
+        return Err(WalletError::invalid_parameter("args", "a unique certificate match"));
+    }
+    let certificate = &matches[0];
     
-    // List certificates from storage (TS line 28)
-    // TODO: Integrate with actual storage
-    // let lcr = await wallet.storage.listCertificates(lc_args);
-    
-    // For now, return error indicating storage integration needed
-    // In real implementation:
-    // 1. Call storage.listCertificates(lc_args)
-    // 2. Verify exactly one certificate matches (TS line 29)
-    // 3. Get the storage certificate (TS line 30)
-    // 4. Create keyring for verifier (TS lines 31-41)
-    //    using MasterCertificate.createKeyringForVerifier
-    
-    // Placeholder implementation (TS lines 31-44)
-    let keyring_for_verifier = HashMap::new();
-    
-    // TODO: Actual implementation would call:
-    // let keyring_for_verifier = MasterCertificate::create_keyring_for_verifier(
-    //     wallet,
-    //     storage_cert.certifier,
-    //     vargs.verifier,
-    //     storage_cert.fields,
-    //     vargs.fields_to_reveal,
-    //     storage_cert.keyring,
-    //     storage_cert.serial_number,
-    //     vargs.privileged,
-    //     vargs.privileged_reason,
-    // ).await?;
+    // Create keyring for verifier (TS lines 30-41)
+    let fields = storage.find_certificate_fields_auth(auth, certificate.certificate_id).await?;
+    let keyring_for_verifier = keyring_for_verifier(key_deriver, certificate, &fields, &vargs).await?;
     
     Ok(ProveCertificateResult {
         keyring_for_verifier,
     })
 }
 
+/// Keyring revealing `vargs.fields_to_reveal` of a stored certificate
+///
+/// The stored field values are the encrypted values and their master keys
+/// form the master keyring shared with the certifier.
+pub async fn keyring_for_verifier(
+    key_deriver: &RootKeyDeriver,
+    certificate: &TableCertificate,
+    fields: &[TableCertificateField],
+    vargs: &ValidProveCertificateArgs,
+) -> WalletResult<HashMap<String, String>> {
+    let values: HashMap<String, String> = fields.iter()
+        .map(|f| (f.field_name.clone(), f.field_value.clone()))
+        .collect();
+    let master_keyring: HashMap<String, String> = fields.iter()
+        .map(|f| (f.field_name.clone(), f.master_key.clone()))
+        .collect();
+    
+    create_keyring_for_verifier(
+        key_deriver,
+        &certificate.certifier,
+        &vargs.verifier,
+        &values,
+        &vargs.fields_to_reveal,
+        &master_keyring,
+        &certificate.serial_number,
+    ).await
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -214,22 +265,60 @@ mod tests {
     use super::*;
     
     #[tokio::test]
-    async fn test_prove_certificate() {
-        let vargs = ValidProveCertificateArgs {
-            cert_type: "identity".to_string(),
-            serial_number: "12345".to_string(),
-            certifier: "certifier_key".to_string(),
-            subject: "subject_key".to_string(),
-            revocation_outpoint: "txid.0".to_string(),
-            signature: "sig_data".to_string(),
-            verifier: "verifier_key".to_string(),
+    async fn test_keyring_for_verifier() {
+        use crate::certificates::{create_certificate_fields, decrypt_fields_for_verifier};
+        
+        let subject = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let certifier = RootKeyDeriver::new(&[2u8; 32]).unwrap();
+        let verifier = RootKeyDeriver::new(&[3u8; 32]).unwrap();
+        
+        let plain = HashMap::from([
+            ("name".to_string(), "Alice".to_string()),
+            ("email".to_string(), "alice@example.com".to_string()),
+        ]);
+        let created = create_certificate_fields(&subject, &certifier.identity_key_hex(), &plain).await.unwrap();
+        let certificate = TableCertificate::new(
+            1, 1, "identity", "c2VyaWFs", certifier.identity_key_hex(), subject.identity_key_hex(), "txid.0", "sig",
+        );
+        let fields: Vec<TableCertificateField> = created.certificate_fields.iter()
+            .map(|(name, value)| TableCertificateField::new(1, 1, name, value, &created.master_keyring[name]))
+            .collect();
+        
+        let args = ProveCertificateArgs {
+            certificate: Default::default(),
+            fields_to_reveal: vec!["email".to_string()],
+            verifier: verifier.identity_key_hex(),
+            privileged: None,
+            privileged_reason: None,
+        };
+        let vargs = validate_prove_certificate_args(&args).unwrap();
+        let keyring = keyring_for_verifier(&subject, &certificate, &fields, &vargs).await.unwrap();
+        
+        let revealed = decrypt_fields_for_verifier(
+            &verifier, &keyring, &created.certificate_fields, &certificate.serial_number, &subject.identity_key_hex(),
+        ).await.unwrap();
+        assert_eq!(revealed, HashMap::from([("email".to_string(), "alice@example.com".to_string())]));
+    }
+    
+    #[test]
+    fn test_validate_prove_certificate_args() {
+        let mut args = ProveCertificateArgs {
+            certificate: Default::default(),
             fields_to_reveal: vec!["name".to_string()],
-            privileged: false,
+            verifier: "verifier_key".to_string(),
+            privileged: None,
             privileged_reason: None,
         };
+        assert!(validate_prove_certificate_args(&args).is_err());
+        
+        args.verifier = "02".repeat(33);
+        args.fields_to_reveal.push(String::new());
+        assert!(validate_prove_certificate_args(&args).is_err());
         
-        let result = prove_certificate(vargs).await;
-        assert!(result.is_ok());
+        args.fields_to_reveal.pop();
+        let vargs = validate_prove_certificate_args(&args).unwrap();
+        assert_eq!(vargs.fields_to_reveal, vec!["name"]);
+        assert!(!vargs.privileged);
     }
     
     #[test]
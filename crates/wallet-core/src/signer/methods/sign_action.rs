@@ -0,0 +1,121 @@
+//! Signer Sign Action
+//!
+//! **Reference**: TypeScript `src/signer/methods/signAction.ts`
+//!
+//! Completes a signable transaction returned by createAction with the
+//! caller's unlocking scripts, then processes it.
+
+use crate::keys::KeyPair;
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::{
+    validate_base64_string, validate_hex_string, SignActionArgs, SignActionResult,
+    ValidProcessActionOptions, ValidSignActionArgs, ValidSignActionOptions,
+};
+use crate::services::{Broadcaster, UtxoStatusProvider};
+use std::collections::HashMap;
+use wallet_storage::{AuthId, WalletStorageProvider};
+
+use super::complete_signed_transaction::{PendingSignAction, SignActionSpend};
+use super::create_action::sign_and_process;
+
+/// Validate BRC-100 sign action arguments
+///
+/// Reference: TS validateSignActionArgs
+pub fn validate_sign_action_args(args: &SignActionArgs) -> WalletResult<ValidSignActionArgs> {
+    let options = args.options.clone().unwrap_or_default();
+    let send_with = options.send_with.unwrap_or_default().iter()
+        .map(|txid| validate_hex_string(txid, "sendWith", Some(64), Some(64)))
+        .collect::<WalletResult<Vec<_>>>()?;
+    for spend in args.spends.values() {
+        validate_hex_string(&spend.unlocking_script, "unlockingScript", None, None)?;
+    }
+    let options = ValidSignActionOptions {
+        accept_delayed_broadcast: options.accept_delayed_broadcast.unwrap_or(true),
+        return_txid_only: options.return_txid_only.unwrap_or(false),
+        no_send: options.no_send.unwrap_or(false),
+        send_with,
+    };
+
+    Ok(ValidSignActionArgs {
+        spends: args.spends.clone(),
+        reference: validate_base64_string(&args.reference, "reference", None, None)?,
+        is_send_with: !options.send_with.is_empty(),
+        is_delayed: options.accept_delayed_broadcast,
+        is_no_send: options.no_send,
+        is_new_tx: true,
+        is_remix_change: false,
+        options,
+    })
+}
+
+/// Sign a pending action and process it
+///
+/// Reference: TS signer signAction
+///
+/// `prior` is the pending action createAction returned for `vargs.reference`.
+pub async fn sign_action(
+    storage: &mut dyn WalletStorageProvider,
+    broadcaster: Option<&dyn Broadcaster>,
+    utxo_status: Option<&dyn UtxoStatusProvider>,
+    auth: &AuthId,
+    change_keys: &KeyPair,
+    prior: PendingSignAction,
+    vargs: ValidSignActionArgs,
+) -> WalletResult<SignActionResult> {
+    if prior.reference != vargs.reference {
+        return Err(WalletError::invalid_parameter("reference", "the reference of the pending action"));
+    }
+    let spends: HashMap<u32, SignActionSpend> = vargs.spends.iter()
+        .map(|(vin, spend)| (*vin, SignActionSpend {
+            unlocking_script: spend.unlocking_script.clone(),
+            sequence_number: Some(spend.sequence_number),
+        }))
+        .collect();
+    let options = ValidProcessActionOptions {
+        accept_delayed_broadcast: vargs.options.accept_delayed_broadcast,
+        return_txid_only: vargs.options.return_txid_only,
+        no_send: vargs.options.no_send,
+        send_with: vargs.options.send_with.clone(),
+    };
+
+    let (txid, tx, processed) = sign_and_process(storage, broadcaster, utxo_status, auth, change_keys, prior, spends, &options).await?;
+    Ok(SignActionResult {
+        txid: Some(txid),
+        tx,
+        send_with_results: processed.send_with_results,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_sign_action_args() {
+        let args: SignActionArgs = serde_json::from_value(json!({
+            "spends": {"0": {"unlockingScript": "0151"}},
+            "reference": "cmVmZXJlbmNl",
+            "options": {"noSend": true},
+        })).unwrap();
+        let vargs = validate_sign_action_args(&args).unwrap();
+
+        assert_eq!(vargs.spends[&0].sequence_number, 0xffffffff);
+        assert!(vargs.is_no_send);
+        assert!(vargs.is_delayed);
+        assert!(!vargs.is_send_with);
+    }
+
+    #[test]
+    fn test_validate_sign_action_args_rejects_bad_scripts() {
+        let args: SignActionArgs = serde_json::from_value(json!({
+            "spends": {"0": {"unlockingScript": "not hex"}},
+            "reference": "cmVmZXJlbmNl",
+        })).unwrap();
+        assert!(validate_sign_action_args(&args).is_err());
+    }
+}
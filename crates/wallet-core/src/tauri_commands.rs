@@ -14,8 +14,10 @@
 //!     // Initialize wallet
 //!     let wallet = Wallet::new(config).unwrap();
 //!     
+//!     let state: WalletState = std::sync::Arc::new(tokio::sync::Mutex::new(wallet));
+//!
 //!     tauri::Builder::default()
-//!         .manage(wallet)
+//!         .manage(state)
 //!         .invoke_handler(tauri::generate_handler![
 //!             wallet_create_action,
 //!             wallet_sign_action,
@@ -25,7 +27,20 @@
 //!         .expect("error while running tauri application");
 //! }
 //! ```
-
+//!
+//! ## Conventions
+//!
+//! - The originator is the host of the invoking window's URL, validated and
+//!   normalized as an `Originator`, never a value supplied by the frontend.
+//! - `args` must be a JSON object; `null` is treated as `{}`.
+//! - Errors are returned as the serialized `WalletError` in the TS JSON
+//!   shape (`{ isError, name, message, code, description, ...details }`) so
+//!   the frontend can match on `code`.
+
+use crate::managers::simple_wallet_manager::WalletInterface;
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::sdk::originator::Originator;
+use crate::sdk::validation::{originator_from_url, validate_command_args};
 use crate::wallet::Wallet;
 use serde_json::Value;
 use std::sync::Arc;
@@ -34,6 +49,12 @@ use tokio::sync::Mutex;
 /// Type alias for managed Wallet state in Tauri
 pub type WalletState = Arc<Mutex<Wallet>>;
 
+/// Originator for a command: the host of the invoking window's URL
+fn window_originator(window: &tauri::WebviewWindow) -> WalletResult<Originator> {
+    let url = window.url().map_err(|e| WalletError::internal(e.to_string()))?;
+    originator_from_url(url.as_str())
+}
+
 // ============================================================================
 // ACTION MANAGEMENT COMMANDS (5)
 // ============================================================================
@@ -42,70 +63,65 @@ pub type WalletState = Arc<Mutex<Wallet>>;
 #[tauri::command]
 pub async fn wallet_create_action(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .create_action(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.create_action(args, Some(originator.as_str())).await
 }
 
 /// Sign a transaction action
 #[tauri::command]
 pub async fn wallet_sign_action(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .sign_action(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.sign_action(args, Some(originator.as_str())).await
 }
 
 /// Abort a pending action
 #[tauri::command]
 pub async fn wallet_abort_action(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .abort_action(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.abort_action(args, Some(originator.as_str())).await
 }
 
 /// List transaction actions
 #[tauri::command]
 pub async fn wallet_list_actions(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .list_actions(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.list_actions(args, Some(originator.as_str())).await
 }
 
 /// Internalize an incoming action
 #[tauri::command]
 pub async fn wallet_internalize_action(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .internalize_action(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.internalize_action(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -116,28 +132,26 @@ pub async fn wallet_internalize_action(
 #[tauri::command]
 pub async fn wallet_list_outputs(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .list_outputs(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.list_outputs(args, Some(originator.as_str())).await
 }
 
 /// Relinquish control of an output
 #[tauri::command]
 pub async fn wallet_relinquish_output(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .relinquish_output(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.relinquish_output(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -148,42 +162,39 @@ pub async fn wallet_relinquish_output(
 #[tauri::command]
 pub async fn wallet_get_public_key(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .get_public_key(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.get_public_key(args, Some(originator.as_str())).await
 }
 
 /// Reveal counterparty key linkage
 #[tauri::command]
 pub async fn wallet_reveal_counterparty_key_linkage(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .reveal_counterparty_key_linkage(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.reveal_counterparty_key_linkage(args, Some(originator.as_str())).await
 }
 
 /// Reveal specific key linkage
 #[tauri::command]
 pub async fn wallet_reveal_specific_key_linkage(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .reveal_specific_key_linkage(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.reveal_specific_key_linkage(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -194,84 +205,78 @@ pub async fn wallet_reveal_specific_key_linkage(
 #[tauri::command]
 pub async fn wallet_encrypt(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .encrypt(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.encrypt(args, Some(originator.as_str())).await
 }
 
 /// Decrypt data
 #[tauri::command]
 pub async fn wallet_decrypt(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .decrypt(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.decrypt(args, Some(originator.as_str())).await
 }
 
 /// Create an HMAC
 #[tauri::command]
 pub async fn wallet_create_hmac(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .create_hmac(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.create_hmac(args, Some(originator.as_str())).await
 }
 
 /// Verify an HMAC
 #[tauri::command]
 pub async fn wallet_verify_hmac(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .verify_hmac(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.verify_hmac(args, Some(originator.as_str())).await
 }
 
 /// Create a signature
 #[tauri::command]
 pub async fn wallet_create_signature(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .create_signature(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.create_signature(args, Some(originator.as_str())).await
 }
 
 /// Verify a signature
 #[tauri::command]
 pub async fn wallet_verify_signature(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .verify_signature(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.verify_signature(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -282,56 +287,52 @@ pub async fn wallet_verify_signature(
 #[tauri::command]
 pub async fn wallet_acquire_certificate(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .acquire_certificate(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.acquire_certificate(args, Some(originator.as_str())).await
 }
 
 /// List certificates
 #[tauri::command]
 pub async fn wallet_list_certificates(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .list_certificates(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.list_certificates(args, Some(originator.as_str())).await
 }
 
 /// Prove certificate ownership
 #[tauri::command]
 pub async fn wallet_prove_certificate(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .prove_certificate(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.prove_certificate(args, Some(originator.as_str())).await
 }
 
 /// Relinquish a certificate
 #[tauri::command]
 pub async fn wallet_relinquish_certificate(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .relinquish_certificate(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.relinquish_certificate(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -342,28 +343,26 @@ pub async fn wallet_relinquish_certificate(
 #[tauri::command]
 pub async fn wallet_discover_by_identity_key(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .discover_by_identity_key(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.discover_by_identity_key(args, Some(originator.as_str())).await
 }
 
 /// Discover by attributes
 #[tauri::command]
 pub async fn wallet_discover_by_attributes(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .discover_by_attributes(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.discover_by_attributes(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -374,28 +373,26 @@ pub async fn wallet_discover_by_attributes(
 #[tauri::command]
 pub async fn wallet_is_authenticated(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .is_authenticated(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.is_authenticated(args, Some(originator.as_str())).await
 }
 
 /// Wait for authentication
 #[tauri::command]
 pub async fn wallet_wait_for_authentication(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .wait_for_authentication(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.wait_for_authentication(args, Some(originator.as_str())).await
 }
 
 // ============================================================================
@@ -406,51 +403,61 @@ pub async fn wallet_wait_for_authentication(
 #[tauri::command]
 pub async fn wallet_get_height(
     wallet: tauri::State<'_, WalletState>,
-    originator: String,
-) -> Result<Value, String> {
+    window: tauri::WebviewWindow,
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
     let wallet = wallet.lock().await;
-    wallet
-        .get_height(Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.get_height(Some(originator.as_str())).await
 }
 
 /// Get block header for specific height
 #[tauri::command]
 pub async fn wallet_get_header_for_height(
     wallet: tauri::State<'_, WalletState>,
+    window: tauri::WebviewWindow,
     args: Value,
-    originator: String,
-) -> Result<Value, String> {
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let args = validate_command_args(args)?;
     let wallet = wallet.lock().await;
-    wallet
-        .get_header_for_height(args, Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.get_header_for_height(args, Some(originator.as_str())).await
 }
 
 /// Get network information
 #[tauri::command]
 pub async fn wallet_get_network(
     wallet: tauri::State<'_, WalletState>,
-    originator: String,
-) -> Result<Value, String> {
+    window: tauri::WebviewWindow,
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
     let wallet = wallet.lock().await;
-    wallet
-        .get_network(Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.get_network(Some(originator.as_str())).await
 }
 
 /// Get wallet version
 #[tauri::command]
 pub async fn wallet_get_version(
     wallet: tauri::State<'_, WalletState>,
-    originator: String,
-) -> Result<Value, String> {
+    window: tauri::WebviewWindow,
+) -> Result<Value, WalletError> {
+    let originator = window_originator(&window)?;
+    let wallet = wallet.lock().await;
+    wallet.get_version(Some(originator.as_str())).await
+}
+
+// ============================================================================
+// DISPLAY COMMANDS (1)
+// ============================================================================
+
+/// Format an amount for display, in the user's preferred currency by default
+///
+/// `currency` is `SATS`, `BSV` or an ISO 4217 code such as `USD`.
+#[tauri::command]
+pub async fn wallet_format_satoshis(
+    wallet: tauri::State<'_, WalletState>,
+    satoshis: i64,
+    currency: Option<String>,
+) -> Result<String, WalletError> {
     let wallet = wallet.lock().await;
-    wallet
-        .get_version(Some(&originator))
-        .await
-        .map_err(|e| e.to_string())
+    wallet.format_satoshis(satoshis, currency.as_deref()).await
 }
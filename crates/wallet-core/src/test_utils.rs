@@ -0,0 +1,208 @@
+//! Test doubles
+//!
+//! A `WalletInterface` answering from canned responses, for testing
+//! managers and transports that wrap a wallet. Built for this crate's own
+//! tests and with the `test-utils` feature; `wallet-test-utils` re-exports it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use crate::managers::simple_wallet_manager::WalletInterface;
+use crate::sdk::errors::{WalletError, WalletResult};
+
+/// One call received by a `MockWallet`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletCall {
+    /// BRC-100 method name, e.g. "createAction"
+    pub method: String,
+    pub args: Value,
+    pub originator: Option<String>,
+}
+
+/// Wallet answering each call with the response set for its method
+///
+/// Every call is recorded. Methods without a response fail with
+/// `WalletError::not_implemented`.
+#[derive(Debug, Default)]
+pub struct MockWallet {
+    responses: Mutex<HashMap<String, Value>>,
+    calls: Mutex<Vec<WalletCall>>,
+}
+
+impl MockWallet {
+    /// Wallet with no responses set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer calls to `method` (the BRC-100 name, e.g. "getHeight") with `response`
+    pub fn with_response(self, method: impl Into<String>, response: Value) -> Self {
+        self.set_response(method, response);
+        self
+    }
+
+    /// Answer later calls to `method` with `response`, e.g. after the state
+    /// a test models has changed
+    pub fn set_response(&self, method: impl Into<String>, response: Value) {
+        self.responses.lock().unwrap().insert(method.into(), response);
+    }
+
+    /// Calls received so far, oldest first
+    pub fn calls(&self) -> Vec<WalletCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn answer(&self, method: &str, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.calls.lock().unwrap().push(WalletCall {
+            method: method.to_string(),
+            args,
+            originator: originator.map(str::to_string),
+        });
+        self.responses
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .ok_or_else(|| WalletError::not_implemented(format!("MockWallet has no response for {}", method)))
+    }
+}
+
+#[async_trait]
+impl WalletInterface for MockWallet {
+    async fn create_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("createAction", args, originator)
+    }
+
+    async fn sign_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("signAction", args, originator)
+    }
+
+    async fn abort_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("abortAction", args, originator)
+    }
+
+    async fn list_actions(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("listActions", args, originator)
+    }
+
+    async fn internalize_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("internalizeAction", args, originator)
+    }
+
+    async fn list_outputs(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("listOutputs", args, originator)
+    }
+
+    async fn relinquish_output(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("relinquishOutput", args, originator)
+    }
+
+    async fn get_public_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("getPublicKey", args, originator)
+    }
+
+    async fn reveal_counterparty_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("revealCounterpartyKeyLinkage", args, originator)
+    }
+
+    async fn reveal_specific_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("revealSpecificKeyLinkage", args, originator)
+    }
+
+    async fn encrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("encrypt", args, originator)
+    }
+
+    async fn decrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("decrypt", args, originator)
+    }
+
+    async fn create_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("createHmac", args, originator)
+    }
+
+    async fn verify_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("verifyHmac", args, originator)
+    }
+
+    async fn create_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("createSignature", args, originator)
+    }
+
+    async fn verify_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("verifySignature", args, originator)
+    }
+
+    async fn acquire_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("acquireCertificate", args, originator)
+    }
+
+    async fn list_certificates(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("listCertificates", args, originator)
+    }
+
+    async fn prove_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("proveCertificate", args, originator)
+    }
+
+    async fn relinquish_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("relinquishCertificate", args, originator)
+    }
+
+    async fn discover_by_identity_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("discoverByIdentityKey", args, originator)
+    }
+
+    async fn discover_by_attributes(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("discoverByAttributes", args, originator)
+    }
+
+    async fn is_authenticated(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("isAuthenticated", args, originator)
+    }
+
+    async fn wait_for_authentication(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("waitForAuthentication", args, originator)
+    }
+
+    async fn get_header_for_height(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("getHeaderForHeight", args, originator)
+    }
+
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("getHeight", Value::Null, originator)
+    }
+
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("getNetwork", Value::Null, originator)
+    }
+
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<Value> {
+        self.answer("getVersion", Value::Null, originator)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_answers_and_records_calls() {
+        let wallet = MockWallet::new().with_response("getHeight", json!({"height": 850000}));
+        assert_eq!(wallet.get_height(Some("example.com")).await.unwrap(), json!({"height": 850000}));
+        assert!(wallet.create_action(json!({"description": "test"}), None).await.is_err());
+
+        let calls = wallet.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].originator.as_deref(), Some("example.com"));
+        assert_eq!(calls[1].method, "createAction");
+        assert_eq!(calls[1].args["description"], "test");
+    }
+}
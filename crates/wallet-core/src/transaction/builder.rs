@@ -0,0 +1,333 @@
+//! Transaction Builder
+//!
+//! Fluent construction of signed transactions for callers that don't need
+//! the storage-backed createAction flow: tooling, tests and monitor tasks.
+//!
+//! ```ignore
+//! let tx = TxBuilder::new()
+//!     .add_p2pkh_input(outpoint, 5000, locking_script, private_key)
+//!     .add_output(1000, recipient_script)
+//!     .change_to(change_script)
+//!     .build()?;
+//! ```
+//!
+//! **Reference**: TypeScript bsv-sdk `Transaction` (addInput/addOutput/fee/sign)
+
+use super::{
+    OutPoint, Script, SigHash, SigHashType, Transaction, TransactionError, TransactionResult, TxInput, TxOutput,
+};
+use crate::beef::Beef;
+use crate::crypto::{derive_public_key, sign_ecdsa};
+use crate::methods::fee_model::{fee_for_size, transaction_size};
+use wallet_storage::StorageFeeModel;
+
+/// Unlocking script size assumed for P2PKH inputs when estimating fees:
+/// a maximal signature push (1 + 73) and compressed key push (1 + 33)
+pub const P2PKH_UNLOCKING_SCRIPT_LENGTH: usize = 108;
+
+/// How an input gets its unlocking script
+#[derive(Debug, Clone)]
+enum Unlock {
+    /// Sign P2PKH with this private key and sighash byte
+    P2pkh { private_key: Vec<u8>, sighash_type: u8 },
+    /// Use a caller-provided unlocking script
+    Script(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+struct BuilderInput {
+    outpoint: OutPoint,
+    sequence: u32,
+    source_satoshis: i64,
+    locking_script: Vec<u8>,
+    unlock: Unlock,
+}
+
+impl BuilderInput {
+    fn unlocking_script_size(&self) -> usize {
+        match &self.unlock {
+            Unlock::P2pkh { .. } => P2PKH_UNLOCKING_SCRIPT_LENGTH,
+            Unlock::Script(script) => script.len(),
+        }
+    }
+}
+
+/// High-level transaction builder
+///
+/// Inputs carry the satoshis and locking script of the output they spend so
+/// the builder can compute the fee and change, and sign. With a change
+/// script set, whatever the inputs hold beyond the outputs and the fee goes
+/// to a final change output (dropped when nothing is left over).
+#[derive(Debug, Clone)]
+pub struct TxBuilder {
+    version: u32,
+    lock_time: u32,
+    inputs: Vec<BuilderInput>,
+    outputs: Vec<TxOutput>,
+    change_script: Option<Vec<u8>>,
+    fee_model: StorageFeeModel,
+    source_transactions: Vec<Vec<u8>>,
+}
+
+impl Default for TxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TxBuilder {
+    /// Version 1, lock time 0, the default `sat/kb` fee model and no inputs
+    /// or outputs
+    pub fn new() -> Self {
+        Self {
+            version: 1,
+            lock_time: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            change_script: None,
+            fee_model: StorageFeeModel::default(),
+            source_transactions: Vec::new(),
+        }
+    }
+
+    /// Set the transaction version
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the lock time
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Set the fee model used to compute change
+    pub fn fee_model(mut self, fee_model: StorageFeeModel) -> Self {
+        self.fee_model = fee_model;
+        self
+    }
+
+    /// Spend a P2PKH output, signing with `private_key` (SIGHASH_ALL | FORKID)
+    pub fn add_p2pkh_input(
+        self,
+        outpoint: OutPoint,
+        source_satoshis: i64,
+        locking_script: Vec<u8>,
+        private_key: Vec<u8>,
+    ) -> Self {
+        self.add_p2pkh_input_with_sighash(outpoint, source_satoshis, locking_script, private_key, SigHashType::All.with_forkid())
+    }
+
+    /// Spend a P2PKH output, signing with `private_key` and `sighash_type`
+    pub fn add_p2pkh_input_with_sighash(
+        mut self,
+        outpoint: OutPoint,
+        source_satoshis: i64,
+        locking_script: Vec<u8>,
+        private_key: Vec<u8>,
+        sighash_type: u8,
+    ) -> Self {
+        self.inputs.push(BuilderInput {
+            outpoint,
+            sequence: 0xffffffff,
+            source_satoshis,
+            locking_script,
+            unlock: Unlock::P2pkh { private_key, sighash_type },
+        });
+        self
+    }
+
+    /// Spend an output with a ready-made unlocking script
+    pub fn add_input_with_unlocking_script(
+        mut self,
+        outpoint: OutPoint,
+        source_satoshis: i64,
+        locking_script: Vec<u8>,
+        unlocking_script: Vec<u8>,
+    ) -> Self {
+        self.inputs.push(BuilderInput {
+            outpoint,
+            sequence: 0xffffffff,
+            source_satoshis,
+            locking_script,
+            unlock: Unlock::Script(unlocking_script),
+        });
+        self
+    }
+
+    /// Set the sequence number of the most recently added input
+    pub fn sequence(mut self, sequence: u32) -> Self {
+        if let Some(input) = self.inputs.last_mut() {
+            input.sequence = sequence;
+        }
+        self
+    }
+
+    /// Add an output
+    pub fn add_output(mut self, satoshis: i64, locking_script: Vec<u8>) -> Self {
+        self.outputs.push(TxOutput::new(satoshis, locking_script));
+        self
+    }
+
+    /// Add a P2PKH output paying `satoshis` to `pub_key_hash`
+    pub fn pay_to_public_key_hash(self, satoshis: i64, pub_key_hash: &[u8]) -> TransactionResult<Self> {
+        let script = Script::p2pkh_locking_script(pub_key_hash)?;
+        Ok(self.add_output(satoshis, script.to_bytes().to_vec()))
+    }
+
+    /// Send whatever is left after outputs and fee to `locking_script`
+    pub fn change_to(mut self, locking_script: Vec<u8>) -> Self {
+        self.change_script = Some(locking_script);
+        self
+    }
+
+    /// Include a source transaction in the BEEF from `build_beef`
+    pub fn source_transaction(mut self, raw_tx: Vec<u8>) -> Self {
+        self.source_transactions.push(raw_tx);
+        self
+    }
+
+    /// Satoshis held by the inputs
+    pub fn input_satoshis(&self) -> i64 {
+        self.inputs.iter().map(|i| i.source_satoshis).sum()
+    }
+
+    /// Satoshis paid to the outputs added so far
+    pub fn output_satoshis(&self) -> i64 {
+        self.outputs.iter().map(|o| o.value).sum()
+    }
+
+    /// Estimated serialized size, including the change output when set
+    pub fn estimate_size(&self) -> usize {
+        let unlocking: Vec<usize> = self.inputs.iter().map(BuilderInput::unlocking_script_size).collect();
+        let mut locking: Vec<usize> = self.outputs.iter().map(|o| o.script_pubkey.len()).collect();
+        if let Some(change) = &self.change_script {
+            locking.push(change.len());
+        }
+        transaction_size(&unlocking, &locking)
+    }
+
+    /// Fee the fee model asks for the estimated size
+    pub fn estimate_fee(&self) -> i64 {
+        fee_for_size(&self.fee_model, self.estimate_size())
+    }
+
+    /// Change the built transaction would carry (0 without a change script)
+    ///
+    /// Fails if the inputs don't cover the outputs and fee.
+    pub fn change(&self) -> TransactionResult<i64> {
+        let available = self.input_satoshis();
+        let required = self.output_satoshis() + self.estimate_fee();
+        if available < required {
+            return Err(TransactionError::InsufficientFunds { required, available });
+        }
+        Ok(if self.change_script.is_some() { available - required } else { 0 })
+    }
+
+    /// Build and sign the transaction
+    pub fn build(&self) -> TransactionResult<Transaction> {
+        let change = self.change()?;
+        let mut tx = Transaction::with_params(self.version, Vec::new(), self.outputs.clone(), self.lock_time);
+        for input in &self.inputs {
+            tx.add_input(TxInput::with_sequence(input.outpoint.clone(), input.sequence));
+        }
+        if let (Some(script), true) = (&self.change_script, change > 0) {
+            tx.add_output(TxOutput::new(change, script.clone()));
+        }
+
+        // Every signature commits to all outputs, so sign once they're final
+        let mut unlocking_scripts = Vec::with_capacity(self.inputs.len());
+        for (vin, input) in self.inputs.iter().enumerate() {
+            let script = match &input.unlock {
+                Unlock::Script(script) => script.clone(),
+                Unlock::P2pkh { private_key, sighash_type } => {
+                    let sighash = SigHash::calculate(&tx, vin, &input.locking_script, *sighash_type, input.source_satoshis)?;
+                    let signature = sign_ecdsa(&sighash, private_key, *sighash_type)
+                        .map_err(|e| TransactionError::Signing(e.to_string()))?;
+                    let public_key = derive_public_key(private_key)
+                        .map_err(|e| TransactionError::Signing(e.to_string()))?;
+                    Script::p2pkh_unlocking_script(&signature, &public_key).to_bytes().to_vec()
+                }
+            };
+            unlocking_scripts.push(script);
+        }
+        for (input, script) in tx.inputs.iter_mut().zip(unlocking_scripts) {
+            input.set_script(script);
+        }
+        Ok(tx)
+    }
+
+    /// Build and sign, returning the serialized transaction
+    pub fn build_raw(&self) -> TransactionResult<Vec<u8>> {
+        self.build()?.serialize()
+    }
+
+    /// Build and sign, returning AtomicBEEF with the source transactions
+    pub fn build_beef(&self) -> TransactionResult<Vec<u8>> {
+        let to_error = |e: crate::beef::BeefError| TransactionError::Serialization(e.to_string());
+        let mut beef = Beef::new_v2();
+        for raw_tx in &self.source_transactions {
+            beef.merge_raw_tx(raw_tx).map_err(to_error)?;
+        }
+        let btx = beef.merge_raw_tx(&self.build_raw()?).map_err(to_error)?;
+        beef.to_binary_atomic(&btx.txid).map_err(to_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash160;
+    use crate::transaction::Spend;
+
+    fn p2pkh(private_key: &[u8]) -> Vec<u8> {
+        let public_key = derive_public_key(private_key).unwrap();
+        Script::p2pkh_locking_script(&hash160(&public_key)).unwrap().to_bytes().to_vec()
+    }
+
+    fn source() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("aa".repeat(32), 0)));
+        tx.add_output(TxOutput::new(10_000, p2pkh(&[1u8; 32])));
+        tx
+    }
+
+    fn builder(source: &Transaction) -> TxBuilder {
+        TxBuilder::new()
+            .add_p2pkh_input(OutPoint::new(source.txid().unwrap(), 0), 10_000, p2pkh(&[1u8; 32]), vec![1u8; 32])
+            .add_output(3_000, p2pkh(&[2u8; 32]))
+    }
+
+    #[test]
+    fn test_build_with_change() {
+        let source = source();
+        let builder = builder(&source)
+            .change_to(p2pkh(&[1u8; 32]))
+            .fee_model(StorageFeeModel { model: "sat/kb".to_string(), value: Some(100.0) });
+        let fee = builder.estimate_fee();
+        assert_eq!(fee, 23);
+        assert_eq!(builder.change().unwrap(), 10_000 - 3_000 - fee);
+
+        let tx = builder.build().unwrap();
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.outputs[1].value, 10_000 - 3_000 - fee);
+        assert!(tx.size().unwrap() <= builder.estimate_size());
+        Spend::new(&tx, 0, &source.outputs[0].script_pubkey, 10_000).validate().unwrap();
+
+        let beef = builder.source_transaction(source.serialize().unwrap()).build_beef().unwrap();
+        let parsed = Beef::from_binary(&beef).unwrap();
+        assert!(parsed.find_txid(&tx.txid().unwrap()).is_some());
+        assert!(parsed.find_txid(&source.txid().unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_build_without_change_or_funds() {
+        let source = source();
+        let tx = builder(&source).build().unwrap();
+        assert_eq!(tx.outputs.len(), 1);
+
+        let err = builder(&source).add_output(7_000, vec![0x6a]).build().unwrap_err();
+        assert!(matches!(err, TransactionError::InsufficientFunds { available: 10_000, .. }));
+    }
+}
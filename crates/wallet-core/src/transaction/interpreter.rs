@@ -0,0 +1,377 @@
+//! Script Interpreter
+//!
+//! Executes an unlocking script followed by the locking script it spends,
+//! enough to validate P2PKH and PushDrop unlocks before broadcast.
+//!
+//! Supported: data pushes, small integers, OP_NOP, OP_VERIFY, OP_RETURN,
+//! OP_DROP/OP_2DROP/OP_NIP/OP_DUP/OP_SWAP/OP_TOALTSTACK/OP_FROMALTSTACK,
+//! OP_EQUAL(VERIFY), OP_SHA256/OP_HASH160/OP_HASH256 and OP_CHECKSIG(VERIFY).
+//! Any other opcode fails validation.
+//!
+//! **Reference**: TypeScript bsv-sdk `Spend` class
+
+use super::sighash::SIGHASH_FORKID;
+use super::{SigHash, SigHashType, Transaction, TransactionError, TransactionResult};
+use crate::crypto::{double_sha256, hash160, sha256};
+use secp256k1::{ecdsa::Signature, Message, PublicKey};
+
+/// Opcodes the interpreter executes
+pub mod opcodes {
+    pub const OP_0: u8 = 0x00;
+    pub const OP_PUSHDATA1: u8 = 0x4c;
+    pub const OP_PUSHDATA2: u8 = 0x4d;
+    pub const OP_PUSHDATA4: u8 = 0x4e;
+    pub const OP_1NEGATE: u8 = 0x4f;
+    pub const OP_1: u8 = 0x51;
+    pub const OP_16: u8 = 0x60;
+    pub const OP_NOP: u8 = 0x61;
+    pub const OP_VERIFY: u8 = 0x69;
+    pub const OP_RETURN: u8 = 0x6a;
+    pub const OP_TOALTSTACK: u8 = 0x6b;
+    pub const OP_FROMALTSTACK: u8 = 0x6c;
+    pub const OP_2DROP: u8 = 0x6d;
+    pub const OP_DROP: u8 = 0x75;
+    pub const OP_DUP: u8 = 0x76;
+    pub const OP_NIP: u8 = 0x77;
+    pub const OP_SWAP: u8 = 0x7c;
+    pub const OP_EQUAL: u8 = 0x87;
+    pub const OP_EQUALVERIFY: u8 = 0x88;
+    pub const OP_SHA256: u8 = 0xa8;
+    pub const OP_HASH160: u8 = 0xa9;
+    pub const OP_HASH256: u8 = 0xaa;
+    pub const OP_CHECKSIG: u8 = 0xac;
+    pub const OP_CHECKSIGVERIFY: u8 = 0xad;
+}
+
+use opcodes::*;
+
+/// One script operation: an opcode and, for pushes, its data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptChunk {
+    pub op: u8,
+    pub data: Option<Vec<u8>>,
+}
+
+/// Split a script into chunks
+///
+/// **Reference**: TypeScript `Script.fromBinary(bytes).chunks`
+pub fn parse_script(script: &[u8]) -> TransactionResult<Vec<ScriptChunk>> {
+    let truncated = || TransactionError::InvalidScript("push extends past end of script".to_string());
+    let mut chunks = Vec::new();
+    let mut pos = 0;
+    while pos < script.len() {
+        let op = script[pos];
+        pos += 1;
+        let len = match op {
+            0x01..=0x4b => op as usize,
+            OP_PUSHDATA1 => {
+                let len = *script.get(pos).ok_or_else(truncated)? as usize;
+                pos += 1;
+                len
+            }
+            OP_PUSHDATA2 => {
+                let bytes = script.get(pos..pos + 2).ok_or_else(truncated)?;
+                pos += 2;
+                u16::from_le_bytes([bytes[0], bytes[1]]) as usize
+            }
+            OP_PUSHDATA4 => {
+                let bytes = script.get(pos..pos + 4).ok_or_else(truncated)?;
+                pos += 4;
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+            }
+            _ => {
+                chunks.push(ScriptChunk { op, data: None });
+                continue;
+            }
+        };
+        let data = script.get(pos..pos + len).ok_or_else(truncated)?;
+        pos += len;
+        chunks.push(ScriptChunk { op, data: Some(data.to_vec()) });
+    }
+    Ok(chunks)
+}
+
+/// Script truthiness: any non-zero byte other than a trailing sign bit
+fn is_true(value: &[u8]) -> bool {
+    value
+        .iter()
+        .enumerate()
+        .any(|(i, &b)| b != 0 && !(i == value.len() - 1 && b == 0x80))
+}
+
+fn encode_bool(value: bool) -> Vec<u8> {
+    if value { vec![1] } else { Vec::new() }
+}
+
+/// Validation of one transaction input against the output it spends
+///
+/// **Reference**: TypeScript `new Spend({...}).validate()`
+#[derive(Debug, Clone)]
+pub struct Spend<'a> {
+    tx: &'a Transaction,
+    input_index: usize,
+    locking_script: &'a [u8],
+    source_satoshis: i64,
+}
+
+impl<'a> Spend<'a> {
+    /// Spend of `tx` input `input_index`, whose source output has
+    /// `locking_script` and `source_satoshis`
+    pub fn new(tx: &'a Transaction, input_index: usize, locking_script: &'a [u8], source_satoshis: i64) -> Self {
+        Self { tx, input_index, locking_script, source_satoshis }
+    }
+
+    /// Run the unlocking then the locking script
+    ///
+    /// The unlocking script must be push-only, and exactly one true value
+    /// must remain on the stack (clean stack rule).
+    pub fn validate(&self) -> TransactionResult<()> {
+        let input = self.tx.inputs.get(self.input_index).ok_or_else(|| {
+            TransactionError::InvalidFormat(format!("Input index {} out of range", self.input_index))
+        })?;
+
+        let unlocking = parse_script(&input.script_sig)?;
+        if unlocking.iter().any(|chunk| chunk.op > OP_16) {
+            return Err(TransactionError::InvalidScript("unlocking script must be push-only".to_string()));
+        }
+
+        let mut stack = Vec::new();
+        self.execute(&unlocking, &mut stack)?;
+        self.execute(&parse_script(self.locking_script)?, &mut stack)?;
+
+        match stack.as_slice() {
+            [top] if is_true(top) => Ok(()),
+            [_] => Err(TransactionError::InvalidScript("script evaluated to false".to_string())),
+            _ => Err(TransactionError::InvalidScript(format!(
+                "clean stack rule requires exactly one item after execution, found {}",
+                stack.len()
+            ))),
+        }
+    }
+
+    fn execute(&self, chunks: &[ScriptChunk], stack: &mut Vec<Vec<u8>>) -> TransactionResult<()> {
+        let mut alt_stack = Vec::new();
+        for chunk in chunks {
+            if let Some(data) = &chunk.data {
+                stack.push(data.clone());
+                continue;
+            }
+            match chunk.op {
+                OP_0 => stack.push(Vec::new()),
+                OP_1NEGATE => stack.push(vec![0x81]),
+                OP_1..=OP_16 => stack.push(vec![chunk.op - OP_1 + 1]),
+                OP_NOP => {}
+                OP_VERIFY => verify(pop(stack)?, "OP_VERIFY")?,
+                OP_RETURN => return Err(TransactionError::InvalidScript("OP_RETURN executed".to_string())),
+                OP_TOALTSTACK => alt_stack.push(pop(stack)?),
+                OP_FROMALTSTACK => stack.push(alt_stack.pop().ok_or_else(|| {
+                    TransactionError::InvalidScript("OP_FROMALTSTACK on empty alt stack".to_string())
+                })?),
+                OP_2DROP => {
+                    pop(stack)?;
+                    pop(stack)?;
+                }
+                OP_DROP => {
+                    pop(stack)?;
+                }
+                OP_DUP => {
+                    let top = stack.last().cloned().ok_or_else(empty_stack)?;
+                    stack.push(top);
+                }
+                OP_NIP => {
+                    let top = pop(stack)?;
+                    pop(stack)?;
+                    stack.push(top);
+                }
+                OP_SWAP => {
+                    let a = pop(stack)?;
+                    let b = pop(stack)?;
+                    stack.push(a);
+                    stack.push(b);
+                }
+                OP_EQUAL | OP_EQUALVERIFY => {
+                    let equal = pop(stack)? == pop(stack)?;
+                    if chunk.op == OP_EQUALVERIFY {
+                        verify(encode_bool(equal), "OP_EQUALVERIFY")?;
+                    } else {
+                        stack.push(encode_bool(equal));
+                    }
+                }
+                OP_SHA256 => {
+                    let value = pop(stack)?;
+                    stack.push(sha256(&value));
+                }
+                OP_HASH160 => {
+                    let value = pop(stack)?;
+                    stack.push(hash160(&value));
+                }
+                OP_HASH256 => {
+                    let value = pop(stack)?;
+                    stack.push(double_sha256(&value));
+                }
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                    let public_key = pop(stack)?;
+                    let signature = pop(stack)?;
+                    let valid = self.check_signature(&signature, &public_key)?;
+                    if chunk.op == OP_CHECKSIGVERIFY {
+                        verify(encode_bool(valid), "OP_CHECKSIGVERIFY")?;
+                    } else {
+                        stack.push(encode_bool(valid));
+                    }
+                }
+                op => {
+                    return Err(TransactionError::InvalidScript(format!("unsupported opcode 0x{:02x}", op)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a `<DER signature><sighash type>` against the input's sighash
+    ///
+    /// An empty signature is a valid "false"; a malformed one is an error.
+    fn check_signature(&self, signature: &[u8], public_key: &[u8]) -> TransactionResult<bool> {
+        let Some((&sighash_type, der)) = signature.split_last() else {
+            return Ok(false);
+        };
+        if sighash_type & SIGHASH_FORKID == 0 || SigHashType::from_u8(sighash_type).is_none() {
+            return Err(TransactionError::InvalidSignature(format!(
+                "sighash type 0x{:02x} is not a SIGHASH_FORKID type",
+                sighash_type
+            )));
+        }
+        let signature = Signature::from_der(der).map_err(|e| TransactionError::InvalidSignature(e.to_string()))?;
+        let public_key = PublicKey::from_slice(public_key)
+            .map_err(|e| TransactionError::InvalidSignature(format!("invalid public key: {}", e)))?;
+
+        let sighash = SigHash::calculate(self.tx, self.input_index, self.locking_script, sighash_type, self.source_satoshis)?;
+        let message = Message::from_digest_slice(&sighash).map_err(|e| TransactionError::Signing(e.to_string()))?;
+        Ok(secp256k1::SECP256K1.verify_ecdsa(&message, &signature, &public_key).is_ok())
+    }
+}
+
+fn empty_stack() -> TransactionError {
+    TransactionError::InvalidScript("stack underflow".to_string())
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> TransactionResult<Vec<u8>> {
+    stack.pop().ok_or_else(empty_stack)
+}
+
+fn verify(value: Vec<u8>, op: &str) -> TransactionResult<()> {
+    if is_true(&value) {
+        Ok(())
+    } else {
+        Err(TransactionError::InvalidScript(format!("{} failed", op)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{derive_public_key, sign_ecdsa};
+    use crate::transaction::{OutPoint, Script, TxInput, TxOutput};
+
+    const SATOSHIS: i64 = 1000;
+
+    fn spending_tx() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("aa".repeat(32), 0)));
+        tx.add_output(TxOutput::new(900, vec![OP_RETURN]));
+        tx
+    }
+
+    fn signature(tx: &Transaction, locking_script: &[u8], private_key: &[u8]) -> Vec<u8> {
+        let sighash = SigHash::calculate(tx, 0, locking_script, SigHashType::All.with_forkid(), SATOSHIS).unwrap();
+        sign_ecdsa(&sighash, private_key, SigHashType::All.with_forkid()).unwrap()
+    }
+
+    fn push(script: &mut Vec<u8>, data: &[u8]) {
+        script.push(data.len() as u8);
+        script.extend_from_slice(data);
+    }
+
+    #[test]
+    fn test_parse_pushdata() {
+        let mut script = vec![OP_PUSHDATA1, 2, 0xaa, 0xbb, OP_PUSHDATA2, 1, 0, 0xcc, OP_DUP];
+        assert_eq!(parse_script(&script).unwrap(), vec![
+            ScriptChunk { op: OP_PUSHDATA1, data: Some(vec![0xaa, 0xbb]) },
+            ScriptChunk { op: OP_PUSHDATA2, data: Some(vec![0xcc]) },
+            ScriptChunk { op: OP_DUP, data: None },
+        ]);
+        script.truncate(6);
+        assert!(parse_script(&script).is_err());
+    }
+
+    #[test]
+    fn test_p2pkh_unlock() {
+        let private_key = [3u8; 32];
+        let public_key = derive_public_key(&private_key).unwrap();
+        let locking_script = Script::p2pkh_locking_script(&hash160(&public_key)).unwrap().to_bytes().to_vec();
+
+        let mut tx = spending_tx();
+        let sig = signature(&tx, &locking_script, &private_key);
+        tx.inputs[0].set_script(Script::p2pkh_unlocking_script(&sig, &public_key).to_bytes().to_vec());
+        Spend::new(&tx, 0, &locking_script, SATOSHIS).validate().unwrap();
+
+        // Signatures without SIGHASH_FORKID are rejected
+        let sighash = SigHash::calculate(&tx, 0, &locking_script, SigHashType::All.as_u8(), SATOSHIS).unwrap();
+        let legacy = sign_ecdsa(&sighash, &private_key, SigHashType::All.as_u8()).unwrap();
+        let mut legacy_tx = tx.clone();
+        legacy_tx.inputs[0].set_script(Script::p2pkh_unlocking_script(&legacy, &public_key).to_bytes().to_vec());
+        assert!(Spend::new(&legacy_tx, 0, &locking_script, SATOSHIS).validate().is_err());
+
+        // Someone else's key fails OP_EQUALVERIFY
+        let other_key = derive_public_key(&[4u8; 32]).unwrap();
+        tx.inputs[0].set_script(Script::p2pkh_unlocking_script(&sig, &other_key).to_bytes().to_vec());
+        assert!(Spend::new(&tx, 0, &locking_script, SATOSHIS).validate().is_err());
+    }
+
+    #[test]
+    fn test_pushdrop_unlock() {
+        // <pubkey> OP_CHECKSIG <field> <field> OP_2DROP
+        let private_key = [5u8; 32];
+        let public_key = derive_public_key(&private_key).unwrap();
+        let mut locking_script = Vec::new();
+        push(&mut locking_script, &public_key);
+        locking_script.push(OP_CHECKSIG);
+        push(&mut locking_script, b"field one");
+        push(&mut locking_script, b"field two");
+        locking_script.push(OP_2DROP);
+
+        let mut tx = spending_tx();
+        let sig = signature(&tx, &locking_script, &private_key);
+        let mut unlocking_script = Vec::new();
+        push(&mut unlocking_script, &sig);
+        tx.inputs[0].set_script(unlocking_script);
+        Spend::new(&tx, 0, &locking_script, SATOSHIS).validate().unwrap();
+
+        // A signature by another key leaves false on the stack
+        let sig = signature(&tx, &locking_script, &[6u8; 32]);
+        let mut unlocking_script = Vec::new();
+        push(&mut unlocking_script, &sig);
+        tx.inputs[0].set_script(unlocking_script);
+        let err = Spend::new(&tx, 0, &locking_script, SATOSHIS).validate().unwrap_err();
+        assert!(err.to_string().contains("false"));
+    }
+
+    #[test]
+    fn test_rules() {
+        let tx = spending_tx();
+        let with_unlock = |unlocking: Vec<u8>, locking: &[u8]| {
+            let mut tx = tx.clone();
+            tx.inputs[0].set_script(unlocking);
+            Spend::new(&tx, 0, locking, SATOSHIS).validate()
+        };
+
+        assert!(with_unlock(vec![OP_1], &[]).is_ok());
+        // Clean stack
+        assert!(with_unlock(vec![OP_1, OP_1], &[]).is_err());
+        // Push-only unlocking scripts
+        assert!(with_unlock(vec![OP_1, OP_DUP], &[OP_DROP]).is_err());
+        assert!(with_unlock(vec![OP_1], &[OP_RETURN]).is_err());
+        assert!(with_unlock(vec![0x01, 0x07], &[0x01, 0x07, OP_EQUAL]).is_ok());
+        assert!(with_unlock(vec![0x01, 0x80], &[]).is_err());
+        assert!(with_unlock(vec![OP_1], &[0xb0]).is_err());
+    }
+}
@@ -13,6 +13,8 @@
 //! - Txid calculation (double SHA-256)
 //! - Sighash calculation for signing
 //! - Script operations
+//! - Script interpretation for unlock validation
+//! - A fluent builder for signed transactions
 //!
 //! ## Design Philosophy
 //!
@@ -30,13 +32,19 @@ pub mod tx_output;
 pub mod transaction;
 pub mod sighash;
 pub mod script;
+pub mod reader;
+pub mod interpreter;
+pub mod builder;
 
 pub use outpoint::OutPoint;
 pub use tx_input::TxInput;
 pub use tx_output::TxOutput;
 pub use transaction::Transaction;
-pub use sighash::{SigHash, SigHashType};
+pub use sighash::{SigHash, SigHashType, SIGHASH_FORKID};
 pub use script::Script;
+pub use reader::ByteReader;
+pub use interpreter::Spend;
+pub use builder::TxBuilder;
 
 /// Transaction error types
 #[derive(Debug, thiserror::Error)]
@@ -55,6 +63,9 @@ pub enum TransactionError {
     
     #[error("invalid signature: {0}")]
     InvalidSignature(String),
+    
+    #[error("insufficient funds: {required} satoshis required, {available} available")]
+    InsufficientFunds { required: i64, available: i64 },
 }
 
 pub type TransactionResult<T> = Result<T, TransactionError>;
@@ -0,0 +1,111 @@
+//! Binary Reader
+//!
+//! Little-endian cursor over a byte slice, used to parse transactions,
+//! merkle paths and BEEF.
+//!
+//! **Reference**: TypeScript bsv-sdk `Utils.Reader`
+
+use super::{TransactionError, TransactionResult};
+
+/// Cursor over serialized bytes
+///
+/// **Reference**: TypeScript `Reader`
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Create a reader positioned at the start of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// True when all bytes have been consumed
+    pub fn eof(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Current read position
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Read `n` bytes
+    pub fn read_bytes(&mut self, n: usize) -> TransactionResult<&'a [u8]> {
+        let end = self.pos.checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| TransactionError::InvalidFormat(
+                format!("unexpected end of data reading {} bytes at {}", n, self.pos)
+            ))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Read `n` bytes in reverse order (txids and hashes)
+    pub fn read_reverse(&mut self, n: usize) -> TransactionResult<Vec<u8>> {
+        Ok(self.read_bytes(n)?.iter().rev().copied().collect())
+    }
+
+    pub fn read_u8(&mut self) -> TransactionResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16_le(&mut self) -> TransactionResult<u16> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn read_u32_le(&mut self) -> TransactionResult<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64_le(&mut self) -> TransactionResult<u64> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Read a Bitcoin VarInt
+    ///
+    /// **Reference**: TypeScript `Reader.readVarIntNum()`
+    pub fn read_varint(&mut self) -> TransactionResult<u64> {
+        match self.read_u8()? {
+            0xFD => Ok(self.read_u16_le()? as u64),
+            0xFE => Ok(self.read_u32_le()? as u64),
+            0xFF => self.read_u64_le(),
+            n => Ok(n as u64),
+        }
+    }
+
+    /// Read a VarInt length followed by that many bytes
+    pub fn read_var_bytes(&mut self) -> TransactionResult<&'a [u8]> {
+        let len = self.read_varint()? as usize;
+        self.read_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_varint_forms() {
+        let data = [0x05, 0xFD, 0x34, 0x12, 0xFE, 0x78, 0x56, 0x34, 0x12];
+        let mut r = ByteReader::new(&data);
+        assert_eq!(r.read_varint().unwrap(), 5);
+        assert_eq!(r.read_varint().unwrap(), 0x1234);
+        assert_eq!(r.read_varint().unwrap(), 0x12345678);
+        assert!(r.eof());
+    }
+
+    #[test]
+    fn test_read_past_end_fails() {
+        let mut r = ByteReader::new(&[1, 2]);
+        assert!(r.read_u32_le().is_err());
+    }
+
+    #[test]
+    fn test_read_reverse() {
+        let mut r = ByteReader::new(&[1, 2, 3]);
+        assert_eq!(r.read_reverse(3).unwrap(), vec![3, 2, 1]);
+    }
+}
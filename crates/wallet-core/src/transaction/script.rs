@@ -1,6 +1,7 @@
 //! Bitcoin Script Operations
 //!
-//! Minimal script building functionality for P2PKH transactions.
+//! Minimal script building functionality for P2PKH transactions and
+//! OP_RETURN data outputs.
 //!
 //! **Reference**: TypeScript bsv-sdk Script class
 
@@ -89,6 +90,19 @@ impl Script {
         Self { bytes }
     }
     
+    /// Build an unspendable data script
+    ///
+    /// Format: OP_FALSE OP_RETURN <data>
+    ///
+    /// **Reference**: bsv legacy `Script.buildSafeDataOut(data)`
+    pub fn data_output_script(data: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(data.len() + 7);
+        bytes.push(0x00); // OP_FALSE
+        bytes.push(0x6a); // OP_RETURN
+        push_data(&mut bytes, data);
+        Self { bytes }
+    }
+    
     /// Get script length
     pub fn len(&self) -> usize {
         self.bytes.len()
@@ -100,6 +114,26 @@ impl Script {
     }
 }
 
+/// Append the smallest push of `data` to `bytes`
+///
+/// **Reference**: TypeScript `Script.writeBin(bin)`
+fn push_data(bytes: &mut Vec<u8>, data: &[u8]) {
+    let len = data.len();
+    if len <= 0x4b {
+        bytes.push(len as u8);
+    } else if len <= 0xff {
+        bytes.push(0x4c); // OP_PUSHDATA1
+        bytes.push(len as u8);
+    } else if len <= 0xffff {
+        bytes.push(0x4d); // OP_PUSHDATA2
+        bytes.extend_from_slice(&(len as u16).to_le_bytes());
+    } else {
+        bytes.push(0x4e); // OP_PUSHDATA4
+        bytes.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(data);
+}
+
 impl Default for Script {
     fn default() -> Self {
         Self::new()
@@ -156,6 +190,17 @@ mod tests {
         assert_eq!(script.bytes[72], 33); // Public key length
     }
     
+    #[test]
+    fn test_data_output_script() {
+        // OP_FALSE OP_RETURN, then each push size's encoding
+        assert_eq!(Script::data_output_script(b"hi").to_hex(), "006a026869");
+        for (len, prefix) in [(75, vec![0x4b]), (76, vec![0x4c, 76]), (256, vec![0x4d, 0, 1]), (70_000, vec![0x4e, 0x70, 0x11, 1, 0])] {
+            let script = Script::data_output_script(&vec![7u8; len]);
+            assert_eq!(&script.bytes[2..2 + prefix.len()], prefix.as_slice(), "push of {} bytes", len);
+            assert_eq!(script.len(), 2 + prefix.len() + len);
+        }
+    }
+    
     #[test]
     fn test_p2pkh_invalid_hash_length() {
         // TS Reference: Validation of public key hash length
@@ -2,14 +2,30 @@
 //!
 //! Implements Bitcoin signature hash calculation for transaction signing.
 //!
-//! **Reference**: TypeScript bsv-sdk sighash calculation
+//! With SIGHASH_FORKID set (every signature BSV accepts) the BIP143-style
+//! preimage is hashed, committing to the spent output's value; without it
+//! the original Satoshi algorithm is used. Both honour NONE, SINGLE and
+//! ANYONECANPAY.
+//!
+//! **Reference**: TypeScript bsv-sdk `TransactionSignature.format`
 
-use super::{Transaction, TxInput, TransactionError, TransactionResult};
+use super::transaction::encode_varint;
+use super::{Transaction, TxOutput, TransactionError, TransactionResult};
 use sha2::{Sha256, Digest};
 
+/// BSV replay protection flag, set on every signature since the fork
+///
+/// **Reference**: TypeScript `TransactionSignature.SIGHASH_FORKID`
+pub const SIGHASH_FORKID: u8 = 0x40;
+
+/// Mask selecting the base type (ALL, NONE, SINGLE) from a sighash byte
+const BASE_TYPE_MASK: u8 = 0x1f;
+
 /// Sighash type flags
 ///
-/// Determines which parts of the transaction are signed.
+/// Determines which parts of the transaction are signed. The sighash byte
+/// carried by a signature is a base type, optionally OR-ed with
+/// `AnyoneCanPay`, with `SIGHASH_FORKID` set.
 ///
 /// **Reference**: TypeScript `SigHash` enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +50,23 @@ impl SigHashType {
         self as u8
     }
     
+    /// Sighash byte for this type with `SIGHASH_FORKID` set
+    ///
+    /// `SigHashType::All.with_forkid()` (0x41) is what wallets sign with.
+    pub fn with_forkid(self) -> u8 {
+        self.as_u8() | SIGHASH_FORKID
+    }
+    
+    /// Base sighash type of a signature's trailing byte
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value & BASE_TYPE_MASK {
+            0x01 => Some(SigHashType::All),
+            0x02 => Some(SigHashType::None),
+            0x03 => Some(SigHashType::Single),
+            _ => None,
+        }
+    }
+    
     /// Get sighash type as u32 for serialization
     pub fn as_u32(self) -> u32 {
         self as u32
@@ -52,14 +85,14 @@ impl SigHash {
     ///
     /// This is the hash that gets signed by the private key.
     ///
-    /// **Reference**: TypeScript `tx.sighash(vin, subscript, type)`
+    /// **Reference**: TypeScript `TransactionSignature.format(params)` then `sha256sha256`
     ///
     /// ## Arguments
     /// - `tx`: The transaction being signed
     /// - `input_index`: Index of the input being signed
     /// - `prev_script`: The locking script from the output being spent (subscript)
-    /// - `sighash_type`: Type of signature (usually SigHashType::All)
-    /// - `prev_value`: Value of the output being spent (for BIP143)
+    /// - `sighash_type`: Full sighash byte, e.g. `SigHashType::All.with_forkid()`
+    /// - `prev_value`: Value of the output being spent (committed to with FORKID)
     ///
     /// ## Returns
     /// 32-byte hash to be signed
@@ -67,43 +100,138 @@ impl SigHash {
         tx: &Transaction,
         input_index: usize,
         prev_script: &[u8],
-        sighash_type: SigHashType,
-        _prev_value: i64, // For BIP143 (not used in legacy)
+        sighash_type: u8,
+        prev_value: i64,
     ) -> TransactionResult<Vec<u8>> {
         if input_index >= tx.inputs.len() {
             return Err(TransactionError::InvalidFormat(
                 format!("Input index {} out of range", input_index)
             ));
         }
+        let base_type = SigHashType::from_u8(sighash_type).ok_or_else(|| {
+            TransactionError::InvalidFormat(format!("Unsupported sighash type 0x{:02x}", sighash_type))
+        })?;
         
-        // For SIGHASH_ALL (most common case):
-        // 1. Copy transaction
-        // 2. Replace all input scripts with empty scripts
-        // 3. Set the current input's script to the previous output's script
-        // 4. Serialize with sighash type appended
-        // 5. Double SHA-256
+        let preimage = if sighash_type & SIGHASH_FORKID != 0 {
+            Self::forkid_preimage(tx, input_index, prev_script, sighash_type, base_type, prev_value)?
+        } else {
+            match Self::legacy_preimage(tx, input_index, prev_script, sighash_type, base_type)? {
+                Some(preimage) => preimage,
+                // SINGLE without a matching output signs the number one
+                None => {
+                    let mut one = vec![0u8; 32];
+                    one[0] = 1;
+                    return Ok(one);
+                }
+            }
+        };
         
+        Ok(double_sha256(&preimage))
+    }
+    
+    /// BIP143-style preimage used with SIGHASH_FORKID
+    ///
+    /// **Reference**: TypeScript `TransactionSignature.format`
+    fn forkid_preimage(
+        tx: &Transaction,
+        input_index: usize,
+        prev_script: &[u8],
+        sighash_type: u8,
+        base_type: SigHashType,
+        prev_value: i64,
+    ) -> TransactionResult<Vec<u8>> {
+        let anyone_can_pay = sighash_type & SigHashType::AnyoneCanPay.as_u8() != 0;
+        let serialize_error = |e: hex::FromHexError| TransactionError::Serialization(e.to_string());
+        
+        let hash_prevouts = if anyone_can_pay {
+            vec![0u8; 32]
+        } else {
+            let mut prevouts = Vec::with_capacity(36 * tx.inputs.len());
+            for input in &tx.inputs {
+                prevouts.extend_from_slice(&input.prev_out.serialize().map_err(serialize_error)?);
+            }
+            double_sha256(&prevouts)
+        };
+        
+        let hash_sequence = if anyone_can_pay || base_type != SigHashType::All {
+            vec![0u8; 32]
+        } else {
+            let sequences: Vec<u8> = tx.inputs.iter().flat_map(|input| input.sequence.to_le_bytes()).collect();
+            double_sha256(&sequences)
+        };
+        
+        let hash_outputs = match base_type {
+            SigHashType::All => double_sha256(&tx.outputs.iter().flat_map(TxOutput::serialize).collect::<Vec<u8>>()),
+            SigHashType::Single if input_index < tx.outputs.len() => double_sha256(&tx.outputs[input_index].serialize()),
+            _ => vec![0u8; 32],
+        };
+        
+        let input = &tx.inputs[input_index];
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&tx.version.to_le_bytes());
+        preimage.extend_from_slice(&hash_prevouts);
+        preimage.extend_from_slice(&hash_sequence);
+        preimage.extend_from_slice(&input.prev_out.serialize().map_err(serialize_error)?);
+        preimage.extend_from_slice(&encode_varint(prev_script.len() as u64));
+        preimage.extend_from_slice(prev_script);
+        preimage.extend_from_slice(&prev_value.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&hash_outputs);
+        preimage.extend_from_slice(&tx.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&(sighash_type as u32).to_le_bytes());
+        Ok(preimage)
+    }
+    
+    /// Original sighash preimage: a modified copy of the transaction
+    ///
+    /// Returns `None` for SINGLE when the input has no matching output.
+    fn legacy_preimage(
+        tx: &Transaction,
+        input_index: usize,
+        prev_script: &[u8],
+        sighash_type: u8,
+        base_type: SigHashType,
+    ) -> TransactionResult<Option<Vec<u8>>> {
         let mut sighash_tx = tx.clone();
         
-        // Clear all input scripts
+        // Clear all input scripts, except the signed input's subscript
         for input in &mut sighash_tx.inputs {
             input.script_sig = Vec::new();
         }
-        
-        // Set current input's script to prev_script (subscript)
         sighash_tx.inputs[input_index].script_sig = prev_script.to_vec();
         
-        // Serialize transaction
-        let mut serialized = sighash_tx.serialize()?;
+        match base_type {
+            SigHashType::None => {
+                sighash_tx.outputs.clear();
+            }
+            SigHashType::Single => {
+                if input_index >= tx.outputs.len() {
+                    return Ok(None);
+                }
+                sighash_tx.outputs.truncate(input_index + 1);
+                for output in &mut sighash_tx.outputs[..input_index] {
+                    *output = TxOutput::new(-1, Vec::new());
+                }
+            }
+            _ => {}
+        }
         
-        // Append sighash type (4 bytes, little-endian)
-        serialized.extend_from_slice(&sighash_type.as_u32().to_le_bytes());
+        // Other inputs' sequences are not signed with NONE or SINGLE
+        if base_type != SigHashType::All {
+            for (i, input) in sighash_tx.inputs.iter_mut().enumerate() {
+                if i != input_index {
+                    input.sequence = 0;
+                }
+            }
+        }
         
-        // Double SHA-256
-        let hash1 = Sha256::digest(&serialized);
-        let hash2 = Sha256::digest(&hash1);
+        if sighash_type & SigHashType::AnyoneCanPay.as_u8() != 0 {
+            sighash_tx.inputs = vec![sighash_tx.inputs[input_index].clone()];
+        }
         
-        Ok(hash2.to_vec())
+        let mut serialized = sighash_tx.serialize()?;
+        serialized.extend_from_slice(&(sighash_type as u32).to_le_bytes());
+        Ok(Some(serialized))
     }
     
     /// Calculate sighash and return as hex string
@@ -111,7 +239,7 @@ impl SigHash {
         tx: &Transaction,
         input_index: usize,
         prev_script: &[u8],
-        sighash_type: SigHashType,
+        sighash_type: u8,
         prev_value: i64,
     ) -> TransactionResult<String> {
         let hash = Self::calculate(tx, input_index, prev_script, sighash_type, prev_value)?;
@@ -119,10 +247,14 @@ impl SigHash {
     }
 }
 
+fn double_sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(Sha256::digest(data)).to_vec()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::transaction::{OutPoint, TxOutput};
+    use crate::transaction::{OutPoint, TxInput};
     
     #[test]
     fn test_sighash_type_values() {
@@ -156,7 +288,7 @@ mod tests {
             &tx,
             0,
             &prev_script,
-            SigHashType::All,
+            SigHashType::All.with_forkid(),
             50000,
         ).unwrap();
         
@@ -174,7 +306,7 @@ mod tests {
             &tx,
             0, // No inputs exist
             &prev_script,
-            SigHashType::All,
+            SigHashType::All.with_forkid(),
             0,
         );
         
@@ -194,10 +326,72 @@ mod tests {
         
         let prev_script = vec![0x76, 0xa9, 0x14];
         
-        let hash1 = SigHash::calculate(&tx, 0, &prev_script, SigHashType::All, 50000).unwrap();
-        let hash2 = SigHash::calculate(&tx, 0, &prev_script, SigHashType::All, 50000).unwrap();
+        let hash1 = SigHash::calculate(&tx, 0, &prev_script, SigHashType::All.with_forkid(), 50000).unwrap();
+        let hash2 = SigHash::calculate(&tx, 0, &prev_script, SigHashType::All.with_forkid(), 50000).unwrap();
         
         // Should be identical
         assert_eq!(hash1, hash2);
     }
+    
+    fn two_by_two() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("11".repeat(32), 0)));
+        tx.add_input(TxInput::new(OutPoint::new("22".repeat(32), 1)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+        tx.add_output(TxOutput::new(2000, vec![0x52]));
+        tx
+    }
+    
+    #[test]
+    fn test_forkid_commits_to_value() {
+        let tx = two_by_two();
+        let all = SigHashType::All.with_forkid();
+        assert_eq!(all, 0x41);
+        assert_ne!(
+            SigHash::calculate(&tx, 0, &[0x51], all, 1000).unwrap(),
+            SigHash::calculate(&tx, 0, &[0x51], all, 1001).unwrap()
+        );
+        assert_ne!(
+            SigHash::calculate(&tx, 0, &[0x51], all, 1000).unwrap(),
+            SigHash::calculate(&tx, 0, &[0x51], SigHashType::All.as_u8(), 1000).unwrap()
+        );
+        assert!(SigHash::calculate(&tx, 0, &[0x51], SIGHASH_FORKID, 1000).is_err());
+    }
+    
+    #[test]
+    fn test_sighash_flags_select_signed_parts() {
+        for forkid in [0, SIGHASH_FORKID] {
+            let tx = two_by_two();
+            let hash = |tx: &Transaction, vin: usize, flags: u8| {
+                SigHash::calculate(tx, vin, &[0x51], flags | forkid, 1000).unwrap()
+            };
+            let all = SigHashType::All.as_u8();
+            let none = SigHashType::None.as_u8();
+            let single = SigHashType::Single.as_u8();
+            let anyone_can_pay = SigHashType::AnyoneCanPay.as_u8();
+            
+            // NONE ignores outputs; ALL does not
+            let mut changed_outputs = tx.clone();
+            changed_outputs.outputs[1].value = 1;
+            assert_eq!(hash(&tx, 0, none), hash(&changed_outputs, 0, none));
+            assert_ne!(hash(&tx, 0, all), hash(&changed_outputs, 0, all));
+            
+            // SINGLE signs only the output at the input's index
+            assert_eq!(hash(&tx, 0, single), hash(&changed_outputs, 0, single));
+            assert_ne!(hash(&tx, 1, single), hash(&changed_outputs, 1, single));
+            
+            // ANYONECANPAY ignores the other inputs
+            let mut added_input = tx.clone();
+            added_input.inputs[1] = TxInput::new(OutPoint::new("33".repeat(32), 2));
+            assert_eq!(hash(&tx, 0, all | anyone_can_pay), hash(&added_input, 0, all | anyone_can_pay));
+            assert_ne!(hash(&tx, 0, all), hash(&added_input, 0, all));
+            
+            // NONE and SINGLE don't sign the other inputs' sequences
+            let mut changed_sequence = tx.clone();
+            changed_sequence.inputs[1].set_sequence(7);
+            assert_eq!(hash(&tx, 0, none), hash(&changed_sequence, 0, none));
+            assert_eq!(hash(&tx, 0, single), hash(&changed_sequence, 0, single));
+            assert_ne!(hash(&tx, 0, all), hash(&changed_sequence, 0, all));
+        }
+    }
 }
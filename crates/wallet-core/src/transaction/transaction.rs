@@ -4,7 +4,7 @@
 //!
 //! **Reference**: TypeScript bsv-sdk Transaction class
 
-use super::{TxInput, TxOutput, OutPoint, Script, TransactionError, TransactionResult};
+use super::{TxInput, TxOutput, OutPoint, Script, ByteReader, TransactionError, TransactionResult};
 use sha2::{Sha256, Digest};
 use serde::{Deserialize, Serialize};
 
@@ -112,6 +112,58 @@ impl Transaction {
         Ok(buffer)
     }
     
+    /// Parse a transaction from its serialized bytes
+    ///
+    /// Fails if the bytes are truncated or have trailing data.
+    ///
+    /// **Reference**: TypeScript `Transaction.fromBinary(bin)`
+    pub fn from_bytes(bytes: &[u8]) -> TransactionResult<Self> {
+        let mut reader = ByteReader::new(bytes);
+        let tx = Self::read_from(&mut reader)?;
+        if !reader.eof() {
+            return Err(TransactionError::InvalidFormat(
+                format!("{} trailing bytes after transaction", bytes.len() - reader.position())
+            ));
+        }
+        Ok(tx)
+    }
+    
+    /// Parse a transaction from a reader, leaving any following data unread
+    ///
+    /// **Reference**: TypeScript `Transaction.fromReader(reader)`
+    pub fn read_from(reader: &mut ByteReader<'_>) -> TransactionResult<Self> {
+        let version = reader.read_u32_le()?;
+        
+        let input_count = reader.read_varint()?;
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            let txid = hex::encode(reader.read_reverse(32)?);
+            let vout = reader.read_u32_le()?;
+            let script_sig = reader.read_var_bytes()?.to_vec();
+            let sequence = reader.read_u32_le()?;
+            let mut input = TxInput::with_sequence(OutPoint::new(txid, vout), sequence);
+            input.set_script(script_sig);
+            inputs.push(input);
+        }
+        
+        let output_count = reader.read_varint()?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            let value = reader.read_u64_le()? as i64;
+            let script_pubkey = reader.read_var_bytes()?.to_vec();
+            outputs.push(TxOutput::new(value, script_pubkey));
+        }
+        
+        let lock_time = reader.read_u32_le()?;
+        
+        Ok(Self {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        })
+    }
+    
     /// Calculate transaction ID (txid)
     ///
     /// Txid is double SHA-256 of serialized transaction, reversed.
@@ -158,7 +210,7 @@ impl Default for Transaction {
 }
 
 /// Encode variable-length integer (varint)
-fn encode_varint(n: u64) -> Vec<u8> {
+pub(crate) fn encode_varint(n: u64) -> Vec<u8> {
     if n < 0xFD {
         vec![n as u8]
     } else if n <= 0xFFFF {
@@ -236,6 +288,37 @@ mod tests {
         assert_eq!(u32::from_le_bytes(serialized[6..10].try_into().unwrap()), 0);
     }
     
+    #[test]
+    fn test_transaction_from_bytes_roundtrip() {
+        // TS Reference: Transaction.fromBinary(tx.toBinary())
+        let mut tx = Transaction::with_params(2, vec![], vec![], 500);
+        let mut input = TxInput::with_sequence(OutPoint::new("ab".repeat(32), 3), 0xFFFFFFFE);
+        input.set_script(vec![0x51; 300]);
+        tx.add_input(input);
+        tx.add_output(TxOutput::new(12345, vec![0x76, 0xa9]));
+        tx.add_output(TxOutput::new(0, vec![0x6a]));
+        
+        let bytes = tx.serialize().unwrap();
+        let parsed = Transaction::from_bytes(&bytes).unwrap();
+        
+        assert_eq!(parsed.version, 2);
+        assert_eq!(parsed.lock_time, 500);
+        assert_eq!(parsed.inputs, tx.inputs);
+        assert_eq!(parsed.outputs.len(), 2);
+        assert_eq!(parsed.outputs[0].value, 12345);
+        assert_eq!(parsed.txid().unwrap(), tx.txid().unwrap());
+    }
+    
+    #[test]
+    fn test_transaction_from_bytes_rejects_truncated_and_trailing() {
+        let bytes = Transaction::new().serialize().unwrap();
+        assert!(Transaction::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(Transaction::from_bytes(&extra).is_err());
+    }
+    
     #[test]
     fn test_transaction_txid() {
         // TS Reference: tx.id('hex') - txid calculation
@@ -0,0 +1,211 @@
+//! Currency Formatting
+//!
+//! Satoshi amounts as display strings for the Tauri and mobile layers, in
+//! satoshis, BSV, or a fiat currency at the current exchange rate.
+//!
+//! Not part of the TypeScript toolbox: metanet-desktop formats amounts in
+//! its UI from `WalletSettings.currency` and the services' exchange rates.
+//! Rates are cached in `DisplayRates` so rendering a list of amounts does
+//! not cost one rate lookup per amount.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::sdk::errors::{WalletError, WalletResult};
+use crate::services::FiatRateProvider;
+
+/// Satoshis per BSV
+pub const SATOSHIS_PER_BSV: i64 = 100_000_000;
+
+/// Currency used when the user has no preference
+pub const DEFAULT_DISPLAY_CURRENCY: &str = "SATS";
+
+/// How long a fetched exchange rate is reused
+///
+/// Matches the 15 minute BSV rate freshness of the wallet services.
+pub const DISPLAY_RATE_TTL: Duration = Duration::minutes(15);
+
+/// Symbols written before the amount; other currencies get their code after it
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[("USD", "$"), ("EUR", "€"), ("GBP", "£"), ("JPY", "¥")];
+
+/// Currencies without minor units
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &["JPY", "KRW"];
+
+/// Display currency: `SATS`, `BSV`, or an ISO 4217 fiat code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayCurrency {
+    Satoshis,
+    Bsv,
+    Fiat(String),
+}
+
+impl DisplayCurrency {
+    /// Parse a currency code, case-insensitively
+    pub fn parse(currency: &str) -> WalletResult<Self> {
+        let code = currency.trim().to_ascii_uppercase();
+        match code.as_str() {
+            "SATS" | "SATOSHIS" => Ok(Self::Satoshis),
+            "BSV" => Ok(Self::Bsv),
+            _ if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => Ok(Self::Fiat(code)),
+            _ => Err(WalletError::invalid_parameter("currency", "SATS, BSV or an ISO 4217 currency code")),
+        }
+    }
+}
+
+/// `digits` with a comma between each group of three
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Format `satoshis` in `currency`
+///
+/// Satoshis and BSV are exact, e.g. "1,234 satoshis" and "0.00001234 BSV".
+/// Fiat amounts need `bsv_rate`, the price of one BSV in that currency, and
+/// are rounded to the currency's minor unit, e.g. "$1.23" or "1.23 CHF".
+pub fn format_satoshis(satoshis: i64, currency: &DisplayCurrency, bsv_rate: Option<f64>) -> WalletResult<String> {
+    let sign = if satoshis < 0 { "-" } else { "" };
+    let amount = satoshis.unsigned_abs();
+    match currency {
+        DisplayCurrency::Satoshis => {
+            let unit = if amount == 1 { "satoshi" } else { "satoshis" };
+            Ok(format!("{}{} {}", sign, group_thousands(&amount.to_string()), unit))
+        }
+        DisplayCurrency::Bsv => {
+            let per_bsv = SATOSHIS_PER_BSV as u64;
+            let fraction = format!("{:08}", amount % per_bsv);
+            let fraction = fraction.trim_end_matches('0');
+            let whole = group_thousands(&(amount / per_bsv).to_string());
+            match fraction {
+                "" => Ok(format!("{}{} BSV", sign, whole)),
+                _ => Ok(format!("{}{}.{} BSV", sign, whole, fraction)),
+            }
+        }
+        DisplayCurrency::Fiat(code) => {
+            let rate = bsv_rate
+                .filter(|r| r.is_finite() && *r >= 0.0)
+                .ok_or_else(|| WalletError::invalid_operation(format!("No exchange rate for {}", code)))?;
+            let decimals = if ZERO_DECIMAL_CURRENCIES.contains(&code.as_str()) { 0 } else { 2 };
+            let value = format!("{:.*}", decimals, amount as f64 / SATOSHIS_PER_BSV as f64 * rate);
+            let (whole, fraction) = value.split_once('.').map_or((value.as_str(), None), |(w, f)| (w, Some(f)));
+            let number = match fraction {
+                Some(fraction) => format!("{}.{}", group_thousands(whole), fraction),
+                None => group_thousands(whole),
+            };
+            match CURRENCY_SYMBOLS.iter().find(|(c, _)| c == code) {
+                Some((_, symbol)) => Ok(format!("{}{}{}", sign, symbol, number)),
+                None => Ok(format!("{}{} {}", sign, number, code)),
+            }
+        }
+    }
+}
+
+/// Current BSV rates by currency, cached for `DISPLAY_RATE_TTL`
+pub struct DisplayRates {
+    provider: Arc<dyn FiatRateProvider>,
+    ttl: Duration,
+    rates: Mutex<HashMap<String, (f64, DateTime<Utc>)>>,
+}
+
+impl DisplayRates {
+    pub fn new(provider: Arc<dyn FiatRateProvider>) -> Self {
+        Self::with_ttl(provider, DISPLAY_RATE_TTL)
+    }
+
+    pub fn with_ttl(provider: Arc<dyn FiatRateProvider>, ttl: Duration) -> Self {
+        Self { provider, ttl, rates: Mutex::new(HashMap::new()) }
+    }
+
+    /// Price of one BSV in `currency`, fetched at most once per TTL
+    pub async fn bsv_rate(&self, currency: &str) -> WalletResult<f64> {
+        let now = Utc::now();
+        if let Some((rate, fetched_at)) = self.rates.lock().unwrap_or_else(|e| e.into_inner()).get(currency) {
+            if now - *fetched_at < self.ttl {
+                return Ok(*rate);
+            }
+        }
+        let rate = self.provider.get_bsv_rate(currency).await?;
+        self.rates.lock().unwrap_or_else(|e| e.into_inner()).insert(currency.to_string(), (rate, now));
+        Ok(rate)
+    }
+
+    /// Format `satoshis` in `currency`, looking up the rate for fiat currencies
+    pub async fn format_satoshis(&self, satoshis: i64, currency: &DisplayCurrency) -> WalletResult<String> {
+        let rate = match currency {
+            DisplayCurrency::Fiat(code) => Some(self.bsv_rate(code).await?),
+            _ => None,
+        };
+        format_satoshis(satoshis, currency, rate)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wallet_storage::StorageResult;
+
+    fn format(satoshis: i64, currency: &str, rate: Option<f64>) -> WalletResult<String> {
+        format_satoshis(satoshis, &DisplayCurrency::parse(currency)?, rate)
+    }
+
+    #[test]
+    fn test_format_satoshis() {
+        assert_eq!(format(1_234_567, "sats", None).unwrap(), "1,234,567 satoshis");
+        assert_eq!(format(1, "SATS", None).unwrap(), "1 satoshi");
+        assert_eq!(format(1_234, "BSV", None).unwrap(), "0.00001234 BSV");
+        assert_eq!(format(-250_000_000_000, "bsv", None).unwrap(), "-2,500 BSV");
+        assert_eq!(format(123_456_789, "USD", Some(50.0)).unwrap(), "$61.73");
+        assert_eq!(format(2_000_000_000, "jpy", Some(7_000.5)).unwrap(), "¥140,010");
+        assert_eq!(format(100_000_000, "CHF", Some(1_234.5)).unwrap(), "1,234.50 CHF");
+
+        assert!(format(1, "USD", None).is_err());
+        assert!(format(1, "dollars", Some(1.0)).is_err());
+    }
+
+    /// USD rates, counting lookups
+    struct CountingRates(AtomicUsize);
+
+    #[async_trait]
+    impl FiatRateProvider for CountingRates {
+        fn currency(&self) -> &str {
+            "USD"
+        }
+
+        async fn get_bsv_rate_at(&self, _time: DateTime<Utc>) -> StorageResult<f64> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(40.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_display_rates_cached() {
+        let provider = Arc::new(CountingRates(AtomicUsize::new(0)));
+        let rates = DisplayRates::new(provider.clone());
+        let usd = DisplayCurrency::parse("USD").unwrap();
+        assert_eq!(rates.format_satoshis(50_000_000, &usd).await.unwrap(), "$20.00");
+        assert_eq!(rates.format_satoshis(25_000_000, &usd).await.unwrap(), "$10.00");
+        assert_eq!(provider.0.load(Ordering::SeqCst), 1);
+
+        // Only the provider's own currency is quoted
+        assert!(rates.format_satoshis(1, &DisplayCurrency::parse("EUR").unwrap()).await.is_err());
+
+        let expired = DisplayRates::with_ttl(provider.clone(), Duration::zero());
+        expired.bsv_rate("USD").await.unwrap();
+        expired.bsv_rate("USD").await.unwrap();
+        assert_eq!(provider.0.load(Ordering::SeqCst), 3);
+    }
+}
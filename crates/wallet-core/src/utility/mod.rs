@@ -1,14 +1,27 @@
 // Utility module stubs
+pub mod currency_format;
+pub mod encrypted_message;
 pub mod index_all;
 pub mod index_client;
+pub mod signed_message;
 
 use crate::sdk::errors::{WalletError, WalletResult};
+use crate::crypto::{derive_public_key, hash160, sign_ecdsa};
+use crate::keys::brc42;
+use crate::transaction::{Script, SigHash, SigHashType, Transaction};
+
+/// BRC-29 protocol ID used for wallet change and payment outputs
+///
+/// Reference: TypeScript `brc29ProtocolID` (ScriptTemplateBRC29.ts)
+pub const BRC29_PROTOCOL_ID: (u8, &str) = (2, "3241645161d8");
 
 /// Script template for SABPPP (Signature-Authenticated Bitcoin Payment Protocol)
 ///
-/// Reference: TypeScript ScriptTemplate from @bsv/sdk
+/// Implements BRC-29 P2PKH locking and unlocking. The locking key is derived
+/// with BRC-42 using the invoice number
+/// `2-3241645161d8-<derivationPrefix> <derivationSuffix>`.
 ///
-/// TODO: Full implementation needed for BRC-29 change locking scripts
+/// Reference: TypeScript ScriptTemplateBRC29 from @wallet-toolbox
 #[derive(Debug, Clone)]
 pub struct ScriptTemplateSABPPP {
     /// Derivation prefix
@@ -18,6 +31,11 @@ pub struct ScriptTemplateSABPPP {
 }
 
 impl ScriptTemplateSABPPP {
+    /// Expected unlocking script length (signature push + pubkey push)
+    ///
+    /// Reference: TypeScript `ScriptTemplateBRC29.unlockLength`
+    pub const UNLOCK_LENGTH: usize = 108;
+
     /// Create a new SABPPP script template
     pub fn new(derivation_prefix: String, derivation_suffix: String) -> Self {
         Self {
@@ -25,26 +43,209 @@ impl ScriptTemplateSABPPP {
             derivation_suffix,
         }
     }
-    
-    /// Lock with private and public keys
-    /// 
-    /// TODO: Implement full BRC-29 locking script generation
-    pub fn lock(&self, _private_key: &[u8], _public_key: &[u8]) -> Vec<u8> {
-        // Placeholder - needs full BRC-29 implementation
-        Vec::new()
+
+    /// BRC-43 key ID: `<derivationPrefix> <derivationSuffix>`
+    ///
+    /// Reference: TypeScript `getKeyID()`
+    pub fn key_id(&self) -> String {
+        format!("{} {}", self.derivation_prefix, self.derivation_suffix)
+    }
+
+    /// BRC-43 invoice number used for BRC-42 derivation
+    pub fn invoice_number(&self) -> String {
+        format!("{}-{}-{}", BRC29_PROTOCOL_ID.0, BRC29_PROTOCOL_ID.1, self.key_id())
     }
-    
-    /// Unlock with signature
+
+    /// Lock with the locker's private key and the unlocker's public key
+    ///
+    /// Derives the unlocker's child public key and returns a P2PKH locking
+    /// script paying to its hash160.
+    ///
+    /// Reference: TypeScript `lock(lockerPrivKey, unlockerPubKey)`
+    pub fn lock(&self, locker_priv_key: &[u8], unlocker_pub_key: &[u8]) -> WalletResult<Vec<u8>> {
+        let child_pub_key = brc42::derive_child_public_key(
+            locker_priv_key,
+            unlocker_pub_key,
+            &self.invoice_number(),
+        ).map_err(|e| WalletError::invalid_operation(format!("BRC-29 lock derivation failed: {}", e)))?;
+
+        let script = Script::p2pkh_locking_script(&hash160(&child_pub_key))
+            .map_err(|e| WalletError::invalid_operation(e.to_string()))?;
+
+        Ok(script.to_bytes().to_vec())
+    }
+
+    /// Unlock with the unlocker's private key and the locker's public key
     ///
-    /// TODO: Implement full BRC-29 unlocking script generation
+    /// Derives the child private key matching `lock` and returns an unlocker
+    /// that signs a specific transaction input.
+    ///
+    /// Reference: TypeScript `unlock(unlockerPrivKey, lockerPubKey, sourceSatoshis, lockingScript)`
     pub fn unlock(
         &self,
-        _locker_priv_key: &[u8],
-        _unlocker_pub_key: &str,
-        _source_satoshis: u64,
-        _locking_script: &str,
-    ) -> WalletResult<Vec<u8>> {
-        // Placeholder - needs full BRC-29 implementation
-        Ok(Vec::new())
+        unlocker_priv_key: &[u8],
+        locker_pub_key: &str,
+        source_satoshis: u64,
+        locking_script: &str,
+    ) -> WalletResult<SABPPPUnlocker> {
+        let locker_pub_key = hex::decode(locker_pub_key)
+            .map_err(|e| WalletError::invalid_parameter("lockerPubKey", format!("valid hex: {}", e)))?;
+        let locking_script = hex::decode(locking_script)
+            .map_err(|e| WalletError::invalid_parameter("lockingScript", format!("valid hex: {}", e)))?;
+
+        let private_key = brc42::derive_child_private_key(
+            unlocker_priv_key,
+            &locker_pub_key,
+            &self.invoice_number(),
+        ).map_err(|e| WalletError::invalid_operation(format!("BRC-29 unlock derivation failed: {}", e)))?;
+
+        Ok(SABPPPUnlocker {
+            private_key,
+            source_satoshis,
+            locking_script,
+        })
+    }
+}
+
+/// Unlocking template produced by `ScriptTemplateSABPPP::unlock`
+///
+/// Reference: TypeScript P2PKH unlock template `{ sign, estimateLength }`
+#[derive(Debug, Clone)]
+pub struct SABPPPUnlocker {
+    private_key: Vec<u8>,
+    source_satoshis: u64,
+    locking_script: Vec<u8>,
+}
+
+impl SABPPPUnlocker {
+    /// Produce the `<signature> <publicKey>` unlocking script for input `vin`
+    ///
+    /// Reference: TypeScript `unlockTemplate.sign(tx, inputIndex)`
+    pub fn sign(&self, tx: &Transaction, vin: usize) -> WalletResult<Vec<u8>> {
+        let sighash = SigHash::calculate(
+            tx,
+            vin,
+            &self.locking_script,
+            SigHashType::All.with_forkid(),
+            self.source_satoshis as i64,
+        ).map_err(|e| WalletError::invalid_operation(format!("Sighash calculation failed: {}", e)))?;
+
+        let signature = sign_ecdsa(&sighash, &self.private_key, SigHashType::All.with_forkid())
+            .map_err(|e| WalletError::invalid_operation(format!("Signing failed: {}", e)))?;
+
+        let public_key = derive_public_key(&self.private_key)
+            .map_err(|e| WalletError::invalid_operation(format!("Public key derivation failed: {}", e)))?;
+
+        Ok(Script::p2pkh_unlocking_script(&signature, &public_key).to_bytes().to_vec())
+    }
+
+    /// Estimated unlocking script length in bytes
+    ///
+    /// Reference: TypeScript `unlockTemplate.estimateLength()`
+    pub fn estimate_length(&self) -> usize {
+        ScriptTemplateSABPPP::UNLOCK_LENGTH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{OutPoint, TxInput, TxOutput};
+    use crate::crypto::verify_ecdsa;
+
+    fn key(byte: u8) -> (Vec<u8>, Vec<u8>) {
+        let private_key = vec![byte; 32];
+        let public_key = derive_public_key(&private_key).unwrap();
+        (private_key, public_key)
+    }
+
+    #[test]
+    fn test_invoice_number_format() {
+        // TS Reference: `${securityLevel}-${protocol}-${derivationPrefix} ${derivationSuffix}`
+        let t = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string());
+        assert_eq!(t.key_id(), "prefix suffix");
+        assert_eq!(t.invoice_number(), "2-3241645161d8-prefix suffix");
+    }
+
+    #[test]
+    fn test_lock_produces_p2pkh() {
+        let (locker_priv, _) = key(1);
+        let (_, unlocker_pub) = key(2);
+        let t = ScriptTemplateSABPPP::new("p".to_string(), "s".to_string());
+
+        let script = t.lock(&locker_priv, &unlocker_pub).unwrap();
+        assert_eq!(script.len(), 25);
+        assert_eq!(&script[..3], &[0x76, 0xa9, 0x14]);
+        assert_eq!(&script[23..], &[0x88, 0xac]);
+    }
+
+    #[test]
+    fn test_lock_differs_by_suffix() {
+        let (locker_priv, locker_pub) = key(1);
+        let a = ScriptTemplateSABPPP::new("p".to_string(), "1".to_string());
+        let b = ScriptTemplateSABPPP::new("p".to_string(), "2".to_string());
+
+        assert_ne!(
+            a.lock(&locker_priv, &locker_pub).unwrap(),
+            b.lock(&locker_priv, &locker_pub).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unlock_spends_lock() {
+        // Locker pays to unlocker; unlocker derives the matching key and signs
+        let (locker_priv, locker_pub) = key(1);
+        let (unlocker_priv, unlocker_pub) = key(2);
+        let t = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string());
+
+        let locking_script = t.lock(&locker_priv, &unlocker_pub).unwrap();
+
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(&"aa".repeat(32), 0)));
+        tx.add_output(TxOutput::new(900, vec![0x6a]));
+
+        let unlocker = t.unlock(
+            &unlocker_priv,
+            &hex::encode(&locker_pub),
+            1000,
+            &hex::encode(&locking_script),
+        ).unwrap();
+        let unlocking_script = unlocker.sign(&tx, 0).unwrap();
+        assert!(unlocking_script.len() <= unlocker.estimate_length());
+
+        // Parse <sig> <pubkey> and check against the locking script
+        let sig_len = unlocking_script[0] as usize;
+        let signature = &unlocking_script[1..1 + sig_len];
+        let pubkey = &unlocking_script[2 + sig_len..];
+        assert_eq!(hash160(pubkey), locking_script[3..23].to_vec());
+
+        let sighash = SigHash::calculate(&tx, 0, &locking_script, SigHashType::All.with_forkid(), 1000).unwrap();
+        assert!(verify_ecdsa(&sighash, signature, pubkey).unwrap());
+    }
+
+    #[test]
+    fn test_self_lock_and_unlock() {
+        // Change outputs: locker and unlocker are the same wallet key
+        let (priv_key, pub_key) = key(7);
+        let t = ScriptTemplateSABPPP::new("prefix".to_string(), "suffix".to_string());
+
+        let locking_script = t.lock(&priv_key, &pub_key).unwrap();
+        let unlocker = t.unlock(&priv_key, &hex::encode(&pub_key), 500, &hex::encode(&locking_script)).unwrap();
+
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(&"bb".repeat(32), 1)));
+        tx.add_output(TxOutput::new(400, vec![0x6a]));
+        let unlocking_script = unlocker.sign(&tx, 0).unwrap();
+
+        let sig_len = unlocking_script[0] as usize;
+        let pubkey = &unlocking_script[2 + sig_len..];
+        assert_eq!(hash160(pubkey), locking_script[3..23].to_vec());
+    }
+
+    #[test]
+    fn test_unlock_rejects_bad_hex() {
+        let (priv_key, _) = key(1);
+        let t = ScriptTemplateSABPPP::new("p".to_string(), "s".to_string());
+        assert!(t.unlock(&priv_key, "zz", 1, "76a9").is_err());
     }
 }
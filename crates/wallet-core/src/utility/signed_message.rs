@@ -0,0 +1,181 @@
+//! BRC-77 Signed Messages
+//!
+//! Messages signed with a BRC-42 child of the signer's key, verifiable by
+//! anyone or only by a designated verifier.
+//!
+//! Wire format: `[version 4][signer key 33][verifier key 33 | 0x00][key ID 32][DER signature]`
+//!
+//! **Reference**: TypeScript `SignedMessage` from @bsv/sdk (messages/SignedMessage.ts)
+
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use secp256k1::{ecdsa::Signature, Message, PublicKey, SecretKey};
+
+use crate::crypto::sha256;
+use crate::keys::{brc42, ANYONE_PUBLIC_KEY};
+use crate::sdk::errors::{WalletError, WalletResult};
+
+/// BRC-77 message version prefix
+pub const VERSION: [u8; 4] = [0x42, 0x42, 0x33, 0x01];
+
+/// Marker byte used in place of the verifier key when anyone may verify
+const ANYONE_VERIFIER: u8 = 0x00;
+
+/// Private key 1, whose public key stands in for "anyone"
+const ANYONE_PRIVATE_KEY: [u8; 32] = {
+    let mut key = [0u8; 32];
+    key[31] = 1;
+    key
+};
+
+/// BRC-43 invoice number for a message signing key ID
+fn invoice_number(key_id: &[u8]) -> String {
+    format!("2-message signing-{}", general_purpose::STANDARD.encode(key_id))
+}
+
+fn derivation_error(e: brc42::Brc42Error) -> WalletError {
+    WalletError::internal(format!("Key derivation failed: {}", e))
+}
+
+/// Sign `message` with `signer` (a 32-byte private key)
+///
+/// With `verifier` (a 33-byte public key) only its holder can verify the
+/// signature; without one anyone can.
+///
+/// Reference: TS `SignedMessage.sign(message, signer, verifier?)`
+pub fn sign(message: &[u8], signer: &[u8], verifier: Option<&[u8]>) -> WalletResult<Vec<u8>> {
+    let signer_key = SecretKey::from_slice(signer)
+        .map_err(|_| WalletError::invalid_parameter("signer", "a valid 32-byte private key"))?;
+    let anyone = hex::decode(ANYONE_PUBLIC_KEY).expect("valid constant");
+    let verifier_key = match verifier {
+        Some(verifier) => PublicKey::from_slice(verifier)
+            .map_err(|_| WalletError::invalid_parameter("verifier", "a valid compressed public key"))?
+            .serialize()
+            .to_vec(),
+        None => anyone,
+    };
+
+    let mut key_id = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key_id);
+    let signing_key = brc42::derive_child_private_key(signer, &verifier_key, &invoice_number(&key_id))
+        .map_err(derivation_error)?;
+    let signing_key = SecretKey::from_slice(&signing_key).map_err(|e| WalletError::internal(e.to_string()))?;
+    let digest = Message::from_digest_slice(&sha256(message)).expect("sha256 is 32 bytes");
+    let signature = secp256k1::SECP256K1.sign_ecdsa(&digest, &signing_key).serialize_der();
+
+    let mut signed = VERSION.to_vec();
+    signed.extend_from_slice(&PublicKey::from_secret_key(secp256k1::SECP256K1, &signer_key).serialize());
+    match verifier {
+        Some(_) => signed.extend_from_slice(&verifier_key),
+        None => signed.push(ANYONE_VERIFIER),
+    }
+    signed.extend_from_slice(&key_id);
+    signed.extend_from_slice(&signature);
+    Ok(signed)
+}
+
+/// Verify a BRC-77 `signature` over `message`
+///
+/// `recipient` is the verifier's 32-byte private key, required when the
+/// signature names a specific verifier. Malformed signatures, and ones meant
+/// for a different verifier, are errors; a well-formed signature that does
+/// not match the message returns `false`.
+///
+/// Reference: TS `SignedMessage.verify(message, sig, recipient?)`
+pub fn verify(message: &[u8], signature: &[u8], recipient: Option<&[u8]>) -> WalletResult<bool> {
+    let malformed = || WalletError::invalid_parameter("signature", "a BRC-77 signed message");
+    if signature.len() < 4 + 33 + 1 {
+        return Err(malformed());
+    }
+    let (version, rest) = signature.split_at(4);
+    if version != VERSION {
+        return Err(WalletError::invalid_parameter(
+            "signature",
+            format!("version {}, not {}", hex::encode(VERSION), hex::encode(version)),
+        ));
+    }
+    let (signer, rest) = rest.split_at(33);
+
+    let (recipient, rest) = if rest[0] == ANYONE_VERIFIER {
+        (ANYONE_PRIVATE_KEY.to_vec(), &rest[1..])
+    } else {
+        if rest.len() < 33 {
+            return Err(malformed());
+        }
+        let (verifier, rest) = rest.split_at(33);
+        let recipient = recipient.ok_or_else(|| {
+            WalletError::invalid_operation(format!(
+                "This signature can only be verified with knowledge of a specific private key. The associated public key is: {}",
+                hex::encode(verifier)
+            ))
+        })?;
+        let recipient_key = SecretKey::from_slice(recipient)
+            .map_err(|_| WalletError::invalid_parameter("recipient", "a valid 32-byte private key"))?;
+        let recipient_public = PublicKey::from_secret_key(secp256k1::SECP256K1, &recipient_key).serialize();
+        if recipient_public[..] != *verifier {
+            return Err(WalletError::invalid_operation(format!(
+                "The recipient public key is {} but the signature requires the recipient to have public key {}",
+                hex::encode(recipient_public),
+                hex::encode(verifier)
+            )));
+        }
+        (recipient.to_vec(), rest)
+    };
+
+    if rest.len() < 32 {
+        return Err(malformed());
+    }
+    let (key_id, der) = rest.split_at(32);
+    let Ok(der) = Signature::from_der(der) else {
+        return Err(malformed());
+    };
+    let signing_key = brc42::derive_child_public_key(&recipient, signer, &invoice_number(key_id))
+        .map_err(derivation_error)?;
+    let signing_key = PublicKey::from_slice(&signing_key).map_err(|e| WalletError::internal(e.to_string()))?;
+    let digest = Message::from_digest_slice(&sha256(message)).expect("sha256 is 32 bytes");
+    Ok(secp256k1::SECP256K1.verify_ecdsa(&digest, &der, &signing_key).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = &[1, 2, 4, 8, 16, 32];
+
+    fn public_key(private_key: &[u8]) -> Vec<u8> {
+        crate::crypto::derive_public_key(private_key).unwrap()
+    }
+
+    #[test]
+    fn test_sign_for_anyone() {
+        let sender = [15u8; 32];
+        let signature = sign(MESSAGE, &sender, None).unwrap();
+        assert_eq!(&signature[..4], &VERSION);
+        assert_eq!(signature[4..37], public_key(&sender)[..]);
+        assert_eq!(signature[37], ANYONE_VERIFIER);
+
+        assert!(verify(MESSAGE, &signature, None).unwrap());
+        assert!(!verify(&[1, 2, 4, 8, 16, 33], &signature, None).unwrap());
+    }
+
+    #[test]
+    fn test_sign_for_specific_recipient() {
+        let sender = [15u8; 32];
+        let recipient = [21u8; 32];
+        let signature = sign(MESSAGE, &sender, Some(&public_key(&recipient))).unwrap();
+
+        assert!(verify(MESSAGE, &signature, Some(&recipient)).unwrap());
+        assert!(verify(MESSAGE, &signature, None).is_err());
+        assert!(verify(MESSAGE, &signature, Some(&[22u8; 32])).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_signatures() {
+        let mut signature = sign(MESSAGE, &[15u8; 32], None).unwrap();
+        assert!(verify(MESSAGE, &signature[..40], None).is_err());
+
+        signature[0] = 0x01;
+        let err = verify(MESSAGE, &signature, None).unwrap_err();
+        assert!(err.to_string().contains("42423301"));
+    }
+}
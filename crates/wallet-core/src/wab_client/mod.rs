@@ -20,6 +20,7 @@ pub trait AuthMethodInteractor: Send + Sync {
 ///
 /// Reference: TS AuthStartResult
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AuthStartResult {
     pub success: bool,
     pub message: Option<String>,
@@ -29,6 +30,7 @@ pub struct AuthStartResult {
 ///
 /// Reference: TS AuthCompleteResult
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AuthCompleteResult {
     pub success: bool,
     pub presentation_key: Option<String>,
@@ -38,9 +40,18 @@ pub struct AuthCompleteResult {
 /// Faucet request result
 ///
 /// Reference: TS FaucetResult
+///
+/// `payment_data` carries the funding transaction and its BRC-29 derivation
+/// (`tx`, `derivationPrefix`, `derivationSuffix`, `senderIdentityKey`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FaucetResult {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(default)]
     pub payment_data: serde_json::Value,
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
 /// WAB Client trait
@@ -75,6 +86,9 @@ pub trait WABClientTrait: Send + Sync {
 pub struct WABClient {
     /// Base URL of the WAB server
     base_url: String,
+
+    /// HTTP client
+    client: reqwest::Client,
 }
 
 impl WABClient {
@@ -83,54 +97,99 @@ impl WABClient {
     /// # Arguments
     /// * `base_url` - Base URL of the WAB server (e.g., "https://wab.example.com")
     pub fn new(base_url: String) -> Self {
-        Self { base_url }
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
     }
     
     /// Get the base URL
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
+
+    /// POST a JSON body to the WAB server and parse the JSON response
+    async fn post<T: serde::de::DeserializeOwned>(&self, path: &str, body: serde_json::Value) -> WalletResult<T> {
+        let response = self.client.post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| WalletError::internal(format!("WAB request to {} failed: {}", path, e)))?;
+
+        if !response.status().is_success() {
+            return Err(WalletError::invalid_operation(format!(
+                "WAB server returned HTTP status {} for {}", response.status(), path
+            )));
+        }
+
+        response.json()
+            .await
+            .map_err(|e| WalletError::invalid_operation(format!("Invalid WAB response from {}: {}", path, e)))
+    }
 }
 
 #[async_trait::async_trait]
 impl WABClientTrait for WABClient {
+    /// Reference: TS AuthMethodInteractor.startAuth - POST /auth/start
     async fn start_auth_method(
         &self,
         method: &dyn AuthMethodInteractor,
         presentation_key: &str,
         payload: serde_json::Value,
     ) -> WalletResult<AuthStartResult> {
-        // TODO: Implement HTTP request to WAB server
-        // POST /auth/{method_name}/start
-        // Body: { presentationKey, ...payload }
-        Err(WalletError::not_implemented("WABClient.start_auth_method requires HTTP client"))
+        self.post("/auth/start", serde_json::json!({
+            "methodType": method.method_name(),
+            "presentationKey": presentation_key,
+            "payload": payload,
+        })).await
     }
     
+    /// Reference: TS AuthMethodInteractor.completeAuth - POST /auth/complete
     async fn complete_auth_method(
         &self,
         method: &dyn AuthMethodInteractor,
         temp_key: &str,
         payload: serde_json::Value,
     ) -> WalletResult<AuthCompleteResult> {
-        // TODO: Implement HTTP request to WAB server
-        // POST /auth/{method_name}/complete
-        // Body: { tempKey, ...payload }
-        Err(WalletError::not_implemented("WABClient.complete_auth_method requires HTTP client"))
+        self.post("/auth/complete", serde_json::json!({
+            "methodType": method.method_name(),
+            "presentationKey": temp_key,
+            "payload": payload,
+        })).await
     }
     
+    /// Reference: TS WABClient.requestFaucet - POST /faucet/request
     async fn request_faucet(&self, presentation_key: &str) -> WalletResult<FaucetResult> {
-        // TODO: Implement HTTP request to WAB server
-        // POST /faucet/request
-        // Body: { presentationKey }
-        Err(WalletError::not_implemented("WABClient.request_faucet requires HTTP client"))
+        self.post("/faucet/request", serde_json::json!({
+            "presentationKey": presentation_key,
+        })).await
     }
 }
 
 pub mod auth_method_interactors {
+    pub use super::AuthMethodInteractor;
+
+    /// SMS verification via Twilio
+    ///
+    /// Reference: TS TwilioPhoneInteractor
     #[derive(Debug, Default)]
     pub struct TwilioPhoneInteractor;
+
+    impl AuthMethodInteractor for TwilioPhoneInteractor {
+        fn method_name(&self) -> &str {
+            "TwilioPhone"
+        }
+    }
+
+    /// Identity verification via Persona
+    ///
+    /// Reference: TS PersonaIDInteractor
     #[derive(Debug, Default)]
     pub struct PersonaIDInteractor;
-    #[derive(Debug, Default)]
-    pub struct AuthMethodInteractor; // placeholder marker type; real trait in wallet-wab-client
+
+    impl AuthMethodInteractor for PersonaIDInteractor {
+        fn method_name(&self) -> &str {
+            "PersonaID"
+        }
+    }
 }
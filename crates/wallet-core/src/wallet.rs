@@ -6,29 +6,96 @@
 ///! the complete WalletInterface. This is the entry point for applications like metanet-desktop.
 
 use crate::sdk::errors::{WalletError, WalletResult};
+use crate::auth_http::AuthFetch;
+use crate::events::{WalletEvent, WalletEventBus};
+use crate::keys::{KeyPair, RootKeyDeriver};
+use crate::methods::{
+    encrypt_decrypt, export_history, fee_model, hmac_operations, identity_discovery, internalize_action, key_linkage,
+    list_actions, list_certificates, list_outputs, output_management, process_action, signature_operations,
+    tag_label_management,
+};
 use crate::managers::simple_wallet_manager::WalletInterface;
 use crate::managers::wallet_permissions_manager::WalletPermissionsManager;
-use crate::managers::wallet_settings_manager::WalletSettingsManager;
+use crate::managers::wallet_settings_manager::{default_settings, TrustSettings, WalletSettingsManager};
 use crate::managers::wallet_auth_manager::WalletAuthenticationManager;
+use crate::sdk::{
+    AbortActionArgs, AbortActionResult, AcquireCertificateArgs, CreateActionArgs, CreateActionResult,
+    DiscoverByAttributesArgs, DiscoverByIdentityKeyArgs, InternalizeActionArgs, ListActionsArgs, ListCertificatesArgs, ListOutputsArgs,
+    ProveCertificateArgs, SignActionArgs, parse_wallet_outpoint, validate_string_length,
+};
+use crate::services::{Broadcaster, FiatRateProvider, IdentityResolver, UtxoStatusProvider};
+use crate::utility::currency_format::{self, DisplayCurrency, DisplayRates, DEFAULT_DISPLAY_CURRENCY};
+use crate::signer::methods::{
+    acquire_certificate, consolidate_outputs, create_action, data_action_args, estimate_data_action_fee,
+    prove_certificate, sign_action, validate_consolidate_outputs_args, validate_create_action_args,
+    validate_prove_certificate_args, validate_sign_action_args, CertifierClient, ConsolidateOutputsArgs,
+    ConsolidateOutputsResult, HttpCertifierClient, PendingSignAction, DEFAULT_MAX_DATA_OUTPUT_BYTES,
+};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use wallet_storage::{
+    AuthId, BasketBalance, OutputTagUsage, TableOutputBasket, TableOutputLock, TableOutputTag, TableTxLabel,
+    WalletStorageProvider,
+};
 
 /// Main wallet configuration
 ///
 /// Reference: TS WalletArgs (Wallet.ts lines 121-130)
 pub struct WalletConfig {
-    /// Network (mainnet or testnet)
+    /// Network: "main" or "test"
     pub chain: String,
     
-    /// Root private key or seed for key derivation
+    /// Root private key for key derivation (32 bytes)
+    ///
+    /// When empty, key operations are delegated to `storage`.
     pub root_key: Vec<u8>,
     
     /// Storage manager (can be any storage backend)
     pub storage: Arc<dyn WalletInterface>,
     
+    /// Optional: Wallet storage used directly by methods implemented here
+    ///
+    /// Requires `root_key`; the user is the root key's identity.
+    pub storage_provider: Option<Arc<Mutex<dyn WalletStorageProvider>>>,
+    
+    /// Optional: Transport to certifiers for certificate issuance
+    ///
+    /// Defaults to `AuthFetch` with the root key, else `HttpCertifierClient`.
+    pub certifier_client: Option<Arc<dyn CertifierClient>>,
+    
+    /// Optional: Broadcaster for processed actions
+    ///
+    /// Without one, actions that are not `noSend` stay queued in storage.
+    pub broadcaster: Option<Arc<dyn Broadcaster>>,
+    
+    /// Optional: UTXO status lookups used when internalizing actions
+    pub utxo_status: Option<Arc<dyn UtxoStatusProvider>>,
+    
+    /// Optional: Historical exchange rates for valuing exported history
+    pub fiat_rates: Option<Arc<dyn FiatRateProvider>>,
+    
+    /// Optional: Identity overlay lookups for discoverByIdentityKey / discoverByAttributes
+    ///
+    /// Without one, discovery delegates to `storage`.
+    pub identity_resolver: Option<Arc<dyn IdentityResolver>>,
+    
+    /// Optional: Certifiers trusted by identity discovery
+    ///
+    /// Defaults to the default wallet settings' trust settings.
+    pub trust_settings: Option<TrustSettings>,
+    
+    /// Optional: User settings, for the preferred display currency
+    pub settings: Option<Arc<WalletSettingsManager>>,
+    
     /// Optional: Admin originator for permission management
     pub admin_originator: Option<String>,
+    
+    /// Optional: Event bus shared with the monitor and managers
+    ///
+    /// Defaults to a new bus; see `Wallet::subscribe`.
+    pub event_bus: Option<WalletEventBus>,
 }
 
 /// Main Wallet orchestrator
@@ -38,7 +105,10 @@ pub struct WalletConfig {
 /// Coordinates all wallet managers and implements the full WalletInterface.
 /// This is the production-ready entry point for applications.
 ///
-/// **Current Status**: Minimal MVP - delegates to inner wallet
+/// With a root key and `storage_provider`, actions, outputs, certificates and
+/// key operations are implemented here; without them every method delegates
+/// to the inner wallet.
+///
 /// TODO: Add full manager integration (permissions, settings, auth) as they're completed
 pub struct Wallet {
     /// Underlying storage/simple wallet
@@ -50,9 +120,46 @@ pub struct Wallet {
     /// Admin originator for internal operations
     admin_originator: String,
     
+    /// BRC-42/43 deriver over the root key, when one was configured
+    key_deriver: Option<Arc<RootKeyDeriver>>,
+    
+    /// Wallet storage for methods implemented here, when configured
+    storage_provider: Option<Arc<Mutex<dyn WalletStorageProvider>>>,
+    
+    /// Transport to certifiers
+    certifier_client: Arc<dyn CertifierClient>,
+    
+    /// Broadcaster for processed actions
+    broadcaster: Option<Arc<dyn Broadcaster>>,
+    
+    /// UTXO status lookups for internalizeAction
+    utxo_status: Option<Arc<dyn UtxoStatusProvider>>,
+    
+    /// Exchange rates for export_history
+    fiat_rates: Option<Arc<dyn FiatRateProvider>>,
+    
+    /// Cached current rates of `fiat_rates` for format_satoshis
+    display_rates: Option<DisplayRates>,
+    
+    /// User settings
+    settings: Option<Arc<WalletSettingsManager>>,
+    
+    /// Identity overlay lookups for identity discovery
+    identity_resolver: Option<Arc<dyn IdentityResolver>>,
+    
+    /// Certifiers trusted by identity discovery
+    trust_settings: TrustSettings,
+    
+    /// Transaction lifecycle notifications
+    event_bus: WalletEventBus,
+    
+    /// Actions created with `signAndProcess: false`, keyed by reference
+    ///
+    /// Reference: TS Wallet.pendingSignActions
+    pending_sign_actions: Mutex<HashMap<String, PendingSignAction>>,
+    
     // TODO: Add when managers are ready
     // permissions: Arc<RwLock<WalletPermissionsManager>>,
-    // settings: WalletSettingsManager,
     // auth: Arc<RwLock<WalletAuthenticationManager>>,
 }
 
@@ -65,10 +172,29 @@ impl Wallet {
     ///
     /// # Returns
     ///
-    /// New wallet instance ready for use
+    /// New wallet instance ready for use; `WERR_NETWORK_CHAIN` if an
+    /// available `storage_provider` holds another chain's data
     pub fn new(config: WalletConfig) -> WalletResult<Self> {
+        // Storage on another chain would mix mainnet and testnet outputs
+        if let Some(storage) = &config.storage_provider {
+            if let Ok(storage) = storage.try_lock() {
+                if storage.is_available() {
+                    storage.get_settings().verify_chain(&config.chain)?;
+                }
+            }
+        }
         let inner = config.storage;
         let admin_originator = config.admin_originator.unwrap_or_else(|| "admin".to_string());
+        let key_deriver = if config.root_key.is_empty() {
+            None
+        } else {
+            Some(Arc::new(RootKeyDeriver::new(&config.root_key)?))
+        };
+        
+        let certifier_client = config.certifier_client.unwrap_or_else(|| match &key_deriver {
+            Some(deriver) => Arc::new(AuthFetch::new(deriver.clone())),
+            None => Arc::new(HttpCertifierClient::new()),
+        });
         
         // TODO: Initialize managers when ready
         // let permissions = Arc::new(RwLock::new(
@@ -79,6 +205,18 @@ impl Wallet {
             inner,
             chain: config.chain,
             admin_originator,
+            key_deriver,
+            storage_provider: config.storage_provider,
+            certifier_client,
+            broadcaster: config.broadcaster,
+            utxo_status: config.utxo_status,
+            display_rates: config.fiat_rates.clone().map(DisplayRates::new),
+            fiat_rates: config.fiat_rates,
+            settings: config.settings,
+            identity_resolver: config.identity_resolver,
+            trust_settings: config.trust_settings.unwrap_or_else(|| default_settings().trust_settings),
+            event_bus: config.event_bus.unwrap_or_default(),
+            pending_sign_actions: Mutex::new(HashMap::new()),
         })
     }
     
@@ -91,6 +229,337 @@ impl Wallet {
     pub fn admin_originator(&self) -> &str {
         &self.admin_originator
     }
+    
+    /// Receive the wallet's events emitted from now on
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<WalletEvent> {
+        self.event_bus.subscribe()
+    }
+    
+    /// Event bus, for handing to monitor tasks and managers
+    pub fn event_bus(&self) -> &WalletEventBus {
+        &self.event_bus
+    }
+    
+    /// Identity public key hex, when a root key was configured
+    pub fn identity_key(&self) -> Option<String> {
+        self.key_deriver.as_ref().map(|d| d.identity_key_hex())
+    }
+    
+    /// Set the change UTXO policy of one of the user's output baskets
+    ///
+    /// Change generation in `createAction` keeps the `default` basket topped
+    /// up to the desired number of UTXOs, each worth at least the minimum value.
+    pub async fn set_basket_utxo_policy(
+        &self,
+        args: output_management::SetBasketUtxoPolicyArgs,
+    ) -> WalletResult<TableOutputBasket> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Basket policies require a root key and storage"));
+        };
+        let vargs = output_management::validate_set_basket_utxo_policy_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(output_management::set_basket_utxo_policy(&mut *storage, &auth, vargs).await?)
+    }
+    
+    /// Reserve one of the user's outputs, given as "txid.vout", for the application
+    ///
+    /// A locked output is not allocated as change and is refused as a
+    /// `createAction` input, with `reason`, until `unlock_output` is called
+    /// or `ttl` elapses. Locking a locked output replaces its reason and expiry.
+    pub async fn lock_output(
+        &self,
+        outpoint: &str,
+        reason: &str,
+        ttl: Option<std::time::Duration>,
+    ) -> WalletResult<TableOutputLock> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Output locks require a root key and storage"));
+        };
+        let outpoint = parse_wallet_outpoint(outpoint)?;
+        let reason = validate_string_length(reason, "reason", Some(1), Some(300))?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(storage
+            .lock_output(auth.user_id_required()?, &outpoint.txid, outpoint.vout, &reason, ttl)
+            .await?)
+    }
+    
+    /// Release an output locked by `lock_output`
+    ///
+    /// Returns whether it was locked.
+    pub async fn unlock_output(&self, outpoint: &str) -> WalletResult<bool> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Output locks require a root key and storage"));
+        };
+        let outpoint = parse_wallet_outpoint(outpoint)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(storage.unlock_output(auth.user_id_required()?, &outpoint.txid, outpoint.vout).await?)
+    }
+    
+    /// The user's outputs currently locked by `lock_output`
+    pub async fn output_locks(&self) -> WalletResult<Vec<TableOutputLock>> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Output locks require a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(storage.find_output_locks(auth.user_id_required()?).await?)
+    }
+    
+    /// Merge small change outputs of the `default` basket into fewer, larger ones
+    ///
+    /// Outputs below the basket's minimum desired value are spent, within
+    /// `maxFeeSatoshis`, into change sized towards its desired UTXO count.
+    pub async fn consolidate_outputs(
+        &self,
+        args: ConsolidateOutputsArgs,
+    ) -> WalletResult<ConsolidateOutputsResult> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Consolidation requires a root key and storage"));
+        };
+        let vargs = validate_consolidate_outputs_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        consolidate_outputs(
+            &mut *storage,
+            self.broadcaster.as_deref(),
+            self.utxo_status.as_deref(),
+            &auth,
+            &Self::change_keys(deriver),
+            vargs,
+        ).await
+    }
+    
+    /// Store `data` on chain in OP_FALSE OP_RETURN outputs, labelled `labels`
+    ///
+    /// Data over `DEFAULT_MAX_DATA_OUTPUT_BYTES` is split across outputs
+    /// that keep its order from vout 0. The action is funded from change at
+    /// the storage fee model's rate for its whole size; see
+    /// `estimate_data_action_fee` for what that comes to.
+    pub async fn create_data_action(&self, data: &[u8], labels: &[String]) -> WalletResult<CreateActionResult> {
+        let args = data_action_args(data, labels, DEFAULT_MAX_DATA_OUTPUT_BYTES)?;
+        self.create_storage_action(args, None).await
+    }
+    
+    /// Fee `create_data_action` is expected to pay to store `data`
+    pub async fn estimate_data_action_fee(&self, data: &[u8]) -> WalletResult<i64> {
+        let Some(storage) = &self.storage_provider else {
+            return Err(WalletError::invalid_operation("Fee estimates require storage"));
+        };
+        let fee_model = fee_model::validate_storage_fee_model(Some(&storage.lock().await.get_fee_model()))?;
+        Ok(estimate_data_action_fee(data, DEFAULT_MAX_DATA_OUTPUT_BYTES, &fee_model))
+    }
+    
+    /// Spendable satoshis across all of the user's output baskets
+    ///
+    /// Summed by storage, so UIs need not page through `listOutputs`.
+    /// Reference: TS Wallet.balance
+    pub async fn balance(&self) -> WalletResult<i64> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Balances require a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(storage.get_balance(auth.user_id_required()?).await?)
+    }
+    
+    /// Spendable satoshis and output count of each output basket holding any
+    pub async fn basket_balances(&self) -> WalletResult<Vec<BasketBalance>> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Balances require a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(storage.get_basket_balances(auth.user_id_required()?).await?)
+    }
+    
+    /// Rename one of the user's output tags, merging into `new_tag` if it exists
+    pub async fn rename_tag(&self, tag: &str, new_tag: &str) -> WalletResult<TableOutputTag> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Tag management requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        tag_label_management::rename_output_tag(&mut *storage, &auth, tag, new_tag).await
+    }
+    
+    /// Delete one of the user's output tags, untagging its outputs
+    pub async fn delete_tag(&self, tag: &str) -> WalletResult<()> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Tag management requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        tag_label_management::delete_output_tag(&mut *storage, &auth, tag).await
+    }
+    
+    /// Number of outputs carrying each of the user's tags
+    ///
+    /// Tags no output carries any more are listed with a count of zero, as
+    /// candidates for `delete_tag`.
+    pub async fn tag_usages(&self) -> WalletResult<Vec<OutputTagUsage>> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Tag management requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        tag_label_management::output_tag_usages(&*storage, &auth).await
+    }
+    
+    /// Rename one of the user's transaction labels, merging into `new_label` if it exists
+    pub async fn rename_label(&self, label: &str, new_label: &str) -> WalletResult<TableTxLabel> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Label management requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        tag_label_management::rename_tx_label(&mut *storage, &auth, label, new_label).await
+    }
+    
+    /// Delete one of the user's transaction labels, unlabelling its transactions
+    pub async fn delete_label(&self, label: &str) -> WalletResult<()> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Label management requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        tag_label_management::delete_tx_label(&mut *storage, &auth, label).await
+    }
+    
+    /// Name, avatar and trust badge of `identity_key`, for display
+    ///
+    /// Shares the identity cache of `discoverByIdentityKey`; `force_refresh`
+    /// looks the identity up again.
+    pub async fn resolve_identity(
+        &self,
+        identity_key: &str,
+        force_refresh: bool,
+    ) -> WalletResult<identity_discovery::DisplayableIdentity> {
+        let (Some(deriver), Some(storage), Some(resolver)) =
+            (&self.key_deriver, &self.storage_provider, &self.identity_resolver)
+        else {
+            return Err(WalletError::invalid_operation(
+                "Identity resolution requires a root key, storage and an identity resolver",
+            ));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        identity_discovery::resolve_identity(
+            &mut *storage,
+            &auth,
+            &**resolver,
+            &self.trust_settings,
+            identity_key,
+            force_refresh,
+        )
+        .await
+    }
+    
+    /// Format `satoshis` for display in `currency`
+    ///
+    /// Without `currency`, the user's preferred currency from the settings
+    /// is used, else satoshis. Fiat amounts use the configured `fiat_rates`
+    /// at the current rate, cached for a few minutes across calls.
+    pub async fn format_satoshis(&self, satoshis: i64, currency: Option<&str>) -> WalletResult<String> {
+        let currency = match (currency, &self.settings) {
+            (Some(currency), _) => currency.to_string(),
+            (None, Some(settings)) => settings.get().await?.currency.unwrap_or_else(|| DEFAULT_DISPLAY_CURRENCY.to_string()),
+            (None, None) => DEFAULT_DISPLAY_CURRENCY.to_string(),
+        };
+        let currency = DisplayCurrency::parse(&currency)?;
+        match (&currency, &self.display_rates) {
+            (DisplayCurrency::Fiat(_), None) => {
+                Err(WalletError::invalid_operation("Fiat amounts require an exchange rate provider"))
+            }
+            (_, Some(rates)) => rates.format_satoshis(satoshis, &currency).await,
+            (_, None) => currency_format::format_satoshis(satoshis, &currency, None),
+        }
+    }
+    
+    /// Export the wallet's transaction history in `date_range` for accounting
+    ///
+    /// Each transaction carries its labels, amount, time and txid, and its
+    /// fiat value at that time when exchange rates were configured.
+    pub async fn export_history(
+        &self,
+        format: export_history::ExportFormat,
+        date_range: export_history::DateRange,
+    ) -> WalletResult<String> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("History export requires a root key and storage"));
+        };
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        Ok(export_history::export_history(&*storage, &auth, self.fiat_rates.as_deref(), format, date_range).await?)
+    }
+    
+    /// createAction against `storage_provider`, signing with the root key
+    ///
+    /// An action left for signAction is kept until it is signed.
+    async fn create_storage_action(
+        &self,
+        args: CreateActionArgs,
+        originator: Option<&str>,
+    ) -> WalletResult<CreateActionResult> {
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return Err(WalletError::invalid_operation("Actions require a root key and storage"));
+        };
+        let vargs = validate_create_action_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        let (result, prior) = create_action(
+            &mut *storage,
+            self.broadcaster.as_deref(),
+            self.utxo_status.as_deref(),
+            &auth,
+            &Self::change_keys(deriver),
+            vargs,
+            originator,
+        ).await?;
+        if let Some(prior) = prior {
+            self.pending_sign_actions.lock().await.insert(prior.reference.clone(), prior);
+        }
+        if let Some(results) = &result.send_with_results {
+            self.event_bus.emit_all(WalletEvent::from_send_with_results(results));
+        }
+        Ok(result)
+    }
+    
+    /// Storage user for the root key's identity, created on first use
+    ///
+    /// The root key is the active profile's key, so each profile gets its own user.
+    async fn storage_auth(
+        &self,
+        storage: &mut dyn WalletStorageProvider,
+        deriver: &RootKeyDeriver,
+    ) -> WalletResult<AuthId> {
+        let identity_key = deriver.identity_key_hex();
+        let user = storage.find_or_insert_user(&identity_key).await?.user;
+        let mut auth = AuthId::new(identity_key);
+        auth.user_id = Some(user.user_id);
+        Ok(auth)
+    }
+    
+    /// Keys for change outputs: the root key pair
+    fn change_keys(deriver: &RootKeyDeriver) -> KeyPair {
+        KeyPair {
+            private_key: deriver.root_key().to_vec(),
+            public_key: deriver.identity_key().to_vec(),
+        }
+    }
+}
+
+/// Parse JSON wallet method args
+fn parse_args<T: serde::de::DeserializeOwned>(args: Value) -> WalletResult<T> {
+    serde_json::from_value(args)
+        .map_err(|e| WalletError::invalid_parameter("args", format!("valid arguments: {}", e)))
+}
+
+/// Serialize a wallet method result to JSON
+fn to_value<T: serde::Serialize>(result: T) -> WalletResult<Value> {
+    serde_json::to_value(result).map_err(|e| WalletError::internal(e.to_string()))
 }
 
 /// Implement WalletInterface for the main Wallet
@@ -98,7 +567,7 @@ impl Wallet {
 /// All 28 methods required by metanet-desktop
 #[async_trait::async_trait]
 impl WalletInterface for Wallet {
-    // 1. createAction - delegate to inner with permission checks
+    // 1. createAction - built, signed and processed against storage when configured
     async fn create_action(
         &self,
         args: Value,
@@ -118,11 +587,13 @@ impl WalletInterface for Wallet {
         // }
         // drop(auth);
         
-        // Delegate to underlying wallet
-        self.inner.create_action(args, Some(originator)).await
+        if self.key_deriver.is_none() || self.storage_provider.is_none() {
+            return self.inner.create_action(args, Some(originator)).await;
+        }
+        to_value(self.create_storage_action(parse_args(args)?, Some(originator)).await?)
     }
     
-    // 2. signAction - delegate to inner with permission checks
+    // 2. signAction - completes a pending action from createAction
     async fn sign_action(
         &self,
         args: Value,
@@ -142,17 +613,46 @@ impl WalletInterface for Wallet {
         // }
         // drop(auth);
         
-        // Delegate to underlying wallet
-        self.inner.sign_action(args, Some(originator)).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.sign_action(args, Some(originator)).await;
+        };
+        let args: SignActionArgs = parse_args(args)?;
+        let vargs = validate_sign_action_args(&args)?;
+        let prior = self.pending_sign_actions.lock().await.remove(&vargs.reference)
+            .ok_or_else(|| WalletError::invalid_parameter("reference", "a pending action reference"))?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        let result = sign_action(
+            &mut *storage,
+            self.broadcaster.as_deref(),
+            self.utxo_status.as_deref(),
+            &auth,
+            &Self::change_keys(deriver),
+            prior,
+            vargs,
+        ).await?;
+        if let Some(results) = &result.send_with_results {
+            self.event_bus.emit_all(WalletEvent::from_send_with_results(results));
+        }
+        to_value(result)
     }
     
-    // 3. abortAction - delegate to inner
+    // 3. abortAction - from storage when configured
     async fn abort_action(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.abort_action(args, originator).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.abort_action(args, originator).await;
+        };
+        let args: AbortActionArgs = parse_args(args)?;
+        let vargs = process_action::validate_abort_action_args(&args)?;
+        self.pending_sign_actions.lock().await.remove(&vargs.reference);
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        process_action::abort_action(&mut *storage, &auth, &vargs.reference).await?;
+        to_value(AbortActionResult { aborted: true })
     }
     
     // 4. listActions - delegate to inner with permission checks
@@ -167,16 +667,47 @@ impl WalletInterface for Wallet {
         
         // TODO: Check permissions for basket/label access
         
-        self.inner.list_actions(args, Some(originator)).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.list_actions(args, Some(originator)).await;
+        };
+        let args: ListActionsArgs = parse_args(args)?;
+        let vargs = list_actions::validate_list_actions_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(list_actions::list_actions(&mut *storage, &auth, vargs).await?)
     }
     
-    // 5. internalizeAction - delegate to inner
+    // 5. internalizeAction - into storage when configured
     async fn internalize_action(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.internalize_action(args, originator).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.internalize_action(args, originator).await;
+        };
+        let args: InternalizeActionArgs = parse_args(args)?;
+        let vargs = internalize_action::validate_internalize_action_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        let result = internalize_action::internalize_action(
+            &mut *storage,
+            self.utxo_status.as_deref(),
+            &auth,
+            vargs,
+        ).await?;
+        if let Some(results) = &result.send_with_results {
+            self.event_bus.emit_all(WalletEvent::from_send_with_results(results));
+        }
+        if result.satoshis > 0 {
+            self.event_bus.emit(WalletEvent::PaymentReceived {
+                txid: result.txid.clone(),
+                satoshis: result.satoshis,
+            });
+        }
+        let mut value = to_value(result)?;
+        value["accepted"] = json!(true);
+        Ok(value)
     }
     
     // 6. listOutputs - delegate to inner with permission checks
@@ -191,7 +722,14 @@ impl WalletInterface for Wallet {
         
         // TODO: Check permissions for basket access
         
-        self.inner.list_outputs(args, Some(originator)).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.list_outputs(args, Some(originator)).await;
+        };
+        let args: ListOutputsArgs = parse_args(args)?;
+        let vargs = list_outputs::validate_list_outputs_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(list_outputs::list_outputs(&mut *storage, &auth, vargs).await?)
     }
     
     // 7. relinquishOutput - TODO: Add to WalletInterface trait
@@ -203,7 +741,7 @@ impl WalletInterface for Wallet {
     //     self.inner.relinquish_output(args, originator).await
     // }
     
-    // 8. getPublicKey - delegate to inner with permission checks
+    // 8. getPublicKey - derived from the root key, else delegated to inner
     async fn get_public_key(
         &self,
         args: Value,
@@ -215,7 +753,10 @@ impl WalletInterface for Wallet {
         
         // TODO: Check protocol permissions
         
-        self.inner.get_public_key(args, Some(originator)).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(key_linkage::get_public_key(&parse_args(args)?, deriver).await?),
+            None => self.inner.get_public_key(args, Some(originator)).await,
+        }
     }
     
     // 9. relinquishOutput - delegate to inner
@@ -227,103 +768,147 @@ impl WalletInterface for Wallet {
         self.inner.relinquish_output(args, originator).await
     }
     
-    // 10. revealCounterpartyKeyLinkage - delegate to inner
+    // 10. revealCounterpartyKeyLinkage - BRC-69 revelation from the root key
     async fn reveal_counterparty_key_linkage(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.reveal_counterparty_key_linkage(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(key_linkage::reveal_counterparty_key_linkage(&parse_args(args)?, deriver).await?),
+            None => self.inner.reveal_counterparty_key_linkage(args, originator).await,
+        }
     }
     
-    // 11. revealSpecificKeyLinkage - delegate to inner
+    // 11. revealSpecificKeyLinkage - BRC-69 revelation from the root key
     async fn reveal_specific_key_linkage(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.reveal_specific_key_linkage(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(key_linkage::reveal_specific_key_linkage(&parse_args(args)?, deriver).await?),
+            None => self.inner.reveal_specific_key_linkage(args, originator).await,
+        }
     }
     
-    // 12. encrypt - delegate to inner
+    // 12. encrypt - BRC-2 from the root key, else delegated to inner
     async fn encrypt(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.encrypt(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(encrypt_decrypt::encrypt(&parse_args(args)?, deriver).await?),
+            None => self.inner.encrypt(args, originator).await,
+        }
     }
     
-    // 13. decrypt - delegate to inner
+    // 13. decrypt - BRC-2 from the root key, else delegated to inner
     async fn decrypt(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.decrypt(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(encrypt_decrypt::decrypt(&parse_args(args)?, deriver).await?),
+            None => self.inner.decrypt(args, originator).await,
+        }
     }
     
-    // 14. createHmac - delegate to inner
+    // 14. createHmac - derived from the root key, else delegated to inner
     async fn create_hmac(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.create_hmac(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(hmac_operations::create_hmac(&parse_args(args)?, deriver.as_ref()).await?),
+            None => self.inner.create_hmac(args, originator).await,
+        }
     }
     
-    // 15. verifyHmac - delegate to inner
+    // 15. verifyHmac - derived from the root key, else delegated to inner
     async fn verify_hmac(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.verify_hmac(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(hmac_operations::verify_hmac(&parse_args(args)?, deriver.as_ref()).await?),
+            None => self.inner.verify_hmac(args, originator).await,
+        }
     }
     
-    // 16. createSignature - delegate to inner
+    // 16. createSignature - derived from the root key, else delegated to inner
     async fn create_signature(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.create_signature(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(signature_operations::create_signature(&parse_args(args)?, deriver.as_ref()).await?),
+            None => self.inner.create_signature(args, originator).await,
+        }
     }
     
-    // 17. verifySignature - delegate to inner
+    // 17. verifySignature - derived from the root key, else delegated to inner
     async fn verify_signature(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.verify_signature(args, originator).await
+        match &self.key_deriver {
+            Some(deriver) => to_value(signature_operations::verify_signature(&parse_args(args)?, deriver.as_ref()).await?),
+            None => self.inner.verify_signature(args, originator).await,
+        }
     }
     
-    // 18. acquireCertificate - delegate to inner
+    // 18. acquireCertificate - direct or issuance into storage when configured
     async fn acquire_certificate(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.acquire_certificate(args, originator).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.acquire_certificate(args, originator).await;
+        };
+        let args: AcquireCertificateArgs = parse_args(args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(acquire_certificate(&mut *storage, &auth, deriver, self.certifier_client.as_ref(), &args).await?)
     }
     
-    // 19. listCertificates - delegate to inner
+    // 19. listCertificates - from storage when configured
     async fn list_certificates(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.list_certificates(args, originator).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.list_certificates(args, originator).await;
+        };
+        let args: ListCertificatesArgs = parse_args(args)?;
+        let vargs = list_certificates::validate_list_certificates_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(list_certificates::list_certificates(&*storage, &auth, &vargs).await?)
     }
     
-    // 20. proveCertificate - delegate to inner
+    // 20. proveCertificate - keyring for verifier from storage when configured
     async fn prove_certificate(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.prove_certificate(args, originator).await
+        let (Some(deriver), Some(storage)) = (&self.key_deriver, &self.storage_provider) else {
+            return self.inner.prove_certificate(args, originator).await;
+        };
+        let args: ProveCertificateArgs = parse_args(args)?;
+        let vargs = validate_prove_certificate_args(&args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(prove_certificate(&*storage, &auth, deriver, vargs).await?)
     }
     
     // 21. relinquishCertificate - delegate to inner
@@ -335,40 +920,68 @@ impl WalletInterface for Wallet {
         self.inner.relinquish_certificate(args, originator).await
     }
     
-    // 22. discoverByIdentityKey - delegate to inner
+    // 22. discoverByIdentityKey - cached overlay lookup when configured
     async fn discover_by_identity_key(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.discover_by_identity_key(args, originator).await
+        let (Some(deriver), Some(storage), Some(resolver)) =
+            (&self.key_deriver, &self.storage_provider, &self.identity_resolver)
+        else {
+            return self.inner.discover_by_identity_key(args, originator).await;
+        };
+        let args: DiscoverByIdentityKeyArgs = parse_args(args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(
+            identity_discovery::discover_by_identity_key(&mut *storage, &auth, &**resolver, &self.trust_settings, &args)
+                .await?,
+        )
     }
     
-    // 23. discoverByAttributes - delegate to inner
+    // 23. discoverByAttributes - cached overlay lookup when configured
     async fn discover_by_attributes(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.discover_by_attributes(args, originator).await
+        let (Some(deriver), Some(storage), Some(resolver)) =
+            (&self.key_deriver, &self.storage_provider, &self.identity_resolver)
+        else {
+            return self.inner.discover_by_attributes(args, originator).await;
+        };
+        let args: DiscoverByAttributesArgs = parse_args(args)?;
+        let mut storage = storage.lock().await;
+        let auth = self.storage_auth(&mut *storage, deriver).await?;
+        to_value(
+            identity_discovery::discover_by_attributes(&mut *storage, &auth, &**resolver, &self.trust_settings, &args)
+                .await?,
+        )
     }
     
-    // 24. isAuthenticated - delegate to inner
+    // 24. isAuthenticated - a wallet holding a root key is always authenticated
     async fn is_authenticated(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.is_authenticated(args, originator).await
+        match &self.key_deriver {
+            Some(_) => Ok(json!({ "authenticated": true })),
+            None => self.inner.is_authenticated(args, originator).await,
+        }
     }
     
-    // 25. waitForAuthentication - delegate to inner
+    // 25. waitForAuthentication - resolves immediately with a root key
     async fn wait_for_authentication(
         &self,
         args: Value,
         originator: Option<&str>,
     ) -> WalletResult<Value> {
-        self.inner.wait_for_authentication(args, originator).await
+        match &self.key_deriver {
+            Some(_) => Ok(json!({ "authenticated": true })),
+            None => self.inner.wait_for_authentication(args, originator).await,
+        }
     }
     
     // 26. getHeight - delegate to inner
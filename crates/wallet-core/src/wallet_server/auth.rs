@@ -0,0 +1,196 @@
+//! BRC-103/104 Mutual Authentication (server side)
+//!
+//! **Reference**: TypeScript `@bsv/auth-express-middleware` and `Peer` from @bsv/sdk
+//!
+//! Answers handshakes posted to `/.well-known/auth`, verifies the signature
+//! on each general message and signs the response to it. The message formats
+//! are shared with the client in [`crate::auth_http`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::auth_http::{
+    handshake_data, random_base64, response_payload, sign_auth_message, verify_auth_message,
+    AuthHeaders, AuthMessage, AUTH_VERSION,
+};
+use crate::keys::{create_nonce, verify_nonce, RootKeyDeriver};
+use crate::sdk::errors::{WalletError, WalletResult};
+
+/// An authenticated peer
+#[derive(Debug, Clone)]
+struct AuthSession {
+    /// Peer identity key hex
+    identity_key: String,
+    /// Peer's session nonce, echoed as `yourNonce` in our responses
+    peer_nonce: String,
+}
+
+/// Server half of BRC-103 sessions
+pub struct AuthServer {
+    deriver: Arc<RootKeyDeriver>,
+    /// Sessions keyed by the session nonce we issued
+    sessions: Mutex<HashMap<String, AuthSession>>,
+}
+
+impl AuthServer {
+    pub fn new(deriver: Arc<RootKeyDeriver>) -> Self {
+        Self {
+            deriver,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Server identity key hex
+    pub fn identity_key(&self) -> String {
+        self.deriver.identity_key_hex()
+    }
+
+    /// Answer an `initialRequest`, opening a session
+    ///
+    /// Reference: TS Peer.processInitialRequest
+    pub async fn handle_initial_request(&self, message: &AuthMessage) -> WalletResult<AuthMessage> {
+        if message.version != AUTH_VERSION {
+            return Err(WalletError::invalid_parameter("version", AUTH_VERSION));
+        }
+        if message.message_type != "initialRequest" {
+            return Err(WalletError::invalid_parameter("messageType", "initialRequest"));
+        }
+        let identity_key = hex::encode(self.deriver.normalize_counterparty(&message.identity_key)?);
+        let peer_nonce = message.initial_nonce.clone()
+            .ok_or_else(|| WalletError::missing_parameter("initialNonce"))?;
+
+        let session_nonce = create_nonce(&self.deriver, "self")?;
+        let signature = sign_auth_message(
+            &self.deriver,
+            handshake_data(&peer_nonce, &session_nonce)?,
+            format!("{} {}", peer_nonce, session_nonce),
+            &identity_key,
+        ).await?;
+
+        self.sessions.lock().await.insert(session_nonce.clone(), AuthSession {
+            identity_key,
+            peer_nonce: peer_nonce.clone(),
+        });
+        Ok(AuthMessage {
+            version: AUTH_VERSION.to_string(),
+            message_type: "initialResponse".to_string(),
+            identity_key: self.identity_key(),
+            initial_nonce: Some(session_nonce),
+            your_nonce: Some(peer_nonce),
+            signature: Some(signature),
+        })
+    }
+
+    /// Verify a general message, returning the peer's identity key
+    ///
+    /// Reference: TS Peer.processGeneralMessage
+    pub async fn verify_request(&self, auth: &AuthHeaders, payload: Vec<u8>) -> WalletResult<String> {
+        if auth.version != AUTH_VERSION {
+            return Err(WalletError::invalid_parameter("x-bsv-auth-version", AUTH_VERSION));
+        }
+        if !verify_nonce(&auth.your_nonce, &self.deriver, "self")? {
+            return Err(WalletError::invalid_parameter("x-bsv-auth-your-nonce", "a nonce issued by this server"));
+        }
+        let session = self.sessions.lock().await.get(&auth.your_nonce).cloned()
+            .filter(|s| s.identity_key == auth.identity_key)
+            .ok_or_else(|| WalletError::invalid_parameter("x-bsv-auth-identity-key", "the identity of an open session"))?;
+
+        verify_auth_message(
+            &self.deriver,
+            payload,
+            auth.signature.clone(),
+            format!("{} {}", auth.nonce, auth.your_nonce),
+            &session.identity_key,
+        ).await?;
+        Ok(session.identity_key)
+    }
+
+    /// Envelope for the response to a verified request
+    ///
+    /// Reference: TS Peer.toPeer
+    pub async fn sign_response(
+        &self,
+        request: &AuthHeaders,
+        status: u16,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> WalletResult<AuthHeaders> {
+        let peer_nonce = self.sessions.lock().await.get(&request.your_nonce)
+            .map(|s| s.peer_nonce.clone())
+            .ok_or_else(|| WalletError::invalid_operation("BRC-103 session closed before the response was signed"))?;
+        let nonce = random_base64(32);
+        let payload = response_payload(&request.request_id_bytes()?, status, headers, body);
+        let signature = sign_auth_message(
+            &self.deriver,
+            payload,
+            format!("{} {}", nonce, peer_nonce),
+            &request.identity_key,
+        ).await?;
+
+        Ok(AuthHeaders {
+            version: AUTH_VERSION.to_string(),
+            identity_key: self.identity_key(),
+            nonce,
+            your_nonce: peer_nonce,
+            request_id: request.request_id.clone(),
+            signature,
+        })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth_http::{request_payload, AuthFetch};
+
+    fn server() -> AuthServer {
+        AuthServer::new(Arc::new(RootKeyDeriver::new(&[9u8; 32]).unwrap()))
+    }
+
+    fn client() -> AuthFetch {
+        AuthFetch::new(Arc::new(RootKeyDeriver::new(&[7u8; 32]).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_general_message_roundtrip() {
+        let server = server();
+        let client = client();
+        let request = client.initial_request().unwrap();
+        let reply = server.handle_initial_request(&request).await.unwrap();
+        let session = client.open_session(&request, &reply).await.unwrap();
+
+        let body = br#"{"a":1}"#;
+        let auth = client.sign_request(&session, "POST", "/getVersion", None, &[], body).await.unwrap();
+        let request_id = auth.request_id_bytes().unwrap();
+
+        let payload = request_payload(&request_id, "POST", "/getVersion", None, &[], body);
+        assert_eq!(server.verify_request(&auth, payload).await.unwrap(), client.identity_key());
+
+        let tampered = request_payload(&request_id, "POST", "/getVersion", None, &[], br#"{"a":2}"#);
+        assert!(server.verify_request(&auth, tampered).await.is_err());
+
+        let response = server.sign_response(&auth, 200, &[], b"{}").await.unwrap();
+        assert!(client.verify_response(&session, &auth, 200, &response.to_headers(), b"{}").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_session() {
+        let server = server();
+        let client = client();
+        let session = crate::auth_http::PeerSession {
+            identity_key: server.identity_key(),
+            server_nonce: create_nonce(&server.deriver, "self").unwrap(),
+            our_nonce: create_nonce(&RootKeyDeriver::new(&[7u8; 32]).unwrap(), "self").unwrap(),
+        };
+
+        let auth = client.sign_request(&session, "POST", "/getVersion", None, &[], b"").await.unwrap();
+        let payload = request_payload(&auth.request_id_bytes().unwrap(), "POST", "/getVersion", None, &[], b"");
+        assert!(server.verify_request(&auth, payload).await.is_err());
+    }
+}
@@ -0,0 +1,392 @@
+//! HTTP Wallet Server (BRC-100 over HTTP)
+//!
+//! **Reference**: TypeScript `HTTPWalletJSON` (@bsv/sdk) wire format
+//!
+//! Serves a `WalletInterface` to local apps without Tauri: each method is
+//! `POST /<methodName>` with the JSON args as the body and the JSON result as
+//! the response, e.g. `POST /createAction`. The calling app is named by the
+//! `Originator` header, or the host of its `Origin` when that is absent.
+//!
+//! With an identity key configured, clients can authenticate with BRC-103/104
+//! (see [`crate::auth_http`]), and `require_auth` rejects calls that do not.
+//!
+//! With `metrics` set, `GET /metrics` returns the wallet's metrics in the
+//! Prometheus text format.
+//!
+//! Requires the `wallet-server` feature.
+
+pub mod auth;
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use wallet_storage::metrics;
+
+use crate::auth_http::{request_payload, AuthHeaders, AuthMessage, AUTH_PATH};
+use crate::keys::RootKeyDeriver;
+use crate::managers::simple_wallet_manager::WalletInterface;
+use crate::sdk::errors::{WalletError, WalletErrorCode, WalletResult};
+use crate::sdk::originator::Originator;
+use crate::sdk::validation::originator_from_url;
+use auth::AuthServer;
+
+pub use crate::managers::simple_wallet_manager::call_wallet;
+
+/// Port HTTPWalletJSON connects to by default
+pub const DEFAULT_WALLET_PORT: u16 = 3321;
+
+/// Server configuration
+#[derive(Debug, Clone)]
+pub struct WalletServerConfig {
+    /// Address to bind
+    pub addr: SocketAddr,
+
+    /// Reject wallet calls that are not BRC-104 authenticated
+    pub require_auth: bool,
+
+    /// Identity keys allowed to make authenticated calls; empty allows any
+    pub allowed_identity_keys: Vec<String>,
+
+    /// Record metrics and serve them at `GET /metrics`
+    pub metrics: bool,
+}
+
+impl Default for WalletServerConfig {
+    fn default() -> Self {
+        Self {
+            addr: ([127, 0, 0, 1], DEFAULT_WALLET_PORT).into(),
+            require_auth: false,
+            allowed_identity_keys: Vec::new(),
+            metrics: false,
+        }
+    }
+}
+
+/// HTTP server for a wallet
+pub struct WalletServer {
+    config: WalletServerConfig,
+    wallet: Arc<dyn WalletInterface>,
+    auth: Option<AuthServer>,
+}
+
+impl WalletServer {
+    /// Create a server; `identity` enables BRC-103/104 authentication
+    pub fn new(
+        config: WalletServerConfig,
+        wallet: Arc<dyn WalletInterface>,
+        identity: Option<Arc<RootKeyDeriver>>,
+    ) -> WalletResult<Self> {
+        if config.require_auth && identity.is_none() {
+            return Err(WalletError::invalid_parameter("identity", "an identity key when require_auth is set"));
+        }
+        if config.metrics {
+            metrics::enable();
+        }
+        Ok(Self {
+            config,
+            wallet,
+            auth: identity.map(AuthServer::new),
+        })
+    }
+
+    /// Serve wallet calls until `shutdown` completes
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) -> WalletResult<()> {
+        let addr = self.config.addr;
+        let server = Arc::new(self);
+        let make_svc = make_service_fn(move |_conn| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle_request(req).await) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr)
+            .map_err(|e| WalletError::internal(format!("wallet server bind {}: {}", addr, e)))?
+            .serve(make_svc)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(|e| WalletError::internal(format!("wallet server: {}", e)))
+    }
+
+    /// Handle one HTTP request
+    pub async fn handle_request(&self, req: Request<Body>) -> Response<Body> {
+        if req.method() == Method::OPTIONS {
+            return response(StatusCode::NO_CONTENT, Vec::new(), Body::empty());
+        }
+        if self.config.metrics && req.method() == Method::GET && req.uri().path() == metrics::METRICS_PATH {
+            return Response::builder()
+                .header(hyper::header::CONTENT_TYPE, metrics::CONTENT_TYPE)
+                .body(Body::from(metrics::render()))
+                .unwrap_or_default();
+        }
+        if req.method() != Method::POST {
+            return error_response(StatusCode::METHOD_NOT_ALLOWED, &WalletError::invalid_operation("wallet calls must be POST"));
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &WalletError::invalid_parameter("body", e.to_string())),
+        };
+        let path = parts.uri.path().to_string();
+        let headers: Vec<(String, String)> = parts.headers.iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_ascii_lowercase(), v.to_str().ok()?.to_string())))
+            .collect();
+
+        if path == AUTH_PATH {
+            return self.handle_handshake(&body).await;
+        }
+
+        let auth_headers = match AuthHeaders::from_headers(&headers) {
+            Ok(auth_headers) => auth_headers,
+            Err(e) => return error_response(StatusCode::UNAUTHORIZED, &e),
+        };
+        let request_auth = match (&self.auth, auth_headers) {
+            (Some(auth), Some(auth_headers)) => {
+                let request_id = match auth_headers.request_id_bytes() {
+                    Ok(request_id) => request_id,
+                    Err(e) => return error_response(StatusCode::UNAUTHORIZED, &e),
+                };
+                let payload = request_payload(&request_id, parts.method.as_str(), &path, parts.uri.query(), &headers, &body);
+                let identity_key = match auth.verify_request(&auth_headers, payload).await {
+                    Ok(identity_key) => identity_key,
+                    Err(e) => return error_response(StatusCode::UNAUTHORIZED, &e),
+                };
+                if !self.config.allowed_identity_keys.is_empty()
+                    && !self.config.allowed_identity_keys.contains(&identity_key)
+                {
+                    return error_response(StatusCode::FORBIDDEN, &WalletError::new("WERR_UNAUTHORIZED", "Identity key is not allowed to use this wallet"));
+                }
+                Some((auth, auth_headers))
+            }
+            _ if self.config.require_auth => {
+                return error_response(StatusCode::UNAUTHORIZED, &WalletError::new("WERR_UNAUTHORIZED", "BRC-104 authentication required"));
+            }
+            _ => None,
+        };
+
+        let (status, body) = self.call(&path, &headers, &body).await;
+        let body = body.to_string().into_bytes();
+        let auth_headers = match request_auth {
+            Some((auth, request)) => match auth.sign_response(&request, status.as_u16(), &[], &body).await {
+                Ok(response_auth) => response_auth.to_headers(),
+                Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e),
+            },
+            None => Vec::new(),
+        };
+        response(status, auth_headers, Body::from(body))
+    }
+
+    /// BRC-103 handshake at `/.well-known/auth`
+    async fn handle_handshake(&self, body: &[u8]) -> Response<Body> {
+        let Some(auth) = &self.auth else {
+            return error_response(StatusCode::NOT_FOUND, &WalletError::not_implemented("BRC-103 authentication is not configured"));
+        };
+        let message: AuthMessage = match serde_json::from_slice(body) {
+            Ok(message) => message,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &WalletError::invalid_parameter("body", e.to_string())),
+        };
+        match auth.handle_initial_request(&message).await {
+            Ok(reply) => json_response(StatusCode::OK, &json!(reply)),
+            Err(e) => error_response(StatusCode::BAD_REQUEST, &e),
+        }
+    }
+
+    /// Run the wallet method named by `path`
+    async fn call(&self, path: &str, headers: &[(String, String)], body: &[u8]) -> (StatusCode, Value) {
+        let args = if body.is_empty() {
+            json!({})
+        } else {
+            match serde_json::from_slice(body) {
+                Ok(args) => args,
+                Err(e) => return (StatusCode::BAD_REQUEST, error_body(&WalletError::invalid_parameter("args", format!("valid JSON: {}", e)))),
+            }
+        };
+        let originator = match request_originator(headers) {
+            Ok(originator) => originator,
+            Err(e) => return (StatusCode::BAD_REQUEST, error_body(&e)),
+        };
+
+        let method = path.trim_start_matches('/');
+        match call_wallet(self.wallet.as_ref(), method, args, originator.as_ref().map(Originator::as_str)).await {
+            None => (StatusCode::NOT_FOUND, error_body(&WalletError::not_implemented(format!("Unknown wallet method: {}", method)))),
+            Some(Ok(result)) => (StatusCode::OK, result),
+            Some(Err(e)) => (error_status(&e), error_body(&e)),
+        }
+    }
+}
+
+/// Originator from the `Originator` header, else the `Origin` host
+fn request_originator(headers: &[(String, String)]) -> WalletResult<Option<Originator>> {
+    let get = |name: &str| headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    match (get("originator"), get("origin")) {
+        (Some(originator), _) if originator.contains("://") => originator_from_url(originator).map(Some),
+        (Some(originator), _) => Originator::parse(originator).map(Some),
+        (None, Some(origin)) => originator_from_url(origin).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// HTTP status for a wallet error
+fn error_status(e: &WalletError) -> StatusCode {
+    match e.code {
+        WalletErrorCode::InvalidParameter | WalletErrorCode::MissingParameter | WalletErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+        WalletErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+        WalletErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+        WalletErrorCode::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+        WalletErrorCode::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Serialized `WalletError`, which carries the `message` and `isError` HTTPWalletJSON reports
+fn error_body(e: &WalletError) -> Value {
+    json!(e)
+}
+
+fn response(status: StatusCode, headers: Vec<(String, String)>, body: Body) -> Response<Body> {
+    let mut builder = Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(hyper::header::ACCESS_CONTROL_ALLOW_HEADERS, "*")
+        .header(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, "POST, OPTIONS")
+        .header(hyper::header::ACCESS_CONTROL_EXPOSE_HEADERS, "*");
+    for (k, v) in headers {
+        builder = builder.header(k, v);
+    }
+    builder.body(body).unwrap_or_default()
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    response(status, Vec::new(), Body::from(body.to_string()))
+}
+
+fn error_response(status: StatusCode, e: &WalletError) -> Response<Body> {
+    json_response(status, &error_body(e))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockWallet;
+    use crate::wallet::{Wallet, WalletConfig};
+    use crate::auth_http::AuthFetch;
+
+    fn server(require_auth: bool) -> WalletServer {
+        server_with_config(WalletServerConfig { require_auth, ..Default::default() })
+    }
+
+    fn server_with_config(config: WalletServerConfig) -> WalletServer {
+        let wallet = Wallet::new(WalletConfig {
+            chain: "test".to_string(),
+            root_key: vec![5u8; 32],
+            storage: Arc::new(MockWallet::new()),
+            storage_provider: None,
+            certifier_client: None,
+            broadcaster: None,
+            utxo_status: None,
+            fiat_rates: None,
+            identity_resolver: None,
+            trust_settings: None,
+            settings: None,
+            event_bus: None,
+            admin_originator: None,
+        }).unwrap();
+        let identity = Arc::new(RootKeyDeriver::new(&[9u8; 32]).unwrap());
+        WalletServer::new(config, Arc::new(wallet), Some(identity)).unwrap()
+    }
+
+    fn post(path: &str, headers: &[(String, String)], body: &[u8]) -> Request<Body> {
+        let mut builder = Request::builder().method(Method::POST).uri(path);
+        for (k, v) in headers {
+            builder = builder.header(k.as_str(), v.as_str());
+        }
+        builder.body(Body::from(body.to_vec())).unwrap()
+    }
+
+    async fn body_json(response: Response<Body>) -> Value {
+        serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_calls() {
+        let server = server(false);
+        let originator = [("Originator".to_string(), "app.example.com".to_string())];
+
+        let response = server.handle_request(post("/getNetwork", &originator, b"{}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await, json!({ "network": "test" }));
+
+        let response = server.handle_request(post("/notAMethod", &originator, b"{}")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = server.handle_request(post("/getPublicKey", &originator, b"{\"identityKey\": 1}")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["code"], "WERR_INVALID_PARAMETER");
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_call() {
+        let server = server(true);
+        let response = server.handle_request(post("/getNetwork", &[], b"{}")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let client = AuthFetch::new(Arc::new(RootKeyDeriver::new(&[7u8; 32]).unwrap()));
+        let request = client.initial_request().unwrap();
+        let response = server.handle_request(post(AUTH_PATH, &[], &serde_json::to_vec(&request).unwrap())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let reply: AuthMessage = serde_json::from_value(body_json(response).await).unwrap();
+        let session = client.open_session(&request, &reply).await.unwrap();
+
+        let auth = client.sign_request(&session, "POST", "/getNetwork", None, &[], b"{}").await.unwrap();
+        let headers = auth.to_headers();
+        let response = server.handle_request(post("/getNetwork", &headers, b"{}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_headers: Vec<(String, String)> = response.headers().iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap().to_string()))
+            .collect();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(client.verify_response(&session, &auth, 200, &response_headers, &body).await.is_ok());
+
+        let response = server.handle_request(post("/getVersion", &headers, b"{}")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let get_metrics = || Request::builder().method(Method::GET).uri("/metrics").body(Body::empty()).unwrap();
+        let response = server(false).handle_request(get_metrics()).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let server = server_with_config(WalletServerConfig { metrics: true, ..Default::default() });
+        metrics::inc(metrics::BROADCASTS_ATTEMPTED, &[]);
+        let response = server.handle_request(get_metrics()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[hyper::header::CONTENT_TYPE], metrics::CONTENT_TYPE);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("# TYPE wallet_broadcasts_attempted_total counter"));
+    }
+
+    #[test]
+    fn test_request_originator() {
+        let header = |k: &str, v: &str| vec![(k.to_string(), v.to_string())];
+        let originator = |headers: &[(String, String)]| request_originator(headers).unwrap().map(String::from);
+        assert_eq!(originator(&header("originator", "App.Example.com")).as_deref(), Some("app.example.com"));
+        assert_eq!(originator(&header("origin", "http://localhost:3000")).as_deref(), Some("localhost:3000"));
+        assert_eq!(originator(&[]), None);
+        assert!(request_originator(&header("originator", "not a domain")).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Conformance with the TypeScript toolbox
+//!
+//! Checks the Rust port byte for byte against vectors written by the
+//! TypeScript implementation into tests/vectors (see generate.mjs there).
+//! A missing vector file fails its test.
+//!
+//! Run with `cargo test -p wallet-core --features conformance`.
+
+#![cfg(feature = "conformance")]
+
+use std::path::PathBuf;
+
+use base64::Engine;
+use serde_json::Value;
+use wallet_core::beef::Beef;
+use wallet_core::keys::brc42::{derive_child_private_key, derive_child_public_key};
+use wallet_core::managers::wallet_permissions_manager::{
+    build_pushdrop_fields, CertificateDetails, PermissionRequest, PermissionType, SpendingDetails,
+};
+use wallet_core::sdk::CreateActionArgs;
+use wallet_core::signer::methods::create_action::validate_create_action_args;
+use wallet_core::transaction::{SigHash, Transaction};
+use wallet_test_utils::MockWallet;
+
+/// The vectors in `tests/vectors/<name>.json`
+fn load(name: &str) -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vectors").join(format!("{}.json", name));
+    let json = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {} (run tests/vectors/generate.mjs to write it)", path.display(), e));
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+fn vectors(file: &Value, key: &str) -> Vec<Value> {
+    let vectors = file[key].as_array().cloned().unwrap_or_default();
+    assert!(!vectors.is_empty(), "no {} vectors", key);
+    vectors
+}
+
+fn str_of<'a>(v: &'a Value, key: &str) -> &'a str {
+    v[key].as_str().unwrap_or_else(|| panic!("vector field {} missing in {}", key, v))
+}
+
+fn hex_of(v: &Value, key: &str) -> Vec<u8> {
+    hex::decode(str_of(v, key)).unwrap()
+}
+
+#[test]
+fn brc42_derivations() {
+    let file = load("brc42");
+    for v in vectors(&file, "private") {
+        let derived = derive_child_private_key(
+            &hex_of(&v, "recipientPrivateKey"),
+            &hex_of(&v, "senderPublicKey"),
+            str_of(&v, "invoiceNumber"),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(derived), str_of(&v, "privateKey"), "{}", v);
+    }
+    for v in vectors(&file, "public") {
+        let derived = derive_child_public_key(
+            &hex_of(&v, "senderPrivateKey"),
+            &hex_of(&v, "recipientPublicKey"),
+            str_of(&v, "invoiceNumber"),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(derived), str_of(&v, "publicKey"), "{}", v);
+    }
+}
+
+#[test]
+fn sighash_digests() {
+    let file = load("sighash");
+    for v in vectors(&file, "vectors") {
+        let tx = Transaction::from_bytes(&hex_of(&v, "rawTx")).unwrap();
+        let digest = SigHash::calculate(
+            &tx,
+            v["inputIndex"].as_u64().unwrap() as usize,
+            &hex_of(&v, "subscript"),
+            v["scope"].as_u64().unwrap() as u8,
+            v["satoshis"].as_i64().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(digest), str_of(&v, "sighash"), "{}", v);
+    }
+}
+
+#[test]
+fn beef_bytes() {
+    let file = load("beef");
+    for v in vectors(&file, "vectors") {
+        let bytes = hex_of(&v, "beef");
+        let beef = Beef::from_binary(&bytes).unwrap();
+        let txids: Vec<&str> = beef.txs.iter().map(|btx| btx.txid.as_str()).collect();
+        let expected: Vec<&str> = v["txids"].as_array().unwrap().iter().map(|t| t.as_str().unwrap()).collect();
+        assert_eq!(txids, expected);
+        assert_eq!(beef.to_binary().unwrap(), bytes, "re-serialized BEEF differs");
+        beef.verify_valid(false).unwrap();
+
+        let atomic = beef.to_binary_atomic(str_of(&v, "atomicTxid")).unwrap();
+        assert_eq!(hex::encode(&atomic), str_of(&v, "atomicBeef"));
+        let parsed = Beef::from_binary(&atomic).unwrap();
+        assert_eq!(parsed.atomic_txid.as_deref(), Some(str_of(&v, "atomicTxid")));
+    }
+}
+
+#[test]
+fn create_action_validation() {
+    let file = load("create_action");
+    for v in vectors(&file, "vectors") {
+        let args: CreateActionArgs = serde_json::from_value(v["args"].clone()).unwrap();
+        let result = validate_create_action_args(&args);
+        if v.get("error").is_some() {
+            assert!(result.is_err(), "TS rejects {}", v["args"]);
+            continue;
+        }
+        let valid = result.unwrap_or_else(|e| panic!("TS accepts {}: {}", v["args"], e));
+        let expected = &v["valid"];
+        assert_eq!(valid.is_new_tx, expected["isNewTx"], "isNewTx of {}", v["args"]);
+        assert_eq!(valid.is_delayed, expected["isDelayed"], "isDelayed of {}", v["args"]);
+        assert_eq!(valid.is_no_send, expected["isNoSend"], "isNoSend of {}", v["args"]);
+        assert_eq!(valid.is_sign_action, expected["isSignAction"], "isSignAction of {}", v["args"]);
+        assert_eq!(valid.version, expected["version"], "version of {}", v["args"]);
+        assert_eq!(valid.lock_time, expected["lockTime"], "lockTime of {}", v["args"]);
+        assert_eq!(
+            valid.include_all_source_transactions, expected["includeAllSourceTransactions"],
+            "includeAllSourceTransactions of {}",
+            v["args"]
+        );
+    }
+}
+
+/// A TS `PermissionRequest` as the Rust type
+fn permission_request(v: &Value) -> PermissionRequest {
+    let permission_type = match str_of(v, "type") {
+        "protocol" => PermissionType::Protocol,
+        "basket" => PermissionType::Basket,
+        "certificate" => PermissionType::Certificate,
+        "spending" => PermissionType::Spending,
+        other => panic!("unknown permission type {}", other),
+    };
+    PermissionRequest {
+        permission_type,
+        originator: str_of(v, "originator").to_string(),
+        privileged: v["privileged"].as_bool(),
+        // TS protocol IDs are [securityLevel, protocolName]
+        protocol_id: v["protocolID"].as_array().map(|p| {
+            p.iter().map(|part| part.as_str().map(str::to_string).unwrap_or_else(|| part.to_string())).collect()
+        }),
+        counterparty: v["counterparty"].as_str().map(str::to_string),
+        basket: v["basket"].as_str().map(str::to_string),
+        certificate: v.get("certificate").map(|c| CertificateDetails {
+            verifier: str_of(c, "verifier").to_string(),
+            cert_type: str_of(c, "certType").to_string(),
+            fields: serde_json::from_value(c["fields"].clone()).unwrap(),
+        }),
+        spending: v.get("spending").map(|s| SpendingDetails {
+            satoshis: s["satoshis"].as_i64().unwrap(),
+            line_items: None,
+        }),
+        reason: None,
+        renewal: None,
+        previous_token: None,
+    }
+}
+
+#[tokio::test]
+async fn permission_token_fields() {
+    let file = load("permission_tokens");
+    let wallet = MockWallet::new();
+    for v in vectors(&file, "vectors") {
+        let request = permission_request(&v["request"]);
+        let fields = build_pushdrop_fields(
+            &wallet,
+            "admin.conformance",
+            &request,
+            v["expiry"].as_i64().unwrap(),
+            v["amount"].as_i64(),
+        )
+        .await
+        .unwrap();
+        // The port stores token fields base64-encoded where TS encrypts them
+        // with a random IV, so the plaintexts are compared
+        let plaintext: Vec<String> = fields
+            .iter()
+            .map(|f| String::from_utf8(base64::engine::general_purpose::STANDARD.decode(f).unwrap()).unwrap())
+            .collect();
+        let expected: Vec<String> = serde_json::from_value(v["fields"].clone()).unwrap();
+        assert_eq!(plaintext, expected, "fields of {}", v["request"]);
+    }
+}
@@ -0,0 +1,205 @@
+// Writes the conformance vectors checked by tests/conformance.rs, from the
+// TypeScript implementation:
+//
+//   npm install @bsv/sdk @bsv/wallet-toolbox
+//   node generate.mjs
+//
+// Inputs come from a fixed-seed generator, so reruns against the same TS
+// versions write the same files. brc42.json holds the BRC-42 specification
+// vectors and is not written here.
+
+import { writeFileSync } from 'node:fs'
+import {
+  Beef,
+  BEEF_V1,
+  BEEF_V2,
+  Hash,
+  LockingScript,
+  MerklePath,
+  Transaction,
+  TransactionSignature,
+  UnlockingScript,
+  Utils
+} from '@bsv/sdk'
+import { sdk, WalletPermissionsManager } from '@bsv/wallet-toolbox'
+
+const toHex = Utils.toHex
+
+function write (name, vectors) {
+  const source = `generate.mjs, @bsv/sdk and @bsv/wallet-toolbox`
+  const file = new URL(`./${name}.json`, import.meta.url)
+  writeFileSync(file, JSON.stringify({ source, vectors }, null, 2) + '\n')
+  console.log(`${name}.json: ${vectors.length} vectors`)
+}
+
+let seed = 42
+function randomBytes (n) {
+  const bytes = []
+  for (let i = 0; i < n; i++) {
+    seed = (seed * 1103515245 + 12345) & 0x7fffffff
+    bytes.push(seed >> 16 & 0xff)
+  }
+  return bytes
+}
+const randomInt = max => (randomBytes(1)[0] * 256 + randomBytes(1)[0]) % max
+const p2pkh = () => `76a914${toHex(randomBytes(20))}88ac`
+
+function makeTx (inputs, outputs, lockTime = 0) {
+  return new Transaction(
+    1,
+    inputs.map(([sourceTXID, sourceOutputIndex, sequence = 0xffffffff]) => ({
+      sourceTXID,
+      sourceOutputIndex,
+      unlockingScript: UnlockingScript.fromHex(toHex(randomBytes(randomInt(3) * 36))),
+      sequence
+    })),
+    outputs.map(([satoshis, script]) => ({ satoshis, lockingScript: LockingScript.fromHex(script) })),
+    lockTime
+  )
+}
+
+// ============================================================================
+// Sighash digests: TransactionSignature.format, hashed with hash256
+// ============================================================================
+
+function sighashVectors () {
+  const vectors = []
+  const scopes = [0x41, 0x42, 0x43, 0xc1, 0xc2, 0xc3]
+  for (let n = 0; n < 8; n++) {
+    const inputs = Array.from({ length: 1 + randomInt(3) }, () => [toHex(randomBytes(32)), randomInt(4), 0xffffffff - randomInt(2)])
+    const outputs = Array.from({ length: 1 + randomInt(3) }, () => [1 + randomInt(100000), p2pkh()])
+    const tx = makeTx(inputs, outputs, randomInt(2) * 800000)
+    for (let inputIndex = 0; inputIndex < tx.inputs.length; inputIndex++) {
+      for (const scope of scopes) {
+        const subscript = p2pkh()
+        const satoshis = 1 + randomInt(1000000)
+        const input = tx.inputs[inputIndex]
+        const preimage = TransactionSignature.format({
+          sourceTXID: input.sourceTXID,
+          sourceOutputIndex: input.sourceOutputIndex,
+          sourceSatoshis: satoshis,
+          transactionVersion: tx.version,
+          otherInputs: tx.inputs.filter((_, i) => i !== inputIndex),
+          outputs: tx.outputs,
+          inputIndex,
+          subscript: LockingScript.fromHex(subscript),
+          inputSequence: input.sequence,
+          lockTime: tx.lockTime,
+          scope
+        })
+        vectors.push({ rawTx: tx.toHex(), inputIndex, subscript, satoshis, scope, sighash: toHex(Hash.hash256(preimage)) })
+      }
+    }
+  }
+  return vectors
+}
+
+// ============================================================================
+// BEEF: a proven parent followed by a chain of unproven descendants
+// ============================================================================
+
+function beefVector (version, chainLength) {
+  const parent = makeTx([[toHex(randomBytes(32)), 0]], [[100000000, p2pkh()], [5000, p2pkh()]])
+  const height = 800000 + randomInt(100000)
+  const path = new MerklePath(height, [[
+    { offset: 0, hash: parent.id('hex'), txid: true },
+    { offset: 1, hash: toHex(randomBytes(32)) }
+  ]])
+
+  const beef = new Beef(version)
+  beef.mergeRawTx(parent.toBinary(), beef.mergeBump(path))
+  let prev = parent
+  for (let i = 0; i < chainLength; i++) {
+    const satoshis = prev.outputs[0].satoshis - 1000
+    const tx = makeTx([[prev.id('hex'), 0]], [[satoshis, p2pkh()]])
+    beef.mergeRawTx(tx.toBinary())
+    prev = tx
+  }
+
+  const txids = beef.txs.map(btx => btx.txid)
+  const last = txids[txids.length - 1]
+  return {
+    beef: toHex(beef.toBinary()),
+    txids,
+    atomicTxid: last,
+    atomicBeef: toHex(beef.toBinaryAtomic(last))
+  }
+}
+
+function beefVectors () {
+  return [beefVector(BEEF_V1, 1), beefVector(BEEF_V2, 0), beefVector(BEEF_V2, 3), beefVector(BEEF_V2, 100)]
+}
+
+// ============================================================================
+// createAction: how validateCreateActionArgs classifies the call
+// ============================================================================
+
+function createActionVectors () {
+  const output = () => ({ lockingScript: p2pkh(), satoshis: 1000, outputDescription: 'conformance payment' })
+  const input = () => ({
+    outpoint: `${toHex(randomBytes(32))}.0`,
+    inputDescription: 'conformance input',
+    unlockingScriptLength: 107
+  })
+  const cases = [
+    { description: 'conformance basic', outputs: [output()] },
+    { description: 'conformance no send', outputs: [output()], options: { noSend: true } },
+    { description: 'conformance immediate', outputs: [output()], options: { acceptDelayedBroadcast: false } },
+    { description: 'conformance sign later', inputs: [input()], outputs: [output()], options: { signAndProcess: false } },
+    { description: 'conformance lock time', outputs: [output()], lockTime: 800000, version: 2 },
+    { description: 'conformance no outputs', inputs: [input()] },
+    { description: 'x', outputs: [output()] },
+    { description: 'conformance bad script', outputs: [{ ...output(), lockingScript: 'zz' }] }
+  ]
+  return cases.map(args => {
+    try {
+      const valid = sdk.validateCreateActionArgs(args)
+      return {
+        args,
+        valid: {
+          isNewTx: valid.isNewTx,
+          isDelayed: valid.isDelayed,
+          isNoSend: valid.isNoSend,
+          isSignAction: valid.isSignAction,
+          version: valid.version,
+          lockTime: valid.lockTime,
+          includeAllSourceTransactions: valid.includeAllSourceTransactions
+        }
+      }
+    } catch (e) {
+      return { args, error: e.name }
+    }
+  })
+}
+
+// ============================================================================
+// Permission tokens: PushDrop fields before encryption
+// ============================================================================
+
+async function permissionTokenVectors () {
+  // Field "encryption" returns the plaintext, exposing the fields themselves
+  const underlying = new Proxy({}, {
+    get: (_, method) => method === 'encrypt'
+      ? async ({ plaintext }) => ({ ciphertext: plaintext })
+      : async () => ({})
+  })
+  const manager = new WalletPermissionsManager(underlying, 'admin.conformance')
+  const requests = [
+    [{ type: 'protocol', originator: 'app.example', privileged: false, protocolID: [2, 'conformance'], counterparty: 'self' }, 1900000000],
+    [{ type: 'protocol', originator: 'app.example', privileged: true, protocolID: [1, 'todo list'], counterparty: '02' + '11'.repeat(32) }, 0],
+    [{ type: 'basket', originator: 'shop.example', basket: 'receipts' }, 1900000000],
+    [{ type: 'certificate', originator: 'id.example', privileged: false, certificate: { verifier: '03' + '22'.repeat(32), certType: 'z40BOInXkI8m7f/wBrv4MJ09bZfzZbTj2fJqCtONqCY=', fields: ['name', 'email'] } }, 1900000000],
+    [{ type: 'spending', originator: 'shop.example', spending: { satoshis: 25000 } }, 0, 50000]
+  ]
+  const vectors = []
+  for (const [request, expiry, amount] of requests) {
+    const fields = await manager.buildPushdropFields(request, expiry, amount)
+    vectors.push({ request, expiry, amount: amount ?? null, fields: fields.map(f => Utils.toUTF8(f)) })
+  }
+  return vectors
+}
+
+write('sighash', sighashVectors())
+write('beef', beefVectors())
+write('create_action', createActionVectors())
+write('permission_tokens', await permissionTokenVectors())
@@ -0,0 +1,51 @@
+//! Wallet Permissions Manager Integration Tests
+//!
+//! Permission checks run against `wallet_test_utils::MockWallet`.
+
+use serde_json::{json, Value};
+use wallet_core::managers::wallet_permissions_manager::{query_spent_since, PermissionToken};
+use wallet_test_utils::MockWallet;
+
+const ADMIN: &str = "admin.example.com";
+
+/// Spending token for `shop.example` allowing `authorized_amount` a month
+fn spending_token(authorized_amount: i64) -> PermissionToken {
+    serde_json::from_value(json!({
+        "txid": "aa".repeat(32),
+        "tx": [],
+        "outputIndex": 0,
+        "outputScript": "",
+        "satoshis": 1,
+        "originator": "shop.example",
+        "expiry": 0,
+        "authorizedAmount": authorized_amount,
+    }))
+    .unwrap()
+}
+
+/// listActions response holding actions with these net satoshis
+fn actions(satoshis: &[i64]) -> Value {
+    let actions: Vec<Value> = satoshis.iter().map(|s| json!({ "satoshis": s })).collect();
+    json!({ "totalActions": actions.len(), "actions": actions })
+}
+
+#[tokio::test]
+async fn test_spent_since_after_abort() {
+    let token = spending_token(1000);
+    let wallet = MockWallet::new().with_response("listActions", actions(&[-100, -300]));
+    assert_eq!(query_spent_since(&wallet, ADMIN, &token).await.unwrap(), 400);
+
+    // The first action is aborted and drops out of listActions; a new one
+    // takes its place in the listing
+    wallet.set_response("listActions", actions(&[-300, -500]));
+    let spent = query_spent_since(&wallet, ADMIN, &token).await.unwrap();
+    assert_eq!(spent, 800);
+    assert!(spent + 300 > token.authorized_amount.unwrap(), "a further 300 must exceed the limit");
+
+    // Every check lists the month's actions from the start
+    let calls = wallet.calls();
+    assert_eq!(calls.len(), 2);
+    assert!(calls.iter().all(|call| call.args["offset"] == 0));
+    assert_eq!(calls[1].originator.as_deref(), Some(ADMIN));
+    assert_eq!(calls[1].args["labels"][0], "admin originator shop.example");
+}
@@ -0,0 +1,228 @@
+/* wallet-toolbox-rs C API. Generated by cbindgen; do not edit. */
+
+#ifndef WALLET_FFI_H
+#define WALLET_FFI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Kind of permission request passed to a `WalletPermissionCallback`
+typedef enum WalletPermissionKind {
+  // A protocol, basket, certificate or spending permission; the JSON is
+  // the TS `PermissionRequest` plus its `requestID`
+  WALLET_PERMISSION_KIND_SINGLE = 0,
+  // A group of permissions requested up front; the JSON is the TS
+  // `GroupedPermissionRequest`
+  WALLET_PERMISSION_KIND_GROUPED = 1,
+} WalletPermissionKind;
+
+// Outcome of a C API call
+typedef enum WalletResultCode {
+  // The call succeeded
+  WALLET_RESULT_CODE_OK = 0,
+  // The wallet reported an error; see `wallet_get_last_error`
+  WALLET_RESULT_CODE_ERROR = 1,
+  // An argument was null, not UTF-8 or otherwise invalid
+  WALLET_RESULT_CODE_INVALID_ARGUMENT = 2,
+  // The call panicked; the handle it was given should not be used again
+  WALLET_RESULT_CODE_PANIC = 3,
+} WalletResultCode;
+
+// A wallet over a `WalletStorage`, behind a permissions manager
+//
+// Requests from originators other than the admin originator are checked
+// against the user's permissions and prompt through the registered
+// `WalletPermissionCallback`. Register one before serving other
+// originators, or their requests wait with nobody to answer them.
+typedef struct WalletHandle WalletHandle;
+
+// An opened SQLite store, shared by the wallets created over it
+typedef struct WalletStorage WalletStorage;
+
+// Called when an app needs a permission the user has not granted
+//
+// Runs on a wallet thread while the call that raised the request waits.
+// Show the prompt and return; answer later from another thread with
+// `wallet_grant_permission` / `wallet_deny_permission` (or their grouped
+// forms), passing the request's `requestID`. `request_json` is valid only
+// until the callback returns.
+typedef void (*WalletPermissionCallback)(void *user_data,
+                                         WalletPermissionKind kind,
+                                         const char *request_json);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The last error on this thread as TS JSON error, or null after a success
+//
+// The string stays valid until the next call on this thread; do not free it.
+const char *wallet_get_last_error(void);
+
+// Free a string returned by this library; null is ignored
+//
+// # Safety
+//
+// `s` must be null or a string returned through an `out_*` pointer of this
+// library that has not been freed yet.
+void wallet_string_free(char *s);
+
+// Open and migrate the SQLite store at `database_path` for `chain`
+//
+// The file is created if missing. On success `*out_storage` holds a handle
+// to free with `wallet_storage_free`.
+//
+// # Safety
+//
+// `database_path` and `chain` must be NUL-terminated strings and
+// `out_storage` must be valid for writes.
+WalletResultCode wallet_storage_open(const char *database_path,
+                                     const char *chain,
+                                     WalletStorage **out_storage);
+
+// Free a storage handle; null is ignored
+//
+// Wallets created over the storage keep working.
+//
+// # Safety
+//
+// `storage` must be null or a handle from `wallet_storage_open` that has
+// not been freed yet.
+void wallet_storage_free(WalletStorage *storage);
+
+// Create a wallet for the 32-byte `root_key` over `storage`
+//
+// `admin_originator` is the integrating app's own originator, allowed
+// everything without prompts. On success `*out_wallet` holds a handle to
+// free with `wallet_destroy`.
+//
+// # Safety
+//
+// `storage` must be a live handle from `wallet_storage_open`, `root_key`
+// must point to `root_key_len` readable bytes, `admin_originator` must be
+// a NUL-terminated string and `out_wallet` must be valid for writes.
+WalletResultCode wallet_create(const WalletStorage *storage,
+                               const uint8_t *root_key,
+                               size_t root_key_len,
+                               const char *admin_originator,
+                               WalletHandle **out_wallet);
+
+// Destroy a wallet handle; null is ignored
+//
+// Pending permission requests are dropped, failing the calls waiting on
+// them. Must not be called from a permission callback.
+//
+// # Safety
+//
+// `wallet` must be null or a handle from `wallet_create` that has not been
+// destroyed and is not in use by another thread.
+void wallet_destroy(WalletHandle *wallet);
+
+// Register `callback` for permission requests, with `user_data` passed back
+//
+// Each registered callback receives every request.
+//
+// # Safety
+//
+// `wallet` must be a live handle. `user_data` is passed to `callback` from
+// wallet threads, so it must be safe to use from any thread and outlive
+// the wallet.
+WalletResultCode wallet_set_permission_callback(const WalletHandle *wallet,
+                                                WalletPermissionCallback callback,
+                                                void *user_data);
+
+// Grant the pending request `request_id`
+//
+// `expiry` (UNIX seconds) and `amount` (authorized satoshis, for spending
+// requests) are optional and may be null. Ephemeral grants last for this
+// one request and create no permission token.
+//
+// # Safety
+//
+// `wallet` must be a live handle, `request_id` a NUL-terminated string,
+// and `expiry` and `amount` null or readable.
+WalletResultCode wallet_grant_permission(const WalletHandle *wallet,
+                                         const char *request_id,
+                                         bool ephemeral,
+                                         const int64_t *expiry,
+                                         const int64_t *amount);
+
+// Deny the pending request `request_id`
+//
+// # Safety
+//
+// `wallet` must be a live handle and `request_id` a NUL-terminated string.
+WalletResultCode wallet_deny_permission(const WalletHandle *wallet, const char *request_id);
+
+// Grant the subset `granted_json` (TS `GroupedPermissions`) of a grouped request
+//
+// `expiry` (UNIX seconds) is optional and may be null.
+//
+// # Safety
+//
+// `wallet` must be a live handle, `request_id` and `granted_json`
+// NUL-terminated strings, and `expiry` null or readable.
+WalletResultCode wallet_grant_grouped_permission(const WalletHandle *wallet,
+                                                 const char *request_id,
+                                                 const char *granted_json,
+                                                 const int64_t *expiry);
+
+// Deny the grouped request `request_id`
+//
+// # Safety
+//
+// `wallet` must be a live handle and `request_id` a NUL-terminated string.
+WalletResultCode wallet_deny_grouped_permission(const WalletHandle *wallet,
+                                                const char *request_id);
+
+// BRC-100 `createAction`
+//
+// # Safety
+//
+// See `wallet_list_outputs`.
+WalletResultCode wallet_create_action(const WalletHandle *wallet,
+                                      const char *originator,
+                                      const char *args_json,
+                                      char **out_result_json);
+
+// BRC-100 `signAction`
+//
+// # Safety
+//
+// See `wallet_list_outputs`.
+WalletResultCode wallet_sign_action(const WalletHandle *wallet,
+                                    const char *originator,
+                                    const char *args_json,
+                                    char **out_result_json);
+
+// BRC-100 `listOutputs`
+//
+// `args_json` is the method's args object; on success `*out_result_json`
+// holds the result, to free with `wallet_string_free`.
+//
+// # Safety
+//
+// `wallet` must be a live handle, `originator` and `args_json`
+// NUL-terminated strings, and `out_result_json` valid for writes.
+WalletResultCode wallet_list_outputs(const WalletHandle *wallet,
+                                     const char *originator,
+                                     const char *args_json,
+                                     char **out_result_json);
+
+// BRC-100 `listActions`
+//
+// # Safety
+//
+// See `wallet_list_outputs`.
+WalletResultCode wallet_list_actions(const WalletHandle *wallet,
+                                     const char *originator,
+                                     const char *args_json,
+                                     char **out_result_json);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WALLET_FFI_H */
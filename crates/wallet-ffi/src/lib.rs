@@ -0,0 +1,210 @@
+//! C ABI for native integrators
+//!
+//! Exposes SQLite storage and the BRC-100 wallet methods to C, C++ and Swift
+//! through `include/wallet_ffi.h`. Arguments and results of wallet methods
+//! are JSON strings in the TS SDK shapes.
+//!
+//! ## Errors
+//!
+//! Every fallible function returns a `WalletResultCode`. On failure,
+//! `wallet_get_last_error` returns the error as TS JSON error
+//! (`{"isError":true,"name":"WERR_...","message":"..."}`) until the next
+//! call on the same thread.
+//!
+//! ## Memory ownership
+//!
+//! - Strings passed in are borrowed for the duration of the call only; they
+//!   must be NUL-terminated UTF-8.
+//! - Strings returned through `out_*` pointers are owned by the caller and
+//!   must be freed with `wallet_string_free`, never with `free`.
+//! - `WalletStorage` and `WalletHandle` are opaque; free them with
+//!   `wallet_storage_free` and `wallet_destroy`. A wallet keeps its own
+//!   reference to the storage, so the storage may be freed first.
+//! - The string returned by `wallet_get_last_error` is owned by the library
+//!   and must not be freed.
+//! - Strings passed to the permission callback are valid only until the
+//!   callback returns.
+//!
+//! ## Threads
+//!
+//! Handles may be used from any thread. Wallet calls block the calling
+//! thread until they finish, including while waiting for a permission
+//! prompt to be answered, so answer prompts from another thread.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use wallet_core::sdk::errors::{WalletError, WalletErrorCode, WalletResult};
+
+pub mod storage;
+pub mod wallet;
+
+pub use storage::{wallet_storage_free, wallet_storage_open, WalletStorage};
+pub use wallet::{
+    wallet_create, wallet_create_action, wallet_deny_grouped_permission, wallet_deny_permission,
+    wallet_destroy, wallet_grant_grouped_permission, wallet_grant_permission, wallet_list_actions,
+    wallet_list_outputs, wallet_set_permission_callback, wallet_sign_action, WalletHandle,
+    WalletPermissionCallback, WalletPermissionKind,
+};
+
+/// Outcome of a C API call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletResultCode {
+    /// The call succeeded
+    Ok = 0,
+    /// The wallet reported an error; see `wallet_get_last_error`
+    Error = 1,
+    /// An argument was null, not UTF-8 or otherwise invalid
+    InvalidArgument = 2,
+    /// The call panicked; the handle it was given should not be used again
+    Panic = 3,
+}
+
+thread_local! {
+    /// Error of the last failed call on this thread, as TS JSON error
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The last error on this thread as TS JSON error, or null after a success
+///
+/// The string stays valid until the next call on this thread; do not free it.
+#[no_mangle]
+pub extern "C" fn wallet_get_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |err| err.as_ptr()))
+}
+
+/// Free a string returned by this library; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a string returned through an `out_*` pointer of this
+/// library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Run the body of a C API call, recording its error and catching panics
+fn ffi_call(body: impl FnOnce() -> WalletResult<()>) -> WalletResultCode {
+    let result = catch_unwind(AssertUnwindSafe(body));
+    let (code, err) = match result {
+        Ok(Ok(())) => (WalletResultCode::Ok, None),
+        Ok(Err(err)) if err.code == WalletErrorCode::InvalidParameter => (WalletResultCode::InvalidArgument, Some(err)),
+        Ok(Err(err)) => (WalletResultCode::Error, Some(err)),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            (WalletResultCode::Panic, Some(WalletError::internal(format!("panicked: {}", message))))
+        }
+    };
+    let json = err.and_then(|err| {
+        let json = serde_json::to_string(&err).ok()?;
+        CString::new(json).ok()
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = json);
+    code
+}
+
+/// Borrow the C string argument `name`
+///
+/// # Safety
+///
+/// `ptr` must be null or a NUL-terminated string valid for `'a`.
+unsafe fn c_str<'a>(name: &str, ptr: *const c_char) -> WalletResult<&'a str> {
+    if ptr.is_null() {
+        return Err(WalletError::invalid_parameter(name, "not null"));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| WalletError::invalid_parameter(name, "UTF-8"))
+}
+
+/// Store `value` in the out parameter `name`
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write_out<T>(name: &str, out: *mut T, value: T) -> WalletResult<()> {
+    if out.is_null() {
+        return Err(WalletError::invalid_parameter(name, "not null"));
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Caller-owned C string for `s`, freed with `wallet_string_free`
+fn into_c_string(s: String) -> WalletResult<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|_| WalletError::internal("result contains a NUL byte"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> Option<String> {
+        let err = wallet_get_last_error();
+        (!err.is_null()).then(|| unsafe { CStr::from_ptr(err) }.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_ffi_call_records_last_error() {
+        let code = ffi_call(|| unsafe { c_str("args", std::ptr::null()) }.map(|_| ()));
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+        let err = last_error().unwrap();
+        assert!(err.contains("\"name\":\"WERR_INVALID_PARAMETER\""));
+
+        assert_eq!(ffi_call(|| Err(WalletError::internal("boom"))), WalletResultCode::Error);
+        assert!(last_error().unwrap().contains("boom"));
+
+        assert_eq!(ffi_call(|| panic!("bad state")), WalletResultCode::Panic);
+        assert!(last_error().unwrap().contains("panicked: bad state"));
+
+        assert_eq!(ffi_call(|| Ok(())), WalletResultCode::Ok);
+        assert!(last_error().is_none());
+    }
+
+    #[test]
+    fn test_returned_strings_are_caller_owned() {
+        let s = into_c_string("{\"ok\":true}".to_string()).unwrap();
+        assert_eq!(unsafe { c_str("s", s) }.unwrap(), "{\"ok\":true}");
+        unsafe {
+            wallet_string_free(s);
+            wallet_string_free(std::ptr::null_mut());
+        }
+        assert!(into_c_string("a\0b".to_string()).is_err());
+    }
+
+    /// The checked-in header declares every exported function
+    #[test]
+    fn test_header_declares_exports() {
+        let header = include_str!("../include/wallet_ffi.h");
+        for name in [
+            "wallet_get_last_error",
+            "wallet_string_free",
+            "wallet_storage_open",
+            "wallet_storage_free",
+            "wallet_create",
+            "wallet_destroy",
+            "wallet_set_permission_callback",
+            "wallet_grant_permission",
+            "wallet_deny_permission",
+            "wallet_grant_grouped_permission",
+            "wallet_deny_grouped_permission",
+            "wallet_create_action",
+            "wallet_sign_action",
+            "wallet_list_outputs",
+            "wallet_list_actions",
+        ] {
+            assert!(header.contains(&format!("{}(", name)), "{} missing from wallet_ffi.h", name);
+        }
+        assert!(header.contains("WALLET_RESULT_CODE_INVALID_ARGUMENT = 2"));
+    }
+}
@@ -0,0 +1,62 @@
+//! SQLite storage handles
+
+use crate::{c_str, ffi_call, write_out, WalletResultCode};
+use std::ffi::c_char;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use wallet_core::sdk::errors::WalletResult;
+use wallet_storage_sqlite::{StorageSqlite, WalletStorageProvider};
+
+/// An opened SQLite store, shared by the wallets created over it
+pub struct WalletStorage {
+    pub(crate) chain: String,
+    pub(crate) provider: Arc<Mutex<dyn WalletStorageProvider>>,
+}
+
+/// Open and migrate the SQLite store at `database_path` for `chain`
+///
+/// The file is created if missing. On success `*out_storage` holds a handle
+/// to free with `wallet_storage_free`.
+///
+/// # Safety
+///
+/// `database_path` and `chain` must be NUL-terminated strings and
+/// `out_storage` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_storage_open(
+    database_path: *const c_char,
+    chain: *const c_char,
+    out_storage: *mut *mut WalletStorage,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let database_path = c_str("database_path", database_path)?;
+        let chain = c_str("chain", chain)?;
+        let storage = WalletStorage::open(database_path, chain)?;
+        write_out("out_storage", out_storage, Box::into_raw(Box::new(storage)))
+    })
+}
+
+/// Free a storage handle; null is ignored
+///
+/// Wallets created over the storage keep working.
+///
+/// # Safety
+///
+/// `storage` must be null or a handle from `wallet_storage_open` that has
+/// not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_storage_free(storage: *mut WalletStorage) {
+    if !storage.is_null() {
+        drop(Box::from_raw(storage));
+    }
+}
+
+impl WalletStorage {
+    fn open(database_path: &str, chain: &str) -> WalletResult<Self> {
+        let storage = StorageSqlite::open_or_create(database_path, "ffi", chain)?;
+        Ok(Self {
+            chain: chain.to_string(),
+            provider: Arc::new(Mutex::new(storage)),
+        })
+    }
+}
@@ -0,0 +1,533 @@
+//! Wallet handles, permission prompts and BRC-100 calls
+
+use crate::storage::WalletStorage;
+use crate::{c_str, ffi_call, into_c_string, write_out, WalletResultCode};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::{Handle, Runtime};
+use wallet_core::managers::wallet_permissions_manager::{
+    GrantGroupedPermissionParams, GrantPermissionParams, WalletPermissionsManager,
+};
+use wallet_core::managers::WalletInterface;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::sdk::validation_args::validate_originator;
+use wallet_core::wallet::{Wallet, WalletConfig};
+
+/// Kind of permission request passed to a `WalletPermissionCallback`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletPermissionKind {
+    /// A protocol, basket, certificate or spending permission; the JSON is
+    /// the TS `PermissionRequest` plus its `requestID`
+    Single = 0,
+    /// A group of permissions requested up front; the JSON is the TS
+    /// `GroupedPermissionRequest`
+    Grouped = 1,
+}
+
+/// Called when an app needs a permission the user has not granted
+///
+/// Runs on a wallet thread while the call that raised the request waits.
+/// Show the prompt and return; answer later from another thread with
+/// `wallet_grant_permission` / `wallet_deny_permission` (or their grouped
+/// forms), passing the request's `requestID`. `request_json` is valid only
+/// until the callback returns.
+pub type WalletPermissionCallback =
+    Option<unsafe extern "C" fn(user_data: *mut c_void, kind: WalletPermissionKind, request_json: *const c_char)>;
+
+/// A wallet over a `WalletStorage`, behind a permissions manager
+///
+/// Requests from originators other than the admin originator are checked
+/// against the user's permissions and prompt through the registered
+/// `WalletPermissionCallback`. Register one before serving other
+/// originators, or their requests wait with nobody to answer them.
+pub struct WalletHandle {
+    runtime: Runtime,
+    permissions: WalletPermissionsManager,
+}
+
+/// Create a wallet for the 32-byte `root_key` over `storage`
+///
+/// `admin_originator` is the integrating app's own originator, allowed
+/// everything without prompts. On success `*out_wallet` holds a handle to
+/// free with `wallet_destroy`.
+///
+/// # Safety
+///
+/// `storage` must be a live handle from `wallet_storage_open`, `root_key`
+/// must point to `root_key_len` readable bytes, `admin_originator` must be
+/// a NUL-terminated string and `out_wallet` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create(
+    storage: *const WalletStorage,
+    root_key: *const u8,
+    root_key_len: usize,
+    admin_originator: *const c_char,
+    out_wallet: *mut *mut WalletHandle,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let storage = storage
+            .as_ref()
+            .ok_or_else(|| WalletError::invalid_parameter("storage", "not null"))?;
+        if root_key.is_null() {
+            return Err(WalletError::invalid_parameter("root_key", "not null"));
+        }
+        let root_key = std::slice::from_raw_parts(root_key, root_key_len).to_vec();
+        let admin_originator = c_str("admin_originator", admin_originator)?;
+        let wallet = WalletHandle::new(storage, root_key, admin_originator)?;
+        write_out("out_wallet", out_wallet, Box::into_raw(Box::new(wallet)))
+    })
+}
+
+/// Destroy a wallet handle; null is ignored
+///
+/// Pending permission requests are dropped, failing the calls waiting on
+/// them. Must not be called from a permission callback.
+///
+/// # Safety
+///
+/// `wallet` must be null or a handle from `wallet_create` that has not been
+/// destroyed and is not in use by another thread.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_destroy(wallet: *mut WalletHandle) {
+    if !wallet.is_null() {
+        drop(Box::from_raw(wallet));
+    }
+}
+
+/// Register `callback` for permission requests, with `user_data` passed back
+///
+/// Each registered callback receives every request.
+///
+/// # Safety
+///
+/// `wallet` must be a live handle. `user_data` is passed to `callback` from
+/// wallet threads, so it must be safe to use from any thread and outlive
+/// the wallet.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_permission_callback(
+    wallet: *const WalletHandle,
+    callback: WalletPermissionCallback,
+    user_data: *mut c_void,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let callback = callback.ok_or_else(|| WalletError::invalid_parameter("callback", "not null"))?;
+        let callback = PermissionCallback { callback, user_data };
+        wallet.block_on(async {
+            let on_request = callback.handler(WalletPermissionKind::Single);
+            wallet.permissions.bind_callback_protocol(on_request.clone()).await;
+            wallet.permissions.bind_callback_basket(on_request.clone()).await;
+            wallet.permissions.bind_callback_certificate(on_request.clone()).await;
+            wallet.permissions.bind_callback_spending(on_request).await;
+            wallet.permissions.bind_callback_grouped(callback.handler(WalletPermissionKind::Grouped)).await;
+        })
+    })
+}
+
+/// Grant the pending request `request_id`
+///
+/// `expiry` (UNIX seconds) and `amount` (authorized satoshis, for spending
+/// requests) are optional and may be null. Ephemeral grants last for this
+/// one request and create no permission token.
+///
+/// # Safety
+///
+/// `wallet` must be a live handle, `request_id` a NUL-terminated string,
+/// and `expiry` and `amount` null or readable.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_grant_permission(
+    wallet: *const WalletHandle,
+    request_id: *const c_char,
+    ephemeral: bool,
+    expiry: *const i64,
+    amount: *const i64,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let params = GrantPermissionParams {
+            request_id: c_str("request_id", request_id)?.to_string(),
+            expiry: expiry.as_ref().copied(),
+            ephemeral: Some(ephemeral),
+            amount: amount.as_ref().copied(),
+        };
+        wallet.block_on(wallet.permissions.grant_permission(params))?
+    })
+}
+
+/// Deny the pending request `request_id`
+///
+/// # Safety
+///
+/// `wallet` must be a live handle and `request_id` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_deny_permission(
+    wallet: *const WalletHandle,
+    request_id: *const c_char,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let request_id = c_str("request_id", request_id)?.to_string();
+        wallet.block_on(wallet.permissions.deny_permission(request_id))?
+    })
+}
+
+/// Grant the subset `granted_json` (TS `GroupedPermissions`) of a grouped request
+///
+/// `expiry` (UNIX seconds) is optional and may be null.
+///
+/// # Safety
+///
+/// `wallet` must be a live handle, `request_id` and `granted_json`
+/// NUL-terminated strings, and `expiry` null or readable.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_grant_grouped_permission(
+    wallet: *const WalletHandle,
+    request_id: *const c_char,
+    granted_json: *const c_char,
+    expiry: *const i64,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let granted = serde_json::from_str(c_str("granted_json", granted_json)?)
+            .map_err(|e| WalletError::invalid_parameter("granted_json", format!("GroupedPermissions JSON: {}", e)))?;
+        let params = GrantGroupedPermissionParams {
+            request_id: c_str("request_id", request_id)?.to_string(),
+            granted,
+            expiry: expiry.as_ref().copied(),
+        };
+        wallet.block_on(wallet.permissions.grant_grouped_permission(params))?
+    })
+}
+
+/// Deny the grouped request `request_id`
+///
+/// # Safety
+///
+/// `wallet` must be a live handle and `request_id` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_deny_grouped_permission(
+    wallet: *const WalletHandle,
+    request_id: *const c_char,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let request_id = c_str("request_id", request_id)?.to_string();
+        wallet.block_on(wallet.permissions.deny_grouped_permission(request_id))?
+    })
+}
+
+/// BRC-100 `createAction`
+///
+/// # Safety
+///
+/// See `wallet_list_outputs`.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_action(
+    wallet: *const WalletHandle,
+    originator: *const c_char,
+    args_json: *const c_char,
+    out_result_json: *mut *mut c_char,
+) -> WalletResultCode {
+    wallet_call(Method::CreateAction, wallet, originator, args_json, out_result_json)
+}
+
+/// BRC-100 `signAction`
+///
+/// # Safety
+///
+/// See `wallet_list_outputs`.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_sign_action(
+    wallet: *const WalletHandle,
+    originator: *const c_char,
+    args_json: *const c_char,
+    out_result_json: *mut *mut c_char,
+) -> WalletResultCode {
+    wallet_call(Method::SignAction, wallet, originator, args_json, out_result_json)
+}
+
+/// BRC-100 `listOutputs`
+///
+/// `args_json` is the method's args object; on success `*out_result_json`
+/// holds the result, to free with `wallet_string_free`.
+///
+/// # Safety
+///
+/// `wallet` must be a live handle, `originator` and `args_json`
+/// NUL-terminated strings, and `out_result_json` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_list_outputs(
+    wallet: *const WalletHandle,
+    originator: *const c_char,
+    args_json: *const c_char,
+    out_result_json: *mut *mut c_char,
+) -> WalletResultCode {
+    wallet_call(Method::ListOutputs, wallet, originator, args_json, out_result_json)
+}
+
+/// BRC-100 `listActions`
+///
+/// # Safety
+///
+/// See `wallet_list_outputs`.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_list_actions(
+    wallet: *const WalletHandle,
+    originator: *const c_char,
+    args_json: *const c_char,
+    out_result_json: *mut *mut c_char,
+) -> WalletResultCode {
+    wallet_call(Method::ListActions, wallet, originator, args_json, out_result_json)
+}
+
+impl WalletHandle {
+    fn new(storage: &WalletStorage, root_key: Vec<u8>, admin_originator: &str) -> WalletResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("wallet-ffi")
+            .enable_all()
+            .build()
+            .map_err(|e| WalletError::internal(format!("failed to start wallet runtime: {}", e)))?;
+        let guard = runtime.enter();
+
+        let wallet = Wallet::new(WalletConfig {
+            chain: storage.chain.clone(),
+            root_key,
+            storage: Arc::new(Unavailable),
+            storage_provider: Some(storage.provider.clone()),
+            certifier_client: None,
+            broadcaster: None,
+            utxo_status: None,
+            fiat_rates: None,
+            identity_resolver: None,
+            trust_settings: None,
+            settings: None,
+            admin_originator: Some(admin_originator.to_string()),
+            event_bus: None,
+        })?;
+        let permissions = WalletPermissionsManager::new(Arc::new(wallet), admin_originator.to_string(), None);
+
+        drop(guard);
+        Ok(Self { runtime, permissions })
+    }
+
+    /// Run `future` on the wallet's runtime, blocking the calling thread
+    ///
+    /// Fails on wallet threads, where blocking would deadlock the runtime.
+    fn block_on<F: Future>(&self, future: F) -> WalletResult<F::Output> {
+        if Handle::try_current().is_ok() {
+            return Err(WalletError::invalid_operation(
+                "Wallet functions cannot be called from a permission callback; answer requests from another thread.",
+            ));
+        }
+        Ok(self.runtime.block_on(future))
+    }
+}
+
+/// Borrow a wallet handle argument
+///
+/// # Safety
+///
+/// `wallet` must be null or a live handle.
+unsafe fn handle<'a>(wallet: *const WalletHandle) -> WalletResult<&'a WalletHandle> {
+    wallet
+        .as_ref()
+        .ok_or_else(|| WalletError::invalid_parameter("wallet", "not null"))
+}
+
+/// A registered permission callback and its user data
+#[derive(Clone, Copy)]
+struct PermissionCallback {
+    callback: unsafe extern "C" fn(*mut c_void, WalletPermissionKind, *const c_char),
+    user_data: *mut c_void,
+}
+
+// SAFETY: `wallet_set_permission_callback` requires `user_data` to be
+// usable from any thread.
+unsafe impl Send for PermissionCallback {}
+unsafe impl Sync for PermissionCallback {}
+
+impl PermissionCallback {
+    /// Permissions manager handler passing requests to the callback as JSON
+    #[allow(clippy::type_complexity)]
+    fn handler<R: Serialize>(
+        self,
+        kind: WalletPermissionKind,
+    ) -> Arc<dyn Fn(R) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync> {
+        Arc::new(move |request| {
+            let json = CString::new(serde_json::to_string(&request)?)?;
+            self.call(kind, &json);
+            Ok(())
+        })
+    }
+
+    fn call(&self, kind: WalletPermissionKind, request_json: &CStr) {
+        // SAFETY: the callback was registered for this purpose and the JSON
+        // outlives the call
+        unsafe { (self.callback)(self.user_data, kind, request_json.as_ptr()) }
+    }
+}
+
+/// Wallet methods callable with JSON args
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    CreateAction,
+    SignAction,
+    ListOutputs,
+    ListActions,
+}
+
+/// Call `method` with JSON args, storing its JSON result in `out_result_json`
+///
+/// # Safety
+///
+/// As for `wallet_list_outputs`.
+unsafe fn wallet_call(
+    method: Method,
+    wallet: *const WalletHandle,
+    originator: *const c_char,
+    args_json: *const c_char,
+    out_result_json: *mut *mut c_char,
+) -> WalletResultCode {
+    ffi_call(|| {
+        let wallet = handle(wallet)?;
+        let (originator, args) = call_args(c_str("originator", originator)?, c_str("args_json", args_json)?)?;
+        let originator = Some(originator.as_str());
+        let permissions = &wallet.permissions;
+        let result = wallet.block_on(async {
+            match method {
+                Method::CreateAction => permissions.create_action(args, originator).await,
+                Method::SignAction => permissions.sign_action(args, originator).await,
+                Method::ListOutputs => permissions.list_outputs(args, originator).await,
+                Method::ListActions => permissions.list_actions(args, originator).await,
+            }
+        })??;
+        write_out("out_result_json", out_result_json, into_c_string(result.to_string())?)
+    })
+}
+
+/// Validated originator and JSON object args of a wallet call
+fn call_args(originator: &str, args: &str) -> WalletResult<(String, Value)> {
+    let originator = validate_originator(Some(originator))?.unwrap_or_default();
+    let args = match serde_json::from_str(args) {
+        Ok(Value::Null) => Value::Object(Map::new()),
+        Ok(args @ Value::Object(_)) => args,
+        _ => return Err(WalletError::invalid_parameter("args_json", "a JSON object")),
+    };
+    Ok((originator, args))
+}
+
+/// Inner wallet for the few methods `Wallet` delegates even with storage:
+/// lookups needing network services, which FFI wallets do not configure
+struct Unavailable;
+
+fn unavailable(method: &str) -> WalletResult<Value> {
+    Err(WalletError::not_implemented(format!("{} is not available through the C API", method)))
+}
+
+#[async_trait::async_trait]
+impl WalletInterface for Unavailable {
+    async fn create_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createAction") }
+    async fn sign_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("signAction") }
+    async fn abort_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("abortAction") }
+    async fn list_actions(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listActions") }
+    async fn internalize_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("internalizeAction") }
+    async fn list_outputs(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listOutputs") }
+    async fn relinquish_output(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishOutput") }
+    async fn get_public_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("getPublicKey") }
+    async fn reveal_counterparty_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealCounterpartyKeyLinkage") }
+    async fn reveal_specific_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealSpecificKeyLinkage") }
+    async fn encrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("encrypt") }
+    async fn decrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("decrypt") }
+    async fn create_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createHmac") }
+    async fn verify_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifyHmac") }
+    async fn create_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createSignature") }
+    async fn verify_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifySignature") }
+    async fn acquire_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("acquireCertificate") }
+    async fn list_certificates(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listCertificates") }
+    async fn prove_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("proveCertificate") }
+    async fn relinquish_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishCertificate") }
+    async fn discover_by_identity_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByIdentityKey") }
+    async fn discover_by_attributes(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByAttributes") }
+    async fn is_authenticated(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("isAuthenticated") }
+    async fn wait_for_authentication(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("waitForAuthentication") }
+    async fn get_height(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getHeight") }
+    async fn get_header_for_height(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("getHeaderForHeight") }
+    async fn get_network(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getNetwork") }
+    async fn get_version(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getVersion") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{wallet_storage_free, wallet_storage_open};
+    use std::ptr;
+    use std::sync::Mutex;
+
+    fn open_wallet() -> *mut WalletHandle {
+        let mut storage = ptr::null_mut();
+        let code = unsafe { wallet_storage_open(c":memory:".as_ptr(), c"test".as_ptr(), &mut storage) };
+        assert_eq!(code, WalletResultCode::Ok);
+
+        let root_key = [7u8; 32];
+        let mut wallet = ptr::null_mut();
+        let code = unsafe {
+            wallet_create(storage, root_key.as_ptr(), root_key.len(), c"admin.example.com".as_ptr(), &mut wallet)
+        };
+        assert_eq!(code, WalletResultCode::Ok);
+
+        // The wallet keeps the storage alive
+        unsafe { wallet_storage_free(storage) };
+        wallet
+    }
+
+    #[test]
+    fn test_wallet_call_argument_checks() {
+        let wallet = open_wallet();
+        let mut result = ptr::null_mut();
+
+        let code = unsafe { wallet_list_outputs(wallet, ptr::null(), c"{}".as_ptr(), &mut result) };
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+        let code = unsafe { wallet_list_outputs(wallet, c"example.com".as_ptr(), c"[1]".as_ptr(), &mut result) };
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+        let code = unsafe { wallet_deny_permission(wallet, c"no-such-request".as_ptr()) };
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+        let code = unsafe { wallet_set_permission_callback(wallet, None, ptr::null_mut()) };
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+        assert!(result.is_null());
+
+        unsafe { wallet_destroy(wallet) };
+        let code = unsafe { wallet_deny_permission(ptr::null(), c"id".as_ptr()) };
+        assert_eq!(code, WalletResultCode::InvalidArgument);
+    }
+
+    static REQUESTS: Mutex<Vec<(WalletPermissionKind, String)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn record(_: *mut c_void, kind: WalletPermissionKind, request_json: *const c_char) {
+        let json = CStr::from_ptr(request_json).to_str().unwrap().to_string();
+        REQUESTS.lock().unwrap().push((kind, json));
+    }
+
+    #[test]
+    fn test_permission_callback_receives_json() {
+        let callback = PermissionCallback { callback: record, user_data: ptr::null_mut() };
+        let handler = callback.handler::<Value>(WalletPermissionKind::Grouped);
+        handler(serde_json::json!({ "requestID": "r1", "originator": "example.com" })).unwrap();
+
+        let requests = REQUESTS.lock().unwrap();
+        assert_eq!(requests[0].0, WalletPermissionKind::Grouped);
+        assert!(requests[0].1.contains("\"requestID\":\"r1\""));
+    }
+
+    #[test]
+    fn test_call_args_validation() {
+        let (originator, args) = call_args("Example.COM", "null").unwrap();
+        assert_eq!(originator, "example.com");
+        assert!(args.as_object().unwrap().is_empty());
+        assert!(call_args("", "{}").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! gRPC Wallet Server
+//!
+//! Serves a `WalletInterface` and, optionally, storage sync to services in
+//! other languages over gRPC (see `proto/wallet.proto`):
+//!
+//! - `WalletService.Call` runs any BRC-100 method by its wire name, with
+//!   JSON args and result as over HTTP (see `wallet_core::wallet_server`).
+//! - `WalletService.SubscribeEvents` streams the wallet event bus, and
+//!   `SubscribePermissionRequests` streams permission prompts to answer with
+//!   `GrantPermission` / `DenyPermission`, so clients need not poll.
+//! - `StorageSync` serves chunked sync of a storage to other storages.
+//!
+//! Failed calls carry the gRPC code closest to the wallet error and the
+//! TS JSON error in the `wallet-error-bin` metadata.
+//!
+//! Calls are not otherwise authenticated: with `auth_token` set, every call
+//! must present it as `authorization: Bearer <token>`. Serve on a trusted
+//! network, or behind a TLS proxy when the token crosses one.
+
+// tonic services return `Status`, which is large by design
+#![allow(clippy::result_large_err)]
+
+pub mod storage_sync;
+pub mod wallet_service;
+
+/// Generated protobuf messages, clients and servers for `wallet.v1`
+pub mod proto {
+    tonic::include_proto!("wallet.v1");
+}
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Status};
+use wallet_core::sdk::errors::{WalletError, WalletErrorCode, WalletResult};
+use wallet_storage::WalletStorageProvider;
+
+use proto::storage_sync_server::StorageSyncServer;
+use proto::wallet_service_server::WalletServiceServer;
+pub use storage_sync::StorageSyncService;
+pub use wallet_service::WalletGrpcService;
+
+/// Port the gRPC server listens on by default, next to the HTTP wallet port
+pub const DEFAULT_GRPC_PORT: u16 = 3322;
+
+/// Server configuration
+#[derive(Debug, Clone)]
+pub struct GrpcServerConfig {
+    /// Address to bind
+    pub addr: SocketAddr,
+
+    /// Token every call must present as `authorization: Bearer <token>`
+    pub auth_token: Option<String>,
+}
+
+impl Default for GrpcServerConfig {
+    fn default() -> Self {
+        Self {
+            addr: ([127, 0, 0, 1], DEFAULT_GRPC_PORT).into(),
+            auth_token: None,
+        }
+    }
+}
+
+/// gRPC server for a wallet and, optionally, its storage
+pub struct WalletGrpcServer {
+    config: GrpcServerConfig,
+    wallet: WalletGrpcService,
+    storage_sync: Option<StorageSyncService>,
+}
+
+impl WalletGrpcServer {
+    pub fn new(config: GrpcServerConfig, wallet: WalletGrpcService) -> Self {
+        Self {
+            config,
+            wallet,
+            storage_sync: None,
+        }
+    }
+
+    /// Also serve `StorageSync` over `storage`
+    pub fn with_storage_sync(mut self, storage: Arc<Mutex<dyn WalletStorageProvider>>) -> Self {
+        self.storage_sync = Some(StorageSyncService::new(storage));
+        self
+    }
+
+    /// Serve on `config.addr` until `shutdown` completes
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) -> WalletResult<()> {
+        let addr = self.config.addr;
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| WalletError::internal(format!("grpc server bind {}: {}", addr, e)))?;
+        self.serve_with_listener(listener, shutdown).await
+    }
+
+    /// Serve connections from `listener` until `shutdown` completes
+    pub async fn serve_with_listener(self, listener: TcpListener, shutdown: impl Future<Output = ()>) -> WalletResult<()> {
+        let check = check_auth_token(self.config.auth_token.map(Arc::from));
+        let storage_sync = self
+            .storage_sync
+            .map(|sync| StorageSyncServer::with_interceptor(sync, check.clone()));
+
+        tonic::transport::Server::builder()
+            .add_service(WalletServiceServer::with_interceptor(self.wallet, check))
+            .add_optional_service(storage_sync)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+            .await
+            .map_err(|e| WalletError::internal(format!("grpc server: {}", e)))
+    }
+}
+
+/// Interceptor rejecting calls without `authorization: Bearer <token>`
+///
+/// Accepts every call when `token` is `None`.
+fn check_auth_token(
+    token: Option<Arc<str>>,
+) -> impl Fn(Request<()>) -> Result<Request<()>, Status> + Clone + Send + Sync + 'static {
+    move |request: Request<()>| {
+        let Some(token) = &token else {
+            return Ok(request);
+        };
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match presented {
+            Some(presented) if presented == token.as_ref() => Ok(request),
+            _ => Err(Status::unauthenticated("a valid bearer token is required")),
+        }
+    }
+}
+
+/// gRPC status for a wallet error, with the TS JSON error in `wallet-error-bin`
+pub(crate) fn wallet_status(err: WalletError) -> Status {
+    let code = match err.code {
+        WalletErrorCode::InvalidParameter
+        | WalletErrorCode::MissingParameter
+        | WalletErrorCode::BadRequest
+        | WalletErrorCode::InvalidPublicKey
+        | WalletErrorCode::InvalidData => Code::InvalidArgument,
+        WalletErrorCode::Unauthorized => Code::Unauthenticated,
+        WalletErrorCode::PermissionDenied => Code::PermissionDenied,
+        WalletErrorCode::NotImplemented => Code::Unimplemented,
+        WalletErrorCode::NotFound => Code::NotFound,
+        WalletErrorCode::InsufficientFunds | WalletErrorCode::NotActive | WalletErrorCode::InvalidOperation => {
+            Code::FailedPrecondition
+        }
+        WalletErrorCode::BroadcastUnavailable => Code::Unavailable,
+        WalletErrorCode::QuotaExceeded => Code::ResourceExhausted,
+        _ => Code::Internal,
+    };
+    let mut status = Status::new(code, err.description.clone());
+    if let Ok(json) = serde_json::to_vec(&err) {
+        status
+            .metadata_mut()
+            .insert_bin("wallet-error-bin", MetadataValue::from_bytes(&json));
+    }
+    status
+}
+
+/// JSON message argument `name`, parsed
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(name: &str, json: &str) -> Result<T, Status> {
+    serde_json::from_str(json)
+        .map_err(|e| wallet_status(WalletError::invalid_parameter(name, format!("valid JSON: {}", e))))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_status() {
+        let status = wallet_status(WalletError::invalid_parameter("args", "a JSON object"));
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let json = status.metadata().get_bin("wallet-error-bin").unwrap().to_bytes().unwrap();
+        let err: WalletError = serde_json::from_slice(&json).unwrap();
+        assert_eq!(err.code, WalletErrorCode::InvalidParameter);
+
+        assert_eq!(wallet_status(WalletError::not_implemented("x")).code(), Code::Unimplemented);
+        assert_eq!(wallet_status(WalletError::internal("x")).code(), Code::Internal);
+    }
+
+    #[test]
+    fn test_check_auth_token() {
+        let request = |auth: Option<&str>| {
+            let mut request = Request::new(());
+            if let Some(auth) = auth {
+                request.metadata_mut().insert("authorization", auth.parse().unwrap());
+            }
+            request
+        };
+
+        let open = check_auth_token(None);
+        assert!(open(request(None)).is_ok());
+
+        let check = check_auth_token(Some(Arc::from("s3cret")));
+        assert!(check(request(Some("Bearer s3cret"))).is_ok());
+        assert_eq!(check(request(Some("Bearer wrong"))).unwrap_err().code(), Code::Unauthenticated);
+        assert_eq!(check(request(None)).unwrap_err().code(), Code::Unauthenticated);
+    }
+}
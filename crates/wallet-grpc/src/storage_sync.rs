@@ -0,0 +1,65 @@
+//! `StorageSync` service: chunked sync between wallet storages
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tonic::{Request, Response, Status};
+use wallet_core::sdk::errors::WalletError;
+use wallet_storage::{RequestSyncChunkArgs, SyncChunk, WalletStorageProvider};
+
+use crate::proto::storage_sync_server::StorageSync;
+use crate::proto::{Empty, JsonMessage, ProcessSyncChunkRequest};
+use crate::{parse_json, wallet_status};
+
+/// Serves sync chunks of a storage, and applies chunks from other storages
+///
+/// Reference: TS StorageServer sync methods
+pub struct StorageSyncService {
+    storage: Arc<Mutex<dyn WalletStorageProvider>>,
+}
+
+impl StorageSyncService {
+    pub fn new(storage: Arc<Mutex<dyn WalletStorageProvider>>) -> Self {
+        Self { storage }
+    }
+}
+
+#[tonic::async_trait]
+impl StorageSync for StorageSyncService {
+    async fn make_available(&self, _request: Request<Empty>) -> Result<Response<JsonMessage>, Status> {
+        let settings = self.storage.lock().await.make_available().await.map_err(storage_status)?;
+        json_response(&settings)
+    }
+
+    async fn get_sync_chunk(&self, request: Request<JsonMessage>) -> Result<Response<JsonMessage>, Status> {
+        let args: RequestSyncChunkArgs = parse_json("args", &request.into_inner().json)?;
+        let chunk = self.storage.lock().await.get_sync_chunk(&args).await.map_err(storage_status)?;
+        json_response(&chunk)
+    }
+
+    async fn process_sync_chunk(
+        &self,
+        request: Request<ProcessSyncChunkRequest>,
+    ) -> Result<Response<JsonMessage>, Status> {
+        let request = request.into_inner();
+        let args: RequestSyncChunkArgs = parse_json("args", &request.args_json)?;
+        let chunk: SyncChunk = parse_json("chunk", &request.chunk_json)?;
+        let result = self
+            .storage
+            .lock()
+            .await
+            .process_sync_chunk(&args, &chunk)
+            .await
+            .map_err(storage_status)?;
+        json_response(&result)
+    }
+}
+
+fn storage_status(err: wallet_storage::StorageError) -> Status {
+    wallet_status(WalletError::from(err))
+}
+
+fn json_response<T: serde::Serialize>(value: &T) -> Result<Response<JsonMessage>, Status> {
+    let json = serde_json::to_string(value).map_err(|e| wallet_status(WalletError::from(e)))?;
+    Ok(Response::new(JsonMessage { json }))
+}
@@ -0,0 +1,327 @@
+//! `WalletService`: BRC-100 calls, wallet events and permission prompts
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use wallet_core::events::{WalletEvent, WalletEventBus, DEFAULT_EVENT_CAPACITY};
+use wallet_core::managers::wallet_permissions_manager::{
+    GrantGroupedPermissionParams, GrantPermissionParams, PermissionEventHandler, WalletPermissionsManager,
+};
+use wallet_core::managers::{call_wallet, WalletInterface};
+use wallet_core::sdk::errors::WalletError;
+use wallet_core::sdk::validation_args::validate_originator;
+
+use crate::proto::wallet_service_server::WalletService;
+use crate::proto::{
+    CallRequest, CallResponse, DenyPermissionRequest, Empty, GrantPermissionRequest, PermissionRequestMessage,
+    SubscribeRequest, WalletEventMessage,
+};
+use crate::{parse_json, wallet_status};
+
+type MessageStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Serves a wallet's methods, events and permission prompts
+///
+/// Events and prompts are streamed only once configured with
+/// `with_event_bus` and `with_permissions`; until then their subscriptions
+/// fail with `UNIMPLEMENTED`.
+pub struct WalletGrpcService {
+    wallet: Arc<dyn WalletInterface>,
+    event_bus: Option<WalletEventBus>,
+    permissions: Option<WalletPermissionsManager>,
+    permission_requests: broadcast::Sender<PermissionRequestMessage>,
+}
+
+impl WalletGrpcService {
+    /// Serve calls to `wallet`, usually a `WalletPermissionsManager`
+    pub fn new(wallet: Arc<dyn WalletInterface>) -> Self {
+        let (permission_requests, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        Self {
+            wallet,
+            event_bus: None,
+            permissions: None,
+            permission_requests,
+        }
+    }
+
+    /// Stream the events emitted on `event_bus`, e.g. `Wallet::event_bus()`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Stream the permission requests of `permissions` and answer them
+    ///
+    /// Requests raised while no client is subscribed are never answered, so
+    /// keep a subscription open while serving other originators.
+    pub async fn with_permissions(mut self, permissions: WalletPermissionsManager) -> Self {
+        let sender = self.permission_requests.clone();
+        let on_request: PermissionEventHandler = Arc::new(move |request| {
+            forward(&sender, &request.request_id, false, &request);
+            Ok(())
+        });
+        permissions.bind_callback_protocol(on_request.clone()).await;
+        permissions.bind_callback_basket(on_request.clone()).await;
+        permissions.bind_callback_certificate(on_request.clone()).await;
+        permissions.bind_callback_spending(on_request).await;
+
+        let sender = self.permission_requests.clone();
+        permissions
+            .bind_callback_grouped(Arc::new(move |request| {
+                forward(&sender, &request.request_id, true, &request);
+                Ok(())
+            }))
+            .await;
+
+        self.permissions = Some(permissions);
+        self
+    }
+
+    fn permissions(&self) -> Result<&WalletPermissionsManager, Status> {
+        self.permissions
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("permission prompts are not served by this wallet"))
+    }
+}
+
+#[tonic::async_trait]
+impl WalletService for WalletGrpcService {
+    async fn call(&self, request: Request<CallRequest>) -> Result<Response<CallResponse>, Status> {
+        let CallRequest { method, originator, args_json } = request.into_inner();
+        let args: Value = if args_json.is_empty() { json!({}) } else { parse_json("args", &args_json)? };
+        let originator = match originator.as_str() {
+            "" => None,
+            originator => validate_originator(Some(originator)).map_err(wallet_status)?,
+        };
+
+        match call_wallet(self.wallet.as_ref(), &method, args, originator.as_deref()).await {
+            None => Err(wallet_status(WalletError::not_implemented(format!("Unknown wallet method: {}", method)))),
+            Some(result) => Ok(Response::new(CallResponse {
+                result_json: result.map_err(wallet_status)?.to_string(),
+            })),
+        }
+    }
+
+    type SubscribeEventsStream = MessageStream<WalletEventMessage>;
+
+    async fn subscribe_events(
+        &self,
+        _request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let event_bus = self
+            .event_bus
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("wallet events are not served by this wallet"))?;
+        // Lagging subscribers skip the events they missed, as on the bus
+        let stream = BroadcastStream::new(event_bus.subscribe())
+            .filter_map(|event| event.ok().and_then(|event| event_message(&event)).map(Ok));
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    type SubscribePermissionRequestsStream = MessageStream<PermissionRequestMessage>;
+
+    async fn subscribe_permission_requests(
+        &self,
+        _request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribePermissionRequestsStream>, Status> {
+        self.permissions()?;
+        let stream = BroadcastStream::new(self.permission_requests.subscribe()).filter_map(|request| request.ok().map(Ok));
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn grant_permission(&self, request: Request<GrantPermissionRequest>) -> Result<Response<Empty>, Status> {
+        let permissions = self.permissions()?;
+        let request = request.into_inner();
+        let expiry = (request.expiry != 0).then_some(request.expiry);
+        let result = if request.granted_json.is_empty() {
+            permissions
+                .grant_permission(GrantPermissionParams {
+                    request_id: request.request_id,
+                    expiry,
+                    ephemeral: Some(request.ephemeral),
+                    amount: (request.amount != 0).then_some(request.amount),
+                })
+                .await
+        } else {
+            permissions
+                .grant_grouped_permission(GrantGroupedPermissionParams {
+                    request_id: request.request_id,
+                    granted: parse_json("granted", &request.granted_json)?,
+                    expiry,
+                })
+                .await
+        };
+        result.map_err(wallet_status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn deny_permission(&self, request: Request<DenyPermissionRequest>) -> Result<Response<Empty>, Status> {
+        let permissions = self.permissions()?;
+        let request = request.into_inner();
+        let result = if request.grouped {
+            permissions.deny_grouped_permission(request.request_id).await
+        } else {
+            permissions.deny_permission(request.request_id).await
+        };
+        result.map_err(wallet_status)?;
+        Ok(Response::new(Empty {}))
+    }
+}
+
+/// Send a permission request to the subscribed clients
+///
+/// With none subscribed the request is dropped and waits unanswered.
+fn forward<T: serde::Serialize>(
+    sender: &broadcast::Sender<PermissionRequestMessage>,
+    request_id: &str,
+    grouped: bool,
+    request: &T,
+) {
+    let json = serde_json::to_string(request).unwrap_or_default();
+    let _ = sender.send(PermissionRequestMessage {
+        request_id: request_id.to_string(),
+        grouped,
+        json,
+    });
+}
+
+fn event_message(event: &WalletEvent) -> Option<WalletEventMessage> {
+    let json = serde_json::to_value(event).ok()?;
+    Some(WalletEventMessage {
+        r#type: json["type"].as_str()?.to_string(),
+        json: json.to_string(),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::wallet_service_client::WalletServiceClient;
+    use crate::{GrpcServerConfig, WalletGrpcServer};
+    use tokio::net::TcpListener;
+    use tonic::Code;
+    use wallet_core::sdk::errors::WalletResult;
+    use wallet_storage::TransactionStatus;
+
+    /// Wallet answering each call with its method and originator
+    struct EchoWallet;
+
+    fn echo(method: &str, originator: Option<&str>) -> WalletResult<Value> {
+        Ok(json!({ "method": method, "originator": originator }))
+    }
+
+    #[async_trait::async_trait]
+    impl WalletInterface for EchoWallet {
+        async fn create_action(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("createAction", o) }
+        async fn sign_action(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("signAction", o) }
+        async fn abort_action(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("abortAction", o) }
+        async fn list_actions(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("listActions", o) }
+        async fn internalize_action(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("internalizeAction", o) }
+        async fn list_outputs(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("listOutputs", o) }
+        async fn relinquish_output(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("relinquishOutput", o) }
+        async fn get_public_key(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("getPublicKey", o) }
+        async fn reveal_counterparty_key_linkage(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("revealCounterpartyKeyLinkage", o) }
+        async fn reveal_specific_key_linkage(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("revealSpecificKeyLinkage", o) }
+        async fn encrypt(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("encrypt", o) }
+        async fn decrypt(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("decrypt", o) }
+        async fn create_hmac(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("createHmac", o) }
+        async fn verify_hmac(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("verifyHmac", o) }
+        async fn create_signature(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("createSignature", o) }
+        async fn verify_signature(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("verifySignature", o) }
+        async fn acquire_certificate(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("acquireCertificate", o) }
+        async fn list_certificates(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("listCertificates", o) }
+        async fn prove_certificate(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("proveCertificate", o) }
+        async fn relinquish_certificate(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("relinquishCertificate", o) }
+        async fn discover_by_identity_key(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("discoverByIdentityKey", o) }
+        async fn discover_by_attributes(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("discoverByAttributes", o) }
+        async fn is_authenticated(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("isAuthenticated", o) }
+        async fn wait_for_authentication(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("waitForAuthentication", o) }
+        async fn get_height(&self, o: Option<&str>) -> WalletResult<Value> { echo("getHeight", o) }
+        async fn get_header_for_height(&self, _: Value, o: Option<&str>) -> WalletResult<Value> { echo("getHeaderForHeight", o) }
+        async fn get_network(&self, o: Option<&str>) -> WalletResult<Value> { echo("getNetwork", o) }
+        async fn get_version(&self, o: Option<&str>) -> WalletResult<Value> { echo("getVersion", o) }
+    }
+
+    fn call(method: &str, originator: &str, args_json: &str) -> CallRequest {
+        CallRequest {
+            method: method.to_string(),
+            originator: originator.to_string(),
+            args_json: args_json.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_dispatch() {
+        let service = WalletGrpcService::new(Arc::new(EchoWallet));
+
+        let response = service.call(Request::new(call("listOutputs", "App.Example.com", ""))).await.unwrap();
+        let result: Value = serde_json::from_str(&response.into_inner().result_json).unwrap();
+        assert_eq!(result, json!({ "method": "listOutputs", "originator": "app.example.com" }));
+
+        let err = service.call(Request::new(call("notAMethod", "", "{}"))).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unimplemented);
+        let err = service.call(Request::new(call("listOutputs", "", "{"))).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let err = service.subscribe_permission_requests(Request::new(SubscribeRequest {})).await.err().unwrap();
+        assert_eq!(err.code(), Code::Unimplemented);
+    }
+
+    fn authorized<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert("authorization", "Bearer s3cret".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_serve_calls_and_events() {
+        let event_bus = WalletEventBus::default();
+        let service = WalletGrpcService::new(Arc::new(EchoWallet)).with_event_bus(event_bus.clone());
+        let config = GrpcServerConfig { auth_token: Some("s3cret".to_string()), ..Default::default() };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(WalletGrpcServer::new(config, service).serve_with_listener(listener, async {
+            stopped.await.ok();
+        }));
+
+        let mut client = WalletServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+        let err = client.call(call("getVersion", "", "")).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+
+        let response = client.call(authorized(call("getVersion", "", ""))).await.unwrap();
+        assert!(response.into_inner().result_json.contains("getVersion"));
+
+        let mut events = client.subscribe_events(authorized(SubscribeRequest {})).await.unwrap().into_inner();
+        while event_bus.subscriber_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        event_bus.emit(WalletEvent::TransactionStatusChanged { txid: "aa".to_string(), status: TransactionStatus::Unproven });
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(event.r#type, "transactionStatusChanged");
+        assert!(event.json.contains("\"txid\":\"aa\""));
+
+        stop.send(()).unwrap();
+        drop(events);
+        server.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_forward_permission_request() {
+        let (sender, mut receiver) = broadcast::channel(4);
+        forward(&sender, "r1", true, &json!({ "requestID": "r1", "originator": "app.example.com" }));
+        let message = receiver.try_recv().unwrap();
+        assert_eq!(message.request_id, "r1");
+        assert!(message.grouped);
+        assert!(message.json.contains("app.example.com"));
+    }
+}
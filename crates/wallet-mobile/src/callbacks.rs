@@ -0,0 +1,69 @@
+//! Callbacks implemented by the native app
+
+use crate::error::MobileWalletError;
+use std::sync::Arc;
+use wallet_core::managers::wallet_permissions_manager::{
+    GroupedPermissionEventHandler, PermissionEventHandler,
+};
+use wallet_core::managers::PrivilegedKeyManager;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use zeroize::Zeroizing;
+
+/// Shows permission prompts to the user
+///
+/// Calls return as soon as the prompt is shown; the app answers later with
+/// `MobileWallet::grant_permission` or `deny_permission`, passing the
+/// request's `requestID`. The wallet call that raised the request waits
+/// until then.
+#[uniffi::export(with_foreign)]
+pub trait PermissionPromptHandler: Send + Sync {
+    /// A protocol, basket, certificate or spending permission is needed
+    ///
+    /// `request_json` is the TS `PermissionRequest` plus its `requestID`.
+    fn on_permission_requested(&self, request_json: String);
+
+    /// A group of permissions was requested up front
+    ///
+    /// `request_json` is the TS `GroupedPermissionRequest`.
+    fn on_grouped_permission_requested(&self, request_json: String);
+}
+
+/// Supplies the privileged key, prompting the user if needed
+///
+/// Called on a blocking thread, so it may wait for the user.
+#[uniffi::export(with_foreign)]
+pub trait PrivilegedKeyProvider: Send + Sync {
+    /// The 32-byte privileged key; `reason` says what it is needed for
+    fn get_privileged_key(&self, reason: String) -> Result<Vec<u8>, MobileWalletError>;
+}
+
+/// `PrivilegedKeyManager` backed by the native app
+pub(crate) struct ForeignKeyManager(pub(crate) Arc<dyn PrivilegedKeyProvider>);
+
+#[async_trait::async_trait]
+impl PrivilegedKeyManager for ForeignKeyManager {
+    async fn get_privileged_key(&self, reason: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let provider = self.0.clone();
+        let reason = reason.to_string();
+        let key = tokio::task::spawn_blocking(move || provider.get_privileged_key(reason))
+            .await
+            .map_err(|e| WalletError::internal(format!("privileged key prompt failed: {}", e)))??;
+        Ok(Zeroizing::new(key))
+    }
+}
+
+/// Permission manager handler forwarding single requests to `handler`
+pub(crate) fn permission_event_handler(handler: Arc<dyn PermissionPromptHandler>) -> PermissionEventHandler {
+    Arc::new(move |request| {
+        handler.on_permission_requested(serde_json::to_string(&request)?);
+        Ok(())
+    })
+}
+
+/// Permission manager handler forwarding grouped requests to `handler`
+pub(crate) fn grouped_event_handler(handler: Arc<dyn PermissionPromptHandler>) -> GroupedPermissionEventHandler {
+    Arc::new(move |request| {
+        handler.on_grouped_permission_requested(serde_json::to_string(&request)?);
+        Ok(())
+    })
+}
@@ -0,0 +1,67 @@
+//! Errors crossing the FFI boundary
+
+use wallet_core::sdk::errors::WalletError;
+
+/// Error returned to, or raised by, the native app
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum MobileWalletError {
+    /// A wallet operation failed
+    ///
+    /// `json` is the serialized `WalletError` in the TS JSON error shape.
+    #[error("{code}: {message}")]
+    Wallet { code: String, message: String, json: String },
+
+    /// A native callback failed, e.g. the user dismissed a key prompt
+    #[error("{message}")]
+    Callback { message: String },
+}
+
+impl From<WalletError> for MobileWalletError {
+    fn from(err: WalletError) -> Self {
+        let json = serde_json::to_string(&err).unwrap_or_default();
+        Self::Wallet {
+            code: err.code.as_str().to_string(),
+            message: err.description,
+            json,
+        }
+    }
+}
+
+impl From<MobileWalletError> for WalletError {
+    fn from(err: MobileWalletError) -> Self {
+        match err {
+            MobileWalletError::Wallet { code, message, json } => {
+                serde_json::from_str(&json).unwrap_or_else(|_| WalletError::new(code, message))
+            }
+            MobileWalletError::Callback { message } => WalletError::internal(message),
+        }
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for MobileWalletError {
+    fn from(err: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        Self::Callback { message: err.reason }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_error_roundtrip() {
+        let err = WalletError::invalid_parameter("args", "a JSON object");
+        let mobile = MobileWalletError::from(err.clone());
+        match &mobile {
+            MobileWalletError::Wallet { code, json, .. } => {
+                assert_eq!(code, "WERR_INVALID_PARAMETER");
+                assert!(json.contains("\"isError\":true"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let back = WalletError::from(mobile);
+        assert_eq!(back.code, err.code);
+        assert_eq!(back.description, err.description);
+    }
+}
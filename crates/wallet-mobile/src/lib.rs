@@ -1,20 +1,30 @@
-//! Mobile wrapper (placeholder)
+//! Mobile FFI for iOS and Android
+//!
+//! Exposes the wallet to native apps through UniFFI. The crate builds as a
+//! `cdylib` (Android) and `staticlib` (iOS); Kotlin and Swift bindings are
+//! generated from the compiled library with `uniffi-bindgen` 0.28:
+//!
+//! ```text
+//! uniffi-bindgen generate --library target/release/libwallet_mobile.so --language kotlin --out-dir out
+//! ```
+//!
+//! ## Conventions
+//!
+//! - Wallet method args and results are BRC-100 JSON strings, as in the
+//!   TypeScript `WalletInterface`; `args` must be a JSON object.
+//! - Every call names its originator; the app is responsible for it being
+//!   the domain of the requesting site or app, never the admin originator.
+//! - Failures are `MobileWalletError::Wallet` carrying the WERR code and the
+//!   serialized `WalletError` in the TS JSON shape.
+//! - Permission prompts and privileged key requests are callbacks into the
+//!   native app; see `PermissionPromptHandler` and `PrivilegedKeyProvider`.
 
-pub use wallet_core::sdk;
-pub use wallet_core::utility::index_client as utility;
-pub use wallet_core::WalletSigner;
-pub use wallet_core::WalletPermissionsManager;
-pub use wallet_core::CWIStyleWalletManager;
-pub use wallet_core::WalletAuthenticationManager;
-pub use wallet_core::wab_client::WABClient;
-pub use wallet_core::wab_client::auth_method_interactors::TwilioPhoneInteractor;
-pub use wallet_core::wab_client::auth_method_interactors::PersonaIDInteractor;
-pub use wallet_core::wab_client::auth_method_interactors::AuthMethodInteractor;
-pub use wallet_core::services::Services;
-pub use wallet_core::sdk::PrivilegedKeyManager;
-pub use wallet_core::SimpleWalletManager;
-pub use wallet_core::storage::index_mobile as storage;
-pub use wallet_core::Wallet;
-pub use wallet_core::monitor::Monitor;
+uniffi::setup_scaffolding!();
 
-pub fn init() {}
+pub mod callbacks;
+pub mod error;
+pub mod mobile_wallet;
+
+pub use callbacks::{PermissionPromptHandler, PrivilegedKeyProvider};
+pub use error::MobileWalletError;
+pub use mobile_wallet::{MobileWallet, MobileWalletConfig};
@@ -0,0 +1,331 @@
+//! The wallet object handed to native apps
+
+use crate::callbacks::{
+    grouped_event_handler, permission_event_handler, ForeignKeyManager, PermissionPromptHandler,
+    PrivilegedKeyProvider,
+};
+use crate::error::MobileWalletError;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use wallet_core::managers::wallet_permissions_manager::{
+    GrantGroupedPermissionParams, GrantPermissionParams, WalletPermissionsManager,
+};
+use wallet_core::managers::{SimpleWalletManager, WalletBuilder, WalletInterface};
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::sdk::validation_args::validate_originator;
+use wallet_core::utility::currency_format::{format_satoshis, DisplayCurrency};
+use wallet_core::wallet::{Wallet, WalletConfig};
+use wallet_storage_sqlite::{StorageSqlite, WalletStorageProvider};
+
+/// Settings for creating a `MobileWallet`
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct MobileWalletConfig {
+    /// `main` or `test`
+    pub chain: String,
+
+    /// SQLite database file in the app's private storage, created if missing
+    pub database_path: String,
+
+    /// The app's own originator, allowed everything without prompts
+    pub admin_originator: String,
+
+    /// Snapshot from `save_snapshot`, restoring the primary key
+    pub snapshot: Option<Vec<u8>>,
+}
+
+/// A wallet embedded in a native app
+///
+/// Wraps a `SimpleWalletManager` whose wallet runs over on-device SQLite
+/// storage behind a `WalletPermissionsManager`. Wallet methods fail until
+/// the user authenticates with `authenticate` or a snapshot.
+#[derive(uniffi::Object)]
+pub struct MobileWallet {
+    manager: SimpleWalletManager,
+
+    /// Permissions manager of the built wallet, once authenticated
+    permissions: Arc<RwLock<Option<WalletPermissionsManager>>>,
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl MobileWallet {
+    /// Open the wallet's storage and restore `config.snapshot`, if any
+    #[uniffi::constructor]
+    pub async fn create(
+        config: MobileWalletConfig,
+        prompts: Arc<dyn PermissionPromptHandler>,
+        keys: Arc<dyn PrivilegedKeyProvider>,
+    ) -> Result<Arc<Self>, MobileWalletError> {
+        let storage = open_storage(&config)?;
+        let permissions = Arc::new(RwLock::new(None));
+        let builder = wallet_builder(&config, storage, prompts, permissions.clone());
+
+        let manager = SimpleWalletManager::new(config.admin_originator.clone(), builder, None);
+        manager.provide_privileged_key_manager(Arc::new(ForeignKeyManager(keys))).await?;
+        if let Some(snapshot) = config.snapshot {
+            manager.load_snapshot(snapshot).await?;
+        }
+
+        Ok(Arc::new(Self { manager, permissions }))
+    }
+
+    /// Authenticate with the user's 32-byte primary key
+    pub async fn authenticate(&self, primary_key: Vec<u8>) -> Result<(), MobileWalletError> {
+        Ok(self.manager.provide_primary_key(primary_key).await?)
+    }
+
+    /// Whether the user has authenticated
+    pub async fn is_authenticated(&self) -> bool {
+        self.manager.is_authenticated(None).await.is_ok()
+    }
+
+    /// Log out, dropping the keys and the built wallet
+    pub async fn destroy(&self) {
+        self.manager.destroy().await;
+        *self.permissions.write().await = None;
+    }
+
+    /// Encrypted snapshot of the primary key, for `MobileWalletConfig::snapshot`
+    ///
+    /// The snapshot is as sensitive as the key itself; keep it in the
+    /// platform keystore.
+    pub async fn save_snapshot(&self) -> Result<Vec<u8>, MobileWalletError> {
+        Ok(self.manager.save_snapshot().await?)
+    }
+
+    /// Restore the primary key from a snapshot, authenticating the user
+    pub async fn load_snapshot(&self, snapshot: Vec<u8>) -> Result<(), MobileWalletError> {
+        Ok(self.manager.load_snapshot(snapshot).await?)
+    }
+
+    /// BRC-100 `createAction`
+    pub async fn create_action(&self, originator: String, args: String) -> Result<String, MobileWalletError> {
+        let (originator, args) = call_args(&originator, &args)?;
+        json_result(self.manager.create_action(args, Some(&originator)).await)
+    }
+
+    /// BRC-100 `signAction`
+    pub async fn sign_action(&self, originator: String, args: String) -> Result<String, MobileWalletError> {
+        let (originator, args) = call_args(&originator, &args)?;
+        json_result(self.manager.sign_action(args, Some(&originator)).await)
+    }
+
+    /// BRC-100 `listOutputs`
+    pub async fn list_outputs(&self, originator: String, args: String) -> Result<String, MobileWalletError> {
+        let (originator, args) = call_args(&originator, &args)?;
+        json_result(self.manager.list_outputs(args, Some(&originator)).await)
+    }
+
+    /// BRC-100 `listActions`
+    pub async fn list_actions(&self, originator: String, args: String) -> Result<String, MobileWalletError> {
+        let (originator, args) = call_args(&originator, &args)?;
+        json_result(self.manager.list_actions(args, Some(&originator)).await)
+    }
+
+    /// Grant the pending request `request_id`
+    ///
+    /// `amount` is the authorized satoshis for spending requests; ephemeral
+    /// grants last for this one request and create no permission token.
+    pub async fn grant_permission(
+        &self,
+        request_id: String,
+        ephemeral: bool,
+        expiry: Option<i64>,
+        amount: Option<i64>,
+    ) -> Result<(), MobileWalletError> {
+        let permissions = self.permissions().await?;
+        let params = GrantPermissionParams {
+            request_id,
+            expiry,
+            ephemeral: Some(ephemeral),
+            amount,
+        };
+        Ok(permissions.grant_permission(params).await?)
+    }
+
+    /// Deny the pending request `request_id`
+    pub async fn deny_permission(&self, request_id: String) -> Result<(), MobileWalletError> {
+        let permissions = self.permissions().await?;
+        Ok(permissions.deny_permission(request_id).await?)
+    }
+
+    /// Grant the subset `granted_json` (TS `GroupedPermissions`) of a grouped request
+    pub async fn grant_grouped_permission(
+        &self,
+        request_id: String,
+        granted_json: String,
+        expiry: Option<i64>,
+    ) -> Result<(), MobileWalletError> {
+        let granted = serde_json::from_str(&granted_json)
+            .map_err(|e| WalletError::invalid_parameter("granted", format!("GroupedPermissions JSON: {}", e)))?;
+        let permissions = self.permissions().await?;
+        let params = GrantGroupedPermissionParams {
+            request_id,
+            granted,
+            expiry,
+        };
+        Ok(permissions.grant_grouped_permission(params).await?)
+    }
+
+    /// Deny the grouped request `request_id`
+    pub async fn deny_grouped_permission(&self, request_id: String) -> Result<(), MobileWalletError> {
+        let permissions = self.permissions().await?;
+        Ok(permissions.deny_grouped_permission(request_id).await?)
+    }
+
+    /// Format an amount for display in `currency`: `SATS`, `BSV` or an ISO 4217 code
+    ///
+    /// Mobile wallets have no exchange rate service, so fiat amounts need
+    /// `bsv_rate`, the app's cached price of one BSV in `currency`.
+    pub fn format_satoshis(
+        &self,
+        satoshis: i64,
+        currency: String,
+        bsv_rate: Option<f64>,
+    ) -> Result<String, MobileWalletError> {
+        let currency = DisplayCurrency::parse(&currency)?;
+        Ok(format_satoshis(satoshis, &currency, bsv_rate)?)
+    }
+}
+
+impl MobileWallet {
+    /// The built wallet's permissions manager
+    async fn permissions(&self) -> WalletResult<WalletPermissionsManager> {
+        self.permissions
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| WalletError::invalid_operation("User is not authenticated."))
+    }
+}
+
+/// Open and migrate the SQLite store at `config.database_path`
+fn open_storage(config: &MobileWalletConfig) -> WalletResult<Arc<Mutex<dyn WalletStorageProvider>>> {
+    let storage = StorageSqlite::open_or_create(&config.database_path, "mobile", &config.chain)?;
+    Ok(Arc::new(Mutex::new(storage)))
+}
+
+/// Builds the wallet for an authenticated user
+///
+/// The wallet runs over `storage` behind a permissions manager whose prompts
+/// go to `prompts`; the manager is kept in `permissions` for grants.
+fn wallet_builder(
+    config: &MobileWalletConfig,
+    storage: Arc<Mutex<dyn WalletStorageProvider>>,
+    prompts: Arc<dyn PermissionPromptHandler>,
+    permissions: Arc<RwLock<Option<WalletPermissionsManager>>>,
+) -> WalletBuilder {
+    let chain = config.chain.clone();
+    let admin_originator = config.admin_originator.clone();
+
+    Arc::new(move |primary_key, _privileged| {
+        let chain = chain.clone();
+        let admin_originator = admin_originator.clone();
+        let storage = storage.clone();
+        let prompts = prompts.clone();
+        let permissions = permissions.clone();
+
+        Box::pin(async move {
+            let wallet = Wallet::new(WalletConfig {
+                chain,
+                root_key: primary_key,
+                storage: Arc::new(Unavailable),
+                storage_provider: Some(storage),
+                certifier_client: None,
+                broadcaster: None,
+                utxo_status: None,
+                fiat_rates: None,
+                identity_resolver: None,
+                trust_settings: None,
+                settings: None,
+                admin_originator: Some(admin_originator.clone()),
+                event_bus: None,
+            })?;
+
+            let manager = WalletPermissionsManager::new(Arc::new(wallet), admin_originator, None);
+            let on_request = permission_event_handler(prompts.clone());
+            manager.bind_callback_protocol(on_request.clone()).await;
+            manager.bind_callback_basket(on_request.clone()).await;
+            manager.bind_callback_certificate(on_request.clone()).await;
+            manager.bind_callback_spending(on_request).await;
+            manager.bind_callback_grouped(grouped_event_handler(prompts)).await;
+
+            *permissions.write().await = Some(manager.clone());
+            Ok(Box::new(manager) as Box<dyn WalletInterface>)
+        })
+    })
+}
+
+/// Validated originator and JSON object args of a wallet call
+fn call_args(originator: &str, args: &str) -> WalletResult<(String, Value)> {
+    let originator = validate_originator(Some(originator))?.unwrap_or_default();
+    let args = match serde_json::from_str(args) {
+        Ok(Value::Null) => Value::Object(Map::new()),
+        Ok(args @ Value::Object(_)) => args,
+        _ => return Err(WalletError::invalid_parameter("args", "a JSON object")),
+    };
+    Ok((originator, args))
+}
+
+fn json_result(result: WalletResult<Value>) -> Result<String, MobileWalletError> {
+    Ok(result?.to_string())
+}
+
+/// Inner wallet for the few methods `Wallet` delegates even with storage:
+/// lookups needing network services, which mobile wallets do not configure
+struct Unavailable;
+
+fn unavailable(method: &str) -> WalletResult<Value> {
+    Err(WalletError::not_implemented(format!("{} is not available in mobile wallets", method)))
+}
+
+#[async_trait::async_trait]
+impl WalletInterface for Unavailable {
+    async fn create_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createAction") }
+    async fn sign_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("signAction") }
+    async fn abort_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("abortAction") }
+    async fn list_actions(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listActions") }
+    async fn internalize_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("internalizeAction") }
+    async fn list_outputs(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listOutputs") }
+    async fn relinquish_output(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishOutput") }
+    async fn get_public_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("getPublicKey") }
+    async fn reveal_counterparty_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealCounterpartyKeyLinkage") }
+    async fn reveal_specific_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealSpecificKeyLinkage") }
+    async fn encrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("encrypt") }
+    async fn decrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("decrypt") }
+    async fn create_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createHmac") }
+    async fn verify_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifyHmac") }
+    async fn create_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createSignature") }
+    async fn verify_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifySignature") }
+    async fn acquire_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("acquireCertificate") }
+    async fn list_certificates(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listCertificates") }
+    async fn prove_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("proveCertificate") }
+    async fn relinquish_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishCertificate") }
+    async fn discover_by_identity_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByIdentityKey") }
+    async fn discover_by_attributes(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByAttributes") }
+    async fn is_authenticated(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("isAuthenticated") }
+    async fn wait_for_authentication(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("waitForAuthentication") }
+    async fn get_height(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getHeight") }
+    async fn get_header_for_height(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("getHeaderForHeight") }
+    async fn get_network(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getNetwork") }
+    async fn get_version(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getVersion") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_args_validation() {
+        let (originator, args) = call_args("Example.COM", "{\"labels\":[\"a\"]}").unwrap();
+        assert_eq!(originator, "example.com");
+        assert_eq!(args["labels"][0], "a");
+
+        let (_, args) = call_args("example.com", "null").unwrap();
+        assert!(args.as_object().unwrap().is_empty());
+
+        assert!(call_args("example.com", "[1]").is_err());
+        assert!(call_args("example.com", "not json").is_err());
+        assert!(call_args("", "{}").is_err());
+    }
+}
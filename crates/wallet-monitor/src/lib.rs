@@ -1,10 +1,24 @@
-//! Monitor and daemon logic (placeholder)
+//! Monitor and daemon logic
+//!
+//! Background tasks that broadcast delayed transactions, track them through
+//! to proof, clean up abandoned actions, consolidate dust change and notify
+//! applications of status changes.
+//!
+//! Reference: wallet-toolbox/src/monitor
 
 pub mod monitor;
 pub mod monitor_daemon;
+pub mod notifications;
+pub mod revalidate;
 pub mod tasks;
 
-pub use monitor::Monitor;
+pub use monitor::{Monitor, SharedStorage, TaskStatus};
 pub use monitor_daemon::MonitorDaemon;
-
-pub fn run() {}
+pub use notifications::{
+    NotificationFilter, NotificationHandler, NotificationRegistry, SignedNotification, TransactionNotification,
+};
+pub use revalidate::{revalidate_proven_txs, RevalidateProvenTxsResult};
+pub use tasks::{
+    MonitorTask, ReorgQueue, TaskCheckForProofs, TaskConsolidateOutputs, TaskFailAbandoned, TaskNotify,
+    TaskPurge, TaskReorg, TaskReviewStatus, TaskSendWaiting,
+};
@@ -1,6 +1,333 @@
-#[derive(Debug, Default)]
-pub struct Monitor;
+//! Monitor
+//!
+//! Holds the registered monitor tasks and runs those that are due.
+//!
+//! Reference: wallet-toolbox/src/monitor/Monitor.ts
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, info_span, warn, Instrument};
+use wallet_core::events::{WalletEvent, WalletEventBus};
+use wallet_storage::{
+    metrics, FindMonitorEventsArgs, MonitorEvent, MonitorEventRecord, Paged, StorageResult, WalletStorageProvider,
+};
+
+use crate::tasks::MonitorTask;
+
+/// Storage shared between the monitor and the wallet
+pub type SharedStorage = Arc<Mutex<Box<dyn WalletStorageProvider>>>;
+
+/// Default fraction of a task's interval added as random jitter
+pub const DEFAULT_JITTER_FRACTION: f64 = 0.1;
+
+/// Scheduling state of a registered task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskStatus {
+    /// Task name
+    pub name: &'static str,
+
+    /// Interval between scheduled runs
+    pub interval_msecs: i64,
+
+    /// When the task last ran, if ever
+    pub last_run_msecs: Option<i64>,
+
+    /// Earliest time the task is next considered
+    pub next_run_msecs: i64,
+
+    /// Log returned by the last run
+    pub last_log: Option<String>,
+
+    /// Error returned by the last run
+    pub last_error: Option<String>,
+}
+
+struct ScheduledTask {
+    task: Box<dyn MonitorTask>,
+    status: TaskStatus,
+}
+
+/// Runs registered monitor tasks against wallet storage
+///
+/// Each task is considered every `interval_msecs` (plus jitter); the task's
+/// own `trigger` then decides whether it actually runs. Non-empty logs and
+/// errors are recorded as monitor events.
+///
+/// Progress is reported on the monitor's event bus by tasks built with
+/// `with_event_bus(monitor.event_bus().clone())`: `TaskSendWaiting` reports
+/// each broadcast and `TaskCheckForProofs` each proof found, so frontends
+/// can `subscribe` for progress indicators, and `TaskNotify` passes status
+/// changes on to registered application handlers.
+///
+/// Reference: TypeScript `Monitor` (`addTask`, `runOnce`)
+pub struct Monitor {
+    storage: SharedStorage,
+    tasks: Vec<ScheduledTask>,
+    event_bus: WalletEventBus,
+
+    /// Fraction of each interval added as random jitter (0 disables)
+    pub jitter_fraction: f64,
+}
 
 impl Monitor {
-    pub fn new() -> Self { Self }
+    pub fn new(storage: SharedStorage) -> Self {
+        Self {
+            storage,
+            tasks: Vec::new(),
+            event_bus: WalletEventBus::default(),
+            jitter_fraction: DEFAULT_JITTER_FRACTION,
+        }
+    }
+
+    /// Share `event_bus`, e.g. the wallet's, instead of a bus of its own
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Bus the monitor's tasks report on
+    pub fn event_bus(&self) -> &WalletEventBus {
+        &self.event_bus
+    }
+
+    /// Receive every event reported on the monitor's bus from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<WalletEvent> {
+        self.event_bus.subscribe()
+    }
+
+    /// Register a task, considered first on the next `run_once`
+    pub fn add_task(&mut self, task: Box<dyn MonitorTask>, interval_msecs: i64) {
+        let status = TaskStatus {
+            name: task.name(),
+            interval_msecs,
+            last_run_msecs: None,
+            next_run_msecs: 0,
+            last_log: None,
+            last_error: None,
+        };
+        self.tasks.push(ScheduledTask { task, status });
+    }
+
+    /// Scheduling state of every registered task
+    pub fn task_status(&self) -> Vec<TaskStatus> {
+        self.tasks.iter().map(|t| t.status.clone()).collect()
+    }
+
+    /// Storage used by the tasks
+    pub fn storage(&self) -> &SharedStorage {
+        &self.storage
+    }
+
+    /// Up to `limit` recorded monitor events, newest first
+    ///
+    /// With `since_msecs`, only events recorded at or after that time.
+    pub async fn recent_events(&self, since_msecs: Option<i64>, limit: u32) -> StorageResult<Vec<MonitorEventRecord>> {
+        let args = FindMonitorEventsArgs {
+            since: since_msecs
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|since| since.to_rfc3339()),
+            paged: Some(Paged { limit, offset: None }),
+            order_descending: Some(true),
+            ..Default::default()
+        };
+        let rows = self.storage.lock().await.find_monitor_events(&args).await?;
+        Ok(rows.iter().map(MonitorEventRecord::from).collect())
+    }
+
+    fn jitter_msecs(&self, interval_msecs: i64) -> i64 {
+        if self.jitter_fraction <= 0.0 {
+            return 0;
+        }
+        (interval_msecs as f64 * self.jitter_fraction * rand::random::<f64>()) as i64
+    }
+
+    /// Run every task that is due at `now_msecs`
+    ///
+    /// Returns the number of tasks that ran. Task failures are recorded in
+    /// their status and as monitor events rather than returned.
+    pub async fn run_once(&mut self, now_msecs: i64) -> usize {
+        let mut ran = 0;
+        for i in 0..self.tasks.len() {
+            if now_msecs < self.tasks[i].status.next_run_msecs {
+                continue;
+            }
+            let interval = self.tasks[i].status.interval_msecs;
+            self.tasks[i].status.next_run_msecs = now_msecs + interval + self.jitter_msecs(interval);
+
+            let scheduled = &mut self.tasks[i];
+            if !scheduled.task.trigger(now_msecs) {
+                continue;
+            }
+            ran += 1;
+
+            let mut storage = self.storage.lock().await;
+            let span = info_span!("monitor_task", task = scheduled.status.name);
+            let started = Instant::now();
+            let result = scheduled.task.run_task(storage.as_mut()).instrument(span.clone()).await;
+            let labels = [("task", scheduled.status.name)];
+            metrics::observe_since(metrics::MONITOR_TASK_SECONDS, &labels, started);
+            scheduled.status.last_run_msecs = Some(now_msecs);
+
+            let task = scheduled.status.name.to_string();
+            let event = match result {
+                Ok(log) => {
+                    span.in_scope(|| debug!(log = log.trim_end(), "task ran"));
+                    scheduled.status.last_error = None;
+                    scheduled.status.last_log = Some(log.clone());
+                    (!log.is_empty()).then_some(MonitorEvent::TaskLog { task, log })
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    span.in_scope(|| warn!(%error, "task failed"));
+                    metrics::inc(metrics::MONITOR_TASK_FAILURES, &labels);
+                    scheduled.status.last_error = Some(error.clone());
+                    Some(MonitorEvent::TaskError { task, error })
+                }
+            };
+
+            if let Some(event) = event {
+                if let Err(e) = storage.insert_monitor_event(&event.to_table()).await {
+                    scheduled.status.last_error = Some(format!("failed to record monitor event: {}", e));
+                }
+            }
+        }
+        ran
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use wallet_storage::{StorageError, TableMonitorEvent};
+    use wallet_test_utils::MockStorage;
+
+    /// Task that always triggers and returns a fixed result
+    pub(crate) struct FixedTask {
+        pub name: &'static str,
+        pub log: &'static str,
+        pub fail: bool,
+    }
+
+    #[async_trait]
+    impl MonitorTask for FixedTask {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn trigger(&mut self, _now_msecs: i64) -> bool {
+            true
+        }
+
+        async fn run_task(&mut self, _storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+            if self.fail {
+                Err(StorageError::Database("boom".to_string()))
+            } else {
+                Ok(self.log.to_string())
+            }
+        }
+    }
+
+    pub(crate) fn shared_mock_storage() -> SharedStorage {
+        Arc::new(Mutex::new(Box::new(MockStorage::new())))
+    }
+
+    async fn event_count(storage: &SharedStorage) -> i64 {
+        // Inserting returns the new row count of the mock
+        storage.lock().await.insert_monitor_event(&TableMonitorEvent::new(0, "probe")).await.unwrap() - 1
+    }
+
+    #[tokio::test]
+    async fn test_run_once_schedules_by_interval() {
+        let mut monitor = Monitor::new(shared_mock_storage());
+        monitor.jitter_fraction = 0.0;
+        monitor.add_task(Box::new(FixedTask { name: "a", log: "", fail: false }), 1000);
+
+        assert_eq!(monitor.run_once(5000).await, 1);
+        let status = &monitor.task_status()[0];
+        assert_eq!(status.last_run_msecs, Some(5000));
+        assert_eq!(status.next_run_msecs, 6000);
+
+        assert_eq!(monitor.run_once(5999).await, 0);
+        assert_eq!(monitor.run_once(6000).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_jitter_bounds() {
+        let mut monitor = Monitor::new(shared_mock_storage());
+        monitor.jitter_fraction = 0.5;
+        monitor.add_task(Box::new(FixedTask { name: "a", log: "", fail: false }), 1000);
+        monitor.run_once(0).await;
+        let next = monitor.task_status()[0].next_run_msecs;
+        assert!((1000..=1500).contains(&next));
+    }
+
+    #[tokio::test]
+    async fn test_run_once_records_events() {
+        let storage = shared_mock_storage();
+        let mut monitor = Monitor::new(storage.clone());
+        monitor.add_task(Box::new(FixedTask { name: "quiet", log: "", fail: false }), 1000);
+        monitor.add_task(Box::new(FixedTask { name: "busy", log: "did work", fail: false }), 1000);
+        monitor.add_task(Box::new(FixedTask { name: "broken", log: "", fail: true }), 1000);
+
+        assert_eq!(monitor.run_once(0).await, 3);
+
+        // Only the non-empty log and the error are recorded
+        assert_eq!(event_count(&storage).await, 2);
+
+        let status = monitor.task_status();
+        assert_eq!(status[1].last_log.as_deref(), Some("did work"));
+        assert!(status[2].last_error.as_deref().unwrap().contains("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_task_events() {
+        let bus = WalletEventBus::default();
+        let monitor = Monitor::new(shared_mock_storage()).with_event_bus(bus.clone());
+        let mut events = monitor.subscribe();
+
+        // Tasks built with the monitor's bus report to its subscribers
+        crate::tasks::emit_progress(
+            Some(monitor.event_bus()),
+            "aa",
+            wallet_storage::TransactionStatus::Sending,
+            wallet_storage::TransactionStatus::Unproven,
+            Some("arc".to_string()),
+        );
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            WalletEvent::TransactionProgress { txid, .. } if txid == "aa"
+        ));
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recent_events() {
+        let storage = shared_mock_storage();
+        let mut monitor = Monitor::new(storage.clone());
+        monitor.add_task(Box::new(FixedTask { name: "busy", log: "did work", fail: false }), 1000);
+        monitor.add_task(Box::new(FixedTask { name: "broken", log: "", fail: true }), 1000);
+        monitor.run_once(0).await;
+
+        let events = monitor.recent_events(None, 10).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, 2);
+        match &events[0].event {
+            MonitorEvent::TaskError { task, error } => {
+                assert_eq!(task, "broken");
+                assert!(error.contains("boom"));
+            }
+            other => panic!("expected a task error, got {:?}", other),
+        }
+        assert_eq!(
+            events[1].event,
+            MonitorEvent::TaskLog { task: "busy".to_string(), log: "did work".to_string() }
+        );
+
+        assert_eq!(monitor.recent_events(None, 1).await.unwrap().len(), 1);
+        let future = chrono::Utc::now().timestamp_millis() + 60_000;
+        assert!(monitor.recent_events(Some(future), 10).await.unwrap().is_empty());
+    }
 }
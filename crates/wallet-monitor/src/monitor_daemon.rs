@@ -1,7 +1,221 @@
-#[derive(Debug, Default)]
-pub struct MonitorDaemon;
+//! MonitorDaemon
+//!
+//! Runs a `Monitor` on a tokio task until shut down. With the
+//! `metrics-server` feature, the daemon can also serve metrics in the
+//! Prometheus text format at `GET /metrics`.
+//!
+//! Reference: wallet-toolbox/src/monitor/MonitorDaemon.ts
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use wallet_core::events::{WalletEvent, WalletEventBus};
+#[cfg(feature = "metrics-server")]
+use wallet_storage::metrics;
+
+use crate::monitor::{Monitor, TaskStatus};
+
+/// Default scheduler tick
+pub const DEFAULT_TICK: Duration = Duration::from_secs(1);
+
+fn now_msecs() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Background scheduler for monitor tasks
+///
+/// Every `tick` the daemon runs the monitor's due tasks. `stop` (or
+/// cancelling the shutdown token) ends the loop after the current tick.
+///
+/// Reference: TypeScript `MonitorDaemon` (`runDaemon`, `stopTasks`)
+pub struct MonitorDaemon {
+    monitor: Arc<Mutex<Monitor>>,
+    event_bus: WalletEventBus,
+    tick: Duration,
+    shutdown: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
 
 impl MonitorDaemon {
-    pub fn new() -> Self { Self }
-    pub fn start(&self) {}
+    pub fn new(monitor: Monitor) -> Self {
+        Self {
+            event_bus: monitor.event_bus().clone(),
+            monitor: Arc::new(Mutex::new(monitor)),
+            tick: DEFAULT_TICK,
+            shutdown: CancellationToken::new(),
+            handle: None,
+        }
+    }
+
+    /// Set how often due tasks are checked
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// The monitor run by this daemon
+    pub fn monitor(&self) -> &Arc<Mutex<Monitor>> {
+        &self.monitor
+    }
+
+    /// Receive every event reported on the monitor's bus from now on
+    ///
+    /// Unlike `monitor().lock()`, does not wait for a running tick.
+    pub fn subscribe(&self) -> broadcast::Receiver<WalletEvent> {
+        self.event_bus.subscribe()
+    }
+
+    /// Token that stops the daemon when cancelled
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Whether the scheduler loop is running
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    /// Start the scheduler loop on the current tokio runtime
+    ///
+    /// Does nothing if already running. A stopped daemon can be restarted.
+    pub fn start(&mut self) {
+        if self.is_running() {
+            return;
+        }
+        if self.shutdown.is_cancelled() {
+            self.shutdown = CancellationToken::new();
+        }
+
+        let monitor = self.monitor.clone();
+        let shutdown = self.shutdown.clone();
+        let mut interval = tokio::time::interval(self.tick);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        self.handle = Some(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => {
+                        monitor.lock().await.run_once(now_msecs()).await;
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Stop the scheduler loop and wait for it to finish
+    pub async fn stop(&mut self) {
+        self.shutdown.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+
+    /// Scheduling state of every registered task
+    pub async fn task_status(&self) -> Vec<TaskStatus> {
+        self.monitor.lock().await.task_status()
+    }
+
+    /// Record metrics and serve them at `GET /metrics` on `addr` until the
+    /// daemon is shut down
+    #[cfg(feature = "metrics-server")]
+    pub async fn serve_metrics(&self, addr: std::net::SocketAddr) -> hyper::Result<()> {
+        use hyper::service::{make_service_fn, service_fn};
+        use std::convert::Infallible;
+
+        metrics::enable();
+        let make_svc = make_service_fn(|_conn| async {
+            Ok::<_, Infallible>(service_fn(|req| async move { Ok::<_, Infallible>(metrics_response(&req)) }))
+        });
+        let shutdown = self.shutdown.clone();
+        hyper::Server::try_bind(&addr)?
+            .serve(make_svc)
+            .with_graceful_shutdown(async move { shutdown.cancelled().await })
+            .await
+    }
+}
+
+/// Response to a request made to the metrics server
+#[cfg(feature = "metrics-server")]
+fn metrics_response(req: &hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+    use hyper::{header, Body, Method, Response, StatusCode};
+
+    if req.method() != Method::GET || req.uri().path() != metrics::METRICS_PATH {
+        return Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap_or_default();
+    }
+    Response::builder()
+        .header(header::CONTENT_TYPE, metrics::CONTENT_TYPE)
+        .body(Body::from(metrics::render()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::tests::{shared_mock_storage, FixedTask};
+
+    fn daemon() -> MonitorDaemon {
+        let mut monitor = Monitor::new(shared_mock_storage());
+        monitor.add_task(Box::new(FixedTask { name: "a", log: "", fail: false }), 60_000);
+        MonitorDaemon::new(monitor).with_tick(Duration::from_millis(5))
+    }
+
+    #[tokio::test]
+    async fn test_start_runs_tasks_and_stop() {
+        let mut daemon = daemon();
+        assert!(!daemon.is_running());
+
+        daemon.start();
+        assert!(daemon.is_running());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let status = daemon.task_status().await;
+        assert!(status[0].last_run_msecs.is_some());
+        assert!(status[0].next_run_msecs > status[0].last_run_msecs.unwrap());
+
+        daemon.stop().await;
+        assert!(!daemon.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_token_stops_daemon() {
+        let mut daemon = daemon();
+        daemon.start();
+        daemon.shutdown_token().cancel();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!daemon.is_running());
+
+        // Restart after shutdown
+        daemon.start();
+        assert!(daemon.is_running());
+        daemon.stop().await;
+    }
+
+    #[cfg(feature = "metrics-server")]
+    #[tokio::test]
+    async fn test_serve_metrics() {
+        use hyper::{Body, Request, StatusCode};
+
+        metrics::enable();
+        let mut daemon = daemon();
+        let shutdown = daemon.shutdown_token();
+        let server = tokio::spawn(async move {
+            daemon.start();
+            daemon.serve_metrics(([127, 0, 0, 1], 0).into()).await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let get = |path: &str| Request::builder().uri(path).body(Body::empty()).unwrap();
+        assert_eq!(metrics_response(&get("/other")).status(), StatusCode::NOT_FOUND);
+        let response = metrics_response(&get("/metrics"));
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("wallet_monitor_task_seconds_count{task=\"a\"}"));
+
+        shutdown.cancel();
+        assert!(server.await.unwrap().is_ok());
+    }
 }
@@ -0,0 +1,359 @@
+//! Transaction notifications
+//!
+//! Applications register handlers for a txid or a label, or with the
+//! `webhooks` feature a callback URL. `TaskNotify` watches the monitor's
+//! event bus and delivers a `SignedNotification` to each matching handler
+//! when a transaction changes status, retrying failed deliveries.
+//!
+//! The payload is JSON signed by the wallet's identity key as a BRC-77
+//! signed message anyone can verify, so a receiver can check it came from
+//! the wallet it expects with `SignedNotification::verify`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use wallet_core::events::WalletEvent;
+use wallet_core::keys::RootKeyDeriver;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::utility::signed_message;
+use wallet_storage::TransactionStatus;
+
+/// Header carrying the hex BRC-77 signature of a webhook body
+pub const SIGNATURE_HEADER: &str = "X-Wallet-Signature";
+
+/// Header carrying the hex identity key of the wallet that signed a webhook body
+pub const IDENTITY_KEY_HEADER: &str = "X-Wallet-Identity-Key";
+
+/// Transactions a handler is notified about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationFilter {
+    /// The transaction with this txid
+    Txid(String),
+    /// Every transaction with this label
+    Label(String),
+}
+
+impl NotificationFilter {
+    fn matches(&self, txid: &str, labels: &[String]) -> bool {
+        match self {
+            NotificationFilter::Txid(t) => t == txid,
+            NotificationFilter::Label(label) => labels.contains(label),
+        }
+    }
+}
+
+/// A transaction status change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionNotification {
+    pub txid: String,
+
+    /// Status before the change, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_status: Option<TransactionStatus>,
+
+    pub status: TransactionStatus,
+
+    /// Name of the service that broadcast or proved the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    /// Labels of the transaction
+    pub labels: Vec<String>,
+
+    /// When the change was seen (RFC 3339)
+    pub timestamp: String,
+}
+
+impl TransactionNotification {
+    /// Notification for `event` if it is a status change, without labels
+    ///
+    /// Progress events that leave the status unchanged, i.e. broadcasts to
+    /// be retried, are not status changes.
+    pub fn from_event(event: &WalletEvent, timestamp: String) -> Option<Self> {
+        let (txid, old_status, status, provider) = match event {
+            WalletEvent::TransactionStatusChanged { txid, status } => (txid, None, *status, None),
+            WalletEvent::TransactionProgress { txid, old_status, new_status, provider } if old_status != new_status => {
+                (txid, Some(*old_status), *new_status, provider.clone())
+            }
+            _ => return None,
+        };
+        Some(Self {
+            txid: txid.clone(),
+            old_status,
+            status,
+            provider,
+            labels: Vec::new(),
+            timestamp,
+        })
+    }
+}
+
+/// A notification and the wallet's signature of its JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedNotification {
+    pub notification: TransactionNotification,
+
+    /// JSON of `notification`, as signed
+    pub body: String,
+
+    /// BRC-77 signature of `body` (hex)
+    pub signature: String,
+
+    /// Identity key of the signing wallet (hex)
+    pub identity_key: String,
+}
+
+impl SignedNotification {
+    /// Sign `notification` with the identity key of `key_deriver`
+    pub fn sign(notification: TransactionNotification, key_deriver: &RootKeyDeriver) -> WalletResult<Self> {
+        let body = serde_json::to_string(&notification)
+            .map_err(|e| WalletError::internal(format!("notification serialization failed: {}", e)))?;
+        let signature = signed_message::sign(body.as_bytes(), &key_deriver.root_key(), None)?;
+        Ok(Self {
+            notification,
+            body,
+            signature: hex::encode(signature),
+            identity_key: key_deriver.identity_key_hex(),
+        })
+    }
+
+    /// Whether `signature` (hex) is the wallet with `identity_key`'s
+    /// signature of `body`
+    pub fn verify(body: &str, signature: &str, identity_key: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        // The signer's key follows the 4-byte version
+        let signed_by = signature.get(4..37).map(hex::encode);
+        signed_by.as_deref() == Some(identity_key)
+            && signed_message::verify(body.as_bytes(), &signature, None).unwrap_or(false)
+    }
+}
+
+/// Receives notifications in process
+#[async_trait]
+pub trait NotificationHandler: Send + Sync {
+    /// Deliver `notification`; an error has the delivery retried
+    async fn notify(&self, notification: &SignedNotification) -> Result<(), String>;
+}
+
+struct Registration {
+    id: u64,
+    filter: NotificationFilter,
+    handler: Arc<dyn NotificationHandler>,
+}
+
+/// Registered notification handlers
+///
+/// Shared between the application, which registers handlers, and the
+/// `TaskNotify` that delivers to them.
+#[derive(Default)]
+pub struct NotificationRegistry {
+    registrations: Mutex<Vec<Registration>>,
+    next_id: AtomicU64,
+}
+
+impl NotificationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notify `handler` of status changes of transactions matching `filter`
+    ///
+    /// Returns the registration id, for `unregister`.
+    pub fn register(&self, filter: NotificationFilter, handler: Arc<dyn NotificationHandler>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.lock().push(Registration { id, filter, handler });
+        id
+    }
+
+    /// POST notifications of transactions matching `filter` to `url`
+    #[cfg(feature = "webhooks")]
+    pub fn register_url(&self, filter: NotificationFilter, url: impl Into<String>) -> u64 {
+        self.register(filter, Arc::new(WebhookHandler::new(url)))
+    }
+
+    /// Stop notifying registration `id`, including deliveries being retried
+    ///
+    /// Returns whether it was registered.
+    pub fn unregister(&self, id: u64) -> bool {
+        let mut registrations = self.lock();
+        let len = registrations.len();
+        registrations.retain(|r| r.id != id);
+        registrations.len() != len
+    }
+
+    pub fn is_registered(&self, id: u64) -> bool {
+        self.lock().iter().any(|r| r.id == id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Registration ids and handlers notified of `txid` with `labels`
+    pub(crate) fn matching(&self, txid: &str, labels: &[String]) -> Vec<(u64, Arc<dyn NotificationHandler>)> {
+        self.lock()
+            .iter()
+            .filter(|r| r.filter.matches(txid, labels))
+            .map(|r| (r.id, r.handler.clone()))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Registration>> {
+        self.registrations.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Handler POSTing each notification's JSON to a callback URL
+///
+/// The signature and identity key are sent in `SIGNATURE_HEADER` and
+/// `IDENTITY_KEY_HEADER`. Responses other than 2xx are failures.
+#[cfg(feature = "webhooks")]
+pub struct WebhookHandler {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "webhooks")]
+impl WebhookHandler {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[cfg(feature = "webhooks")]
+#[async_trait]
+impl NotificationHandler for WebhookHandler {
+    async fn notify(&self, notification: &SignedNotification) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &notification.signature)
+            .header(IDENTITY_KEY_HEADER, &notification.identity_key)
+            .body(notification.body.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(format!("{} returned {}", self.url, status)),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Ignore;
+
+    #[async_trait]
+    impl NotificationHandler for Ignore {
+        async fn notify(&self, _notification: &SignedNotification) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn notification() -> TransactionNotification {
+        TransactionNotification::from_event(
+            &WalletEvent::TransactionProgress {
+                txid: "aa".to_string(),
+                old_status: TransactionStatus::Sending,
+                new_status: TransactionStatus::Unproven,
+                provider: Some("TAAL".to_string()),
+            },
+            "2024-01-01T00:00:00+00:00".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_event() {
+        assert_eq!(
+            serde_json::to_value(notification()).unwrap(),
+            serde_json::json!({
+                "txid": "aa",
+                "oldStatus": "sending",
+                "status": "unproven",
+                "provider": "TAAL",
+                "labels": [],
+                "timestamp": "2024-01-01T00:00:00+00:00",
+            })
+        );
+
+        let retried = WalletEvent::TransactionProgress {
+            txid: "aa".to_string(),
+            old_status: TransactionStatus::Sending,
+            new_status: TransactionStatus::Sending,
+            provider: None,
+        };
+        assert_eq!(TransactionNotification::from_event(&retried, String::new()), None);
+        let received = WalletEvent::PaymentReceived { txid: "aa".to_string(), satoshis: 1 };
+        assert_eq!(TransactionNotification::from_event(&received, String::new()), None);
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let wallet = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let signed = SignedNotification::sign(notification(), &wallet).unwrap();
+        assert_eq!(signed.identity_key, wallet.identity_key_hex());
+        assert!(SignedNotification::verify(&signed.body, &signed.signature, &signed.identity_key));
+
+        let tampered = signed.body.replace("unproven", "completed");
+        assert!(!SignedNotification::verify(&tampered, &signed.signature, &signed.identity_key));
+        let other = RootKeyDeriver::new(&[2u8; 32]).unwrap().identity_key_hex();
+        assert!(!SignedNotification::verify(&signed.body, &signed.signature, &other));
+        assert!(!SignedNotification::verify(&signed.body, "zz", &signed.identity_key));
+    }
+
+    #[test]
+    fn test_registry_matching() {
+        let registry = NotificationRegistry::new();
+        let by_txid = registry.register(NotificationFilter::Txid("aa".to_string()), Arc::new(Ignore));
+        let by_label = registry.register(NotificationFilter::Label("payroll".to_string()), Arc::new(Ignore));
+        assert_eq!(registry.len(), 2);
+
+        let ids = |txid: &str, labels: &[String]| -> Vec<u64> {
+            registry.matching(txid, labels).into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(ids("aa", &[]), vec![by_txid]);
+        assert_eq!(ids("aa", &["payroll".to_string()]), vec![by_txid, by_label]);
+        assert!(ids("bb", &["rent".to_string()]).is_empty());
+
+        assert!(registry.unregister(by_txid));
+        assert!(!registry.unregister(by_txid));
+        assert!(!registry.is_registered(by_txid));
+        assert_eq!(ids("aa", &["payroll".to_string()]), vec![by_label]);
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_webhook_failure_is_an_error() {
+        let wallet = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let signed = SignedNotification::sign(notification(), &wallet).unwrap();
+        let registry = NotificationRegistry::new();
+        registry.register_url(NotificationFilter::Txid("aa".to_string()), "http://127.0.0.1:1/notify");
+        let (_, handler) = registry.matching("aa", &[]).remove(0);
+        assert!(handler.notify(&signed).await.is_err());
+    }
+}
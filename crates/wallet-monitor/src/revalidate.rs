@@ -0,0 +1,227 @@
+//! ProvenTx revalidation
+//!
+//! Re-verifies the merkle paths stored with ProvenTx records against the
+//! header chain, so long-lived wallets notice proofs that silently stopped
+//! verifying: paths saved in an outdated BUMP encoding, or proofs against
+//! blocks orphaned while the monitor was not running.
+//!
+//! Proofs that fail are re-fetched. When no new proof is available and the
+//! stored block is no longer on the active chain, the ProvenTx is rolled
+//! back as `TaskReorg` does, so `TaskCheckForProofs` proves it again.
+//!
+//! Reference: TypeScript `StorageProvider.reproveProven` / `reproveHeader`
+
+use std::ops::RangeInclusive;
+
+use wallet_core::beef::{ChainTracker, MerklePath};
+use wallet_core::events::WalletEventBus;
+use wallet_core::services::MerklePathProvider;
+use wallet_core::transaction::ByteReader;
+use wallet_storage::{
+    MonitorEvent, ProvenTxUpdates, StorageResult, TableProvenTx, TransactionStatus, WalletStorageProvider,
+};
+
+use crate::tasks::emit_status_change;
+use crate::tasks::task_check_for_proofs::validate_merkle_proof;
+
+/// Summary of a `revalidate_proven_txs` run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevalidateProvenTxsResult {
+    /// Number of ProvenTxs checked
+    pub checked: usize,
+
+    /// Number of stored proofs that still verify
+    pub valid: usize,
+
+    /// Txids whose proof was replaced by a re-fetched one
+    pub reproven: Vec<String>,
+
+    /// Txids whose block left the active chain with no new proof yet;
+    /// their transactions are back to 'unproven'
+    pub rolled_back: Vec<String>,
+
+    /// Txids whose proof does not verify, left as they are because no new
+    /// proof is available and their block is still active
+    pub unavailable: Vec<String>,
+
+    /// What was found and done, one line per proof that failed
+    pub log: String,
+}
+
+/// Why the merkle path stored with `ptx` does not verify, if it does not
+///
+/// The path must parse, be for the ProvenTx's height, compute its stored
+/// merkle root from its txid, and that root must be on the active chain.
+pub fn verify_stored_proof(ptx: &TableProvenTx, chain_tracker: &dyn ChainTracker) -> Result<(), String> {
+    let path = MerklePath::read_from(&mut ByteReader::new(&ptx.merkle_path))
+        .map_err(|e| format!("unreadable merkle path: {}", e))?;
+    if i64::from(path.block_height) != ptx.height {
+        return Err(format!("merkle path height {} does not match height {}", path.block_height, ptx.height));
+    }
+    let merkle_root = path.compute_root(Some(&ptx.txid)).map_err(|e| e.to_string())?;
+    if merkle_root != ptx.merkle_root {
+        return Err(format!("computed merkle root {} does not match stored root", merkle_root));
+    }
+    let valid = chain_tracker
+        .is_valid_root_for_height(&merkle_root, ptx.height as u32)
+        .map_err(|e| e.to_string())?;
+    if !valid {
+        return Err(format!("merkle root {} is not valid for height {}", merkle_root, ptx.height));
+    }
+    Ok(())
+}
+
+/// Re-verify the proofs of ProvenTxs mined at `heights`, replacing those
+/// that no longer verify
+///
+/// Transactions reverted to 'unproven' are reported on `event_bus`, when set.
+pub async fn revalidate_proven_txs(
+    storage: &mut dyn WalletStorageProvider,
+    services: &dyn MerklePathProvider,
+    chain_tracker: &dyn ChainTracker,
+    heights: RangeInclusive<i64>,
+    event_bus: Option<&WalletEventBus>,
+) -> StorageResult<RevalidateProvenTxsResult> {
+    let mut result = RevalidateProvenTxsResult::default();
+    let ptxs = storage.find_proven_txs_from_height(*heights.start()).await?;
+    for ptx in ptxs.into_iter().filter(|p| heights.contains(&p.height)) {
+        result.checked += 1;
+        let Err(reason) = verify_stored_proof(&ptx, chain_tracker) else {
+            result.valid += 1;
+            continue;
+        };
+
+        let refetched = match services.get_merkle_path(&ptx.txid).await {
+            Ok(lookup) => validate_merkle_proof(&ptx.txid, &lookup, chain_tracker).map(|p| (p, lookup.name)),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Ok((Some(proof), provider)) = refetched {
+            storage.update_proven_tx(ptx.proven_tx_id, &ProvenTxUpdates {
+                height: Some(proof.height),
+                index: Some(proof.index),
+                merkle_path: Some(proof.merkle_path),
+                block_hash: Some(proof.block_hash.clone()),
+                merkle_root: Some(proof.merkle_root),
+            }).await?;
+            let event = MonitorEvent::ProofReplaced {
+                txid: ptx.txid.clone(),
+                proven_tx_id: ptx.proven_tx_id,
+                old_block_hash: ptx.block_hash,
+                height: proof.height,
+                block_hash: proof.block_hash,
+            };
+            storage.insert_monitor_event(&event.to_table()).await?;
+            result.log.push_str(&format!(
+                "txid {} {}; replaced with proof at height {} from {}\n",
+                ptx.txid, reason, proof.height, provider.as_deref().unwrap_or("unknown service")
+            ));
+            result.reproven.push(ptx.txid);
+            continue;
+        }
+
+        let no_proof = match refetched {
+            Err(e) => format!("new proof invalid: {}", e),
+            _ => "no new proof available".to_string(),
+        };
+        let orphaned = !chain_tracker.is_valid_root_for_height(&ptx.merkle_root, ptx.height as u32).unwrap_or(true);
+        if !orphaned {
+            result.log.push_str(&format!("txid {} {}; {}, left unchanged\n", ptx.txid, reason, no_proof));
+            result.unavailable.push(ptx.txid);
+            continue;
+        }
+
+        let reverted = storage.rollback_proven_tx(ptx.proven_tx_id).await?;
+        result.log.push_str(&format!(
+            "txid {} {}; {}, block {} is no longer active so transactionIds {:?} reverted to 'unproven'\n",
+            ptx.txid, reason, no_proof, ptx.block_hash, reverted
+        ));
+        if !reverted.is_empty() {
+            emit_status_change(event_bus, &ptx.txid, TransactionStatus::Unproven);
+        }
+        let event = MonitorEvent::Reorg {
+            txid: ptx.txid.clone(),
+            block_hash: ptx.block_hash,
+            height: ptx.height,
+            reverted_transaction_ids: reverted,
+        };
+        storage.insert_monitor_event(&event.to_table()).await?;
+        result.rolled_back.push(ptx.txid);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::services::{BlockHeader, GetMerklePathResult};
+    use wallet_storage::{ProvenTxReqStatus, TableProvenTxReq, TableTransaction};
+    use wallet_test_utils::fixtures::single_tx_merkle_path;
+    use wallet_test_utils::{MockChainTracker, MockMerklePathProvider, MockStorage};
+
+    fn txid(id: i64) -> String {
+        format!("{:02x}", id).repeat(32)
+    }
+
+    /// ProvenTx `id` mined alone in its block at `height`, stored with `merkle_path`
+    fn proven(storage: &mut MockStorage, id: i64, height: i64, merkle_path: Vec<u8>) {
+        let txid = txid(id);
+        let block_hash = format!("block{}", height);
+        storage.proven_txs.push(TableProvenTx::new(id, &txid, height, 0, merkle_path, vec![], block_hash, &txid));
+        storage.reqs.push(
+            TableProvenTxReq::new(id, ProvenTxReqStatus::Completed, &txid, "{}", "{}", vec![]).with_proven_tx_id(id),
+        );
+        let mut tx = TableTransaction::new(id, 1, TransactionStatus::Completed, &txid, true, 0, &txid);
+        tx.proven_tx_id = Some(id);
+        storage.transactions.push(tx);
+    }
+
+    fn path(id: i64, height: u32) -> Vec<u8> {
+        single_tx_merkle_path(&txid(id), height).to_binary().unwrap()
+    }
+
+    fn mined(id: i64, height: u32) -> GetMerklePathResult {
+        GetMerklePathResult {
+            name: Some("mock".to_string()),
+            merkle_path: Some(single_tx_merkle_path(&txid(id), height)),
+            header: Some(BlockHeader { height, hash: format!("block{}", height), merkle_root: txid(id) }),
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revalidate_proven_txs() {
+        let mut storage = MockStorage::new();
+        proven(&mut storage, 1, 100, path(1, 100)); // valid
+        proven(&mut storage, 2, 101, vec![0xff]); // stale encoding, re-fetched
+        proven(&mut storage, 3, 102, path(3, 102)); // orphaned, not yet re-mined
+        proven(&mut storage, 4, 103, vec![0xff]); // stale encoding, no new proof
+        proven(&mut storage, 5, 200, vec![0xff]); // outside the range
+
+        let chain = MockChainTracker::new()
+            .with_root(100, txid(1))
+            .with_root(101, txid(2))
+            .with_root(103, txid(4));
+        let services = MockMerklePathProvider::new().with_result(txid(2), mined(2, 101));
+        let bus = WalletEventBus::default();
+        let mut events = bus.subscribe();
+
+        let result = revalidate_proven_txs(&mut storage, &services, &chain, 100..=150, Some(&bus)).await.unwrap();
+        assert_eq!((result.checked, result.valid), (4, 1));
+        assert_eq!(result.reproven, vec![txid(2)]);
+        assert_eq!(result.rolled_back, vec![txid(3)]);
+        assert_eq!(result.unavailable, vec![txid(4)]);
+        assert!(result.log.contains("unreadable merkle path"));
+
+        assert_eq!(storage.proven_txs[1].merkle_path, path(2, 101));
+        assert!(verify_stored_proof(&storage.proven_txs[1], &chain).is_ok());
+        assert_eq!(storage.transaction(3).status, TransactionStatus::Unproven);
+        assert_eq!(storage.proven_txs.len(), 4);
+        assert_eq!(storage.proven_txs[3].merkle_path, vec![0xff]);
+
+        let recorded: Vec<MonitorEvent> = storage.events.iter().map(MonitorEvent::from_table).collect();
+        assert!(matches!(&recorded[0], MonitorEvent::ProofReplaced { txid: replaced, .. } if *replaced == txid(2)));
+        assert!(matches!(&recorded[1], MonitorEvent::Reorg { reverted_transaction_ids, .. } if *reverted_transaction_ids == vec![3]));
+        assert!(events.try_recv().is_ok());
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -1 +1,132 @@
-// Placeholder tasks module; populate with specific monitor tasks during translation
+//! Monitor tasks
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks
+
+use async_trait::async_trait;
+use tracing::{info, instrument};
+use wallet_core::events::{WalletEvent, WalletEventBus};
+use wallet_storage::{
+    OutputUpdates, StorageError, StorageResult, TableMonitorEvent, TableTransaction, TransactionStatus,
+    WalletStorageProvider,
+};
+
+pub mod task_check_for_proofs;
+pub mod task_consolidate_outputs;
+pub mod task_fail_abandoned;
+pub mod task_notify;
+pub mod task_purge;
+pub mod task_reorg;
+pub mod task_review_status;
+pub mod task_send_waiting;
+
+pub use task_check_for_proofs::TaskCheckForProofs;
+pub use task_consolidate_outputs::TaskConsolidateOutputs;
+pub use task_fail_abandoned::TaskFailAbandoned;
+pub use task_notify::TaskNotify;
+pub use task_purge::TaskPurge;
+pub use task_reorg::{ReorgQueue, TaskReorg};
+pub use task_review_status::TaskReviewStatus;
+pub use task_send_waiting::TaskSendWaiting;
+
+/// A periodic task run by the monitor
+///
+/// Reference: TypeScript `WalletMonitorTask`
+#[async_trait]
+pub trait MonitorTask: Send {
+    /// Task name, used in logs
+    fn name(&self) -> &'static str;
+
+    /// Whether the task should run at `now_msecs` (milliseconds since epoch)
+    fn trigger(&mut self, now_msecs: i64) -> bool;
+
+    /// Run the task once, returning a log of what was done
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String>;
+}
+
+/// RFC 3339 timestamp for `msecs` milliseconds since epoch
+pub(crate) fn msecs_to_rfc3339(msecs: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(msecs)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+/// Mark a transaction failed and release the outputs it spends
+///
+/// Returns the number of outputs made spendable again, or `None` when the
+/// transaction was updated after `tx` was read, e.g. by its owner signing
+/// it; it is then left as it is.
+///
+/// Reference: TypeScript `StorageProvider.updateTransactionStatus(status: 'failed')`
+#[instrument(skip_all, fields(transaction_id = tx.transaction_id, reference = %tx.reference, txid = tx.txid.as_deref()))]
+pub(crate) async fn fail_transaction(
+    storage: &mut dyn WalletStorageProvider,
+    tx: &TableTransaction,
+) -> StorageResult<Option<usize>> {
+    match storage
+        .update_transaction_status_if_unchanged(tx.transaction_id, TransactionStatus::Failed, &tx.updated_at)
+        .await
+    {
+        Err(StorageError::Conflict(e)) => {
+            info!(conflict = %e, "transaction updated since it was read; left as it is");
+            return Ok(None);
+        }
+        result => result?,
+    }
+
+    let inputs = storage.find_outputs_by_transaction(tx.user_id, tx.transaction_id, true).await?;
+    for output in &inputs {
+        storage.update_output(output.output_id, &OutputUpdates {
+            spendable: Some(true),
+            spent_by: Some(None),
+            ..Default::default()
+        }).await?;
+    }
+    info!(released_inputs = inputs.len(), "failed transaction");
+    Ok(Some(inputs.len()))
+}
+
+/// Notify `bus`, when set, that the transaction `txid` is now `status`
+pub(crate) fn emit_status_change(bus: Option<&WalletEventBus>, txid: &str, status: TransactionStatus) {
+    if let Some(bus) = bus {
+        bus.emit(WalletEvent::TransactionStatusChanged { txid: txid.to_string(), status });
+    }
+}
+
+/// Notify `bus`, when set, that a broadcast or proof moved `txid` from
+/// `old_status` to `new_status`
+pub(crate) fn emit_progress(
+    bus: Option<&WalletEventBus>,
+    txid: &str,
+    old_status: TransactionStatus,
+    new_status: TransactionStatus,
+    provider: Option<String>,
+) {
+    if let Some(bus) = bus {
+        bus.emit(WalletEvent::TransactionProgress {
+            txid: txid.to_string(),
+            old_status,
+            new_status,
+            provider,
+        });
+    }
+}
+
+/// Record an automated monitor action
+pub(crate) async fn log_monitor_event(
+    storage: &mut dyn WalletStorageProvider,
+    event: &str,
+    details: String,
+) -> StorageResult<i64> {
+    storage.insert_monitor_event(&TableMonitorEvent::new(0, event).with_details(details)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msecs_to_rfc3339() {
+        assert_eq!(msecs_to_rfc3339(0), "1970-01-01T00:00:00+00:00");
+        assert_eq!(msecs_to_rfc3339(1_000), "1970-01-01T00:00:01+00:00");
+    }
+}
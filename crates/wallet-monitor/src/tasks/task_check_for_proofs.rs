@@ -0,0 +1,348 @@
+//! TaskCheckForProofs
+//!
+//! Acquires merkle proofs for broadcast transactions and promotes their
+//! ProvenTxReqs to ProvenTx records.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskCheckForProofs.ts
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::instrument;
+use wallet_core::beef::ChainTracker;
+use wallet_core::events::WalletEventBus;
+use wallet_core::services::{GetMerklePathResult, MerklePathProvider};
+use wallet_storage::{
+    metrics, FindProvenTxReqsArgs, MonitorEvent, Paged, ProvenTxReqStatus, ProvenTxReqUpdates, StorageResult,
+    TableProvenTxReq, TransactionStatus, UpdateProvenTxReqWithNewProvenTxArgs, WalletStorageProvider,
+};
+
+use super::{emit_progress, MonitorTask};
+
+/// Maximum number of requests checked per status on each run
+const MAX_REQS_PER_RUN: u32 = 100;
+
+/// A merkle proof validated against the header chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidMerkleProof {
+    pub height: i64,
+    pub index: i64,
+    pub block_hash: String,
+    pub merkle_root: String,
+    /// Serialized merkle path (BRC-74)
+    pub merkle_path: Vec<u8>,
+}
+
+/// Validate a merkle path lookup for `txid`
+///
+/// Returns `Ok(None)` when the service has no proof yet (not mined) and
+/// `Err` with a reason when the proof does not match the header chain.
+///
+/// Reference: TypeScript TaskCheckForProofs.ts (`getProofs`)
+pub fn validate_merkle_proof(
+    txid: &str,
+    result: &GetMerklePathResult,
+    chain_tracker: &dyn ChainTracker,
+) -> Result<Option<ValidMerkleProof>, String> {
+    let Some(merkle_path) = &result.merkle_path else {
+        return Ok(None);
+    };
+    let header = result.header.as_ref()
+        .ok_or_else(|| "merkle path returned without block header".to_string())?;
+
+    if merkle_path.block_height != header.height {
+        return Err(format!(
+            "merkle path height {} does not match header height {}",
+            merkle_path.block_height, header.height
+        ));
+    }
+
+    let merkle_root = merkle_path.compute_root(Some(txid)).map_err(|e| e.to_string())?;
+    if merkle_root != header.merkle_root {
+        return Err(format!("computed merkle root {} does not match header", merkle_root));
+    }
+
+    let valid = chain_tracker
+        .is_valid_root_for_height(&merkle_root, header.height)
+        .map_err(|e| e.to_string())?;
+    if !valid {
+        return Err(format!(
+            "merkle root {} is not valid for height {}",
+            merkle_root, header.height
+        ));
+    }
+
+    let index = merkle_path.path.first()
+        .and_then(|level| level.iter().find(|n| n.hash == txid))
+        .and_then(|n| n.offset)
+        .ok_or_else(|| "txid not found in merkle path".to_string())?;
+
+    Ok(Some(ValidMerkleProof {
+        height: header.height as i64,
+        index: index as i64,
+        block_hash: header.hash.clone(),
+        merkle_root,
+        merkle_path: merkle_path.to_binary().map_err(|e| e.to_string())?,
+    }))
+}
+
+/// Monitor task that checks 'unmined' and 'callback' requests for proofs
+///
+/// Reference: TypeScript `TaskCheckForProofs`
+pub struct TaskCheckForProofs {
+    services: Arc<dyn MerklePathProvider>,
+    chain_tracker: Arc<dyn ChainTracker>,
+
+    /// Milliseconds between periodic runs; 0 runs only when `check_now` is set
+    pub trigger_msecs: i64,
+
+    /// Run on the next trigger, e.g. when a new block has been found.
+    /// Failed lookups on such runs count as attempts.
+    pub check_now: bool,
+
+    event_bus: Option<WalletEventBus>,
+
+    last_run_msecs: i64,
+}
+
+impl TaskCheckForProofs {
+    pub fn new(
+        services: Arc<dyn MerklePathProvider>,
+        chain_tracker: Arc<dyn ChainTracker>,
+        trigger_msecs: i64,
+    ) -> Self {
+        Self {
+            services,
+            chain_tracker,
+            trigger_msecs,
+            check_now: false,
+            event_bus: None,
+            last_run_msecs: 0,
+        }
+    }
+
+    /// Report transactions completed by new proofs, with the service that
+    /// found them, on `event_bus`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Look for a proof for one request and promote it if found
+    #[instrument(level = "debug", skip_all, fields(txid = %req.txid))]
+    async fn get_proof(
+        &self,
+        storage: &mut dyn WalletStorageProvider,
+        req: &TableProvenTxReq,
+        counts_as_attempt: bool,
+    ) -> StorageResult<String> {
+        if req.proven_tx_id.is_some() {
+            return Ok(format!("{} already proven\n", req.txid));
+        }
+
+        let result = self.services.get_merkle_path(&req.txid).await?;
+        match validate_merkle_proof(&req.txid, &result, self.chain_tracker.as_ref()) {
+            Ok(Some(proof)) => {
+                let r = storage.update_proven_tx_req_with_new_proven_tx(&UpdateProvenTxReqWithNewProvenTxArgs {
+                    proven_tx_req_id: req.proven_tx_req_id,
+                    txid: req.txid.clone(),
+                    attempts: req.attempts,
+                    status: ProvenTxReqStatus::Completed,
+                    history: req.history.clone(),
+                    height: proof.height,
+                    index: proof.index,
+                    block_hash: proof.block_hash,
+                    merkle_root: proof.merkle_root,
+                    merkle_path: proof.merkle_path,
+                }).await?;
+                storage.insert_monitor_event(&MonitorEvent::ProofFound {
+                    txid: req.txid.clone(),
+                    height: proof.height,
+                    proven_tx_id: r.proven_tx_id,
+                }.to_table()).await?;
+                metrics::inc(metrics::PROOFS_FOUND, &[]);
+                emit_progress(
+                    self.event_bus.as_ref(),
+                    &req.txid,
+                    TransactionStatus::Unproven,
+                    TransactionStatus::Completed,
+                    result.name.clone(),
+                );
+                Ok(format!("{} proven at height {} (provenTxId {})\n", req.txid, proof.height, r.proven_tx_id))
+            }
+            outcome => {
+                if counts_as_attempt {
+                    storage.update_proven_tx_req(req.proven_tx_req_id, &ProvenTxReqUpdates {
+                        attempts: Some(req.attempts + 1),
+                        ..Default::default()
+                    }).await?;
+                }
+                Ok(match outcome {
+                    Err(reason) => format!("{} invalid proof: {}\n", req.txid, reason),
+                    _ => format!("{} not mined yet\n", req.txid),
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskCheckForProofs {
+    fn name(&self) -> &'static str {
+        "CheckForProofs"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = self.check_now
+            || (self.trigger_msecs > 0 && now_msecs - self.last_run_msecs > self.trigger_msecs);
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let counts_as_attempt = self.check_now;
+        self.check_now = false;
+
+        let mut log = String::new();
+        for status in [ProvenTxReqStatus::Unmined, ProvenTxReqStatus::Callback] {
+            let reqs = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+                status: Some(status),
+                since: None,
+                paged: Some(Paged::new(MAX_REQS_PER_RUN)),
+                txids: None,
+            }).await?;
+            for req in &reqs {
+                log.push_str(&self.get_proof(storage, req, counts_as_attempt).await?);
+            }
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::events::WalletEvent;
+    use wallet_core::beef::{MerklePath, MerklePathNode};
+    use wallet_core::services::BlockHeader;
+    use wallet_test_utils::{MockChainTracker, MockMerklePathProvider, MockStorage};
+
+    fn txid() -> String {
+        "aa".repeat(32)
+    }
+
+    /// Single-transaction block: the merkle root is the txid
+    fn mined_result(height: u32, merkle_root: &str) -> GetMerklePathResult {
+        GetMerklePathResult {
+            name: Some("mock".to_string()),
+            merkle_path: Some(MerklePath {
+                block_height: 850_000,
+                path: vec![vec![MerklePathNode {
+                    hash: txid(),
+                    offset: Some(0),
+                    duplicate: false,
+                    txid: true,
+                }]],
+            }),
+            header: Some(BlockHeader {
+                height,
+                hash: "bb".repeat(32),
+                merkle_root: merkle_root.to_string(),
+            }),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_merkle_proof_valid() {
+        let tracker = MockChainTracker::accepting();
+        let proof = validate_merkle_proof(&txid(), &mined_result(850_000, &txid()), &tracker)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proof.height, 850_000);
+        assert_eq!(proof.index, 0);
+        assert_eq!(proof.merkle_root, txid());
+        assert_eq!(proof.block_hash, "bb".repeat(32));
+        assert!(!proof.merkle_path.is_empty());
+    }
+
+    #[test]
+    fn test_validate_merkle_proof_not_mined() {
+        let tracker = MockChainTracker::accepting();
+        let r = validate_merkle_proof(&txid(), &GetMerklePathResult::default(), &tracker);
+        assert_eq!(r, Ok(None));
+    }
+
+    #[test]
+    fn test_validate_merkle_proof_rejects_mismatches() {
+        let tracker = MockChainTracker::accepting();
+        // Header root doesn't match computed root
+        assert!(validate_merkle_proof(&txid(), &mined_result(850_000, &"cc".repeat(32)), &tracker).is_err());
+        // Header height doesn't match path height
+        assert!(validate_merkle_proof(&txid(), &mined_result(1, &txid()), &tracker).is_err());
+        // Root not on the active chain
+        let stale = MockChainTracker::rejecting();
+        assert!(validate_merkle_proof(&txid(), &mined_result(850_000, &txid()), &stale).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_proof_records_proof_found() {
+        let mut storage = MockStorage::new();
+        storage.reqs.push(TableProvenTxReq::new(1, ProvenTxReqStatus::Unmined, txid(), "{}", "{}", vec![1]));
+        let bus = WalletEventBus::default();
+        let mut events = bus.subscribe();
+        let task = TaskCheckForProofs::new(
+            Arc::new(MockMerklePathProvider::new().with_result(txid(), mined_result(850_000, &txid()))),
+            Arc::new(MockChainTracker::accepting()),
+            1000,
+        )
+        .with_event_bus(bus);
+
+        let req = storage.reqs[0].clone();
+        let log = task.get_proof(&mut storage, &req, true).await.unwrap();
+        assert!(log.contains("proven at height 850000"));
+        assert_eq!(storage.events.len(), 1);
+        assert_eq!(
+            MonitorEvent::from_table(&storage.events[0]),
+            MonitorEvent::ProofFound { txid: txid(), height: 850_000, proven_tx_id: 1 }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            WalletEvent::TransactionProgress {
+                txid: txid(),
+                old_status: TransactionStatus::Unproven,
+                new_status: TransactionStatus::Completed,
+                provider: Some("mock".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut task = TaskCheckForProofs::new(
+            Arc::new(MockMerklePathProvider::new()),
+            Arc::new(MockChainTracker::accepting()),
+            1000,
+        );
+        assert!(task.trigger(2000));
+        assert!(!task.trigger(2500));
+        assert!(task.trigger(3001));
+
+        task.check_now = true;
+        assert!(task.trigger(3002));
+    }
+
+    #[test]
+    fn test_trigger_disabled_without_check_now() {
+        let mut task = TaskCheckForProofs::new(
+            Arc::new(MockMerklePathProvider::new()),
+            Arc::new(MockChainTracker::accepting()),
+            0,
+        );
+        assert!(!task.trigger(i64::MAX));
+        task.check_now = true;
+        assert!(task.trigger(0));
+    }
+}
@@ -0,0 +1,125 @@
+//! TaskConsolidateOutputs
+//!
+//! Periodically merges dust change of a wallet's `default` basket into fewer,
+//! larger outputs.
+//!
+//! There is no TypeScript counterpart; see `wallet_core::signer::methods::consolidate_outputs`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use wallet_core::keys::{KeyPair, RootKeyDeriver};
+use wallet_core::services::Broadcaster;
+use wallet_core::signer::methods::{consolidate_outputs, ValidConsolidateOutputsArgs};
+use wallet_storage::{AuthId, StorageError, StorageResult, WalletStorageProvider};
+
+use super::MonitorTask;
+
+/// Default interval between consolidations (one hour)
+pub const DEFAULT_CONSOLIDATE_MSECS: i64 = 1000 * 60 * 60;
+
+/// Monitor task that consolidates one wallet's change outputs
+///
+/// Unlike the other tasks it signs transactions, so it holds the wallet's
+/// root key. Each run spends small change outputs into change sized by the
+/// basket's UTXO policy, within the fee limit of `args`.
+pub struct TaskConsolidateOutputs {
+    /// Milliseconds between runs
+    pub trigger_msecs: i64,
+
+    /// Consolidation limits
+    pub args: ValidConsolidateOutputsArgs,
+
+    deriver: Arc<RootKeyDeriver>,
+    broadcaster: Option<Arc<dyn Broadcaster>>,
+    last_run_msecs: i64,
+}
+
+impl TaskConsolidateOutputs {
+    pub fn new(
+        deriver: Arc<RootKeyDeriver>,
+        broadcaster: Option<Arc<dyn Broadcaster>>,
+        args: ValidConsolidateOutputsArgs,
+    ) -> Self {
+        Self {
+            trigger_msecs: DEFAULT_CONSOLIDATE_MSECS,
+            args,
+            deriver,
+            broadcaster,
+            last_run_msecs: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskConsolidateOutputs {
+    fn name(&self) -> &'static str {
+        "ConsolidateOutputs"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = now_msecs - self.last_run_msecs > self.trigger_msecs;
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let identity_key = self.deriver.identity_key_hex();
+        let user = storage.find_or_insert_user(&identity_key).await?.user;
+        let auth = AuthId::new(identity_key).with_user_id(user.user_id);
+        let change_keys = KeyPair {
+            private_key: self.deriver.root_key().to_vec(),
+            public_key: self.deriver.identity_key().to_vec(),
+        };
+
+        let r = consolidate_outputs(storage, self.broadcaster.as_deref(), None, &auth, &change_keys, self.args.clone())
+            .await
+            .map_err(|e| StorageError::Database(format!("consolidation failed: {}", e)))?;
+        Ok(match r.txid {
+            Some(txid) => format!(
+                "txid {} consolidated {} change outputs ({} sats) into {}, fee {}\n",
+                txid, r.outputs_spent, r.satoshis, r.outputs_created, r.fee
+            ),
+            None => String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::signer::methods::{validate_consolidate_outputs_args, ConsolidateOutputsArgs};
+    use wallet_storage::{StorageProvidedBy, TableOutput};
+    use wallet_test_utils::MockStorage;
+
+    fn task() -> TaskConsolidateOutputs {
+        let deriver = Arc::new(RootKeyDeriver::new(&[3u8; 32]).unwrap());
+        let args = validate_consolidate_outputs_args(&ConsolidateOutputsArgs::default()).unwrap();
+        TaskConsolidateOutputs::new(deriver, None, args)
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut task = task();
+        task.trigger_msecs = 1000;
+        assert!(task.trigger(1001));
+        assert!(!task.trigger(2000));
+        assert!(task.trigger(2002));
+    }
+
+    #[tokio::test]
+    async fn test_run_task_skips_single_small_output() {
+        let mut storage = MockStorage::new();
+        let mut task = task();
+        let basket = storage.find_or_insert_output_basket(1, "default").await.unwrap();
+        let mut dust = TableOutput::new(10, 1, 1, true, true, "change", 0, 500, StorageProvidedBy::Storage, "change", "P2PKH");
+        dust.basket_id = Some(basket.basket_id);
+        storage.outputs.push(dust);
+
+        assert_eq!(task.run_task(&mut storage).await.unwrap(), "");
+        assert!(storage.outputs[0].spendable);
+        assert!(storage.transactions.is_empty());
+    }
+}
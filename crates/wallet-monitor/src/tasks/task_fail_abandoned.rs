@@ -0,0 +1,176 @@
+//! TaskFailAbandoned
+//!
+//! Fails transactions that were created but never completed by their owner.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskFailAbandoned.ts
+
+use async_trait::async_trait;
+use wallet_core::events::WalletEventBus;
+use wallet_storage::{StorageResult, TransactionStatus, WalletStorageProvider};
+
+use super::{emit_status_change, fail_transaction, log_monitor_event, msecs_to_rfc3339, MonitorTask};
+
+/// Default age after which a stuck transaction is abandoned (5 minutes)
+pub const DEFAULT_ABANDONED_MSECS: i64 = 1000 * 60 * 5;
+
+/// Monitor task that fails transactions stuck in an unfinished status
+///
+/// Transactions in one of `statuses` that have not been updated for
+/// `abandoned_msecs` are marked 'failed', the outputs they spend are released,
+/// and a monitor event is recorded for each.
+///
+/// Reference: TypeScript `TaskFailAbandoned`
+pub struct TaskFailAbandoned {
+    /// Milliseconds between runs
+    pub trigger_msecs: i64,
+
+    /// Age after which a transaction is considered abandoned
+    pub abandoned_msecs: i64,
+
+    /// Statuses considered unfinished
+    pub statuses: Vec<TransactionStatus>,
+
+    event_bus: Option<WalletEventBus>,
+
+    last_run_msecs: i64,
+}
+
+impl TaskFailAbandoned {
+    pub fn new(trigger_msecs: i64, abandoned_msecs: i64) -> Self {
+        Self {
+            trigger_msecs,
+            abandoned_msecs,
+            statuses: vec![
+                TransactionStatus::Unsigned,
+                TransactionStatus::Nosend,
+                TransactionStatus::Unprocessed,
+            ],
+            event_bus: None,
+            last_run_msecs: 0,
+        }
+    }
+
+    /// Report failed transactions that have a txid on `event_bus`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+}
+
+impl Default for TaskFailAbandoned {
+    fn default() -> Self {
+        Self::new(DEFAULT_ABANDONED_MSECS, DEFAULT_ABANDONED_MSECS)
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskFailAbandoned {
+    fn name(&self) -> &'static str {
+        "FailAbandoned"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = now_msecs - self.last_run_msecs > self.trigger_msecs;
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let updated_before = msecs_to_rfc3339(self.last_run_msecs - self.abandoned_msecs);
+        let txs = storage.find_aged_transactions(&self.statuses, &updated_before).await?;
+
+        let mut log = String::new();
+        for tx in &txs {
+            let Some(released) = fail_transaction(storage, tx).await? else {
+                continue;
+            };
+            if let Some(txid) = &tx.txid {
+                emit_status_change(self.event_bus.as_ref(), txid, TransactionStatus::Failed);
+            }
+            let details = format!(
+                "transactionId {} ({}) abandoned in status '{}' since {}; released {} inputs",
+                tx.transaction_id, tx.reference, tx.status, tx.updated_at, released
+            );
+            log_monitor_event(storage, self.name(), details.clone()).await?;
+            log.push_str(&details);
+            log.push('\n');
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_storage::{StorageProvidedBy, TableOutput, TableTransaction};
+    use wallet_test_utils::MockStorage;
+
+    #[test]
+    fn test_default_statuses() {
+        // TS Reference: TaskFailAbandoned fails 'unsigned' and 'unprocessed' transactions
+        let task = TaskFailAbandoned::default();
+        assert_eq!(task.abandoned_msecs, DEFAULT_ABANDONED_MSECS);
+        assert!(task.statuses.contains(&TransactionStatus::Unsigned));
+        assert!(task.statuses.contains(&TransactionStatus::Unprocessed));
+        assert!(task.statuses.contains(&TransactionStatus::Nosend));
+        assert!(!task.statuses.contains(&TransactionStatus::Sending));
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut task = TaskFailAbandoned::new(1000, 5000);
+        assert!(task.trigger(1001));
+        assert!(!task.trigger(2000));
+        assert!(task.trigger(2002));
+    }
+
+    #[tokio::test]
+    async fn test_run_task_fails_aged_transactions() {
+        let mut storage = MockStorage::new();
+        let mut stale = TableTransaction::new(1, 1, TransactionStatus::Unsigned, "stale", true, 0, "stale");
+        stale.updated_at = "2000-01-01T00:00:00+00:00".to_string();
+        storage.transactions.push(stale);
+        storage.transactions.push(TableTransaction::new(2, 1, TransactionStatus::Unsigned, "fresh", true, 0, "fresh"));
+
+        let mut input = TableOutput::new(10, 1, 99, false, true, "change", 0, 1000, StorageProvidedBy::Storage, "change", "P2PKH");
+        input.mark_spent(1, None, None);
+        storage.outputs.push(input);
+
+        let mut task = TaskFailAbandoned::default();
+        assert!(task.trigger(msecs_now()));
+        let log = task.run_task(&mut storage).await.unwrap();
+
+        assert!(log.contains("transactionId 1"));
+        assert_eq!(storage.transaction(1).status, TransactionStatus::Failed);
+        assert_eq!(storage.transaction(2).status, TransactionStatus::Unsigned);
+        assert!(storage.outputs[0].spendable);
+        assert_eq!(storage.outputs[0].spent_by, None);
+        assert!(storage.find_outputs_by_transaction(1, 1, true).await.unwrap().is_empty());
+        assert_eq!(storage.events.len(), 1);
+        assert_eq!(storage.events[0].event, "FailAbandoned");
+    }
+
+    #[tokio::test]
+    async fn test_fail_transaction_skips_updated_transaction() {
+        let mut storage = MockStorage::new();
+        let mut tx = TableTransaction::new(1, 1, TransactionStatus::Unsigned, "ref1", true, 0, "test");
+        tx.updated_at = "2000-01-01T00:00:00+00:00".to_string();
+        storage.transactions.push(tx.clone());
+
+        // Signed by its owner after the monitor read it
+        storage.update_transaction_status(1, TransactionStatus::Sending).await.unwrap();
+
+        assert_eq!(fail_transaction(&mut storage, &tx).await.unwrap(), None);
+        assert_eq!(storage.transaction(1).status, TransactionStatus::Sending);
+
+        let tx = storage.transaction(1).clone();
+        assert_eq!(fail_transaction(&mut storage, &tx).await.unwrap(), Some(0));
+        assert_eq!(storage.transaction(1).status, TransactionStatus::Failed);
+    }
+
+    fn msecs_now() -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
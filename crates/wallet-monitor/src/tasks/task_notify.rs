@@ -0,0 +1,337 @@
+//! TaskNotify
+//!
+//! Delivers transaction status changes reported on the event bus to the
+//! handlers applications registered in a `NotificationRegistry`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tracing::warn;
+use wallet_core::events::{WalletEvent, WalletEventBus};
+use wallet_core::keys::RootKeyDeriver;
+use wallet_storage::{FindProvenTxReqsArgs, StorageResult, WalletStorageProvider};
+
+use super::{msecs_to_rfc3339, MonitorTask};
+use crate::notifications::{NotificationHandler, NotificationRegistry, SignedNotification, TransactionNotification};
+
+/// Default delay before retrying a failed delivery (10 seconds), doubled
+/// after each further failure
+pub const DEFAULT_NOTIFY_RETRY_MSECS: i64 = 1000 * 10;
+
+/// Default number of attempts before a delivery is given up
+pub const DEFAULT_NOTIFY_MAX_ATTEMPTS: u32 = 5;
+
+/// One notification for one registered handler
+struct Delivery {
+    registration_id: u64,
+    handler: Arc<dyn NotificationHandler>,
+    notification: Arc<SignedNotification>,
+    attempts: u32,
+    next_attempt_msecs: i64,
+}
+
+/// Monitor task that notifies registered handlers of status changes
+///
+/// Status changes are read from the event bus, so the wallet and the other
+/// monitor tasks must report on the same bus (see `Monitor::event_bus`).
+/// Each change is signed with the wallet's identity key, labelled with the
+/// transaction's labels, and delivered to every handler whose filter
+/// matches. Failed deliveries are retried with exponential backoff until
+/// `max_attempts`, or until their handler is unregistered.
+pub struct TaskNotify {
+    registry: Arc<NotificationRegistry>,
+    key_deriver: RootKeyDeriver,
+    events: broadcast::Receiver<WalletEvent>,
+
+    /// Delay before the first retry of a failed delivery
+    pub retry_msecs: i64,
+
+    /// Attempts after which a delivery is given up
+    pub max_attempts: u32,
+
+    pending: Vec<Delivery>,
+    last_run_msecs: i64,
+}
+
+impl TaskNotify {
+    /// Deliver changes reported on `event_bus` from now on, signed by `key_deriver`
+    pub fn new(registry: Arc<NotificationRegistry>, key_deriver: RootKeyDeriver, event_bus: &WalletEventBus) -> Self {
+        Self {
+            registry,
+            key_deriver,
+            events: event_bus.subscribe(),
+            retry_msecs: DEFAULT_NOTIFY_RETRY_MSECS,
+            max_attempts: DEFAULT_NOTIFY_MAX_ATTEMPTS,
+            pending: Vec::new(),
+            last_run_msecs: 0,
+        }
+    }
+
+    /// Deliveries waiting to be retried
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Queue a delivery of `notification` to each matching handler
+    async fn queue(
+        &mut self,
+        storage: &mut dyn WalletStorageProvider,
+        mut notification: TransactionNotification,
+        log: &mut String,
+    ) -> StorageResult<()> {
+        if self.registry.is_empty() {
+            return Ok(());
+        }
+        notification.labels = labels_of(storage, &notification.txid).await?;
+        let handlers = self.registry.matching(&notification.txid, &notification.labels);
+        if handlers.is_empty() {
+            return Ok(());
+        }
+
+        let txid = notification.txid.clone();
+        let signed = match SignedNotification::sign(notification, &self.key_deriver) {
+            Ok(signed) => Arc::new(signed),
+            Err(e) => {
+                warn!(%txid, error = %e, "cannot sign notification");
+                log.push_str(&format!("{} not signed: {}\n", txid, e));
+                return Ok(());
+            }
+        };
+        self.pending.extend(handlers.into_iter().map(|(registration_id, handler)| Delivery {
+            registration_id,
+            handler,
+            notification: signed.clone(),
+            attempts: 0,
+            next_attempt_msecs: self.last_run_msecs,
+        }));
+        Ok(())
+    }
+
+    /// Attempt each due delivery, keeping failures to retry
+    async fn deliver(&mut self, log: &mut String) {
+        let now_msecs = self.last_run_msecs;
+        let mut retry = Vec::new();
+        for mut delivery in std::mem::take(&mut self.pending) {
+            if !self.registry.is_registered(delivery.registration_id) {
+                continue;
+            }
+            if delivery.next_attempt_msecs > now_msecs {
+                retry.push(delivery);
+                continue;
+            }
+            let txid = &delivery.notification.notification.txid;
+            let status = delivery.notification.notification.status;
+            match delivery.handler.notify(&delivery.notification).await {
+                Ok(()) => log.push_str(&format!("{} {} notified\n", txid, status)),
+                Err(e) => {
+                    delivery.attempts += 1;
+                    warn!(%txid, attempts = delivery.attempts, error = %e, "notification failed");
+                    if delivery.attempts >= self.max_attempts {
+                        log.push_str(&format!("{} {} notification given up: {}\n", txid, status, e));
+                    } else {
+                        let backoff = self.retry_msecs.saturating_mul(1 << (delivery.attempts - 1).min(20));
+                        delivery.next_attempt_msecs = now_msecs.saturating_add(backoff);
+                        retry.push(delivery);
+                    }
+                }
+            }
+        }
+        self.pending = retry;
+    }
+}
+
+/// Labels of the transactions notified by the request for `txid`
+async fn labels_of(storage: &mut dyn WalletStorageProvider, txid: &str) -> StorageResult<Vec<String>> {
+    let reqs = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+        status: None,
+        since: None,
+        paged: None,
+        txids: Some(vec![txid.to_string()]),
+    }).await?;
+    let mut labels = Vec::new();
+    for transaction_id in reqs.iter().flat_map(|req| req.notify_transaction_ids()) {
+        for label in storage.find_tx_labels_for_transaction(transaction_id).await? {
+            if !labels.contains(&label.label) {
+                labels.push(label.label);
+            }
+        }
+    }
+    Ok(labels)
+}
+
+#[async_trait]
+impl MonitorTask for TaskNotify {
+    fn name(&self) -> &'static str {
+        "Notify"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = !self.events.is_empty() || self.pending.iter().any(|d| d.next_attempt_msecs <= now_msecs);
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let mut log = String::new();
+        loop {
+            let event = match self.events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Lagged(skipped)) => {
+                    warn!(skipped, "notification events skipped");
+                    log.push_str(&format!("{} events skipped\n", skipped));
+                    continue;
+                }
+                Err(_) => break,
+            };
+            if let Some(notification) = TransactionNotification::from_event(&event, msecs_to_rfc3339(self.last_run_msecs)) {
+                self.queue(storage, notification, &mut log).await?;
+            }
+        }
+        self.deliver(&mut log).await;
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::notifications::NotificationFilter;
+    use wallet_storage::{ProvenTxReqStatus, TableProvenTxReq, TableTxLabel, TableTxLabelMap, TransactionStatus};
+    use wallet_test_utils::MockStorage;
+
+    /// Handler failing its first `failures` deliveries, recording the rest
+    struct Recorder {
+        failures: Mutex<u32>,
+        received: Mutex<Vec<SignedNotification>>,
+    }
+
+    impl Recorder {
+        fn new(failures: u32) -> Arc<Self> {
+            Arc::new(Self { failures: Mutex::new(failures), received: Mutex::new(Vec::new()) })
+        }
+
+        fn received(&self) -> Vec<SignedNotification> {
+            self.received.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl NotificationHandler for Recorder {
+        async fn notify(&self, notification: &SignedNotification) -> Result<(), String> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err("unreachable".to_string());
+            }
+            self.received.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    /// Storage where the request for "aa" notifies transaction 7, labelled "payroll"
+    fn labelled_storage() -> MockStorage {
+        let mut storage = MockStorage::new();
+        storage.reqs.push(TableProvenTxReq::new(
+            1,
+            ProvenTxReqStatus::Unmined,
+            "aa",
+            "{}",
+            r#"{"transactionIds":[7]}"#,
+            vec![],
+        ));
+        storage.labels.push(TableTxLabel::new(3, 1, "payroll"));
+        storage.label_maps.push(TableTxLabelMap::new(3, 7));
+        storage
+    }
+
+    fn progress(txid: &str) -> WalletEvent {
+        WalletEvent::TransactionProgress {
+            txid: txid.to_string(),
+            old_status: TransactionStatus::Sending,
+            new_status: TransactionStatus::Unproven,
+            provider: Some("TAAL".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifies_matching_handlers() {
+        let bus = WalletEventBus::default();
+        let registry = Arc::new(NotificationRegistry::new());
+        let by_label = Recorder::new(0);
+        let other = Recorder::new(0);
+        registry.register(NotificationFilter::Label("payroll".to_string()), by_label.clone());
+        registry.register(NotificationFilter::Txid("bb".to_string()), other.clone());
+        let wallet = RootKeyDeriver::new(&[1u8; 32]).unwrap();
+        let mut task = TaskNotify::new(registry, wallet.clone(), &bus);
+        let mut storage = labelled_storage();
+
+        assert!(!task.trigger(1_000));
+        bus.emit(progress("aa"));
+        assert!(task.trigger(1_000));
+        let log = task.run_task(&mut storage).await.unwrap();
+        assert_eq!(log, "aa unproven notified\n");
+
+        let received = by_label.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].notification.labels, vec!["payroll".to_string()]);
+        assert_eq!(received[0].notification.provider.as_deref(), Some("TAAL"));
+        assert!(SignedNotification::verify(&received[0].body, &received[0].signature, &wallet.identity_key_hex()));
+        assert!(other.received().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_backoff_then_gives_up() {
+        let bus = WalletEventBus::default();
+        let registry = Arc::new(NotificationRegistry::new());
+        let flaky = Recorder::new(1);
+        registry.register(NotificationFilter::Txid("aa".to_string()), flaky.clone());
+        let mut task = TaskNotify::new(registry.clone(), RootKeyDeriver::new(&[1u8; 32]).unwrap(), &bus);
+        task.retry_msecs = 100;
+        let mut storage = labelled_storage();
+
+        bus.emit(progress("aa"));
+        task.trigger(1_000);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(task.pending_count(), 1);
+        assert!(!task.trigger(1_050));
+        assert!(task.trigger(1_100));
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(task.pending_count(), 0);
+        assert_eq!(flaky.received().len(), 1);
+
+        // A handler that never answers is given up after max_attempts
+        let down = Recorder::new(u32::MAX);
+        registry.register(NotificationFilter::Txid("aa".to_string()), down);
+        task.max_attempts = 2;
+        bus.emit(progress("aa"));
+        task.trigger(2_000);
+        task.run_task(&mut storage).await.unwrap();
+        task.trigger(2_100);
+        let log = task.run_task(&mut storage).await.unwrap();
+        assert!(log.contains("aa unproven notification given up: unreachable"));
+        assert_eq!(task.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_handler_is_not_retried() {
+        let bus = WalletEventBus::default();
+        let registry = Arc::new(NotificationRegistry::new());
+        let id = registry.register(NotificationFilter::Txid("aa".to_string()), Recorder::new(u32::MAX));
+        let mut task = TaskNotify::new(registry.clone(), RootKeyDeriver::new(&[1u8; 32]).unwrap(), &bus);
+        let mut storage = labelled_storage();
+
+        bus.emit(progress("aa"));
+        task.trigger(1_000);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(task.pending_count(), 1);
+
+        registry.unregister(id);
+        task.trigger(i64::MAX);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(task.pending_count(), 0);
+    }
+}
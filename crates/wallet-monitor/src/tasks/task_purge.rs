@@ -0,0 +1,104 @@
+//! TaskPurge
+//!
+//! Removes aged transaction data so long-lived wallets stay small.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskPurge.ts
+
+use async_trait::async_trait;
+use wallet_storage::{PurgeParams, StorageResult, WalletStorageProvider};
+
+use super::{log_monitor_event, MonitorTask};
+
+/// Default interval between purges (six hours)
+pub const DEFAULT_PURGE_MSECS: i64 = 1000 * 60 * 60 * 6;
+
+/// Monitor task that purges aged data with `WalletStorageProvider::purge_data`
+///
+/// What is purged, and after how long, is set by `params`. A monitor event
+/// is recorded when a run updates or deletes anything.
+///
+/// Reference: TypeScript `TaskPurge`
+pub struct TaskPurge {
+    /// Milliseconds between runs
+    pub trigger_msecs: i64,
+
+    /// What to purge
+    pub params: PurgeParams,
+
+    last_run_msecs: i64,
+}
+
+impl TaskPurge {
+    pub fn new(trigger_msecs: i64, params: PurgeParams) -> Self {
+        Self {
+            trigger_msecs,
+            params,
+            last_run_msecs: 0,
+        }
+    }
+}
+
+impl Default for TaskPurge {
+    fn default() -> Self {
+        Self::new(DEFAULT_PURGE_MSECS, PurgeParams::default())
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskPurge {
+    fn name(&self) -> &'static str {
+        "Purge"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = now_msecs - self.last_run_msecs > self.trigger_msecs;
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let r = storage.purge_data(&self.params).await?;
+        if r.count == 0 {
+            return Ok(String::new());
+        }
+
+        let log = format!("{} records updated or deleted.\n{}", r.count, r.log);
+        log_monitor_event(storage, self.name(), log.clone()).await?;
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_storage::{StorageProvidedBy, TableOutput, TableTransaction, TransactionStatus};
+    use wallet_test_utils::MockStorage;
+
+    #[tokio::test]
+    async fn test_run_task_purges_aged_failed_transactions() {
+        let mut storage = MockStorage::new();
+        let mut old = TableTransaction::new(1, 1, TransactionStatus::Failed, "old", true, 0, "old");
+        old.updated_at = "2000-01-01T00:00:00+00:00".to_string();
+        storage.transactions.push(old);
+        storage.transactions.push(TableTransaction::new(2, 1, TransactionStatus::Failed, "recent", true, 0, "recent"));
+        storage.outputs.push(TableOutput::new(10, 1, 1, false, true, "change", 0, 1000, StorageProvidedBy::Storage, "change", "P2PKH"));
+
+        let mut task = TaskPurge::default();
+        assert!(task.trigger(chrono::Utc::now().timestamp_millis()));
+        let log = task.run_task(&mut storage).await.unwrap();
+
+        assert!(log.starts_with("2 records updated or deleted."));
+        assert!(log.contains("1 failedTransactions"));
+        assert_eq!(storage.transactions.len(), 1);
+        assert_eq!(storage.transactions[0].transaction_id, 2);
+        assert!(storage.outputs.is_empty());
+        assert_eq!(storage.events.len(), 1);
+        assert_eq!(storage.events[0].event, "Purge");
+
+        // Nothing left to purge: no log and no event
+        assert_eq!(task.run_task(&mut storage).await.unwrap(), "");
+        assert_eq!(storage.events.len(), 1);
+    }
+}
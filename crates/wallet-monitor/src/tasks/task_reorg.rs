@@ -0,0 +1,221 @@
+//! TaskReorg
+//!
+//! Rolls back proofs for blocks that a chain reorganization deactivated.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskReorg.ts
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use wallet_core::events::WalletEventBus;
+use wallet_core::services::ChainHeaderProvider;
+use wallet_storage::{MonitorEvent, StorageResult, TransactionStatus, WalletStorageProvider};
+
+use super::{emit_status_change, MonitorTask};
+
+/// Reorgs waiting to be processed by a `TaskReorg`
+///
+/// Cloned out of the task before it is added to the monitor, and fed from
+/// whatever follows the header chain (e.g. a Chaintracks subscription).
+#[derive(Debug, Clone, Default)]
+pub struct ReorgQueue {
+    /// Lowest block height whose proofs must be rechecked
+    min_height: Arc<Mutex<Option<u32>>>,
+}
+
+impl ReorgQueue {
+    /// Queue a reorg that deactivated `depth` blocks ending at `old_tip_height`
+    ///
+    /// Reference: TS Monitor.processReorg(depth, oldTip, newTip)
+    pub fn process_reorg(&self, depth: u32, old_tip_height: u32) {
+        let height = (old_tip_height + 1).saturating_sub(depth.max(1));
+        let mut min_height = self.min_height.lock().unwrap_or_else(|e| e.into_inner());
+        *min_height = Some(min_height.map_or(height, |h| h.min(height)));
+    }
+
+    fn is_pending(&self) -> bool {
+        self.min_height.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    fn take(&self) -> Option<u32> {
+        self.min_height.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// Monitor task that invalidates proofs from deactivated blocks
+///
+/// After a reorg, each ProvenTx at or above the lowest affected height is
+/// compared against the active header chain. Those whose block hash no
+/// longer matches are deleted: their ProvenTxReqs return to 'unmined' so
+/// `TaskCheckForProofs` acquires new proofs, 'completed' transactions return
+/// to 'unproven', and a `MonitorEvent::Reorg` is recorded for each.
+///
+/// Reference: TypeScript `TaskReorg`
+pub struct TaskReorg {
+    headers: Arc<dyn ChainHeaderProvider>,
+    queue: ReorgQueue,
+    event_bus: Option<WalletEventBus>,
+}
+
+impl TaskReorg {
+    pub fn new(headers: Arc<dyn ChainHeaderProvider>) -> Self {
+        Self {
+            headers,
+            queue: ReorgQueue::default(),
+            event_bus: None,
+        }
+    }
+
+    /// Report transactions reverted to 'unproven' on `event_bus`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Handle for queueing reorgs once the task belongs to the monitor
+    pub fn queue(&self) -> ReorgQueue {
+        self.queue.clone()
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskReorg {
+    fn name(&self) -> &'static str {
+        "Reorg"
+    }
+
+    fn trigger(&mut self, _now_msecs: i64) -> bool {
+        self.queue.is_pending()
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let Some(min_height) = self.queue.take() else {
+            return Ok(String::new());
+        };
+
+        let mut log = String::new();
+        for ptx in storage.find_proven_txs_from_height(min_height as i64).await? {
+            let header = match self.headers.find_header_for_height(ptx.height as u32).await {
+                Ok(header) => header,
+                Err(e) => {
+                    // Retry the remaining heights on the next run
+                    self.queue.process_reorg(1, ptx.height as u32);
+                    return Err(e);
+                }
+            };
+            if header.is_some_and(|h| h.hash == ptx.block_hash) {
+                continue;
+            }
+
+            let reverted = storage.rollback_proven_tx(ptx.proven_tx_id).await?;
+            log.push_str(&format!(
+                "txid {} proven in block {} at height {} is no longer on the active chain; transactionIds {:?} reverted to 'unproven'\n",
+                ptx.txid, ptx.block_hash, ptx.height, reverted
+            ));
+            if !reverted.is_empty() {
+                emit_status_change(self.event_bus.as_ref(), &ptx.txid, TransactionStatus::Unproven);
+            }
+            let event = MonitorEvent::Reorg {
+                txid: ptx.txid,
+                block_hash: ptx.block_hash,
+                height: ptx.height,
+                reverted_transaction_ids: reverted,
+            };
+            storage.insert_monitor_event(&event.to_table()).await?;
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::events::WalletEvent;
+    use wallet_core::services::BlockHeader;
+    use wallet_storage::{ProvenTxReqStatus, TableProvenTx, TableProvenTxReq, TableTransaction};
+    use wallet_test_utils::MockStorage;
+
+    /// Active chain whose block at height h has hash "h{h}"
+    struct MockHeaders;
+
+    #[async_trait]
+    impl ChainHeaderProvider for MockHeaders {
+        async fn find_header_for_height(&self, height: u32) -> StorageResult<Option<BlockHeader>> {
+            Ok(Some(BlockHeader {
+                height,
+                hash: format!("h{}", height),
+                merkle_root: String::new(),
+            }))
+        }
+    }
+
+    fn proven(storage: &mut MockStorage, id: i64, height: i64, block_hash: &str) {
+        let txid = format!("tx{}", id);
+        storage.proven_txs.push(TableProvenTx::new(id, &txid, height, 0, vec![], vec![], block_hash, "root"));
+        storage.reqs.push(
+            TableProvenTxReq::new(
+                id, ProvenTxReqStatus::Completed, &txid, "{}", format!(r#"{{"transactionIds":[{}]}}"#, id), vec![]
+            )
+            .with_proven_tx_id(id),
+        );
+        let mut tx = TableTransaction::new(id, 1, TransactionStatus::Completed, &txid, true, 0, &txid);
+        tx.proven_tx_id = Some(id);
+        storage.transactions.push(tx);
+    }
+
+    #[test]
+    fn test_queue_keeps_lowest_height() {
+        let queue = ReorgQueue::default();
+        assert!(!queue.is_pending());
+        queue.process_reorg(2, 100);
+        queue.process_reorg(1, 101);
+        assert!(queue.is_pending());
+        assert_eq!(queue.take(), Some(99));
+        assert_eq!(queue.take(), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_task_rolls_back_deactivated_proofs() {
+        let mut storage = MockStorage::new();
+        proven(&mut storage, 1, 98, "stale");
+        proven(&mut storage, 2, 99, "h99");
+        proven(&mut storage, 3, 100, "stale");
+
+        let bus = WalletEventBus::default();
+        let mut events = bus.subscribe();
+        let mut task = TaskReorg::new(Arc::new(MockHeaders)).with_event_bus(bus);
+        assert!(!task.trigger(0));
+        task.queue().process_reorg(2, 100);
+        assert!(task.trigger(0));
+
+        let log = task.run_task(&mut storage).await.unwrap();
+        assert!(log.contains("tx3"));
+        assert!(!task.trigger(0));
+
+        // Below the reorg, and still active: untouched
+        assert_eq!(storage.transaction(1).status, TransactionStatus::Completed);
+        assert_eq!(storage.transaction(2).status, TransactionStatus::Completed);
+
+        let tx = storage.transaction(3);
+        assert_eq!(tx.status, TransactionStatus::Unproven);
+        assert_eq!(tx.proven_tx_id, None);
+        assert_eq!(storage.reqs[2].status, ProvenTxReqStatus::Unmined);
+        assert_eq!(storage.reqs[2].proven_tx_id, None);
+        assert_eq!(storage.proven_txs.len(), 2);
+        assert_eq!(storage.events.len(), 1);
+        assert_eq!(
+            MonitorEvent::from_table(&storage.events[0]),
+            MonitorEvent::Reorg {
+                txid: "tx3".to_string(),
+                block_hash: "stale".to_string(),
+                height: 100,
+                reverted_transaction_ids: vec![3],
+            }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            WalletEvent::TransactionStatusChanged { txid: "tx3".to_string(), status: TransactionStatus::Unproven }
+        );
+        assert!(events.try_recv().is_err());
+    }
+}
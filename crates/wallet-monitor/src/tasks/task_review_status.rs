@@ -0,0 +1,158 @@
+//! TaskReviewStatus
+//!
+//! Propagates terminal ProvenTxReq failures to the transactions they notify.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskReviewStatus.ts
+
+use async_trait::async_trait;
+use wallet_core::events::WalletEventBus;
+use wallet_storage::{
+    FindProvenTxReqsArgs, ProvenTxReqStatus, StorageResult, TransactionStatus, WalletStorageProvider,
+};
+
+use super::{emit_status_change, fail_transaction, log_monitor_event, msecs_to_rfc3339, MonitorTask};
+
+/// Default interval between reviews (15 minutes)
+pub const DEFAULT_REVIEW_MSECS: i64 = 1000 * 60 * 15;
+
+/// Whether a transaction in `status` should be failed when its request is invalid
+fn is_failable(status: TransactionStatus) -> bool {
+    !matches!(status, TransactionStatus::Failed | TransactionStatus::Completed)
+}
+
+/// Monitor task that fails transactions whose ProvenTxReq is 'invalid' or
+/// 'doubleSpend'
+///
+/// Only requests last updated more than `aged_msecs` ago are reviewed, giving
+/// in-flight processing time to settle.
+///
+/// Reference: TypeScript `TaskReviewStatus` (`storage.reviewStatus({ agedLimit })`)
+pub struct TaskReviewStatus {
+    /// Milliseconds between runs
+    pub trigger_msecs: i64,
+
+    /// Minimum age of a request before it is reviewed
+    pub aged_msecs: i64,
+
+    event_bus: Option<WalletEventBus>,
+
+    last_run_msecs: i64,
+}
+
+impl TaskReviewStatus {
+    pub fn new(trigger_msecs: i64, aged_msecs: i64) -> Self {
+        Self {
+            trigger_msecs,
+            aged_msecs,
+            event_bus: None,
+            last_run_msecs: 0,
+        }
+    }
+
+    /// Report failed transactions on `event_bus`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+}
+
+impl Default for TaskReviewStatus {
+    fn default() -> Self {
+        Self::new(DEFAULT_REVIEW_MSECS, DEFAULT_REVIEW_MSECS)
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskReviewStatus {
+    fn name(&self) -> &'static str {
+        "ReviewStatus"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = now_msecs - self.last_run_msecs > self.trigger_msecs;
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        let aged_limit = msecs_to_rfc3339(self.last_run_msecs - self.aged_msecs);
+
+        let mut log = String::new();
+        for status in [ProvenTxReqStatus::Invalid, ProvenTxReqStatus::DoubleSpend] {
+            let reqs = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+                status: Some(status),
+                since: None,
+                paged: None,
+                txids: None,
+            }).await?;
+
+            for req in reqs.iter().filter(|r| r.updated_at < aged_limit) {
+                for transaction_id in req.notify_transaction_ids() {
+                    let Some(tx) = storage.find_transaction_by_id(transaction_id).await? else {
+                        continue;
+                    };
+                    if !is_failable(tx.status) {
+                        continue;
+                    }
+                    let Some(released) = fail_transaction(storage, &tx).await? else {
+                        continue;
+                    };
+                    emit_status_change(self.event_bus.as_ref(), &req.txid, TransactionStatus::Failed);
+                    let details = format!(
+                        "transactionId {} failed: txid {} is '{}'; released {} inputs",
+                        tx.transaction_id, req.txid, req.status, released
+                    );
+                    log_monitor_event(storage, self.name(), details.clone()).await?;
+                    log.push_str(&details);
+                    log.push('\n');
+                }
+            }
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_storage::{TableProvenTxReq, TableTransaction};
+    use wallet_test_utils::MockStorage;
+
+    #[test]
+    fn test_is_failable() {
+        assert!(is_failable(TransactionStatus::Sending));
+        assert!(is_failable(TransactionStatus::Unproven));
+        assert!(!is_failable(TransactionStatus::Failed));
+        assert!(!is_failable(TransactionStatus::Completed));
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut task = TaskReviewStatus::new(1000, 1000);
+        assert!(task.trigger(1001));
+        assert!(!task.trigger(1500));
+    }
+
+    #[tokio::test]
+    async fn test_run_task_fails_transactions_of_invalid_reqs() {
+        let mut storage = MockStorage::new();
+        storage.transactions.push(TableTransaction::new(1, 1, TransactionStatus::Sending, "a", true, 0, "a"));
+        storage.transactions.push(TableTransaction::new(2, 1, TransactionStatus::Completed, "b", true, 0, "b"));
+        let mut req = TableProvenTxReq::new(
+            1, ProvenTxReqStatus::DoubleSpend, "txid", "{}", r#"{"transactionIds":[1,2]}"#, vec![]
+        );
+        req.updated_at = "2000-01-01T00:00:00+00:00".to_string();
+        storage.reqs.push(req);
+
+        let mut task = TaskReviewStatus::default();
+        assert!(task.trigger(chrono::Utc::now().timestamp_millis()));
+        task.run_task(&mut storage).await.unwrap();
+
+        assert_eq!(storage.transaction(1).status, TransactionStatus::Failed);
+        assert_eq!(storage.transaction(2).status, TransactionStatus::Completed);
+        assert_eq!(storage.events.len(), 1);
+        assert!(storage.events[0].details.as_deref().unwrap().contains("doubleSpend"));
+    }
+}
@@ -0,0 +1,320 @@
+//! TaskSendWaiting
+//!
+//! Broadcasts transactions queued by delayed `createAction` / `signAction`
+//! calls, and retries broadcasts that failed with a service error.
+//!
+//! Reference: wallet-toolbox/src/monitor/tasks/TaskSendWaiting.ts
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use wallet_core::events::WalletEventBus;
+use wallet_core::methods::attempt_to_post_reqs_to_network;
+use wallet_core::sdk::action_process::ReviewActionResultStatus;
+use wallet_core::services::{Broadcaster, UtxoStatusProvider};
+use wallet_storage::{
+    FindProvenTxReqsArgs, Paged, ProvenTxReqStatus, StorageResult, TableProvenTxReq, TransactionStatus,
+    WalletStorageProvider,
+};
+
+use super::{emit_progress, MonitorTask};
+
+/// Default milliseconds between runs (8 seconds)
+pub const DEFAULT_SEND_WAITING_MSECS: i64 = 1000 * 8;
+
+/// Default age before an 'unsent' request is posted (7 seconds)
+pub const DEFAULT_SEND_AGED_MSECS: i64 = 1000 * 7;
+
+/// Default milliseconds between retries of 'sending' requests (5 minutes)
+pub const DEFAULT_SENDING_RETRY_MSECS: i64 = 1000 * 60 * 5;
+
+/// Maximum number of requests posted per status on each run
+const MAX_REQS_PER_RUN: u32 = 100;
+
+/// Monitor task that posts 'unsent' requests, and periodically 'sending' ones
+///
+/// Requests are left alone for `aged_msecs` after their last update, so the
+/// action that queued them can finish first. Requests sharing a batch id are
+/// posted together as one BEEF.
+///
+/// Reference: TypeScript `TaskSendWaiting`
+pub struct TaskSendWaiting {
+    broadcaster: Arc<dyn Broadcaster>,
+    utxo_status: Option<Arc<dyn UtxoStatusProvider>>,
+
+    /// Milliseconds between runs
+    pub trigger_msecs: i64,
+
+    /// Age after their last update at which requests are posted
+    pub aged_msecs: i64,
+
+    /// Milliseconds between runs that also retry 'sending' requests
+    pub sending_msecs: i64,
+
+    /// Run on the next trigger, e.g. right after a delayed action
+    pub check_now: bool,
+
+    event_bus: Option<WalletEventBus>,
+
+    last_run_msecs: i64,
+    last_sending_run_msecs: Option<i64>,
+}
+
+impl TaskSendWaiting {
+    pub fn new(broadcaster: Arc<dyn Broadcaster>, trigger_msecs: i64) -> Self {
+        Self {
+            broadcaster,
+            utxo_status: None,
+            trigger_msecs,
+            aged_msecs: DEFAULT_SEND_AGED_MSECS,
+            sending_msecs: DEFAULT_SENDING_RETRY_MSECS,
+            check_now: false,
+            event_bus: None,
+            last_run_msecs: 0,
+            last_sending_run_msecs: None,
+        }
+    }
+
+    /// Check the inputs of double spends with `utxo_status`
+    pub fn with_utxo_status(mut self, utxo_status: Arc<dyn UtxoStatusProvider>) -> Self {
+        self.utxo_status = Some(utxo_status);
+        self
+    }
+
+    /// Report each broadcast attempt on `event_bus`
+    pub fn with_event_bus(mut self, event_bus: WalletEventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Whether `req` was last updated at least `aged_msecs` before `now_msecs`
+    ///
+    /// Requests with an unreadable timestamp are considered aged.
+    fn is_aged(&self, req: &TableProvenTxReq, now_msecs: i64) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&req.updated_at)
+            .map(|updated| now_msecs - updated.timestamp_millis() >= self.aged_msecs)
+            .unwrap_or(true)
+    }
+
+    /// Post `reqs` as one batch and report each outcome
+    async fn post_batch(
+        &self,
+        storage: &mut dyn WalletStorageProvider,
+        reqs: &[TableProvenTxReq],
+    ) -> StorageResult<String> {
+        let results = attempt_to_post_reqs_to_network(
+            storage,
+            self.broadcaster.as_ref(),
+            self.utxo_status.as_deref(),
+            reqs,
+        )
+        .await?;
+
+        let mut log = String::new();
+        for result in &results {
+            let new_status = match result.status {
+                ReviewActionResultStatus::Success => TransactionStatus::Unproven,
+                ReviewActionResultStatus::DoubleSpend | ReviewActionResultStatus::InvalidTx => {
+                    TransactionStatus::Failed
+                }
+                ReviewActionResultStatus::ServiceError => TransactionStatus::Sending,
+            };
+            emit_progress(
+                self.event_bus.as_ref(),
+                &result.txid,
+                TransactionStatus::Sending,
+                new_status,
+                result.service.clone().or_else(|| self.broadcaster.name().map(str::to_string)),
+            );
+            log.push_str(&format!("{} {:?}\n", result.txid, result.status));
+        }
+        Ok(log)
+    }
+}
+
+#[async_trait]
+impl MonitorTask for TaskSendWaiting {
+    fn name(&self) -> &'static str {
+        "SendWaiting"
+    }
+
+    fn trigger(&mut self, now_msecs: i64) -> bool {
+        let run = self.check_now || now_msecs - self.last_run_msecs > self.trigger_msecs;
+        if run {
+            self.last_run_msecs = now_msecs;
+        }
+        run
+    }
+
+    async fn run_task(&mut self, storage: &mut dyn WalletStorageProvider) -> StorageResult<String> {
+        self.check_now = false;
+        let now_msecs = self.last_run_msecs;
+
+        let include_sending = self
+            .last_sending_run_msecs
+            .is_none_or(|last| now_msecs - last > self.sending_msecs);
+        let mut statuses = vec![ProvenTxReqStatus::Unsent];
+        if include_sending {
+            self.last_sending_run_msecs = Some(now_msecs);
+            statuses.push(ProvenTxReqStatus::Sending);
+        }
+
+        // Gather before posting, so requests posted now are not retried now
+        let mut reqs: Vec<TableProvenTxReq> = Vec::new();
+        for status in statuses {
+            let found = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+                status: Some(status),
+                since: None,
+                paged: Some(Paged::new(MAX_REQS_PER_RUN)),
+                txids: None,
+            }).await?;
+            reqs.extend(found.into_iter().filter(|req| self.is_aged(req, now_msecs)));
+        }
+
+        // Unbatched requests are posted alone; batches together
+        let mut batches: Vec<Vec<TableProvenTxReq>> = Vec::new();
+        for req in reqs {
+            let batch = req.batch.clone();
+            match batches.iter_mut().find(|b| batch.is_some() && b[0].batch == batch) {
+                Some(b) => b.push(req),
+                None => batches.push(vec![req]),
+            }
+        }
+
+        let mut log = String::new();
+        for batch in &batches {
+            log.push_str(&self.post_batch(storage, batch).await?);
+        }
+        Ok(log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::beef::Beef;
+    use wallet_core::events::WalletEvent;
+    use wallet_core::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+    use wallet_storage::{EntityProvenTxReq, MonitorEvent, TableTransaction};
+    use wallet_test_utils::{MockBroadcaster, MockStorage};
+
+    /// An 'unsent' request for a new transaction spending `prev_txid`,
+    /// notifying transaction `transaction_id`
+    fn unsent_req(proven_tx_req_id: i64, prev_txid: &str, transaction_id: i64) -> TableProvenTxReq {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(prev_txid, 0)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+        let mut input_beef = Beef::new_v2();
+        input_beef.merge_txid_only(prev_txid);
+
+        let mut req = TableProvenTxReq::new(
+            proven_tx_req_id,
+            ProvenTxReqStatus::Unsent,
+            tx.txid().unwrap(),
+            "{}",
+            format!(r#"{{"transactionIds":[{}]}}"#, transaction_id),
+            tx.serialize().unwrap(),
+        )
+        .with_input_beef(input_beef.to_binary().unwrap());
+        req.updated_at = "2024-01-01T00:00:00+00:00".to_string();
+        req
+    }
+
+    fn storage_with(reqs: Vec<TableProvenTxReq>) -> MockStorage {
+        let mut storage = MockStorage::new();
+        for req in &reqs {
+            let transaction_id = req.notify_transaction_ids()[0];
+            let mut tx = TableTransaction::new(transaction_id, 1, TransactionStatus::Sending, "ref", false, 0, "");
+            tx.txid = Some(req.txid.clone());
+            storage.transactions.push(tx);
+        }
+        storage.reqs = reqs;
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_posts_unsent_and_reports_progress() {
+        let req = unsent_req(1, &"11".repeat(32), 7);
+        let txid = req.txid.clone();
+        let mut storage = storage_with(vec![req]);
+        let broadcaster = Arc::new(MockBroadcaster::new(ReviewActionResultStatus::Success));
+        let bus = WalletEventBus::default();
+        let mut events = bus.subscribe();
+        let mut task = TaskSendWaiting::new(broadcaster.clone(), 1000).with_event_bus(bus);
+
+        assert!(task.trigger(chrono::Utc::now().timestamp_millis()));
+        let log = task.run_task(&mut storage).await.unwrap();
+        assert!(log.contains(&txid));
+
+        assert_eq!(broadcaster.posts().len(), 1);
+        assert_eq!(storage.reqs[0].status, ProvenTxReqStatus::Unmined);
+        let notes = EntityProvenTxReq::parse_history(&storage.reqs[0].history).notes.unwrap();
+        assert_eq!((notes[0].what.as_str(), &notes[0].extra["name"]), ("postBeefSuccess", &serde_json::json!("mockArc")));
+        assert_eq!(storage.transaction(7).status, TransactionStatus::Unproven);
+        assert!(matches!(
+            MonitorEvent::from_table(&storage.events[0]),
+            MonitorEvent::BroadcastAttempt { status, .. } if status == "success"
+        ));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            WalletEvent::TransactionProgress {
+                txid,
+                old_status: TransactionStatus::Sending,
+                new_status: TransactionStatus::Unproven,
+                provider: Some("mockArc".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_posts_batches_together_and_skips_recent_reqs() {
+        let a = unsent_req(1, &"11".repeat(32), 1).with_batch("b1");
+        let b = unsent_req(2, &"22".repeat(32), 2).with_batch("b1");
+        let alone = unsent_req(3, &"33".repeat(32), 3);
+        let mut recent = unsent_req(4, &"44".repeat(32), 4);
+        recent.updated_at = chrono::Utc::now().to_rfc3339();
+        let mut storage = storage_with(vec![a.clone(), b.clone(), alone.clone(), recent]);
+        let broadcaster = Arc::new(MockBroadcaster::new(ReviewActionResultStatus::Success));
+        let mut task = TaskSendWaiting::new(broadcaster.clone(), 1000);
+
+        task.trigger(chrono::Utc::now().timestamp_millis());
+        task.run_task(&mut storage).await.unwrap();
+
+        assert_eq!(
+            broadcaster.posts(),
+            vec![vec![a.txid, b.txid], vec![alone.txid]]
+        );
+        assert_eq!(storage.reqs[3].status, ProvenTxReqStatus::Unsent);
+    }
+
+    #[tokio::test]
+    async fn test_service_error_is_retried_after_sending_msecs() {
+        let mut storage = storage_with(vec![unsent_req(1, &"11".repeat(32), 1)]);
+        let broadcaster = Arc::new(MockBroadcaster::new(ReviewActionResultStatus::ServiceError));
+        let mut task = TaskSendWaiting::new(broadcaster.clone(), 1000);
+        let now = chrono::Utc::now().timestamp_millis();
+
+        task.trigger(now);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(storage.reqs[0].status, ProvenTxReqStatus::Sending);
+        assert_eq!(broadcaster.posts().len(), 1);
+
+        // 'sending' requests wait for the retry interval
+        task.trigger(now + 2000);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(broadcaster.posts().len(), 1);
+
+        task.trigger(now + DEFAULT_SENDING_RETRY_MSECS + 1);
+        task.run_task(&mut storage).await.unwrap();
+        assert_eq!(broadcaster.posts().len(), 2);
+    }
+
+    #[test]
+    fn test_trigger() {
+        let mut task = TaskSendWaiting::new(Arc::new(MockBroadcaster::accepting()), 1000);
+        assert!(task.trigger(2000));
+        assert!(!task.trigger(2500));
+        task.check_now = true;
+        assert!(task.trigger(2600));
+    }
+}
@@ -9,7 +9,7 @@ use reqwest::Client;
 use crate::error::{ServiceError, ServiceResult};
 use crate::traits::Broadcaster;
 use crate::types::{PostRawTxResult, PostBeefResult, GetStatusForTxidsResult, TxStatus, TxStatusType};
-use super::types::{ArcConfig, ArcResponse};
+use super::types::{ArcConfig, ArcEndpoint, ArcResponse, ArcTxStatus, BroadcastResult, BroadcastStatus};
 
 /// ARC broadcaster client
 ///
@@ -48,78 +48,213 @@ impl ArcBroadcaster {
         }
     }
     
-    /// Post transaction to ARC
+    /// Create a broadcaster for `endpoint`, named after it
+    pub fn from_endpoint(endpoint: &ArcEndpoint) -> Self {
+        Self::new(endpoint.url.clone(), Some(endpoint.config()), Some(endpoint.name.clone()))
+    }
+    
+    /// Build request headers
     ///
-    /// Reference: TS ARC.postRawTx (lines 129-234)
-    async fn post_tx_to_arc(&self, raw_tx_hex: &str, txid: &str) -> ServiceResult<ArcResponse> {
-        let url = format!("{}/v1/tx", self.url);
+    /// Reference: TS ARC.requestHeaders (lines 95-111)
+    ///
+    /// Callback URL and token are passed as `X-CallbackUrl` / `X-CallbackToken`
+    /// so ARC reports status changes for every posted transaction.
+    fn request_headers(&self) -> ServiceResult<reqwest::header::HeaderMap> {
+        use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
         
-        // Build headers (TS lines 95-111)
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::CONTENT_TYPE,
-            reqwest::header::HeaderValue::from_static("application/json"),
-        );
+        fn value(v: &str, what: &str) -> ServiceResult<HeaderValue> {
+            HeaderValue::from_str(v).map_err(|_| ServiceError::InvalidParams(format!("Invalid {}", what)))
+        }
+        
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
         
         // Add deployment ID header
         if let Some(ref deployment_id) = self.config.deployment_id {
-            headers.insert(
-                reqwest::header::HeaderName::from_static("x-deployment-id"),
-                reqwest::header::HeaderValue::from_str(deployment_id)
-                    .map_err(|_| ServiceError::InvalidParams("Invalid deployment ID".to_string()))?,
-            );
+            headers.insert(HeaderName::from_static("xdeployment-id"), value(deployment_id, "deployment ID")?);
         }
         
         // Add API key if provided
         if let Some(ref api_key) = self.config.api_key {
-            headers.insert(
-                reqwest::header::AUTHORIZATION,
-                reqwest::header::HeaderValue::from_str(&format!("Bearer {}", api_key))
-                    .map_err(|_| ServiceError::InvalidParams("Invalid API key".to_string()))?,
-            );
+            headers.insert(reqwest::header::AUTHORIZATION, value(&format!("Bearer {}", api_key), "API key")?);
+        }
+        
+        // Add callback info if configured
+        if let Some(ref callback_url) = self.config.callback_url {
+            headers.insert(HeaderName::from_static("x-callbackurl"), value(callback_url, "callback URL")?);
+            if let Some(ref callback_token) = self.config.callback_token {
+                headers.insert(HeaderName::from_static("x-callbacktoken"), value(callback_token, "callback token")?);
+            }
         }
         
         // Add custom headers
         if let Some(ref custom_headers) = self.config.headers {
-            for (key, value) in custom_headers {
+            for (key, v) in custom_headers {
                 headers.insert(
-                    reqwest::header::HeaderName::from_bytes(key.as_bytes())
+                    HeaderName::from_bytes(key.as_bytes())
                         .map_err(|_| ServiceError::InvalidParams(format!("Invalid header key: {}", key)))?,
-                    reqwest::header::HeaderValue::from_str(value)
-                        .map_err(|_| ServiceError::InvalidParams(format!("Invalid header value: {}", value)))?,
+                    value(v, &format!("header value: {}", v))?,
                 );
             }
         }
         
+        Ok(headers)
+    }
+    
+    /// Post transaction to ARC
+    ///
+    /// Reference: TS ARC.postRawTx (lines 129-234)
+    ///
+    /// `raw_tx_hex` may be a raw transaction, extended format or BEEF.
+    /// Returns the HTTP status along with the parsed body; error bodies
+    /// that fail to parse are reported through an empty `ArcResponse`.
+    async fn post_tx_to_arc(&self, raw_tx_hex: &str) -> ServiceResult<(u16, ArcResponse)> {
+        let url = format!("{}/v1/tx", self.url);
+        
         // Build request body (TS lines 150-155)
-        let mut body = serde_json::json!({
+        let body = serde_json::json!({
             "rawTx": raw_tx_hex,
         });
         
-        // Add callback info if configured
-        if let Some(ref callback_url) = self.config.callback_url {
-            body["callbackUrl"] = serde_json::json!(callback_url);
-            if let Some(ref callback_token) = self.config.callback_token {
-                body["callbackToken"] = serde_json::json!(callback_token);
-            }
-        }
-        
-        // Make request
         let response = self.client
             .post(&url)
-            .headers(headers)
+            .headers(self.request_headers()?)
             .json(&body)
             .send()
             .await
             .map_err(ServiceError::Http)?;
         
         // Parse response (TS lines 156-180)
-        let arc_response: ArcResponse = response
-            .json()
+        let http_status = response.status().as_u16();
+        let text = response.text().await.map_err(ServiceError::Http)?;
+        let arc_response = serde_json::from_str(&text).unwrap_or_else(|_| ArcResponse {
+            status: http_status as i32,
+            extra_info: Some(text),
+            ..Default::default()
+        });
+        
+        Ok((http_status, arc_response))
+    }
+    
+    /// Broadcast a single transaction
+    ///
+    /// Reference: TS ARC.postRawTx
+    ///
+    /// Transport failures are reported as `BroadcastStatus::ServiceError`
+    /// rather than as an `Err`, so callers can try the next service.
+    pub async fn broadcast(&self, raw_tx: &[u8]) -> ServiceResult<BroadcastResult> {
+        let raw_tx_hex = hex::encode(raw_tx);
+        let txid = Self::calculate_txid(&raw_tx_hex)?;
+        
+        Ok(match self.post_tx_to_arc(&raw_tx_hex).await {
+            Ok((http_status, arc_response)) => {
+                BroadcastResult::from_arc_response(&self.name, http_status, &arc_response, &txid)
+            }
+            Err(e) => BroadcastResult::service_error(&self.name, &txid, e.to_string()),
+        })
+    }
+    
+    /// Broadcast several transactions in one request
+    ///
+    /// Reference: ARC `POST /v1/txs`
+    ///
+    /// Returns one result per transaction, in input order.
+    pub async fn broadcast_batch(&self, raw_txs: &[Vec<u8>]) -> ServiceResult<Vec<BroadcastResult>> {
+        let txids = raw_txs.iter()
+            .map(|raw_tx| Self::calculate_txid(&hex::encode(raw_tx)))
+            .collect::<ServiceResult<Vec<_>>>()?;
+        if txids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let url = format!("{}/v1/txs", self.url);
+        let body: Vec<serde_json::Value> = raw_txs.iter()
+            .map(|raw_tx| serde_json::json!({ "rawTx": hex::encode(raw_tx) }))
+            .collect();
+        
+        let response = match self.client
+            .post(&url)
+            .headers(self.request_headers()?)
+            .json(&body)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                return Ok(txids.iter()
+                    .map(|txid| BroadcastResult::service_error(&self.name, txid, e.to_string()))
+                    .collect());
+            }
+        };
+        
+        let http_status = response.status().as_u16();
+        let text = response.text().await.map_err(ServiceError::Http)?;
+        
+        // Success: an array of per-transaction responses. Failure: a single error body.
+        if let Ok(responses) = serde_json::from_str::<Vec<ArcResponse>>(&text) {
+            return Ok(txids.iter()
+                .map(|txid| {
+                    let arc_response = responses.iter().find(|r| &r.txid == txid);
+                    match arc_response {
+                        Some(r) => BroadcastResult::from_arc_response(&self.name, http_status, r, txid),
+                        None => BroadcastResult::service_error(&self.name, txid, "missing from ARC batch response"),
+                    }
+                })
+                .collect());
+        }
+        
+        let arc_response = serde_json::from_str(&text).unwrap_or_else(|_| ArcResponse {
+            status: http_status as i32,
+            extra_info: Some(text),
+            ..Default::default()
+        });
+        Ok(txids.iter()
+            .map(|txid| BroadcastResult::from_arc_response(&self.name, http_status, &arc_response, txid))
+            .collect())
+    }
+    
+    /// Query ARC for the current status of a transaction
+    ///
+    /// Reference: ARC `GET /v1/tx/{txid}`
+    ///
+    /// Used by the Monitor to reconcile statuses when callbacks are missed.
+    pub async fn get_tx_status(&self, txid: &str) -> ServiceResult<BroadcastResult> {
+        let url = format!("{}/v1/tx/{}", self.url, txid);
+        
+        let response = self.client
+            .get(&url)
+            .headers(self.request_headers()?)
+            .send()
             .await
             .map_err(ServiceError::Http)?;
         
-        Ok(arc_response)
+        let http_status = response.status().as_u16();
+        if http_status == 404 {
+            return Err(ServiceError::TxNotFound(txid.to_string()));
+        }
+        
+        let arc_response: ArcResponse = response.json().await.map_err(ServiceError::Http)?;
+        Ok(BroadcastResult::from_arc_response(&self.name, http_status, &arc_response, txid))
+    }
+    
+    /// Map an ARC status result to the services `TxStatus`
+    fn tx_status_from_result(txid: &str, result: Option<&BroadcastResult>) -> TxStatus {
+        let status = match result.and_then(|r| r.tx_status) {
+            Some(ArcTxStatus::Mined) => TxStatusType::Mined,
+            Some(s) if s.is_accepted() => TxStatusType::Known,
+            Some(ArcTxStatus::SeenInOrphanMempool)
+            | Some(ArcTxStatus::DoubleSpendAttempted)
+            | Some(ArcTxStatus::SentToNetwork)
+            | Some(ArcTxStatus::AnnouncedToNetwork)
+            | Some(ArcTxStatus::RequestedByNetwork)
+            | Some(ArcTxStatus::Stored) => TxStatusType::Known,
+            _ => TxStatusType::Unknown,
+        };
+        TxStatus {
+            txid: txid.to_string(),
+            status,
+            depth: None,
+        }
     }
     
     /// Calculate transaction ID from raw hex
@@ -148,90 +283,77 @@ impl Broadcaster for ArcBroadcaster {
     ///
     /// Reference: TS ARC.postRawTx
     async fn post_raw_tx(&self, raw_tx: &[u8]) -> ServiceResult<PostRawTxResult> {
-        let raw_tx_hex = hex::encode(raw_tx);
-        let txid = Self::calculate_txid(&raw_tx_hex)?;
+        let result = self.broadcast(raw_tx).await?;
+        let success = result.status == BroadcastStatus::Success;
         
-        match self.post_tx_to_arc(&raw_tx_hex, &txid).await {
-            Ok(arc_response) => {
-                Ok(PostRawTxResult {
-                    txid: arc_response.txid.clone(),
-                    success: arc_response.is_success(),
-                    name: Some(self.name.clone()),
-                    error: if arc_response.is_success() {
-                        None
-                    } else {
-                        Some(crate::types::ServiceError {
-                            service: self.name.clone(),
-                            message: arc_response.title.clone(),
-                            status_code: Some(arc_response.status as u16),
-                        })
-                    },
-                })
-            }
-            Err(e) => {
-                Ok(PostRawTxResult {
-                    txid: txid.clone(),
-                    success: false,
-                    name: Some(self.name.clone()),
-                    error: Some(crate::types::ServiceError {
-                        service: self.name.clone(),
-                        message: e.to_string(),
-                        status_code: None,
-                    }),
+        Ok(PostRawTxResult {
+            txid: result.txid,
+            success,
+            name: Some(self.name.clone()),
+            error: if success {
+                None
+            } else {
+                Some(crate::types::ServiceError {
+                    service: self.name.clone(),
+                    message: result.message.unwrap_or_default(),
+                    status_code: None,
                 })
-            }
-        }
+            },
+        })
     }
     
     /// Post BEEF transaction(s)
     ///
     /// Reference: TS ARC.postBeef (lines 241-276)
+    ///
+    /// ARC accepts BEEF in the `rawTx` field; the result for the last
+    /// (primary) txid is applied to all txids.
     async fn post_beef(&self, beef: &[u8], txids: &[String]) -> ServiceResult<Vec<PostBeefResult>> {
-        // Convert BEEF to hex
-        let beef_hex = hex::encode(beef);
-        
         // Get last txid (primary transaction)
         let primary_txid = txids.last()
             .ok_or_else(|| ServiceError::InvalidParams("No txids provided".to_string()))?;
         
-        // Post the BEEF as raw tx
-        let result = self.post_tx_to_arc(&beef_hex, primary_txid).await?;
+        let result = match self.post_tx_to_arc(&hex::encode(beef)).await {
+            Ok((http_status, arc_response)) => {
+                BroadcastResult::from_arc_response(&self.name, http_status, &arc_response, primary_txid)
+            }
+            Err(e) => BroadcastResult::service_error(&self.name, primary_txid, e.to_string()),
+        };
         
-        // Build results for all txids
-        let mut results = Vec::new();
-        for txid in txids {
-            results.push(PostBeefResult {
+        let status = match result.status {
+            BroadcastStatus::Success => "success",
+            BroadcastStatus::DoubleSpend => "doubleSpend",
+            BroadcastStatus::InvalidTx => "invalidTx",
+            BroadcastStatus::ServiceError => "serviceError",
+        };
+        
+        Ok(txids.iter()
+            .map(|txid| PostBeefResult {
                 txid: txid.clone(),
-                status: if result.is_success() { "success" } else { "error" }.to_string(),
+                status: status.to_string(),
                 name: Some(self.name.clone()),
-                error: if result.is_success() {
+                error: if result.status == BroadcastStatus::Success {
                     None
                 } else {
                     Some(crate::types::ServiceError {
                         service: self.name.clone(),
-                        message: result.title.clone(),
-                        status_code: Some(result.status as u16),
+                        message: result.message.clone().unwrap_or_default(),
+                        status_code: None,
                     })
                 },
-            });
-        }
-        
-        Ok(results)
+            })
+            .collect())
     }
     
     /// Get status for multiple transactions
+    ///
+    /// Polls `GET /v1/tx/{txid}` for each txid; unknown or failed lookups
+    /// are reported as `Unknown`.
     async fn get_status_for_txids(&self, txids: &[String]) -> ServiceResult<GetStatusForTxidsResult> {
-        // ARC doesn't have a dedicated status endpoint
-        // This would typically query the transaction status via separate API calls
-        // For now, return a basic implementation
-        
         let mut statuses = Vec::new();
         for txid in txids {
-            statuses.push(TxStatus {
-                txid: txid.clone(),
-                status: TxStatusType::Unknown,
-                depth: None,
-            });
+            let result = self.get_tx_status(txid).await.ok();
+            statuses.push(Self::tx_status_from_result(txid, result.as_ref()));
         }
         
         Ok(GetStatusForTxidsResult {
@@ -284,4 +406,79 @@ mod tests {
         assert!(broadcaster.config.api_key.is_some());
         assert!(broadcaster.config.callback_url.is_some());
     }
+    
+    #[test]
+    fn test_request_headers() {
+        // TS Reference: ARC.requestHeaders
+        let config = ArcConfig {
+            api_key: Some("test-key".to_string()),
+            deployment_id: Some("wallet-toolbox-test".to_string()),
+            callback_url: Some("https://callback.example.com/arc-ingest".to_string()),
+            callback_token: Some("secret".to_string()),
+            headers: None,
+        };
+        let broadcaster = ArcBroadcaster::new("https://arc.example.com".to_string(), Some(config), None);
+        
+        let headers = broadcaster.request_headers().unwrap();
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers["xdeployment-id"], "wallet-toolbox-test");
+        assert_eq!(headers["authorization"], "Bearer test-key");
+        assert_eq!(headers["x-callbackurl"], "https://callback.example.com/arc-ingest");
+        assert_eq!(headers["x-callbacktoken"], "secret");
+    }
+    
+    #[test]
+    fn test_endpoint_request_headers() {
+        let taal = ArcEndpoint::taal(crate::types::Chain::Main).unwrap()
+            .with_api_key("taal-key")
+            .with_deployment_id("wallet-1");
+        let broadcaster = ArcBroadcaster::from_endpoint(&taal);
+        assert_eq!(broadcaster.name, "TAAL");
+        assert_eq!(broadcaster.url, "https://arc.taal.com");
+        let headers = broadcaster.request_headers().unwrap();
+        assert_eq!(headers["authorization"], "Bearer taal-key");
+        assert_eq!(headers["xdeployment-id"], "wallet-1");
+        
+        let gorilla = ArcEndpoint::gorilla_pool(crate::types::Chain::Test).unwrap();
+        let broadcaster = ArcBroadcaster::from_endpoint(&gorilla);
+        assert_eq!(broadcaster.name, "GorillaPool");
+        assert_eq!(broadcaster.url, "https://testnet.arc.gorillapool.io");
+        let headers = broadcaster.request_headers().unwrap();
+        assert!(!headers.contains_key("authorization"));
+        assert!(headers["xdeployment-id"].to_str().unwrap().starts_with("rs-sdk-"));
+        
+        assert!(ArcEndpoint::gorilla_pool(crate::types::Chain::Regtest).is_none());
+    }
+    
+    #[test]
+    fn test_request_headers_minimal() {
+        let broadcaster = ArcBroadcaster::new("https://arc.example.com".to_string(), None, None);
+        let headers = broadcaster.request_headers().unwrap();
+        // Default config always carries a generated deployment ID
+        assert_eq!(headers.len(), 2);
+        assert!(headers.contains_key("xdeployment-id"));
+        assert!(!headers.contains_key("authorization"));
+        assert!(!headers.contains_key("x-callbackurl"));
+    }
+    
+    #[test]
+    fn test_tx_status_from_result() {
+        let result = |tx_status| BroadcastResult {
+            tx_status: Some(tx_status),
+            ..BroadcastResult::service_error("ARC", "abc", "")
+        };
+        
+        let mined = ArcBroadcaster::tx_status_from_result("abc", Some(&result(ArcTxStatus::Mined)));
+        assert!(matches!(mined.status, TxStatusType::Mined));
+        
+        let seen = ArcBroadcaster::tx_status_from_result("abc", Some(&result(ArcTxStatus::SeenOnNetwork)));
+        assert!(matches!(seen.status, TxStatusType::Known));
+        
+        let rejected = ArcBroadcaster::tx_status_from_result("abc", Some(&result(ArcTxStatus::Rejected)));
+        assert!(matches!(rejected.status, TxStatusType::Unknown));
+        
+        let missing = ArcBroadcaster::tx_status_from_result("abc", None);
+        assert!(matches!(missing.status, TxStatusType::Unknown));
+        assert_eq!(missing.txid, "abc");
+    }
 }
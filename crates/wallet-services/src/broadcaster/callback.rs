@@ -0,0 +1,304 @@
+//! ARC Callback Listener
+//!
+//! **Reference**: ARC callback API (`X-CallbackUrl` / `X-CallbackToken`)
+//!
+//! Receives ARC status webhooks for broadcast transactions and updates the
+//! matching ProvenTxReq in storage, so server deployments learn about mined,
+//! rejected and double-spent transactions without polling.
+//!
+//! Requires the `arc-callback` feature.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+use wallet_storage::{
+    FindProvenTxReqsArgs, ProvenTxReqStatus, ProvenTxReqUpdates, StorageResult, WalletStorageProvider,
+};
+
+use crate::error::{ServiceError, ServiceResult};
+use super::types::{ArcResponse, ArcTxStatus};
+
+/// Storage shared between the listener and the rest of the wallet
+pub type SharedStorage = Arc<Mutex<Box<dyn WalletStorageProvider>>>;
+
+/// Listener configuration
+#[derive(Debug, Clone)]
+pub struct ArcCallbackConfig {
+    /// Address to bind
+    pub addr: SocketAddr,
+
+    /// Request path ARC posts to, e.g. `/arc-ingest`
+    pub path: String,
+
+    /// Token configured as `ArcConfig::callback_token`; ARC sends it back
+    /// as `Authorization: Bearer <token>`
+    pub callback_token: Option<String>,
+}
+
+/// Result of processing one callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArcCallbackOutcome {
+    /// The ProvenTxReq was moved to a new status
+    Updated {
+        txid: String,
+        status: ProvenTxReqStatus,
+    },
+    /// Status carries no actionable information, or the req is already past it
+    Ignored { txid: String },
+    /// No ProvenTxReq for this txid
+    UnknownTxid { txid: String },
+}
+
+/// Verify the `Authorization` header against the configured callback token
+///
+/// Accepts any request when no token is configured. Compares in constant
+/// time so response timing does not reveal how much of a guess matched.
+pub fn verify_callback_token(authorization: Option<&str>, expected: Option<&str>) -> bool {
+    match expected {
+        None => true,
+        Some(token) => authorization
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .is_some_and(|t| bool::from(t.trim().as_bytes().ct_eq(token.as_bytes()))),
+    }
+}
+
+/// ProvenTxReq status implied by an ARC transaction status
+///
+/// Mined transactions move to `callback` so `TaskCheckForProofs` acquires
+/// their proofs on its next run.
+pub fn req_status_for_arc_status(tx_status: ArcTxStatus) -> Option<ProvenTxReqStatus> {
+    match tx_status {
+        ArcTxStatus::Mined => Some(ProvenTxReqStatus::Callback),
+        ArcTxStatus::SeenOnNetwork | ArcTxStatus::AcceptedByNetwork => Some(ProvenTxReqStatus::Unmined),
+        ArcTxStatus::DoubleSpendAttempted => Some(ProvenTxReqStatus::DoubleSpend),
+        ArcTxStatus::Rejected => Some(ProvenTxReqStatus::Invalid),
+        _ => None,
+    }
+}
+
+/// Whether a req in status `current` may move to `next`
+///
+/// Proven reqs are never changed, and network acceptance never overrides a
+/// later mined notification.
+fn can_transition(current: ProvenTxReqStatus, next: ProvenTxReqStatus) -> bool {
+    match current {
+        ProvenTxReqStatus::Completed | ProvenTxReqStatus::Unconfirmed => false,
+        ProvenTxReqStatus::Callback => next != ProvenTxReqStatus::Unmined && next != current,
+        _ => next != current,
+    }
+}
+
+/// Apply one ARC callback to storage
+pub async fn process_arc_callback(
+    storage: &mut dyn WalletStorageProvider,
+    callback: &ArcResponse,
+) -> StorageResult<ArcCallbackOutcome> {
+    let txid = callback.txid.clone();
+    let Some(next) = callback.tx_status.and_then(req_status_for_arc_status) else {
+        return Ok(ArcCallbackOutcome::Ignored { txid });
+    };
+
+    let reqs = storage.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+        status: None,
+        since: None,
+        paged: None,
+        txids: Some(vec![txid.clone()]),
+    }).await?;
+    let Some(req) = reqs.into_iter().find(|r| r.txid == txid) else {
+        return Ok(ArcCallbackOutcome::UnknownTxid { txid });
+    };
+
+    if !can_transition(req.status, next) {
+        return Ok(ArcCallbackOutcome::Ignored { txid });
+    }
+
+    storage.update_proven_tx_req(req.proven_tx_req_id, &ProvenTxReqUpdates {
+        status: Some(next),
+        ..Default::default()
+    }).await?;
+    Ok(ArcCallbackOutcome::Updated { txid, status: next })
+}
+
+/// HTTP listener for ARC callbacks
+pub struct ArcCallbackListener {
+    config: Arc<ArcCallbackConfig>,
+    storage: SharedStorage,
+}
+
+impl ArcCallbackListener {
+    pub fn new(config: ArcCallbackConfig, storage: SharedStorage) -> Self {
+        Self {
+            config: Arc::new(config),
+            storage,
+        }
+    }
+
+    /// Serve callbacks until `shutdown` completes
+    pub async fn serve(self, shutdown: impl Future<Output = ()>) -> ServiceResult<()> {
+        let addr = self.config.addr;
+        let config = self.config;
+        let storage = self.storage;
+        let make_svc = make_service_fn(move |_conn| {
+            let config = config.clone();
+            let storage = storage.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let config = config.clone();
+                    let storage = storage.clone();
+                    async move { Ok::<_, Infallible>(handle_request(req, &config, &storage).await) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr)
+            .map_err(|e| ServiceError::Unavailable(format!("ARC callback listener bind {}: {}", addr, e)))?
+            .serve(make_svc)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(|e| ServiceError::Unavailable(format!("ARC callback listener: {}", e)))
+    }
+}
+
+/// Check method, path and token before touching storage
+fn check_request(req: &Request<Body>, config: &ArcCallbackConfig) -> Result<(), StatusCode> {
+    if req.uri().path() != config.path {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if req.method() != Method::POST {
+        return Err(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let authorization = req.headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    if !verify_callback_token(authorization, config.callback_token.as_deref()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap_or_default()
+}
+
+async fn handle_request(
+    req: Request<Body>,
+    config: &ArcCallbackConfig,
+    storage: &SharedStorage,
+) -> Response<Body> {
+    if let Err(status) = check_request(&req, config) {
+        return json_response(status, serde_json::json!({ "status": "error", "code": status.as_u16() }));
+    }
+
+    let callback: ArcResponse = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(callback) => callback,
+            Err(e) => {
+                return json_response(StatusCode::BAD_REQUEST, serde_json::json!({ "status": "error", "description": e.to_string() }));
+            }
+        },
+        Err(e) => {
+            return json_response(StatusCode::BAD_REQUEST, serde_json::json!({ "status": "error", "description": e.to_string() }));
+        }
+    };
+
+    let mut storage = storage.lock().await;
+    match process_arc_callback(storage.as_mut(), &callback).await {
+        Ok(_) => json_response(StatusCode::OK, serde_json::json!({ "status": "success" })),
+        Err(e) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            serde_json::json!({ "status": "error", "description": e.to_string() }),
+        ),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ArcCallbackConfig {
+        ArcCallbackConfig {
+            addr: ([127, 0, 0, 1], 0).into(),
+            path: "/arc-ingest".to_string(),
+            callback_token: Some("secret".to_string()),
+        }
+    }
+
+    fn request(method: Method, path: &str, authorization: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().method(method).uri(path);
+        if let Some(a) = authorization {
+            builder = builder.header("Authorization", a);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_callback_token() {
+        assert!(verify_callback_token(Some("Bearer secret"), Some("secret")));
+        assert!(!verify_callback_token(Some("Bearer wrong"), Some("secret")));
+        assert!(!verify_callback_token(Some("Bearer secre"), Some("secret")));
+        assert!(!verify_callback_token(Some("Bearer secrets"), Some("secret")));
+        assert!(!verify_callback_token(Some("secret"), Some("secret")));
+        assert!(!verify_callback_token(None, Some("secret")));
+        assert!(verify_callback_token(None, None));
+    }
+
+    #[test]
+    fn test_req_status_for_arc_status() {
+        assert_eq!(req_status_for_arc_status(ArcTxStatus::Mined), Some(ProvenTxReqStatus::Callback));
+        assert_eq!(req_status_for_arc_status(ArcTxStatus::SeenOnNetwork), Some(ProvenTxReqStatus::Unmined));
+        assert_eq!(req_status_for_arc_status(ArcTxStatus::DoubleSpendAttempted), Some(ProvenTxReqStatus::DoubleSpend));
+        assert_eq!(req_status_for_arc_status(ArcTxStatus::Rejected), Some(ProvenTxReqStatus::Invalid));
+        assert_eq!(req_status_for_arc_status(ArcTxStatus::Stored), None);
+    }
+
+    #[test]
+    fn test_can_transition() {
+        assert!(can_transition(ProvenTxReqStatus::Unmined, ProvenTxReqStatus::Callback));
+        assert!(can_transition(ProvenTxReqStatus::Sending, ProvenTxReqStatus::Unmined));
+        assert!(!can_transition(ProvenTxReqStatus::Callback, ProvenTxReqStatus::Unmined));
+        assert!(!can_transition(ProvenTxReqStatus::Completed, ProvenTxReqStatus::Invalid));
+        assert!(!can_transition(ProvenTxReqStatus::Unmined, ProvenTxReqStatus::Unmined));
+    }
+
+    #[test]
+    fn test_check_request() {
+        let config = config();
+        assert_eq!(check_request(&request(Method::POST, "/arc-ingest", Some("Bearer secret")), &config), Ok(()));
+        assert_eq!(
+            check_request(&request(Method::POST, "/arc-ingest", Some("Bearer wrong")), &config),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            check_request(&request(Method::GET, "/arc-ingest", Some("Bearer secret")), &config),
+            Err(StatusCode::METHOD_NOT_ALLOWED)
+        );
+        assert_eq!(
+            check_request(&request(Method::POST, "/other", Some("Bearer secret")), &config),
+            Err(StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_parse_callback_body() {
+        // ARC callback payload
+        let json = r#"{"timestamp":"2025-01-07T00:00:00Z","txid":"abc","txStatus":"MINED","blockHash":"00ff","blockHeight":880000,"merklePath":"fe"}"#;
+        let callback: ArcResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(callback.txid, "abc");
+        assert_eq!(callback.tx_status, Some(ArcTxStatus::Mined));
+        assert_eq!(callback.block_height, Some(880000));
+    }
+}
@@ -5,7 +5,11 @@
 //! Provides transaction broadcasting to the BSV network
 
 pub mod arc;
+#[cfg(feature = "arc-callback")]
+pub mod callback;
 pub mod types;
 
 pub use arc::ArcBroadcaster;
+#[cfg(feature = "arc-callback")]
+pub use callback::{ArcCallbackConfig, ArcCallbackListener, ArcCallbackOutcome};
 pub use types::*;
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::types::Chain;
+
 /// ARC configuration
 /// Reference: TypeScript ArcConfig
 #[derive(Debug, Clone)]
@@ -47,9 +49,227 @@ impl ArcConfig {
     }
 }
 
+/// One ARC deployment, e.g. a miner's public endpoint
+///
+/// Each endpoint carries its own API key and deployment ID; the name is
+/// reported in broadcast results and provider statistics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArcEndpoint {
+    /// Service name, e.g. "TAAL"
+    pub name: String,
+
+    /// Base URL of the ARC service
+    pub url: String,
+
+    /// Authentication token for this endpoint
+    pub api_key: Option<String>,
+
+    /// Deployment ID for this endpoint; a generated one when unset
+    pub deployment_id: Option<String>,
+}
+
+impl ArcEndpoint {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            api_key: None,
+            deployment_id: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_deployment_id(mut self, deployment_id: impl Into<String>) -> Self {
+        self.deployment_id = Some(deployment_id.into());
+        self
+    }
+
+    /// TAAL's ARC for `chain`; none on regtest
+    pub fn taal(chain: Chain) -> Option<Self> {
+        chain.default_arc_url().map(|url| Self::new("TAAL", url))
+    }
+
+    /// GorillaPool's ARC for `chain`; none on regtest
+    pub fn gorilla_pool(chain: Chain) -> Option<Self> {
+        let url = match chain {
+            Chain::Main => "https://arc.gorillapool.io",
+            Chain::Test => "https://testnet.arc.gorillapool.io",
+            Chain::Regtest => return None,
+        };
+        Some(Self::new("GorillaPool", url))
+    }
+
+    /// Configuration for requests to this endpoint
+    pub fn config(&self) -> ArcConfig {
+        let defaults = ArcConfig::default();
+        ArcConfig {
+            api_key: self.api_key.clone(),
+            deployment_id: self.deployment_id.clone().or(defaults.deployment_id),
+            ..defaults
+        }
+    }
+}
+
+/// ARC transaction status (`txStatus`)
+/// Reference: ARC API TransactionStatus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ArcTxStatus {
+    Queued,
+    Received,
+    Stored,
+    AnnouncedToNetwork,
+    RequestedByNetwork,
+    SentToNetwork,
+    AcceptedByNetwork,
+    SeenInOrphanMempool,
+    SeenOnNetwork,
+    DoubleSpendAttempted,
+    Rejected,
+    Mined,
+    MinedInStaleBlock,
+    /// Any status not known to this client
+    #[serde(other)]
+    Unknown,
+}
+
+impl ArcTxStatus {
+    /// Whether the network has accepted the transaction
+    pub fn is_accepted(&self) -> bool {
+        matches!(
+            self,
+            ArcTxStatus::AcceptedByNetwork
+                | ArcTxStatus::SeenOnNetwork
+                | ArcTxStatus::Mined
+                | ArcTxStatus::MinedInStaleBlock
+        )
+    }
+}
+
+/// Outcome of a broadcast
+/// Reference: TypeScript PostTxResultForTxid (status, doubleSpend, serviceError)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BroadcastStatus {
+    /// Accepted (or already known) by the service
+    Success,
+    /// Conflicts with a transaction already seen by the network
+    DoubleSpend,
+    /// Rejected as invalid; resending will not help
+    InvalidTx,
+    /// Service failure; the broadcast may be retried
+    ServiceError,
+}
+
+/// Typed result of an ARC broadcast or status query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastResult {
+    /// Transaction ID
+    pub txid: String,
+    
+    /// Broadcast outcome
+    pub status: BroadcastStatus,
+    
+    /// ARC transaction status, if reported
+    #[serde(rename = "txStatus", skip_serializing_if = "Option::is_none")]
+    pub tx_status: Option<ArcTxStatus>,
+    
+    /// Competing transactions (double spend)
+    #[serde(rename = "competingTxs", default, skip_serializing_if = "Vec::is_empty")]
+    pub competing_txs: Vec<String>,
+    
+    /// Block hash (if mined)
+    #[serde(rename = "blockHash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<String>,
+    
+    /// Block height (if mined)
+    #[serde(rename = "blockHeight", skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u32>,
+    
+    /// Merkle path (BUMP hex, if mined)
+    #[serde(rename = "merklePath", skip_serializing_if = "Option::is_none")]
+    pub merkle_path: Option<String>,
+    
+    /// Error or extra information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    
+    /// Service name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl BroadcastResult {
+    /// Map an ARC HTTP status and response body to a typed result
+    ///
+    /// Reference: TS ARC.postRawTx response handling
+    ///
+    /// - `DOUBLE_SPEND_ATTEMPTED` / `SEEN_IN_ORPHAN_MEMPOOL` or competing txs => DoubleSpend
+    /// - `REJECTED` or ARC 4xx validation errors (e.g. 461 malformed, 463 BUMP) => InvalidTx
+    /// - 5xx, auth and rate-limit errors => ServiceError
+    pub fn from_arc_response(name: &str, http_status: u16, response: &ArcResponse, txid: &str) -> Self {
+        let status = match response.tx_status {
+            Some(ArcTxStatus::DoubleSpendAttempted) | Some(ArcTxStatus::SeenInOrphanMempool) => {
+                BroadcastStatus::DoubleSpend
+            }
+            _ if response.is_double_spend() => BroadcastStatus::DoubleSpend,
+            Some(ArcTxStatus::Rejected) => BroadcastStatus::InvalidTx,
+            _ => match http_status {
+                200..=299 | 409 => BroadcastStatus::Success,
+                401 | 403 | 429 => BroadcastStatus::ServiceError,
+                400..=499 => BroadcastStatus::InvalidTx,
+                _ => BroadcastStatus::ServiceError,
+            },
+        };
+        
+        let message = match status {
+            BroadcastStatus::Success => response.extra_info.clone().filter(|s| !s.is_empty()),
+            _ => Some(
+                [Some(response.title.as_str()), response.detail.as_deref(), response.extra_info.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(": "),
+            ),
+        };
+        
+        Self {
+            txid: if response.txid.is_empty() { txid.to_string() } else { response.txid.clone() },
+            status,
+            tx_status: response.tx_status,
+            competing_txs: response.competing_txs.clone().unwrap_or_default(),
+            block_hash: response.block_hash.clone(),
+            block_height: response.block_height,
+            merkle_path: response.merkle_path.clone(),
+            message,
+            name: Some(name.to_string()),
+        }
+    }
+    
+    /// Result for a request that never reached ARC
+    pub fn service_error(name: &str, txid: &str, message: impl Into<String>) -> Self {
+        Self {
+            txid: txid.to_string(),
+            status: BroadcastStatus::ServiceError,
+            tx_status: None,
+            competing_txs: Vec::new(),
+            block_hash: None,
+            block_height: None,
+            merkle_path: None,
+            message: Some(message.into()),
+            name: Some(name.to_string()),
+        }
+    }
+}
+
 /// ARC API response
 /// Reference: TypeScript ARC response structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ArcResponse {
     /// Block hash (if mined)
     #[serde(rename = "blockHash", skip_serializing_if = "Option::is_none")]
@@ -64,17 +284,33 @@ pub struct ArcResponse {
     pub extra_info: Option<String>,
     
     /// Status
+    #[serde(default)]
     pub status: i32,
     
     /// Timestamp
+    #[serde(default)]
     pub timestamp: String,
     
     /// Title (status description)
+    #[serde(default)]
     pub title: String,
     
     /// Transaction ID
+    #[serde(default)]
     pub txid: String,
     
+    /// ARC transaction status
+    #[serde(rename = "txStatus", skip_serializing_if = "Option::is_none")]
+    pub tx_status: Option<ArcTxStatus>,
+    
+    /// Merkle path (BUMP hex, if mined)
+    #[serde(rename = "merklePath", skip_serializing_if = "Option::is_none")]
+    pub merkle_path: Option<String>,
+    
+    /// Error detail (RFC 7807 error responses)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    
     /// Txid (duplicate field for compatibility)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub txid_field: Option<String>,
@@ -119,6 +355,7 @@ mod tests {
             txid: "abc123".to_string(),
             txid_field: None,
             competing_txs: None,
+            ..Default::default()
         };
         
         assert!(response.is_success());
@@ -137,9 +374,78 @@ mod tests {
             txid: "abc123".to_string(),
             txid_field: None,
             competing_txs: Some(vec!["def456".to_string()]),
+            ..Default::default()
         };
         
         assert!(response.is_success()); // 409 is still "success" (already in mempool)
         assert!(response.is_double_spend());
     }
+    
+    #[test]
+    fn test_arc_tx_status_serde() {
+        let status: ArcTxStatus = serde_json::from_str("\"SEEN_ON_NETWORK\"").unwrap();
+        assert_eq!(status, ArcTxStatus::SeenOnNetwork);
+        assert!(status.is_accepted());
+        
+        let status: ArcTxStatus = serde_json::from_str("\"DOUBLE_SPEND_ATTEMPTED\"").unwrap();
+        assert_eq!(status, ArcTxStatus::DoubleSpendAttempted);
+        
+        let status: ArcTxStatus = serde_json::from_str("\"SOMETHING_NEW\"").unwrap();
+        assert_eq!(status, ArcTxStatus::Unknown);
+    }
+    
+    #[test]
+    fn test_arc_response_parse_success() {
+        let json = r#"{"blockHash":"","blockHeight":0,"extraInfo":"","status":200,"timestamp":"2025-01-07T00:00:00Z","title":"OK","txStatus":"SEEN_ON_NETWORK","txid":"abc"}"#;
+        let response: ArcResponse = serde_json::from_str(json).unwrap();
+        let result = BroadcastResult::from_arc_response("ARC", 200, &response, "abc");
+        assert_eq!(result.status, BroadcastStatus::Success);
+        assert_eq!(result.tx_status, Some(ArcTxStatus::SeenOnNetwork));
+        assert_eq!(result.message, None);
+    }
+    
+    #[test]
+    fn test_broadcast_result_double_spend() {
+        // TS Reference: txStatus DOUBLE_SPEND_ATTEMPTED => doubleSpend with competingTxs
+        let response = ArcResponse {
+            status: 200,
+            txid: "abc".to_string(),
+            tx_status: Some(ArcTxStatus::DoubleSpendAttempted),
+            competing_txs: Some(vec!["def".to_string()]),
+            ..Default::default()
+        };
+        let result = BroadcastResult::from_arc_response("ARC", 200, &response, "abc");
+        assert_eq!(result.status, BroadcastStatus::DoubleSpend);
+        assert_eq!(result.competing_txs, vec!["def".to_string()]);
+    }
+    
+    #[test]
+    fn test_broadcast_result_rejected_and_errors() {
+        let rejected = ArcResponse {
+            title: "Rejected".to_string(),
+            tx_status: Some(ArcTxStatus::Rejected),
+            extra_info: Some("bad script".to_string()),
+            ..Default::default()
+        };
+        let result = BroadcastResult::from_arc_response("ARC", 200, &rejected, "abc");
+        assert_eq!(result.status, BroadcastStatus::InvalidTx);
+        assert_eq!(result.txid, "abc");
+        assert_eq!(result.message.as_deref(), Some("Rejected: bad script"));
+        
+        // RFC 7807 error body for a malformed transaction
+        let json = r#"{"type":"https://arc.bitcoinsv.com/errors/461","title":"Malformed transaction","status":461,"detail":"Transaction is malformed"}"#;
+        let malformed: ArcResponse = serde_json::from_str(json).unwrap();
+        let result = BroadcastResult::from_arc_response("ARC", 461, &malformed, "abc");
+        assert_eq!(result.status, BroadcastStatus::InvalidTx);
+        
+        let unavailable = ArcResponse::default();
+        assert_eq!(
+            BroadcastResult::from_arc_response("ARC", 503, &unavailable, "abc").status,
+            BroadcastStatus::ServiceError
+        );
+        assert_eq!(
+            BroadcastResult::from_arc_response("ARC", 401, &unavailable, "abc").status,
+            BroadcastStatus::ServiceError
+        );
+    }
 }
@@ -7,7 +7,7 @@
 use async_trait::async_trait;
 use reqwest::Client;
 use crate::error::{ServiceError, ServiceResult};
-use crate::traits::ChainTracker;
+use crate::traits::{ChainHeaderSource, ChainTracker};
 use crate::types::{Chain, MerklePath};
 use super::types::{BlockHeader, ChaintracksInfo, FetchStatus};
 
@@ -124,6 +124,25 @@ impl ChaintracksClient {
         matches!(error, ServiceError::Timeout | ServiceError::Http(_))
     }
     
+    /// Chain tracked by the service
+    ///
+    /// Reference: TS ChaintracksServiceClient.getChain
+    pub async fn get_chain(&self) -> ServiceResult<Chain> {
+        self.get_json("/getChain").await
+    }
+    
+    /// Verify the service tracks the chain this client was created for
+    pub async fn verify_chain(&self) -> ServiceResult<()> {
+        let chain = self.get_chain().await?;
+        if chain != self.chain {
+            return Err(ServiceError::InvalidResponse(format!(
+                "Chaintracks service tracks {:?}, expected {:?}",
+                chain, self.chain
+            )));
+        }
+        Ok(())
+    }
+    
     /// Get current blockchain height
     ///
     /// Reference: TS ChaintracksServiceClient.getPresentHeight
@@ -145,6 +164,14 @@ impl ChaintracksClient {
         self.get_json_or_none(&format!("/findHeaderHexForHeight?height={}", height)).await
     }
     
+    /// Get up to `count` serialized 80-byte headers starting at `height`
+    ///
+    /// Reference: TS ChaintracksServiceClient.getHeaders
+    pub async fn get_headers(&self, height: u32, count: u32) -> ServiceResult<Vec<u8>> {
+        let hex_headers: String = self.get_json(&format!("/getHeaders?height={}&count={}", height, count)).await?;
+        parse_headers_hex(&hex_headers)
+    }
+    
     /// Find header for block hash
     ///
     /// Reference: TS ChaintracksServiceClient.findHeaderForBlockHash
@@ -174,6 +201,30 @@ impl ChaintracksClient {
     }
 }
 
+/// Decode concatenated 80-byte headers returned by `/getHeaders`
+fn parse_headers_hex(hex_headers: &str) -> ServiceResult<Vec<u8>> {
+    let bytes = hex::decode(hex_headers)
+        .map_err(|e| ServiceError::InvalidResponse(format!("headers are not hex: {}", e)))?;
+    if bytes.len() % 80 != 0 {
+        return Err(ServiceError::InvalidResponse(format!(
+            "headers length {} is not a multiple of 80",
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+#[async_trait]
+impl ChainHeaderSource for ChaintracksClient {
+    async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader> {
+        ChaintracksClient::find_chain_tip_header(self).await
+    }
+    
+    async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+        ChaintracksClient::find_header_for_height(self, height).await
+    }
+}
+
 #[async_trait]
 impl ChainTracker for ChaintracksClient {
     /// Check if merkle root is valid for height
@@ -191,11 +242,7 @@ impl ChainTracker for ChaintracksClient {
     async fn get_header_for_height(&self, height: u32) -> ServiceResult<Vec<u8>> {
         let header = self.find_header_for_height(height).await?;
         match header {
-            Some(_h) => {
-                // TODO: Convert BlockHeader to raw bytes
-                // For now, return placeholder
-                Ok(vec![])
-            }
+            Some(h) => h.to_bytes().map_err(ServiceError::InvalidResponse),
             None => Err(ServiceError::BlockNotFound(height)),
         }
     }
@@ -243,6 +290,14 @@ mod tests {
         assert!(!client.is_transient_error(&ServiceError::InvalidParams("test".to_string())));
     }
     
+    #[test]
+    fn test_parse_headers_hex() {
+        assert_eq!(parse_headers_hex(&"00".repeat(160)).unwrap().len(), 160);
+        assert!(parse_headers_hex("").unwrap().is_empty());
+        assert!(parse_headers_hex(&"00".repeat(79)).is_err());
+        assert!(parse_headers_hex("zz").is_err());
+    }
+    
     // Integration tests would require a real Chaintracks service
     // or mock server. For now, we have unit tests only.
 }
@@ -0,0 +1,579 @@
+//! Local Block Header Store
+//!
+//! **Reference**: TypeScript `src/services/chaintracker/chaintracks/Chaintracks.ts` (local storage mode)
+//!
+//! Persists block headers in SQLite and validates each one before accepting it:
+//! the header must hash to its claimed hash, meet its own proof-of-work target,
+//! and link to a stored parent. The branch with the most cumulative work is the
+//! active chain, so reorgs are followed as soon as a heavier branch is stored.
+//! Serving `is_valid_root_for_height` from the store lets wallets verify BEEF
+//! offline.
+//!
+//! Difficulty adjustment rules are not enforced; headers are only as
+//! trustworthy as the source they were synced from plus their own PoW.
+//!
+//! Requires the `header-store` feature.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::{ChainHeaderSource, ChainTracker};
+use crate::types::{Chain, MerklePath};
+use crate::utxo::bitails::double_sha256_txid;
+use super::types::BlockHeader;
+
+/// SQL for the header store schema
+const HEADER_STORE_SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS block_headers (
+    headerId INTEGER PRIMARY KEY AUTOINCREMENT,
+    height INTEGER NOT NULL,
+    hash TEXT NOT NULL UNIQUE,
+    previousHash TEXT NOT NULL,
+    merkleRoot TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    bits INTEGER NOT NULL,
+    nonce INTEGER NOT NULL,
+    chainWork TEXT NOT NULL,
+    isActive INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_headers_height_isActive ON block_headers(height, isActive);
+CREATE INDEX IF NOT EXISTS idx_block_headers_previousHash ON block_headers(previousHash);
+"#;
+
+const HEADER_COLUMNS: &str = "height, hash, previousHash, merkleRoot, version, time, bits, nonce, chainWork";
+
+/// Result of storing one header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertHeaderResult {
+    /// Header was already stored
+    Duplicate,
+    /// Header extends the active chain
+    NewTip,
+    /// Header stored on a branch with less work than the active chain
+    SideBranch,
+    /// Header's branch now has the most work and became the active chain
+    Reorg {
+        /// Active headers replaced, counted back from `old_tip`
+        depth: u32,
+        old_tip: BlockHeader,
+        new_tip: BlockHeader,
+    },
+}
+
+/// Compute the block hash (reversed double SHA-256, hex) of a serialized header
+pub fn block_hash(header_bytes: &[u8]) -> String {
+    double_sha256_txid(header_bytes)
+}
+
+/// Expand compact difficulty `bits` to a 32-byte big-endian target
+pub fn target_from_bits(bits: u32) -> ServiceResult<[u8; 32]> {
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x00ff_ffff;
+    if mantissa & 0x0080_0000 != 0 || mantissa == 0 {
+        return Err(ServiceError::InvalidParams(format!("invalid difficulty bits {:08x}", bits)));
+    }
+
+    // Mantissa byte i lands at index 32 - exponent + i; bytes past the end
+    // are shifted out, bytes before the start overflow 256 bits.
+    let mut target = [0u8; 32];
+    for (i, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+        match (32 + i).checked_sub(exponent) {
+            Some(index) if index < 32 => target[index] = *byte,
+            Some(_) => {}
+            None if *byte != 0 => {
+                return Err(ServiceError::InvalidParams(format!("difficulty bits {:08x} overflow", bits)));
+            }
+            None => {}
+        }
+    }
+    Ok(target)
+}
+
+/// Approximate work represented by a block with difficulty `bits` (2^256 / target)
+fn block_work(bits: u32) -> u128 {
+    let exponent = (bits >> 24) as i32;
+    let mantissa = (bits & 0x007f_ffff).max(1) as u128;
+    let shift = (256 - 8 * (exponent - 3)).clamp(0, 127);
+    ((1u128 << shift) / mantissa).max(1)
+}
+
+fn format_work(work: u128) -> String {
+    format!("{:032x}", work)
+}
+
+fn parse_work(work: &str) -> ServiceResult<u128> {
+    u128::from_str_radix(work, 16)
+        .map_err(|e| ServiceError::InvalidResponse(format!("invalid chain work {}: {}", work, e)))
+}
+
+/// Check that `header` hashes to its claimed hash and meets its PoW target
+pub fn validate_header_pow(header: &BlockHeader) -> ServiceResult<()> {
+    let bytes = header.to_bytes().map_err(ServiceError::InvalidParams)?;
+    let hash = block_hash(&bytes);
+    if hash != header.hash {
+        return Err(ServiceError::InvalidParams(format!(
+            "header at height {} hashes to {}, not {}",
+            header.height, hash, header.hash
+        )));
+    }
+
+    let target = target_from_bits(header.bits)?;
+    let hash_bytes = hex::decode(&hash).map_err(|e| ServiceError::InvalidParams(e.to_string()))?;
+    if hash_bytes.as_slice() > target.as_slice() {
+        return Err(ServiceError::InvalidParams(format!(
+            "header {} does not meet its proof-of-work target",
+            hash
+        )));
+    }
+    Ok(())
+}
+
+fn db_error(e: rusqlite::Error) -> ServiceError {
+    ServiceError::Storage(e.to_string())
+}
+
+/// Stored header with its cumulative chain work
+struct StoredHeader {
+    header: BlockHeader,
+    chain_work: u128,
+}
+
+impl StoredHeader {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<(BlockHeader, String)> {
+        Ok((
+            BlockHeader {
+                height: row.get(0)?,
+                hash: row.get(1)?,
+                previous_hash: row.get(2)?,
+                merkle_root: row.get(3)?,
+                version: row.get(4)?,
+                time: row.get(5)?,
+                bits: row.get(6)?,
+                nonce: row.get(7)?,
+            },
+            row.get(8)?,
+        ))
+    }
+}
+
+/// SQLite-backed block header store
+pub struct HeaderStore {
+    chain: Chain,
+    conn: Mutex<Connection>,
+}
+
+impl HeaderStore {
+    /// Open (or create) a header store at `path`
+    pub fn open(path: impl AsRef<Path>, chain: Chain) -> ServiceResult<Self> {
+        Self::with_connection(Connection::open(path).map_err(db_error)?, chain)
+    }
+
+    /// Open a header store that lives only in memory
+    pub fn open_in_memory(chain: Chain) -> ServiceResult<Self> {
+        Self::with_connection(Connection::open_in_memory().map_err(db_error)?, chain)
+    }
+
+    fn with_connection(conn: Connection, chain: Chain) -> ServiceResult<Self> {
+        conn.execute_batch(HEADER_STORE_SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            chain,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Chain whose headers are stored
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn query_one(conn: &Connection, where_clause: &str, params: &[&dyn rusqlite::ToSql]) -> ServiceResult<Option<StoredHeader>> {
+        let row = conn
+            .query_row(
+                &format!("SELECT {} FROM block_headers WHERE {}", HEADER_COLUMNS, where_clause),
+                params,
+                StoredHeader::from_row,
+            )
+            .optional()
+            .map_err(db_error)?;
+        row.map(|(header, work)| Ok(StoredHeader { header, chain_work: parse_work(&work)? }))
+            .transpose()
+    }
+
+    fn tip(conn: &Connection) -> ServiceResult<Option<StoredHeader>> {
+        Self::query_one(conn, "isActive = 1 ORDER BY height DESC LIMIT 1", &[])
+    }
+
+    fn by_hash(conn: &Connection, hash: &str) -> ServiceResult<Option<StoredHeader>> {
+        Self::query_one(conn, "hash = ?", &[&hash])
+    }
+
+    /// Number of stored headers, including side branches
+    pub fn header_count(&self) -> ServiceResult<u64> {
+        self.lock()
+            .query_row("SELECT COUNT(*) FROM block_headers", [], |row| row.get(0))
+            .map_err(db_error)
+    }
+
+    /// Active chain tip, None while the store is empty
+    pub fn find_chain_tip(&self) -> ServiceResult<Option<BlockHeader>> {
+        Ok(Self::tip(&self.lock())?.map(|s| s.header))
+    }
+
+    /// Active-chain header at `height`
+    pub fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+        Ok(Self::query_one(&self.lock(), "height = ? AND isActive = 1", &[&height])?.map(|s| s.header))
+    }
+
+    /// Stored header with `hash`, on any branch
+    pub fn find_header_for_block_hash(&self, hash: &str) -> ServiceResult<Option<BlockHeader>> {
+        Ok(Self::by_hash(&self.lock(), hash)?.map(|s| s.header))
+    }
+
+    /// Validate and store one header
+    ///
+    /// The first header stored becomes the anchor of the chain and is trusted
+    /// as-is beyond its own PoW; every later header must link to a stored parent.
+    pub fn insert_header(&self, header: &BlockHeader) -> ServiceResult<InsertHeaderResult> {
+        let mut conn = self.lock();
+        if Self::by_hash(&conn, &header.hash)?.is_some() {
+            return Ok(InsertHeaderResult::Duplicate);
+        }
+        validate_header_pow(header)?;
+
+        let tip = Self::tip(&conn)?;
+        let parent = Self::by_hash(&conn, &header.previous_hash)?;
+        let chain_work = match (&tip, &parent) {
+            (None, _) => block_work(header.bits),
+            (Some(_), None) => {
+                return Err(ServiceError::InvalidParams(format!(
+                    "header {} at height {} does not connect to a stored header",
+                    header.hash, header.height
+                )));
+            }
+            (Some(_), Some(parent)) => {
+                if header.height != parent.header.height + 1 {
+                    return Err(ServiceError::InvalidParams(format!(
+                        "header {} has height {} but its parent is at height {}",
+                        header.hash, header.height, parent.header.height
+                    )));
+                }
+                parent.chain_work.saturating_add(block_work(header.bits))
+            }
+        };
+
+        let tx = conn.transaction().map_err(db_error)?;
+        let extends_tip = tip.as_ref().is_none_or(|t| t.header.hash == header.previous_hash);
+        tx.execute(
+            &format!("INSERT INTO block_headers ({}, isActive) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", HEADER_COLUMNS),
+            params![
+                header.height,
+                header.hash,
+                header.previous_hash,
+                header.merkle_root,
+                header.version,
+                header.time,
+                header.bits,
+                header.nonce,
+                format_work(chain_work),
+                extends_tip,
+            ],
+        ).map_err(db_error)?;
+
+        let result = match tip {
+            _ if extends_tip => InsertHeaderResult::NewTip,
+            Some(tip) if chain_work > tip.chain_work => {
+                let ancestor_height = Self::activate_branch(&tx, header)?;
+                InsertHeaderResult::Reorg {
+                    depth: tip.header.height - ancestor_height,
+                    old_tip: tip.header,
+                    new_tip: header.clone(),
+                }
+            }
+            _ => InsertHeaderResult::SideBranch,
+        };
+        tx.commit().map_err(db_error)?;
+        Ok(result)
+    }
+
+    /// Make the branch ending at `new_tip` active, returning the height of the
+    /// common ancestor with the previously active chain
+    fn activate_branch(tx: &rusqlite::Transaction<'_>, new_tip: &BlockHeader) -> ServiceResult<u32> {
+        let mut branch = Vec::new();
+        let mut current = new_tip.clone();
+        let ancestor_height = loop {
+            let active: bool = tx
+                .query_row("SELECT isActive FROM block_headers WHERE hash = ?", [&current.hash], |row| row.get(0))
+                .map_err(db_error)?;
+            if active {
+                break current.height;
+            }
+            let previous_hash = current.previous_hash.clone();
+            branch.push(current.hash);
+            current = Self::by_hash(tx, &previous_hash)?
+                .ok_or_else(|| ServiceError::Storage(format!("missing stored header {}", previous_hash)))?
+                .header;
+        };
+
+        tx.execute("UPDATE block_headers SET isActive = 0 WHERE isActive = 1 AND height > ?", [ancestor_height])
+            .map_err(db_error)?;
+        for hash in &branch {
+            tx.execute("UPDATE block_headers SET isActive = 1 WHERE hash = ?", [hash])
+                .map_err(db_error)?;
+        }
+        Ok(ancestor_height)
+    }
+
+    /// Fetch headers from `source` until the store reaches its chain tip
+    ///
+    /// An empty store is anchored `max_headers` below the source tip. When the
+    /// source has reorganized, sync steps back to the fork point first.
+    /// Returns the number of headers stored.
+    pub async fn sync_from(&self, source: &dyn ChainHeaderSource, max_headers: u32) -> ServiceResult<u32> {
+        let remote_tip = source.find_chain_tip_header().await?;
+        let mut height = match self.find_chain_tip()? {
+            Some(tip) => tip.height + 1,
+            None => (remote_tip.height + 1).saturating_sub(max_headers.max(1)),
+        };
+
+        let mut stored = 0;
+        let mut steps = 0;
+        while height <= remote_tip.height && stored < max_headers {
+            steps += 1;
+            if steps > max_headers.saturating_mul(2) {
+                break;
+            }
+
+            let header = source.find_header_for_height(height).await?
+                .ok_or(ServiceError::BlockNotFound(height))?;
+            let connects = self.header_count()? == 0 || self.find_header_for_block_hash(&header.previous_hash)?.is_some();
+            if !connects {
+                // Step back towards the fork point
+                height = height.checked_sub(1).ok_or(ServiceError::BlockNotFound(0))?;
+                continue;
+            }
+
+            if self.insert_header(&header)? != InsertHeaderResult::Duplicate {
+                stored += 1;
+            }
+            height += 1;
+        }
+        Ok(stored)
+    }
+}
+
+#[async_trait]
+impl ChainHeaderSource for HeaderStore {
+    async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader> {
+        self.find_chain_tip()?
+            .ok_or_else(|| ServiceError::Unavailable("header store is empty".to_string()))
+    }
+
+    async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+        HeaderStore::find_header_for_height(self, height)
+    }
+}
+
+#[async_trait]
+impl ChainTracker for HeaderStore {
+    /// Check if merkle root is valid for height on the active chain
+    ///
+    /// Reference: TS ChainTracker.isValidRootForHeight
+    async fn is_valid_root_for_height(&self, root: &str, height: u32) -> ServiceResult<bool> {
+        Ok(HeaderStore::find_header_for_height(self, height)?.is_some_and(|h| h.merkle_root == root))
+    }
+
+    /// Get serialized 80-byte header for block height
+    async fn get_header_for_height(&self, height: u32) -> ServiceResult<Vec<u8>> {
+        let header = HeaderStore::find_header_for_height(self, height)?
+            .ok_or(ServiceError::BlockNotFound(height))?;
+        header.to_bytes().map_err(ServiceError::InvalidResponse)
+    }
+
+    /// Height of the active chain tip
+    async fn get_height(&self) -> ServiceResult<u32> {
+        Ok(self.find_chain_tip_header().await?.height)
+    }
+
+    /// Get merkle path for transaction
+    async fn get_merkle_path(&self, _txid: &str) -> ServiceResult<MerklePath> {
+        Err(ServiceError::InvalidParams("Merkle path not supported by HeaderStore".to_string()))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Regtest difficulty; about every other nonce meets the target
+    const EASY_BITS: u32 = 0x207f_ffff;
+
+    fn genesis() -> BlockHeader {
+        BlockHeader {
+            height: 0,
+            hash: "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f".to_string(),
+            previous_hash: "00".repeat(32),
+            merkle_root: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b".to_string(),
+            time: 1231006505,
+            bits: 0x1d00ffff,
+            nonce: 2083236893,
+            version: 1,
+        }
+    }
+
+    /// Mine a header on top of `parent`; `tag` varies the merkle root between branches
+    fn mine(parent: &BlockHeader, tag: u8) -> BlockHeader {
+        let mut header = BlockHeader {
+            height: parent.height + 1,
+            hash: String::new(),
+            previous_hash: parent.hash.clone(),
+            merkle_root: hex::encode([tag; 32]),
+            time: parent.time + 600,
+            bits: EASY_BITS,
+            nonce: 0,
+            version: 1,
+        };
+        loop {
+            header.hash = block_hash(&header.to_bytes().unwrap());
+            if validate_header_pow(&header).is_ok() {
+                return header;
+            }
+            header.nonce += 1;
+        }
+    }
+
+    fn mine_chain(parent: &BlockHeader, len: usize, tag: u8) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::new();
+        for _ in 0..len {
+            let next = mine(headers.last().unwrap_or(parent), tag);
+            headers.push(next);
+        }
+        headers
+    }
+
+    #[test]
+    fn test_target_from_bits() {
+        let target = target_from_bits(0x1d00ffff).unwrap();
+        assert_eq!(hex::encode(target), format!("00000000ffff{}", "00".repeat(26)));
+        let target = target_from_bits(EASY_BITS).unwrap();
+        assert_eq!(hex::encode(target), format!("7fffff{}", "00".repeat(29)));
+        assert!(target_from_bits(0x1d800000).is_err());
+        assert!(block_work(0x1d00ffff) > block_work(EASY_BITS));
+    }
+
+    #[test]
+    fn test_validate_genesis_pow() {
+        let header = genesis();
+        assert!(validate_header_pow(&header).is_ok());
+
+        let mut tampered = header.clone();
+        tampered.nonce += 1;
+        assert!(validate_header_pow(&tampered).is_err());
+        tampered.hash = block_hash(&tampered.to_bytes().unwrap());
+        assert!(validate_header_pow(&tampered).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_insert_extends_chain() {
+        let store = HeaderStore::open_in_memory(Chain::Main).unwrap();
+        let anchor = genesis();
+        assert_eq!(store.insert_header(&anchor).unwrap(), InsertHeaderResult::NewTip);
+        for header in mine_chain(&anchor, 3, 1) {
+            assert_eq!(store.insert_header(&header).unwrap(), InsertHeaderResult::NewTip);
+        }
+        assert_eq!(store.insert_header(&anchor).unwrap(), InsertHeaderResult::Duplicate);
+
+        assert_eq!(store.get_height().await.unwrap(), 3);
+        assert!(store.is_valid_root_for_height(&hex::encode([1u8; 32]), 2).await.unwrap());
+        assert!(!store.is_valid_root_for_height(&hex::encode([2u8; 32]), 2).await.unwrap());
+        assert_eq!(store.get_header_for_height(0).await.unwrap(), anchor.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_insert_rejects_disconnected_header() {
+        let store = HeaderStore::open_in_memory(Chain::Main).unwrap();
+        let anchor = genesis();
+        store.insert_header(&anchor).unwrap();
+
+        let orphan = mine(&mine(&anchor, 1), 1);
+        assert!(store.insert_header(&orphan).is_err());
+
+        let mut wrong_height = mine(&anchor, 1);
+        wrong_height.height = 5;
+        assert!(store.insert_header(&wrong_height).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heavier_branch_reorgs() {
+        let store = HeaderStore::open_in_memory(Chain::Main).unwrap();
+        let anchor = genesis();
+        store.insert_header(&anchor).unwrap();
+        let main = mine_chain(&anchor, 2, 1);
+        for header in &main {
+            store.insert_header(header).unwrap();
+        }
+
+        let branch = mine_chain(&anchor, 3, 2);
+        assert_eq!(store.insert_header(&branch[0]).unwrap(), InsertHeaderResult::SideBranch);
+        assert_eq!(store.insert_header(&branch[1]).unwrap(), InsertHeaderResult::SideBranch);
+        assert_eq!(
+            store.insert_header(&branch[2]).unwrap(),
+            InsertHeaderResult::Reorg { depth: 2, old_tip: main[1].clone(), new_tip: branch[2].clone() }
+        );
+
+        assert_eq!(store.find_chain_tip().unwrap().unwrap(), branch[2]);
+        assert_eq!(store.find_header_for_height(1).unwrap().unwrap(), branch[0]);
+        assert!(!store.is_valid_root_for_height(&hex::encode([1u8; 32]), 1).await.unwrap());
+        assert!(store.is_valid_root_for_height(&hex::encode([2u8; 32]), 1).await.unwrap());
+        assert_eq!(store.find_header_for_block_hash(&main[0].hash).unwrap().unwrap(), main[0]);
+        assert_eq!(store.header_count().unwrap(), 6);
+    }
+
+    struct MockSource {
+        chain: Vec<BlockHeader>,
+    }
+
+    #[async_trait]
+    impl ChainHeaderSource for MockSource {
+        async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader> {
+            Ok(self.chain.last().cloned().unwrap())
+        }
+
+        async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+            Ok(self.chain.get(height as usize).cloned())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_from_follows_source_reorg() {
+        let anchor = genesis();
+        let mut chain = vec![anchor.clone()];
+        chain.extend(mine_chain(&anchor, 4, 1));
+
+        let store = HeaderStore::open_in_memory(Chain::Main).unwrap();
+        let source = MockSource { chain: chain.clone() };
+        assert_eq!(store.sync_from(&source, 100).await.unwrap(), 5);
+        assert_eq!(store.sync_from(&source, 100).await.unwrap(), 0);
+
+        // Source replaces heights 3 and 4 with a longer branch
+        let mut reorged = chain[..3].to_vec();
+        reorged.extend(mine_chain(&chain[2], 3, 2));
+        let source = MockSource { chain: reorged.clone() };
+        assert_eq!(store.sync_from(&source, 100).await.unwrap(), 3);
+        assert_eq!(store.find_chain_tip().unwrap().unwrap(), reorged[5]);
+        assert_eq!(store.find_header_for_height(3).unwrap().unwrap(), reorged[3]);
+    }
+}
@@ -5,7 +5,15 @@
 //! Provides blockchain state tracking and merkle proof verification
 
 pub mod chaintracks;
+#[cfg(feature = "header-store")]
+pub mod header_store;
+pub mod subscription;
 pub mod types;
+pub mod whatsonchain;
 
 pub use chaintracks::ChaintracksClient;
+#[cfg(feature = "header-store")]
+pub use header_store::{HeaderStore, InsertHeaderResult};
+pub use subscription::{HeaderEvent, HeaderPoller};
 pub use types::*;
+pub use whatsonchain::WhatsOnChainChainTracker;
@@ -0,0 +1,263 @@
+//! Header Subscription
+//!
+//! **Reference**: TypeScript `ChaintracksClientApi.subscribeHeaders` / `subscribeReorgs`
+//!
+//! Chaintracks pushes new headers and reorgs to in-process subscribers. Remote
+//! clients have no push channel, so `HeaderPoller` follows the chain tip of any
+//! `ChainHeaderSource` and turns tip changes into the same events.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::ChainHeaderSource;
+use super::types::BlockHeader;
+
+/// Default interval between chain tip polls
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of intermediate headers reported for one tip advance, and
+/// number of recent hashes remembered for measuring reorg depth
+const MAX_CATCH_UP_HEADERS: u32 = 100;
+
+/// Chain tip change observed by a `HeaderPoller`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderEvent {
+    /// New header extending the previously seen tip
+    NewHeader(BlockHeader),
+
+    /// The previously seen tip is no longer on the active chain
+    ///
+    /// Reference: TS Chaintracks reorg listener (depth, oldTip, newTip)
+    Reorg {
+        /// Blocks deactivated, counted back from `old_tip` to the common
+        /// ancestor; a lower bound when the ancestor is older than the
+        /// remembered headers
+        depth: u32,
+        old_tip: BlockHeader,
+        new_tip: BlockHeader,
+    },
+}
+
+/// Polls a header source for new chain tips
+pub struct HeaderPoller {
+    source: Arc<dyn ChainHeaderSource>,
+    interval: Duration,
+    tip: Option<BlockHeader>,
+    /// Hashes of recently seen active-chain headers by height
+    recent: BTreeMap<u32, String>,
+}
+
+impl HeaderPoller {
+    pub fn new(source: Arc<dyn ChainHeaderSource>) -> Self {
+        Self {
+            source,
+            interval: DEFAULT_POLL_INTERVAL,
+            tip: None,
+            recent: BTreeMap::new(),
+        }
+    }
+
+    /// Set the interval `next_events` waits between polls
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Last chain tip seen
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.tip.as_ref()
+    }
+
+    /// Fetch the current tip and report what changed since the last poll
+    ///
+    /// The first poll only records the tip and returns no events.
+    pub async fn poll(&mut self) -> ServiceResult<Vec<HeaderEvent>> {
+        let new_tip = self.source.find_chain_tip_header().await?;
+        let Some(old_tip) = self.tip.clone() else {
+            self.set_tip(new_tip);
+            return Ok(vec![]);
+        };
+        if new_tip.hash == old_tip.hash {
+            return Ok(vec![]);
+        }
+
+        let events = if self.is_active(&old_tip).await? {
+            let headers = self.headers_after(&old_tip, &new_tip).await?;
+            for h in &headers {
+                self.remember(h);
+            }
+            headers.into_iter().map(HeaderEvent::NewHeader).collect()
+        } else {
+            let depth = self.reorg_depth(&old_tip).await?;
+            self.recent.split_off(&(old_tip.height - depth + 1));
+            vec![HeaderEvent::Reorg { depth, old_tip, new_tip: new_tip.clone() }]
+        };
+
+        self.set_tip(new_tip);
+        Ok(events)
+    }
+
+    /// Wait one interval, then poll
+    pub async fn next_events(&mut self) -> ServiceResult<Vec<HeaderEvent>> {
+        tokio::time::sleep(self.interval).await;
+        self.poll().await
+    }
+
+    fn set_tip(&mut self, tip: BlockHeader) {
+        self.remember(&tip);
+        self.tip = Some(tip);
+    }
+
+    fn remember(&mut self, header: &BlockHeader) {
+        if header.height > 0 {
+            self.recent.entry(header.height - 1).or_insert_with(|| header.previous_hash.clone());
+        }
+        self.recent.insert(header.height, header.hash.clone());
+        while self.recent.len() > MAX_CATCH_UP_HEADERS as usize {
+            self.recent.pop_first();
+        }
+    }
+
+    /// Whether `header` is still on the active chain
+    async fn is_active(&self, header: &BlockHeader) -> ServiceResult<bool> {
+        Ok(self.source.find_header_for_height(header.height).await?
+            .is_some_and(|h| h.hash == header.hash))
+    }
+
+    /// Headers from just above `old_tip` up to and including `new_tip`
+    ///
+    /// Long catch-ups report only the new tip.
+    async fn headers_after(&self, old_tip: &BlockHeader, new_tip: &BlockHeader) -> ServiceResult<Vec<BlockHeader>> {
+        if new_tip.height <= old_tip.height || new_tip.height - old_tip.height > MAX_CATCH_UP_HEADERS {
+            return Ok(vec![new_tip.clone()]);
+        }
+
+        let mut headers = Vec::new();
+        for height in old_tip.height + 1..new_tip.height {
+            let header = self.source.find_header_for_height(height).await?
+                .ok_or(ServiceError::BlockNotFound(height))?;
+            headers.push(header);
+        }
+        headers.push(new_tip.clone());
+        Ok(headers)
+    }
+
+    /// Number of blocks back from `old_tip` to the first remembered header
+    /// that is still on the active chain
+    async fn reorg_depth(&self, old_tip: &BlockHeader) -> ServiceResult<u32> {
+        let mut depth = 1;
+        while depth <= old_tip.height {
+            let height = old_tip.height - depth;
+            let Some(hash) = self.recent.get(&height) else {
+                break;
+            };
+            let active = self.source.find_header_for_height(height).await?;
+            if active.is_some_and(|h| &h.hash == hash) {
+                break;
+            }
+            depth += 1;
+        }
+        Ok(depth)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Active chain as a list of headers indexed by height
+    struct MockSource {
+        chain: Mutex<Vec<BlockHeader>>,
+    }
+
+    impl MockSource {
+        fn set_chain(&self, chain: Vec<BlockHeader>) {
+            *self.chain.lock().unwrap() = chain;
+        }
+    }
+
+    #[async_trait]
+    impl ChainHeaderSource for MockSource {
+        async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader> {
+            Ok(self.chain.lock().unwrap().last().cloned().unwrap())
+        }
+
+        async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+            Ok(self.chain.lock().unwrap().get(height as usize).cloned())
+        }
+    }
+
+    /// Chain of `len` headers; `branch` distinguishes hashes from height `fork` up
+    fn chain(len: u32, fork: u32, branch: &str) -> Vec<BlockHeader> {
+        let hash = |h: u32| if h >= fork { format!("{}{:062}", branch, h) } else { format!("00{:062}", h) };
+        (0..len)
+            .map(|h| BlockHeader {
+                height: h,
+                hash: hash(h),
+                previous_hash: if h == 0 { "00".repeat(32) } else { hash(h - 1) },
+                merkle_root: "33".repeat(32),
+                time: 0,
+                bits: 0,
+                nonce: 0,
+                version: 1,
+            })
+            .collect()
+    }
+
+    fn poller(chain: Vec<BlockHeader>) -> (Arc<MockSource>, HeaderPoller) {
+        let source = Arc::new(MockSource { chain: Mutex::new(chain) });
+        let poller = HeaderPoller::new(source.clone());
+        (source, poller)
+    }
+
+    #[tokio::test]
+    async fn test_poll_reports_new_headers() {
+        let (source, mut poller) = poller(chain(10, 100, "aa"));
+        assert!(poller.poll().await.unwrap().is_empty());
+        assert_eq!(poller.tip().unwrap().height, 9);
+        assert!(poller.poll().await.unwrap().is_empty());
+
+        source.set_chain(chain(12, 100, "aa"));
+        let events = poller.poll().await.unwrap();
+        let heights: Vec<u32> = events.iter().map(|e| match e {
+            HeaderEvent::NewHeader(h) => h.height,
+            HeaderEvent::Reorg { .. } => panic!("unexpected reorg"),
+        }).collect();
+        assert_eq!(heights, vec![10, 11]);
+        assert_eq!(poller.tip().unwrap().height, 11);
+    }
+
+    #[tokio::test]
+    async fn test_poll_reports_reorg() {
+        let (source, mut poller) = poller(chain(8, 100, "aa"));
+        poller.poll().await.unwrap();
+        source.set_chain(chain(10, 100, "aa"));
+        assert_eq!(poller.poll().await.unwrap().len(), 2);
+
+        // Blocks 7, 8 and 9 replaced, new branch one block longer
+        source.set_chain(chain(11, 7, "bb"));
+        let events = poller.poll().await.unwrap();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            HeaderEvent::Reorg { depth, old_tip, new_tip } => {
+                assert_eq!(*depth, 3);
+                assert_eq!(old_tip.height, 9);
+                assert_eq!(new_tip.height, 10);
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+        assert_eq!(poller.tip().unwrap().hash, chain(11, 7, "bb")[10].hash);
+
+        // Following the new branch
+        source.set_chain(chain(12, 7, "bb"));
+        assert_eq!(poller.poll().await.unwrap(), vec![HeaderEvent::NewHeader(chain(12, 7, "bb")[11].clone())]);
+    }
+}
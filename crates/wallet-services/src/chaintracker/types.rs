@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// Block header structure
 /// Reference: TypeScript BlockHeader
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BlockHeader {
     /// Block height
     pub height: u32,
@@ -35,6 +35,89 @@ pub struct BlockHeader {
     pub version: u32,
 }
 
+impl BlockHeader {
+    /// Serialize to the 80-byte block header format
+    ///
+    /// Reference: TypeScript `serializeBaseBlockHeader`
+    ///
+    /// Hashes are hex in display (big-endian) order and are reversed on the wire.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        fn hash_le(hash: &str) -> Result<Vec<u8>, String> {
+            let mut bytes = hex::decode(hash).map_err(|e| format!("invalid hash {}: {}", hash, e))?;
+            if bytes.len() != 32 {
+                return Err(format!("invalid hash length {}", bytes.len()));
+            }
+            bytes.reverse();
+            Ok(bytes)
+        }
+        
+        let mut bytes = Vec::with_capacity(80);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&hash_le(&self.previous_hash)?);
+        bytes.extend_from_slice(&hash_le(&self.merkle_root)?);
+        bytes.extend_from_slice(&self.time.to_le_bytes());
+        bytes.extend_from_slice(&self.bits.to_le_bytes());
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        Ok(bytes)
+    }
+}
+
+/// WhatsOnChain block header response
+/// Reference: WoC API `GET /block/{height}/header`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WocBlockHeader {
+    pub hash: String,
+    
+    pub confirmations: u32,
+    
+    pub height: u32,
+    
+    pub version: u32,
+    
+    #[serde(rename = "merkleroot")]
+    pub merkle_root: String,
+    
+    pub time: u32,
+    
+    pub nonce: u32,
+    
+    /// Difficulty bits (hex)
+    pub bits: String,
+    
+    /// Absent for the genesis block
+    #[serde(rename = "previousblockhash", default)]
+    pub previous_block_hash: Option<String>,
+}
+
+impl WocBlockHeader {
+    /// Convert to a `BlockHeader`
+    pub fn to_block_header(&self) -> Result<BlockHeader, String> {
+        Ok(BlockHeader {
+            height: self.height,
+            hash: self.hash.clone(),
+            previous_hash: self.previous_block_hash.clone().unwrap_or_else(|| "00".repeat(32)),
+            merkle_root: self.merkle_root.clone(),
+            time: self.time,
+            bits: u32::from_str_radix(&self.bits, 16).map_err(|e| format!("invalid bits {}: {}", self.bits, e))?,
+            nonce: self.nonce,
+            version: self.version,
+        })
+    }
+}
+
+/// WhatsOnChain chain info response
+/// Reference: WoC API `GET /chain/info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WocChainInfo {
+    pub chain: String,
+    
+    /// Current chain height
+    pub blocks: u32,
+    
+    #[serde(rename = "bestblockhash")]
+    pub best_block_hash: String,
+}
+
 /// Chaintracks service info
 /// Reference: TypeScript ChaintracksInfoApi
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,4 +215,39 @@ mod tests {
         assert!(json.contains("\"height\":100"));
         assert!(json.contains("merkleRoot"));
     }
+    
+    #[test]
+    fn test_block_header_to_bytes_genesis() {
+        // Mainnet genesis block header
+        let header = BlockHeader {
+            height: 0,
+            hash: "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f".to_string(),
+            previous_hash: "00".repeat(32),
+            merkle_root: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b".to_string(),
+            time: 1231006505,
+            bits: 0x1d00ffff,
+            nonce: 2083236893,
+            version: 1,
+        };
+        
+        let bytes = header.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 80);
+        assert_eq!(
+            hex::encode(&bytes),
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c"
+        );
+        
+        let bad = BlockHeader { merkle_root: "abc".to_string(), ..header };
+        assert!(bad.to_bytes().is_err());
+    }
+    
+    #[test]
+    fn test_woc_block_header_parse() {
+        let json = r#"{"hash":"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f","confirmations":880000,"size":285,"height":0,"version":1,"versionHex":"00000001","merkleroot":"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b","time":1231006505,"mediantime":1231006505,"nonce":2083236893,"bits":"1d00ffff","difficulty":1,"chainwork":"0000000000000000000000000000000000000000000000000000000100010001","nextblockhash":"00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048"}"#;
+        let woc: WocBlockHeader = serde_json::from_str(json).unwrap();
+        let header = woc.to_block_header().unwrap();
+        assert_eq!(header.bits, 0x1d00ffff);
+        assert_eq!(header.previous_hash, "00".repeat(32));
+        assert_eq!(header.to_bytes().unwrap().len(), 80);
+    }
 }
@@ -0,0 +1,339 @@
+//! WhatsOnChain ChainTracker
+//!
+//! **Reference**: TypeScript `src/services/chaintracker/WhatsOnChainChainTracker` (via `@bsv/sdk`)
+//!
+//! Validates merkle roots against block headers served by the WhatsOnChain API,
+//! so BEEF verification works without a Chaintracks deployment.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::{ChainHeaderSource, ChainTracker};
+use crate::types::{Chain, MerklePath};
+use super::types::{BlockHeader, WocBlockHeader, WocChainInfo};
+
+/// Default request rate without an API key (WoC free tier: 3 requests/second)
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 3;
+
+/// Headers with fewer confirmations may still be reorganized and are not cached
+const MIN_CACHE_CONFIRMATIONS: u32 = 6;
+
+/// Maximum number of cached headers
+const MAX_CACHED_HEADERS: usize = 10_000;
+
+/// Spaces requests at a fixed minimum interval
+#[derive(Debug)]
+struct RateLimiter {
+    min_interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            min_interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next request slot, returning how long to wait for it
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = next_slot.map_or(now, |s| s.max(now));
+        *next_slot = Some(slot + self.min_interval);
+        slot - now
+    }
+
+    async fn wait(&self) {
+        let delay = self.reserve(Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// WhatsOnChain chain tracker
+///
+/// Reference: TypeScript WhatsOnChain ChainTracker
+pub struct WhatsOnChainChainTracker {
+    /// Chain (main or test)
+    chain: Chain,
+
+    /// Base URL
+    url: String,
+
+    /// HTTP client
+    client: Client,
+
+    /// API key (optional)
+    api_key: Option<String>,
+
+    /// Maximum retries on rate limiting
+    max_retries: usize,
+
+    rate_limiter: RateLimiter,
+
+    /// Confirmed headers by height
+    header_cache: Mutex<HashMap<u32, BlockHeader>>,
+}
+
+impl WhatsOnChainChainTracker {
+    /// Create new WhatsOnChain chain tracker
+    ///
+    /// # Arguments
+    /// * `chain` - Chain to query (main or test)
+    /// * `api_key` - Optional API key for higher rate limits
+    pub fn new(chain: Chain, api_key: Option<String>) -> Self {
+        let url = match chain {
+            Chain::Main => "https://api.whatsonchain.com/v1/bsv/main",
+            // No public indexer serves regtest
+            Chain::Test | Chain::Regtest => "https://api.whatsonchain.com/v1/bsv/test",
+        };
+
+        Self {
+            chain,
+            url: url.to_string(),
+            client: Client::new(),
+            api_key,
+            max_retries: 3,
+            rate_limiter: RateLimiter::new(DEFAULT_REQUESTS_PER_SECOND),
+            header_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the request rate, e.g. to match the plan of the configured API key
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limiter = RateLimiter::new(requests_per_second);
+        self
+    }
+
+    /// Chain being tracked
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    /// Get HTTP headers
+    ///
+    /// Reference: TS getHttpHeaders() method
+    fn get_headers(&self) -> ServiceResult<reqwest::header::HeaderMap> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(ref api_key) = self.api_key {
+            headers.insert(
+                "woc-api-key",
+                reqwest::header::HeaderValue::from_str(api_key)
+                    .map_err(|_| ServiceError::InvalidParams("Invalid API key".to_string()))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    /// Rate-limited GET, returns None on 404
+    ///
+    /// Retries with backoff when WoC responds 429.
+    async fn get_json_or_none<T>(&self, path: &str) -> ServiceResult<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let url = format!("{}{}", self.url, path);
+
+        for retry in 0..self.max_retries {
+            self.rate_limiter.wait().await;
+
+            let response = self.client
+                .get(&url)
+                .headers(self.get_headers()?)
+                .send()
+                .await
+                .map_err(ServiceError::Http)?;
+
+            match response.status() {
+                reqwest::StatusCode::NOT_FOUND => return Ok(None),
+                reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    tokio::time::sleep(Duration::from_millis(1000 << retry)).await;
+                    continue;
+                }
+                status if !status.is_success() => {
+                    return Err(ServiceError::ServiceFailed {
+                        service: "WoC".to_string(),
+                        message: format!("HTTP {} for {}", status, path),
+                    });
+                }
+                _ => {}
+            }
+
+            return response.json().await.map(Some).map_err(ServiceError::Http);
+        }
+
+        Err(ServiceError::RateLimitExceeded("WoC".to_string()))
+    }
+
+    fn cached_header(&self, height: u32) -> Option<BlockHeader> {
+        self.header_cache.lock().unwrap_or_else(|e| e.into_inner()).get(&height).cloned()
+    }
+
+    fn cache_header(&self, header: &BlockHeader, confirmations: u32) {
+        if confirmations < MIN_CACHE_CONFIRMATIONS {
+            return;
+        }
+        let mut cache = self.header_cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= MAX_CACHED_HEADERS {
+            cache.clear();
+        }
+        cache.insert(header.height, header.clone());
+    }
+
+    /// Find header for specific height
+    ///
+    /// Reference: WoC API `GET /block/{height}/header`
+    pub async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+        if let Some(header) = self.cached_header(height) {
+            return Ok(Some(header));
+        }
+
+        let woc: Option<WocBlockHeader> = self.get_json_or_none(&format!("/block/{}/header", height)).await?;
+        let Some(woc) = woc else {
+            return Ok(None);
+        };
+
+        let header = woc.to_block_header().map_err(ServiceError::InvalidResponse)?;
+        if header.height != height {
+            return Err(ServiceError::InvalidResponse(format!(
+                "requested header at height {} but received height {}",
+                height, header.height
+            )));
+        }
+        self.cache_header(&header, woc.confirmations);
+        Ok(Some(header))
+    }
+
+    /// Get chain info
+    ///
+    /// Reference: WoC API `GET /chain/info`
+    pub async fn get_chain_info(&self) -> ServiceResult<WocChainInfo> {
+        self.get_json_or_none("/chain/info")
+            .await?
+            .ok_or_else(|| ServiceError::InvalidResponse("missing chain info".to_string()))
+    }
+}
+
+#[async_trait]
+impl ChainHeaderSource for WhatsOnChainChainTracker {
+    async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader> {
+        let height = self.get_chain_info().await?.blocks;
+        self.find_header_for_height(height).await?
+            .ok_or(ServiceError::BlockNotFound(height))
+    }
+
+    async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>> {
+        WhatsOnChainChainTracker::find_header_for_height(self, height).await
+    }
+}
+
+#[async_trait]
+impl ChainTracker for WhatsOnChainChainTracker {
+    /// Check if merkle root is valid for height
+    ///
+    /// Reference: TS ChainTracker.isValidRootForHeight
+    async fn is_valid_root_for_height(&self, root: &str, height: u32) -> ServiceResult<bool> {
+        let header = self.find_header_for_height(height).await?;
+        Ok(header.is_some_and(|h| h.merkle_root == root))
+    }
+
+    /// Get serialized 80-byte header for block height
+    async fn get_header_for_height(&self, height: u32) -> ServiceResult<Vec<u8>> {
+        let header = self.find_header_for_height(height).await?
+            .ok_or(ServiceError::BlockNotFound(height))?;
+        header.to_bytes().map_err(ServiceError::InvalidResponse)
+    }
+
+    /// Get current blockchain height
+    ///
+    /// Reference: TS ChainTracker.currentHeight
+    async fn get_height(&self) -> ServiceResult<u32> {
+        Ok(self.get_chain_info().await?.blocks)
+    }
+
+    /// Get merkle path for transaction
+    async fn get_merkle_path(&self, _txid: &str) -> ServiceResult<MerklePath> {
+        Err(ServiceError::InvalidParams("Merkle path not supported by WhatsOnChainChainTracker".to_string()))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(height: u32) -> BlockHeader {
+        BlockHeader {
+            height,
+            hash: "11".repeat(32),
+            previous_hash: "22".repeat(32),
+            merkle_root: "33".repeat(32),
+            time: 0,
+            bits: 0,
+            nonce: 0,
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_tracker_creation() {
+        let tracker = WhatsOnChainChainTracker::new(Chain::Test, Some("key".to_string()));
+        assert_eq!(tracker.chain(), Chain::Test);
+        assert_eq!(tracker.url, "https://api.whatsonchain.com/v1/bsv/test");
+        assert_eq!(tracker.get_headers().unwrap()["woc-api-key"], "key");
+
+        let tracker = WhatsOnChainChainTracker::new(Chain::Main, None);
+        assert_eq!(tracker.url, "https://api.whatsonchain.com/v1/bsv/main");
+        assert!(tracker.get_headers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rate_limiter_spacing() {
+        let limiter = RateLimiter::new(4);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::from_millis(250));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(500));
+
+        // Idle time is not banked
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_rate_limiter_zero_rate() {
+        let limiter = RateLimiter::new(0);
+        assert_eq!(limiter.min_interval, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_header_cache_requires_confirmations() {
+        let tracker = WhatsOnChainChainTracker::new(Chain::Main, None);
+
+        tracker.cache_header(&header(100), 1);
+        assert!(tracker.cached_header(100).is_none());
+
+        tracker.cache_header(&header(100), MIN_CACHE_CONFIRMATIONS);
+        assert_eq!(tracker.cached_header(100).unwrap().merkle_root, "33".repeat(32));
+    }
+
+    #[tokio::test]
+    async fn test_is_valid_root_for_height_from_cache() {
+        let tracker = WhatsOnChainChainTracker::new(Chain::Main, None);
+        tracker.cache_header(&header(100), 100);
+
+        assert!(tracker.is_valid_root_for_height(&"33".repeat(32), 100).await.unwrap());
+        assert!(!tracker.is_valid_root_for_height(&"44".repeat(32), 100).await.unwrap());
+        assert_eq!(tracker.get_header_for_height(100).await.unwrap().len(), 80);
+    }
+}
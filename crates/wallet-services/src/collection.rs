@@ -6,13 +6,20 @@
 
 use async_trait::async_trait;
 use crate::error::{ServiceError, ServiceResult};
-use crate::traits::{WalletServices, ChainTracker, ExchangeRateProvider, FiatCurrency, Broadcaster, UtxoStatusChecker};
+use crate::traits::{
+    WalletServices, ChainTracker, ExchangeRateProvider, FiatCurrency, Broadcaster, UtxoStatusChecker,
+    RawTxProvider, MerklePathProvider,
+};
 use crate::types::*;
 use crate::chaintracker::ChaintracksClient;
-use crate::broadcaster::ArcBroadcaster;
-use crate::utxo::WhatsOnChainClient;
-use crate::exchange::WhatsOnChainExchangeRate;
+use crate::broadcaster::{ArcBroadcaster, ArcConfig, ArcEndpoint};
+use crate::utxo::{BitailsClient, WhatsOnChainClient};
+use crate::exchange::{ExchangeRatesApiClient, RateProvider, RateProviderConfig, WhatsOnChainExchangeRate};
+use crate::regtest::{NodeRpcConfig, RegtestNode};
+use crate::provider_collection::{CallOutcome, FailoverConfig, ProviderCollection, ProviderStats, ServiceCall};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tracing::instrument;
 
 /// Service collection configuration
 ///
@@ -27,14 +34,36 @@ pub struct ServiceConfig {
     /// ARC broadcaster URL
     pub arc_url: Option<String>,
     
+    /// ARC API key, sent as a bearer token (TS taalApiKey)
+    pub arc_api_key: Option<String>,
+    
+    /// Further ARC endpoints, tried in order after `arc_url`
+    pub arc_endpoints: Vec<ArcEndpoint>,
+    
+    /// Post every broadcast to all ARC endpoints rather than stopping at the
+    /// first that accepts it
+    pub arc_fan_out: bool,
+    
     /// WhatsOnChain API key
     pub whatsonchain_api_key: Option<String>,
     
+    /// Bitails API key
+    pub bitails_api_key: Option<String>,
+    
+    /// exchangeratesapi.io API key (fiat rates)
+    pub exchangeratesapi_key: Option<String>,
+    
     /// BSV exchange rate update interval (milliseconds)
     pub bsv_update_msecs: u64,
     
     /// Fiat exchange rate update interval (milliseconds)
     pub fiat_update_msecs: u64,
+    
+    /// Provider failover and cooldown settings
+    pub failover: FailoverConfig,
+    
+    /// Node serving `Chain::Regtest`; the default RPC endpoint when unset
+    pub node_rpc: Option<NodeRpcConfig>,
 }
 
 impl Default for ServiceConfig {
@@ -43,9 +72,30 @@ impl Default for ServiceConfig {
             chain: Chain::Main,
             chaintracks_url: None,
             arc_url: None,
+            arc_api_key: None,
+            arc_endpoints: Vec::new(),
+            arc_fan_out: false,
             whatsonchain_api_key: None,
+            bitails_api_key: None,
+            exchangeratesapi_key: None,
             bsv_update_msecs: 1000 * 60 * 15, // 15 minutes
             fiat_update_msecs: 1000 * 60 * 60 * 24, // 24 hours
+            failover: FailoverConfig::default(),
+            node_rpc: None,
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Configuration for `chain` with its default ARC and Chaintracks endpoints
+    ///
+    /// Reference: TS Services.createDefaultOptions
+    pub fn for_chain(chain: Chain) -> Self {
+        Self {
+            chain,
+            arc_url: chain.default_arc_url().map(str::to_string),
+            chaintracks_url: chain.default_chaintracks_url().map(str::to_string),
+            ..Default::default()
         }
     }
 }
@@ -66,14 +116,26 @@ pub struct ServiceCollection {
     /// ChainTracker client
     chain_tracker: Option<Arc<ChaintracksClient>>,
     
-    /// Broadcaster client
-    broadcaster: Option<Arc<ArcBroadcaster>>,
+    /// Broadcasters (TS postBeefServices)
+    broadcasters: ProviderCollection<dyn Broadcaster>,
     
-    /// UTXO status checker
+    /// Transaction status lookups
     utxo_checker: Arc<WhatsOnChainClient>,
     
-    /// Exchange rate provider
-    exchange_rate: Arc<WhatsOnChainExchangeRate>,
+    /// Regtest node, serving chain lookups and transaction status
+    node: Option<Arc<RegtestNode>>,
+    
+    /// Raw transaction providers (TS getRawTxServices)
+    raw_tx_providers: ProviderCollection<dyn RawTxProvider>,
+    
+    /// Merkle path providers (TS getMerklePathServices)
+    merkle_path_providers: ProviderCollection<dyn MerklePathProvider>,
+    
+    /// UTXO status providers (TS getUtxoStatusServices)
+    utxo_status_providers: ProviderCollection<dyn UtxoStatusChecker>,
+    
+    /// Exchange rates (cached, with provider fallback)
+    exchange_rate: RateProvider,
 }
 
 impl ServiceCollection {
@@ -90,37 +152,137 @@ impl ServiceCollection {
             config.whatsonchain_api_key.clone()
         ));
         
-        // Initialize exchange rate provider
-        let exchange_rate = Arc::new(WhatsOnChainExchangeRate::new(config.chain));
+        // Initialize Bitails fallback provider
+        let bitails = Arc::new(BitailsClient::new(
+            config.chain,
+            config.bitails_api_key.clone()
+        ));
+        
+        // Initialize exchange rate providers (TS lines 97-111)
+        let mut exchange_rate = RateProvider::new(RateProviderConfig {
+            bsv_update_msecs: config.bsv_update_msecs,
+            fiat_update_msecs: config.fiat_update_msecs,
+        })
+        .with_bsv_source(Arc::new(WhatsOnChainExchangeRate::new(config.chain)));
+        if let Some(key) = &config.exchangeratesapi_key {
+            exchange_rate = exchange_rate.with_fiat_source(Arc::new(ExchangeRatesApiClient::new(key.clone())));
+        }
         
         // Initialize ChainTracker if URL provided (TS lines 126-130)
         let chain_tracker = config.chaintracks_url.as_ref().map(|url| {
             Arc::new(ChaintracksClient::new(config.chain, url.clone()))
         });
         
+        // Regtest is served by a local node rather than public indexers
+        let node = (config.chain == Chain::Regtest)
+            .then(|| Arc::new(RegtestNode::new(config.node_rpc.clone().unwrap_or_default())));
+        
         // Initialize broadcaster if URL provided (TS lines 67-70)
-        let broadcaster = config.arc_url.as_ref().map(|url| {
-            Arc::new(ArcBroadcaster::new(url.clone(), None, None))
-        });
+        let mut broadcasters = ProviderCollection::<dyn Broadcaster>::new("postBeef", config.failover);
+        if let Some(url) = &config.arc_url {
+            let arc_config = ArcConfig {
+                api_key: config.arc_api_key.clone(),
+                ..Default::default()
+            };
+            broadcasters = broadcasters.add("ARC", Arc::new(ArcBroadcaster::new(url.clone(), Some(arc_config), None)));
+        }
+        for endpoint in &config.arc_endpoints {
+            broadcasters = broadcasters.add(endpoint.name.clone(), Arc::new(ArcBroadcaster::from_endpoint(endpoint)));
+        }
+        
+        let mut raw_tx_providers = ProviderCollection::<dyn RawTxProvider>::new("getRawTx", config.failover);
+        let mut merkle_path_providers = ProviderCollection::<dyn MerklePathProvider>::new("getMerklePath", config.failover);
+        let mut utxo_status_providers = ProviderCollection::<dyn UtxoStatusChecker>::new("getUtxoStatus", config.failover);
+        match &node {
+            Some(node) => {
+                // A mock ARC, when configured, takes broadcasts instead of the node
+                if broadcasters.is_empty() {
+                    broadcasters = broadcasters.add("Node", node.clone());
+                }
+                raw_tx_providers = raw_tx_providers.add("Node", node.clone());
+                merkle_path_providers = merkle_path_providers.add("Node", node.clone());
+                utxo_status_providers = utxo_status_providers.add("Node", node.clone());
+            }
+            None => {
+                // Provider order: WhatsOnChain first, Bitails as fallback (TS lines 72-96)
+                raw_tx_providers = raw_tx_providers
+                    .add("WoC", utxo_checker.clone())
+                    .add("Bitails", bitails.clone());
+                merkle_path_providers = merkle_path_providers
+                    .add("WoC", utxo_checker.clone())
+                    .add("Bitails", bitails.clone());
+                utxo_status_providers = utxo_status_providers
+                    .add("WoC", utxo_checker.clone())
+                    .add("Bitails", bitails);
+            }
+        }
         
         Self {
             config,
             chain_tracker,
-            broadcaster,
+            broadcasters,
             utxo_checker,
+            node,
+            raw_tx_providers,
+            merkle_path_providers,
+            utxo_status_providers,
             exchange_rate,
         }
     }
     
-    /// Create service collection for specific chain
-    ///
-    /// Reference: TS Services.createDefaultOptions
+    /// Create service collection for `chain` with its default endpoints
     pub fn for_chain(chain: Chain) -> Self {
-        let config = ServiceConfig {
-            chain,
-            ..Default::default()
-        };
-        Self::new(config)
+        Self::new(ServiceConfig::for_chain(chain))
+    }
+    
+    /// Exchange rates for arbitrary currency codes and satoshi conversions
+    pub fn exchange_rates(&self) -> &RateProvider {
+        &self.exchange_rate
+    }
+    
+    /// Recent provider calls across all failover-managed methods
+    ///
+    /// Reference: TS Services.getServicesCallHistory
+    pub fn service_call_history(&self) -> Vec<ServiceCall> {
+        let mut calls = self.broadcasters.call_history();
+        calls.extend(self.raw_tx_providers.call_history());
+        calls.extend(self.merkle_path_providers.call_history());
+        calls.extend(self.utxo_status_providers.call_history());
+        calls
+    }
+    
+    /// Per-provider statistics for a service method
+    ///
+    /// `method` is one of "postBeef", "getRawTx", "getMerklePath", "getUtxoStatus".
+    pub fn provider_stats(&self, method: &str) -> HashMap<String, ProviderStats> {
+        match method {
+            "postBeef" => self.broadcasters.stats(),
+            "getRawTx" => self.raw_tx_providers.stats(),
+            "getMerklePath" => self.merkle_path_providers.stats(),
+            "getUtxoStatus" => self.utxo_status_providers.stats(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// Failover outcome for lookups that report errors in their result
+fn lookup_outcome(found: bool, error: Option<&crate::types::ServiceError>) -> CallOutcome {
+    match (found, error) {
+        (true, _) => CallOutcome::Done,
+        (false, Some(e)) => CallOutcome::Failed(e.message.clone()),
+        (false, None) => CallOutcome::NotFound,
+    }
+}
+
+/// A postBeef attempt succeeded when every txid was accepted
+fn post_beef_outcome(results: &[PostBeefResult]) -> CallOutcome {
+    match results.iter().find(|r| r.status != "success") {
+        None => CallOutcome::Done,
+        Some(r) => match r.status.as_str() {
+            // Definitive answers from the network; another service won't differ
+            "doubleSpend" | "invalidTx" => CallOutcome::Done,
+            _ => CallOutcome::Failed(r.error.as_ref().map(|e| e.message.clone()).unwrap_or_else(|| r.status.clone())),
+        },
     }
 }
 
@@ -137,6 +299,9 @@ impl WalletServices for ServiceCollection {
     ///
     /// Reference: TS Services.getChainTracker (Services.ts lines 126-130)
     async fn get_chain_tracker(&self) -> ServiceResult<Box<dyn ChainTracker>> {
+        if let Some(node) = &self.node {
+            return Ok(Box::new((**node).clone()));
+        }
         match &self.chain_tracker {
             Some(tracker) => Ok(Box::new((**tracker).clone())),
             None => Err(ServiceError::InvalidParams(
@@ -182,28 +347,48 @@ impl WalletServices for ServiceCollection {
     /// Get raw transaction
     ///
     /// Reference: TS Services.getRawTx
-    async fn get_raw_tx(&self, _txid: &str, _use_next: bool) -> ServiceResult<GetRawTxResult> {
-        // Simplified implementation - would cycle through providers in production
-        Err(ServiceError::InvalidParams("Not implemented yet".to_string()))
+    #[instrument(skip(self))]
+    async fn get_raw_tx(&self, txid: &str, use_next: bool) -> ServiceResult<GetRawTxResult> {
+        self.raw_tx_providers.call(
+            use_next,
+            |p| async move { p.get_raw_tx(txid).await },
+            |r| lookup_outcome(r.raw_tx.is_some(), r.error.as_ref()),
+        ).await
     }
     
     /// Get merkle path
     ///
     /// Reference: TS Services.getMerklePath
-    async fn get_merkle_path(&self, _txid: &str, _use_next: bool) -> ServiceResult<GetMerklePathResult> {
-        Err(ServiceError::InvalidParams("Not implemented yet".to_string()))
+    #[instrument(skip(self))]
+    async fn get_merkle_path(&self, txid: &str, use_next: bool) -> ServiceResult<GetMerklePathResult> {
+        self.merkle_path_providers.call(
+            use_next,
+            |p| async move { p.get_merkle_path(txid).await },
+            |r| lookup_outcome(r.proof.is_some(), r.error.as_ref()),
+        ).await
     }
     
     /// Post BEEF
     ///
     /// Reference: TS Services.postBeef
+    #[instrument(skip(self, beef), fields(beef_bytes = beef.len()))]
     async fn post_beef(&self, beef: &[u8], txids: &[String]) -> ServiceResult<Vec<PostBeefResult>> {
-        match &self.broadcaster {
-            Some(broadcaster) => broadcaster.post_beef(beef, txids).await,
-            None => Err(ServiceError::InvalidParams(
+        if self.broadcasters.is_empty() {
+            return Err(ServiceError::InvalidParams(
                 "Broadcaster not configured".to_string()
-            )),
+            ));
+        }
+        if self.config.arc_fan_out {
+            return self.broadcasters.call_all(
+                |b| async move { b.post_beef(beef, txids).await },
+                |r| post_beef_outcome(r),
+            ).await;
         }
+        self.broadcasters.call(
+            false,
+            |b| async move { b.post_beef(beef, txids).await },
+            |r| post_beef_outcome(r),
+        ).await
     }
     
     /// Hash output script
@@ -231,15 +416,21 @@ impl WalletServices for ServiceCollection {
         txids: &[String],
         _use_next: bool,
     ) -> ServiceResult<GetStatusForTxidsResult> {
-        self.utxo_checker.get_status_for_txids(txids).await
+        match &self.node {
+            Some(node) => node.get_status_for_txids(txids).await,
+            None => self.utxo_checker.get_status_for_txids(txids).await,
+        }
     }
     
     /// Check if output is UTXO
     ///
     /// Reference: TS Services.isUtxo
     async fn is_utxo(&self, output: &crate::traits::OutputRef) -> ServiceResult<bool> {
-        use crate::traits::UtxoStatusChecker;
-        self.utxo_checker.is_utxo(output).await
+        let script = output.script.as_ref()
+            .ok_or_else(|| ServiceError::InvalidParams("Script required".to_string()))?;
+        let outpoint = format!("{}.{}", output.txid, output.vout);
+        let r = self.get_utxo_status(script, Some(GetUtxoStatusOutputFormat::Script), Some(&outpoint), false).await?;
+        Ok(r.is_utxo)
     }
     
     /// Get UTXO status
@@ -250,10 +441,14 @@ impl WalletServices for ServiceCollection {
         output: &str,
         output_format: Option<GetUtxoStatusOutputFormat>,
         outpoint: Option<&str>,
-        _use_next: bool,
+        use_next: bool,
     ) -> ServiceResult<GetUtxoStatusResult> {
-        use crate::traits::UtxoStatusChecker;
-        self.utxo_checker.get_utxo_status(output, output_format, outpoint).await
+        // Providers report failures in the result (TS Services.getUtxoStatus)
+        self.utxo_status_providers.call(
+            use_next,
+            |p| async move { p.get_utxo_status(output, output_format, outpoint).await },
+            |r| lookup_outcome(r.error.is_none(), r.error.as_ref()),
+        ).await
     }
     
     /// Get script hash history
@@ -262,10 +457,13 @@ impl WalletServices for ServiceCollection {
     async fn get_script_hash_history(
         &self,
         hash: &str,
-        _use_next: bool,
+        use_next: bool,
     ) -> ServiceResult<GetScriptHashHistoryResult> {
-        use crate::traits::UtxoStatusChecker;
-        self.utxo_checker.get_script_hash_history(hash).await
+        self.utxo_status_providers.call(
+            use_next,
+            |p| async move { p.get_script_hash_history(hash).await },
+            |_| CallOutcome::Done,
+        ).await
     }
 }
 
@@ -308,10 +506,98 @@ mod tests {
         assert_eq!(config.bsv_update_msecs, 1000 * 60 * 15);
     }
     
+    #[test]
+    fn test_provider_order() {
+        // TS Reference: Services constructor registers WhatsOnChain before Bitails
+        let services = ServiceCollection::new(ServiceConfig {
+            chain: Chain::Test,
+            bitails_api_key: Some("key".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(services.raw_tx_providers.names(), vec!["WoC", "Bitails"]);
+        assert_eq!(services.merkle_path_providers.names(), vec!["WoC", "Bitails"]);
+        assert_eq!(services.utxo_status_providers.names(), vec!["WoC", "Bitails"]);
+        assert!(services.broadcasters.is_empty());
+        
+        let services = ServiceCollection::new(ServiceConfig {
+            arc_url: Some("https://arc.example.com".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(services.broadcasters.names(), vec!["ARC"]);
+        assert!(services.service_call_history().is_empty());
+        assert!(services.provider_stats("getRawTx").is_empty());
+    }
+    
+    #[test]
+    fn test_arc_endpoints() {
+        let services = ServiceCollection::new(ServiceConfig {
+            arc_endpoints: vec![
+                ArcEndpoint::taal(Chain::Main).unwrap().with_api_key("taal-key"),
+                ArcEndpoint::gorilla_pool(Chain::Main).unwrap(),
+            ],
+            ..Default::default()
+        });
+        assert_eq!(services.broadcasters.names(), vec!["TAAL", "GorillaPool"]);
+        
+        // Endpoints follow the primary ARC
+        let services = ServiceCollection::new(ServiceConfig {
+            arc_endpoints: ArcEndpoint::gorilla_pool(Chain::Test).into_iter().collect(),
+            ..ServiceConfig::for_chain(Chain::Test)
+        });
+        assert_eq!(services.broadcasters.names(), vec!["ARC", "GorillaPool"]);
+    }
+    
+    #[test]
+    fn test_regtest_providers() {
+        let services = ServiceCollection::for_chain(Chain::Regtest);
+        assert_eq!(services.chain(), Chain::Regtest);
+        assert_eq!(services.broadcasters.names(), vec!["Node"]);
+        assert_eq!(services.raw_tx_providers.names(), vec!["Node"]);
+        assert_eq!(services.merkle_path_providers.names(), vec!["Node"]);
+        assert_eq!(services.utxo_status_providers.names(), vec!["Node"]);
+        
+        // A mock ARC takes broadcasts instead
+        let services = ServiceCollection::new(ServiceConfig {
+            chain: Chain::Regtest,
+            arc_url: Some("http://localhost:9090".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(services.broadcasters.names(), vec!["ARC"]);
+        assert_eq!(services.raw_tx_providers.names(), vec!["Node"]);
+    }
+    
+    #[test]
+    fn test_lookup_outcome() {
+        let error = crate::types::ServiceError {
+            service: "WoC".to_string(),
+            message: "down".to_string(),
+            status_code: None,
+        };
+        assert_eq!(lookup_outcome(true, None), CallOutcome::Done);
+        assert_eq!(lookup_outcome(false, None), CallOutcome::NotFound);
+        assert_eq!(lookup_outcome(false, Some(&error)), CallOutcome::Failed("down".to_string()));
+    }
+    
+    #[test]
+    fn test_post_beef_outcome() {
+        let result = |status: &str| PostBeefResult {
+            txid: "abc".to_string(),
+            status: status.to_string(),
+            name: Some("ARC".to_string()),
+            error: None,
+        };
+        assert_eq!(post_beef_outcome(&[result("success")]), CallOutcome::Done);
+        assert_eq!(post_beef_outcome(&[result("doubleSpend")]), CallOutcome::Done);
+        assert_eq!(post_beef_outcome(&[result("serviceError")]), CallOutcome::Failed("serviceError".to_string()));
+    }
+    
     #[test]
     fn test_for_chain() {
         let services = ServiceCollection::for_chain(Chain::Test);
         assert_eq!(services.chain(), Chain::Test);
+        assert_eq!(services.broadcasters.names(), vec!["ARC"]);
+        assert_eq!(services.config.arc_url.as_deref(), Some("https://arc-test.taal.com"));
+        assert!(services.chain_tracker.is_some());
     }
     
     #[test]
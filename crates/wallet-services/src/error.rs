@@ -50,6 +50,10 @@ pub enum ServiceError {
     #[error("Invalid parameters: {0}")]
     InvalidParams(String),
     
+    /// Local storage error
+    #[error("Storage error: {0}")]
+    Storage(String),
+    
     /// No services configured
     #[error("No services configured")]
     NoServices,
@@ -8,6 +8,7 @@ use chrono::{Utc, DateTime};
 use std::collections::HashMap;
 use crate::error::{ServiceError, ServiceResult};
 use crate::traits::{ExchangeRateProvider, FiatCurrency};
+use super::rate_provider::FiatRateSource;
 use super::types::{FiatExchangeRates, ExchangeRatesApiResponse};
 
 /// ExchangeRatesAPI client
@@ -93,6 +94,21 @@ impl ExchangeRatesApiClient {
             }
         }
         
+        let result = self.fetch_fiat_exchange_rates(target_currencies).await?;
+        
+        // Cache the result
+        self.cached_rates = Some(result.clone());
+        
+        Ok(result)
+    }
+    
+    /// Fetch USD-based rates for target currencies, bypassing the cache
+    ///
+    /// Reference: TS updateExchangeratesapi (exchangeRates.ts lines 32-60)
+    pub async fn fetch_fiat_exchange_rates(
+        &self,
+        target_currencies: &[String],
+    ) -> ServiceResult<FiatExchangeRates> {
         // Fetch rates (TS line 32)
         let io_rates = self.get_exchange_rates_io().await?;
         
@@ -133,17 +149,12 @@ impl ExchangeRatesApiClient {
         }
         
         // Build result (TS lines 39-43)
-        let result = FiatExchangeRates {
+        Ok(FiatExchangeRates {
             timestamp: DateTime::from_timestamp(io_rates.timestamp as i64, 0)
                 .unwrap_or_else(Utc::now),
             base: "USD".to_string(),
             rates: usd_based_rates,
-        };
-        
-        // Cache the result
-        self.cached_rates = Some(result.clone());
-        
-        Ok(result)
+        })
     }
 }
 
@@ -187,6 +198,17 @@ impl ExchangeRateProvider for ExchangeRatesApiClient {
     }
 }
 
+#[async_trait]
+impl FiatRateSource for ExchangeRatesApiClient {
+    fn name(&self) -> &str {
+        "ExchangeRatesAPI"
+    }
+    
+    async fn fetch_fiat_rates(&self, currencies: &[String]) -> ServiceResult<FiatExchangeRates> {
+        self.fetch_fiat_exchange_rates(currencies).await
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
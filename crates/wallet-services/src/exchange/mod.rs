@@ -8,7 +8,9 @@
 pub mod types;
 pub mod whatsonchain;
 pub mod exchangeratesapi;
+pub mod rate_provider;
 
 pub use types::*;
 pub use whatsonchain::WhatsOnChainExchangeRate;
 pub use exchangeratesapi::ExchangeRatesApiClient;
+pub use rate_provider::{BsvRateSource, FiatRateSource, RateProvider, RateProviderConfig};
@@ -0,0 +1,400 @@
+//! Exchange Rate Provider Facade
+//!
+//! **Reference**: TypeScript `src/services/Services.ts` (getBsvExchangeRate, getFiatExchangeRate)
+//! **Reference**: TypeScript `src/services/providers/exchangeRates.ts` (updateFiatExchangeRates)
+//!
+//! Caches BSV and fiat exchange rates with configurable freshness, falls back
+//! between providers, and converts between arbitrary currency codes.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::{ExchangeRateProvider, FiatCurrency};
+use super::types::{BsvExchangeRate, FiatExchangeRates};
+
+/// Currencies refreshed on every fiat update
+///
+/// Reference: TS `fiatExchangeRates.rates` defaults (USD, GBP, EUR)
+pub const DEFAULT_FIAT_CURRENCIES: [&str; 3] = ["USD", "GBP", "EUR"];
+
+/// Satoshis per BSV
+const SATOSHIS_PER_BSV: f64 = 100_000_000.0;
+
+/// Source of BSV/USD rates
+#[async_trait]
+pub trait BsvRateSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Fetch the current rate without caching
+    async fn fetch_bsv_rate(&self) -> ServiceResult<BsvExchangeRate>;
+}
+
+/// Source of USD-based fiat rates
+#[async_trait]
+pub trait FiatRateSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Fetch units of each currency per USD without caching
+    async fn fetch_fiat_rates(&self, currencies: &[String]) -> ServiceResult<FiatExchangeRates>;
+}
+
+/// Cache freshness settings
+///
+/// Reference: TS WalletServicesOptions (bsvUpdateMsecs, fiatUpdateMsecs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateProviderConfig {
+    /// Maximum age of a cached BSV rate (milliseconds)
+    pub bsv_update_msecs: u64,
+
+    /// Maximum age of cached fiat rates (milliseconds)
+    pub fiat_update_msecs: u64,
+}
+
+impl Default for RateProviderConfig {
+    fn default() -> Self {
+        Self {
+            bsv_update_msecs: 1000 * 60 * 15, // 15 minutes
+            fiat_update_msecs: 1000 * 60 * 60 * 24, // 24 hours
+        }
+    }
+}
+
+/// Exchange rate facade with caching and provider fallback
+pub struct RateProvider {
+    config: RateProviderConfig,
+    bsv_sources: Vec<Arc<dyn BsvRateSource>>,
+    fiat_sources: Vec<Arc<dyn FiatRateSource>>,
+    bsv_rate: Mutex<Option<BsvExchangeRate>>,
+    fiat_rates: Mutex<Option<FiatExchangeRates>>,
+    /// Currencies kept in the fiat cache; grows as new codes are requested
+    fiat_currencies: Mutex<Vec<String>>,
+}
+
+impl RateProvider {
+    pub fn new(config: RateProviderConfig) -> Self {
+        Self {
+            config,
+            bsv_sources: Vec::new(),
+            fiat_sources: Vec::new(),
+            bsv_rate: Mutex::new(None),
+            fiat_rates: Mutex::new(None),
+            fiat_currencies: Mutex::new(DEFAULT_FIAT_CURRENCIES.iter().map(|c| c.to_string()).collect()),
+        }
+    }
+
+    /// Add a BSV rate source; sources are tried in the order added
+    pub fn with_bsv_source(mut self, source: Arc<dyn BsvRateSource>) -> Self {
+        self.bsv_sources.push(source);
+        self
+    }
+
+    /// Add a fiat rate source; sources are tried in the order added
+    pub fn with_fiat_source(mut self, source: Arc<dyn FiatRateSource>) -> Self {
+        self.fiat_sources.push(source);
+        self
+    }
+
+    fn is_fresh(timestamp: chrono::DateTime<Utc>, max_age_msecs: u64) -> bool {
+        let age = Utc::now().signed_duration_since(timestamp).num_milliseconds();
+        age >= 0 && (age as u64) < max_age_msecs
+    }
+
+    /// Get the BSV/USD rate, refreshing it when stale
+    ///
+    /// Reference: TS Services.getBsvExchangeRate
+    ///
+    /// When every source fails a stale cached rate is returned, if any.
+    pub async fn get_bsv_exchange_rate(&self) -> ServiceResult<BsvExchangeRate> {
+        let cached = self.bsv_rate.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(rate) = &cached {
+            if Self::is_fresh(rate.timestamp, self.config.bsv_update_msecs) {
+                return Ok(rate.clone());
+            }
+        }
+
+        let mut last_error = None;
+        for source in &self.bsv_sources {
+            match source.fetch_bsv_rate().await {
+                Ok(rate) => {
+                    *self.bsv_rate.lock().unwrap_or_else(|e| e.into_inner()) = Some(rate.clone());
+                    return Ok(rate);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        cached.ok_or(last_error.unwrap_or(ServiceError::NoServices))
+    }
+
+    /// Get USD-based rates covering `currencies`, refreshing when stale
+    ///
+    /// Reference: TS updateFiatExchangeRates
+    ///
+    /// Cached rates are reused while fresh and complete. Each source is asked
+    /// for all tracked currencies; later sources fill in codes earlier ones lack.
+    pub async fn get_fiat_exchange_rates(&self, currencies: &[&str]) -> ServiceResult<FiatExchangeRates> {
+        let targets = {
+            let mut tracked = self.fiat_currencies.lock().unwrap_or_else(|e| e.into_inner());
+            for c in currencies {
+                let c = c.to_uppercase();
+                if !tracked.contains(&c) {
+                    tracked.push(c);
+                }
+            }
+            tracked.clone()
+        };
+
+        let cached = self.fiat_rates.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(rates) = &cached {
+            let complete = currencies.iter().all(|c| rates.rates.contains_key(&c.to_uppercase()));
+            if complete && Self::is_fresh(rates.timestamp, self.config.fiat_update_msecs) {
+                return Ok(rates.clone());
+            }
+        }
+
+        let mut merged: HashMap<String, f64> = HashMap::from([("USD".to_string(), 1.0)]);
+        let mut timestamp = None;
+        let mut last_error = None;
+        for source in &self.fiat_sources {
+            let missing: Vec<String> = targets.iter().filter(|c| !merged.contains_key(*c)).cloned().collect();
+            if missing.is_empty() {
+                break;
+            }
+            match source.fetch_fiat_rates(&missing).await {
+                Ok(r) => {
+                    timestamp = Some(timestamp.map_or(r.timestamp, |t: chrono::DateTime<Utc>| t.min(r.timestamp)));
+                    for (code, rate) in r.rates {
+                        merged.entry(code.to_uppercase()).or_insert(rate);
+                    }
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        if timestamp.is_none() {
+            // Every source failed (or none configured): fall back to stale rates
+            return cached.ok_or(last_error.unwrap_or(ServiceError::NoServices));
+        }
+
+        // Keep previously cached codes the sources did not return
+        if let Some(old) = cached {
+            for (code, rate) in old.rates {
+                merged.entry(code).or_insert(rate);
+            }
+        }
+
+        let rates = FiatExchangeRates {
+            timestamp: timestamp.unwrap_or_else(Utc::now),
+            base: "USD".to_string(),
+            rates: merged,
+        };
+        *self.fiat_rates.lock().unwrap_or_else(|e| e.into_inner()) = Some(rates.clone());
+        Ok(rates)
+    }
+
+    /// Units of `currency` per one unit of `base`
+    ///
+    /// Reference: TS Services.getFiatExchangeRate
+    pub async fn fiat_rate(&self, currency: &str, base: &str) -> ServiceResult<f64> {
+        let rates = self.get_fiat_exchange_rates(&[currency, base]).await?;
+        cross_rate(&rates, currency, base)
+    }
+
+    /// Convert a fiat amount between currency codes
+    pub async fn convert_fiat(&self, amount: f64, from: &str, to: &str) -> ServiceResult<f64> {
+        Ok(amount * self.fiat_rate(to, from).await?)
+    }
+
+    /// Value of `satoshis` in `currency`
+    pub async fn satoshis_to_fiat(&self, satoshis: i64, currency: &str) -> ServiceResult<f64> {
+        let usd = satoshis as f64 / SATOSHIS_PER_BSV * self.get_bsv_exchange_rate().await?.rate;
+        Ok(usd * self.fiat_rate(currency, "USD").await?)
+    }
+
+    /// Satoshis worth `amount` of `currency`, rounded to the nearest satoshi
+    pub async fn fiat_to_satoshis(&self, amount: f64, currency: &str) -> ServiceResult<i64> {
+        let usd = amount * self.fiat_rate("USD", currency).await?;
+        let bsv_rate = self.get_bsv_exchange_rate().await?.rate;
+        if bsv_rate <= 0.0 {
+            return Err(ServiceError::InvalidResponse(format!("invalid BSV rate {}", bsv_rate)));
+        }
+        Ok((usd / bsv_rate * SATOSHIS_PER_BSV).round() as i64)
+    }
+}
+
+/// Units of `currency` per unit of `base`, given USD-based rates
+fn cross_rate(rates: &FiatExchangeRates, currency: &str, base: &str) -> ServiceResult<f64> {
+    let lookup = |code: &str| {
+        rates.rates.get(&code.to_uppercase()).copied()
+            .ok_or_else(|| ServiceError::InvalidParams(format!("No exchange rate for {}", code)))
+    };
+    let base_rate = lookup(base)?;
+    if base_rate == 0.0 {
+        return Err(ServiceError::InvalidResponse(format!("zero exchange rate for {}", base)));
+    }
+    Ok(lookup(currency)? / base_rate)
+}
+
+#[async_trait]
+impl ExchangeRateProvider for RateProvider {
+    async fn get_bsv_rate(&self) -> ServiceResult<f64> {
+        Ok(self.get_bsv_exchange_rate().await?.rate)
+    }
+
+    async fn get_fiat_rate(
+        &self,
+        currency: FiatCurrency,
+        base: Option<FiatCurrency>,
+    ) -> ServiceResult<f64> {
+        self.fiat_rate(currency.as_str(), base.unwrap_or(FiatCurrency::USD).as_str()).await
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockBsv {
+        rate: Option<f64>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BsvRateSource for MockBsv {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        async fn fetch_bsv_rate(&self) -> ServiceResult<BsvExchangeRate> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let rate = self.rate.ok_or_else(|| ServiceError::Unavailable("mock".to_string()))?;
+            Ok(BsvExchangeRate { timestamp: Utc::now(), base: "USD".to_string(), rate })
+        }
+    }
+
+    struct MockFiat {
+        rates: Option<Vec<(&'static str, f64)>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl FiatRateSource for MockFiat {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        async fn fetch_fiat_rates(&self, currencies: &[String]) -> ServiceResult<FiatExchangeRates> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let rates = self.rates.as_ref().ok_or_else(|| ServiceError::Unavailable("mock".to_string()))?;
+            Ok(FiatExchangeRates {
+                timestamp: Utc::now(),
+                base: "USD".to_string(),
+                rates: rates.iter()
+                    .filter(|(c, _)| currencies.iter().any(|t| t == c))
+                    .map(|(c, r)| (c.to_string(), *r))
+                    .collect(),
+            })
+        }
+    }
+
+    fn bsv(rate: Option<f64>) -> Arc<MockBsv> {
+        Arc::new(MockBsv { rate, calls: AtomicUsize::new(0) })
+    }
+
+    fn fiat(rates: Option<Vec<(&'static str, f64)>>) -> Arc<MockFiat> {
+        Arc::new(MockFiat { rates, calls: AtomicUsize::new(0) })
+    }
+
+    #[tokio::test]
+    async fn test_bsv_rate_cached_while_fresh() {
+        let source = bsv(Some(50.0));
+        let provider = RateProvider::new(RateProviderConfig::default()).with_bsv_source(source.clone());
+
+        assert_eq!(provider.get_bsv_rate().await.unwrap(), 50.0);
+        assert_eq!(provider.get_bsv_rate().await.unwrap(), 50.0);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        // Zero freshness always refetches
+        let source = bsv(Some(50.0));
+        let provider = RateProvider::new(RateProviderConfig { bsv_update_msecs: 0, fiat_update_msecs: 0 })
+            .with_bsv_source(source.clone());
+        provider.get_bsv_rate().await.unwrap();
+        provider.get_bsv_rate().await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bsv_rate_fallback_and_stale() {
+        let provider = RateProvider::new(RateProviderConfig::default())
+            .with_bsv_source(bsv(None))
+            .with_bsv_source(bsv(Some(42.0)));
+        assert_eq!(provider.get_bsv_rate().await.unwrap(), 42.0);
+
+        // All sources down: stale cached rate is used
+        let provider = RateProvider::new(RateProviderConfig::default()).with_bsv_source(bsv(None));
+        assert!(provider.get_bsv_rate().await.is_err());
+        *provider.bsv_rate.lock().unwrap() = Some(BsvExchangeRate {
+            timestamp: Utc::now() - chrono::Duration::days(1),
+            base: "USD".to_string(),
+            rate: 30.0,
+        });
+        assert_eq!(provider.get_bsv_rate().await.unwrap(), 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_fiat_rates_merged_across_sources() {
+        let first = fiat(Some(vec![("EUR", 0.9), ("GBP", 0.8)]));
+        let second = fiat(Some(vec![("JPY", 150.0), ("EUR", 0.5)]));
+        let provider = RateProvider::new(RateProviderConfig::default())
+            .with_fiat_source(first.clone())
+            .with_fiat_source(second.clone());
+
+        let rates = provider.get_fiat_exchange_rates(&["jpy"]).await.unwrap();
+        assert_eq!(rates.rates["USD"], 1.0);
+        assert_eq!(rates.rates["EUR"], 0.9);
+        assert_eq!(rates.rates["JPY"], 150.0);
+
+        // Fresh and complete: served from cache
+        provider.get_fiat_exchange_rates(&["EUR", "JPY"]).await.unwrap();
+        assert_eq!(first.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fiat_cross_rates_and_conversion() {
+        let provider = RateProvider::new(RateProviderConfig::default())
+            .with_bsv_source(bsv(Some(50.0)))
+            .with_fiat_source(fiat(Some(vec![("EUR", 0.5), ("GBP", 0.25), ("CHF", 2.0)])));
+
+        assert_eq!(provider.fiat_rate("EUR", "USD").await.unwrap(), 0.5);
+        assert_eq!(provider.fiat_rate("CHF", "GBP").await.unwrap(), 8.0);
+        assert_eq!(provider.convert_fiat(10.0, "EUR", "GBP").await.unwrap(), 5.0);
+        assert_eq!(
+            provider.get_fiat_rate(FiatCurrency::GBP, Some(FiatCurrency::EUR)).await.unwrap(),
+            0.5
+        );
+
+        // 1 BSV = $50 = €25
+        assert_eq!(provider.satoshis_to_fiat(100_000_000, "EUR").await.unwrap(), 25.0);
+        assert_eq!(provider.fiat_to_satoshis(25.0, "EUR").await.unwrap(), 100_000_000);
+
+        assert!(provider.fiat_rate("XYZ", "USD").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fiat_rates_no_sources() {
+        let provider = RateProvider::new(RateProviderConfig::default());
+        assert!(matches!(
+            provider.get_fiat_exchange_rates(&["EUR"]).await,
+            Err(ServiceError::NoServices)
+        ));
+    }
+}
@@ -9,6 +9,7 @@ use crate::error::{ServiceError, ServiceResult};
 use crate::traits::ExchangeRateProvider;
 use crate::traits::FiatCurrency;
 use crate::types::Chain;
+use super::rate_provider::BsvRateSource;
 use super::types::{BsvExchangeRate, WhatsOnChainExchangeRateResponse};
 
 /// WhatsOnChain exchange rate provider
@@ -41,7 +42,8 @@ impl WhatsOnChainExchangeRate {
     pub fn new(chain: Chain) -> Self {
         let url = match chain {
             Chain::Main => "https://api.whatsonchain.com/v1/bsv/main",
-            Chain::Test => "https://api.whatsonchain.com/v1/bsv/test",
+            // No public indexer serves regtest
+            Chain::Test | Chain::Regtest => "https://api.whatsonchain.com/v1/bsv/test",
         };
         
         Self {
@@ -68,6 +70,18 @@ impl WhatsOnChainExchangeRate {
             }
         }
         
+        let new_rate = self.fetch_bsv_exchange_rate().await?;
+        
+        // Cache the rate
+        self.cached_rate = Some(new_rate.clone());
+        
+        Ok(new_rate)
+    }
+    
+    /// Fetch the current BSV exchange rate, bypassing the cache
+    ///
+    /// Reference: TS WhatsOnChain.updateBsvExchangeRate (lines 321-347)
+    pub async fn fetch_bsv_exchange_rate(&self) -> ServiceResult<BsvExchangeRate> {
         // Fetch new rate with retry (TS lines 321-346)
         for retry in 0..2 {
             let url = format!("{}/exchangerate", self.url);
@@ -100,16 +114,11 @@ impl WhatsOnChainExchangeRate {
                     }
                     
                     // Build result (TS lines 339-343)
-                    let new_rate = BsvExchangeRate {
+                    return Ok(BsvExchangeRate {
                         timestamp: Utc::now(),
                         base: "USD".to_string(),
                         rate: woc_rate.rate,
-                    };
-                    
-                    // Cache the rate
-                    self.cached_rate = Some(new_rate.clone());
-                    
-                    return Ok(new_rate);
+                    });
                 }
                 Err(e) => {
                     if retry >= 1 {
@@ -153,6 +162,17 @@ impl ExchangeRateProvider for WhatsOnChainExchangeRate {
     }
 }
 
+#[async_trait]
+impl BsvRateSource for WhatsOnChainExchangeRate {
+    fn name(&self) -> &str {
+        "WhatsOnChain"
+    }
+    
+    async fn fetch_bsv_rate(&self) -> ServiceResult<BsvExchangeRate> {
+        self.fetch_bsv_exchange_rate().await
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
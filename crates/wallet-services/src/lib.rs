@@ -18,13 +18,20 @@ pub mod broadcaster;
 pub mod utxo;
 pub mod exchange;
 pub mod collection;
+pub mod provider_collection;
+pub mod regtest;
 
 // Re-exports
 pub use error::{ServiceError, ServiceResult};
 pub use types::*;
 pub use traits::*;
-pub use chaintracker::{ChaintracksClient, BlockHeader, ChaintracksInfo};
-pub use broadcaster::{ArcBroadcaster, ArcConfig};
-pub use utxo::{WhatsOnChainClient, UtxoDetail, validate_script_hash};
-pub use exchange::{BsvExchangeRate, FiatExchangeRates, WhatsOnChainExchangeRate, ExchangeRatesApiClient};
+pub use chaintracker::{ChaintracksClient, WhatsOnChainChainTracker, BlockHeader, ChaintracksInfo, HeaderEvent, HeaderPoller};
+pub use broadcaster::{ArcBroadcaster, ArcConfig, ArcEndpoint, ArcTxStatus, BroadcastResult, BroadcastStatus};
+pub use utxo::{BitailsClient, WhatsOnChainClient, UtxoDetail, validate_script_hash};
+pub use exchange::{
+    BsvExchangeRate, FiatExchangeRates, WhatsOnChainExchangeRate, ExchangeRatesApiClient,
+    RateProvider, RateProviderConfig,
+};
 pub use collection::{ServiceCollection, ServiceConfig};
+pub use provider_collection::{CallOutcome, FailoverConfig, ProviderCollection, ProviderStats, ServiceCall};
+pub use regtest::{regtest_address, NodeRpcConfig, RegtestNode};
@@ -0,0 +1,472 @@
+//! Provider Collection
+//!
+//! **Reference**: TypeScript `src/services/ServiceCollection.ts`
+//!
+//! Ordered set of interchangeable providers for one service method, with
+//! round-robin rotation, per-provider statistics, cooldown after repeated
+//! failures, and a history of which provider served each call.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::error::{ServiceError, ServiceResult};
+
+/// Number of recent calls kept in the call history
+const MAX_CALL_HISTORY: usize = 64;
+
+/// Failover configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailoverConfig {
+    /// Consecutive failures after which a provider is skipped
+    pub failures_before_cooldown: u32,
+
+    /// How long a failing provider is skipped
+    pub cooldown: Duration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            failures_before_cooldown: 3,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// How a provider's result should be treated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// Result is final; stop trying providers
+    Done,
+    /// Provider worked but had no answer (e.g. tx not found); try the next one
+    NotFound,
+    /// Provider reported an error in its result; try the next one
+    Failed(String),
+}
+
+/// Per-provider statistics
+///
+/// Reference: TS ServiceCollection call history counts (success, failure, error)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderStats {
+    pub calls: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// Total latency of all calls in milliseconds
+    pub total_msecs: u64,
+    pub last_error: Option<String>,
+    /// Skipped until this instant
+    pub cooldown_until: Option<Instant>,
+}
+
+impl ProviderStats {
+    /// Fraction of calls that failed
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.calls as f64
+        }
+    }
+
+    /// Average call latency in milliseconds
+    pub fn average_msecs(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.total_msecs as f64 / self.calls as f64
+        }
+    }
+
+    fn in_cooldown(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| now < until)
+    }
+}
+
+/// One provider call
+///
+/// Reference: TS ServiceCall
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceCall {
+    /// Service method, e.g. "getRawTx"
+    pub method: &'static str,
+    /// Provider name
+    pub provider: String,
+    pub success: bool,
+    pub msecs: u64,
+    pub error: Option<String>,
+}
+
+/// Named provider
+struct Provider<T: ?Sized> {
+    name: String,
+    service: Arc<T>,
+}
+
+/// Providers for one service method
+///
+/// Reference: TS ServiceCollection<T>
+pub struct ProviderCollection<T: ?Sized> {
+    /// Service method name used in call history
+    method: &'static str,
+    providers: Vec<Provider<T>>,
+    /// Index of the preferred provider
+    index: AtomicUsize,
+    config: FailoverConfig,
+    stats: Mutex<HashMap<String, ProviderStats>>,
+    history: Mutex<VecDeque<ServiceCall>>,
+}
+
+impl<T: ?Sized> ProviderCollection<T> {
+    pub fn new(method: &'static str, config: FailoverConfig) -> Self {
+        Self {
+            method,
+            providers: Vec::new(),
+            index: AtomicUsize::new(0),
+            config,
+            stats: Mutex::new(HashMap::new()),
+            history: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Add a provider at the end of the rotation
+    ///
+    /// Reference: TS ServiceCollection.add
+    pub fn add(mut self, name: impl Into<String>, service: Arc<T>) -> Self {
+        self.providers.push(Provider {
+            name: name.into(),
+            service,
+        });
+        self
+    }
+
+    /// Provider names in rotation order
+    pub fn names(&self) -> Vec<String> {
+        self.providers.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Advance the preferred provider
+    ///
+    /// Reference: TS ServiceCollection.next
+    pub fn next(&self) -> usize {
+        if self.providers.is_empty() {
+            return 0;
+        }
+        let len = self.providers.len();
+        let prev = self.index
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| Some((i + 1) % len))
+            .unwrap_or_default();
+        (prev + 1) % len
+    }
+
+    /// Make the first provider preferred again
+    ///
+    /// Reference: TS ServiceCollection.reset
+    pub fn reset(&self) {
+        self.index.store(0, Ordering::SeqCst);
+    }
+
+    /// Statistics for each provider that has been called
+    pub fn stats(&self) -> HashMap<String, ProviderStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Most recent calls, oldest first
+    pub fn call_history(&self) -> Vec<ServiceCall> {
+        self.history.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// Indices of providers to try, starting at the preferred one
+    ///
+    /// Providers in cooldown are skipped unless every provider is in cooldown.
+    fn call_order(&self, now: Instant) -> Vec<usize> {
+        let len = self.providers.len();
+        let start = self.index.load(Ordering::SeqCst) % len.max(1);
+        let rotated: Vec<usize> = (0..len).map(|i| (start + i) % len).collect();
+
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let available: Vec<usize> = rotated.iter()
+            .copied()
+            .filter(|&i| !stats.get(&self.providers[i].name).is_some_and(|s| s.in_cooldown(now)))
+            .collect();
+        if available.is_empty() { rotated } else { available }
+    }
+
+    fn record(&self, provider: &str, msecs: u64, error: Option<String>, now: Instant) {
+        match &error {
+            None => debug!(method = self.method, provider, msecs, "service call succeeded"),
+            Some(e) => warn!(method = self.method, provider, msecs, error = %e, "service call failed"),
+        }
+        #[cfg(feature = "metrics")]
+        wallet_storage::metrics::observe(
+            wallet_storage::metrics::SERVICE_CALL_SECONDS,
+            &[
+                ("method", self.method),
+                ("provider", provider),
+                ("outcome", if error.is_none() { "success" } else { "failure" }),
+            ],
+            msecs as f64 / 1000.0,
+        );
+        {
+            let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+            let s = stats.entry(provider.to_string()).or_default();
+            s.calls += 1;
+            s.total_msecs += msecs;
+            match &error {
+                None => {
+                    s.successes += 1;
+                    s.consecutive_failures = 0;
+                    s.cooldown_until = None;
+                }
+                Some(e) => {
+                    s.failures += 1;
+                    s.consecutive_failures += 1;
+                    s.last_error = Some(e.clone());
+                    if s.consecutive_failures >= self.config.failures_before_cooldown {
+                        s.cooldown_until = Some(now + self.config.cooldown);
+                    }
+                }
+            }
+        }
+
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if history.len() >= MAX_CALL_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(ServiceCall {
+            method: self.method,
+            provider: provider.to_string(),
+            success: error.is_none(),
+            msecs,
+            error,
+        });
+    }
+
+    /// Try providers in order until one produces a final result
+    ///
+    /// Reference: TS Services.getRawTx / getMerklePath / postBeef provider loops
+    ///
+    /// With `use_next` the rotation advances first, so repeated calls spread
+    /// across providers. Returns the first `Done` result; otherwise the last
+    /// result obtained, or the last error if no provider returned a result.
+    pub async fn call<R, F, Fut>(
+        &self,
+        use_next: bool,
+        call: F,
+        outcome: impl Fn(&R) -> CallOutcome,
+    ) -> ServiceResult<R>
+    where
+        F: Fn(Arc<T>) -> Fut,
+        Fut: Future<Output = ServiceResult<R>>,
+    {
+        if use_next {
+            self.next();
+        }
+        self.call_providers(false, call, outcome).await
+    }
+
+    /// Call every provider, e.g. to fan a broadcast out to all of them
+    ///
+    /// Providers are called one after another in rotation order. Returns the
+    /// first `Done` result; otherwise as `call`.
+    pub async fn call_all<R, F, Fut>(&self, call: F, outcome: impl Fn(&R) -> CallOutcome) -> ServiceResult<R>
+    where
+        F: Fn(Arc<T>) -> Fut,
+        Fut: Future<Output = ServiceResult<R>>,
+    {
+        self.call_providers(true, call, outcome).await
+    }
+
+    async fn call_providers<R, F, Fut>(
+        &self,
+        all: bool,
+        call: F,
+        outcome: impl Fn(&R) -> CallOutcome,
+    ) -> ServiceResult<R>
+    where
+        F: Fn(Arc<T>) -> Fut,
+        Fut: Future<Output = ServiceResult<R>>,
+    {
+        if self.providers.is_empty() {
+            return Err(ServiceError::NoServices);
+        }
+
+        let mut done: Option<R> = None;
+        let mut last_result: Option<R> = None;
+        let mut last_error: Option<ServiceError> = None;
+        for i in self.call_order(Instant::now()) {
+            let provider = &self.providers[i];
+            let started = Instant::now();
+            let r = call(provider.service.clone()).await;
+            let msecs = started.elapsed().as_millis() as u64;
+
+            match r {
+                Ok(r) => match outcome(&r) {
+                    CallOutcome::Done => {
+                        self.record(&provider.name, msecs, None, Instant::now());
+                        if !all {
+                            return Ok(r);
+                        }
+                        done.get_or_insert(r);
+                    }
+                    CallOutcome::NotFound => {
+                        self.record(&provider.name, msecs, None, Instant::now());
+                        last_result = Some(r);
+                    }
+                    CallOutcome::Failed(e) => {
+                        self.record(&provider.name, msecs, Some(e), Instant::now());
+                        last_result = Some(r);
+                    }
+                },
+                Err(e) => {
+                    self.record(&provider.name, msecs, Some(e.to_string()), Instant::now());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if let Some(r) = done {
+            return Ok(r);
+        }
+        match (last_result, last_error) {
+            (Some(r), _) => Ok(r),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(ServiceError::AllServicesFailed),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test provider returning a fixed answer
+    struct Fixed(Result<Option<u32>, &'static str>);
+
+    impl Fixed {
+        async fn get(&self) -> ServiceResult<Option<u32>> {
+            self.0.map_err(|e| ServiceError::Unavailable(e.to_string()))
+        }
+    }
+
+    fn outcome(r: &Option<u32>) -> CallOutcome {
+        if r.is_some() { CallOutcome::Done } else { CallOutcome::NotFound }
+    }
+
+    fn collection(providers: Vec<(&str, Fixed)>, config: FailoverConfig) -> ProviderCollection<Fixed> {
+        providers.into_iter()
+            .fold(ProviderCollection::new("getTest", config), |c, (name, p)| c.add(name, Arc::new(p)))
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_in_order() {
+        // TS Reference: Services.getRawTx tries each service until one succeeds
+        let c = collection(
+            vec![("a", Fixed(Err("down"))), ("b", Fixed(Ok(None))), ("c", Fixed(Ok(Some(3))))],
+            FailoverConfig::default(),
+        );
+
+        let r = c.call(false, |p| async move { p.get().await }, outcome).await.unwrap();
+        assert_eq!(r, Some(3));
+
+        let history = c.call_history();
+        let served: Vec<(&str, bool)> = history.iter().map(|h| (h.provider.as_str(), h.success)).collect();
+        assert_eq!(served, vec![("a", false), ("b", true), ("c", true)]);
+        assert_eq!(history[0].method, "getTest");
+
+        let stats = c.stats();
+        assert_eq!(stats["a"].failures, 1);
+        assert_eq!(stats["a"].error_rate(), 1.0);
+        assert_eq!(stats["c"].successes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_returns_last_result_or_error() {
+        let c = collection(vec![("a", Fixed(Ok(None))), ("b", Fixed(Err("down")))], FailoverConfig::default());
+        assert_eq!(c.call(false, |p| async move { p.get().await }, outcome).await.unwrap(), None);
+
+        let c = collection(vec![("a", Fixed(Err("down")))], FailoverConfig::default());
+        assert!(c.call(false, |p| async move { p.get().await }, outcome).await.is_err());
+
+        let empty: ProviderCollection<Fixed> = ProviderCollection::new("getTest", FailoverConfig::default());
+        assert!(matches!(
+            empty.call(false, |p| async move { p.get().await }, outcome).await,
+            Err(ServiceError::NoServices)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_call_all() {
+        let c = collection(
+            vec![("a", Fixed(Ok(Some(1)))), ("b", Fixed(Err("down"))), ("c", Fixed(Ok(Some(3))))],
+            FailoverConfig::default(),
+        );
+        assert_eq!(c.call_all(|p| async move { p.get().await }, outcome).await.unwrap(), Some(1));
+        let served: Vec<(String, bool)> = c.call_history().into_iter().map(|h| (h.provider, h.success)).collect();
+        assert_eq!(served, vec![("a".to_string(), true), ("b".to_string(), false), ("c".to_string(), true)]);
+
+        let c = collection(vec![("a", Fixed(Ok(None))), ("b", Fixed(Err("down")))], FailoverConfig::default());
+        assert_eq!(c.call_all(|p| async move { p.get().await }, outcome).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_with_use_next() {
+        // TS Reference: ServiceCollection.next
+        let c = collection(vec![("a", Fixed(Ok(Some(1)))), ("b", Fixed(Ok(Some(2))))], FailoverConfig::default());
+
+        assert_eq!(c.call(false, |p| async move { p.get().await }, outcome).await.unwrap(), Some(1));
+        assert_eq!(c.call(true, |p| async move { p.get().await }, outcome).await.unwrap(), Some(2));
+        assert_eq!(c.call(true, |p| async move { p.get().await }, outcome).await.unwrap(), Some(1));
+
+        c.next();
+        c.reset();
+        assert_eq!(c.call(false, |p| async move { p.get().await }, outcome).await.unwrap(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_skips_failing_provider() {
+        let config = FailoverConfig {
+            failures_before_cooldown: 2,
+            cooldown: Duration::from_secs(60),
+        };
+        let c = collection(vec![("a", Fixed(Err("down"))), ("b", Fixed(Ok(Some(2))))], config);
+
+        for _ in 0..2 {
+            c.call(false, |p| async move { p.get().await }, outcome).await.unwrap();
+        }
+        assert!(c.stats()["a"].cooldown_until.is_some());
+
+        // "a" is now skipped entirely
+        c.call(false, |p| async move { p.get().await }, outcome).await.unwrap();
+        assert_eq!(c.stats()["a"].calls, 2);
+        assert_eq!(c.stats()["b"].calls, 3);
+    }
+
+    #[test]
+    fn test_all_in_cooldown_still_tried() {
+        let c = collection(vec![("a", Fixed(Err("down")))], FailoverConfig::default());
+        let now = Instant::now();
+        for _ in 0..3 {
+            c.record("a", 10, Some("down".to_string()), now);
+        }
+        assert_eq!(c.call_order(now), vec![0]);
+        assert_eq!(c.stats()["a"].average_msecs(), 10.0);
+    }
+}
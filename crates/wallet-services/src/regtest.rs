@@ -0,0 +1,658 @@
+//! Regtest node
+//!
+//! Services over the JSON-RPC interface of a local SV node, for running a
+//! wallet end to end against a disposable regtest network: headers, merkle
+//! proofs, raw transactions and broadcasting all come from the node, and
+//! `mine_blocks` / `fund_p2pkh` drive the chain from tests.
+//!
+//! With `Chain::Regtest`, `ServiceCollection` uses a `RegtestNode` for every
+//! lookup, and for broadcasting unless an ARC URL (e.g. a mock ARC) is
+//! configured.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::{Broadcaster, ChainTracker, MerklePathProvider, OutputRef, RawTxProvider, UtxoStatusChecker};
+use crate::types::*;
+use crate::utxo::tsc_proof::{tsc_proof_to_merkle_path, TscMerkleProof};
+
+/// Service name reported in results
+const NODE_NAME: &str = "Node";
+
+/// Regtest P2PKH address version byte
+const REGTEST_P2PKH_VERSION: u8 = 0x6f;
+
+const BEEF_V1: u32 = 4022206465;
+const BEEF_V2: u32 = 4022206466;
+const ATOMIC_BEEF: u32 = 0x01010101;
+
+/// Node JSON-RPC endpoint
+#[derive(Debug, Clone)]
+pub struct NodeRpcConfig {
+    /// RPC URL
+    pub url: String,
+
+    /// `rpcuser`
+    pub user: Option<String>,
+
+    /// `rpcpassword`
+    pub password: Option<String>,
+}
+
+impl Default for NodeRpcConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:18332".to_string(),
+            user: None,
+            password: None,
+        }
+    }
+}
+
+/// JSON-RPC response envelope
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// Local SV node reached over JSON-RPC
+#[derive(Debug, Clone)]
+pub struct RegtestNode {
+    config: NodeRpcConfig,
+    client: Client,
+}
+
+impl RegtestNode {
+    pub fn new(config: NodeRpcConfig) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    /// Call RPC `method` with `params`
+    pub async fn rpc<T: DeserializeOwned>(&self, method: &str, params: Value) -> ServiceResult<T> {
+        let mut request = self.client.post(&self.config.url).json(&json!({
+            "jsonrpc": "1.0",
+            "id": "wallet-services",
+            "method": method,
+            "params": params,
+        }));
+        if let Some(user) = &self.config.user {
+            request = request.basic_auth(user, self.config.password.as_ref());
+        }
+        let response = request.send().await?;
+
+        // RPC errors arrive with an HTTP error status and a JSON body
+        let status = response.status();
+        let body = response.text().await?;
+        let parsed: RpcResponse = serde_json::from_str(&body)
+            .map_err(|_| ServiceError::InvalidResponse(format!("{} returned HTTP {}", method, status)))?;
+        if let Some(error) = parsed.error {
+            return Err(ServiceError::ServiceFailed {
+                service: NODE_NAME.to_string(),
+                message: error.message,
+            });
+        }
+        Ok(serde_json::from_value(parsed.result.unwrap_or(Value::Null))?)
+    }
+
+    /// Height of the chain tip
+    pub async fn get_block_count(&self) -> ServiceResult<u32> {
+        self.rpc("getblockcount", json!([])).await
+    }
+
+    /// Hash of the block at `height`
+    pub async fn get_block_hash(&self, height: u32) -> ServiceResult<String> {
+        self.rpc("getblockhash", json!([height]))
+            .await
+            .map_err(|e| rejected_as(e, ServiceError::BlockNotFound(height)))
+    }
+
+    /// Mine `count` blocks to a fresh node wallet address, returning their hashes
+    pub async fn mine_blocks(&self, count: u32) -> ServiceResult<Vec<String>> {
+        let address: String = self.rpc("getnewaddress", json!([])).await?;
+        self.rpc("generatetoaddress", json!([count, address])).await
+    }
+
+    /// Pay `satoshis` from the node's wallet to the P2PKH output of
+    /// `pub_key_hash`, returning the txid
+    ///
+    /// The node's wallet needs mature coinbase outputs: mine at least 101
+    /// blocks first.
+    pub async fn fund_p2pkh(&self, pub_key_hash: &[u8], satoshis: u64) -> ServiceResult<String> {
+        let amount = satoshis as f64 / 100_000_000.0;
+        self.rpc("sendtoaddress", json!([regtest_address(pub_key_hash)?, amount]))
+            .await
+    }
+
+    /// Transaction `txid` in the mempool or chain
+    pub async fn get_raw_transaction(&self, txid: &str) -> ServiceResult<Vec<u8>> {
+        let raw: String = self
+            .rpc("getrawtransaction", json!([txid, 0]))
+            .await
+            .map_err(|e| rejected_as(e, ServiceError::TxNotFound(txid.to_string())))?;
+        hex::decode(raw).map_err(|e| ServiceError::InvalidResponse(e.to_string()))
+    }
+
+    /// Merkle path of `txid`, or None while it is unmined
+    pub async fn find_merkle_path(&self, txid: &str) -> ServiceResult<Option<MerklePath>> {
+        let tx: Value = self
+            .rpc("getrawtransaction", json!([txid, 1]))
+            .await
+            .map_err(|e| rejected_as(e, ServiceError::TxNotFound(txid.to_string())))?;
+        let Some(block_hash) = tx["blockhash"].as_str() else {
+            return Ok(None);
+        };
+        let block: Value = self.rpc("getblockheader", json!([block_hash, true])).await?;
+        let height = block["height"]
+            .as_u64()
+            .ok_or_else(|| ServiceError::InvalidResponse("block header without height".to_string()))?;
+        let proof: TscMerkleProof = self.rpc("getmerkleproof2", json!([block_hash, txid])).await?;
+        Ok(Some(tsc_proof_to_merkle_path(txid, &proof, height as u32)?))
+    }
+
+    fn service_error(&self, e: &ServiceError) -> crate::types::ServiceError {
+        crate::types::ServiceError {
+            service: NODE_NAME.to_string(),
+            message: e.to_string(),
+            status_code: None,
+        }
+    }
+}
+
+/// `not_found` in place of a call the node rejected; other failures as is
+fn rejected_as(e: ServiceError, not_found: ServiceError) -> ServiceError {
+    match e {
+        ServiceError::ServiceFailed { .. } => not_found,
+        e => e,
+    }
+}
+
+/// Regtest address paying to `pub_key_hash`
+pub fn regtest_address(pub_key_hash: &[u8]) -> ServiceResult<String> {
+    if pub_key_hash.len() != 20 {
+        return Err(ServiceError::InvalidParams("public key hash must be 20 bytes".to_string()));
+    }
+    let mut payload = vec![REGTEST_P2PKH_VERSION];
+    payload.extend_from_slice(pub_key_hash);
+    Ok(bs58::encode(payload).with_check().into_string())
+}
+
+/// txid (display hex) of a raw transaction
+fn txid_of(raw_tx: &[u8]) -> String {
+    let hash = Sha256::digest(Sha256::digest(raw_tx));
+    hex::encode(hash.iter().rev().copied().collect::<Vec<u8>>())
+}
+
+/// Status of a `sendrawtransaction` rejection, as reported by ARC
+fn rejection_status(message: &str) -> &'static str {
+    let message = message.to_lowercase();
+    if message.contains("already") {
+        "success"
+    } else if ["mempool-conflict", "double-spend", "missing inputs", "missing-inputs"]
+        .iter()
+        .any(|reason| message.contains(reason))
+    {
+        "doubleSpend"
+    } else {
+        "invalidTx"
+    }
+}
+
+/// Cursor over BEEF bytes
+struct BeefReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BeefReader<'a> {
+    fn bytes(&mut self, n: usize) -> ServiceResult<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| ServiceError::InvalidParams("truncated BEEF".to_string()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> ServiceResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> ServiceResult<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn varint(&mut self) -> ServiceResult<u64> {
+        let len = match self.u8()? {
+            0xfd => 2,
+            0xfe => 4,
+            0xff => 8,
+            n => return Ok(n as u64),
+        };
+        let mut value = [0u8; 8];
+        value[..len].copy_from_slice(self.bytes(len)?);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    /// Skip a varint-prefixed script
+    fn script(&mut self) -> ServiceResult<()> {
+        let len = self.varint()? as usize;
+        self.bytes(len)?;
+        Ok(())
+    }
+
+    /// Raw bytes of the transaction at the cursor
+    fn tx(&mut self) -> ServiceResult<Vec<u8>> {
+        let start = self.pos;
+        self.bytes(4)?;
+        for _ in 0..self.varint()? {
+            self.bytes(36)?;
+            self.script()?;
+            self.bytes(4)?;
+        }
+        for _ in 0..self.varint()? {
+            self.bytes(8)?;
+            self.script()?;
+        }
+        self.bytes(4)?;
+        Ok(self.data[start..self.pos].to_vec())
+    }
+}
+
+/// Transactions of a BEEF (V1, V2 or Atomic) in order, each with whether
+/// the BEEF proves it; txid-only entries are skipped
+fn beef_transactions(beef: &[u8]) -> ServiceResult<Vec<(Vec<u8>, bool)>> {
+    let mut reader = BeefReader { data: beef, pos: 0 };
+    let mut version = reader.u32()?;
+    if version == ATOMIC_BEEF {
+        reader.bytes(32)?;
+        version = reader.u32()?;
+    }
+    if version != BEEF_V1 && version != BEEF_V2 {
+        return Err(ServiceError::InvalidParams(format!("unknown BEEF version {:#x}", version)));
+    }
+
+    for _ in 0..reader.varint()? {
+        reader.varint()?;
+        for _ in 0..reader.u8()? {
+            for _ in 0..reader.varint()? {
+                reader.varint()?;
+                if reader.u8()? & 1 == 0 {
+                    reader.bytes(32)?;
+                }
+            }
+        }
+    }
+
+    let mut txs = Vec::new();
+    for _ in 0..reader.varint()? {
+        if version == BEEF_V1 {
+            let raw_tx = reader.tx()?;
+            let proven = reader.u8()? == 1;
+            if proven {
+                reader.varint()?;
+            }
+            txs.push((raw_tx, proven));
+        } else {
+            match reader.u8()? {
+                2 => {
+                    reader.bytes(32)?;
+                }
+                format => {
+                    let proven = format == 1;
+                    if proven {
+                        reader.varint()?;
+                    }
+                    txs.push((reader.tx()?, proven));
+                }
+            }
+        }
+    }
+    Ok(txs)
+}
+
+#[async_trait]
+impl ChainTracker for RegtestNode {
+    async fn is_valid_root_for_height(&self, root: &str, height: u32) -> ServiceResult<bool> {
+        let header = self.get_header_for_height(height).await?;
+        let merkle_root: Vec<u8> = header[36..68].iter().rev().copied().collect();
+        Ok(hex::encode(merkle_root) == root)
+    }
+
+    async fn get_header_for_height(&self, height: u32) -> ServiceResult<Vec<u8>> {
+        let hash = self.get_block_hash(height).await?;
+        let header: String = self.rpc("getblockheader", json!([hash, false])).await?;
+        let header = hex::decode(header).map_err(|e| ServiceError::InvalidResponse(e.to_string()))?;
+        if header.len() != 80 {
+            return Err(ServiceError::InvalidResponse(format!("block header is {} bytes", header.len())));
+        }
+        Ok(header)
+    }
+
+    async fn get_height(&self) -> ServiceResult<u32> {
+        self.get_block_count().await
+    }
+
+    async fn get_merkle_path(&self, txid: &str) -> ServiceResult<MerklePath> {
+        self.find_merkle_path(txid)
+            .await?
+            .ok_or_else(|| ServiceError::TxNotFound(format!("{} is not mined", txid)))
+    }
+}
+
+#[async_trait]
+impl RawTxProvider for RegtestNode {
+    async fn get_raw_tx(&self, txid: &str) -> ServiceResult<GetRawTxResult> {
+        let mut result = GetRawTxResult {
+            txid: txid.to_string(),
+            raw_tx: None,
+            name: Some(NODE_NAME.to_string()),
+            error: None,
+        };
+        match self.get_raw_transaction(txid).await {
+            Ok(raw_tx) => result.raw_tx = Some(raw_tx),
+            Err(ServiceError::TxNotFound(_)) => {}
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl MerklePathProvider for RegtestNode {
+    async fn get_merkle_path(&self, txid: &str) -> ServiceResult<GetMerklePathResult> {
+        let mut result = GetMerklePathResult {
+            txid: txid.to_string(),
+            proof: None,
+            name: Some(NODE_NAME.to_string()),
+            error: None,
+        };
+        match self.find_merkle_path(txid).await {
+            Ok(proof) => result.proof = proof,
+            Err(ServiceError::TxNotFound(_)) => {}
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Broadcaster for RegtestNode {
+    async fn post_raw_tx(&self, raw_tx: &[u8]) -> ServiceResult<PostRawTxResult> {
+        let txid = txid_of(raw_tx);
+        let mut result = PostRawTxResult {
+            txid: txid.clone(),
+            success: true,
+            name: Some(NODE_NAME.to_string()),
+            error: None,
+        };
+        if let Err(e) = self.rpc::<String>("sendrawtransaction", json!([hex::encode(raw_tx)])).await {
+            match &e {
+                ServiceError::ServiceFailed { message, .. } if rejection_status(message) == "success" => {}
+                ServiceError::ServiceFailed { .. } => {
+                    result.success = false;
+                    result.error = Some(self.service_error(&e));
+                }
+                _ => return Err(e),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Send the BEEF's unproven transactions in order
+    ///
+    /// The node takes raw transactions, so each is sent on its own; ones
+    /// the node already has count as accepted.
+    async fn post_beef(&self, beef: &[u8], txids: &[String]) -> ServiceResult<Vec<PostBeefResult>> {
+        let mut outcomes = std::collections::HashMap::new();
+        for (raw_tx, proven) in beef_transactions(beef)? {
+            if proven {
+                continue;
+            }
+            let posted = self.post_raw_tx(&raw_tx).await?;
+            let status = match &posted.error {
+                None => "success",
+                Some(e) => rejection_status(&e.message),
+            };
+            outcomes.insert(posted.txid, (status, posted.error));
+        }
+
+        Ok(txids
+            .iter()
+            .map(|txid| {
+                let (status, error) = outcomes.remove(txid).unwrap_or(("success", None));
+                PostBeefResult {
+                    txid: txid.clone(),
+                    status: status.to_string(),
+                    name: Some(NODE_NAME.to_string()),
+                    error,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_status_for_txids(&self, txids: &[String]) -> ServiceResult<GetStatusForTxidsResult> {
+        let mut statuses = Vec::with_capacity(txids.len());
+        for txid in txids {
+            let status = match self.rpc::<Value>("getrawtransaction", json!([txid, 1])).await {
+                Ok(tx) => match tx["confirmations"].as_u64() {
+                    Some(depth) if depth > 0 => TxStatus {
+                        txid: txid.clone(),
+                        status: TxStatusType::Mined,
+                        depth: Some(depth as u32),
+                    },
+                    _ => TxStatus {
+                        txid: txid.clone(),
+                        status: TxStatusType::Known,
+                        depth: None,
+                    },
+                },
+                Err(ServiceError::ServiceFailed { .. }) => TxStatus {
+                    txid: txid.clone(),
+                    status: TxStatusType::Unknown,
+                    depth: None,
+                },
+                Err(e) => return Err(e),
+            };
+            statuses.push(status);
+        }
+        Ok(GetStatusForTxidsResult {
+            statuses,
+            name: Some(NODE_NAME.to_string()),
+        })
+    }
+}
+
+#[async_trait]
+impl UtxoStatusChecker for RegtestNode {
+    /// Whether the output is unspent, counting mempool spends
+    async fn is_utxo(&self, output: &OutputRef) -> ServiceResult<bool> {
+        let out: Value = self.rpc("gettxout", json!([output.txid, output.vout, true])).await?;
+        Ok(!out.is_null())
+    }
+
+    /// Only outpoint queries can be answered: the node has no script index
+    async fn get_utxo_status(
+        &self,
+        _output: &str,
+        _output_format: Option<GetUtxoStatusOutputFormat>,
+        outpoint: Option<&str>,
+    ) -> ServiceResult<GetUtxoStatusResult> {
+        let mut result = GetUtxoStatusResult {
+            is_utxo: false,
+            name: Some(NODE_NAME.to_string()),
+            error: None,
+        };
+        let parsed = outpoint.and_then(|outpoint| {
+            let (txid, vout) = outpoint.split_once('.')?;
+            Some(OutputRef {
+                txid: txid.to_string(),
+                vout: vout.parse().ok()?,
+                script: None,
+            })
+        });
+        match parsed {
+            Some(output) => match self.is_utxo(&output).await {
+                Ok(is_utxo) => result.is_utxo = is_utxo,
+                Err(e) => result.error = Some(self.service_error(&e)),
+            },
+            None => {
+                result.error = Some(self.service_error(&ServiceError::InvalidParams(
+                    "the node looks up outputs by outpoint only".to_string(),
+                )))
+            }
+        }
+        Ok(result)
+    }
+
+    async fn get_script_hash_history(&self, _hash: &str) -> ServiceResult<GetScriptHashHistoryResult> {
+        Err(ServiceError::Unavailable("the node has no script history index".to_string()))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Minimal transaction: one input, one output
+    fn raw_tx(seed: u8) -> Vec<u8> {
+        let mut tx = vec![1, 0, 0, 0, 1];
+        tx.extend_from_slice(&[seed; 32]);
+        tx.extend_from_slice(&[0, 0, 0, 0]);
+        tx.extend_from_slice(&[1, 0x51]);
+        tx.extend_from_slice(&[0xff; 4]);
+        tx.push(1);
+        tx.extend_from_slice(&1000u64.to_le_bytes());
+        tx.extend_from_slice(&[1, 0x51]);
+        tx.extend_from_slice(&[0; 4]);
+        tx
+    }
+
+    #[test]
+    fn test_beef_transactions() {
+        // V2: one bump (height 100, one level, two leaves), a txid-only
+        // entry, a proven tx and an unproven tx
+        let mut beef = BEEF_V2.to_le_bytes().to_vec();
+        assert_eq!(beef, [0x02, 0x00, 0xbe, 0xef]);
+        beef.extend_from_slice(&[1, 100, 1, 2, 0, 2]);
+        beef.extend_from_slice(&[0xaa; 32]);
+        beef.extend_from_slice(&[1, 1]);
+        beef.push(3);
+        beef.push(2);
+        beef.extend_from_slice(&[0xbb; 32]);
+        beef.extend_from_slice(&[1, 0]);
+        beef.extend_from_slice(&raw_tx(1));
+        beef.push(0);
+        beef.extend_from_slice(&raw_tx(2));
+
+        let txs = beef_transactions(&beef).unwrap();
+        assert_eq!(txs, vec![(raw_tx(1), true), (raw_tx(2), false)]);
+
+        let mut atomic = ATOMIC_BEEF.to_le_bytes().to_vec();
+        atomic.extend_from_slice(&[0; 32]);
+        atomic.extend_from_slice(&beef);
+        assert_eq!(beef_transactions(&atomic).unwrap().len(), 2);
+
+        assert!(beef_transactions(&beef[..beef.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_regtest_address() {
+        let address = regtest_address(&[0u8; 20]).unwrap();
+        assert_eq!(address, "mfWxJ45yp2SFn7UciZyNpvDKrzbhyfKrY8");
+        assert!(regtest_address(&[0u8; 19]).is_err());
+    }
+
+    #[test]
+    fn test_rejection_status() {
+        assert_eq!(rejection_status("txn-already-known"), "success");
+        assert_eq!(rejection_status("txn-mempool-conflict"), "doubleSpend");
+        assert_eq!(rejection_status("Missing inputs"), "doubleSpend");
+        assert_eq!(rejection_status("mandatory-script-verify-flag-failed"), "invalidTx");
+    }
+
+    /// Serve one JSON-RPC request with `body`, returning the request text
+    fn serve_once(status: &'static str, body: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(head_end) = text.find("\r\n\r\n") {
+                    let length = text[..head_end]
+                        .lines()
+                        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if request.len() >= head_end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_rpc_call() {
+        let (url, handle) = serve_once("200 OK", r#"{"result":205,"error":null,"id":"wallet-services"}"#);
+        let node = RegtestNode::new(NodeRpcConfig {
+            url,
+            user: Some("user".to_string()),
+            password: Some("pass".to_string()),
+        });
+        assert_eq!(node.get_height().await.unwrap(), 205);
+
+        let request = handle.join().unwrap();
+        assert!(request.contains(r#""method":"getblockcount""#));
+        // "user:pass"
+        assert!(request.contains("Basic dXNlcjpwYXNz"));
+    }
+
+    #[tokio::test]
+    async fn test_post_raw_tx_rejected() {
+        let (url, handle) = serve_once(
+            "500 Internal Server Error",
+            r#"{"result":null,"error":{"code":-26,"message":"258: txn-mempool-conflict"},"id":"wallet-services"}"#,
+        );
+        let node = RegtestNode::new(NodeRpcConfig { url, ..Default::default() });
+        let tx = raw_tx(3);
+        let result = node.post_raw_tx(&tx).await.unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(result.txid, txid_of(&tx));
+        assert!(!result.success);
+        assert!(result.error.unwrap().message.contains("txn-mempool-conflict"));
+    }
+}
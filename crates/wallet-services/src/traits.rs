@@ -6,6 +6,7 @@
 
 use async_trait::async_trait;
 use crate::types::*;
+use crate::chaintracker::BlockHeader;
 use crate::error::ServiceResult;
 
 /// Main wallet services trait
@@ -125,6 +126,20 @@ pub trait ChainTracker: Send + Sync {
     async fn get_merkle_path(&self, txid: &str) -> ServiceResult<MerklePath>;
 }
 
+/// Block header lookup
+///
+/// Reference: TypeScript ChaintracksClientApi (findChainTipHeader, findHeaderForHeight)
+///
+/// Source of headers for `HeaderPoller` and other chain-following consumers.
+#[async_trait]
+pub trait ChainHeaderSource: Send + Sync {
+    /// Header of the active chain tip
+    async fn find_chain_tip_header(&self) -> ServiceResult<BlockHeader>;
+    
+    /// Header at `height` on the active chain, None if beyond the tip
+    async fn find_header_for_height(&self, height: u32) -> ServiceResult<Option<BlockHeader>>;
+}
+
 /// Broadcaster trait
 ///
 /// Handles transaction broadcasting to the network
@@ -147,6 +162,28 @@ pub trait Broadcaster: Send + Sync {
     ) -> ServiceResult<GetStatusForTxidsResult>;
 }
 
+/// Raw transaction provider trait
+///
+/// Reference: TS GetRawTxService
+#[async_trait]
+pub trait RawTxProvider: Send + Sync {
+    /// Get raw transaction bytes
+    ///
+    /// Lookup failures are reported in `GetRawTxResult::error`.
+    async fn get_raw_tx(&self, txid: &str) -> ServiceResult<GetRawTxResult>;
+}
+
+/// Merkle path provider trait
+///
+/// Reference: TS GetMerklePathService
+#[async_trait]
+pub trait MerklePathProvider: Send + Sync {
+    /// Get merkle proof for a mined transaction
+    ///
+    /// Lookup failures are reported in `GetMerklePathResult::error`.
+    async fn get_merkle_path(&self, txid: &str) -> ServiceResult<GetMerklePathResult>;
+}
+
 /// UTXO status checker trait
 ///
 /// Checks if outputs are currently spendable
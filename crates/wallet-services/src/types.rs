@@ -12,6 +12,8 @@ use serde::{Deserialize, Serialize};
 pub enum Chain {
     Main,
     Test,
+    /// Local regtest network, served by a node (see `regtest`)
+    Regtest,
 }
 
 impl Default for Chain {
@@ -20,6 +22,38 @@ impl Default for Chain {
     }
 }
 
+impl std::fmt::Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Chain::Main => write!(f, "main"),
+            Chain::Test => write!(f, "test"),
+            Chain::Regtest => write!(f, "regtest"),
+        }
+    }
+}
+
+impl Chain {
+    /// ARC endpoint transactions are broadcast to by default; none on
+    /// regtest, where the node takes them
+    pub fn default_arc_url(self) -> Option<&'static str> {
+        match self {
+            Chain::Main => Some("https://arc.taal.com"),
+            Chain::Test => Some("https://arc-test.taal.com"),
+            Chain::Regtest => None,
+        }
+    }
+
+    /// Chaintracks endpoint block headers are read from by default; none on
+    /// regtest, where the node serves them
+    pub fn default_chaintracks_url(self) -> Option<&'static str> {
+        match self {
+            Chain::Main => Some("https://mainnet-chaintracks.babbage.systems"),
+            Chain::Test => Some("https://testnet-chaintracks.babbage.systems"),
+            Chain::Regtest => None,
+        }
+    }
+}
+
 /// GetRawTx result
 /// Reference: TypeScript GetRawTxResult
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +117,10 @@ pub struct PathElement {
     
     /// Duplicate flag
     pub duplicate: Option<bool>,
+    
+    /// Offset within the tree level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
 }
 
 /// PostBeef result
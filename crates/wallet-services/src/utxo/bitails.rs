@@ -0,0 +1,307 @@
+//! Bitails Service
+//!
+//! **Reference**: TypeScript `src/services/providers/Bitails.ts`
+//!
+//! Bitails API client for raw transactions, merkle proofs and UTXO status.
+//! Used as a fallback to WhatsOnChain.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use crate::error::{ServiceError, ServiceResult};
+use crate::traits::{MerklePathProvider, OutputRef, RawTxProvider, UtxoStatusChecker};
+use crate::types::{
+    Chain, GetMerklePathResult, GetRawTxResult, GetScriptHashHistoryResult, GetUtxoStatusOutputFormat,
+    GetUtxoStatusResult, HistoryEntry, MerklePath,
+};
+use super::types::{BitailsBlock, BitailsHistory, BitailsUnspent};
+use super::tsc_proof::{tsc_proof_to_merkle_path, TscMerkleProof};
+use super::script_hash::validate_script_hash;
+
+/// Bitails client
+///
+/// Reference: TypeScript Bitails class
+pub struct BitailsClient {
+    /// Service name
+    name: String,
+
+    /// Chain (main or test)
+    chain: Chain,
+
+    /// Base URL
+    url: String,
+
+    /// HTTP client
+    client: Client,
+
+    /// API key (optional)
+    api_key: Option<String>,
+}
+
+impl BitailsClient {
+    /// Create new Bitails client
+    ///
+    /// Reference: TS Bitails.constructor
+    ///
+    /// # Arguments
+    /// * `chain` - Chain to query (main or test)
+    /// * `api_key` - Optional API key
+    pub fn new(chain: Chain, api_key: Option<String>) -> Self {
+        let url = match chain {
+            Chain::Main => "https://api.bitails.io",
+            // No public indexer serves regtest
+            Chain::Test | Chain::Regtest => "https://test-api.bitails.io",
+        };
+
+        Self {
+            name: "Bitails".to_string(),
+            chain,
+            url: url.to_string(),
+            client: Client::new(),
+            api_key,
+        }
+    }
+
+    /// Chain being queried
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    /// Get HTTP headers
+    ///
+    /// Reference: TS Bitails.getHttpHeaders
+    fn get_headers(&self) -> ServiceResult<reqwest::header::HeaderMap> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(ref api_key) = self.api_key {
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                reqwest::header::HeaderValue::from_str(api_key)
+                    .map_err(|_| ServiceError::InvalidParams("Invalid API key".to_string()))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    /// GET `path`, returning None on 404
+    async fn get(&self, path: &str) -> ServiceResult<Option<reqwest::Response>> {
+        let response = self.client
+            .get(format!("{}{}", self.url, path))
+            .headers(self.get_headers()?)
+            .send()
+            .await
+            .map_err(ServiceError::Http)?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(ServiceError::RateLimitExceeded(self.name.clone())),
+            status if !status.is_success() => Err(ServiceError::ServiceFailed {
+                service: self.name.clone(),
+                message: format!("HTTP {}", status),
+            }),
+            _ => Ok(Some(response)),
+        }
+    }
+
+    fn service_error(&self, e: &ServiceError) -> crate::types::ServiceError {
+        crate::types::ServiceError {
+            service: self.name.clone(),
+            message: e.to_string(),
+            status_code: None,
+        }
+    }
+
+    async fn try_get_raw_tx(&self, txid: &str) -> ServiceResult<Option<Vec<u8>>> {
+        let Some(response) = self.get(&format!("/download/tx/{}/hex", txid)).await? else {
+            return Ok(None);
+        };
+        let text = response.text().await.map_err(ServiceError::Http)?;
+        let raw_tx = hex::decode(text.trim())
+            .map_err(|_| ServiceError::InvalidResponse("Raw transaction is not hex".to_string()))?;
+
+        let computed = double_sha256_txid(&raw_tx);
+        if computed != txid {
+            return Err(ServiceError::InvalidResponse(format!(
+                "computed txid {} doesn't match requested value {}",
+                computed, txid
+            )));
+        }
+        Ok(Some(raw_tx))
+    }
+
+    async fn try_get_merkle_path(&self, txid: &str) -> ServiceResult<Option<MerklePath>> {
+        let Some(response) = self.get(&format!("/tx/{}/proof/tsc", txid)).await? else {
+            return Ok(None);
+        };
+        let proof: TscMerkleProof = response.json().await.map_err(ServiceError::Http)?;
+
+        let block: BitailsBlock = self.get(&format!("/block/{}", proof.target)).await?
+            .ok_or_else(|| ServiceError::InvalidResponse(format!("unknown block {}", proof.target)))?
+            .json()
+            .await
+            .map_err(ServiceError::Http)?;
+
+        tsc_proof_to_merkle_path(txid, &proof, block.height).map(Some)
+    }
+
+    async fn try_get_utxo_status(
+        &self,
+        output: &str,
+        output_format: Option<GetUtxoStatusOutputFormat>,
+        outpoint: Option<&str>,
+    ) -> ServiceResult<bool> {
+        let script_hash = validate_script_hash(output, output_format)?;
+
+        let Some(response) = self.get(&format!("/scripthash/{}/unspent", script_hash)).await? else {
+            return Ok(false);
+        };
+        let data: BitailsUnspent = response.json().await.map_err(ServiceError::Http)?;
+
+        match outpoint {
+            Some(outpoint) => {
+                let (txid, vout) = outpoint.split_once('.')
+                    .and_then(|(txid, vout)| Some((txid, vout.parse::<u32>().ok()?)))
+                    .ok_or_else(|| ServiceError::InvalidParams("Outpoint must be in format txid.vout".to_string()))?;
+                Ok(data.unspent.iter().any(|u| u.txid == txid && u.vout == vout))
+            }
+            None => Ok(!data.unspent.is_empty()),
+        }
+    }
+}
+
+/// Compute the txid (reversed double SHA-256, hex) of a raw transaction
+pub(crate) fn double_sha256_txid(raw_tx: &[u8]) -> String {
+    let hash = Sha256::digest(Sha256::digest(raw_tx));
+    hex::encode(hash.iter().rev().copied().collect::<Vec<u8>>())
+}
+
+#[async_trait]
+impl RawTxProvider for BitailsClient {
+    /// Get raw transaction
+    ///
+    /// Reference: TS Bitails.getRawTx
+    ///
+    /// The returned bytes are verified to hash to `txid`.
+    async fn get_raw_tx(&self, txid: &str) -> ServiceResult<GetRawTxResult> {
+        let mut result = GetRawTxResult {
+            txid: txid.to_string(),
+            raw_tx: None,
+            name: Some(self.name.clone()),
+            error: None,
+        };
+
+        match self.try_get_raw_tx(txid).await {
+            Ok(raw_tx) => result.raw_tx = raw_tx,
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl MerklePathProvider for BitailsClient {
+    /// Get merkle path for a mined transaction
+    ///
+    /// Reference: TS Bitails.getMerklePath
+    ///
+    /// Fetches the TSC proof and resolves the block height from its target hash.
+    async fn get_merkle_path(&self, txid: &str) -> ServiceResult<GetMerklePathResult> {
+        let mut result = GetMerklePathResult {
+            txid: txid.to_string(),
+            proof: None,
+            name: Some(self.name.clone()),
+            error: None,
+        };
+
+        match self.try_get_merkle_path(txid).await {
+            Ok(proof) => result.proof = proof,
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl UtxoStatusChecker for BitailsClient {
+    /// Check if output is unspent
+    async fn is_utxo(&self, output: &OutputRef) -> ServiceResult<bool> {
+        let script = output.script.as_ref()
+            .ok_or_else(|| ServiceError::InvalidParams("Script required".to_string()))?;
+
+        let outpoint = format!("{}.{}", output.txid, output.vout);
+        let result = self.get_utxo_status(script, Some(GetUtxoStatusOutputFormat::Script), Some(&outpoint)).await?;
+        Ok(result.is_utxo)
+    }
+
+    /// Get UTXO status
+    async fn get_utxo_status(
+        &self,
+        output: &str,
+        output_format: Option<GetUtxoStatusOutputFormat>,
+        outpoint: Option<&str>,
+    ) -> ServiceResult<GetUtxoStatusResult> {
+        let mut result = GetUtxoStatusResult {
+            is_utxo: false,
+            name: Some(self.name.clone()),
+            error: None,
+        };
+
+        match self.try_get_utxo_status(output, output_format, outpoint).await {
+            Ok(is_utxo) => result.is_utxo = is_utxo,
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+
+    /// Get script hash history
+    async fn get_script_hash_history(&self, hash: &str) -> ServiceResult<GetScriptHashHistoryResult> {
+        // Reverse hash from LE to BE, as for WoC
+        let hash_be = validate_script_hash(hash, Some(GetUtxoStatusOutputFormat::HashLE))?;
+
+        let history = match self.get(&format!("/scripthash/{}/history", hash_be)).await? {
+            Some(response) => {
+                let data: BitailsHistory = response.json().await.map_err(ServiceError::Http)?;
+                data.history.into_iter()
+                    .map(|h| HistoryEntry { txid: h.txid, height: h.height })
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        Ok(GetScriptHashHistoryResult {
+            script_hash: hash.to_string(),
+            history,
+            name: Some(self.name.clone()),
+        })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitails_client_creation() {
+        let client = BitailsClient::new(Chain::Main, None);
+        assert_eq!(client.chain(), Chain::Main);
+        assert_eq!(client.url, "https://api.bitails.io");
+        assert!(client.get_headers().unwrap().is_empty());
+
+        let client = BitailsClient::new(Chain::Test, Some("key".to_string()));
+        assert_eq!(client.url, "https://test-api.bitails.io");
+        assert_eq!(client.get_headers().unwrap()["authorization"], "key");
+    }
+
+    #[test]
+    fn test_double_sha256_txid() {
+        // Mainnet genesis coinbase
+        let raw_tx = hex::decode("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000").unwrap();
+        assert_eq!(
+            double_sha256_txid(&raw_tx),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+    }
+}
@@ -4,10 +4,13 @@
 //!
 //! Provides UTXO status checking and script hash history
 
+pub mod bitails;
 pub mod whatsonchain;
 pub mod types;
 pub mod script_hash;
+pub mod tsc_proof;
 
+pub use bitails::BitailsClient;
 pub use whatsonchain::WhatsOnChainClient;
 pub use types::*;
 pub use script_hash::validate_script_hash;
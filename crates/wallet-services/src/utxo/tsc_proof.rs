@@ -0,0 +1,119 @@
+//! TSC merkle proofs
+//!
+//! **Reference**: TypeScript `src/utility/tscProofToMerklePath.ts`
+//!
+//! Conversion of TSC-format merkle proofs, as served by Bitails and
+//! WhatsOnChain, to merkle paths.
+
+use serde::{Deserialize, Serialize};
+use crate::error::{ServiceError, ServiceResult};
+use crate::types::{MerklePath, PathElement};
+
+/// TSC merkle proof
+///
+/// Reference: TS TscMerkleProofApi (Bitails / WhatsOnChain `proof/tsc`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TscMerkleProof {
+    /// Index of the transaction in the block
+    pub index: u64,
+    
+    /// Transaction ID
+    #[serde(rename = "txOrId")]
+    pub tx_or_id: String,
+    
+    /// Block hash
+    pub target: String,
+    
+    /// Sibling hashes from leaf to root; "*" duplicates the computed hash
+    pub nodes: Vec<String>,
+}
+
+/// Convert a TSC proof to a merkle path
+///
+/// Reference: TS convertProofToMerklePath
+///
+/// Level `i` holds the sibling of the ancestor at `index >> i`; level 0 also
+/// holds the txid itself. A `"*"` node duplicates the computed hash.
+pub fn tsc_proof_to_merkle_path(txid: &str, proof: &TscMerkleProof, height: u32) -> ServiceResult<MerklePath> {
+    if proof.tx_or_id != txid {
+        return Err(ServiceError::InvalidResponse(format!(
+            "proof is for {} not {}",
+            proof.tx_or_id, txid
+        )));
+    }
+
+    let mut path = Vec::with_capacity(proof.nodes.len());
+    for (level, node) in proof.nodes.iter().enumerate() {
+        let offset = (proof.index >> level) ^ 1;
+        let sibling = if node == "*" {
+            PathElement { hash: None, txid: None, duplicate: Some(true), offset: Some(offset) }
+        } else {
+            PathElement { hash: Some(node.clone()), txid: None, duplicate: None, offset: Some(offset) }
+        };
+
+        let mut elements = vec![sibling];
+        if level == 0 {
+            elements.push(PathElement {
+                hash: Some(txid.to_string()),
+                txid: Some(true),
+                duplicate: None,
+                offset: Some(proof.index),
+            });
+            elements.sort_by_key(|e| e.offset);
+        }
+        path.push(elements);
+    }
+
+    Ok(MerklePath {
+        block_height: height,
+        path,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tsc_proof_to_merkle_path() {
+        // TS Reference: convertProofToMerklePath
+        let txid = "aa".repeat(32);
+        let proof = TscMerkleProof {
+            index: 2,
+            tx_or_id: txid.clone(),
+            target: "00".repeat(32),
+            nodes: vec!["bb".repeat(32), "*".to_string()],
+        };
+
+        let path = tsc_proof_to_merkle_path(&txid, &proof, 850_000).unwrap();
+        assert_eq!(path.block_height, 850_000);
+        assert_eq!(path.path.len(), 2);
+
+        // Level 0: txid at offset 2, sibling at offset 3
+        assert_eq!(path.path[0].len(), 2);
+        assert_eq!(path.path[0][0].offset, Some(2));
+        assert_eq!(path.path[0][0].txid, Some(true));
+        assert_eq!(path.path[0][1].offset, Some(3));
+        assert_eq!(path.path[0][1].hash.as_deref(), Some("bb".repeat(32).as_str()));
+
+        // Level 1: duplicate sibling of node 1 at offset 0
+        assert_eq!(path.path[1][0].offset, Some(0));
+        assert_eq!(path.path[1][0].duplicate, Some(true));
+        assert!(path.path[1][0].hash.is_none());
+    }
+
+    #[test]
+    fn test_tsc_proof_txid_mismatch() {
+        let proof = TscMerkleProof {
+            index: 0,
+            tx_or_id: "aa".repeat(32),
+            target: "00".repeat(32),
+            nodes: vec![],
+        };
+        assert!(tsc_proof_to_merkle_path(&"bb".repeat(32), &proof, 1).is_err());
+    }
+}
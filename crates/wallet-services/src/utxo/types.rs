@@ -106,6 +106,82 @@ pub struct UtxoDetail {
     pub height: u32,
 }
 
+/// WhatsOnChain block response (fields used)
+///
+/// Reference: WoC API `GET /block/hash/{hash}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhatsOnChainBlock {
+    /// Block hash
+    pub hash: String,
+    
+    /// Block height
+    pub height: u32,
+}
+
+/// Bitails unspent outputs response
+///
+/// Reference: Bitails API `GET /scripthash/{hash}/unspent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitailsUnspent {
+    /// Script hash
+    #[serde(default)]
+    pub scripthash: String,
+    
+    /// Unspent outputs
+    #[serde(default)]
+    pub unspent: Vec<BitailsUtxo>,
+}
+
+/// Bitails unspent output entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitailsUtxo {
+    /// Transaction ID
+    pub txid: String,
+    
+    /// Output index
+    pub vout: u32,
+    
+    /// Satoshi value
+    pub satoshis: u64,
+    
+    /// Block height (absent while unconfirmed)
+    #[serde(default)]
+    pub blockheight: Option<u32>,
+}
+
+/// Bitails script hash history response
+///
+/// Reference: Bitails API `GET /scripthash/{hash}/history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitailsHistory {
+    /// History entries
+    #[serde(default)]
+    pub history: Vec<BitailsHistoryEntry>,
+}
+
+/// Bitails history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitailsHistoryEntry {
+    /// Transaction ID
+    pub txid: String,
+    
+    /// Block height (absent while unconfirmed)
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+/// Bitails block header response
+///
+/// Reference: Bitails API `GET /block/{hash}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitailsBlock {
+    /// Block hash
+    pub hash: String,
+    
+    /// Block height
+    pub height: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,15 +7,17 @@
 use async_trait::async_trait;
 use reqwest::Client;
 use crate::error::{ServiceError, ServiceResult};
-use crate::traits::UtxoStatusChecker;
+use crate::traits::{MerklePathProvider, RawTxProvider, UtxoStatusChecker};
 use crate::types::{
-    Chain, GetUtxoStatusResult, GetUtxoStatusOutputFormat,
+    Chain, GetUtxoStatusResult, GetUtxoStatusOutputFormat, GetRawTxResult, GetMerklePathResult, MerklePath,
     GetScriptHashHistoryResult, HistoryEntry, GetStatusForTxidsResult,
     TxStatus, TxStatusType,
 };
 use crate::traits::OutputRef;
 use super::types::*;
 use super::script_hash::validate_script_hash;
+use super::bitails::double_sha256_txid;
+use super::tsc_proof::{tsc_proof_to_merkle_path, TscMerkleProof};
 
 /// WhatsOnChain client
 ///
@@ -53,7 +55,8 @@ impl WhatsOnChainClient {
     pub fn new(chain: Chain, api_key: Option<String>) -> Self {
         let url = match chain {
             Chain::Main => "https://api.whatsonchain.com/v1/bsv/main",
-            Chain::Test => "https://api.whatsonchain.com/v1/bsv/test",
+            // No public indexer serves regtest
+            Chain::Test | Chain::Regtest => "https://api.whatsonchain.com/v1/bsv/test",
         };
         
         Self {
@@ -386,6 +389,115 @@ impl WhatsOnChainClient {
     }
 }
 
+impl WhatsOnChainClient {
+    /// GET `path`, returning None on 404
+    async fn get_or_none(&self, path: &str) -> ServiceResult<Option<reqwest::Response>> {
+        let response = self.client
+            .get(format!("{}{}", self.url, path))
+            .headers(self.get_headers())
+            .send()
+            .await
+            .map_err(ServiceError::Http)?;
+        
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(ServiceError::RateLimitExceeded(self.name.clone())),
+            status if !status.is_success() => Err(ServiceError::ServiceFailed {
+                service: self.name.clone(),
+                message: format!("HTTP {}", status),
+            }),
+            _ => Ok(Some(response)),
+        }
+    }
+    
+    fn service_error(&self, e: &ServiceError) -> crate::types::ServiceError {
+        crate::types::ServiceError {
+            service: self.name.clone(),
+            message: e.to_string(),
+            status_code: None,
+        }
+    }
+    
+    async fn try_get_raw_tx(&self, txid: &str) -> ServiceResult<Option<Vec<u8>>> {
+        let Some(response) = self.get_or_none(&format!("/tx/{}/hex", txid)).await? else {
+            return Ok(None);
+        };
+        let text = response.text().await.map_err(ServiceError::Http)?;
+        let raw_tx = hex::decode(text.trim())
+            .map_err(|_| ServiceError::InvalidResponse("Raw transaction is not hex".to_string()))?;
+        
+        let computed = double_sha256_txid(&raw_tx);
+        if computed != txid {
+            return Err(ServiceError::InvalidResponse(format!(
+                "computed txid {} doesn't match requested value {}",
+                computed, txid
+            )));
+        }
+        Ok(Some(raw_tx))
+    }
+    
+    async fn try_get_merkle_path(&self, txid: &str) -> ServiceResult<Option<MerklePath>> {
+        let Some(response) = self.get_or_none(&format!("/tx/{}/proof/tsc", txid)).await? else {
+            return Ok(None);
+        };
+        // Unmined transactions return null or an empty array
+        let proofs: Option<Vec<TscMerkleProof>> = response.json().await.map_err(ServiceError::Http)?;
+        let Some(proof) = proofs.and_then(|p| p.into_iter().next()) else {
+            return Ok(None);
+        };
+        
+        let block: WhatsOnChainBlock = self.get_or_none(&format!("/block/hash/{}", proof.target)).await?
+            .ok_or_else(|| ServiceError::InvalidResponse(format!("unknown block {}", proof.target)))?
+            .json()
+            .await
+            .map_err(ServiceError::Http)?;
+        
+        tsc_proof_to_merkle_path(txid, &proof, block.height).map(Some)
+    }
+}
+
+#[async_trait]
+impl RawTxProvider for WhatsOnChainClient {
+    /// Get raw transaction
+    ///
+    /// Reference: TS WhatsOnChain.getRawTxResult
+    async fn get_raw_tx(&self, txid: &str) -> ServiceResult<GetRawTxResult> {
+        let mut result = GetRawTxResult {
+            txid: txid.to_string(),
+            raw_tx: None,
+            name: Some(self.name.clone()),
+            error: None,
+        };
+        
+        match self.try_get_raw_tx(txid).await {
+            Ok(raw_tx) => result.raw_tx = raw_tx,
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl MerklePathProvider for WhatsOnChainClient {
+    /// Get merkle path for a mined transaction
+    ///
+    /// Reference: TS WhatsOnChain.getMerklePath
+    async fn get_merkle_path(&self, txid: &str) -> ServiceResult<GetMerklePathResult> {
+        let mut result = GetMerklePathResult {
+            txid: txid.to_string(),
+            proof: None,
+            name: Some(self.name.clone()),
+            error: None,
+        };
+        
+        match self.try_get_merkle_path(txid).await {
+            Ok(proof) => result.proof = proof,
+            Err(e) => result.error = Some(self.service_error(&e)),
+        }
+        Ok(result)
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -0,0 +1,93 @@
+//! HTTP wallet client
+//!
+//! A `WalletInterface` calling a remote wallet over the HTTPWalletJSON wire
+//! format served by `wallet_core::wallet_server`: each method is
+//! `POST <endpoint>/<methodName>` with JSON args and result, and the calling
+//! app is named by the `Originator` header.
+//!
+//! Reference: @bsv/sdk `HTTPWalletJSON`
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use wallet_core::managers::simple_wallet_manager::WalletInterface;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+
+/// Remote wallet reached over HTTP
+pub struct HttpWalletJson {
+    endpoint_url: String,
+    http: reqwest::Client,
+}
+
+impl HttpWalletJson {
+    /// Client for the wallet served at `endpoint_url`, e.g. "http://localhost:3321"
+    pub fn new(endpoint_url: impl Into<String>) -> Self {
+        Self {
+            endpoint_url: endpoint_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// URL the wallet is served at
+    pub fn endpoint_url(&self) -> &str {
+        &self.endpoint_url
+    }
+
+    /// Call `method` with `args`
+    ///
+    /// Failed calls return the remote's error when it sent one.
+    pub async fn call(&self, method: &str, args: Value, originator: Option<&str>) -> WalletResult<Value> {
+        let url = format!("{}/{}", self.endpoint_url, method);
+        let mut request = self.http.post(&url).json(&args);
+        if let Some(originator) = originator {
+            request = request.header("Originator", originator);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| WalletError::internal(format!("{} failed: {}", url, e)))?;
+
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| WalletError::internal(format!("{} failed: {}", url, e)))?;
+        if status.is_success() {
+            return serde_json::from_slice(&body)
+                .map_err(|e| WalletError::internal(format!("{} returned invalid JSON: {}", url, e)));
+        }
+        Err(serde_json::from_slice::<WalletError>(&body)
+            .unwrap_or_else(|_| WalletError::internal(format!("{} returned HTTP {}", url, status))))
+    }
+}
+
+#[async_trait]
+impl WalletInterface for HttpWalletJson {
+    async fn create_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("createAction", args, originator).await }
+    async fn sign_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("signAction", args, originator).await }
+    async fn abort_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("abortAction", args, originator).await }
+    async fn list_actions(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("listActions", args, originator).await }
+    async fn internalize_action(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("internalizeAction", args, originator).await }
+    async fn list_outputs(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("listOutputs", args, originator).await }
+    async fn relinquish_output(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("relinquishOutput", args, originator).await }
+    async fn get_public_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("getPublicKey", args, originator).await }
+    async fn reveal_counterparty_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("revealCounterpartyKeyLinkage", args, originator).await }
+    async fn reveal_specific_key_linkage(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("revealSpecificKeyLinkage", args, originator).await }
+    async fn encrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("encrypt", args, originator).await }
+    async fn decrypt(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("decrypt", args, originator).await }
+    async fn create_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("createHmac", args, originator).await }
+    async fn verify_hmac(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("verifyHmac", args, originator).await }
+    async fn create_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("createSignature", args, originator).await }
+    async fn verify_signature(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("verifySignature", args, originator).await }
+    async fn acquire_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("acquireCertificate", args, originator).await }
+    async fn list_certificates(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("listCertificates", args, originator).await }
+    async fn prove_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("proveCertificate", args, originator).await }
+    async fn relinquish_certificate(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("relinquishCertificate", args, originator).await }
+    async fn discover_by_identity_key(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("discoverByIdentityKey", args, originator).await }
+    async fn discover_by_attributes(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("discoverByAttributes", args, originator).await }
+    async fn is_authenticated(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("isAuthenticated", args, originator).await }
+    async fn wait_for_authentication(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("waitForAuthentication", args, originator).await }
+    async fn get_height(&self, originator: Option<&str>) -> WalletResult<Value> { self.call("getHeight", json!({}), originator).await }
+    async fn get_header_for_height(&self, args: Value, originator: Option<&str>) -> WalletResult<Value> { self.call("getHeaderForHeight", args, originator).await }
+    async fn get_network(&self, originator: Option<&str>) -> WalletResult<Value> { self.call("getNetwork", json!({}), originator).await }
+    async fn get_version(&self, originator: Option<&str>) -> WalletResult<Value> { self.call("getVersion", json!({}), originator).await }
+}
@@ -0,0 +1,398 @@
+//! Setup environment
+//!
+//! Deployment settings for setting up a wallet, read from environment
+//! variables or a TOML file so a deployment is configured without code
+//! changes. Both sources are validated the same way.
+//!
+//! Environment variables, where `<CHAIN>` is `MAIN`, `TEST` or `REGTEST`:
+//!
+//! | Variable                  | Setting           |
+//! |---------------------------|-------------------|
+//! | `MY_<CHAIN>_IDENTITY`     | `identity_key`    |
+//! | `MY_<CHAIN>_IDENTITY2`    | `identity_key2`   |
+//! | `MY_<CHAIN>_FILEPATH`     | `file_path`       |
+//! | `<CHAIN>_STORAGE_URL`     | `storage_url`     |
+//! | `<CHAIN>_TAAL_API_KEY`    | `arc_api_key`     |
+//! | `<CHAIN>_ARC_URL`         | `arc_url`         |
+//! | `<CHAIN>_CHAINTRACKS_URL` | `chaintracks_url` |
+//! | `<CHAIN>_WOC_API_KEY`     | `woc_api_key`     |
+//! | `<CHAIN>_NODE_URL`        | `node_url`        |
+//! | `<CHAIN>_NODE_USER`       | `node_user`       |
+//! | `<CHAIN>_NODE_PASSWORD`   | `node_password`   |
+//! | `DEV_KEYS`                | `dev_keys` (JSON) |
+//!
+//! The ARC API key is required except on regtest, where the node settings
+//! name the local node's RPC endpoint instead.
+//!
+//! A TOML file uses the setting names as keys, with `chain` and a
+//! `[dev_keys]` table:
+//!
+//! ```toml
+//! chain = "test"
+//! identity_key = "02..."
+//! arc_api_key = "mainnet_..."
+//!
+//! [dev_keys]
+//! "02..." = "<root private key hex>"
+//! ```
+//!
+//! Reference: wallet-toolbox/src/Setup.ts (SetupEnv, Setup.getEnv)
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use wallet_core::keys::RootKeyDeriver;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_services::{Chain, NodeRpcConfig, ServiceCollection, ServiceConfig};
+
+use crate::services::{parse_chain, SetupServices};
+
+/// Environment variable naming a TOML file `SetupEnv::load` reads instead
+/// of the environment
+pub const SETUP_CONFIG_VAR: &str = "WALLET_SETUP_CONFIG";
+
+/// Settings for setting up a wallet
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetupEnv {
+    /// "main", "test" or "regtest"
+    pub chain: String,
+
+    /// Identity key of the wallet's user (hex)
+    pub identity_key: String,
+
+    /// Identity key of a second user, e.g. the counterparty in tests
+    pub identity_key2: Option<String>,
+
+    /// SQLite file the wallet is kept in
+    pub file_path: Option<String>,
+
+    /// Wallet served over HTTP, for `create_wallet_client`
+    pub storage_url: Option<String>,
+
+    /// API key sent to ARC (TAAL); may be empty on regtest
+    #[serde(default)]
+    pub arc_api_key: String,
+
+    /// ARC endpoint, when not the chain's default
+    pub arc_url: Option<String>,
+
+    /// Chaintracks endpoint, when not the chain's default
+    pub chaintracks_url: Option<String>,
+
+    /// WhatsOnChain API key
+    pub woc_api_key: Option<String>,
+
+    /// Regtest node RPC endpoint, when not the default local one
+    pub node_url: Option<String>,
+
+    /// Regtest node `rpcuser`
+    pub node_user: Option<String>,
+
+    /// Regtest node `rpcpassword`
+    pub node_password: Option<String>,
+
+    /// Root private keys (hex) by identity key, for development
+    #[serde(default)]
+    pub dev_keys: HashMap<String, String>,
+}
+
+impl SetupEnv {
+    /// Settings for `chain` from the file named by `WALLET_SETUP_CONFIG`
+    /// when set, otherwise from the environment
+    pub fn load(chain: &str) -> WalletResult<Self> {
+        match std::env::var(SETUP_CONFIG_VAR) {
+            Ok(path) if !path.is_empty() => {
+                let env = Self::from_toml_file(&path)?;
+                if parse_chain(&env.chain)? != parse_chain(chain)? {
+                    return Err(WalletError::invalid_operation(format!(
+                        "{} is for chain '{}', not '{}'",
+                        path, env.chain, chain
+                    )));
+                }
+                Ok(env)
+            }
+            _ => Self::from_env(chain),
+        }
+    }
+
+    /// Settings for `chain` from environment variables
+    ///
+    /// Reference: TS Setup.getEnv
+    pub fn from_env(chain: &str) -> WalletResult<Self> {
+        Self::from_vars(chain, |name| std::env::var(name).ok())
+    }
+
+    /// Settings for `chain` from the variables `var` looks up
+    ///
+    /// Empty variables count as unset.
+    pub fn from_vars(chain: &str, var: impl Fn(&str) -> Option<String>) -> WalletResult<Self> {
+        let parsed = parse_chain(chain)?;
+        let prefix = match parsed {
+            Chain::Main => "MAIN",
+            Chain::Test => "TEST",
+            Chain::Regtest => "REGTEST",
+        };
+        let get = |name: String| var(&name).filter(|value| !value.trim().is_empty());
+        let require = |name: String| get(name.clone()).ok_or_else(|| WalletError::missing_parameter(name));
+
+        let dev_keys = match get("DEV_KEYS".to_string()) {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|_| WalletError::invalid_parameter("DEV_KEYS", "a JSON object of identity key to root key hex"))?,
+            None => HashMap::new(),
+        };
+
+        let env = Self {
+            chain: chain.to_string(),
+            identity_key: require(format!("MY_{}_IDENTITY", prefix))?,
+            identity_key2: get(format!("MY_{}_IDENTITY2", prefix)),
+            file_path: get(format!("MY_{}_FILEPATH", prefix)),
+            storage_url: get(format!("{}_STORAGE_URL", prefix)),
+            arc_api_key: match parsed {
+                Chain::Regtest => get(format!("{}_TAAL_API_KEY", prefix)).unwrap_or_default(),
+                _ => require(format!("{}_TAAL_API_KEY", prefix))?,
+            },
+            arc_url: get(format!("{}_ARC_URL", prefix)),
+            chaintracks_url: get(format!("{}_CHAINTRACKS_URL", prefix)),
+            woc_api_key: get(format!("{}_WOC_API_KEY", prefix)),
+            node_url: get(format!("{}_NODE_URL", prefix)),
+            node_user: get(format!("{}_NODE_USER", prefix)),
+            node_password: get(format!("{}_NODE_PASSWORD", prefix)),
+            dev_keys,
+        };
+        env.validate()?;
+        Ok(env)
+    }
+
+    /// Settings from the TOML file at `path`
+    pub fn from_toml_file(path: impl AsRef<Path>) -> WalletResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| WalletError::invalid_operation(format!("cannot read {}: {}", path.display(), e)))?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Settings from TOML text
+    pub fn from_toml_str(text: &str) -> WalletResult<Self> {
+        let env: Self =
+            toml::from_str(text).map_err(|e| WalletError::invalid_operation(format!("invalid setup config: {}", e)))?;
+        env.validate()?;
+        Ok(env)
+    }
+
+    /// Check the chain, keys and URLs are well formed, and that each dev
+    /// key is the root key of the identity key it is listed under
+    pub fn validate(&self) -> WalletResult<()> {
+        let chain = parse_chain(&self.chain)?;
+        check_identity_key("identity_key", &self.identity_key)?;
+        if let Some(key) = &self.identity_key2 {
+            check_identity_key("identity_key2", key)?;
+        }
+        for (name, url) in [
+            ("storage_url", &self.storage_url),
+            ("arc_url", &self.arc_url),
+            ("chaintracks_url", &self.chaintracks_url),
+            ("node_url", &self.node_url),
+        ] {
+            if let Some(url) = url {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(WalletError::invalid_parameter(name, "an http or https URL"));
+                }
+            }
+        }
+        if chain != Chain::Regtest && self.arc_api_key.trim().is_empty() {
+            return Err(WalletError::missing_parameter("arc_api_key"));
+        }
+        for (identity_key, root_key) in &self.dev_keys {
+            let derived = hex::decode(root_key)
+                .ok()
+                .filter(|key| key.len() == 32)
+                .and_then(|key| RootKeyDeriver::new(&key).ok())
+                .map(|deriver| deriver.identity_key_hex());
+            if derived.as_deref() != Some(identity_key.as_str()) {
+                return Err(WalletError::invalid_parameter(
+                    format!("dev_keys[{}]", identity_key),
+                    "the 32-byte root key (hex) of that identity key",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Root key of `identity_key`, from `dev_keys`
+    pub fn root_key_for(&self, identity_key: &str) -> WalletResult<Vec<u8>> {
+        let root_key = self
+            .dev_keys
+            .get(identity_key)
+            .ok_or_else(|| WalletError::invalid_operation(format!("no dev key for identity key {}", identity_key)))?;
+        hex::decode(root_key).map_err(|_| WalletError::invalid_parameter("dev_keys", "hex root keys"))
+    }
+
+    /// Service endpoints and keys, with the chain's defaults where unset
+    ///
+    /// On regtest an `arc_url` names a mock ARC that takes broadcasts in
+    /// place of the node.
+    pub fn service_config(&self) -> WalletResult<ServiceConfig> {
+        let chain = parse_chain(&self.chain)?;
+        let node_rpc = (chain == Chain::Regtest).then(|| {
+            let default = NodeRpcConfig::default();
+            NodeRpcConfig {
+                url: self.node_url.clone().unwrap_or(default.url),
+                user: self.node_user.clone(),
+                password: self.node_password.clone(),
+            }
+        });
+        let defaults = ServiceConfig::for_chain(chain);
+        Ok(ServiceConfig {
+            arc_url: self.arc_url.clone().or(defaults.arc_url.clone()),
+            arc_api_key: Some(self.arc_api_key.clone()).filter(|key| !key.is_empty()),
+            chaintracks_url: self.chaintracks_url.clone().or(defaults.chaintracks_url.clone()),
+            whatsonchain_api_key: self.woc_api_key.clone(),
+            node_rpc,
+            ..defaults
+        })
+    }
+
+    /// Services over `service_config`
+    pub fn services(&self) -> WalletResult<SetupServices> {
+        Ok(SetupServices::with_collection(ServiceCollection::new(self.service_config()?)))
+    }
+}
+
+fn check_identity_key(name: &str, key: &str) -> WalletResult<()> {
+    match hex::decode(key) {
+        Ok(bytes) if bytes.len() == 33 && (bytes[0] == 2 || bytes[0] == 3) => Ok(()),
+        _ => Err(WalletError::invalid_parameter(name, "a compressed public key (hex)")),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dev_identity() -> (String, String) {
+        let root_key = hex::encode([7u8; 32]);
+        let identity_key = RootKeyDeriver::new(&[7u8; 32]).unwrap().identity_key_hex();
+        (identity_key, root_key)
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let (identity_key, root_key) = dev_identity();
+        let vars: HashMap<String, String> = [
+            ("MY_TEST_IDENTITY", identity_key.clone()),
+            ("MY_TEST_FILEPATH", "/tmp/wallet.sqlite".to_string()),
+            ("TEST_TAAL_API_KEY", "testnet_key".to_string()),
+            ("TEST_WOC_API_KEY", "".to_string()),
+            ("MAIN_TAAL_API_KEY", "mainnet_key".to_string()),
+            ("DEV_KEYS", format!(r#"{{"{}":"{}"}}"#, identity_key, root_key)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        let env = SetupEnv::from_vars("test", |name| vars.get(name).cloned()).unwrap();
+
+        assert_eq!(env.identity_key, identity_key);
+        assert_eq!(env.file_path.as_deref(), Some("/tmp/wallet.sqlite"));
+        assert_eq!(env.arc_api_key, "testnet_key");
+        assert_eq!(env.woc_api_key, None);
+        assert_eq!(env.root_key_for(&identity_key).unwrap(), vec![7u8; 32]);
+
+        let config = env.service_config().unwrap();
+        assert_eq!(config.arc_url.as_deref(), Some("https://arc-test.taal.com"));
+        assert_eq!(config.arc_api_key.as_deref(), Some("testnet_key"));
+
+        // Regtest names the node instead of an ARC key
+        let regtest = [
+            ("MY_REGTEST_IDENTITY", identity_key.clone()),
+            ("REGTEST_NODE_URL", "http://127.0.0.1:18332".to_string()),
+            ("REGTEST_NODE_USER", "bitcoin".to_string()),
+        ];
+        let env = SetupEnv::from_vars("regtest", |name| {
+            regtest.iter().find(|(var, _)| *var == name).map(|(_, value)| value.clone())
+        })
+        .unwrap();
+        let config = env.service_config().unwrap();
+        assert_eq!(config.arc_url, None);
+        assert_eq!(config.arc_api_key, None);
+        let node_rpc = config.node_rpc.unwrap();
+        assert_eq!(node_rpc.user.as_deref(), Some("bitcoin"));
+
+        // The main chain reads its own variables
+        let err = SetupEnv::from_vars("main", |name| vars.get(name).cloned()).unwrap_err();
+        assert!(err.description.contains("MY_MAIN_IDENTITY"));
+    }
+
+    #[test]
+    fn test_from_toml_str() {
+        let (identity_key, root_key) = dev_identity();
+        let text = format!(
+            r#"
+chain = "main"
+identity_key = "{identity_key}"
+storage_url = "https://wallet.example.com"
+arc_api_key = "mainnet_key"
+arc_url = "https://arc.example.com"
+
+[dev_keys]
+"{identity_key}" = "{root_key}"
+"#
+        );
+        let env = SetupEnv::from_toml_str(&text).unwrap();
+        assert_eq!(env.storage_url.as_deref(), Some("https://wallet.example.com"));
+        let config = env.service_config().unwrap();
+        assert_eq!(config.arc_url.as_deref(), Some("https://arc.example.com"));
+        assert_eq!(config.chaintracks_url.as_deref(), Some("https://mainnet-chaintracks.babbage.systems"));
+
+        assert!(SetupEnv::from_toml_str(&text.replace("storage_url", "storage_uri")).is_err());
+        assert!(SetupEnv::from_toml_str(&text.replace("https://wallet", "ftp://wallet")).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let (identity_key, root_key) = dev_identity();
+        let valid = SetupEnv {
+            chain: "test".to_string(),
+            identity_key: identity_key.clone(),
+            identity_key2: None,
+            file_path: None,
+            storage_url: None,
+            arc_api_key: "key".to_string(),
+            arc_url: None,
+            chaintracks_url: None,
+            woc_api_key: None,
+            node_url: None,
+            node_user: None,
+            node_password: None,
+            dev_keys: HashMap::from([(identity_key.clone(), root_key)]),
+        };
+        assert!(valid.validate().is_ok());
+
+        let mut env = valid.clone();
+        env.chain = "stn".to_string();
+        assert!(env.validate().is_err());
+
+        // Only regtest goes without an ARC API key
+        let mut env = valid.clone();
+        env.arc_api_key = String::new();
+        assert!(env.validate().is_err());
+        env.chain = "regtest".to_string();
+        assert!(env.validate().is_ok());
+
+        let mut env = valid.clone();
+        env.identity_key2 = Some("04".repeat(33));
+        assert!(env.validate().is_err());
+
+        // A dev key must belong to the identity key it is listed under
+        let mut env = valid.clone();
+        env.dev_keys = HashMap::from([(identity_key, hex::encode([8u8; 32]))]);
+        assert!(env.validate().is_err());
+
+        assert!(valid.root_key_for(&"02".repeat(33)).is_err());
+    }
+}
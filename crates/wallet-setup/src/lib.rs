@@ -0,0 +1,419 @@
+//! Setup
+//!
+//! One-call construction of a working wallet, for integrators who want the
+//! defaults rather than wiring storage, services, monitor and wallet by hand:
+//!
+//! - `create_wallet_sqlite` opens (or creates) a SQLite wallet file and
+//!   returns a `Wallet` over it with network services and a `Monitor` whose
+//!   tasks broadcast delayed actions and collect proofs, all reporting on
+//!   one event bus.
+//! - `open_wallet_sqlite` reopens such a file on the chain it was created
+//!   for, with that chain's default services.
+//! - `create_wallet_client` connects to a wallet served elsewhere over
+//!   HTTP, which owns the storage and keys.
+//!
+//! Storage, services and remote wallets on another chain than the one asked
+//! for are refused with `WERR_NETWORK_CHAIN`.
+//!
+//! `SetupEnv` reads the chain, keys and service endpoints from environment
+//! variables or a TOML file, for `create_wallet_sqlite_from_env`.
+//!
+//! On "regtest" the wallet is served by a local node, and
+//! `regtest::fund_wallet` pays it coins from the node for end-to-end tests.
+//!
+//! Reference: wallet-toolbox/src/Setup.ts, SetupClient.ts
+
+pub mod client;
+pub mod env;
+pub mod regtest;
+pub mod services;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use wallet_core::events::WalletEventBus;
+use wallet_core::keys::RootKeyDeriver;
+use wallet_core::managers::simple_wallet_manager::WalletInterface;
+use wallet_core::sdk::errors::{WErrNetworkChain, WalletError, WalletResult};
+use wallet_core::wallet::{Wallet, WalletConfig};
+use wallet_monitor::tasks::task_check_for_proofs::TaskCheckForProofs;
+use wallet_monitor::tasks::task_fail_abandoned::DEFAULT_ABANDONED_MSECS;
+use wallet_monitor::tasks::task_review_status::DEFAULT_REVIEW_MSECS;
+use wallet_monitor::tasks::task_send_waiting::DEFAULT_SEND_WAITING_MSECS;
+use wallet_monitor::{Monitor, TaskFailAbandoned, TaskReviewStatus, TaskSendWaiting};
+use wallet_services::WalletServices;
+use wallet_storage::{WalletStorageProvider, WalletStorageWriter};
+use wallet_storage_sqlite::StorageSqlite;
+
+pub use client::HttpWalletJson;
+pub use env::SetupEnv;
+pub use services::SetupServices;
+
+/// Milliseconds between periodic proof checks (2 hours)
+///
+/// Reference: TS Monitor.addDefaultTasks (TaskCheckForProofs)
+const CHECK_FOR_PROOFS_MSECS: i64 = 1000 * 60 * 60 * 2;
+
+/// A wallet over SQLite storage, with the parts it was built from
+///
+/// Reference: TS SetupWalletKnex
+pub struct SetupWallet {
+    /// "main", "test" or "regtest"
+    pub chain: String,
+
+    /// Identity key of the root key (hex)
+    pub identity_key: String,
+
+    /// Key deriver over the root key
+    pub key_deriver: Arc<RootKeyDeriver>,
+
+    /// The wallet's storage
+    pub storage: Arc<Mutex<dyn WalletStorageProvider>>,
+
+    /// The user's id in `storage`
+    pub user_id: i64,
+
+    /// Broadcasting, proofs and headers
+    pub services: Arc<SetupServices>,
+
+    /// Monitor with the default tasks, over its own connection to the file;
+    /// not started (see `wallet_monitor::MonitorDaemon`)
+    pub monitor: Monitor,
+
+    /// Bus shared by the wallet and monitor tasks
+    pub event_bus: WalletEventBus,
+
+    pub wallet: Arc<Wallet>,
+}
+
+/// Create a wallet for the 32-byte `root_key` over the SQLite file at `file_path`
+///
+/// The file is created and migrated if needed. `chain` is "main", "test" or
+/// "regtest"; a regtest wallet is a test wallet served by a local node.
+/// The monitor opens the file separately, so `file_path` must name a file
+/// rather than an in-memory database.
+///
+/// Reference: TS Setup.createWalletSQLite
+pub async fn create_wallet_sqlite(chain: &str, file_path: &str, root_key: &[u8]) -> WalletResult<SetupWallet> {
+    create_wallet_sqlite_with_services(chain, file_path, root_key, Arc::new(SetupServices::new(chain)?)).await
+}
+
+/// Open the wallet for `root_key` in the existing SQLite file at `file_path`
+///
+/// The chain is the one the file was created for, with its default services.
+pub async fn open_wallet_sqlite(file_path: &str, root_key: &[u8]) -> WalletResult<SetupWallet> {
+    if !std::path::Path::new(file_path).is_file() {
+        return Err(WalletError::invalid_parameter("file_path", "an existing wallet file"));
+    }
+    let chain = StorageSqlite::new(file_path)?.make_available().await?.chain.to_string();
+    create_wallet_sqlite(&chain, file_path, root_key).await
+}
+
+/// Create the wallet of `env.identity_key` over the SQLite file `env.file_path`
+///
+/// The root key is the identity's entry in `env.dev_keys`, and services use
+/// the endpoints and API keys in `env`.
+///
+/// Reference: TS Setup.createWalletSQLite with Setup.getEnv
+pub async fn create_wallet_sqlite_from_env(env: &SetupEnv) -> WalletResult<SetupWallet> {
+    let file_path = env
+        .file_path
+        .as_deref()
+        .ok_or_else(|| WalletError::missing_parameter("file_path"))?;
+    let root_key = env.root_key_for(&env.identity_key)?;
+    create_wallet_sqlite_with_services(&env.chain, file_path, &root_key, Arc::new(env.services()?)).await
+}
+
+/// `create_wallet_sqlite` with the given network services
+pub async fn create_wallet_sqlite_with_services(
+    chain: &str,
+    file_path: &str,
+    root_key: &[u8],
+    services: Arc<SetupServices>,
+) -> WalletResult<SetupWallet> {
+    let parsed = services::parse_chain(chain)?;
+    if services.collection().chain() != parsed {
+        return Err(WErrNetworkChain::new(Some(format!(
+            "services are on '{}', not '{}'",
+            services.collection().chain(),
+            chain
+        ))));
+    }
+    let wallet_chain = services::wallet_chain(parsed);
+    let key_deriver = Arc::new(RootKeyDeriver::new(root_key)?);
+    let identity_key = key_deriver.identity_key_hex();
+
+    let mut sqlite = open_sqlite(wallet_chain, file_path)?;
+    sqlite.make_available().await?;
+    let user_id = sqlite.find_or_insert_user(&identity_key).await?.user.user_id;
+    let storage: Arc<Mutex<dyn WalletStorageProvider>> = Arc::new(Mutex::new(sqlite));
+
+    let event_bus = WalletEventBus::default();
+    let wallet = Wallet::new(WalletConfig {
+        chain: wallet_chain.to_string(),
+        root_key: root_key.to_vec(),
+        storage: Arc::new(NetworkLookups { services: services.clone() }),
+        storage_provider: Some(storage.clone()),
+        certifier_client: None,
+        broadcaster: Some(services.clone()),
+        utxo_status: None,
+        fiat_rates: None,
+        identity_resolver: None,
+        trust_settings: None,
+        settings: None,
+        admin_originator: None,
+        event_bus: Some(event_bus.clone()),
+    })?;
+
+    let monitor_storage: Box<dyn WalletStorageProvider> = Box::new(open_sqlite(wallet_chain, file_path)?);
+    let mut monitor = Monitor::new(Arc::new(Mutex::new(monitor_storage))).with_event_bus(event_bus.clone());
+    monitor.add_task(
+        Box::new(TaskSendWaiting::new(services.clone(), DEFAULT_SEND_WAITING_MSECS).with_event_bus(event_bus.clone())),
+        DEFAULT_SEND_WAITING_MSECS,
+    );
+    monitor.add_task(
+        Box::new(
+            TaskCheckForProofs::new(services.clone(), services.clone(), CHECK_FOR_PROOFS_MSECS)
+                .with_event_bus(event_bus.clone()),
+        ),
+        CHECK_FOR_PROOFS_MSECS,
+    );
+    monitor.add_task(
+        Box::new(TaskFailAbandoned::default().with_event_bus(event_bus.clone())),
+        DEFAULT_ABANDONED_MSECS,
+    );
+    monitor.add_task(Box::new(TaskReviewStatus::default()), DEFAULT_REVIEW_MSECS);
+
+    Ok(SetupWallet {
+        chain: chain.to_string(),
+        identity_key,
+        key_deriver,
+        storage,
+        user_id,
+        services,
+        monitor,
+        event_bus,
+        wallet: Arc::new(wallet),
+    })
+}
+
+fn open_sqlite(chain: &str, file_path: &str) -> WalletResult<StorageSqlite> {
+    Ok(StorageSqlite::open_or_create(file_path, "setup", chain)?)
+}
+
+/// A remote wallet, with the chain it was checked to be on
+///
+/// Reference: TS SetupWalletClient
+pub struct SetupWalletClient {
+    /// "main", "test" or "regtest"
+    pub chain: String,
+
+    pub wallet: Arc<HttpWalletJson>,
+}
+
+/// Connect to the wallet served at `endpoint_url`, checking it is on `chain`
+///
+/// The remote wallet holds the keys and storage; calls name their app with
+/// the originator passed to each method.
+///
+/// Reference: TS SetupClient.createWalletClient
+pub async fn create_wallet_client(chain: &str, endpoint_url: &str) -> WalletResult<SetupWalletClient> {
+    let expected = services::wallet_chain(services::parse_chain(chain)?);
+    let wallet = HttpWalletJson::new(endpoint_url);
+
+    let network = wallet.get_network(None).await?;
+    let network = network["network"].as_str().unwrap_or_default();
+    if services::parse_chain(network).map(services::wallet_chain).ok() != Some(expected) {
+        return Err(WErrNetworkChain::new(Some(format!(
+            "wallet at {} is on '{}', not '{}'",
+            endpoint_url, network, chain
+        ))));
+    }
+
+    Ok(SetupWalletClient {
+        chain: chain.to_string(),
+        wallet: Arc::new(wallet),
+    })
+}
+
+/// Inner wallet for the methods `Wallet` delegates even with storage:
+/// chain lookups are answered by the services, the rest are unavailable
+struct NetworkLookups {
+    services: Arc<SetupServices>,
+}
+
+fn unavailable(method: &str) -> WalletResult<Value> {
+    Err(WalletError::not_implemented(format!("{} is not available in a set-up wallet", method)))
+}
+
+fn lookup_error(e: wallet_services::ServiceError) -> WalletError {
+    WalletError::internal(e.to_string())
+}
+
+#[async_trait]
+impl WalletInterface for NetworkLookups {
+    async fn create_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createAction") }
+    async fn sign_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("signAction") }
+    async fn abort_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("abortAction") }
+    async fn list_actions(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listActions") }
+    async fn internalize_action(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("internalizeAction") }
+    async fn list_outputs(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listOutputs") }
+    async fn relinquish_output(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishOutput") }
+    async fn get_public_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("getPublicKey") }
+    async fn reveal_counterparty_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealCounterpartyKeyLinkage") }
+    async fn reveal_specific_key_linkage(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("revealSpecificKeyLinkage") }
+    async fn encrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("encrypt") }
+    async fn decrypt(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("decrypt") }
+    async fn create_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createHmac") }
+    async fn verify_hmac(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifyHmac") }
+    async fn create_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("createSignature") }
+    async fn verify_signature(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("verifySignature") }
+    async fn acquire_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("acquireCertificate") }
+    async fn list_certificates(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("listCertificates") }
+    async fn prove_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("proveCertificate") }
+    async fn relinquish_certificate(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("relinquishCertificate") }
+    async fn discover_by_identity_key(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByIdentityKey") }
+    async fn discover_by_attributes(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("discoverByAttributes") }
+    async fn is_authenticated(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("isAuthenticated") }
+    async fn wait_for_authentication(&self, _: Value, _: Option<&str>) -> WalletResult<Value> { unavailable("waitForAuthentication") }
+    async fn get_network(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getNetwork") }
+    async fn get_version(&self, _: Option<&str>) -> WalletResult<Value> { unavailable("getVersion") }
+
+    async fn get_height(&self, _: Option<&str>) -> WalletResult<Value> {
+        let height = self.services.collection().get_height().await.map_err(lookup_error)?;
+        Ok(json!({ "height": height }))
+    }
+
+    async fn get_header_for_height(&self, args: Value, _: Option<&str>) -> WalletResult<Value> {
+        let height = args["height"]
+            .as_u64()
+            .and_then(|height| u32::try_from(height).ok())
+            .ok_or_else(|| WalletError::invalid_parameter("height", "a block height"))?;
+        let header = self.services.collection().get_header_for_height(height).await.map_err(lookup_error)?;
+        Ok(json!({ "header": hex::encode(header) }))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{SocketAddr, TcpListener};
+    use wallet_core::sdk::errors::WalletErrorCode;
+    use wallet_core::wallet_server::{WalletServer, WalletServerConfig};
+
+    fn temp_db(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("wallet-setup-{}-{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_create_wallet_sqlite() {
+        let path = temp_db("create");
+        let setup = create_wallet_sqlite("test", &path, &[7u8; 32]).await.unwrap();
+        assert_eq!(setup.identity_key, RootKeyDeriver::new(&[7u8; 32]).unwrap().identity_key_hex());
+        assert_eq!(setup.wallet.identity_key(), Some(setup.identity_key.clone()));
+        assert_eq!(setup.monitor.task_status().len(), 4);
+
+        let outputs = setup.wallet.list_outputs(json!({ "basket": "default" }), Some("app.example.com")).await.unwrap();
+        assert_eq!(outputs["totalOutputs"], 0);
+
+        // Monitor tasks report on the wallet's bus
+        let _events = setup.wallet.subscribe();
+        assert_eq!(setup.monitor.event_bus().subscriber_count(), 1);
+
+        // Reopening finds the same user
+        let user_id = setup.user_id;
+        drop(setup);
+        let reopened = create_wallet_sqlite("test", &path, &[7u8; 32]).await.unwrap();
+        assert_eq!(reopened.user_id, user_id);
+
+        assert!(create_wallet_sqlite("stn", &path, &[7u8; 32]).await.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_chain_switch() {
+        let path = temp_db("chain");
+        assert!(open_wallet_sqlite(&path, &[7u8; 32]).await.is_err());
+        let test = create_wallet_sqlite("test", &path, &[7u8; 32]).await.unwrap();
+        assert_eq!(test.wallet.chain(), "test");
+        drop(test);
+
+        // Key derivation does not depend on the chain
+        let main_path = temp_db("chain-main");
+        let main = create_wallet_sqlite("main", &main_path, &[7u8; 32]).await.unwrap();
+        assert_eq!(main.wallet.chain(), "main");
+        assert_eq!(main.identity_key, RootKeyDeriver::new(&[7u8; 32]).unwrap().identity_key_hex());
+        drop(main);
+
+        let err = create_wallet_sqlite("main", &path, &[7u8; 32]).await.err().unwrap();
+        assert_eq!(err.code, WalletErrorCode::NetworkChain);
+        let services = Arc::new(SetupServices::new("main").unwrap());
+        let err = create_wallet_sqlite_with_services("test", &path, &[7u8; 32], services).await.err().unwrap();
+        assert_eq!(err.code, WalletErrorCode::NetworkChain);
+
+        // The file's chain picks the services
+        let reopened = open_wallet_sqlite(&path, &[7u8; 32]).await.unwrap();
+        assert_eq!(reopened.chain, "test");
+        assert_eq!(reopened.services.collection().chain(), wallet_services::Chain::Test);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&main_path);
+    }
+
+    #[tokio::test]
+    async fn test_create_wallet_sqlite_from_env() {
+        let path = temp_db("env");
+        let identity_key = RootKeyDeriver::new(&[7u8; 32]).unwrap().identity_key_hex();
+        let mut env = SetupEnv::from_toml_str(&format!(
+            "chain = \"test\"\nidentity_key = \"{0}\"\narc_api_key = \"key\"\n[dev_keys]\n\"{0}\" = \"{1}\"\n",
+            identity_key,
+            hex::encode([7u8; 32])
+        ))
+        .unwrap();
+        assert!(create_wallet_sqlite_from_env(&env).await.is_err());
+
+        env.file_path = Some(path.clone());
+        let setup = create_wallet_sqlite_from_env(&env).await.unwrap();
+        assert_eq!(setup.identity_key, identity_key);
+        assert_eq!(setup.chain, "test");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_create_wallet_client() {
+        let path = temp_db("client");
+        let setup = create_wallet_sqlite("test", &path, &[7u8; 32]).await.unwrap();
+
+        let addr: SocketAddr = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let server = WalletServer::new(WalletServerConfig { addr, ..Default::default() }, setup.wallet.clone(), None)
+            .unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let serving = tokio::spawn(server.serve(async {
+            let _ = stopped.await;
+        }));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let url = format!("http://{}", addr);
+        let client = create_wallet_client("test", &url).await.unwrap();
+        let key = client.wallet.get_public_key(json!({ "identityKey": true }), Some("app.example.com")).await.unwrap();
+        assert_eq!(key["publicKey"], setup.identity_key);
+
+        let err = client.wallet.call("noSuchMethod", json!({}), None).await.unwrap_err();
+        assert!(err.description.contains("noSuchMethod"));
+        assert!(create_wallet_client("main", &url).await.is_err());
+
+        let _ = stop.send(());
+        serving.await.unwrap().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,144 @@
+//! Regtest funding
+//!
+//! Puts coins from a local regtest node into a set-up wallet, for end-to-end
+//! tests on a disposable network: fund, then create and broadcast actions
+//! and let the monitor collect their proofs after `RegtestNode::mine_blocks`.
+//!
+//! Reference: wallet_services::regtest
+
+use base64::Engine;
+use serde_json::json;
+use wallet_core::beef::Beef;
+use wallet_core::keys::RootKeyDeriver;
+use wallet_core::managers::simple_wallet_manager::WalletInterface;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::services::MerklePathProvider;
+use wallet_core::transaction::Transaction;
+use wallet_core::utility::ScriptTemplateSABPPP;
+use wallet_services::RegtestNode;
+
+use crate::SetupWallet;
+
+/// Originator funding payments are internalized as
+const FUNDING_ORIGINATOR: &str = "localhost";
+
+/// Sender of funding payments: the "anyone" key, private key 1
+fn anyone_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key[31] = 1;
+    key
+}
+
+fn node_error(e: wallet_services::ServiceError) -> WalletError {
+    WalletError::internal(format!("regtest node: {}", e))
+}
+
+/// BRC-29 P2PKH script paying `identity_key` from the anyone key
+fn funding_script(identity_key: &str, template: &ScriptTemplateSABPPP) -> WalletResult<Vec<u8>> {
+    let recipient = hex::decode(identity_key).map_err(|_| WalletError::invalid_parameter("identity_key", "hex"))?;
+    template.lock(&anyone_key(), &recipient)
+}
+
+/// Pay `satoshis` from the node into `setup`'s wallet, returning the txid
+///
+/// The payment is mined in a new block and internalized as a wallet
+/// payment with its proof, so it can be spent at once. The node's own
+/// wallet pays: on a fresh network mine 101 blocks first so it has mature
+/// coinbase outputs.
+pub async fn fund_wallet(setup: &SetupWallet, node: &RegtestNode, satoshis: u64) -> WalletResult<String> {
+    let mut prefix = [0u8; 8];
+    let mut suffix = [0u8; 8];
+    rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut prefix);
+    rand::RngCore::fill_bytes(&mut rand::rngs::OsRng, &mut suffix);
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let template = ScriptTemplateSABPPP::new(b64.encode(prefix), b64.encode(suffix));
+
+    // P2PKH: OP_DUP OP_HASH160 <20-byte hash> OP_EQUALVERIFY OP_CHECKSIG
+    let locking_script = funding_script(&setup.identity_key, &template)?;
+    let txid = node.fund_p2pkh(&locking_script[3..23], satoshis).await.map_err(node_error)?;
+    node.mine_blocks(1).await.map_err(node_error)?;
+
+    let raw_tx = node.get_raw_transaction(&txid).await.map_err(node_error)?;
+    let tx = Transaction::from_bytes(&raw_tx).map_err(|e| WalletError::internal(e.to_string()))?;
+    let output_index = tx
+        .outputs
+        .iter()
+        .position(|output| output.script_pubkey == locking_script)
+        .ok_or_else(|| WalletError::internal(format!("{} does not pay the wallet", txid)))?;
+
+    // Read through the services so the wallet's chain tracker knows the block
+    let merkle_path = setup
+        .services
+        .get_merkle_path(&txid)
+        .await?
+        .merkle_path
+        .ok_or_else(|| WalletError::internal(format!("no merkle path for {}", txid)))?;
+    let mut beef = Beef::new_v2();
+    beef.merge_proven_tx(&raw_tx, merkle_path)
+        .map_err(|e| WalletError::internal(e.to_string()))?;
+    let atomic_beef = beef
+        .to_binary_atomic(&txid)
+        .map_err(|e| WalletError::internal(e.to_string()))?;
+
+    setup
+        .wallet
+        .internalize_action(
+            json!({
+                "tx": atomic_beef,
+                "outputs": [{
+                    "outputIndex": output_index,
+                    "protocol": "wallet payment",
+                    "paymentRemittance": {
+                        "derivationPrefix": template.derivation_prefix,
+                        "derivationSuffix": template.derivation_suffix,
+                        "senderIdentityKey": RootKeyDeriver::new(&anyone_key())?.identity_key_hex(),
+                    },
+                }],
+                "description": "regtest funding",
+            }),
+            Some(FUNDING_ORIGINATOR),
+        )
+        .await?;
+    Ok(txid)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::crypto::hash160;
+
+    #[tokio::test]
+    async fn test_funding_script_pays_wallet_key() {
+        let path = std::env::temp_dir().join(format!("wallet-setup-regtest-{}.sqlite", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = std::fs::remove_file(&path);
+        let setup = crate::create_wallet_sqlite("regtest", &path, &[7u8; 32]).await.unwrap();
+
+        let template = ScriptTemplateSABPPP::new("cHJlZml4".to_string(), "c3VmZml4".to_string());
+        let script = funding_script(&setup.identity_key, &template).unwrap();
+
+        // The wallet derives the same key as the recipient of the payment
+        let anyone = RootKeyDeriver::new(&anyone_key()).unwrap().identity_key_hex();
+        let key = setup
+            .wallet
+            .get_public_key(
+                json!({
+                    "protocolID": [2, "3241645161d8"],
+                    "keyID": template.key_id(),
+                    "counterparty": anyone,
+                    "forSelf": true,
+                }),
+                Some(FUNDING_ORIGINATOR),
+            )
+            .await
+            .unwrap();
+        let key = hex::decode(key["publicKey"].as_str().unwrap()).unwrap();
+        assert_eq!(&script[3..23], hash160(&key).as_slice());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
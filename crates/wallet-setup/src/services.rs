@@ -0,0 +1,206 @@
+//! Network services for set-up wallets
+//!
+//! Adapts a wallet-services `ServiceCollection` to the service traits the
+//! wallet and monitor are built on.
+//!
+//! Reference: wallet-toolbox/src/services/Services.ts
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use wallet_core::beef::{BeefResult, ChainTracker, MerklePath, MerklePathNode};
+use wallet_core::crypto::double_sha256;
+use wallet_core::sdk::action_process::{ReviewActionResult, ReviewActionResultStatus};
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::services::{BlockHeader, Broadcaster, GetMerklePathResult, MerklePathProvider};
+use wallet_services::{Chain, ServiceCollection, ServiceConfig, WalletServices};
+use wallet_storage::{StorageError, StorageResult};
+
+/// Chain named `chain`: "main", "test" or "regtest"
+pub fn parse_chain(chain: &str) -> WalletResult<Chain> {
+    match chain {
+        "main" | "mainnet" => Ok(Chain::Main),
+        "test" | "testnet" => Ok(Chain::Test),
+        "regtest" => Ok(Chain::Regtest),
+        _ => Err(WalletError::invalid_parameter("chain", "'main', 'test' or 'regtest'")),
+    }
+}
+
+/// Chain the wallet and its storage are on: regtest wallets are test wallets
+pub fn wallet_chain(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Main => "main",
+        Chain::Test | Chain::Regtest => "test",
+    }
+}
+
+/// Broadcasting, merkle proofs and block headers over a `ServiceCollection`
+///
+/// Merkle roots of the block headers read for proofs are remembered, and
+/// are the roots the `ChainTracker` implementation accepts: a proof is only
+/// valid for a header the header service returned.
+pub struct SetupServices {
+    services: ServiceCollection,
+    roots: Mutex<HashMap<u32, String>>,
+}
+
+impl SetupServices {
+    /// Services for `chain` with the default ARC and Chaintracks endpoints,
+    /// or the default local node on regtest
+    pub fn new(chain: &str) -> WalletResult<Self> {
+        Ok(Self::with_collection(ServiceCollection::new(ServiceConfig::for_chain(parse_chain(chain)?))))
+    }
+
+    /// Services over an already configured collection
+    pub fn with_collection(services: ServiceCollection) -> Self {
+        Self {
+            services,
+            roots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The underlying service collection
+    pub fn collection(&self) -> &ServiceCollection {
+        &self.services
+    }
+
+    /// Block header at `height`, remembering its merkle root
+    pub async fn header_for_height(&self, height: u32) -> StorageResult<BlockHeader> {
+        let raw = self.services.get_header_for_height(height).await.map_err(service_error)?;
+        let header = parse_header(height, &raw)?;
+        self.roots.lock().unwrap().insert(height, header.merkle_root.clone());
+        Ok(header)
+    }
+}
+
+fn service_error(e: wallet_services::ServiceError) -> StorageError {
+    StorageError::Io(e.to_string())
+}
+
+/// Hash and merkle root (display-order hex) of an 80-byte block header
+fn parse_header(height: u32, raw: &[u8]) -> StorageResult<BlockHeader> {
+    if raw.len() != 80 {
+        return Err(StorageError::InvalidArg(format!(
+            "block header at height {} is {} bytes, expected 80",
+            height,
+            raw.len()
+        )));
+    }
+    let reversed = |bytes: &[u8]| hex::encode(bytes.iter().rev().copied().collect::<Vec<u8>>());
+    Ok(BlockHeader {
+        height,
+        hash: reversed(&double_sha256(raw)),
+        merkle_root: reversed(&raw[36..68]),
+    })
+}
+
+#[async_trait]
+impl Broadcaster for SetupServices {
+    async fn post_beef(&self, beef: &[u8], txids: &[String]) -> StorageResult<Vec<ReviewActionResult>> {
+        let posted = self.services.post_beef(beef, txids).await.map_err(service_error)?;
+        Ok(posted
+            .into_iter()
+            .map(|r| ReviewActionResult {
+                txid: r.txid,
+                status: serde_json::from_value(serde_json::Value::String(r.status))
+                    .unwrap_or(ReviewActionResultStatus::ServiceError),
+                message: r.error.map(|e| e.message),
+                competing_txs: None,
+                competing_beef: None,
+                double_spend: None,
+                service: r.name,
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl MerklePathProvider for SetupServices {
+    async fn get_merkle_path(&self, txid: &str) -> StorageResult<GetMerklePathResult> {
+        let r = self.services.get_merkle_path(txid, false).await.map_err(service_error)?;
+        let mut result = GetMerklePathResult {
+            name: r.name,
+            merkle_path: None,
+            header: None,
+            error: r.error.map(|e| e.message),
+        };
+        if let Some(proof) = r.proof {
+            let path = MerklePath {
+                block_height: proof.block_height,
+                path: proof
+                    .path
+                    .into_iter()
+                    .map(|level| {
+                        level
+                            .into_iter()
+                            .map(|node| MerklePathNode {
+                                hash: node.hash.unwrap_or_default(),
+                                offset: node.offset.map(|offset| offset as u32),
+                                duplicate: node.duplicate.unwrap_or(false),
+                                txid: node.txid.unwrap_or(false),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            };
+            result.header = Some(self.header_for_height(path.block_height).await?);
+            result.merkle_path = Some(path);
+        }
+        Ok(result)
+    }
+}
+
+impl ChainTracker for SetupServices {
+    fn verify_merkle_path(&self, path: &MerklePath) -> BeefResult<bool> {
+        let root = path.compute_root(None)?;
+        self.is_valid_root_for_height(&root, path.block_height)
+    }
+
+    fn is_valid_root_for_height(&self, merkle_root: &str, height: u32) -> BeefResult<bool> {
+        Ok(self.roots.lock().unwrap().get(&height).is_some_and(|root| root == merkle_root))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        // Genesis block header
+        let raw = hex::decode(concat!(
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa",
+            "4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        ))
+        .unwrap();
+        let header = parse_header(0, &raw).unwrap();
+        assert_eq!(header.hash, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert_eq!(header.merkle_root, "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b");
+
+        assert!(parse_header(0, &raw[..79]).is_err());
+    }
+
+    #[test]
+    fn test_chain_tracker_accepts_only_fetched_roots() {
+        let services = SetupServices::new("test").unwrap();
+        services.roots.lock().unwrap().insert(100, "aa".repeat(32));
+        assert!(services.is_valid_root_for_height(&"aa".repeat(32), 100).unwrap());
+        assert!(!services.is_valid_root_for_height(&"bb".repeat(32), 100).unwrap());
+        assert!(!services.is_valid_root_for_height(&"aa".repeat(32), 101).unwrap());
+    }
+
+    #[test]
+    fn test_parse_chain() {
+        assert_eq!(parse_chain("main").unwrap(), Chain::Main);
+        assert_eq!(parse_chain("testnet").unwrap(), Chain::Test);
+        assert_eq!(parse_chain("regtest").unwrap(), Chain::Regtest);
+        assert!(parse_chain("stn").is_err());
+        assert_eq!(wallet_chain(Chain::Regtest), "test");
+    }
+}
@@ -0,0 +1,33 @@
+//! Tables shared by the output query benchmarks
+
+use rusqlite::{params, Connection};
+
+/// Fill a migrated database with `count` change outputs in basket 1 of user
+/// 1, all from the completed transaction 1
+pub fn populate(conn: &mut Connection, count: i64) {
+    conn.execute_batch(
+        "INSERT INTO users (identityKey, activeStorage) VALUES ('bench_user', 'bench_key');
+         INSERT INTO output_baskets (userId, name) VALUES (1, 'default');
+         INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+            VALUES (1, 'completed', 'ref_funding', 0, 0, 'Funding');",
+    )
+    .unwrap();
+
+    let tx = conn.transaction().unwrap();
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO outputs (userId, transactionId, basketId, spendable, `change`, vout, satoshis,
+                    providedBy, purpose, type, outputDescription, txid, lockingScript)
+                 VALUES (1, 1, 1, 1, 1, ?1, ?2, 'storage', 'change', 'P2PKH', '', ?3, ?4)",
+            )
+            .unwrap();
+        for vout in 0..count {
+            // Spread satoshis so allocation has to find a value, not take the first row
+            let satoshis = 1000 + (vout * 7919) % 1_000_000;
+            let txid = format!("{:064x}", vout / 16);
+            stmt.execute(params![vout, satoshis, txid, vec![0x76u8; 25]]).unwrap();
+        }
+    }
+    tx.commit().unwrap();
+}
@@ -0,0 +1,104 @@
+//! Output query benchmark
+//!
+//! Times change allocation, a listOutputs basket page and an outpoint lookup
+//! against growing outputs tables, with and without the output query indexes.
+//!
+//! Run with `cargo bench -p wallet-storage-sqlite --bench find_outputs`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rusqlite::{params, Connection};
+use std::sync::{Arc, Mutex};
+use wallet_storage_sqlite::migrations::{apply_initial_migration, apply_pending_migrations};
+use wallet_storage_sqlite::output_ops;
+use wallet_storage_sqlite::*;
+
+const SIZES: &[i64] = &[1_000, 10_000, 100_000];
+
+/// Storage holding `count` change outputs and an unsigned transaction 2 to
+/// allocate them to
+fn populate(count: i64, indexed: bool) -> Arc<Mutex<Connection>> {
+    let mut conn = Connection::open_in_memory().unwrap();
+    apply_initial_migration(&conn, "bench_key", "Bench", "main", 100000).unwrap();
+    apply_pending_migrations(&conn).unwrap();
+    if !indexed {
+        conn.execute_batch(
+            "DROP INDEX idx_outputs_userId_basketId_spendable;
+             DROP INDEX idx_outputs_userId_basketId_spendable_satoshis;
+             DROP INDEX idx_outputs_userId_txid_vout;
+             DROP INDEX idx_outputs_spentBy;",
+        )
+        .unwrap();
+    }
+    common::populate(&mut conn, count);
+    conn.execute(
+        "INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+            VALUES (1, 'unsigned', 'ref_spend', 1, 0, 'Spend')",
+        [],
+    )
+    .unwrap();
+
+    Arc::new(Mutex::new(conn))
+}
+
+fn find_outputs(c: &mut Criterion) {
+    let args = FindOutputsArgs {
+        user_id: 1,
+        since: None,
+        paged: Some(Paged { limit: 10, offset: None }),
+        order_descending: None,
+        partial: Some(PartialOutput {
+            basket_id: Some(1),
+            spendable: Some(true),
+            change: None,
+            transaction_id: None,
+            txid: None,
+        }),
+        no_script: Some(true),
+        tx_status: Some(vec![TransactionStatus::Completed, TransactionStatus::Unproven]),
+    };
+
+    let mut group = c.benchmark_group("find_outputs");
+    for &count in SIZES {
+        for indexed in [false, true] {
+            let conn = populate(count, indexed);
+            let parameter = format!("{}/{}", count, if indexed { "indexed" } else { "unindexed" });
+
+            let mut i = 0;
+            group.bench_function(BenchmarkId::new("allocate", &parameter), |b| {
+                b.iter(|| {
+                    i = (i + 1) % 200;
+                    let output = output_ops::allocate_change_input(&conn, 1, 1, 500_000 + i, None, false, 2)
+                        .unwrap()
+                        .unwrap();
+                    // Put it back so every iteration sees the same table
+                    conn.lock()
+                        .unwrap()
+                        .execute(
+                            "UPDATE outputs SET spendable = 1, spentBy = NULL WHERE outputId = ?1",
+                            params![output.output_id],
+                        )
+                        .unwrap();
+                })
+            });
+            group.bench_function(BenchmarkId::new("list_page", &parameter), |b| {
+                b.iter(|| assert_eq!(output_ops::find_outputs(&conn, &args).unwrap().len(), 10))
+            });
+            group.bench_function(BenchmarkId::new("outpoint", &parameter), |b| {
+                b.iter(|| {
+                    i += 1;
+                    let vout = (i * 7) % count;
+                    let txid = format!("{:064x}", vout / 16);
+                    assert!(output_ops::find_output_by_outpoint(&conn, 1, &txid, vout as u32, true)
+                        .unwrap()
+                        .is_some());
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, find_outputs);
+criterion_main!(benches);
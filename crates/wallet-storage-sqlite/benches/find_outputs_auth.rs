@@ -0,0 +1,76 @@
+//! findOutputsAuth benchmark
+//!
+//! Times `find_outputs_auth` through the storage API, authentication and
+//! ownership checks included, against databases of up to 100k outputs: a
+//! listOutputs basket page, a deep page, and the outputs of one txid.
+//!
+//! Run with `cargo bench -p wallet-storage-sqlite --bench find_outputs_auth`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rusqlite::Connection;
+use tempfile::TempDir;
+use wallet_storage_sqlite::migrations::apply_initial_migration;
+use wallet_storage_sqlite::*;
+
+const SIZES: &[i64] = &[1_000, 10_000, 100_000];
+
+/// Storage holding `count` change outputs in basket 1 of user 1
+fn populate(dir: &TempDir, count: i64) -> StorageSqlite {
+    let path = dir.path().join(format!("outputs-{}.sqlite", count));
+    let mut conn = Connection::open(&path).unwrap();
+    apply_initial_migration(&conn, "bench_key", "Bench", "main", 100000).unwrap();
+    common::populate(&mut conn, count);
+    drop(conn);
+
+    StorageSqlite::new(&path).unwrap()
+}
+
+fn args(paged: Option<Paged>, txid: Option<String>) -> FindOutputsArgs {
+    FindOutputsArgs {
+        user_id: 1,
+        since: None,
+        paged,
+        order_descending: None,
+        partial: Some(PartialOutput {
+            basket_id: Some(1),
+            spendable: Some(true),
+            change: None,
+            transaction_id: None,
+            txid,
+        }),
+        no_script: Some(true),
+        tx_status: Some(vec![TransactionStatus::Completed, TransactionStatus::Unproven]),
+    }
+}
+
+fn find_outputs_auth(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let dir = TempDir::new().unwrap();
+    let auth = AuthId::new("bench_user").with_user_id(1);
+
+    let mut group = c.benchmark_group("find_outputs_auth");
+    for &count in SIZES {
+        let mut storage = populate(&dir, count);
+        runtime.block_on(storage.make_available()).unwrap();
+
+        let first_page = args(Some(Paged { limit: 10, offset: None }), None);
+        let deep_page = args(Some(Paged { limit: 10, offset: Some(count as u32 / 2) }), None);
+        let by_txid = args(None, Some(format!("{:064x}", count / 32)));
+
+        group.bench_with_input(BenchmarkId::new("first_page", count), &first_page, |b, args| {
+            b.iter(|| assert_eq!(runtime.block_on(storage.find_outputs_auth(&auth, args)).unwrap().len(), 10))
+        });
+        group.bench_with_input(BenchmarkId::new("deep_page", count), &deep_page, |b, args| {
+            b.iter(|| assert_eq!(runtime.block_on(storage.find_outputs_auth(&auth, args)).unwrap().len(), 10))
+        });
+        group.bench_with_input(BenchmarkId::new("by_txid", count), &by_txid, |b, args| {
+            b.iter(|| assert_eq!(runtime.block_on(storage.find_outputs_auth(&auth, args)).unwrap().len(), 16))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, find_outputs_auth);
+criterion_main!(benches);
@@ -69,7 +69,7 @@ pub fn update_output_basket(
 
     let rows = conn.execute(
         "UPDATE output_baskets
-         SET updated_at = datetime('now'),
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
              numberOfDesiredUTXOs = ?1,
              minimumDesiredUTXOValue = ?2,
              isDeleted = ?3
@@ -86,6 +86,110 @@ pub fn update_output_basket(
     Ok(rows)
 }
 
+/// Find the user's basket `name`, inserting it with no change policy or
+/// restoring it if deleted
+///
+/// Matches TypeScript `StorageReaderWriter.findOrInsertOutputBasket`
+pub fn find_or_insert_output_basket(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    name: &str,
+) -> Result<TableOutputBasket, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        "INSERT INTO output_baskets (userId, name, numberOfDesiredUTXOs, minimumDesiredUTXOValue, isDeleted)
+         VALUES (?1, ?2, 0, 0, 0)
+         ON CONFLICT(name, userId) DO UPDATE SET
+             isDeleted = 0,
+             updated_at = CASE WHEN isDeleted = 0 THEN updated_at ELSE strftime('%Y-%m-%d %H:%M:%f', 'now') END
+         RETURNING created_at, updated_at, basketId, userId, name, numberOfDesiredUTXOs, minimumDesiredUTXOValue, isDeleted",
+        params![user_id, name],
+        |row| {
+            Ok(TableOutputBasket {
+                created_at: row.get(0)?,
+                updated_at: row.get(1)?,
+                basket_id: row.get(2)?,
+                user_id: row.get(3)?,
+                name: row.get(4)?,
+                number_of_desired_utxos: row.get(5)?,
+                minimum_desired_utxo_value: row.get(6)?,
+                is_deleted: row.get::<_, i32>(7)? != 0,
+            })
+        },
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to find or insert output_basket: {}", e)))
+}
+
+/// The user's output baskets matching `args`, ordered by basketId
+///
+/// Matches TypeScript `findOutputBaskets(args: FindOutputBasketsArgs)`
+pub fn find_output_baskets(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindOutputBasketsArgs,
+) -> Result<Vec<TableOutputBasket>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find output_baskets: {}", e));
+
+    let mut query = String::from(
+        "SELECT created_at, updated_at, basketId, userId, name, numberOfDesiredUTXOs, minimumDesiredUTXOValue, isDeleted
+         FROM output_baskets WHERE userId = ?",
+    );
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(args.user_id)];
+    if let Some(name) = &args.name {
+        query.push_str(" AND name = ?");
+        params_vec.push(Box::new(name.clone()));
+    }
+    if let Some(since) = &args.since {
+        query.push_str(" AND updated_at >= ?");
+        params_vec.push(Box::new(since.clone()));
+    }
+    query.push_str(" ORDER BY basketId ASC");
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query).map_err(db_err)?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(TableOutputBasket {
+            created_at: row.get(0)?,
+            updated_at: row.get(1)?,
+            basket_id: row.get(2)?,
+            user_id: row.get(3)?,
+            name: row.get(4)?,
+            number_of_desired_utxos: row.get(5)?,
+            minimum_desired_utxo_value: row.get(6)?,
+            is_deleted: row.get::<_, i32>(7)? != 0,
+        })
+    }).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Update the given change policy fields of a basket, failing with NotFound
+/// if it is missing
+pub fn update_output_basket_fields(
+    conn: &Arc<Mutex<Connection>>,
+    basket_id: i64,
+    updates: &OutputBasketUpdates,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        "UPDATE output_baskets
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+             numberOfDesiredUTXOs = COALESCE(?1, numberOfDesiredUTXOs),
+             minimumDesiredUTXOValue = COALESCE(?2, minimumDesiredUTXOValue)
+         WHERE basketId = ?3",
+        params![updates.number_of_desired_utxos, updates.minimum_desired_utxo_value, basket_id],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update output_basket: {}", e)))?;
+    if rows == 0 {
+        return Err(StorageError::NotFound(format!("output basket {}", basket_id)));
+    }
+    Ok(())
+}
+
 // ============ OUTPUT TAG ============
 
 pub fn insert_output_tag(
@@ -156,6 +260,25 @@ pub fn insert_output_tag_map(
     Ok(())
 }
 
+/// Map a tag to an output, restoring a deleted mapping
+pub fn find_or_insert_output_tag_map(
+    conn: &Arc<Mutex<Connection>>,
+    output_id: i64,
+    output_tag_id: i64,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.execute(
+        "INSERT INTO output_tags_map (outputTagId, outputId, isDeleted) VALUES (?1, ?2, 0)
+         ON CONFLICT(outputTagId, outputId) DO UPDATE SET isDeleted = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+         WHERE isDeleted = 1",
+        params![output_tag_id, output_id],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to find or insert output_tag_map: {}", e)))?;
+
+    Ok(())
+}
+
 // ============ TX LABEL ============
 
 pub fn insert_tx_label(
@@ -205,6 +328,36 @@ pub fn find_tx_label_by_name(
     Ok(result)
 }
 
+/// Labels mapped to a transaction, excluding deleted labels and mappings
+///
+/// Matches TypeScript `StorageReader.getLabelsForTransactionId`
+pub fn find_tx_labels_for_transaction(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+) -> Result<Vec<TableTxLabel>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find tx_labels: {}", e));
+
+    let mut stmt = conn.prepare(
+        "SELECT l.created_at, l.updated_at, l.txLabelId, l.userId, l.label, l.isDeleted
+         FROM tx_labels l JOIN tx_labels_map m ON m.txLabelId = l.txLabelId
+         WHERE m.transactionId = ?1 AND m.isDeleted = 0 AND l.isDeleted = 0
+         ORDER BY l.label",
+    ).map_err(db_err)?;
+    let rows = stmt.query_map(params![transaction_id], |row| {
+        Ok(TableTxLabel {
+            created_at: row.get(0)?,
+            updated_at: row.get(1)?,
+            tx_label_id: row.get(2)?,
+            user_id: row.get(3)?,
+            label: row.get(4)?,
+            is_deleted: row.get::<_, i32>(5)? != 0,
+        })
+    }).map_err(db_err)?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
 // ============ TX LABEL MAP ============
 
 pub fn insert_tx_label_map(
@@ -226,6 +379,335 @@ pub fn insert_tx_label_map(
     Ok(())
 }
 
+/// Map a label to a transaction, restoring a deleted mapping
+pub fn find_or_insert_tx_label_map(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    tx_label_id: i64,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.execute(
+        "INSERT INTO tx_labels_map (txLabelId, transactionId, isDeleted) VALUES (?1, ?2, 0)
+         ON CONFLICT(txLabelId, transactionId) DO UPDATE SET isDeleted = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+         WHERE isDeleted = 1",
+        params![tx_label_id, transaction_id],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to find or insert tx_label_map: {}", e)))?;
+
+    Ok(())
+}
+
+// ============ RENAME / DELETE ============
+
+/// Columns of a name table (tags or labels) and its map table
+struct NameTable {
+    what: &'static str,
+    table: &'static str,
+    id: &'static str,
+    name: &'static str,
+    map: &'static str,
+    mapped: &'static str,
+}
+
+const OUTPUT_TAGS: NameTable = NameTable {
+    what: "output tag",
+    table: "output_tags",
+    id: "outputTagId",
+    name: "tag",
+    map: "output_tags_map",
+    mapped: "outputId",
+};
+
+const TX_LABELS: NameTable = NameTable {
+    what: "tx label",
+    table: "tx_labels",
+    id: "txLabelId",
+    name: "label",
+    map: "tx_labels_map",
+    mapped: "transactionId",
+};
+
+/// Id of the user's undeleted entry named `name`
+fn find_live_id(conn: &Connection, t: &NameTable, user_id: i64, name: &str) -> Result<i64, StorageError> {
+    conn.query_row(
+        &format!("SELECT {} FROM {} WHERE userId = ?1 AND {} = ?2 AND isDeleted = 0", t.id, t.table, t.name),
+        params![user_id, name],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| StorageError::Database(format!("Failed to find {}: {}", t.what, e)))?
+    .ok_or_else(|| StorageError::NotFound(format!("{} {}", t.what, name)))
+}
+
+/// Soft-delete entry `id` and its mappings
+fn soft_delete(conn: &Connection, t: &NameTable, id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        &format!("UPDATE {} SET isDeleted = 1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE {} = ?1", t.table, t.id),
+        params![id],
+    )?;
+    conn.execute(
+        &format!("UPDATE {} SET isDeleted = 1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE {} = ?1", t.map, t.id),
+        params![id],
+    )?;
+    Ok(())
+}
+
+/// Rename the user's entry `name` to `new_name`, merging into an existing
+/// `new_name` (restoring it if deleted) by moving the undeleted mappings
+///
+/// Returns the id of the renamed entry.
+fn rename(
+    conn: &Arc<Mutex<Connection>>,
+    t: &NameTable,
+    user_id: i64,
+    name: &str,
+    new_name: &str,
+) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to rename {}: {}", t.what, e));
+
+    let from = find_live_id(&conn, t, user_id, name)?;
+    if name == new_name {
+        return Ok(from);
+    }
+    let to: Option<i64> = conn
+        .query_row(
+            &format!("SELECT {} FROM {} WHERE userId = ?1 AND {} = ?2", t.id, t.table, t.name),
+            params![user_id, new_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(db_err)?;
+
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    let id = match to {
+        None => {
+            tx.execute(
+                &format!("UPDATE {} SET {} = ?1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE {} = ?2", t.table, t.name, t.id),
+                params![new_name, from],
+            )
+            .map_err(db_err)?;
+            from
+        }
+        Some(to) => {
+            tx.execute(
+                &format!("UPDATE {} SET isDeleted = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE {} = ?1", t.table, t.id),
+                params![to],
+            )
+            .map_err(db_err)?;
+            // `WHERE 1` keeps the upsert's ON CONFLICT from parsing as a join
+            tx.execute(
+                &format!(
+                    "INSERT INTO {map} ({id}, {mapped})
+                     SELECT ?1, {mapped} FROM {map} WHERE {id} = ?2 AND isDeleted = 0 AND 1
+                     ON CONFLICT({id}, {mapped}) DO UPDATE SET isDeleted = 0, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')",
+                    map = t.map,
+                    id = t.id,
+                    mapped = t.mapped,
+                ),
+                params![to, from],
+            )
+            .map_err(db_err)?;
+            soft_delete(&tx, t, from).map_err(db_err)?;
+            to
+        }
+    };
+    tx.commit().map_err(db_err)?;
+    Ok(id)
+}
+
+/// Soft-delete the user's undeleted entry `name` and its mappings
+fn delete(conn: &Arc<Mutex<Connection>>, t: &NameTable, user_id: i64, name: &str) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to delete {}: {}", t.what, e));
+
+    let id = find_live_id(&conn, t, user_id, name)?;
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    soft_delete(&tx, t, id).map_err(db_err)?;
+    tx.commit().map_err(db_err)
+}
+
+/// Find or insert the user's entries named `names`, restoring deleted ones,
+/// in a single upsert
+///
+/// Returns the entries in the order of `names`, without repeats.
+fn find_or_insert<T>(
+    conn: &Arc<Mutex<Connection>>,
+    t: &NameTable,
+    user_id: i64,
+    names: &[String],
+    from_row: fn(&rusqlite::Row) -> rusqlite::Result<T>,
+    name_of: fn(&T) -> &str,
+) -> Result<Vec<T>, StorageError> {
+    let mut unique: Vec<&String> = Vec::new();
+    for name in names {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+    if unique.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find or insert {}: {}", t.what, e));
+
+    // An entry that already exists is only touched when it is restored, but
+    // DO UPDATE rather than DO NOTHING so RETURNING includes it
+    let values = (0..unique.len()).map(|i| format!("(?1, ?{}, 0)", i + 2)).collect::<Vec<_>>().join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "INSERT INTO {table} (userId, {name}, isDeleted) VALUES {values}
+         ON CONFLICT({name}, userId) DO UPDATE SET
+             isDeleted = 0,
+             updated_at = CASE WHEN isDeleted = 0 THEN updated_at ELSE strftime('%Y-%m-%d %H:%M:%f', 'now') END
+         RETURNING created_at, updated_at, {id}, userId, {name}, isDeleted",
+        table = t.table,
+        name = t.name,
+        id = t.id,
+    )).map_err(db_err)?;
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&user_id];
+    params.extend(unique.iter().map(|name| *name as &dyn rusqlite::ToSql));
+    let mut entries = stmt
+        .query_map(params.as_slice(), from_row)
+        .map_err(db_err)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_err)?;
+
+    entries.sort_by_key(|e| unique.iter().position(|name| name.as_str() == name_of(e)));
+    Ok(entries)
+}
+
+/// Find or insert the user's output tags `tags`, in a single statement
+pub fn find_or_insert_output_tags(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    tags: &[String],
+) -> Result<Vec<TableOutputTag>, StorageError> {
+    find_or_insert(conn, &OUTPUT_TAGS, user_id, tags, output_tag_from_row, |t| &t.tag)
+}
+
+/// Find or insert the user's transaction labels `labels`, in a single statement
+pub fn find_or_insert_tx_labels(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    labels: &[String],
+) -> Result<Vec<TableTxLabel>, StorageError> {
+    find_or_insert(conn, &TX_LABELS, user_id, labels, tx_label_from_row, |l| &l.label)
+}
+
+fn output_tag_from_row(row: &rusqlite::Row) -> rusqlite::Result<TableOutputTag> {
+    Ok(TableOutputTag {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        output_tag_id: row.get(2)?,
+        user_id: row.get(3)?,
+        tag: row.get(4)?,
+        is_deleted: row.get::<_, i32>(5)? != 0,
+    })
+}
+
+fn tx_label_from_row(row: &rusqlite::Row) -> rusqlite::Result<TableTxLabel> {
+    Ok(TableTxLabel {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        tx_label_id: row.get(2)?,
+        user_id: row.get(3)?,
+        label: row.get(4)?,
+        is_deleted: row.get::<_, i32>(5)? != 0,
+    })
+}
+
+/// The user's output tags, excluding deleted tags
+pub fn find_output_tags(conn: &Arc<Mutex<Connection>>, user_id: i64) -> Result<Vec<TableOutputTag>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find output_tags: {}", e));
+
+    let mut stmt = conn.prepare(
+        "SELECT created_at, updated_at, outputTagId, userId, tag, isDeleted
+         FROM output_tags WHERE userId = ?1 AND isDeleted = 0 ORDER BY tag",
+    ).map_err(db_err)?;
+    let rows = stmt.query_map(params![user_id], output_tag_from_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// The user's transaction labels, excluding deleted labels
+pub fn find_tx_labels(conn: &Arc<Mutex<Connection>>, user_id: i64) -> Result<Vec<TableTxLabel>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find tx_labels: {}", e));
+
+    let mut stmt = conn.prepare(
+        "SELECT created_at, updated_at, txLabelId, userId, label, isDeleted
+         FROM tx_labels WHERE userId = ?1 AND isDeleted = 0 ORDER BY label",
+    ).map_err(db_err)?;
+    let rows = stmt.query_map(params![user_id], tx_label_from_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Rename an output tag, merging into `new_tag` if the user already has it
+pub fn rename_output_tag(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    tag: &str,
+    new_tag: &str,
+) -> Result<TableOutputTag, StorageError> {
+    let id = rename(conn, &OUTPUT_TAGS, user_id, tag, new_tag)?;
+    conn.lock().unwrap().query_row(
+        "SELECT created_at, updated_at, outputTagId, userId, tag, isDeleted FROM output_tags WHERE outputTagId = ?1",
+        params![id],
+        output_tag_from_row,
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to find output_tag: {}", e)))
+}
+
+/// Soft-delete an output tag and its output mappings
+pub fn delete_output_tag(conn: &Arc<Mutex<Connection>>, user_id: i64, tag: &str) -> Result<(), StorageError> {
+    delete(conn, &OUTPUT_TAGS, user_id, tag)
+}
+
+/// Rename a transaction label, merging into `new_label` if the user already has it
+pub fn rename_tx_label(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    label: &str,
+    new_label: &str,
+) -> Result<TableTxLabel, StorageError> {
+    let id = rename(conn, &TX_LABELS, user_id, label, new_label)?;
+    conn.lock().unwrap().query_row(
+        "SELECT created_at, updated_at, txLabelId, userId, label, isDeleted FROM tx_labels WHERE txLabelId = ?1",
+        params![id],
+        tx_label_from_row,
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to find tx_label: {}", e)))
+}
+
+/// Soft-delete a transaction label and its transaction mappings
+pub fn delete_tx_label(conn: &Arc<Mutex<Connection>>, user_id: i64, label: &str) -> Result<(), StorageError> {
+    delete(conn, &TX_LABELS, user_id, label)
+}
+
+/// Number of outputs mapped to each of the user's undeleted tags
+pub fn get_output_tag_usages(conn: &Arc<Mutex<Connection>>, user_id: i64) -> Result<Vec<OutputTagUsage>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to count output_tag usages: {}", e));
+
+    let mut stmt = conn.prepare(
+        "SELECT t.outputTagId, t.tag, COUNT(m.outputId)
+         FROM output_tags t LEFT JOIN output_tags_map m ON m.outputTagId = t.outputTagId AND m.isDeleted = 0
+         WHERE t.userId = ?1 AND t.isDeleted = 0
+         GROUP BY t.outputTagId ORDER BY t.tag",
+    ).map_err(db_err)?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok(OutputTagUsage {
+            output_tag_id: row.get(0)?,
+            tag: row.get(1)?,
+            output_count: row.get(2)?,
+        })
+    }).map_err(db_err)?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,4 +770,99 @@ mod tests {
         assert!(found.is_some());
         assert_eq!(found.unwrap().label, "invoice-123");
     }
+
+    #[test]
+    fn test_find_tx_labels_for_transaction() {
+        let conn = create_test_storage();
+        conn.lock().unwrap().execute(
+            "INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+             VALUES (1, 'completed', 'ref_test', 1, -1000, 'Test transaction')",
+            params![],
+        ).unwrap();
+
+        for (label, is_deleted) in [("rent", false), ("invoice-123", false), ("old", true)] {
+            let tx_label_id = insert_tx_label(&conn, &TableTxLabel::new(0, 1, label)).unwrap();
+            let mut map = TableTxLabelMap::new(tx_label_id, 1);
+            map.is_deleted = is_deleted;
+            insert_tx_label_map(&conn, &map).unwrap();
+        }
+
+        let labels = find_tx_labels_for_transaction(&conn, 1).unwrap();
+        assert_eq!(labels.iter().map(|l| l.label.as_str()).collect::<Vec<_>>(), vec!["invoice-123", "rent"]);
+        assert!(find_tx_labels_for_transaction(&conn, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rename_and_delete_output_tags() {
+        let conn = create_test_storage();
+        conn.lock().unwrap().execute_batch(
+            "INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+                VALUES (1, 'completed', 'ref_test', 0, 3000, 'Test transaction');
+             INSERT INTO outputs (userId, transactionId, spendable, `change`, vout, satoshis, providedBy, purpose, type, outputDescription)
+                VALUES (1, 1, 1, 0, 0, 1000, 'you', '', 'custom', ''),
+                       (1, 1, 1, 0, 1, 1000, 'you', '', 'custom', ''),
+                       (1, 1, 1, 0, 2, 1000, 'you', '', 'custom', '');",
+        ).unwrap();
+        let rent = insert_output_tag(&conn, &TableOutputTag::new(0, 1, "rent")).unwrap();
+        let bills = insert_output_tag(&conn, &TableOutputTag::new(0, 1, "bills")).unwrap();
+        for (tag_id, output_id) in [(rent, 1), (rent, 2), (bills, 2), (bills, 3)] {
+            insert_output_tag_map(&conn, &TableOutputTagMap::new(tag_id, output_id)).unwrap();
+        }
+
+        // Renaming onto an existing tag merges the mappings
+        let merged = rename_output_tag(&conn, 1, "rent", "bills").unwrap();
+        assert_eq!(merged.output_tag_id, bills);
+        let usages = get_output_tag_usages(&conn, 1).unwrap();
+        assert_eq!(usages.len(), 1);
+        assert_eq!((usages[0].tag.as_str(), usages[0].output_count), ("bills", 3));
+        assert!(find_output_tag_by_name(&conn, 1, "rent").unwrap().unwrap().is_deleted);
+
+        let renamed = rename_output_tag(&conn, 1, "bills", "utilities").unwrap();
+        assert_eq!((renamed.output_tag_id, renamed.tag.as_str()), (bills, "utilities"));
+        assert!(matches!(rename_output_tag(&conn, 1, "bills", "x"), Err(StorageError::NotFound(_))));
+
+        delete_output_tag(&conn, 1, "utilities").unwrap();
+        assert!(find_output_tags(&conn, 1).unwrap().is_empty());
+        assert!(get_output_tag_usages(&conn, 1).unwrap().is_empty());
+        assert!(matches!(delete_output_tag(&conn, 1, "utilities"), Err(StorageError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_rename_and_delete_tx_labels() {
+        let conn = create_test_storage();
+        conn.lock().unwrap().execute(
+            "INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+             VALUES (1, 'completed', 'ref_test', 1, -1000, 'Test transaction')",
+            params![],
+        ).unwrap();
+        let id = insert_tx_label(&conn, &TableTxLabel::new(0, 1, "invoice")).unwrap();
+        insert_tx_label_map(&conn, &TableTxLabelMap::new(id, 1)).unwrap();
+
+        rename_tx_label(&conn, 1, "invoice", "invoice-123").unwrap();
+        assert_eq!(find_tx_labels_for_transaction(&conn, 1).unwrap()[0].label, "invoice-123");
+
+        delete_tx_label(&conn, 1, "invoice-123").unwrap();
+        assert!(find_tx_labels_for_transaction(&conn, 1).unwrap().is_empty());
+        assert!(find_tx_labels(&conn, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_or_insert_tags_and_labels() {
+        let conn = create_test_storage();
+        let rent = insert_output_tag(&conn, &TableOutputTag::new(0, 1, "rent")).unwrap();
+        let mut old = TableTxLabel::new(0, 1, "old");
+        old.is_deleted = true;
+        let old = insert_tx_label(&conn, &old).unwrap();
+
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let tags = find_or_insert_output_tags(&conn, 1, &names(&["bills", "rent", "bills"])).unwrap();
+        assert_eq!(tags.iter().map(|t| t.tag.as_str()).collect::<Vec<_>>(), vec!["bills", "rent"]);
+        assert_eq!(tags[1].output_tag_id, rent);
+        assert_eq!(find_output_tags(&conn, 1).unwrap().len(), 2);
+
+        let labels = find_or_insert_tx_labels(&conn, 1, &names(&["old", "new"])).unwrap();
+        assert_eq!((labels[0].tx_label_id, labels[0].is_deleted), (old, false));
+        assert_eq!(labels[1].label, "new");
+        assert!(find_or_insert_tx_labels(&conn, 1, &[]).unwrap().is_empty());
+    }
 }
@@ -1,4 +1,4 @@
-//! Certificate, Commission, SyncState, MonitorEvent CRUD operations
+//! Certificate, Commission, SyncState, MonitorEvent, IdentityCache CRUD operations
 //!
 //! Reference: @wallet-toolbox/src/storage/StorageKnex.ts
 
@@ -35,6 +35,26 @@ pub fn insert_certificate(
     Ok(conn.last_insert_rowid())
 }
 
+const CERTIFICATE_COLUMNS: &str = "created_at, updated_at, certificateId, userId, serialNumber, type, certifier,
+                subject, verifier, revocationOutpoint, signature, isDeleted";
+
+fn parse_certificate_row(row: &rusqlite::Row) -> rusqlite::Result<TableCertificate> {
+    Ok(TableCertificate {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        certificate_id: row.get(2)?,
+        user_id: row.get(3)?,
+        serial_number: row.get(4)?,     // serialNumber column
+        certificate_type: row.get(5)?,   // type column
+        certifier: row.get(6)?,
+        subject: row.get(7)?,
+        verifier: row.get(8)?,
+        revocation_outpoint: row.get(9)?,
+        signature: row.get(10)?,
+        is_deleted: row.get::<_, i32>(11)? != 0,
+    })
+}
+
 pub fn find_certificate_by_id(
     conn: &Arc<Mutex<Connection>>,
     cert_id: i64,
@@ -42,26 +62,9 @@ pub fn find_certificate_by_id(
     let conn = conn.lock().unwrap();
 
     let result = conn.query_row(
-        "SELECT created_at, updated_at, certificateId, userId, serialNumber, type, certifier,
-                subject, verifier, revocationOutpoint, signature, isDeleted
-         FROM certificates WHERE certificateId = ?1",
+        &format!("SELECT {} FROM certificates WHERE certificateId = ?1", CERTIFICATE_COLUMNS),
         params![cert_id],
-        |row| {
-            Ok(TableCertificate {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                certificate_id: row.get(2)?,
-                user_id: row.get(3)?,
-                serial_number: row.get(4)?,     // serialNumber column
-                certificate_type: row.get(5)?,   // type column
-                certifier: row.get(6)?,
-                subject: row.get(7)?,
-                verifier: row.get(8)?,
-                revocation_outpoint: row.get(9)?,
-                signature: row.get(10)?,
-                is_deleted: row.get::<_, i32>(11)? != 0,
-            })
-        },
+        parse_certificate_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find certificate: {}", e)))?;
@@ -69,6 +72,100 @@ pub fn find_certificate_by_id(
     Ok(result)
 }
 
+/// WHERE clause for the user's live certificates matching `args`
+///
+/// Reference: TS StorageKnex.findCertificatesQuery
+fn certificates_where(args: &FindCertificatesArgs) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+    let mut clause = String::from(" WHERE userId = ? AND isDeleted = 0");
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(args.user_id)];
+
+    if let Some(since) = &args.since {
+        clause.push_str(" AND updated_at >= ?");
+        params_vec.push(Box::new(since.clone()));
+    }
+
+    if let Some(partial) = &args.partial {
+        let columns = [
+            ("type", &partial.certificate_type),
+            ("serialNumber", &partial.serial_number),
+            ("certifier", &partial.certifier),
+            ("subject", &partial.subject),
+            ("revocationOutpoint", &partial.revocation_outpoint),
+            ("signature", &partial.signature),
+        ];
+        for (column, value) in columns {
+            if let Some(value) = value {
+                clause.push_str(&format!(" AND {} = ?", column));
+                params_vec.push(Box::new(value.clone()));
+            }
+        }
+    }
+
+    for (column, values) in [("certifier", &args.certifiers), ("type", &args.types)] {
+        if let Some(values) = values.as_ref().filter(|v| !v.is_empty()) {
+            let placeholders = vec!["?"; values.len()].join(", ");
+            clause.push_str(&format!(" AND {} IN ({})", column, placeholders));
+            for value in values {
+                params_vec.push(Box::new(value.clone()));
+            }
+        }
+    }
+
+    (clause, params_vec)
+}
+
+/// Find the user's certificates matching `args`, ordered by certificateId
+pub fn find_certificates(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindCertificatesArgs,
+) -> Result<Vec<TableCertificate>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let (clause, params_vec) = certificates_where(args);
+    let mut query = format!("SELECT {} FROM certificates{}", CERTIFICATE_COLUMNS, clause);
+    query.push_str(if args.order_descending == Some(true) {
+        " ORDER BY certificateId DESC"
+    } else {
+        " ORDER BY certificateId ASC"
+    });
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let rows = stmt.query_map(params_refs.as_slice(), parse_certificate_row)
+        .map_err(|e| StorageError::Database(format!("Failed to query certificates: {}", e)))?;
+
+    let mut certificates = Vec::new();
+    for row in rows {
+        certificates.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+
+    Ok(certificates)
+}
+
+/// Count the user's certificates matching `args`, ignoring paging
+pub fn count_certificates(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindCertificatesArgs,
+) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let (clause, params_vec) = certificates_where(args);
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM certificates{}", clause),
+        params_refs.as_slice(),
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to count certificates: {}", e)))
+}
+
 pub fn update_certificate(
     conn: &Arc<Mutex<Connection>>,
     cert_id: i64,
@@ -78,7 +175,7 @@ pub fn update_certificate(
 
     let rows = conn.execute(
         "UPDATE certificates
-         SET updated_at = datetime('now'),
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
              verifier = ?1,
              isDeleted = ?2
          WHERE certificateId = ?3",
@@ -99,8 +196,14 @@ pub fn insert_certificate_field(
     conn: &Arc<Mutex<Connection>>,
     field: &TableCertificateField,
 ) -> Result<(), StorageError> {
-    let conn = conn.lock().unwrap();
+    insert_certificate_field_row(&conn.lock().unwrap(), field)
+}
 
+/// Insert a certificate field on a connection the caller has locked
+pub(crate) fn insert_certificate_field_row(
+    conn: &Connection,
+    field: &TableCertificateField,
+) -> Result<(), StorageError> {
     conn.execute(
         "INSERT INTO certificate_fields (userId, certificateId, fieldName, fieldValue, masterKey)
          VALUES (?1, ?2, ?3, ?4, ?5)",
@@ -236,6 +339,28 @@ pub fn insert_sync_state(
     Ok(conn.last_insert_rowid())
 }
 
+const SYNC_STATE_COLUMNS: &str = "created_at, updated_at, syncStateId, userId, storageIdentityKey, storageName,
+                status, init, refNum, syncMap, `when`, satoshis, errorLocal, errorOther";
+
+fn sync_state_from_row(row: &rusqlite::Row) -> rusqlite::Result<TableSyncState> {
+    Ok(TableSyncState {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        sync_state_id: row.get(2)?,
+        user_id: row.get(3)?,
+        storage_identity_key: row.get(4)?,
+        storage_name: row.get(5)?,
+        status: row.get::<_, String>(6)?.parse().unwrap_or(SyncStatus::Unknown),
+        init: row.get::<_, i32>(7)? != 0,
+        ref_num: row.get(8)?,
+        sync_map: row.get(9)?,
+        when: row.get(10)?,
+        satoshis: row.get(11)?,
+        error_local: row.get(12)?,
+        error_other: row.get(13)?,
+    })
+}
+
 pub fn find_sync_state_by_ref(
     conn: &Arc<Mutex<Connection>>,
     ref_num: &str,
@@ -243,28 +368,9 @@ pub fn find_sync_state_by_ref(
     let conn = conn.lock().unwrap();
 
     let result = conn.query_row(
-        "SELECT created_at, updated_at, syncStateId, userId, storageIdentityKey, storageName,
-                status, init, refNum, syncMap, `when`, satoshis, errorLocal, errorOther
-         FROM sync_states WHERE refNum = ?1",
+        &format!("SELECT {} FROM sync_states WHERE refNum = ?1", SYNC_STATE_COLUMNS),
         params![ref_num],
-        |row| {
-            Ok(TableSyncState {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                sync_state_id: row.get(2)?,
-                user_id: row.get(3)?,
-                storage_identity_key: row.get(4)?,
-                storage_name: row.get(5)?,
-                status: row.get::<_, String>(6)?.parse().unwrap_or(SyncStatus::Unknown),
-                init: row.get::<_, i32>(7)? != 0,
-                ref_num: row.get(8)?,
-                sync_map: row.get(9)?,
-                when: row.get(10)?,
-                satoshis: row.get(11)?,
-                error_local: row.get(12)?,
-                error_other: row.get(13)?,
-            })
-        },
+        sync_state_from_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find sync_state: {}", e)))?;
@@ -272,6 +378,84 @@ pub fn find_sync_state_by_ref(
     Ok(result)
 }
 
+/// Find sync states matching `args`, oldest first
+pub fn find_sync_states(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindSyncStatesArgs,
+) -> Result<Vec<TableSyncState>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if let Some(user_id) = args.user_id {
+        conditions.push("userId = ?");
+        params_vec.push(Box::new(user_id));
+    }
+    if let Some(key) = &args.storage_identity_key {
+        conditions.push("storageIdentityKey = ?");
+        params_vec.push(Box::new(key.clone()));
+    }
+    if let Some(status) = args.status {
+        conditions.push("status = ?");
+        params_vec.push(Box::new(status.to_string()));
+    }
+
+    let mut query = format!("SELECT {} FROM sync_states", SYNC_STATE_COLUMNS);
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    query.push_str(" ORDER BY syncStateId ASC");
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let rows = stmt.query_map(params_refs.as_slice(), sync_state_from_row)
+        .map_err(|e| StorageError::Database(format!("Failed to query sync_states: {}", e)))?;
+
+    let mut states = Vec::new();
+    for row in rows {
+        states.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+
+    Ok(states)
+}
+
+/// Save the progress fields of a sync state
+pub fn update_sync_state(
+    conn: &Arc<Mutex<Connection>>,
+    sync_state: &TableSyncState,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let updated = conn.execute(
+        "UPDATE sync_states
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), status = ?1, init = ?2, syncMap = ?3, `when` = ?4,
+             satoshis = ?5, errorLocal = ?6, errorOther = ?7
+         WHERE syncStateId = ?8",
+        params![
+            sync_state.status.to_string(),
+            if sync_state.init { 1 } else { 0 },
+            sync_state.sync_map,
+            sync_state.when,
+            sync_state.satoshis,
+            sync_state.error_local,
+            sync_state.error_other,
+            sync_state.sync_state_id,
+        ],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update sync_state: {}", e)))?;
+
+    if updated == 0 {
+        return Err(StorageError::NotFound(format!("sync_state {}", sync_state.sync_state_id)));
+    }
+    Ok(())
+}
+
 // ============ MONITOR EVENT ============
 
 pub fn insert_monitor_event(
@@ -289,6 +473,137 @@ pub fn insert_monitor_event(
     Ok(conn.last_insert_rowid())
 }
 
+/// Find monitor events matching `args`
+///
+/// `since` and `until` are normalized with `datetime()` so RFC 3339 bounds
+/// compare with the stored `YYYY-MM-DD HH:MM:SS` times.
+pub fn find_monitor_events(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindMonitorEventsArgs,
+) -> Result<Vec<TableMonitorEvent>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if let Some(event) = &args.event {
+        conditions.push("event = ?");
+        params_vec.push(Box::new(event.clone()));
+    }
+    if let Some(since) = &args.since {
+        conditions.push("created_at >= datetime(?)");
+        params_vec.push(Box::new(since.clone()));
+    }
+    if let Some(until) = &args.until {
+        conditions.push("created_at < datetime(?)");
+        params_vec.push(Box::new(until.clone()));
+    }
+
+    let mut query = "SELECT id, created_at, updated_at, event, details FROM monitor_events".to_string();
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    query.push_str(if args.order_descending == Some(true) {
+        " ORDER BY id DESC"
+    } else {
+        " ORDER BY id ASC"
+    });
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(TableMonitorEvent {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            updated_at: row.get(2)?,
+            event: row.get(3)?,
+            details: row.get(4)?,
+        })
+    })
+    .map_err(|e| StorageError::Database(format!("Failed to query monitor_events: {}", e)))?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        events.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+
+    Ok(events)
+}
+
+// ============ IDENTITY CACHE ============
+
+pub fn find_identity_cache(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    query: &str,
+) -> Result<Option<TableIdentityCache>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        "SELECT created_at, updated_at, identityCacheId, userId, query, identityKey, name, avatarURL,
+                certificates, trustScore, expiresAt
+         FROM identity_caches WHERE userId = ?1 AND query = ?2",
+        params![user_id, query],
+        |row| {
+            Ok(TableIdentityCache {
+                created_at: row.get(0)?,
+                updated_at: row.get(1)?,
+                identity_cache_id: row.get(2)?,
+                user_id: row.get(3)?,
+                query: row.get(4)?,
+                identity_key: row.get(5)?,
+                name: row.get(6)?,
+                avatar_url: row.get(7)?,
+                certificates: row.get(8)?,
+                trust_score: row.get(9)?,
+                expires_at: row.get(10)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| StorageError::Database(format!("Failed to find identity_cache: {}", e)))
+}
+
+/// Insert `entry`, replacing the user's entry for the same query
+pub fn upsert_identity_cache(
+    conn: &Arc<Mutex<Connection>>,
+    entry: &TableIdentityCache,
+) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        "INSERT INTO identity_caches
+            (userId, query, identityKey, name, avatarURL, certificates, trustScore, expiresAt)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT(userId, query) DO UPDATE SET
+            updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+            identityKey = excluded.identityKey,
+            name = excluded.name,
+            avatarURL = excluded.avatarURL,
+            certificates = excluded.certificates,
+            trustScore = excluded.trustScore,
+            expiresAt = excluded.expiresAt
+         RETURNING identityCacheId",
+        params![
+            entry.user_id,
+            entry.query,
+            entry.identity_key,
+            entry.name,
+            entry.avatar_url,
+            entry.certificates,
+            entry.trust_score,
+            entry.expires_at,
+        ],
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to upsert identity_cache: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +641,41 @@ mod tests {
         assert_eq!(found.certificate_type, "identity");
     }
 
+    #[test]
+    fn test_find_and_count_certificates() {
+        let conn = create_test_storage();
+        for (serial, cert_type, certifier) in [("s1", "identity", "c1"), ("s2", "identity", "c2"), ("s3", "email", "c1")] {
+            let cert = TableCertificate::new(0, 1, cert_type, serial, certifier, "subject_key", "outpoint_abc", "signature_xyz");
+            insert_certificate(&conn, &cert).unwrap();
+        }
+
+        let mut args = FindCertificatesArgs {
+            user_id: 1,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            certifiers: Some(vec!["c1".to_string()]),
+            types: None,
+            include_fields: None,
+        };
+        let found = find_certificates(&conn, &args).unwrap();
+        assert_eq!(found.iter().map(|c| c.serial_number.as_str()).collect::<Vec<_>>(), vec!["s1", "s3"]);
+
+        args.certifiers = None;
+        args.types = Some(vec!["identity".to_string()]);
+        args.paged = Some(Paged { limit: 1, offset: Some(1) });
+        let found = find_certificates(&conn, &args).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].serial_number, "s2");
+        assert_eq!(count_certificates(&conn, &args).unwrap(), 2);
+
+        args.types = None;
+        args.paged = None;
+        args.partial = Some(PartialCertificate { serial_number: Some("s3".to_string()), ..Default::default() });
+        assert_eq!(count_certificates(&conn, &args).unwrap(), 1);
+    }
+
     #[test]
     fn test_certificate_fields() {
         let conn = create_test_storage();
@@ -383,9 +733,27 @@ mod tests {
         let found = find_sync_state_by_ref(&conn, "ref_unique_123").unwrap();
         assert!(found.is_some());
         
-        let found = found.unwrap();
+        let mut found = found.unwrap();
         assert_eq!(found.status, SyncStatus::Success);
         assert_eq!(found.storage_name, "Storage Name");
+
+        found.status = SyncStatus::Error;
+        found.error_local = Some("{\"code\":\"WERR_INTERNAL\",\"description\":\"lost\"}".to_string());
+        found.when = Some("2024-01-01T00:00:00Z".to_string());
+        update_sync_state(&conn, &found).unwrap();
+
+        let errored = find_sync_states(&conn, &FindSyncStatesArgs {
+            status: Some(SyncStatus::Error),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(errored.len(), 1);
+        assert_eq!(errored[0].error_local, found.error_local);
+        assert_eq!(errored[0].when, found.when);
+        let other_store = find_sync_states(&conn, &FindSyncStatesArgs {
+            storage_identity_key: Some("other".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert!(other_store.is_empty());
     }
 
     #[test]
@@ -398,4 +766,57 @@ mod tests {
         let id = insert_monitor_event(&conn, &event).unwrap();
         assert!(id > 0);
     }
+
+    #[test]
+    fn test_find_monitor_events() {
+        let conn = create_test_storage();
+        conn.lock().unwrap().execute_batch(
+            "INSERT INTO monitor_events (event, details, created_at) VALUES ('TaskError', 'a', '2024-03-01 10:00:00');
+             INSERT INTO monitor_events (event, details, created_at) VALUES ('ProofFound', 'b', '2024-03-01 12:00:00');
+             INSERT INTO monitor_events (event, details, created_at) VALUES ('TaskError', 'c', '2024-03-02 09:00:00');",
+        ).unwrap();
+
+        let all = find_monitor_events(&conn, &FindMonitorEventsArgs::default()).unwrap();
+        assert_eq!(all.iter().map(|e| e.details.as_deref().unwrap()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(all[0].created_at, "2024-03-01 10:00:00");
+
+        let mut args = FindMonitorEventsArgs {
+            since: Some("2024-03-01T12:00:00+00:00".to_string()),
+            until: Some("2024-03-02T09:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let found = find_monitor_events(&conn, &args).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].event, "ProofFound");
+
+        args = FindMonitorEventsArgs {
+            event: Some("TaskError".to_string()),
+            order_descending: Some(true),
+            paged: Some(Paged { limit: 1, offset: None }),
+            ..Default::default()
+        };
+        let found = find_monitor_events(&conn, &args).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].details.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_identity_cache_upsert() {
+        let conn = create_test_storage();
+        crate::migrations::apply_pending_migrations(&conn.lock().unwrap()).unwrap();
+
+        let mut entry = TableIdentityCache::new(0, 1, "identityKey:02ab", "[]", 0, "2024-03-01T10:00:00Z");
+        let id = upsert_identity_cache(&conn, &entry).unwrap();
+
+        entry.name = Some("Alice".to_string());
+        entry.trust_score = 7;
+        entry.expires_at = "2024-03-02T10:00:00Z".to_string();
+        assert_eq!(upsert_identity_cache(&conn, &entry).unwrap(), id);
+
+        let found = find_identity_cache(&conn, 1, "identityKey:02ab").unwrap().unwrap();
+        assert_eq!(found.identity_cache_id, id);
+        assert_eq!(found.name.as_deref(), Some("Alice"));
+        assert_eq!((found.trust_score, found.expires_at.as_str()), (7, "2024-03-02T10:00:00Z"));
+        assert!(find_identity_cache(&conn, 1, "identityKey:03cd").unwrap().is_none());
+    }
 }
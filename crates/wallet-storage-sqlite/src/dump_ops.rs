@@ -0,0 +1,216 @@
+//! Export and import of portable storage dumps
+//!
+//! Exports every row one user owns, with its proven transactions and their
+//! requests, into a `StorageDump`; imports one into a store without users,
+//! keeping the original ids so foreign keys still line up.
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+use wallet_storage::*;
+
+/// Dumped tables in restore order, with the filter selecting the rows of user `?1`
+const DUMP_TABLES: &[(&str, &str)] = &[
+    ("users", "userId = ?1"),
+    ("proven_txs", "txid IN (SELECT txid FROM transactions WHERE userId = ?1)"),
+    ("proven_tx_reqs", "txid IN (SELECT txid FROM transactions WHERE userId = ?1)"),
+    ("output_baskets", "userId = ?1"),
+    ("transactions", "userId = ?1"),
+    ("commissions", "userId = ?1"),
+    ("outputs", "userId = ?1"),
+    ("output_tags", "userId = ?1"),
+    ("output_tags_map", "outputId IN (SELECT outputId FROM outputs WHERE userId = ?1)"),
+    ("tx_labels", "userId = ?1"),
+    ("tx_labels_map", "transactionId IN (SELECT transactionId FROM transactions WHERE userId = ?1)"),
+    ("certificates", "userId = ?1"),
+    ("certificate_fields", "userId = ?1"),
+    ("sync_states", "userId = ?1"),
+];
+
+/// Dump everything the user with `identity_key` owns
+pub fn export_user(
+    conn: &Arc<Mutex<Connection>>,
+    identity_key: &str,
+    settings: &TableSettings,
+) -> Result<StorageDump, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let user_id: i64 = conn
+        .query_row("SELECT userId FROM users WHERE identityKey = ?1", params![identity_key], |row| row.get(0))
+        .optional()
+        .map_err(|e| StorageError::Database(format!("Failed to find user: {}", e)))?
+        .ok_or_else(|| StorageError::NotFound(format!("user {}", identity_key)))?;
+
+    let mut tables = Vec::new();
+    for (name, filter) in DUMP_TABLES {
+        let mut stmt = conn
+            .prepare(&format!("SELECT * FROM {} WHERE {} ORDER BY rowid", name, filter))
+            .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        let rows = stmt
+            .query_map(params![user_id], |row| {
+                (0..columns.len())
+                    .map(|i| row.get_ref(i).map(dump_value))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .map_err(|e| StorageError::Database(format!("Failed to query {}: {}", name, e)))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| StorageError::Database(format!("Row error: {}", e)))?;
+
+        tables.push(StorageDumpTable::new(*name, columns, rows));
+    }
+
+    Ok(StorageDump::new(identity_key, settings, tables))
+}
+
+/// Restore `dump` into a store that has no users yet
+///
+/// Verifies the dump's checksums first and restores all tables in one
+/// database transaction, so a failed import leaves the store empty.
+pub fn import_dump(
+    conn: &Arc<Mutex<Connection>>,
+    dump: &StorageDump,
+    settings: &TableSettings,
+) -> Result<(), StorageError> {
+    dump.verify()?;
+    if dump.chain != settings.chain.to_string() {
+        return Err(StorageError::InvalidArg(format!(
+            "storage dump is for chain {}, this store is on {}",
+            dump.chain, settings.chain
+        )));
+    }
+
+    let mut conn = conn.lock().unwrap();
+
+    let users: i64 = conn
+        .query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))
+        .map_err(|e| StorageError::Database(format!("Failed to count users: {}", e)))?;
+    if users > 0 {
+        return Err(StorageError::Conflict(
+            "storage dumps can only be imported into a store without users".to_string(),
+        ));
+    }
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| StorageError::Database(format!("Failed to begin transaction: {}", e)))?;
+    for table in &dump.tables {
+        // Names come from the file, so only known tables and plain column names reach SQL
+        if !DUMP_TABLES.iter().any(|(name, _)| *name == table.name) {
+            return Err(StorageError::InvalidArg(format!("unknown table {} in storage dump", table.name)));
+        }
+        if let Some(column) = table
+            .columns
+            .iter()
+            .find(|c| c.is_empty() || !c.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_'))
+        {
+            return Err(StorageError::InvalidArg(format!("invalid column {} in storage dump", column)));
+        }
+
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table.name,
+            table.columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", "),
+            (1..=table.columns.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", "),
+        );
+        let mut stmt = tx
+            .prepare(&sql)
+            .map_err(|e| StorageError::Database(format!("Failed to prepare insert into {}: {}", table.name, e)))?;
+        for row in &table.rows {
+            stmt.execute(rusqlite::params_from_iter(row.iter().map(sql_value)))
+                .map_err(|e| StorageError::Database(format!("Failed to insert into {}: {}", table.name, e)))?;
+        }
+    }
+    tx.commit()
+        .map_err(|e| StorageError::Database(format!("Failed to commit import: {}", e)))
+}
+
+fn dump_value(value: ValueRef) -> DumpValue {
+    match value {
+        ValueRef::Null => DumpValue::Null,
+        ValueRef::Integer(i) => DumpValue::Integer(i),
+        ValueRef::Real(f) => DumpValue::Real(f),
+        ValueRef::Text(t) => DumpValue::Text(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => DumpValue::Blob(b.to_vec()),
+    }
+}
+
+fn sql_value(value: &DumpValue) -> Value {
+    match value {
+        DumpValue::Null => Value::Null,
+        DumpValue::Integer(i) => Value::Integer(*i),
+        DumpValue::Real(f) => Value::Real(*f),
+        DumpValue::Text(t) => Value::Text(t.clone()),
+        DumpValue::Blob(b) => Value::Blob(b.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::apply_initial_migration;
+
+    fn create_test_storage() -> Arc<Mutex<Connection>> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        apply_initial_migration(&conn, "test_key", "Test", "main", 100000).unwrap();
+        Arc::new(Mutex::new(conn))
+    }
+
+    fn settings() -> TableSettings {
+        TableSettings::new("test_key", "Test", SettingsChain::Main, DbType::SQLite, 100000)
+    }
+
+    fn count(conn: &Arc<Mutex<Connection>>, table: &str) -> i64 {
+        conn.lock().unwrap()
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let source = create_test_storage();
+        source.lock().unwrap().execute_batch(
+            "INSERT INTO users (identityKey, activeStorage) VALUES ('alice', 'test_key');
+             INSERT INTO users (identityKey, activeStorage) VALUES ('bob', 'test_key');
+             INSERT INTO output_baskets (userId, name, numberOfDesiredUTXOs, minimumDesiredUTXOValue)
+                 VALUES (1, 'default', 6, 10000);
+             INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description, txid, rawTx)
+                 VALUES (1, 'completed', 'ref1', 0, 1000, 'received', 'aa', X'0100');
+             INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+                 VALUES (2, 'completed', 'ref2', 0, 5, 'bob');
+             INSERT INTO outputs (userId, transactionId, basketId, spendable, vout, satoshis, providedBy, purpose, type, lockingScript)
+                 VALUES (1, 1, 1, 1, 0, 1000, 'storage', 'change', 'P2PKH', X'76a9');",
+        ).unwrap();
+
+        let dump = export_user(&source, "alice", &settings()).unwrap();
+        assert_eq!(dump.table("transactions").unwrap().rows.len(), 1);
+
+        let target = create_test_storage();
+        import_dump(&target, &dump, &settings()).unwrap();
+        assert_eq!(count(&target, "users"), 1);
+        assert_eq!(count(&target, "outputs"), 1);
+        let raw_tx: Vec<u8> = target.lock().unwrap()
+            .query_row("SELECT rawTx FROM transactions WHERE reference = 'ref1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(raw_tx, vec![0x01, 0x00]);
+
+        // A second import would collide with the restored user
+        assert!(matches!(import_dump(&target, &dump, &settings()), Err(StorageError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_import_rejects_tampered_dump() {
+        let source = create_test_storage();
+        source.lock().unwrap()
+            .execute("INSERT INTO users (identityKey, activeStorage) VALUES ('alice', 'test_key')", [])
+            .unwrap();
+        let mut dump = export_user(&source, "alice", &settings()).unwrap();
+        dump.tables[0].rows[0].push(DumpValue::Null);
+
+        let target = create_test_storage();
+        assert!(matches!(import_dump(&target, &dump, &settings()), Err(StorageError::InvalidArg(_))));
+        assert_eq!(count(&target, "users"), 0);
+    }
+}
@@ -0,0 +1,185 @@
+//! SQLCipher database encryption
+//!
+//! Wallet databases hold derivation prefixes, certificates and identity keys.
+//! With the `sqlcipher` feature rusqlite is built against bundled SQLCipher
+//! and these functions key, verify and rekey encrypted databases.
+//!
+//! Keying a plain SQLite build fails instead of silently leaving the
+//! database unencrypted.
+
+use hmac::{Hmac, Mac};
+use rusqlite::{Connection, OptionalExtension};
+use sha2::Sha256;
+use wallet_storage::StorageError;
+
+/// HMAC label for deriving the database key from a wallet primary key
+const PRIMARY_KEY_DERIVATION_LABEL: &[u8] = b"wallet-storage-sqlite database encryption";
+
+/// Key for an SQLCipher-encrypted database
+#[derive(Clone, PartialEq, Eq)]
+pub enum SqliteKey {
+    /// 256-bit key used directly, skipping SQLCipher's passphrase KDF
+    Raw([u8; 32]),
+
+    /// Passphrase stretched by SQLCipher's PBKDF2
+    Passphrase(String),
+}
+
+impl SqliteKey {
+    /// Raw key derived from a wallet primary key
+    ///
+    /// HMAC-SHA256 of a fixed label keyed by `primary_key`, so the same
+    /// wallet always reopens its database and the key reveals nothing about
+    /// the primary key.
+    pub fn from_primary_key(primary_key: &[u8]) -> Self {
+        let mut mac = Hmac::<Sha256>::new_from_slice(primary_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(PRIMARY_KEY_DERIVATION_LABEL);
+        SqliteKey::Raw(mac.finalize().into_bytes().into())
+    }
+
+    /// Value for `PRAGMA key` / `PRAGMA rekey`
+    fn pragma_value(&self) -> String {
+        match self {
+            SqliteKey::Raw(bytes) => {
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                format!("\"x'{}'\"", hex)
+            }
+            SqliteKey::Passphrase(passphrase) => format!("'{}'", passphrase.replace('\'', "''")),
+        }
+    }
+}
+
+impl std::fmt::Debug for SqliteKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqliteKey::Raw(_) => write!(f, "SqliteKey::Raw(..)"),
+            SqliteKey::Passphrase(_) => write!(f, "SqliteKey::Passphrase(..)"),
+        }
+    }
+}
+
+/// SQLCipher version of `conn`, or None for a plain SQLite build
+pub fn cipher_version(conn: &Connection) -> Result<Option<String>, StorageError> {
+    conn.query_row("PRAGMA cipher_version", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| StorageError::Database(format!("Failed to query cipher version: {}", e)))
+}
+
+/// Key `conn` with `key`
+///
+/// Must run before any other statement reads the database. A database
+/// written by an older SQLCipher major version is upgraded in place with
+/// `PRAGMA cipher_migrate`. Fails with `Unauthorized` when the key does not
+/// open the database.
+pub fn apply_key(conn: &Connection, key: &SqliteKey) -> Result<(), StorageError> {
+    require_sqlcipher(conn)?;
+
+    conn.execute_batch(&format!("PRAGMA key = {};", key.pragma_value()))
+        .map_err(|e| StorageError::Database(format!("Failed to set database key: {}", e)))?;
+
+    if key_opens_database(conn) {
+        return Ok(());
+    }
+
+    let migrated: Option<String> = conn
+        .query_row("PRAGMA cipher_migrate", [], |row| row.get(0))
+        .optional()
+        .unwrap_or(None);
+    if migrated.as_deref() == Some("0") && key_opens_database(conn) {
+        return Ok(());
+    }
+
+    Err(StorageError::Unauthorized(
+        "database key is incorrect or the file is not an encrypted database".to_string(),
+    ))
+}
+
+/// Re-encrypt the database `conn` opens under `new_key`
+///
+/// `conn` must already be keyed with the current key.
+pub fn rekey(conn: &Connection, new_key: &SqliteKey) -> Result<(), StorageError> {
+    require_sqlcipher(conn)?;
+
+    conn.execute_batch(&format!("PRAGMA rekey = {};", new_key.pragma_value()))
+        .map_err(|e| StorageError::Database(format!("Failed to rekey database: {}", e)))
+}
+
+fn require_sqlcipher(conn: &Connection) -> Result<(), StorageError> {
+    match cipher_version(conn)? {
+        Some(_) => Ok(()),
+        None => Err(StorageError::Database(
+            "SQLCipher support is not enabled; build with the `sqlcipher` feature".to_string(),
+        )),
+    }
+}
+
+/// Whether the keyed connection can read the schema
+fn key_opens_database(conn: &Connection) -> bool {
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .is_ok()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pragma_values() {
+        assert_eq!(SqliteKey::Raw([0xab; 32]).pragma_value(), format!("\"x'{}'\"", "ab".repeat(32)));
+        assert_eq!(SqliteKey::Passphrase("it's".to_string()).pragma_value(), "'it''s'");
+        assert_eq!(format!("{:?}", SqliteKey::Passphrase("secret".to_string())), "SqliteKey::Passphrase(..)");
+    }
+
+    #[test]
+    fn test_key_from_primary_key() {
+        let key = SqliteKey::from_primary_key(&[1; 32]);
+        assert_eq!(key, SqliteKey::from_primary_key(&[1; 32]));
+        assert_ne!(key, SqliteKey::from_primary_key(&[2; 32]));
+        assert_ne!(key, SqliteKey::Raw([1; 32]));
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[test]
+    fn test_apply_key_requires_sqlcipher() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(cipher_version(&conn).unwrap().is_none());
+        assert!(apply_key(&conn, &SqliteKey::Raw([1; 32])).is_err());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_encrypted_database_rekey() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.sqlite");
+        let key = SqliteKey::from_primary_key(&[1; 32]);
+        let new_key = SqliteKey::Passphrase("rotated".to_string());
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, &key).unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1);").unwrap();
+        drop(conn);
+
+        // Unkeyed and wrongly keyed connections cannot read it
+        let conn = Connection::open(&path).unwrap();
+        assert!(!key_opens_database(&conn));
+        drop(conn);
+        let conn = Connection::open(&path).unwrap();
+        assert!(apply_key(&conn, &new_key).is_err());
+        drop(conn);
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, &key).unwrap();
+        rekey(&conn, &new_key).unwrap();
+        drop(conn);
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, &new_key).unwrap();
+        let x: i64 = conn.query_row("SELECT x FROM t", [], |row| row.get(0)).unwrap();
+        assert_eq!(x, 1);
+    }
+}
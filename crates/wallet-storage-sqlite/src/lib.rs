@@ -11,8 +11,14 @@ pub mod output_ops;
 pub mod proven_tx_ops;
 pub mod basket_tag_label_ops;
 pub mod cert_commission_ops;
+pub mod encryption;
+pub mod purge_ops;
+pub mod quota_ops;
+pub mod dump_ops;
+mod row_version;
 
 pub use storage_sqlite::StorageSqlite;
+pub use encryption::SqliteKey;
 
 // Re-export commonly used types
 pub use wallet_storage::*;
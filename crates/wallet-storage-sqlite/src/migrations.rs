@@ -12,8 +12,8 @@ use wallet_storage::StorageError;
 pub const INITIAL_MIGRATION: &str = r#"
 -- proven_txs table
 CREATE TABLE IF NOT EXISTS proven_txs (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     provenTxId INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     txid TEXT NOT NULL UNIQUE,
     height INTEGER NOT NULL,
@@ -28,8 +28,8 @@ CREATE INDEX IF NOT EXISTS idx_proven_txs_blockHash ON proven_txs(blockHash);
 
 -- proven_tx_reqs table  
 CREATE TABLE IF NOT EXISTS proven_tx_reqs (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     provenTxReqId INTEGER PRIMARY KEY AUTOINCREMENT,
     provenTxId INTEGER REFERENCES proven_txs(provenTxId),
     status TEXT NOT NULL DEFAULT 'unknown',
@@ -48,8 +48,8 @@ CREATE INDEX IF NOT EXISTS idx_proven_tx_reqs_batch ON proven_tx_reqs(batch);
 
 -- users table
 CREATE TABLE IF NOT EXISTS users (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     userId INTEGER PRIMARY KEY AUTOINCREMENT,
     identityKey TEXT NOT NULL UNIQUE,
     activeStorage TEXT NOT NULL
@@ -57,8 +57,8 @@ CREATE TABLE IF NOT EXISTS users (
 
 -- certificates table
 CREATE TABLE IF NOT EXISTS certificates (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     certificateId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     serialNumber TEXT NOT NULL,
@@ -74,8 +74,8 @@ CREATE TABLE IF NOT EXISTS certificates (
 
 -- certificate_fields table
 CREATE TABLE IF NOT EXISTS certificate_fields (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     userId INTEGER NOT NULL REFERENCES users(userId),
     certificateId INTEGER NOT NULL REFERENCES certificates(certificateId),
     fieldName TEXT NOT NULL,
@@ -86,8 +86,8 @@ CREATE TABLE IF NOT EXISTS certificate_fields (
 
 -- output_baskets table
 CREATE TABLE IF NOT EXISTS output_baskets (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     basketId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     name TEXT NOT NULL,
@@ -99,8 +99,8 @@ CREATE TABLE IF NOT EXISTS output_baskets (
 
 -- transactions table
 CREATE TABLE IF NOT EXISTS transactions (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     transactionId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     provenTxId INTEGER REFERENCES proven_txs(provenTxId),
@@ -120,8 +120,8 @@ CREATE INDEX IF NOT EXISTS idx_transactions_status ON transactions(status);
 
 -- commissions table
 CREATE TABLE IF NOT EXISTS commissions (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     commissionId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     transactionId INTEGER NOT NULL UNIQUE REFERENCES transactions(transactionId),
@@ -135,8 +135,8 @@ CREATE INDEX IF NOT EXISTS idx_commissions_transactionId ON commissions(transact
 
 -- outputs table
 CREATE TABLE IF NOT EXISTS outputs (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     outputId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     transactionId INTEGER NOT NULL REFERENCES transactions(transactionId),
@@ -165,8 +165,8 @@ CREATE TABLE IF NOT EXISTS outputs (
 
 -- output_tags table
 CREATE TABLE IF NOT EXISTS output_tags (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     outputTagId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     tag TEXT NOT NULL,
@@ -176,8 +176,8 @@ CREATE TABLE IF NOT EXISTS output_tags (
 
 -- output_tags_map table
 CREATE TABLE IF NOT EXISTS output_tags_map (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     outputTagId INTEGER NOT NULL REFERENCES output_tags(outputTagId),
     outputId INTEGER NOT NULL REFERENCES outputs(outputId),
     isDeleted INTEGER NOT NULL DEFAULT 0,
@@ -188,8 +188,8 @@ CREATE INDEX IF NOT EXISTS idx_output_tags_map_outputId ON output_tags_map(outpu
 
 -- tx_labels table
 CREATE TABLE IF NOT EXISTS tx_labels (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     txLabelId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     label TEXT NOT NULL,
@@ -199,8 +199,8 @@ CREATE TABLE IF NOT EXISTS tx_labels (
 
 -- tx_labels_map table
 CREATE TABLE IF NOT EXISTS tx_labels_map (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     txLabelId INTEGER NOT NULL REFERENCES tx_labels(txLabelId),
     transactionId INTEGER NOT NULL REFERENCES transactions(transactionId),
     isDeleted INTEGER NOT NULL DEFAULT 0,
@@ -211,8 +211,8 @@ CREATE INDEX IF NOT EXISTS idx_tx_labels_map_transactionId ON tx_labels_map(tran
 
 -- monitor_events table
 CREATE TABLE IF NOT EXISTS monitor_events (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     event TEXT NOT NULL,
     details TEXT
@@ -222,8 +222,8 @@ CREATE INDEX IF NOT EXISTS idx_monitor_events_event ON monitor_events(event);
 
 -- settings table
 CREATE TABLE IF NOT EXISTS settings (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     storageIdentityKey TEXT NOT NULL,
     storageName TEXT NOT NULL,
     chain TEXT NOT NULL,
@@ -233,8 +233,8 @@ CREATE TABLE IF NOT EXISTS settings (
 
 -- sync_states table
 CREATE TABLE IF NOT EXISTS sync_states (
-    created_at TEXT NOT NULL DEFAULT(datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT(datetime('now')),
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
     syncStateId INTEGER PRIMARY KEY AUTOINCREMENT,
     userId INTEGER NOT NULL REFERENCES users(userId),
     storageIdentityKey TEXT NOT NULL DEFAULT '',
@@ -253,7 +253,265 @@ CREATE INDEX IF NOT EXISTS idx_sync_states_status ON sync_states(status);
 CREATE INDEX IF NOT EXISTS idx_sync_states_refNum ON sync_states(refNum);
 "#;
 
-/// Apply initial migration and insert settings
+/// SQL for the output query indexes
+///
+/// - `(userId, basketId, spendable)` serves listOutputs basket queries; index
+///   entries are in outputId order within a basket, so pages need no sort
+/// - `(userId, basketId, spendable, satoshis)` serves change allocation,
+///   seeking the smallest sufficient output instead of sorting the basket
+/// - `(userId, txid, vout)` serves outpoint lookups for createAction inputs
+/// - `spentBy` serves finding the inputs of a transaction
+pub const OUTPUT_INDEXES_MIGRATION: &str = r#"
+CREATE INDEX IF NOT EXISTS idx_outputs_userId_basketId_spendable ON outputs(userId, basketId, spendable);
+CREATE INDEX IF NOT EXISTS idx_outputs_userId_basketId_spendable_satoshis ON outputs(userId, basketId, spendable, satoshis);
+CREATE INDEX IF NOT EXISTS idx_outputs_userId_txid_vout ON outputs(userId, txid, vout);
+CREATE INDEX IF NOT EXISTS idx_outputs_spentBy ON outputs(spentBy);
+"#;
+
+/// SQL reverting `INITIAL_MIGRATION`, dropping tables before those they reference
+pub const INITIAL_MIGRATION_DOWN: &str = r#"
+DROP TABLE IF EXISTS sync_states;
+DROP TABLE IF EXISTS settings;
+DROP TABLE IF EXISTS monitor_events;
+DROP TABLE IF EXISTS tx_labels_map;
+DROP TABLE IF EXISTS tx_labels;
+DROP TABLE IF EXISTS output_tags_map;
+DROP TABLE IF EXISTS output_tags;
+DROP TABLE IF EXISTS outputs;
+DROP TABLE IF EXISTS commissions;
+DROP TABLE IF EXISTS transactions;
+DROP TABLE IF EXISTS output_baskets;
+DROP TABLE IF EXISTS certificate_fields;
+DROP TABLE IF EXISTS certificates;
+DROP TABLE IF EXISTS users;
+DROP TABLE IF EXISTS proven_tx_reqs;
+DROP TABLE IF EXISTS proven_txs;
+"#;
+
+/// SQL reverting `OUTPUT_INDEXES_MIGRATION`
+pub const OUTPUT_INDEXES_MIGRATION_DOWN: &str = r#"
+DROP INDEX IF EXISTS idx_outputs_spentBy;
+DROP INDEX IF EXISTS idx_outputs_userId_txid_vout;
+DROP INDEX IF EXISTS idx_outputs_userId_basketId_spendable_satoshis;
+DROP INDEX IF EXISTS idx_outputs_userId_basketId_spendable;
+"#;
+
+/// SQL for the monitor event time index, serving `findMonitorEvents` time ranges
+pub const MONITOR_EVENT_INDEXES_MIGRATION: &str = r#"
+CREATE INDEX IF NOT EXISTS idx_monitor_events_created_at ON monitor_events(created_at);
+"#;
+
+/// SQL reverting `MONITOR_EVENT_INDEXES_MIGRATION`
+pub const MONITOR_EVENT_INDEXES_MIGRATION_DOWN: &str = r#"
+DROP INDEX IF EXISTS idx_monitor_events_created_at;
+"#;
+
+/// SQL for the identity cache, holding resolved `discoverBy*` lookups per user
+pub const IDENTITY_CACHE_MIGRATION: &str = r#"
+CREATE TABLE IF NOT EXISTS identity_caches (
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    identityCacheId INTEGER PRIMARY KEY AUTOINCREMENT,
+    userId INTEGER NOT NULL REFERENCES users(userId),
+    query TEXT NOT NULL,
+    identityKey TEXT,
+    name TEXT,
+    avatarURL TEXT,
+    certificates TEXT NOT NULL,
+    trustScore INTEGER NOT NULL DEFAULT 0,
+    expiresAt TEXT NOT NULL,
+    UNIQUE(userId, query)
+);
+"#;
+
+/// SQL reverting `IDENTITY_CACHE_MIGRATION`
+pub const IDENTITY_CACHE_MIGRATION_DOWN: &str = r#"
+DROP TABLE IF EXISTS identity_caches;
+"#;
+
+/// SQL for per-user usage counters, kept current by triggers
+///
+/// Counts each user's transactions, outputs, input BEEF bytes and
+/// certificate field bytes (values and master keys) for quota checks, and
+/// backfills the counters from existing rows.
+pub const USER_USAGE_MIGRATION: &str = r#"
+CREATE TABLE IF NOT EXISTS user_usages (
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    userId INTEGER PRIMARY KEY REFERENCES users(userId),
+    transactions INTEGER NOT NULL DEFAULT 0,
+    outputs INTEGER NOT NULL DEFAULT 0,
+    certificateBytes INTEGER NOT NULL DEFAULT 0,
+    beefBytes INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO user_usages (userId, transactions, outputs, certificateBytes, beefBytes)
+SELECT u.userId,
+    (SELECT COUNT(*) FROM transactions t WHERE t.userId = u.userId),
+    (SELECT COUNT(*) FROM outputs o WHERE o.userId = u.userId),
+    (SELECT COALESCE(SUM(LENGTH(f.fieldValue) + LENGTH(f.masterKey)), 0) FROM certificate_fields f WHERE f.userId = u.userId),
+    (SELECT COALESCE(SUM(LENGTH(t.inputBEEF)), 0) FROM transactions t WHERE t.userId = u.userId)
+FROM users u;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_user_insert AFTER INSERT ON users
+BEGIN
+    INSERT OR IGNORE INTO user_usages (userId) VALUES (NEW.userId);
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_transaction_insert AFTER INSERT ON transactions
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), transactions = transactions + 1, beefBytes = beefBytes + COALESCE(LENGTH(NEW.inputBEEF), 0)
+    WHERE userId = NEW.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_transaction_update AFTER UPDATE OF inputBEEF ON transactions
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), beefBytes = beefBytes - COALESCE(LENGTH(OLD.inputBEEF), 0) + COALESCE(LENGTH(NEW.inputBEEF), 0)
+    WHERE userId = NEW.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_transaction_delete AFTER DELETE ON transactions
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), transactions = transactions - 1, beefBytes = beefBytes - COALESCE(LENGTH(OLD.inputBEEF), 0)
+    WHERE userId = OLD.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_output_insert AFTER INSERT ON outputs
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), outputs = outputs + 1 WHERE userId = NEW.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_output_delete AFTER DELETE ON outputs
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), outputs = outputs - 1 WHERE userId = OLD.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_certificate_field_insert AFTER INSERT ON certificate_fields
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), certificateBytes = certificateBytes + LENGTH(NEW.fieldValue) + LENGTH(NEW.masterKey)
+    WHERE userId = NEW.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_certificate_field_update AFTER UPDATE OF fieldValue, masterKey ON certificate_fields
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), certificateBytes = certificateBytes
+        - LENGTH(OLD.fieldValue) - LENGTH(OLD.masterKey) + LENGTH(NEW.fieldValue) + LENGTH(NEW.masterKey)
+    WHERE userId = NEW.userId;
+END;
+
+CREATE TRIGGER IF NOT EXISTS user_usages_certificate_field_delete AFTER DELETE ON certificate_fields
+BEGIN
+    UPDATE user_usages SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), certificateBytes = certificateBytes - LENGTH(OLD.fieldValue) - LENGTH(OLD.masterKey)
+    WHERE userId = OLD.userId;
+END;
+"#;
+
+/// SQL reverting `USER_USAGE_MIGRATION`
+pub const USER_USAGE_MIGRATION_DOWN: &str = r#"
+DROP TRIGGER IF EXISTS user_usages_user_insert;
+DROP TRIGGER IF EXISTS user_usages_transaction_insert;
+DROP TRIGGER IF EXISTS user_usages_transaction_update;
+DROP TRIGGER IF EXISTS user_usages_transaction_delete;
+DROP TRIGGER IF EXISTS user_usages_output_insert;
+DROP TRIGGER IF EXISTS user_usages_output_delete;
+DROP TRIGGER IF EXISTS user_usages_certificate_field_insert;
+DROP TRIGGER IF EXISTS user_usages_certificate_field_update;
+DROP TRIGGER IF EXISTS user_usages_certificate_field_delete;
+DROP TABLE IF EXISTS user_usages;
+"#;
+
+/// SQL for output locks, reserving outputs an application manages
+///
+/// A locked output is skipped by change allocation and refused as a
+/// createAction input until it is unlocked or `expiresAt` passes.
+pub const OUTPUT_LOCK_MIGRATION: &str = r#"
+CREATE TABLE IF NOT EXISTS output_locks (
+    created_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    updated_at TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now')),
+    outputId INTEGER PRIMARY KEY REFERENCES outputs(outputId) ON DELETE CASCADE,
+    userId INTEGER NOT NULL REFERENCES users(userId),
+    reason TEXT NOT NULL,
+    expiresAt TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_output_locks_userId ON output_locks(userId);
+"#;
+
+/// SQL reverting `OUTPUT_LOCK_MIGRATION`
+pub const OUTPUT_LOCK_MIGRATION_DOWN: &str = r#"
+DROP INDEX IF EXISTS idx_output_locks_userId;
+DROP TABLE IF EXISTS output_locks;
+"#;
+
+/// SQL adding the configured fee model to settings
+///
+/// `feeModel` holds the JSON of a `StorageFeeModel`; NULL means the default.
+pub const SETTINGS_FEE_MODEL_MIGRATION: &str = r#"
+ALTER TABLE settings ADD COLUMN feeModel TEXT;
+"#;
+
+/// SQL reverting `SETTINGS_FEE_MODEL_MIGRATION`
+pub const SETTINGS_FEE_MODEL_MIGRATION_DOWN: &str = r#"
+ALTER TABLE settings DROP COLUMN feeModel;
+"#;
+
+/// A versioned schema migration
+///
+/// Matches a TypeScript `KnexMigrations` entry: `up` moves the schema
+/// forward, `down` reverts it for development.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// Unique name, ordered by its date prefix
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+/// All migrations, in the order they are applied
+///
+/// Applied migrations are recorded by name in `schema_migrations`. Append
+/// new migrations; never edit or reorder applied ones.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "2024-12-26-001 initial migration",
+        up: INITIAL_MIGRATION,
+        down: INITIAL_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-001 output query indexes",
+        up: OUTPUT_INDEXES_MIGRATION,
+        down: OUTPUT_INDEXES_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-002 monitor event time index",
+        up: MONITOR_EVENT_INDEXES_MIGRATION,
+        down: MONITOR_EVENT_INDEXES_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-003 identity cache",
+        up: IDENTITY_CACHE_MIGRATION,
+        down: IDENTITY_CACHE_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-004 user usage counters",
+        up: USER_USAGE_MIGRATION,
+        down: USER_USAGE_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-005 output locks",
+        up: OUTPUT_LOCK_MIGRATION,
+        down: OUTPUT_LOCK_MIGRATION_DOWN,
+    },
+    Migration {
+        name: "2026-10-16-006 settings fee model",
+        up: SETTINGS_FEE_MODEL_MIGRATION,
+        down: SETTINGS_FEE_MODEL_MIGRATION_DOWN,
+    },
+];
+
+/// Apply the initial migration and insert settings
+///
+/// Later migrations are left to `apply_pending_migrations`.
 pub fn apply_initial_migration(
     conn: &Connection,
     storage_identity_key: &str,
@@ -261,8 +519,10 @@ pub fn apply_initial_migration(
     chain: &str,
     max_output_script: i64,
 ) -> Result<(), StorageError> {
-    conn.execute_batch(INITIAL_MIGRATION)
-        .map_err(|e| StorageError::Database(format!("Migration failed: {}", e)))?;
+    let applied = applied_migrations(conn)?;
+    if !applied.contains(&MIGRATIONS[0].name.to_string()) {
+        run_migration(conn, &MIGRATIONS[0], true)?;
+    }
 
     // Insert initial settings
     conn.execute(
@@ -275,6 +535,145 @@ pub fn apply_initial_migration(
     Ok(())
 }
 
+/// Apply any of `MIGRATIONS` not yet recorded as applied
+///
+/// Safe to call on every open; returns the number of migrations applied.
+/// Fails if the database records a migration this build does not know, as
+/// it was migrated by a newer version.
+pub fn apply_pending_migrations(conn: &Connection) -> Result<usize, StorageError> {
+    let applied = applied_migrations(conn)?;
+    if let Some(unknown) = applied.iter().find(|name| !MIGRATIONS.iter().any(|m| m.name == name.as_str())) {
+        return Err(StorageError::Database(format!(
+            "Database has migration '{}' unknown to this version",
+            unknown
+        )));
+    }
+
+    let mut count = 0;
+    for migration in MIGRATIONS {
+        if !applied.contains(&migration.name.to_string()) {
+            run_migration(conn, migration, true)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Revert the `count` most recently applied migrations, newest first
+///
+/// For development; reverting the initial migration drops every table and
+/// its data. Returns the names of the reverted migrations.
+pub fn rollback_migrations(conn: &Connection, count: usize) -> Result<Vec<&'static str>, StorageError> {
+    let applied = applied_migrations(conn)?;
+
+    let mut reverted = Vec::new();
+    for migration in MIGRATIONS.iter().rev() {
+        if reverted.len() == count {
+            break;
+        }
+        if applied.contains(&migration.name.to_string()) {
+            run_migration(conn, migration, false)?;
+            reverted.push(migration.name);
+        }
+    }
+
+    Ok(reverted)
+}
+
+/// Name of the latest applied migration, or None for an empty database
+///
+/// Matches TypeScript `knex.migrate.currentVersion`
+pub fn current_version(conn: &Connection) -> Result<Option<String>, StorageError> {
+    let applied = applied_migrations(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .rev()
+        .find(|m| applied.contains(&m.name.to_string()))
+        .map(|m| m.name.to_string()))
+}
+
+/// Names of `MIGRATIONS` not yet applied, oldest first
+///
+/// Only reads the database, so works on read-only connections. Fails as
+/// `apply_pending_migrations` does on a database migrated by a newer version.
+pub fn pending_migrations(conn: &Connection) -> Result<Vec<&'static str>, StorageError> {
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to query migrations: {}", e));
+
+    let recorded: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='schema_migrations'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(db_err)?;
+    let applied: Vec<String> = if recorded {
+        let mut stmt = conn.prepare("SELECT name FROM schema_migrations").map_err(db_err)?;
+        let rows = stmt.query_map([], |row| row.get(0)).map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)?
+    } else {
+        Vec::new()
+    };
+    if let Some(unknown) = applied.iter().find(|name| !MIGRATIONS.iter().any(|m| m.name == name.as_str())) {
+        return Err(StorageError::Database(format!(
+            "Database has migration '{}' unknown to this version",
+            unknown
+        )));
+    }
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| !applied.contains(&m.name.to_string()))
+        .map(|m| m.name)
+        .collect())
+}
+
+/// Names recorded in `schema_migrations`, creating the table if needed
+fn applied_migrations(conn: &Connection) -> Result<Vec<String>, StorageError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            name TEXT PRIMARY KEY,
+            migration_time TEXT NOT NULL DEFAULT(strftime('%Y-%m-%d %H:%M:%f', 'now'))
+        )",
+    )
+    .map_err(|e| StorageError::Database(format!("Migration failed: {}", e)))?;
+
+    let mut stmt = conn.prepare("SELECT name FROM schema_migrations")
+        .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+    let rows = stmt.query_map([], |row| row.get(0))
+        .map_err(|e| StorageError::Database(format!("Failed to query migrations: {}", e)))?;
+
+    let mut names = Vec::new();
+    for row in rows {
+        names.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+    Ok(names)
+}
+
+/// Run `migration` up or down in a transaction with its bookkeeping
+fn run_migration(conn: &Connection, migration: &Migration, up: bool) -> Result<(), StorageError> {
+    let (sql, record) = if up {
+        (migration.up, "INSERT INTO schema_migrations (name) VALUES (?1)")
+    } else {
+        (migration.down, "DELETE FROM schema_migrations WHERE name = ?1")
+    };
+
+    let result = conn.execute_batch("BEGIN")
+        .and_then(|_| conn.execute_batch(sql))
+        .and_then(|_| conn.execute(record, rusqlite::params![migration.name]))
+        .and_then(|_| conn.execute_batch("COMMIT"));
+
+    result.map_err(|e| {
+        let _ = conn.execute_batch("ROLLBACK");
+        StorageError::Database(format!(
+            "Migration '{}' {} failed: {}",
+            migration.name,
+            if up { "up" } else { "down" },
+            e
+        ))
+    })
+}
+
 /// Check if database is initialized
 pub fn is_initialized(conn: &Connection) -> Result<bool, StorageError> {
     let result: Result<i64, _> = conn.query_row(
@@ -345,6 +744,63 @@ mod tests {
         assert!(is_initialized(&conn).unwrap());
     }
 
+    #[test]
+    fn test_pending_migrations_applied_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(pending_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+        apply_initial_migration(&conn, "key", "name", "main", 100).unwrap();
+
+        assert_eq!(pending_migrations(&conn).unwrap()[0], MIGRATIONS[1].name);
+        assert_eq!(apply_pending_migrations(&conn).unwrap(), MIGRATIONS.len() - 1);
+        assert_eq!(apply_pending_migrations(&conn).unwrap(), 0);
+        assert!(pending_migrations(&conn).unwrap().is_empty());
+
+        let indexes: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type='index' AND tbl_name='outputs'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        for index in [
+            "idx_outputs_userId_basketId_spendable",
+            "idx_outputs_userId_basketId_spendable_satoshis",
+            "idx_outputs_userId_txid_vout",
+            "idx_outputs_spentBy",
+        ] {
+            assert!(indexes.contains(&index.to_string()), "Missing index: {}", index);
+        }
+    }
+
+    #[test]
+    fn test_rollback_and_current_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(current_version(&conn).unwrap(), None);
+
+        apply_pending_migrations(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap().as_deref(), Some(MIGRATIONS.last().unwrap().name));
+
+        assert_eq!(rollback_migrations(&conn, 1).unwrap(), vec![MIGRATIONS.last().unwrap().name]);
+        assert_eq!(current_version(&conn).unwrap().as_deref(), Some(MIGRATIONS[MIGRATIONS.len() - 2].name));
+        assert!(is_initialized(&conn).unwrap());
+
+        let remaining: Vec<&str> = MIGRATIONS[..MIGRATIONS.len() - 1].iter().rev().map(|m| m.name).collect();
+        assert_eq!(rollback_migrations(&conn, 10).unwrap(), remaining);
+        assert_eq!(current_version(&conn).unwrap(), None);
+        assert!(!is_initialized(&conn).unwrap());
+
+        assert_eq!(apply_pending_migrations(&conn).unwrap(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_unknown_migration_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_pending_migrations(&conn).unwrap();
+        conn.execute("INSERT INTO schema_migrations (name) VALUES ('2099-01-01-001 future')", []).unwrap();
+
+        assert!(apply_pending_migrations(&conn).is_err());
+    }
+
     #[test]
     fn test_settings_inserted() {
         let conn = Connection::open_in_memory().unwrap();
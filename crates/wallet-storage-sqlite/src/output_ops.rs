@@ -7,6 +7,25 @@ use rusqlite::{Connection, params, OptionalExtension};
 use std::sync::{Arc, Mutex};
 use wallet_storage::*;
 
+use crate::row_version::verify_updated;
+
+/// Output columns in `parse_output_row` order, without lockingScript
+///
+/// Matches TypeScript `outputColumnsWithoutLockingScript`
+const OUTPUT_COLUMNS: &str = "created_at, updated_at, outputId, userId, transactionId, basketId, spendable, `change`,
+    vout, satoshis, providedBy, purpose, type, outputDescription, txid, senderIdentityKey,
+    derivationPrefix, derivationSuffix, customInstructions, spentBy, sequenceNumber,
+    spendingDescription, scriptLength, scriptOffset";
+
+/// Select list for outputs, with lockingScript unless `no_script`
+fn output_columns(no_script: bool) -> String {
+    if no_script {
+        OUTPUT_COLUMNS.to_string()
+    } else {
+        format!("{}, lockingScript", OUTPUT_COLUMNS)
+    }
+}
+
 /// Insert a new output
 /// 
 /// Matches TypeScript `insertOutput(output: TableOutput, trx?: TrxToken): Promise<number>`
@@ -14,8 +33,14 @@ pub fn insert_output(
     conn: &Arc<Mutex<Connection>>,
     output: &TableOutput,
 ) -> Result<i64, StorageError> {
-    let conn = conn.lock().unwrap();
+    insert_output_row(&conn.lock().unwrap(), output)
+}
 
+/// Insert a new output on a connection the caller has locked
+pub(crate) fn insert_output_row(
+    conn: &Connection,
+    output: &TableOutput,
+) -> Result<i64, StorageError> {
     conn.execute(
         "INSERT INTO outputs (
             userId, transactionId, basketId, spendable, `change`, vout, satoshis,
@@ -151,7 +176,7 @@ pub fn update_output(
 
     let rows = conn.execute(
         "UPDATE outputs 
-         SET updated_at = datetime('now'),
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
              basketId = ?1,
              spendable = ?2,
              `change` = ?3,
@@ -192,6 +217,82 @@ pub fn update_output(
     Ok(rows)
 }
 
+/// Update the given fields of an output, failing with NotFound if it is missing
+///
+/// Matches TypeScript `updateOutput(id: number, update: Partial<TableOutput>)`.
+/// With `expected_updated_at`, fails with Conflict and updates nothing unless
+/// the output is still that version.
+pub fn update_output_fields(
+    conn: &Arc<Mutex<Connection>>,
+    output_id: i64,
+    updates: &OutputUpdates,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        "UPDATE outputs
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+             spendable = COALESCE(?1, spendable),
+             spentBy = CASE WHEN ?2 THEN ?3 ELSE spentBy END,
+             spendingDescription = COALESCE(?4, spendingDescription),
+             basketId = COALESCE(?5, basketId),
+             `change` = COALESCE(?6, `change`),
+             providedBy = COALESCE(?7, providedBy),
+             purpose = COALESCE(?8, purpose),
+             type = COALESCE(?9, type),
+             customInstructions = COALESCE(?10, customInstructions),
+             senderIdentityKey = COALESCE(?11, senderIdentityKey),
+             derivationPrefix = COALESCE(?12, derivationPrefix),
+             derivationSuffix = COALESCE(?13, derivationSuffix)
+         WHERE outputId = ?14 AND (?15 IS NULL OR updated_at = ?15)",
+        params![
+            updates.spendable.map(i32::from),
+            // spentBy can be cleared, so whether to set it is a separate flag
+            updates.spent_by.is_some(),
+            updates.spent_by.flatten(),
+            updates.spending_description,
+            updates.basket_id,
+            updates.change.map(i32::from),
+            updates.provided_by.map(|p| p.to_string()),
+            updates.purpose,
+            updates.output_type,
+            updates.custom_instructions,
+            updates.sender_identity_key,
+            updates.derivation_prefix,
+            updates.derivation_suffix,
+            output_id,
+            updates.expected_updated_at,
+        ],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update output: {}", e)))?;
+    if rows == 0 {
+        return verify_updated(&conn, "outputs", "outputId", output_id, updates.expected_updated_at.as_deref());
+    }
+    Ok(())
+}
+
+/// Find the user's outputs created by a transaction, or spent by it when
+/// `is_input`, ordered by outputId
+pub fn find_outputs_by_transaction(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    transaction_id: i64,
+    is_input: bool,
+) -> Result<Vec<TableOutput>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to query outputs: {}", e));
+
+    let column = if is_input { "spentBy" } else { "transactionId" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM outputs WHERE userId = ?1 AND {} = ?2 ORDER BY outputId ASC",
+        output_columns(false),
+        column
+    ))
+    .map_err(db_err)?;
+    let rows = stmt.query_map(params![user_id, transaction_id], |row| parse_output_row(row, false)).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
 /// Find outputs for transaction
 pub fn find_outputs_for_transaction(
     conn: &Arc<Mutex<Connection>>,
@@ -275,16 +376,447 @@ pub fn find_spendable_outputs_for_user(
     Ok(outputs)
 }
 
+/// WHERE clause and parameters for `FindOutputsArgs`
+///
+/// Equality terms come first in the order of the
+/// `idx_outputs_userId_basketId_spendable` and `idx_outputs_userId_txid_vout`
+/// index columns so the planner can seek on them. The txStatus filter is a
+/// correlated primary key lookup on transactions rather than a join, keeping
+/// the outputs index as the driving scan.
+fn outputs_where(args: &FindOutputsArgs) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+    let mut clause = String::from(" WHERE userId = ?");
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(args.user_id)];
+
+    if let Some(partial) = &args.partial {
+        if let Some(basket_id) = partial.basket_id {
+            clause.push_str(" AND basketId = ?");
+            params_vec.push(Box::new(basket_id));
+        }
+        if let Some(spendable) = partial.spendable {
+            clause.push_str(" AND spendable = ?");
+            params_vec.push(Box::new(spendable as i32));
+        }
+        if let Some(txid) = &partial.txid {
+            clause.push_str(" AND txid = ?");
+            params_vec.push(Box::new(txid.clone()));
+        }
+        if let Some(transaction_id) = partial.transaction_id {
+            clause.push_str(" AND transactionId = ?");
+            params_vec.push(Box::new(transaction_id));
+        }
+        if let Some(change) = partial.change {
+            clause.push_str(" AND `change` = ?");
+            params_vec.push(Box::new(change as i32));
+        }
+    }
+
+    if let Some(since) = &args.since {
+        clause.push_str(" AND updated_at >= ?");
+        params_vec.push(Box::new(since.clone()));
+    }
+
+    if let Some(statuses) = args.tx_status.as_ref().filter(|s| !s.is_empty()) {
+        let placeholders = vec!["?"; statuses.len()].join(", ");
+        clause.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM transactions t WHERE t.transactionId = outputs.transactionId AND t.status IN ({}))",
+            placeholders
+        ));
+        for status in statuses {
+            params_vec.push(Box::new(status.to_string()));
+        }
+    }
+
+    (clause, params_vec)
+}
+
+/// Find the user's outputs matching `args`, ordered by outputId
+///
+/// Matches TypeScript `findOutputs(args: FindOutputsArgs)`
+pub fn find_outputs(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindOutputsArgs,
+) -> Result<Vec<TableOutput>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let no_script = args.no_script == Some(true);
+    let (clause, params_vec) = outputs_where(args);
+    let mut query = format!("SELECT {} FROM outputs{}", output_columns(no_script), clause);
+    query.push_str(if args.order_descending == Some(true) {
+        " ORDER BY outputId DESC"
+    } else {
+        " ORDER BY outputId ASC"
+    });
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let rows = stmt.query_map(params_refs.as_slice(), |row| parse_output_row(row, no_script))
+        .map_err(|e| StorageError::Database(format!("Failed to query outputs: {}", e)))?;
+
+    let mut outputs = Vec::new();
+    for row in rows {
+        outputs.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+
+    Ok(outputs)
+}
+
+/// Count the user's outputs matching `args`, ignoring paging
+///
+/// Matches TypeScript `countOutputs(args: FindOutputsArgs)`
+pub fn count_outputs(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindOutputsArgs,
+) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let (clause, params_vec) = outputs_where(args);
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM outputs{}", clause),
+        params_refs.as_slice(),
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to count outputs: {}", e)))
+}
+
+/// Find the user's output at outpoint `txid.vout`
+///
+/// Served by `idx_outputs_userId_txid_vout`.
+pub fn find_output_by_outpoint(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    txid: &str,
+    vout: u32,
+    no_script: bool,
+) -> Result<Option<TableOutput>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM outputs WHERE userId = ?1 AND txid = ?2 AND vout = ?3",
+            output_columns(no_script)
+        ),
+        params![user_id, txid, vout],
+        |row| parse_output_row(row, no_script),
+    )
+    .optional()
+    .map_err(|e| StorageError::Database(format!("Failed to find output: {}", e)))
+}
+
+/// Find the outputs spent by a transaction (its inputs)
+///
+/// Served by `idx_outputs_spentBy`.
+pub fn find_outputs_spent_by(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    no_script: bool,
+) -> Result<Vec<TableOutput>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM outputs WHERE spentBy = ?1 ORDER BY outputId ASC",
+        output_columns(no_script)
+    ))
+    .map_err(|e| StorageError::Database(format!("Failed to prepare query: {}", e)))?;
+
+    let rows = stmt.query_map(params![transaction_id], |row| parse_output_row(row, no_script))
+        .map_err(|e| StorageError::Database(format!("Failed to query outputs: {}", e)))?;
+
+    let mut outputs = Vec::new();
+    for row in rows {
+        outputs.push(row.map_err(|e| StorageError::Database(format!("Row error: {}", e)))?);
+    }
+
+    Ok(outputs)
+}
+
+/// Locks still in force, as a condition on `output_locks l`
+const LOCK_UNEXPIRED: &str = "(l.expiresAt IS NULL OR julianday(l.expiresAt) > julianday('now'))";
+
+/// Outputs change may be allocated from, as a WHERE clause on `outputs`
+/// with the user id bound to ?1 and the basket id to ?2
+fn change_where(exclude_sending: bool) -> String {
+    let statuses: &[&str] = if exclude_sending {
+        &["completed", "unproven"]
+    } else {
+        &["completed", "unproven", "sending"]
+    };
+    let status_list = statuses.iter().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(", ");
+    format!(
+        "userId = ?1 AND basketId = ?2 AND spendable = 1
+           AND EXISTS (SELECT 1 FROM transactions t WHERE t.transactionId = outputs.transactionId AND t.status IN ({}))
+           AND NOT EXISTS (SELECT 1 FROM output_locks l WHERE l.outputId = outputs.outputId AND {})",
+        status_list, LOCK_UNEXPIRED
+    )
+}
+
+/// Count the outputs in `basket_id` that `allocate_change_input` may choose
+///
+/// Matches TypeScript `StorageKnex.countChangeInputs`
+pub fn count_change_inputs(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    basket_id: i64,
+    exclude_sending: bool,
+) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM outputs WHERE {}", change_where(exclude_sending)),
+        params![user_id, basket_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to count change inputs: {}", e)))
+}
+
+/// Allocate a change output in `basket_id` to fund `transaction_id`
+///
+/// Matches TypeScript `StorageKnex.allocateChangeInput`. Prefers an output of
+/// exactly `exact_satoshis`, then the smallest output of at least
+/// `target_satoshis`, then the largest output. Candidates must belong to a
+/// 'completed' or 'unproven' transaction, or 'sending' unless
+/// `exclude_sending`, and must not be locked. The chosen output is marked
+/// spent by `transaction_id`.
+///
+/// Each probe is an `idx_outputs_userId_basketId_spendable_satoshis` seek on
+/// `(userId, basketId, spendable)` ordered by its trailing `satoshis` column.
+pub fn allocate_change_input(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    basket_id: i64,
+    target_satoshis: i64,
+    exact_satoshis: Option<i64>,
+    exclude_sending: bool,
+    transaction_id: i64,
+) -> Result<Option<TableOutput>, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let base = format!("SELECT outputId FROM outputs WHERE {}", change_where(exclude_sending));
+
+    let probe = |sql: String, satoshis: Option<i64>| -> Result<Option<i64>, StorageError> {
+        let result = match satoshis {
+            Some(satoshis) => conn.query_row(&sql, params![user_id, basket_id, satoshis], |row| row.get(0)),
+            None => conn.query_row(&sql, params![user_id, basket_id], |row| row.get(0)),
+        };
+        result
+            .optional()
+            .map_err(|e| StorageError::Database(format!("Failed to allocate change input: {}", e)))
+    };
+
+    let mut output_id = None;
+    if let Some(exact) = exact_satoshis {
+        output_id = probe(format!("{} AND satoshis = ?3 LIMIT 1", base), Some(exact))?;
+    }
+    if output_id.is_none() {
+        output_id = probe(format!("{} AND satoshis >= ?3 ORDER BY satoshis ASC LIMIT 1", base), Some(target_satoshis))?;
+    }
+    if output_id.is_none() {
+        output_id = probe(format!("{} ORDER BY satoshis DESC LIMIT 1", base), None)?;
+    }
+    let Some(output_id) = output_id else {
+        return Ok(None);
+    };
+
+    conn.execute(
+        "UPDATE outputs SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), spendable = 0, spentBy = ?1 WHERE outputId = ?2",
+        params![transaction_id, output_id],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to allocate change input: {}", e)))?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM outputs WHERE outputId = ?1", output_columns(false)),
+        params![output_id],
+        |row| parse_output_row(row, false),
+    )
+    .optional()
+    .map_err(|e| StorageError::Database(format!("Failed to find output: {}", e)))
+}
+
+// ============ OUTPUT LOCKS ============
+
+const OUTPUT_LOCK_SELECT: &str = "SELECT l.created_at, l.updated_at, l.outputId, l.userId, o.txid, o.vout, l.reason, l.expiresAt
+    FROM output_locks l JOIN outputs o ON o.outputId = l.outputId";
+
+/// Locks matching `clause`, a condition on `output_locks l` and `outputs o`
+fn query_output_locks(
+    conn: &Connection,
+    clause: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<TableOutputLock>, StorageError> {
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find output locks: {}", e));
+    let mut stmt = conn
+        .prepare(&format!("{} WHERE {} ORDER BY l.outputId", OUTPUT_LOCK_SELECT, clause))
+        .map_err(db_err)?;
+    let rows = stmt.query_map(params, |row| {
+        Ok(TableOutputLock {
+            created_at: row.get(0)?,
+            updated_at: row.get(1)?,
+            output_id: row.get(2)?,
+            user_id: row.get(3)?,
+            txid: row.get(4)?,
+            vout: row.get(5)?,
+            reason: row.get(6)?,
+            expires_at: row.get(7)?,
+        })
+    }).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Lock the user's spendable output `txid.vout`, replacing any lock on it
+pub fn lock_output(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    txid: &str,
+    vout: u32,
+    reason: &str,
+    ttl: Option<std::time::Duration>,
+) -> Result<TableOutputLock, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to lock output: {}", e));
+
+    let (output_id, spendable): (i64, bool) = conn
+        .query_row(
+            "SELECT outputId, spendable FROM outputs WHERE userId = ?1 AND txid = ?2 AND vout = ?3",
+            params![user_id, txid, vout],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(db_err)?
+        .ok_or_else(|| StorageError::NotFound(format!("output {}.{}", txid, vout)))?;
+    if !spendable {
+        return Err(StorageError::InvalidArg(format!("output {}.{} is not spendable", txid, vout)));
+    }
+
+    let lock = TableOutputLock::new(output_id, user_id, txid, vout, reason, ttl);
+    conn.execute(
+        "INSERT INTO output_locks (outputId, userId, reason, expiresAt) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(outputId) DO UPDATE SET
+            updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+            reason = excluded.reason,
+            expiresAt = excluded.expiresAt",
+        params![output_id, user_id, lock.reason, lock.expires_at],
+    )
+    .map_err(db_err)?;
+
+    query_output_locks(&conn, "l.outputId = ?1", params![output_id])?
+        .pop()
+        .ok_or_else(|| StorageError::Database(format!("output lock {} not stored", output_id)))
+}
+
+/// Remove the lock on the user's output `txid.vout`, returning whether there was one
+pub fn unlock_output(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    txid: &str,
+    vout: u32,
+) -> Result<bool, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let deleted = conn.execute(
+        "DELETE FROM output_locks WHERE userId = ?1
+           AND outputId IN (SELECT outputId FROM outputs WHERE userId = ?1 AND txid = ?2 AND vout = ?3)",
+        params![user_id, txid, vout],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to unlock output: {}", e)))?;
+    Ok(deleted > 0)
+}
+
+/// The unexpired lock on output `output_id`
+pub fn find_output_lock(
+    conn: &Arc<Mutex<Connection>>,
+    output_id: i64,
+) -> Result<Option<TableOutputLock>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let clause = format!("l.outputId = ?1 AND {}", LOCK_UNEXPIRED);
+    Ok(query_output_locks(&conn, &clause, params![output_id])?.pop())
+}
+
+/// The user's unexpired output locks, ordered by output id
+pub fn find_output_locks(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+) -> Result<Vec<TableOutputLock>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let clause = format!("l.userId = ?1 AND {}", LOCK_UNEXPIRED);
+    query_output_locks(&conn, &clause, params![user_id])
+}
+
+/// Spendable outputs counted by balances, as a WHERE clause on `outputs`
+/// with the user id bound to ?1
+fn balance_where() -> String {
+    let status_list = BasketBalance::TX_STATUSES
+        .iter()
+        .map(|s| format!("'{}'", s))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "outputs.userId = ?1 AND outputs.basketId IS NOT NULL AND outputs.spendable = 1
+         AND EXISTS (SELECT 1 FROM transactions t WHERE t.transactionId = outputs.transactionId AND t.status IN ({}))",
+        status_list
+    )
+}
+
+/// Sum of the user's spendable basket outputs
+///
+/// Matches TypeScript `Wallet.balance` totals without listing outputs.
+pub fn get_balance(conn: &Arc<Mutex<Connection>>, user_id: i64) -> Result<i64, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    conn.query_row(
+        &format!("SELECT COALESCE(SUM(satoshis), 0) FROM outputs WHERE {}", balance_where()),
+        params![user_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to get balance: {}", e)))
+}
+
+/// Spendable satoshis and output count of each of the user's baskets
+///
+/// Baskets without spendable outputs are omitted. Ordered by basket id.
+pub fn get_basket_balances(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+) -> Result<Vec<BasketBalance>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to get basket balances: {}", e));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT b.basketId, b.name, SUM(outputs.satoshis), COUNT(*)
+         FROM outputs JOIN output_baskets b ON b.basketId = outputs.basketId
+         WHERE {}
+         GROUP BY b.basketId ORDER BY b.basketId",
+        balance_where()
+    )).map_err(db_err)?;
+    let rows = stmt.query_map(params![user_id], |row| {
+        Ok(BasketBalance {
+            basket_id: row.get(0)?,
+            name: row.get(1)?,
+            satoshis: row.get(2)?,
+            output_count: row.get(3)?,
+        })
+    }).map_err(db_err)?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rusqlite::Connection;
-    use crate::migrations::apply_initial_migration;
+    use crate::migrations::{apply_initial_migration, apply_pending_migrations};
 
     fn create_test_storage() -> Arc<Mutex<Connection>> {
         let conn = Connection::open_in_memory().unwrap();
         conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
         apply_initial_migration(&conn, "test_key", "Test", "main", 100000).unwrap();
+        apply_pending_migrations(&conn).unwrap();
         
         // Insert test user
         conn.execute(
@@ -389,6 +921,36 @@ mod tests {
         assert_eq!(found.txid, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn test_update_output_fields_if_unchanged() {
+        let conn = create_test_storage();
+        let output = TableOutput::new(0, 1, 1, true, false, "Original", 0, 1000, StorageProvidedBy::You, "payment", "P2PKH");
+        let output_id = insert_output(&conn, &output).unwrap();
+        let read = find_output_by_id(&conn, output_id, true).unwrap().unwrap();
+
+        let mut updates = OutputUpdates {
+            spendable: Some(false),
+            provided_by: Some(StorageProvidedBy::Storage),
+            expected_updated_at: Some(read.updated_at.clone()),
+            ..Default::default()
+        };
+        update_output_fields(&conn, output_id, &updates).unwrap();
+        let found = find_output_by_id(&conn, output_id, true).unwrap().unwrap();
+        assert_eq!((found.spendable, found.provided_by), (false, StorageProvidedBy::Storage));
+        assert_eq!(found.output_description, "Original");
+
+        // A stale version updates nothing
+        updates.spendable = Some(true);
+        updates.expected_updated_at = Some("2000-01-01 00:00:00.000".to_string());
+        assert!(matches!(update_output_fields(&conn, output_id, &updates), Err(StorageError::Conflict(_))));
+        assert!(!find_output_by_id(&conn, output_id, true).unwrap().unwrap().spendable);
+        assert!(matches!(update_output_fields(&conn, 99, &updates), Err(StorageError::NotFound(_))));
+
+        updates.expected_updated_at = None;
+        update_output_fields(&conn, output_id, &updates).unwrap();
+        assert!(find_output_by_id(&conn, output_id, true).unwrap().unwrap().spendable);
+    }
+
     #[test]
     fn test_find_outputs_for_transaction() {
         let conn = create_test_storage();
@@ -470,6 +1032,211 @@ mod tests {
         assert!(spendable[0].spent_by.is_none());
     }
 
+    fn insert_change(conn: &Arc<Mutex<Connection>>, vout: u32, satoshis: i64) -> i64 {
+        let mut output = TableOutput::new(
+            0, 1, 1,
+            true, true,
+            "change",
+            vout, satoshis,
+            StorageProvidedBy::Storage,
+            "change",
+            "P2PKH",
+        );
+        output.basket_id = Some(1);
+        output.txid = Some("aa".repeat(32));
+        insert_output(conn, &output).unwrap()
+    }
+
+    fn insert_default_basket(conn: &Arc<Mutex<Connection>>) {
+        conn.lock().unwrap().execute(
+            "INSERT INTO output_baskets (userId, name) VALUES (1, 'default')",
+            params![],
+        ).unwrap();
+    }
+
+    #[test]
+    fn test_find_and_count_outputs() {
+        let conn = create_test_storage();
+        insert_default_basket(&conn);
+        for (vout, satoshis) in [(0, 1000), (1, 2000), (2, 3000)] {
+            insert_change(&conn, vout, satoshis);
+        }
+        insert_output(&conn, &TableOutput::new(
+            0, 1, 1, false, false, "Not in basket", 3, 500,
+            StorageProvidedBy::You, "payment", "P2PKH",
+        )).unwrap();
+
+        let mut args = FindOutputsArgs {
+            user_id: 1,
+            since: None,
+            paged: Some(Paged { limit: 2, offset: Some(1) }),
+            order_descending: None,
+            partial: Some(PartialOutput {
+                basket_id: Some(1),
+                spendable: Some(true),
+                change: None,
+                transaction_id: None,
+                txid: None,
+            }),
+            no_script: Some(true),
+            tx_status: Some(vec![TransactionStatus::Completed]),
+        };
+        let outputs = find_outputs(&conn, &args).unwrap();
+        assert_eq!(outputs.iter().map(|o| o.vout).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(count_outputs(&conn, &args).unwrap(), 3);
+
+        args.tx_status = Some(vec![TransactionStatus::Failed]);
+        assert_eq!(count_outputs(&conn, &args).unwrap(), 0);
+
+        let found = find_output_by_outpoint(&conn, 1, &"aa".repeat(32), 2, true).unwrap().unwrap();
+        assert_eq!(found.satoshis, 3000);
+    }
+
+    #[test]
+    fn test_allocate_change_input() {
+        let conn = create_test_storage();
+        insert_default_basket(&conn);
+        for (vout, satoshis) in [(0, 1000), (1, 5000), (2, 3000)] {
+            insert_change(&conn, vout, satoshis);
+        }
+        conn.lock().unwrap().execute(
+            "INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+             VALUES (1, 'unsigned', 'ref_tx2', 1, 0, 'Funded tx')",
+            params![],
+        ).unwrap();
+
+        // Exact match first, then smallest sufficient, then largest
+        let exact = allocate_change_input(&conn, 1, 1, 2000, Some(1000), false, 2).unwrap().unwrap();
+        assert_eq!(exact.satoshis, 1000);
+        let sufficient = allocate_change_input(&conn, 1, 1, 2000, None, false, 2).unwrap().unwrap();
+        assert_eq!(sufficient.satoshis, 3000);
+        let largest = allocate_change_input(&conn, 1, 1, 9000, None, false, 2).unwrap().unwrap();
+        assert_eq!(largest.satoshis, 5000);
+        assert!(!largest.spendable);
+        assert_eq!(largest.spent_by, Some(2));
+        assert!(allocate_change_input(&conn, 1, 1, 1, None, false, 2).unwrap().is_none());
+
+        assert_eq!(find_outputs_spent_by(&conn, 2, true).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_output_locks() {
+        let conn = create_test_storage();
+        insert_default_basket(&conn);
+        let small = insert_change(&conn, 0, 1000);
+        let large = insert_change(&conn, 1, 5000);
+        let txid = "aa".repeat(32);
+
+        let lock = lock_output(&conn, 1, &txid, 1, "token", None).unwrap();
+        assert_eq!((lock.output_id, lock.vout, lock.expires_at.as_deref()), (large, 1, None));
+        assert_eq!(find_output_lock(&conn, large).unwrap(), Some(lock.clone()));
+        assert!(matches!(lock_output(&conn, 1, &txid, 7, "token", None), Err(StorageError::NotFound(_))));
+
+        // Relocking replaces the reason and expiry
+        let relocked = lock_output(&conn, 1, &txid, 1, "auction", Some(std::time::Duration::from_secs(60))).unwrap();
+        assert_eq!(relocked.reason, "auction");
+        assert!(relocked.expires_at.is_some());
+        assert_eq!(find_output_locks(&conn, 1).unwrap(), vec![relocked]);
+
+        // Change allocation passes over the locked output
+        let allocated = allocate_change_input(&conn, 1, 1, 9000, None, false, 1).unwrap().unwrap();
+        assert_eq!(allocated.output_id, small);
+        assert!(allocate_change_input(&conn, 1, 1, 1, None, false, 1).unwrap().is_none());
+        assert!(matches!(lock_output(&conn, 1, &txid, 0, "token", None), Err(StorageError::InvalidArg(_))));
+
+        // Lapsed locks no longer hold
+        conn.lock().unwrap().execute(
+            "UPDATE output_locks SET expiresAt = '2000-01-01T00:00:00Z'",
+            params![],
+        ).unwrap();
+        assert_eq!(find_output_lock(&conn, large).unwrap(), None);
+        assert!(find_output_locks(&conn, 1).unwrap().is_empty());
+        lock_output(&conn, 1, &txid, 1, "token", None).unwrap();
+
+        assert!(unlock_output(&conn, 1, &txid, 1).unwrap());
+        assert!(!unlock_output(&conn, 1, &txid, 1).unwrap());
+        let allocated = allocate_change_input(&conn, 1, 1, 1, None, false, 1).unwrap().unwrap();
+        assert_eq!(allocated.output_id, large);
+    }
+
+    #[test]
+    fn test_balances() {
+        let conn = create_test_storage();
+        assert_eq!(get_balance(&conn, 1).unwrap(), 0);
+        assert!(get_basket_balances(&conn, 1).unwrap().is_empty());
+
+        insert_default_basket(&conn);
+        for (vout, satoshis) in [(0, 1000), (1, 2000)] {
+            insert_change(&conn, vout, satoshis);
+        }
+        let spent = insert_change(&conn, 2, 4000);
+        let failed_tx = {
+            let conn = conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO output_baskets (userId, name) VALUES (1, 'tokens');
+                 INSERT INTO transactions (userId, status, reference, isOutgoing, satoshis, description)
+                    VALUES (1, 'failed', 'ref_failed', 1, 0, 'Failed tx');",
+            ).unwrap();
+            conn.last_insert_rowid()
+        };
+        conn.lock().unwrap().execute(
+            "UPDATE outputs SET spendable = 0 WHERE outputId = ?1",
+            params![spent],
+        ).unwrap();
+
+        let mut token = TableOutput::new(0, 1, 1, true, false, "token", 3, 1, StorageProvidedBy::You, "", "custom");
+        token.basket_id = Some(2);
+        insert_output(&conn, &token).unwrap();
+        // Outputs outside baskets or of failed transactions do not count
+        insert_output(&conn, &TableOutput::new(0, 1, 1, true, false, "payment", 4, 500, StorageProvidedBy::You, "", "P2PKH")).unwrap();
+        let mut failed = TableOutput::new(0, 1, failed_tx, true, true, "change", 0, 8000, StorageProvidedBy::Storage, "change", "P2PKH");
+        failed.basket_id = Some(1);
+        insert_output(&conn, &failed).unwrap();
+
+        assert_eq!(get_balance(&conn, 1).unwrap(), 3001);
+        let balances = get_basket_balances(&conn, 1).unwrap();
+        assert_eq!(balances, vec![
+            BasketBalance { basket_id: 1, name: "default".to_string(), satoshis: 3000, output_count: 2 },
+            BasketBalance { basket_id: 2, name: "tokens".to_string(), satoshis: 1, output_count: 1 },
+        ]);
+        assert_eq!(get_balance(&conn, 2).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_output_queries_use_indexes() {
+        let conn = create_test_storage();
+        let conn = conn.lock().unwrap();
+        let plan = |sql: &str| -> String {
+            conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+                .unwrap()
+                .query_map([], |row| row.get::<_, String>(3))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+                .join("\n")
+        };
+
+        let allocation = plan(
+            "SELECT outputId FROM outputs WHERE userId = 1 AND basketId = 1 AND spendable = 1
+             AND satoshis >= 1000 ORDER BY satoshis ASC LIMIT 1",
+        );
+        assert!(allocation.contains("idx_outputs_userId_basketId_spendable_satoshis"), "{}", allocation);
+        assert!(!allocation.contains("TEMP B-TREE"), "{}", allocation);
+
+        let list = plan(
+            "SELECT outputId FROM outputs WHERE userId = 1 AND basketId = 1 AND spendable = 1
+             ORDER BY outputId ASC LIMIT 10",
+        );
+        assert!(list.contains("idx_outputs_userId_basketId_spendable "), "{}", list);
+        assert!(!list.contains("TEMP B-TREE"), "{}", list);
+
+        let outpoint = plan("SELECT outputId FROM outputs WHERE userId = 1 AND txid = 'aa' AND vout = 0");
+        assert!(outpoint.contains("idx_outputs_userId_txid_vout"), "{}", outpoint);
+
+        let spent_by = plan("SELECT outputId FROM outputs WHERE spentBy = 1");
+        assert!(spent_by.contains("idx_outputs_spentBy"), "{}", spent_by);
+    }
+
     #[test]
     fn test_output_optional_fields() {
         let conn = create_test_storage();
@@ -6,6 +6,54 @@ use rusqlite::{Connection, params, OptionalExtension};
 use std::sync::{Arc, Mutex};
 use wallet_storage::*;
 
+use crate::row_version::verify_updated;
+
+/// ProvenTx columns in `parse_proven_tx_row` order
+const PROVEN_TX_COLUMNS: &str = "created_at, updated_at, provenTxId, txid, height, `index`, merklePath, rawTx,
+    blockHash, merkleRoot";
+
+/// ProvenTxReq columns in `parse_proven_tx_req_row` order
+const PROVEN_TX_REQ_COLUMNS: &str = "created_at, updated_at, provenTxReqId, provenTxId, status, attempts, notified,
+    txid, batch, history, notify, rawTx, inputBEEF";
+
+/// Statuses of a ProvenTxReq whose raw transaction is known to be valid
+///
+/// Reference: StorageKnex.ts getProvenOrRawTx
+const KNOWN_VALID_REQ_STATUSES: &str = "'unsent', 'unmined', 'unconfirmed', 'sending', 'nosend', 'completed'";
+
+fn parse_proven_tx_row(row: &rusqlite::Row) -> rusqlite::Result<TableProvenTx> {
+    Ok(TableProvenTx {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        proven_tx_id: row.get(2)?,
+        txid: row.get(3)?,
+        height: row.get(4)?,
+        index: row.get(5)?,
+        merkle_path: row.get(6)?,
+        raw_tx: row.get(7)?,
+        block_hash: row.get(8)?,
+        merkle_root: row.get(9)?,
+    })
+}
+
+fn parse_proven_tx_req_row(row: &rusqlite::Row) -> rusqlite::Result<TableProvenTxReq> {
+    Ok(TableProvenTxReq {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        proven_tx_req_id: row.get(2)?,
+        proven_tx_id: row.get(3)?,
+        status: row.get::<_, String>(4)?.parse().unwrap_or(ProvenTxReqStatus::Unknown),
+        attempts: row.get(5)?,
+        notified: row.get::<_, i32>(6)? != 0,
+        txid: row.get(7)?,
+        batch: row.get(8)?,
+        history: row.get(9)?,
+        notify: row.get(10)?,
+        raw_tx: row.get(11)?,
+        input_beef: row.get(12)?,
+    })
+}
+
 /// Insert proven transaction
 pub fn insert_proven_tx(
     conn: &Arc<Mutex<Connection>>,
@@ -39,23 +87,9 @@ pub fn find_proven_tx_by_txid(
     let conn = conn.lock().unwrap();
 
     let result = conn.query_row(
-        "SELECT created_at, updated_at, provenTxId, txid, height, `index`, merklePath, rawTx, blockHash, merkleRoot
-         FROM proven_txs WHERE txid = ?1",
+        &format!("SELECT {} FROM proven_txs WHERE txid = ?1", PROVEN_TX_COLUMNS),
         params![txid],
-        |row| {
-            Ok(TableProvenTx {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                proven_tx_id: row.get(2)?,
-                txid: row.get(3)?,
-                height: row.get(4)?,
-                index: row.get(5)?,
-                merkle_path: row.get(6)?,
-                raw_tx: row.get(7)?,
-                block_hash: row.get(8)?,
-                merkle_root: row.get(9)?,
-            })
-        },
+        parse_proven_tx_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find proven_tx: {}", e)))?;
@@ -63,6 +97,39 @@ pub fn find_proven_tx_by_txid(
     Ok(result)
 }
 
+/// Update the given fields of a proven transaction
+///
+/// Reference: @wallet-toolbox/src/storage/StorageKnex.ts updateProvenTx
+pub fn update_proven_tx(
+    conn: &Arc<Mutex<Connection>>,
+    proven_tx_id: i64,
+    updates: &ProvenTxUpdates,
+) -> Result<usize, StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        "UPDATE proven_txs
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+             height = COALESCE(?1, height),
+             `index` = COALESCE(?2, `index`),
+             merklePath = COALESCE(?3, merklePath),
+             blockHash = COALESCE(?4, blockHash),
+             merkleRoot = COALESCE(?5, merkleRoot)
+         WHERE provenTxId = ?6",
+        params![
+            updates.height,
+            updates.index,
+            updates.merkle_path,
+            updates.block_hash,
+            updates.merkle_root,
+            proven_tx_id,
+        ],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update proven_tx: {}", e)))?;
+
+    Ok(rows)
+}
+
 /// Insert proven transaction request
 pub fn insert_proven_tx_req(
     conn: &Arc<Mutex<Connection>>,
@@ -102,7 +169,7 @@ pub fn update_proven_tx_req(
 
     let rows = conn.execute(
         "UPDATE proven_tx_reqs
-         SET updated_at = datetime('now'),
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
              provenTxId = ?1,
              status = ?2,
              attempts = ?3,
@@ -135,27 +202,9 @@ pub fn find_proven_tx_req_by_txid(
     let conn = conn.lock().unwrap();
 
     let result = conn.query_row(
-        "SELECT created_at, updated_at, provenTxReqId, provenTxId, status, attempts, notified,
-                txid, batch, history, notify, rawTx, inputBEEF
-         FROM proven_tx_reqs WHERE txid = ?1",
+        &format!("SELECT {} FROM proven_tx_reqs WHERE txid = ?1", PROVEN_TX_REQ_COLUMNS),
         params![txid],
-        |row| {
-            Ok(TableProvenTxReq {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                proven_tx_req_id: row.get(2)?,
-                proven_tx_id: row.get(3)?,
-                status: row.get::<_, String>(4)?.parse().unwrap_or(ProvenTxReqStatus::Unknown),
-                attempts: row.get(5)?,
-                notified: row.get::<_, i32>(6)? != 0,
-                txid: row.get(7)?,
-                batch: row.get(8)?,
-                history: row.get(9)?,
-                notify: row.get(10)?,
-                raw_tx: row.get(11)?,
-                input_beef: row.get(12)?,
-            })
-        },
+        parse_proven_tx_req_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find proven_tx_req: {}", e)))?;
@@ -163,6 +212,227 @@ pub fn find_proven_tx_req_by_txid(
     Ok(result)
 }
 
+/// Find proven tx reqs matching `args`, ordered by provenTxReqId
+///
+/// `since` is normalized with `datetime()` so RFC 3339 bounds compare with
+/// the stored times.
+/// Reference: @wallet-toolbox/src/storage/StorageKnex.ts findProvenTxReqs
+pub fn find_proven_tx_reqs(
+    conn: &Arc<Mutex<Connection>>,
+    args: &FindProvenTxReqsArgs,
+) -> Result<Vec<TableProvenTxReq>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find proven_tx_reqs: {}", e));
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if let Some(status) = args.status {
+        conditions.push("status = ?".to_string());
+        params_vec.push(Box::new(status.to_string()));
+    }
+    if let Some(since) = &args.since {
+        conditions.push("updated_at >= datetime(?)".to_string());
+        params_vec.push(Box::new(since.clone()));
+    }
+    if let Some(txids) = &args.txids {
+        if txids.is_empty() {
+            return Ok(Vec::new());
+        }
+        conditions.push(format!("txid IN ({})", vec!["?"; txids.len()].join(", ")));
+        for txid in txids {
+            params_vec.push(Box::new(txid.clone()));
+        }
+    }
+
+    let mut query = format!("SELECT {} FROM proven_tx_reqs", PROVEN_TX_REQ_COLUMNS);
+    if !conditions.is_empty() {
+        query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    query.push_str(" ORDER BY provenTxReqId ASC");
+    if let Some(paged) = &args.paged {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", paged.limit, paged.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&query).map_err(db_err)?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), parse_proven_tx_req_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Update the given fields of a proven transaction request, failing with
+/// NotFound if it is missing
+///
+/// With `expected_updated_at`, fails with Conflict and updates nothing unless
+/// the request is still that version.
+/// Reference: @wallet-toolbox/src/storage/StorageKnex.ts updateProvenTxReq
+pub fn update_proven_tx_req_fields(
+    conn: &Arc<Mutex<Connection>>,
+    req_id: i64,
+    updates: &ProvenTxReqUpdates,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        "UPDATE proven_tx_reqs
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+             status = COALESCE(?1, status),
+             batch = COALESCE(?2, batch),
+             attempts = COALESCE(?3, attempts),
+             history = COALESCE(?4, history)
+         WHERE provenTxReqId = ?5 AND (?6 IS NULL OR updated_at = ?6)",
+        params![
+            updates.status.map(|s| s.to_string()),
+            updates.batch,
+            updates.attempts,
+            updates.history,
+            req_id,
+            updates.expected_updated_at,
+        ],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update proven_tx_req: {}", e)))?;
+    if rows == 0 {
+        return verify_updated(&conn, "proven_tx_reqs", "provenTxReqId", req_id, updates.expected_updated_at.as_deref());
+    }
+    Ok(())
+}
+
+/// ProvenTxs mined at or above `min_height`, ordered by height
+pub fn find_proven_txs_from_height(
+    conn: &Arc<Mutex<Connection>>,
+    min_height: i64,
+) -> Result<Vec<TableProvenTx>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find proven_txs: {}", e));
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM proven_txs WHERE height >= ?1 ORDER BY height ASC, provenTxId ASC",
+        PROVEN_TX_COLUMNS
+    )).map_err(db_err)?;
+    let rows = stmt.query_map(params![min_height], parse_proven_tx_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// The ProvenTx of `txid`, else the raw transaction and input BEEF of a
+/// request for it whose transaction is known to be valid
+///
+/// Matches TypeScript `StorageKnex.getProvenOrRawTx`
+pub fn get_proven_or_raw_tx(
+    conn: &Arc<Mutex<Connection>>,
+    txid: &str,
+) -> Result<ProvenOrRawTx, StorageError> {
+    if let Some(proven) = find_proven_tx_by_txid(conn, txid)? {
+        return Ok(ProvenOrRawTx { proven: Some(proven), raw_tx: None, input_beef: None });
+    }
+
+    let conn = conn.lock().unwrap();
+    let req: Option<(Vec<u8>, Option<Vec<u8>>)> = conn.query_row(
+        &format!("SELECT rawTx, inputBEEF FROM proven_tx_reqs WHERE txid = ?1 AND status IN ({})", KNOWN_VALID_REQ_STATUSES),
+        params![txid],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| StorageError::Database(format!("Failed to find proven_tx_req: {}", e)))?;
+
+    let (raw_tx, input_beef) = req.unzip();
+    Ok(ProvenOrRawTx { proven: None, raw_tx, input_beef: input_beef.flatten() })
+}
+
+/// Insert the ProvenTx proving a request's transaction, mark the request
+/// 'completed', and link and complete the transactions it notifies, in one
+/// database transaction
+///
+/// Matches TypeScript `StorageProvider.updateProvenTxReqWithNewProvenTx`
+pub fn update_proven_tx_req_with_new_proven_tx(
+    conn: &Arc<Mutex<Connection>>,
+    args: &UpdateProvenTxReqWithNewProvenTxArgs,
+) -> Result<UpdateProvenTxReqWithNewProvenTxResult, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to insert proven_tx for request: {}", e));
+
+    let req = conn.query_row(
+        &format!("SELECT {} FROM proven_tx_reqs WHERE provenTxReqId = ?1", PROVEN_TX_REQ_COLUMNS),
+        params![args.proven_tx_req_id],
+        parse_proven_tx_req_row,
+    )
+    .optional()
+    .map_err(db_err)?
+    .ok_or_else(|| StorageError::NotFound(format!("proven_tx_req {}", args.proven_tx_req_id)))?;
+
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    tx.execute(
+        "INSERT INTO proven_txs (txid, height, `index`, merklePath, rawTx, blockHash, merkleRoot)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![args.txid, args.height, args.index, &args.merkle_path, &req.raw_tx, args.block_hash, args.merkle_root],
+    )
+    .map_err(db_err)?;
+    let proven_tx_id = tx.last_insert_rowid();
+
+    let status = ProvenTxReqStatus::Completed;
+    tx.execute(
+        "UPDATE proven_tx_reqs
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), provenTxId = ?1, status = ?2, attempts = ?3, history = ?4
+         WHERE provenTxReqId = ?5",
+        params![proven_tx_id, status.to_string(), args.attempts, args.history, args.proven_tx_req_id],
+    )
+    .map_err(db_err)?;
+    for transaction_id in req.notify_transaction_ids() {
+        tx.execute(
+            "UPDATE transactions SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), provenTxId = ?1, status = ?2 WHERE transactionId = ?3",
+            params![proven_tx_id, TransactionStatus::Completed.to_string(), transaction_id],
+        )
+        .map_err(db_err)?;
+    }
+    tx.commit().map_err(db_err)?;
+
+    Ok(UpdateProvenTxReqWithNewProvenTxResult {
+        status,
+        history: args.history.clone(),
+        proven_tx_id,
+        log: None,
+    })
+}
+
+/// Delete a ProvenTx, returning its requests to 'unmined' and its
+/// 'completed' transactions to 'unproven'
+///
+/// Returns the ids of the reverted transactions. Reference: TaskReorg.ts
+pub fn rollback_proven_tx(
+    conn: &Arc<Mutex<Connection>>,
+    proven_tx_id: i64,
+) -> Result<Vec<i64>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to roll back proven_tx: {}", e));
+
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    let reverted = {
+        let mut stmt = tx.prepare(
+            "SELECT transactionId FROM transactions WHERE provenTxId = ?1 AND status = 'completed' ORDER BY transactionId",
+        ).map_err(db_err)?;
+        let rows = stmt.query_map(params![proven_tx_id], |row| row.get(0)).map_err(db_err)?;
+        rows.collect::<Result<Vec<i64>, _>>().map_err(db_err)?
+    };
+    tx.execute(
+        "UPDATE proven_tx_reqs SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), provenTxId = NULL, status = 'unmined' WHERE provenTxId = ?1",
+        params![proven_tx_id],
+    )
+    .map_err(db_err)?;
+    tx.execute(
+        "UPDATE transactions
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
+             provenTxId = NULL,
+             status = CASE WHEN status = 'completed' THEN 'unproven' ELSE status END
+         WHERE provenTxId = ?1",
+        params![proven_tx_id],
+    )
+    .map_err(db_err)?;
+    if tx.execute("DELETE FROM proven_txs WHERE provenTxId = ?1", params![proven_tx_id]).map_err(db_err)? == 0 {
+        return Err(StorageError::NotFound(format!("proven_tx {}", proven_tx_id)));
+    }
+    tx.commit().map_err(db_err)?;
+
+    Ok(reverted)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,6 +472,27 @@ mod tests {
         assert_eq!(found.index, 1);
     }
 
+    #[test]
+    fn test_update_proven_tx() {
+        let conn = create_test_storage();
+        let proven_tx = TableProvenTx::new(0, "abc123", 850000, 1, vec![0x01], vec![0xAA], "old_hash", "old_root");
+        let id = insert_proven_tx(&conn, &proven_tx).unwrap();
+
+        let updates = ProvenTxUpdates {
+            height: Some(850001),
+            merkle_path: Some(vec![0x02, 0x03]),
+            block_hash: Some("new_hash".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(update_proven_tx(&conn, id, &updates).unwrap(), 1);
+
+        let found = find_proven_tx_by_txid(&conn, "abc123").unwrap().unwrap();
+        assert_eq!((found.height, found.index), (850001, 1));
+        assert_eq!(found.merkle_path, vec![0x02, 0x03]);
+        assert_eq!((found.block_hash.as_str(), found.merkle_root.as_str()), ("new_hash", "old_root"));
+        assert_eq!(update_proven_tx(&conn, id + 1, &updates).unwrap(), 0);
+    }
+
     #[test]
     fn test_insert_proven_tx_req() {
         let conn = create_test_storage();
@@ -226,4 +517,54 @@ mod tests {
         assert_eq!(found.status, ProvenTxReqStatus::Unsent);
         assert_eq!(found.batch, Some("batch_1".to_string()));
     }
+
+    #[test]
+    fn test_update_proven_tx_req_fields_if_unchanged() {
+        let conn = create_test_storage();
+        let req = TableProvenTxReq::new(0, ProvenTxReqStatus::Unsent, "txid_1", "{}", "{}", vec![0x01]);
+        let id = insert_proven_tx_req(&conn, &req).unwrap();
+        let read = find_proven_tx_req_by_txid(&conn, "txid_1").unwrap().unwrap();
+
+        let mut updates = ProvenTxReqUpdates {
+            status: Some(ProvenTxReqStatus::Sending),
+            attempts: Some(1),
+            expected_updated_at: Some(read.updated_at.clone()),
+            ..Default::default()
+        };
+        update_proven_tx_req_fields(&conn, id, &updates).unwrap();
+        let found = find_proven_tx_req_by_txid(&conn, "txid_1").unwrap().unwrap();
+        assert_eq!((found.status, found.attempts, found.history.as_str()), (ProvenTxReqStatus::Sending, 1, "{}"));
+
+        // A stale version updates nothing
+        updates.status = Some(ProvenTxReqStatus::Invalid);
+        updates.expected_updated_at = Some("2000-01-01 00:00:00.000".to_string());
+        assert!(matches!(update_proven_tx_req_fields(&conn, id, &updates), Err(StorageError::Conflict(_))));
+        assert_eq!(find_proven_tx_req_by_txid(&conn, "txid_1").unwrap().unwrap().status, ProvenTxReqStatus::Sending);
+        assert!(matches!(update_proven_tx_req_fields(&conn, 99, &updates), Err(StorageError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_find_proven_tx_reqs() {
+        let conn = create_test_storage();
+        for (txid, status) in [("txid_a", ProvenTxReqStatus::Unsent), ("txid_b", ProvenTxReqStatus::Unmined), ("txid_c", ProvenTxReqStatus::Unsent)] {
+            insert_proven_tx_req(&conn, &TableProvenTxReq::new(0, status, txid, "{}", "{}", vec![0x01])).unwrap();
+        }
+        let txids = |args: &FindProvenTxReqsArgs| -> Vec<String> {
+            find_proven_tx_reqs(&conn, args).unwrap().into_iter().map(|r| r.txid).collect()
+        };
+
+        let all = FindProvenTxReqsArgs { status: None, since: None, paged: None, txids: None };
+        assert_eq!(txids(&all), ["txid_a", "txid_b", "txid_c"]);
+        let mut args = FindProvenTxReqsArgs { status: Some(ProvenTxReqStatus::Unsent), ..all.clone() };
+        assert_eq!(txids(&args), ["txid_a", "txid_c"]);
+        args.paged = Some(Paged { limit: 1, offset: Some(1) });
+        assert_eq!(txids(&args), ["txid_c"]);
+
+        let args = FindProvenTxReqsArgs { txids: Some(vec!["txid_b".into(), "txid_c".into()]), ..all.clone() };
+        assert_eq!(txids(&args), ["txid_b", "txid_c"]);
+        let args = FindProvenTxReqsArgs { txids: Some(vec![]), ..all.clone() };
+        assert!(txids(&args).is_empty());
+        let args = FindProvenTxReqsArgs { since: Some("2999-01-01T00:00:00Z".into()), ..all };
+        assert!(txids(&args).is_empty());
+    }
 }
@@ -0,0 +1,247 @@
+//! Purge of aged transaction data
+//!
+//! Reference: wallet-toolbox/src/storage/methods/purgeData.ts
+
+use rusqlite::{params, Connection};
+use std::sync::{Arc, Mutex};
+use wallet_storage::*;
+
+/// Delete or trim aged data as selected by `params`, then optionally VACUUM
+///
+/// Matches TypeScript `StorageKnex.purgeData`. All steps run in one
+/// transaction; VACUUM runs after it commits.
+pub fn purge_data(
+    conn: &Arc<Mutex<Connection>>,
+    params: &PurgeParams,
+) -> Result<PurgeResults, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to purge data: {}", e));
+
+    let tx = conn.unchecked_transaction().map_err(db_err)?;
+    let mut r = PurgeResults::default();
+
+    if params.purge_completed {
+        let before = cutoff(&tx, params.purge_completed_age)?;
+
+        r.add(tx.execute(
+            "UPDATE transactions SET rawTx = NULL, inputBEEF = NULL
+             WHERE status = 'completed' AND provenTxId IS NOT NULL
+               AND (rawTx IS NOT NULL OR inputBEEF IS NOT NULL)
+               AND julianday(updated_at) < ?1",
+            params![before],
+        ).map_err(db_err)?, "completedTransactions");
+
+        r.add(tx.execute(
+            "DELETE FROM proven_tx_reqs
+             WHERE status = 'completed' AND provenTxId IS NOT NULL AND notified = 1
+               AND julianday(updated_at) < ?1",
+            params![before],
+        ).map_err(db_err)?, "completedProvenTxReqs");
+    }
+
+    if params.purge_failed {
+        let before = cutoff(&tx, params.purge_failed_age)?;
+
+        let failed = ids(&tx,
+            "SELECT transactionId FROM transactions
+             WHERE status = 'failed' AND julianday(updated_at) < ?1",
+            before,
+        )?;
+        delete_transactions(&tx, &failed, "failed", &mut r)?;
+
+        r.add(tx.execute(
+            "DELETE FROM proven_tx_reqs
+             WHERE status IN ('invalid', 'doubleSpend') AND julianday(updated_at) < ?1",
+            params![before],
+        ).map_err(db_err)?, "failedProvenTxReqs");
+    }
+
+    if params.purge_spent {
+        let before = cutoff(&tx, params.purge_spent_age)?;
+
+        let spent = ids(&tx,
+            "SELECT o.outputId FROM outputs o JOIN transactions t ON t.transactionId = o.spentBy
+             WHERE o.spendable = 0 AND t.status = 'completed' AND julianday(o.updated_at) < ?1",
+            before,
+        )?;
+        let mut tag_maps = 0;
+        for output_id in &spent {
+            tag_maps += tx.execute("DELETE FROM output_tags_map WHERE outputId = ?1", params![output_id])
+                .map_err(db_err)?;
+            tx.execute("DELETE FROM outputs WHERE outputId = ?1", params![output_id]).map_err(db_err)?;
+        }
+        r.add(tag_maps, "spentOutputTagMaps");
+        r.add(spent.len(), "spentOutputs");
+
+        // Proven transactions left with neither outputs nor inputs
+        let emptied = ids(&tx,
+            "SELECT transactionId FROM transactions t
+             WHERE status = 'completed' AND provenTxId IS NOT NULL AND julianday(updated_at) < ?1
+               AND NOT EXISTS (SELECT 1 FROM outputs o WHERE o.transactionId = t.transactionId)
+               AND NOT EXISTS (SELECT 1 FROM outputs o WHERE o.spentBy = t.transactionId)",
+            before,
+        )?;
+        delete_transactions(&tx, &emptied, "spent", &mut r)?;
+    }
+
+    tx.commit().map_err(db_err)?;
+
+    if params.vacuum {
+        conn.execute_batch("VACUUM").map_err(db_err)?;
+    }
+
+    Ok(r)
+}
+
+/// Julian day `age_msecs` before now, defaulting to `PurgeParams::DEFAULT_AGE_MSECS`
+fn cutoff(conn: &Connection, age_msecs: Option<i64>) -> Result<f64, StorageError> {
+    let age_msecs = age_msecs.unwrap_or(PurgeParams::DEFAULT_AGE_MSECS);
+    conn.query_row(
+        "SELECT julianday('now') - ?1 / 86400000.0",
+        params![age_msecs],
+        |row| row.get(0),
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to purge data: {}", e)))
+}
+
+/// Ids selected by `query` with the cutoff bound to ?1
+fn ids(conn: &Connection, query: &str, before: f64) -> Result<Vec<i64>, StorageError> {
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to purge data: {}", e));
+
+    let mut stmt = conn.prepare(query).map_err(db_err)?;
+    let rows = stmt.query_map(params![before], |row| row.get(0)).map_err(db_err)?;
+
+    let mut ids = Vec::new();
+    for row in rows {
+        ids.push(row.map_err(db_err)?);
+    }
+    Ok(ids)
+}
+
+/// Delete transactions with their outputs, labels and commissions
+///
+/// Outputs they spend keep their spendable flag but no longer reference them.
+fn delete_transactions(
+    conn: &Connection,
+    transaction_ids: &[i64],
+    reason: &str,
+    r: &mut PurgeResults,
+) -> Result<(), StorageError> {
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to purge data: {}", e));
+
+    let steps = [
+        ("UPDATE outputs SET spentBy = NULL WHERE spentBy = ?1", "InputsReleased"),
+        (
+            "DELETE FROM output_tags_map WHERE outputId IN (SELECT outputId FROM outputs WHERE transactionId = ?1)",
+            "OutputTagMaps",
+        ),
+        ("DELETE FROM outputs WHERE transactionId = ?1", "Outputs"),
+        ("DELETE FROM tx_labels_map WHERE transactionId = ?1", "TxLabelMaps"),
+        ("DELETE FROM commissions WHERE transactionId = ?1", "Commissions"),
+        ("DELETE FROM transactions WHERE transactionId = ?1", "Transactions"),
+    ];
+    for (sql, step) in steps {
+        let mut count = 0;
+        for transaction_id in transaction_ids {
+            count += conn.execute(sql, params![transaction_id]).map_err(db_err)?;
+        }
+        r.add(count, &format!("{}{}", reason, step));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::{apply_initial_migration, apply_pending_migrations};
+
+    fn create_test_storage() -> Arc<Mutex<Connection>> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        apply_initial_migration(&conn, "test_key", "Test", "main", 100000).unwrap();
+        apply_pending_migrations(&conn).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO users (identityKey, activeStorage) VALUES ('test_user', 'test_storage');
+             INSERT INTO proven_txs (txid, height, `index`, merklePath, rawTx, blockHash, merkleRoot)
+                VALUES ('aa', 1, 0, x'00', x'01', 'bh', 'mr');
+             INSERT INTO transactions (userId, provenTxId, status, reference, isOutgoing, description, rawTx, inputBEEF, updated_at)
+                VALUES (1, 1, 'completed', 'ref_completed', 0, 'completed', x'01', x'02', '2000-01-01 00:00:00');
+             INSERT INTO transactions (userId, status, reference, isOutgoing, description, updated_at)
+                VALUES (1, 'failed', 'ref_failed', 1, 'failed', '2000-01-01 00:00:00');
+             INSERT INTO transactions (userId, status, reference, isOutgoing, description)
+                VALUES (1, 'failed', 'ref_failed_recent', 1, 'failed recently');
+             INSERT INTO outputs (userId, transactionId, spendable, vout, satoshis, providedBy, purpose, type, outputDescription, spentBy)
+                VALUES (1, 1, 0, 0, 1000, 'storage', 'change', 'P2PKH', '', 2);
+             INSERT INTO outputs (userId, transactionId, spendable, vout, satoshis, providedBy, purpose, type, outputDescription)
+                VALUES (1, 2, 1, 0, 900, 'storage', 'change', 'P2PKH', '');
+             INSERT INTO proven_tx_reqs (provenTxId, status, notified, txid, rawTx, updated_at)
+                VALUES (1, 'completed', 1, 'aa', x'01', '2000-01-01 00:00:00');
+             INSERT INTO proven_tx_reqs (status, txid, rawTx, updated_at)
+                VALUES ('doubleSpend', 'bb', x'01', '2000-01-01 00:00:00');",
+        ).unwrap();
+
+        Arc::new(Mutex::new(conn))
+    }
+
+    fn count(conn: &Arc<Mutex<Connection>>, sql: &str) -> i64 {
+        conn.lock().unwrap().query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_purge_completed() {
+        let conn = create_test_storage();
+        let r = purge_data(&conn, &PurgeParams {
+            purge_completed: true,
+            purge_failed: false,
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(r.count, 2);
+        assert!(r.log.contains("1 completedTransactions"));
+        assert!(r.log.contains("1 completedProvenTxReqs"));
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM transactions WHERE rawTx IS NOT NULL OR inputBEEF IS NOT NULL"), 0);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM proven_tx_reqs"), 1);
+    }
+
+    #[test]
+    fn test_purge_failed_and_vacuum() {
+        let conn = create_test_storage();
+        let r = purge_data(&conn, &PurgeParams { vacuum: true, ..Default::default() }).unwrap();
+
+        assert!(r.log.contains("1 failedInputsReleased"));
+        assert!(r.log.contains("1 failedOutputs"));
+        assert!(r.log.contains("1 failedTransactions"));
+        assert!(r.log.contains("1 failedProvenTxReqs"));
+        // The recently failed transaction is kept
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM transactions WHERE status = 'failed'"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM outputs WHERE spentBy IS NOT NULL"), 0);
+    }
+
+    #[test]
+    fn test_purge_spent() {
+        let conn = create_test_storage();
+        conn.lock().unwrap().execute_batch(
+            "UPDATE transactions SET status = 'completed', provenTxId = 1, updated_at = '2000-01-01 00:00:00'
+                WHERE reference = 'ref_failed';
+             UPDATE outputs SET updated_at = '2000-01-01 00:00:00';",
+        ).unwrap();
+
+        let r = purge_data(&conn, &PurgeParams {
+            purge_failed: false,
+            purge_spent: true,
+            ..Default::default()
+        }).unwrap();
+
+        // The spent output goes; its transaction is then empty and goes too.
+        // The spending transaction still has an unspent output.
+        assert!(r.log.contains("1 spentOutputs"));
+        assert!(r.log.contains("1 spentTransactions"));
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM transactions WHERE reference = 'ref_completed'"), 0);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM transactions WHERE reference = 'ref_failed'"), 1);
+    }
+}
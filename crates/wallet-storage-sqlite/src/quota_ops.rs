@@ -0,0 +1,106 @@
+//! Per-user usage counters and quota checks
+//!
+//! The `user_usages` counters are maintained by triggers on the counted
+//! tables; these operations read them and check writes against a policy.
+
+use rusqlite::{Connection, params, OptionalExtension};
+use std::sync::{Arc, Mutex};
+use wallet_storage::*;
+
+/// What user `user_id`'s stored data counts against their quota
+pub fn get_user_usage(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+) -> Result<UserUsage, StorageError> {
+    query_user_usage(&conn.lock().unwrap(), user_id)
+}
+
+fn query_user_usage(conn: &Connection, user_id: i64) -> Result<UserUsage, StorageError> {
+    conn.query_row(
+        "SELECT transactions, outputs, certificateBytes, beefBytes FROM user_usages WHERE userId = ?1",
+        params![user_id],
+        |row| {
+            Ok(UserUsage {
+                transactions: row.get(0)?,
+                outputs: row.get(1)?,
+                certificate_bytes: row.get(2)?,
+                beef_bytes: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map(Option::unwrap_or_default)
+    .map_err(|e| StorageError::Database(format!("Failed to find user_usage: {}", e)))
+}
+
+/// Check that a write adding `added` keeps user `user_id` within `policy`
+///
+/// Run it on the transaction that makes the write, so that no other write
+/// lands between the check and the write.
+pub fn check_quota(
+    conn: &Connection,
+    policy: &QuotaPolicy,
+    user_id: i64,
+    added: &UserUsage,
+) -> Result<(), StorageError> {
+    if *policy == QuotaPolicy::unlimited() {
+        return Ok(());
+    }
+    policy.check(user_id, &query_user_usage(conn, user_id)?, added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert_commission_ops::{insert_certificate, insert_certificate_field};
+    use crate::migrations::{apply_initial_migration, apply_pending_migrations};
+    use crate::output_ops::insert_output;
+    use crate::transaction_ops::insert_transaction;
+
+    fn create_test_storage() -> Arc<Mutex<Connection>> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        apply_initial_migration(&conn, "test_key", "Test", "main", 100000).unwrap();
+        apply_pending_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO users (identityKey, activeStorage) VALUES (?1, ?2)",
+            params!["test_user", "test_storage"],
+        ).unwrap();
+
+        Arc::new(Mutex::new(conn))
+    }
+
+    #[test]
+    fn test_user_usage_counters() {
+        let conn = create_test_storage();
+        assert_eq!(get_user_usage(&conn, 1).unwrap(), UserUsage::default());
+
+        let mut tx = TableTransaction::new(0, 1, TransactionStatus::Completed, "ref1", false, 100, "test");
+        tx.input_beef = Some(vec![0; 50]);
+        let tx_id = insert_transaction(&conn, 1, &tx).unwrap();
+        let mut output = TableOutput::new(0, 1, tx_id, true, false, "test", 0, 100, StorageProvidedBy::You, "", "P2PKH");
+        insert_output(&conn, &output).unwrap();
+        output.vout = 1;
+        insert_output(&conn, &output).unwrap();
+        let cert = TableCertificate::new(0, 1, "identity", "serial", "certifier", "subject", "outpoint", "signature");
+        let cert_id = insert_certificate(&conn, &cert).unwrap();
+        insert_certificate_field(&conn, &TableCertificateField::new(1, cert_id, "name", "alice", "key01")).unwrap();
+
+        let usage = get_user_usage(&conn, 1).unwrap();
+        assert_eq!(usage, UserUsage { transactions: 1, outputs: 2, certificate_bytes: 10, beef_bytes: 50 });
+
+        conn.lock().unwrap().execute("UPDATE transactions SET inputBEEF = NULL", []).unwrap();
+        conn.lock().unwrap().execute("DELETE FROM outputs WHERE vout = 1", []).unwrap();
+        let usage = get_user_usage(&conn, 1).unwrap();
+        assert_eq!((usage.outputs, usage.beef_bytes), (1, 0));
+
+        let policy = QuotaPolicy { max_outputs: Some(1), ..Default::default() };
+        let conn = conn.lock().unwrap();
+        assert!(check_quota(&conn, &policy, 1, &UserUsage::transaction(None)).is_ok());
+        assert!(matches!(
+            check_quota(&conn, &policy, 1, &UserUsage::output()),
+            Err(StorageError::QuotaExceeded { resource: QuotaResource::Outputs, limit: 1, requested: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+//! Optimistic concurrency checks
+//!
+//! Conditional updates add `AND (?n IS NULL OR updated_at = ?n)` to their
+//! WHERE clause, so a stale `expected_updated_at` updates nothing. When no
+//! row was updated, `verify_updated` tells a missing row from a changed one.
+//!
+//! Every statement writing `updated_at`, and the column defaults, use
+//! `strftime('%Y-%m-%d %H:%M:%f', 'now')`: millisecond precision keeps updates
+//! within the same second distinct versions.
+
+use rusqlite::{Connection, params, OptionalExtension};
+use wallet_storage::*;
+
+/// Error for an update of row `id` that changed no rows: NotFound if the row
+/// is missing, else Conflict if it is no longer the version last updated at
+/// `expected_updated_at`
+pub(crate) fn verify_updated(
+    conn: &Connection,
+    table: &str,
+    id_column: &str,
+    id: i64,
+    expected_updated_at: Option<&str>,
+) -> Result<(), StorageError> {
+    let updated_at: Option<String> = conn
+        .query_row(
+            &format!("SELECT updated_at FROM {} WHERE {} = ?1", table, id_column),
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| StorageError::Database(format!("Failed to find {} {}: {}", table, id, e)))?;
+    match updated_at {
+        Some(updated_at) => verify_unchanged(table, id, expected_updated_at, &updated_at),
+        None => Err(StorageError::NotFound(format!("{} {}", table, id))),
+    }
+}
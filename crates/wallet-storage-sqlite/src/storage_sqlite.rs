@@ -4,17 +4,49 @@
 //! Reference: wallet-toolbox/src/storage/StorageKnex.ts
 
 use async_trait::async_trait;
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OpenFlags, params};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use wallet_storage::*;
+use wallet_storage::schema::entities::SyncMap;
 
-use crate::migrations::{apply_initial_migration, is_initialized};
+use crate::migrations::{
+    apply_initial_migration, apply_pending_migrations, current_version, is_initialized, pending_migrations,
+    rollback_migrations, MIGRATIONS,
+};
 use crate::transaction_ops;
 use crate::output_ops;
 use crate::proven_tx_ops;
 use crate::basket_tag_label_ops;
 use crate::cert_commission_ops;
+use crate::encryption::{self, SqliteKey};
+use crate::purge_ops;
+use crate::quota_ops;
+use crate::dump_ops;
+
+/// How long a read-only connection waits for a writer to finish
+const READ_ONLY_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Output script length above which scripts are stored outside the outputs table
+///
+/// Reference: TS KnexMigrations maxOutputScriptLength
+const MAX_OUTPUT_SCRIPT: i64 = 1024;
+
+/// Apply pending migrations, or on a read-only connection check there are none
+fn migrate_schema(conn: &Connection, read_only: bool) -> Result<(), StorageError> {
+    if !read_only {
+        return apply_pending_migrations(conn).map(|_| ());
+    }
+    let pending = pending_migrations(conn)?;
+    if !pending.is_empty() {
+        return Err(StorageError::Database(format!(
+            "Read-only database needs migrations {}; open it for writing first",
+            pending.join(", ")
+        )));
+    }
+    Ok(())
+}
 
 /// SQLite storage backend
 ///
@@ -22,6 +54,14 @@ use crate::cert_commission_ops;
 pub struct StorageSqlite {
     conn: Arc<Mutex<Connection>>,
     settings: Option<TableSettings>,
+    fee_model: StorageFeeModel,
+    change_baskets: StorageChangeBaskets,
+    /// Limits on each user's usage, checked before inserts
+    quota_policy: QuotaPolicy,
+    /// Key applied before the database is first read
+    pending_key: Option<SqliteKey>,
+    /// Opened with `new_read_only`
+    read_only: bool,
 }
 
 impl StorageSqlite {
@@ -37,6 +77,11 @@ impl StorageSqlite {
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             settings: None,
+            fee_model: StorageFeeModel::default(),
+            change_baskets: StorageChangeBaskets::default(),
+            quota_policy: QuotaPolicy::unlimited(),
+            pending_key: None,
+            read_only: false,
         })
     }
 
@@ -51,9 +96,107 @@ impl StorageSqlite {
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             settings: None,
+            fee_model: StorageFeeModel::default(),
+            change_baskets: StorageChangeBaskets::default(),
+            quota_policy: QuotaPolicy::unlimited(),
+            pending_key: None,
+            read_only: false,
+        })
+    }
+
+    /// Open an existing database for reading only
+    ///
+    /// The connection cannot write, so reports and dashboards can read a
+    /// wallet database while its wallet is running; every write fails at
+    /// runtime. `initialize` and `make_available` load the settings without
+    /// migrating, and fail if this build would migrate the database. Wrap
+    /// it in `ReadOnly` to reject writes at compile time as well.
+    pub fn new_read_only<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(path, flags)
+            .map_err(|e| StorageError::Database(format!("Failed to open database: {}", e)))?;
+
+        // Wait out the wallet's writes rather than failing with SQLITE_BUSY
+        conn.busy_timeout(READ_ONLY_BUSY_TIMEOUT)
+            .map_err(|e| StorageError::Database(format!("Failed to set busy timeout: {}", e)))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            settings: None,
+            fee_model: StorageFeeModel::default(),
+            change_baskets: StorageChangeBaskets::default(),
+            quota_policy: QuotaPolicy::unlimited(),
+            pending_key: None,
+            read_only: true,
+        })
+    }
+
+    /// Whether the database was opened with `new_read_only`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Open an SQLCipher-encrypted database, creating it if needed
+    ///
+    /// Requires the `sqlcipher` feature. Fails with `Unauthorized` when `key`
+    /// does not open an existing database.
+    pub fn new_encrypted<P: AsRef<Path>>(path: P, key: &SqliteKey) -> Result<Self, StorageError> {
+        let conn = Connection::open(path)
+            .map_err(|e| StorageError::Database(format!("Failed to open database: {}", e)))?;
+
+        encryption::apply_key(&conn, key)?;
+
+        conn.execute("PRAGMA foreign_keys = ON", [])
+            .map_err(|e| StorageError::Database(format!("Failed to enable foreign keys: {}", e)))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            settings: None,
+            fee_model: StorageFeeModel::default(),
+            change_baskets: StorageChangeBaskets::default(),
+            quota_policy: QuotaPolicy::unlimited(),
+            pending_key: None,
+            read_only: false,
         })
     }
 
+    /// Set the key for an encrypted database opened with `new`
+    ///
+    /// The key is applied by the next `initialize` or `make_available`,
+    /// before anything reads the database, and is not retained after.
+    pub fn set_encryption_key(&mut self, key: SqliteKey) {
+        self.pending_key = Some(key);
+    }
+
+    /// Re-encrypt the database under `new_key`
+    ///
+    /// The database must already be open with its current key.
+    pub fn rekey(&self, new_key: &SqliteKey) -> Result<(), StorageError> {
+        let conn = self.conn.lock().unwrap();
+        encryption::rekey(&conn, new_key)
+    }
+
+    /// Apply a key set by `set_encryption_key`
+    fn unlock(&mut self) -> Result<(), StorageError> {
+        if let Some(key) = self.pending_key.take() {
+            let conn = self.conn.lock().unwrap();
+            encryption::apply_key(&conn, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Open the database at `path` and initialize it, creating it if needed
+    ///
+    /// A new database gets a random storage identity key; an existing one
+    /// keeps its own and must be on `chain`.
+    pub fn open_or_create<P: AsRef<Path>>(path: P, storage_name: &str, chain: &str) -> Result<Self, StorageError> {
+        let mut storage = Self::new(path)?;
+        let secp = secp256k1::Secp256k1::new();
+        let (_, storage_identity_key) = secp.generate_keypair(&mut secp256k1::rand::rngs::OsRng);
+        storage.initialize(&storage_identity_key.to_string(), storage_name, chain, MAX_OUTPUT_SCRIPT)?;
+        Ok(storage)
+    }
+
     /// Initialize storage with settings
     pub fn initialize(
         &mut self,
@@ -62,9 +205,13 @@ impl StorageSqlite {
         chain: &str,
         max_output_script: i64,
     ) -> Result<(), StorageError> {
+        self.unlock()?;
         let conn = self.conn.lock().unwrap();
         
         if !is_initialized(&conn)? {
+            if self.read_only {
+                return Err(StorageError::Database("Storage not initialized".to_string()));
+            }
             apply_initial_migration(
                 &conn,
                 storage_identity_key,
@@ -73,24 +220,77 @@ impl StorageSqlite {
                 max_output_script,
             )?;
         }
+        migrate_schema(&conn, self.read_only)?;
 
         drop(conn);
 
-        // Load settings
+        // Load settings; an existing store must be on the requested chain
         self.load_settings()?;
+        self.get_settings().verify_chain(chain)
+    }
 
+    /// Set the fee model used by createAction
+    ///
+    /// Stored in the settings table, so it survives reopening the database.
+    /// Matches TypeScript `StorageProviderOptions.feeModel`
+    pub fn set_fee_model(&mut self, fee_model: StorageFeeModel) -> Result<(), StorageError> {
+        let json = serde_json::to_string(&fee_model)
+            .map_err(|e| StorageError::Database(format!("Failed to serialize fee model: {}", e)))?;
+        {
+            let conn = self.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE settings SET feeModel = ?1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')",
+                params![json],
+            )
+            .map_err(|e| StorageError::Database(format!("Failed to update fee model: {}", e)))?;
+        }
+        self.fee_model = fee_model;
         Ok(())
     }
 
+    /// Set the change basket used by createAction, overall or per originator
+    pub fn set_change_baskets(&mut self, change_baskets: StorageChangeBaskets) {
+        self.change_baskets = change_baskets;
+    }
+
+    /// Set the limits on each user's usage
+    ///
+    /// Inserts of transactions, outputs and certificate fields, and input
+    /// BEEF updates, that would take a user past a limit fail with
+    /// `StorageError::QuotaExceeded`. Defaults to unlimited.
+    pub fn set_quota_policy(&mut self, quota_policy: QuotaPolicy) {
+        self.quota_policy = quota_policy;
+    }
+
+    /// What a user's stored data counts against their quota
+    pub fn get_user_usage(&self, user_id: i64) -> Result<UserUsage, StorageError> {
+        quota_ops::get_user_usage(&self.conn, user_id)
+    }
+
+    /// Run a write counted against a user's quota in one transaction with
+    /// its quota check, holding the connection throughout, so concurrent
+    /// writes cannot both pass the check
+    fn write_within_quota<T>(
+        &self,
+        write: impl FnOnce(&Connection, &QuotaPolicy) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to write within quota: {}", e));
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction().map_err(db_err)?;
+        let result = write(&tx, &self.quota_policy)?;
+        tx.commit().map_err(db_err)?;
+        Ok(result)
+    }
+
     fn load_settings(&mut self) -> Result<(), StorageError> {
         let conn = self.conn.lock().unwrap();
 
-        let settings = conn.query_row(
-            "SELECT created_at, updated_at, storageIdentityKey, storageName, chain, dbtype, maxOutputScript 
+        let (settings, fee_model) = conn.query_row(
+            "SELECT created_at, updated_at, storageIdentityKey, storageName, chain, dbtype, maxOutputScript, feeModel 
              FROM settings LIMIT 1",
             [],
             |row| {
-                Ok(TableSettings {
+                let settings = TableSettings {
                     created_at: row.get(0)?,
                     updated_at: row.get(1)?,
                     storage_identity_key: row.get(2)?,
@@ -98,11 +298,17 @@ impl StorageSqlite {
                     chain: row.get(4)?,
                     dbtype: row.get(5)?,
                     max_output_script: row.get(6)?,
-                })
+                };
+                Ok((settings, row.get::<_, Option<String>>(7)?))
             },
         )
         .map_err(|e| StorageError::Database(format!("Failed to load settings: {}", e)))?;
 
+        self.fee_model = match fee_model {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| StorageError::Database(format!("Invalid fee model in settings: {}", e)))?,
+            None => StorageFeeModel::default(),
+        };
         self.settings = Some(settings);
         Ok(())
     }
@@ -179,7 +385,7 @@ impl StorageSqlite {
 
         conn.execute(
             "UPDATE users 
-             SET updated_at = datetime('now'), activeStorage = ?1 
+             SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), activeStorage = ?1 
              WHERE userId = ?2",
             params![user.active_storage, user_id],
         )
@@ -190,7 +396,11 @@ impl StorageSqlite {
 
     /// Insert transaction
     pub fn insert_transaction(&self, user_id: i64, transaction: &TableTransaction) -> Result<i64, StorageError> {
-        transaction_ops::insert_transaction(&self.conn, user_id, transaction)
+        self.write_within_quota(|conn, policy| {
+            let added = UserUsage::transaction(transaction.input_beef.as_deref());
+            quota_ops::check_quota(conn, policy, user_id, &added)?;
+            transaction_ops::insert_transaction_row(conn, user_id, transaction)
+        })
     }
 
     /// Find transaction by ID
@@ -205,7 +415,25 @@ impl StorageSqlite {
 
     /// Update transaction
     pub fn update_transaction(&self, transaction_id: i64, transaction: &TableTransaction) -> Result<(), StorageError> {
-        transaction_ops::update_transaction(&self.conn, transaction_id, transaction)
+        self.write_within_quota(|conn, policy| {
+            if let Some(existing) = transaction_ops::find_transaction_row(conn, transaction_id)? {
+                let beef_len = |tx: &TableTransaction| tx.input_beef.as_ref().map_or(0, |b| b.len() as i64);
+                let added = UserUsage { beef_bytes: beef_len(transaction) - beef_len(&existing), ..Default::default() };
+                quota_ops::check_quota(conn, policy, existing.user_id, &added)?;
+            }
+            transaction_ops::update_transaction_row(conn, transaction_id, transaction)
+        })
+    }
+
+    /// Update transaction status, unless the transaction has been updated
+    /// since the version last updated at `expected_updated_at` was read
+    pub fn update_transaction_status_if_unchanged(
+        &self,
+        transaction_id: i64,
+        status: TransactionStatus,
+        expected_updated_at: &str,
+    ) -> Result<(), StorageError> {
+        transaction_ops::update_transaction_status_if_unchanged(&self.conn, transaction_id, status, expected_updated_at)
     }
 
     /// Find transactions for user
@@ -220,7 +448,10 @@ impl StorageSqlite {
 
     /// Insert output
     pub fn insert_output(&self, output: &TableOutput) -> Result<i64, StorageError> {
-        output_ops::insert_output(&self.conn, output)
+        self.write_within_quota(|conn, policy| {
+            quota_ops::check_quota(conn, policy, output.user_id, &UserUsage::output())?;
+            output_ops::insert_output_row(conn, output)
+        })
     }
 
     /// Find output by ID
@@ -248,6 +479,90 @@ impl StorageSqlite {
         output_ops::find_spendable_outputs_for_user(&self.conn, user_id, basket_id, limit)
     }
 
+    /// Delete or trim aged transaction data, optionally compacting the file
+    ///
+    /// Matches TypeScript `StorageKnex.purgeData`
+    pub fn purge_data(&self, params: &PurgeParams) -> Result<PurgeResults, StorageError> {
+        purge_ops::purge_data(&self.conn, params)
+    }
+
+    /// Find the user's output at outpoint `txid.vout`
+    pub fn find_output_by_outpoint(
+        &self,
+        user_id: i64,
+        txid: &str,
+        vout: u32,
+        no_script: bool,
+    ) -> Result<Option<TableOutput>, StorageError> {
+        output_ops::find_output_by_outpoint(&self.conn, user_id, txid, vout, no_script)
+    }
+
+    /// Find the outputs spent by a transaction
+    pub fn find_outputs_spent_by(&self, transaction_id: i64, no_script: bool) -> Result<Vec<TableOutput>, StorageError> {
+        output_ops::find_outputs_spent_by(&self.conn, transaction_id, no_script)
+    }
+
+    /// Sum of the user's spendable basket outputs
+    pub fn get_balance(&self, user_id: i64) -> Result<i64, StorageError> {
+        output_ops::get_balance(&self.conn, user_id)
+    }
+
+    /// Spendable satoshis of each of the user's baskets
+    pub fn get_basket_balances(&self, user_id: i64) -> Result<Vec<BasketBalance>, StorageError> {
+        output_ops::get_basket_balances(&self.conn, user_id)
+    }
+
+    /// Allocate a change output to fund a transaction
+    ///
+    /// Matches TypeScript `StorageKnex.allocateChangeInput`
+    pub fn allocate_change_input(
+        &self,
+        user_id: i64,
+        basket_id: i64,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+        exclude_sending: bool,
+        transaction_id: i64,
+    ) -> Result<Option<TableOutput>, StorageError> {
+        output_ops::allocate_change_input(
+            &self.conn,
+            user_id,
+            basket_id,
+            target_satoshis,
+            exact_satoshis,
+            exclude_sending,
+            transaction_id,
+        )
+    }
+
+    /// Lock the user's output `txid.vout` against change allocation and
+    /// createAction inputs
+    pub fn lock_output(
+        &self,
+        user_id: i64,
+        txid: &str,
+        vout: u32,
+        reason: &str,
+        ttl: Option<std::time::Duration>,
+    ) -> Result<TableOutputLock, StorageError> {
+        output_ops::lock_output(&self.conn, user_id, txid, vout, reason, ttl)
+    }
+
+    /// Remove the lock on the user's output `txid.vout`
+    pub fn unlock_output(&self, user_id: i64, txid: &str, vout: u32) -> Result<bool, StorageError> {
+        output_ops::unlock_output(&self.conn, user_id, txid, vout)
+    }
+
+    /// The unexpired lock on an output
+    pub fn find_output_lock(&self, output_id: i64) -> Result<Option<TableOutputLock>, StorageError> {
+        output_ops::find_output_lock(&self.conn, output_id)
+    }
+
+    /// The user's unexpired output locks
+    pub fn find_output_locks(&self, user_id: i64) -> Result<Vec<TableOutputLock>, StorageError> {
+        output_ops::find_output_locks(&self.conn, user_id)
+    }
+
     /// Insert proven tx
     pub fn insert_proven_tx(&self, proven_tx: &TableProvenTx) -> Result<i64, StorageError> {
         proven_tx_ops::insert_proven_tx(&self.conn, proven_tx)
@@ -258,6 +573,11 @@ impl StorageSqlite {
         proven_tx_ops::find_proven_tx_by_txid(&self.conn, txid)
     }
 
+    /// Update proven tx
+    pub fn update_proven_tx(&self, proven_tx_id: i64, updates: &ProvenTxUpdates) -> Result<usize, StorageError> {
+        proven_tx_ops::update_proven_tx(&self.conn, proven_tx_id, updates)
+    }
+
     /// Insert proven tx req
     pub fn insert_proven_tx_req(&self, req: &TableProvenTxReq) -> Result<i64, StorageError> {
         proven_tx_ops::insert_proven_tx_req(&self.conn, req)
@@ -308,16 +628,76 @@ impl StorageSqlite {
         basket_tag_label_ops::find_tx_label_by_name(&self.conn, user_id, label)
     }
 
+    /// Labels of a transaction
+    pub fn find_tx_labels_for_transaction(&self, transaction_id: i64) -> Result<Vec<TableTxLabel>, StorageError> {
+        basket_tag_label_ops::find_tx_labels_for_transaction(&self.conn, transaction_id)
+    }
+
     /// Insert tx label map
     pub fn insert_tx_label_map(&self, map: &TableTxLabelMap) -> Result<(), StorageError> {
         basket_tag_label_ops::insert_tx_label_map(&self.conn, map)
     }
 
+    /// The user's undeleted output tags
+    pub fn find_output_tags(&self, user_id: i64) -> Result<Vec<TableOutputTag>, StorageError> {
+        basket_tag_label_ops::find_output_tags(&self.conn, user_id)
+    }
+
+    /// The user's undeleted tx labels
+    pub fn find_tx_labels(&self, user_id: i64) -> Result<Vec<TableTxLabel>, StorageError> {
+        basket_tag_label_ops::find_tx_labels(&self.conn, user_id)
+    }
+
+    /// Find or insert output tags, in a single statement
+    pub fn find_or_insert_output_tags(&self, user_id: i64, tags: &[String]) -> Result<Vec<TableOutputTag>, StorageError> {
+        basket_tag_label_ops::find_or_insert_output_tags(&self.conn, user_id, tags)
+    }
+
+    /// Find or insert transaction labels, in a single statement
+    pub fn find_or_insert_tx_labels(&self, user_id: i64, labels: &[String]) -> Result<Vec<TableTxLabel>, StorageError> {
+        basket_tag_label_ops::find_or_insert_tx_labels(&self.conn, user_id, labels)
+    }
+
+    /// Rename an output tag, merging into an existing tag of the new name
+    pub fn rename_output_tag(&self, user_id: i64, tag: &str, new_tag: &str) -> Result<TableOutputTag, StorageError> {
+        basket_tag_label_ops::rename_output_tag(&self.conn, user_id, tag, new_tag)
+    }
+
+    /// Soft-delete an output tag and its mappings
+    pub fn delete_output_tag(&self, user_id: i64, tag: &str) -> Result<(), StorageError> {
+        basket_tag_label_ops::delete_output_tag(&self.conn, user_id, tag)
+    }
+
+    /// Rename a tx label, merging into an existing label of the new name
+    pub fn rename_tx_label(&self, user_id: i64, label: &str, new_label: &str) -> Result<TableTxLabel, StorageError> {
+        basket_tag_label_ops::rename_tx_label(&self.conn, user_id, label, new_label)
+    }
+
+    /// Soft-delete a tx label and its mappings
+    pub fn delete_tx_label(&self, user_id: i64, label: &str) -> Result<(), StorageError> {
+        basket_tag_label_ops::delete_tx_label(&self.conn, user_id, label)
+    }
+
+    /// Number of outputs carrying each of the user's tags
+    pub fn get_output_tag_usages(&self, user_id: i64) -> Result<Vec<OutputTagUsage>, StorageError> {
+        basket_tag_label_ops::get_output_tag_usages(&self.conn, user_id)
+    }
+
     /// Insert certificate
     pub fn insert_certificate(&self, cert: &TableCertificate) -> Result<i64, StorageError> {
         cert_commission_ops::insert_certificate(&self.conn, cert)
     }
 
+    /// The user's cached identity lookup `query`
+    pub fn find_identity_cache(&self, user_id: i64, query: &str) -> Result<Option<TableIdentityCache>, StorageError> {
+        cert_commission_ops::find_identity_cache(&self.conn, user_id, query)
+    }
+
+    /// Insert or replace a cached identity lookup
+    pub fn upsert_identity_cache(&self, entry: &TableIdentityCache) -> Result<i64, StorageError> {
+        cert_commission_ops::upsert_identity_cache(&self.conn, entry)
+    }
+
     /// Find certificate by id
     pub fn find_certificate_by_id(&self, cert_id: i64) -> Result<Option<TableCertificate>, StorageError> {
         cert_commission_ops::find_certificate_by_id(&self.conn, cert_id)
@@ -325,7 +705,11 @@ impl StorageSqlite {
 
     /// Insert certificate field
     pub fn insert_certificate_field(&self, field: &TableCertificateField) -> Result<(), StorageError> {
-        cert_commission_ops::insert_certificate_field(&self.conn, field)
+        self.write_within_quota(|conn, policy| {
+            let added = UserUsage::certificate_field(&field.field_value, &field.master_key);
+            quota_ops::check_quota(conn, policy, field.user_id, &added)?;
+            cert_commission_ops::insert_certificate_field_row(conn, field)
+        })
     }
 
     /// Find certificate fields
@@ -353,11 +737,54 @@ impl StorageSqlite {
         cert_commission_ops::find_sync_state_by_ref(&self.conn, ref_num)
     }
 
+    /// Find sync states
+    pub fn find_sync_states_internal(&self, args: &FindSyncStatesArgs) -> Result<Vec<TableSyncState>, StorageError> {
+        cert_commission_ops::find_sync_states(&self.conn, args)
+    }
+
+    /// Update sync state
+    pub fn update_sync_state_internal(&self, sync_state: &TableSyncState) -> Result<(), StorageError> {
+        cert_commission_ops::update_sync_state(&self.conn, sync_state)
+    }
+
     /// Insert monitor event
     pub fn insert_monitor_event(&self, event: &TableMonitorEvent) -> Result<i64, StorageError> {
         cert_commission_ops::insert_monitor_event(&self.conn, event)
     }
 
+    /// Find monitor events
+    pub fn find_monitor_events(&self, args: &FindMonitorEventsArgs) -> Result<Vec<TableMonitorEvent>, StorageError> {
+        cert_commission_ops::find_monitor_events(&self.conn, args)
+    }
+
+    /// Dump everything the user with `identity_key` owns, for restoring
+    /// into a store on another device
+    pub fn export_user_dump(&self, identity_key: &str) -> Result<StorageDump, StorageError> {
+        dump_ops::export_user(&self.conn, identity_key, self.loaded_settings()?)
+    }
+
+    /// Write the dump of the user with `identity_key` to `path`
+    ///
+    /// The file is plain JSON even when this database is encrypted.
+    pub fn export_user_to_file<P: AsRef<Path>>(&self, identity_key: &str, path: P) -> Result<(), StorageError> {
+        self.export_user_dump(identity_key)?.write_to_file(path)
+    }
+
+    /// Restore a dump into this store, which must not have any users yet
+    pub fn import_dump(&self, dump: &StorageDump) -> Result<(), StorageError> {
+        dump_ops::import_dump(&self.conn, dump, self.loaded_settings()?)
+    }
+
+    /// Restore the dump written to `path` by `export_user_to_file`
+    pub fn import_from_file<P: AsRef<Path>>(&self, path: P) -> Result<(), StorageError> {
+        self.import_dump(&StorageDump::read_from_file(path)?)
+    }
+
+    fn loaded_settings(&self) -> Result<&TableSettings, StorageError> {
+        self.settings.as_ref()
+            .ok_or_else(|| StorageError::Database("Settings not loaded".to_string()))
+    }
+
     /// Find or insert user (upsert operation)
     pub fn find_or_insert_user_internal(&self, identity_key: &str) -> Result<FindOrInsertUserResult, StorageError> {
         // Try to find existing user
@@ -397,35 +824,81 @@ impl WalletStorageReader for StorageSqlite {
         self.settings.as_ref().expect("Settings not loaded")
     }
 
+    fn get_fee_model(&self) -> StorageFeeModel {
+        self.fee_model.clone()
+    }
+
+    fn get_change_baskets(&self) -> StorageChangeBaskets {
+        self.change_baskets.clone()
+    }
+
+    async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> {
+        self.find_user_by_identity(identity_key)
+    }
+
     async fn find_certificates_auth(
         &self,
-        _auth: &AuthId,
-        _args: &FindCertificatesArgs,
+        auth: &AuthId,
+        args: &FindCertificatesArgs,
     ) -> StorageResult<Vec<TableCertificate>> {
-        Err(StorageError::NotImplemented("find_certificates_auth"))
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        cert_commission_ops::find_certificates(&self.conn, args)
+    }
+
+    async fn count_certificates_auth(
+        &self,
+        auth: &AuthId,
+        args: &FindCertificatesArgs,
+    ) -> StorageResult<i64> {
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        cert_commission_ops::count_certificates(&self.conn, args)
+    }
+
+    async fn find_certificate_fields_auth(
+        &self,
+        auth: &AuthId,
+        certificate_id: i64,
+    ) -> StorageResult<Vec<TableCertificateField>> {
+        self.validate_auth(auth).await?;
+        let certificate = cert_commission_ops::find_certificate_by_id(&self.conn, certificate_id)?
+            .ok_or_else(|| StorageError::NotFound(format!("certificate {}", certificate_id)))?;
+        verify_owned(auth, &certificate)?;
+        let fields = cert_commission_ops::find_certificate_fields(&self.conn, certificate_id)?;
+        verify_all_owned(auth, &fields)?;
+        Ok(fields)
     }
 
     async fn find_output_baskets_auth(
         &self,
-        _auth: &AuthId,
-        _args: &FindOutputBasketsArgs,
+        auth: &AuthId,
+        args: &FindOutputBasketsArgs,
     ) -> StorageResult<Vec<TableOutputBasket>> {
-        Err(StorageError::NotImplemented("find_output_baskets_auth"))
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        let baskets = basket_tag_label_ops::find_output_baskets(&self.conn, args)?;
+        verify_all_owned(auth, &baskets)?;
+        Ok(baskets)
     }
 
     async fn find_outputs_auth(
         &self,
-        _auth: &AuthId,
-        _args: &FindOutputsArgs,
+        auth: &AuthId,
+        args: &FindOutputsArgs,
     ) -> StorageResult<Vec<TableOutput>> {
-        Err(StorageError::NotImplemented("find_outputs_auth"))
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        let outputs = output_ops::find_outputs(&self.conn, args)?;
+        verify_all_owned(auth, &outputs)?;
+        Ok(outputs)
     }
 
     async fn find_proven_tx_reqs(
         &self,
-        _args: &FindProvenTxReqsArgs,
+        args: &FindProvenTxReqsArgs,
     ) -> StorageResult<Vec<TableProvenTxReq>> {
-        Err(StorageError::NotImplemented("find_proven_tx_reqs"))
+        proven_tx_ops::find_proven_tx_reqs(&self.conn, args)
     }
 }
 
@@ -433,7 +906,15 @@ impl WalletStorageReader for StorageSqlite {
 impl WalletStorageWriter for StorageSqlite {
     async fn make_available(&mut self) -> StorageResult<TableSettings> {
         if !self.is_available() {
-            return Err(StorageError::Database("Storage not initialized".to_string()));
+            self.unlock()?;
+            {
+                let conn = self.conn.lock().unwrap();
+                if !is_initialized(&conn)? {
+                    return Err(StorageError::Database("Storage not initialized".to_string()));
+                }
+                migrate_schema(&conn, self.read_only)?;
+            }
+            self.load_settings()?;
         }
         Ok(self.get_settings().clone())
     }
@@ -443,11 +924,27 @@ impl WalletStorageWriter for StorageSqlite {
         _storage_name: &str,
         _storage_identity_key: &str,
     ) -> StorageResult<String> {
-        Err(StorageError::NotImplemented("migrate"))
+        self.unlock()?;
+        let version = {
+            let conn = self.conn.lock().unwrap();
+            if !is_initialized(&conn)? {
+                return Err(StorageError::Database("Storage not initialized".to_string()));
+            }
+            migrate_schema(&conn, self.read_only)?;
+            current_version(&conn)?.unwrap_or_default()
+        };
+        self.load_settings()?;
+        Ok(version)
     }
 
+    /// Drops every table and its data by reverting all migrations
+    ///
+    /// Reference: TS StorageKnex.dropAllData
     async fn destroy(&mut self) -> StorageResult<()> {
-        Err(StorageError::NotImplemented("destroy"))
+        self.unlock()?;
+        rollback_migrations(&self.conn.lock().unwrap(), MIGRATIONS.len())?;
+        self.settings = None;
+        Ok(())
     }
 
     async fn find_or_insert_user(
@@ -459,10 +956,25 @@ impl WalletStorageWriter for StorageSqlite {
 
     async fn insert_certificate_auth(
         &mut self,
-        _auth: &AuthId,
-        _certificate: &TableCertificate,
+        auth: &AuthId,
+        certificate: &TableCertificate,
     ) -> StorageResult<i64> {
-        Err(StorageError::NotImplemented("insert_certificate_auth"))
+        self.validate_auth(auth).await?;
+        verify_owned(auth, certificate)?;
+        cert_commission_ops::insert_certificate(&self.conn, certificate)
+    }
+
+    async fn insert_certificate_field_auth(
+        &mut self,
+        auth: &AuthId,
+        field: &TableCertificateField,
+    ) -> StorageResult<()> {
+        self.validate_auth(auth).await?;
+        verify_owned(auth, field)?;
+        let certificate = cert_commission_ops::find_certificate_by_id(&self.conn, field.certificate_id)?
+            .ok_or_else(|| StorageError::NotFound(format!("certificate {}", field.certificate_id)))?;
+        verify_owned(auth, &certificate)?;
+        self.insert_certificate_field(field)
     }
 }
 
@@ -470,23 +982,327 @@ impl WalletStorageWriter for StorageSqlite {
 impl WalletStorageSync for StorageSqlite {
     async fn find_or_insert_sync_state_auth(
         &mut self,
-        _auth: &AuthId,
-        _storage_identity_key: &str,
-        _storage_name: &str,
+        auth: &AuthId,
+        storage_identity_key: &str,
+        storage_name: &str,
     ) -> StorageResult<FindOrInsertSyncStateResult> {
-        Err(StorageError::NotImplemented("find_or_insert_sync_state_auth"))
+        let user_id = self.validate_auth(auth).await?;
+        let existing = self.find_sync_states_internal(&FindSyncStatesArgs {
+            user_id: Some(user_id),
+            storage_identity_key: Some(storage_identity_key.to_string()),
+            ..Default::default()
+        })?;
+        if let Some(sync_state) = existing.into_iter().next() {
+            return Ok(FindOrInsertSyncStateResult { sync_state, is_new: false });
+        }
+
+        // One sync state per user and store, so the pair is a unique refNum
+        let ref_num = format!("{}.{}", user_id, storage_identity_key);
+        let sync_map = serde_json::to_string(&SyncMap::new())
+            .map_err(|e| StorageError::Database(format!("Failed to serialize syncMap: {}", e)))?;
+        let mut sync_state = TableSyncState::new(
+            0, user_id, storage_identity_key, storage_name, SyncStatus::Unknown, false, ref_num, sync_map,
+        );
+        sync_state.sync_state_id = self.insert_sync_state(&sync_state)?;
+        Ok(FindOrInsertSyncStateResult { sync_state, is_new: true })
+    }
+
+    async fn update_sync_state(&mut self, sync_state: &TableSyncState) -> StorageResult<()> {
+        self.update_sync_state_internal(sync_state)
+    }
+
+    async fn find_sync_states(&self, args: &FindSyncStatesArgs) -> StorageResult<Vec<TableSyncState>> {
+        self.find_sync_states_internal(args)
     }
 
     async fn set_active(
         &mut self,
-        _auth: &AuthId,
-        _new_active_storage_identity_key: &str,
+        auth: &AuthId,
+        new_active_storage_identity_key: &str,
     ) -> StorageResult<i64> {
-        Err(StorageError::NotImplemented("set_active"))
+        let user_id = self.validate_auth(auth).await?;
+        let mut user = self.find_user_by_id(user_id)?
+            .ok_or_else(|| StorageError::NotFound(format!("user {}", user_id)))?;
+        user.active_storage = new_active_storage_identity_key.to_string();
+        self.update_user(user_id, &user)?;
+        Ok(1)
     }
 }
 
-impl WalletStorageProvider for StorageSqlite {}
+#[async_trait]
+impl WalletStorageProvider for StorageSqlite {
+    async fn count_change_inputs(&self, user_id: i64, basket_id: i64, exclude_sending: bool) -> StorageResult<i64> {
+        output_ops::count_change_inputs(&self.conn, user_id, basket_id, exclude_sending)
+    }
+
+    async fn allocate_change_input(
+        &mut self,
+        user_id: i64,
+        basket_id: i64,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+        exclude_sending: bool,
+        transaction_id: i64,
+    ) -> StorageResult<Option<TableOutput>> {
+        StorageSqlite::allocate_change_input(
+            self,
+            user_id,
+            basket_id,
+            target_satoshis,
+            exact_satoshis,
+            exclude_sending,
+            transaction_id,
+        )
+    }
+
+    async fn verify_known_valid_transaction(&self, txid: &str) -> StorageResult<bool> {
+        let r = proven_tx_ops::get_proven_or_raw_tx(&self.conn, txid)?;
+        Ok(r.proven.is_some() || r.raw_tx.is_some())
+    }
+
+    async fn get_proven_or_raw_tx(&self, txid: &str) -> StorageResult<ProvenOrRawTx> {
+        proven_tx_ops::get_proven_or_raw_tx(&self.conn, txid)
+    }
+
+    async fn insert_proven_tx_req(&mut self, req: &TableProvenTxReq) -> StorageResult<i64> {
+        StorageSqlite::insert_proven_tx_req(self, req)
+    }
+
+    async fn update_proven_tx_req(&mut self, proven_tx_req_id: i64, updates: &ProvenTxReqUpdates) -> StorageResult<()> {
+        proven_tx_ops::update_proven_tx_req_fields(&self.conn, proven_tx_req_id, updates)
+    }
+
+    async fn update_proven_tx_req_with_new_proven_tx(
+        &mut self,
+        args: &UpdateProvenTxReqWithNewProvenTxArgs,
+    ) -> StorageResult<UpdateProvenTxReqWithNewProvenTxResult> {
+        proven_tx_ops::update_proven_tx_req_with_new_proven_tx(&self.conn, args)
+    }
+
+    async fn find_proven_txs_from_height(&self, min_height: i64) -> StorageResult<Vec<TableProvenTx>> {
+        proven_tx_ops::find_proven_txs_from_height(&self.conn, min_height)
+    }
+
+    async fn rollback_proven_tx(&mut self, proven_tx_id: i64) -> StorageResult<Vec<i64>> {
+        proven_tx_ops::rollback_proven_tx(&self.conn, proven_tx_id)
+    }
+
+    async fn update_proven_tx(&mut self, proven_tx_id: i64, updates: &ProvenTxUpdates) -> StorageResult<()> {
+        if StorageSqlite::update_proven_tx(self, proven_tx_id, updates)? == 0 {
+            return Err(StorageError::NotFound(format!("proven_tx {}", proven_tx_id)));
+        }
+        Ok(())
+    }
+
+    async fn get_raw_tx_of_known_valid_transaction(
+        &self,
+        txid: &str,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> StorageResult<Option<Vec<u8>>> {
+        let r = proven_tx_ops::get_proven_or_raw_tx(&self.conn, txid)?;
+        let Some(raw_tx) = r.proven.map(|p| p.raw_tx).or(r.raw_tx) else {
+            return Ok(None);
+        };
+        let start = offset.unwrap_or(0).min(raw_tx.len());
+        let end = length.map_or(raw_tx.len(), |length| (start + length).min(raw_tx.len()));
+        Ok(Some(raw_tx[start..end].to_vec()))
+    }
+
+    async fn find_transactions(
+        &self,
+        user_id: i64,
+        reference: Option<&str>,
+        status: Option<TransactionStatus>,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        transaction_ops::find_transactions(&self.conn, user_id, reference, status)
+    }
+
+    async fn find_transaction_by_id(&self, transaction_id: i64) -> StorageResult<Option<TableTransaction>> {
+        StorageSqlite::find_transaction_by_id(self, transaction_id)
+    }
+
+    async fn find_aged_transactions(
+        &self,
+        statuses: &[TransactionStatus],
+        updated_before: &str,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        transaction_ops::find_aged_transactions(&self.conn, statuses, updated_before)
+    }
+
+    async fn find_outputs_by_transaction(
+        &self,
+        user_id: i64,
+        transaction_id: i64,
+        is_input: bool,
+    ) -> StorageResult<Vec<TableOutput>> {
+        output_ops::find_outputs_by_transaction(&self.conn, user_id, transaction_id, is_input)
+    }
+
+    async fn insert_transaction(&mut self, tx: &TableTransaction) -> StorageResult<i64> {
+        StorageSqlite::insert_transaction(self, tx.user_id, tx)
+    }
+
+    async fn update_transaction(&mut self, transaction_id: i64, satoshis: i64) -> StorageResult<()> {
+        transaction_ops::update_transaction_satoshis(&self.conn, transaction_id, satoshis)
+    }
+
+    async fn update_transaction_status(&mut self, transaction_id: i64, status: TransactionStatus) -> StorageResult<()> {
+        transaction_ops::update_transaction_status(&self.conn, transaction_id, status)
+    }
+
+    async fn update_transaction_status_if_unchanged(
+        &mut self,
+        transaction_id: i64,
+        status: TransactionStatus,
+        expected_updated_at: &str,
+    ) -> StorageResult<()> {
+        StorageSqlite::update_transaction_status_if_unchanged(self, transaction_id, status, expected_updated_at)
+    }
+
+    async fn update_transaction_txid(&mut self, transaction_id: i64, txid: &str) -> StorageResult<()> {
+        transaction_ops::update_transaction_txid(&self.conn, transaction_id, txid)
+    }
+
+    async fn update_transaction_raw_tx(&mut self, transaction_id: i64, raw_tx: &[u8]) -> StorageResult<()> {
+        transaction_ops::update_transaction_raw_tx(&self.conn, transaction_id, raw_tx)
+    }
+
+    async fn insert_output(&mut self, output: &TableOutput) -> StorageResult<i64> {
+        StorageSqlite::insert_output(self, output)
+    }
+
+    async fn update_output(&mut self, output_id: i64, updates: &OutputUpdates) -> StorageResult<()> {
+        output_ops::update_output_fields(&self.conn, output_id, updates)
+    }
+
+    async fn insert_commission(&mut self, commission: &TableCommission) -> StorageResult<i64> {
+        StorageSqlite::insert_commission(self, commission)
+    }
+
+    async fn get_balance(&self, user_id: i64) -> StorageResult<i64> {
+        StorageSqlite::get_balance(self, user_id)
+    }
+
+    async fn get_basket_balances(&self, user_id: i64) -> StorageResult<Vec<BasketBalance>> {
+        StorageSqlite::get_basket_balances(self, user_id)
+    }
+
+    async fn find_or_insert_output_basket(&mut self, user_id: i64, name: &str) -> StorageResult<TableOutputBasket> {
+        basket_tag_label_ops::find_or_insert_output_basket(&self.conn, user_id, name)
+    }
+
+    async fn update_output_basket(&mut self, basket_id: i64, updates: &OutputBasketUpdates) -> StorageResult<()> {
+        basket_tag_label_ops::update_output_basket_fields(&self.conn, basket_id, updates)
+    }
+
+    async fn find_or_insert_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<TableOutputTag> {
+        StorageSqlite::find_or_insert_output_tags(self, user_id, &[tag.to_string()])?
+            .pop()
+            .ok_or_else(|| StorageError::Database(format!("output tag {} not stored", tag)))
+    }
+
+    async fn find_or_insert_output_tag_map(&mut self, output_id: i64, output_tag_id: i64) -> StorageResult<()> {
+        basket_tag_label_ops::find_or_insert_output_tag_map(&self.conn, output_id, output_tag_id)
+    }
+
+    async fn find_or_insert_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<TableTxLabel> {
+        StorageSqlite::find_or_insert_tx_labels(self, user_id, &[label.to_string()])?
+            .pop()
+            .ok_or_else(|| StorageError::Database(format!("tx label {} not stored", label)))
+    }
+
+    async fn find_or_insert_tx_label_map(&mut self, transaction_id: i64, tx_label_id: i64) -> StorageResult<()> {
+        basket_tag_label_ops::find_or_insert_tx_label_map(&self.conn, transaction_id, tx_label_id)
+    }
+
+    async fn find_or_insert_output_tags(&mut self, user_id: i64, tags: &[String]) -> StorageResult<Vec<TableOutputTag>> {
+        StorageSqlite::find_or_insert_output_tags(self, user_id, tags)
+    }
+
+    async fn find_or_insert_tx_labels(&mut self, user_id: i64, labels: &[String]) -> StorageResult<Vec<TableTxLabel>> {
+        StorageSqlite::find_or_insert_tx_labels(self, user_id, labels)
+    }
+
+    async fn find_tx_labels_for_transaction(&self, transaction_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        StorageSqlite::find_tx_labels_for_transaction(self, transaction_id)
+    }
+
+    async fn find_output_tags(&self, user_id: i64) -> StorageResult<Vec<TableOutputTag>> {
+        StorageSqlite::find_output_tags(self, user_id)
+    }
+
+    async fn find_tx_labels(&self, user_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        StorageSqlite::find_tx_labels(self, user_id)
+    }
+
+    async fn rename_output_tag(&mut self, user_id: i64, tag: &str, new_tag: &str) -> StorageResult<TableOutputTag> {
+        StorageSqlite::rename_output_tag(self, user_id, tag, new_tag)
+    }
+
+    async fn delete_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<()> {
+        StorageSqlite::delete_output_tag(self, user_id, tag)
+    }
+
+    async fn rename_tx_label(&mut self, user_id: i64, label: &str, new_label: &str) -> StorageResult<TableTxLabel> {
+        StorageSqlite::rename_tx_label(self, user_id, label, new_label)
+    }
+
+    async fn delete_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<()> {
+        StorageSqlite::delete_tx_label(self, user_id, label)
+    }
+
+    async fn get_output_tag_usages(&self, user_id: i64) -> StorageResult<Vec<OutputTagUsage>> {
+        StorageSqlite::get_output_tag_usages(self, user_id)
+    }
+
+    async fn get_user_usage(&self, user_id: i64) -> StorageResult<UserUsage> {
+        StorageSqlite::get_user_usage(self, user_id)
+    }
+
+    async fn find_identity_cache(&self, user_id: i64, query: &str) -> StorageResult<Option<TableIdentityCache>> {
+        StorageSqlite::find_identity_cache(self, user_id, query)
+    }
+
+    async fn upsert_identity_cache(&mut self, entry: &TableIdentityCache) -> StorageResult<i64> {
+        StorageSqlite::upsert_identity_cache(self, entry)
+    }
+
+    async fn lock_output(
+        &mut self,
+        user_id: i64,
+        txid: &str,
+        vout: u32,
+        reason: &str,
+        ttl: Option<Duration>,
+    ) -> StorageResult<TableOutputLock> {
+        StorageSqlite::lock_output(self, user_id, txid, vout, reason, ttl)
+    }
+
+    async fn unlock_output(&mut self, user_id: i64, txid: &str, vout: u32) -> StorageResult<bool> {
+        StorageSqlite::unlock_output(self, user_id, txid, vout)
+    }
+
+    async fn find_output_lock(&self, output_id: i64) -> StorageResult<Option<TableOutputLock>> {
+        StorageSqlite::find_output_lock(self, output_id)
+    }
+
+    async fn find_output_locks(&self, user_id: i64) -> StorageResult<Vec<TableOutputLock>> {
+        StorageSqlite::find_output_locks(self, user_id)
+    }
+
+    async fn insert_monitor_event(&mut self, event: &TableMonitorEvent) -> StorageResult<i64> {
+        StorageSqlite::insert_monitor_event(self, event)
+    }
+
+    async fn find_monitor_events(&self, args: &FindMonitorEventsArgs) -> StorageResult<Vec<TableMonitorEvent>> {
+        StorageSqlite::find_monitor_events(self, args)
+    }
+
+    async fn purge_data(&mut self, params: &PurgeParams) -> StorageResult<PurgeResults> {
+        StorageSqlite::purge_data(self, params)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -506,6 +1322,30 @@ mod tests {
         assert!(storage.is_available());
     }
 
+    #[test]
+    fn test_initialize_on_other_chain() {
+        let mut storage = create_test_storage();
+        let err = storage.initialize("test_storage_key", "Test Storage", "test", 100000).unwrap_err();
+        assert!(matches!(err, StorageError::NetworkChain(_)));
+        assert!(storage.initialize("test_storage_key", "Test Storage", "main", 100000).is_ok());
+    }
+
+    #[test]
+    fn test_open_or_create_keeps_identity_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.sqlite");
+
+        let storage = StorageSqlite::open_or_create(&path, "Test Storage", "test").unwrap();
+        let settings = storage.get_settings().clone();
+        assert_eq!(settings.storage_identity_key.len(), 66);
+        assert_eq!(settings.max_output_script, MAX_OUTPUT_SCRIPT);
+        drop(storage);
+
+        let reopened = StorageSqlite::open_or_create(&path, "Other", "test").unwrap();
+        assert_eq!(reopened.get_settings().storage_identity_key, settings.storage_identity_key);
+        assert!(matches!(StorageSqlite::open_or_create(&path, "Other", "main"), Err(StorageError::NetworkChain(_))));
+    }
+
     #[test]
     fn test_get_settings() {
         use wallet_storage::schema::tables::table_settings::{Chain, DbType};
@@ -520,6 +1360,46 @@ mod tests {
         assert_eq!(settings.max_output_script, 100000);
     }
 
+    #[test]
+    fn test_fee_model_configuration() {
+        let mut storage = create_test_storage();
+        assert_eq!(storage.get_fee_model(), StorageFeeModel::default());
+
+        storage.set_fee_model(StorageFeeModel {
+            model: "sat/kb".to_string(),
+            value: Some(50.0),
+        }).unwrap();
+        assert_eq!(storage.get_fee_model().value, Some(50.0));
+    }
+
+    #[test]
+    fn test_fee_model_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.sqlite");
+
+        let mut storage = StorageSqlite::open_or_create(&path, "Test Storage", "test").unwrap();
+        let fee_model = StorageFeeModel {
+            model: "sat/kb".to_string(),
+            value: Some(50.0),
+        };
+        storage.set_fee_model(fee_model.clone()).unwrap();
+        drop(storage);
+
+        let reopened = StorageSqlite::open_or_create(&path, "Test Storage", "test").unwrap();
+        assert_eq!(reopened.get_fee_model(), fee_model);
+    }
+
+    #[test]
+    fn test_change_baskets_configuration() {
+        let mut storage = create_test_storage();
+        assert_eq!(storage.get_change_baskets().for_originator(Some("app.example.com")), "default");
+
+        let mut change_baskets = StorageChangeBaskets::default();
+        change_baskets.by_originator.insert("app.example.com".to_string(), "app change".to_string());
+        storage.set_change_baskets(change_baskets);
+        assert_eq!(storage.get_change_baskets().for_originator(Some("app.example.com")), "app change");
+    }
+
     #[test]
     fn test_insert_and_find_user() {
         let storage = create_test_storage();
@@ -589,6 +1469,100 @@ mod tests {
         assert_eq!(updated.active_storage, "storage_2");
     }
 
+    #[test]
+    fn test_quota_policy_enforced() {
+        let mut storage = create_test_storage();
+        storage.set_quota_policy(QuotaPolicy { max_transactions: Some(1), max_beef_bytes: Some(10), ..Default::default() });
+        let user_id = storage.insert_user("user_key", "storage_1").unwrap();
+
+        let mut tx = TableTransaction::new(0, user_id, TransactionStatus::Completed, "ref1", false, 0, "first");
+        tx.input_beef = Some(vec![0; 8]);
+        let tx_id = storage.insert_transaction(user_id, &tx).unwrap();
+
+        tx.reference = "ref2".to_string();
+        assert!(matches!(
+            storage.insert_transaction(user_id, &tx),
+            Err(StorageError::QuotaExceeded { resource: QuotaResource::Transactions, .. })
+        ));
+
+        tx.input_beef = Some(vec![0; 11]);
+        assert!(matches!(
+            storage.update_transaction(tx_id, &tx),
+            Err(StorageError::QuotaExceeded { resource: QuotaResource::BeefBytes, limit: 10, requested: 11, .. })
+        ));
+        tx.input_beef = None;
+        storage.update_transaction(tx_id, &tx).unwrap();
+
+        let usage = storage.get_user_usage(user_id).unwrap();
+        assert_eq!((usage.transactions, usage.beef_bytes), (1, 0));
+    }
+
+    #[test]
+    fn test_quota_holds_under_concurrent_inserts() {
+        let mut storage = create_test_storage();
+        storage.set_quota_policy(QuotaPolicy { max_outputs: Some(4), ..Default::default() });
+        let user_id = storage.insert_user("user_key", "storage_1").unwrap();
+        let tx = TableTransaction::new(0, user_id, TransactionStatus::Completed, "ref1", false, 0, "outputs");
+        let tx_id = storage.insert_transaction(user_id, &tx).unwrap();
+
+        let storage = Arc::new(storage);
+        let inserted: usize = (0..16)
+            .map(|vout| {
+                let storage = storage.clone();
+                std::thread::spawn(move || {
+                    let output = TableOutput::new(
+                        0, user_id, tx_id, true, false, "test", vout, 1, StorageProvidedBy::You, "", "P2PKH",
+                    );
+                    storage.insert_output(&output).is_ok()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|&ok| ok)
+            .count();
+
+        assert_eq!(inserted, 4);
+        assert_eq!(storage.get_user_usage(user_id).unwrap().outputs, 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.sqlite");
+        assert!(StorageSqlite::new_read_only(&path).is_err());
+
+        let mut writer = StorageSqlite::new(&path).unwrap();
+        writer.initialize("test_storage_key", "Test Storage", "main", 100000).unwrap();
+        let user_id = writer.insert_user("user_key", "test_storage_key").unwrap();
+
+        let mut storage = StorageSqlite::new_read_only(&path).unwrap();
+        assert!(storage.is_read_only());
+        assert_eq!(storage.make_available().await.unwrap().storage_identity_key, "test_storage_key");
+        assert!(storage.insert_user("other_key", "test_storage_key").is_err());
+
+        // Reads see the writer's later commits
+        writer.insert_user("other_key", "test_storage_key").unwrap();
+        let reader = ReadOnly::new(storage);
+        assert!(reader.find_user_by_identity_key("other_key").await.unwrap().is_some());
+        let auth = AuthId { identity_key: "user_key".to_string(), user_id: Some(user_id), is_active: None };
+        let args = FindOutputsArgs {
+            user_id,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            no_script: None,
+            tx_status: None,
+        };
+        assert!(reader.find_outputs_auth(&auth, &args).await.unwrap().is_empty());
+
+        crate::migrations::rollback_migrations(&writer.conn.lock().unwrap(), 1).unwrap();
+        let mut storage = StorageSqlite::new_read_only(&path).unwrap();
+        let err = storage.make_available().await.unwrap_err();
+        assert!(err.to_string().contains("open it for writing first"));
+    }
+
     #[tokio::test]
     async fn test_async_trait_methods() {
         let mut storage = create_test_storage();
@@ -601,4 +1575,252 @@ mod tests {
         let result = storage.find_or_insert_user("async_user").await.unwrap();
         assert!(result.is_new);
     }
+
+    #[tokio::test]
+    async fn test_provider_action_lifecycle() {
+        let mut sqlite = create_test_storage();
+        let storage: &mut dyn WalletStorageProvider = &mut sqlite;
+        let user_id = storage.find_or_insert_user("user_key").await.unwrap().user.user_id;
+        let basket = storage.find_or_insert_output_basket(user_id, "default").await.unwrap();
+        assert_eq!(storage.find_or_insert_output_basket(user_id, "default").await.unwrap().basket_id, basket.basket_id);
+        let updates = OutputBasketUpdates { number_of_desired_utxos: Some(6), minimum_desired_utxo_value: Some(10000) };
+        storage.update_output_basket(basket.basket_id, &updates).await.unwrap();
+
+        // A funding transaction with two change outputs
+        let funding = TableTransaction::new(0, user_id, TransactionStatus::Unproven, "fund", false, 3000, "funding")
+            .with_txid("aa".repeat(32));
+        let funding_id = storage.insert_transaction(&funding).await.unwrap();
+        for (vout, satoshis) in [(0, 1000), (1, 2000)] {
+            let output = TableOutput::new(
+                0, user_id, funding_id, true, true, "change", vout, satoshis, StorageProvidedBy::Storage, "change", "P2PKH",
+            )
+            .with_basket_id(basket.basket_id)
+            .with_txid("aa".repeat(32));
+            storage.insert_output(&output).await.unwrap();
+        }
+        assert_eq!(storage.count_change_inputs(user_id, basket.basket_id, true).await.unwrap(), 2);
+        assert_eq!(storage.get_balance(user_id).await.unwrap(), 3000);
+
+        // An action spending the smallest covering output
+        let action = TableTransaction::new(0, user_id, TransactionStatus::Unsigned, "act", true, 0, "action");
+        let action_id = storage.insert_transaction(&action).await.unwrap();
+        let input = storage
+            .allocate_change_input(user_id, basket.basket_id, 1500, None, true, action_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(input.satoshis, 2000);
+        assert_eq!(storage.count_change_inputs(user_id, basket.basket_id, true).await.unwrap(), 1);
+        let inputs = storage.find_outputs_by_transaction(user_id, action_id, true).await.unwrap();
+        assert_eq!(inputs.iter().map(|o| o.output_id).collect::<Vec<_>>(), vec![input.output_id]);
+        let updates = OutputUpdates { spending_description: Some("spent".to_string()), ..Default::default() };
+        storage.update_output(input.output_id, &updates).await.unwrap();
+        assert!(matches!(storage.update_output(99, &updates).await, Err(StorageError::NotFound(_))));
+
+        storage.update_transaction(action_id, -1500).await.unwrap();
+        storage.update_transaction_txid(action_id, &"bb".repeat(32)).await.unwrap();
+        storage.update_transaction_raw_tx(action_id, &[1, 2, 3, 4]).await.unwrap();
+        storage.update_transaction_status(action_id, TransactionStatus::Unproven).await.unwrap();
+        let action = storage.find_transaction_by_id(action_id).await.unwrap().unwrap();
+        assert_eq!((action.satoshis, action.status), (-1500, TransactionStatus::Unproven));
+        assert_eq!(storage.find_transactions(user_id, Some("act"), None).await.unwrap().len(), 1);
+        assert_eq!(storage.find_transactions(user_id, None, Some(TransactionStatus::Unproven)).await.unwrap().len(), 2);
+
+        let label = storage.find_or_insert_tx_label(user_id, "payment").await.unwrap();
+        storage.find_or_insert_tx_label_map(action_id, label.tx_label_id).await.unwrap();
+        storage.find_or_insert_tx_label_map(action_id, label.tx_label_id).await.unwrap();
+        assert_eq!(storage.find_tx_labels_for_transaction(action_id).await.unwrap(), vec![label]);
+        let tag = storage.find_or_insert_output_tag(user_id, "coin").await.unwrap();
+        storage.find_or_insert_output_tag_map(input.output_id, tag.output_tag_id).await.unwrap();
+        assert_eq!(storage.get_output_tag_usages(user_id).await.unwrap()[0].output_count, 1);
+
+        // Its proof arrives, and is then rolled back by a reorg
+        let notify = format!(r#"{{"transactionIds":[{}]}}"#, action_id);
+        let req = TableProvenTxReq::new(0, ProvenTxReqStatus::Unmined, "bb".repeat(32), "{}", notify, vec![1, 2, 3, 4]);
+        let req_id = storage.insert_proven_tx_req(&req).await.unwrap();
+        let updates = ProvenTxReqUpdates { attempts: Some(2), ..Default::default() };
+        storage.update_proven_tx_req(req_id, &updates).await.unwrap();
+        assert!(storage.verify_known_valid_transaction(&"bb".repeat(32)).await.unwrap());
+        assert_eq!(
+            storage.get_raw_tx_of_known_valid_transaction(&"bb".repeat(32), Some(1), Some(2)).await.unwrap(),
+            Some(vec![2, 3])
+        );
+        let args = UpdateProvenTxReqWithNewProvenTxArgs {
+            proven_tx_req_id: req_id,
+            txid: "bb".repeat(32),
+            attempts: 3,
+            status: ProvenTxReqStatus::Completed,
+            history: "{}".to_string(),
+            height: 900000,
+            index: 0,
+            block_hash: "cc".repeat(32),
+            merkle_root: "dd".repeat(32),
+            merkle_path: vec![0],
+        };
+        let proven = storage.update_proven_tx_req_with_new_proven_tx(&args).await.unwrap();
+        let action = storage.find_transaction_by_id(action_id).await.unwrap().unwrap();
+        assert_eq!((action.status, action.proven_tx_id), (TransactionStatus::Completed, Some(proven.proven_tx_id)));
+        let r = storage.get_proven_or_raw_tx(&"bb".repeat(32)).await.unwrap();
+        assert_eq!(r.proven.unwrap().raw_tx, vec![1, 2, 3, 4]);
+        assert_eq!(storage.find_proven_txs_from_height(900000).await.unwrap().len(), 1);
+
+        assert_eq!(storage.rollback_proven_tx(proven.proven_tx_id).await.unwrap(), vec![action_id]);
+        let action = storage.find_transaction_by_id(action_id).await.unwrap().unwrap();
+        assert_eq!((action.status, action.proven_tx_id), (TransactionStatus::Unproven, None));
+        assert!(storage.find_proven_txs_from_height(0).await.unwrap().is_empty());
+        assert!(matches!(storage.rollback_proven_tx(proven.proven_tx_id).await, Err(StorageError::NotFound(_))));
+
+        let later = (chrono::Utc::now() + chrono::Duration::minutes(1)).to_rfc3339();
+        let aged = storage.find_aged_transactions(&[TransactionStatus::Unproven], &later).await.unwrap();
+        assert_eq!(aged.len(), 2);
+        let earlier = (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339();
+        assert!(storage.find_aged_transactions(&[TransactionStatus::Unproven], &earlier).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_action_releases_inputs() {
+        let mut sqlite = create_test_storage();
+        let storage: &mut dyn WalletStorageProvider = &mut sqlite;
+        let user_id = storage.find_or_insert_user("user_key").await.unwrap().user.user_id;
+        let basket = storage.find_or_insert_output_basket(user_id, "default").await.unwrap();
+        let funding = TableTransaction::new(0, user_id, TransactionStatus::Completed, "fund", false, 1000, "funding")
+            .with_txid("aa".repeat(32));
+        let funding_id = storage.insert_transaction(&funding).await.unwrap();
+        let output = TableOutput::new(
+            0, user_id, funding_id, true, true, "change", 0, 1000, StorageProvidedBy::Storage, "change", "P2PKH",
+        )
+        .with_basket_id(basket.basket_id)
+        .with_txid("aa".repeat(32));
+        let output_id = storage.insert_output(&output).await.unwrap();
+
+        // The first action takes the only output, then fails
+        let failed = TableTransaction::new(0, user_id, TransactionStatus::Unsigned, "failed", true, 0, "failed");
+        let failed_id = storage.insert_transaction(&failed).await.unwrap();
+        let input = storage.allocate_change_input(user_id, basket.basket_id, 500, None, true, failed_id).await.unwrap();
+        assert_eq!(input.unwrap().output_id, output_id);
+        storage.update_transaction_status(failed_id, TransactionStatus::Failed).await.unwrap();
+        let updates = OutputUpdates { spendable: Some(true), spent_by: Some(None), ..Default::default() };
+        storage.update_output(output_id, &updates).await.unwrap();
+
+        assert!(storage.find_outputs_by_transaction(user_id, failed_id, true).await.unwrap().is_empty());
+        let released = output_ops::find_output_by_id(&sqlite.conn, output_id, true).unwrap().unwrap();
+        assert_eq!((released.spendable, released.spent_by), (true, None));
+        assert_eq!(output_ops::find_spendable_outputs_for_user(&sqlite.conn, user_id, None, None).unwrap().len(), 1);
+
+        // A second action can select it again
+        let storage: &mut dyn WalletStorageProvider = &mut sqlite;
+        let retry = TableTransaction::new(0, user_id, TransactionStatus::Unsigned, "retry", true, 0, "retry");
+        let retry_id = storage.insert_transaction(&retry).await.unwrap();
+        let input = storage.allocate_change_input(user_id, basket.basket_id, 500, None, true, retry_id).await.unwrap();
+        assert_eq!(input.unwrap().spent_by, Some(retry_id));
+    }
+
+    #[tokio::test]
+    async fn test_find_output_baskets_auth() {
+        let mut storage = create_test_storage();
+        let user_id = storage.find_or_insert_user("user_key").await.unwrap().user.user_id;
+        let other_id = storage.find_or_insert_user("other_key").await.unwrap().user.user_id;
+        for name in ["default", "tokens", "savings"] {
+            storage.find_or_insert_output_basket(user_id, name).await.unwrap();
+        }
+        storage.find_or_insert_output_basket(other_id, "default").await.unwrap();
+
+        let auth = AuthId { identity_key: "user_key".to_string(), user_id: Some(user_id), is_active: None };
+        let args = |name: Option<&str>, paged: Option<Paged>| FindOutputBasketsArgs {
+            user_id,
+            since: None,
+            paged,
+            name: name.map(str::to_string),
+        };
+        let names = |baskets: Vec<TableOutputBasket>| baskets.into_iter().map(|b| b.name).collect::<Vec<_>>();
+
+        let all = storage.find_output_baskets_auth(&auth, &args(None, None)).await.unwrap();
+        assert_eq!(names(all), vec!["default", "tokens", "savings"]);
+        let named = storage.find_output_baskets_auth(&auth, &args(Some("tokens"), None)).await.unwrap();
+        assert_eq!(names(named), vec!["tokens"]);
+        let page = storage.find_output_baskets_auth(&auth, &args(None, Some(Paged { limit: 1, offset: Some(1) }))).await.unwrap();
+        assert_eq!(names(page), vec!["tokens"]);
+
+        let other_user = FindOutputBasketsArgs { user_id: other_id, ..args(None, None) };
+        assert!(storage.find_output_baskets_auth(&auth, &other_user).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_destroy_drops_all_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.sqlite");
+        let mut storage = StorageSqlite::open_or_create(&path, "Test Storage", "test").unwrap();
+        storage.find_or_insert_user("user_key").await.unwrap();
+
+        storage.destroy().await.unwrap();
+        assert!(!storage.is_available());
+        assert!(storage.make_available().await.is_err());
+        drop(storage);
+
+        let mut reopened = StorageSqlite::open_or_create(&path, "Test Storage", "test").unwrap();
+        assert!(reopened.find_or_insert_user("user_key").await.unwrap().is_new);
+    }
+
+    fn certificates_args(user_id: i64) -> FindCertificatesArgs {
+        FindCertificatesArgs {
+            user_id,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            certifiers: None,
+            types: None,
+            include_fields: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_scoping_rejects_cross_user_access() {
+        let mut storage = create_test_storage();
+        let alice_id = storage.find_or_insert_user("alice").await.unwrap().user.user_id;
+        let bob_id = storage.find_or_insert_user("bob").await.unwrap().user.user_id;
+        let alice = AuthId::new("alice").with_user_id(alice_id);
+        let bob = AuthId::new("bob").with_user_id(bob_id);
+
+        let certificate = TableCertificate::new(0, bob_id, "type", "serial", "certifier", "subject", "outpoint", "sig");
+        let certificate_id = storage.insert_certificate_auth(&bob, &certificate).await.unwrap();
+        storage
+            .insert_certificate_field_auth(&bob, &TableCertificateField::new(bob_id, certificate_id, "name", "Bob", "mk"))
+            .await
+            .unwrap();
+        assert_eq!(storage.find_certificates_auth(&bob, &certificates_args(bob_id)).await.unwrap().len(), 1);
+        assert_eq!(storage.find_certificate_fields_auth(&bob, certificate_id).await.unwrap().len(), 1);
+
+        let unauthorized = |r: StorageResult<()>| matches!(r, Err(StorageError::Unauthorized(_)));
+        let forged = AuthId::new("alice").with_user_id(bob_id);
+
+        // Reads of Bob's records by Alice, by argument or by claiming Bob's user id
+        for auth in [&alice, &forged] {
+            assert!(unauthorized(storage.find_certificates_auth(auth, &certificates_args(bob_id)).await.map(|_| ())));
+            assert!(unauthorized(storage.count_certificates_auth(auth, &certificates_args(bob_id)).await.map(|_| ())));
+            assert!(unauthorized(storage.find_certificate_fields_auth(auth, certificate_id).await.map(|_| ())));
+        }
+        let outputs_args = FindOutputsArgs {
+            user_id: bob_id,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            no_script: None,
+            tx_status: None,
+        };
+        assert!(unauthorized(storage.find_outputs_auth(&alice, &outputs_args).await.map(|_| ())));
+        assert!(unauthorized(storage.find_outputs_auth(&forged, &outputs_args).await.map(|_| ())));
+        assert!(unauthorized(storage.find_outputs_auth(&AuthId::new("").with_user_id(bob_id), &outputs_args).await.map(|_| ())));
+
+        // Writes into Bob's records by Alice
+        let field = TableCertificateField::new(alice_id, certificate_id, "name", "Mallory", "mk");
+        assert!(unauthorized(storage.insert_certificate_field_auth(&alice, &field).await));
+        let certificate = TableCertificate::new(0, bob_id, "type", "serial2", "certifier", "subject", "outpoint", "sig");
+        assert!(unauthorized(storage.insert_certificate_auth(&alice, &certificate).await.map(|_| ())));
+        assert_eq!(storage.find_certificate_fields_auth(&bob, certificate_id).await.unwrap().len(), 1);
+
+        // Alice's own, empty, scope
+        assert!(storage.find_certificates_auth(&alice, &certificates_args(alice_id)).await.unwrap().is_empty());
+    }
 }
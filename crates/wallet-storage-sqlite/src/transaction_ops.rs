@@ -7,14 +7,48 @@ use rusqlite::{Connection, params, OptionalExtension};
 use std::sync::{Arc, Mutex};
 use wallet_storage::*;
 
+use crate::row_version::verify_updated;
+
+/// Transaction columns in `parse_transaction_row` order
+const TRANSACTION_COLUMNS: &str = "created_at, updated_at, transactionId, userId, provenTxId, status, reference,
+    isOutgoing, satoshis, version, lockTime, description, txid, inputBEEF, rawTx";
+
+/// Helper to parse transaction row from database
+fn parse_transaction_row(row: &rusqlite::Row) -> rusqlite::Result<TableTransaction> {
+    Ok(TableTransaction {
+        created_at: row.get(0)?,
+        updated_at: row.get(1)?,
+        transaction_id: row.get(2)?,
+        user_id: row.get(3)?,
+        proven_tx_id: row.get(4)?,
+        status: row.get::<_, String>(5)?.parse().unwrap_or(TransactionStatus::Unprocessed),
+        reference: row.get(6)?,
+        is_outgoing: row.get::<_, i32>(7)? != 0,
+        satoshis: row.get(8)?,
+        version: row.get(9)?,
+        lock_time: row.get(10)?,
+        description: row.get(11)?,
+        txid: row.get(12)?,
+        input_beef: row.get::<_, Option<Vec<u8>>>(13)?,
+        raw_tx: row.get::<_, Option<Vec<u8>>>(14)?,
+    })
+}
+
 /// Insert a new transaction
 pub fn insert_transaction(
     conn: &Arc<Mutex<Connection>>,
     user_id: i64,
     transaction: &TableTransaction,
 ) -> Result<i64, StorageError> {
-    let conn = conn.lock().unwrap();
+    insert_transaction_row(&conn.lock().unwrap(), user_id, transaction)
+}
 
+/// Insert a new transaction on a connection the caller has locked
+pub(crate) fn insert_transaction_row(
+    conn: &Connection,
+    user_id: i64,
+    transaction: &TableTransaction,
+) -> Result<i64, StorageError> {
     conn.execute(
         "INSERT INTO transactions (
             userId, provenTxId, status, reference, isOutgoing, satoshis,
@@ -45,32 +79,18 @@ pub fn find_transaction_by_id(
     conn: &Arc<Mutex<Connection>>,
     transaction_id: i64,
 ) -> Result<Option<TableTransaction>, StorageError> {
-    let conn = conn.lock().unwrap();
+    find_transaction_row(&conn.lock().unwrap(), transaction_id)
+}
 
+/// Find transaction by ID on a connection the caller has locked
+pub(crate) fn find_transaction_row(
+    conn: &Connection,
+    transaction_id: i64,
+) -> Result<Option<TableTransaction>, StorageError> {
     let result = conn.query_row(
-        "SELECT created_at, updated_at, transactionId, userId, provenTxId, status, reference,
-                isOutgoing, satoshis, version, lockTime, description, txid, inputBEEF, rawTx
-         FROM transactions WHERE transactionId = ?1",
+        &format!("SELECT {} FROM transactions WHERE transactionId = ?1", TRANSACTION_COLUMNS),
         params![transaction_id],
-        |row| {
-            Ok(TableTransaction {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                transaction_id: row.get(2)?,
-                user_id: row.get(3)?,
-                proven_tx_id: row.get(4)?,
-                status: row.get::<_, String>(5)?.parse().unwrap_or(TransactionStatus::Unprocessed),
-                reference: row.get(6)?,
-                is_outgoing: row.get::<_, i32>(7)? != 0,
-                satoshis: row.get(8)?,
-                version: row.get(9)?,
-                lock_time: row.get(10)?,
-                description: row.get(11)?,
-                txid: row.get(12)?,
-                input_beef: row.get::<_, Option<Vec<u8>>>(13)?,
-                raw_tx: row.get::<_, Option<Vec<u8>>>(14)?,
-            })
-        },
+        parse_transaction_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find transaction: {}", e)))?;
@@ -86,29 +106,9 @@ pub fn find_transaction_by_reference(
     let conn = conn.lock().unwrap();
 
     let result = conn.query_row(
-        "SELECT created_at, updated_at, transactionId, userId, provenTxId, status, reference,
-                isOutgoing, satoshis, version, lockTime, description, txid, inputBEEF, rawTx
-         FROM transactions WHERE reference = ?1",
+        &format!("SELECT {} FROM transactions WHERE reference = ?1", TRANSACTION_COLUMNS),
         params![reference],
-        |row| {
-            Ok(TableTransaction {
-                created_at: row.get(0)?,
-                updated_at: row.get(1)?,
-                transaction_id: row.get(2)?,
-                user_id: row.get(3)?,
-                proven_tx_id: row.get(4)?,
-                status: row.get::<_, String>(5)?.parse().unwrap_or(TransactionStatus::Unprocessed),
-                reference: row.get(6)?,
-                is_outgoing: row.get::<_, i32>(7)? != 0,
-                satoshis: row.get(8)?,
-                version: row.get(9)?,
-                lock_time: row.get(10)?,
-                description: row.get(11)?,
-                txid: row.get(12)?,
-                input_beef: row.get::<_, Option<Vec<u8>>>(13)?,
-                raw_tx: row.get::<_, Option<Vec<u8>>>(14)?,
-            })
-        },
+        parse_transaction_row,
     )
     .optional()
     .map_err(|e| StorageError::Database(format!("Failed to find transaction by reference: {}", e)))?;
@@ -122,11 +122,18 @@ pub fn update_transaction(
     transaction_id: i64,
     transaction: &TableTransaction,
 ) -> Result<(), StorageError> {
-    let conn = conn.lock().unwrap();
+    update_transaction_row(&conn.lock().unwrap(), transaction_id, transaction)
+}
 
+/// Update transaction on a connection the caller has locked
+pub(crate) fn update_transaction_row(
+    conn: &Connection,
+    transaction_id: i64,
+    transaction: &TableTransaction,
+) -> Result<(), StorageError> {
     conn.execute(
         "UPDATE transactions 
-         SET updated_at = datetime('now'),
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'),
              provenTxId = ?1,
              status = ?2,
              isOutgoing = ?3,
@@ -157,6 +164,29 @@ pub fn update_transaction(
     Ok(())
 }
 
+/// Update transaction status, unless the transaction has been updated since
+/// the version last updated at `expected_updated_at` was read
+pub fn update_transaction_status_if_unchanged(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    status: TransactionStatus,
+    expected_updated_at: &str,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        "UPDATE transactions
+         SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), status = ?1
+         WHERE transactionId = ?2 AND updated_at = ?3",
+        params![status.to_string(), transaction_id, expected_updated_at],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update transaction: {}", e)))?;
+    if rows == 0 {
+        return verify_updated(&conn, "transactions", "transactionId", transaction_id, Some(expected_updated_at));
+    }
+    Ok(())
+}
+
 /// Find transactions for user with optional filters
 pub fn find_transactions_for_user(
     conn: &Arc<Mutex<Connection>>,
@@ -166,11 +196,7 @@ pub fn find_transactions_for_user(
 ) -> Result<Vec<TableTransaction>, StorageError> {
     let conn = conn.lock().unwrap();
 
-    let mut query = String::from(
-        "SELECT created_at, updated_at, transactionId, userId, provenTxId, status, reference,
-                isOutgoing, satoshis, version, lockTime, description, txid, inputBEEF, rawTx
-         FROM transactions WHERE userId = ?1"
-    );
+    let mut query = format!("SELECT {} FROM transactions WHERE userId = ?1", TRANSACTION_COLUMNS);
 
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(user_id)];
 
@@ -190,25 +216,7 @@ pub fn find_transactions_for_user(
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
-    let rows = stmt.query_map(params_refs.as_slice(), |row| {
-        Ok(TableTransaction {
-            created_at: row.get(0)?,
-            updated_at: row.get(1)?,
-            transaction_id: row.get(2)?,
-            user_id: row.get(3)?,
-            proven_tx_id: row.get(4)?,
-            status: row.get::<_, String>(5)?.parse().unwrap_or(TransactionStatus::Unprocessed),
-            reference: row.get(6)?,
-            is_outgoing: row.get::<_, i32>(7)? != 0,
-            satoshis: row.get(8)?,
-            version: row.get(9)?,
-            lock_time: row.get(10)?,
-            description: row.get(11)?,
-            txid: row.get(12)?,
-            input_beef: row.get::<_, Option<Vec<u8>>>(13)?,
-            raw_tx: row.get::<_, Option<Vec<u8>>>(14)?,
-        })
-    })
+    let rows = stmt.query_map(params_refs.as_slice(), parse_transaction_row)
     .map_err(|e| StorageError::Database(format!("Failed to query transactions: {}", e)))?;
 
     let mut transactions = Vec::new();
@@ -219,6 +227,121 @@ pub fn find_transactions_for_user(
     Ok(transactions)
 }
 
+/// Find the user's transactions, optionally with `reference` and `status`,
+/// ordered by transactionId
+///
+/// Matches TypeScript `findTransactions({ partial: { userId, reference, status } })`
+pub fn find_transactions(
+    conn: &Arc<Mutex<Connection>>,
+    user_id: i64,
+    reference: Option<&str>,
+    status: Option<TransactionStatus>,
+) -> Result<Vec<TableTransaction>, StorageError> {
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find transactions: {}", e));
+
+    let mut query = format!("SELECT {} FROM transactions WHERE userId = ?", TRANSACTION_COLUMNS);
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(user_id)];
+    if let Some(reference) = reference {
+        query.push_str(" AND reference = ?");
+        params_vec.push(Box::new(reference.to_string()));
+    }
+    if let Some(status) = status {
+        query.push_str(" AND status = ?");
+        params_vec.push(Box::new(status.to_string()));
+    }
+    query.push_str(" ORDER BY transactionId ASC");
+
+    let mut stmt = conn.prepare(&query).map_err(db_err)?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), parse_transaction_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Find transactions of any user in one of `statuses` last updated before
+/// `updated_before` (RFC 3339), ordered by transactionId
+pub fn find_aged_transactions(
+    conn: &Arc<Mutex<Connection>>,
+    statuses: &[TransactionStatus],
+    updated_before: &str,
+) -> Result<Vec<TableTransaction>, StorageError> {
+    if statuses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let conn = conn.lock().unwrap();
+    let db_err = |e: rusqlite::Error| StorageError::Database(format!("Failed to find aged transactions: {}", e));
+
+    let placeholders = vec!["?"; statuses.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions
+         WHERE status IN ({}) AND julianday(updated_at) < julianday(?)
+         ORDER BY transactionId ASC",
+        TRANSACTION_COLUMNS, placeholders
+    )).map_err(db_err)?;
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> =
+        statuses.iter().map(|s| Box::new(s.to_string()) as Box<dyn rusqlite::ToSql>).collect();
+    params_vec.push(Box::new(updated_before.to_string()));
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), parse_transaction_row).map_err(db_err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(db_err)
+}
+
+/// Set one column of a transaction, failing with NotFound if it is missing
+fn set_transaction_column(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    column: &str,
+    value: &dyn rusqlite::ToSql,
+) -> Result<(), StorageError> {
+    let conn = conn.lock().unwrap();
+
+    let rows = conn.execute(
+        &format!("UPDATE transactions SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now'), {} = ?1 WHERE transactionId = ?2", column),
+        params![value, transaction_id],
+    )
+    .map_err(|e| StorageError::Database(format!("Failed to update transaction: {}", e)))?;
+    if rows == 0 {
+        return Err(StorageError::NotFound(format!("transaction {}", transaction_id)));
+    }
+    Ok(())
+}
+
+/// Update transaction satoshis
+pub fn update_transaction_satoshis(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    satoshis: i64,
+) -> Result<(), StorageError> {
+    set_transaction_column(conn, transaction_id, "satoshis", &satoshis)
+}
+
+/// Update transaction status
+pub fn update_transaction_status(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    status: TransactionStatus,
+) -> Result<(), StorageError> {
+    set_transaction_column(conn, transaction_id, "status", &status.to_string())
+}
+
+/// Update transaction txid
+pub fn update_transaction_txid(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    txid: &str,
+) -> Result<(), StorageError> {
+    set_transaction_column(conn, transaction_id, "txid", &txid)
+}
+
+/// Update transaction raw transaction bytes
+pub fn update_transaction_raw_tx(
+    conn: &Arc<Mutex<Connection>>,
+    transaction_id: i64,
+    raw_tx: &[u8],
+) -> Result<(), StorageError> {
+    set_transaction_column(conn, transaction_id, "rawTx", &raw_tx)
+}
+
 /// Delete transaction (for testing)
 #[cfg(test)]
 pub fn delete_transaction(
@@ -259,6 +382,32 @@ mod tests {
         Arc::new(Mutex::new(conn))
     }
 
+    #[test]
+    fn test_update_transaction_status_if_unchanged() {
+        let conn = create_test_storage();
+        let transaction = TableTransaction::new(0, 1, TransactionStatus::Unsigned, "ref_1", true, 0, "test");
+        let id = insert_transaction(&conn, 1, &transaction).unwrap();
+        // Written in an earlier millisecond than the update below
+        conn.lock().unwrap()
+            .execute("UPDATE transactions SET updated_at = '2000-01-01 00:00:00.000' WHERE transactionId = ?1", params![id])
+            .unwrap();
+        let read = find_transaction_by_id(&conn, id).unwrap().unwrap();
+
+        update_transaction_status_if_unchanged(&conn, id, TransactionStatus::Sending, &read.updated_at).unwrap();
+        assert_eq!(find_transaction_by_id(&conn, id).unwrap().unwrap().status, TransactionStatus::Sending);
+
+        // The version read before the update is stale
+        assert!(matches!(
+            update_transaction_status_if_unchanged(&conn, id, TransactionStatus::Failed, &read.updated_at),
+            Err(StorageError::Conflict(_))
+        ));
+        assert_eq!(find_transaction_by_id(&conn, id).unwrap().unwrap().status, TransactionStatus::Sending);
+        assert!(matches!(
+            update_transaction_status_if_unchanged(&conn, 99, TransactionStatus::Failed, &read.updated_at),
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn test_insert_and_find_transaction() {
         let conn = create_test_storage();
@@ -0,0 +1,244 @@
+//! Schema integration tests
+//!
+//! A freshly migrated database must match the TypeScript wallet-toolbox
+//! schema: all 16 tables with their columns, foreign keys and indexes,
+//! plus the wallet's own identity cache and user usage tables.
+//! Reference: wallet-toolbox/src/storage/schema/KnexMigrations.ts
+
+use rusqlite::Connection;
+use wallet_storage_sqlite::migrations::{
+    apply_initial_migration, apply_pending_migrations, current_version, rollback_migrations, MIGRATIONS,
+};
+
+/// Table, its columns in order, and its foreign keys as (column, table, column)
+type TableSchema = (&'static str, &'static [&'static str], &'static [(&'static str, &'static str, &'static str)]);
+
+const TIMESTAMPS: [&str; 2] = ["created_at", "updated_at"];
+
+const EXPECTED_TABLES: &[TableSchema] = &[
+    (
+        "proven_txs",
+        &["provenTxId", "txid", "height", "index", "merklePath", "rawTx", "blockHash", "merkleRoot"],
+        &[],
+    ),
+    (
+        "proven_tx_reqs",
+        &[
+            "provenTxReqId", "provenTxId", "status", "attempts", "notified", "txid", "batch", "history",
+            "notify", "rawTx", "inputBEEF",
+        ],
+        &[("provenTxId", "proven_txs", "provenTxId")],
+    ),
+    ("users", &["userId", "identityKey", "activeStorage"], &[]),
+    (
+        "certificates",
+        &[
+            "certificateId", "userId", "serialNumber", "type", "certifier", "subject", "verifier",
+            "revocationOutpoint", "signature", "isDeleted",
+        ],
+        &[("userId", "users", "userId")],
+    ),
+    (
+        "certificate_fields",
+        &["userId", "certificateId", "fieldName", "fieldValue", "masterKey"],
+        &[("userId", "users", "userId"), ("certificateId", "certificates", "certificateId")],
+    ),
+    (
+        "output_baskets",
+        &["basketId", "userId", "name", "numberOfDesiredUTXOs", "minimumDesiredUTXOValue", "isDeleted"],
+        &[("userId", "users", "userId")],
+    ),
+    (
+        "transactions",
+        &[
+            "transactionId", "userId", "provenTxId", "status", "reference", "isOutgoing", "satoshis",
+            "version", "lockTime", "description", "txid", "inputBEEF", "rawTx",
+        ],
+        &[("userId", "users", "userId"), ("provenTxId", "proven_txs", "provenTxId")],
+    ),
+    (
+        "commissions",
+        &["commissionId", "userId", "transactionId", "satoshis", "keyOffset", "isRedeemed", "lockingScript"],
+        &[("userId", "users", "userId"), ("transactionId", "transactions", "transactionId")],
+    ),
+    (
+        "outputs",
+        &[
+            "outputId", "userId", "transactionId", "basketId", "spendable", "change", "vout", "satoshis",
+            "providedBy", "purpose", "type", "outputDescription", "txid", "senderIdentityKey",
+            "derivationPrefix", "derivationSuffix", "customInstructions", "spentBy", "sequenceNumber",
+            "spendingDescription", "scriptLength", "scriptOffset", "lockingScript",
+        ],
+        &[
+            ("userId", "users", "userId"),
+            ("transactionId", "transactions", "transactionId"),
+            ("basketId", "output_baskets", "basketId"),
+            ("spentBy", "transactions", "transactionId"),
+        ],
+    ),
+    (
+        "output_tags",
+        &["outputTagId", "userId", "tag", "isDeleted"],
+        &[("userId", "users", "userId")],
+    ),
+    (
+        "output_tags_map",
+        &["outputTagId", "outputId", "isDeleted"],
+        &[("outputTagId", "output_tags", "outputTagId"), ("outputId", "outputs", "outputId")],
+    ),
+    (
+        "tx_labels",
+        &["txLabelId", "userId", "label", "isDeleted"],
+        &[("userId", "users", "userId")],
+    ),
+    (
+        "tx_labels_map",
+        &["txLabelId", "transactionId", "isDeleted"],
+        &[("txLabelId", "tx_labels", "txLabelId"), ("transactionId", "transactions", "transactionId")],
+    ),
+    ("monitor_events", &["id", "event", "details"], &[]),
+    (
+        "settings",
+        &["storageIdentityKey", "storageName", "chain", "dbtype", "maxOutputScript", "feeModel"],
+        &[],
+    ),
+    (
+        "sync_states",
+        &[
+            "syncStateId", "userId", "storageIdentityKey", "storageName", "status", "init", "refNum",
+            "syncMap", "when", "satoshis", "errorLocal", "errorOther",
+        ],
+        &[("userId", "users", "userId")],
+    ),
+    // Not in the TypeScript schema
+    (
+        "identity_caches",
+        &[
+            "identityCacheId", "userId", "query", "identityKey", "name", "avatarURL", "certificates",
+            "trustScore", "expiresAt",
+        ],
+        &[("userId", "users", "userId")],
+    ),
+    // Not in the TypeScript schema
+    (
+        "user_usages",
+        &["userId", "transactions", "outputs", "certificateBytes", "beefBytes"],
+        &[("userId", "users", "userId")],
+    ),
+    // Not in the TypeScript schema
+    (
+        "output_locks",
+        &["outputId", "userId", "reason", "expiresAt"],
+        &[("outputId", "outputs", "outputId"), ("userId", "users", "userId")],
+    ),
+];
+
+const EXPECTED_INDEXES: &[&str] = &[
+    "idx_proven_txs_blockHash",
+    "idx_proven_tx_reqs_status",
+    "idx_proven_tx_reqs_batch",
+    "idx_transactions_status",
+    "idx_commissions_transactionId",
+    "idx_output_tags_map_outputId",
+    "idx_tx_labels_map_transactionId",
+    "idx_monitor_events_event",
+    "idx_monitor_events_created_at",
+    "idx_sync_states_status",
+    "idx_sync_states_refNum",
+    "idx_outputs_userId_basketId_spendable",
+    "idx_outputs_userId_basketId_spendable_satoshis",
+    "idx_outputs_userId_txid_vout",
+    "idx_outputs_spentBy",
+    "idx_output_locks_userId",
+];
+
+fn fresh_database() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    apply_initial_migration(&conn, "test_key", "Test", "main", 100000).unwrap();
+    apply_pending_migrations(&conn).unwrap();
+    conn
+}
+
+fn strings(conn: &Connection, sql: &str) -> Vec<String> {
+    conn.prepare(sql)
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+/// All CREATE statements, so schemas can be compared whole
+fn schema_sql(conn: &Connection) -> Vec<String> {
+    strings(conn, "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY name")
+}
+
+#[test]
+fn test_fresh_database_tables() {
+    let conn = fresh_database();
+
+    let mut tables = strings(
+        &conn,
+        "SELECT name FROM sqlite_master WHERE type='table'
+         AND name NOT IN ('sqlite_sequence', 'schema_migrations') ORDER BY name",
+    );
+    let mut expected: Vec<String> = EXPECTED_TABLES.iter().map(|(name, _, _)| name.to_string()).collect();
+    tables.sort();
+    expected.sort();
+    assert_eq!(tables, expected);
+    assert_eq!(tables.len(), 19);
+}
+
+#[test]
+fn test_fresh_database_columns_and_foreign_keys() {
+    let conn = fresh_database();
+
+    for (table, columns, foreign_keys) in EXPECTED_TABLES {
+        let actual = strings(&conn, &format!("SELECT name FROM pragma_table_info('{}')", table));
+        let expected: Vec<String> = TIMESTAMPS.iter().chain(columns.iter()).map(|c| c.to_string()).collect();
+        assert_eq!(actual, expected, "columns of {}", table);
+
+        let mut actual: Vec<(String, String, String)> = conn
+            .prepare(&format!("SELECT \"from\", \"table\", \"to\" FROM pragma_foreign_key_list('{}')", table))
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut expected: Vec<(String, String, String)> = foreign_keys
+            .iter()
+            .map(|(from, to_table, to)| (from.to_string(), to_table.to_string(), to.to_string()))
+            .collect();
+        actual.sort();
+        expected.sort();
+        assert_eq!(actual, expected, "foreign keys of {}", table);
+    }
+}
+
+#[test]
+fn test_fresh_database_indexes() {
+    let conn = fresh_database();
+
+    let indexes = strings(&conn, "SELECT name FROM sqlite_master WHERE type='index'");
+    for index in EXPECTED_INDEXES {
+        assert!(indexes.contains(&index.to_string()), "Missing index: {}", index);
+    }
+}
+
+#[test]
+fn test_migrations_down_and_up_restore_schema() {
+    let conn = fresh_database();
+    let migrated = schema_sql(&conn);
+    assert_eq!(current_version(&conn).unwrap().as_deref(), Some(MIGRATIONS.last().unwrap().name));
+
+    let reverted = rollback_migrations(&conn, MIGRATIONS.len()).unwrap();
+    assert_eq!(reverted.len(), MIGRATIONS.len());
+    let remaining = strings(
+        &conn,
+        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT IN ('sqlite_sequence', 'schema_migrations')",
+    );
+    assert!(remaining.is_empty(), "Tables left after rollback: {:?}", remaining);
+
+    assert_eq!(apply_pending_migrations(&conn).unwrap(), MIGRATIONS.len());
+    assert_eq!(schema_sql(&conn), migrated);
+}
@@ -0,0 +1,211 @@
+//! Auth scoping for storage calls
+//!
+//! Every `*_auth` call acts for the one user its `AuthId` names. A storage
+//! serving several users must not trust the user ids carried in arguments
+//! or rows, so implementations:
+//! 1. **Resolve** the caller with `WalletStorageReader::validate_auth`, which
+//!    checks `identity_key` belongs to `user_id`
+//! 2. **Scope** queries with `verify_args_user`, rejecting arguments naming
+//!    another user
+//! 3. **Check** rows read or written with `verify_owned` / `verify_all_owned`
+//!
+//! Reference: TypeScript `StorageProvider` auth checks (`validateUserId`)
+
+use crate::{
+    AuthId, StorageError, StorageResult, TableCertificate, TableCertificateField, TableCommission,
+    TableOutput, TableOutputBasket, TableOutputTag, TableSyncState, TableTransaction, TableTxLabel,
+};
+
+/// A record owned by one user
+pub trait UserOwned {
+    /// Id of the owning user
+    fn owner_user_id(&self) -> i64;
+}
+
+macro_rules! impl_user_owned {
+    ($($table:ty),* $(,)?) => {
+        $(impl UserOwned for $table {
+            fn owner_user_id(&self) -> i64 {
+                self.user_id
+            }
+        })*
+    };
+}
+
+impl_user_owned!(
+    TableCertificate,
+    TableCertificateField,
+    TableCommission,
+    TableOutput,
+    TableOutputBasket,
+    TableOutputTag,
+    TableSyncState,
+    TableTransaction,
+    TableTxLabel,
+);
+
+impl AuthId {
+    /// The caller's user id, required by every `*_auth` call
+    pub fn user_id_required(&self) -> StorageResult<i64> {
+        self.user_id
+            .ok_or_else(|| StorageError::Unauthorized("user_id required".to_string()))
+    }
+}
+
+/// Check arguments naming `args_user_id` are scoped to the caller
+///
+/// Returns the caller's user id.
+pub fn verify_args_user(auth: &AuthId, args_user_id: i64) -> StorageResult<i64> {
+    let user_id = auth.user_id_required()?;
+    if args_user_id != user_id {
+        return Err(StorageError::Unauthorized(format!(
+            "userId {} does not match the authenticated user",
+            args_user_id
+        )));
+    }
+    Ok(user_id)
+}
+
+/// Check `row` belongs to the caller
+pub fn verify_owned<T: UserOwned>(auth: &AuthId, row: &T) -> StorageResult<()> {
+    if row.owner_user_id() != auth.user_id_required()? {
+        return Err(StorageError::Unauthorized(
+            "record does not belong to the authenticated user".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check every row belongs to the caller
+pub fn verify_all_owned<T: UserOwned>(auth: &AuthId, rows: &[T]) -> StorageResult<()> {
+    rows.iter().try_for_each(|row| verify_owned(auth, row))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use async_trait::async_trait;
+
+    /// Reader holding two users' outputs, enforcing scoping as storages should
+    struct TwoUserReader {
+        settings: TableSettings,
+        users: Vec<TableUser>,
+        outputs: Vec<TableOutput>,
+    }
+
+    impl TwoUserReader {
+        fn new() -> Self {
+            Self {
+                settings: TableSettings::new("key", "test", SettingsChain::Test, DbType::SQLite, 1024),
+                users: vec![TableUser::new(1, "alice", "s"), TableUser::new(2, "bob", "s")],
+                outputs: vec![
+                    TableOutput::new(10, 1, 1, true, false, "alice's", 0, 1000, StorageProvidedBy::You, "", "P2PKH"),
+                    TableOutput::new(20, 2, 2, true, false, "bob's", 0, 2000, StorageProvidedBy::You, "", "P2PKH"),
+                ],
+            }
+        }
+    }
+
+    #[async_trait]
+    impl WalletStorageReader for TwoUserReader {
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn get_settings(&self) -> &TableSettings {
+            &self.settings
+        }
+
+        async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> {
+            Ok(self.users.iter().find(|u| u.identity_key == identity_key).cloned())
+        }
+
+        async fn find_certificates_auth(&self, _auth: &AuthId, _args: &FindCertificatesArgs) -> StorageResult<Vec<TableCertificate>> {
+            Ok(Vec::new())
+        }
+
+        async fn count_certificates_auth(&self, _auth: &AuthId, _args: &FindCertificatesArgs) -> StorageResult<i64> {
+            Ok(0)
+        }
+
+        async fn find_certificate_fields_auth(&self, _auth: &AuthId, _certificate_id: i64) -> StorageResult<Vec<TableCertificateField>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_output_baskets_auth(&self, _auth: &AuthId, _args: &FindOutputBasketsArgs) -> StorageResult<Vec<TableOutputBasket>> {
+            Ok(Vec::new())
+        }
+
+        async fn find_outputs_auth(&self, auth: &AuthId, args: &FindOutputsArgs) -> StorageResult<Vec<TableOutput>> {
+            let user_id = self.validate_auth(auth).await?;
+            verify_args_user(auth, args.user_id)?;
+            Ok(self.outputs.iter().filter(|o| o.user_id == user_id).cloned().collect())
+        }
+
+        async fn find_proven_tx_reqs(&self, _args: &FindProvenTxReqsArgs) -> StorageResult<Vec<TableProvenTxReq>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn outputs_args(user_id: i64) -> FindOutputsArgs {
+        FindOutputsArgs {
+            user_id,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            no_script: None,
+            tx_status: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_own_outputs_readable() {
+        let reader = TwoUserReader::new();
+        let auth = AuthId::new("alice").with_user_id(1);
+
+        let outputs = reader.find_outputs_auth(&auth, &outputs_args(1)).await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].output_id, 10);
+    }
+
+    #[tokio::test]
+    async fn test_cross_user_reads_rejected() {
+        let reader = TwoUserReader::new();
+
+        // Alice asking for Bob's outputs by argument
+        let alice = AuthId::new("alice").with_user_id(1);
+        let err = reader.find_outputs_auth(&alice, &outputs_args(2)).await.unwrap_err();
+        assert!(matches!(err, StorageError::Unauthorized(_)));
+
+        // Alice claiming Bob's user id
+        let forged = AuthId::new("alice").with_user_id(2);
+        let err = reader.find_outputs_auth(&forged, &outputs_args(2)).await.unwrap_err();
+        assert!(matches!(err, StorageError::Unauthorized(_)));
+
+        // An empty identity key, as some internal callers once used
+        let anonymous = AuthId::new("").with_user_id(2);
+        let err = reader.find_outputs_auth(&anonymous, &outputs_args(2)).await.unwrap_err();
+        assert!(matches!(err, StorageError::Unauthorized(_)));
+
+        // No user id at all
+        let err = reader.find_outputs_auth(&AuthId::new("bob"), &outputs_args(2)).await.unwrap_err();
+        assert!(matches!(err, StorageError::Unauthorized(_)));
+    }
+
+    #[test]
+    fn test_verify_owned() {
+        let reader = TwoUserReader::new();
+        let alice = AuthId::new("alice").with_user_id(1);
+
+        assert!(verify_owned(&alice, &reader.outputs[0]).is_ok());
+        assert!(matches!(verify_owned(&alice, &reader.outputs[1]), Err(StorageError::Unauthorized(_))));
+        assert!(verify_all_owned(&alice, &reader.outputs).is_err());
+        assert!(verify_all_owned(&alice, &reader.outputs[..1]).is_ok());
+    }
+}
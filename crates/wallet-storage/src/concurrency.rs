@@ -0,0 +1,46 @@
+//! Optimistic concurrency for storage updates
+//!
+//! The monitor, the user's own actions and other devices syncing through a
+//! storage server all update the same rows. Rather than locking, an update
+//! can name the `updated_at` of the row version it read; stores refuse the
+//! update with `StorageError::Conflict` when the row has changed since, and
+//! the caller re-reads the row and decides whether the update still applies.
+
+use crate::{StorageError, StorageResult};
+
+/// How many times callers re-read a row and retry an update refused with
+/// `StorageError::Conflict` before giving up
+pub const MAX_CONFLICT_RETRIES: usize = 3;
+
+/// Check the row `id` of `table`, last updated at `updated_at`, is still the
+/// version last updated at `expected_updated_at`
+///
+/// Passes when no version is expected.
+pub fn verify_unchanged(
+    table: &str,
+    id: i64,
+    expected_updated_at: Option<&str>,
+    updated_at: &str,
+) -> StorageResult<()> {
+    match expected_updated_at {
+        Some(expected) if expected != updated_at => Err(StorageError::Conflict(format!(
+            "{} {} was updated at {}, after the version updated at {} was read",
+            table, id, updated_at, expected
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_unchanged() {
+        assert!(verify_unchanged("outputs", 1, None, "2026-10-16T00:00:01Z").is_ok());
+        assert!(verify_unchanged("outputs", 1, Some("2026-10-16T00:00:01Z"), "2026-10-16T00:00:01Z").is_ok());
+        let err = verify_unchanged("outputs", 1, Some("2026-10-16T00:00:00Z"), "2026-10-16T00:00:01Z").unwrap_err();
+        assert!(matches!(err, StorageError::Conflict(_)));
+        assert!(err.to_string().contains("outputs 1 was updated at 2026-10-16T00:00:01Z"));
+    }
+}
@@ -6,13 +6,31 @@
 use async_trait::async_trait;
 use thiserror::Error;
 
+pub mod auth;
+pub mod concurrency;
+pub mod dump;
+pub mod manager;
+pub mod metrics;
 pub mod schema;
 pub mod methods;
+pub mod monitor_events;
+pub mod quota;
+pub mod read_only;
 pub mod sync;
 pub mod types;
 
 // Re-export commonly used types
+pub use auth::{verify_all_owned, verify_args_user, verify_owned, UserOwned};
+pub use concurrency::{verify_unchanged, MAX_CONFLICT_RETRIES};
+pub use dump::{DumpValue, StorageDump, StorageDumpTable};
+pub use manager::WalletStorageManager;
+pub use monitor_events::{MonitorEvent, MonitorEventRecord};
+pub use quota::{QuotaPolicy, QuotaResource, UserUsage};
+pub use read_only::ReadOnly;
 pub use schema::tables::*;
+pub use schema::entities::EntityProvenTxReq;
+pub use schema::entities::entity_proven_tx_req::{ProvenTxReqHistory, ProvenTxReqNotify, ReqHistoryNote};
+pub use sync::{ProcessSyncChunkResult, RequestSyncChunkArgs, SyncChunk, SyncChunkOffset};
 pub use types::*;
 
 /// Unified error for storage operations
@@ -36,8 +54,42 @@ pub enum StorageError {
     #[error("unauthorized: {0}")]
     Unauthorized(String),
     
+    /// An update named a version of a row that has since been updated
     #[error("conflict: {0}")]
     Conflict(String),
+    
+    /// A write reached a store that is not the user's active storage
+    ///
+    /// Matches TypeScript `WERR_NOT_ACTIVE`
+    #[error("not active: {0}")]
+    NotActive(String),
+    
+    /// Available inputs cannot fund the outputs and fee
+    ///
+    /// Matches TypeScript `WERR_INSUFFICIENT_FUNDS`
+    #[error("insufficient funds: {more_satoshis_needed} more satoshis needed, for a total of {total_satoshis_needed}")]
+    InsufficientFunds {
+        /// Satoshis the transaction needs in total, including the fee
+        total_satoshis_needed: i64,
+        /// Satoshis still missing after all available inputs
+        more_satoshis_needed: i64,
+    },
+    
+    /// Storage, services or wallet are configured for different chains
+    ///
+    /// Matches TypeScript `WERR_NETWORK_CHAIN`
+    #[error("network chain: {0}")]
+    NetworkChain(String),
+    
+    /// A write would take the user past the store's quota for `resource`
+    #[error("quota exceeded: user {user_id} {resource} would be {requested}, limit {limit}")]
+    QuotaExceeded {
+        user_id: i64,
+        resource: QuotaResource,
+        limit: i64,
+        /// The user's usage of `resource` had the write been made
+        requested: i64,
+    },
 }
 
 pub type StorageResult<T> = Result<T, StorageError>;
@@ -53,6 +105,39 @@ pub trait WalletStorageReader: Send + Sync {
     /// Get storage settings
     fn get_settings(&self) -> &TableSettings;
     
+    /// Get the configured fee model
+    ///
+    /// Matches TypeScript `StorageProvider.feeModel` option
+    fn get_fee_model(&self) -> StorageFeeModel {
+        StorageFeeModel::default()
+    }
+    
+    /// Get the configured change baskets
+    ///
+    /// createAction uses the basket for its originator unless the call names one
+    fn get_change_baskets(&self) -> StorageChangeBaskets {
+        StorageChangeBaskets::default()
+    }
+    
+    /// Find the user with `identity_key`
+    async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>>;
+    
+    /// Check `auth` names a known user and its identity key owns `user_id`
+    ///
+    /// Returns the user id every `*_auth` call must scope its reads and
+    /// writes to. Fails with `Unauthorized` when `user_id` is missing or
+    /// belongs to another identity key.
+    /// Reference: TypeScript `StorageProvider.validateUserId`
+    async fn validate_auth(&self, auth: &AuthId) -> StorageResult<i64> {
+        let user_id = auth.user_id_required()?;
+        match self.find_user_by_identity_key(&auth.identity_key).await? {
+            Some(user) if user.user_id == user_id => Ok(user_id),
+            _ => Err(StorageError::Unauthorized(
+                "identityKey does not match userId".to_string(),
+            )),
+        }
+    }
+    
     /// Find certificates with filters
     async fn find_certificates_auth(
         &self,
@@ -60,6 +145,20 @@ pub trait WalletStorageReader: Send + Sync {
         args: &FindCertificatesArgs,
     ) -> StorageResult<Vec<TableCertificate>>;
     
+    /// Count certificates matching the filters, ignoring paging
+    async fn count_certificates_auth(
+        &self,
+        auth: &AuthId,
+        args: &FindCertificatesArgs,
+    ) -> StorageResult<i64>;
+    
+    /// Find the fields of one of the user's certificates
+    async fn find_certificate_fields_auth(
+        &self,
+        auth: &AuthId,
+        certificate_id: i64,
+    ) -> StorageResult<Vec<TableCertificateField>>;
+    
     /// Find output baskets
     async fn find_output_baskets_auth(
         &self,
@@ -79,6 +178,20 @@ pub trait WalletStorageReader: Send + Sync {
         &self,
         args: &FindProvenTxReqsArgs,
     ) -> StorageResult<Vec<TableProvenTxReq>>;
+    
+    /// History notes of the ProvenTxReq for `txid`, for debugging broadcasts
+    ///
+    /// Returns `None` when there is no request for `txid`.
+    /// Reference: TypeScript `EntityProvenTxReq.history`
+    async fn get_req_history(&self, txid: &str) -> StorageResult<Option<ProvenTxReqHistory>> {
+        let reqs = self.find_proven_tx_reqs(&FindProvenTxReqsArgs {
+            status: None,
+            since: None,
+            paged: None,
+            txids: Some(vec![txid.to_string()]),
+        }).await?;
+        Ok(reqs.first().map(|req| EntityProvenTxReq::parse_history(&req.history)))
+    }
 }
 
 /// Writer capabilities - write operations on storage
@@ -100,6 +213,9 @@ pub trait WalletStorageWriter: WalletStorageReader {
     async fn destroy(&mut self) -> StorageResult<()>;
     
     /// Find or create user by identity key
+    ///
+    /// Wallet profiles derive their own root keys, so each profile has a
+    /// distinct identity key and maps to its own user.
     async fn find_or_insert_user(
         &mut self,
         identity_key: &str,
@@ -111,6 +227,13 @@ pub trait WalletStorageWriter: WalletStorageReader {
         auth: &AuthId,
         certificate: &TableCertificate,
     ) -> StorageResult<i64>;
+    
+    /// Insert a field of a certificate inserted by `insert_certificate_auth`
+    async fn insert_certificate_field_auth(
+        &mut self,
+        auth: &AuthId,
+        field: &TableCertificateField,
+    ) -> StorageResult<()>;
 }
 
 /// Sync capabilities - synchronization between storage providers
@@ -132,6 +255,30 @@ pub trait WalletStorageSync: WalletStorageWriter {
         auth: &AuthId,
         new_active_storage_identity_key: &str,
     ) -> StorageResult<i64>;
+    
+    /// Save a sync state found or inserted by `find_or_insert_sync_state_auth`
+    async fn update_sync_state(&mut self, _sync_state: &TableSyncState) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("update_sync_state"))
+    }
+    
+    /// Find sync states, for inspecting how each store last synced
+    async fn find_sync_states(&self, _args: &FindSyncStatesArgs) -> StorageResult<Vec<TableSyncState>> {
+        Err(StorageError::NotImplemented("find_sync_states"))
+    }
+    
+    /// Read the next chunk of the user named in `args` for another store
+    async fn get_sync_chunk(&self, _args: &RequestSyncChunkArgs) -> StorageResult<SyncChunk> {
+        Err(StorageError::NotImplemented("get_sync_chunk"))
+    }
+    
+    /// Apply a chunk read from the store named in `args`
+    async fn process_sync_chunk(
+        &mut self,
+        _args: &RequestSyncChunkArgs,
+        _chunk: &SyncChunk,
+    ) -> StorageResult<ProcessSyncChunkResult> {
+        Err(StorageError::NotImplemented("process_sync_chunk"))
+    }
 }
 
 /// Full storage provider interface
@@ -177,6 +324,43 @@ pub trait WalletStorageProvider: WalletStorageSync {
     /// Reference: StorageKnex.ts line 82
     async fn get_proven_or_raw_tx(&self, txid: &str) -> StorageResult<ProvenOrRawTx>;
     
+    /// Insert a proven transaction request, returning its id
+    /// Reference: StorageReaderWriter.ts insertProvenTxReq
+    async fn insert_proven_tx_req(&mut self, req: &TableProvenTxReq) -> StorageResult<i64>;
+    
+    /// Update a proven transaction request
+    /// Reference: StorageReaderWriter.ts updateProvenTxReq
+    async fn update_proven_tx_req(
+        &mut self,
+        proven_tx_req_id: i64,
+        updates: &ProvenTxReqUpdates,
+    ) -> StorageResult<()>;
+    
+    /// Insert a ProvenTx for a ProvenTxReq's txid, mark the request
+    /// 'completed', and link and complete the transactions it notifies
+    /// Reference: StorageProvider.ts updateProvenTxReqWithNewProvenTx
+    async fn update_proven_tx_req_with_new_proven_tx(
+        &mut self,
+        args: &UpdateProvenTxReqWithNewProvenTxArgs,
+    ) -> StorageResult<UpdateProvenTxReqWithNewProvenTxResult>;
+    
+    /// Find ProvenTxs mined at or above `min_height`
+    /// Reference: StorageReader.ts findProvenTxs
+    async fn find_proven_txs_from_height(&self, min_height: i64) -> StorageResult<Vec<TableProvenTx>>;
+    
+    /// Delete a ProvenTx whose block is no longer on the active chain,
+    /// returning its ProvenTxReqs to 'unmined' and its 'completed'
+    /// transactions to 'unproven'; returns the reverted transaction ids
+    /// Reference: TaskReorg.ts (proof invalidation)
+    async fn rollback_proven_tx(&mut self, proven_tx_id: i64) -> StorageResult<Vec<i64>>;
+    
+    /// Replace fields of a ProvenTx, e.g. a proof re-fetched after its
+    /// stored merkle path stopped verifying
+    /// Reference: StorageReaderWriter.ts updateProvenTx
+    async fn update_proven_tx(&mut self, _proven_tx_id: i64, _updates: &ProvenTxUpdates) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("update_proven_tx"))
+    }
+    
     /// Get raw tx of known valid transaction
     /// Reference: StorageKnex.ts line 111
     async fn get_raw_tx_of_known_valid_transaction(
@@ -195,6 +379,28 @@ pub trait WalletStorageProvider: WalletStorageSync {
         status: Option<crate::TransactionStatus>,
     ) -> StorageResult<Vec<TableTransaction>>;
     
+    /// Find a transaction by id
+    /// Reference: StorageReader.ts findTransactionById
+    async fn find_transaction_by_id(&self, transaction_id: i64) -> StorageResult<Option<TableTransaction>>;
+    
+    /// Find the user's transaction with `txid`
+    /// Reference: StorageReader.ts findTransactions ({ partial: { userId, txid } })
+    async fn find_transaction_by_txid(&self, user_id: i64, txid: &str) -> StorageResult<Option<TableTransaction>> {
+        Ok(self
+            .find_transactions(user_id, None, None)
+            .await?
+            .into_iter()
+            .find(|t| t.txid.as_deref() == Some(txid)))
+    }
+    
+    /// Find transactions of any user in one of `statuses` last updated before `updated_before` (RFC 3339)
+    /// Reference: TaskFailAbandoned.ts (findTransactions with status filter and age check)
+    async fn find_aged_transactions(
+        &self,
+        statuses: &[crate::TransactionStatus],
+        updated_before: &str,
+    ) -> StorageResult<Vec<TableTransaction>>;
+    
     /// Find outputs by transaction (as inputs or outputs)
     /// Reference: signAction.ts lines 62-75
     async fn find_outputs_by_transaction(
@@ -216,6 +422,17 @@ pub trait WalletStorageProvider: WalletStorageSync {
     /// Reference: signAction.ts line 188
     async fn update_transaction_status(&mut self, transaction_id: i64, status: TransactionStatus) -> StorageResult<()>;
     
+    /// Update transaction status, unless the transaction has been updated
+    /// since the version last updated at `expected_updated_at` was read
+    ///
+    /// Fails with `StorageError::Conflict` when it has.
+    async fn update_transaction_status_if_unchanged(
+        &mut self,
+        transaction_id: i64,
+        status: TransactionStatus,
+        expected_updated_at: &str,
+    ) -> StorageResult<()>;
+    
     /// Update transaction txid
     /// Reference: signAction.ts line 189
     async fn update_transaction_txid(&mut self, transaction_id: i64, txid: &str) -> StorageResult<()>;
@@ -236,10 +453,23 @@ pub trait WalletStorageProvider: WalletStorageSync {
     /// Reference: createAction.ts line 329
     async fn insert_commission(&mut self, commission: &TableCommission) -> StorageResult<i64>;
     
+    /// Sum of the user's spendable outputs across all baskets
+    ///
+    /// Counts the outputs `get_basket_balances` does, without paging
+    /// through them. Reference: TS Wallet.balance
+    async fn get_balance(&self, user_id: i64) -> StorageResult<i64>;
+    
+    /// Spendable satoshis of each of the user's baskets holding any
+    async fn get_basket_balances(&self, user_id: i64) -> StorageResult<Vec<BasketBalance>>;
+    
     /// Find or insert output basket
     /// Reference: StorageReaderWriter.ts line 206
     async fn find_or_insert_output_basket(&mut self, user_id: i64, name: &str) -> StorageResult<TableOutputBasket>;
     
+    /// Update output basket change policy
+    /// Reference: StorageReaderWriter.ts updateOutputBasket
+    async fn update_output_basket(&mut self, basket_id: i64, updates: &OutputBasketUpdates) -> StorageResult<()>;
+    
     /// Find or insert output tag
     /// Reference: StorageReaderWriter.ts line 291
     async fn find_or_insert_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<TableOutputTag>;
@@ -255,6 +485,148 @@ pub trait WalletStorageProvider: WalletStorageSync {
     /// Find or insert transaction label map
     /// Reference: StorageReaderWriter.ts line 264
     async fn find_or_insert_tx_label_map(&mut self, transaction_id: i64, tx_label_id: i64) -> StorageResult<()>;
+    
+    /// Find or insert each of `tags`, in order, dropping repeats
+    ///
+    /// Stores that can should do this in a single statement; by default each
+    /// tag is found or inserted in turn.
+    async fn find_or_insert_output_tags(&mut self, user_id: i64, tags: &[String]) -> StorageResult<Vec<TableOutputTag>> {
+        let mut found: Vec<TableOutputTag> = Vec::new();
+        for tag in tags {
+            if !found.iter().any(|t| &t.tag == tag) {
+                found.push(self.find_or_insert_output_tag(user_id, tag).await?);
+            }
+        }
+        Ok(found)
+    }
+    
+    /// Find or insert each of `labels`, in order, dropping repeats
+    ///
+    /// Stores that can should do this in a single statement; by default each
+    /// label is found or inserted in turn.
+    async fn find_or_insert_tx_labels(&mut self, user_id: i64, labels: &[String]) -> StorageResult<Vec<TableTxLabel>> {
+        let mut found: Vec<TableTxLabel> = Vec::new();
+        for label in labels {
+            if !found.iter().any(|l| &l.label == label) {
+                found.push(self.find_or_insert_tx_label(user_id, label).await?);
+            }
+        }
+        Ok(found)
+    }
+    
+    /// Labels of a transaction, excluding deleted labels
+    /// Reference: StorageReader.ts getLabelsForTransactionId
+    async fn find_tx_labels_for_transaction(&self, transaction_id: i64) -> StorageResult<Vec<TableTxLabel>>;
+    
+    /// The user's output tags, excluding deleted tags
+    /// Reference: StorageReader.ts findOutputTags ({ partial: { userId, isDeleted: false } })
+    async fn find_output_tags(&self, _user_id: i64) -> StorageResult<Vec<TableOutputTag>> {
+        Err(StorageError::NotImplemented("find_output_tags"))
+    }
+    
+    /// The user's transaction labels, excluding deleted labels
+    /// Reference: StorageReader.ts findTxLabels ({ partial: { userId, isDeleted: false } })
+    async fn find_tx_labels(&self, _user_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        Err(StorageError::NotImplemented("find_tx_labels"))
+    }
+    
+    /// Rename one of the user's output tags, returning the renamed tag
+    ///
+    /// When `new_tag` already exists, deleted or not, the outputs of `tag`
+    /// are mapped to it instead and `tag` is deleted. NotFound if `tag` is
+    /// missing or deleted.
+    async fn rename_output_tag(&mut self, _user_id: i64, _tag: &str, _new_tag: &str) -> StorageResult<TableOutputTag> {
+        Err(StorageError::NotImplemented("rename_output_tag"))
+    }
+    
+    /// Soft-delete one of the user's output tags and its output mappings
+    ///
+    /// NotFound if `tag` is missing or already deleted. Tagging an output
+    /// with it again restores the tag, without its old mappings.
+    async fn delete_output_tag(&mut self, _user_id: i64, _tag: &str) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("delete_output_tag"))
+    }
+    
+    /// Rename one of the user's transaction labels, returning the renamed label
+    ///
+    /// Merges into an existing `new_label` as `rename_output_tag` does.
+    async fn rename_tx_label(&mut self, _user_id: i64, _label: &str, _new_label: &str) -> StorageResult<TableTxLabel> {
+        Err(StorageError::NotImplemented("rename_tx_label"))
+    }
+    
+    /// Soft-delete one of the user's transaction labels and its mappings
+    async fn delete_tx_label(&mut self, _user_id: i64, _label: &str) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("delete_tx_label"))
+    }
+    
+    /// Number of outputs carrying each of the user's tags, deleted tags excluded
+    async fn get_output_tag_usages(&self, _user_id: i64) -> StorageResult<Vec<OutputTagUsage>> {
+        Err(StorageError::NotImplemented("get_output_tag_usages"))
+    }
+    
+    /// What the user's stored data counts against the store's quota
+    async fn get_user_usage(&self, _user_id: i64) -> StorageResult<UserUsage> {
+        Err(StorageError::NotImplemented("get_user_usage"))
+    }
+    
+    /// The user's cached identity lookup `query`, expired or not
+    async fn find_identity_cache(&self, _user_id: i64, _query: &str) -> StorageResult<Option<TableIdentityCache>> {
+        Err(StorageError::NotImplemented("find_identity_cache"))
+    }
+    
+    /// Insert or replace the user's cached identity lookup `entry.query`
+    ///
+    /// Returns the entry's id.
+    async fn upsert_identity_cache(&mut self, _entry: &TableIdentityCache) -> StorageResult<i64> {
+        Err(StorageError::NotImplemented("upsert_identity_cache"))
+    }
+
+    /// Lock the user's output `txid.vout` against change allocation and
+    /// use as a createAction input, until unlocked or `ttl` elapses
+    ///
+    /// Locking a locked output replaces its reason and expiry. NotFound if
+    /// the user has no such output, InvalidArg if it is not spendable.
+    async fn lock_output(
+        &mut self,
+        _user_id: i64,
+        _txid: &str,
+        _vout: u32,
+        _reason: &str,
+        _ttl: Option<std::time::Duration>,
+    ) -> StorageResult<TableOutputLock> {
+        Err(StorageError::NotImplemented("lock_output"))
+    }
+
+    /// Remove the lock on the user's output `txid.vout`
+    ///
+    /// Returns whether the output was locked.
+    async fn unlock_output(&mut self, _user_id: i64, _txid: &str, _vout: u32) -> StorageResult<bool> {
+        Err(StorageError::NotImplemented("unlock_output"))
+    }
+
+    /// The unexpired lock on output `output_id`, if any
+    ///
+    /// Stores without output locks have none.
+    async fn find_output_lock(&self, _output_id: i64) -> StorageResult<Option<TableOutputLock>> {
+        Ok(None)
+    }
+
+    /// The user's unexpired output locks
+    async fn find_output_locks(&self, _user_id: i64) -> StorageResult<Vec<TableOutputLock>> {
+        Err(StorageError::NotImplemented("find_output_locks"))
+    }
+
+    /// Record a monitor event
+    /// Reference: StorageReaderWriter.ts insertMonitorEvent
+    async fn insert_monitor_event(&mut self, event: &TableMonitorEvent) -> StorageResult<i64>;
+    
+    /// Find monitor events, oldest first unless `order_descending`
+    /// Reference: StorageReader.ts findMonitorEvents
+    async fn find_monitor_events(&self, args: &FindMonitorEventsArgs) -> StorageResult<Vec<TableMonitorEvent>>;
+    
+    /// Delete or trim aged transaction data as selected by `params`
+    /// Reference: StorageKnex.ts purgeData
+    async fn purge_data(&mut self, params: &PurgeParams) -> StorageResult<PurgeResults>;
 }
 
 #[cfg(test)]
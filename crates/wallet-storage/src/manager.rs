@@ -0,0 +1,965 @@
+//! WalletStorageManager - routes a user's storage calls to their active store
+//!
+//! A user's data lives in one active store and any number of backups. Each
+//! store's user record names the store the user treats as active; writes are
+//! refused with `StorageError::NotActive` unless the manager's active store is
+//! that store and no backup also claims to be active. `set_active` takes over
+//! by syncing the current active into the new one and re-pointing every store.
+//!
+//! Translates TypeScript WalletStorageManager class to Rust.
+//! Reference: wallet-toolbox/src/storage/WalletStorageManager.ts
+
+use crate::schema::entities::{EntitySyncState, SyncError};
+use crate::*;
+use async_trait::async_trait;
+
+/// One store of the manager and its record of the user
+struct ManagedStorage {
+    storage: Box<dyn WalletStorageProvider>,
+    user: Option<TableUser>,
+}
+
+impl ManagedStorage {
+    fn storage_identity_key(&self) -> &str {
+        &self.storage.get_settings().storage_identity_key
+    }
+
+    fn user_active_storage(&self) -> Option<&str> {
+        self.user.as_ref().map(|u| u.active_storage.as_str())
+    }
+
+    /// The store's own user record names it as the active storage
+    fn claims_active(&self) -> bool {
+        self.user_active_storage() == Some(self.storage_identity_key())
+    }
+}
+
+/// Storage for one user spread over an active store and its backups
+///
+/// Implements `WalletStorageProvider` so a wallet can use it in place of a
+/// single store: reads go to the active store, writes go to it only while it
+/// is the user's active storage.
+///
+/// Matches TypeScript `WalletStorageManager` class
+pub struct WalletStorageManager {
+    identity_key: String,
+    /// The first store is the active store, the rest are backups
+    stores: Vec<ManagedStorage>,
+    is_available: bool,
+    is_active_enabled: bool,
+}
+
+impl WalletStorageManager {
+    /// Manage `identity_key`'s storage in `active`, backed up to `backups`
+    pub fn new(
+        identity_key: impl Into<String>,
+        active: Box<dyn WalletStorageProvider>,
+        backups: Vec<Box<dyn WalletStorageProvider>>,
+    ) -> Self {
+        let stores = std::iter::once(active)
+            .chain(backups)
+            .map(|storage| ManagedStorage { storage, user: None })
+            .collect();
+        Self {
+            identity_key: identity_key.into(),
+            stores,
+            is_available: false,
+            is_active_enabled: false,
+        }
+    }
+
+    /// Add a backup store; the next call re-checks which store is active
+    ///
+    /// Matches TypeScript `WalletStorageManager.addWalletStorageProvider`
+    pub fn add_wallet_storage_provider(&mut self, storage: Box<dyn WalletStorageProvider>) {
+        self.stores.push(ManagedStorage { storage, user: None });
+        self.is_available = false;
+    }
+
+    /// True when writes are allowed: the active store is the user's active
+    /// storage and no backup claims to be active
+    pub fn is_active_enabled(&self) -> bool {
+        self.is_available && self.is_active_enabled
+    }
+
+    /// Storage identity key of the active store, once available
+    pub fn get_active_store(&self) -> Option<&str> {
+        self.is_available.then(|| self.stores[0].storage_identity_key())
+    }
+
+    /// The storage identity key the active store's user record names as active
+    pub fn get_user_active_storage(&self) -> Option<&str> {
+        self.stores[0].user_active_storage()
+    }
+
+    /// Storage identity keys of the backup stores, once available
+    pub fn get_backup_stores(&self) -> Vec<&str> {
+        if !self.is_available {
+            return Vec::new();
+        }
+        self.stores[1..].iter().map(|s| s.storage_identity_key()).collect()
+    }
+
+    /// Storage identity keys of backups whose user record names themselves as active
+    pub fn get_conflicting_stores(&self) -> Vec<&str> {
+        if !self.is_available {
+            return Vec::new();
+        }
+        self.stores[1..]
+            .iter()
+            .filter(|s| s.claims_active())
+            .map(|s| s.storage_identity_key())
+            .collect()
+    }
+
+    /// The user's auth on the active store, with `is_active` set when writes are allowed
+    ///
+    /// Matches TypeScript `WalletStorageManager.getAuth`
+    pub async fn get_auth(&mut self) -> StorageResult<AuthId> {
+        self.ensure_available().await?;
+        Ok(AuthId {
+            identity_key: self.identity_key.clone(),
+            user_id: self.stores[0].user.as_ref().map(|u| u.user_id),
+            is_active: Some(self.is_active_enabled),
+        })
+    }
+
+    /// Sync the active store into every backup, returning the number of
+    /// items inserted or updated
+    ///
+    /// Matches TypeScript `WalletStorageManager.updateBackups`
+    pub async fn update_backups(&mut self) -> StorageResult<i64> {
+        self.ensure_active_enabled().await?;
+        let mut changes = 0;
+        for to in 1..self.stores.len() {
+            changes += self.sync_to_writer(0, to).await?;
+        }
+        Ok(changes)
+    }
+
+    async fn ensure_available(&mut self) -> StorageResult<()> {
+        if !self.is_available {
+            self.make_available().await?;
+        }
+        Ok(())
+    }
+
+    async fn ensure_active_enabled(&mut self) -> StorageResult<()> {
+        self.ensure_available().await?;
+        if self.is_active_enabled {
+            return Ok(());
+        }
+        let active = &self.stores[0];
+        let conflicts = self.get_conflicting_stores();
+        let message = if conflicts.is_empty() {
+            format!(
+                "{} is not the active storage {} of user {}; call set_active to take it over",
+                active.storage_identity_key(),
+                active.user_active_storage().unwrap_or_default(),
+                self.identity_key,
+            )
+        } else {
+            format!(
+                "stores {} also claim to be the active storage of user {}; call set_active to resolve",
+                conflicts.join(", "),
+                self.identity_key,
+            )
+        };
+        Err(StorageError::NotActive(message))
+    }
+
+    /// The active store, for a write
+    async fn active_writer(&mut self) -> StorageResult<&mut dyn WalletStorageProvider> {
+        self.ensure_active_enabled().await?;
+        Ok(self.stores[0].storage.as_mut())
+    }
+
+    /// The active store, for a read
+    fn active(&self) -> &dyn WalletStorageProvider {
+        self.stores[0].storage.as_ref()
+    }
+
+    /// Copy the user's data from store `from` into store `to`, one chunk at
+    /// a time, returning the number of items inserted or updated
+    ///
+    /// Progress is saved in the writer's sync state for the reader after each
+    /// chunk, so a sync that fails part way resumes from the last chunk.
+    ///
+    /// Matches TypeScript `WalletStorageManager.syncToWriter`
+    async fn sync_to_writer(&mut self, from: usize, to: usize) -> StorageResult<i64> {
+        let from_settings = self.stores[from].storage.get_settings().clone();
+        let to_key = self.stores[to].storage_identity_key().to_string();
+        let writer_auth = AuthId {
+            identity_key: self.identity_key.clone(),
+            user_id: self.stores[to].user.as_ref().map(|u| u.user_id),
+            is_active: None,
+        };
+        let mut ss = EntitySyncState::from_storage(
+            self.stores[to].storage.as_mut(),
+            &writer_auth,
+            &from_settings.storage_identity_key,
+            &from_settings.storage_name,
+        )
+        .await?;
+
+        let mut changes = 0;
+        loop {
+            let args = ss.make_request_sync_chunk_args(&self.identity_key, &to_key);
+            let step = match self.stores[from].storage.get_sync_chunk(&args).await {
+                Ok(chunk) => self.stores[to]
+                    .storage
+                    .process_sync_chunk(&args, &chunk)
+                    .await
+                    .map(|r| (chunk, r)),
+                Err(err) => Err(err),
+            };
+            let (chunk, r) = match step {
+                Ok((_, ProcessSyncChunkResult { error: Some(error), .. })) => {
+                    let err = StorageError::Database(format!(
+                        "sync from {} to {} failed: {} {}",
+                        from_settings.storage_identity_key, to_key, error.code, error.description
+                    ));
+                    ss.set_error(error);
+                    ss.update_storage(self.stores[to].storage.as_mut()).await?;
+                    return Err(err);
+                }
+                Ok(step) => step,
+                Err(err) => {
+                    ss.set_error(SyncError::from(&err));
+                    ss.update_storage(self.stores[to].storage.as_mut()).await?;
+                    return Err(err);
+                }
+            };
+            changes += r.inserts + r.updates;
+            let done = r.done || chunk.is_empty();
+            ss.record_chunk(&chunk, &ProcessSyncChunkResult { done, ..r });
+            ss.update_storage(self.stores[to].storage.as_mut()).await?;
+            if done {
+                return Ok(changes);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl WalletStorageReader for WalletStorageManager {
+    fn is_available(&self) -> bool {
+        self.is_available
+    }
+
+    fn get_settings(&self) -> &TableSettings {
+        self.active().get_settings()
+    }
+
+    fn get_fee_model(&self) -> StorageFeeModel {
+        self.active().get_fee_model()
+    }
+
+    fn get_change_baskets(&self) -> StorageChangeBaskets {
+        self.active().get_change_baskets()
+    }
+
+    async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> {
+        self.active().find_user_by_identity_key(identity_key).await
+    }
+
+    async fn find_certificates_auth(&self, auth: &AuthId, args: &FindCertificatesArgs) -> StorageResult<Vec<TableCertificate>> {
+        self.active().find_certificates_auth(auth, args).await
+    }
+
+    async fn count_certificates_auth(&self, auth: &AuthId, args: &FindCertificatesArgs) -> StorageResult<i64> {
+        self.active().count_certificates_auth(auth, args).await
+    }
+
+    async fn find_certificate_fields_auth(&self, auth: &AuthId, certificate_id: i64) -> StorageResult<Vec<TableCertificateField>> {
+        self.active().find_certificate_fields_auth(auth, certificate_id).await
+    }
+
+    async fn find_output_baskets_auth(&self, auth: &AuthId, args: &FindOutputBasketsArgs) -> StorageResult<Vec<TableOutputBasket>> {
+        self.active().find_output_baskets_auth(auth, args).await
+    }
+
+    async fn find_outputs_auth(&self, auth: &AuthId, args: &FindOutputsArgs) -> StorageResult<Vec<TableOutput>> {
+        metrics::timed("find_outputs_auth", self.active().find_outputs_auth(auth, args)).await
+    }
+
+    async fn find_proven_tx_reqs(&self, args: &FindProvenTxReqsArgs) -> StorageResult<Vec<TableProvenTxReq>> {
+        metrics::timed("find_proven_tx_reqs", self.active().find_proven_tx_reqs(args)).await
+    }
+}
+
+#[async_trait]
+impl WalletStorageWriter for WalletStorageManager {
+    /// Make every store available, record each store's user and decide
+    /// whether the active store may be written
+    async fn make_available(&mut self) -> StorageResult<TableSettings> {
+        for store in &mut self.stores {
+            store.storage.make_available().await?;
+            store.user = Some(store.storage.find_or_insert_user(&self.identity_key).await?.user);
+        }
+
+        // The active store's user record may name a backup as the active storage
+        if !self.stores[0].claims_active() {
+            let named = self.stores[0].user_active_storage().unwrap_or_default();
+            if let Some(i) = self.stores.iter().position(|s| s.storage_identity_key() == named) {
+                self.stores.swap(0, i);
+            }
+        }
+
+        self.is_active_enabled =
+            self.stores[0].claims_active() && !self.stores[1..].iter().any(ManagedStorage::claims_active);
+        self.is_available = true;
+        Ok(self.stores[0].storage.get_settings().clone())
+    }
+
+    async fn migrate(&mut self, storage_name: &str, storage_identity_key: &str) -> StorageResult<String> {
+        self.active_writer().await?.migrate(storage_name, storage_identity_key).await
+    }
+
+    /// Destroy every store
+    async fn destroy(&mut self) -> StorageResult<()> {
+        for store in &mut self.stores {
+            store.storage.destroy().await?;
+        }
+        self.is_available = false;
+        Ok(())
+    }
+
+    async fn find_or_insert_user(&mut self, identity_key: &str) -> StorageResult<FindOrInsertUserResult> {
+        self.ensure_available().await?;
+        self.stores[0].storage.find_or_insert_user(identity_key).await
+    }
+
+    async fn insert_certificate_auth(&mut self, auth: &AuthId, certificate: &TableCertificate) -> StorageResult<i64> {
+        self.active_writer().await?.insert_certificate_auth(auth, certificate).await
+    }
+
+    async fn insert_certificate_field_auth(&mut self, auth: &AuthId, field: &TableCertificateField) -> StorageResult<()> {
+        self.active_writer().await?.insert_certificate_field_auth(auth, field).await
+    }
+}
+
+#[async_trait]
+impl WalletStorageSync for WalletStorageManager {
+    async fn find_or_insert_sync_state_auth(
+        &mut self,
+        auth: &AuthId,
+        storage_identity_key: &str,
+        storage_name: &str,
+    ) -> StorageResult<FindOrInsertSyncStateResult> {
+        self.ensure_available().await?;
+        self.stores[0]
+            .storage
+            .find_or_insert_sync_state_auth(auth, storage_identity_key, storage_name)
+            .await
+    }
+
+    /// Make the store with `new_active_storage_identity_key` the user's active storage
+    ///
+    /// The current active store and any backup claiming to be active are
+    /// synced into the new active store first, then every store's user record
+    /// is pointed at it. Returns the number of items the sync inserted or
+    /// updated; zero when the store is already active.
+    async fn set_active(&mut self, auth: &AuthId, new_active_storage_identity_key: &str) -> StorageResult<i64> {
+        if auth.identity_key != self.identity_key {
+            return Err(StorageError::Unauthorized(
+                "identityKey does not match the managed user".to_string(),
+            ));
+        }
+        self.ensure_available().await?;
+        let new_index = self
+            .stores
+            .iter()
+            .position(|s| s.storage_identity_key() == new_active_storage_identity_key)
+            .ok_or_else(|| {
+                StorageError::InvalidArg(format!(
+                    "{} is not one of the managed stores",
+                    new_active_storage_identity_key
+                ))
+            })?;
+        if new_index == 0 && self.is_active_enabled {
+            return Ok(0);
+        }
+
+        let sources: Vec<usize> = (0..self.stores.len())
+            .filter(|&i| i != new_index && (i == 0 || self.stores[i].claims_active()))
+            .collect();
+        let mut changes = 0;
+        for from in sources {
+            changes += self.sync_to_writer(from, new_index).await?;
+        }
+
+        for store in &mut self.stores {
+            let store_auth = AuthId {
+                identity_key: self.identity_key.clone(),
+                user_id: store.user.as_ref().map(|u| u.user_id),
+                is_active: None,
+            };
+            store.storage.set_active(&store_auth, new_active_storage_identity_key).await?;
+        }
+
+        self.stores.swap(0, new_index);
+        self.is_available = false;
+        self.make_available().await?;
+        Ok(changes)
+    }
+
+    async fn update_sync_state(&mut self, sync_state: &TableSyncState) -> StorageResult<()> {
+        self.ensure_available().await?;
+        self.stores[0].storage.update_sync_state(sync_state).await
+    }
+
+    async fn find_sync_states(&self, args: &FindSyncStatesArgs) -> StorageResult<Vec<TableSyncState>> {
+        self.active().find_sync_states(args).await
+    }
+
+    async fn get_sync_chunk(&self, args: &RequestSyncChunkArgs) -> StorageResult<SyncChunk> {
+        self.active().get_sync_chunk(args).await
+    }
+
+    async fn process_sync_chunk(&mut self, args: &RequestSyncChunkArgs, chunk: &SyncChunk) -> StorageResult<ProcessSyncChunkResult> {
+        self.active_writer().await?.process_sync_chunk(args, chunk).await
+    }
+}
+
+#[async_trait]
+impl WalletStorageProvider for WalletStorageManager {
+    fn is_storage_provider(&self) -> bool {
+        false
+    }
+
+    async fn count_change_inputs(&self, user_id: i64, basket_id: i64, exclude_sending: bool) -> StorageResult<i64> {
+        metrics::timed(
+            "count_change_inputs",
+            self.active().count_change_inputs(user_id, basket_id, exclude_sending),
+        )
+        .await
+    }
+
+    async fn allocate_change_input(
+        &mut self,
+        user_id: i64,
+        basket_id: i64,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+        exclude_sending: bool,
+        transaction_id: i64,
+    ) -> StorageResult<Option<TableOutput>> {
+        let store = self.active_writer().await?;
+        metrics::timed(
+            "allocate_change_input",
+            store.allocate_change_input(user_id, basket_id, target_satoshis, exact_satoshis, exclude_sending, transaction_id),
+        )
+        .await
+    }
+
+    async fn verify_known_valid_transaction(&self, txid: &str) -> StorageResult<bool> {
+        self.active().verify_known_valid_transaction(txid).await
+    }
+
+    async fn get_proven_or_raw_tx(&self, txid: &str) -> StorageResult<ProvenOrRawTx> {
+        self.active().get_proven_or_raw_tx(txid).await
+    }
+
+    async fn insert_proven_tx_req(&mut self, req: &TableProvenTxReq) -> StorageResult<i64> {
+        self.active_writer().await?.insert_proven_tx_req(req).await
+    }
+
+    async fn update_proven_tx_req(&mut self, proven_tx_req_id: i64, updates: &ProvenTxReqUpdates) -> StorageResult<()> {
+        self.active_writer().await?.update_proven_tx_req(proven_tx_req_id, updates).await
+    }
+
+    async fn update_proven_tx_req_with_new_proven_tx(
+        &mut self,
+        args: &UpdateProvenTxReqWithNewProvenTxArgs,
+    ) -> StorageResult<UpdateProvenTxReqWithNewProvenTxResult> {
+        self.active_writer().await?.update_proven_tx_req_with_new_proven_tx(args).await
+    }
+
+    async fn find_proven_txs_from_height(&self, min_height: i64) -> StorageResult<Vec<TableProvenTx>> {
+        self.active().find_proven_txs_from_height(min_height).await
+    }
+
+    async fn rollback_proven_tx(&mut self, proven_tx_id: i64) -> StorageResult<Vec<i64>> {
+        self.active_writer().await?.rollback_proven_tx(proven_tx_id).await
+    }
+    
+    async fn update_proven_tx(&mut self, proven_tx_id: i64, updates: &ProvenTxUpdates) -> StorageResult<()> {
+        self.active_writer().await?.update_proven_tx(proven_tx_id, updates).await
+    }
+
+    async fn get_raw_tx_of_known_valid_transaction(
+        &self,
+        txid: &str,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> StorageResult<Option<Vec<u8>>> {
+        self.active().get_raw_tx_of_known_valid_transaction(txid, offset, length).await
+    }
+
+    async fn find_transactions(
+        &self,
+        user_id: i64,
+        reference: Option<&str>,
+        status: Option<TransactionStatus>,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        metrics::timed("find_transactions", self.active().find_transactions(user_id, reference, status)).await
+    }
+
+    async fn find_transaction_by_id(&self, transaction_id: i64) -> StorageResult<Option<TableTransaction>> {
+        self.active().find_transaction_by_id(transaction_id).await
+    }
+
+    async fn find_transaction_by_txid(&self, user_id: i64, txid: &str) -> StorageResult<Option<TableTransaction>> {
+        self.active().find_transaction_by_txid(user_id, txid).await
+    }
+
+    async fn find_aged_transactions(
+        &self,
+        statuses: &[TransactionStatus],
+        updated_before: &str,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        self.active().find_aged_transactions(statuses, updated_before).await
+    }
+
+    async fn find_outputs_by_transaction(
+        &self,
+        user_id: i64,
+        transaction_id: i64,
+        is_input: bool,
+    ) -> StorageResult<Vec<TableOutput>> {
+        self.active().find_outputs_by_transaction(user_id, transaction_id, is_input).await
+    }
+
+    async fn insert_transaction(&mut self, tx: &TableTransaction) -> StorageResult<i64> {
+        let store = self.active_writer().await?;
+        metrics::timed("insert_transaction", store.insert_transaction(tx)).await
+    }
+
+    async fn update_transaction(&mut self, transaction_id: i64, satoshis: i64) -> StorageResult<()> {
+        self.active_writer().await?.update_transaction(transaction_id, satoshis).await
+    }
+
+    async fn update_transaction_status(&mut self, transaction_id: i64, status: TransactionStatus) -> StorageResult<()> {
+        self.active_writer().await?.update_transaction_status(transaction_id, status).await
+    }
+
+    async fn update_transaction_status_if_unchanged(
+        &mut self,
+        transaction_id: i64,
+        status: TransactionStatus,
+        expected_updated_at: &str,
+    ) -> StorageResult<()> {
+        self.active_writer()
+            .await?
+            .update_transaction_status_if_unchanged(transaction_id, status, expected_updated_at)
+            .await
+    }
+
+    async fn update_transaction_txid(&mut self, transaction_id: i64, txid: &str) -> StorageResult<()> {
+        self.active_writer().await?.update_transaction_txid(transaction_id, txid).await
+    }
+
+    async fn update_transaction_raw_tx(&mut self, transaction_id: i64, raw_tx: &[u8]) -> StorageResult<()> {
+        self.active_writer().await?.update_transaction_raw_tx(transaction_id, raw_tx).await
+    }
+
+    async fn insert_output(&mut self, output: &TableOutput) -> StorageResult<i64> {
+        let store = self.active_writer().await?;
+        metrics::timed("insert_output", store.insert_output(output)).await
+    }
+
+    async fn update_output(&mut self, output_id: i64, updates: &OutputUpdates) -> StorageResult<()> {
+        let store = self.active_writer().await?;
+        metrics::timed("update_output", store.update_output(output_id, updates)).await
+    }
+
+    async fn insert_commission(&mut self, commission: &TableCommission) -> StorageResult<i64> {
+        self.active_writer().await?.insert_commission(commission).await
+    }
+
+    async fn get_balance(&self, user_id: i64) -> StorageResult<i64> {
+        self.active().get_balance(user_id).await
+    }
+
+    async fn get_basket_balances(&self, user_id: i64) -> StorageResult<Vec<BasketBalance>> {
+        self.active().get_basket_balances(user_id).await
+    }
+
+    async fn find_or_insert_output_basket(&mut self, user_id: i64, name: &str) -> StorageResult<TableOutputBasket> {
+        self.active_writer().await?.find_or_insert_output_basket(user_id, name).await
+    }
+
+    async fn update_output_basket(&mut self, basket_id: i64, updates: &OutputBasketUpdates) -> StorageResult<()> {
+        self.active_writer().await?.update_output_basket(basket_id, updates).await
+    }
+
+    async fn find_or_insert_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<TableOutputTag> {
+        self.active_writer().await?.find_or_insert_output_tag(user_id, tag).await
+    }
+
+    async fn find_or_insert_output_tag_map(&mut self, output_id: i64, output_tag_id: i64) -> StorageResult<()> {
+        self.active_writer().await?.find_or_insert_output_tag_map(output_id, output_tag_id).await
+    }
+
+    async fn find_or_insert_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<TableTxLabel> {
+        self.active_writer().await?.find_or_insert_tx_label(user_id, label).await
+    }
+
+    async fn find_or_insert_tx_label_map(&mut self, transaction_id: i64, tx_label_id: i64) -> StorageResult<()> {
+        self.active_writer().await?.find_or_insert_tx_label_map(transaction_id, tx_label_id).await
+    }
+
+    async fn find_or_insert_output_tags(&mut self, user_id: i64, tags: &[String]) -> StorageResult<Vec<TableOutputTag>> {
+        self.active_writer().await?.find_or_insert_output_tags(user_id, tags).await
+    }
+
+    async fn find_or_insert_tx_labels(&mut self, user_id: i64, labels: &[String]) -> StorageResult<Vec<TableTxLabel>> {
+        self.active_writer().await?.find_or_insert_tx_labels(user_id, labels).await
+    }
+
+    async fn find_tx_labels_for_transaction(&self, transaction_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        self.active().find_tx_labels_for_transaction(transaction_id).await
+    }
+
+    async fn find_output_tags(&self, user_id: i64) -> StorageResult<Vec<TableOutputTag>> {
+        self.active().find_output_tags(user_id).await
+    }
+
+    async fn find_tx_labels(&self, user_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        self.active().find_tx_labels(user_id).await
+    }
+
+    async fn rename_output_tag(&mut self, user_id: i64, tag: &str, new_tag: &str) -> StorageResult<TableOutputTag> {
+        self.active_writer().await?.rename_output_tag(user_id, tag, new_tag).await
+    }
+
+    async fn delete_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<()> {
+        self.active_writer().await?.delete_output_tag(user_id, tag).await
+    }
+
+    async fn rename_tx_label(&mut self, user_id: i64, label: &str, new_label: &str) -> StorageResult<TableTxLabel> {
+        self.active_writer().await?.rename_tx_label(user_id, label, new_label).await
+    }
+
+    async fn delete_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<()> {
+        self.active_writer().await?.delete_tx_label(user_id, label).await
+    }
+
+    async fn get_output_tag_usages(&self, user_id: i64) -> StorageResult<Vec<OutputTagUsage>> {
+        self.active().get_output_tag_usages(user_id).await
+    }
+
+    async fn get_user_usage(&self, user_id: i64) -> StorageResult<UserUsage> {
+        self.active().get_user_usage(user_id).await
+    }
+
+    async fn find_identity_cache(&self, user_id: i64, query: &str) -> StorageResult<Option<TableIdentityCache>> {
+        self.active().find_identity_cache(user_id, query).await
+    }
+
+    async fn upsert_identity_cache(&mut self, entry: &TableIdentityCache) -> StorageResult<i64> {
+        self.active_writer().await?.upsert_identity_cache(entry).await
+    }
+
+    async fn lock_output(
+        &mut self,
+        user_id: i64,
+        txid: &str,
+        vout: u32,
+        reason: &str,
+        ttl: Option<std::time::Duration>,
+    ) -> StorageResult<TableOutputLock> {
+        self.active_writer().await?.lock_output(user_id, txid, vout, reason, ttl).await
+    }
+
+    async fn unlock_output(&mut self, user_id: i64, txid: &str, vout: u32) -> StorageResult<bool> {
+        self.active_writer().await?.unlock_output(user_id, txid, vout).await
+    }
+
+    async fn find_output_lock(&self, output_id: i64) -> StorageResult<Option<TableOutputLock>> {
+        self.active().find_output_lock(output_id).await
+    }
+
+    async fn find_output_locks(&self, user_id: i64) -> StorageResult<Vec<TableOutputLock>> {
+        self.active().find_output_locks(user_id).await
+    }
+
+    async fn insert_monitor_event(&mut self, event: &TableMonitorEvent) -> StorageResult<i64> {
+        self.active_writer().await?.insert_monitor_event(event).await
+    }
+
+    async fn find_monitor_events(&self, args: &FindMonitorEventsArgs) -> StorageResult<Vec<TableMonitorEvent>> {
+        self.active().find_monitor_events(args).await
+    }
+
+    async fn purge_data(&mut self, params: &PurgeParams) -> StorageResult<PurgeResults> {
+        self.active_writer().await?.purge_data(params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct MemoryState {
+        active_storage: String,
+        labels: Vec<TableTxLabel>,
+        transactions: i64,
+        sync_state: Option<TableSyncState>,
+        /// Fail the chunk after this many more are applied
+        fail_after: Option<usize>,
+    }
+
+    /// A store holding one user's labels and a transaction count
+    struct MemoryStore {
+        settings: TableSettings,
+        state: Arc<Mutex<MemoryState>>,
+    }
+
+    impl MemoryStore {
+        /// A store whose user names `active_storage` as active, and a handle on its state
+        fn boxed(key: &str, active_storage: &str, labels: &[&str]) -> (Box<dyn WalletStorageProvider>, Arc<Mutex<MemoryState>>) {
+            let state = Arc::new(Mutex::new(MemoryState {
+                active_storage: active_storage.to_string(),
+                labels: labels.iter().enumerate().map(|(i, l)| TableTxLabel::new(i as i64 + 1, 1, *l)).collect(),
+                transactions: 0,
+                sync_state: None,
+                fail_after: None,
+            }));
+            let store = Self {
+                settings: TableSettings::new(key, key, SettingsChain::Test, DbType::SQLite, 1024),
+                state: state.clone(),
+            };
+            (Box::new(store), state)
+        }
+
+        fn user(&self, identity_key: &str) -> TableUser {
+            TableUser::new(1, identity_key, self.state.lock().unwrap().active_storage.clone())
+        }
+    }
+
+    #[async_trait]
+    impl WalletStorageReader for MemoryStore {
+        fn is_available(&self) -> bool { true }
+        fn get_settings(&self) -> &TableSettings { &self.settings }
+        async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> { Ok(Some(self.user(identity_key))) }
+        async fn find_certificates_auth(&self, _: &AuthId, _: &FindCertificatesArgs) -> StorageResult<Vec<TableCertificate>> { Ok(Vec::new()) }
+        async fn count_certificates_auth(&self, _: &AuthId, _: &FindCertificatesArgs) -> StorageResult<i64> { Ok(0) }
+        async fn find_certificate_fields_auth(&self, _: &AuthId, _: i64) -> StorageResult<Vec<TableCertificateField>> { Ok(Vec::new()) }
+        async fn find_output_baskets_auth(&self, _: &AuthId, _: &FindOutputBasketsArgs) -> StorageResult<Vec<TableOutputBasket>> { Ok(Vec::new()) }
+        async fn find_outputs_auth(&self, _: &AuthId, _: &FindOutputsArgs) -> StorageResult<Vec<TableOutput>> { Ok(Vec::new()) }
+        async fn find_proven_tx_reqs(&self, _: &FindProvenTxReqsArgs) -> StorageResult<Vec<TableProvenTxReq>> { Ok(Vec::new()) }
+    }
+
+    #[async_trait]
+    impl WalletStorageWriter for MemoryStore {
+        async fn make_available(&mut self) -> StorageResult<TableSettings> { Ok(self.settings.clone()) }
+        async fn migrate(&mut self, _: &str, _: &str) -> StorageResult<String> { Err(StorageError::NotImplemented("migrate")) }
+        async fn destroy(&mut self) -> StorageResult<()> { Err(StorageError::NotImplemented("destroy")) }
+        async fn find_or_insert_user(&mut self, identity_key: &str) -> StorageResult<FindOrInsertUserResult> {
+            Ok(FindOrInsertUserResult { user: self.user(identity_key), is_new: false })
+        }
+        async fn insert_certificate_auth(&mut self, _: &AuthId, _: &TableCertificate) -> StorageResult<i64> { Err(StorageError::NotImplemented("insert_certificate_auth")) }
+        async fn insert_certificate_field_auth(&mut self, _: &AuthId, _: &TableCertificateField) -> StorageResult<()> { Err(StorageError::NotImplemented("insert_certificate_field_auth")) }
+    }
+
+    #[async_trait]
+    impl WalletStorageSync for MemoryStore {
+        async fn find_or_insert_sync_state_auth(&mut self, _: &AuthId, key: &str, name: &str) -> StorageResult<FindOrInsertSyncStateResult> {
+            let mut state = self.state.lock().unwrap();
+            let is_new = state.sync_state.is_none();
+            let sync_state = state
+                .sync_state
+                .get_or_insert_with(|| TableSyncState::new(1, 1, key, name, SyncStatus::Unknown, false, "ref", "{}"))
+                .clone();
+            Ok(FindOrInsertSyncStateResult { sync_state, is_new })
+        }
+
+        async fn update_sync_state(&mut self, sync_state: &TableSyncState) -> StorageResult<()> {
+            self.state.lock().unwrap().sync_state = Some(sync_state.clone());
+            Ok(())
+        }
+
+        async fn find_sync_states(&self, _: &FindSyncStatesArgs) -> StorageResult<Vec<TableSyncState>> {
+            Ok(self.state.lock().unwrap().sync_state.iter().cloned().collect())
+        }
+
+        async fn set_active(&mut self, _: &AuthId, new_active_storage_identity_key: &str) -> StorageResult<i64> {
+            self.state.lock().unwrap().active_storage = new_active_storage_identity_key.to_string();
+            Ok(1)
+        }
+
+        async fn get_sync_chunk(&self, args: &RequestSyncChunkArgs) -> StorageResult<SyncChunk> {
+            let offset = args.offsets.iter().find(|o| o.name == "txLabel").map_or(0, |o| o.offset);
+            // One label per chunk, to exercise paging
+            let labels: Vec<_> = self.state.lock().unwrap().labels.iter().skip(offset).take(1).cloned().collect();
+            Ok(SyncChunk { tx_labels: Some(labels), ..Default::default() })
+        }
+
+        async fn process_sync_chunk(&mut self, _: &RequestSyncChunkArgs, chunk: &SyncChunk) -> StorageResult<ProcessSyncChunkResult> {
+            let labels = chunk.tx_labels.clone().unwrap_or_default();
+            let inserts = labels.len() as i64;
+            let mut state = self.state.lock().unwrap();
+            match state.fail_after {
+                Some(0) => {
+                    state.fail_after = None;
+                    return Err(StorageError::Database("connection lost".to_string()));
+                }
+                Some(n) => state.fail_after = Some(n - 1),
+                None => {}
+            }
+            state.labels.extend(labels);
+            Ok(ProcessSyncChunkResult { done: chunk.is_empty(), inserts, ..Default::default() })
+        }
+    }
+
+    #[async_trait]
+    impl WalletStorageProvider for MemoryStore {
+        async fn count_change_inputs(&self, _: i64, _: i64, _: bool) -> StorageResult<i64> { Ok(0) }
+        async fn allocate_change_input(&mut self, _: i64, _: i64, _: i64, _: Option<i64>, _: bool, _: i64) -> StorageResult<Option<TableOutput>> { Ok(None) }
+        async fn verify_known_valid_transaction(&self, _: &str) -> StorageResult<bool> { Ok(false) }
+        async fn get_proven_or_raw_tx(&self, _: &str) -> StorageResult<ProvenOrRawTx> { Err(StorageError::NotImplemented("get_proven_or_raw_tx")) }
+        async fn insert_proven_tx_req(&mut self, _: &TableProvenTxReq) -> StorageResult<i64> { Err(StorageError::NotImplemented("insert_proven_tx_req")) }
+        async fn update_proven_tx_req(&mut self, _: i64, _: &ProvenTxReqUpdates) -> StorageResult<()> { Err(StorageError::NotImplemented("update_proven_tx_req")) }
+        async fn update_proven_tx_req_with_new_proven_tx(&mut self, _: &UpdateProvenTxReqWithNewProvenTxArgs) -> StorageResult<UpdateProvenTxReqWithNewProvenTxResult> {
+            Err(StorageError::NotImplemented("update_proven_tx_req_with_new_proven_tx"))
+        }
+        async fn find_proven_txs_from_height(&self, _: i64) -> StorageResult<Vec<TableProvenTx>> { Ok(Vec::new()) }
+        async fn rollback_proven_tx(&mut self, _: i64) -> StorageResult<Vec<i64>> { Err(StorageError::NotImplemented("rollback_proven_tx")) }
+        async fn get_raw_tx_of_known_valid_transaction(&self, _: &str, _: Option<usize>, _: Option<usize>) -> StorageResult<Option<Vec<u8>>> { Ok(None) }
+        async fn find_transactions(&self, _: i64, _: Option<&str>, _: Option<TransactionStatus>) -> StorageResult<Vec<TableTransaction>> { Ok(Vec::new()) }
+        async fn find_transaction_by_id(&self, _: i64) -> StorageResult<Option<TableTransaction>> { Ok(None) }
+        async fn find_aged_transactions(&self, _: &[TransactionStatus], _: &str) -> StorageResult<Vec<TableTransaction>> { Ok(Vec::new()) }
+        async fn find_outputs_by_transaction(&self, _: i64, _: i64, _: bool) -> StorageResult<Vec<TableOutput>> { Ok(Vec::new()) }
+        async fn insert_transaction(&mut self, _: &TableTransaction) -> StorageResult<i64> {
+            let mut state = self.state.lock().unwrap();
+            state.transactions += 1;
+            Ok(state.transactions)
+        }
+        async fn update_transaction(&mut self, _: i64, _: i64) -> StorageResult<()> { Ok(()) }
+        async fn update_transaction_status(&mut self, _: i64, _: TransactionStatus) -> StorageResult<()> { Ok(()) }
+        async fn update_transaction_status_if_unchanged(&mut self, _: i64, _: TransactionStatus, _: &str) -> StorageResult<()> { Ok(()) }
+        async fn update_transaction_txid(&mut self, _: i64, _: &str) -> StorageResult<()> { Ok(()) }
+        async fn update_transaction_raw_tx(&mut self, _: i64, _: &[u8]) -> StorageResult<()> { Ok(()) }
+        async fn insert_output(&mut self, _: &TableOutput) -> StorageResult<i64> { Err(StorageError::NotImplemented("insert_output")) }
+        async fn update_output(&mut self, _: i64, _: &OutputUpdates) -> StorageResult<()> { Err(StorageError::NotImplemented("update_output")) }
+        async fn insert_commission(&mut self, _: &TableCommission) -> StorageResult<i64> { Err(StorageError::NotImplemented("insert_commission")) }
+        async fn get_balance(&self, _: i64) -> StorageResult<i64> { Ok(0) }
+        async fn get_basket_balances(&self, _: i64) -> StorageResult<Vec<BasketBalance>> { Ok(Vec::new()) }
+        async fn find_or_insert_output_basket(&mut self, _: i64, _: &str) -> StorageResult<TableOutputBasket> { Err(StorageError::NotImplemented("find_or_insert_output_basket")) }
+        async fn update_output_basket(&mut self, _: i64, _: &OutputBasketUpdates) -> StorageResult<()> { Err(StorageError::NotImplemented("update_output_basket")) }
+        async fn find_or_insert_output_tag(&mut self, _: i64, _: &str) -> StorageResult<TableOutputTag> { Err(StorageError::NotImplemented("find_or_insert_output_tag")) }
+        async fn find_or_insert_output_tag_map(&mut self, _: i64, _: i64) -> StorageResult<()> { Err(StorageError::NotImplemented("find_or_insert_output_tag_map")) }
+        async fn find_or_insert_tx_label(&mut self, _: i64, _: &str) -> StorageResult<TableTxLabel> { Err(StorageError::NotImplemented("find_or_insert_tx_label")) }
+        async fn find_or_insert_tx_label_map(&mut self, _: i64, _: i64) -> StorageResult<()> { Err(StorageError::NotImplemented("find_or_insert_tx_label_map")) }
+        async fn find_tx_labels_for_transaction(&self, _: i64) -> StorageResult<Vec<TableTxLabel>> { Ok(Vec::new()) }
+        async fn insert_monitor_event(&mut self, _: &TableMonitorEvent) -> StorageResult<i64> { Err(StorageError::NotImplemented("insert_monitor_event")) }
+        async fn find_monitor_events(&self, _: &FindMonitorEventsArgs) -> StorageResult<Vec<TableMonitorEvent>> { Ok(Vec::new()) }
+        async fn purge_data(&mut self, _: &PurgeParams) -> StorageResult<PurgeResults> { Err(StorageError::NotImplemented("purge_data")) }
+    }
+
+    fn tx() -> TableTransaction {
+        TableTransaction::new(0, 1, TransactionStatus::Unsigned, "ref", true, 0, "test")
+    }
+
+    #[tokio::test]
+    async fn test_writes_to_active_storage() {
+        let (a, a_state) = MemoryStore::boxed("A", "A", &[]);
+        let (b, _) = MemoryStore::boxed("B", "A", &[]);
+        let mut manager = WalletStorageManager::new("alice", a, vec![b]);
+
+        manager.make_available().await.unwrap();
+        assert!(manager.is_active_enabled());
+        assert_eq!(manager.get_active_store(), Some("A"));
+        assert_eq!(manager.get_backup_stores(), vec!["B"]);
+        assert_eq!(manager.get_auth().await.unwrap().is_active, Some(true));
+
+        manager.insert_transaction(&tx()).await.unwrap();
+        assert_eq!(a_state.lock().unwrap().transactions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_writes_to_non_active_storage_rejected() {
+        // The user moved to C, which this manager does not know about
+        let (a, a_state) = MemoryStore::boxed("A", "C", &[]);
+        let mut manager = WalletStorageManager::new("alice", a, vec![]);
+
+        let err = manager.insert_transaction(&tx()).await.unwrap_err();
+        assert!(matches!(err, StorageError::NotActive(_)));
+        assert!(!manager.is_active_enabled());
+        assert_eq!(manager.get_user_active_storage(), Some("C"));
+        assert_eq!(a_state.lock().unwrap().transactions, 0);
+
+        // Reads still reach the store
+        assert_eq!(manager.get_balance(1).await.unwrap(), 0);
+
+        // So do conflicting backups that claim to be active themselves
+        let (a, _) = MemoryStore::boxed("A", "A", &[]);
+        let (b, _) = MemoryStore::boxed("B", "B", &[]);
+        let mut manager = WalletStorageManager::new("alice", a, vec![b]);
+        let err = manager.insert_transaction(&tx()).await.unwrap_err();
+        assert!(matches!(err, StorageError::NotActive(_)));
+        assert_eq!(manager.get_conflicting_stores(), vec!["B"]);
+    }
+
+    #[tokio::test]
+    async fn test_set_active_syncs_and_takes_over() {
+        let (a, a_state) = MemoryStore::boxed("A", "A", &["one", "two"]);
+        let (b, b_state) = MemoryStore::boxed("B", "A", &[]);
+        let mut manager = WalletStorageManager::new("alice", a, vec![b]);
+        let auth = manager.get_auth().await.unwrap();
+
+        let changes = manager.set_active(&auth, "B").await.unwrap();
+        assert_eq!(changes, 2);
+        assert_eq!(b_state.lock().unwrap().labels.len(), 2);
+        assert_eq!(a_state.lock().unwrap().active_storage, "B");
+        assert_eq!(manager.get_active_store(), Some("B"));
+        assert!(manager.is_active_enabled());
+
+        manager.insert_transaction(&tx()).await.unwrap();
+        assert_eq!(b_state.lock().unwrap().transactions, 1);
+        assert_eq!(a_state.lock().unwrap().transactions, 0);
+
+        // Already active
+        assert_eq!(manager.set_active(&auth, "B").await.unwrap(), 0);
+        let err = manager.set_active(&auth, "Z").await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidArg(_)));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_sync_resumes() {
+        let (a, _) = MemoryStore::boxed("A", "A", &["one", "two", "three"]);
+        let (b, b_state) = MemoryStore::boxed("B", "A", &[]);
+        b_state.lock().unwrap().fail_after = Some(1);
+        let mut manager = WalletStorageManager::new("alice", a, vec![b]);
+        let auth = manager.get_auth().await.unwrap();
+
+        let err = manager.set_active(&auth, "B").await.unwrap_err();
+        assert!(matches!(err, StorageError::Database(_)));
+        assert_eq!(manager.get_active_store(), Some("A"));
+        {
+            let state = b_state.lock().unwrap();
+            assert_eq!(state.labels.len(), 1);
+            let ss = EntitySyncState::new(state.sync_state.clone());
+            assert_eq!(ss.status(), SyncStatus::Error);
+            assert_eq!(ss.storage_identity_key(), "A");
+            assert_eq!(ss.error_local().unwrap().code, "WERR_INTERNAL");
+            assert_eq!(ss.sync_map().tx_label.count, 1);
+        }
+
+        // The retry picks up after the label already copied
+        manager.set_active(&auth, "B").await.unwrap();
+        let state = b_state.lock().unwrap();
+        let labels: Vec<_> = state.labels.iter().map(|l| l.label.as_str()).collect();
+        assert_eq!(labels, vec!["one", "two", "three"]);
+        let ss = EntitySyncState::new(state.sync_state.clone());
+        assert_eq!(ss.status(), SyncStatus::Success);
+        assert!(ss.init());
+        assert!(ss.when().is_some());
+        assert!(ss.error_local().is_none());
+        assert_eq!(ss.sync_map().tx_label.count, 0);
+    }
+}
@@ -1,10 +0,0 @@
-use crate::StorageError;
-
-// Placeholder types - will be replaced with BSV SDK types
-pub type Transaction = Vec<u8>;
-pub type Beef = Vec<u8>;
-
-/// Placeholder for getBEEFForTransaction method
-pub fn get_beef_for_transaction(_tx: &Transaction) -> Result<Beef, StorageError> {
-    Err(StorageError::NotImplemented("getBEEFForTransaction"))
-}
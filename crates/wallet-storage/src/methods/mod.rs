@@ -1,7 +1,6 @@
 pub mod attempt_to_post_reqs_to_network;
 pub mod create_action;
 pub mod generate_change;
-pub mod get_beef_for_transaction;
 pub mod internalize_action;
 pub mod list_certificates;
 pub mod list_actions_spec_op;
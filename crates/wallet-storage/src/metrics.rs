@@ -0,0 +1,221 @@
+//! Metrics in the Prometheus text format
+//!
+//! Storage, createAction, the monitor and service calls record counters and
+//! latency histograms here; the wallet server and the monitor daemon expose
+//! them with `render`. Nothing is recorded until `enable` is called, so a
+//! wallet that exports no metrics pays one atomic load per record.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// Path metrics are served at
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Content type of `render`'s output
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Transactions handed to a broadcaster
+pub const BROADCASTS_ATTEMPTED: &str = "wallet_broadcasts_attempted_total";
+/// Transactions a broadcaster accepted
+pub const BROADCASTS_SUCCEEDED: &str = "wallet_broadcasts_succeeded_total";
+/// Merkle proofs the monitor found for unproven transactions
+pub const PROOFS_FOUND: &str = "wallet_proofs_found_total";
+/// Service call latency, labelled by `method`, `provider` and `outcome`
+pub const SERVICE_CALL_SECONDS: &str = "wallet_service_call_seconds";
+/// Storage query latency, labelled by `query`
+pub const STORAGE_QUERY_SECONDS: &str = "wallet_storage_query_seconds";
+/// Time createAction spends allocating change inputs
+pub const CHANGE_ALLOCATION_SECONDS: &str = "wallet_change_allocation_seconds";
+/// Monitor task run time, labelled by `task`
+pub const MONITOR_TASK_SECONDS: &str = "wallet_monitor_task_seconds";
+/// Monitor task runs that failed, labelled by `task`
+pub const MONITOR_TASK_FAILURES: &str = "wallet_monitor_task_failures_total";
+
+/// Upper bounds, in seconds, of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 11] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 2.5, 10.0];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+type Key = (&'static str, Vec<(String, String)>);
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Observations at or below each of `LATENCY_BUCKETS`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    counters: BTreeMap<Key, u64>,
+    histograms: BTreeMap<Key, Histogram>,
+}
+
+impl Registry {
+    fn inc_by(&mut self, name: &'static str, labels: &[(&str, &str)], n: u64) {
+        *self.counters.entry(key(name, labels)).or_default() += n;
+    }
+
+    fn observe(&mut self, name: &'static str, labels: &[(&str, &str)], seconds: f64) {
+        let histogram = self.histograms.entry(key(name, labels)).or_default();
+        for (count, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        histogram.count += 1;
+        histogram.sum += seconds;
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let mut family = "";
+        for ((name, labels), value) in &self.counters {
+            if *name != family {
+                family = name;
+                let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help(name), name);
+            }
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+        family = "";
+        for ((name, labels), histogram) in &self.histograms {
+            if *name != family {
+                family = name;
+                let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help(name), name);
+            }
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                let le = bound.to_string();
+                let _ = writeln!(out, "{}_bucket{} {}", name, format_labels(labels, Some(&le)), count);
+            }
+            let _ = writeln!(out, "{}_bucket{} {}", name, format_labels(labels, Some("+Inf")), histogram.count);
+            let _ = writeln!(out, "{}_sum{} {}", name, format_labels(labels, None), histogram.sum);
+            let _ = writeln!(out, "{}_count{} {}", name, format_labels(labels, None), histogram.count);
+        }
+        out
+    }
+}
+
+fn key(name: &'static str, labels: &[(&str, &str)]) -> Key {
+    (name, labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+}
+
+fn help(name: &str) -> &'static str {
+    match name {
+        BROADCASTS_ATTEMPTED => "Transactions handed to a broadcaster",
+        BROADCASTS_SUCCEEDED => "Transactions a broadcaster accepted",
+        PROOFS_FOUND => "Merkle proofs found for unproven transactions",
+        SERVICE_CALL_SECONDS => "Service call latency in seconds",
+        STORAGE_QUERY_SECONDS => "Storage query latency in seconds",
+        CHANGE_ALLOCATION_SECONDS => "Time spent allocating change inputs in seconds",
+        MONITOR_TASK_SECONDS => "Monitor task run time in seconds",
+        MONITOR_TASK_FAILURES => "Monitor task runs that failed",
+        _ => "",
+    }
+}
+
+fn format_labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Start recording metrics
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add one to the counter `name`
+pub fn inc(name: &'static str, labels: &[(&str, &str)]) {
+    inc_by(name, labels, 1);
+}
+
+/// Add `n` to the counter `name`
+pub fn inc_by(name: &'static str, labels: &[(&str, &str)], n: u64) {
+    if is_enabled() {
+        registry().inc_by(name, labels, n);
+    }
+}
+
+/// Record `seconds` in the histogram `name`
+pub fn observe(name: &'static str, labels: &[(&str, &str)], seconds: f64) {
+    if is_enabled() {
+        registry().observe(name, labels, seconds);
+    }
+}
+
+/// Record the time since `started` in the histogram `name`
+pub fn observe_since(name: &'static str, labels: &[(&str, &str)], started: Instant) {
+    observe(name, labels, started.elapsed().as_secs_f64());
+}
+
+/// Await `fut`, recording how long it took as storage query `query`
+pub async fn timed<F: Future>(query: &str, fut: F) -> F::Output {
+    let started = Instant::now();
+    let output = fut.await;
+    observe_since(STORAGE_QUERY_SECONDS, &[("query", query)], started);
+    output
+}
+
+/// Everything recorded so far, in the Prometheus text format
+pub fn render() -> String {
+    registry().render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut registry = Registry::default();
+        assert_eq!(registry.render(), "");
+
+        registry.inc_by(BROADCASTS_ATTEMPTED, &[], 2);
+        registry.inc_by(PROOFS_FOUND, &[], 1);
+        registry.observe(SERVICE_CALL_SECONDS, &[("method", "getRawTx"), ("provider", "WhatsOnChain")], 0.02);
+        registry.observe(SERVICE_CALL_SECONDS, &[("method", "getRawTx"), ("provider", "WhatsOnChain")], 3.0);
+        registry.observe(MONITOR_TASK_SECONDS, &[("task", "say \"hi\"")], 0.0);
+
+        let text = registry.render();
+        assert!(text.contains("# TYPE wallet_broadcasts_attempted_total counter\nwallet_broadcasts_attempted_total 2\n"));
+        assert!(text.contains("wallet_proofs_found_total 1\n"));
+        assert!(text.contains("# TYPE wallet_service_call_seconds histogram\n"));
+        assert!(text.contains(
+            "wallet_service_call_seconds_bucket{method=\"getRawTx\",provider=\"WhatsOnChain\",le=\"0.01\"} 0\n"
+        ));
+        assert!(text.contains(
+            "wallet_service_call_seconds_bucket{method=\"getRawTx\",provider=\"WhatsOnChain\",le=\"0.025\"} 1\n"
+        ));
+        assert!(text.contains(
+            "wallet_service_call_seconds_bucket{method=\"getRawTx\",provider=\"WhatsOnChain\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(text.contains("wallet_service_call_seconds_sum{method=\"getRawTx\",provider=\"WhatsOnChain\"} 3.02\n"));
+        assert!(text.contains("wallet_service_call_seconds_count{method=\"getRawTx\",provider=\"WhatsOnChain\"} 2\n"));
+        assert!(text.contains("wallet_monitor_task_seconds_count{task=\"say \\\"hi\\\"\"} 1\n"));
+    }
+}
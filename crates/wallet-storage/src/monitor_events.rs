@@ -0,0 +1,201 @@
+//! Typed monitor events
+//!
+//! Monitor events are stored as `TableMonitorEvent` rows of an event name and
+//! free-form details. `MonitorEvent` types the events the wallet and monitor
+//! record, with JSON details, so operators can query and chart them.
+//!
+//! Task logs keep the TypeScript form: the task name as the event and the
+//! log as its details. Rows of any other unknown event read back as task logs.
+//!
+//! Reference: wallet-toolbox/src/monitor/Monitor.ts (logEvent)
+
+use serde::{Deserialize, Serialize};
+
+use crate::TableMonitorEvent;
+
+/// An event recorded by the wallet or its monitor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all_fields = "camelCase")]
+pub enum MonitorEvent {
+    /// A transaction was posted to the network
+    BroadcastAttempt {
+        txid: String,
+        /// Broadcast outcome: success, doubleSpend, serviceError or invalidTx
+        status: String,
+        message: Option<String>,
+    },
+
+    /// A merkle proof was found and stored for a transaction
+    ProofFound {
+        txid: String,
+        height: i64,
+        proven_tx_id: i64,
+    },
+
+    /// A proven transaction's block left the active chain
+    Reorg {
+        txid: String,
+        block_hash: String,
+        height: i64,
+        /// Transactions reverted to 'unproven'
+        reverted_transaction_ids: Vec<i64>,
+    },
+
+    /// A proven transaction's stored proof stopped verifying and was
+    /// replaced by a re-fetched one
+    ProofReplaced {
+        txid: String,
+        proven_tx_id: i64,
+        /// Block of the replaced proof
+        old_block_hash: String,
+        height: i64,
+        block_hash: String,
+    },
+
+    /// A monitor task run failed
+    TaskError {
+        task: String,
+        error: String,
+    },
+
+    /// Work done by a monitor task run
+    TaskLog {
+        task: String,
+        log: String,
+    },
+}
+
+/// Event names of the typed variants, as stored in `TableMonitorEvent.event`
+const TYPED_EVENTS: [&str; 5] = ["BroadcastAttempt", "ProofFound", "Reorg", "ProofReplaced", "TaskError"];
+
+impl MonitorEvent {
+    /// Stored event name: the variant, or the task for a `TaskLog`
+    pub fn name(&self) -> &str {
+        match self {
+            MonitorEvent::BroadcastAttempt { .. } => "BroadcastAttempt",
+            MonitorEvent::ProofFound { .. } => "ProofFound",
+            MonitorEvent::Reorg { .. } => "Reorg",
+            MonitorEvent::ProofReplaced { .. } => "ProofReplaced",
+            MonitorEvent::TaskError { .. } => "TaskError",
+            MonitorEvent::TaskLog { task, .. } => task,
+        }
+    }
+
+    /// Row recording this event
+    pub fn to_table(&self) -> TableMonitorEvent {
+        let details = match self {
+            MonitorEvent::TaskLog { log, .. } => log.clone(),
+            _ => {
+                // Externally tagged: {"<name>": {<fields>}}
+                let value = serde_json::to_value(self).expect("monitor events serialize");
+                value.get(self.name()).map(|fields| fields.to_string()).unwrap_or_default()
+            }
+        };
+        TableMonitorEvent::new(0, self.name()).with_details(details)
+    }
+
+    /// Event recorded by `row`
+    ///
+    /// Rows of unknown events, or typed events with unreadable details,
+    /// read back as task logs.
+    pub fn from_table(row: &TableMonitorEvent) -> Self {
+        let details = row.details.clone().unwrap_or_default();
+        if TYPED_EVENTS.contains(&row.event.as_str()) {
+            let tagged = serde_json::from_str::<serde_json::Value>(&details)
+                .map(|fields| serde_json::json!({ row.event.as_str(): fields }));
+            if let Ok(event) = tagged.and_then(serde_json::from_value) {
+                return event;
+            }
+        }
+        MonitorEvent::TaskLog {
+            task: row.event.clone(),
+            log: details,
+        }
+    }
+}
+
+/// A monitor event with its row id and time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorEventRecord {
+    pub id: i64,
+
+    /// When the event was recorded
+    pub created_at: String,
+
+    pub event: MonitorEvent,
+}
+
+impl From<&TableMonitorEvent> for MonitorEventRecord {
+    fn from(row: &TableMonitorEvent) -> Self {
+        Self {
+            id: row.id,
+            created_at: row.created_at.clone(),
+            event: MonitorEvent::from_table(row),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_events_round_trip() {
+        let events = [
+            MonitorEvent::BroadcastAttempt {
+                txid: "aa".to_string(),
+                status: "serviceError".to_string(),
+                message: Some("timeout".to_string()),
+            },
+            MonitorEvent::ProofFound { txid: "aa".to_string(), height: 850_000, proven_tx_id: 7 },
+            MonitorEvent::Reorg {
+                txid: "aa".to_string(),
+                block_hash: "bb".to_string(),
+                height: 850_000,
+                reverted_transaction_ids: vec![1, 2],
+            },
+            MonitorEvent::ProofReplaced {
+                txid: "aa".to_string(),
+                proven_tx_id: 7,
+                old_block_hash: "bb".to_string(),
+                height: 850_001,
+                block_hash: "cc".to_string(),
+            },
+            MonitorEvent::TaskError { task: "Purge".to_string(), error: "boom".to_string() },
+            MonitorEvent::TaskLog { task: "Purge".to_string(), log: "2 records deleted".to_string() },
+        ];
+        for event in events {
+            let row = event.to_table();
+            assert_eq!(row.event, event.name());
+            assert_eq!(MonitorEvent::from_table(&row), event);
+        }
+    }
+
+    #[test]
+    fn test_event_details_are_camel_case_json() {
+        let row = MonitorEvent::ProofFound { txid: "aa".to_string(), height: 1, proven_tx_id: 7 }.to_table();
+        assert_eq!(row.event, "ProofFound");
+        let details: serde_json::Value = serde_json::from_str(row.details.as_deref().unwrap()).unwrap();
+        assert_eq!(details, serde_json::json!({ "txid": "aa", "height": 1, "provenTxId": 7 }));
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_rows_are_task_logs() {
+        let row = TableMonitorEvent::new(3, "CheckForProofs").with_details("aa not mined yet");
+        assert_eq!(
+            MonitorEvent::from_table(&row),
+            MonitorEvent::TaskLog { task: "CheckForProofs".to_string(), log: "aa not mined yet".to_string() }
+        );
+
+        let row = TableMonitorEvent::new(4, "Reorg").with_details("txid aa is no longer on the active chain");
+        assert!(matches!(MonitorEvent::from_table(&row), MonitorEvent::TaskLog { .. }));
+
+        let record = MonitorEventRecord::from(&TableMonitorEvent::new(5, "Startup"));
+        assert_eq!(record.id, 5);
+        assert_eq!(record.event, MonitorEvent::TaskLog { task: "Startup".to_string(), log: String::new() });
+    }
+}
@@ -0,0 +1,166 @@
+//! Per-user storage quotas
+//!
+//! Storage servers holding many users' wallets keep per-user usage counters
+//! and refuse writes that would take a user past the operator's limits.
+//! Stores maintain the counters as rows are inserted and deleted, and check
+//! a write's usage against their `QuotaPolicy` before making it.
+//!
+//! Not part of the TypeScript toolbox.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{StorageError, StorageResult};
+
+/// A counted resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaResource {
+    /// Number of transactions
+    Transactions,
+    /// Number of outputs
+    Outputs,
+    /// Bytes of certificate field values and master keys
+    CertificateBytes,
+    /// Bytes of transaction input BEEFs
+    BeefBytes,
+}
+
+impl QuotaResource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transactions => "transactions",
+            Self::Outputs => "outputs",
+            Self::CertificateBytes => "certificateBytes",
+            Self::BeefBytes => "beefBytes",
+        }
+    }
+}
+
+impl fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a user's stored data counts against their quota
+///
+/// Also used for the usage a single write adds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserUsage {
+    pub transactions: i64,
+
+    pub outputs: i64,
+
+    #[serde(rename = "certificateBytes")]
+    pub certificate_bytes: i64,
+
+    #[serde(rename = "beefBytes")]
+    pub beef_bytes: i64,
+}
+
+impl UserUsage {
+    /// Usage of one transaction with `input_beef`
+    pub fn transaction(input_beef: Option<&[u8]>) -> Self {
+        Self { transactions: 1, beef_bytes: input_beef.map_or(0, |b| b.len() as i64), ..Default::default() }
+    }
+
+    /// Usage of one output
+    pub fn output() -> Self {
+        Self { outputs: 1, ..Default::default() }
+    }
+
+    /// Usage of one certificate field
+    pub fn certificate_field(field_value: &str, master_key: &str) -> Self {
+        Self { certificate_bytes: (field_value.len() + master_key.len()) as i64, ..Default::default() }
+    }
+
+    pub fn get(&self, resource: QuotaResource) -> i64 {
+        match resource {
+            QuotaResource::Transactions => self.transactions,
+            QuotaResource::Outputs => self.outputs,
+            QuotaResource::CertificateBytes => self.certificate_bytes,
+            QuotaResource::BeefBytes => self.beef_bytes,
+        }
+    }
+}
+
+/// Limits on each user's usage; `None` is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaPolicy {
+    #[serde(rename = "maxTransactions", skip_serializing_if = "Option::is_none")]
+    pub max_transactions: Option<i64>,
+
+    #[serde(rename = "maxOutputs", skip_serializing_if = "Option::is_none")]
+    pub max_outputs: Option<i64>,
+
+    #[serde(rename = "maxCertificateBytes", skip_serializing_if = "Option::is_none")]
+    pub max_certificate_bytes: Option<i64>,
+
+    #[serde(rename = "maxBeefBytes", skip_serializing_if = "Option::is_none")]
+    pub max_beef_bytes: Option<i64>,
+}
+
+impl QuotaPolicy {
+    /// No limits
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn limit(&self, resource: QuotaResource) -> Option<i64> {
+        match resource {
+            QuotaResource::Transactions => self.max_transactions,
+            QuotaResource::Outputs => self.max_outputs,
+            QuotaResource::CertificateBytes => self.max_certificate_bytes,
+            QuotaResource::BeefBytes => self.max_beef_bytes,
+        }
+    }
+
+    /// Check that adding `added` to user `user_id`'s `usage` stays within limits
+    ///
+    /// Fails with `StorageError::QuotaExceeded` for the first resource that
+    /// would exceed its limit. Writes that add nothing to a resource pass even
+    /// when the user is already over its limit, so deletes and updates that
+    /// shrink usage are never refused.
+    pub fn check(&self, user_id: i64, usage: &UserUsage, added: &UserUsage) -> StorageResult<()> {
+        let resources = [
+            QuotaResource::Transactions,
+            QuotaResource::Outputs,
+            QuotaResource::CertificateBytes,
+            QuotaResource::BeefBytes,
+        ];
+        for resource in resources {
+            let (Some(limit), amount) = (self.limit(resource), added.get(resource)) else {
+                continue;
+            };
+            let requested = usage.get(resource) + amount;
+            if amount > 0 && requested > limit {
+                return Err(StorageError::QuotaExceeded { user_id, resource, limit, requested });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_policy_check() {
+        let policy = QuotaPolicy { max_transactions: Some(2), max_beef_bytes: Some(100), ..Default::default() };
+        let usage = UserUsage { transactions: 1, outputs: 1_000, beef_bytes: 60, ..Default::default() };
+
+        assert!(policy.check(7, &usage, &UserUsage::transaction(Some(&[0; 40]))).is_ok());
+        assert!(policy.check(7, &usage, &UserUsage::output()).is_ok());
+        assert!(matches!(
+            policy.check(7, &usage, &UserUsage::transaction(Some(&[0; 41]))),
+            Err(StorageError::QuotaExceeded { user_id: 7, resource: QuotaResource::BeefBytes, limit: 100, requested: 101 })
+        ));
+
+        let full = UserUsage { transactions: 2, ..usage };
+        let err = policy.check(7, &full, &UserUsage::transaction(None)).unwrap_err();
+        assert_eq!(err.to_string(), "quota exceeded: user 7 transactions would be 3, limit 2");
+        assert!(QuotaPolicy::unlimited().check(7, &full, &UserUsage::transaction(None)).is_ok());
+    }
+}
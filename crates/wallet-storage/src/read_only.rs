@@ -0,0 +1,87 @@
+//! Read-only access to a store
+//!
+//! Dashboards and reports only read the wallet. Wrapping their store in
+//! `ReadOnly` exposes just `WalletStorageReader`, so a write through it does
+//! not compile, whatever the store underneath allows.
+
+use async_trait::async_trait;
+
+use crate::*;
+
+/// A store restricted to `WalletStorageReader`
+pub struct ReadOnly<S> {
+    inner: S,
+}
+
+impl<S: WalletStorageReader> ReadOnly<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<S: WalletStorageReader> WalletStorageReader for ReadOnly<S> {
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+
+    fn get_settings(&self) -> &TableSettings {
+        self.inner.get_settings()
+    }
+
+    fn get_fee_model(&self) -> StorageFeeModel {
+        self.inner.get_fee_model()
+    }
+
+    fn get_change_baskets(&self) -> StorageChangeBaskets {
+        self.inner.get_change_baskets()
+    }
+
+    async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> {
+        self.inner.find_user_by_identity_key(identity_key).await
+    }
+
+    async fn validate_auth(&self, auth: &AuthId) -> StorageResult<i64> {
+        self.inner.validate_auth(auth).await
+    }
+
+    async fn find_certificates_auth(
+        &self,
+        auth: &AuthId,
+        args: &FindCertificatesArgs,
+    ) -> StorageResult<Vec<TableCertificate>> {
+        self.inner.find_certificates_auth(auth, args).await
+    }
+
+    async fn count_certificates_auth(&self, auth: &AuthId, args: &FindCertificatesArgs) -> StorageResult<i64> {
+        self.inner.count_certificates_auth(auth, args).await
+    }
+
+    async fn find_certificate_fields_auth(
+        &self,
+        auth: &AuthId,
+        certificate_id: i64,
+    ) -> StorageResult<Vec<TableCertificateField>> {
+        self.inner.find_certificate_fields_auth(auth, certificate_id).await
+    }
+
+    async fn find_output_baskets_auth(
+        &self,
+        auth: &AuthId,
+        args: &FindOutputBasketsArgs,
+    ) -> StorageResult<Vec<TableOutputBasket>> {
+        self.inner.find_output_baskets_auth(auth, args).await
+    }
+
+    async fn find_outputs_auth(&self, auth: &AuthId, args: &FindOutputsArgs) -> StorageResult<Vec<TableOutput>> {
+        self.inner.find_outputs_auth(auth, args).await
+    }
+
+    async fn find_proven_tx_reqs(&self, args: &FindProvenTxReqsArgs) -> StorageResult<Vec<TableProvenTxReq>> {
+        self.inner.find_proven_tx_reqs(args).await
+    }
+
+    async fn get_req_history(&self, txid: &str) -> StorageResult<Option<ProvenTxReqHistory>> {
+        self.inner.get_req_history(txid).await
+    }
+}
@@ -82,18 +82,61 @@ impl EntityProvenTxReq {
 
     /// Unpack history from API JSON string
     pub fn unpack_api_history(&mut self) {
-        self.history = serde_json::from_str(&self.api.history).unwrap_or_default();
+        self.history = Self::parse_history(&self.api.history);
     }
 
     /// Unpack notify from API JSON string
     pub fn unpack_api_notify(&mut self) {
-        self.notify = serde_json::from_str(&self.api.notify).unwrap_or_default();
+        self.notify = Self::parse_notify(&self.api.notify);
+    }
+
+    /// Parse a `history` JSON string, empty when it is malformed
+    ///
+    /// Reference: TypeScript `EntityProvenTxReq.parseHistory`
+    pub fn parse_history(json: &str) -> ProvenTxReqHistory {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+
+    /// Parse a `notify` JSON string, empty when it is malformed
+    ///
+    /// Reference: TypeScript `EntityProvenTxReq.parseNotify`
+    pub fn parse_notify(json: &str) -> ProvenTxReqNotify {
+        let mut notify: ProvenTxReqNotify = serde_json::from_str(json).unwrap_or_default();
         
         // Cleanup null values and duplicates
-        if let Some(transaction_ids) = &mut self.notify.transaction_ids {
+        if let Some(transaction_ids) = &mut notify.transaction_ids {
             transaction_ids.sort_unstable();
             transaction_ids.dedup();
         }
+        notify
+    }
+
+    /// Append a note to the history, stamping `when` if it is missing
+    ///
+    /// With `no_dupes`, a note with the same `what` and extra fields as an
+    /// existing one is not added again.
+    ///
+    /// Reference: TypeScript `EntityProvenTxReq.addHistoryNote`
+    pub fn add_history_note(&mut self, mut note: ReqHistoryNote, no_dupes: bool) {
+        let notes = self.history.notes.get_or_insert_with(Vec::new);
+        if no_dupes && notes.iter().any(|n| n.what == note.what && n.extra == note.extra) {
+            return;
+        }
+        if note.when.is_none() {
+            note.when = Some(chrono::Utc::now().to_rfc3339());
+        }
+        notes.push(note);
+    }
+
+    /// Add a transaction to notify when the request completes
+    ///
+    /// Reference: TypeScript `EntityProvenTxReq.addNotifyTransactionId`
+    pub fn add_notify_transaction_id(&mut self, transaction_id: i64) {
+        let transaction_ids = self.notify.transaction_ids.get_or_insert_with(Vec::new);
+        if !transaction_ids.contains(&transaction_id) {
+            transaction_ids.push(transaction_id);
+            transaction_ids.sort_unstable();
+        }
     }
 
     /// Unpack both history and notify from API
@@ -442,6 +485,24 @@ mod tests {
         assert_eq!(entity.notify().transaction_ids, Some(vec![1, 2, 3]));
     }
 
+    #[test]
+    fn test_entity_proven_tx_req_add_history_note() {
+        let mut entity = EntityProvenTxReq::new(None);
+        let note = ReqHistoryNote {
+            when: None,
+            what: "postBeef".to_string(),
+            extra: HashMap::from([("name".to_string(), serde_json::json!("ARC"))]),
+        };
+
+        entity.add_history_note(note.clone(), true);
+        entity.add_history_note(note.clone(), true);
+        assert_eq!(entity.history().notes.as_ref().unwrap().len(), 1);
+        assert!(entity.history().notes.as_ref().unwrap()[0].when.is_some());
+
+        entity.add_history_note(note, false);
+        assert_eq!(entity.history().notes.as_ref().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_entity_proven_tx_req_equals_same() {
         let req = TableProvenTxReq {
@@ -4,7 +4,9 @@
 //! Reference: wallet-toolbox/src/storage/schema/entities/EntitySyncState.ts
 
 use crate::schema::tables::{TableSyncState, SyncStatus};
-use super::{EntityBase, SyncMap, SyncError};
+use super::{max_date, EntityBase, SyncMap, SyncError};
+use crate::sync::{entity_maps_mut, ProcessSyncChunkResult, RequestSyncChunkArgs, SyncChunk};
+use crate::{AuthId, StorageResult, WalletStorageSync};
 use serde::{Deserialize, Serialize};
 
 /// SyncState entity wrapper providing merge logic and property accessors
@@ -187,6 +189,85 @@ impl EntitySyncState {
         self.api
     }
 
+    /// Find or insert the sync state `storage` keeps for syncs with the
+    /// store `storage_identity_key`
+    ///
+    /// Matches TypeScript `EntitySyncState.fromStorage`
+    pub async fn from_storage<S: WalletStorageSync + ?Sized>(
+        storage: &mut S,
+        auth: &AuthId,
+        storage_identity_key: &str,
+        storage_name: &str,
+    ) -> StorageResult<Self> {
+        let r = storage
+            .find_or_insert_sync_state_auth(auth, storage_identity_key, storage_name)
+            .await?;
+        Ok(Self::new(Some(r.sync_state)))
+    }
+
+    /// Request the chunk following what this sync already received
+    ///
+    /// Asks for items updated since the last completed sync, skipping the
+    /// per-entity counts received since then, so an interrupted sync resumes
+    /// where it stopped.
+    ///
+    /// Matches TypeScript `EntitySyncState.makeRequestSyncChunkArgs`
+    pub fn make_request_sync_chunk_args(
+        &self,
+        identity_key: &str,
+        for_storage_identity_key: &str,
+    ) -> RequestSyncChunkArgs {
+        let mut args = RequestSyncChunkArgs::new(
+            self.storage_identity_key(),
+            for_storage_identity_key,
+            identity_key,
+            &self.sync_map,
+        );
+        args.since = self.api.when.clone();
+        args
+    }
+
+    /// Record a chunk the writer applied
+    ///
+    /// Once `result.done`, `when` advances to the latest `updated_at`
+    /// received and the counts restart for the next sync.
+    pub fn record_chunk(&mut self, chunk: &SyncChunk, result: &ProcessSyncChunkResult) {
+        chunk.record_into(&mut self.sync_map);
+        self.error_local = None;
+        if !result.done {
+            self.api.status = SyncStatus::Updated;
+            return;
+        }
+        let mut when = self.api.when.take();
+        for m in entity_maps_mut(&mut self.sync_map) {
+            if let Some(updated_at) = &m.max_updated_at {
+                when = max_date(when, updated_at.clone());
+            }
+            m.count = 0;
+        }
+        if let Some(updated_at) = &result.max_updated_at {
+            when = max_date(when, updated_at.clone());
+        }
+        self.api.when = when;
+        self.api.status = SyncStatus::Success;
+        self.api.init = true;
+    }
+
+    /// Mark the sync failed; the counts received so far are kept for a retry
+    pub fn set_error(&mut self, error: SyncError) {
+        self.api.status = SyncStatus::Error;
+        self.error_local = Some(error);
+    }
+
+    /// Save this sync state to `storage`
+    ///
+    /// Matches TypeScript `EntitySyncState.updateStorage`
+    pub async fn update_storage<S: WalletStorageSync + ?Sized>(&mut self, storage: &mut S) -> StorageResult<()> {
+        self.api.updated_at = chrono::Utc::now().to_rfc3339();
+        self.pack_api();
+        storage.update_sync_state(&self.api).await
+    }
+
     /// Pack JSON fields into API strings
     fn pack_api(&mut self) {
         self.api.error_local = self.error_local.as_ref()
@@ -378,4 +459,34 @@ mod tests {
         assert_eq!(entity.id(), 999);
         assert_eq!(entity.sync_state_id(), 999);
     }
+
+    #[test]
+    fn test_entity_sync_state_record_chunk() {
+        let mut entity = EntitySyncState::new(None);
+        entity.set_storage_identity_key("reader");
+        let chunk = SyncChunk {
+            tx_labels: Some(vec![crate::TableTxLabel::new(1, 1, "a")]),
+            ..Default::default()
+        };
+
+        entity.record_chunk(&chunk, &ProcessSyncChunkResult::default());
+        assert_eq!(entity.status(), SyncStatus::Updated);
+        let args = entity.make_request_sync_chunk_args("id", "writer");
+        assert_eq!(args.from_storage_identity_key, "reader");
+        assert_eq!(args.since, None);
+        assert_eq!(args.offsets.iter().find(|o| o.name == "txLabel").unwrap().offset, 1);
+
+        let done = ProcessSyncChunkResult { done: true, ..Default::default() };
+        entity.record_chunk(&SyncChunk::default(), &done);
+        assert_eq!(entity.status(), SyncStatus::Success);
+        let args = entity.make_request_sync_chunk_args("id", "writer");
+        assert_eq!(args.since.as_deref(), entity.sync_map().tx_label.max_updated_at.as_deref());
+        assert!(args.offsets.iter().all(|o| o.offset == 0));
+
+        entity.set_error(SyncError::from(&crate::StorageError::NotActive("b".to_string())));
+        assert_eq!(entity.status(), SyncStatus::Error);
+        assert_eq!(entity.error_local().unwrap().code, "WERR_NOT_ACTIVE");
+        let api = entity.into_api();
+        assert!(api.error_local.unwrap().contains("WERR_NOT_ACTIVE"));
+    }
 }
@@ -155,6 +155,27 @@ pub struct SyncError {
     pub stack: Option<String>,
 }
 
+impl From<&StorageError> for SyncError {
+    /// Record a failed sync step, with the WERR code the error maps to
+    fn from(err: &StorageError) -> Self {
+        let code = match err {
+            StorageError::NotImplemented(_) => "WERR_NOT_IMPLEMENTED",
+            StorageError::InvalidArg(_) => "WERR_INVALID_PARAMETER",
+            StorageError::Unauthorized(_) => "WERR_UNAUTHORIZED",
+            StorageError::NotActive(_) => "WERR_NOT_ACTIVE",
+            StorageError::NetworkChain(_) => "WERR_NETWORK_CHAIN",
+            StorageError::InsufficientFunds { .. } => "WERR_INSUFFICIENT_FUNDS",
+            StorageError::QuotaExceeded { .. } => "WERR_QUOTA_EXCEEDED",
+            _ => "WERR_INTERNAL",
+        };
+        Self {
+            code: code.to_string(),
+            description: err.to_string(),
+            stack: None,
+        }
+    }
+}
+
 /// Base entity trait - defines common entity operations
 ///
 /// Matches TypeScript `EntityBase<T>` abstract class
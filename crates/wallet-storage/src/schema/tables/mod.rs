@@ -19,6 +19,8 @@ pub mod table_monitor_event;
 pub mod table_settings;
 pub mod table_certificate;
 pub mod table_certificate_field;
+pub mod table_identity_cache;
+pub mod table_output_lock;
 
 pub use table_user::TableUser;
 pub use table_sync_state::{TableSyncState, SyncStatus};
@@ -36,3 +38,5 @@ pub use table_monitor_event::TableMonitorEvent;
 pub use table_settings::{TableSettings, Chain as SettingsChain, DbType};
 pub use table_certificate::TableCertificate;
 pub use table_certificate_field::TableCertificateField;
+pub use table_identity_cache::TableIdentityCache;
+pub use table_output_lock::TableOutputLock;
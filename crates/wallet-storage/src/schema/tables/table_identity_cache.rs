@@ -0,0 +1,103 @@
+//! TableIdentityCache - Cached identity discovery results
+//!
+//! Not part of the TypeScript schema: the toolbox re-queries the identity
+//! overlay on every discovery, which is slow and rate-limited.
+
+use serde::{Deserialize, Serialize};
+
+/// IdentityCache table - a user's resolved identity lookups, until they expire
+///
+/// One row per lookup: `discoverByIdentityKey` of a key, or
+/// `discoverByAttributes` of a set of field values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableIdentityCache {
+    pub created_at: String,
+    pub updated_at: String,
+
+    #[serde(rename = "identityCacheId")]
+    pub identity_cache_id: i64,
+
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+
+    /// The lookup answered, e.g. "identityKey:02ab.." or "attributes:{..}"
+    pub query: String,
+
+    /// Subject of an identity key lookup
+    #[serde(rename = "identityKey", skip_serializing_if = "Option::is_none")]
+    pub identity_key: Option<String>,
+
+    /// Display name of the subject, from its most trusted certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Avatar of the subject, from its most trusted certificate
+    #[serde(rename = "avatarURL", skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+
+    /// Certificates found, trust-annotated, as JSON
+    pub certificates: String,
+
+    /// Highest summed certifier trust of any subject found
+    #[serde(rename = "trustScore")]
+    pub trust_score: i64,
+
+    /// When the entry stops being served (RFC 3339)
+    #[serde(rename = "expiresAt")]
+    pub expires_at: String,
+}
+
+impl TableIdentityCache {
+    /// Entry for `query` served until `expires_at`
+    pub fn new(
+        identity_cache_id: i64,
+        user_id: i64,
+        query: impl Into<String>,
+        certificates: impl Into<String>,
+        trust_score: i64,
+        expires_at: impl Into<String>,
+    ) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            created_at: now.clone(),
+            updated_at: now,
+            identity_cache_id,
+            user_id,
+            query: query.into(),
+            identity_key: None,
+            name: None,
+            avatar_url: None,
+            certificates: certificates.into(),
+            trust_score,
+            expires_at: expires_at.into(),
+        }
+    }
+
+    /// Whether the entry has expired at `now`
+    ///
+    /// Entries with an unparseable expiry count as expired.
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.expires_at).map_or(true, |expires| expires <= now)
+    }
+
+    pub fn touch(&mut self) {
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_identity_cache_expiry() {
+        let now = chrono::Utc::now();
+        let later = (now + chrono::Duration::minutes(5)).to_rfc3339();
+        let entry = TableIdentityCache::new(1, 1, "identityKey:02ab", "[]", 3, later);
+        assert!(!entry.is_expired(now));
+        assert!(entry.is_expired(now + chrono::Duration::minutes(6)));
+
+        let garbled = TableIdentityCache { expires_at: "soon".to_string(), ..entry };
+        assert!(garbled.is_expired(now));
+    }
+}
@@ -170,6 +170,32 @@ impl TableProvenTxReq {
         self.notified = true;
         self.touch();
     }
+
+    /// Competing txids recorded by `doubleSpend` history notes
+    pub fn competing_txids(&self) -> Vec<String> {
+        let history: serde_json::Value = serde_json::from_str(&self.history).unwrap_or_default();
+        let mut txids: Vec<String> = Vec::new();
+        for note in history.get("notes").and_then(|n| n.as_array()).into_iter().flatten() {
+            if note.get("what").and_then(|w| w.as_str()) != Some("doubleSpend") {
+                continue;
+            }
+            for txid in note.get("competingTxs").and_then(|c| c.as_array()).into_iter().flatten() {
+                if let Some(txid) = txid.as_str().filter(|t| !txids.iter().any(|x| x == t)) {
+                    txids.push(txid.to_string());
+                }
+            }
+        }
+        txids
+    }
+
+    /// Transaction ids listed in the `notify` JSON (`transactionIds`)
+    pub fn notify_transaction_ids(&self) -> Vec<i64> {
+        serde_json::from_str::<serde_json::Value>(&self.notify)
+            .ok()
+            .and_then(|v| v.get("transactionIds").cloned())
+            .and_then(|ids| serde_json::from_value::<Vec<i64>>(ids).ok())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -211,4 +237,30 @@ mod tests {
         let deserialized: TableProvenTxReq = serde_json::from_str(&json).unwrap();
         assert_eq!(req, deserialized);
     }
+
+    #[test]
+    fn test_table_proven_tx_req_notify_transaction_ids() {
+        let mut req = TableProvenTxReq::new(
+            1, ProvenTxReqStatus::Unsent, "txid", "{}", r#"{"transactionIds":[1,2]}"#, vec![]
+        );
+        assert_eq!(req.notify_transaction_ids(), vec![1, 2]);
+        req.notify = "{}".to_string();
+        assert!(req.notify_transaction_ids().is_empty());
+        req.notify = "not json".to_string();
+        assert!(req.notify_transaction_ids().is_empty());
+    }
+
+    #[test]
+    fn test_table_proven_tx_req_history_notes() {
+        let mut req = TableProvenTxReq::new(1, ProvenTxReqStatus::Sending, "txid", "{}", "{}", vec![]);
+        req.history = r#"{"notes":[
+            {"what":"doubleSpend","competingTxs":["a","b"]},
+            {"what":"doubleSpend","competingTxs":["b","c"]},
+            {"what":"postBeef"}
+        ]}"#.to_string();
+        assert_eq!(req.competing_txids(), vec!["a", "b", "c"]);
+
+        req.history = "not json".to_string();
+        assert!(req.competing_txids().is_empty());
+    }
 }
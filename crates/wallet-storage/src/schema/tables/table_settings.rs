@@ -132,6 +132,20 @@ impl TableSettings {
     pub fn touch(&mut self) {
         self.updated_at = chrono::Utc::now().to_rfc3339();
     }
+
+    /// Check the store holds `chain`'s data ("main" or "test")
+    ///
+    /// Fails with `StorageError::NetworkChain` when it does not, so a store
+    /// is never used with keys, services or a wallet on another chain.
+    pub fn verify_chain(&self, chain: &str) -> crate::StorageResult<()> {
+        if chain.parse::<Chain>() != Ok(self.chain) {
+            return Err(crate::StorageError::NetworkChain(format!(
+                "storage {} is on '{}', not '{}'",
+                self.storage_identity_key, self.chain, chain
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +177,14 @@ mod tests {
         assert_eq!(settings, deserialized);
     }
 
+    #[test]
+    fn test_verify_chain() {
+        let settings = TableSettings::new("key", "name", Chain::Test, DbType::SQLite, 5000);
+        assert!(settings.verify_chain("test").is_ok());
+        assert!(matches!(settings.verify_chain("main"), Err(crate::StorageError::NetworkChain(_))));
+        assert!(matches!(settings.verify_chain("regtest"), Err(crate::StorageError::NetworkChain(_))));
+    }
+
     #[test]
     fn test_chain_serialization() {
         assert_eq!(
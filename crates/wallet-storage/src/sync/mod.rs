@@ -1 +1,270 @@
-// Placeholder sync module for storage synchronization utilities
+//! Storage synchronization types
+//!
+//! Stores replicate a user's data between each other one chunk at a time:
+//! the reader answers `get_sync_chunk` and the writer applies the chunk with
+//! `process_sync_chunk`, until the writer reports it is done.
+//! Reference: wallet-toolbox/src/sdk/WalletStorage.interfaces.ts
+
+use crate::schema::entities::{max_date, EntitySyncMap, SyncError, SyncMap};
+use crate::schema::tables::*;
+use serde::{Deserialize, Serialize};
+
+/// Default upper bound on the serialized size of one chunk
+pub const DEFAULT_MAX_ROUGH_SIZE: i64 = 10_000_000;
+
+/// Default upper bound on the number of items in one chunk
+pub const DEFAULT_MAX_ITEMS: i64 = 1000;
+
+/// Number of items of one entity the writer already received
+///
+/// Matches TypeScript `RequestSyncChunkArgs.offsets` entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncChunkOffset {
+    pub name: String,
+    pub offset: usize,
+}
+
+/// Request for the next chunk of a user's data
+///
+/// Matches TypeScript `RequestSyncChunkArgs` interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestSyncChunkArgs {
+    #[serde(rename = "fromStorageIdentityKey")]
+    pub from_storage_identity_key: String,
+
+    #[serde(rename = "toStorageIdentityKey")]
+    pub to_storage_identity_key: String,
+
+    #[serde(rename = "identityKey")]
+    pub identity_key: String,
+
+    /// Only items updated after this ISO 8601 timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+
+    #[serde(rename = "maxRoughSize")]
+    pub max_rough_size: i64,
+
+    #[serde(rename = "maxItems")]
+    pub max_items: i64,
+
+    pub offsets: Vec<SyncChunkOffset>,
+}
+
+impl RequestSyncChunkArgs {
+    /// Request the chunk following the items counted in `sync_map`
+    pub fn new(
+        from_storage_identity_key: impl Into<String>,
+        to_storage_identity_key: impl Into<String>,
+        identity_key: impl Into<String>,
+        sync_map: &SyncMap,
+    ) -> Self {
+        let offsets = entity_maps(sync_map)
+            .iter()
+            .map(|m| SyncChunkOffset { name: m.entity_name.clone(), offset: m.count })
+            .collect();
+        Self {
+            from_storage_identity_key: from_storage_identity_key.into(),
+            to_storage_identity_key: to_storage_identity_key.into(),
+            identity_key: identity_key.into(),
+            since: None,
+            max_rough_size: DEFAULT_MAX_ROUGH_SIZE,
+            max_items: DEFAULT_MAX_ITEMS,
+            offsets,
+        }
+    }
+}
+
+/// One chunk of a user's data, in dependency order
+///
+/// Matches TypeScript `SyncChunk` interface
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncChunk {
+    #[serde(rename = "fromStorageIdentityKey")]
+    pub from_storage_identity_key: String,
+
+    #[serde(rename = "toStorageIdentityKey")]
+    pub to_storage_identity_key: String,
+
+    #[serde(rename = "userIdentityKey")]
+    pub user_identity_key: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<TableUser>,
+
+    #[serde(rename = "provenTxs", skip_serializing_if = "Option::is_none")]
+    pub proven_txs: Option<Vec<TableProvenTx>>,
+
+    #[serde(rename = "provenTxReqs", skip_serializing_if = "Option::is_none")]
+    pub proven_tx_reqs: Option<Vec<TableProvenTxReq>>,
+
+    #[serde(rename = "outputBaskets", skip_serializing_if = "Option::is_none")]
+    pub output_baskets: Option<Vec<TableOutputBasket>>,
+
+    #[serde(rename = "txLabels", skip_serializing_if = "Option::is_none")]
+    pub tx_labels: Option<Vec<TableTxLabel>>,
+
+    #[serde(rename = "outputTags", skip_serializing_if = "Option::is_none")]
+    pub output_tags: Option<Vec<TableOutputTag>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<Vec<TableTransaction>>,
+
+    #[serde(rename = "txLabelMaps", skip_serializing_if = "Option::is_none")]
+    pub tx_label_maps: Option<Vec<TableTxLabelMap>>,
+
+    #[serde(rename = "outputTagMaps", skip_serializing_if = "Option::is_none")]
+    pub output_tag_maps: Option<Vec<TableOutputTagMap>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificates: Option<Vec<TableCertificate>>,
+
+    #[serde(rename = "certificateFields", skip_serializing_if = "Option::is_none")]
+    pub certificate_fields: Option<Vec<TableCertificateField>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<TableOutput>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commissions: Option<Vec<TableCommission>>,
+}
+
+impl SyncChunk {
+    /// Item counts per entity, in `SyncMap` entity names
+    pub fn item_counts(&self) -> [(&'static str, usize); 12] {
+        fn len<T>(v: &Option<Vec<T>>) -> usize {
+            v.as_ref().map_or(0, Vec::len)
+        }
+        [
+            ("provenTx", len(&self.proven_txs)),
+            ("outputBasket", len(&self.output_baskets)),
+            ("transaction", len(&self.transactions)),
+            ("provenTxReq", len(&self.proven_tx_reqs)),
+            ("txLabel", len(&self.tx_labels)),
+            ("txLabelMap", len(&self.tx_label_maps)),
+            ("output", len(&self.outputs)),
+            ("outputTag", len(&self.output_tags)),
+            ("outputTagMap", len(&self.output_tag_maps)),
+            ("certificate", len(&self.certificates)),
+            ("certificateField", len(&self.certificate_fields)),
+            ("commission", len(&self.commissions)),
+        ]
+    }
+
+    /// True when the chunk carries no entity items
+    pub fn is_empty(&self) -> bool {
+        self.item_counts().iter().all(|(_, n)| *n == 0)
+    }
+
+    /// Latest `updated_at` of each entity's items, in `item_counts` order
+    fn max_updated_ats(&self) -> [Option<&str>; 12] {
+        fn max<T>(v: &Option<Vec<T>>, updated_at: impl Fn(&T) -> &str) -> Option<&str> {
+            v.as_ref()?.iter().map(updated_at).max()
+        }
+        [
+            max(&self.proven_txs, |e| &e.updated_at),
+            max(&self.output_baskets, |e| &e.updated_at),
+            max(&self.transactions, |e| &e.updated_at),
+            max(&self.proven_tx_reqs, |e| &e.updated_at),
+            max(&self.tx_labels, |e| &e.updated_at),
+            max(&self.tx_label_maps, |e| &e.updated_at),
+            max(&self.outputs, |e| &e.updated_at),
+            max(&self.output_tags, |e| &e.updated_at),
+            max(&self.output_tag_maps, |e| &e.updated_at),
+            max(&self.certificates, |e| &e.updated_at),
+            max(&self.certificate_fields, |e| &e.updated_at),
+            max(&self.commissions, |e| &e.updated_at),
+        ]
+    }
+
+    /// Add this chunk's item counts and latest `updated_at` to `sync_map`,
+    /// so the next request asks for the items that follow
+    pub fn record_into(&self, sync_map: &mut SyncMap) {
+        let counts = self.item_counts();
+        let maxes = self.max_updated_ats();
+        for (i, m) in entity_maps_mut(sync_map).into_iter().enumerate() {
+            m.count += counts[i].1;
+            if let Some(updated_at) = maxes[i] {
+                m.max_updated_at = max_date(m.max_updated_at.take(), updated_at.to_string());
+            }
+        }
+    }
+}
+
+/// Outcome of applying one chunk
+///
+/// Matches TypeScript `ProcessSyncChunkResult` interface
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessSyncChunkResult {
+    /// No further chunks are needed
+    pub done: bool,
+
+    #[serde(rename = "maxUpdated_at", skip_serializing_if = "Option::is_none")]
+    pub max_updated_at: Option<String>,
+
+    pub updates: i64,
+
+    pub inserts: i64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<SyncError>,
+}
+
+pub(crate) fn entity_maps(m: &SyncMap) -> [&EntitySyncMap; 12] {
+    [
+        &m.proven_tx,
+        &m.output_basket,
+        &m.transaction,
+        &m.proven_tx_req,
+        &m.tx_label,
+        &m.tx_label_map,
+        &m.output,
+        &m.output_tag,
+        &m.output_tag_map,
+        &m.certificate,
+        &m.certificate_field,
+        &m.commission,
+    ]
+}
+
+pub(crate) fn entity_maps_mut(m: &mut SyncMap) -> [&mut EntitySyncMap; 12] {
+    [
+        &mut m.proven_tx,
+        &mut m.output_basket,
+        &mut m.transaction,
+        &mut m.proven_tx_req,
+        &mut m.tx_label,
+        &mut m.tx_label_map,
+        &mut m.output,
+        &mut m.output_tag,
+        &mut m.output_tag_map,
+        &mut m.certificate,
+        &mut m.certificate_field,
+        &mut m.commission,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_offsets_follow_chunk_counts() {
+        let mut sync_map = SyncMap::new();
+        let chunk = SyncChunk {
+            tx_labels: Some(vec![TableTxLabel::new(1, 1, "a"), TableTxLabel::new(2, 1, "b")]),
+            ..Default::default()
+        };
+        assert!(!chunk.is_empty());
+        chunk.record_into(&mut sync_map);
+        chunk.record_into(&mut sync_map);
+
+        let args = RequestSyncChunkArgs::new("from", "to", "id", &sync_map);
+        let labels = args.offsets.iter().find(|o| o.name == "txLabel").unwrap();
+        assert_eq!(labels.offset, 4);
+        assert_eq!(args.offsets.len(), 12);
+        assert!(sync_map.tx_label.max_updated_at.is_some());
+        assert!(sync_map.output.max_updated_at.is_none());
+        assert!(SyncChunk::default().is_empty());
+    }
+}
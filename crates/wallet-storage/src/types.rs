@@ -4,6 +4,7 @@
 //! Reference: wallet-toolbox/src/sdk/WalletStorage.interfaces.ts
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::schema::tables::*;
 
 /// Authentication identity
@@ -115,7 +116,7 @@ pub struct FindCertificatesArgs {
 }
 
 /// Partial certificate for filtering
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PartialCertificate {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub certificate_type: Option<String>,
@@ -128,6 +129,12 @@ pub struct PartialCertificate {
     
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subject: Option<String>,
+    
+    #[serde(rename = "revocationOutpoint", skip_serializing_if = "Option::is_none")]
+    pub revocation_outpoint: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 /// Partial output for filtering
@@ -204,6 +211,249 @@ pub struct FindProvenTxReqsArgs {
     
     #[serde(skip_serializing_if = "Option::is_none")]
     pub paged: Option<Paged>,
+    
+    /// Restrict to requests for these txids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txids: Option<Vec<String>>,
+}
+
+/// Find monitor events arguments
+///
+/// `since` is inclusive and `until` exclusive; both are RFC 3339 timestamps
+/// compared with `created_at`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindMonitorEventsArgs {
+    /// Only events with this name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paged: Option<Paged>,
+    
+    #[serde(rename = "orderDescending", skip_serializing_if = "Option::is_none")]
+    pub order_descending: Option<bool>,
+}
+
+/// Arguments for finding sync states
+///
+/// Every filter is optional; with none set all sync states are returned.
+/// Reference: TypeScript `FindSyncStatesArgs`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindSyncStatesArgs {
+    #[serde(rename = "userId", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i64>,
+    
+    /// Only sync states recording syncs with this store
+    #[serde(rename = "storageIdentityKey", skip_serializing_if = "Option::is_none")]
+    pub storage_identity_key: Option<String>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<SyncStatus>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paged: Option<Paged>,
+}
+
+/// Proven transaction request update fields
+/// Used for partial updates to proven_tx_reqs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenTxReqUpdates {
+    /// New request status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ProvenTxReqStatus>,
+    
+    /// Broadcast batch identifier shared by transactions sent together
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
+    
+    /// Number of broadcast / proof attempts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<i32>,
+    
+    /// Replacement processing history JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<String>,
+    
+    /// Refuse the update with `StorageError::Conflict` unless the request
+    /// is still the version last updated at this time
+    #[serde(rename = "expectedUpdatedAt", skip_serializing_if = "Option::is_none")]
+    pub expected_updated_at: Option<String>,
+}
+
+/// Proven transaction update fields
+/// Used to replace the proof of a proven_txs row
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenTxUpdates {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i64>,
+    
+    /// Index of the transaction in its block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<i64>,
+    
+    /// Serialized merkle path (BRC-74)
+    #[serde(rename = "merklePath", skip_serializing_if = "Option::is_none")]
+    pub merkle_path: Option<Vec<u8>>,
+    
+    #[serde(rename = "blockHash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<String>,
+    
+    #[serde(rename = "merkleRoot", skip_serializing_if = "Option::is_none")]
+    pub merkle_root: Option<String>,
+}
+
+/// Arguments for promoting a ProvenTxReq to a new ProvenTx
+/// Matches TypeScript `UpdateProvenTxReqWithNewProvenTxArgs`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateProvenTxReqWithNewProvenTxArgs {
+    #[serde(rename = "provenTxReqId")]
+    pub proven_tx_req_id: i64,
+    
+    pub txid: String,
+    
+    pub attempts: i32,
+    
+    pub status: ProvenTxReqStatus,
+    
+    pub history: String,
+    
+    pub height: i64,
+    
+    pub index: i64,
+    
+    #[serde(rename = "blockHash")]
+    pub block_hash: String,
+    
+    #[serde(rename = "merkleRoot")]
+    pub merkle_root: String,
+    
+    /// Serialized merkle path (BRC-74)
+    #[serde(rename = "merklePath")]
+    pub merkle_path: Vec<u8>,
+}
+
+/// Result of promoting a ProvenTxReq to a new ProvenTx
+/// Matches TypeScript `UpdateProvenTxReqWithNewProvenTxResult`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateProvenTxReqWithNewProvenTxResult {
+    pub status: ProvenTxReqStatus,
+    
+    pub history: String,
+    
+    #[serde(rename = "provenTxId")]
+    pub proven_tx_id: i64,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+}
+
+/// Which aged data `purge_data` removes
+/// Matches TypeScript `PurgeParams`; ages are milliseconds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeParams {
+    /// Trim raw transactions and input BEEFs of proven 'completed'
+    /// transactions and delete their notified 'completed' requests
+    #[serde(rename = "purgeCompleted")]
+    pub purge_completed: bool,
+    
+    /// Delete 'failed' transactions with their outputs, labels and
+    /// commissions, and 'invalid' or 'doubleSpend' requests
+    #[serde(rename = "purgeFailed")]
+    pub purge_failed: bool,
+    
+    /// Delete spent outputs, then proven 'completed' transactions left
+    /// without outputs
+    #[serde(rename = "purgeSpent")]
+    pub purge_spent: bool,
+    
+    #[serde(rename = "purgeCompletedAge", skip_serializing_if = "Option::is_none")]
+    pub purge_completed_age: Option<i64>,
+    
+    #[serde(rename = "purgeFailedAge", skip_serializing_if = "Option::is_none")]
+    pub purge_failed_age: Option<i64>,
+    
+    #[serde(rename = "purgeSpentAge", skip_serializing_if = "Option::is_none")]
+    pub purge_spent_age: Option<i64>,
+    
+    /// Compact the database file afterwards where the backend supports it
+    #[serde(default)]
+    pub vacuum: bool,
+}
+
+impl PurgeParams {
+    /// Age used when a purge age is not given (two weeks)
+    pub const DEFAULT_AGE_MSECS: i64 = 1000 * 60 * 60 * 24 * 14;
+}
+
+impl Default for PurgeParams {
+    /// Matches TypeScript `TaskPurge.defaultPurgeParams`
+    fn default() -> Self {
+        Self {
+            purge_completed: false,
+            purge_failed: true,
+            purge_spent: false,
+            purge_completed_age: Some(Self::DEFAULT_AGE_MSECS),
+            purge_failed_age: Some(1000 * 60 * 60 * 24 * 5),
+            purge_spent_age: Some(Self::DEFAULT_AGE_MSECS),
+            vacuum: false,
+        }
+    }
+}
+
+/// Result of `purge_data`
+/// Matches TypeScript `PurgeResults`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeResults {
+    /// Records updated or deleted
+    pub count: i64,
+    
+    /// One line per purge step: "<count> <step>"
+    pub log: String,
+}
+
+impl PurgeResults {
+    /// Record a purge step that touched `count` records
+    pub fn add(&mut self, count: usize, step: &str) {
+        if count > 0 {
+            self.count += count as i64;
+            self.log.push_str(&format!("{} {}\n", count, step));
+        }
+    }
+}
+
+/// Spendable satoshis in one of a user's output baskets
+///
+/// Returned by `get_basket_balances`. Only outputs of 'completed',
+/// 'unproven' or 'sending' transactions count, as for change allocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasketBalance {
+    #[serde(rename = "basketId")]
+    pub basket_id: i64,
+    
+    /// Basket name
+    pub name: String,
+    
+    /// Sum of the basket's spendable outputs
+    pub satoshis: i64,
+    
+    /// Number of spendable outputs in the basket
+    #[serde(rename = "outputCount")]
+    pub output_count: i64,
+}
+
+impl BasketBalance {
+    /// Statuses of transactions whose spendable outputs count towards balances
+    pub const TX_STATUSES: &'static [TransactionStatus] = &[
+        TransactionStatus::Completed,
+        TransactionStatus::Unproven,
+        TransactionStatus::Sending,
+    ];
 }
 
 /// Proven or raw transaction result
@@ -223,20 +473,149 @@ pub struct ProvenOrRawTx {
 }
 
 /// Output update fields
-/// Used for partial updates to outputs
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Used for partial updates to outputs; `None` leaves a column unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OutputUpdates {
     /// Mark output as spendable/unspendable
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spendable: Option<bool>,
     
-    /// Transaction ID that spent this output
-    #[serde(rename = "spentBy", skip_serializing_if = "Option::is_none")]
-    pub spent_by: Option<i64>,
+    /// Transaction ID that spent this output; `Some(None)` clears it when
+    /// an output is released
+    #[serde(
+        rename = "spentBy",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    pub spent_by: Option<Option<i64>>,
     
     /// Description of spending
     #[serde(rename = "spendingDescription", skip_serializing_if = "Option::is_none")]
     pub spending_description: Option<String>,
+    
+    /// Basket the output moves to
+    #[serde(rename = "basketId", skip_serializing_if = "Option::is_none")]
+    pub basket_id: Option<i64>,
+    
+    /// Whether the output is wallet change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<bool>,
+    
+    /// Who provided the output
+    #[serde(rename = "providedBy", skip_serializing_if = "Option::is_none")]
+    pub provided_by: Option<StorageProvidedBy>,
+    
+    /// Output purpose ('change' for wallet change)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    
+    /// Output type ('P2PKH', 'custom')
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub output_type: Option<String>,
+    
+    /// Custom unlocking instructions
+    #[serde(rename = "customInstructions", skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<String>,
+    
+    /// Sender of a wallet payment
+    #[serde(rename = "senderIdentityKey", skip_serializing_if = "Option::is_none")]
+    pub sender_identity_key: Option<String>,
+    
+    /// BRC-29 derivation prefix of a wallet payment
+    #[serde(rename = "derivationPrefix", skip_serializing_if = "Option::is_none")]
+    pub derivation_prefix: Option<String>,
+    
+    /// BRC-29 derivation suffix of a wallet payment
+    #[serde(rename = "derivationSuffix", skip_serializing_if = "Option::is_none")]
+    pub derivation_suffix: Option<String>,
+    
+    /// Refuse the update with `StorageError::Conflict` unless the output is
+    /// still the version last updated at this time
+    #[serde(rename = "expectedUpdatedAt", skip_serializing_if = "Option::is_none")]
+    pub expected_updated_at: Option<String>,
+}
+
+impl OutputUpdates {
+    /// Apply the updates to `output`
+    pub fn apply_to(&self, output: &mut TableOutput) {
+        if let Some(spendable) = self.spendable {
+            output.spendable = spendable;
+        }
+        if let Some(spent_by) = self.spent_by {
+            output.spent_by = spent_by;
+        }
+        if let Some(description) = &self.spending_description {
+            output.spending_description = Some(description.clone());
+        }
+        if let Some(basket_id) = self.basket_id {
+            output.basket_id = Some(basket_id);
+        }
+        if let Some(change) = self.change {
+            output.change = change;
+        }
+        if let Some(provided_by) = self.provided_by {
+            output.provided_by = provided_by;
+        }
+        if let Some(purpose) = &self.purpose {
+            output.purpose = purpose.clone();
+        }
+        if let Some(output_type) = &self.output_type {
+            output.output_type = output_type.clone();
+        }
+        if let Some(instructions) = &self.custom_instructions {
+            output.custom_instructions = Some(instructions.clone());
+        }
+        if let Some(key) = &self.sender_identity_key {
+            output.sender_identity_key = Some(key.clone());
+        }
+        if let Some(prefix) = &self.derivation_prefix {
+            output.derivation_prefix = Some(prefix.clone());
+        }
+        if let Some(suffix) = &self.derivation_suffix {
+            output.derivation_suffix = Some(suffix.clone());
+        }
+    }
+}
+
+/// Deserialize a present field, even `null`, as `Some`, so `Option<Option<T>>`
+/// tells an explicit `null` from an absent field
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Number of outputs carrying one of a user's output tags
+///
+/// Returned by `get_output_tag_usages`. Deleted mappings are not counted,
+/// so a tag no output carries any more has a count of zero.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputTagUsage {
+    #[serde(rename = "outputTagId")]
+    pub output_tag_id: i64,
+    
+    /// Tag name
+    pub tag: String,
+    
+    /// Number of outputs mapped to the tag
+    #[serde(rename = "outputCount")]
+    pub output_count: i64,
+}
+
+/// Output basket update fields
+/// Used for partial updates to a basket's change UTXO policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutputBasketUpdates {
+    /// Number of change UTXOs the wallet tries to keep in the basket
+    #[serde(rename = "numberOfDesiredUTXOs", skip_serializing_if = "Option::is_none")]
+    pub number_of_desired_utxos: Option<i32>,
+    
+    /// Smallest value of a new change UTXO
+    #[serde(rename = "minimumDesiredUTXOValue", skip_serializing_if = "Option::is_none")]
+    pub minimum_desired_utxo_value: Option<i64>,
 }
 
 /// User insertion result
@@ -289,6 +668,64 @@ pub struct WalletStorageInfo {
     pub endpoint_url: Option<String>,
 }
 
+/// Fee model used to compute transaction fees
+///
+/// Matches TypeScript `StorageFeeModel` interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageFeeModel {
+    /// Fee model name; only `"sat/kb"` is currently supported
+    pub model: String,
+
+    /// Satoshis per 1000 bytes of serialized transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+}
+
+impl Default for StorageFeeModel {
+    /// Matches TypeScript `StorageProvider.defaultOptions().feeModel`
+    fn default() -> Self {
+        Self {
+            model: "sat/kb".to_string(),
+            value: Some(1.0),
+        }
+    }
+}
+
+/// Basket receiving createAction change when the caller doesn't name one
+pub const DEFAULT_CHANGE_BASKET: &str = "default";
+
+/// Which output basket funds and receives change for new transactions
+///
+/// Apps wanting their change isolated from the rest of the wallet get a
+/// basket of their own keyed by originator; everyone else shares `default`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageChangeBaskets {
+    /// Basket used when no originator-specific basket is configured
+    pub default: String,
+
+    /// Change basket per originator domain
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub by_originator: HashMap<String, String>,
+}
+
+impl StorageChangeBaskets {
+    /// Change basket for transactions created by `originator`
+    pub fn for_originator(&self, originator: Option<&str>) -> &str {
+        originator
+            .and_then(|o| self.by_originator.get(o))
+            .unwrap_or(&self.default)
+    }
+}
+
+impl Default for StorageChangeBaskets {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_CHANGE_BASKET.to_string(),
+            by_originator: HashMap::new(),
+        }
+    }
+}
+
 /// Paged type (re-exported for convenience)
 pub use crate::schema::tables::TransactionStatus;
 pub use crate::schema::tables::ProvenTxReqStatus;
@@ -342,10 +779,87 @@ mod tests {
         assert_eq!(auth, deserialized);
     }
 
+    #[test]
+    fn test_output_updates_spent_by() {
+        let clear = OutputUpdates { spent_by: Some(None), ..Default::default() };
+        let json = serde_json::to_string(&clear).unwrap();
+        assert_eq!(json, r#"{"spentBy":null}"#);
+        assert_eq!(serde_json::from_str::<OutputUpdates>(&json).unwrap().spent_by, Some(None));
+        assert_eq!(serde_json::from_str::<OutputUpdates>("{}").unwrap().spent_by, None);
+
+        let mut output = TableOutput::new(1, 1, 1, false, true, "", 0, 1000, StorageProvidedBy::Storage, "change", "P2PKH");
+        output.spent_by = Some(7);
+        OutputUpdates::default().apply_to(&mut output);
+        assert_eq!(output.spent_by, Some(7));
+        clear.apply_to(&mut output);
+        assert_eq!(output.spent_by, None);
+    }
+
     #[test]
     fn test_paged() {
         let paged = Paged::with_offset(20, 40);
         assert_eq!(paged.limit, 20);
         assert_eq!(paged.offset, Some(40));
     }
+
+    #[test]
+    fn test_purge_params_and_results() {
+        let params: PurgeParams = serde_json::from_str(
+            r#"{"purgeCompleted":true,"purgeFailed":false,"purgeSpent":false,"purgeCompletedAge":1000}"#,
+        ).unwrap();
+        assert!(params.purge_completed);
+        assert_eq!(params.purge_completed_age, Some(1000));
+        assert_eq!(params.purge_failed_age, None);
+        assert!(!params.vacuum);
+
+        let mut results = PurgeResults::default();
+        results.add(0, "completedTransactions");
+        results.add(3, "failedTransactions");
+        results.add(2, "failedOutputs");
+        assert_eq!(results.count, 5);
+        assert_eq!(results.log, "3 failedTransactions\n2 failedOutputs\n");
+    }
+
+    #[test]
+    fn test_storage_fee_model_default() {
+        let fee_model = StorageFeeModel::default();
+        assert_eq!(fee_model.model, "sat/kb");
+        assert_eq!(fee_model.value, Some(1.0));
+    }
+
+    #[test]
+    fn test_change_baskets_for_originator() {
+        let mut baskets = StorageChangeBaskets::default();
+        assert_eq!(baskets.for_originator(None), "default");
+
+        baskets.by_originator.insert("app.example.com".to_string(), "app change".to_string());
+        assert_eq!(baskets.for_originator(Some("app.example.com")), "app change");
+        assert_eq!(baskets.for_originator(Some("other.example.com")), "default");
+    }
+
+    #[test]
+    fn test_storage_fee_model_serde() {
+        let fee_model: StorageFeeModel =
+            serde_json::from_str(r#"{"model":"sat/kb","value":0.5}"#).unwrap();
+        assert_eq!(fee_model.value, Some(0.5));
+
+        let fee_model: StorageFeeModel = serde_json::from_str(r#"{"model":"sat/kb"}"#).unwrap();
+        assert_eq!(fee_model.value, None);
+    }
+
+    #[test]
+    fn test_proven_tx_req_updates_serde() {
+        let updates = ProvenTxReqUpdates {
+            status: Some(ProvenTxReqStatus::Unsent),
+            batch: Some("batch1".to_string()),
+            attempts: None,
+            history: None,
+            expected_updated_at: None,
+        };
+        let json = serde_json::to_string(&updates).unwrap();
+        assert_eq!(json, r#"{"status":"unsent","batch":"batch1"}"#);
+
+        let json = serde_json::to_string(&ProvenTxReqUpdates::default()).unwrap();
+        assert_eq!(json, "{}");
+    }
 }
@@ -0,0 +1,135 @@
+//! Fixture keys, transactions and BEEFs
+//!
+//! Everything here is deterministic, so the same bytes come out of every
+//! run. Fixture transactions descend from the genesis coinbase, which is
+//! real chain data: its txid can be checked against any block explorer.
+
+use wallet_core::beef::{Beef, MerklePath, MerklePathNode};
+use wallet_core::keys::RootKeyDeriver;
+use wallet_core::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+
+/// Root key of the first fixture user
+pub const ALICE_ROOT_KEY: [u8; 32] = [0x11; 32];
+
+/// Root key of the second fixture user
+pub const BOB_ROOT_KEY: [u8; 32] = [0x22; 32];
+
+/// Change outputs `MockStorage::with_fixtures` gives the first user
+pub const ALICE_CHANGE: [i64; 3] = [1_000, 2_000, 5_000];
+
+/// Change outputs `MockStorage::with_fixtures` gives the second user
+pub const BOB_CHANGE: [i64; 1] = [10_000];
+
+/// Height the first fixture funding transaction is mined at
+pub const FUNDING_HEIGHT: u32 = 850_000;
+
+/// Raw genesis block coinbase transaction
+pub const GENESIS_COINBASE_HEX: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+
+/// Txid of the genesis coinbase, also the genesis block merkle root
+pub const GENESIS_TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+/// Identity key (compressed public key hex) of the first fixture user
+pub fn alice_identity_key() -> String {
+    identity_key(&ALICE_ROOT_KEY)
+}
+
+/// Identity key (compressed public key hex) of the second fixture user
+pub fn bob_identity_key() -> String {
+    identity_key(&BOB_ROOT_KEY)
+}
+
+fn identity_key(root_key: &[u8; 32]) -> String {
+    RootKeyDeriver::new(root_key).expect("fixture root key").identity_key_hex()
+}
+
+/// Raw genesis coinbase
+pub fn genesis_coinbase() -> Vec<u8> {
+    hex::decode(GENESIS_COINBASE_HEX).expect("fixture hex")
+}
+
+/// Merkle path of a transaction mined alone in its block at `height`
+///
+/// The merkle root of such a block is the txid itself.
+pub fn single_tx_merkle_path(txid: &str, height: u32) -> MerklePath {
+    MerklePath {
+        block_height: height,
+        path: vec![vec![MerklePathNode {
+            hash: txid.to_string(),
+            offset: Some(0),
+            duplicate: false,
+            txid: true,
+        }]],
+    }
+}
+
+/// Transaction spending output `vout` of `prev_txid` into OP_TRUE outputs
+pub fn spend_tx(prev_txid: &str, vout: u32, satoshis: &[i64]) -> Transaction {
+    let mut tx = Transaction::new();
+    tx.add_input(TxInput::new(OutPoint::new(prev_txid, vout)));
+    for &amount in satoshis {
+        tx.add_output(TxOutput::new(amount, vec![0x51]));
+    }
+    tx
+}
+
+/// Funding transaction paying `satoshis` out of the genesis coinbase
+pub fn funding_tx(satoshis: &[i64]) -> Transaction {
+    spend_tx(GENESIS_TXID, 0, satoshis)
+}
+
+/// BEEF proving `funding_tx(satoshis)` mined alone at `height`
+pub fn funding_beef(satoshis: &[i64], height: u32) -> Beef {
+    let tx = funding_tx(satoshis);
+    let txid = tx.txid().expect("fixture txid");
+    let mut beef = Beef::new_v2();
+    beef.merge_proven_tx(&tx.serialize().expect("fixture tx"), single_tx_merkle_path(&txid, height))
+        .expect("fixture beef");
+    beef
+}
+
+/// Atomic BEEF of an unproven transaction spending output 0 of a proven
+/// funding transaction, as received by `internalizeAction`
+///
+/// Returns the txid of the spending transaction with the BEEF bytes.
+pub fn payment_beef(funding_satoshis: i64, payment_satoshis: i64) -> (String, Vec<u8>) {
+    let mut beef = funding_beef(&[funding_satoshis], FUNDING_HEIGHT);
+    let funding_txid = beef.txs[0].txid.clone();
+    let payment = spend_tx(&funding_txid, 0, &[payment_satoshis]);
+    let txid = payment.txid().expect("fixture txid");
+    beef.merge_raw_tx(&payment.serialize().expect("fixture tx")).expect("fixture beef");
+    let bytes = beef.to_binary_atomic(&txid).expect("fixture beef");
+    (txid, bytes)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockChainTracker;
+
+    #[test]
+    fn test_genesis_coinbase() {
+        let tx = Transaction::from_bytes(&genesis_coinbase()).unwrap();
+        assert_eq!(tx.txid().unwrap(), GENESIS_TXID);
+        assert_eq!(tx.outputs[0].value, 50 * 100_000_000);
+    }
+
+    #[test]
+    fn test_fixture_keys() {
+        assert_eq!(alice_identity_key().len(), 66);
+        assert_ne!(alice_identity_key(), bob_identity_key());
+    }
+
+    #[tokio::test]
+    async fn test_payment_beef_verifies() {
+        let (txid, bytes) = payment_beef(10_000, 9_000);
+        let beef = Beef::from_binary(&bytes).unwrap();
+        assert_eq!(beef.atomic_txid.as_deref(), Some(txid.as_str()));
+        assert!(beef.verify(&MockChainTracker::for_beef(&beef).unwrap(), false).await.unwrap());
+        assert!(!beef.verify(&MockChainTracker::rejecting(), false).await.unwrap());
+    }
+}
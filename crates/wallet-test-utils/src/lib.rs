@@ -0,0 +1,16 @@
+//! Test doubles for the wallet toolbox
+//!
+//! In-memory storage preloaded with fixture users and outputs, mock chain
+//! tracker, broadcaster and merkle path services, a mock wallet, and fixture
+//! transactions and BEEFs, for tests of code built on the wallet crates.
+//!
+//! Reference: wallet-toolbox/test/utils
+
+pub mod fixtures;
+pub mod services;
+pub mod storage;
+pub mod wallet;
+
+pub use services::{MockBroadcaster, MockChainTracker, MockMerklePathProvider};
+pub use storage::MockStorage;
+pub use wallet::{MockWallet, WalletCall};
@@ -0,0 +1,206 @@
+//! Mock network services
+//!
+//! Stand-ins for the chain tracker, broadcaster and merkle path service
+//! that answer from memory, so tests run without a network.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use wallet_core::beef::{Beef, BeefResult, ChainTracker, MerklePath};
+use wallet_core::sdk::action_process::{ReviewActionResult, ReviewActionResultStatus};
+use wallet_core::services::{Broadcaster, GetMerklePathResult, MerklePathProvider};
+use wallet_storage::StorageResult;
+
+/// Chain tracker answering from a table of merkle roots by height
+#[derive(Debug, Clone, Default)]
+pub struct MockChainTracker {
+    roots: BTreeMap<u32, String>,
+
+    /// Answer for every root, ignoring `roots`
+    all: Option<bool>,
+}
+
+impl MockChainTracker {
+    /// Tracker knowing no roots; add them with `with_root`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracker accepting every root at every height
+    pub fn accepting() -> Self {
+        Self { roots: BTreeMap::new(), all: Some(true) }
+    }
+
+    /// Tracker rejecting every root, as if the chain had reorganized
+    pub fn rejecting() -> Self {
+        Self { roots: BTreeMap::new(), all: Some(false) }
+    }
+
+    /// Tracker knowing the roots `beef`'s merkle paths compute to
+    pub fn for_beef(beef: &Beef) -> BeefResult<Self> {
+        Ok(Self { roots: beef.verify_valid(true)?, all: None })
+    }
+
+    /// Add `merkle_root` as the root of the block at `height`
+    pub fn with_root(mut self, height: u32, merkle_root: impl Into<String>) -> Self {
+        self.roots.insert(height, merkle_root.into());
+        self
+    }
+}
+
+impl ChainTracker for MockChainTracker {
+    fn verify_merkle_path(&self, path: &MerklePath) -> BeefResult<bool> {
+        self.is_valid_root_for_height(&path.compute_root(None)?, path.block_height)
+    }
+
+    fn is_valid_root_for_height(&self, merkle_root: &str, height: u32) -> BeefResult<bool> {
+        Ok(self.all.unwrap_or_else(|| self.roots.get(&height).is_some_and(|root| root == merkle_root)))
+    }
+}
+
+/// Broadcaster answering every post with a configured status
+///
+/// Each post is recorded, so tests can check what was broadcast.
+#[derive(Debug)]
+pub struct MockBroadcaster {
+    status: ReviewActionResultStatus,
+    statuses: HashMap<String, ReviewActionResultStatus>,
+    delay: Option<Duration>,
+    posts: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockBroadcaster {
+    /// Broadcaster answering `status` for every txid
+    pub fn new(status: ReviewActionResultStatus) -> Self {
+        Self { status, statuses: HashMap::new(), delay: None, posts: Mutex::new(Vec::new()) }
+    }
+
+    /// Broadcaster accepting every transaction
+    pub fn accepting() -> Self {
+        Self::new(ReviewActionResultStatus::Success)
+    }
+
+    /// Broadcaster rejecting every transaction as invalid
+    pub fn rejecting() -> Self {
+        Self::new(ReviewActionResultStatus::InvalidTx)
+    }
+
+    /// Answer `status` for `txid` instead of the default
+    pub fn with_txid_status(mut self, txid: impl Into<String>, status: ReviewActionResultStatus) -> Self {
+        self.statuses.insert(txid.into(), status);
+        self
+    }
+
+    /// Wait `delay` before answering each post
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Txids of each post so far, oldest first
+    pub fn posts(&self) -> Vec<Vec<String>> {
+        self.posts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Broadcaster for MockBroadcaster {
+    async fn post_beef(&self, _beef: &[u8], txids: &[String]) -> StorageResult<Vec<ReviewActionResult>> {
+        self.posts.lock().unwrap().push(txids.to_vec());
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        Ok(txids
+            .iter()
+            .map(|txid| ReviewActionResult {
+                txid: txid.clone(),
+                status: self.statuses.get(txid).copied().unwrap_or(self.status),
+                message: None,
+                competing_txs: None,
+                competing_beef: None,
+                double_spend: None,
+                service: self.name().map(str::to_string),
+            })
+            .collect())
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("mockArc")
+    }
+}
+
+/// Merkle path service answering from a table of results by txid
+///
+/// Unknown txids are reported as not yet mined.
+#[derive(Debug, Clone, Default)]
+pub struct MockMerklePathProvider {
+    results: HashMap<String, GetMerklePathResult>,
+}
+
+impl MockMerklePathProvider {
+    /// Provider that has seen no transaction mined
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `result` for `txid`
+    pub fn with_result(mut self, txid: impl Into<String>, result: GetMerklePathResult) -> Self {
+        self.results.insert(txid.into(), result);
+        self
+    }
+}
+
+#[async_trait]
+impl MerklePathProvider for MockMerklePathProvider {
+    async fn get_merkle_path(&self, txid: &str) -> StorageResult<GetMerklePathResult> {
+        Ok(self.results.get(txid).cloned().unwrap_or_default())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{single_tx_merkle_path, GENESIS_TXID};
+
+    #[test]
+    fn test_chain_tracker_roots() {
+        let path = single_tx_merkle_path(GENESIS_TXID, 0);
+        assert!(MockChainTracker::new().with_root(0, GENESIS_TXID).verify_merkle_path(&path).unwrap());
+        assert!(!MockChainTracker::new().with_root(1, GENESIS_TXID).verify_merkle_path(&path).unwrap());
+        assert!(MockChainTracker::accepting().is_valid_root_for_height("00", 5).unwrap());
+        assert!(!MockChainTracker::rejecting().verify_merkle_path(&path).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_broadcaster_statuses_and_posts() {
+        let txids = vec!["aa".repeat(32), "bb".repeat(32)];
+        let broadcaster = MockBroadcaster::accepting()
+            .with_txid_status("bb".repeat(32), ReviewActionResultStatus::DoubleSpend)
+            .with_delay(Duration::from_millis(1));
+
+        let results = broadcaster.post_beef(&[], &txids).await.unwrap();
+        assert_eq!(results[0].status, ReviewActionResultStatus::Success);
+        assert_eq!(results[1].status, ReviewActionResultStatus::DoubleSpend);
+        assert_eq!(broadcaster.posts(), vec![txids]);
+
+        let rejected = MockBroadcaster::rejecting().post_beef(&[], &["cc".repeat(32)]).await.unwrap();
+        assert_eq!(rejected[0].status, ReviewActionResultStatus::InvalidTx);
+    }
+
+    #[tokio::test]
+    async fn test_merkle_path_provider() {
+        let result = GetMerklePathResult {
+            merkle_path: Some(single_tx_merkle_path(GENESIS_TXID, 0)),
+            ..Default::default()
+        };
+        let provider = MockMerklePathProvider::new().with_result(GENESIS_TXID, result);
+        assert!(provider.get_merkle_path(GENESIS_TXID).await.unwrap().merkle_path.is_some());
+        assert!(provider.get_merkle_path(&"aa".repeat(32)).await.unwrap().merkle_path.is_none());
+    }
+}
@@ -0,0 +1,1097 @@
+//! In-memory storage
+//!
+//! Implements the parts of `WalletStorageProvider` the monitor tasks,
+//! balance queries and `createAction` use; everything else returns
+//! `StorageError::NotImplemented`.
+
+use async_trait::async_trait;
+use wallet_storage::*;
+
+use crate::fixtures;
+
+pub struct MockStorage {
+    pub settings: TableSettings,
+    pub users: Vec<TableUser>,
+    pub transactions: Vec<TableTransaction>,
+    pub outputs: Vec<TableOutput>,
+    pub baskets: Vec<TableOutputBasket>,
+    pub reqs: Vec<TableProvenTxReq>,
+    pub proven_txs: Vec<TableProvenTx>,
+    pub events: Vec<TableMonitorEvent>,
+    pub tags: Vec<TableOutputTag>,
+    pub tag_maps: Vec<TableOutputTagMap>,
+    pub labels: Vec<TableTxLabel>,
+    pub label_maps: Vec<TableTxLabelMap>,
+    pub commissions: Vec<TableCommission>,
+    pub identity_caches: Vec<TableIdentityCache>,
+    pub output_locks: Vec<TableOutputLock>,
+}
+
+/// Statuses of a ProvenTxReq whose raw transaction is known to be valid
+///
+/// Reference: StorageKnex.ts getProvenOrRawTx
+const KNOWN_VALID_REQ_STATUSES: &[ProvenTxReqStatus] = &[
+    ProvenTxReqStatus::Unsent,
+    ProvenTxReqStatus::Unmined,
+    ProvenTxReqStatus::Unconfirmed,
+    ProvenTxReqStatus::Sending,
+    ProvenTxReqStatus::Nosend,
+    ProvenTxReqStatus::Completed,
+];
+
+impl MockStorage {
+    pub fn new() -> Self {
+        Self {
+            settings: TableSettings::new("key", "mock", SettingsChain::Test, DbType::SQLite, 1024),
+            users: Vec::new(),
+            transactions: Vec::new(),
+            outputs: Vec::new(),
+            baskets: Vec::new(),
+            reqs: Vec::new(),
+            proven_txs: Vec::new(),
+            events: Vec::new(),
+            tags: Vec::new(),
+            tag_maps: Vec::new(),
+            labels: Vec::new(),
+            label_maps: Vec::new(),
+            commissions: Vec::new(),
+            identity_caches: Vec::new(),
+            output_locks: Vec::new(),
+        }
+    }
+
+    /// Storage holding the two fixture users, each with a proven funding
+    /// transaction whose outputs are spendable change in a "default" basket
+    ///
+    /// The first user is `fixtures::alice_identity_key()` with outputs of
+    /// `fixtures::ALICE_CHANGE`, the second `fixtures::bob_identity_key()`
+    /// with `fixtures::BOB_CHANGE`.
+    pub fn with_fixtures() -> Self {
+        let mut storage = Self::new();
+        storage.add_fixture_user(&fixtures::alice_identity_key(), &fixtures::ALICE_CHANGE);
+        storage.add_fixture_user(&fixtures::bob_identity_key(), &fixtures::BOB_CHANGE);
+        storage
+    }
+
+    /// Add a user owning a proven funding transaction with change outputs
+    /// of `change`, returning its user id
+    pub fn add_fixture_user(&mut self, identity_key: &str, change: &[i64]) -> i64 {
+        let user_id = self.users.len() as i64 + 1;
+        self.users.push(TableUser::new(user_id, identity_key, self.settings.storage_identity_key.clone()));
+
+        let basket_id = self.baskets.len() as i64 + 1;
+        self.baskets.push(TableOutputBasket::new(basket_id, user_id, "default", 6, 10000));
+
+        // Each funding transaction is mined alone in its own block
+        let height = fixtures::FUNDING_HEIGHT + self.proven_txs.len() as u32;
+        let tx = fixtures::funding_tx(change);
+        let txid = tx.txid().expect("fixture txid");
+        let raw_tx = tx.serialize().expect("fixture tx");
+        let merkle_path = fixtures::single_tx_merkle_path(&txid, height);
+        let proven_tx_id = self.proven_txs.len() as i64 + 1;
+        self.proven_txs.push(TableProvenTx::new(
+            proven_tx_id,
+            txid.clone(),
+            height as i64,
+            0,
+            merkle_path.to_binary().expect("fixture merkle path"),
+            raw_tx.clone(),
+            hex::encode([height as u8; 32]),
+            txid.clone(),
+        ));
+
+        let transaction_id = self.transactions.len() as i64 + 1;
+        self.transactions.push(
+            TableTransaction::new(
+                transaction_id,
+                user_id,
+                TransactionStatus::Completed,
+                format!("fixture{}", transaction_id),
+                false,
+                change.iter().sum(),
+                "fixture funding",
+            )
+            .with_txid(txid.clone())
+            .with_raw_tx(raw_tx)
+            .with_proven_tx_id(proven_tx_id),
+        );
+
+        for (vout, &satoshis) in change.iter().enumerate() {
+            let output_id = self.outputs.len() as i64 + 1;
+            self.outputs.push(
+                TableOutput::new(
+                    output_id,
+                    user_id,
+                    transaction_id,
+                    true,
+                    true,
+                    "fixture change",
+                    vout as u32,
+                    satoshis,
+                    StorageProvidedBy::Storage,
+                    "change",
+                    "P2PKH",
+                )
+                .with_basket_id(basket_id)
+                .with_txid(txid.clone())
+                .with_locking_script(vec![0x51]),
+            );
+        }
+        user_id
+    }
+
+    pub fn transaction(&self, transaction_id: i64) -> &TableTransaction {
+        self.transactions.iter().find(|t| t.transaction_id == transaction_id).unwrap()
+    }
+
+    /// The transaction, for an update, with its `updated_at` bumped
+    fn transaction_mut(&mut self, transaction_id: i64) -> StorageResult<&mut TableTransaction> {
+        let tx = self.transactions
+            .iter_mut()
+            .find(|t| t.transaction_id == transaction_id)
+            .ok_or_else(|| StorageError::NotFound(format!("transaction {}", transaction_id)))?;
+        tx.touch();
+        Ok(tx)
+    }
+
+    /// Spendable outputs of `user_id` in `basket_id` that change may be
+    /// allocated from, as `allocate_change_input` selects them
+    fn change_candidates(&self, user_id: i64, basket_id: i64, exclude_sending: bool) -> Vec<&TableOutput> {
+        let statuses: &[TransactionStatus] = if exclude_sending {
+            &[TransactionStatus::Completed, TransactionStatus::Unproven]
+        } else {
+            &[TransactionStatus::Completed, TransactionStatus::Unproven, TransactionStatus::Sending]
+        };
+        self.outputs
+            .iter()
+            .filter(|o| o.user_id == user_id && o.basket_id == Some(basket_id) && o.spendable)
+            .filter(|o| self.transactions.iter().any(|t| t.transaction_id == o.transaction_id && statuses.contains(&t.status)))
+            .filter(|o| self.live_output_lock(o.output_id).is_none())
+            .collect()
+    }
+
+    /// The unexpired lock on output `output_id`
+    fn live_output_lock(&self, output_id: i64) -> Option<&TableOutputLock> {
+        let now = chrono::Utc::now();
+        self.output_locks.iter().find(|l| l.output_id == output_id && !l.is_expired(now))
+    }
+
+    /// Index of the user's undeleted tag named `tag`
+    fn live_tag(&self, user_id: i64, tag: &str) -> StorageResult<usize> {
+        self.tags
+            .iter()
+            .position(|t| t.user_id == user_id && t.tag == tag && !t.is_deleted)
+            .ok_or_else(|| StorageError::NotFound(format!("output tag {}", tag)))
+    }
+
+    /// Index of the user's undeleted label named `label`
+    fn live_label(&self, user_id: i64, label: &str) -> StorageResult<usize> {
+        self.labels
+            .iter()
+            .position(|l| l.user_id == user_id && l.label == label && !l.is_deleted)
+            .ok_or_else(|| StorageError::NotFound(format!("tx label {}", label)))
+    }
+
+    /// The request, for an update, with its `updated_at` bumped
+    fn req_mut(&mut self, proven_tx_req_id: i64) -> StorageResult<&mut TableProvenTxReq> {
+        let req = self.reqs
+            .iter_mut()
+            .find(|r| r.proven_tx_req_id == proven_tx_req_id)
+            .ok_or_else(|| StorageError::NotFound(format!("proven_tx_req {}", proven_tx_req_id)))?;
+        req.touch();
+        Ok(req)
+    }
+}
+
+impl Default for MockStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WalletStorageReader for MockStorage {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn get_settings(&self) -> &TableSettings {
+        &self.settings
+    }
+
+    async fn find_user_by_identity_key(&self, identity_key: &str) -> StorageResult<Option<TableUser>> {
+        Ok(self.users.iter().find(|u| u.identity_key == identity_key).cloned())
+    }
+
+    async fn find_certificates_auth(&self, _auth: &AuthId, _args: &FindCertificatesArgs) -> StorageResult<Vec<TableCertificate>> {
+        Err(StorageError::NotImplemented("find_certificates_auth"))
+    }
+
+    async fn count_certificates_auth(&self, _auth: &AuthId, _args: &FindCertificatesArgs) -> StorageResult<i64> {
+        Err(StorageError::NotImplemented("count_certificates_auth"))
+    }
+
+    async fn find_certificate_fields_auth(&self, _auth: &AuthId, _certificate_id: i64) -> StorageResult<Vec<TableCertificateField>> {
+        Err(StorageError::NotImplemented("find_certificate_fields_auth"))
+    }
+
+    async fn find_output_baskets_auth(&self, auth: &AuthId, args: &FindOutputBasketsArgs) -> StorageResult<Vec<TableOutputBasket>> {
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        Ok(self.baskets
+            .iter()
+            .filter(|b| b.user_id == args.user_id)
+            .filter(|b| args.name.as_ref().is_none_or(|name| &b.name == name))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_outputs_auth(&self, auth: &AuthId, args: &FindOutputsArgs) -> StorageResult<Vec<TableOutput>> {
+        self.validate_auth(auth).await?;
+        verify_args_user(auth, args.user_id)?;
+        let partial = args.partial.as_ref();
+        Ok(self.outputs.iter()
+            .filter(|o| o.user_id == args.user_id)
+            .filter(|o| partial.and_then(|p| p.basket_id).is_none_or(|id| o.basket_id == Some(id)))
+            .filter(|o| partial.and_then(|p| p.spendable).is_none_or(|s| o.spendable == s))
+            .filter(|o| partial.and_then(|p| p.change).is_none_or(|c| o.change == c))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_proven_tx_reqs(&self, args: &FindProvenTxReqsArgs) -> StorageResult<Vec<TableProvenTxReq>> {
+        Ok(self.reqs
+            .iter()
+            .filter(|r| args.status.is_none_or(|s| r.status == s))
+            .filter(|r| args.txids.as_ref().is_none_or(|t| t.contains(&r.txid)))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl WalletStorageWriter for MockStorage {
+    async fn make_available(&mut self) -> StorageResult<TableSettings> {
+        Ok(self.settings.clone())
+    }
+
+    async fn migrate(&mut self, _storage_name: &str, _storage_identity_key: &str) -> StorageResult<String> {
+        Err(StorageError::NotImplemented("migrate"))
+    }
+
+    async fn destroy(&mut self) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("destroy"))
+    }
+
+    async fn find_or_insert_user(&mut self, identity_key: &str) -> StorageResult<FindOrInsertUserResult> {
+        if let Some(user) = self.find_user_by_identity_key(identity_key).await? {
+            return Ok(FindOrInsertUserResult { user, is_new: false });
+        }
+        let user = TableUser::new(self.users.len() as i64 + 1, identity_key, self.settings.storage_identity_key.clone());
+        self.users.push(user.clone());
+        Ok(FindOrInsertUserResult { user, is_new: true })
+    }
+
+    async fn insert_certificate_auth(&mut self, _auth: &AuthId, _certificate: &TableCertificate) -> StorageResult<i64> {
+        Err(StorageError::NotImplemented("insert_certificate_auth"))
+    }
+
+    async fn insert_certificate_field_auth(&mut self, _auth: &AuthId, _field: &TableCertificateField) -> StorageResult<()> {
+        Err(StorageError::NotImplemented("insert_certificate_field_auth"))
+    }
+}
+
+#[async_trait]
+impl WalletStorageSync for MockStorage {
+    async fn find_or_insert_sync_state_auth(
+        &mut self,
+        _auth: &AuthId,
+        _storage_identity_key: &str,
+        _storage_name: &str,
+    ) -> StorageResult<FindOrInsertSyncStateResult> {
+        Err(StorageError::NotImplemented("find_or_insert_sync_state_auth"))
+    }
+
+    async fn set_active(&mut self, _auth: &AuthId, _new_active_storage_identity_key: &str) -> StorageResult<i64> {
+        Err(StorageError::NotImplemented("set_active"))
+    }
+}
+
+#[async_trait]
+impl WalletStorageProvider for MockStorage {
+    async fn count_change_inputs(&self, user_id: i64, basket_id: i64, exclude_sending: bool) -> StorageResult<i64> {
+        Ok(self.change_candidates(user_id, basket_id, exclude_sending).len() as i64)
+    }
+
+    /// Same choice as the SQLite storage: an output of exactly
+    /// `exact_satoshis`, else the smallest covering `target_satoshis`, else
+    /// the largest
+    async fn allocate_change_input(
+        &mut self,
+        user_id: i64,
+        basket_id: i64,
+        target_satoshis: i64,
+        exact_satoshis: Option<i64>,
+        exclude_sending: bool,
+        transaction_id: i64,
+    ) -> StorageResult<Option<TableOutput>> {
+        let candidates = self.change_candidates(user_id, basket_id, exclude_sending);
+        let chosen = exact_satoshis
+            .and_then(|exact| candidates.iter().find(|o| o.satoshis == exact))
+            .or_else(|| candidates.iter().filter(|o| o.satoshis >= target_satoshis).min_by_key(|o| o.satoshis))
+            .or_else(|| candidates.iter().max_by_key(|o| o.satoshis))
+            .map(|o| o.output_id);
+        let Some(output_id) = chosen else {
+            return Ok(None);
+        };
+        let output = self.outputs.iter_mut().find(|o| o.output_id == output_id).unwrap();
+        output.spendable = false;
+        output.spent_by = Some(transaction_id);
+        Ok(Some(output.clone()))
+    }
+
+    async fn verify_known_valid_transaction(&self, txid: &str) -> StorageResult<bool> {
+        let r = self.get_proven_or_raw_tx(txid).await?;
+        Ok(r.proven.is_some() || r.raw_tx.is_some())
+    }
+
+    async fn get_proven_or_raw_tx(&self, txid: &str) -> StorageResult<ProvenOrRawTx> {
+        if let Some(proven) = self.proven_txs.iter().find(|p| p.txid == txid) {
+            return Ok(ProvenOrRawTx { proven: Some(proven.clone()), raw_tx: None, input_beef: None });
+        }
+        let req = self.reqs.iter().find(|r| r.txid == txid && KNOWN_VALID_REQ_STATUSES.contains(&r.status));
+        Ok(ProvenOrRawTx {
+            proven: None,
+            raw_tx: req.map(|r| r.raw_tx.clone()),
+            input_beef: req.and_then(|r| r.input_beef.clone()),
+        })
+    }
+
+    async fn insert_proven_tx_req(&mut self, req: &TableProvenTxReq) -> StorageResult<i64> {
+        let mut req = req.clone();
+        req.proven_tx_req_id = self.reqs.len() as i64 + 1;
+        self.reqs.push(req);
+        Ok(self.reqs.len() as i64)
+    }
+
+    async fn update_proven_tx_req(&mut self, proven_tx_req_id: i64, updates: &ProvenTxReqUpdates) -> StorageResult<()> {
+        if let Some(req) = self.reqs.iter().find(|r| r.proven_tx_req_id == proven_tx_req_id) {
+            verify_unchanged("proven_tx_reqs", proven_tx_req_id, updates.expected_updated_at.as_deref(), &req.updated_at)?;
+        }
+        let req = self.req_mut(proven_tx_req_id)?;
+        if let Some(status) = updates.status {
+            req.status = status;
+        }
+        if let Some(batch) = &updates.batch {
+            req.batch = Some(batch.clone());
+        }
+        if let Some(attempts) = updates.attempts {
+            req.attempts = attempts;
+        }
+        if let Some(history) = &updates.history {
+            req.history = history.clone();
+        }
+        Ok(())
+    }
+
+    async fn update_proven_tx_req_with_new_proven_tx(
+        &mut self,
+        args: &UpdateProvenTxReqWithNewProvenTxArgs,
+    ) -> StorageResult<UpdateProvenTxReqWithNewProvenTxResult> {
+        let proven_tx_id = self.proven_txs.len() as i64 + 1;
+        let raw_tx = self.req_mut(args.proven_tx_req_id)?.raw_tx.clone();
+        self.proven_txs.push(TableProvenTx::new(
+            proven_tx_id,
+            args.txid.clone(),
+            args.height,
+            args.index,
+            args.merkle_path.clone(),
+            raw_tx,
+            args.block_hash.clone(),
+            args.merkle_root.clone(),
+        ));
+
+        let req = self.req_mut(args.proven_tx_req_id)?;
+        req.status = ProvenTxReqStatus::Completed;
+        req.proven_tx_id = Some(proven_tx_id);
+        for transaction_id in req.notify_transaction_ids() {
+            let tx = self.transaction_mut(transaction_id)?;
+            tx.proven_tx_id = Some(proven_tx_id);
+            tx.status = TransactionStatus::Completed;
+        }
+
+        Ok(UpdateProvenTxReqWithNewProvenTxResult {
+            status: ProvenTxReqStatus::Completed,
+            history: args.history.clone(),
+            proven_tx_id,
+            log: None,
+        })
+    }
+
+    async fn find_proven_txs_from_height(&self, min_height: i64) -> StorageResult<Vec<TableProvenTx>> {
+        Ok(self.proven_txs.iter().filter(|p| p.height >= min_height).cloned().collect())
+    }
+
+    async fn rollback_proven_tx(&mut self, proven_tx_id: i64) -> StorageResult<Vec<i64>> {
+        let before = self.proven_txs.len();
+        self.proven_txs.retain(|p| p.proven_tx_id != proven_tx_id);
+        if self.proven_txs.len() == before {
+            return Err(StorageError::NotFound(format!("proven_tx {}", proven_tx_id)));
+        }
+
+        for req in self.reqs.iter_mut().filter(|r| r.proven_tx_id == Some(proven_tx_id)) {
+            req.proven_tx_id = None;
+            req.status = ProvenTxReqStatus::Unmined;
+        }
+        let mut reverted = Vec::new();
+        for tx in self.transactions.iter_mut().filter(|t| t.proven_tx_id == Some(proven_tx_id)) {
+            tx.proven_tx_id = None;
+            if tx.status == TransactionStatus::Completed {
+                tx.status = TransactionStatus::Unproven;
+                reverted.push(tx.transaction_id);
+            }
+        }
+        Ok(reverted)
+    }
+
+    async fn update_proven_tx(&mut self, proven_tx_id: i64, updates: &ProvenTxUpdates) -> StorageResult<()> {
+        let ptx = self
+            .proven_txs
+            .iter_mut()
+            .find(|p| p.proven_tx_id == proven_tx_id)
+            .ok_or_else(|| StorageError::NotFound(format!("proven_tx {}", proven_tx_id)))?;
+        if let Some(height) = updates.height {
+            ptx.height = height;
+        }
+        if let Some(index) = updates.index {
+            ptx.index = index;
+        }
+        if let Some(merkle_path) = &updates.merkle_path {
+            ptx.merkle_path = merkle_path.clone();
+        }
+        if let Some(block_hash) = &updates.block_hash {
+            ptx.block_hash = block_hash.clone();
+        }
+        if let Some(merkle_root) = &updates.merkle_root {
+            ptx.merkle_root = merkle_root.clone();
+        }
+        ptx.touch();
+        Ok(())
+    }
+
+    async fn get_raw_tx_of_known_valid_transaction(
+        &self,
+        txid: &str,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> StorageResult<Option<Vec<u8>>> {
+        let r = self.get_proven_or_raw_tx(txid).await?;
+        let Some(raw_tx) = r.proven.map(|p| p.raw_tx).or(r.raw_tx) else {
+            return Ok(None);
+        };
+        let start = offset.unwrap_or(0).min(raw_tx.len());
+        let end = length.map_or(raw_tx.len(), |length| (start + length).min(raw_tx.len()));
+        Ok(Some(raw_tx[start..end].to_vec()))
+    }
+
+    async fn find_transactions(
+        &self,
+        user_id: i64,
+        reference: Option<&str>,
+        status: Option<TransactionStatus>,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        Ok(self.transactions
+            .iter()
+            .filter(|t| t.user_id == user_id)
+            .filter(|t| reference.is_none_or(|r| t.reference == r))
+            .filter(|t| status.is_none_or(|s| t.status == s))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_transaction_by_id(&self, transaction_id: i64) -> StorageResult<Option<TableTransaction>> {
+        Ok(self.transactions.iter().find(|t| t.transaction_id == transaction_id).cloned())
+    }
+
+    async fn find_aged_transactions(
+        &self,
+        statuses: &[TransactionStatus],
+        updated_before: &str,
+    ) -> StorageResult<Vec<TableTransaction>> {
+        Ok(self.transactions
+            .iter()
+            .filter(|t| statuses.contains(&t.status) && t.updated_at.as_str() < updated_before)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_outputs_by_transaction(
+        &self,
+        user_id: i64,
+        transaction_id: i64,
+        is_input: bool,
+    ) -> StorageResult<Vec<TableOutput>> {
+        Ok(self.outputs
+            .iter()
+            .filter(|o| o.user_id == user_id)
+            .filter(|o| if is_input { o.spent_by == Some(transaction_id) } else { o.transaction_id == transaction_id })
+            .cloned()
+            .collect())
+    }
+
+    /// A `transaction_id` of 0 is assigned the next id
+    async fn insert_transaction(&mut self, tx: &TableTransaction) -> StorageResult<i64> {
+        let mut tx = tx.clone();
+        if tx.transaction_id == 0 {
+            tx.transaction_id = self.transactions.iter().map(|t| t.transaction_id).max().unwrap_or(0) + 1;
+        }
+        self.transactions.push(tx);
+        Ok(self.transactions.last().unwrap().transaction_id)
+    }
+
+    async fn update_transaction(&mut self, transaction_id: i64, satoshis: i64) -> StorageResult<()> {
+        self.transaction_mut(transaction_id)?.satoshis = satoshis;
+        Ok(())
+    }
+
+    async fn update_transaction_status(&mut self, transaction_id: i64, status: TransactionStatus) -> StorageResult<()> {
+        self.transaction_mut(transaction_id)?.status = status;
+        Ok(())
+    }
+
+    async fn update_transaction_status_if_unchanged(
+        &mut self,
+        transaction_id: i64,
+        status: TransactionStatus,
+        expected_updated_at: &str,
+    ) -> StorageResult<()> {
+        if let Some(tx) = self.transactions.iter().find(|t| t.transaction_id == transaction_id) {
+            verify_unchanged("transactions", transaction_id, Some(expected_updated_at), &tx.updated_at)?;
+        }
+        self.update_transaction_status(transaction_id, status).await
+    }
+
+    async fn update_transaction_txid(&mut self, transaction_id: i64, txid: &str) -> StorageResult<()> {
+        self.transaction_mut(transaction_id)?.txid = Some(txid.to_string());
+        Ok(())
+    }
+
+    async fn update_transaction_raw_tx(&mut self, transaction_id: i64, raw_tx: &[u8]) -> StorageResult<()> {
+        self.transaction_mut(transaction_id)?.raw_tx = Some(raw_tx.to_vec());
+        Ok(())
+    }
+
+    /// An `output_id` of 0 is assigned the next id
+    async fn insert_output(&mut self, output: &TableOutput) -> StorageResult<i64> {
+        let mut output = output.clone();
+        if output.output_id == 0 {
+            output.output_id = self.outputs.iter().map(|o| o.output_id).max().unwrap_or(0) + 1;
+        }
+        self.outputs.push(output);
+        Ok(self.outputs.last().unwrap().output_id)
+    }
+
+    async fn update_output(&mut self, output_id: i64, updates: &OutputUpdates) -> StorageResult<()> {
+        let output = self.outputs
+            .iter_mut()
+            .find(|o| o.output_id == output_id)
+            .ok_or_else(|| StorageError::NotFound(format!("output {}", output_id)))?;
+        verify_unchanged("outputs", output_id, updates.expected_updated_at.as_deref(), &output.updated_at)?;
+        updates.apply_to(output);
+        output.touch();
+        Ok(())
+    }
+
+    async fn insert_commission(&mut self, commission: &TableCommission) -> StorageResult<i64> {
+        let mut commission = commission.clone();
+        commission.commission_id = self.commissions.len() as i64 + 1;
+        self.commissions.push(commission);
+        Ok(self.commissions.len() as i64)
+    }
+
+    async fn get_balance(&self, user_id: i64) -> StorageResult<i64> {
+        Ok(self.get_basket_balances(user_id).await?.iter().map(|b| b.satoshis).sum())
+    }
+
+    async fn get_basket_balances(&self, user_id: i64) -> StorageResult<Vec<BasketBalance>> {
+        let mut balances: Vec<BasketBalance> = Vec::new();
+        for basket in self.baskets.iter().filter(|b| b.user_id == user_id) {
+            let outputs: Vec<_> = self.outputs.iter()
+                .filter(|o| o.user_id == user_id && o.basket_id == Some(basket.basket_id) && o.spendable)
+                .filter(|o| self.transactions.iter().any(|t| {
+                    t.transaction_id == o.transaction_id && BasketBalance::TX_STATUSES.contains(&t.status)
+                }))
+                .collect();
+            if !outputs.is_empty() {
+                balances.push(BasketBalance {
+                    basket_id: basket.basket_id,
+                    name: basket.name.clone(),
+                    satoshis: outputs.iter().map(|o| o.satoshis).sum(),
+                    output_count: outputs.len() as i64,
+                });
+            }
+        }
+        Ok(balances)
+    }
+
+    async fn find_or_insert_output_basket(&mut self, user_id: i64, name: &str) -> StorageResult<TableOutputBasket> {
+        if let Some(basket) = self.baskets.iter().find(|b| b.user_id == user_id && b.name == name) {
+            return Ok(basket.clone());
+        }
+        let basket = TableOutputBasket::new(self.baskets.len() as i64 + 1, user_id, name, 6, 10000);
+        self.baskets.push(basket.clone());
+        Ok(basket)
+    }
+
+    async fn update_output_basket(&mut self, basket_id: i64, updates: &OutputBasketUpdates) -> StorageResult<()> {
+        let basket = self.baskets
+            .iter_mut()
+            .find(|b| b.basket_id == basket_id)
+            .ok_or_else(|| StorageError::NotFound(format!("output basket {}", basket_id)))?;
+        if let Some(n) = updates.number_of_desired_utxos {
+            basket.number_of_desired_utxos = n;
+        }
+        if let Some(v) = updates.minimum_desired_utxo_value {
+            basket.minimum_desired_utxo_value = v;
+        }
+        Ok(())
+    }
+
+    async fn find_or_insert_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<TableOutputTag> {
+        if let Some(t) = self.tags.iter_mut().find(|t| t.user_id == user_id && t.tag == tag) {
+            if t.is_deleted {
+                t.restore();
+            }
+            return Ok(t.clone());
+        }
+        let t = TableOutputTag::new(self.tags.len() as i64 + 1, user_id, tag);
+        self.tags.push(t.clone());
+        Ok(t)
+    }
+
+    async fn find_or_insert_output_tag_map(&mut self, output_id: i64, output_tag_id: i64) -> StorageResult<()> {
+        match self.tag_maps.iter_mut().find(|m| m.output_id == output_id && m.output_tag_id == output_tag_id) {
+            Some(m) => m.is_deleted = false,
+            None => self.tag_maps.push(TableOutputTagMap::new(output_tag_id, output_id)),
+        }
+        Ok(())
+    }
+
+    async fn find_or_insert_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<TableTxLabel> {
+        if let Some(l) = self.labels.iter_mut().find(|l| l.user_id == user_id && l.label == label) {
+            if l.is_deleted {
+                l.restore();
+            }
+            return Ok(l.clone());
+        }
+        let l = TableTxLabel::new(self.labels.len() as i64 + 1, user_id, label);
+        self.labels.push(l.clone());
+        Ok(l)
+    }
+
+    async fn find_or_insert_tx_label_map(&mut self, transaction_id: i64, tx_label_id: i64) -> StorageResult<()> {
+        match self.label_maps.iter_mut().find(|m| m.transaction_id == transaction_id && m.tx_label_id == tx_label_id) {
+            Some(m) => m.is_deleted = false,
+            None => self.label_maps.push(TableTxLabelMap::new(tx_label_id, transaction_id)),
+        }
+        Ok(())
+    }
+
+    async fn find_tx_labels_for_transaction(&self, transaction_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        Ok(self.label_maps
+            .iter()
+            .filter(|m| m.transaction_id == transaction_id && !m.is_deleted)
+            .filter_map(|m| self.labels.iter().find(|l| l.tx_label_id == m.tx_label_id && !l.is_deleted))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_output_tags(&self, user_id: i64) -> StorageResult<Vec<TableOutputTag>> {
+        Ok(self.tags.iter().filter(|t| t.user_id == user_id && !t.is_deleted).cloned().collect())
+    }
+
+    async fn find_tx_labels(&self, user_id: i64) -> StorageResult<Vec<TableTxLabel>> {
+        Ok(self.labels.iter().filter(|l| l.user_id == user_id && !l.is_deleted).cloned().collect())
+    }
+
+    async fn rename_output_tag(&mut self, user_id: i64, tag: &str, new_tag: &str) -> StorageResult<TableOutputTag> {
+        let from = self.live_tag(user_id, tag)?;
+        if tag == new_tag {
+            return Ok(self.tags[from].clone());
+        }
+        let Some(to) = self.tags.iter().position(|t| t.user_id == user_id && t.tag == new_tag) else {
+            self.tags[from].tag = new_tag.to_string();
+            self.tags[from].touch();
+            return Ok(self.tags[from].clone());
+        };
+        self.tags[to].restore();
+        let (from_id, to_id) = (self.tags[from].output_tag_id, self.tags[to].output_tag_id);
+        let outputs: Vec<i64> = self.tag_maps.iter()
+            .filter(|m| m.output_tag_id == from_id && !m.is_deleted)
+            .map(|m| m.output_id)
+            .collect();
+        for output_id in outputs {
+            self.find_or_insert_output_tag_map(output_id, to_id).await?;
+        }
+        self.delete_output_tag(user_id, tag).await?;
+        Ok(self.tags[to].clone())
+    }
+
+    async fn delete_output_tag(&mut self, user_id: i64, tag: &str) -> StorageResult<()> {
+        let index = self.live_tag(user_id, tag)?;
+        self.tags[index].delete();
+        let output_tag_id = self.tags[index].output_tag_id;
+        for m in self.tag_maps.iter_mut().filter(|m| m.output_tag_id == output_tag_id) {
+            m.is_deleted = true;
+        }
+        Ok(())
+    }
+
+    async fn rename_tx_label(&mut self, user_id: i64, label: &str, new_label: &str) -> StorageResult<TableTxLabel> {
+        let from = self.live_label(user_id, label)?;
+        if label == new_label {
+            return Ok(self.labels[from].clone());
+        }
+        let Some(to) = self.labels.iter().position(|l| l.user_id == user_id && l.label == new_label) else {
+            self.labels[from].label = new_label.to_string();
+            self.labels[from].touch();
+            return Ok(self.labels[from].clone());
+        };
+        self.labels[to].restore();
+        let (from_id, to_id) = (self.labels[from].tx_label_id, self.labels[to].tx_label_id);
+        let transactions: Vec<i64> = self.label_maps.iter()
+            .filter(|m| m.tx_label_id == from_id && !m.is_deleted)
+            .map(|m| m.transaction_id)
+            .collect();
+        for transaction_id in transactions {
+            self.find_or_insert_tx_label_map(transaction_id, to_id).await?;
+        }
+        self.delete_tx_label(user_id, label).await?;
+        Ok(self.labels[to].clone())
+    }
+
+    async fn delete_tx_label(&mut self, user_id: i64, label: &str) -> StorageResult<()> {
+        let index = self.live_label(user_id, label)?;
+        self.labels[index].delete();
+        let tx_label_id = self.labels[index].tx_label_id;
+        for m in self.label_maps.iter_mut().filter(|m| m.tx_label_id == tx_label_id) {
+            m.is_deleted = true;
+        }
+        Ok(())
+    }
+
+    async fn get_output_tag_usages(&self, user_id: i64) -> StorageResult<Vec<OutputTagUsage>> {
+        let mut usages: Vec<OutputTagUsage> = self.tags.iter()
+            .filter(|t| t.user_id == user_id && !t.is_deleted)
+            .map(|t| OutputTagUsage {
+                output_tag_id: t.output_tag_id,
+                tag: t.tag.clone(),
+                output_count: self.tag_maps.iter()
+                    .filter(|m| m.output_tag_id == t.output_tag_id && !m.is_deleted)
+                    .count() as i64,
+            })
+            .collect();
+        usages.sort_by(|a, b| a.tag.cmp(&b.tag));
+        Ok(usages)
+    }
+
+    async fn get_user_usage(&self, user_id: i64) -> StorageResult<UserUsage> {
+        let transactions: Vec<&TableTransaction> = self.transactions.iter().filter(|t| t.user_id == user_id).collect();
+        Ok(UserUsage {
+            transactions: transactions.len() as i64,
+            outputs: self.outputs.iter().filter(|o| o.user_id == user_id).count() as i64,
+            certificate_bytes: 0,
+            beef_bytes: transactions.iter().map(|t| t.input_beef.as_ref().map_or(0, |b| b.len() as i64)).sum(),
+        })
+    }
+
+    async fn find_identity_cache(&self, user_id: i64, query: &str) -> StorageResult<Option<TableIdentityCache>> {
+        Ok(self.identity_caches.iter().find(|e| e.user_id == user_id && e.query == query).cloned())
+    }
+
+    async fn upsert_identity_cache(&mut self, entry: &TableIdentityCache) -> StorageResult<i64> {
+        let mut entry = entry.clone();
+        match self.identity_caches.iter_mut().find(|e| e.user_id == entry.user_id && e.query == entry.query) {
+            Some(existing) => {
+                entry.identity_cache_id = existing.identity_cache_id;
+                entry.created_at = existing.created_at.clone();
+                entry.touch();
+                *existing = entry;
+                Ok(existing.identity_cache_id)
+            }
+            None => {
+                entry.identity_cache_id = self.identity_caches.len() as i64 + 1;
+                self.identity_caches.push(entry);
+                Ok(self.identity_caches.len() as i64)
+            }
+        }
+    }
+
+    async fn lock_output(
+        &mut self,
+        user_id: i64,
+        txid: &str,
+        vout: u32,
+        reason: &str,
+        ttl: Option<std::time::Duration>,
+    ) -> StorageResult<TableOutputLock> {
+        let output = self
+            .outputs
+            .iter()
+            .find(|o| o.user_id == user_id && o.txid.as_deref() == Some(txid) && o.vout == vout)
+            .ok_or_else(|| StorageError::NotFound(format!("output {}.{}", txid, vout)))?;
+        if !output.spendable {
+            return Err(StorageError::InvalidArg(format!("output {}.{} is not spendable", txid, vout)));
+        }
+        let lock = TableOutputLock::new(output.output_id, user_id, txid, vout, reason, ttl);
+        self.output_locks.retain(|l| l.output_id != lock.output_id);
+        self.output_locks.push(lock.clone());
+        Ok(lock)
+    }
+
+    async fn unlock_output(&mut self, user_id: i64, txid: &str, vout: u32) -> StorageResult<bool> {
+        let len = self.output_locks.len();
+        self.output_locks.retain(|l| !(l.user_id == user_id && l.txid == txid && l.vout == vout));
+        Ok(self.output_locks.len() != len)
+    }
+
+    async fn find_output_lock(&self, output_id: i64) -> StorageResult<Option<TableOutputLock>> {
+        Ok(self.live_output_lock(output_id).cloned())
+    }
+
+    async fn find_output_locks(&self, user_id: i64) -> StorageResult<Vec<TableOutputLock>> {
+        let now = chrono::Utc::now();
+        Ok(self.output_locks.iter().filter(|l| l.user_id == user_id && !l.is_expired(now)).cloned().collect())
+    }
+
+    async fn insert_monitor_event(&mut self, event: &TableMonitorEvent) -> StorageResult<i64> {
+        let mut event = event.clone();
+        event.id = self.events.len() as i64 + 1;
+        self.events.push(event);
+        Ok(self.events.len() as i64)
+    }
+
+    async fn find_monitor_events(&self, args: &FindMonitorEventsArgs) -> StorageResult<Vec<TableMonitorEvent>> {
+        let mut events: Vec<TableMonitorEvent> = self.events.iter()
+            .filter(|e| args.event.as_ref().is_none_or(|event| &e.event == event))
+            .filter(|e| args.since.as_ref().is_none_or(|since| &e.created_at >= since))
+            .filter(|e| args.until.as_ref().is_none_or(|until| &e.created_at < until))
+            .cloned()
+            .collect();
+        if args.order_descending == Some(true) {
+            events.reverse();
+        }
+        if let Some(paged) = &args.paged {
+            events = events.into_iter()
+                .skip(paged.offset.unwrap_or(0) as usize)
+                .take(paged.limit as usize)
+                .collect();
+        }
+        Ok(events)
+    }
+
+    async fn purge_data(&mut self, params: &PurgeParams) -> StorageResult<PurgeResults> {
+        let before = |age: Option<i64>| {
+            let age = chrono::Duration::milliseconds(age.unwrap_or(PurgeParams::DEFAULT_AGE_MSECS));
+            (chrono::Utc::now() - age).to_rfc3339()
+        };
+        let mut r = PurgeResults::default();
+
+        if params.purge_completed {
+            let before = before(params.purge_completed_age);
+            let mut trimmed = 0;
+            for t in self.transactions.iter_mut().filter(|t| {
+                t.status == TransactionStatus::Completed && t.proven_tx_id.is_some() && t.updated_at < before
+            }) {
+                if t.raw_tx.is_some() || t.input_beef.is_some() {
+                    t.raw_tx = None;
+                    t.input_beef = None;
+                    trimmed += 1;
+                }
+            }
+            r.add(trimmed, "completedTransactions");
+
+            let count = self.reqs.len();
+            self.reqs.retain(|q| !(q.status == ProvenTxReqStatus::Completed && q.notified && q.updated_at < before));
+            r.add(count - self.reqs.len(), "completedProvenTxReqs");
+        }
+
+        if params.purge_failed {
+            let before = before(params.purge_failed_age);
+            let failed: Vec<i64> = self.transactions
+                .iter()
+                .filter(|t| t.status == TransactionStatus::Failed && t.updated_at < before)
+                .map(|t| t.transaction_id)
+                .collect();
+            for o in self.outputs.iter_mut().filter(|o| o.spent_by.is_some_and(|id| failed.contains(&id))) {
+                o.spent_by = None;
+            }
+            let count = self.outputs.len();
+            self.outputs.retain(|o| !failed.contains(&o.transaction_id));
+            r.add(count - self.outputs.len(), "failedOutputs");
+            self.transactions.retain(|t| !failed.contains(&t.transaction_id));
+            r.add(failed.len(), "failedTransactions");
+
+            let count = self.reqs.len();
+            self.reqs.retain(|q| {
+                !(matches!(q.status, ProvenTxReqStatus::Invalid | ProvenTxReqStatus::DoubleSpend) && q.updated_at < before)
+            });
+            r.add(count - self.reqs.len(), "failedProvenTxReqs");
+        }
+
+        if params.purge_spent {
+            return Err(StorageError::NotImplemented("purge_data (purge_spent)"));
+        }
+
+        Ok(r)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixtures() {
+        let storage = MockStorage::with_fixtures();
+        let alice = storage.find_user_by_identity_key(&fixtures::alice_identity_key()).await.unwrap().unwrap();
+        let bob = storage.find_user_by_identity_key(&fixtures::bob_identity_key()).await.unwrap().unwrap();
+
+        assert_eq!(storage.get_balance(alice.user_id).await.unwrap(), fixtures::ALICE_CHANGE.iter().sum::<i64>());
+        assert_eq!(storage.get_balance(bob.user_id).await.unwrap(), fixtures::BOB_CHANGE.iter().sum::<i64>());
+
+        let auth = AuthId::new(fixtures::alice_identity_key()).with_user_id(alice.user_id);
+        let args = FindOutputsArgs {
+            user_id: alice.user_id,
+            since: None,
+            paged: None,
+            order_descending: None,
+            partial: None,
+            no_script: None,
+            tx_status: None,
+        };
+        let outputs = storage.find_outputs_auth(&auth, &args).await.unwrap();
+        assert_eq!(outputs.len(), fixtures::ALICE_CHANGE.len());
+
+        // Funding transactions are mined alone, so the merkle root is the txid
+        let proven = &storage.proven_txs[0];
+        assert_eq!(Some(&proven.txid), storage.transaction(1).txid.as_ref());
+        assert_eq!(proven.merkle_root, proven.txid);
+    }
+
+    #[tokio::test]
+    async fn test_allocate_change_input() {
+        let mut storage = MockStorage::with_fixtures();
+        assert_eq!(storage.count_change_inputs(1, 1, true).await.unwrap(), 3);
+
+        // Exact match, then the smallest covering output, then the largest
+        let exact = storage.allocate_change_input(1, 1, 1_500, Some(1_000), true, 9).await.unwrap().unwrap();
+        assert_eq!(exact.satoshis, 1_000);
+        assert_eq!(exact.spent_by, Some(9));
+        let covering = storage.allocate_change_input(1, 1, 1_500, None, true, 9).await.unwrap().unwrap();
+        assert_eq!(covering.satoshis, 2_000);
+        let largest = storage.allocate_change_input(1, 1, 99_000, None, true, 9).await.unwrap().unwrap();
+        assert_eq!(largest.satoshis, 5_000);
+
+        assert_eq!(storage.count_change_inputs(1, 1, true).await.unwrap(), 0);
+        assert!(storage.allocate_change_input(1, 1, 1, None, true, 9).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_output_locks() {
+        let mut storage = MockStorage::with_fixtures();
+        let txid = storage.transaction(1).txid.clone().unwrap();
+        let lock = storage.lock_output(1, &txid, 2, "token", None).await.unwrap();
+        assert_eq!(lock.output_id, 3);
+        assert_eq!(storage.find_output_lock(3).await.unwrap(), Some(lock));
+        assert_eq!(storage.count_change_inputs(1, 1, true).await.unwrap(), 2);
+        assert!(matches!(storage.lock_output(1, &txid, 9, "token", None).await, Err(StorageError::NotFound(_))));
+
+        // The largest output is locked, so the largest unlocked one is chosen
+        let largest = storage.allocate_change_input(1, 1, 99_000, None, true, 9).await.unwrap().unwrap();
+        assert_eq!(largest.satoshis, 2_000);
+
+        // Lapsed locks are ignored
+        storage.output_locks[0].expires_at = Some("2000-01-01T00:00:00Z".to_string());
+        assert!(storage.find_output_locks(1).await.unwrap().is_empty());
+        assert_eq!(storage.count_change_inputs(1, 1, true).await.unwrap(), 2);
+
+        assert!(storage.unlock_output(1, &txid, 2).await.unwrap());
+        assert!(!storage.unlock_output(1, &txid, 2).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_known_valid_transactions() {
+        let mut storage = MockStorage::with_fixtures();
+        let proven_txid = storage.proven_txs[0].txid.clone();
+        assert!(storage.verify_known_valid_transaction(&proven_txid).await.unwrap());
+
+        let raw_tx = fixtures::spend_tx(&proven_txid, 0, &[900]).serialize().unwrap();
+        let txid = "cd".repeat(32);
+        let req = TableProvenTxReq::new(0, ProvenTxReqStatus::Invalid, &txid, "{}", "{}", raw_tx.clone());
+        let id = storage.insert_proven_tx_req(&req).await.unwrap();
+        assert!(!storage.verify_known_valid_transaction(&txid).await.unwrap());
+
+        let updates = ProvenTxReqUpdates { status: Some(ProvenTxReqStatus::Unmined), ..Default::default() };
+        storage.update_proven_tx_req(id, &updates).await.unwrap();
+        assert!(storage.verify_known_valid_transaction(&txid).await.unwrap());
+        let part = storage.get_raw_tx_of_known_valid_transaction(&txid, Some(4), Some(10)).await.unwrap();
+        assert_eq!(part.as_deref(), Some(&raw_tx[4..14]));
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_delete_output_tags() {
+        let mut storage = MockStorage::with_fixtures();
+        for (output_id, tag) in [(1, "rent"), (2, "rent"), (2, "bills"), (3, "bills")] {
+            let tag = storage.find_or_insert_output_tag(1, tag).await.unwrap();
+            storage.find_or_insert_output_tag_map(output_id, tag.output_tag_id).await.unwrap();
+        }
+
+        // Renaming onto an existing tag merges the mappings
+        let bills = storage.rename_output_tag(1, "rent", "bills").await.unwrap();
+        assert_eq!(bills.tag, "bills");
+        let usages = storage.get_output_tag_usages(1).await.unwrap();
+        assert_eq!(usages.len(), 1);
+        assert_eq!((usages[0].tag.as_str(), usages[0].output_count), ("bills", 3));
+        assert!(matches!(storage.delete_output_tag(1, "rent").await, Err(StorageError::NotFound(_))));
+
+        let renamed = storage.rename_output_tag(1, "bills", "utilities").await.unwrap();
+        assert_eq!(renamed.output_tag_id, bills.output_tag_id);
+        storage.delete_output_tag(1, "utilities").await.unwrap();
+        assert!(storage.find_output_tags(1).await.unwrap().is_empty());
+
+        // Tagging again restores the tag without its old mappings
+        let restored = storage.find_or_insert_output_tag(1, "utilities").await.unwrap();
+        assert!(!restored.is_deleted);
+        assert_eq!(storage.get_output_tag_usages(1).await.unwrap()[0].output_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_delete_tx_labels() {
+        let mut storage = MockStorage::with_fixtures();
+        let label = storage.find_or_insert_tx_label(1, "funding").await.unwrap();
+        storage.find_or_insert_tx_label_map(1, label.tx_label_id).await.unwrap();
+
+        storage.rename_tx_label(1, "funding", "deposit").await.unwrap();
+        assert_eq!(storage.find_tx_labels_for_transaction(1).await.unwrap()[0].label, "deposit");
+        storage.delete_tx_label(1, "deposit").await.unwrap();
+        assert!(storage.find_tx_labels_for_transaction(1).await.unwrap().is_empty());
+        assert!(storage.find_tx_labels(1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_or_insert_user() {
+        let mut storage = MockStorage::with_fixtures();
+        let existing = storage.find_or_insert_user(&fixtures::bob_identity_key()).await.unwrap();
+        assert!(!existing.is_new);
+        assert_eq!(existing.user.user_id, 2);
+
+        let new = storage.find_or_insert_user(&"02".repeat(33)).await.unwrap();
+        assert!(new.is_new);
+        assert_eq!(new.user.user_id, 3);
+    }
+}
@@ -0,0 +1,7 @@
+//! Mock wallet
+//!
+//! A `WalletInterface` answering from canned responses, for testing
+//! managers and transports that wrap a wallet. Defined in wallet-core so that
+//! its own unit tests share it.
+
+pub use wallet_core::test_utils::{MockWallet, WalletCall};
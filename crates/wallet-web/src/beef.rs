@@ -0,0 +1,166 @@
+//! BEEF parsing and verification
+
+use crate::{invalid_data, js_error, to_js};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use wallet_core::beef::Beef;
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wasm_bindgen::prelude::*;
+
+/// What a BEEF contains, as returned by `parseBeef`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeefSummary {
+    pub version: u32,
+    pub atomic_txid: Option<String>,
+    pub bumps: Vec<BumpSummary>,
+    pub txs: Vec<BeefTxSummary>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BumpSummary {
+    pub block_height: u32,
+    pub merkle_root: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeefTxSummary {
+    pub txid: String,
+    pub bump_index: Option<usize>,
+    pub is_txid_only: bool,
+    /// Hex raw transaction, absent for txid-only entries
+    pub raw_tx: Option<String>,
+}
+
+/// Parse a BEEF, Atomic BEEF or BEEF V2 into `{ version, atomicTxid, bumps, txs }`
+#[wasm_bindgen(js_name = parseBeef)]
+pub fn parse_beef(beef: &[u8]) -> Result<JsValue, JsError> {
+    to_js(&summarize(beef).map_err(js_error)?)
+}
+
+/// Check a BEEF's structure and return the merkle roots it claims, as
+/// `{ [height]: rootHex }`, for checking with a chain tracker
+///
+/// Throws when a transaction is not proven by a BUMP or by proven inputs.
+#[wasm_bindgen(js_name = beefMerkleRoots)]
+pub fn beef_merkle_roots(beef: &[u8], allow_txid_only: bool) -> Result<JsValue, JsError> {
+    to_js(&merkle_roots(beef, allow_txid_only).map_err(js_error)?)
+}
+
+/// Verify a BEEF against known block roots, `{ [height]: rootHex }`
+///
+/// False when the structure is invalid or a claimed root is not known.
+#[wasm_bindgen(js_name = verifyBeef)]
+pub fn verify_beef(beef: &[u8], known_roots: JsValue, allow_txid_only: bool) -> Result<bool, JsError> {
+    let known_roots: HashMap<String, String> = serde_wasm_bindgen::from_value(known_roots)
+        .map_err(|e| JsError::new(&format!("knownRoots must map block heights to merkle roots: {}", e)))?;
+    verify(beef, &known_roots, allow_txid_only).map_err(js_error)
+}
+
+fn summarize(beef: &[u8]) -> WalletResult<BeefSummary> {
+    let beef = Beef::from_binary(beef).map_err(invalid_data)?;
+    let bumps = beef
+        .bumps
+        .iter()
+        .map(|bump| {
+            Ok(BumpSummary {
+                block_height: bump.block_height,
+                merkle_root: bump.compute_root(None).map_err(invalid_data)?,
+            })
+        })
+        .collect::<WalletResult<_>>()?;
+    let txs = beef
+        .txs
+        .iter()
+        .map(|btx| BeefTxSummary {
+            txid: btx.txid.clone(),
+            bump_index: btx.bump_index,
+            is_txid_only: btx.is_txid_only,
+            raw_tx: btx.raw_tx.as_ref().map(hex::encode),
+        })
+        .collect();
+    Ok(BeefSummary {
+        version: beef.version,
+        atomic_txid: beef.atomic_txid,
+        bumps,
+        txs,
+    })
+}
+
+fn merkle_roots(beef: &[u8], allow_txid_only: bool) -> WalletResult<BTreeMap<u32, String>> {
+    let beef = Beef::from_binary(beef).map_err(invalid_data)?;
+    beef.verify_valid(allow_txid_only).map_err(invalid_data)
+}
+
+fn verify(beef: &[u8], known_roots: &HashMap<String, String>, allow_txid_only: bool) -> WalletResult<bool> {
+    let known_roots = known_roots
+        .iter()
+        .map(|(height, root)| match height.parse::<u32>() {
+            Ok(height) => Ok((height, root)),
+            Err(_) => Err(WalletError::invalid_parameter("knownRoots", format!("keyed by block height, not {}", height))),
+        })
+        .collect::<WalletResult<HashMap<u32, &String>>>()?;
+
+    let beef = Beef::from_binary(beef).map_err(invalid_data)?;
+    let Ok(roots) = beef.verify_valid(allow_txid_only) else {
+        return Ok(false);
+    };
+    Ok(roots.iter().all(|(height, root)| known_roots.get(height) == Some(&root)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::beef::{MerklePath, MerklePathNode};
+    use wallet_core::transaction::{OutPoint, Transaction, TxInput, TxOutput};
+
+    fn raw_tx(prev_txid: &str) -> (String, Vec<u8>) {
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new(prev_txid, 0)));
+        tx.add_output(TxOutput::new(1000, vec![0x51]));
+        (tx.txid().unwrap(), tx.serialize().unwrap())
+    }
+
+    fn beef() -> (String, Vec<u8>) {
+        let (parent_txid, parent_raw) = raw_tx(&"11".repeat(32));
+        let (_, child_raw) = raw_tx(&parent_txid);
+        let mut beef = Beef::new_v2();
+        let bump = MerklePath {
+            block_height: 7,
+            path: vec![vec![MerklePathNode {
+                hash: parent_txid.clone(),
+                offset: Some(0),
+                duplicate: false,
+                txid: true,
+            }]],
+        };
+        beef.merge_proven_tx(&parent_raw, bump).unwrap();
+        beef.merge_raw_tx(&child_raw).unwrap();
+        (parent_txid, beef.to_binary().unwrap())
+    }
+
+    #[test]
+    fn test_summarize_and_verify() {
+        let (parent_txid, bin) = beef();
+
+        let summary = summarize(&bin).unwrap();
+        assert_eq!(summary.bumps[0].merkle_root, parent_txid);
+        assert_eq!(summary.txs.len(), 2);
+        assert_eq!(summary.txs[0].bump_index, Some(0));
+        assert!(summary.txs[1].raw_tx.is_some());
+
+        let roots = merkle_roots(&bin, false).unwrap();
+        assert_eq!(roots.get(&7), Some(&parent_txid));
+
+        let known = HashMap::from([("7".to_string(), parent_txid)]);
+        assert!(verify(&bin, &known, false).unwrap());
+        let wrong = HashMap::from([("7".to_string(), "22".repeat(32))]);
+        assert!(!verify(&bin, &wrong, false).unwrap());
+        let unkeyed = HashMap::from([("tip".to_string(), "22".repeat(32))]);
+        assert!(verify(&bin, &unkeyed, false).is_err());
+
+        assert!(summarize(&[1, 2, 3]).is_err());
+    }
+}
@@ -1,3 +1,73 @@
-//! Web server integration (placeholder)
+//! WebAssembly bindings for web apps
+//!
+//! Exposes the Rust key derivation, BEEF and transaction signing code to
+//! JavaScript through wasm-bindgen, for web apps where the TS SDK is too
+//! slow. Build with wasm-pack:
+//!
+//! ```text
+//! wasm-pack build crates/wallet-web --target web
+//! ```
+//!
+//! ## Conventions
+//!
+//! - Keys, txids and scripts are hex strings; transactions and BEEFs are
+//!   `Uint8Array`s.
+//! - Structured results are plain JS objects with camelCase properties.
+//! - Failures throw an `Error` whose message starts with the WERR code.
 
-pub fn start() {}
+use serde::Serialize;
+use std::fmt::Display;
+use wallet_core::sdk::errors::{WalletError, WalletErrorCode, WalletResult};
+use wasm_bindgen::prelude::*;
+
+pub mod beef;
+pub mod keys;
+pub mod signing;
+
+pub use beef::{beef_merkle_roots, parse_beef, verify_beef};
+pub use keys::KeyDeriver;
+pub use signing::{sign_p2pkh_inputs, transaction_sighash};
+
+/// Throwable JS error for a failed call
+fn js_error(err: WalletError) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/// Plain JS object for `value`
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Bytes of the hex argument `name`, which must be `len` bytes long if given
+fn decode_hex(name: &str, value: &str, len: Option<usize>) -> WalletResult<Vec<u8>> {
+    let must_be = || match len {
+        Some(len) => format!("{} bytes of hex", len),
+        None => "hex".to_string(),
+    };
+    let bytes = hex::decode(value).map_err(|_| WalletError::invalid_parameter(name, must_be()))?;
+    if len.is_some_and(|len| bytes.len() != len) {
+        return Err(WalletError::invalid_parameter(name, must_be()));
+    }
+    Ok(bytes)
+}
+
+fn invalid_data(err: impl Display) -> WalletError {
+    WalletError::new(WalletErrorCode::InvalidData, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("key", "0aff", None).unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(decode_hex("key", "0aff", Some(2)).unwrap(), vec![0x0a, 0xff]);
+
+        let err = decode_hex("key", "0aff", Some(32)).unwrap_err();
+        assert_eq!(err.code, WalletErrorCode::InvalidParameter);
+        assert!(decode_hex("key", "zz", None).is_err());
+    }
+}
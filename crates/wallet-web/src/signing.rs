@@ -0,0 +1,119 @@
+//! Transaction signing
+
+use crate::{decode_hex, invalid_data, js_error};
+use serde::Deserialize;
+use wallet_core::crypto::{derive_public_key, sign_ecdsa};
+use wallet_core::sdk::errors::{WalletError, WalletResult};
+use wallet_core::transaction::{Script, SigHash, SigHashType, Transaction};
+use wasm_bindgen::prelude::*;
+
+/// A P2PKH input to sign, as passed to `signP2pkhInputs`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct P2pkhUnlock {
+    pub input_index: usize,
+    /// 32-byte hex private key for the spent output
+    pub private_key: String,
+    /// Hex locking script of the spent output
+    pub locking_script: String,
+    pub source_satoshis: i64,
+    /// Defaults to SIGHASH_ALL | FORKID
+    pub sighash_type: Option<u8>,
+}
+
+/// Sign P2PKH inputs of a raw transaction, returning the signed transaction
+///
+/// `unlocks` is an array of `{ inputIndex, privateKey, lockingScript,
+/// sourceSatoshis, sighashType? }`. Inputs not listed are left as they are.
+#[wasm_bindgen(js_name = signP2pkhInputs)]
+pub fn sign_p2pkh_inputs(raw_tx: &[u8], unlocks: JsValue) -> Result<Vec<u8>, JsError> {
+    let unlocks: Vec<P2pkhUnlock> = serde_wasm_bindgen::from_value(unlocks)
+        .map_err(|e| JsError::new(&format!("unlocks must be an array of P2PKH inputs: {}", e)))?;
+    sign_inputs(raw_tx, &unlocks).map_err(js_error)
+}
+
+/// Signature hash of an input, for signing with keys held elsewhere
+#[wasm_bindgen(js_name = transactionSighash)]
+pub fn transaction_sighash(
+    raw_tx: &[u8],
+    input_index: usize,
+    locking_script: &str,
+    source_satoshis: i64,
+    sighash_type: Option<u8>,
+) -> Result<Vec<u8>, JsError> {
+    sighash(raw_tx, input_index, locking_script, source_satoshis, sighash_type).map_err(js_error)
+}
+
+fn sign_inputs(raw_tx: &[u8], unlocks: &[P2pkhUnlock]) -> WalletResult<Vec<u8>> {
+    let mut tx = Transaction::from_bytes(raw_tx).map_err(invalid_data)?;
+
+    // Sign everything before setting any unlocking script
+    let mut unlocking_scripts = Vec::with_capacity(unlocks.len());
+    for unlock in unlocks {
+        let private_key = decode_hex("privateKey", &unlock.private_key, Some(32))?;
+        let locking_script = decode_hex("lockingScript", &unlock.locking_script, None)?;
+        let sighash_type = unlock.sighash_type.unwrap_or(SigHashType::All.with_forkid());
+        let sighash = SigHash::calculate(&tx, unlock.input_index, &locking_script, sighash_type, unlock.source_satoshis)
+            .map_err(|e| WalletError::invalid_parameter("unlocks", e.to_string()))?;
+        let signature = sign_ecdsa(&sighash, &private_key, sighash_type)
+            .map_err(|e| WalletError::invalid_parameter("privateKey", e.to_string()))?;
+        let public_key = derive_public_key(&private_key)
+            .map_err(|e| WalletError::invalid_parameter("privateKey", e.to_string()))?;
+        unlocking_scripts.push((unlock.input_index, Script::p2pkh_unlocking_script(&signature, &public_key)));
+    }
+    for (vin, script) in unlocking_scripts {
+        tx.inputs[vin].set_script(script.to_bytes().to_vec());
+    }
+    tx.serialize().map_err(invalid_data)
+}
+
+fn sighash(
+    raw_tx: &[u8],
+    input_index: usize,
+    locking_script: &str,
+    source_satoshis: i64,
+    sighash_type: Option<u8>,
+) -> WalletResult<Vec<u8>> {
+    let tx = Transaction::from_bytes(raw_tx).map_err(invalid_data)?;
+    let locking_script = decode_hex("lockingScript", locking_script, None)?;
+    let sighash_type = sighash_type.unwrap_or(SigHashType::All.with_forkid());
+    SigHash::calculate(&tx, input_index, &locking_script, sighash_type, source_satoshis)
+        .map_err(|e| WalletError::invalid_parameter("inputIndex", e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wallet_core::crypto::hash160;
+    use wallet_core::transaction::{OutPoint, Spend, TxInput, TxOutput};
+
+    fn p2pkh(private_key: &[u8]) -> Vec<u8> {
+        let public_key = derive_public_key(private_key).unwrap();
+        Script::p2pkh_locking_script(&hash160(&public_key)).unwrap().to_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_sign_p2pkh_inputs() {
+        let locking_script = p2pkh(&[1u8; 32]);
+        let mut tx = Transaction::new();
+        tx.add_input(TxInput::new(OutPoint::new("aa".repeat(32), 0)));
+        tx.add_output(TxOutput::new(9_000, p2pkh(&[2u8; 32])));
+        let raw = tx.serialize().unwrap();
+
+        let unlock = P2pkhUnlock {
+            input_index: 0,
+            private_key: "01".repeat(32),
+            locking_script: hex::encode(&locking_script),
+            source_satoshis: 10_000,
+            sighash_type: None,
+        };
+        let signed = Transaction::from_bytes(&sign_inputs(&raw, &[unlock]).unwrap()).unwrap();
+        Spend::new(&signed, 0, &locking_script, 10_000).validate().unwrap();
+
+        // The sighash is over the unsigned input, so signing leaves it unchanged
+        let digest = sighash(&raw, 0, &hex::encode(&locking_script), 10_000, None).unwrap();
+        let after = sighash(&signed.serialize().unwrap(), 0, &hex::encode(&locking_script), 10_000, None).unwrap();
+        assert_eq!(digest, after);
+        assert!(sighash(&raw, 1, &hex::encode(&locking_script), 10_000, None).is_err());
+    }
+}